@@ -0,0 +1,152 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Bearer token authentication of the admin methods of the gRPC servers. A server checks the `authorization` metadata
+//! of every call to an admin method with `AdminToken::authenticate`, and a client sends the token with every call
+//! through `ClientAuthInterceptor`.
+
+use crate::tari_rpc::base_node_client::BaseNodeClient;
+use std::fmt;
+use tonic::{
+    codegen::InterceptedService,
+    metadata::{errors::InvalidMetadataValue, AsciiMetadataValue},
+    service::Interceptor,
+    transport::Channel,
+    Request,
+    Status,
+};
+
+const AUTHORIZATION: &str = "authorization";
+const BEARER: &str = "Bearer ";
+
+/// A base node gRPC client that sends the admin token with every call
+pub type BaseNodeAdminClient = BaseNodeClient<InterceptedService<Channel, ClientAuthInterceptor>>;
+
+/// The token that calls to the admin methods of a gRPC server must carry. The admin methods are not authenticated when
+/// no token is set.
+#[derive(Clone, Default)]
+pub struct AdminToken {
+    token: Option<String>,
+}
+
+impl AdminToken {
+    /// An empty token is the same as no token
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            token: token.filter(|token| !token.is_empty()),
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Checks that a call to the admin `method` carries the token
+    pub fn authenticate<T>(&self, request: &Request<T>, method: &str) -> Result<(), Status> {
+        let token = match self.token {
+            Some(ref token) => token,
+            None => return Ok(()),
+        };
+        let provided = request
+            .metadata()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix(BEARER));
+        match provided {
+            Some(provided) if constant_time_eq(provided.as_bytes(), token.as_bytes()) => Ok(()),
+            _ => Err(Status::unauthenticated(format!(
+                "{} requires the gRPC admin token",
+                method
+            ))),
+        }
+    }
+}
+
+/// Compares without returning early, so that the time taken does not reveal how much of the token was guessed
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Adds the admin token to the `authorization` metadata of every call made by a gRPC client. Calls are sent unchanged
+/// when there is no token.
+#[derive(Clone, Default)]
+pub struct ClientAuthInterceptor {
+    authorization: Option<AsciiMetadataValue>,
+}
+
+impl ClientAuthInterceptor {
+    pub fn new(token: Option<&str>) -> Result<Self, InvalidMetadataValue> {
+        let authorization = token
+            .filter(|token| !token.is_empty())
+            .map(|token| format!("{}{}", BEARER, token).parse())
+            .transpose()?;
+        Ok(Self { authorization })
+    }
+}
+
+impl fmt::Debug for ClientAuthInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientAuthInterceptor")
+            .field("authorization", &self.authorization.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl Interceptor for ClientAuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(ref authorization) = self.authorization {
+            request.metadata_mut().insert(AUTHORIZATION, authorization.clone());
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tonic::Code;
+
+    #[test]
+    fn it_authenticates_calls_with_the_token() {
+        let token = AdminToken::new(Some("secret".to_string()));
+        let mut interceptor = ClientAuthInterceptor::new(Some("secret")).unwrap();
+        let request = interceptor.call(Request::new(())).unwrap();
+        assert!(token.authenticate(&request, "SubmitBlock").is_ok());
+
+        let mut interceptor = ClientAuthInterceptor::new(Some("wrong")).unwrap();
+        let request = interceptor.call(Request::new(())).unwrap();
+        let err = token.authenticate(&request, "SubmitBlock").unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated);
+
+        let mut interceptor = ClientAuthInterceptor::new(None).unwrap();
+        let request = interceptor.call(Request::new(())).unwrap();
+        assert!(token.authenticate(&request, "SubmitBlock").is_err());
+    }
+
+    #[test]
+    fn it_does_not_authenticate_without_a_token() {
+        let token = AdminToken::new(Some(String::new()));
+        assert!(!token.is_set());
+        assert!(token.authenticate(&Request::new(()), "BanPeer").is_ok());
+    }
+}
@@ -21,7 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{conversions::datetime_to_timestamp, tari_rpc as grpc};
-use tari_comms::{connectivity::ConnectivityStatus, net_address::MutliaddrWithStats, peer_manager::Peer};
+use tari_comms::{
+    connection_manager::ConnectionDirection,
+    connectivity::ConnectivityStatus,
+    net_address::MutliaddrWithStats,
+    peer_manager::Peer,
+};
 use tari_core::crypto::tari_utilities::ByteArray;
 
 impl From<Peer> for grpc::Peer {
@@ -54,6 +59,7 @@ impl From<Peer> for grpc::Peer {
         };
         let supported_protocols = peer.supported_protocols.into_iter().map(|p| p.to_vec()).collect();
         let user_agent = peer.user_agent;
+        let capabilities = peer.capabilities.bits();
         Self {
             public_key,
             node_id,
@@ -67,6 +73,7 @@ impl From<Peer> for grpc::Peer {
             last_connected_at,
             supported_protocols,
             user_agent,
+            capabilities,
         }
     }
 }
@@ -102,3 +109,12 @@ impl From<ConnectivityStatus> for grpc::ConnectivityStatus {
         }
     }
 }
+
+impl From<ConnectionDirection> for grpc::ConnectionDirection {
+    fn from(direction: ConnectionDirection) -> Self {
+        match direction {
+            ConnectionDirection::Inbound => grpc::ConnectionDirection::Inbound,
+            ConnectionDirection::Outbound => grpc::ConnectionDirection::Outbound,
+        }
+    }
+}
@@ -70,6 +70,7 @@ impl From<TransactionInput> for grpc::TransactionInput {
             features: Some(grpc::OutputFeatures {
                 flags: input.features.flags.bits() as u32,
                 maturity: input.features.maturity,
+                encrypted_memo: input.features.encrypted_memo.clone(),
             }),
             commitment: Vec::from(input.commitment.as_bytes()),
             hash,
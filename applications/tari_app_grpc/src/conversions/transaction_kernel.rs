@@ -48,6 +48,7 @@ impl TryFrom<grpc::TransactionKernel> for TransactionKernel {
             excess,
             excess_sig,
             fee: MicroTari::from(kernel.fee),
+            burn_amount: MicroTari::from(kernel.burn_amount),
             lock_height: kernel.lock_height,
         })
     }
@@ -60,6 +61,7 @@ impl From<TransactionKernel> for grpc::TransactionKernel {
         grpc::TransactionKernel {
             features: kernel.features.bits() as u32,
             fee: kernel.fee.0,
+            burn_amount: kernel.burn_amount.0,
             lock_height: kernel.lock_height,
             excess: Vec::from(kernel.excess.as_bytes()),
             excess_sig: Some(grpc::Signature {
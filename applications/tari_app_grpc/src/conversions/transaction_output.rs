@@ -73,6 +73,7 @@ impl From<TransactionOutput> for grpc::TransactionOutput {
             features: Some(grpc::OutputFeatures {
                 flags: output.features.flags.bits() as u32,
                 maturity: output.features.maturity,
+                encrypted_memo: output.features.encrypted_memo.clone(),
             }),
             commitment: Vec::from(output.commitment.as_bytes()),
             range_proof: Vec::from(output.proof.as_bytes()),
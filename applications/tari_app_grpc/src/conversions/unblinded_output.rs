@@ -39,6 +39,7 @@ impl From<UnblindedOutput> for grpc::UnblindedOutput {
             features: Some(grpc::OutputFeatures {
                 flags: output.features.flags.bits() as u32,
                 maturity: output.features.maturity,
+                encrypted_memo: output.features.encrypted_memo.clone(),
             }),
             script: output.script.as_bytes(),
             input_data: output.input_data.as_bytes(),
@@ -0,0 +1,352 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Request metrics, slow request logging and per client rate limiting for the gRPC servers. `GrpcMetrics::layer`
+//! wraps a generated gRPC server, so that every call is counted and timed by method before it reaches the service.
+
+use log::*;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tonic::{
+    body::BoxBody,
+    codegen::{http, BoxFuture, Context, Poll, Service},
+    transport::{server::TcpConnectInfo, Body, NamedService},
+    Status,
+};
+
+const LOG_TARGET: &str = "tari::application::grpc";
+
+/// The upper bounds of the latency histogram buckets in milliseconds. Slower calls fall into a last, unbounded bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 8] = [5, 10, 50, 100, 500, 1_000, 5_000, 10_000];
+/// Idle rate limit buckets are dropped once more than this many clients have been seen
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GrpcLimits {
+    /// Calls that take longer than this are logged
+    pub slow_request_threshold: Option<Duration>,
+    /// The number of calls per second each client IP may make to each method, with bursts of up to `burst` calls.
+    pub rate_limit: Option<RateLimit>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub requests_per_sec: u32,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// `None` if `requests_per_sec` is 0. The burst is at least one request.
+    pub fn new(requests_per_sec: u32, burst: u32) -> Option<Self> {
+        if requests_per_sec == 0 {
+            return None;
+        }
+        Some(Self {
+            requests_per_sec,
+            burst: burst.max(1),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MethodStats {
+    pub count: u64,
+    pub rate_limited: u64,
+    pub slow: u64,
+    pub total_latency: Duration,
+    pub max_latency: Duration,
+    pub latency_histogram: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+impl MethodStats {
+    fn record(&mut self, latency: Duration, is_slow: bool) {
+        self.count += 1;
+        if is_slow {
+            self.slow += 1;
+        }
+        self.total_latency += latency;
+        self.max_latency = self.max_latency.max(latency);
+        let millis = latency.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| millis <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency_histogram[bucket] += 1;
+    }
+
+    pub fn average_latency(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::default();
+        }
+        self.total_latency / self.count as u32
+    }
+
+    /// The upper bound of the histogram bucket that contains the given percentile, or the maximum latency if it is in
+    /// the last bucket
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (self.count as f64 * percentile / 100.0).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, count) in self.latency_histogram.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(
+                    LATENCY_BUCKETS_MS
+                        .get(i)
+                        .map(|ms| Duration::from_millis(*ms).min(self.max_latency))
+                        .unwrap_or(self.max_latency),
+                );
+            }
+        }
+        Some(self.max_latency)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * f64::from(limit.requests_per_sec)).min(f64::from(limit.burst));
+        self.updated_at = now;
+    }
+
+    fn try_take(&mut self, limit: &RateLimit, now: Instant) -> bool {
+        self.refill(limit, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_full(&self, limit: &RateLimit) -> bool {
+        self.tokens >= f64::from(limit.burst)
+    }
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    methods: HashMap<String, MethodStats>,
+    buckets: HashMap<(IpAddr, String), TokenBucket>,
+}
+
+/// Per method request counts and latencies of a gRPC server, shared by all its connections
+#[derive(Debug, Clone)]
+pub struct GrpcMetrics {
+    limits: GrpcLimits,
+    state: Arc<Mutex<MetricsState>>,
+}
+
+impl GrpcMetrics {
+    pub fn new(limits: GrpcLimits) -> Self {
+        Self {
+            limits,
+            state: Arc::new(Mutex::new(MetricsState::default())),
+        }
+    }
+
+    pub fn limits(&self) -> GrpcLimits {
+        self.limits
+    }
+
+    /// Wraps a gRPC service so that its calls are recorded and rate limited
+    pub fn layer<S>(&self, service: S) -> InstrumentedService<S> {
+        InstrumentedService {
+            inner: service,
+            metrics: self.clone(),
+        }
+    }
+
+    /// The stats of every method that has been called, by method name
+    pub fn method_stats(&self) -> Vec<(String, MethodStats)> {
+        let state = self.state.lock().unwrap();
+        let mut stats = state
+            .methods
+            .iter()
+            .map(|(method, stats)| (method.clone(), stats.clone()))
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        stats
+    }
+
+    /// Takes a token from the client's bucket for the method. Calls are always allowed when the client address is
+    /// unknown.
+    fn check_rate_limit(&self, client: Option<IpAddr>, method: &str, now: Instant) -> bool {
+        let (limit, client) = match (self.limits.rate_limit, client) {
+            (Some(limit), Some(client)) => (limit, client),
+            _ => return true,
+        };
+        let mut state = self.state.lock().unwrap();
+        if state.buckets.len() > MAX_TRACKED_CLIENTS {
+            state.buckets.retain(|_, bucket| {
+                bucket.refill(&limit, now);
+                !bucket.is_full(&limit)
+            });
+        }
+        let allowed = state
+            .buckets
+            .entry((client, method.to_string()))
+            .or_insert_with(|| TokenBucket::full(&limit, now))
+            .try_take(&limit, now);
+        if !allowed {
+            state.methods.entry(method.to_string()).or_default().rate_limited += 1;
+        }
+        allowed
+    }
+
+    fn record(&self, client: Option<IpAddr>, method: &str, latency: Duration) {
+        let is_slow = self
+            .limits
+            .slow_request_threshold
+            .map(|threshold| latency > threshold)
+            .unwrap_or(false);
+        if is_slow {
+            warn!(
+                target: LOG_TARGET,
+                "Slow gRPC call {} from {} took {:.2?}",
+                method,
+                client.map(|ip| ip.to_string()).unwrap_or_else(|| "<unknown>".to_string()),
+                latency
+            );
+        }
+        let mut state = self.state.lock().unwrap();
+        state
+            .methods
+            .entry(method.to_string())
+            .or_default()
+            .record(latency, is_slow);
+    }
+}
+
+/// A gRPC service wrapped by `GrpcMetrics::layer`. The latency of streaming calls is the time until the response
+/// headers are sent.
+#[derive(Debug, Clone)]
+pub struct InstrumentedService<S> {
+    inner: S,
+    metrics: GrpcMetrics,
+}
+
+impl<S> Service<http::Request<Body>> for InstrumentedService<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    type Response = http::Response<BoxBody>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let method = request.uri().path().rsplit('/').next().unwrap_or_default().to_string();
+        let client = request
+            .extensions()
+            .get::<TcpConnectInfo>()
+            .and_then(|info| info.remote_addr())
+            .map(|addr| addr.ip());
+        let metrics = self.metrics.clone();
+        let start = Instant::now();
+        if !metrics.check_rate_limit(client, &method, start) {
+            debug!(target: LOG_TARGET, "Rate limited gRPC call {} from {:?}", method, client);
+            let status = Status::resource_exhausted(format!("Rate limit exceeded for {}", method));
+            return Box::pin(async move { Ok(status.to_http()) });
+        }
+        // The service that was polled ready handles this call, a fresh clone takes its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let response = inner.call(request).await;
+            metrics.record(client, &method, start.elapsed());
+            response
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for InstrumentedService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn rate_limit() {
+        let metrics = GrpcMetrics::new(GrpcLimits {
+            slow_request_threshold: None,
+            rate_limit: RateLimit::new(2, 3),
+        });
+        let client = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(metrics.check_rate_limit(client, "GetTipInfo", now));
+        }
+        assert!(!metrics.check_rate_limit(client, "GetTipInfo", now));
+        // Each method has its own bucket
+        assert!(metrics.check_rate_limit(client, "GetVersion", now));
+        // Unknown clients are not limited
+        assert!(metrics.check_rate_limit(None, "GetTipInfo", now));
+        // Two tokens are added every second
+        let later = now + Duration::from_millis(500);
+        assert!(metrics.check_rate_limit(client, "GetTipInfo", later));
+        assert!(!metrics.check_rate_limit(client, "GetTipInfo", later));
+        assert_eq!(metrics.method_stats()[0].1.rate_limited, 2);
+    }
+
+    #[test]
+    fn latency_percentiles() {
+        let mut stats = MethodStats::default();
+        assert_eq!(stats.latency_percentile(50.0), None);
+        for _ in 0..9 {
+            stats.record(Duration::from_millis(3), false);
+        }
+        stats.record(Duration::from_millis(700), true);
+        assert_eq!(stats.count, 10);
+        assert_eq!(stats.slow, 1);
+        assert_eq!(stats.latency_percentile(50.0), Some(Duration::from_millis(5)));
+        assert_eq!(stats.latency_percentile(95.0), Some(Duration::from_millis(700)));
+        assert_eq!(stats.max_latency, Duration::from_millis(700));
+    }
+}
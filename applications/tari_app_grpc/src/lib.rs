@@ -26,7 +26,10 @@
 #![deny(unused_must_use)]
 #![deny(unreachable_patterns)]
 #![deny(unknown_lints)]
+pub mod authentication;
 pub mod conversions;
+pub mod instrumentation;
+pub mod paging;
 
 pub mod tari_rpc {
     tonic::include_proto!("tari.rpc");
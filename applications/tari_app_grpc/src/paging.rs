@@ -0,0 +1,182 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Helpers shared by the bulk gRPC queries to apply `PageRequest` limits and `BlockFieldMask`s to their responses.
+
+use crate::tari_rpc;
+use prost::Message;
+use tonic::{metadata::MetadataValue, Response};
+
+/// The limits applied to a page of a bulk query, after capping the limits requested by the client at the server's
+/// maximums
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageLimits {
+    pub limit: usize,
+    pub max_response_bytes: usize,
+}
+
+impl PageLimits {
+    /// Caps the limits in the client's request at the given server maximums. Limits that are not specified default to
+    /// the server maximums.
+    pub fn negotiate(request: Option<&tari_rpc::PageRequest>, max_limit: usize, max_response_bytes: usize) -> Self {
+        let cap = |requested: u64, max: usize| match requested {
+            0 => max,
+            requested => (requested as usize).min(max),
+        };
+        match request {
+            Some(request) => Self {
+                limit: cap(request.limit, max_limit),
+                max_response_bytes: cap(request.max_response_bytes, max_response_bytes),
+            },
+            None => Self {
+                limit: max_limit,
+                max_response_bytes,
+            },
+        }
+    }
+
+    /// Tells the client which limits were applied to the page
+    pub fn add_to_metadata<T>(&self, response: &mut Response<T>) {
+        let metadata = response.metadata_mut();
+        metadata.insert("page-limit", MetadataValue::from(self.limit as u64));
+        metadata.insert(
+            "max-response-bytes",
+            MetadataValue::from(self.max_response_bytes as u64),
+        );
+    }
+
+    /// Starts tracking the items added to a page
+    pub fn budget(self) -> PageBudget {
+        PageBudget {
+            limits: self,
+            num_items: 0,
+            num_bytes: 0,
+        }
+    }
+}
+
+/// Tracks the number of items and the encoded size of a page as it is streamed
+#[derive(Debug, Clone)]
+pub struct PageBudget {
+    limits: PageLimits,
+    num_items: usize,
+    num_bytes: usize,
+}
+
+impl PageBudget {
+    /// Adds the item to the page if it fits within the page limits, returning false if the page is full. The first item
+    /// is always added, so that a client can make progress through items larger than its size limit.
+    pub fn try_add<M: Message>(&mut self, item: &M) -> bool {
+        if self.num_items >= self.limits.limit {
+            return false;
+        }
+        let size = item.encoded_len();
+        if self.num_items > 0 && self.num_bytes + size > self.limits.max_response_bytes {
+            return false;
+        }
+        self.num_items += 1;
+        self.num_bytes += size;
+        true
+    }
+
+    pub fn num_items(&self) -> usize {
+        self.num_items
+    }
+}
+
+impl tari_rpc::BlockFieldMask {
+    /// Removes the parts of the block that are not selected by the mask
+    pub fn apply(&self, block: &mut tari_rpc::HistoricalBlock) {
+        let body = match block.block.as_mut().and_then(|block| block.body.as_mut()) {
+            Some(body) => body,
+            None => return,
+        };
+        if self.headers_only {
+            *body = Default::default();
+            return;
+        }
+        if self.skip_range_proofs {
+            for output in &mut body.outputs {
+                output.range_proof.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negotiate_limits() {
+        let limits = PageLimits::negotiate(None, 100, 1_000);
+        assert_eq!(limits, PageLimits {
+            limit: 100,
+            max_response_bytes: 1_000
+        });
+
+        let request = tari_rpc::PageRequest {
+            cursor: String::new(),
+            limit: 10,
+            max_response_bytes: 5_000,
+        };
+        let limits = PageLimits::negotiate(Some(&request), 100, 1_000);
+        assert_eq!(limits, PageLimits {
+            limit: 10,
+            max_response_bytes: 1_000
+        });
+    }
+
+    #[test]
+    fn page_budget() {
+        let item = tari_rpc::StringValue { value: "a".repeat(100) };
+        let size = item.encoded_len();
+
+        let mut budget = PageLimits {
+            limit: 3,
+            max_response_bytes: size * 2,
+        }
+        .budget();
+        assert!(budget.try_add(&item));
+        assert!(budget.try_add(&item));
+        assert!(!budget.try_add(&item));
+        assert_eq!(budget.num_items(), 2);
+
+        // The first item is always added
+        let mut budget = PageLimits {
+            limit: 3,
+            max_response_bytes: 1,
+        }
+        .budget();
+        assert!(budget.try_add(&item));
+        assert!(!budget.try_add(&item));
+
+        let mut budget = PageLimits {
+            limit: 1,
+            max_response_bytes: size * 10,
+        }
+        .budget();
+        assert!(budget.try_add(&item));
+        assert!(!budget.try_add(&item));
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::utilities::ExitCodes;
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::Path,
+    time::Duration,
+};
+use tari_common::{CommsTransport, DatabaseType, GlobalConfig};
+use tari_comms::{multiaddr::Multiaddr, utils::multiaddr::multiaddr_to_socketaddr};
+
+const BYTES_PER_MIB: u64 = 1024 * 1024;
+/// Below this amount of free disk space the database is likely to fail writing
+const LOW_DISK_SPACE: u64 = 1024 * BYTES_PER_MIB;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Collects context for the hint of an exit code from the environment, such as the database path and the free disk
+/// space for database errors, or whether the configured ports are in use for network and gRPC errors. The returned
+/// lines include the steps to recover from the error.
+pub fn diagnose(exit_code: &ExitCodes, config: &GlobalConfig) -> Vec<String> {
+    match exit_code {
+        ExitCodes::DatabaseError(_) => {
+            let mut lines = database_diagnostics(&config.db_type);
+            lines.push("Make sure that no other application is using the same data directory".to_string());
+            lines
+        },
+        ExitCodes::InsufficientDiskSpace(_) => database_diagnostics(&config.db_type),
+        ExitCodes::DbInconsistentState(_) => {
+            let mut lines = database_diagnostics(&config.db_type);
+            lines.push("Run the base node with `--rebuild-db` to rebuild the database from its blocks".to_string());
+            if let DatabaseType::LMDB(path) = &config.db_type {
+                lines.push(format!(
+                    "If rebuilding fails, stop the base node and delete '{}' to sync the chain again",
+                    path.display()
+                ));
+            }
+            lines
+        },
+        ExitCodes::NetworkError(_) | ExitCodes::TorOffline => network_diagnostics(&config.comms_transport),
+        ExitCodes::GrpcError(_) => vec![
+            grpc_diagnostics("base node", config.grpc_base_node_address),
+            grpc_diagnostics("console wallet", config.grpc_console_wallet_address),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn database_diagnostics(db_type: &DatabaseType) -> Vec<String> {
+    let path = match db_type {
+        DatabaseType::LMDB(path) => path,
+        DatabaseType::Memory => return vec!["The in-memory database is configured".to_string()],
+    };
+    let mut lines = vec![format!("Database path: {}", path.display())];
+    if !path.exists() {
+        lines.push("The database directory does not exist".to_string());
+    }
+    match available_space(path) {
+        Ok(space) => {
+            lines.push(format!("Free disk space: {} MiB", space / BYTES_PER_MIB));
+            if space < LOW_DISK_SPACE {
+                lines.push(
+                    "The disk is almost full, free up space or move the data directory to a larger disk".to_string(),
+                );
+            }
+        },
+        Err(err) => lines.push(format!("Could not determine the free disk space: {}", err)),
+    }
+    lines
+}
+
+/// The free space of the disk the path is on, or will be created on if it does not exist yet
+fn available_space(path: &Path) -> io::Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no parent directory exists"))?;
+    fs2::available_space(existing)
+}
+
+fn network_diagnostics(transport: &CommsTransport) -> Vec<String> {
+    match transport {
+        CommsTransport::Tcp { listener_address, .. } | CommsTransport::Socks5 { listener_address, .. } => {
+            vec![listener_diagnostics("Listener", listener_address)]
+        },
+        CommsTransport::TorHiddenService {
+            control_server_address,
+            forward_address,
+            ..
+        } => {
+            let control = match multiaddr_to_socketaddr(control_server_address) {
+                Ok(addr) => match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                    Ok(_) => format!("The Tor control port {} is reachable", addr),
+                    Err(err) => format!(
+                        "The Tor control port {} is not reachable ({}), start the Tor proxy with its control port \
+                         turned on",
+                        addr, err
+                    ),
+                },
+                Err(err) => format!("Invalid Tor control address {}: {}", control_server_address, err),
+            };
+            vec![control, listener_diagnostics("Tor forward", forward_address)]
+        },
+    }
+}
+
+fn listener_diagnostics(name: &str, address: &Multiaddr) -> String {
+    match multiaddr_to_socketaddr(address) {
+        Ok(addr) => match TcpListener::bind(addr) {
+            Ok(_) => format!("{} address {} is available", name, addr),
+            Err(err) if err.kind() == io::ErrorKind::AddrInUse => format!(
+                "{} address {} is in use by another process, stop it or configure a different port",
+                name, addr
+            ),
+            Err(err) => format!("{} address {} cannot be used: {}", name, addr, err),
+        },
+        Err(err) => format!("Invalid {} address {}: {}", name.to_lowercase(), address, err),
+    }
+}
+
+fn grpc_diagnostics(name: &str, addr: SocketAddr) -> String {
+    match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+        Ok(_) => format!("The {} gRPC address {} is in use", name, addr),
+        Err(err) => format!("Nothing is listening on the {} gRPC address {}: {}", name, addr, err),
+    }
+}
@@ -23,7 +23,14 @@
 use crate::utilities::ExitCodes;
 use log::*;
 use rand::rngs::OsRng;
-use std::{clone::Clone, fs, path::Path, string::ToString, sync::Arc};
+use std::{
+    clone::Clone,
+    fs,
+    path::{Path, PathBuf},
+    string::ToString,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use tari_common::configuration::bootstrap::prompt;
 use tari_common_types::types::PrivateKey;
 use tari_comms::{multiaddr::Multiaddr, peer_manager::PeerFeatures, NodeIdentity};
@@ -151,6 +158,59 @@ pub fn create_new_identity<P: AsRef<Path>>(
     Ok(node_identity)
 }
 
+/// Replace the node identity at `path` with a new one with the same public address and features. The previous
+/// identity file is kept as a backup, and so is the tor identity file if one is given, so that a new hidden service
+/// with a new onion address is created on the next start.
+/// ## Parameters
+/// `path` - Path of the node identity file
+/// `tor_identity_path` - Path of the tor identity file of the hidden service
+/// `current` - The node identity being replaced
+///
+/// ## Returns
+/// Result containing the new node identity, string will indicate reason on error
+pub fn rotate_node_identity<P: AsRef<Path>>(
+    path: P,
+    tor_identity_path: Option<&Path>,
+    current: &NodeIdentity,
+) -> Result<NodeIdentity, String> {
+    let suffix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    backup_file(path.as_ref(), suffix)?;
+    if let Some(tor_identity_path) = tor_identity_path {
+        backup_file(tor_identity_path, suffix)?;
+    }
+    let node_identity = create_new_identity(&path, current.public_address(), current.features())?;
+    info!(
+        target: LOG_TARGET,
+        "Node identity [{}] has been replaced by [{}] with public key {}",
+        current.node_id(),
+        node_identity.node_id(),
+        node_identity.public_key(),
+    );
+    Ok(node_identity)
+}
+
+/// Renames the file to `<file name>.<suffix>.bak`, if it exists
+fn backup_file(path: &Path, suffix: u64) -> Result<Option<PathBuf>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut backup = path.as_os_str().to_os_string();
+    backup.push(format!(".{}.bak", suffix));
+    let backup = PathBuf::from(backup);
+    fs::rename(path, &backup).map_err(|e| {
+        format!(
+            "Could not back up {} to {}. {}",
+            path.display(),
+            backup.display(),
+            e.to_string()
+        )
+    })?;
+    Ok(Some(backup))
+}
+
 /// Recover a node id from a given private key and save it to disk
 /// ## Parameters
 /// `private_key` - The private key
@@ -214,3 +274,62 @@ pub fn save_as_json<P: AsRef<Path>, T: MessageFormat>(path: P, object: &T) -> Re
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn find_backup(dir: &Path, file_name: &str) -> PathBuf {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                let name = path.file_name().unwrap().to_string_lossy();
+                name.starts_with(&format!("{}.", file_name)) && name.ends_with(".bak")
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn it_rotates_the_node_identity_and_keeps_backups() {
+        let dir = tempdir().unwrap();
+        let identity_path = dir.path().join("node_id.json");
+        let tor_identity_path = dir.path().join("tor_id.json");
+        let address = "/ip4/127.0.0.1/tcp/18189".parse::<Multiaddr>().unwrap();
+        let current = create_new_identity(&identity_path, address.clone(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+        fs::write(&tor_identity_path, "tor identity").unwrap();
+
+        let rotated = rotate_node_identity(&identity_path, Some(&tor_identity_path), &current).unwrap();
+        assert_ne!(rotated.public_key(), current.public_key());
+        assert_eq!(rotated.public_address(), address);
+        assert_eq!(rotated.features(), current.features());
+        assert_eq!(
+            load_identity(&identity_path).unwrap().public_key(),
+            rotated.public_key()
+        );
+        // The tor identity is moved aside, so that a new hidden service is created on the next start
+        assert!(!tor_identity_path.exists());
+
+        let backup = load_identity(find_backup(dir.path(), "node_id.json")).unwrap();
+        assert_eq!(backup.public_key(), current.public_key());
+        assert_eq!(backup.secret_key(), current.secret_key());
+        assert_eq!(
+            fs::read_to_string(find_backup(dir.path(), "tor_id.json")).unwrap(),
+            "tor identity"
+        );
+    }
+
+    #[test]
+    fn it_rotates_without_a_tor_identity() {
+        let dir = tempdir().unwrap();
+        let identity_path = dir.path().join("node_id.json");
+        let address = "/ip4/127.0.0.1/tcp/18189".parse::<Multiaddr>().unwrap();
+        let current = create_new_identity(&identity_path, address, PeerFeatures::COMMUNICATION_NODE).unwrap();
+
+        let rotated = rotate_node_identity(&identity_path, Some(&dir.path().join("tor_id.json")), &current).unwrap();
+        assert_ne!(rotated.public_key(), current.public_key());
+        let backup = load_identity(find_backup(dir.path(), "node_id.json")).unwrap();
+        assert_eq!(backup.public_key(), current.public_key());
+    }
+}
@@ -1,9 +1,12 @@
-use crate::{consts, utilities::ExitCodes};
+use crate::{
+    consts,
+    utilities::{ExitCodes, ExitError},
+};
 use config::Config;
-use std::{path::PathBuf, str::FromStr};
+use std::{path::PathBuf, process, str::FromStr};
 use structopt::StructOpt;
 use tari_common::{
-    configuration::{bootstrap::ApplicationType, Network},
+    configuration::{bootstrap::ApplicationType, doctor::ConfigDoctor, Network},
     ConfigBootstrap,
     DatabaseType,
     GlobalConfig,
@@ -13,9 +16,24 @@ pub const LOG_TARGET: &str = "tari::application";
 
 pub fn init_configuration(
     application_type: ApplicationType,
-) -> Result<(ConfigBootstrap, GlobalConfig, Config), ExitCodes> {
+) -> Result<(ConfigBootstrap, GlobalConfig, Config), ExitError> {
     // Parse and validate command-line arguments
-    let mut bootstrap = ConfigBootstrap::from_args();
+    let bootstrap = ConfigBootstrap::from_args();
+    init_configuration_from_bootstrap(application_type, bootstrap)
+}
+
+/// Initialise the configuration from command-line arguments that have already been parsed. This is used by
+/// applications that extend `ConfigBootstrap` with arguments of their own.
+pub fn init_configuration_from_bootstrap(
+    application_type: ApplicationType,
+    mut bootstrap: ConfigBootstrap,
+) -> Result<(ConfigBootstrap, GlobalConfig, Config), ExitError> {
+    if bootstrap.list_profiles {
+        for profile in bootstrap.profiles()? {
+            println!("{}", profile);
+        }
+        process::exit(0);
+    }
 
     // Check and initialize configuration files
     bootstrap.init_dirs(application_type)?;
@@ -26,11 +44,27 @@ pub fn init_configuration(
     // Initialise the logger
     bootstrap.initialize_logging()?;
 
+    if bootstrap.config_doctor {
+        let doctor = ConfigDoctor::diagnose(&bootstrap)?;
+        println!("{}", doctor);
+        process::exit(if doctor.issues().is_empty() { 0 } else { 1 });
+    }
+    // The configuration was loaded above, so a failure to check it does not stop the application
+    match ConfigDoctor::check(&bootstrap) {
+        Ok(issues) => {
+            for issue in issues {
+                log::warn!(target: LOG_TARGET, "{}", issue);
+                eprintln!("Configuration warning: {}", issue);
+            }
+        },
+        Err(err) => log::warn!(target: LOG_TARGET, "Could not check the configuration: {}", err),
+    }
+
     log::info!(target: LOG_TARGET, "{} ({})", application_type, consts::APP_VERSION);
 
     // Populate the configuration struct
     let mut global_config = GlobalConfig::convert_from(application_type, cfg.clone())
-        .map_err(|err| ExitCodes::ConfigError(err.to_string()))?;
+        .map_err(|err| ExitError::new(ExitCodes::ConfigError(err.to_string())).with_source(err))?;
     check_file_paths(&mut global_config, &bootstrap);
 
     if let Some(str) = bootstrap.network.clone() {
@@ -57,6 +91,17 @@ pub fn init_configuration(
     Ok((bootstrap, global_config, cfg))
 }
 
+/// Load and validate the configuration without initialising the logger, which starts threads. A process that forks
+/// uses this to fail on an invalid configuration while it is still attached to the terminal.
+pub fn check_configuration(application_type: ApplicationType, bootstrap: &ConfigBootstrap) -> Result<(), ExitError> {
+    let mut bootstrap = bootstrap.clone();
+    bootstrap.init_dirs(application_type)?;
+    let cfg = bootstrap.load_configuration()?;
+    GlobalConfig::convert_from(application_type, cfg)
+        .map_err(|err| ExitError::new(ExitCodes::ConfigError(err.to_string())).with_source(err))?;
+    Ok(())
+}
+
 fn check_file_paths(config: &mut GlobalConfig, bootstrap: &ConfigBootstrap) {
     let prepend = bootstrap.base_path.clone();
     if !config.data_dir.is_absolute() {
@@ -20,8 +20,11 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod diagnostics;
 pub mod identity_management;
 pub mod initialization;
+pub mod message_signature;
+pub mod telemetry;
 pub mod utilities;
 
 pub mod consts {
@@ -0,0 +1,178 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Signs arbitrary messages with a node or wallet identity key, so that operators can prove that they control a node
+//! or wallet, e.g. when registering it with a third party. The signature is a Schnorr signature over a domain separated
+//! challenge that commits to the signer's public key, and is written as `v1:<public nonce>:<signature>` in hex.
+
+use digest::Digest;
+use rand::rngs::OsRng;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use tari_comms::types::{Challenge, CommsPublicKey, CommsSecretKey};
+use tari_crypto::{
+    keys::{PublicKey, SecretKey},
+    signatures::{SchnorrSignature, SchnorrSignatureError},
+    tari_utilities::{hex::Hex, ByteArray},
+};
+
+/// The version of the signature format. It is part of the signed challenge, so a signature cannot be reinterpreted
+/// under a later version.
+const MESSAGE_SIGNATURE_VERSION: u8 = 1;
+const MESSAGE_SIGNATURE_DOMAIN: &[u8] = b"com.tari.message_signature";
+
+#[derive(Debug, thiserror::Error)]
+pub enum MessageSignatureError {
+    #[error("Unsupported message signature version `{0}`")]
+    UnsupportedVersion(String),
+    #[error("Malformed message signature: {0}")]
+    Malformed(String),
+    #[error("Could not sign the message: {0}")]
+    SigningFailed(#[from] SchnorrSignatureError),
+}
+
+/// A signature over a message, made with the secret key of a node or wallet identity
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageSignature {
+    public_nonce: CommsPublicKey,
+    signature: CommsSecretKey,
+}
+
+impl MessageSignature {
+    pub fn sign(secret_key: &CommsSecretKey, message: &str) -> Result<Self, MessageSignatureError> {
+        let public_key = CommsPublicKey::from_secret_key(secret_key);
+        let nonce = CommsSecretKey::random(&mut OsRng);
+        let public_nonce = CommsPublicKey::from_secret_key(&nonce);
+        let challenge = message_challenge(&public_key, &public_nonce, message);
+        let signature = SchnorrSignature::sign(secret_key.clone(), nonce, &challenge)?;
+        Ok(Self {
+            public_nonce,
+            signature: signature.get_signature().clone(),
+        })
+    }
+
+    /// Returns true if this is a signature over `message` by the owner of `public_key`
+    pub fn verify(&self, public_key: &CommsPublicKey, message: &str) -> bool {
+        let challenge = message_challenge(public_key, &self.public_nonce, message);
+        SchnorrSignature::new(self.public_nonce.clone(), self.signature.clone())
+            .verify_challenge(public_key, &challenge)
+    }
+}
+
+impl Display for MessageSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "v{}:{}:{}",
+            MESSAGE_SIGNATURE_VERSION,
+            self.public_nonce.to_hex(),
+            self.signature.to_hex()
+        )
+    }
+}
+
+impl FromStr for MessageSignature {
+    type Err = MessageSignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+        let version = parts.next().unwrap_or_default();
+        if version != format!("v{}", MESSAGE_SIGNATURE_VERSION) {
+            return Err(MessageSignatureError::UnsupportedVersion(version.to_string()));
+        }
+        let (public_nonce, signature) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(public_nonce), Some(signature), None) => (public_nonce, signature),
+            _ => {
+                return Err(MessageSignatureError::Malformed(
+                    "expected `<version>:<public nonce>:<signature>`".to_string(),
+                ))
+            },
+        };
+        let public_nonce = CommsPublicKey::from_hex(public_nonce)
+            .map_err(|err| MessageSignatureError::Malformed(format!("invalid public nonce: {}", err)))?;
+        let signature = CommsSecretKey::from_hex(signature)
+            .map_err(|err| MessageSignatureError::Malformed(format!("invalid signature: {}", err)))?;
+        Ok(Self {
+            public_nonce,
+            signature,
+        })
+    }
+}
+
+fn message_challenge(public_key: &CommsPublicKey, public_nonce: &CommsPublicKey, message: &str) -> Vec<u8> {
+    Challenge::new()
+        .chain(MESSAGE_SIGNATURE_DOMAIN)
+        .chain([MESSAGE_SIGNATURE_VERSION])
+        .chain(public_key.as_bytes())
+        .chain(public_nonce.as_bytes())
+        .chain(message.as_bytes())
+        .finalize()
+        .to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_verifies_a_signed_message() {
+        let (secret_key, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let signature = MessageSignature::sign(&secret_key, "I own this node").unwrap();
+        assert!(signature.verify(&public_key, "I own this node"));
+        assert!(!signature.verify(&public_key, "I own this node!"));
+
+        let (_, other_public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        assert!(!signature.verify(&other_public_key, "I own this node"));
+    }
+
+    #[test]
+    fn it_round_trips_the_signature_format() {
+        let (secret_key, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let signature = MessageSignature::sign(&secret_key, "message").unwrap();
+        let formatted = signature.to_string();
+        assert!(formatted.starts_with("v1:"));
+        let parsed = formatted.parse::<MessageSignature>().unwrap();
+        assert_eq!(parsed, signature);
+        assert!(parsed.verify(&public_key, "message"));
+    }
+
+    #[test]
+    fn it_rejects_malformed_signatures() {
+        let (secret_key, _) = CommsPublicKey::random_keypair(&mut OsRng);
+        let formatted = MessageSignature::sign(&secret_key, "message").unwrap().to_string();
+        assert!(matches!(
+            formatted.replacen("v1", "v2", 1).parse::<MessageSignature>(),
+            Err(MessageSignatureError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            format!("{}:00", formatted).parse::<MessageSignature>(),
+            Err(MessageSignatureError::Malformed(_))
+        ));
+        assert!(matches!(
+            "v1:zz:zz".parse::<MessageSignature>(),
+            Err(MessageSignatureError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Lightweight process telemetry for the Tari applications: uptime, memory, open files, threads, disk IO and the load
+//! of the tokio runtime. Apart from uptime and the runtime load, the values are read from procfs and are only
+//! available on Linux.
+
+use std::{
+    fs,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::runtime;
+
+static STARTED_AT: AtomicU64 = AtomicU64::new(0);
+static RUNTIME_THREADS: AtomicUsize = AtomicUsize::new(0);
+static RUNTIME_BUSY_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Records the process start time, if it has not been recorded yet
+pub fn init() {
+    let _ = STARTED_AT.compare_exchange(0, unix_time_secs(), Ordering::Relaxed, Ordering::Relaxed);
+}
+
+/// Counts the threads of the runtime built by `builder`, and how many of them are busy running tasks rather than
+/// parked waiting for work
+pub fn instrument_runtime(builder: &mut runtime::Builder) -> &mut runtime::Builder {
+    init();
+    builder
+        .on_thread_start(|| {
+            RUNTIME_THREADS.fetch_add(1, Ordering::Relaxed);
+            RUNTIME_BUSY_THREADS.fetch_add(1, Ordering::Relaxed);
+        })
+        .on_thread_stop(|| {
+            RUNTIME_THREADS.fetch_sub(1, Ordering::Relaxed);
+            RUNTIME_BUSY_THREADS.fetch_sub(1, Ordering::Relaxed);
+        })
+        .on_thread_park(|| {
+            RUNTIME_BUSY_THREADS.fetch_sub(1, Ordering::Relaxed);
+        })
+        .on_thread_unpark(|| {
+            RUNTIME_BUSY_THREADS.fetch_add(1, Ordering::Relaxed);
+        })
+}
+
+/// Starts the tokio-console subscriber when built with the `tokio-console` feature. The subscriber is installed as the
+/// global tracing subscriber, so it cannot be used together with `--tracing-enabled`.
+pub fn enable_tokio_console() {
+    #[cfg(feature = "tokio-console")]
+    console_subscriber::init();
+}
+
+fn unix_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+}
+
+/// The cumulative bytes read from and written to storage by the process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoCounters {
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+impl IoCounters {
+    /// The read and write rates in bytes per second between an earlier sample and this one
+    pub fn rates_since(&self, earlier: &IoCounters, elapsed: Duration) -> (f64, f64) {
+        let secs = elapsed.as_secs_f64();
+        if secs <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (
+            self.read_bytes.saturating_sub(earlier.read_bytes) as f64 / secs,
+            self.write_bytes.saturating_sub(earlier.write_bytes) as f64 / secs,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProcessStats {
+    /// The time since `init` was called, usually at start up
+    pub uptime: Option<Duration>,
+    /// Resident set size in bytes
+    pub resident_memory: Option<u64>,
+    pub open_files: Option<usize>,
+    pub threads: Option<usize>,
+    pub io: Option<IoCounters>,
+    /// The number of tokio runtime threads, including the blocking pool, if the runtime was instrumented
+    pub runtime_threads: Option<usize>,
+    /// The number of tokio runtime threads that are running tasks, rather than waiting for work
+    pub runtime_busy_threads: Option<usize>,
+}
+
+impl ProcessStats {
+    /// Reads the current stats of this process
+    pub fn sample() -> Self {
+        let started_at = STARTED_AT.load(Ordering::Relaxed);
+        let runtime_threads = RUNTIME_THREADS.load(Ordering::Relaxed);
+        let (resident_memory, threads) = fs::read_to_string("/proc/self/status")
+            .map(|status| parse_status(&status))
+            .unwrap_or_default();
+        Self {
+            uptime: Some(started_at)
+                .filter(|started_at| *started_at > 0)
+                .map(|started_at| Duration::from_secs(unix_time_secs().saturating_sub(started_at))),
+            resident_memory,
+            open_files: fs::read_dir("/proc/self/fd").map(|entries| entries.count()).ok(),
+            threads,
+            io: fs::read_to_string("/proc/self/io").ok().and_then(|io| parse_io(&io)),
+            runtime_threads: Some(runtime_threads).filter(|n| *n > 0),
+            runtime_busy_threads: Some(RUNTIME_BUSY_THREADS.load(Ordering::Relaxed))
+                .filter(|_| runtime_threads > 0)
+                .map(|busy| busy.min(runtime_threads)),
+        }
+    }
+}
+
+/// Reads the resident set size in bytes and the number of threads from the contents of `/proc/<pid>/status`
+fn parse_status(status: &str) -> (Option<u64>, Option<usize>) {
+    let mut resident_memory = None;
+    let mut threads = None;
+    for line in status.lines() {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            // e.g. `VmRSS:     12345 kB`
+            Some("VmRSS:") => resident_memory = parts.next().and_then(|kb| kb.parse::<u64>().ok()).map(|kb| kb * 1024),
+            Some("Threads:") => threads = parts.next().and_then(|n| n.parse().ok()),
+            _ => {},
+        }
+    }
+    (resident_memory, threads)
+}
+
+/// Reads the storage IO counters from the contents of `/proc/<pid>/io`
+fn parse_io(io: &str) -> Option<IoCounters> {
+    let mut read_bytes = None;
+    let mut write_bytes = None;
+    for line in io.lines() {
+        let mut parts = line.split(':').map(str::trim);
+        match (parts.next(), parts.next()) {
+            (Some("read_bytes"), Some(value)) => read_bytes = value.parse().ok(),
+            (Some("write_bytes"), Some(value)) => write_bytes = value.parse().ok(),
+            _ => {},
+        }
+    }
+    Some(IoCounters {
+        read_bytes: read_bytes?,
+        write_bytes: write_bytes?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn proc_parsing() {
+        let status = "Name:\ttari_base_node\nVmPeak:\t  900000 kB\nVmRSS:\t  204800 kB\nThreads:\t31\n";
+        assert_eq!(parse_status(status), (Some(204_800 * 1024), Some(31)));
+        assert_eq!(parse_status("Name:\tx\n"), (None, None));
+
+        let io = "rchar: 100\nwchar: 200\nsyscr: 3\nsyscw: 4\nread_bytes: 4096\nwrite_bytes: \
+                  8192\ncancelled_write_bytes: 0\n";
+        assert_eq!(
+            parse_io(io),
+            Some(IoCounters {
+                read_bytes: 4096,
+                write_bytes: 8192
+            })
+        );
+        assert_eq!(parse_io("rchar: 100\n"), None);
+    }
+
+    #[test]
+    fn io_rates() {
+        let earlier = IoCounters {
+            read_bytes: 1000,
+            write_bytes: 500,
+        };
+        let later = IoCounters {
+            read_bytes: 3000,
+            write_bytes: 500,
+        };
+        assert_eq!(later.rates_since(&earlier, Duration::from_secs(2)), (1000.0, 0.0));
+        assert_eq!(later.rates_since(&earlier, Duration::from_secs(0)), (0.0, 0.0));
+    }
+}
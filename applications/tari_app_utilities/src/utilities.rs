@@ -22,6 +22,8 @@
 
 use futures::future::Either;
 use log::*;
+use serde::Serialize;
+use std::{error::Error as StdError, fmt, fs, io, path::Path, sync::Arc};
 use thiserror::Error;
 use tokio::{runtime, runtime::Runtime};
 
@@ -40,7 +42,7 @@ use tari_comms::{
 use tari_core::tari_utilities::hex::Hex;
 use tari_p2p::transport::{TorConfig, TransportType};
 
-use crate::identity_management::load_from_json;
+use crate::{diagnostics, identity_management::load_from_json, telemetry};
 use tari_common_types::emoji::EmojiId;
 
 pub const LOG_TARGET: &str = "tari::application";
@@ -76,6 +78,12 @@ pub enum ExitCodes {
     NoPassword,
     #[error("Tor connection is offline")]
     TorOffline,
+    #[error("The database could not be accessed: {0}")]
+    DatabaseError(String),
+    #[error("The database is in an inconsistent state: {0}")]
+    DbInconsistentState(String),
+    #[error("The node ran out of disk space: {0}")]
+    InsufficientDiskSpace(String),
 }
 
 impl ExitCodes {
@@ -94,6 +102,148 @@ impl ExitCodes {
             Self::ConversionError(_) => 111,
             Self::IncorrectPassword | Self::NoPassword => 112,
             Self::TorOffline => 113,
+            Self::DatabaseError(_) => 114,
+            Self::DbInconsistentState(_) => 115,
+            Self::InsufficientDiskSpace(_) => 116,
+        }
+    }
+
+    /// A suggestion of what to check to resolve the error
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::ConfigError(_) => "Check the configuration file, `--config-doctor` lists the problems found in it",
+            Self::UnknownError | Self::InterfaceError | Self::WalletError(_) => "Check the logs for details",
+            Self::GrpcError(_) => "Check that the gRPC server is running and that its address is configured correctly",
+            Self::InputError(_) | Self::CommandError(_) => "Check the command line arguments and commands",
+            Self::IOError(_) => "Check that the data directory is writable and that there is enough disk space",
+            Self::RecoveryError(_) => "Check the logs for details and run the recovery again",
+            Self::NetworkError(_) => "Check the network connection and the peer seeds in the configuration",
+            Self::ConversionError(_) => "Check that the connected applications are running compatible versions",
+            Self::IncorrectPassword | Self::NoPassword => "Provide the correct password",
+            Self::TorOffline => "Check that the Tor proxy is running and that its control port is turned on",
+            Self::DatabaseError(_) => "Check the free disk space and the permissions of the data directory",
+            Self::DbInconsistentState(_) => "Rebuild the database with `--rebuild-db`, or delete it and sync again",
+            Self::InsufficientDiskSpace(_) => {
+                "Free up space on the disk holding the data directory, or move the data directory to a larger disk"
+            },
+        }
+    }
+}
+
+/// The exit code of an application together with the errors that caused it to exit
+#[derive(Debug, Clone)]
+pub struct ExitError {
+    exit_code: ExitCodes,
+    source: Option<Arc<dyn StdError + Send + Sync>>,
+    diagnostics: Vec<String>,
+}
+
+impl ExitError {
+    pub fn new(exit_code: ExitCodes) -> Self {
+        Self {
+            exit_code,
+            source: None,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Retains the error that caused the exit, its `source()` chain is included in the exit report
+    pub fn with_source<E: Into<Box<dyn StdError + Send + Sync>>>(mut self, source: E) -> Self {
+        self.source = Some(Arc::from(source.into()));
+        self
+    }
+
+    /// Adds context from the environment and recovery steps for the exit code, see [diagnostics::diagnose]
+    pub fn with_diagnostics(mut self, config: &GlobalConfig) -> Self {
+        self.diagnostics = diagnostics::diagnose(&self.exit_code, config);
+        self
+    }
+
+    pub fn diagnostics(&self) -> &[String] {
+        &self.diagnostics
+    }
+
+    pub fn exit_code(&self) -> &ExitCodes {
+        &self.exit_code
+    }
+
+    pub fn as_i32(&self) -> i32 {
+        self.exit_code.as_i32()
+    }
+
+    /// The messages of the source error and each of its sources, outermost first
+    pub fn chain(&self) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut next = self.source();
+        while let Some(err) = next {
+            chain.push(err.to_string());
+            next = err.source();
+        }
+        chain
+    }
+
+    pub fn report(&self) -> ExitReport {
+        ExitReport {
+            code: self.as_i32(),
+            hint: self.exit_code.hint(),
+            details: self.exit_code.to_string(),
+            chain: self.chain(),
+            diagnostics: self.diagnostics.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.exit_code)
+    }
+}
+
+impl StdError for ExitError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.as_deref().map(|err| err as &(dyn StdError + 'static))
+    }
+}
+
+impl From<ExitCodes> for ExitError {
+    fn from(exit_code: ExitCodes) -> Self {
+        Self::new(exit_code)
+    }
+}
+
+impl From<ExitError> for ExitCodes {
+    fn from(err: ExitError) -> Self {
+        err.exit_code
+    }
+}
+
+impl From<tari_common::ConfigError> for ExitError {
+    fn from(err: tari_common::ConfigError) -> Self {
+        error!(target: LOG_TARGET, "{}", err);
+        Self::new(ExitCodes::ConfigError(err.to_string())).with_source(err)
+    }
+}
+
+/// A machine-readable description of why an application exited, for scripts that supervise it
+#[derive(Debug, Clone, Serialize)]
+pub struct ExitReport {
+    pub code: i32,
+    pub hint: &'static str,
+    pub details: String,
+    pub chain: Vec<String>,
+    pub diagnostics: Vec<String>,
+}
+
+impl ExitReport {
+    /// Writes the report as JSON to the given file, or to stderr if no file is given
+    pub fn write(&self, path: Option<&Path>) -> io::Result<()> {
+        let json = serde_json::to_string(self)?;
+        match path {
+            Some(path) => fs::write(path, json),
+            None => {
+                eprintln!("{}", json);
+                Ok(())
+            },
         }
     }
 }
@@ -244,6 +394,18 @@ pub fn create_transport_type(config: &GlobalConfig) -> TransportType {
     }
 }
 
+/// Returns the SOCKS5 proxy configured for fetching software updates, if any
+pub fn create_autoupdate_socks_config(config: &GlobalConfig) -> Option<SocksConfig> {
+    config
+        .autoupdate_socks5_proxy_address
+        .clone()
+        .map(|proxy_address| SocksConfig {
+            proxy_address,
+            authentication: convert_socks_authentication(config.autoupdate_socks5_auth.clone()),
+            proxy_bypass_addresses: vec![],
+        })
+}
+
 /// Converts one socks authentication struct into another
 /// ## Parameters
 /// `auth` - Socks authentication of type SocksAuthentication
@@ -281,7 +443,7 @@ pub fn setup_runtime(config: &GlobalConfig) -> Result<Runtime, String> {
         builder.worker_threads(core_threads);
     }
 
-    builder
+    telemetry::instrument_runtime(&mut builder)
         .enable_all()
         .build()
         .map_err(|e| format!("There was an error while building the node runtime. {}", e.to_string()))
@@ -0,0 +1,342 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Alerts for node operators. Alerts are sent to a webhook, a Slack incoming webhook and/or a Telegram chat when the
+//! node falls behind the network, exits with a fatal error, sees a deep reorg, bans a peer or runs low on disk space.
+
+use log::*;
+use serde_json::json;
+use std::{fmt, path::PathBuf, sync::Arc, time::Duration};
+use tari_common::GlobalConfig;
+use tari_comms::connectivity::{ConnectivityEvent, ConnectivityEventRx};
+use tari_core::{
+    base_node::{
+        comms_interface::{BlockEvent, BlockEventReceiver},
+        state_machine_service::states::{StateEvent, StateInfo, StatusInfo},
+    },
+    chain_storage::BlockAddResult,
+};
+use tari_shutdown::ShutdownSignal;
+use tokio::{
+    sync::{broadcast, broadcast::error::RecvError, watch},
+    task,
+    time,
+};
+
+const LOG_TARGET: &str = "base_node::alerts";
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+const DISK_SPACE_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    FellBehind,
+    FatalError,
+    Reorg,
+    PeerBanned,
+    LowDiskSpace,
+    MiningConcentration,
+    ClockSkew,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AlertKind::FellBehind => "fell_behind",
+            AlertKind::FatalError => "fatal_error",
+            AlertKind::Reorg => "reorg",
+            AlertKind::PeerBanned => "peer_banned",
+            AlertKind::LowDiskSpace => "low_disk_space",
+            AlertKind::MiningConcentration => "mining_concentration",
+            AlertKind::ClockSkew => "clock_skew",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum AlertChannel {
+    /// Receives a JSON object with the alert kind, text and node
+    Webhook(String),
+    /// A Slack incoming webhook
+    Slack(String),
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+    },
+}
+
+/// Sends alerts to the channels configured for the node. When no channels are configured, alerts are only logged.
+#[derive(Clone)]
+pub struct Alerter {
+    client: reqwest::Client,
+    channels: Arc<Vec<AlertChannel>>,
+    node: String,
+}
+
+impl Alerter {
+    /// `node` identifies the node in the alerts, for operators running more than one node
+    pub fn new(config: &GlobalConfig, node: String) -> Result<Self, reqwest::Error> {
+        let mut channels = Vec::new();
+        if let Some(ref url) = config.alert_webhook_url {
+            channels.push(AlertChannel::Webhook(url.clone()));
+        }
+        if let Some(ref url) = config.alert_slack_webhook_url {
+            channels.push(AlertChannel::Slack(url.clone()));
+        }
+        if let (Some(bot_token), Some(chat_id)) = (&config.alert_telegram_bot_token, &config.alert_telegram_chat_id) {
+            channels.push(AlertChannel::Telegram {
+                bot_token: bot_token.clone(),
+                chat_id: chat_id.clone(),
+            });
+        }
+        Ok(Self {
+            client: reqwest::Client::builder().timeout(SEND_TIMEOUT).build()?,
+            channels: Arc::new(channels),
+            node,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.channels.is_empty()
+    }
+
+    /// Sends an alert in the background
+    pub fn notify<T: Into<String>>(&self, kind: AlertKind, text: T) {
+        let alerter = self.clone();
+        let text = text.into();
+        task::spawn(async move { alerter.send(kind, &text).await });
+    }
+
+    /// Sends an alert to every channel, logging the channels that fail
+    pub async fn send(&self, kind: AlertKind, text: &str) {
+        warn!(target: LOG_TARGET, "Alert ({}): {}", kind, text);
+        for channel in self.channels.iter() {
+            if let Err(err) = self.send_to(channel, kind, text).await {
+                warn!(target: LOG_TARGET, "Failed to send a {} alert: {}", kind, err);
+            }
+        }
+    }
+
+    async fn send_to(&self, channel: &AlertChannel, kind: AlertKind, text: &str) -> Result<(), reqwest::Error> {
+        let message = format!("[{}] {}", self.node, text);
+        let request = match channel {
+            AlertChannel::Webhook(url) => self.client.post(url).json(&json!({
+                "kind": kind.to_string(),
+                "text": text,
+                "node": self.node,
+            })),
+            AlertChannel::Slack(url) => self.client.post(url).json(&json!({ "text": message })),
+            AlertChannel::Telegram { bot_token, chat_id } => self
+                .client
+                .post(format!("https://api.telegram.org/bot{}/sendMessage", bot_token))
+                .json(&json!({ "chat_id": chat_id, "text": message })),
+        };
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// The conditions that trigger alerts. A value of 0 disables the trigger.
+#[derive(Debug, Clone)]
+pub struct AlertTriggers {
+    pub blocks_behind: u64,
+    pub reorg_depth: u64,
+    pub peer_ban: bool,
+    pub min_free_disk_space_mb: u64,
+}
+
+impl AlertTriggers {
+    pub fn from_config(config: &GlobalConfig) -> Self {
+        Self {
+            blocks_behind: config.alert_blocks_behind,
+            reorg_depth: config.alert_reorg_depth,
+            peer_ban: config.alert_on_peer_ban,
+            min_free_disk_space_mb: config.alert_min_free_disk_space_mb,
+        }
+    }
+}
+
+/// Watches the state machine, chain events, connectivity events and free disk space, and sends alerts when the
+/// configured triggers fire. Alerts for ongoing conditions (falling behind, low disk space) are sent once, and again
+/// only after the condition has cleared.
+pub struct AlertMonitor {
+    alerter: Alerter,
+    triggers: AlertTriggers,
+    data_dir: PathBuf,
+    status: watch::Receiver<StatusInfo>,
+    state_events: broadcast::Receiver<Arc<StateEvent>>,
+    block_events: BlockEventReceiver,
+    connectivity_events: ConnectivityEventRx,
+}
+
+impl AlertMonitor {
+    pub fn new(
+        alerter: Alerter,
+        triggers: AlertTriggers,
+        data_dir: PathBuf,
+        status: watch::Receiver<StatusInfo>,
+        state_events: broadcast::Receiver<Arc<StateEvent>>,
+        block_events: BlockEventReceiver,
+        connectivity_events: ConnectivityEventRx,
+    ) -> Self {
+        Self {
+            alerter,
+            triggers,
+            data_dir,
+            status,
+            state_events,
+            block_events,
+            connectivity_events,
+        }
+    }
+
+    pub async fn run(mut self, mut shutdown_signal: ShutdownSignal) {
+        let mut fell_behind = false;
+        let mut low_disk_space = false;
+        let mut disk_space_check = time::interval(DISK_SPACE_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                Ok(_) = self.status.changed() => {
+                    let state_info = self.status.borrow().state_info.clone();
+                    match blocks_behind(&state_info) {
+                        Some(behind) if self.triggers.blocks_behind > 0 && behind > self.triggers.blocks_behind => {
+                            if !fell_behind {
+                                fell_behind = true;
+                                self.alerter.notify(
+                                    AlertKind::FellBehind,
+                                    format!("The node is {} blocks behind the network and is syncing", behind),
+                                );
+                            }
+                        },
+                        _ if state_info.is_synced() => fell_behind = false,
+                        _ => {},
+                    }
+                },
+                event = self.state_events.recv() => match event {
+                    Ok(event) => {
+                        if let StateEvent::FatalError(reason) = event.as_ref() {
+                            self.alerter.notify(
+                                AlertKind::FatalError,
+                                format!("The state machine stopped with a fatal error: {}", reason),
+                            );
+                        }
+                    },
+                    Err(RecvError::Lagged(_)) => {},
+                    Err(RecvError::Closed) => break,
+                },
+                event = self.block_events.recv() => match event {
+                    Ok(event) => {
+                        if let Some(depth) = reorg_depth(&event) {
+                            if self.triggers.reorg_depth > 0 && depth >= self.triggers.reorg_depth {
+                                self.alerter.notify(
+                                    AlertKind::Reorg,
+                                    format!("A reorg removed {} block(s) from the chain", depth),
+                                );
+                            }
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Missed {} block event(s), reorg alerts may not have been sent", n);
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                event = self.connectivity_events.recv() => match event {
+                    Ok(ConnectivityEvent::PeerBanned(node_id)) if self.triggers.peer_ban => {
+                        self.alerter.notify(AlertKind::PeerBanned, format!("Banned peer {}", node_id));
+                    },
+                    Ok(_) | Err(RecvError::Lagged(_)) => {},
+                    Err(RecvError::Closed) => break,
+                },
+                _ = disk_space_check.tick(), if self.triggers.min_free_disk_space_mb > 0 => {
+                    let min_free = self.triggers.min_free_disk_space_mb * BYTES_PER_MB;
+                    match fs2::available_space(&self.data_dir) {
+                        Ok(available) if available < min_free => {
+                            if !low_disk_space {
+                                low_disk_space = true;
+                                self.alerter.notify(
+                                    AlertKind::LowDiskSpace,
+                                    format!(
+                                        "Only {} MiB of disk space is left for {}",
+                                        available / BYTES_PER_MB,
+                                        self.data_dir.display()
+                                    ),
+                                );
+                            }
+                        },
+                        Ok(_) => low_disk_space = false,
+                        Err(err) => {
+                            debug!(target: LOG_TARGET, "Unable to check the free disk space: {}", err);
+                        },
+                    }
+                },
+                _ = shutdown_signal.wait() => {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// The number of blocks the node is behind the network while it is syncing
+fn blocks_behind(state_info: &StateInfo) -> Option<u64> {
+    match state_info {
+        StateInfo::HeaderSync(Some(info)) | StateInfo::BlockSync(info) => {
+            Some(info.tip_height.saturating_sub(info.local_height))
+        },
+        _ => None,
+    }
+}
+
+/// The number of blocks removed from the main chain by a reorg
+fn reorg_depth(event: &BlockEvent) -> Option<u64> {
+    match event {
+        BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { removed, .. }, _) => Some(removed.len() as u64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_common::SyncMode;
+    use tari_core::base_node::state_machine_service::states::BlockSyncInfo;
+
+    #[test]
+    fn sync_distance() {
+        assert_eq!(blocks_behind(&StateInfo::StartUp), None);
+        assert_eq!(blocks_behind(&StateInfo::HeaderSync(None)), None);
+        let info = BlockSyncInfo::new(1_000, 950, vec![], SyncMode::Archival);
+        assert_eq!(blocks_behind(&StateInfo::HeaderSync(Some(info.clone()))), Some(50));
+        assert_eq!(blocks_behind(&StateInfo::BlockSync(info)), Some(50));
+        // The local chain can briefly be ahead of the sync peer's advertised tip
+        let info = BlockSyncInfo::new(950, 1_000, vec![], SyncMode::Pruned);
+        assert_eq!(blocks_behind(&StateInfo::BlockSync(info)), Some(0));
+    }
+
+    #[test]
+    fn alert_kind_names() {
+        assert_eq!(AlertKind::FellBehind.to_string(), "fell_behind");
+        assert_eq!(AlertKind::LowDiskSpace.to_string(), "low_disk_space");
+    }
+}
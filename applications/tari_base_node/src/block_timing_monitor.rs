@@ -0,0 +1,583 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Rolling analysis of the time between blocks. The monitor follows the block event stream and warns when blocks are
+//! much slower or faster than the target block interval, or when their timestamps look manipulated: timestamps
+//! earlier than the parent block, or repeated timestamps close to the future time limit for the same proof of work
+//! algorithm.
+
+use chrono::{DateTime, Utc};
+use log::*;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+};
+use tari_common_types::types::HashOutput;
+use tari_core::{
+    base_node::comms_interface::{BlockEvent, BlockEventReceiver},
+    blocks::BlockHeader,
+    chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, LMDBDatabase},
+    consensus::ConsensusManager,
+    proof_of_work::PowAlgorithm,
+};
+use tari_crypto::tari_utilities::Hashable;
+use tari_shutdown::ShutdownSignal;
+use tokio::sync::{broadcast::error::RecvError, watch};
+
+const LOG_TARGET: &str = "base_node::block_timing_monitor";
+
+/// A block that took this many times the target block interval is reported as slow
+const SLOW_BLOCK_FACTOR: f64 = 10.0;
+/// The blocks in the window are reported as fast if their average interval is less than the target block interval
+/// divided by this factor
+const FAST_BLOCKS_FACTOR: f64 = 4.0;
+/// A timestamp is close to the future time limit if it is ahead of the local clock by this fraction of the limit
+const NEAR_FUTURE_LIMIT_FRACTION: f64 = 0.75;
+/// The number of suspicious timestamps in the window at which a warning is raised
+const REPEATED_TIMESTAMP_COUNT: usize = 3;
+/// The number of anomalies kept in the status
+const MAX_RECENT_ANOMALIES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockTimingAnomaly {
+    SlowBlock {
+        height: u64,
+        block_time: u64,
+        target: f64,
+    },
+    FastBlocks {
+        height: u64,
+        num_blocks: usize,
+        average_block_time: f64,
+        target: f64,
+    },
+    FutureTimestamps {
+        height: u64,
+        pow_algo: PowAlgorithm,
+        count: usize,
+        num_blocks: usize,
+    },
+    BackwardTimestamps {
+        height: u64,
+        count: usize,
+        num_blocks: usize,
+    },
+}
+
+impl fmt::Display for BlockTimingAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockTimingAnomaly::SlowBlock {
+                height,
+                block_time,
+                target,
+            } => write!(
+                f,
+                "Block #{} took {}s, the target block interval is {:.0}s",
+                height, block_time, target
+            ),
+            BlockTimingAnomaly::FastBlocks {
+                height,
+                num_blocks,
+                average_block_time,
+                target,
+            } => write!(
+                f,
+                "The {} blocks up to #{} were mined every {:.0}s on average, the target block interval is {:.0}s",
+                num_blocks, height, average_block_time, target
+            ),
+            BlockTimingAnomaly::FutureTimestamps {
+                height,
+                pow_algo,
+                count,
+                num_blocks,
+            } => write!(
+                f,
+                "{} of the {} blocks up to #{} were {:?} blocks with a timestamp close to the future time limit",
+                count, num_blocks, height, pow_algo
+            ),
+            BlockTimingAnomaly::BackwardTimestamps {
+                height,
+                count,
+                num_blocks,
+            } => write!(
+                f,
+                "{} of the {} blocks up to #{} have a timestamp earlier than their parent block",
+                count, num_blocks, height
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TimingSample {
+    height: u64,
+    hash: HashOutput,
+    timestamp: u64,
+    pow_algo: PowAlgorithm,
+    /// Seconds since the parent block, negative if the timestamp is earlier than the parent's
+    block_time: Option<i64>,
+    near_future_limit: bool,
+}
+
+/// Keeps the timing of the most recent blocks of the chain and detects anomalies as blocks are added
+pub struct BlockTimingAnalyzer {
+    window: usize,
+    target_block_time: f64,
+    samples: VecDeque<TimingSample>,
+    fast_blocks_reported: bool,
+    future_timestamps_reported: HashSet<PowAlgorithm>,
+    backward_timestamps_reported: bool,
+}
+
+impl BlockTimingAnalyzer {
+    pub fn new(window: usize, target_block_time: f64) -> Self {
+        Self {
+            window: window.max(2),
+            target_block_time,
+            samples: VecDeque::with_capacity(window),
+            fast_blocks_reported: false,
+            future_timestamps_reported: HashSet::new(),
+            backward_timestamps_reported: false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.fast_blocks_reported = false;
+        self.future_timestamps_reported.clear();
+        self.backward_timestamps_reported = false;
+    }
+
+    pub fn set_target_block_time(&mut self, target_block_time: f64) {
+        self.target_block_time = target_block_time;
+    }
+
+    pub fn target_block_time(&self) -> f64 {
+        self.target_block_time
+    }
+
+    /// The average time between the blocks in the window, in seconds
+    pub fn average_block_time(&self) -> Option<f64> {
+        let first = self.samples.front()?;
+        let last = self.samples.back()?;
+        if last.height <= first.height {
+            return None;
+        }
+        Some((last.timestamp as f64 - first.timestamp as f64) / (last.height - first.height) as f64)
+    }
+
+    /// Adds a block that extends the chain and returns the anomalies it caused. `ahead_of_clock` is the number of
+    /// seconds the block's timestamp was ahead of the local clock when it was received, and `future_time_limit` is
+    /// how far ahead a timestamp may be. A block that does not follow the last block clears the window.
+    ///
+    /// Warnings about the state of the window are raised once, and again only after the window has recovered.
+    pub fn add_block(
+        &mut self,
+        header: &BlockHeader,
+        ahead_of_clock: Option<i64>,
+        future_time_limit: u64,
+    ) -> Vec<BlockTimingAnomaly> {
+        let block_time = match self.samples.back() {
+            Some(parent) if parent.hash == header.prev_hash => {
+                Some(header.timestamp.as_u64() as i64 - parent.timestamp as i64)
+            },
+            _ => {
+                self.clear();
+                None
+            },
+        };
+        let near_future_limit = ahead_of_clock
+            .map(|secs| secs as f64 >= future_time_limit as f64 * NEAR_FUTURE_LIMIT_FRACTION)
+            .unwrap_or(false);
+        self.samples.push_back(TimingSample {
+            height: header.height,
+            hash: header.hash(),
+            timestamp: header.timestamp.as_u64(),
+            pow_algo: header.pow_algo(),
+            block_time,
+            near_future_limit,
+        });
+        while self.samples.len() > self.window {
+            self.samples.pop_front();
+        }
+
+        let height = header.height;
+        let num_blocks = self.samples.len();
+        let mut anomalies = Vec::new();
+
+        if let Some(block_time) = block_time {
+            if block_time as f64 > self.target_block_time * SLOW_BLOCK_FACTOR {
+                anomalies.push(BlockTimingAnomaly::SlowBlock {
+                    height,
+                    block_time: block_time as u64,
+                    target: self.target_block_time,
+                });
+            }
+        }
+
+        if num_blocks == self.window {
+            if let Some(average_block_time) = self.average_block_time() {
+                let is_fast = average_block_time < self.target_block_time / FAST_BLOCKS_FACTOR;
+                if is_fast && !self.fast_blocks_reported {
+                    anomalies.push(BlockTimingAnomaly::FastBlocks {
+                        height,
+                        num_blocks,
+                        average_block_time,
+                        target: self.target_block_time,
+                    });
+                }
+                self.fast_blocks_reported = is_fast;
+            }
+        }
+
+        let pow_algo = header.pow_algo();
+        let count = self
+            .samples
+            .iter()
+            .filter(|s| s.pow_algo == pow_algo && s.near_future_limit)
+            .count();
+        if count < REPEATED_TIMESTAMP_COUNT {
+            self.future_timestamps_reported.remove(&pow_algo);
+        } else if self.future_timestamps_reported.insert(pow_algo) {
+            anomalies.push(BlockTimingAnomaly::FutureTimestamps {
+                height,
+                pow_algo,
+                count,
+                num_blocks,
+            });
+        }
+
+        let count = self
+            .samples
+            .iter()
+            .filter(|s| s.block_time.map(|t| t < 0).unwrap_or(false))
+            .count();
+        if count < REPEATED_TIMESTAMP_COUNT {
+            self.backward_timestamps_reported = false;
+        } else if !self.backward_timestamps_reported {
+            self.backward_timestamps_reported = true;
+            anomalies.push(BlockTimingAnomaly::BackwardTimestamps {
+                height,
+                count,
+                num_blocks,
+            });
+        }
+
+        anomalies
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BlockTimingStatus {
+    pub height: u64,
+    /// The average time between the blocks in the window, in seconds
+    pub average_block_time: Option<f64>,
+    pub target_block_time: f64,
+    pub recent_anomalies: Vec<(DateTime<Utc>, BlockTimingAnomaly)>,
+}
+
+impl BlockTimingStatus {
+    pub fn num_anomalies_since(&self, since: DateTime<Utc>) -> usize {
+        self.recent_anomalies.iter().filter(|(at, _)| *at >= since).count()
+    }
+}
+
+/// Handle to the latest block timing status
+#[derive(Clone)]
+pub struct BlockTimingHandle {
+    status: watch::Receiver<BlockTimingStatus>,
+    is_enabled: bool,
+}
+
+impl BlockTimingHandle {
+    /// A handle for when the block timing window is set to 0
+    pub fn disabled() -> Self {
+        let (_, status) = watch::channel(BlockTimingStatus::default());
+        Self {
+            status,
+            is_enabled: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn status(&self) -> BlockTimingStatus {
+        self.status.borrow().clone()
+    }
+}
+
+/// Follows the blocks added to the chain and warns about block timing anomalies
+pub struct BlockTimingMonitor {
+    analyzer: BlockTimingAnalyzer,
+    window: usize,
+    db: AsyncBlockchainDb<LMDBDatabase>,
+    consensus_rules: ConsensusManager,
+    block_events: BlockEventReceiver,
+    status: watch::Sender<BlockTimingStatus>,
+}
+
+impl BlockTimingMonitor {
+    pub fn new(
+        window: usize,
+        db: AsyncBlockchainDb<LMDBDatabase>,
+        consensus_rules: ConsensusManager,
+        block_events: BlockEventReceiver,
+    ) -> (Self, BlockTimingHandle) {
+        let (status, status_rx) = watch::channel(BlockTimingStatus::default());
+        let handle = BlockTimingHandle {
+            status: status_rx,
+            is_enabled: true,
+        };
+        let monitor = Self {
+            analyzer: BlockTimingAnalyzer::new(window, 0.0),
+            window,
+            db,
+            consensus_rules,
+            block_events,
+            status,
+        };
+        (monitor, handle)
+    }
+
+    pub async fn run(mut self, mut shutdown_signal: ShutdownSignal) {
+        self.reload().await;
+        loop {
+            tokio::select! {
+                event = self.block_events.recv() => match event {
+                    Ok(event) => self.handle_event(&event).await,
+                    Err(RecvError::Lagged(n)) => {
+                        debug!(target: LOG_TARGET, "Missed {} block event(s), reloading the window", n);
+                        self.reload().await;
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown_signal.wait() => {
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn handle_event(&mut self, event: &BlockEvent) {
+        match event {
+            BlockEvent::ValidBlockAdded(block, BlockAddResult::Ok(_), _) => {
+                let header = &block.header;
+                self.update_target_block_time(header.height);
+                let ahead_of_clock = header.timestamp.as_u64() as i64 - Utc::now().timestamp();
+                let future_time_limit = self.future_time_limit(header.height);
+                let anomalies = self.analyzer.add_block(header, Some(ahead_of_clock), future_time_limit);
+                self.report(anomalies);
+                self.update_status(header.height);
+            },
+            BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { .. }, _) |
+            BlockEvent::BlockSyncComplete(_) |
+            BlockEvent::BlockSyncRewind(_) => {
+                self.reload().await;
+            },
+            _ => {},
+        }
+    }
+
+    /// Fills the window with the headers at the chain tip. Anomalies in these blocks are logged, but not reported as
+    /// warnings, since the local receive times of these blocks are unknown.
+    async fn reload(&mut self) {
+        let tip = match self.db.get_chain_metadata().await {
+            Ok(metadata) => metadata.height_of_longest_chain(),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Could not read the local chain metadata: {}", err);
+                return;
+            },
+        };
+        let start = (tip + 1).saturating_sub(self.window as u64);
+        let headers = match self.db.fetch_headers(start..=tip).await {
+            Ok(headers) => headers,
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Could not fetch the headers at the chain tip: {}", err);
+                return;
+            },
+        };
+        self.update_target_block_time(tip);
+        self.analyzer.clear();
+        for header in &headers {
+            for anomaly in self.analyzer.add_block(header, None, 0) {
+                info!(target: LOG_TARGET, "{}", anomaly);
+            }
+        }
+        self.update_status(tip);
+    }
+
+    fn report(&mut self, anomalies: Vec<BlockTimingAnomaly>) {
+        if anomalies.is_empty() {
+            return;
+        }
+        let now = Utc::now();
+        let mut status = self.status.borrow().clone();
+        for anomaly in anomalies {
+            warn!(target: LOG_TARGET, "Block timing anomaly: {}", anomaly);
+            println!("WARNING: {}", anomaly);
+            status.recent_anomalies.push((now, anomaly));
+        }
+        let excess = status.recent_anomalies.len().saturating_sub(MAX_RECENT_ANOMALIES);
+        status.recent_anomalies.drain(..excess);
+        let _ = self.status.send(status);
+    }
+
+    fn update_status(&self, height: u64) {
+        let mut status = self.status.borrow().clone();
+        status.height = height;
+        status.average_block_time = self.analyzer.average_block_time();
+        status.target_block_time = self.analyzer.target_block_time();
+        let _ = self.status.send(status);
+    }
+
+    /// The chain's target block interval is the combined target of the proof of work algorithms
+    fn update_target_block_time(&mut self, height: u64) {
+        let constants = self.consensus_rules.consensus_constants(height);
+        let rate = [PowAlgorithm::Sha3, PowAlgorithm::Monero]
+            .iter()
+            .map(|algo| constants.get_diff_target_block_interval(*algo))
+            .filter(|target| *target > 0)
+            .map(|target| 1.0 / target as f64)
+            .sum::<f64>();
+        if rate > 0.0 {
+            self.analyzer.set_target_block_time(1.0 / rate);
+        }
+    }
+
+    fn future_time_limit(&self, height: u64) -> u64 {
+        let constants = self.consensus_rules.consensus_constants(height);
+        (constants.ftl_as_time() - Utc::now()).num_seconds().max(0) as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TARGET: f64 = 120.0;
+    const FTL: u64 = 540;
+
+    fn next_header(parent: Option<&BlockHeader>, block_time: i64, pow_algo: PowAlgorithm) -> BlockHeader {
+        let mut header = BlockHeader::new(0);
+        header.pow.pow_algo = pow_algo;
+        match parent {
+            Some(parent) => {
+                header.height = parent.height + 1;
+                header.prev_hash = parent.hash();
+                header.timestamp = ((parent.timestamp.as_u64() as i64 + block_time) as u64).into();
+            },
+            None => {
+                header.timestamp = 1_600_000_000u64.into();
+            },
+        }
+        header
+    }
+
+    fn add_chain(
+        analyzer: &mut BlockTimingAnalyzer,
+        parent: &mut BlockHeader,
+        block_times: &[i64],
+        ahead_of_clock: Option<i64>,
+    ) -> Vec<BlockTimingAnomaly> {
+        let mut anomalies = Vec::new();
+        for block_time in block_times {
+            let header = next_header(Some(parent), *block_time, PowAlgorithm::Sha3);
+            anomalies.extend(analyzer.add_block(&header, ahead_of_clock, FTL));
+            *parent = header;
+        }
+        anomalies
+    }
+
+    #[test]
+    fn it_detects_slow_and_fast_blocks() {
+        let mut analyzer = BlockTimingAnalyzer::new(10, TARGET);
+        let mut tip = next_header(None, 0, PowAlgorithm::Sha3);
+        assert!(analyzer.add_block(&tip, None, FTL).is_empty());
+        assert!(add_chain(&mut analyzer, &mut tip, &[120; 9], None).is_empty());
+        assert_eq!(analyzer.average_block_time(), Some(120.0));
+
+        let anomalies = add_chain(&mut analyzer, &mut tip, &[1500], None);
+        assert_eq!(anomalies, vec![BlockTimingAnomaly::SlowBlock {
+            height: 10,
+            block_time: 1500,
+            target: TARGET
+        }]);
+
+        let anomalies = add_chain(&mut analyzer, &mut tip, &[5; 10], None);
+        assert_eq!(anomalies.len(), 1);
+        assert!(matches!(anomalies[0], BlockTimingAnomaly::FastBlocks {
+            height: 19,
+            ..
+        }));
+        // Reported once while the blocks stay fast
+        assert!(add_chain(&mut analyzer, &mut tip, &[5; 5], None).is_empty());
+    }
+
+    #[test]
+    fn it_detects_repeated_future_timestamps() {
+        let mut analyzer = BlockTimingAnalyzer::new(10, TARGET);
+        let mut tip = next_header(None, 0, PowAlgorithm::Sha3);
+        analyzer.add_block(&tip, None, FTL);
+        assert!(add_chain(&mut analyzer, &mut tip, &[120; 2], Some(500)).is_empty());
+        // Other algorithms are counted separately
+        let header = next_header(Some(&tip), 120, PowAlgorithm::Monero);
+        assert!(analyzer.add_block(&header, Some(500), FTL).is_empty());
+        tip = header;
+        let anomalies = add_chain(&mut analyzer, &mut tip, &[120], Some(500));
+        assert_eq!(anomalies, vec![BlockTimingAnomaly::FutureTimestamps {
+            height: 4,
+            pow_algo: PowAlgorithm::Sha3,
+            count: 3,
+            num_blocks: 5
+        }]);
+        assert!(add_chain(&mut analyzer, &mut tip, &[120], Some(10)).is_empty());
+    }
+
+    #[test]
+    fn it_detects_backward_timestamps() {
+        let mut analyzer = BlockTimingAnalyzer::new(10, TARGET);
+        let mut tip = next_header(None, 0, PowAlgorithm::Sha3);
+        analyzer.add_block(&tip, None, FTL);
+        let anomalies = add_chain(&mut analyzer, &mut tip, &[300, -60, 300, -60, 300, -60], None);
+        assert_eq!(anomalies, vec![BlockTimingAnomaly::BackwardTimestamps {
+            height: 6,
+            count: 3,
+            num_blocks: 7
+        }]);
+    }
+
+    #[test]
+    fn it_clears_the_window_when_a_block_does_not_follow_the_tip() {
+        let mut analyzer = BlockTimingAnalyzer::new(10, TARGET);
+        let mut tip = next_header(None, 0, PowAlgorithm::Sha3);
+        analyzer.add_block(&tip, None, FTL);
+        add_chain(&mut analyzer, &mut tip, &[60; 5], None);
+        let mut other = next_header(None, 0, PowAlgorithm::Sha3);
+        other.height = 5;
+        assert!(analyzer.add_block(&other, None, FTL).is_empty());
+        assert_eq!(analyzer.average_block_time(), None);
+    }
+}
@@ -25,16 +25,29 @@ use std::{cmp, fs, str::FromStr, sync::Arc, time::Duration};
 use anyhow::anyhow;
 use log::*;
 
-use tari_app_utilities::{consts, identity_management, utilities::create_transport_type};
+use tari_app_utilities::{
+    consts,
+    identity_management,
+    utilities::{create_autoupdate_socks_config, create_transport_type},
+};
 use tari_common::{configuration::bootstrap::ApplicationType, GlobalConfig};
-use tari_comms::{peer_manager::Peer, protocol::rpc::RpcServer, NodeIdentity, UnspawnedCommsNode};
+use tari_comms::{
+    peer_manager::{Peer, PeerCapabilities},
+    protocol::rpc::RpcServer,
+    NodeIdentity,
+    UnspawnedCommsNode,
+};
 use tari_comms_dht::{DbConnectionUrl, Dht, DhtConfig};
 use tari_core::{
     base_node,
     base_node::{
-        chain_metadata_service::ChainMetadataServiceInitializer,
+        chain_metadata_service::{ChainMetadataServiceConfig, ChainMetadataServiceInitializer},
+        rpc::BalanceAuditConfig,
         service::{BaseNodeServiceConfig, BaseNodeServiceInitializer},
-        state_machine_service::{initializer::BaseNodeStateMachineInitializer, states::HorizonSyncConfig},
+        state_machine_service::{
+            initializer::BaseNodeStateMachineInitializer,
+            states::{HorizonSyncConfig, WarmupConfig},
+        },
         BaseNodeStateMachineConfig,
         BlockSyncConfig,
         StateMachineHandle,
@@ -45,6 +58,7 @@ use tari_core::{
     mempool::{
         service::MempoolHandle,
         Mempool,
+        MempoolConfig,
         MempoolServiceConfig,
         MempoolServiceInitializer,
         MempoolSyncInitializer,
@@ -61,19 +75,30 @@ use tari_p2p::{
 };
 use tari_service_framework::{ServiceHandles, StackBuilder};
 use tari_shutdown::ShutdownSignal;
+use tokio::sync::watch;
 
 const LOG_TARGET: &str = "c::bn::initialization";
 /// The minimum buffer size for the base node pubsub_connector channel
 const BASE_NODE_BUFFER_MIN_SIZE: usize = 30;
+/// Seed nodes accept many more inbound connections and sessions than a regular node
+const SEED_MODE_MAX_CONCURRENT_INBOUND_TASKS: usize = 500;
+const SEED_MODE_MIN_RPC_SESSIONS: usize = 10_000;
+const SEED_MODE_LISTENER_LIVENESS_MAX_SESSIONS: usize = 50;
+/// Seed nodes keep a larger random peer pool so that the peer lists they share cover more of the network
+const SEED_MODE_NUM_RANDOM_NODES: usize = 16;
+/// The file in the data directory that holds the recently connected base nodes
+const PEER_SNAPSHOT_FILE_NAME: &str = "peer_snapshot.json";
 
 pub struct BaseNodeBootstrapper<'a, B> {
     pub config: &'a GlobalConfig,
     pub node_identity: Arc<NodeIdentity>,
     pub db: BlockchainDatabase<B>,
     pub mempool: Mempool,
+    pub mempool_config_updates: watch::Receiver<MempoolConfig>,
     pub rules: ConsensusManager,
     pub factories: CryptoFactories,
     pub interrupt_signal: ShutdownSignal,
+    pub comms_shutdown_signal: ShutdownSignal,
 }
 
 impl<B> BaseNodeBootstrapper<'_, B>
@@ -81,6 +106,12 @@ where B: BlockchainBackend + 'static
 {
     pub async fn bootstrap(self) -> Result<ServiceHandles, anyhow::Error> {
         let config = self.config;
+        if config.seed_mode {
+            info!(
+                target: LOG_TARGET,
+                "Seed mode is enabled: inbound limits are raised and the wallet RPC service is disabled"
+            );
+        }
 
         fs::create_dir_all(&config.peer_db_path)?;
 
@@ -94,7 +125,13 @@ where B: BlockchainBackend + 'static
             fetch_utxos_timeout: config.fetch_utxos_timeout,
             ..Default::default()
         };
-        let mempool_config = MempoolServiceConfig::default(); // TODO - make this configurable
+        let mempool_config = MempoolServiceConfig {
+            min_relay_fee_per_gram: config.min_relay_fee_per_gram.into(),
+            ingress_workers: config.mempool_ingress_workers,
+            ingress_queue_size: config.mempool_ingress_queue_size,
+            ingress_max_queued_per_peer: config.mempool_ingress_max_queued_per_peer,
+            ..Default::default()
+        };
 
         let comms_config = self.create_comms_config();
         let transport_type = comms_config.transport_type.clone();
@@ -115,7 +152,9 @@ where B: BlockchainBackend + 'static
         let mempool_protocol = mempool_sync.get_protocol_extension();
 
         let mut handles = StackBuilder::new(self.interrupt_signal)
-            .add_initializer(P2pInitializer::new(comms_config, publisher))
+            .add_initializer(
+                P2pInitializer::new(comms_config, publisher).with_shutdown_signal(self.comms_shutdown_signal),
+            )
             .add_initializer(SoftwareUpdaterService::new(
                 ApplicationType::BaseNode,
                 consts::APP_VERSION_NUMBER
@@ -128,6 +167,7 @@ where B: BlockchainBackend + 'static
                     download_base_url: "https://tari-binaries.s3.amazonaws.com/latest".to_string(),
                     hashes_url: config.autoupdate_hashes_url.clone(),
                     hashes_sig_url: config.autoupdate_hashes_sig_url.clone(),
+                    socks_proxy: create_autoupdate_socks_config(config),
                 },
                 config.autoupdate_check_interval,
             ))
@@ -138,11 +178,14 @@ where B: BlockchainBackend + 'static
                 self.rules.clone(),
                 node_config,
             ))
-            .add_initializer(MempoolServiceInitializer::new(
-                mempool_config,
-                self.mempool.clone(),
-                peer_message_subscriptions.clone(),
-            ))
+            .add_initializer(
+                MempoolServiceInitializer::new(
+                    mempool_config,
+                    self.mempool.clone(),
+                    peer_message_subscriptions.clone(),
+                )
+                .with_config_updates(self.mempool_config_updates),
+            )
             .add_initializer(mempool_sync)
             .add_initializer(LivenessInitializer::new(
                 LivenessConfig {
@@ -153,19 +196,30 @@ where B: BlockchainBackend + 'static
                 },
                 peer_message_subscriptions,
             ))
-            .add_initializer(ChainMetadataServiceInitializer)
+            .add_initializer(ChainMetadataServiceInitializer::new(ChainMetadataServiceConfig {
+                min_broadcast_interval: Duration::from_secs(config.auto_ping_interval),
+                max_broadcast_interval: Duration::from_secs(config.auto_ping_interval_max),
+            }))
             .add_initializer(BaseNodeStateMachineInitializer::new(
                 self.db.clone().into(),
                 BaseNodeStateMachineConfig {
                     block_sync_config: BlockSyncConfig {
                         sync_peers,
+                        commit_batch_size: config.block_sync_commit_batch_size,
                         ..Default::default()
                     },
                     horizon_sync_config: HorizonSyncConfig {
                         horizon_sync_height_offset: rules.consensus_constants(0).coinbase_lock_height() + 50,
                         ..Default::default()
                     },
+                    warmup_config: WarmupConfig {
+                        num_recent_peers: config.warmup_recent_peers,
+                        num_sync_candidates: config.warmup_sync_candidates,
+                        timeout: config.warmup_timeout,
+                        sleep_threshold: config.warmup_sleep_threshold,
+                    },
                     pruning_horizon: config.pruning_horizon,
+                    sync_mode: config.sync_mode,
                     orphan_db_clean_out_threshold: config.orphan_db_clean_out_threshold,
                     max_randomx_vms: config.max_randomx_vms,
                     blocks_behind_before_considered_lagging: self.config.blocks_behind_before_considered_lagging,
@@ -208,6 +262,9 @@ where B: BlockchainBackend + 'static
         let dht = handles.expect_handle::<Dht>();
         let builder = RpcServer::builder();
         let builder = match config.rpc_max_simultaneous_sessions {
+            Some(limit) if config.seed_mode => {
+                builder.with_maximum_simultaneous_sessions(cmp::max(limit, SEED_MODE_MIN_RPC_SESSIONS))
+            },
             Some(limit) => builder.with_maximum_simultaneous_sessions(limit),
             None => {
                 warn!(
@@ -217,6 +274,14 @@ where B: BlockchainBackend + 'static
                 builder.with_unlimited_simultaneous_sessions()
             },
         };
+        let builder = match config.rpc_max_sessions_per_peer {
+            Some(limit) => builder.with_maximum_sessions_per_client(limit),
+            None => builder,
+        };
+        let builder = match config.rpc_session_bandwidth_limit {
+            Some(limit) => builder.with_session_bandwidth_limit(limit),
+            None => builder,
+        };
         let rpc_server = builder.finish();
         handles.register(rpc_server.get_handle());
 
@@ -226,17 +291,41 @@ where B: BlockchainBackend + 'static
             .add_service(base_node::create_base_node_sync_rpc_service(db.clone()))
             .add_service(mempool::create_mempool_rpc_service(
                 handles.expect_handle::<MempoolHandle>(),
-            ))
+            ));
+
+        // Seed nodes serve peers and headers, wallets are expected to use regular base nodes
+        if config.seed_mode {
+            return comms.add_protocol_extension(rpc_server);
+        }
+
+        let rpc_server = rpc_server
+            .add_service(base_node::rpc::create_light_client_rpc_service(db.clone()))
             .add_service(base_node::rpc::create_base_node_wallet_rpc_service(
-                db,
+                db.clone(),
                 handles.expect_handle::<MempoolHandle>(),
                 handles.expect_handle::<StateMachineHandle>(),
             ));
 
+        if config.balance_audit_enabled {
+            info!(
+                target: LOG_TARGET,
+                "Balance audit RPC is enabled (one audit per peer every {:.0?})", config.balance_audit_min_interval
+            );
+            let rpc_server = rpc_server.add_service(base_node::rpc::create_balance_audit_rpc_service(
+                db,
+                BalanceAuditConfig {
+                    min_interval: config.balance_audit_min_interval,
+                    ..Default::default()
+                },
+            ));
+            return comms.add_protocol_extension(rpc_server);
+        }
+
         comms.add_protocol_extension(rpc_server)
     }
 
     fn create_comms_config(&self) -> P2pConfig {
+        let seed_mode = self.config.seed_mode;
         P2pConfig {
             network: self.config.network,
             node_identity: self.node_identity.clone(),
@@ -244,7 +333,11 @@ where B: BlockchainBackend + 'static
             auxilary_tcp_listener_address: self.config.auxilary_tcp_listener_address.clone(),
             datastore_path: self.config.peer_db_path.clone(),
             peer_database_name: "peers".to_string(),
-            max_concurrent_inbound_tasks: 100,
+            max_concurrent_inbound_tasks: if seed_mode {
+                SEED_MODE_MAX_CONCURRENT_INBOUND_TASKS
+            } else {
+                100
+            },
             outbound_buffer_size: 100,
             dht: DhtConfig {
                 database_url: DbConnectionUrl::File(self.config.data_dir.join("dht.db")),
@@ -253,12 +346,27 @@ where B: BlockchainBackend + 'static
                 flood_ban_max_msg_count: self.config.flood_ban_max_msg_count,
                 saf_msg_validity: self.config.saf_expiry_duration,
                 dedup_cache_capacity: self.config.dedup_cache_capacity,
+                num_random_nodes: if seed_mode {
+                    SEED_MODE_NUM_RANDOM_NODES
+                } else {
+                    DhtConfig::default().num_random_nodes
+                },
                 ..Default::default()
             },
             allow_test_addresses: self.config.allow_test_addresses,
             listener_liveness_allowlist_cidrs: self.config.listener_liveness_allowlist_cidrs.clone(),
-            listener_liveness_max_sessions: self.config.listnener_liveness_max_sessions,
+            allowed_addresses: self.config.allowed_addresses.clone(),
+            blocked_addresses: self.config.blocked_addresses.clone(),
+            listener_liveness_max_sessions: if seed_mode {
+                cmp::max(
+                    self.config.listnener_liveness_max_sessions,
+                    SEED_MODE_LISTENER_LIVENESS_MAX_SESSIONS,
+                )
+            } else {
+                self.config.listnener_liveness_max_sessions
+            },
             user_agent: format!("tari/basenode/{}", env!("CARGO_PKG_VERSION")),
+            capabilities: PeerCapabilities::BASE_NODE,
             // Also add sync peers to the peer seed list. Duplicates are acceptable.
             peer_seeds: self
                 .config
@@ -270,6 +378,7 @@ where B: BlockchainBackend + 'static
             dns_seeds: self.config.dns_seeds.clone(),
             dns_seeds_name_server: self.config.dns_seeds_name_server,
             dns_seeds_use_dnssec: self.config.dns_seeds_use_dnssec,
+            peer_snapshot_path: Some(self.config.data_dir.join(PEER_SNAPSHOT_FILE_NAME)),
         }
     }
 }
@@ -20,21 +20,37 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::Arc;
+use std::{future::Future, sync::Arc};
 
+use anyhow::anyhow;
 use log::*;
-use tokio::sync::watch;
+use tokio::{sync::watch, task};
 
 use tari_common::{configuration::Network, DatabaseType, GlobalConfig};
-use tari_comms::{peer_manager::NodeIdentity, protocol::rpc::RpcServerHandle, CommsNode};
+use tari_common_types::types::Commitment;
+use tari_comms::{peer_manager::NodeIdentity, protocol::rpc::RpcServerHandle, runtime::spawn_named, CommsNode};
 use tari_comms_dht::Dht;
 use tari_core::{
-    base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface, StateMachineHandle},
-    chain_storage::{create_lmdb_database, BlockchainDatabase, BlockchainDatabaseConfig, LMDBDatabase, Validators},
-    consensus::ConsensusManager,
+    base_node::{
+        state_machine_service::{states::StatusInfo, StateTransitionObserver},
+        LocalNodeCommsInterface,
+        StateMachineHandle,
+    },
+    chain_storage::{
+        create_lmdb_database,
+        AsyncBlockchainDb,
+        BlockchainDatabase,
+        BlockchainDatabaseConfig,
+        LMDBDatabase,
+        Validators,
+        WitnessGarbageCollector,
+        WitnessGcConfig,
+    },
+    consensus::{Checkpoint, Checkpoints, ConsensusManager},
     mempool::{service::LocalMempoolService, Mempool, MempoolConfig},
     proof_of_work::randomx_factory::RandomXFactory,
-    transactions::CryptoFactories,
+    tari_utilities::hex::Hex,
+    transactions::{verification_cache::VerificationCache, CryptoFactories},
     validation::{
         block_validators::{BodyOnlyValidator, OrphanBlockValidator},
         header_validator::HeaderValidator,
@@ -49,9 +65,21 @@ use tari_core::{
 };
 use tari_p2p::{auto_update::SoftwareUpdaterHandle, services::liveness::LivenessHandle};
 use tari_service_framework::ServiceHandles;
-use tari_shutdown::ShutdownSignal;
+use tari_shutdown::{
+    phases::{ShutdownOrchestrator, ShutdownPhase, ShutdownReport, ShutdownTimeouts},
+    ShutdownSignal,
+};
 
-use crate::bootstrap::BaseNodeBootstrapper;
+use crate::{
+    alerts::{AlertMonitor, AlertTriggers, Alerter},
+    block_timing_monitor::{BlockTimingHandle, BlockTimingMonitor},
+    bootstrap::BaseNodeBootstrapper,
+    chain_monitor::{ChainMonitor, ChainMonitorConfig, ChainMonitorHandle, ReferenceNode},
+    disk_space_monitor::DiskSpaceMonitor,
+    event_journal::{EventJournal, EventRecorder},
+    mining_distribution_monitor::{MiningDistributionHandle, MiningDistributionMonitor},
+    time_sync::{TimeSyncConfig, TimeSyncHandle, TimeSyncMonitor},
+};
 
 const LOG_TARGET: &str = "c::bn::initialization";
 
@@ -65,20 +93,54 @@ pub struct BaseNodeContext {
     base_node_comms: CommsNode,
     base_node_dht: Dht,
     base_node_handles: ServiceHandles,
+    chain_monitor: ChainMonitorHandle,
+    block_timing: BlockTimingHandle,
+    mining_distribution: MiningDistributionHandle,
+    time_sync: TimeSyncHandle,
+    event_journal: EventJournal,
 }
 
 impl BaseNodeContext {
     /// Starts the node container. This entails the base node state machine.
-    /// This call consumes the NodeContainer instance.
-    #[tracing::instrument(name = "base_node::run", skip(self))]
-    pub async fn run(self) {
+    /// This call consumes the NodeContainer instance. Once `shutdown_requested` resolves the orchestrator runs the
+    /// shutdown phases: the services stop, the blockchain database is flushed and then the comms stack is closed.
+    #[tracing::instrument(name = "base_node::run", skip(self, shutdown_requested, orchestrator))]
+    pub async fn run<F: Future<Output = ()>>(
+        self,
+        shutdown_requested: F,
+        mut orchestrator: ShutdownOrchestrator,
+    ) -> ShutdownReport {
         info!(target: LOG_TARGET, "Tari base node has STARTED");
 
-        self.state_machine().shutdown_signal().wait().await;
-        info!(target: LOG_TARGET, "Waiting for communications stack shutdown");
+        shutdown_requested.await;
+        info!(target: LOG_TARGET, "Tari base node is shutting down");
+
+        let mut flush = orchestrator.register("blockchain database", ShutdownPhase::FlushDatabases);
+        let db = AsyncBlockchainDb::from(self.blockchain_db.clone());
+        task::spawn(async move {
+            flush.wait().await;
+            match db.flush().await {
+                Ok(_) => flush.complete(),
+                Err(err) => error!(target: LOG_TARGET, "Failed to flush the blockchain database: {}", err),
+            }
+        });
 
-        self.base_node_comms.wait_until_shutdown().await;
-        info!(target: LOG_TARGET, "Communications stack has shutdown");
+        let mut comms = orchestrator.register("comms", ShutdownPhase::CloseComms);
+        let base_node_comms = self.base_node_comms.clone();
+        task::spawn(async move {
+            comms.wait().await;
+            info!(target: LOG_TARGET, "Waiting for communications stack shutdown");
+            base_node_comms.wait_until_shutdown().await;
+            info!(target: LOG_TARGET, "Communications stack has shutdown");
+            comms.complete();
+        });
+
+        let timeouts = ShutdownTimeouts {
+            stop_accepting_work: self.config.shutdown_stop_accepting_work_timeout,
+            flush_databases: self.config.shutdown_flush_databases_timeout,
+            close_comms: self.config.shutdown_close_comms_timeout,
+        };
+        orchestrator.run(&timeouts).await
     }
 
     /// Return the node config
@@ -111,6 +173,11 @@ impl BaseNodeContext {
         self.base_node_handles.expect_handle()
     }
 
+    /// Registers an observer that is called with every transition of the base node state machine
+    pub fn register_state_transition_observer<T: StateTransitionObserver>(&self, observer: T) {
+        self.state_machine().register_transition_observer(observer);
+    }
+
     /// Returns this node's identity.
     pub fn base_node_identity(&self) -> Arc<NodeIdentity> {
         self.base_node_comms.node_identity()
@@ -146,6 +213,31 @@ impl BaseNodeContext {
         &self.consensus_rules
     }
 
+    /// Returns the handle to the chain tip divergence monitor
+    pub fn chain_monitor(&self) -> ChainMonitorHandle {
+        self.chain_monitor.clone()
+    }
+
+    /// Returns the handle to the block timing monitor
+    pub fn block_timing_monitor(&self) -> BlockTimingHandle {
+        self.block_timing.clone()
+    }
+
+    /// Returns the handle to the mining distribution monitor
+    pub fn mining_distribution_monitor(&self) -> MiningDistributionHandle {
+        self.mining_distribution.clone()
+    }
+
+    /// Returns the handle to the local clock skew monitor
+    pub fn time_sync(&self) -> TimeSyncHandle {
+        self.time_sync.clone()
+    }
+
+    /// Returns the journal of significant node events
+    pub fn event_journal(&self) -> EventJournal {
+        self.event_journal.clone()
+    }
+
     /// Return the state machine channel to provide info updates
     pub fn get_state_machine_info_channel(&self) -> watch::Receiver<StatusInfo> {
         self.base_node_handles
@@ -157,16 +249,25 @@ impl BaseNodeContext {
 /// Sets up and initializes the base node, creating the context and database
 /// ## Parameters
 /// `config` - The configuration for the base node
+/// `mempool_config` - The mempool configuration, later values are applied to the running mempool
 /// `node_identity` - The node identity information of the base node
 /// `wallet_node_identity` - The node identity information of the base node's wallet
-/// `interrupt_signal` - The signal used to stop the application
+/// `interrupt_signal` - The signal used to stop the services of the application
+/// `comms_shutdown_signal` - The signal used to close the comms stack once the services have stopped
+/// `event_journal` - The journal the events of the running node are recorded in
+/// `alerter` - Sends alerts to the node operator
 /// ## Returns
 /// Result containing the NodeContainer, String will contain the reason on error
+#[allow(clippy::too_many_arguments)]
 pub async fn configure_and_initialize_node(
     config: Arc<GlobalConfig>,
+    mempool_config: watch::Receiver<MempoolConfig>,
     node_identity: Arc<NodeIdentity>,
     interrupt_signal: ShutdownSignal,
+    comms_shutdown_signal: ShutdownSignal,
     cleanup_orphans_at_startup: bool,
+    event_journal: EventJournal,
+    alerter: Alerter,
 ) -> Result<BaseNodeContext, anyhow::Error> {
     let result = match &config.db_type {
         DatabaseType::Memory => {
@@ -188,8 +289,12 @@ pub async fn configure_and_initialize_node(
                 backend,
                 node_identity,
                 config,
+                mempool_config,
                 interrupt_signal,
+                comms_shutdown_signal,
                 cleanup_orphans_at_startup,
+                event_journal,
+                alerter,
             )
             .await?
         },
@@ -205,21 +310,35 @@ pub async fn configure_and_initialize_node(
 /// `base_node_identity` - The node identity information of the base node
 /// `wallet_node_identity` - The node identity information of the base node's wallet
 /// `config` - The configuration for the base node
-/// `interrupt_signal` - The signal used to stop the application
+/// `mempool_config` - The mempool configuration, later values are applied to the running mempool
+/// `interrupt_signal` - The signal used to stop the services of the application
+/// `comms_shutdown_signal` - The signal used to close the comms stack once the services have stopped
+/// `event_journal` - The journal the events of the running node are recorded in
+/// `alerter` - Sends alerts to the node operator
 /// ## Returns
 /// Result containing the BaseNodeContext, String will contain the reason on error
+#[allow(clippy::too_many_arguments)]
 async fn build_node_context(
     backend: LMDBDatabase,
     base_node_identity: Arc<NodeIdentity>,
     config: Arc<GlobalConfig>,
+    mempool_config: watch::Receiver<MempoolConfig>,
     interrupt_signal: ShutdownSignal,
+    comms_shutdown_signal: ShutdownSignal,
     cleanup_orphans_at_startup: bool,
+    event_journal: EventJournal,
+    alerter: Alerter,
 ) -> Result<BaseNodeContext, anyhow::Error> {
     //---------------------------------- Blockchain --------------------------------------------//
 
-    let rules = ConsensusManager::builder(config.network).build();
+    let rules = ConsensusManager::builder(config.network)
+        .with_checkpoints(configure_checkpoints(&config)?)
+        .build();
+    check_genesis_checkpoint(&rules)?;
     let factories = CryptoFactories::default();
     let randomx_factory = RandomXFactory::new(config.max_randomx_vms);
+    // Shared by the mempool and orphan validators so that mined transactions are not verified twice
+    let verification_cache = Arc::new(VerificationCache::new(config.verification_cache_size));
     let validators = Validators::new(
         BodyOnlyValidator::default(),
         HeaderValidator::new(rules.clone()),
@@ -227,12 +346,14 @@ async fn build_node_context(
             rules.clone(),
             config.base_node_bypass_range_proof_verification,
             factories.clone(),
-        ),
+        )
+        .with_verification_cache(verification_cache.clone()),
     );
     let db_config = BlockchainDatabaseConfig {
         orphan_storage_capacity: config.orphan_storage_capacity,
         pruning_horizon: config.pruning_horizon,
         pruning_interval: config.pruned_mode_cleanup_interval,
+        header_cache_size: config.header_cache_size,
     };
     let blockchain_db = BlockchainDatabase::new(
         backend,
@@ -241,16 +362,21 @@ async fn build_node_context(
         db_config,
         DifficultyCalculator::new(rules.clone(), randomx_factory),
         cleanup_orphans_at_startup,
-    )?;
+    )?
+    .with_retained_commitments(configure_retained_commitments(&config)?);
     let mempool_validator = MempoolValidator::new(vec![
-        Box::new(TxInternalConsistencyValidator::new(
-            factories.clone(),
-            config.base_node_bypass_range_proof_verification,
-        )),
+        Box::new(
+            TxInternalConsistencyValidator::new(
+                blockchain_db.clone(),
+                factories.clone(),
+                config.base_node_bypass_range_proof_verification,
+            )
+            .with_verification_cache(verification_cache),
+        ),
         Box::new(TxInputAndMaturityValidator::new(blockchain_db.clone())),
         Box::new(TxConsensusValidator::new(blockchain_db.clone())),
     ]);
-    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
+    let mempool = Mempool::new(*mempool_config.borrow(), Arc::new(mempool_validator));
 
     //---------------------------------- Base Node  --------------------------------------------//
     debug!(target: LOG_TARGET, "Creating base node state machine.");
@@ -260,15 +386,81 @@ async fn build_node_context(
         node_identity: base_node_identity,
         db: blockchain_db.clone(),
         mempool,
+        mempool_config_updates: mempool_config,
         rules: rules.clone(),
         factories: factories.clone(),
         interrupt_signal: interrupt_signal.clone(),
+        comms_shutdown_signal,
     }
     .bootstrap()
     .await?;
 
     let base_node_comms = base_node_handles.expect_handle::<CommsNode>();
     let base_node_dht = base_node_handles.expect_handle::<Dht>();
+    if config.pruning_horizon > 0 {
+        spawn_witness_gc(&config, &blockchain_db, interrupt_signal.clone());
+    }
+    let chain_monitor =
+        spawn_chain_monitor(&config, &blockchain_db, &base_node_comms, interrupt_signal.clone()).await?;
+    let block_timing = spawn_block_timing_monitor(
+        &config,
+        &blockchain_db,
+        &rules,
+        &base_node_handles.expect_handle::<LocalNodeCommsInterface>(),
+        interrupt_signal.clone(),
+    );
+    let mining_distribution = spawn_mining_distribution_monitor(
+        &config,
+        &blockchain_db,
+        &base_node_handles.expect_handle::<LocalNodeCommsInterface>(),
+        alerter.clone(),
+        interrupt_signal.clone(),
+    );
+    let time_sync = spawn_time_sync_monitor(
+        &config,
+        &base_node_handles.expect_handle::<LivenessHandle>(),
+        alerter.clone(),
+        interrupt_signal.clone(),
+    );
+    let recorder = EventRecorder::new(
+        event_journal.clone(),
+        base_node_handles
+            .expect_handle::<StateMachineHandle>()
+            .get_status_info_watch(),
+        base_node_handles
+            .expect_handle::<LocalNodeCommsInterface>()
+            .get_block_event_stream(),
+        base_node_comms.connectivity().get_event_subscription(),
+        base_node_handles
+            .expect_handle::<SoftwareUpdaterHandle>()
+            .new_update_notifier()
+            .clone(),
+    );
+    spawn_named("base_node::event_recorder", recorder.run(interrupt_signal.clone()));
+    if config.disk_space_warning_mb > 0 || config.disk_space_read_only_mb > 0 {
+        let monitor = DiskSpaceMonitor::new(
+            &config,
+            blockchain_db.clone(),
+            base_node_handles.expect_handle::<StateMachineHandle>(),
+            alerter.clone(),
+        );
+        spawn_named("base_node::disk_space_monitor", monitor.run(interrupt_signal.clone()));
+    }
+    if alerter.is_enabled() {
+        let state_machine = base_node_handles.expect_handle::<StateMachineHandle>();
+        let monitor = AlertMonitor::new(
+            alerter,
+            AlertTriggers::from_config(&config),
+            config.data_dir.clone(),
+            state_machine.get_status_info_watch(),
+            state_machine.get_state_change_event_stream(),
+            base_node_handles
+                .expect_handle::<LocalNodeCommsInterface>()
+                .get_block_event_stream(),
+            base_node_comms.connectivity().get_event_subscription(),
+        );
+        spawn_named("base_node::alert_monitor", monitor.run(interrupt_signal));
+    }
 
     Ok(BaseNodeContext {
         config,
@@ -277,5 +469,197 @@ async fn build_node_context(
         base_node_comms,
         base_node_dht,
         base_node_handles,
+        chain_monitor,
+        block_timing,
+        mining_distribution,
+        time_sync,
+        event_journal,
     })
 }
+
+/// Parses the configured checkpoints
+fn configure_checkpoints(config: &GlobalConfig) -> Result<Checkpoints, anyhow::Error> {
+    let mut checkpoints = Checkpoints::new().with_assume_valid_before_height(config.assume_valid_before_height);
+    for checkpoint in &config.checkpoints {
+        let checkpoint = checkpoint
+            .parse::<Checkpoint>()
+            .map_err(|err| anyhow!("Invalid checkpoint: {}", err))?;
+        checkpoints = checkpoints.with_checkpoint(checkpoint);
+    }
+    if !checkpoints.is_empty() {
+        info!(
+            target: LOG_TARGET,
+            "{} checkpoint(s) configured, the highest at height {}",
+            checkpoints.len(),
+            checkpoints.highest_height().unwrap_or_default()
+        );
+    }
+    if checkpoints.assume_valid_height() > 0 {
+        warn!(
+            target: LOG_TARGET,
+            "Blocks below height {} are assumed valid. Their range proofs and scripts will not be verified during sync.",
+            checkpoints.assume_valid_height()
+        );
+    } else if config.assume_valid_before_height > 0 {
+        warn!(
+            target: LOG_TARGET,
+            "assume_valid_before_height is set but no checkpoints are configured, all blocks will be verified"
+        );
+    }
+    Ok(checkpoints)
+}
+
+/// Parses the commitments of the outputs that are kept when pruning
+fn configure_retained_commitments(config: &GlobalConfig) -> Result<Vec<Commitment>, anyhow::Error> {
+    let commitments = config
+        .pruning_retained_commitments
+        .iter()
+        .map(|hex| Commitment::from_hex(hex).map_err(|err| anyhow!("Invalid retained commitment `{}`: {}", hex, err)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !commitments.is_empty() {
+        if config.pruning_horizon == 0 {
+            warn!(
+                target: LOG_TARGET,
+                "pruning_retained_commitments is set but pruning is disabled, all outputs are kept"
+            );
+        } else {
+            info!(
+                target: LOG_TARGET,
+                "{} output commitment(s) will be kept when pruning",
+                commitments.len()
+            );
+        }
+    }
+    Ok(commitments)
+}
+
+/// A checkpoint at height 0 must be the network's genesis block
+fn check_genesis_checkpoint(rules: &ConsensusManager) -> Result<(), anyhow::Error> {
+    if let Some(hash) = rules.checkpoints().get(0) {
+        let genesis_hash = rules.get_genesis_block().hash().clone();
+        if *hash != genesis_hash {
+            return Err(anyhow!(
+                "The checkpoint at height 0 does not match the {} genesis block {}",
+                rules.network().as_network(),
+                genesis_hash.to_hex()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Starts the background removal of spent witness data below the pruning horizon
+fn spawn_witness_gc(
+    config: &GlobalConfig,
+    blockchain_db: &BlockchainDatabase<LMDBDatabase>,
+    shutdown_signal: ShutdownSignal,
+) {
+    let gc = WitnessGarbageCollector::new(
+        blockchain_db.clone().into(),
+        WitnessGcConfig {
+            batch_size: config.pruned_mode_gc_batch_size,
+            batch_delay: config.pruned_mode_gc_batch_delay,
+            interval: config.pruned_mode_gc_interval,
+        },
+        shutdown_signal,
+    );
+    spawn_named("base_node::witness_gc", gc.run());
+}
+
+/// Starts the chain monitor if reference nodes are configured
+async fn spawn_chain_monitor(
+    config: &GlobalConfig,
+    blockchain_db: &BlockchainDatabase<LMDBDatabase>,
+    base_node_comms: &CommsNode,
+    shutdown_signal: ShutdownSignal,
+) -> Result<ChainMonitorHandle, anyhow::Error> {
+    if config.chain_monitor_reference_nodes.is_empty() {
+        return Ok(ChainMonitorHandle::disabled());
+    }
+    let reference_nodes = config
+        .chain_monitor_reference_nodes
+        .iter()
+        .map(|s| s.parse())
+        .collect::<Result<Vec<ReferenceNode>, _>>()?;
+    let (chain_monitor, handle) = ChainMonitor::new(
+        ChainMonitorConfig {
+            reference_nodes,
+            interval: config.chain_monitor_interval,
+            fork_depth: config.chain_monitor_fork_depth,
+        },
+        blockchain_db.clone().into(),
+        base_node_comms.connectivity(),
+    );
+    chain_monitor
+        .add_reference_peers(&base_node_comms.peer_manager())
+        .await?;
+    spawn_named("base_node::chain_monitor", chain_monitor.run(shutdown_signal));
+    Ok(handle)
+}
+
+/// Starts the block timing monitor unless its window is set to 0
+fn spawn_block_timing_monitor(
+    config: &GlobalConfig,
+    blockchain_db: &BlockchainDatabase<LMDBDatabase>,
+    rules: &ConsensusManager,
+    local_node: &LocalNodeCommsInterface,
+    shutdown_signal: ShutdownSignal,
+) -> BlockTimingHandle {
+    if config.block_timing_window == 0 {
+        return BlockTimingHandle::disabled();
+    }
+    let (monitor, handle) = BlockTimingMonitor::new(
+        config.block_timing_window,
+        blockchain_db.clone().into(),
+        rules.clone(),
+        local_node.get_block_event_stream(),
+    );
+    spawn_named("base_node::block_timing_monitor", monitor.run(shutdown_signal));
+    handle
+}
+
+/// Starts the mining distribution monitor unless its window is set to 0
+fn spawn_mining_distribution_monitor(
+    config: &GlobalConfig,
+    blockchain_db: &BlockchainDatabase<LMDBDatabase>,
+    local_node: &LocalNodeCommsInterface,
+    alerter: Alerter,
+    shutdown_signal: ShutdownSignal,
+) -> MiningDistributionHandle {
+    if config.mining_distribution_window == 0 {
+        return MiningDistributionHandle::disabled();
+    }
+    let (monitor, handle) = MiningDistributionMonitor::new(
+        config.mining_distribution_window,
+        config.alert_mining_share_percent as f64 / 100.0,
+        blockchain_db.clone().into(),
+        local_node.get_block_event_stream(),
+        alerter,
+    );
+    spawn_named("base_node::mining_distribution_monitor", monitor.run(shutdown_signal));
+    handle
+}
+
+/// Starts the clock skew monitor unless its interval is set to 0
+fn spawn_time_sync_monitor(
+    config: &GlobalConfig,
+    liveness: &LivenessHandle,
+    alerter: Alerter,
+    shutdown_signal: ShutdownSignal,
+) -> TimeSyncHandle {
+    if config.time_sync_interval.as_secs() == 0 {
+        return TimeSyncHandle::disabled();
+    }
+    let (monitor, handle) = TimeSyncMonitor::new(
+        TimeSyncConfig {
+            ntp_servers: config.time_sync_ntp_servers.clone(),
+            interval: config.time_sync_interval,
+            max_clock_skew: config.max_clock_skew,
+            refuse_mining_on_clock_skew: config.refuse_mining_on_clock_skew,
+        },
+        liveness.get_event_stream(),
+        alerter,
+    );
+    spawn_named("base_node::time_sync_monitor", monitor.run(shutdown_signal));
+    handle
+}
@@ -0,0 +1,471 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use chrono::{DateTime, Utc};
+use log::*;
+use std::{cmp, convert::TryFrom, fmt, str::FromStr, sync::Arc, time::Duration};
+use tari_app_grpc::tari_rpc as grpc;
+use tari_common_types::types::HashOutput;
+use tari_comms::{
+    connectivity::{ConnectivityError, ConnectivityRequester},
+    peer_manager::{Peer, PeerManager, PeerManagerError},
+    protocol::rpc::RpcError,
+};
+use tari_core::{
+    base_node::sync::rpc::BaseNodeSyncRpcClient,
+    blocks::BlockHeader,
+    chain_storage::{async_db::AsyncBlockchainDb, ChainStorageError, LMDBDatabase},
+};
+use tari_crypto::tari_utilities::{hex::to_hex, Hashable};
+use tari_p2p::peer_seeds::SeedPeer;
+use tari_shutdown::ShutdownSignal;
+use thiserror::Error;
+use tokio::{
+    sync::{broadcast, watch},
+    time,
+};
+use tonic::transport::Channel;
+
+const LOG_TARGET: &str = "base_node::chain_monitor";
+
+#[derive(Debug, Error)]
+pub enum ChainMonitorError {
+    #[error("Invalid reference node '{0}': expected a gRPC address or `<public key>::<address>`")]
+    InvalidReferenceNode(String),
+    #[error("gRPC error: {0}")]
+    GrpcStatus(#[from] tonic::Status),
+    #[error("gRPC connection error: {0}")]
+    GrpcTransport(#[from] tonic::transport::Error),
+    #[error("RPC error: {0}")]
+    RpcError(#[from] RpcError),
+    #[error("Connectivity error: {0}")]
+    ConnectivityError(#[from] ConnectivityError),
+    #[error("Peer manager error: {0}")]
+    PeerManagerError(#[from] PeerManagerError),
+    #[error("Chain storage error: {0}")]
+    ChainStorageError(#[from] ChainStorageError),
+    #[error("No header at height {0}")]
+    HeaderNotFound(u64),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+/// A trusted node that the local chain tip is compared against
+#[derive(Debug, Clone)]
+pub enum ReferenceNode {
+    /// The gRPC address of a base node
+    Grpc(String),
+    /// A base node peer, queried over the p2p network
+    Peer(Box<Peer>),
+}
+
+impl FromStr for ReferenceNode {
+    type Err = ChainMonitorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("http://") || s.starts_with("https://") {
+            return Ok(ReferenceNode::Grpc(s.to_string()));
+        }
+        SeedPeer::from_str(s)
+            .map(|seed| ReferenceNode::Peer(Box::new(seed.into())))
+            .map_err(|_| ChainMonitorError::InvalidReferenceNode(s.to_string()))
+    }
+}
+
+impl fmt::Display for ReferenceNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceNode::Grpc(address) => write!(f, "{}", address),
+            ReferenceNode::Peer(peer) => write!(f, "{}", peer.node_id.short_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainMonitorConfig {
+    pub reference_nodes: Vec<ReferenceNode>,
+    pub interval: Duration,
+    /// The number of blocks below the common height at which the chains must agree
+    pub fork_depth: u64,
+}
+
+/// How the local chain compares to a reference node's chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TipComparison {
+    /// The chains agree up to the lower of the two tips
+    Agrees,
+    /// The chains differ at the lower of the two tips, but agree `fork_depth` blocks below it
+    ShallowFork,
+    /// The chains differ `fork_depth` blocks below the lower of the two tips
+    DeepFork,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReferenceReport {
+    pub reference: String,
+    /// The reference node's tip height and how its chain compares, or the reason it could not be compared
+    pub result: Result<(u64, TipComparison), String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChainMonitorStatus {
+    pub checked_at: Option<DateTime<Utc>>,
+    pub local_height: u64,
+    pub reports: Vec<ReferenceReport>,
+    pub minority_fork: bool,
+}
+
+impl ChainMonitorStatus {
+    /// The number of reference nodes that responded, and the number of those that are on a different chain
+    pub fn num_disagreeing(&self) -> (usize, usize) {
+        let comparisons = self
+            .reports
+            .iter()
+            .filter_map(|r| r.result.as_ref().ok())
+            .collect::<Vec<_>>();
+        let num_deep_forks = comparisons
+            .iter()
+            .filter(|(_, c)| *c == TipComparison::DeepFork)
+            .count();
+        (comparisons.len(), num_deep_forks)
+    }
+
+    /// The node is on a minority fork if most of the reference nodes that responded are on a different chain
+    fn is_minority_fork(&self) -> bool {
+        let (num_responded, num_deep_forks) = self.num_disagreeing();
+        num_deep_forks > 0 && num_deep_forks * 2 > num_responded
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ChainMonitorEvent {
+    MinorityForkDetected {
+        local_height: u64,
+        num_disagreeing: usize,
+        num_responded: usize,
+    },
+    MinorityForkResolved,
+}
+
+/// Handle to the latest chain monitor status and its events
+#[derive(Clone)]
+pub struct ChainMonitorHandle {
+    status: watch::Receiver<ChainMonitorStatus>,
+    event_publisher: broadcast::Sender<Arc<ChainMonitorEvent>>,
+    is_enabled: bool,
+}
+
+impl ChainMonitorHandle {
+    /// A handle for when no reference nodes are configured
+    pub fn disabled() -> Self {
+        let (_, status) = watch::channel(ChainMonitorStatus::default());
+        let (event_publisher, _) = broadcast::channel(1);
+        Self {
+            status,
+            event_publisher,
+            is_enabled: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn status(&self) -> ChainMonitorStatus {
+        self.status.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<ChainMonitorEvent>> {
+        self.event_publisher.subscribe()
+    }
+}
+
+/// Periodically compares the local chain tip with the chains of trusted reference nodes, and warns when the node is
+/// on a minority fork
+pub struct ChainMonitor {
+    config: ChainMonitorConfig,
+    db: AsyncBlockchainDb<LMDBDatabase>,
+    connectivity: ConnectivityRequester,
+    status: watch::Sender<ChainMonitorStatus>,
+    event_publisher: broadcast::Sender<Arc<ChainMonitorEvent>>,
+}
+
+impl ChainMonitor {
+    pub fn new(
+        config: ChainMonitorConfig,
+        db: AsyncBlockchainDb<LMDBDatabase>,
+        connectivity: ConnectivityRequester,
+    ) -> (Self, ChainMonitorHandle) {
+        let (status, status_rx) = watch::channel(ChainMonitorStatus::default());
+        let (event_publisher, _) = broadcast::channel(10);
+        let handle = ChainMonitorHandle {
+            status: status_rx,
+            event_publisher: event_publisher.clone(),
+            is_enabled: true,
+        };
+        let monitor = Self {
+            config,
+            db,
+            connectivity,
+            status,
+            event_publisher,
+        };
+        (monitor, handle)
+    }
+
+    /// Adds the reference peers to the peer manager, so that they can be dialed
+    pub async fn add_reference_peers(&self, peer_manager: &PeerManager) -> Result<(), ChainMonitorError> {
+        for reference in &self.config.reference_nodes {
+            if let ReferenceNode::Peer(peer) = reference {
+                if !peer_manager.exists_node_id(&peer.node_id).await {
+                    peer_manager.add_peer((**peer).clone()).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn run(self, mut shutdown_signal: ShutdownSignal) {
+        let mut interval = time::interval(self.config.interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    self.check().await;
+                },
+                _ = shutdown_signal.wait() => {
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn check(&self) {
+        let local_height = match self.db.get_chain_metadata().await {
+            Ok(metadata) => metadata.height_of_longest_chain(),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Could not read the local chain metadata: {}", err);
+                return;
+            },
+        };
+
+        let mut reports = Vec::with_capacity(self.config.reference_nodes.len());
+        for reference in &self.config.reference_nodes {
+            let result = self.compare(reference, local_height).await.map_err(|err| {
+                debug!(target: LOG_TARGET, "Could not compare with '{}': {}", reference, err);
+                err.to_string()
+            });
+            reports.push(ReferenceReport {
+                reference: reference.to_string(),
+                result,
+            });
+        }
+
+        let mut status = ChainMonitorStatus {
+            checked_at: Some(Utc::now()),
+            local_height,
+            reports,
+            minority_fork: false,
+        };
+        status.minority_fork = status.is_minority_fork();
+        let was_minority_fork = self.status.borrow().minority_fork;
+
+        match (was_minority_fork, status.minority_fork) {
+            (_, true) => {
+                let (num_responded, num_disagreeing) = status.num_disagreeing();
+                let message = format!(
+                    "WARNING: {} of {} reference node(s) are on a different chain more than {} block(s) below height \
+                     {}. This node may be on a minority fork.",
+                    num_disagreeing, num_responded, self.config.fork_depth, local_height
+                );
+                warn!(target: LOG_TARGET, "{}", message);
+                println!("{}", message);
+                if !was_minority_fork {
+                    let _ = self
+                        .event_publisher
+                        .send(Arc::new(ChainMonitorEvent::MinorityForkDetected {
+                            local_height,
+                            num_disagreeing,
+                            num_responded,
+                        }));
+                }
+            },
+            (true, false) => {
+                info!(target: LOG_TARGET, "The node is no longer on a minority fork");
+                println!("The node's chain agrees with the reference nodes again");
+                let _ = self
+                    .event_publisher
+                    .send(Arc::new(ChainMonitorEvent::MinorityForkResolved));
+            },
+            (false, false) => {},
+        }
+
+        let _ = self.status.send(status);
+    }
+
+    async fn compare(
+        &self,
+        reference: &ReferenceNode,
+        local_height: u64,
+    ) -> Result<(u64, TipComparison), ChainMonitorError> {
+        let mut client = ReferenceClient::connect(reference, &self.connectivity).await?;
+        let reference_height = client.tip_height().await?;
+        let height = cmp::min(local_height, reference_height);
+        if self.hashes_match(&mut client, height).await? {
+            return Ok((reference_height, TipComparison::Agrees));
+        }
+        let comparison = if self
+            .hashes_match(&mut client, height.saturating_sub(self.config.fork_depth))
+            .await?
+        {
+            TipComparison::ShallowFork
+        } else {
+            TipComparison::DeepFork
+        };
+        Ok((reference_height, comparison))
+    }
+
+    async fn hashes_match(&self, client: &mut ReferenceClient, height: u64) -> Result<bool, ChainMonitorError> {
+        let local_hash = self
+            .db
+            .fetch_header(height)
+            .await?
+            .ok_or(ChainMonitorError::HeaderNotFound(height))?
+            .hash();
+        let reference_hash = client.header_hash(height).await?;
+        if local_hash != reference_hash {
+            debug!(
+                target: LOG_TARGET,
+                "Block hashes at height {} differ: local {}, reference {}",
+                height,
+                to_hex(&local_hash),
+                to_hex(&reference_hash)
+            );
+        }
+        Ok(local_hash == reference_hash)
+    }
+}
+
+enum ReferenceClient {
+    Grpc(grpc::base_node_client::BaseNodeClient<Channel>),
+    Rpc(BaseNodeSyncRpcClient),
+}
+
+impl ReferenceClient {
+    async fn connect(
+        reference: &ReferenceNode,
+        connectivity: &ConnectivityRequester,
+    ) -> Result<Self, ChainMonitorError> {
+        match reference {
+            ReferenceNode::Grpc(address) => Ok(ReferenceClient::Grpc(
+                grpc::base_node_client::BaseNodeClient::connect(address.clone()).await?,
+            )),
+            ReferenceNode::Peer(peer) => {
+                let mut conn = connectivity.dial_peer(peer.node_id.clone()).await?;
+                Ok(ReferenceClient::Rpc(conn.connect_rpc().await?))
+            },
+        }
+    }
+
+    async fn tip_height(&mut self) -> Result<u64, ChainMonitorError> {
+        match self {
+            ReferenceClient::Grpc(client) => client
+                .get_tip_info(grpc::Empty {})
+                .await?
+                .into_inner()
+                .metadata
+                .map(|metadata| metadata.height_of_longest_chain)
+                .ok_or_else(|| ChainMonitorError::InvalidResponse("metadata is missing".to_string())),
+            ReferenceClient::Rpc(client) => Ok(client.get_chain_metadata().await?.height_of_longest_chain()),
+        }
+    }
+
+    async fn header_hash(&mut self, height: u64) -> Result<HashOutput, ChainMonitorError> {
+        match self {
+            ReferenceClient::Grpc(client) => {
+                let mut stream = client
+                    .list_headers(grpc::ListHeadersRequest {
+                        from_height: height,
+                        num_headers: 1,
+                        sorting: grpc::Sorting::Asc.into(),
+                    })
+                    .await?
+                    .into_inner();
+                stream
+                    .message()
+                    .await?
+                    .filter(|header| header.height == height)
+                    .map(|header| header.hash)
+                    .ok_or(ChainMonitorError::HeaderNotFound(height))
+            },
+            ReferenceClient::Rpc(client) => {
+                let header = client.get_header_by_height(height).await?;
+                let header = BlockHeader::try_from(header).map_err(ChainMonitorError::InvalidResponse)?;
+                Ok(header.hash())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn report(result: Result<(u64, TipComparison), String>) -> ReferenceReport {
+        ReferenceReport {
+            reference: "reference".to_string(),
+            result,
+        }
+    }
+
+    #[test]
+    fn it_detects_a_minority_fork() {
+        let mut status = ChainMonitorStatus {
+            reports: vec![
+                report(Ok((100, TipComparison::Agrees))),
+                report(Ok((100, TipComparison::DeepFork))),
+                report(Err("unreachable".to_string())),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(status.num_disagreeing(), (2, 1));
+        assert!(!status.is_minority_fork());
+
+        status.reports.push(report(Ok((101, TipComparison::DeepFork))));
+        assert!(status.is_minority_fork());
+
+        status.reports = vec![report(Ok((100, TipComparison::ShallowFork)))];
+        assert!(!status.is_minority_fork());
+    }
+
+    #[test]
+    fn it_parses_reference_nodes() {
+        let grpc = ReferenceNode::from_str("http://127.0.0.1:18142").unwrap();
+        assert!(matches!(grpc, ReferenceNode::Grpc(_)));
+        let peer = ReferenceNode::from_str(
+            "e856839057aac496b9e25f10821116d02b58f20129e9b9ba681b830568e47c4d::/ip4/127.0.0.1/tcp/18189",
+        )
+        .unwrap();
+        assert!(matches!(peer, ReferenceNode::Peer(_)));
+        assert!(ReferenceNode::from_str("not a node").is_err());
+    }
+}
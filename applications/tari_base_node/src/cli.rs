@@ -21,7 +21,50 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use chrono::{Datelike, Utc};
+use std::path::PathBuf;
+use structopt::StructOpt;
+use strum_macros::{Display, EnumString};
 use tari_app_utilities::consts;
+use tari_common::ConfigBootstrap;
+
+/// The base node command-line arguments: the bootstrap arguments shared by all the Tari applications, optionally
+/// followed by a command for a base node that is running as a daemon.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "tari_base_node")]
+pub struct Cli {
+    #[structopt(flatten)]
+    pub bootstrap: ConfigBootstrap,
+    /// Detach from the terminal and run in the background, writing a PID file and listening for commands on a unix
+    /// socket in the data directory. Implies non-interactive mode.
+    #[structopt(long)]
+    pub daemon: bool,
+    /// Run as a Windows service, started and stopped by the Service Control Manager. Implies non-interactive mode.
+    #[structopt(long)]
+    pub windows_service: bool,
+    /// Run the console commands in the file, one per line, and shut down once they are done. Use `-` to read the
+    /// commands from stdin. Exits with an error if any command failed.
+    #[structopt(long, parse(from_os_str))]
+    pub command_file: Option<PathBuf>,
+    /// Stop running the command file at the first command that fails
+    #[structopt(long)]
+    pub fail_fast: bool,
+    /// Run as a dedicated seed node, see `seed_mode` in the config
+    #[structopt(long)]
+    pub seed_mode: bool,
+    #[structopt(subcommand)]
+    pub command: Option<ControlCommand>,
+}
+
+/// A command sent to a base node running as a daemon
+#[derive(StructOpt, Clone, Copy, PartialEq, Debug, Display, EnumString)]
+#[structopt(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab_case")]
+pub enum ControlCommand {
+    /// Print the status of the running base node
+    Status,
+    /// Shut the running base node down
+    Stop,
+}
 
 /// returns the top or bottom box line of the specified length
 fn box_line(length: usize, is_top: bool) -> String {
@@ -0,0 +1,190 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Non-interactive execution of console commands, e.g. from cron:
+//!
+//! `tari_base_node --command-file maintenance.txt --fail-fast`
+//!
+//! The file has one console command per line, blank lines and lines starting with `#` are skipped, and `-` reads the
+//! commands from stdin. Each command runs to completion before the next one starts. A command fails if it is not a
+//! valid command, if it panics or if it reports a failure, e.g. `check-db` finding missing blocks. The base node shuts
+//! down once the commands have run and exits with an error if any command failed.
+
+use crate::parser::Parser;
+use log::*;
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tari_shutdown::Shutdown;
+use tokio::sync::Notify;
+
+const LOG_TARGET: &str = "base_node::app::command_batch";
+
+/// Tracks the console commands that are still running in the background and how many of them failed
+#[derive(Debug, Clone, Default)]
+pub struct CommandTracker {
+    state: Arc<TrackerState>,
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    running: AtomicUsize,
+    failed: AtomicUsize,
+    idle: Notify,
+}
+
+impl CommandTracker {
+    pub fn start(&self) {
+        self.state.running.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn finish(&self) {
+        if self.state.running.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.state.idle.notify_one();
+        }
+    }
+
+    /// Marks a command as failed
+    pub fn report_failure(&self) {
+        self.state.failed.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// The number of commands that failed since the node started
+    pub fn failures(&self) -> usize {
+        self.state.failed.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once no commands are running
+    pub async fn wait_until_idle(&self) {
+        while self.state.running.load(Ordering::SeqCst) > 0 {
+            self.state.idle.notified().await;
+        }
+    }
+}
+
+/// A sequence of console commands read from a command file
+#[derive(Debug, Clone)]
+pub struct CommandBatch {
+    commands: Vec<String>,
+    fail_fast: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    pub total: usize,
+    pub run: usize,
+    pub failed: Vec<String>,
+}
+
+impl BatchReport {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl CommandBatch {
+    /// Reads the commands from the file, or from stdin if the path is `-`
+    pub fn read(path: &Path, fail_fast: bool) -> io::Result<Self> {
+        let contents = if path == Path::new("-") {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)?;
+            contents
+        } else {
+            fs::read_to_string(path)?
+        };
+        Ok(Self::parse(&contents, fail_fast))
+    }
+
+    pub fn parse(contents: &str, fail_fast: bool) -> Self {
+        let commands = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(ToString::to_string)
+            .collect();
+        Self { commands, fail_fast }
+    }
+
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /// Runs the commands one after the other and then shuts the node down
+    pub async fn run(self, mut parser: Parser, mut shutdown: Shutdown) -> BatchReport {
+        let tracker = parser.get_command_handler().command_tracker();
+        let mut report = BatchReport {
+            total: self.commands.len(),
+            ..Default::default()
+        };
+        for command in self.commands {
+            if shutdown.is_triggered() {
+                break;
+            }
+            println!("> {}", command);
+            let failures = tracker.failures();
+            let is_valid = parser.handle_command(&command, &mut shutdown);
+            tracker.wait_until_idle().await;
+            report.run += 1;
+            if !is_valid || tracker.failures() > failures {
+                warn!(target: LOG_TARGET, "Command '{}' failed", command);
+                report.failed.push(command);
+                if self.fail_fast {
+                    break;
+                }
+            }
+        }
+        shutdown.trigger();
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_parse_skips_comments_and_blank_lines() {
+        let batch = CommandBatch::parse("# nightly maintenance\ncheck-db\n\n  export-peers peers.json  \n", true);
+        assert_eq!(batch.commands(), ["check-db", "export-peers peers.json"]);
+    }
+
+    #[test]
+    fn test_tracker_counts_failures() {
+        let tracker = CommandTracker::default();
+        block_on(tracker.wait_until_idle());
+        tracker.start();
+        tracker.start();
+        tracker.report_failure();
+        tracker.finish();
+        tracker.finish();
+        block_on(tracker.wait_until_idle());
+        assert_eq!(tracker.failures(), 1);
+    }
+}
@@ -21,54 +21,114 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::LOG_TARGET;
-use crate::{builder::BaseNodeContext, status_line::StatusLine, table::Table, utils::format_duration_basic};
+use crate::{
+    block_timing_monitor::BlockTimingHandle,
+    builder::BaseNodeContext,
+    chain_monitor::{ChainMonitorHandle, TipComparison},
+    command_batch::CommandTracker,
+    config_reload::ConfigReloader,
+    debug_bundle::{DebugBundle, StateEventHistory},
+    discovery_stats::{DiscoveryReport, DiscoveryStage, DiscoveryStatsStore, DISCOVERY_STATS_FILE_NAME},
+    event_journal::EventJournal,
+    hash_rate::{self, sparkline, DEFAULT_HASH_RATE_WINDOW},
+    mining_distribution_monitor::{AlgoDistribution, MiningDistributionHandle},
+    mmr_verifier,
+    peer_db::{self, PurgeCriteria},
+    stats_writer::{ColumnType, Columns, StatsWriter},
+    status_line::{format_hash_rate, StatusLine, StatusLineField, StatusTicker},
+    table::Table,
+    time_sync::TimeSyncHandle,
+    utils::format_duration_basic,
+    utxo_stats::{OutputKind, UtxoStatsCache, SIZE_BUCKET_WIDTH},
+};
 use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt};
 use log::*;
 use std::{
     cmp,
-    fs::File,
+    collections::{HashMap, HashSet, VecDeque},
+    env,
+    fs,
+    future::Future,
     io::{self, Write},
+    path::PathBuf,
     string::ToString,
     sync::Arc,
     time::{Duration, Instant},
 };
-use tari_app_utilities::consts;
-use tari_common::GlobalConfig;
+use strum::IntoEnumIterator;
+use tari_app_grpc::instrumentation::GrpcMetrics;
+use tari_app_utilities::{
+    consts,
+    identity_management,
+    message_signature::MessageSignature,
+    telemetry::{IoCounters, ProcessStats},
+};
+use tari_common::{
+    configuration::{bootstrap::ApplicationType, doctor::ConfigDoctor},
+    logging,
+    set_config_file_value,
+    GlobalConfig,
+    SyncMode,
+};
 use tari_common_types::{
     emoji::EmojiId,
     types::{Commitment, HashOutput, Signature},
 };
 use tari_comms::{
+    connection_manager::{AccessList, AccessRule},
     connectivity::ConnectivityRequester,
     peer_manager::{NodeId, Peer, PeerFeatures, PeerManager, PeerManagerError, PeerQuery},
     protocol::rpc::RpcServerHandle,
+    types::CommsPublicKey,
     NodeIdentity,
 };
 use tari_comms_dht::{envelope::NodeDestination, DhtDiscoveryRequester, MetricsCollectorHandle};
 use tari_core::{
     base_node::{
         comms_interface::BlockEvent,
-        state_machine_service::states::{PeerMetadata, StatusInfo},
+        state_machine_service::states::{PeerMetadata, StatusInfo, WarmupReport},
         LocalNodeCommsInterface,
     },
-    blocks::BlockHeader,
-    chain_storage::{async_db::AsyncBlockchainDb, ChainHeader, LMDBDatabase},
-    consensus::ConsensusManager,
+    blocks::{BlockHeader, OutputProof},
+    chain_storage::{
+        async_db::AsyncBlockchainDb,
+        ChainHeader,
+        ChainStorageError,
+        HistoricalBlock,
+        LMDBDatabase,
+        PrunedOutput,
+    },
+    consensus::{ConsensusManager, LinearWeights, WeightAudit},
     mempool::service::LocalMempoolService,
-    proof_of_work::PowAlgorithm,
+    proof_of_work::{monero_rx::MoneroPowData, randomx_factory::RandomXFactory, PowAlgorithm},
     tari_utilities::{hex::Hex, message_format::MessageFormat},
+    transactions::{script_debug::ScriptDebugReport, transaction::Transaction, CryptoFactories},
+    validation::{
+        block_revalidation::{BlockRevalidator, RevalidationStage},
+        transaction_validators::TxValidationReport,
+    },
+};
+use tari_crypto::{
+    ristretto::RistrettoPublicKey,
+    script::{ExecutionStack, TariScript},
+    tari_utilities::{hex::to_hex, Hashable},
 };
-use tari_crypto::{ristretto::RistrettoPublicKey, tari_utilities::Hashable};
 use tari_p2p::{
-    auto_update::SoftwareUpdaterHandle,
-    services::liveness::{LivenessEvent, LivenessHandle},
+    auto_update::{self, SoftwareUpdaterHandle},
+    services::liveness::{LivenessEvent, LivenessEventReceiver, LivenessHandle, PeerPingStats, RTT_BUCKET_BOUNDS_MS},
 };
 use tokio::{
     runtime,
-    sync::{broadcast, watch},
+    sync::{broadcast, watch, Mutex},
+    task,
     time,
 };
 
+/// The maximum number of headers, including the header of the block the output was mined in, written to an output
+/// proof
+pub const PROVE_OUTPUT_MAX_HEADERS: u64 = 1000;
+
 pub enum StatusOutput {
     Log,
     Full,
@@ -84,18 +144,71 @@ pub struct CommandHandler {
     base_node_identity: Arc<NodeIdentity>,
     peer_manager: Arc<PeerManager>,
     connectivity: ConnectivityRequester,
+    access_list: AccessList,
     liveness: LivenessHandle,
     node_service: LocalNodeCommsInterface,
     mempool_service: LocalMempoolService,
     state_machine_info: watch::Receiver<StatusInfo>,
+    warmup_report: watch::Receiver<Option<WarmupReport>>,
     software_updater: SoftwareUpdaterHandle,
+    restart: Arc<watch::Sender<Option<PathBuf>>>,
+    restart_notifier: watch::Receiver<Option<PathBuf>>,
+    config_reloader: ConfigReloader,
+    state_event_history: StateEventHistory,
+    chain_monitor: ChainMonitorHandle,
+    block_timing: BlockTimingHandle,
+    mining_distribution: MiningDistributionHandle,
+    time_sync: TimeSyncHandle,
+    status_line_fields: Vec<StatusLineField>,
+    status_ticker: Arc<watch::Sender<StatusTicker>>,
+    status_ticker_receiver: watch::Receiver<StatusTicker>,
+    commands: CommandTracker,
+    utxo_stats: Arc<Mutex<UtxoStatsCache>>,
+    discovery_stats: Arc<Mutex<DiscoveryStatsStore>>,
+    grpc_metrics: Option<GrpcMetrics>,
+    event_journal: EventJournal,
 }
 
+/// The number of blocks or headers that `period-stats` and `header-stats` fetch from the database at a time
+const STATS_BATCH_SIZE: u64 = 100;
+const HEADER_STATS_COLUMNS: Columns = &[
+    ("Height", ColumnType::UInt),
+    ("Achieved", ColumnType::UInt),
+    ("TargetDifficulty", ColumnType::UInt),
+    ("CalculatedDifficulty", ColumnType::UInt),
+    ("SolveTime", ColumnType::Int),
+    ("NormalizedSolveTime", ColumnType::UInt),
+    ("Algo", ColumnType::Text),
+    ("Timestamp", ColumnType::Text),
+    ("Window", ColumnType::UInt),
+    ("Acc.Monero", ColumnType::UInt),
+    ("Acc.Sha3", ColumnType::UInt),
+];
+const PERIOD_STATS_COLUMNS: Columns = &[
+    ("TxCount", ColumnType::UInt),
+    ("HashRate", ColumnType::Float),
+    ("TargetDifficulty", ColumnType::UInt),
+    ("SolveTime", ColumnType::UInt),
+    ("BlockCount", ColumnType::UInt),
+];
+
 impl CommandHandler {
-    pub fn new(executor: runtime::Handle, ctx: &BaseNodeContext) -> Self {
+    pub fn new(
+        executor: runtime::Handle,
+        ctx: &BaseNodeContext,
+        config_reloader: ConfigReloader,
+        grpc_metrics: Option<GrpcMetrics>,
+    ) -> Self {
+        let (restart, restart_notifier) = watch::channel(None);
+        let config = ctx.config();
+        let (status_ticker, status_ticker_receiver) =
+            watch::channel(StatusTicker::from_interval(config.status_line_interval));
+        let status_line_fields = parse_status_line_fields(&config.status_line_fields);
+        let state_event_history =
+            StateEventHistory::spawn(&executor, ctx.state_machine().get_state_change_event_stream());
         Self {
             executor,
-            config: ctx.config(),
+            config,
             blockchain_db: ctx.blockchain_db().into(),
             discovery_service: ctx.base_node_dht().discovery_service_requester(),
             dht_metrics_collector: ctx.base_node_dht().metrics_collector(),
@@ -103,97 +216,83 @@ impl CommandHandler {
             base_node_identity: ctx.base_node_identity(),
             peer_manager: ctx.base_node_comms().peer_manager(),
             connectivity: ctx.base_node_comms().connectivity(),
+            access_list: ctx.base_node_comms().access_list().clone(),
             liveness: ctx.liveness(),
             node_service: ctx.local_node(),
             mempool_service: ctx.local_mempool(),
             state_machine_info: ctx.get_state_machine_info_channel(),
+            warmup_report: ctx.state_machine().get_warmup_report_watch(),
             software_updater: ctx.software_updater(),
+            restart: Arc::new(restart),
+            restart_notifier,
+            config_reloader,
+            state_event_history,
+            chain_monitor: ctx.chain_monitor(),
+            block_timing: ctx.block_timing_monitor(),
+            mining_distribution: ctx.mining_distribution_monitor(),
+            time_sync: ctx.time_sync(),
+            status_line_fields,
+            status_ticker: Arc::new(status_ticker),
+            status_ticker_receiver,
+            commands: CommandTracker::default(),
+            utxo_stats: Arc::new(Mutex::new(UtxoStatsCache::default())),
+            discovery_stats: Arc::new(Mutex::new(DiscoveryStatsStore::load(
+                config.data_dir.join(DISCOVERY_STATS_FILE_NAME),
+            ))),
+            grpc_metrics,
+            event_journal: ctx.event_journal(),
         }
     }
 
-    pub fn status(&self, output: StatusOutput) {
-        let state_info = self.state_machine_info.clone();
-        let mut node = self.node_service.clone();
-        let mut mempool = self.mempool_service.clone();
-        let peer_manager = self.peer_manager.clone();
-        let mut connectivity = self.connectivity.clone();
-        let mut metrics = self.dht_metrics_collector.clone();
-        let mut rpc_server = self.rpc_server.clone();
-        let config = self.config.clone();
-
+    /// Runs a command in the background. The command is tracked so that a batch of commands can wait for it to
+    /// complete, and counts as failed if it panics.
+    fn spawn<F>(&self, command: F)
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let commands = self.commands.clone();
+        commands.start();
+        let task = self.executor.spawn(command);
         self.executor.spawn(async move {
-            let mut status_line = StatusLine::new();
-            status_line.add_field("", format!("v{}", consts::APP_VERSION_NUMBER));
-            status_line.add_field("", config.network);
-            status_line.add_field("State", state_info.borrow().state_info.short_desc());
-
-            let metadata = node.get_metadata().await.unwrap();
-
-            let last_header = node
-                .get_headers(vec![metadata.height_of_longest_chain()])
-                .await
-                .unwrap()
-                .pop()
-                .unwrap();
-            let last_block_time = DateTime::<Utc>::from(last_header.timestamp);
-            status_line.add_field(
-                "Tip",
-                format!(
-                    "{} ({})",
-                    metadata.height_of_longest_chain(),
-                    last_block_time.to_rfc2822()
-                ),
-            );
-
-            let mempool_stats = mempool.get_mempool_stats().await.unwrap();
-            status_line.add_field(
-                "Mempool",
-                format!(
-                    "{}tx ({}g, +/- {}blks)",
-                    mempool_stats.total_txs,
-                    mempool_stats.total_weight,
-                    if mempool_stats.total_weight == 0 {
-                        0
-                    } else {
-                        1 + mempool_stats.total_weight / 19500
-                    },
-                ),
-            );
+            if task.await.is_err() {
+                commands.report_failure();
+            }
+            commands.finish();
+        });
+    }
 
-            let conns = connectivity.get_active_connections().await.unwrap();
-            status_line.add_field("Connections", conns.len());
-            let banned_peers = fetch_banned_peers(&peer_manager).await.unwrap();
-            status_line.add_field("Banned", banned_peers.len());
+    /// Returns the tracker of the commands that are running in the background
+    pub fn command_tracker(&self) -> CommandTracker {
+        self.commands.clone()
+    }
 
-            let num_messages = metrics
-                .get_total_message_count_in_timespan(Duration::from_secs(60))
-                .await
-                .unwrap();
-            status_line.add_field("Messages (last 60s)", num_messages);
+    /// Returns a watch notifier that contains the binary to restart with once an update was installed or rolled back
+    pub fn restart_notifier(&self) -> watch::Receiver<Option<PathBuf>> {
+        self.restart_notifier.clone()
+    }
 
-            let num_active_rpc_sessions = rpc_server.get_num_active_sessions().await.unwrap();
-            status_line.add_field(
-                "Rpc",
-                format!(
-                    "{}/{} sessions",
-                    num_active_rpc_sessions,
-                    config
-                        .rpc_max_simultaneous_sessions
-                        .as_ref()
-                        .map(ToString::to_string)
-                        .unwrap_or_else(|| "∞".to_string()),
-                ),
-            );
+    /// Returns a watch receiver that contains how often the status line should be printed
+    pub fn status_ticker(&self) -> watch::Receiver<StatusTicker> {
+        self.status_ticker_receiver.clone()
+    }
 
-            status_line.add_field(
-                "RandomX",
-                format!(
-                    "#{} with flags {:?}",
-                    state_info.borrow().randomx_vm_cnt,
-                    state_info.borrow().randomx_vm_flags
-                ),
-            );
+    /// Function to process the status --watch command
+    pub fn set_status_ticker(&self, ticker: StatusTicker) {
+        match ticker {
+            StatusTicker::Default => println!("Status line restored to the default interval"),
+            StatusTicker::Every(interval) => {
+                println!("Printing the status line every {}", format_duration_basic(interval))
+            },
+            StatusTicker::Off => println!("Status line turned off"),
+        }
+        let _ = self.status_ticker.send(ticker);
+    }
 
+    pub fn status(&self, output: StatusOutput) {
+        let status_line = self.status_line();
+        self.spawn(async move {
+            let status_line = status_line.await;
             let target = "base_node::app::status";
             match output {
                 StatusOutput::Full => {
@@ -205,17 +304,192 @@ impl CommandHandler {
         });
     }
 
+    /// Collects the node status summary with the fields selected in the config: version, state, tip, hash rate,
+    /// mempool, connections and RPC sessions
+    pub fn status_line(&self) -> impl Future<Output = StatusLine> {
+        let state_info = self.state_machine_info.clone();
+        let mut node = self.node_service.clone();
+        let mut mempool = self.mempool_service.clone();
+        let peer_manager = self.peer_manager.clone();
+        let mut connectivity = self.connectivity.clone();
+        let mut metrics = self.dht_metrics_collector.clone();
+        let mut rpc_server = self.rpc_server.clone();
+        let config = self.config.clone();
+        let fields = self.status_line_fields.clone();
+        let db = self.blockchain_db.clone();
+        let block_timing = self.block_timing.clone();
+        let mining_distribution = self.mining_distribution.clone();
+
+        async move {
+            let mut status_line = StatusLine::new();
+            for field in fields {
+                match field {
+                    StatusLineField::Version => {
+                        status_line.add_field("", format!("v{}", consts::APP_VERSION_NUMBER));
+                    },
+                    StatusLineField::Network => {
+                        status_line.add_field("", config.network);
+                    },
+                    StatusLineField::State => {
+                        let status = state_info.borrow();
+                        if status.read_only {
+                            status_line.add_field("State", format!("{} (read-only)", status.state_info.short_desc()));
+                        } else {
+                            status_line.add_field("State", status.state_info.short_desc());
+                        }
+                    },
+                    StatusLineField::Tip => {
+                        let metadata = node.get_metadata().await.unwrap();
+                        let last_header = node
+                            .get_headers(vec![metadata.height_of_longest_chain()])
+                            .await
+                            .unwrap()
+                            .pop()
+                            .unwrap();
+                        let last_block_time = DateTime::<Utc>::from(last_header.timestamp);
+                        status_line.add_field(
+                            "Tip",
+                            format!(
+                                "{} ({})",
+                                metadata.height_of_longest_chain(),
+                                last_block_time.to_rfc2822()
+                            ),
+                        );
+                    },
+                    StatusLineField::Hashrate => {
+                        let estimate = hash_rate::estimate_hash_rates(&db, DEFAULT_HASH_RATE_WINDOW, 1).await;
+                        if let Some(window) = estimate.ok().and_then(|mut windows| windows.pop()) {
+                            status_line.add_field(
+                                "Hashrate",
+                                format!(
+                                    "{} (SHA3), {} (RandomX)",
+                                    format_hash_rate(window.sha3),
+                                    format_hash_rate(window.monero)
+                                ),
+                            );
+                        }
+                    },
+                    StatusLineField::BlockTiming => {
+                        if !block_timing.is_enabled() {
+                            continue;
+                        }
+                        let status = block_timing.status();
+                        if let Some(average) = status.average_block_time {
+                            let mut value = format!("{:.0}s avg (target {:.0}s)", average, status.target_block_time);
+                            let num_anomalies = status.num_anomalies_since(Utc::now() - chrono::Duration::hours(1));
+                            if num_anomalies > 0 {
+                                value.push_str(&format!(", {} warning(s) in the last hour", num_anomalies));
+                            }
+                            status_line.add_field("Block time", value);
+                        }
+                    },
+                    StatusLineField::MiningDistribution => {
+                        if !mining_distribution.is_enabled() {
+                            continue;
+                        }
+                        let status = mining_distribution.status();
+                        let top_shares = [("RandomX", &status.monero), ("SHA3", &status.sha3)]
+                            .iter()
+                            .filter_map(|(name, distribution)| {
+                                distribution
+                                    .top_miner()
+                                    .map(|(_, share)| format!("{:.0}% ({})", share * 100.0, name))
+                            })
+                            .collect::<Vec<_>>();
+                        if !top_shares.is_empty() {
+                            status_line.add_field("Top miner", top_shares.join(", "));
+                        }
+                    },
+                    StatusLineField::Mempool => {
+                        let mempool_stats = mempool.get_mempool_stats().await.unwrap();
+                        status_line.add_field(
+                            "Mempool",
+                            format!(
+                                "{}tx ({}g, +/- {}blks)",
+                                mempool_stats.total_txs,
+                                mempool_stats.total_weight,
+                                if mempool_stats.total_weight == 0 {
+                                    0
+                                } else {
+                                    1 + mempool_stats.total_weight / 19500
+                                },
+                            ),
+                        );
+                    },
+                    StatusLineField::Connections => {
+                        let conns = connectivity.get_active_connections().await.unwrap();
+                        status_line.add_field("Connections", conns.len());
+                    },
+                    StatusLineField::Churn => {
+                        let churn = connectivity.get_connection_churn().await.unwrap();
+                        status_line.add_field(
+                            "Churn",
+                            format!(
+                                "+{}/-{} ({}m)",
+                                churn.num_recent_connected,
+                                churn.num_recent_disconnected,
+                                churn.window.as_secs() / 60
+                            ),
+                        );
+                    },
+                    StatusLineField::Banned => {
+                        let banned_peers = fetch_banned_peers(&peer_manager).await.unwrap();
+                        status_line.add_field("Banned", banned_peers.len());
+                    },
+                    StatusLineField::Messages => {
+                        let num_messages = metrics
+                            .get_total_message_count_in_timespan(Duration::from_secs(60))
+                            .await
+                            .unwrap();
+                        status_line.add_field("Messages (last 60s)", num_messages);
+                    },
+                    StatusLineField::Rpc => {
+                        let num_active_rpc_sessions = rpc_server.get_num_active_sessions().await.unwrap();
+                        status_line.add_field(
+                            "Rpc",
+                            format!(
+                                "{}/{} sessions",
+                                num_active_rpc_sessions,
+                                config
+                                    .rpc_max_simultaneous_sessions
+                                    .as_ref()
+                                    .map(ToString::to_string)
+                                    .unwrap_or_else(|| "∞".to_string()),
+                            ),
+                        );
+                    },
+                    StatusLineField::RandomX => {
+                        status_line.add_field(
+                            "RandomX",
+                            format!(
+                                "#{} with flags {:?}",
+                                state_info.borrow().randomx_vm_cnt,
+                                state_info.borrow().randomx_vm_flags
+                            ),
+                        );
+                    },
+                }
+            }
+            status_line
+        }
+    }
+
     /// Function to process the get-state-info command
     pub fn state_info(&self) {
         let watch = self.state_machine_info.clone();
         println!("Current state machine state:\n{}", *watch.borrow());
+        println!("Sync mode: {}", self.config.sync_mode);
+        match &*self.warmup_report.borrow() {
+            Some(report) => println!("Last connection warm-up: {}", report),
+            None => println!("Last connection warm-up: none"),
+        }
     }
 
     /// Check for updates
     pub fn check_for_updates(&self) {
         let mut updater = self.software_updater.clone();
         println!("Checking for updates (current version: {})...", consts::APP_VERSION);
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match updater.check_for_updates().await {
                 Some(update) => {
                     println!(
@@ -233,6 +507,235 @@ impl CommandHandler {
         });
     }
 
+    /// Re-reads the configuration file and applies the settings that can change without a restart
+    pub fn reload_config(&self) {
+        match self.config_reloader.reload() {
+            Ok(report) => {
+                info!(target: LOG_TARGET, "Configuration reloaded. {}", report);
+                print!("{}", report);
+            },
+            Err(err) => {
+                println!("The configuration was not reloaded: {}", err);
+                warn!(target: LOG_TARGET, "Failed to reload the configuration: {}", err);
+            },
+        }
+    }
+
+    /// Writes the sync mode to the configuration file, or shows the active sync mode if none is given. The new mode is
+    /// used after the node is restarted.
+    pub fn set_sync_mode(&self, mode: Option<SyncMode>) {
+        let mode = match mode {
+            Some(mode) => mode,
+            None => {
+                println!("Sync mode: {}", self.config.sync_mode);
+                return;
+            },
+        };
+        if let Err(err) = mode.check_pruning_horizon(self.config.pruning_horizon) {
+            println!("{}", err);
+            return;
+        }
+        let db = self.blockchain_db.clone();
+        let config_file = self.config_reloader.bootstrap().config.clone();
+        let section = format!("base_node.{}", self.config.network.as_str());
+        let active_mode = self.config.sync_mode;
+        self.spawn(async move {
+            let metadata = try_or_print!(db.get_chain_metadata().await);
+            if mode == SyncMode::Archival && metadata.pruned_height() > 0 {
+                println!(
+                    "The blockchain database is pruned up to height {}, it cannot be used in the archival sync mode",
+                    metadata.pruned_height()
+                );
+                return;
+            }
+            if let Err(err) = set_config_file_value(&config_file, &section, "sync_mode", &format!("\"{}\"", mode)) {
+                println!("Failed to write the sync mode to {}: {}", config_file.display(), err);
+                return;
+            }
+            info!(
+                target: LOG_TARGET,
+                "Sync mode set to {} in {}", mode, config_file.display()
+            );
+            println!(
+                "Sync mode set to {} in {}. Restart the node to apply it, {} is used until then.",
+                mode,
+                config_file.display(),
+                active_mode
+            );
+        });
+    }
+
+    /// Writes configuration (with secrets masked), recent logs, chain, peer and database information to a zip file
+    /// that can be attached to bug reports
+    pub fn generate_debug_bundle(&self, path: Option<PathBuf>) {
+        let path = path.unwrap_or_else(|| {
+            self.config
+                .data_dir
+                .join(format!("debug-bundle-{}.zip", Utc::now().format("%Y%m%d-%H%M%S")))
+        });
+        let config_report = match ConfigDoctor::diagnose(self.config_reloader.bootstrap()) {
+            Ok(doctor) => doctor.to_string(),
+            Err(err) => format!("Could not load the configuration: {}", err),
+        };
+        let mut node = self.node_service.clone();
+        let peer_manager = self.peer_manager.clone();
+        let mut connectivity = self.connectivity.clone();
+        let db = self.blockchain_db.clone();
+        let status = self.state_machine_info.borrow().to_string();
+        let state_events = self.state_event_history.events();
+        let network = self.config.network;
+
+        println!("Generating debug bundle...");
+        self.spawn(async move {
+            let mut bundle = DebugBundle::new();
+            bundle.add(
+                "version.txt",
+                format!(
+                    "{} {}\nNetwork: {}\nPlatform: {} {}\nGenerated: {}\n",
+                    ApplicationType::BaseNode,
+                    consts::APP_VERSION,
+                    network,
+                    env::consts::OS,
+                    env::consts::ARCH,
+                    Utc::now().to_rfc2822()
+                ),
+            );
+            bundle.add("config.txt", config_report);
+            bundle.add("status.txt", status);
+            bundle.add(
+                "state_events.txt",
+                state_events
+                    .iter()
+                    .map(|(time, event)| format!("{} {}\n", time.to_rfc3339(), event))
+                    .collect::<String>(),
+            );
+            bundle.add("chain_metadata.txt", match node.get_metadata().await {
+                Ok(metadata) => metadata.to_string(),
+                Err(err) => format!("Failed to retrieve chain metadata: {:?}", err),
+            });
+
+            let mut peers = format!("Known peers: {}\n", peer_manager.count().await);
+            match fetch_banned_peers(&peer_manager).await {
+                Ok(banned) => peers.push_str(&format!("Banned peers: {}\n", banned.len())),
+                Err(err) => peers.push_str(&format!("Failed to retrieve banned peers: {:?}\n", err)),
+            }
+            match connectivity.get_connectivity_status().await {
+                Ok(status) => peers.push_str(&format!("Connectivity: {}\n", status)),
+                Err(err) => peers.push_str(&format!("Failed to retrieve connectivity status: {:?}\n", err)),
+            }
+            match connectivity.get_active_connections().await {
+                Ok(conns) => peers.push_str(&format!("Active connections: {}\n", conns.len())),
+                Err(err) => peers.push_str(&format!("Failed to retrieve active connections: {:?}\n", err)),
+            }
+            bundle.add("peers.txt", peers);
+
+            bundle.add("db_stats.txt", match db.get_stats().await {
+                Ok(stats) => stats.to_string(),
+                Err(err) => format!("Failed to retrieve database stats: {:?}", err),
+            });
+            bundle.add_log_files(&logging::log_files());
+
+            let written = task::spawn_blocking(move || bundle.write_zip(&path).map(|_| path)).await;
+            match written {
+                Ok(Ok(path)) => {
+                    println!("Debug bundle written to {}", path.display());
+                    println!("Please review its contents before sharing it");
+                },
+                Ok(Err(err)) => {
+                    println!("Failed to write the debug bundle: {}", err);
+                    warn!(target: LOG_TARGET, "Failed to write the debug bundle: {}", err);
+                },
+                Err(err) => {
+                    println!("Failed to write the debug bundle: {}", err);
+                },
+            }
+        });
+    }
+
+    /// Changes the level of a log target while the node is running
+    pub fn set_log_level(&self, target: &str, level: LevelFilter) {
+        match logging::set_log_level(target, level) {
+            Ok(_) => {
+                info!(target: LOG_TARGET, "Log level of '{}' set to {}", target, level);
+                println!("Log level of '{}' set to {}", target, level);
+            },
+            Err(err) => {
+                println!("Could not set the log level: {}", err);
+            },
+        }
+    }
+
+    /// Lists the log levels changed while the node is running
+    pub fn list_log_levels(&self) {
+        let levels = logging::log_levels();
+        if levels.is_empty() {
+            println!("No log levels have been changed, the levels of the logging configuration file apply");
+            return;
+        }
+        for (target, level) in levels {
+            println!("{}: {}", target, level);
+        }
+    }
+
+    /// Check for updates and install the update if one is found, the node restarts once it is installed
+    pub fn install_update(&self) {
+        let mut updater = self.software_updater.clone();
+        let staging_dir = self.config.data_dir.join("updates");
+        let restart = self.restart.clone();
+        println!("Checking for updates (current version: {})...", consts::APP_VERSION);
+        self.spawn(async move {
+            let update = match updater.check_for_updates().await {
+                Some(update) => update,
+                None => {
+                    println!("No updates found.",);
+                    return;
+                },
+            };
+            let exe = match env::current_exe() {
+                Ok(exe) => exe,
+                Err(err) => {
+                    println!("Could not find the installed base node binary: {}", err);
+                    return;
+                },
+            };
+            println!("Downloading version {} of the {}...", update.version(), update.app());
+            let staged = match auto_update::download_update(&update, &staging_dir, &exe).await {
+                Ok(staged) => staged,
+                Err(err) => {
+                    println!("Failed to download the update: {}", err);
+                    warn!(target: LOG_TARGET, "Failed to download update {}: {}", update, err);
+                    return;
+                },
+            };
+            match auto_update::install_update(&staged, &exe) {
+                Ok(()) => {
+                    println!("Version {} installed, restarting...", update.version());
+                    let _ = restart.send(Some(exe));
+                },
+                Err(err) => {
+                    println!("Failed to install the update: {}", err);
+                    warn!(target: LOG_TARGET, "Failed to install update {}: {}", update, err);
+                },
+            }
+        });
+    }
+
+    /// Restore the version replaced by the last installed update and restart
+    pub fn rollback_update(&self) {
+        let result = env::current_exe()
+            .map_err(Into::into)
+            .and_then(|exe| auto_update::rollback_update(&exe).map(|_| exe));
+        match result {
+            Ok(exe) => {
+                println!("Rolled back to the previous version, restarting...");
+                let _ = self.restart.send(Some(exe));
+            },
+            Err(err) => {
+                println!("Failed to roll back the update: {}", err);
+            },
+        }
+    }
+
     /// Function process the version command
     pub fn print_version(&self) {
         println!("Version: {}", consts::APP_VERSION);
@@ -255,7 +758,7 @@ impl CommandHandler {
 
     pub fn get_chain_meta(&self) {
         let mut handler = self.node_service.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match handler.get_metadata().await {
                 Err(err) => {
                     println!("Failed to retrieve chain metadata: {:?}", err);
@@ -268,7 +771,7 @@ impl CommandHandler {
 
     pub fn get_block(&self, height: u64, format: Format) {
         let blockchain = self.blockchain_db.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match blockchain.fetch_blocks(height..=height).await {
                 Ok(mut data) => match (data.pop(), format) {
                     (Some(block), Format::Text) => {
@@ -295,7 +798,7 @@ impl CommandHandler {
 
     pub fn get_block_by_hash(&self, hash: HashOutput, format: Format) {
         let blockchain = self.blockchain_db.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match blockchain.fetch_block_by_hash(hash).await {
                 Err(err) => {
                     println!("Failed to retrieve blocks: {}", err);
@@ -313,54 +816,230 @@ impl CommandHandler {
         });
     }
 
-    pub fn search_utxo(&self, commitment: Commitment) {
-        let mut handler = self.node_service.clone();
-        self.executor.spawn(async move {
-            match handler.fetch_blocks_with_utxos(vec![commitment.clone()]).await {
+    /// Prints the estimated network hash rate per PoW algorithm over the last `num_windows` windows of `window` blocks,
+    /// with a chart of the hash rate over the windows
+    pub fn get_network_hash_rate(&self, window: u64, num_windows: u64) {
+        let db = self.blockchain_db.clone();
+        self.spawn(async move {
+            let windows = match hash_rate::estimate_hash_rates(&db, window, num_windows).await {
+                Ok(windows) => windows,
                 Err(err) => {
-                    println!("Failed to retrieve blocks: {:?}", err);
-                    warn!(
-                        target: LOG_TARGET,
-                        "Error communicating with local base node: {:?}", err,
-                    );
+                    println!("Failed to estimate the network hash rate: {}", err);
+                    warn!(target: LOG_TARGET, "{}", err);
+                    return;
                 },
-                Ok(mut data) => match data.pop() {
-                    Some(v) => println!("{}", v.block()),
-                    _ => println!(
-                        "Pruned node: utxo found, but block not found for utxo commitment {}",
-                        commitment.to_hex()
-                    ),
+            };
+            let (first, latest) = match (windows.first(), windows.last()) {
+                (Some(first), Some(latest)) => (first, latest),
+                _ => {
+                    println!("The chain is not long enough to estimate the hash rate");
+                    return;
                 },
             };
+            println!(
+                "Estimated network hash rate over blocks #{} to #{} in windows of {} blocks",
+                first.start_height + 1,
+                latest.end_height,
+                window
+            );
+            for (name, pow_algo) in [("SHA3", PowAlgorithm::Sha3), ("RandomX", PowAlgorithm::Monero)] {
+                let rates = windows.iter().map(|w| w.hash_rate(pow_algo)).collect::<Vec<_>>();
+                let max = rates.iter().cloned().fold(0.0, f64::max);
+                let avg = rates.iter().sum::<f64>() / rates.len() as f64;
+                println!(
+                    "{:<8} {} latest: {}, avg: {}, max: {}",
+                    name,
+                    sparkline(&rates),
+                    format_hash_rate(latest.hash_rate(pow_algo)),
+                    format_hash_rate(avg),
+                    format_hash_rate(max)
+                );
+            }
         });
     }
 
-    pub fn search_kernel(&self, excess_sig: Signature) {
-        let mut handler = self.node_service.clone();
-        let hex_sig = excess_sig.get_signature().to_hex();
-        self.executor.spawn(async move {
-            match handler.get_blocks_with_kernels(vec![excess_sig]).await {
+    pub fn get_header(&self, height: u64, format: Format) {
+        let blockchain = self.blockchain_db.clone();
+        self.spawn(async move {
+            match blockchain.fetch_chain_header(height).await {
+                Ok(header) => print_chain_header(&header, format),
                 Err(err) => {
-                    println!("Failed to retrieve blocks: {:?}", err);
-                    warn!(
-                        target: LOG_TARGET,
-                        "Error communicating with local base node: {:?}", err,
+                    println!("Failed to retrieve the header at height {}: {}", height, err);
+                    warn!(target: LOG_TARGET, "{}", err);
+                },
+            }
+        });
+    }
+
+    pub fn get_header_by_hash(&self, hash: HashOutput, format: Format) {
+        let blockchain = self.blockchain_db.clone();
+        self.spawn(async move {
+            match blockchain.fetch_chain_header_by_block_hash(hash).await {
+                Ok(Some(header)) => print_chain_header(&header, format),
+                Ok(None) => println!("Header not found"),
+                Err(err) => {
+                    println!("Failed to retrieve the header: {}", err);
+                    warn!(target: LOG_TARGET, "{}", err);
+                },
+            }
+        });
+    }
+
+    /// Looks up outputs by commitment and prints the block each one was mined in and whether it has been spent
+    pub fn search_utxos(&self, commitments: Vec<Commitment>) {
+        let db = self.blockchain_db.clone();
+        self.spawn(async move {
+            let tip = match db.get_chain_metadata().await {
+                Ok(metadata) => metadata.height_of_longest_chain(),
+                Err(err) => {
+                    println!("Failed to read the chain metadata: {}", err);
+                    return;
+                },
+            };
+            let mut table = Table::new();
+            table.set_titles(vec!["Commitment", "Height", "Block hash", "Confirmations", "Status"]);
+            let mut num_found = 0;
+            for commitment in &commitments {
+                let key = commitment.to_hex();
+                match find_utxo(&db, commitment.clone()).await {
+                    Ok(Some((height, block_hash, spent))) => {
+                        num_found += 1;
+                        table.add_row(row![
+                            key,
+                            height,
+                            block_hash.to_hex(),
+                            tip.saturating_sub(height) + 1,
+                            if spent { "Spent" } else { "Unspent" }
+                        ]);
+                    },
+                    Ok(None) => table.add_row(row![key, "-", "-", "-", "Not in the UTXO set"]),
+                    Err(err) => {
+                        warn!(target: LOG_TARGET, "Error searching for output {}: {}", key, err);
+                        table.add_row(row![key, "-", "-", "-", format!("Error: {}", err)]);
+                    },
+                }
+            }
+            table.print_stdout();
+            println!(
+                "{} of {} output(s) found. Spent outputs are not indexed by commitment and are not found.",
+                num_found,
+                commitments.len()
+            );
+        });
+    }
+
+    /// Writes a proof that the unspent output with the given commitment was mined, along with the headers that build on
+    /// its block, to a JSON file that can be verified offline
+    pub fn prove_output(&self, commitment: Commitment, filename: String) {
+        let db = self.blockchain_db.clone();
+        self.spawn(async move {
+            let output_hash = match try_or_print!(db.fetch_unspent_output_by_commitment(commitment.clone()).await) {
+                Some(hash) => hash,
+                None => {
+                    println!(
+                        "No unspent output with commitment {} was found. Spent outputs are not indexed by commitment.",
+                        commitment.to_hex()
                     );
+                    return;
+                },
+            };
+            let output = match try_or_print!(db.fetch_output(output_hash.clone()).await) {
+                Some((PrunedOutput::NotPruned { output }, _, _)) => output,
+                Some((PrunedOutput::Pruned { .. }, _, _)) => {
+                    println!("Output {} has been pruned and cannot be proven", commitment.to_hex());
+                    return;
+                },
+                None => {
+                    println!("Output {} was not found", commitment.to_hex());
+                    return;
                 },
-                Ok(mut data) => match data.pop() {
-                    Some(v) => println!("{}", v),
-                    _ => println!("No kernel with signature {} found", hex_sig),
+            };
+            let membership_proof = match try_or_print!(db.fetch_output_membership_proof(output_hash).await) {
+                Some(proof) => proof,
+                None => {
+                    println!("Output {} was not found", commitment.to_hex());
+                    return;
+                },
+            };
+
+            let mined_height = membership_proof.header.height;
+            let headers = try_or_print!(
+                db.fetch_headers(mined_height..mined_height + PROVE_OUTPUT_MAX_HEADERS)
+                    .await
+            );
+            let proof = OutputProof {
+                output,
+                leaf_index: membership_proof.leaf_index,
+                merkle_proof: membership_proof.merkle_proof,
+                headers,
+            };
+            // A reorg between fetching the proof and the headers results in an invalid proof
+            try_or_print!(
+                proof.verify(&CryptoFactories::default().range_proof),
+                "The generated proof is invalid, please try again: {error}"
+            );
+            let json = try_or_print!(serde_json::to_string_pretty(&proof));
+            try_or_print!(fs::write(&filename, json), "Could not write '{}': {error}", filename);
+
+            println!(
+                "Proof that output {} was mined in block #{} with {} confirmation(s) written to {}",
+                commitment.to_hex(),
+                mined_height,
+                proof.confirmations(),
+                filename
+            );
+            if let Some(tip) = proof.tip_header() {
+                println!(
+                    "The last header in the proof is #{} ({})",
+                    tip.height,
+                    tip.hash().to_hex()
+                );
+            }
+        });
+    }
+
+    /// Looks up kernels by excess signature and prints the block each one was mined in
+    pub fn search_kernels(&self, excess_sigs: Vec<Signature>) {
+        let db = self.blockchain_db.clone();
+        self.spawn(async move {
+            let tip = match db.get_chain_metadata().await {
+                Ok(metadata) => metadata.height_of_longest_chain(),
+                Err(err) => {
+                    println!("Failed to read the chain metadata: {}", err);
+                    return;
                 },
             };
+            let mut table = Table::new();
+            table.set_titles(vec!["Excess signature", "Height", "Block hash", "Confirmations"]);
+            let mut num_found = 0;
+            for excess_sig in &excess_sigs {
+                let key = excess_sig.get_signature().to_hex();
+                match find_kernel(&db, excess_sig.clone()).await {
+                    Ok(Some((height, block_hash))) => {
+                        num_found += 1;
+                        table.add_row(row![key, height, block_hash.to_hex(), tip.saturating_sub(height) + 1]);
+                    },
+                    Ok(None) => table.add_row(row![key, "-", "Not found", "-"]),
+                    Err(err) => {
+                        warn!(target: LOG_TARGET, "Error searching for kernel {}: {}", key, err);
+                        table.add_row(row![key, "-", format!("Error: {}", err), "-"]);
+                    },
+                }
+            }
+            table.print_stdout();
+            println!("{} of {} kernel(s) found", num_found, excess_sigs.len());
         });
     }
 
     /// Function to process the get-mempool-stats command
     pub fn get_mempool_stats(&self) {
         let mut handler = self.mempool_service.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match handler.get_mempool_stats().await {
-                Ok(stats) => println!("{}", stats),
+                Ok(stats) => {
+                    println!("{}", stats);
+                    println!("{}", handler.get_ingress_stats());
+                },
                 Err(err) => {
                     println!("Failed to retrieve mempool stats: {:?}", err);
                     warn!(target: LOG_TARGET, "Error communicating with local mempool: {:?}", err,);
@@ -372,7 +1051,7 @@ impl CommandHandler {
     /// Function to process the get-mempool-state command
     pub fn get_mempool_state(&self) {
         let mut handler = self.mempool_service.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match handler.get_mempool_state().await {
                 Ok(state) => println!("{}", state),
                 Err(err) => {
@@ -383,33 +1062,217 @@ impl CommandHandler {
         });
     }
 
+    /// Prints the events recorded in the event journal since the given time
+    pub fn events(&self, since: DateTime<Utc>) {
+        let events = match self.event_journal.events_since(since) {
+            Ok(events) => events,
+            Err(err) => {
+                println!("Could not read the event journal: {}", err);
+                self.commands.report_failure();
+                return;
+            },
+        };
+        if events.is_empty() {
+            println!("No events since {}", since.format("%Y-%m-%d %H:%M:%S"));
+            return;
+        }
+        let mut table = Table::new();
+        table.set_titles(vec!["Time", "Event", "Details"]);
+        for event in events {
+            table.add_row(row![
+                event.time().format("%Y-%m-%d %H:%M:%S"),
+                event.kind,
+                event.message
+            ]);
+        }
+        table.print_stdout();
+    }
+
+    /// Prints the number of gRPC calls and their latency by method
+    pub fn grpc_stats(&self) {
+        let metrics = match self.grpc_metrics {
+            Some(ref metrics) => metrics,
+            None => {
+                println!("gRPC is not enabled");
+                return;
+            },
+        };
+        let stats = metrics.method_stats();
+        if stats.is_empty() {
+            println!("No gRPC calls have been made");
+            return;
+        }
+        let mut table = Table::new();
+        table.set_titles(vec!["Method", "Calls", "Rate limited", "Slow", "Avg", "p95", "Max"]);
+        for (method, stats) in stats {
+            table.add_row(row![
+                method,
+                stats.count,
+                stats.rate_limited,
+                stats.slow,
+                format!("{:.1?}", stats.average_latency()),
+                stats
+                    .latency_percentile(95.0)
+                    .map(|latency| format!("{:.1?}", latency))
+                    .unwrap_or_else(|| "-".to_string()),
+                format!("{:.1?}", stats.max_latency)
+            ]);
+        }
+        table.print_stdout();
+        let limits = metrics.limits();
+        if let Some(threshold) = limits.slow_request_threshold {
+            println!("Calls slower than {:.1?} are logged", threshold);
+        }
+        if let Some(limit) = limits.rate_limit {
+            println!(
+                "Each client may make {} call(s) per second to each method, with bursts of up to {}",
+                limit.requests_per_sec, limit.burst
+            );
+        }
+    }
+
+    /// Prints the share of the recent blocks of each proof of work algorithm mined by each miner signature
+    pub fn mining_distribution(&self) {
+        if !self.mining_distribution.is_enabled() {
+            println!("The mining distribution monitor is disabled, set `mining_distribution_window` to enable it");
+            return;
+        }
+        let status = self.mining_distribution.status();
+        if status.num_blocks == 0 {
+            println!("No blocks have been analysed yet");
+            return;
+        }
+        println!(
+            "Miners of the last {} block(s) up to #{}",
+            status.num_blocks, status.height
+        );
+        let mut table = Table::new();
+        table.set_titles(vec!["Algorithm", "Blocks", "Miner signature", "Miner blocks", "Share"]);
+        for (name, distribution) in &[("RandomX", &status.monero), ("SHA3", &status.sha3)] {
+            add_distribution_rows(&mut table, name, distribution);
+        }
+        table.print_stdout();
+
+        if self.config.alert_mining_share_percent > 0 {
+            println!(
+                "Miners with more than {}% of the blocks of their algorithm are reported",
+                self.config.alert_mining_share_percent
+            );
+        }
+        if !status.recent_alerts.is_empty() {
+            println!();
+            println!("Recent reports:");
+            for (at, concentration) in &status.recent_alerts {
+                println!("{}: {}", at.format("%Y-%m-%d %H:%M:%S"), concentration);
+            }
+        }
+    }
+
+    /// Discovers a peer through the DHT and dials it, reporting the time taken by each stage. The timings are added
+    /// to the discovery statistics in the data directory.
     pub fn discover_peer(&self, dest_pubkey: Box<RistrettoPublicKey>) {
         let mut dht = self.discovery_service.clone();
+        let connectivity = self.connectivity.clone();
+        let discovery_stats = self.discovery_stats.clone();
+        let commands = self.commands.clone();
 
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let start = Instant::now();
             println!("🌎 Peer discovery started.");
+            let mut report = DiscoveryReport::default();
 
             match dht
-                .discover_peer(dest_pubkey.clone(), NodeDestination::PublicKey(dest_pubkey))
+                .discover_peer_with_timings(dest_pubkey.clone(), NodeDestination::PublicKey(dest_pubkey))
                 .await
             {
-                Ok(p) => {
+                Ok((peer, timings)) => {
+                    report.add_stage(DiscoveryStage::DiscoverySent, timings.sent);
+                    report.add_stage(DiscoveryStage::Response, timings.response);
                     println!("⚡️ Discovery succeeded in {}ms!", start.elapsed().as_millis());
                     println!("This peer was found:");
-                    println!("{}", p);
+                    println!("{}", peer);
+
+                    println!("☎️  Dialing peer...");
+                    let dial_start = Instant::now();
+                    match connectivity.dial_peer(peer.node_id.clone()).await {
+                        // A connection that is older than the dial already existed, so nothing was dialed
+                        Ok(conn) if conn.age() > dial_start.elapsed() => {
+                            println!("The peer was already connected, the dial was skipped");
+                        },
+                        Ok(conn) => {
+                            let elapsed = dial_start.elapsed();
+                            let identity_exchange = conn.identity_exchange_time().unwrap_or_default();
+                            report.add_stage(DiscoveryStage::Dial, elapsed.saturating_sub(identity_exchange));
+                            report.add_stage(DiscoveryStage::IdentityExchange, identity_exchange);
+                            println!("⚡️ Peer connected in {}ms!", elapsed.as_millis());
+                        },
+                        Err(err) => {
+                            println!("📞  Dial failed: {}", err);
+                            report.fail(DiscoveryStage::Dial);
+                        },
+                    }
                 },
                 Err(err) => {
                     println!("💀 Discovery failed: '{:?}'", err);
+                    report.fail(DiscoveryStage::Response);
                 },
             }
+
+            if !report.stages.is_empty() {
+                let mut table = Table::new();
+                table.set_titles(vec!["Stage", "Time"]);
+                for (stage, duration) in &report.stages {
+                    table.add_row(row![stage, format!("{}ms", duration.as_millis())]);
+                }
+                table.add_row(row!["Total", format!("{}ms", report.total().as_millis())]);
+                table.print_stdout();
+            }
+            if let Err(err) = discovery_stats.lock().await.record(&report) {
+                warn!(target: LOG_TARGET, "Could not save the discovery stats: {}", err);
+            }
+            if report.failed_stage.is_some() {
+                commands.report_failure();
+            }
+        });
+    }
+
+    /// Prints the aggregate timings of the discoveries run by `discover-peer`
+    pub fn discovery_stats(&self) {
+        let discovery_stats = self.discovery_stats.clone();
+        self.spawn(async move {
+            let store = discovery_stats.lock().await;
+            let stats = store.stats();
+            if stats.attempts == 0 {
+                println!("No discoveries have been run with `discover-peer` yet");
+                return;
+            }
+            println!(
+                "{} discoveries, {} succeeded ({:.1}%), saved to {}",
+                stats.attempts,
+                stats.succeeded,
+                percentage(stats.succeeded as usize, stats.attempts as usize),
+                store.file().display()
+            );
+            let mut table = Table::new();
+            table.set_titles(vec!["Stage", "Runs", "Failures", "Min", "Mean", "Max"]);
+            for (stage, stage_stats) in &stats.stages {
+                table.add_row(row![
+                    stage,
+                    stage_stats.count,
+                    stats.failures.get(stage).copied().unwrap_or_default(),
+                    format!("{}ms", stage_stats.min_ms),
+                    format!("{}ms", stage_stats.mean_ms().unwrap_or_default()),
+                    format!("{}ms", stage_stats.max_ms),
+                ]);
+            }
+            table.print_stdout();
         });
     }
 
     pub fn get_peer(&self, partial: Vec<u8>, original_str: String) {
         let peer_manager = self.peer_manager.clone();
 
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match peer_manager.find_all_starts_with(&partial).await {
                 Ok(peers) if peers.is_empty() => {
                     println!("No peer matching '{}'", original_str);
@@ -426,6 +1289,7 @@ impl CommandHandler {
                     });
                     println!("User agent: {}", peer.user_agent);
                     println!("Features: {:?}", peer.features);
+                    println!("Capabilities: {}", peer.capabilities);
                     println!("Supported protocols:");
                     peer.supported_protocols.iter().for_each(|p| {
                         println!("- {}", String::from_utf8_lossy(p));
@@ -446,7 +1310,7 @@ impl CommandHandler {
 
     pub fn list_peers(&self, filter: Option<String>) {
         let peer_manager = self.peer_manager.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let mut query = PeerQuery::new();
             if let Some(f) = filter {
                 let filter = f.to_lowercase();
@@ -463,7 +1327,15 @@ impl CommandHandler {
                     let num_peers = peers.len();
                     println!();
                     let mut table = Table::new();
-                    table.set_titles(vec!["NodeId", "Public Key", "Flags", "Role", "User Agent", "Info"]);
+                    table.set_titles(vec![
+                        "NodeId",
+                        "Public Key",
+                        "Flags",
+                        "Role",
+                        "User Agent",
+                        "Capabilities",
+                        "Info",
+                    ]);
 
                     for peer in peers {
                         let info_str = {
@@ -525,6 +1397,7 @@ impl CommandHandler {
                             Some(peer.user_agent)
                                 .map(|ua| if ua.is_empty() { "<unknown>".to_string() } else { ua })
                                 .unwrap(),
+                            peer.capabilities,
                             info_str,
                         ]);
                     }
@@ -543,7 +1416,7 @@ impl CommandHandler {
     pub fn dial_peer(&self, dest_node_id: NodeId) {
         let connectivity = self.connectivity.clone();
 
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let start = Instant::now();
             println!("☎️  Dialing peer...");
 
@@ -559,40 +1432,71 @@ impl CommandHandler {
         });
     }
 
-    pub fn ping_peer(&self, dest_node_id: NodeId) {
-        let mut liveness = self.liveness.clone();
+    /// Pings a peer `count` times, one ping at a time, and prints the round trip time of each
+    pub fn ping_peer(&self, dest_node_id: NodeId, count: usize) {
+        const PING_TIMEOUT: Duration = Duration::from_secs(30);
+        const PING_INTERVAL: Duration = Duration::from_secs(1);
 
-        self.executor.spawn(time::timeout(Duration::from_secs(30), async move {
+        let mut liveness = self.liveness.clone();
+        self.spawn(async move {
             println!("🏓 Pinging peer...");
             let mut liveness_events = liveness.get_event_stream();
+            let mut round_trips = Vec::with_capacity(count);
+            for i in 0..count {
+                if i > 0 {
+                    time::sleep(PING_INTERVAL).await;
+                }
+                if let Err(err) = liveness.send_ping(dest_node_id.clone()).await {
+                    println!("📞  Could not send ping: {}", err);
+                    return;
+                }
+                match time::timeout(PING_TIMEOUT, wait_for_pong(&mut liveness_events, &dest_node_id)).await {
+                    Ok(Some(latency)) => {
+                        println!("🏓️ Pong received, latency in is {}ms!", latency);
+                        round_trips.push(latency);
+                    },
+                    Ok(None) => return,
+                    Err(_) => println!("No pong received within {}s", PING_TIMEOUT.as_secs()),
+                }
+            }
+            if count > 1 {
+                println!(
+                    "{} pings sent, {} pongs received, {:.0}% lost",
+                    count,
+                    round_trips.len(),
+                    100.0 - percentage(round_trips.len(), count)
+                );
+                if let (Some(min), Some(max)) = (round_trips.iter().min(), round_trips.iter().max()) {
+                    let mean = round_trips.iter().sum::<u32>() / round_trips.len() as u32;
+                    println!("Round trip min/avg/max = {}/{}/{}ms", min, mean, max);
+                }
+            }
+        });
+    }
 
-            match liveness.send_ping(dest_node_id.clone()).await {
-                Ok(_) => loop {
-                    match liveness_events.recv().await {
-                        Ok(event) =>
-                        {
-                            #[allow(clippy::single_match)]
-                            match &*event {
-                                LivenessEvent::ReceivedPong(pong) => {
-                                    if pong.node_id == dest_node_id {
-                                        println!("🏓️ Pong received, latency in is {}ms!", pong.latency.unwrap_or(0));
-                                        break;
-                                    }
-                                },
-                                _ => {},
-                            }
-                        },
-                        Err(broadcast::error::RecvError::Closed) => {
-                            break;
-                        },
-                        _ => {},
-                    }
-                },
+    /// Prints the round trip times and path quality of the peers that were pinged, or the round trip time histogram
+    /// of one peer
+    pub fn ping_stats(&self, node_id: Option<NodeId>) {
+        let mut liveness = self.liveness.clone();
+        let commands = self.commands.clone();
+        self.spawn(async move {
+            let stats = match liveness.get_peer_ping_stats().await {
+                Ok(stats) => stats,
                 Err(err) => {
-                    println!("📞  Could not send ping: {}", err);
+                    println!("Failed to get the ping stats: {}", err);
+                    error!(target: LOG_TARGET, "Failed to get the ping stats: {}", err);
+                    commands.report_failure();
+                    return;
+                },
+            };
+            match node_id {
+                Some(node_id) => match stats.get(&node_id) {
+                    Some(stats) => print_rtt_histogram(&node_id, stats),
+                    None => println!("Peer {} has not been pinged", node_id),
                 },
+                None => print_ping_stats(stats),
             }
-        }));
+        });
     }
 
     pub fn ban_peer(&self, node_id: NodeId, duration: Duration, must_ban: bool) {
@@ -604,7 +1508,7 @@ impl CommandHandler {
         let mut connectivity = self.connectivity.clone();
         let peer_manager = self.peer_manager.clone();
 
-        self.executor.spawn(async move {
+        self.spawn(async move {
             if must_ban {
                 match connectivity
                     .ban_peer_until(node_id.clone(), duration, "UI manual ban".to_string())
@@ -635,7 +1539,7 @@ impl CommandHandler {
 
     pub fn unban_all_peers(&self) {
         let peer_manager = self.peer_manager.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             async fn unban_all(pm: &PeerManager) -> usize {
                 let query = PeerQuery::new().select_where(|p| p.is_banned());
                 match pm.perform_query(query).await {
@@ -662,7 +1566,7 @@ impl CommandHandler {
 
     pub fn list_banned_peers(&self) {
         let peer_manager = self.peer_manager.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match fetch_banned_peers(&peer_manager).await {
                 Ok(banned) => {
                     if banned.is_empty() {
@@ -679,12 +1583,146 @@ impl CommandHandler {
         });
     }
 
+    /// Allows connections to and from the peers matching the rule. Once any rule is allowed, connected peers that
+    /// match no allowed rule are disconnected.
+    pub fn allow_address(&self, rule: AccessRule) {
+        self.access_list.allow(rule.clone());
+        println!("Connections with {} are allowed", rule);
+        println!(
+            "Only peers matching the {} allowed rule(s) may connect",
+            self.access_list.allowed().len()
+        );
+        self.disconnect_denied_peers();
+    }
+
+    /// Blocks connections to and from the peers matching the rule, and disconnects the connected peers that match it
+    pub fn block_address(&self, rule: AccessRule) {
+        self.access_list.block(rule.clone());
+        println!("Connections with {} are blocked", rule);
+        self.disconnect_denied_peers();
+    }
+
+    pub fn remove_access_rule(&self, rule: AccessRule) {
+        if self.access_list.remove(&rule) {
+            println!("Removed {} from the access list", rule);
+        } else {
+            println!("{} is not in the access list", rule);
+        }
+    }
+
+    pub fn list_access_rules(&self) {
+        let allowed = self.access_list.allowed();
+        let blocked = self.access_list.blocked();
+        if allowed.is_empty() {
+            println!("Allowed: all peers that are not blocked");
+        } else {
+            println!("Allowed ({}):", allowed.len());
+            allowed.iter().for_each(|rule| println!("  {}", rule));
+        }
+        println!("Blocked ({}):", blocked.len());
+        blocked.iter().for_each(|rule| println!("  {}", rule));
+    }
+
+    fn disconnect_denied_peers(&self) {
+        let mut connectivity = self.connectivity.clone();
+        let peer_manager = self.peer_manager.clone();
+        let access_list = self.access_list.clone();
+        self.spawn(async move {
+            let conns = match connectivity.get_active_connections().await {
+                Ok(conns) => conns,
+                Err(err) => {
+                    println!("Failed to retrieve the active connections: {:?}", err);
+                    error!(target: LOG_TARGET, "Could not retrieve active connections: {:?}", err);
+                    return;
+                },
+            };
+            let mut num_disconnected = 0;
+            for mut conn in conns {
+                let public_key_permitted = match peer_manager.find_by_node_id(conn.peer_node_id()).await {
+                    Ok(peer) => access_list.permits_public_key(&peer.public_key),
+                    Err(_) => true,
+                };
+                if public_key_permitted && access_list.permits_address(conn.address()) {
+                    continue;
+                }
+                match conn.disconnect().await {
+                    Ok(_) => num_disconnected += 1,
+                    Err(err) => {
+                        error!(
+                            target: LOG_TARGET,
+                            "Could not disconnect peer '{}': {:?}",
+                            conn.peer_node_id(),
+                            err
+                        );
+                    },
+                }
+            }
+            if num_disconnected > 0 {
+                println!("Disconnected {} peer(s) that are no longer permitted", num_disconnected);
+            }
+        });
+    }
+
+    /// Function to process the rpc-sessions command
+    pub fn list_rpc_sessions(&self) {
+        let mut rpc_server = self.rpc_server.clone();
+
+        self.spawn(async move {
+            match rpc_server.get_active_sessions().await {
+                Ok(sessions) if sessions.is_empty() => {
+                    println!("No active RPC sessions.");
+                },
+                Ok(sessions) => {
+                    println!();
+                    let num_sessions = sessions.len();
+                    let total_throughput = sessions.iter().map(|s| s.throughput()).sum::<f64>();
+                    let mut table = Table::new();
+                    table.set_titles(vec![
+                        "Session",
+                        "NodeId",
+                        "Protocol",
+                        "Age",
+                        "Requests",
+                        "Sent (KiB)",
+                        "Throughput (KiB/s)",
+                        "Compression",
+                    ]);
+                    for session in sessions {
+                        table.add_row(row![
+                            session.session_id,
+                            session.node_id,
+                            session.protocol,
+                            format_duration_basic(session.age),
+                            session.num_requests,
+                            format!("{:.2}", session.bytes_sent as f64 / 1024.0),
+                            format!("{:.2}", session.throughput() / 1024.0),
+                            session.compression,
+                        ]);
+                    }
+
+                    table.print_stdout();
+
+                    println!();
+                    println!(
+                        "{} active RPC session(s), {:.2} KiB/s total",
+                        num_sessions,
+                        total_throughput / 1024.0
+                    );
+                },
+                Err(err) => {
+                    println!("Failed to list RPC sessions: {}", err);
+                    error!(target: LOG_TARGET, "Could not list RPC sessions: {}", err);
+                },
+            }
+        });
+    }
+
     /// Function to process the list-connections command
     pub fn list_connections(&self) {
         let mut connectivity = self.connectivity.clone();
         let peer_manager = self.peer_manager.clone();
 
-        self.executor.spawn(async move {
+        self.spawn(async move {
             match connectivity.get_active_connections().await {
                 Ok(conns) if conns.is_empty() => {
                     println!("No active peer connections.");
@@ -741,10 +1779,74 @@ impl CommandHandler {
                     table.print_stdout();
 
                     println!("{} active connection(s)", num_connections);
+                    if let Ok(churn) = connectivity.get_connection_churn().await {
+                        println!("Connection churn: {}", churn);
+                    }
+                },
+                Err(err) => {
+                    println!("Failed to list connections: {:?}", err);
+                    error!(target: LOG_TARGET, "Could not list connections: {:?}", err);
+                },
+            }
+        });
+    }
+
+    /// Deletes the peers matching the criteria from the peer database, other than the connected peers
+    pub fn purge_peers(&self, criteria: PurgeCriteria) {
+        let peer_manager = self.peer_manager.clone();
+        let mut connectivity = self.connectivity.clone();
+        self.spawn(async move {
+            let connected = match connectivity.get_active_connections().await {
+                Ok(conns) => conns
+                    .iter()
+                    .map(|conn| conn.peer_node_id().clone())
+                    .collect::<HashSet<_>>(),
+                Err(err) => {
+                    println!("Failed to retrieve the connected peers: {:?}", err);
+                    return;
+                },
+            };
+            match peer_db::purge_peers(&peer_manager, criteria, &connected).await {
+                Ok(stats) => println!(
+                    "{} peer(s) were purged, {} peer(s) remain.",
+                    stats.purged, stats.remaining
+                ),
+                Err(err) => {
+                    println!("Failed to purge peers: {}", err);
+                    error!(target: LOG_TARGET, "{}", err);
+                },
+            }
+        });
+    }
+
+    /// Writes the peer database to a JSON file
+    pub fn export_peers(&self, path: Option<PathBuf>) {
+        let path = path.unwrap_or_else(|| self.config.data_dir.join("peers.json"));
+        let peer_manager = self.peer_manager.clone();
+        self.spawn(async move {
+            match peer_db::export_peers(&peer_manager, &path).await {
+                Ok(num_peers) => println!("{} peer(s) were exported to {}", num_peers, path.display()),
+                Err(err) => {
+                    println!("Failed to export peers: {}", err);
+                    error!(target: LOG_TARGET, "{}", err);
                 },
+            }
+        });
+    }
+
+    /// Adds the peers in a JSON file to the peer database
+    pub fn import_peers(&self, path: PathBuf) {
+        let peer_manager = self.peer_manager.clone();
+        let own_node_id = self.base_node_identity.node_id().clone();
+        self.spawn(async move {
+            match peer_db::import_peers(&peer_manager, &own_node_id, &path).await {
+                Ok(stats) => println!(
+                    "{} peer(s) were added, {} known peer(s) were updated and {} were skipped.",
+                    stats.added, stats.updated, stats.skipped
+                ),
                 Err(err) => {
-                    println!("Failed to list connections: {:?}", err);
-                    error!(target: LOG_TARGET, "Could not list connections: {:?}", err);
+                    println!("Failed to import peers from {}: {}", path.display(), err);
+                    error!(target: LOG_TARGET, "{}", err);
                 },
             }
         });
@@ -752,7 +1854,7 @@ impl CommandHandler {
 
     pub fn reset_offline_peers(&self) {
         let peer_manager = self.peer_manager.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let result = peer_manager
                 .update_each(|mut peer| {
                     if peer.is_offline() {
@@ -778,7 +1880,7 @@ impl CommandHandler {
 
     pub fn list_headers(&self, start: u64, end: Option<u64>) {
         let blockchain_db = self.blockchain_db.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let headers = match Self::get_chain_headers(&blockchain_db, start, end).await {
                 Ok(h) if h.is_empty() => {
                     println!("No headers found");
@@ -823,7 +1925,7 @@ impl CommandHandler {
 
     pub fn block_timing(&self, start: u64, end: Option<u64>) {
         let blockchain_db = self.blockchain_db.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let headers = match Self::get_chain_headers(&blockchain_db, start, end).await {
                 Ok(h) if h.is_empty() => {
                     println!("No headers found");
@@ -852,7 +1954,8 @@ impl CommandHandler {
     /// Function to process the check-db command
     pub fn check_db(&self) {
         let mut node = self.node_service.clone();
-        self.executor.spawn(async move {
+        let commands = self.commands.clone();
+        self.spawn(async move {
             let meta = node.get_metadata().await.expect("Could not retrieve chain meta");
 
             let mut height = meta.height_of_longest_chain();
@@ -887,6 +1990,9 @@ impl CommandHandler {
                 print!("\x1B[{}D\x1B[K", (height + 1).to_string().chars().count());
             }
             println!("Complete");
+            if !missing_blocks.is_empty() || !missing_headers.is_empty() {
+                commands.report_failure();
+            }
             for missing_block in missing_blocks {
                 println!("Missing block at height: {}", missing_block);
             }
@@ -896,70 +2002,305 @@ impl CommandHandler {
         });
     }
 
+    pub fn verify_utxo_root(&self) {
+        let db = self.blockchain_db.clone();
+        let commands = self.commands.clone();
+        self.spawn(async move {
+            println!("Verifying the MMR roots. This may take a while...");
+            let start = Instant::now();
+            let result = mmr_verifier::verify_mmr_roots(&db, |height, tip_height| {
+                print!("\rVerified {}/{} blocks", height, tip_height);
+                let _ = io::stdout().flush();
+            })
+            .await;
+            println!();
+            match result {
+                Ok(report) => {
+                    println!(
+                        "Checked {} kernels and {} outputs ({} spent) up to #{} {} in {}",
+                        report.num_kernels,
+                        report.num_outputs,
+                        report.num_spent,
+                        report.tip_height,
+                        report.tip_hash.to_hex(),
+                        format_duration_basic(start.elapsed())
+                    );
+                    match report.divergence {
+                        Some(divergence) => {
+                            warn!(target: LOG_TARGET, "MMR root verification failed: {}", divergence);
+                            println!("FAILED: {}", divergence);
+                            commands.report_failure();
+                        },
+                        None => println!("OK: The MMR roots match the headers"),
+                    }
+                },
+                Err(err) => {
+                    println!("Failed to verify the MMR roots: {}", err);
+                    error!(target: LOG_TARGET, "Failed to verify the MMR roots: {}", err);
+                    commands.report_failure();
+                },
+            }
+        });
+    }
+
+    /// Re-runs the validation of the main chain blocks in the range, `parallelism` blocks at a time, and prints the
+    /// time taken by each validation stage
+    pub fn revalidate_blocks(&self, start: u64, end: u64, parallelism: usize) {
+        let db = self.blockchain_db.clone();
+        let network = self.config.network;
+        let max_randomx_vms = self.config.max_randomx_vms;
+        let commands = self.commands.clone();
+        self.spawn(async move {
+            let tip_height = try_or_print!(db.get_chain_metadata().await).height_of_longest_chain();
+            let end = cmp::min(end, tip_height);
+            if start > end {
+                println!(
+                    "The start height must not be greater than the end height or the tip (#{})",
+                    tip_height
+                );
+                return;
+            }
+            let revalidator = Arc::new(BlockRevalidator::new(
+                db.into_inner(),
+                ConsensusManager::builder(network).build(),
+                RandomXFactory::new(max_randomx_vms),
+            ));
+
+            println!(
+                "Revalidating blocks #{} to #{}, {} at a time. This may take a while...",
+                start, end, parallelism
+            );
+            let timer = Instant::now();
+            let mut revalidations = stream::iter(start..=end)
+                .map(|height| {
+                    let revalidator = revalidator.clone();
+                    task::spawn_blocking(move || revalidator.revalidate(height))
+                })
+                .buffered(parallelism);
+            // The total and maximum time and the number of blocks for each stage
+            let mut stage_stats = HashMap::<RevalidationStage, (Duration, Duration, u64)>::new();
+            let mut num_failed = 0;
+            while let Some(result) = revalidations.next().await {
+                let revalidation = match result {
+                    Ok(Ok(revalidation)) => revalidation,
+                    Ok(Err(err)) => {
+                        println!();
+                        println!("Could not revalidate the blocks: {}", err);
+                        commands.report_failure();
+                        return;
+                    },
+                    Err(err) => {
+                        println!();
+                        println!("Revalidation task failed: {}", err);
+                        commands.report_failure();
+                        return;
+                    },
+                };
+                for (stage, elapsed) in &revalidation.timings {
+                    let (total, max, count) = stage_stats.entry(*stage).or_default();
+                    *total += *elapsed;
+                    *max = cmp::max(*max, *elapsed);
+                    *count += 1;
+                }
+                if let Some((stage, err)) = &revalidation.failure {
+                    num_failed += 1;
+                    println!(
+                        "\rFAILED: Block #{} ({}) failed at stage '{}': {}",
+                        revalidation.height,
+                        revalidation.hash.to_hex(),
+                        stage,
+                        err
+                    );
+                }
+                print!("\rRevalidated {}/{} blocks", revalidation.height, end);
+                let _ = io::stdout().flush();
+            }
+            println!();
+
+            let mut table = Table::new();
+            table.set_titles(vec!["Stage", "Blocks", "Total", "Mean", "Max"]);
+            for stage in RevalidationStage::ALL.iter() {
+                if let Some((total, max, count)) = stage_stats.get(stage) {
+                    table.add_row(row![
+                        stage,
+                        count,
+                        format!("{:.2?}", total),
+                        format!("{:.2?}", *total / *count as u32),
+                        format!("{:.2?}", max)
+                    ]);
+                }
+            }
+            table.print_stdout();
+
+            let num_blocks = end - start + 1;
+            println!(
+                "Revalidated {} block(s) in {}, {} failed",
+                num_blocks,
+                format_duration_basic(timer.elapsed()),
+                num_failed
+            );
+            if num_failed > 0 {
+                commands.report_failure();
+            }
+        });
+    }
+
+    /// Prints statistics about the UTXO set. The outputs found are cached, so that later runs only scan the blocks
+    /// added since.
+    pub fn utxo_stats(&self, rescan: bool) {
+        let db = self.blockchain_db.clone();
+        let cache = self.utxo_stats.clone();
+        let commands = self.commands.clone();
+        self.spawn(async move {
+            let mut cache = cache.lock().await;
+            if rescan {
+                cache.clear();
+            }
+            let start = Instant::now();
+            let result = cache
+                .update(&db, |height, tip_height| {
+                    print!("\r\x1B[KScanned blocks up to {}/{}", height, tip_height);
+                    let _ = io::stdout().flush();
+                })
+                .await;
+            print!("\r\x1B[K");
+            let num_blocks = match result {
+                Ok(num_blocks) => num_blocks,
+                Err(err) => {
+                    println!("Failed to scan the UTXO set: {}", err);
+                    error!(target: LOG_TARGET, "Failed to scan the UTXO set: {}", err);
+                    commands.report_failure();
+                    return;
+                },
+            };
+            let stats = cache.stats();
+            println!(
+                "{} unspent output(s) at height {}, {} block(s) scanned in {}",
+                stats.total.count,
+                stats.tip_height,
+                num_blocks,
+                format_duration_basic(start.elapsed())
+            );
+            println!();
+
+            let mut table = Table::new();
+            table.set_titles(vec!["Type", "Count", "Share", "Avg size (bytes)", "Avg age (blocks)"]);
+            for kind in OutputKind::iter() {
+                let kind_stats = stats.by_kind.get(&kind).copied().unwrap_or_default();
+                table.add_row(row![
+                    kind,
+                    kind_stats.count,
+                    format!("{:.1}%", percentage(kind_stats.count, stats.total.count)),
+                    format!("{:.0}", kind_stats.average_size()),
+                    format!("{:.0}", kind_stats.average_age())
+                ]);
+            }
+            table.print_stdout();
+            println!();
+
+            let mut table = Table::new();
+            table.set_titles(vec!["Spendable", "Count", "Share"]);
+            for (bound, count) in &stats.maturity {
+                let label = match bound {
+                    Some(0) => "Now".to_string(),
+                    Some(bound) => format!("Within {} blocks", bound),
+                    None => "Later".to_string(),
+                };
+                table.add_row(row![
+                    label,
+                    count,
+                    format!("{:.1}%", percentage(*count, stats.total.count))
+                ]);
+            }
+            table.print_stdout();
+            println!();
+
+            let max_count = stats.sizes.values().copied().max().unwrap_or_default();
+            let mut table = Table::new();
+            table.set_titles(vec!["Size (bytes)", "Count", ""]);
+            for (bucket, count) in &stats.sizes {
+                table.add_row(row![
+                    format!("{}-{}", bucket, bucket + SIZE_BUCKET_WIDTH - 1),
+                    count,
+                    "#".repeat((count * 40 + max_count - 1) / cmp::max(max_count, 1))
+                ]);
+            }
+            table.print_stdout();
+        });
+    }
+
+    /// Prints the tx count, hash rate estimation, target difficulty, solve time and block count per period, from the
+    /// tip down, or writes them to the file if one is given. Blocks are fetched in batches and each row is printed as
+    /// soon as its period is complete. A file is flushed after each batch.
     #[allow(deprecated)]
-    pub fn period_stats(&self, period_end: u64, mut period_ticker_end: u64, period: u64) {
+    pub fn period_stats(&self, period_end: u64, mut period_ticker_end: u64, period: u64, filename: Option<String>) {
         let mut node = self.node_service.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let meta = node.get_metadata().await.expect("Could not retrieve chain meta");
-
-            let mut height = meta.height_of_longest_chain();
-            // Currently gets the stats for: tx count, hash rate estimation, target difficulty, solvetime.
-            let mut results: Vec<(usize, f64, u64, u64, usize)> = Vec::new();
+            let mut output = match filename {
+                Some(filename) => {
+                    println!("Writing the period stats to [working-dir]/{}", filename);
+                    Some(try_or_print!(StatsWriter::create(&filename, PERIOD_STATS_COLUMNS)))
+                },
+                None => None,
+            };
 
             let mut period_ticker_start = period_ticker_end - period;
             let mut period_tx_count = 0;
-            let mut period_block_count = 0;
+            let mut period_block_count = 0u64;
             let mut period_hash = 0.0;
             let mut period_difficulty = 0;
             let mut period_solvetime = 0;
-            print!("Searching for height: ");
-            while height > 0 {
-                print!("{}", height);
-                io::stdout().flush().unwrap();
+            if output.is_none() {
+                println!("Results of tx count, hash rate estimation, target difficulty, solvetime, block count");
+            }
 
-                let block = match node.get_blocks(vec![height]).await {
-                    Err(_err) => {
-                        println!("Error in db, could not get block");
-                        break;
-                    },
-                    Ok(mut data) => match data.pop() {
-                        // We need to check the data it self, as FetchMatchingBlocks will suppress any error, only
-                        // logging it.
-                        Some(historical_block) => historical_block,
-                        None => {
-                            println!("Error in db, could not get block");
-                            break;
-                        },
-                    },
-                };
-                let prev_block = match node.get_blocks(vec![height - 1]).await {
-                    Err(_err) => {
-                        println!("Error in db, could not get block");
-                        break;
-                    },
-                    Ok(mut data) => match data.pop() {
-                        // We need to check the data it self, as FetchMatchingBlocks will suppress any error, only
-                        // logging it.
-                        Some(historical_block) => historical_block,
-                        None => {
-                            println!("Error in db, could not get block");
-                            break;
-                        },
-                    },
+            let mut blocks = VecDeque::new();
+            let mut next_height = Some(meta.height_of_longest_chain());
+            loop {
+                if blocks.len() < 2 {
+                    if let Some(output) = output.as_mut() {
+                        try_or_print!(output.flush());
+                    }
+                    if let Some(from_height) = next_height {
+                        match fetch_blocks_descending(&mut node, from_height, STATS_BATCH_SIZE).await {
+                            Ok(batch) => {
+                                next_height = batch.last().and_then(|b| b.header().height.checked_sub(1));
+                                blocks.extend(batch);
+                            },
+                            Err(err) => {
+                                println!("\r\x1B[KError in db, could not get blocks: {}", err);
+                                break;
+                            },
+                        }
+                    }
+                }
+                // The genesis block has no previous block to calculate the solve time with
+                let block = match blocks.pop_front() {
+                    Some(block) if !blocks.is_empty() => block,
+                    _ => break,
                 };
-                height -= 1;
+                let prev_block = &blocks[0];
+                print!("\r\x1B[KSearching for height: {}", block.header().height);
+                io::stdout().flush().unwrap();
+
                 if block.header().timestamp.as_u64() > period_ticker_end {
-                    print!("\x1B[{}D\x1B[K", (height + 1).to_string().chars().count());
                     continue;
                 };
                 while block.header().timestamp.as_u64() < period_ticker_start {
-                    results.push((
-                        period_tx_count,
-                        period_hash,
-                        period_difficulty,
-                        period_solvetime,
-                        period_block_count,
-                    ));
+                    match output.as_mut() {
+                        Some(output) => try_or_print!(output.write_row(vec![
+                            period_tx_count.into(),
+                            period_hash.into(),
+                            period_difficulty.into(),
+                            period_solvetime.into(),
+                            period_block_count.into(),
+                        ])),
+                        None => println!(
+                            "\r\x1B[K{},{},{},{},{}",
+                            period_tx_count, period_hash, period_difficulty, period_solvetime, period_block_count
+                        ),
+                    }
                     period_tx_count = 0;
                     period_block_count = 0;
                     period_hash = 0.0;
@@ -982,16 +2323,63 @@ impl CommandHandler {
                 if period_ticker_end <= period_end {
                     break;
                 }
-                print!("\x1B[{}D\x1B[K", (height + 1).to_string().chars().count());
             }
-            println!("Complete");
-            println!("Results of tx count, hash rate estimation, target difficulty, solvetime, block count");
-            for data in results {
-                println!("{},{},{},{},{}", data.0, data.1, data.2, data.3, data.4);
+            if let Some(output) = output {
+                try_or_print!(output.finish());
+            }
+            println!("\r\x1B[KComplete");
+        });
+    }
+
+    /// Compares the weights of the blocks in the range under the given weights to the current consensus weights
+    pub fn weight_audit(&self, start_height: u64, end_height: u64, weights: LinearWeights) {
+        const BATCH_SIZE: u64 = 100;
+
+        let db = self.blockchain_db.clone();
+        let network = self.config.network;
+        self.spawn(async move {
+            println!(
+                "Auditing blocks #{} - #{} with weights {} (consensus weights are {})",
+                start_height,
+                end_height,
+                weights,
+                LinearWeights::consensus()
+            );
+            let consensus_rules = ConsensusManager::builder(network).build();
+            let audit = WeightAudit::new(LinearWeights::consensus(), weights);
+            let report = try_or_print!(
+                audit
+                    .audit_chain(&db, &consensus_rules, start_height..=end_height, BATCH_SIZE)
+                    .await
+            );
+            println!("{}", report);
+        });
+    }
+
+    /// Executes a script against the input stack at the given height and prints the outcome. The execution context
+    /// has no previous block hash or commitment, so scripts that depend on them cannot be reproduced exactly.
+    pub fn debug_script(&self, script: TariScript, input_data: ExecutionStack, height: u64) {
+        let report = ScriptDebugReport::execute(&script, &input_data, height, [0; 32], &Commitment::default());
+        println!("{}", report);
+    }
+
+    /// Runs the mempool validation rules against the transaction without submitting it
+    pub fn validate_tx(&self, tx: Transaction) {
+        let db = self.blockchain_db.clone().into_inner();
+        self.spawn(async move {
+            let excess_sig = tx.first_kernel_excess_sig().cloned();
+            let report = try_or_print!(
+                task::spawn_blocking(move || TxValidationReport::run(db, CryptoFactories::default(), &tx)).await
+            );
+            if let Some(sig) = excess_sig {
+                println!("Transaction with excess signature {}", sig.get_signature().to_hex());
             }
+            println!("{}", report);
         });
     }
 
+    /// Writes the difficulty stats of the headers in the range to a CSV file, or a Parquet file if the name ends in
+    /// `.parquet`. Headers are fetched in batches and each batch is flushed to the file once it is processed.
     pub fn save_header_stats(
         &self,
         start_height: u64,
@@ -1001,8 +2389,8 @@ impl CommandHandler {
     ) {
         let db = self.blockchain_db.clone();
         let network = self.config.network;
-        self.executor.spawn(async move {
-            let mut output = try_or_print!(File::create(&filename));
+        self.spawn(async move {
+            let mut output = try_or_print!(StatsWriter::create(&filename, HEADER_STATS_COLUMNS));
 
             println!(
                 "Loading header from height {} to {} and dumping to file [working-dir]/{}.{}",
@@ -1018,90 +2406,97 @@ impl CommandHandler {
             let mut prev_header = try_or_print!(db.fetch_chain_header(start_height - 1).await);
             let consensus_rules = ConsensusManager::builder(network).build();
 
-            writeln!(
-                output,
-                "Height,Achieved,TargetDifficulty,CalculatedDifficulty,SolveTime,NormalizedSolveTime,Algo,Timestamp,\
-                 Window,Acc.Monero,Acc.Sha3"
-            )
-            .unwrap();
-
-            for height in start_height..=end_height {
-                let header = try_or_print!(db.fetch_chain_header(height).await);
-
-                // Optionally, filter out pow algos
-                if pow_algo.map(|algo| header.header().pow_algo() != algo).unwrap_or(false) {
-                    continue;
+            let mut batch_start = start_height;
+            while batch_start <= end_height {
+                let batch_end = cmp::min(batch_start + STATS_BATCH_SIZE - 1, end_height);
+                let headers = try_or_print!(db.fetch_chain_headers(batch_start..=batch_end).await);
+                if headers.is_empty() {
+                    println!("\nNo headers found from height {}", batch_start);
+                    break;
                 }
+                for header in headers {
+                    let height = header.height();
+                    // Optionally, filter out pow algos
+                    if pow_algo.map(|algo| header.header().pow_algo() != algo).unwrap_or(false) {
+                        prev_header = header;
+                        continue;
+                    }
 
-                let target_diff = try_or_print!(
-                    db.fetch_target_difficulties_for_next_block(prev_header.hash().clone())
-                        .await
-                );
-                let pow_algo = header.header().pow_algo();
-
-                let min = consensus_rules.consensus_constants(height).min_pow_difficulty(pow_algo);
-                let max = consensus_rules.consensus_constants(height).max_pow_difficulty(pow_algo);
-
-                let calculated_target_difficulty = target_diff.get(pow_algo).calculate(min, max);
-                let existing_target_difficulty = header.accumulated_data().target_difficulty;
-                let achieved = header.accumulated_data().achieved_difficulty;
-                let solve_time =
-                    header.header().timestamp.as_u64() as i64 - prev_header.header().timestamp.as_u64() as i64;
-                let normalized_solve_time = cmp::min(
-                    cmp::max(solve_time, 1) as u64,
-                    consensus_rules
-                        .consensus_constants(height)
-                        .get_difficulty_max_block_interval(pow_algo),
-                );
-                let acc_sha3 = header.accumulated_data().accumulated_sha_difficulty;
-                let acc_monero = header.accumulated_data().accumulated_monero_difficulty;
-
-                writeln!(
-                    output,
-                    "{},{},{},{},{},{},{},{},{},{},{}",
-                    height,
-                    achieved.as_u64(),
-                    existing_target_difficulty.as_u64(),
-                    calculated_target_difficulty.as_u64(),
-                    solve_time,
-                    normalized_solve_time,
-                    pow_algo,
-                    chrono::DateTime::from(header.header().timestamp),
-                    target_diff.get(pow_algo).len(),
-                    acc_monero.as_u64(),
-                    acc_sha3.as_u64(),
-                )
-                .unwrap();
-
-                if header.header().hash() != header.accumulated_data().hash {
-                    eprintln!(
-                        "Difference in hash at {}! header = {} and accum hash = {}",
-                        height,
-                        header.header().hash().to_hex(),
-                        header.accumulated_data().hash.to_hex()
+                    let target_diff = try_or_print!(
+                        db.fetch_target_difficulties_for_next_block(prev_header.hash().clone())
+                            .await
                     );
-                }
-
-                if existing_target_difficulty != calculated_target_difficulty {
-                    eprintln!(
-                        "Difference at {}! existing = {} and calculated = {}",
-                        height, existing_target_difficulty, calculated_target_difficulty
+                    let pow_algo = header.header().pow_algo();
+
+                    let min = consensus_rules.consensus_constants(height).min_pow_difficulty(pow_algo);
+                    let max = consensus_rules.consensus_constants(height).max_pow_difficulty(pow_algo);
+
+                    let calculated_target_difficulty = target_diff.get(pow_algo).calculate(min, max);
+                    let existing_target_difficulty = header.accumulated_data().target_difficulty;
+                    let achieved = header.accumulated_data().achieved_difficulty;
+                    let solve_time =
+                        header.header().timestamp.as_u64() as i64 - prev_header.header().timestamp.as_u64() as i64;
+                    let normalized_solve_time = cmp::min(
+                        cmp::max(solve_time, 1) as u64,
+                        consensus_rules
+                            .consensus_constants(height)
+                            .get_difficulty_max_block_interval(pow_algo),
                     );
-                }
+                    let acc_sha3 = header.accumulated_data().accumulated_sha_difficulty;
+                    let acc_monero = header.accumulated_data().accumulated_monero_difficulty;
+
+                    try_or_print!(output.write_row(vec![
+                        height.into(),
+                        achieved.as_u64().into(),
+                        existing_target_difficulty.as_u64().into(),
+                        calculated_target_difficulty.as_u64().into(),
+                        solve_time.into(),
+                        normalized_solve_time.into(),
+                        pow_algo.to_string().into(),
+                        chrono::DateTime::<Utc>::from(header.header().timestamp)
+                            .to_string()
+                            .into(),
+                        target_diff.get(pow_algo).len().into(),
+                        acc_monero.as_u64().into(),
+                        acc_sha3.as_u64().into(),
+                    ]));
+
+                    if header.header().hash() != header.accumulated_data().hash {
+                        eprintln!(
+                            "\nDifference in hash at {}! header = {} and accum hash = {}",
+                            height,
+                            header.header().hash().to_hex(),
+                            header.accumulated_data().hash.to_hex()
+                        );
+                    }
 
-                print!("{}", height);
+                    if existing_target_difficulty != calculated_target_difficulty {
+                        eprintln!(
+                            "\nDifference at {}! existing = {} and calculated = {}",
+                            height, existing_target_difficulty, calculated_target_difficulty
+                        );
+                    }
+                    prev_header = header;
+                }
+                try_or_print!(output.flush());
+                print!(
+                    "\r\x1B[KProcessed headers up to height {} ({}/{})",
+                    batch_end,
+                    batch_end + 1 - start_height,
+                    end_height + 1 - start_height
+                );
                 try_or_print!(io::stdout().flush());
-                print!("\x1B[{}D\x1B[K", (height + 1).to_string().chars().count());
-                prev_header = header;
+                batch_start = batch_end + 1;
             }
-            println!("Complete");
+            try_or_print!(output.finish());
+            println!("\nComplete");
         });
     }
 
     pub fn rewind_blockchain(&self, new_height: u64) {
         let db = self.blockchain_db.clone();
         let local_node_comms_interface = self.node_service.clone();
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let blocks = try_or_print!(db.rewind_to_height(new_height).await);
             local_node_comms_interface.publish_block_event(BlockEvent::BlockSyncRewind(blocks));
         });
@@ -1112,16 +2507,231 @@ impl CommandHandler {
         println!("{}", self.base_node_identity);
     }
 
+    /// Replaces the node identity, and with it the onion address, and restarts the node. The identity is not swapped in
+    /// the running node: comms, the Tor hidden service and the peer manager hold the identity they were started with,
+    /// so the new identity takes effect when the node restarts. Once it is back online it announces its new identity
+    /// to the network with a DHT join.
+    pub fn rotate_identity(&self) {
+        let result = identity_management::rotate_node_identity(
+            &self.config.base_node_identity_file,
+            Some(&self.config.base_node_tor_identity_file),
+            &self.base_node_identity,
+        )
+        .and_then(|identity| {
+            env::current_exe()
+                .map(|exe| (identity, exe))
+                .map_err(|err| format!("Could not find the node executable to restart. {}", err))
+        });
+        match result {
+            Ok((identity, exe)) => {
+                println!(
+                    "Node identity {} has been replaced by {}, the previous identity files were kept with a .bak \
+                     extension",
+                    self.base_node_identity.node_id(),
+                    identity.node_id()
+                );
+                println!("Restarting to announce the new identity...");
+                let _ = self.restart.send(Some(exe));
+            },
+            Err(err) => {
+                println!("Failed to rotate the node identity: {}", err);
+            },
+        }
+    }
+
+    /// Signs a message with the node identity key and prints the signature
+    pub fn sign_message(&self, message: String) {
+        match MessageSignature::sign(self.base_node_identity.secret_key(), &message) {
+            Ok(signature) => {
+                println!("Public key: {}", self.base_node_identity.public_key());
+                println!("Message: {}", message);
+                println!("Signature: {}", signature);
+            },
+            Err(err) => println!("Failed to sign the message: {}", err),
+        }
+    }
+
+    /// Checks and prints whether the signature over the message was made by the owner of the public key
+    pub fn verify_message(&self, public_key: &CommsPublicKey, signature: &MessageSignature, message: &str) {
+        if signature.verify(public_key, message) {
+            println!("The signature is valid: the message was signed by {}", public_key);
+        } else {
+            println!("The signature is NOT valid for this public key and message");
+        }
+    }
+
     pub(crate) fn get_software_updater(&self) -> SoftwareUpdaterHandle {
         self.software_updater.clone()
     }
 
+    pub fn chain_monitor_status(&self) {
+        if !self.chain_monitor.is_enabled() {
+            println!("The chain monitor is disabled. Set chain_monitor_reference_nodes in the config to enable it.");
+            return;
+        }
+        let status = self.chain_monitor.status();
+        let checked_at = match status.checked_at {
+            Some(checked_at) => checked_at,
+            None => {
+                println!("The chain tips have not been compared yet");
+                return;
+            },
+        };
+        println!(
+            "Checked at {} with local height {}",
+            checked_at.format("%Y-%m-%d %H:%M:%S"),
+            status.local_height
+        );
+        let mut table = Table::new();
+        table.set_titles(vec!["Reference node", "Height", "Chain"]);
+        for report in &status.reports {
+            match &report.result {
+                Ok((height, comparison)) => table.add_row(row![report.reference, height, match comparison {
+                    TipComparison::Agrees => "Agrees",
+                    TipComparison::ShallowFork => "Differs at the tip",
+                    TipComparison::DeepFork => "Forked",
+                }]),
+                Err(err) => table.add_row(row![report.reference, "-", err]),
+            };
+        }
+        table.print_stdout();
+        if status.minority_fork {
+            println!("WARNING: This node appears to be on a minority fork");
+        }
+    }
+
+    pub fn orphan_stats(&self) {
+        let db = self.blockchain_db.clone();
+        self.spawn(async move {
+            match db.fetch_orphan_stats().await {
+                Ok(stats) => {
+                    let mut table = Table::new();
+                    table.set_titles(vec!["Orphan pool", ""]);
+                    table.add_row(row!["Orphans", format!("{}/{}", stats.num_orphans, stats.capacity)]);
+                    table.add_row(row!["Linked to the main chain", stats.num_linked]);
+                    table.add_row(row!["Chain tips", stats.num_chain_tips]);
+                    table.add_row(row![
+                        "Heights",
+                        stats
+                            .height_range
+                            .map(|(min, max)| format!("{} - {}", min, max))
+                            .unwrap_or_else(|| "-".to_string())
+                    ]);
+                    table.add_row(row![
+                        "Best total accumulated difficulty",
+                        stats.best_total_accumulated_difficulty
+                    ]);
+                    table.add_row(row!["Size (KiB)", format!("{:.2}", stats.size_bytes as f64 / 1024.0)]);
+                    table.print_stdout();
+                },
+                Err(err) => {
+                    println!("Failed to fetch orphan stats: {}", err);
+                    error!(target: LOG_TARGET, "Failed to fetch orphan stats: {}", err);
+                },
+            }
+        });
+    }
+
+    pub fn node_stats(&self) {
+        const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+        const IO_SAMPLE_PERIOD: Duration = Duration::from_secs(1);
+
+        fn or_unavailable<T: ToString>(value: Option<T>) -> String {
+            value.map(|v| v.to_string()).unwrap_or_else(|| "n/a".to_string())
+        }
+
+        let db = self.blockchain_db.clone();
+        let time_sync = self.time_sync.clone();
+        self.spawn(async move {
+            let first_sample = ProcessStats::sample();
+            let start = Instant::now();
+            time::sleep(IO_SAMPLE_PERIOD).await;
+            let stats = ProcessStats::sample();
+            let io_rates = match (first_sample.io, stats.io) {
+                (Some(earlier), Some(later)) => Some(later.rates_since(&earlier, start.elapsed())),
+                _ => None,
+            };
+
+            let mut table = Table::new();
+            table.set_titles(vec!["Name", "Value"]);
+            table.add_row(row!["Uptime", or_unavailable(stats.uptime.map(format_duration_basic))]);
+            table.add_row(row![
+                "Resident memory",
+                or_unavailable(
+                    stats
+                        .resident_memory
+                        .map(|bytes| format!("{:.2} MiB", bytes as f64 / BYTES_PER_MB))
+                )
+            ]);
+            table.add_row(row!["Open files", or_unavailable(stats.open_files)]);
+            table.add_row(row!["Threads", or_unavailable(stats.threads)]);
+            table.add_row(row!["Runtime threads", or_unavailable(stats.runtime_threads)]);
+            table.add_row(row!["Busy runtime threads", or_unavailable(stats.runtime_busy_threads)]);
+            table.add_row(row![
+                "Disk read",
+                or_unavailable(io_rates.map(|(read, _)| format!("{:.2} MiB/s", read / BYTES_PER_MB)))
+            ]);
+            table.add_row(row![
+                "Disk write",
+                or_unavailable(io_rates.map(|(_, write)| format!("{:.2} MiB/s", write / BYTES_PER_MB)))
+            ]);
+            if let Some(IoCounters {
+                read_bytes,
+                write_bytes,
+            }) = stats.io
+            {
+                table.add_row(row![
+                    "Total disk read/written",
+                    format!(
+                        "{:.2} MiB / {:.2} MiB",
+                        read_bytes as f64 / BYTES_PER_MB,
+                        write_bytes as f64 / BYTES_PER_MB
+                    )
+                ]);
+            }
+            match db.get_stats().await {
+                Ok(db_stats) => {
+                    let map_size = db_stats.env_info().mapsize;
+                    let used = (db_stats.env_info().last_pgno + 1) * db_stats.root().psize as usize;
+                    table.add_row(row![
+                        "Database map size",
+                        format!("{:.2} MiB", map_size as f64 / BYTES_PER_MB)
+                    ]);
+                    table.add_row(row![
+                        "Database map used",
+                        format!(
+                            "{:.2} MiB ({:.2}%)",
+                            used as f64 / BYTES_PER_MB,
+                            used as f64 / map_size as f64 * 100.0
+                        )
+                    ]);
+                },
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "Error retrieving database stats: {}", err);
+                    table.add_row(row!["Database map", "n/a"]);
+                },
+            }
+            table.add_row(row!["Header cache", db.inner().header_cache_stats()]);
+            if time_sync.is_enabled() {
+                let clock_skew = time_sync.status();
+                let last_checked = clock_skew
+                    .last_checked
+                    .map(|at| format!(", checked at {}", at.format("%H:%M:%S UTC")))
+                    .unwrap_or_default();
+                table.add_row(row!["Clock skew", format!("{}{}", clock_skew, last_checked)]);
+            } else {
+                table.add_row(row!["Clock skew", "n/a"]);
+            }
+            table.print_stdout();
+        });
+    }
+
     pub fn get_blockchain_db_stats(&self) {
         const BYTES_PER_MB: usize = 1024 * 1024;
 
         let db = self.blockchain_db.clone();
 
-        self.executor.spawn(async move {
+        self.spawn(async move {
             let total_db_size = match db.get_stats().await {
                 Ok(stats) => {
                     let mut table = Table::new();
@@ -1208,6 +2818,274 @@ impl CommandHandler {
     }
 }
 
+/// Fetches up to `count` blocks from `from_height` down, ordered by descending height
+async fn fetch_blocks_descending(
+    node: &mut LocalNodeCommsInterface,
+    from_height: u64,
+    count: u64,
+) -> Result<Vec<HistoricalBlock>, String> {
+    let heights = (from_height.saturating_sub(count - 1)..=from_height)
+        .rev()
+        .collect::<Vec<_>>();
+    let num_heights = heights.len();
+    let mut blocks = node.get_blocks(heights).await.map_err(|err| err.to_string())?;
+    // FetchMatchingBlocks suppresses any error, only logging it, so the number of blocks has to be checked
+    if blocks.len() != num_heights {
+        return Err(format!("expected {} blocks, got {}", num_heights, blocks.len()));
+    }
+    blocks.sort_by(|a, b| b.header().height.cmp(&a.header().height));
+    Ok(blocks)
+}
+
+/// Waits for a pong from the peer and returns its round trip time in milliseconds, or None if the liveness service has
+/// stopped
+async fn wait_for_pong(events: &mut LivenessEventReceiver, node_id: &NodeId) -> Option<u32> {
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if let LivenessEvent::ReceivedPong(pong) = &*event {
+                    if pong.node_id == *node_id {
+                        return Some(pong.latency.unwrap_or(0));
+                    }
+                }
+            },
+            Err(broadcast::error::RecvError::Closed) => return None,
+            Err(broadcast::error::RecvError::Lagged(_)) => {},
+        }
+    }
+}
+
+fn print_ping_stats(stats: HashMap<NodeId, PeerPingStats>) {
+    fn or_dash(ms: Option<u32>) -> String {
+        ms.map(|ms| format!("{}ms", ms)).unwrap_or_else(|| "--".to_string())
+    }
+
+    if stats.is_empty() {
+        println!("No peers have been pinged yet");
+        return;
+    }
+    let mut stats = stats.into_iter().collect::<Vec<_>>();
+    // Peers with the best path first, peers that never answered last
+    stats.sort_by(|(_, a), (_, b)| {
+        b.path_quality()
+            .unwrap_or(-1.0)
+            .partial_cmp(&a.path_quality().unwrap_or(-1.0))
+            .unwrap_or(cmp::Ordering::Equal)
+    });
+    let mut table = Table::new();
+    table.set_titles(vec![
+        "NodeId", "Sent", "Answered", "Lost", "Min", "p50", "p90", "Max", "Last", "Quality",
+    ]);
+    for (node_id, stats) in &stats {
+        table.add_row(row![
+            node_id,
+            stats.pings_sent,
+            stats.pongs_received,
+            format!("{:.1}%", stats.loss_rate() * 100.0),
+            or_dash(stats.rtt.min_ms()),
+            or_dash(stats.rtt.percentile_ms(0.5)),
+            or_dash(stats.rtt.percentile_ms(0.9)),
+            or_dash(stats.rtt.max_ms()),
+            or_dash(stats.last_rtt_ms),
+            stats
+                .path_quality()
+                .map(|quality| format!("{:.2}", quality))
+                .unwrap_or_else(|| "--".to_string()),
+        ]);
+    }
+    table.print_stdout();
+    println!(
+        "Percentiles are estimated from the round trip time histogram, run `ping-stats <node id>` to show the \
+         histogram of a peer"
+    );
+}
+
+fn print_rtt_histogram(node_id: &NodeId, stats: &PeerPingStats) {
+    const BAR_WIDTH: u64 = 40;
+
+    println!(
+        "Peer {}: {} pings sent, {} answered, {} lost",
+        node_id, stats.pings_sent, stats.pongs_received, stats.pings_lost
+    );
+    let count = stats.rtt.count();
+    if count == 0 {
+        return;
+    }
+    let mut table = Table::new();
+    table.set_titles(vec!["Round trip", "Pongs", ""]);
+    for (i, bucket) in stats.rtt.buckets().iter().enumerate() {
+        let label = match (
+            i.checked_sub(1).map(|i| RTT_BUCKET_BOUNDS_MS[i]),
+            RTT_BUCKET_BOUNDS_MS.get(i),
+        ) {
+            (None, Some(upper)) => format!("< {}ms", upper),
+            (Some(lower), Some(upper)) => format!("{}-{}ms", lower, upper),
+            (Some(lower), None) => format!(">= {}ms", lower),
+            (None, None) => unreachable!("there is at least one bucket bound"),
+        };
+        let bar = "#".repeat((bucket * BAR_WIDTH / count) as usize);
+        table.add_row(row![label, bucket, bar]);
+    }
+    table.print_stdout();
+    if let (Some(mean), Some(quality)) = (stats.rtt.mean_ms(), stats.path_quality()) {
+        println!("Mean round trip {}ms, path quality {:.2}", mean, quality);
+    }
+}
+
+fn percentage(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    count as f64 * 100.0 / total as f64
+}
+
+/// The number of miner signatures of each algorithm listed by `mining-distribution`
+const MINING_DISTRIBUTION_MAX_MINERS: usize = 5;
+
+fn add_distribution_rows(table: &mut Table, algo: &str, distribution: &AlgoDistribution) {
+    if distribution.miners.is_empty() {
+        table.add_row(row![algo, 0, "-", "-", "-"]);
+        return;
+    }
+    for (i, (signature, count)) in distribution
+        .miners
+        .iter()
+        .take(MINING_DISTRIBUTION_MAX_MINERS)
+        .enumerate()
+    {
+        let (algo, num_blocks) = if i == 0 {
+            (algo.to_string(), distribution.num_blocks.to_string())
+        } else {
+            (String::new(), String::new())
+        };
+        table.add_row(row![
+            algo,
+            num_blocks,
+            signature,
+            count,
+            format!("{:.1}%", percentage(*count, distribution.num_blocks))
+        ]);
+    }
+    let others = &distribution.miners[distribution.miners.len().min(MINING_DISTRIBUTION_MAX_MINERS)..];
+    if !others.is_empty() {
+        let count = others.iter().map(|(_, count)| count).sum::<usize>();
+        table.add_row(row![
+            "",
+            "",
+            format!("{} other(s)", others.len()),
+            count,
+            format!("{:.1}%", percentage(count, distribution.num_blocks))
+        ]);
+    }
+}
+
+/// Finds the unspent output with the given commitment, returning the height and hash of the block it was mined in
+/// and whether it has been spent
+async fn find_utxo(
+    db: &AsyncBlockchainDb<LMDBDatabase>,
+    commitment: Commitment,
+) -> Result<Option<(u64, HashOutput, bool)>, ChainStorageError> {
+    let output_hash = match db.fetch_unspent_output_by_commitment(commitment).await? {
+        Some(hash) => hash,
+        None => return Ok(None),
+    };
+    let mined_height = match db.fetch_output(output_hash.clone()).await? {
+        Some((_, _, mined_height)) => mined_height,
+        None => return Ok(None),
+    };
+    let spent = db
+        .fetch_utxos(vec![output_hash])
+        .await?
+        .pop()
+        .flatten()
+        .map(|(_, spent)| spent)
+        .unwrap_or(false);
+    let header = db.fetch_header(mined_height).await?;
+    Ok(header.map(|header| (mined_height, header.hash(), spent)))
+}
+
+/// Finds the kernel with the given excess signature, returning the height and hash of the block it was mined in
+async fn find_kernel(
+    db: &AsyncBlockchainDb<LMDBDatabase>,
+    excess_sig: Signature,
+) -> Result<Option<(u64, HashOutput)>, ChainStorageError> {
+    let block_hash = match db.fetch_kernel_by_excess_sig(excess_sig).await? {
+        Some((_, block_hash)) => block_hash,
+        None => return Ok(None),
+    };
+    let header = db.fetch_header_by_block_hash(block_hash.clone()).await?;
+    Ok(header.map(|header| (header.height, block_hash)))
+}
+
+/// Prints the header with its proof of work: the algorithm, nonce, target and achieved difficulty and, for RandomX,
+/// the seed
+fn print_chain_header(chain_header: &ChainHeader, format: Format) {
+    let header = chain_header.header();
+    let accumulated_data = chain_header.accumulated_data();
+    let randomx_seed = match header.pow_algo() {
+        PowAlgorithm::Monero => Some(
+            MoneroPowData::from_header(header)
+                .map(|pow_data| to_hex(pow_data.randomx_key()))
+                .unwrap_or_else(|err| format!("invalid Monero PoW data: {}", err)),
+        ),
+        PowAlgorithm::Sha3 => None,
+    };
+    match format {
+        Format::Text => {
+            println!("{}", header);
+            println!("-- Proof of work --");
+            println!("Algorithm: {:?}", header.pow_algo());
+            println!("Nonce: {}", header.nonce);
+            println!("Target difficulty: {}", accumulated_data.target_difficulty);
+            println!("Achieved difficulty: {}", accumulated_data.achieved_difficulty);
+            if let Some(seed) = randomx_seed {
+                println!("RandomX seed: {}", seed);
+            }
+            println!("-- Accumulated data --");
+            println!("{}", accumulated_data);
+        },
+        Format::Json => {
+            let json = serde_json::json!({
+                "hash": accumulated_data.hash.to_hex(),
+                "header": header,
+                "pow": {
+                    "algorithm": header.pow_algo(),
+                    "nonce": header.nonce,
+                    "target_difficulty": accumulated_data.target_difficulty.as_u64(),
+                    "achieved_difficulty": accumulated_data.achieved_difficulty.as_u64(),
+                    "randomx_seed": randomx_seed,
+                },
+                "accumulated_data": accumulated_data,
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).unwrap_or_else(|_| "Error serializing header".into())
+            );
+        },
+    }
+}
+
+/// Parses the configured status line fields, all fields are shown if none are configured
+fn parse_status_line_fields(fields: &[String]) -> Vec<StatusLineField> {
+    let fields = fields
+        .iter()
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty())
+        .filter_map(|field| match field.parse() {
+            Ok(field) => Some(field),
+            Err(_) => {
+                warn!(target: LOG_TARGET, "Ignoring unknown status line field '{}'", field);
+                None
+            },
+        })
+        .collect::<Vec<_>>();
+    if fields.is_empty() {
+        StatusLineField::iter().collect()
+    } else {
+        fields
+    }
+}
+
 async fn fetch_banned_peers(pm: &PeerManager) -> Result<Vec<Peer>, PeerManagerError> {
     let query = PeerQuery::new().select_where(|p| p.is_banned());
     pm.perform_query(query).await
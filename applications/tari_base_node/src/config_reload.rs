@@ -0,0 +1,103 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::builder::BaseNodeContext;
+use config::Config;
+use log::*;
+use tari_common::{
+    configuration::{
+        bootstrap::ApplicationType,
+        reload::{ConfigChanges, ReloadReport},
+    },
+    ConfigBootstrap,
+    ConfigurationError,
+    DefaultConfigLoader,
+    GlobalConfig,
+};
+use tari_comms_dht::Dht;
+use tari_core::mempool::MempoolConfig;
+use tokio::sync::watch;
+
+const LOG_TARGET: &str = "base_node::app::config_reload";
+
+/// Keys, or whole sections, that are applied to the running base node. A `*` segment matches any network.
+const RELOADABLE_KEYS: &[&str] = &[
+    "base_node.*.flood_ban_max_msg_count",
+    "mempool.unconfirmed_pool",
+    "mempool.*.unconfirmed_pool",
+];
+
+/// Loads the mempool configuration from the `[mempool]` section
+pub fn load_mempool_config(cfg: &Config) -> Result<MempoolConfig, ConfigurationError> {
+    <MempoolConfig as DefaultConfigLoader>::load_from(cfg)
+}
+
+/// Re-reads the configuration file and applies the settings that can change while the base node is running. Changes
+/// are reported relative to the configuration the node was started with.
+pub struct ConfigReloader {
+    bootstrap: ConfigBootstrap,
+    startup_config: Config,
+    mempool_config: watch::Sender<MempoolConfig>,
+    dht: Dht,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        bootstrap: ConfigBootstrap,
+        startup_config: Config,
+        mempool_config: watch::Sender<MempoolConfig>,
+        ctx: &BaseNodeContext,
+    ) -> Self {
+        Self {
+            bootstrap,
+            startup_config,
+            mempool_config,
+            dht: ctx.base_node_dht().clone(),
+        }
+    }
+
+    /// The command line arguments the configuration is loaded with
+    pub fn bootstrap(&self) -> &ConfigBootstrap {
+        &self.bootstrap
+    }
+
+    /// Loads the configuration, validates it and applies the reloadable settings. Nothing is applied if the
+    /// configuration is invalid.
+    pub fn reload(&self) -> Result<ReloadReport, anyhow::Error> {
+        let cfg = self.bootstrap.load_configuration()?;
+        let global_config = GlobalConfig::convert_from(ApplicationType::BaseNode, cfg.clone())?;
+        let mempool_config = load_mempool_config(&cfg)?;
+        let report = ConfigChanges::between(&self.startup_config, &cfg)?.into_report(RELOADABLE_KEYS);
+
+        self.dht
+            .set_flood_ban_max_msg_count(global_config.flood_ban_max_msg_count);
+        if self.mempool_config.send(mempool_config).is_err() {
+            warn!(
+                target: LOG_TARGET,
+                "The mempool is not running, the mempool configuration was not applied"
+            );
+        }
+        debug!(target: LOG_TARGET, "Configuration reloaded: {:?}", report);
+        Ok(report)
+    }
+}
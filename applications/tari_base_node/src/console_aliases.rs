@@ -0,0 +1,231 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Console aliases: names that expand to one or more base node commands, e.g. `st = get-state-info; get-chain-metadata`.
+//!
+//! Aliases are read from the `console_aliases` setting and from the aliases file in the data directory, which is
+//! written by the `alias` command. An alias in the file takes precedence over a configured alias with the same name.
+//! The file has one `<name> = <command>; <command>...` definition per line, lines starting with `#` are ignored.
+
+use log::*;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+const LOG_TARGET: &str = "base_node::app::console_aliases";
+
+/// The file in the data directory that aliases added with the `alias` command are saved to
+pub const ALIASES_FILE_NAME: &str = "console_aliases.txt";
+
+#[derive(Debug, Error)]
+pub enum AliasError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Invalid alias '{0}', expected `<name> = <command>; <command>...`")]
+    InvalidDefinition(String),
+    #[error("Invalid alias name '{0}', names may only contain letters, digits, '-' and '_'")]
+    InvalidName(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleAliases {
+    configured: BTreeMap<String, Vec<String>>,
+    saved: BTreeMap<String, Vec<String>>,
+    file: PathBuf,
+}
+
+impl ConsoleAliases {
+    /// Loads the configured aliases and the aliases saved in the file. Invalid definitions are logged and skipped.
+    pub fn load(configured: &[String], file: PathBuf) -> Self {
+        let configured = parse_definitions(configured.iter().map(String::as_str));
+        let saved = match fs::read_to_string(&file) {
+            Ok(contents) => parse_definitions(contents.lines()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Could not read aliases from {}: {}", file.display(), err);
+                BTreeMap::new()
+            },
+        };
+        Self {
+            configured,
+            saved,
+            file,
+        }
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    /// The commands the alias expands to
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.saved
+            .get(name)
+            .or_else(|| self.configured.get(name))
+            .map(Vec::as_slice)
+    }
+
+    /// All aliases and the commands they expand to, ordered by name
+    pub fn all(&self) -> BTreeMap<&str, &[String]> {
+        self.configured
+            .iter()
+            .chain(self.saved.iter())
+            .map(|(name, commands)| (name.as_str(), commands.as_slice()))
+            .collect()
+    }
+
+    /// Whether the alias is defined in the config file, and can therefore not be removed with the `alias` command
+    pub fn is_configured(&self, name: &str) -> bool {
+        self.configured.contains_key(name)
+    }
+
+    /// Adds or replaces an alias from a `<name> = <command>; <command>...` definition and saves it to the file
+    pub fn set(&mut self, definition: &str) -> Result<String, AliasError> {
+        let (name, commands) = parse_definition(definition)?;
+        self.saved.insert(name.clone(), commands);
+        self.save()?;
+        Ok(name)
+    }
+
+    /// Removes a saved alias, returns false if there is no such alias in the file
+    pub fn remove(&mut self, name: &str) -> Result<bool, AliasError> {
+        if self.saved.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Expands a command line that starts with an alias into the commands of the alias. The remaining arguments on the
+    /// line are appended to the last command. Lines that do not start with an alias are returned as is.
+    pub fn expand(&self, line: &str) -> Vec<String> {
+        let mut words = line.split_whitespace();
+        let commands = match words.next().and_then(|name| self.get(name)) {
+            Some(commands) => commands,
+            None => return vec![line.to_string()],
+        };
+        let mut expanded = commands.to_vec();
+        let args = words.collect::<Vec<_>>();
+        if let Some(last) = expanded.last_mut() {
+            if !args.is_empty() {
+                last.push(' ');
+                last.push_str(&args.join(" "));
+            }
+        }
+        expanded
+    }
+
+    fn save(&self) -> Result<(), AliasError> {
+        let contents = self
+            .saved
+            .iter()
+            .map(|(name, commands)| format!("{} = {}\n", name, commands.join("; ")))
+            .collect::<String>();
+        fs::write(&self.file, contents)?;
+        Ok(())
+    }
+}
+
+/// Parses a `<name> = <command>; <command>...` alias definition
+pub fn parse_definition(definition: &str) -> Result<(String, Vec<String>), AliasError> {
+    let (name, commands) = definition
+        .split_once('=')
+        .ok_or_else(|| AliasError::InvalidDefinition(definition.to_string()))?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(AliasError::InvalidName(name.to_string()));
+    }
+    let commands = commands
+        .split(';')
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if commands.is_empty() {
+        return Err(AliasError::InvalidDefinition(definition.to_string()));
+    }
+    Ok((name.to_string(), commands))
+}
+
+fn parse_definitions<'a, I: Iterator<Item = &'a str>>(definitions: I) -> BTreeMap<String, Vec<String>> {
+    definitions
+        .map(str::trim)
+        .filter(|definition| !definition.is_empty() && !definition.starts_with('#'))
+        .filter_map(|definition| match parse_definition(definition) {
+            Ok(alias) => Some(alias),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Ignoring console alias: {}", err);
+                None
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_definition() {
+        let (name, commands) = parse_definition("st = get-state-info; get-chain-metadata;").unwrap();
+        assert_eq!(name, "st");
+        assert_eq!(commands, vec!["get-state-info", "get-chain-metadata"]);
+        assert!(matches!(
+            parse_definition("get-state-info"),
+            Err(AliasError::InvalidDefinition(_))
+        ));
+        assert!(matches!(
+            parse_definition("s t = status"),
+            Err(AliasError::InvalidName(_))
+        ));
+        assert!(matches!(
+            parse_definition("st = ;"),
+            Err(AliasError::InvalidDefinition(_))
+        ));
+    }
+
+    #[test]
+    fn test_expand_and_persist() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("console_aliases.txt");
+        let mut aliases = ConsoleAliases::load(&["peers = list-peers".to_string()], file.clone());
+        aliases.set("gb = get-chain-metadata; get-block").unwrap();
+        assert_eq!(aliases.expand("gb 10 json"), vec![
+            "get-chain-metadata",
+            "get-block 10 json"
+        ]);
+        assert_eq!(aliases.expand("peers"), vec!["list-peers"]);
+        assert_eq!(aliases.expand("status"), vec!["status"]);
+
+        let mut aliases = ConsoleAliases::load(&[], file);
+        assert_eq!(aliases.get("gb").unwrap(), ["get-chain-metadata", "get-block"]);
+        assert!(aliases.remove("gb").unwrap());
+        assert!(!aliases.remove("gb").unwrap());
+        assert!(aliases.get("gb").is_none());
+    }
+}
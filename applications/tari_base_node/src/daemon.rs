@@ -0,0 +1,266 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Running the base node as a daemon: detaching from the terminal, the PID file that prevents a second instance from
+//! starting and the unix socket that `tari_base_node status` and `tari_base_node stop` connect to.
+
+use crate::{cli::ControlCommand, command_handler::CommandHandler};
+use log::*;
+use std::{
+    fs,
+    fs::OpenOptions,
+    io,
+    io::{Read, Write},
+    os::unix::{
+        io::{AsRawFd, FromRawFd},
+        net::UnixStream as StdUnixStream,
+    },
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+    time::Duration,
+};
+use tari_shutdown::Shutdown;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    time,
+};
+
+const LOG_TARGET: &str = "base_node::app::daemon";
+
+pub const PID_FILE_NAME: &str = "base_node.pid";
+pub const SOCKET_FILE_NAME: &str = "base_node.sock";
+/// How long a client may take to send its command
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// What a daemon writes to its startup pipe once it has started
+const STARTED: &str = "started";
+
+/// Detaches the process from the terminal: the child starts a new session and its standard streams are redirected to
+/// /dev/null. The parent process stays attached until the child reports through the returned [StartupReport] whether
+/// it started, and exits with an error if it did not. This must be called before any threads are started.
+pub fn daemonize() -> io::Result<StartupReport> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // Safety: both file descriptors were just opened and are owned by nothing else
+    let (reader, writer) = unsafe { (fs::File::from_raw_fd(fds[0]), fs::File::from_raw_fd(fds[1])) };
+    // Safety: no other threads are running, so the child process is a complete copy of this one
+    match unsafe { libc::fork() } {
+        -1 => return Err(io::Error::last_os_error()),
+        0 => drop(reader),
+        pid => {
+            drop(writer);
+            process::exit(wait_for_startup(reader, pid));
+        },
+    }
+    if unsafe { libc::setsid() } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    let dev_null = OpenOptions::new().read(true).write(true).open("/dev/null")?;
+    for fd in 0..=2 {
+        if unsafe { libc::dup2(dev_null.as_raw_fd(), fd) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(StartupReport { pipe: writer })
+}
+
+/// Waits for the daemon to report whether it started and returns the exit code of the parent process
+fn wait_for_startup(mut pipe: fs::File, pid: i32) -> i32 {
+    let mut report = String::new();
+    // The pipe is closed without a report if the daemon dies during startup
+    let _ = pipe.read_to_string(&mut report);
+    match report.trim() {
+        STARTED => {
+            println!("Base node started as a daemon (pid = {})", pid);
+            0
+        },
+        "" => {
+            eprintln!("The base node exited during startup, see its log for the reason");
+            1
+        },
+        error => {
+            eprintln!("The base node could not start: {}", error);
+            1
+        },
+    }
+}
+
+/// The write end of the pipe that the parent of a daemon waits on until the daemon has started or failed to start
+#[derive(Debug)]
+pub struct StartupReport {
+    pipe: fs::File,
+}
+
+impl StartupReport {
+    pub fn started(mut self) {
+        let _ = writeln!(self.pipe, "{}", STARTED);
+    }
+
+    pub fn failed(mut self, error: &str) {
+        let _ = writeln!(self.pipe, "{}", error);
+    }
+}
+
+/// Contains the id of the running base node process. The file is removed when this is dropped.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Writes the id of this process to the file. Fails if the file contains the id of a process that is still
+    /// running.
+    pub fn create<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if let Some(pid) = read_pid(&path) {
+            if is_running(pid) {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("A base node is already running with pid {} ({})", pid, path.display()),
+                ));
+            }
+        }
+        fs::write(&path, format!("{}\n", process::id()))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            warn!(target: LOG_TARGET, "Could not remove PID file {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+fn read_pid(path: &Path) -> Option<i32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn is_running(pid: i32) -> bool {
+    // Signal 0 only checks whether the process exists, EPERM means it exists but belongs to another user
+    unsafe { libc::kill(pid, 0) == 0 }
+    || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// The unix socket a daemon listens on for [ControlCommand]s. Each connection sends a single command on one line and
+/// receives the reply. The socket file is removed when this is dropped.
+pub struct ControlSocket {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl ControlSocket {
+    /// Listens on the given path, replacing the socket file of a previous instance that did not shut down cleanly
+    pub fn bind<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+        let path = path.into();
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        let listener = UnixListener::bind(&path)?;
+        Ok(Self { listener, path })
+    }
+
+    /// Handles commands until the shutdown is triggered, either by a `stop` command or by dropping it
+    pub async fn serve(self, command_handler: Arc<CommandHandler>, mut shutdown: Shutdown) {
+        let mut shutdown_signal = shutdown.to_signal();
+        loop {
+            tokio::select! {
+                _ = shutdown_signal.wait() => {
+                    break;
+                },
+                result = self.listener.accept() => match result {
+                    Ok((stream, _)) => {
+                        if let Some(ControlCommand::Stop) = handle_connection(stream, &command_handler).await {
+                            info!(target: LOG_TARGET, "Stop command received, shutting down");
+                            shutdown.trigger();
+                        }
+                    },
+                    Err(err) => warn!(target: LOG_TARGET, "Failed to accept control connection: {}", err),
+                },
+            }
+        }
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+async fn handle_connection(stream: UnixStream, command_handler: &CommandHandler) -> Option<ControlCommand> {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    let command = match time::timeout(REQUEST_TIMEOUT, BufReader::new(reader).read_line(&mut line)).await {
+        Ok(Ok(_)) => line.trim().parse::<ControlCommand>().ok(),
+        Ok(Err(err)) => {
+            warn!(target: LOG_TARGET, "Failed to read control command: {}", err);
+            return None;
+        },
+        Err(_) => {
+            warn!(target: LOG_TARGET, "Control client did not send a command in time");
+            return None;
+        },
+    };
+    let reply = match command {
+        Some(ControlCommand::Status) => command_handler.status_line().await.to_string(),
+        Some(ControlCommand::Stop) => "Stopping the base node".to_string(),
+        None => format!("Unknown command '{}'", line.trim()),
+    };
+    if let Err(err) = writer.write_all(format!("{}\n", reply).as_bytes()).await {
+        warn!(target: LOG_TARGET, "Failed to reply to control command: {}", err);
+    }
+    command
+}
+
+/// Sends a command to the daemon listening on the socket and returns its reply
+pub fn send_command(socket_path: &Path, command: ControlCommand) -> io::Result<String> {
+    let mut stream = StdUnixStream::connect(socket_path)?;
+    writeln!(stream, "{}", command)?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_refuses_a_second_pid_file_for_a_running_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PID_FILE_NAME);
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(process::id() as i32));
+        assert_eq!(PidFile::create(&path).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use chrono::{DateTime, Utc};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tari_core::base_node::state_machine_service::states::StateEvent;
+use tokio::{runtime, sync::broadcast};
+use zip::{result::ZipResult, write::FileOptions, CompressionMethod, ZipWriter};
+
+/// The number of most recent lines included from every log file
+const NUM_LOG_LINES: usize = 1000;
+/// The number of most recent state machine events that are kept
+const STATE_EVENT_HISTORY_SIZE: usize = 100;
+
+/// Keeps the most recent base node state machine events, for diagnostics
+#[derive(Clone, Default)]
+pub struct StateEventHistory {
+    events: Arc<Mutex<VecDeque<(DateTime<Utc>, String)>>>,
+}
+
+impl StateEventHistory {
+    /// Records the events received on `events` on a background task
+    pub fn spawn(executor: &runtime::Handle, mut events: broadcast::Receiver<Arc<StateEvent>>) -> Self {
+        let history = Self::default();
+        let recorder = history.clone();
+        executor.spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => recorder.push(event.to_string()),
+                    Err(broadcast::error::RecvError::Lagged(n)) => recorder.push(format!("{} event(s) missed", n)),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        history
+    }
+
+    fn push(&self, event: String) {
+        let mut events = self.events.lock().expect("state event history lock poisoned");
+        if events.len() >= STATE_EVENT_HISTORY_SIZE {
+            events.pop_front();
+        }
+        events.push_back((Utc::now(), event));
+    }
+
+    /// The recorded events, oldest first
+    pub fn events(&self) -> Vec<(DateTime<Utc>, String)> {
+        self.events
+            .lock()
+            .expect("state event history lock poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Diagnostics to attach to bug reports, written as a zip file with one entry per item
+#[derive(Default)]
+pub struct DebugBundle {
+    files: Vec<(String, String)>,
+}
+
+impl DebugBundle {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add<N: Into<String>, C: Into<String>>(&mut self, name: N, contents: C) {
+        self.files.push((name.into(), contents.into()));
+    }
+
+    /// Adds the last lines of each log file to the `logs` directory of the bundle
+    pub fn add_log_files(&mut self, log_files: &[PathBuf]) {
+        for path in log_files {
+            let name = match path.file_name() {
+                Some(name) => format!("logs/{}", name.to_string_lossy()),
+                None => continue,
+            };
+            let contents = File::open(path)
+                .and_then(|file| last_lines(BufReader::new(file), NUM_LOG_LINES))
+                .unwrap_or_else(|err| format!("Could not read {}: {}", path.display(), err));
+            self.add(name, contents);
+        }
+    }
+
+    pub fn write_zip(&self, path: &Path) -> ZipResult<()> {
+        let mut zip = ZipWriter::new(File::create(path)?);
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (name, contents) in &self.files {
+            zip.start_file(name.as_str(), options)?;
+            zip.write_all(contents.as_bytes())?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+/// Returns the last `count` lines of `reader`
+fn last_lines<R: BufRead>(reader: R, count: usize) -> io::Result<String> {
+    let mut lines = VecDeque::with_capacity(count);
+    for line in reader.lines() {
+        if lines.len() >= count {
+            lines.pop_front();
+        }
+        lines.push_back(line?);
+    }
+    Ok(lines.into_iter().map(|line| line + "\n").collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn it_keeps_the_last_lines() {
+        let log = (1..=5).map(|i| format!("line {}\n", i)).collect::<String>();
+        assert_eq!(last_lines(Cursor::new(&log), 2).unwrap(), "line 4\nline 5\n");
+        assert_eq!(last_lines(Cursor::new(&log), 10).unwrap(), log);
+    }
+
+    #[test]
+    fn it_limits_the_state_event_history() {
+        let history = StateEventHistory::default();
+        for i in 0..STATE_EVENT_HISTORY_SIZE + 5 {
+            history.push(format!("event {}", i));
+        }
+        let events = history.events();
+        assert_eq!(events.len(), STATE_EVENT_HISTORY_SIZE);
+        assert_eq!(events[0].1, "event 5");
+    }
+}
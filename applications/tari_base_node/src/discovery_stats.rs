@@ -0,0 +1,250 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Aggregate timings of the peer discoveries run by the `discover-peer` command. The statistics are saved to a JSON
+//! file in the data directory, so that they survive restarts and can be collected by network health monitoring.
+
+use chrono::{DateTime, Utc};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const LOG_TARGET: &str = "base_node::app::discovery_stats";
+
+/// The file in the data directory that the discovery statistics are saved to
+pub const DISCOVERY_STATS_FILE_NAME: &str = "discovery_stats.json";
+
+/// The stages of a `discover-peer` run, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoveryStage {
+    /// Sending the discovery message to the neighbouring peers
+    DiscoverySent,
+    /// Waiting for the discovery response, which propagates through the network and may be held by store and forward
+    /// nodes until the peer comes online
+    Response,
+    /// Dialing one of the addresses in the discovery response
+    Dial,
+    /// Exchanging identities with the dialed peer
+    IdentityExchange,
+}
+
+impl fmt::Display for DiscoveryStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DiscoveryStage::DiscoverySent => "Discovery sent",
+            DiscoveryStage::Response => "Discovery response (store and forward)",
+            DiscoveryStage::Dial => "Direct dial",
+            DiscoveryStage::IdentityExchange => "Identity exchange",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The timings of a single `discover-peer` run
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryReport {
+    pub stages: Vec<(DiscoveryStage, Duration)>,
+    /// The stage that failed, if any. The stages after it were not run.
+    pub failed_stage: Option<DiscoveryStage>,
+}
+
+impl DiscoveryReport {
+    pub fn add_stage(&mut self, stage: DiscoveryStage, duration: Duration) {
+        self.stages.push((stage, duration));
+    }
+
+    pub fn fail(&mut self, stage: DiscoveryStage) {
+        self.failed_stage = Some(stage);
+    }
+
+    pub fn total(&self) -> Duration {
+        self.stages.iter().map(|(_, duration)| *duration).sum()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageStats {
+    pub count: u64,
+    pub total_ms: u64,
+    pub min_ms: u64,
+    pub max_ms: u64,
+}
+
+impl StageStats {
+    pub fn add(&mut self, duration: Duration) {
+        let ms = duration.as_millis() as u64;
+        self.min_ms = if self.count == 0 { ms } else { self.min_ms.min(ms) };
+        self.max_ms = self.max_ms.max(ms);
+        self.total_ms += ms;
+        self.count += 1;
+    }
+
+    pub fn mean_ms(&self) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(self.total_ms / self.count)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryStats {
+    pub attempts: u64,
+    /// Discoveries that completed every stage that was run
+    pub succeeded: u64,
+    pub failures: BTreeMap<DiscoveryStage, u64>,
+    pub stages: BTreeMap<DiscoveryStage, StageStats>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl DiscoveryStats {
+    pub fn record(&mut self, report: &DiscoveryReport) {
+        self.attempts += 1;
+        match report.failed_stage {
+            Some(stage) => *self.failures.entry(stage).or_default() += 1,
+            None => self.succeeded += 1,
+        }
+        for (stage, duration) in &report.stages {
+            self.stages.entry(*stage).or_default().add(*duration);
+        }
+        self.updated_at = Some(Utc::now());
+    }
+}
+
+/// The discovery statistics and the file they are saved to
+#[derive(Debug, Clone)]
+pub struct DiscoveryStatsStore {
+    stats: DiscoveryStats,
+    file: PathBuf,
+}
+
+impl DiscoveryStatsStore {
+    /// Loads the statistics saved in the file. A missing or unreadable file starts new statistics.
+    pub fn load(file: PathBuf) -> Self {
+        let stats = match fs::read_to_string(&file) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not parse the discovery stats in {}, starting over: {}",
+                    file.display(),
+                    err
+                );
+                DiscoveryStats::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => DiscoveryStats::default(),
+            Err(err) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not read the discovery stats from {}: {}",
+                    file.display(),
+                    err
+                );
+                DiscoveryStats::default()
+            },
+        };
+        Self { stats, file }
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn stats(&self) -> &DiscoveryStats {
+        &self.stats
+    }
+
+    /// Adds the report to the statistics and saves them
+    pub fn record(&mut self, report: &DiscoveryReport) -> io::Result<()> {
+        self.stats.record(report);
+        fs::write(&self.file, serde_json::to_vec_pretty(&self.stats)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn report(stages: &[(DiscoveryStage, u64)], failed_stage: Option<DiscoveryStage>) -> DiscoveryReport {
+        DiscoveryReport {
+            stages: stages
+                .iter()
+                .map(|(stage, ms)| (*stage, Duration::from_millis(*ms)))
+                .collect(),
+            failed_stage,
+        }
+    }
+
+    #[test]
+    fn it_aggregates_and_persists_the_stats() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join(DISCOVERY_STATS_FILE_NAME);
+        let mut store = DiscoveryStatsStore::load(file.clone());
+        assert_eq!(store.stats(), &DiscoveryStats::default());
+
+        store
+            .record(&report(
+                &[(DiscoveryStage::DiscoverySent, 10), (DiscoveryStage::Response, 3000)],
+                Some(DiscoveryStage::Dial),
+            ))
+            .unwrap();
+        store
+            .record(&report(
+                &[
+                    (DiscoveryStage::DiscoverySent, 30),
+                    (DiscoveryStage::Response, 1000),
+                    (DiscoveryStage::Dial, 400),
+                    (DiscoveryStage::IdentityExchange, 100),
+                ],
+                None,
+            ))
+            .unwrap();
+
+        let stats = DiscoveryStatsStore::load(file).stats().clone();
+        assert_eq!(stats.attempts, 2);
+        assert_eq!(stats.succeeded, 1);
+        assert_eq!(stats.failures.get(&DiscoveryStage::Dial), Some(&1));
+        let response = stats.stages.get(&DiscoveryStage::Response).unwrap();
+        assert_eq!(response.count, 2);
+        assert_eq!(response.min_ms, 1000);
+        assert_eq!(response.max_ms, 3000);
+        assert_eq!(response.mean_ms(), Some(2000));
+        assert_eq!(stats.stages.get(&DiscoveryStage::Dial).unwrap().count, 1);
+    }
+
+    #[test]
+    fn it_starts_over_when_the_file_is_invalid() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join(DISCOVERY_STATS_FILE_NAME);
+        fs::write(&file, "not json").unwrap();
+        assert_eq!(DiscoveryStatsStore::load(file).stats(), &DiscoveryStats::default());
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Watches the free space on the disk holding the data directory. The node warns when the free space runs low and
+//! switches the blockchain database to read-only mode before the disk is full, so that it stops accepting blocks
+//! instead of failing with an LMDB error in the middle of a write. The node keeps serving the chain it has while it is
+//! read-only, and returns to normal once enough space has been freed.
+
+use crate::alerts::{AlertKind, Alerter};
+use log::*;
+use std::{path::PathBuf, time::Duration};
+use tari_common::GlobalConfig;
+use tari_core::{
+    base_node::StateMachineHandle,
+    chain_storage::{BlockchainDatabase, LMDBDatabase},
+};
+use tari_shutdown::ShutdownSignal;
+use tokio::time;
+
+const LOG_TARGET: &str = "base_node::disk_space_monitor";
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskSpaceLevel {
+    Ok,
+    /// Below the warning threshold
+    Low,
+    /// Below the read-only threshold, the blockchain database should not grow
+    Exhausted,
+}
+
+/// The free disk space thresholds in bytes, 0 disables a threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskSpaceThresholds {
+    pub warning: u64,
+    pub read_only: u64,
+}
+
+impl DiskSpaceThresholds {
+    pub fn from_config(config: &GlobalConfig) -> Self {
+        Self {
+            warning: config.disk_space_warning_mb * BYTES_PER_MB,
+            read_only: config.disk_space_read_only_mb * BYTES_PER_MB,
+        }
+    }
+
+    /// Returns the level for the available space. A database that is read-only stays read-only until the available
+    /// space is back above the warning threshold, so that the node does not switch modes every time a few blocks are
+    /// written or pruned.
+    pub fn level(&self, available: u64, is_read_only: bool) -> DiskSpaceLevel {
+        if available < self.read_only || (is_read_only && available < self.warning.max(self.read_only)) {
+            DiskSpaceLevel::Exhausted
+        } else if available < self.warning {
+            DiskSpaceLevel::Low
+        } else {
+            DiskSpaceLevel::Ok
+        }
+    }
+}
+
+pub struct DiskSpaceMonitor {
+    data_dir: PathBuf,
+    interval: Duration,
+    thresholds: DiskSpaceThresholds,
+    db: BlockchainDatabase<LMDBDatabase>,
+    state_machine: StateMachineHandle,
+    alerter: Alerter,
+    warned: bool,
+}
+
+impl DiskSpaceMonitor {
+    pub fn new(
+        config: &GlobalConfig,
+        db: BlockchainDatabase<LMDBDatabase>,
+        state_machine: StateMachineHandle,
+        alerter: Alerter,
+    ) -> Self {
+        Self {
+            data_dir: config.data_dir.clone(),
+            interval: config.disk_space_check_interval,
+            thresholds: DiskSpaceThresholds::from_config(config),
+            db,
+            state_machine,
+            alerter,
+            warned: false,
+        }
+    }
+
+    pub async fn run(mut self, mut shutdown_signal: ShutdownSignal) {
+        let mut interval = time::interval(self.interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    match fs2::available_space(&self.data_dir) {
+                        Ok(available) => self.update(available),
+                        Err(err) => {
+                            debug!(target: LOG_TARGET, "Unable to check the free disk space: {}", err);
+                        },
+                    }
+                },
+                _ = shutdown_signal.wait() => {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, available: u64) {
+        let is_read_only = self.db.is_read_only();
+        match self.thresholds.level(available, is_read_only) {
+            DiskSpaceLevel::Exhausted => {
+                if !is_read_only {
+                    let message = format!(
+                        "Only {} MiB of disk space is left for {}. The node has stopped accepting blocks until space \
+                         is freed.",
+                        available / BYTES_PER_MB,
+                        self.data_dir.display()
+                    );
+                    error!(target: LOG_TARGET, "{}", message);
+                    self.set_read_only(true);
+                    self.alerter.notify(AlertKind::LowDiskSpace, message);
+                }
+                self.warned = true;
+            },
+            level => {
+                if is_read_only {
+                    info!(
+                        target: LOG_TARGET,
+                        "{} MiB of disk space is available again, the node is accepting blocks",
+                        available / BYTES_PER_MB
+                    );
+                    self.set_read_only(false);
+                }
+                if level == DiskSpaceLevel::Low {
+                    if !self.warned {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Only {} MiB of disk space is left for {}. The node will stop accepting blocks below {} \
+                             MiB.",
+                            available / BYTES_PER_MB,
+                            self.data_dir.display(),
+                            self.thresholds.read_only / BYTES_PER_MB
+                        );
+                        self.warned = true;
+                    }
+                } else {
+                    self.warned = false;
+                }
+            },
+        }
+    }
+
+    fn set_read_only(&self, read_only: bool) {
+        self.db.set_read_only(read_only);
+        self.state_machine.publish_read_only_status(read_only);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn disk_space_levels() {
+        let thresholds = DiskSpaceThresholds {
+            warning: 2048,
+            read_only: 256,
+        };
+        assert_eq!(thresholds.level(4096, false), DiskSpaceLevel::Ok);
+        assert_eq!(thresholds.level(1024, false), DiskSpaceLevel::Low);
+        assert_eq!(thresholds.level(100, false), DiskSpaceLevel::Exhausted);
+        // Stays read-only until the free space is above the warning threshold
+        assert_eq!(thresholds.level(1024, true), DiskSpaceLevel::Exhausted);
+        assert_eq!(thresholds.level(4096, true), DiskSpaceLevel::Ok);
+
+        let disabled = DiskSpaceThresholds {
+            warning: 0,
+            read_only: 0,
+        };
+        assert_eq!(disabled.level(0, false), DiskSpaceLevel::Ok);
+        let read_only_only = DiskSpaceThresholds {
+            warning: 0,
+            read_only: 256,
+        };
+        assert_eq!(read_only_only.level(100, false), DiskSpaceLevel::Exhausted);
+        assert_eq!(read_only_only.level(1024, true), DiskSpaceLevel::Ok);
+    }
+}
@@ -0,0 +1,391 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! A rolling journal of significant node events: state changes, reorgs, peer bans, fatal errors and software update
+//! notices. Log files rotate away, so the journal is kept in its own LMDB database in the data directory, where it
+//! holds the most recent `event_journal_max_events` events.
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    fs,
+    io,
+    mem,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use strum_macros::Display;
+use tari_comms::connectivity::{ConnectivityEvent, ConnectivityEventRx};
+use tari_core::{
+    base_node::{
+        comms_interface::{BlockEvent, BlockEventReceiver},
+        state_machine_service::states::{StateInfo, StatusInfo},
+    },
+    chain_storage::BlockAddResult,
+};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_p2p::auto_update::SoftwareUpdate;
+use tari_shutdown::ShutdownSignal;
+use tari_storage::{
+    lmdb_store::{db, LMDBBuilder, LMDBConfig, LMDBDatabase, LMDBError},
+    IterationResult,
+    KeyValStoreError,
+};
+use thiserror::Error;
+use tokio::sync::{broadcast::error::RecvError, watch};
+
+const LOG_TARGET: &str = "base_node::event_journal";
+/// The directory of the journal database, relative to the data directory
+pub const EVENT_JOURNAL_DIR: &str = "event_journal";
+const DATABASE_NAME: &str = "events";
+
+#[derive(Debug, Error)]
+pub enum EventJournalError {
+    #[error("LMDB error: {0}")]
+    LmdbError(#[from] LMDBError),
+    #[error("Key-value store error: {0}")]
+    KeyValStoreError(#[from] KeyValStoreError),
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("The journal database could not be opened")]
+    DatabaseNotFound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum NodeEventKind {
+    Started,
+    Stopped,
+    StateChanged,
+    Reorg,
+    PeerBanned,
+    FatalError,
+    SoftwareUpdate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeEvent {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub kind: NodeEventKind,
+    pub message: String,
+}
+
+impl NodeEvent {
+    pub fn time(&self) -> DateTime<Utc> {
+        Utc.timestamp(self.timestamp, 0)
+    }
+}
+
+impl fmt::Display for NodeEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {}",
+            self.time().format("%Y-%m-%d %H:%M:%S"),
+            self.kind,
+            self.message
+        )
+    }
+}
+
+/// The sequence numbers of the oldest event and of the next event to be recorded. Events are keyed by their big endian
+/// sequence number, so that the database keeps them in the order they were recorded.
+struct Sequence {
+    first: u64,
+    next: u64,
+}
+
+#[derive(Clone)]
+pub struct EventJournal {
+    db: LMDBDatabase,
+    max_events: u64,
+    sequence: Arc<Mutex<Sequence>>,
+}
+
+impl EventJournal {
+    /// Opens the journal in the given directory, creating it if it does not exist
+    pub fn open<P: AsRef<Path>>(path: P, max_events: usize) -> Result<Self, EventJournalError> {
+        fs::create_dir_all(&path)?;
+        let store = LMDBBuilder::new()
+            .set_path(path)
+            .set_env_config(LMDBConfig::default())
+            .set_max_number_of_databases(1)
+            .add_database(DATABASE_NAME, db::CREATE)
+            .build()?;
+        let db = store
+            .get_handle(DATABASE_NAME)
+            .ok_or(EventJournalError::DatabaseNotFound)?;
+
+        let mut sequence = None;
+        db.for_each::<[u8; 8], NodeEvent, _>(|pair| {
+            if let Ok((key, _)) = pair {
+                let key = u64::from_be_bytes(key);
+                let first = sequence.map(|(first, _)| first).unwrap_or(key);
+                sequence = Some((first, key));
+            }
+            IterationResult::Continue
+        })?;
+        let sequence = sequence
+            .map(|(first, last)| Sequence { first, next: last + 1 })
+            .unwrap_or(Sequence { first: 0, next: 0 });
+
+        Ok(Self {
+            db,
+            max_events: (max_events as u64).max(1),
+            sequence: Arc::new(Mutex::new(sequence)),
+        })
+    }
+
+    /// Records an event, dropping the oldest events once the journal is full. Failures are logged, recording an event
+    /// never fails the operation that caused it.
+    pub fn record<T: Into<String>>(&self, kind: NodeEventKind, message: T) {
+        let event = NodeEvent {
+            timestamp: Utc::now().timestamp(),
+            kind,
+            message: message.into(),
+        };
+        debug!(target: LOG_TARGET, "{}", event);
+        if let Err(err) = self.append(&event) {
+            warn!(target: LOG_TARGET, "Could not record event '{}': {}", event, err);
+        }
+    }
+
+    fn append(&self, event: &NodeEvent) -> Result<(), EventJournalError> {
+        let mut sequence = self.sequence.lock().unwrap();
+        self.db.insert(&sequence.next.to_be_bytes(), event)?;
+        sequence.next += 1;
+        while sequence.next - sequence.first > self.max_events {
+            self.db.remove(&sequence.first.to_be_bytes())?;
+            sequence.first += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns the events recorded at or after the given time, oldest first
+    pub fn events_since(&self, since: DateTime<Utc>) -> Result<Vec<NodeEvent>, EventJournalError> {
+        let since = since.timestamp();
+        let mut events = Vec::new();
+        let mut error = None;
+        self.db.for_each::<[u8; 8], NodeEvent, _>(|pair| match pair {
+            Ok((_, event)) => {
+                if event.timestamp >= since {
+                    events.push(event);
+                }
+                IterationResult::Continue
+            },
+            Err(err) => {
+                error = Some(err);
+                IterationResult::Break
+            },
+        })?;
+        match error {
+            Some(err) => Err(err.into()),
+            None => Ok(events),
+        }
+    }
+}
+
+/// Parses the `--since` time of the events command: an RFC 3339 time, a date (`2021-09-30`) or a time relative to
+/// now, such as `30m`, `12h` or `7d`
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(Utc.from_utc_date(&date).and_hms(0, 0, 0));
+    }
+    let invalid = || {
+        format!(
+            "Invalid time '{}', expected e.g. '2021-09-30T12:00:00Z', '2021-09-30' or '12h'",
+            value
+        )
+    };
+    if value.len() < 2 {
+        return Err(invalid());
+    }
+    let (amount, unit) = value.split_at(value.len() - 1);
+    let amount = amount.parse::<i64>().map_err(|_| invalid())?;
+    let duration = match unit {
+        "s" => Duration::seconds(amount),
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(now - duration)
+}
+
+/// Records the events of the running node in the journal
+pub struct EventRecorder {
+    journal: EventJournal,
+    status: watch::Receiver<StatusInfo>,
+    block_events: BlockEventReceiver,
+    connectivity_events: ConnectivityEventRx,
+    software_updates: watch::Receiver<Option<SoftwareUpdate>>,
+}
+
+impl EventRecorder {
+    pub fn new(
+        journal: EventJournal,
+        status: watch::Receiver<StatusInfo>,
+        block_events: BlockEventReceiver,
+        connectivity_events: ConnectivityEventRx,
+        software_updates: watch::Receiver<Option<SoftwareUpdate>>,
+    ) -> Self {
+        Self {
+            journal,
+            status,
+            block_events,
+            connectivity_events,
+            software_updates,
+        }
+    }
+
+    pub async fn run(mut self, mut shutdown_signal: ShutdownSignal) {
+        let mut state = mem::discriminant(&self.status.borrow().state_info);
+        loop {
+            tokio::select! {
+                Ok(_) = self.status.changed() => {
+                    let state_info = self.status.borrow().state_info.clone();
+                    if mem::discriminant(&state_info) != state {
+                        state = mem::discriminant(&state_info);
+                        self.journal.record(NodeEventKind::StateChanged, state_name(&state_info));
+                    }
+                },
+                event = self.block_events.recv() => match event {
+                    Ok(event) => self.handle_block_event(&event),
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Missed {} block event(s), reorgs may not have been recorded", n);
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                event = self.connectivity_events.recv() => match event {
+                    Ok(ConnectivityEvent::PeerBanned(node_id)) => {
+                        self.journal.record(NodeEventKind::PeerBanned, format!("Banned peer {}", node_id));
+                    },
+                    Ok(_) => {},
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Missed {} connectivity event(s), bans may not have been recorded", n);
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                Ok(_) = self.software_updates.changed() => {
+                    let update = self.software_updates.borrow().clone();
+                    if let Some(update) = update {
+                        self.journal.record(
+                            NodeEventKind::SoftwareUpdate,
+                            format!("Version {} is available at {}", update.version(), update.download_url()),
+                        );
+                    }
+                },
+                _ = shutdown_signal.wait() => {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_block_event(&self, event: &BlockEvent) {
+        if let BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { added, removed }, _) = event {
+            let fork_height = removed
+                .iter()
+                .chain(added.iter())
+                .map(|block| block.height())
+                .min()
+                .unwrap_or_default();
+            let tip = added.iter().max_by_key(|block| block.height());
+            self.journal.record(
+                NodeEventKind::Reorg,
+                format!(
+                    "Reorg at height {}: {} block(s) removed, {} added, new tip {}",
+                    fork_height,
+                    removed.len(),
+                    added.len(),
+                    tip.map(|block| format!("#{} {}", block.height(), block.hash().to_hex()))
+                        .unwrap_or_else(|| "unknown".to_string())
+                ),
+            );
+        }
+    }
+}
+
+fn state_name(state_info: &StateInfo) -> &'static str {
+    match state_info {
+        StateInfo::StartUp => "Starting up",
+        StateInfo::HeaderSync(_) => "Syncing headers",
+        StateInfo::HorizonSync(_) => "Horizon sync",
+        StateInfo::BlockSyncStarting | StateInfo::BlockSync(_) => "Syncing blocks",
+        StateInfo::Listening(_) => "Listening",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn rolling_journal() {
+        let dir = tempdir().unwrap();
+        let journal = EventJournal::open(dir.path(), 3).unwrap();
+        for i in 0..5 {
+            journal.record(NodeEventKind::StateChanged, format!("event {}", i));
+        }
+        let events = journal.events_since(Utc.timestamp(0, 0)).unwrap();
+        let messages = events.iter().map(|e| e.message.as_str()).collect::<Vec<_>>();
+        assert_eq!(messages, vec!["event 2", "event 3", "event 4"]);
+        drop(journal);
+
+        // The events are kept when the journal is reopened
+        let journal = EventJournal::open(dir.path(), 3).unwrap();
+        journal.record(NodeEventKind::Stopped, "event 5");
+        let events = journal.events_since(Utc.timestamp(0, 0)).unwrap();
+        let messages = events.iter().map(|e| e.message.as_str()).collect::<Vec<_>>();
+        assert_eq!(messages, vec!["event 3", "event 4", "event 5"]);
+
+        assert!(journal
+            .events_since(Utc::now() + Duration::hours(1))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn since_formats() {
+        let now = Utc.ymd(2021, 10, 1).and_hms(12, 0, 0);
+        assert_eq!(
+            parse_since("2021-09-30T10:00:00Z", now).unwrap(),
+            Utc.ymd(2021, 9, 30).and_hms(10, 0, 0)
+        );
+        assert_eq!(
+            parse_since("2021-09-30", now).unwrap(),
+            Utc.ymd(2021, 9, 30).and_hms(0, 0, 0)
+        );
+        assert_eq!(parse_since("12h", now).unwrap(), Utc.ymd(2021, 10, 1).and_hms(0, 0, 0));
+        assert_eq!(parse_since("2d", now).unwrap(), Utc.ymd(2021, 9, 29).and_hms(12, 0, 0));
+        assert!(parse_since("h", now).is_err());
+        assert!(parse_since("12w", now).is_err());
+        assert!(parse_since("yesterday", now).is_err());
+    }
+}
@@ -21,24 +21,44 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::{
     builder::BaseNodeContext,
+    event_journal::EventJournal,
     grpc::{
-        blocks::{block_fees, block_heights, block_size, GET_BLOCKS_MAX_HEIGHTS, GET_BLOCKS_PAGE_SIZE},
+        blocks::{
+            block_fees,
+            block_heights,
+            block_size,
+            GET_BLOCKS_MAX_HEIGHTS,
+            GET_BLOCKS_MAX_RESPONSE_BYTES,
+            GET_BLOCKS_PAGE_SIZE,
+        },
         helpers::{mean, median},
     },
+    hash_rate::{self, DEFAULT_HASH_RATE_NUM_WINDOWS, DEFAULT_HASH_RATE_WINDOW, MAX_HASH_RATE_NUM_WINDOWS},
+    time_sync::TimeSyncHandle,
 };
+use chrono::{TimeZone, Utc};
 use futures::{channel::mpsc, SinkExt};
 use log::*;
 use std::{
     cmp,
     convert::{TryFrom, TryInto},
+    time::{Duration, Instant},
 };
 use tari_app_grpc::{
+    authentication::AdminToken,
+    paging::PageLimits,
     tari_rpc,
     tari_rpc::{CalcType, Sorting},
 };
 use tari_app_utilities::consts;
 use tari_common_types::types::Signature;
-use tari_comms::{Bytes, CommsNode};
+use tari_comms::{
+    peer_manager::{NodeId, Peer, PeerQuery},
+    types::CommsPublicKey,
+    Bytes,
+    CommsNode,
+    PeerConnection,
+};
 use tari_core::{
     base_node::{
         comms_interface::{Broadcast, CommsInterfaceError},
@@ -46,7 +66,7 @@ use tari_core::{
         StateMachineHandle,
     },
     blocks::{Block, BlockHeader, NewBlockTemplate},
-    chain_storage::ChainStorageError,
+    chain_storage::{async_db::AsyncBlockchainDb, ChainStorageError, LMDBDatabase},
     consensus::{emission::Emission, ConsensusManager, NetworkConsensus},
     crypto::tari_utilities::{hex::Hex, ByteArray},
     mempool::{service::LocalMempoolService, TxStorageResponse},
@@ -74,6 +94,10 @@ const LIST_HEADERS_MAX_NUM_HEADERS: u64 = 10_000;
 const LIST_HEADERS_PAGE_SIZE: usize = 10;
 // The `num_headers` value if none is provided.
 const LIST_HEADERS_DEFAULT_NUM_HEADERS: u64 = 10;
+// The maximum number of future blocks for which mempool fee per gram stats can be requested
+const FEE_PER_GRAM_STATS_LIMIT: u64 = 20;
+// The ban reason recorded for BanPeer requests that do not give one
+const DEFAULT_BAN_REASON: &str = "Banned via gRPC";
 
 pub struct BaseNodeGrpcServer {
     node_service: LocalNodeCommsInterface,
@@ -84,6 +108,12 @@ pub struct BaseNodeGrpcServer {
     software_updater: SoftwareUpdaterHandle,
     comms: CommsNode,
     liveness: LivenessHandle,
+    blockchain_db: AsyncBlockchainDb<LMDBDatabase>,
+    event_journal: EventJournal,
+    time_sync: TimeSyncHandle,
+    /// The token the methods that control the node or reveal its peers require, the same methods the read-only
+    /// server refuses
+    admin_token: AdminToken,
 }
 
 impl BaseNodeGrpcServer {
@@ -97,8 +127,17 @@ impl BaseNodeGrpcServer {
             software_updater: ctx.software_updater(),
             comms: ctx.base_node_comms().clone(),
             liveness: ctx.liveness(),
+            blockchain_db: ctx.blockchain_db().into(),
+            event_journal: ctx.event_journal(),
+            time_sync: ctx.time_sync(),
+            admin_token: AdminToken::default(),
         }
     }
+
+    pub fn with_admin_token(mut self, admin_token: AdminToken) -> Self {
+        self.admin_token = admin_token;
+        self
+    }
 }
 
 pub async fn get_heights(
@@ -108,6 +147,47 @@ pub async fn get_heights(
     block_heights(handler, request.start_height, request.end_height, request.from_tip).await
 }
 
+/// Returns the node ID of the peer identified by its public key or, if no public key is given, its node ID
+fn peer_request_node_id(request: &tari_rpc::PeerRequest) -> Result<NodeId, Status> {
+    if !request.public_key.is_empty() {
+        let public_key = CommsPublicKey::from_bytes(&request.public_key)
+            .map_err(|_| Status::invalid_argument("Invalid peer public key"))?;
+        return Ok(NodeId::from_public_key(&public_key));
+    }
+    NodeId::from_bytes(&request.node_id).map_err(|_| Status::invalid_argument("Invalid peer node ID"))
+}
+
+fn peer_connection_response(conn: &PeerConnection, peer: Peer) -> tari_rpc::PeerConnection {
+    tari_rpc::PeerConnection {
+        peer: Some(peer.into()),
+        address: conn.address().to_string(),
+        direction: tari_rpc::ConnectionDirection::from(conn.direction()) as i32,
+        age: conn.age().as_secs(),
+        substream_count: conn.substream_count() as u32,
+    }
+}
+
+/// Returns the height following the block identified by a GetBlocks page cursor. Fails with `ABORTED` if the block is no
+/// longer in the best chain, since the pages already received may then belong to a different chain.
+async fn height_after_cursor(handler: &mut LocalNodeCommsInterface, cursor: &str) -> Result<u64, Status> {
+    let hash = Vec::<u8>::from_hex(cursor)
+        .map_err(|_| Status::invalid_argument("Invalid cursor: expected the hex encoded hash of a block"))?;
+    let not_in_best_chain = || Status::aborted(format!("Block `{}` is not in the best chain", cursor));
+    let header = handler
+        .get_header_by_hash(hash.clone())
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?
+        .ok_or_else(not_in_best_chain)?;
+    let best_chain_header = handler
+        .get_headers(vec![header.height])
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?;
+    match best_chain_header.first() {
+        Some(best_chain_header) if best_chain_header.hash() == hash => Ok(header.height + 1),
+        _ => Err(not_in_best_chain()),
+    }
+}
+
 #[tonic::async_trait]
 impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
     type FetchMatchingUtxosStream = mpsc::Receiver<Result<tari_rpc::FetchMatchingUtxosResponse, Status>>;
@@ -393,6 +473,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         &self,
         request: Request<tari_rpc::NewBlockTemplateRequest>,
     ) -> Result<Response<tari_rpc::NewBlockTemplateResponse>, Status> {
+        self.admin_token.authenticate(&request, "GetNewBlockTemplate")?;
         let request = request.into_inner();
         debug!(target: LOG_TARGET, "Incoming GRPC request for get new block template");
         trace!(target: LOG_TARGET, "Request {:?}", request);
@@ -401,6 +482,16 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             .pow_algo as u64)
             .try_into()
             .map_err(|_| Status::invalid_argument("No valid pow algo selected".to_string()))?;
+        if let Some(clock_skew) = self.time_sync.refuses_mining() {
+            warn!(
+                target: LOG_TARGET,
+                "Refusing to create a block template, the local clock is {}", clock_skew
+            );
+            return Err(Status::failed_precondition(format!(
+                "The local clock of the base node is {}. Correct the system clock to mine.",
+                clock_skew
+            )));
+        }
         let mut handler = self.node_service.clone();
 
         let new_template = handler
@@ -437,6 +528,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         &self,
         request: Request<tari_rpc::NewBlockTemplate>,
     ) -> Result<Response<tari_rpc::GetNewBlockResult>, Status> {
+        self.admin_token.authenticate(&request, "GetNewBlock")?;
         let request = request.into_inner();
         debug!(target: LOG_TARGET, "Incoming GRPC request for get new block");
         let block_template: NewBlockTemplate = request
@@ -475,6 +567,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         &self,
         request: Request<tari_rpc::Block>,
     ) -> Result<Response<tari_rpc::SubmitBlockResponse>, Status> {
+        self.admin_token.authenticate(&request, "SubmitBlock")?;
         let request = request.into_inner();
         let block = Block::try_from(request)
             .map_err(|e| Status::invalid_argument(format!("Failed to convert arguments. Invalid block: {:?}", e)))?;
@@ -501,6 +594,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         &self,
         request: Request<tari_rpc::SubmitTransactionRequest>,
     ) -> Result<Response<tari_rpc::SubmitTransactionResponse>, Status> {
+        self.admin_token.authenticate(&request, "SubmitTransaction")?;
         let request = request.into_inner();
         let txn: Transaction = request
             .transaction
@@ -608,8 +702,9 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
 
     async fn get_peers(
         &self,
-        _request: Request<tari_rpc::GetPeersRequest>,
+        request: Request<tari_rpc::GetPeersRequest>,
     ) -> Result<Response<Self::GetPeersStream>, Status> {
+        self.admin_token.authenticate(&request, "GetPeers")?;
         debug!(target: LOG_TARGET, "Incoming GRPC request for get all peers");
 
         let peers = self
@@ -650,16 +745,39 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         let request = request.into_inner();
         debug!(
             target: LOG_TARGET,
-            "Incoming GRPC request for GetBlocks: {:?}", request.heights
+            "Incoming GRPC request for GetBlocks: {:?}, page: {:?}", request.heights, request.page
         );
-        let mut heights = request.heights;
-        heights = heights
-            .drain(..cmp::min(heights.len(), GET_BLOCKS_MAX_HEIGHTS))
-            .collect();
+        let limits = PageLimits::negotiate(
+            request.page.as_ref(),
+            GET_BLOCKS_MAX_HEIGHTS,
+            GET_BLOCKS_MAX_RESPONSE_BYTES,
+        );
+        let field_mask = request.field_mask.unwrap_or_default();
 
         let mut handler = self.node_service.clone();
+        let mut heights = if request.heights.is_empty() {
+            let cursor = request.page.as_ref().map(|page| page.cursor.as_str()).unwrap_or("");
+            let from_height = if cursor.is_empty() {
+                request.from_height
+            } else {
+                height_after_cursor(&mut handler, cursor).await?
+            };
+            let tip = handler
+                .get_metadata()
+                .await
+                .map_err(|err| Status::internal(err.to_string()))?
+                .height_of_longest_chain();
+            let to_height = cmp::min(tip, from_height.saturating_add(limits.limit as u64 - 1));
+            (from_height..=to_height).collect()
+        } else {
+            let mut heights = request.heights;
+            heights.truncate(limits.limit);
+            heights
+        };
+
         let (mut tx, rx) = mpsc::channel(GET_BLOCKS_PAGE_SIZE);
         task::spawn(async move {
+            let mut budget = limits.budget();
             let mut page: Vec<u64> = heights.drain(..cmp::min(heights.len(), GET_BLOCKS_PAGE_SIZE)).collect();
 
             while !page.is_empty() {
@@ -675,14 +793,18 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 };
                 let result_size = blocks.len();
                 for block in blocks {
-                    match tx
-                        .send(
+                    let block = tari_rpc::HistoricalBlock::try_from(block)
+                        .map(|mut block| {
+                            field_mask.apply(&mut block);
                             block
-                                .try_into()
-                                .map_err(|err| Status::internal(format!("Could not provide block: {}", err))),
-                        )
-                        .await
-                    {
+                        })
+                        .map_err(|err| Status::internal(format!("Could not provide block: {}", err)));
+                    if let Ok(ref block) = block {
+                        if !budget.try_add(block) {
+                            return;
+                        }
+                    }
+                    match tx.send(block).await {
                         Ok(_) => (),
                         Err(err) => {
                             warn!(target: LOG_TARGET, "Error sending header via GRPC:  {}", err);
@@ -704,7 +826,9 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         });
 
         debug!(target: LOG_TARGET, "Sending GetBlocks response stream to client");
-        Ok(Response::new(rx))
+        let mut response = Response::new(rx);
+        limits.add_to_metadata(&mut response);
+        Ok(response)
     }
 
     async fn get_tip_info(
@@ -740,6 +864,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
 
         let converted: Result<Vec<_>, _> = request.signatures.into_iter().map(|s| s.try_into()).collect();
         let kernels = converted.map_err(|_| Status::internal("Failed to convert one or more arguments."))?;
+        let field_mask = request.field_mask.unwrap_or_default();
 
         let mut handler = self.node_service.clone();
 
@@ -756,14 +881,13 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 Ok(data) => data,
             };
             for block in blocks {
-                match tx
-                    .send(
+                let block = tari_rpc::HistoricalBlock::try_from(block)
+                    .map(|mut block| {
+                        field_mask.apply(&mut block);
                         block
-                            .try_into()
-                            .map_err(|err| Status::internal(format!("Could not provide block:{}", err))),
-                    )
-                    .await
-                {
+                    })
+                    .map_err(|err| Status::internal(format!("Could not provide block:{}", err)));
+                match tx.send(block).await {
                     Ok(_) => (),
                     Err(err) => {
                         warn!(target: LOG_TARGET, "Error sending header via GRPC:  {}", err);
@@ -916,8 +1040,9 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
 
     async fn check_for_updates(
         &self,
-        _request: Request<tari_rpc::Empty>,
+        request: Request<tari_rpc::Empty>,
     ) -> Result<Response<tari_rpc::SoftwareUpdate>, Status> {
+        self.admin_token.authenticate(&request, "CheckForUpdates")?;
         let mut resp = tari_rpc::SoftwareUpdate::default();
 
         if let Some(ref update) = *self.software_updater.new_update_notifier().borrow() {
@@ -1087,8 +1212,9 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
 
     async fn list_connected_peers(
         &self,
-        _: Request<tari_rpc::Empty>,
+        request: Request<tari_rpc::Empty>,
     ) -> Result<Response<tari_rpc::ListConnectedPeersResponse>, Status> {
+        self.admin_token.authenticate(&request, "ListConnectedPeers")?;
         let mut connectivity = self.comms.connectivity();
         let peer_manager = self.comms.peer_manager();
         let connected_peers = connectivity
@@ -1133,6 +1259,277 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
 
         Ok(Response::new(response))
     }
+
+    async fn get_network_hash_rate(
+        &self,
+        request: Request<tari_rpc::GetNetworkHashRateRequest>,
+    ) -> Result<Response<tari_rpc::GetNetworkHashRateResponse>, Status> {
+        let request = request.into_inner();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetNetworkHashRate: {:?}", request);
+        let window = match request.window {
+            0 => DEFAULT_HASH_RATE_WINDOW,
+            window => window,
+        };
+        let num_windows = match request.num_windows {
+            0 => DEFAULT_HASH_RATE_NUM_WINDOWS,
+            num_windows if num_windows > MAX_HASH_RATE_NUM_WINDOWS => {
+                return Err(Status::invalid_argument(format!(
+                    "Cannot request more than {} hash rate windows",
+                    MAX_HASH_RATE_NUM_WINDOWS
+                )));
+            },
+            num_windows => num_windows,
+        };
+
+        let windows = hash_rate::estimate_hash_rates(&self.blockchain_db, window, num_windows)
+            .await
+            .map_err(|err| {
+                error!(target: LOG_TARGET, "Error estimating the network hash rate: {}", err);
+                Status::internal(err.to_string())
+            })?
+            .into_iter()
+            .map(|window| tari_rpc::NetworkHashRateWindow {
+                start_height: window.start_height,
+                end_height: window.end_height,
+                start_timestamp: window.start_timestamp,
+                end_timestamp: window.end_timestamp,
+                sha3_hash_rate: window.sha3 as u64,
+                monero_hash_rate: window.monero as u64,
+            })
+            .collect();
+
+        Ok(Response::new(tari_rpc::GetNetworkHashRateResponse { windows }))
+    }
+
+    async fn get_node_events(
+        &self,
+        request: Request<tari_rpc::GetNodeEventsRequest>,
+    ) -> Result<Response<tari_rpc::GetNodeEventsResponse>, Status> {
+        self.admin_token.authenticate(&request, "GetNodeEvents")?;
+        let request = request.into_inner();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetNodeEvents: {:?}", request);
+        let since = Utc
+            .timestamp_opt(cmp::min(request.since, i64::MAX as u64) as i64, 0)
+            .single()
+            .ok_or_else(|| Status::invalid_argument("Invalid since timestamp"))?;
+        let journal = self.event_journal.clone();
+        let events = task::spawn_blocking(move || journal.events_since(since))
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?
+            .map_err(|err| {
+                error!(target: LOG_TARGET, "Error reading the event journal: {}", err);
+                Status::internal(err.to_string())
+            })?
+            .into_iter()
+            .map(|event| tari_rpc::NodeEvent {
+                timestamp: event.timestamp.max(0) as u64,
+                kind: event.kind.to_string(),
+                message: event.message,
+            })
+            .collect();
+
+        Ok(Response::new(tari_rpc::GetNodeEventsResponse { events }))
+    }
+
+    async fn get_total_burnt(
+        &self,
+        request: Request<tari_rpc::GetTotalBurntRequest>,
+    ) -> Result<Response<tari_rpc::GetTotalBurntResponse>, Status> {
+        let request = request.into_inner();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetTotalBurnt: {:?}", request);
+        let height = match request.height {
+            0 => {
+                let mut handler = self.node_service.clone();
+                handler
+                    .get_metadata()
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?
+                    .height_of_longest_chain()
+            },
+            height => height,
+        };
+        let total_burnt = self.blockchain_db.fetch_total_burnt(height).await.map_err(|err| {
+            error!(target: LOG_TARGET, "Error fetching the total burnt value: {}", err);
+            Status::internal(err.to_string())
+        })?;
+
+        Ok(Response::new(tari_rpc::GetTotalBurntResponse {
+            height,
+            total_burnt: total_burnt.as_u64(),
+        }))
+    }
+
+    async fn list_connections(
+        &self,
+        request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::ListConnectionsResponse>, Status> {
+        self.admin_token.authenticate(&request, "ListConnections")?;
+        let mut connectivity = self.comms.connectivity();
+        let peer_manager = self.comms.peer_manager();
+        let active_connections = connectivity
+            .get_active_connections()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        let mut connections = Vec::with_capacity(active_connections.len());
+        for conn in active_connections {
+            let peer = peer_manager
+                .find_by_node_id(conn.peer_node_id())
+                .await
+                .map_err(|err| Status::internal(err.to_string()))?;
+            connections.push(peer_connection_response(&conn, peer));
+        }
+
+        Ok(Response::new(tari_rpc::ListConnectionsResponse { connections }))
+    }
+
+    async fn list_banned_peers(
+        &self,
+        request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::ListBannedPeersResponse>, Status> {
+        self.admin_token.authenticate(&request, "ListBannedPeers")?;
+        let query = PeerQuery::new().select_where(|p| p.is_banned());
+        let banned_peers = self
+            .comms
+            .peer_manager()
+            .perform_query(query)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(tari_rpc::ListBannedPeersResponse {
+            banned_peers: banned_peers.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn ban_peer(&self, request: Request<tari_rpc::BanPeerRequest>) -> Result<Response<tari_rpc::Empty>, Status> {
+        self.admin_token.authenticate(&request, "BanPeer")?;
+        let request = request.into_inner();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for BanPeer: {:?}", request);
+        let node_id = peer_request_node_id(
+            request
+                .peer
+                .as_ref()
+                .ok_or_else(|| Status::invalid_argument("No peer given"))?,
+        )?;
+        if self.comms.node_identity().node_id() == &node_id {
+            return Err(Status::invalid_argument("Cannot ban our own node"));
+        }
+        let duration = match request.duration {
+            0 => Duration::from_secs(u64::MAX),
+            secs => Duration::from_secs(secs),
+        };
+        let reason = if request.reason.is_empty() {
+            DEFAULT_BAN_REASON.to_string()
+        } else {
+            request.reason
+        };
+
+        self.comms
+            .connectivity()
+            .ban_peer_until(node_id, duration, reason)
+            .await
+            .map_err(|err| {
+                error!(target: LOG_TARGET, "Could not ban peer: {:?}", err);
+                Status::internal(err.to_string())
+            })?;
+
+        Ok(Response::new(tari_rpc::Empty {}))
+    }
+
+    async fn unban_peer(&self, request: Request<tari_rpc::PeerRequest>) -> Result<Response<tari_rpc::Empty>, Status> {
+        self.admin_token.authenticate(&request, "UnbanPeer")?;
+        let node_id = peer_request_node_id(request.get_ref())?;
+        debug!(target: LOG_TARGET, "Incoming GRPC request for UnbanPeer: {}", node_id);
+
+        match self.comms.peer_manager().unban_peer(&node_id).await {
+            Ok(_) => Ok(Response::new(tari_rpc::Empty {})),
+            Err(err) if err.is_peer_not_found() => Err(Status::not_found("Peer not found")),
+            Err(err) => {
+                error!(target: LOG_TARGET, "Could not unban peer: {:?}", err);
+                Err(Status::internal(err.to_string()))
+            },
+        }
+    }
+
+    async fn dial_peer(
+        &self,
+        request: Request<tari_rpc::PeerRequest>,
+    ) -> Result<Response<tari_rpc::DialPeerResponse>, Status> {
+        self.admin_token.authenticate(&request, "DialPeer")?;
+        let node_id = peer_request_node_id(request.get_ref())?;
+        debug!(target: LOG_TARGET, "Incoming GRPC request for DialPeer: {}", node_id);
+
+        let timer = Instant::now();
+        let conn = self
+            .comms
+            .connectivity()
+            .dial_peer(node_id)
+            .await
+            .map_err(|err| Status::unavailable(format!("Dial failed: {}", err)))?;
+        let dial_time = timer.elapsed().as_millis() as u64;
+        let peer = self
+            .comms
+            .peer_manager()
+            .find_by_node_id(conn.peer_node_id())
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(tari_rpc::DialPeerResponse {
+            connection: Some(peer_connection_response(&conn, peer)),
+            dial_time,
+        }))
+    }
+
+    async fn get_mempool_fee_per_gram_stats(
+        &self,
+        request: Request<tari_rpc::GetMempoolFeePerGramStatsRequest>,
+    ) -> Result<Response<tari_rpc::GetMempoolFeePerGramStatsResponse>, Status> {
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for GetMempoolFeePerGramStats: {:?}", request
+        );
+        if request.count > FEE_PER_GRAM_STATS_LIMIT {
+            return Err(Status::invalid_argument(format!(
+                "Cannot request fee per gram stats for more than {} blocks",
+                FEE_PER_GRAM_STATS_LIMIT
+            )));
+        }
+
+        let mut handler = self.node_service.clone();
+        let tip = handler
+            .get_metadata()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .height_of_longest_chain();
+        let max_block_weight = self
+            .consensus_rules
+            .consensus_constants(tip)
+            .get_max_block_weight_excluding_coinbase();
+
+        let mut mempool_handle = self.mempool_service.clone();
+        let response = mempool_handle
+            .get_fee_per_gram_stats(request.count, max_block_weight)
+            .await
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Error submitting query:{}", e);
+                Status::internal(e.to_string())
+            })?;
+
+        let stats = response
+            .stats
+            .into_iter()
+            .map(|stat| tari_rpc::MempoolFeePerGramStat {
+                order: stat.order,
+                min_fee_per_gram: stat.min_fee_per_gram.into(),
+                avg_fee_per_gram: stat.avg_fee_per_gram.into(),
+                max_fee_per_gram: stat.max_fee_per_gram.into(),
+                total_weight: stat.total_weight,
+            })
+            .collect();
+
+        Ok(Response::new(tari_rpc::GetMempoolFeePerGramStatsResponse { stats }))
+    }
 }
 
 enum BlockGroupType {
@@ -33,6 +33,10 @@ pub const GET_BLOCKS_MAX_HEIGHTS: usize = 1000;
 // requests to the base node, but if you'd like to stream directly, this can be set to 1.
 pub const GET_BLOCKS_PAGE_SIZE: usize = 10;
 
+// The maximum total size of the blocks streamed in response to a single GetBlocks request. Clients can request a
+// smaller size.
+pub const GET_BLOCKS_MAX_RESPONSE_BYTES: usize = 64 * 1024 * 1024;
+
 /// Magic number for input and output sizes
 pub const BLOCK_INPUT_SIZE: u64 = 4;
 pub const BLOCK_OUTPUT_SIZE: u64 = 13;
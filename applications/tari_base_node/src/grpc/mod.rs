@@ -23,3 +23,4 @@
 pub mod base_node_grpc_server;
 pub mod blocks;
 pub mod helpers;
+pub mod read_only;
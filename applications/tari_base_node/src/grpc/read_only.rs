@@ -0,0 +1,298 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::grpc::base_node_grpc_server::BaseNodeGrpcServer;
+use tari_app_grpc::tari_rpc::{self, base_node_server::BaseNode};
+use tonic::{Request, Response, Status};
+
+/// The base node gRPC service without the methods that control the node or reveal its peers: submitting blocks and
+/// transactions, creating block templates, checking for updates, and listing, banning and dialing peers. It can be
+/// exposed publicly while the full service stays on a private address.
+pub struct ReadOnlyBaseNodeGrpcServer {
+    inner: BaseNodeGrpcServer,
+}
+
+impl ReadOnlyBaseNodeGrpcServer {
+    pub fn new(inner: BaseNodeGrpcServer) -> Self {
+        Self { inner }
+    }
+}
+
+fn admin_only(method: &str) -> Status {
+    Status::permission_denied(format!(
+        "{} is not available on the read-only gRPC address, use the grpc_base_node_address",
+        method
+    ))
+}
+
+#[tonic::async_trait]
+impl BaseNode for ReadOnlyBaseNodeGrpcServer {
+    type FetchMatchingUtxosStream = <BaseNodeGrpcServer as BaseNode>::FetchMatchingUtxosStream;
+    type GetBlocksStream = <BaseNodeGrpcServer as BaseNode>::GetBlocksStream;
+    type GetMempoolTransactionsStream = <BaseNodeGrpcServer as BaseNode>::GetMempoolTransactionsStream;
+    type GetNetworkDifficultyStream = <BaseNodeGrpcServer as BaseNode>::GetNetworkDifficultyStream;
+    type GetPeersStream = <BaseNodeGrpcServer as BaseNode>::GetPeersStream;
+    type GetTokensInCirculationStream = <BaseNodeGrpcServer as BaseNode>::GetTokensInCirculationStream;
+    type ListHeadersStream = <BaseNodeGrpcServer as BaseNode>::ListHeadersStream;
+    type SearchKernelsStream = <BaseNodeGrpcServer as BaseNode>::SearchKernelsStream;
+
+    async fn get_network_difficulty(
+        &self,
+        request: Request<tari_rpc::HeightRequest>,
+    ) -> Result<Response<Self::GetNetworkDifficultyStream>, Status> {
+        self.inner.get_network_difficulty(request).await
+    }
+
+    async fn get_mempool_transactions(
+        &self,
+        request: Request<tari_rpc::GetMempoolTransactionsRequest>,
+    ) -> Result<Response<Self::GetMempoolTransactionsStream>, Status> {
+        self.inner.get_mempool_transactions(request).await
+    }
+
+    async fn list_headers(
+        &self,
+        request: Request<tari_rpc::ListHeadersRequest>,
+    ) -> Result<Response<Self::ListHeadersStream>, Status> {
+        self.inner.list_headers(request).await
+    }
+
+    async fn get_new_block_template(
+        &self,
+        _request: Request<tari_rpc::NewBlockTemplateRequest>,
+    ) -> Result<Response<tari_rpc::NewBlockTemplateResponse>, Status> {
+        Err(admin_only("GetNewBlockTemplate"))
+    }
+
+    async fn get_new_block(
+        &self,
+        _request: Request<tari_rpc::NewBlockTemplate>,
+    ) -> Result<Response<tari_rpc::GetNewBlockResult>, Status> {
+        Err(admin_only("GetNewBlock"))
+    }
+
+    async fn submit_block(
+        &self,
+        _request: Request<tari_rpc::Block>,
+    ) -> Result<Response<tari_rpc::SubmitBlockResponse>, Status> {
+        Err(admin_only("SubmitBlock"))
+    }
+
+    async fn submit_transaction(
+        &self,
+        _request: Request<tari_rpc::SubmitTransactionRequest>,
+    ) -> Result<Response<tari_rpc::SubmitTransactionResponse>, Status> {
+        Err(admin_only("SubmitTransaction"))
+    }
+
+    async fn transaction_state(
+        &self,
+        request: Request<tari_rpc::TransactionStateRequest>,
+    ) -> Result<Response<tari_rpc::TransactionStateResponse>, Status> {
+        self.inner.transaction_state(request).await
+    }
+
+    async fn get_peers(
+        &self,
+        _request: Request<tari_rpc::GetPeersRequest>,
+    ) -> Result<Response<Self::GetPeersStream>, Status> {
+        Err(admin_only("GetPeers"))
+    }
+
+    async fn get_blocks(
+        &self,
+        request: Request<tari_rpc::GetBlocksRequest>,
+    ) -> Result<Response<Self::GetBlocksStream>, Status> {
+        self.inner.get_blocks(request).await
+    }
+
+    async fn get_tip_info(
+        &self,
+        request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::TipInfoResponse>, Status> {
+        self.inner.get_tip_info(request).await
+    }
+
+    async fn search_kernels(
+        &self,
+        request: Request<tari_rpc::SearchKernelsRequest>,
+    ) -> Result<Response<Self::SearchKernelsStream>, Status> {
+        self.inner.search_kernels(request).await
+    }
+
+    async fn fetch_matching_utxos(
+        &self,
+        request: Request<tari_rpc::FetchMatchingUtxosRequest>,
+    ) -> Result<Response<Self::FetchMatchingUtxosStream>, Status> {
+        self.inner.fetch_matching_utxos(request).await
+    }
+
+    async fn get_calc_timing(
+        &self,
+        request: Request<tari_rpc::HeightRequest>,
+    ) -> Result<Response<tari_rpc::CalcTimingResponse>, Status> {
+        self.inner.get_calc_timing(request).await
+    }
+
+    async fn get_block_timing(
+        &self,
+        request: Request<tari_rpc::HeightRequest>,
+    ) -> Result<Response<tari_rpc::BlockTimingResponse>, Status> {
+        self.inner.get_block_timing(request).await
+    }
+
+    async fn get_constants(
+        &self,
+        request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::ConsensusConstants>, Status> {
+        self.inner.get_constants(request).await
+    }
+
+    async fn get_block_size(
+        &self,
+        request: Request<tari_rpc::BlockGroupRequest>,
+    ) -> Result<Response<tari_rpc::BlockGroupResponse>, Status> {
+        self.inner.get_block_size(request).await
+    }
+
+    async fn get_block_fees(
+        &self,
+        request: Request<tari_rpc::BlockGroupRequest>,
+    ) -> Result<Response<tari_rpc::BlockGroupResponse>, Status> {
+        self.inner.get_block_fees(request).await
+    }
+
+    async fn get_version(&self, request: Request<tari_rpc::Empty>) -> Result<Response<tari_rpc::StringValue>, Status> {
+        self.inner.get_version(request).await
+    }
+
+    async fn check_for_updates(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::SoftwareUpdate>, Status> {
+        Err(admin_only("CheckForUpdates"))
+    }
+
+    async fn get_tokens_in_circulation(
+        &self,
+        request: Request<tari_rpc::GetBlocksRequest>,
+    ) -> Result<Response<Self::GetTokensInCirculationStream>, Status> {
+        self.inner.get_tokens_in_circulation(request).await
+    }
+
+    async fn get_sync_info(
+        &self,
+        request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::SyncInfoResponse>, Status> {
+        self.inner.get_sync_info(request).await
+    }
+
+    async fn get_header_by_hash(
+        &self,
+        request: Request<tari_rpc::GetHeaderByHashRequest>,
+    ) -> Result<Response<tari_rpc::BlockHeaderResponse>, Status> {
+        self.inner.get_header_by_hash(request).await
+    }
+
+    async fn identify(&self, request: Request<tari_rpc::Empty>) -> Result<Response<tari_rpc::NodeIdentity>, Status> {
+        self.inner.identify(request).await
+    }
+
+    async fn get_network_status(
+        &self,
+        request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::NetworkStatusResponse>, Status> {
+        self.inner.get_network_status(request).await
+    }
+
+    async fn list_connected_peers(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::ListConnectedPeersResponse>, Status> {
+        Err(admin_only("ListConnectedPeers"))
+    }
+
+    async fn get_mempool_stats(
+        &self,
+        request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::MempoolStatsResponse>, Status> {
+        self.inner.get_mempool_stats(request).await
+    }
+
+    async fn get_network_hash_rate(
+        &self,
+        request: Request<tari_rpc::GetNetworkHashRateRequest>,
+    ) -> Result<Response<tari_rpc::GetNetworkHashRateResponse>, Status> {
+        self.inner.get_network_hash_rate(request).await
+    }
+
+    async fn get_mempool_fee_per_gram_stats(
+        &self,
+        request: Request<tari_rpc::GetMempoolFeePerGramStatsRequest>,
+    ) -> Result<Response<tari_rpc::GetMempoolFeePerGramStatsResponse>, Status> {
+        self.inner.get_mempool_fee_per_gram_stats(request).await
+    }
+
+    async fn get_node_events(
+        &self,
+        _request: Request<tari_rpc::GetNodeEventsRequest>,
+    ) -> Result<Response<tari_rpc::GetNodeEventsResponse>, Status> {
+        Err(admin_only("GetNodeEvents"))
+    }
+
+    async fn get_total_burnt(
+        &self,
+        request: Request<tari_rpc::GetTotalBurntRequest>,
+    ) -> Result<Response<tari_rpc::GetTotalBurntResponse>, Status> {
+        self.inner.get_total_burnt(request).await
+    }
+
+    async fn list_connections(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::ListConnectionsResponse>, Status> {
+        Err(admin_only("ListConnections"))
+    }
+
+    async fn list_banned_peers(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::ListBannedPeersResponse>, Status> {
+        Err(admin_only("ListBannedPeers"))
+    }
+
+    async fn ban_peer(&self, _request: Request<tari_rpc::BanPeerRequest>) -> Result<Response<tari_rpc::Empty>, Status> {
+        Err(admin_only("BanPeer"))
+    }
+
+    async fn unban_peer(&self, _request: Request<tari_rpc::PeerRequest>) -> Result<Response<tari_rpc::Empty>, Status> {
+        Err(admin_only("UnbanPeer"))
+    }
+
+    async fn dial_peer(
+        &self,
+        _request: Request<tari_rpc::PeerRequest>,
+    ) -> Result<Response<tari_rpc::DialPeerResponse>, Status> {
+        Err(admin_only("DialPeer"))
+    }
+}
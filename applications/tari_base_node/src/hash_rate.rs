@@ -0,0 +1,138 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Network hash rate estimation. The difficulty accumulated per proof of work algorithm between two headers, divided
+//! by the time between them, estimates the hash rate of that algorithm over the blocks in between.
+
+use tari_core::{
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainHeader, ChainStorageError},
+    proof_of_work::PowAlgorithm,
+};
+
+/// The default number of blocks in a hash rate window
+pub const DEFAULT_HASH_RATE_WINDOW: u64 = 60;
+/// The default number of windows in a hash rate report
+pub const DEFAULT_HASH_RATE_NUM_WINDOWS: u64 = 24;
+/// The maximum number of windows in a hash rate report
+pub const MAX_HASH_RATE_NUM_WINDOWS: u64 = 1000;
+
+/// The estimated hash rates, in hashes per second, over the blocks after `start_height` up to `end_height`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HashRateWindow {
+    pub start_height: u64,
+    pub end_height: u64,
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+    pub sha3: f64,
+    pub monero: f64,
+}
+
+impl HashRateWindow {
+    /// Returns `None` if `end` is not later than `start`
+    pub fn between(start: &ChainHeader, end: &ChainHeader) -> Option<Self> {
+        let start_timestamp = start.header().timestamp.as_u64();
+        let end_timestamp = end.header().timestamp.as_u64();
+        if end.height() <= start.height() || end_timestamp <= start_timestamp {
+            return None;
+        }
+        let secs = (end_timestamp - start_timestamp) as f64;
+        let start_data = start.accumulated_data();
+        let end_data = end.accumulated_data();
+        let sha3 = end_data
+            .accumulated_sha_difficulty
+            .as_u64()
+            .saturating_sub(start_data.accumulated_sha_difficulty.as_u64());
+        let monero = end_data
+            .accumulated_monero_difficulty
+            .as_u64()
+            .saturating_sub(start_data.accumulated_monero_difficulty.as_u64());
+        Some(Self {
+            start_height: start.height(),
+            end_height: end.height(),
+            start_timestamp,
+            end_timestamp,
+            sha3: sha3 as f64 / secs,
+            monero: monero as f64 / secs,
+        })
+    }
+
+    pub fn hash_rate(&self, pow_algo: PowAlgorithm) -> f64 {
+        match pow_algo {
+            PowAlgorithm::Sha3 => self.sha3,
+            PowAlgorithm::Monero => self.monero,
+        }
+    }
+}
+
+/// Estimates the hash rates over up to `num_windows` consecutive windows of `window` blocks that end at the chain tip,
+/// ordered from the oldest to the latest window. Fewer windows are returned if the chain is not long enough.
+pub async fn estimate_hash_rates<B: BlockchainBackend + 'static>(
+    db: &AsyncBlockchainDb<B>,
+    window: u64,
+    num_windows: u64,
+) -> Result<Vec<HashRateWindow>, ChainStorageError> {
+    let window = window.max(1);
+    let mut end = db.fetch_tip_header().await?;
+    let mut windows = Vec::new();
+    for _ in 0..num_windows {
+        let start_height = match end.height().checked_sub(window) {
+            Some(height) => height,
+            None => break,
+        };
+        let start = db.fetch_chain_header(start_height).await?;
+        if let Some(hash_rate) = HashRateWindow::between(&start, &end) {
+            windows.push(hash_rate);
+        }
+        end = start;
+    }
+    windows.reverse();
+    Ok(windows)
+}
+
+/// Renders the values as a text chart of one bar per value, scaled from zero to the largest value
+pub fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().cloned().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|value| {
+            if max <= 0.0 {
+                BARS[0]
+            } else {
+                BARS[((value / max) * (BARS.len() - 1) as f64).round() as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::sparkline;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0.0, 0.0]), "▁▁");
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0]), "▁▅█");
+    }
+}
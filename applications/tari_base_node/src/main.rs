@@ -56,24 +56,57 @@
 /// Subsequent runs
 /// `cargo run tari_base_node`
 ///
+/// To run in the background, controlled through a unix socket in the data directory
+/// `tari_base_node --daemon`, then `tari_base_node status` or `tari_base_node stop`
+///
+/// To run console commands from a file, e.g. from cron, and exit once they are done:
+/// `tari_base_node --command-file maintenance.txt --fail-fast`
+///
 /// ## Commands
 ///
 /// `help` - Displays a list of commands
+/// `alias` - Lists, adds or removes console aliases that expand to one or more commands
+/// `status` - Prints the status line, `status --watch <seconds>` changes how often it is printed periodically
 /// `get-balance` - Displays the balance of the wallet (available, pending incoming, pending outgoing)
 /// `send-tari` - Sends Tari, the amount needs to be specified, followed by the destination (public key or emoji id) and
 /// an optional message `get-chain-metadata` - Lists information about the blockchain of this Base Node
+/// `chain-monitor` - Shows how the local chain tip compares with the configured reference nodes
+/// `orphan-stats` - Shows the number, heights and size of the blocks in the orphan pool
 /// `list-peers` - Lists information about peers known by this base node
+/// `purge-peers` - Deletes stale, and optionally banned, peers from the peer database
+/// `export-peers` - Writes the peer database to a JSON file
+/// `import-peers` - Adds the peers in a JSON file to the peer database
 /// `ban-peer` - Bans a peer
 /// `unban-peer` - Removes a ban for a peer
+/// `block-address` - Blocks connections with an IP address, CIDR range, onion address or public key
+/// `allow-address` - Only allows connections with the allowed IP addresses, CIDR ranges, onion addresses or public keys
 /// `list-connections` - Lists active connections to this Base Node
+/// `ping-peer` - Pings a peer, `ping-peer <public key> --count <n>` sends several pings and summarises the round trips
+/// `ping-stats` - Shows the round trip time percentiles, lost pings and path quality of pinged peers
 /// `list-headers` - Lists header information. Either the first header height and the last header height needs to be
 /// specified, or the amount of headers from the top `check-db` - Checks the blockchain database for missing blocks and
 /// headers `calc-timing` - Calculates the time average time taken to mine a given range of blocks
-/// `discover-peer` - Attempts to discover a peer on the network, a public key or emoji id needs to be specified
+/// `verify-utxo-root` - Recomputes the output, kernel and witness MMR roots from the database and compares them with
+/// the headers
+/// `utxo-stats` - Counts the unspent outputs by type, maturity and size
+/// `discover-peer` - Attempts to discover a peer on the network and dial it, timing each stage. A public key or emoji
+/// id needs to be specified, `discover-peer --stats` shows the aggregate timings of previous discoveries
 /// `get-block` - Retrieves a block, the height of the block needs to be specified
+/// `get-header` - Retrieves a header by height, with its proof of work and MMR roots
+/// `get-header-by-hash` - Retrieves a header by hash, with its proof of work and MMR roots
+/// `get-network-hashrate` - Estimates the network hash rate per PoW algorithm over windows of blocks
 /// `get-mempool-stats` - Displays information about the mempool
 /// `get-mempool-state` - Displays state information for the mempool
+/// `grpc-stats` - Shows the number of gRPC calls and their latency by method
+/// `node-stats` - Shows the uptime, resource usage, disk IO and clock skew of the node
+/// `reload-config` - Re-reads the configuration file and applies the settings that can change without a restart
+/// `set-log-level` - Changes the level of a log target while the node is running
+/// `generate-debug-bundle` - Writes diagnostics for bug reports to a zip file
+/// `events` - Lists the state changes, reorgs, bans, fatal errors and update notices recorded in the event journal
 /// `whoami` - Displays identity information about this Base Node and it's wallet
+/// `rotate-identity` - Replaces the node identity and onion address, then restarts and announces the new identity
+/// `sign-message` - Signs a message with the node identity key, to prove ownership of the node
+/// `verify-message` - Checks a message signature made with `sign-message`
 /// `quit` - Exits the Base Node
 /// `exit` - Same as quit
 
@@ -84,17 +117,49 @@ mod table;
 #[macro_use]
 mod macros;
 
+mod alerts;
+mod block_timing_monitor;
 mod bootstrap;
 mod builder;
+mod chain_monitor;
 mod cli;
+mod command_batch;
 mod command_handler;
+mod config_reload;
+mod console_aliases;
+#[cfg(unix)]
+mod daemon;
+mod debug_bundle;
+mod discovery_stats;
+mod disk_space_monitor;
+mod event_journal;
 mod grpc;
+mod hash_rate;
+mod mining_distribution_monitor;
+mod mmr_verifier;
 mod parser;
+mod peer_db;
 mod recovery;
+mod service_integration;
+mod stats_writer;
 mod status_line;
+mod time_sync;
 mod utils;
-
-use crate::command_handler::{CommandHandler, StatusOutput};
+mod utxo_stats;
+
+use crate::{
+    alerts::{AlertKind, Alerter},
+    cli::{Cli, ControlCommand},
+    command_batch::CommandBatch,
+    command_handler::{CommandHandler, StatusOutput},
+    config_reload::ConfigReloader,
+    console_aliases::{ConsoleAliases, ALIASES_FILE_NAME},
+    event_journal::{EventJournal, NodeEventKind, EVENT_JOURNAL_DIR},
+    service_integration::ServiceIntegration,
+    status_line::StatusTicker,
+};
+#[cfg(unix)]
+use daemon::StartupReport;
 use futures::{pin_mut, FutureExt};
 use log::*;
 use opentelemetry::{self, global, KeyValue};
@@ -103,21 +168,34 @@ use rustyline::{config::OutputStreamType, error::ReadlineError, CompletionType,
 use std::{
     env,
     net::SocketAddr,
+    path::{Path, PathBuf},
     process,
     sync::Arc,
     time::{Duration, Instant},
 };
+use structopt::StructOpt;
+use tari_app_grpc::{
+    authentication::AdminToken,
+    instrumentation::{GrpcLimits, GrpcMetrics, RateLimit},
+};
 use tari_app_utilities::{
     consts,
     identity_management::setup_node_identity,
-    initialization::init_configuration,
-    utilities::{setup_runtime, ExitCodes},
+    initialization::{check_configuration, init_configuration_from_bootstrap},
+    telemetry,
+    utilities::{setup_runtime, ExitCodes, ExitError},
 };
 use tari_common::{configuration::bootstrap::ApplicationType, ConfigBootstrap, GlobalConfig};
-use tari_comms::{peer_manager::PeerFeatures, tor::HiddenServiceControllerError};
-use tari_shutdown::{Shutdown, ShutdownSignal};
+use tari_comms::{peer_manager::PeerFeatures, runtime::spawn_named, tor::HiddenServiceControllerError};
+use tari_core::chain_storage::ChainStorageError;
+use tari_shutdown::{
+    phases::{ShutdownHandle, ShutdownOrchestrator, ShutdownPhase},
+    Shutdown,
+    ShutdownSignal,
+};
 use tokio::{
     runtime,
+    sync::watch,
     task,
     time::{self},
 };
@@ -127,38 +205,148 @@ use tracing_subscriber::{layer::SubscriberExt, Registry};
 const LOG_TARGET: &str = "base_node::app";
 /// Application entry point
 fn main() {
-    if let Err(exit_code) = main_inner() {
-        eprintln!("{:?}", exit_code);
+    let cli = Cli::from_args();
+    let machine_readable_errors = cli.bootstrap.machine_readable_errors.clone();
+    let result = if cli.windows_service {
+        run_as_windows_service()
+    } else {
+        main_inner(cli, ServiceIntegration::new())
+    };
+    if let Err(err) = result {
+        let chain = err.chain();
         error!(
             target: LOG_TARGET,
-            "Exiting with code ({}): {:?}",
-            exit_code.as_i32(),
-            exit_code
+            "Exiting with code ({}): {:?} caused by {:?}",
+            err.as_i32(),
+            err.exit_code(),
+            chain
         );
-        process::exit(exit_code.as_i32());
+        match machine_readable_errors {
+            Some(path) => {
+                if let Err(report_err) = err.report().write(path.as_deref()) {
+                    eprintln!("{:?}", err.exit_code());
+                    eprintln!("Failed to write the exit report: {}", report_err);
+                }
+            },
+            None => {
+                eprintln!("{:?}", err.exit_code());
+                for cause in chain {
+                    eprintln!("Caused by: {}", cause);
+                }
+                eprintln!("Hint: {}", err.exit_code().hint());
+                for line in err.diagnostics() {
+                    eprintln!("  {}", line);
+                }
+            },
+        }
+        process::exit(err.as_i32());
+    }
+}
+
+fn main_inner(cli: Cli, service: ServiceIntegration) -> Result<(), ExitError> {
+    let Cli {
+        mut bootstrap,
+        daemon,
+        command,
+        command_file,
+        fail_fast,
+        seed_mode,
+        ..
+    } = cli;
+    let mut startup_report = None;
+    if daemon {
+        bootstrap.non_interactive_mode = true;
+        check_configuration(ApplicationType::BaseNode, &bootstrap)?;
+        // Detach before any threads, such as the runtime's and the logger's, are started
+        startup_report = Some(daemonize()?);
     }
+    let result = start_node(
+        bootstrap,
+        command,
+        command_file,
+        fail_fast,
+        seed_mode,
+        service,
+        &mut startup_report,
+    );
+    // The parent of a daemon that failed to start prints the error, the daemon's own streams go to /dev/null
+    if let (Err(err), Some(report)) = (&result, startup_report) {
+        report.failed(&err.to_string());
+    }
+    result
 }
 
-fn main_inner() -> Result<(), ExitCodes> {
-    let (bootstrap, node_config, _) = init_configuration(ApplicationType::BaseNode)?;
+/// Loads the configuration and runs the node. The startup report of a daemon is taken once the node is up.
+fn start_node(
+    bootstrap: ConfigBootstrap,
+    command: Option<ControlCommand>,
+    command_file: Option<PathBuf>,
+    fail_fast: bool,
+    seed_mode: bool,
+    service: ServiceIntegration,
+    startup_report: &mut Option<StartupReport>,
+) -> Result<(), ExitError> {
+    let (bootstrap, mut node_config, cfg) = init_configuration_from_bootstrap(ApplicationType::BaseNode, bootstrap)?;
+    if seed_mode {
+        node_config.seed_mode = true;
+    }
+    if let Some(command) = command {
+        return send_control_command(&node_config, command);
+    }
 
     debug!(target: LOG_TARGET, "Using configuration: {:?}", node_config);
 
+    let batch = command_file
+        .map(|path| {
+            CommandBatch::read(&path, fail_fast).map_err(|err| {
+                ExitCodes::InputError(format!("Could not read the command file {}: {}", path.display(), err))
+            })
+        })
+        .transpose()?;
+
     // Set up the Tokio runtime
     let rt = setup_runtime(&node_config).map_err(|e| {
         error!(target: LOG_TARGET, "{}", e);
         ExitCodes::UnknownError
     })?;
 
-    rt.block_on(run_node(node_config.into(), bootstrap))?;
+    let node_config = Arc::new(node_config);
+    let result = rt.block_on(run_node(
+        node_config.clone(),
+        bootstrap,
+        cfg,
+        startup_report,
+        service,
+        batch,
+    ));
     // Shutdown and send any traces
     global::shutdown_tracer_provider();
+    // Release the runtime, and with it the ports and database locks, before the updated node starts or the exit is
+    // diagnosed
+    drop(rt);
+    let restart_exe = result.map_err(|err| err.with_diagnostics(&node_config))?;
+    if let Some(exe) = restart_exe {
+        restart(&exe)?;
+    }
     Ok(())
 }
 
-/// Sets up the base node and runs the cli_loop
-async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) -> Result<(), ExitCodes> {
-    if bootstrap.tracing_enabled {
+/// Sets up the base node and runs the cli_loop, or the command batch if one is given. Returns the binary to restart
+/// with once an update was installed.
+async fn run_node(
+    node_config: Arc<GlobalConfig>,
+    bootstrap: ConfigBootstrap,
+    cfg: config::Config,
+    startup_report: &mut Option<StartupReport>,
+    service: ServiceIntegration,
+    batch: Option<CommandBatch>,
+) -> Result<Option<PathBuf>, ExitError> {
+    if cfg!(feature = "tokio-console") {
+        if bootstrap.tracing_enabled {
+            warn!(target: LOG_TARGET, "Tracing is not available when built with the tokio-console feature");
+        }
+        telemetry::enable_tokio_console();
+    } else if bootstrap.tracing_enabled {
         enable_tracing();
     }
     // Load or create the Node identity
@@ -176,73 +364,170 @@ async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) ->
             "Base node's node ID created at '{}'. Done.",
             node_config.base_node_identity_file.to_string_lossy(),
         );
-        return Ok(());
+        return Ok(None);
     }
-    // This is the main and only shutdown trigger for the system.
+    // This is the main and only shutdown trigger for the system. Once triggered, the orchestrator shuts the node down
+    // in phases.
     let shutdown = Shutdown::new();
+    let mut orchestrator = ShutdownOrchestrator::new();
 
     if bootstrap.rebuild_db {
         info!(target: LOG_TARGET, "Node is in recovery mode, entering recovery");
         recovery::initiate_recover_db(&node_config)?;
         recovery::run_recovery(&node_config)
             .await
-            .map_err(|e| ExitCodes::RecoveryError(e.to_string()))?;
-        return Ok(());
+            .map_err(|e| ExitError::new(ExitCodes::RecoveryError(e.to_string())).with_source(e))?;
+        return Ok(None);
     };
 
     if bootstrap.init {
         info!(target: LOG_TARGET, "Default configuration created. Done.");
-        return Ok(());
+        return Ok(None);
     }
 
+    // A daemon is controlled through its socket, the PID file prevents a second instance from starting
+    #[cfg(unix)]
+    let (_pid_file, control_socket) = if startup_report.is_some() {
+        let pid_file =
+            daemon::PidFile::create(node_config.data_dir.join(daemon::PID_FILE_NAME)).map_err(io_exit_error)?;
+        let control_socket =
+            daemon::ControlSocket::bind(node_config.data_dir.join(daemon::SOCKET_FILE_NAME)).map_err(io_exit_error)?;
+        (Some(pid_file), Some(control_socket))
+    } else {
+        (None, None)
+    };
+
+    let mempool_config =
+        config_reload::load_mempool_config(&cfg).map_err(|err| ExitCodes::ConfigError(err.to_string()))?;
+    let (mempool_config, mempool_config_updates) = watch::channel(mempool_config);
+
+    let event_journal = EventJournal::open(
+        node_config.data_dir.join(EVENT_JOURNAL_DIR),
+        node_config.event_journal_max_events,
+    )
+    .map_err(|err| ExitError::new(ExitCodes::DatabaseError(err.to_string())).with_source(err))?;
+    event_journal.record(
+        NodeEventKind::Started,
+        format!(
+            "Tari base node v{} started on {}",
+            consts::APP_VERSION,
+            node_config.network
+        ),
+    );
+
+    let alerter = Alerter::new(
+        &node_config,
+        format!("{} {}", node_config.network, node_identity.public_key()),
+    )
+    .map_err(|err| ExitError::new(ExitCodes::ConfigError(err.to_string())).with_source(err))?;
+
     // Build, node, build!
     let ctx = builder::configure_and_initialize_node(
         node_config.clone(),
+        mempool_config_updates,
         node_identity,
-        shutdown.to_signal(),
+        orchestrator.phase_signal(ShutdownPhase::StopAcceptingWork),
+        orchestrator.phase_signal(ShutdownPhase::CloseComms),
         bootstrap.clean_orphans_db,
+        event_journal.clone(),
+        alerter.clone(),
     )
-    .await
-    .map_err(|err| {
-        for boxed_error in err.chain() {
-            if let Some(HiddenServiceControllerError::TorControlPortOffline) =
-                boxed_error.downcast_ref::<HiddenServiceControllerError>()
-            {
-                println!("Unable to connect to the Tor control port.");
-                println!(
-                    "Please check that you have the Tor proxy running and that access to the Tor control port is \
-                     turned on.",
-                );
-                println!("If you are unsure of what to do, use the following command to start the Tor proxy:");
-                println!(
-                    "tor --allow-missing-torrc --ignore-missing-torrc --clientonly 1 --socksport 9050 --controlport \
-                     127.0.0.1:9051 --log \"notice stdout\" --clientuseipv6 1",
-                );
-                return ExitCodes::TorOffline;
-            }
-
-            // todo: find a better way to do this
-            if boxed_error.to_string().contains("Invalid force sync peer") {
-                println!("Please check your force sync peers configuration");
-                return ExitCodes::ConfigError(boxed_error.to_string());
-            }
-        }
-        ExitCodes::UnknownError
-    })?;
+    .await;
+    let ctx = match ctx {
+        Ok(ctx) => ctx,
+        Err(err) => {
+            let message = format!("Initialization failed: {:#}", err);
+            event_journal.record(NodeEventKind::FatalError, message.clone());
+            alerter.send(AlertKind::FatalError, &message).await;
+            return Err(ExitError::new(initialization_exit_code(&err)).with_source(err));
+        },
+    };
 
-    if node_config.grpc_enabled {
+    let grpc_metrics = if node_config.grpc_enabled {
         // Go, GRPC, go go
-        let grpc = crate::grpc::base_node_grpc_server::BaseNodeGrpcServer::from_base_node_context(&ctx);
-        task::spawn(run_grpc(grpc, node_config.grpc_base_node_address, shutdown.to_signal()));
-    }
+        let grpc_metrics = GrpcMetrics::new(GrpcLimits {
+            slow_request_threshold: node_config.grpc_slow_request_threshold,
+            rate_limit: RateLimit::new(node_config.grpc_rate_limit, node_config.grpc_rate_limit_burst),
+        });
+        let admin_token = AdminToken::new(node_config.grpc_base_node_admin_token.clone());
+        if !admin_token.is_set() {
+            warn!(
+                target: LOG_TARGET,
+                "No grpc_base_node_admin_token is set, anyone who can reach {} can submit blocks and manage peers",
+                node_config.grpc_base_node_address
+            );
+        }
+        let grpc = crate::grpc::base_node_grpc_server::BaseNodeGrpcServer::from_base_node_context(&ctx)
+            .with_admin_token(admin_token);
+        let grpc_shutdown = orchestrator.register("grpc", ShutdownPhase::StopAcceptingWork);
+        spawn_named(
+            "base_node::grpc",
+            run_grpc(
+                grpc,
+                grpc_metrics.clone(),
+                node_config.grpc_base_node_address,
+                grpc_shutdown,
+            ),
+        );
+        if let Some(address) = node_config.grpc_base_node_read_only_address {
+            let grpc = crate::grpc::read_only::ReadOnlyBaseNodeGrpcServer::new(
+                crate::grpc::base_node_grpc_server::BaseNodeGrpcServer::from_base_node_context(&ctx),
+            );
+            let grpc_shutdown = orchestrator.register("grpc_read_only", ShutdownPhase::StopAcceptingWork);
+            spawn_named(
+                "base_node::grpc_read_only",
+                run_grpc(grpc, grpc_metrics.clone(), address, grpc_shutdown),
+            );
+        }
+        Some(grpc_metrics)
+    } else {
+        None
+    };
 
     // Run, node, run!
-    let command_handler = Arc::new(CommandHandler::new(runtime::Handle::current(), &ctx));
-    if bootstrap.non_interactive_mode {
-        task::spawn(status_loop(command_handler, shutdown));
+    let config_reloader = ConfigReloader::new(bootstrap.clone(), cfg, mempool_config, &ctx);
+    let command_handler = Arc::new(CommandHandler::new(
+        runtime::Handle::current(),
+        &ctx,
+        config_reloader,
+        grpc_metrics,
+    ));
+    let restart_notifier = command_handler.restart_notifier();
+    let shutdown_signal = shutdown.to_signal();
+    service.spawn_state_notifier(ctx.get_state_machine_info_channel(), shutdown.to_signal());
+    #[cfg(unix)]
+    spawn_named(
+        "base_node::config_reloader",
+        reload_config_on_sighup(command_handler.clone(), shutdown.to_signal()),
+    );
+    let aliases = ConsoleAliases::load(
+        &node_config.console_aliases,
+        node_config.data_dir.join(ALIASES_FILE_NAME),
+    );
+    let mut batch_task = None;
+    if let Some(batch) = batch {
+        info!(
+            target: LOG_TARGET,
+            "Running {} commands from the command file",
+            batch.commands().len()
+        );
+        let parser = Parser::new(command_handler, aliases);
+        batch_task = Some(spawn_named("base_node::command_batch", batch.run(parser, shutdown)));
+    } else if bootstrap.non_interactive_mode {
+        spawn_named(
+            "base_node::status_loop",
+            status_loop(command_handler.clone(), shutdown.to_signal()),
+        );
+        #[cfg(unix)]
+        if let Some(control_socket) = control_socket {
+            spawn_named(
+                "base_node::control_socket",
+                control_socket.serve(command_handler, shutdown),
+            );
+        }
         println!("Node started in non-interactive mode (pid = {})", process::id());
     } else {
-        let parser = Parser::new(command_handler);
+        let parser = Parser::new(command_handler, aliases);
         cli::print_banner(parser.get_commands(), 3);
 
         info!(
@@ -250,7 +535,10 @@ async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) ->
             "Node has been successfully configured and initialized. Starting CLI loop."
         );
 
-        task::spawn(cli_loop(parser, shutdown));
+        spawn_named("base_node::cli", cli_loop(parser, shutdown));
+    }
+    if let Some(report) = startup_report.take() {
+        report.started();
     }
     if !node_config.force_sync_peers.is_empty() {
         warn!(
@@ -259,9 +547,200 @@ async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) ->
         );
     }
 
-    ctx.run().await;
+    let blockchain_db = ctx.blockchain_db();
+    let report = ctx.run(service.wait_for_shutdown(shutdown_signal), orchestrator).await;
+    for service in report.services() {
+        info!(target: LOG_TARGET, "{}", service);
+    }
+    if report.is_clean() {
+        event_journal.record(NodeEventKind::Stopped, "The node shut down");
+    } else {
+        println!("The node did not shut down cleanly:");
+        print!("{}", report);
+        let message = format!(
+            "The node did not shut down cleanly: {}",
+            report.to_string().lines().map(str::trim).collect::<Vec<_>>().join("; ")
+        );
+        event_journal.record(NodeEventKind::Stopped, message.clone());
+        alerter.send(AlertKind::FatalError, &message).await;
+    }
+
+    if let Some(batch_task) = batch_task {
+        let batch_report = batch_task
+            .await
+            .map_err(|err| ExitCodes::CommandError(err.to_string()))?;
+        println!(
+            "Ran {} of {} commands, {} failed",
+            batch_report.run,
+            batch_report.total,
+            batch_report.failed.len()
+        );
+        if !batch_report.is_success() {
+            return Err(ExitCodes::CommandError(format!("Failed commands: {}", batch_report.failed.join(", "))).into());
+        }
+    }
+
+    if blockchain_db.is_read_only() {
+        return Err(ExitCodes::InsufficientDiskSpace(format!(
+            "The node was in read-only mode because the free disk space for {} was below {} MiB",
+            node_config.data_dir.display(),
+            node_config.disk_space_read_only_mb
+        ))
+        .into());
+    }
+
+    let restart_exe = restart_notifier.borrow().clone();
+    if restart_exe.is_none() {
+        println!("Goodbye!");
+    }
+    Ok(restart_exe)
+}
+
+/// Prints advice for known causes of a failed node initialization and returns the matching exit code
+fn initialization_exit_code(err: &anyhow::Error) -> ExitCodes {
+    for boxed_error in err.chain() {
+        if let Some(HiddenServiceControllerError::TorControlPortOffline) =
+            boxed_error.downcast_ref::<HiddenServiceControllerError>()
+        {
+            println!("Unable to connect to the Tor control port.");
+            println!(
+                "Please check that you have the Tor proxy running and that access to the Tor control port is turned \
+                 on.",
+            );
+            println!("If you are unsure of what to do, use the following command to start the Tor proxy:");
+            println!(
+                "tor --allow-missing-torrc --ignore-missing-torrc --clientonly 1 --socksport 9050 --controlport \
+                 127.0.0.1:9051 --log \"notice stdout\" --clientuseipv6 1",
+            );
+            return ExitCodes::TorOffline;
+        }
+
+        match boxed_error.downcast_ref::<ChainStorageError>() {
+            Some(err @ ChainStorageError::CorruptedDatabase(_)) |
+            Some(err @ ChainStorageError::DataInconsistencyDetected { .. }) => {
+                return ExitCodes::DbInconsistentState(err.to_string());
+            },
+            Some(err) => return ExitCodes::DatabaseError(err.to_string()),
+            None => {},
+        }
+
+        // todo: find a better way to do this
+        if boxed_error.to_string().contains("Invalid force sync peer") {
+            println!("Please check your force sync peers configuration");
+            return ExitCodes::ConfigError(boxed_error.to_string());
+        }
+    }
+    ExitCodes::UnknownError
+}
+
+/// Reloads the configuration whenever the process receives SIGHUP
+#[cfg(unix)]
+async fn reload_config_on_sighup(command_handler: Arc<CommandHandler>, mut shutdown_signal: ShutdownSignal) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            warn!(target: LOG_TARGET, "Could not listen for SIGHUP: {}", err);
+            return;
+        },
+    };
+    loop {
+        tokio::select! {
+            Some(_) = hangup.recv() => {
+                info!(target: LOG_TARGET, "SIGHUP received, reloading the configuration");
+                command_handler.reload_config();
+            },
+            _ = shutdown_signal.wait() => {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn io_exit_error(err: std::io::Error) -> ExitError {
+    ExitError::new(ExitCodes::IOError(err.to_string())).with_source(err)
+}
+
+/// Daemon mode is not supported, so there is never a parent process to report the startup to
+#[cfg(not(unix))]
+enum StartupReport {}
+
+#[cfg(not(unix))]
+impl StartupReport {
+    fn started(self) {
+        match self {}
+    }
+
+    fn failed(self, _: &str) {
+        match self {}
+    }
+}
+
+#[cfg(unix)]
+fn daemonize() -> Result<StartupReport, ExitError> {
+    daemon::daemonize().map_err(io_exit_error)
+}
+
+#[cfg(not(unix))]
+fn daemonize() -> Result<StartupReport, ExitError> {
+    Err(ExitCodes::CommandError("Daemon mode is only supported on unix".to_string()).into())
+}
+
+#[cfg(windows)]
+fn run_as_windows_service() -> Result<(), ExitError> {
+    service_integration::windows::run_as_service().map_err(|err| {
+        ExitError::new(ExitCodes::CommandError(format!(
+            "Could not connect to the Service Control Manager: {}",
+            err
+        )))
+        .with_source(err)
+    })
+}
+
+#[cfg(not(windows))]
+fn run_as_windows_service() -> Result<(), ExitError> {
+    Err(ExitCodes::CommandError("Running as a Windows service is only supported on Windows".to_string()).into())
+}
 
-    println!("Goodbye!");
+/// Sends a command to the base node running as a daemon and prints its reply
+#[cfg(unix)]
+fn send_control_command(node_config: &GlobalConfig, command: ControlCommand) -> Result<(), ExitError> {
+    let socket_path = node_config.data_dir.join(daemon::SOCKET_FILE_NAME);
+    let reply = daemon::send_command(&socket_path, command).map_err(|err| {
+        ExitError::new(ExitCodes::CommandError(format!(
+            "Could not reach a running base node at {}: {}",
+            socket_path.display(),
+            err
+        )))
+        .with_source(err)
+    })?;
+    print!("{}", reply);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_control_command(_: &GlobalConfig, command: ControlCommand) -> Result<(), ExitError> {
+    Err(ExitCodes::CommandError(format!("`{}` is only supported on unix", command)).into())
+}
+
+/// Replaces the process with the given binary, started with the same arguments
+#[cfg(unix)]
+fn restart(exe: &Path) -> Result<(), ExitError> {
+    use std::os::unix::process::CommandExt;
+    println!("Restarting {}...", exe.display());
+    let err = process::Command::new(exe).args(env::args_os().skip(1)).exec();
+    Err(ExitError::new(ExitCodes::IOError(err.to_string())).with_source(err))
+}
+
+/// Starts the given binary with the same arguments, the current process exits once this returns
+#[cfg(not(unix))]
+fn restart(exe: &Path) -> Result<(), ExitError> {
+    println!("Restarting {}...", exe.display());
+    process::Command::new(exe)
+        .args(env::args_os().skip(1))
+        .spawn()
+        .map_err(|err| ExitError::new(ExitCodes::IOError(err.to_string())).with_source(err))?;
     Ok(())
 }
 
@@ -288,16 +767,17 @@ fn enable_tracing() {
 }
 
 /// Runs the gRPC server
-async fn run_grpc(
-    grpc: crate::grpc::base_node_grpc_server::BaseNodeGrpcServer,
+async fn run_grpc<S: tari_app_grpc::tari_rpc::base_node_server::BaseNode>(
+    grpc: S,
+    metrics: GrpcMetrics,
     grpc_address: SocketAddr,
-    interrupt_signal: ShutdownSignal,
+    shutdown: ShutdownHandle,
 ) -> Result<(), anyhow::Error> {
     info!(target: LOG_TARGET, "Starting GRPC on {}", grpc_address);
 
     Server::builder()
-        .add_service(tari_app_grpc::tari_rpc::base_node_server::BaseNodeServer::new(grpc))
-        .serve_with_shutdown(grpc_address, interrupt_signal.map(|_| ()))
+        .add_service(metrics.layer(tari_app_grpc::tari_rpc::base_node_server::BaseNodeServer::new(grpc)))
+        .serve_with_shutdown(grpc_address, shutdown.to_signal().map(|_| ()))
         .await
         .map_err(|err| {
             error!(target: LOG_TARGET, "GRPC encountered an  error:{}", err);
@@ -305,6 +785,7 @@ async fn run_grpc(
         })?;
 
     info!(target: LOG_TARGET, "Stopping GRPC");
+    shutdown.complete();
     Ok(())
 }
 
@@ -336,26 +817,27 @@ async fn read_command(mut rustyline: Editor<Parser>) -> Result<(String, Editor<P
     .expect("Could not spawn rustyline task")
 }
 
-fn status_interval(start_time: Instant) -> time::Sleep {
-    let duration = match start_time.elapsed().as_secs() {
-        0..=120 => Duration::from_secs(5),
-        _ => Duration::from_secs(30),
-    };
-    time::sleep(duration)
+/// Returns the time until the next status line, or `None` if the status ticker is off
+fn status_interval(start_time: Instant, ticker: &watch::Receiver<StatusTicker>) -> Option<time::Sleep> {
+    ticker.borrow().interval(start_time.elapsed()).map(time::sleep)
 }
 
-async fn status_loop(command_handler: Arc<CommandHandler>, shutdown: Shutdown) {
+async fn status_loop(command_handler: Arc<CommandHandler>, mut shutdown_signal: ShutdownSignal) {
     let start_time = Instant::now();
-    let mut shutdown_signal = shutdown.to_signal();
+    let mut status_ticker = command_handler.status_ticker();
     loop {
-        let interval = status_interval(start_time);
+        let interval = status_interval(start_time, &status_ticker);
+        let ticker_on = interval.is_some();
+        let interval = interval.unwrap_or_else(|| time::sleep(Duration::from_secs(3600)));
         tokio::select! {
             biased;
             _ = shutdown_signal.wait() => {
                 break;
             }
 
-            _ = interval => {
+            Ok(_) = status_ticker.changed() => {},
+
+            _ = interval, if ticker_on => {
                command_handler.status(StatusOutput::Log);
             },
         }
@@ -385,8 +867,12 @@ async fn cli_loop(parser: Parser, mut shutdown: Shutdown) {
     let mut shutdown_signal = shutdown.to_signal();
     let start_time = Instant::now();
     let mut software_update_notif = command_handler.get_software_updater().new_update_notifier().clone();
+    let mut restart_notifier = command_handler.restart_notifier();
+    let mut status_ticker = command_handler.status_ticker();
     loop {
-        let interval = status_interval(start_time);
+        let interval = status_interval(start_time, &status_ticker);
+        let ticker_on = interval.is_some();
+        let interval = interval.unwrap_or_else(|| time::sleep(Duration::from_secs(3600)));
         tokio::select! {
             res = &mut read_command_fut => {
                 match res {
@@ -416,7 +902,14 @@ async fn cli_loop(parser: Parser, mut shutdown: Shutdown) {
                     );
                 }
             }
-            _ = interval => {
+            Ok(_) = restart_notifier.changed() => {
+                if restart_notifier.borrow().is_some() {
+                    info!(target: LOG_TARGET, "Restarting the base node to complete the software update");
+                    let _ = shutdown.trigger();
+                }
+            },
+            Ok(_) = status_ticker.changed() => {},
+            _ = interval, if ticker_on => {
                command_handler.status(StatusOutput::Full);
             },
             _ = shutdown_signal.wait() => {
@@ -0,0 +1,482 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Rolling share of the blocks at the chain tip per proof of work algorithm and per miner signature. The monitor
+//! follows the block event stream and alerts when a single miner signature mines more than the configured share of
+//! the recent blocks of its algorithm, which is an early sign that one entity is approaching a majority of that
+//! algorithm's hash rate.
+//!
+//! Blocks do not identify their miner, so the signature is a heuristic: the leading bytes of the extra nonce of the
+//! Monero coinbase transaction, which pools use to tag their blocks, and the upper bytes of the nonce of SHA3 blocks,
+//! which miners commonly partition between their workers.
+
+use crate::alerts::{AlertKind, Alerter};
+use chrono::{DateTime, Utc};
+use log::*;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+};
+use tari_common_types::types::HashOutput;
+use tari_core::{
+    base_node::comms_interface::{BlockEvent, BlockEventReceiver},
+    blocks::BlockHeader,
+    chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, LMDBDatabase},
+    proof_of_work::{
+        monero_rx::{extract_extra_nonce, MoneroPowData},
+        PowAlgorithm,
+    },
+};
+use tari_crypto::tari_utilities::{hex::to_hex, Hashable};
+use tari_shutdown::ShutdownSignal;
+use tokio::sync::{broadcast::error::RecvError, watch};
+
+const LOG_TARGET: &str = "base_node::mining_distribution_monitor";
+
+/// The share of an algorithm's miners is only checked once the window holds this many blocks of the algorithm
+const MIN_ALGO_BLOCKS: usize = 10;
+/// The number of leading extra nonce bytes of a Monero coinbase used as the miner signature
+const EXTRA_NONCE_SIGNATURE_LEN: usize = 4;
+/// The number of alerts kept in the status
+const MAX_RECENT_ALERTS: usize = 20;
+
+/// A heuristic identifier of the entity that mined the block, see the module documentation
+pub fn miner_signature(header: &BlockHeader) -> String {
+    match header.pow_algo() {
+        PowAlgorithm::Sha3 => format!("nonce:{:04x}", header.nonce >> 48),
+        PowAlgorithm::Monero => match MoneroPowData::from_header(header) {
+            Ok(monero_data) => match extract_extra_nonce(&monero_data) {
+                Some(nonce) => format!("extra:{}", to_hex(&nonce[..nonce.len().min(EXTRA_NONCE_SIGNATURE_LEN)])),
+                None => "extra:none".to_string(),
+            },
+            Err(_) => "unknown".to_string(),
+        },
+    }
+}
+
+/// A miner signature that mined more than the alert threshold of the recent blocks of its algorithm
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinerConcentration {
+    pub height: u64,
+    pub pow_algo: PowAlgorithm,
+    pub signature: String,
+    pub count: usize,
+    /// The number of blocks of the algorithm in the window
+    pub num_blocks: usize,
+}
+
+impl MinerConcentration {
+    pub fn share(&self) -> f64 {
+        self.count as f64 / self.num_blocks as f64
+    }
+}
+
+impl fmt::Display for MinerConcentration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Miner signature {} mined {} of the last {} {:?} blocks up to #{} ({:.0}%)",
+            self.signature,
+            self.count,
+            self.num_blocks,
+            self.pow_algo,
+            self.height,
+            self.share() * 100.0
+        )
+    }
+}
+
+/// The number of blocks mined by each miner signature of an algorithm, most blocks first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlgoDistribution {
+    pub num_blocks: usize,
+    pub miners: Vec<(String, usize)>,
+}
+
+impl AlgoDistribution {
+    /// The miner signature with the most blocks and its share of the algorithm's blocks
+    pub fn top_miner(&self) -> Option<(&str, f64)> {
+        self.miners
+            .first()
+            .map(|(signature, count)| (signature.as_str(), *count as f64 / self.num_blocks as f64))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MiningSample {
+    hash: HashOutput,
+    pow_algo: PowAlgorithm,
+    signature: String,
+}
+
+/// Keeps the miner signatures of the most recent blocks of the chain and detects concentrations as blocks are added
+pub struct MiningDistributionAnalyzer {
+    window: usize,
+    /// The share of an algorithm's blocks above which a miner is reported, 0 disables reports
+    threshold: f64,
+    samples: VecDeque<MiningSample>,
+    reported: HashSet<(PowAlgorithm, String)>,
+}
+
+impl MiningDistributionAnalyzer {
+    pub fn new(window: usize, threshold: f64) -> Self {
+        Self {
+            window: window.max(1),
+            threshold,
+            samples: VecDeque::with_capacity(window),
+            reported: HashSet::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.reported.clear();
+    }
+
+    pub fn num_blocks(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn distribution(&self, pow_algo: PowAlgorithm) -> AlgoDistribution {
+        let mut counts = HashMap::<&str, usize>::new();
+        for sample in self.samples.iter().filter(|s| s.pow_algo == pow_algo) {
+            *counts.entry(sample.signature.as_str()).or_default() += 1;
+        }
+        let mut miners = counts
+            .into_iter()
+            .map(|(signature, count)| (signature.to_string(), count))
+            .collect::<Vec<_>>();
+        miners.sort_by(|(a_sig, a), (b_sig, b)| b.cmp(a).then_with(|| a_sig.cmp(b_sig)));
+        AlgoDistribution {
+            num_blocks: miners.iter().map(|(_, count)| count).sum(),
+            miners,
+        }
+    }
+
+    /// Adds a block that extends the chain and returns the miner signatures of its algorithm that now exceed the
+    /// threshold. A block that does not follow the last block clears the window.
+    ///
+    /// A miner is reported once, and again only after its share has dropped below the threshold.
+    pub fn add_block(&mut self, header: &BlockHeader) -> Vec<MinerConcentration> {
+        if self.samples.back().map(|s| s.hash != header.prev_hash).unwrap_or(false) {
+            self.clear();
+        }
+        let pow_algo = header.pow_algo();
+        self.samples.push_back(MiningSample {
+            hash: header.hash(),
+            pow_algo,
+            signature: miner_signature(header),
+        });
+        while self.samples.len() > self.window {
+            self.samples.pop_front();
+        }
+
+        if self.threshold <= 0.0 {
+            return Vec::new();
+        }
+        let distribution = self.distribution(pow_algo);
+        if distribution.num_blocks < MIN_ALGO_BLOCKS {
+            return Vec::new();
+        }
+        let mut concentrations = Vec::new();
+        for (signature, count) in &distribution.miners {
+            let key = (pow_algo, signature.clone());
+            if *count as f64 / distribution.num_blocks as f64 <= self.threshold {
+                self.reported.remove(&key);
+            } else if self.reported.insert(key) {
+                concentrations.push(MinerConcentration {
+                    height: header.height,
+                    pow_algo,
+                    signature: signature.clone(),
+                    count: *count,
+                    num_blocks: distribution.num_blocks,
+                });
+            }
+        }
+        // Miners that left the window are no longer concentrated
+        self.reported
+            .retain(|(algo, signature)| *algo != pow_algo || distribution.miners.iter().any(|(s, _)| s == signature));
+        concentrations
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MiningDistributionStatus {
+    pub height: u64,
+    pub num_blocks: usize,
+    pub sha3: AlgoDistribution,
+    pub monero: AlgoDistribution,
+    pub recent_alerts: Vec<(DateTime<Utc>, MinerConcentration)>,
+}
+
+/// Handle to the latest mining distribution status
+#[derive(Clone)]
+pub struct MiningDistributionHandle {
+    status: watch::Receiver<MiningDistributionStatus>,
+    is_enabled: bool,
+}
+
+impl MiningDistributionHandle {
+    /// A handle for when the mining distribution window is set to 0
+    pub fn disabled() -> Self {
+        let (_, status) = watch::channel(MiningDistributionStatus::default());
+        Self {
+            status,
+            is_enabled: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn status(&self) -> MiningDistributionStatus {
+        self.status.borrow().clone()
+    }
+}
+
+/// Follows the blocks added to the chain and alerts when a single miner dominates an algorithm
+pub struct MiningDistributionMonitor {
+    analyzer: MiningDistributionAnalyzer,
+    window: usize,
+    db: AsyncBlockchainDb<LMDBDatabase>,
+    block_events: BlockEventReceiver,
+    alerter: Alerter,
+    status: watch::Sender<MiningDistributionStatus>,
+}
+
+impl MiningDistributionMonitor {
+    pub fn new(
+        window: usize,
+        threshold: f64,
+        db: AsyncBlockchainDb<LMDBDatabase>,
+        block_events: BlockEventReceiver,
+        alerter: Alerter,
+    ) -> (Self, MiningDistributionHandle) {
+        let (status, status_rx) = watch::channel(MiningDistributionStatus::default());
+        let handle = MiningDistributionHandle {
+            status: status_rx,
+            is_enabled: true,
+        };
+        let monitor = Self {
+            analyzer: MiningDistributionAnalyzer::new(window, threshold),
+            window,
+            db,
+            block_events,
+            alerter,
+            status,
+        };
+        (monitor, handle)
+    }
+
+    pub async fn run(mut self, mut shutdown_signal: ShutdownSignal) {
+        self.reload().await;
+        loop {
+            tokio::select! {
+                event = self.block_events.recv() => match event {
+                    Ok(event) => self.handle_event(&event).await,
+                    Err(RecvError::Lagged(n)) => {
+                        debug!(target: LOG_TARGET, "Missed {} block event(s), reloading the window", n);
+                        self.reload().await;
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown_signal.wait() => {
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn handle_event(&mut self, event: &BlockEvent) {
+        match event {
+            BlockEvent::ValidBlockAdded(block, BlockAddResult::Ok(_), _) => {
+                let concentrations = self.analyzer.add_block(&block.header);
+                self.report(concentrations);
+                self.update_status(block.header.height);
+            },
+            BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { .. }, _) |
+            BlockEvent::BlockSyncComplete(_) |
+            BlockEvent::BlockSyncRewind(_) => {
+                self.reload().await;
+            },
+            _ => {},
+        }
+    }
+
+    /// Fills the window with the headers at the chain tip. Concentrations found in these blocks are reported, since
+    /// a miner that dominated the blocks the node synced is as much of a risk as one found live.
+    async fn reload(&mut self) {
+        let tip = match self.db.get_chain_metadata().await {
+            Ok(metadata) => metadata.height_of_longest_chain(),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Could not read the local chain metadata: {}", err);
+                return;
+            },
+        };
+        let start = (tip + 1).saturating_sub(self.window as u64);
+        let headers = match self.db.fetch_headers(start..=tip).await {
+            Ok(headers) => headers,
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Could not fetch the headers at the chain tip: {}", err);
+                return;
+            },
+        };
+        // Keep the reported miners so that a reload does not repeat alerts that are still current
+        let reported = self.analyzer.reported.clone();
+        self.analyzer.clear();
+        let mut concentrations = Vec::new();
+        for header in &headers {
+            concentrations.extend(self.analyzer.add_block(header));
+        }
+        concentrations.retain(|c| !reported.contains(&(c.pow_algo, c.signature.clone())));
+        // Only the latest report for each miner is of interest after a reload
+        concentrations.reverse();
+        let mut seen = HashSet::new();
+        concentrations.retain(|c| seen.insert((c.pow_algo, c.signature.clone())));
+        self.report(concentrations);
+        self.update_status(tip);
+    }
+
+    fn report(&mut self, concentrations: Vec<MinerConcentration>) {
+        if concentrations.is_empty() {
+            return;
+        }
+        let now = Utc::now();
+        let mut status = self.status.borrow().clone();
+        for concentration in concentrations {
+            println!("WARNING: {}", concentration);
+            self.alerter
+                .notify(AlertKind::MiningConcentration, concentration.to_string());
+            status.recent_alerts.push((now, concentration));
+        }
+        let excess = status.recent_alerts.len().saturating_sub(MAX_RECENT_ALERTS);
+        status.recent_alerts.drain(..excess);
+        let _ = self.status.send(status);
+    }
+
+    fn update_status(&self, height: u64) {
+        let mut status = self.status.borrow().clone();
+        status.height = height;
+        status.num_blocks = self.analyzer.num_blocks();
+        status.sha3 = self.analyzer.distribution(PowAlgorithm::Sha3);
+        status.monero = self.analyzer.distribution(PowAlgorithm::Monero);
+        let _ = self.status.send(status);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WINDOW: usize = 20;
+
+    fn next_header(parent: Option<&BlockHeader>, nonce_prefix: u16) -> BlockHeader {
+        let mut header = BlockHeader::new(0);
+        header.pow.pow_algo = PowAlgorithm::Sha3;
+        header.nonce = (nonce_prefix as u64) << 48;
+        if let Some(parent) = parent {
+            header.height = parent.height + 1;
+            header.prev_hash = parent.hash();
+        }
+        header
+    }
+
+    fn add_chain(
+        analyzer: &mut MiningDistributionAnalyzer,
+        tip: &mut BlockHeader,
+        nonce_prefixes: &[u16],
+    ) -> Vec<MinerConcentration> {
+        let mut concentrations = Vec::new();
+        for prefix in nonce_prefixes {
+            let header = next_header(Some(tip), *prefix);
+            concentrations.extend(analyzer.add_block(&header));
+            *tip = header;
+        }
+        concentrations
+    }
+
+    #[test]
+    fn it_uses_the_upper_nonce_bytes_as_the_sha3_signature() {
+        assert_eq!(miner_signature(&next_header(None, 0xab12)), "nonce:ab12");
+    }
+
+    #[test]
+    fn it_reports_a_dominant_miner_once() {
+        let mut analyzer = MiningDistributionAnalyzer::new(WINDOW, 0.5);
+        let mut tip = next_header(None, 1);
+        assert!(analyzer.add_block(&tip).is_empty());
+        // Below the minimum number of blocks nothing is reported
+        assert!(add_chain(&mut analyzer, &mut tip, &[2, 3, 4]).is_empty());
+        assert!(add_chain(&mut analyzer, &mut tip, &[1; 5]).is_empty());
+
+        let concentrations = add_chain(&mut analyzer, &mut tip, &[1]);
+        assert_eq!(concentrations, vec![MinerConcentration {
+            height: 9,
+            pow_algo: PowAlgorithm::Sha3,
+            signature: "nonce:0001".to_string(),
+            count: 7,
+            num_blocks: 10,
+        }]);
+        assert!(add_chain(&mut analyzer, &mut tip, &[1; 5]).is_empty());
+
+        let distribution = analyzer.distribution(PowAlgorithm::Sha3);
+        assert_eq!(distribution.num_blocks, 15);
+        assert_eq!(distribution.miners[0], ("nonce:0001".to_string(), 12));
+        assert!(analyzer.distribution(PowAlgorithm::Monero).miners.is_empty());
+    }
+
+    #[test]
+    fn it_reports_again_after_the_share_recovers() {
+        let mut analyzer = MiningDistributionAnalyzer::new(WINDOW, 0.5);
+        let mut tip = next_header(None, 1);
+        analyzer.add_block(&tip);
+        assert_eq!(add_chain(&mut analyzer, &mut tip, &[1; 11]).len(), 1);
+        // The window slides until miner 1 holds no more than half of it
+        let others = (2..22).collect::<Vec<_>>();
+        add_chain(&mut analyzer, &mut tip, &others);
+        assert_eq!(analyzer.distribution(PowAlgorithm::Sha3).top_miner().unwrap().1, 0.05);
+        assert_eq!(add_chain(&mut analyzer, &mut tip, &[1; 11]).len(), 1);
+    }
+
+    #[test]
+    fn it_does_not_report_when_the_threshold_is_zero() {
+        let mut analyzer = MiningDistributionAnalyzer::new(WINDOW, 0.0);
+        let mut tip = next_header(None, 1);
+        analyzer.add_block(&tip);
+        assert!(add_chain(&mut analyzer, &mut tip, &[1; 15]).is_empty());
+        assert_eq!(analyzer.num_blocks(), 16);
+    }
+
+    #[test]
+    fn it_clears_the_window_when_a_block_does_not_follow_the_tip() {
+        let mut analyzer = MiningDistributionAnalyzer::new(WINDOW, 0.5);
+        let mut tip = next_header(None, 1);
+        analyzer.add_block(&tip);
+        add_chain(&mut analyzer, &mut tip, &[1; 5]);
+        let mut other = next_header(None, 2);
+        other.height = 6;
+        analyzer.add_block(&other);
+        assert_eq!(analyzer.num_blocks(), 1);
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use log::*;
+use std::{cmp, fmt, ops::Range, sync::Arc};
+use tari_common_types::types::{HashDigest, HashOutput};
+use tari_core::chain_storage::{async_db::AsyncBlockchainDb, ChainStorageError, LMDBDatabase, MmrTree, PrunedOutput};
+use tari_crypto::tari_utilities::{hex::to_hex, Hashable};
+use tari_mmr::{error::MerkleMountainRangeError, MerkleMountainRange, MutableMmr};
+use thiserror::Error;
+
+const LOG_TARGET: &str = "base_node::mmr_verifier";
+
+/// The number of blocks that are loaded from the database at a time
+const BATCH_SIZE: u64 = 100;
+
+#[derive(Debug, Error)]
+pub enum MmrVerifierError {
+    #[error("Chain storage error: {0}")]
+    ChainStorageError(#[from] ChainStorageError),
+    #[error("Merkle mountain range error: {0}")]
+    MerkleMountainRangeError(#[from] MerkleMountainRangeError),
+}
+
+/// The first point at which the MMRs rebuilt from the database disagree with the block headers
+#[derive(Debug, Clone)]
+pub enum Divergence {
+    /// The root of `tree` after adding the leaves of the block at `height` does not match the header. One of the
+    /// leaves in `leaves` differs from the leaf that was committed to.
+    Root {
+        tree: MmrTree,
+        height: u64,
+        expected: HashOutput,
+        actual: HashOutput,
+        leaves: Range<u64>,
+    },
+    /// The database contains a different number of leaves for the block at `height` than the header commits to
+    LeafCount {
+        tree: MmrTree,
+        height: u64,
+        expected: u64,
+        actual: u64,
+    },
+    /// The spent output set marks a leaf as deleted that does not exist in the output MMR
+    DeletedLeaf { index: u32, leaf_count: u64 },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::Root {
+                tree,
+                height,
+                expected,
+                actual,
+                leaves,
+            } => write!(
+                f,
+                "{} MMR root mismatch at height {} (expected {}, got {}). The first divergent leaf is in the range \
+                 {}..{}",
+                tree,
+                height,
+                to_hex(expected),
+                to_hex(actual),
+                leaves.start,
+                leaves.end
+            ),
+            Divergence::LeafCount {
+                tree,
+                height,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} MMR size mismatch at height {} (expected {} leaves, got {}). The first divergent leaf is {}",
+                tree,
+                height,
+                expected,
+                actual,
+                cmp::min(*expected, *actual)
+            ),
+            Divergence::DeletedLeaf { index, leaf_count } => write!(
+                f,
+                "UTXO MMR leaf {} is marked as spent but the MMR only has {} leaves",
+                index, leaf_count
+            ),
+        }
+    }
+}
+
+/// The result of rebuilding the MMRs from the database and comparing them against the block headers
+#[derive(Debug, Clone)]
+pub struct MmrVerificationReport {
+    pub tip_height: u64,
+    pub tip_hash: HashOutput,
+    pub num_kernels: u64,
+    pub num_outputs: u64,
+    pub num_spent: u64,
+    pub divergence: Option<Divergence>,
+}
+
+impl MmrVerificationReport {
+    pub fn is_valid(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// Rebuilds the kernel, witness and output MMRs from the kernels and (pruned) outputs stored in the database and
+/// checks them against the headers from genesis up to the current tip.
+///
+/// The kernel and witness MMRs are append-only, so their roots are checked after every block. The output MMR root
+/// commits to the set of spent outputs, which is only available for the tip, so it is checked once all blocks have
+/// been added. Only output and witness hashes are needed, so this works on pruned nodes. `on_progress` is called
+/// with the height reached and the tip height after every batch of blocks.
+pub async fn verify_mmr_roots<F>(
+    db: &AsyncBlockchainDb<LMDBDatabase>,
+    mut on_progress: F,
+) -> Result<MmrVerificationReport, MmrVerifierError>
+where
+    F: FnMut(u64, u64),
+{
+    let tip = db.fetch_tip_header().await?;
+    let tip_height = tip.height();
+    let deleted = db
+        .fetch_complete_deleted_bitmap_at(tip.hash().clone())
+        .await?
+        .into_bitmap();
+    let num_spent = deleted.cardinality();
+    let deleted = Arc::new(deleted);
+    debug!(
+        target: LOG_TARGET,
+        "Verifying MMR roots up to #{} ({}) with {} spent outputs",
+        tip_height,
+        to_hex(tip.hash()),
+        num_spent
+    );
+
+    let mut kernel_mmr = MerkleMountainRange::<HashDigest, _>::new(Vec::new());
+    let mut witness_mmr = MerkleMountainRange::<HashDigest, _>::new(Vec::new());
+    let mut output_mmr = MutableMmr::<HashDigest, _>::new(Vec::new(), deleted.as_ref().clone())?;
+    let mut report = MmrVerificationReport {
+        tip_height,
+        tip_hash: tip.hash().clone(),
+        num_kernels: 0,
+        num_outputs: 0,
+        num_spent,
+        divergence: None,
+    };
+
+    let mut height = 0;
+    while height <= tip_height {
+        let batch_end = cmp::min(height + BATCH_SIZE - 1, tip_height);
+        let headers = db.fetch_headers(height..=batch_end).await?;
+        let last = match headers.last() {
+            Some(h) => h,
+            None => {
+                return Err(ChainStorageError::ValueNotFound {
+                    entity: "BlockHeader",
+                    field: "height",
+                    value: height.to_string(),
+                }
+                .into())
+            },
+        };
+
+        let kernels = if last.kernel_mmr_size > report.num_kernels {
+            db.fetch_kernels_by_mmr_position(report.num_kernels, last.kernel_mmr_size - 1)
+                .await?
+        } else {
+            Vec::new()
+        };
+        let outputs = if last.output_mmr_size > report.num_outputs {
+            db.fetch_utxos_by_mmr_position(report.num_outputs, last.output_mmr_size - 1, deleted.clone())
+                .await?
+                .0
+        } else {
+            Vec::new()
+        };
+        let mut kernels = kernels.into_iter();
+        let mut outputs = outputs.into_iter();
+
+        for header in &headers {
+            let kernel_start = report.num_kernels;
+            while report.num_kernels < header.kernel_mmr_size {
+                match kernels.next() {
+                    Some(kernel) => {
+                        kernel_mmr.push(kernel.hash())?;
+                        report.num_kernels += 1;
+                    },
+                    None => break,
+                }
+            }
+            if report.num_kernels != header.kernel_mmr_size {
+                report.divergence = Some(Divergence::LeafCount {
+                    tree: MmrTree::Kernel,
+                    height: header.height,
+                    expected: header.kernel_mmr_size,
+                    actual: report.num_kernels,
+                });
+                return Ok(report);
+            }
+            let kernel_mr = kernel_mmr.get_merkle_root()?;
+            if kernel_mr != header.kernel_mr {
+                report.divergence = Some(Divergence::Root {
+                    tree: MmrTree::Kernel,
+                    height: header.height,
+                    expected: header.kernel_mr.clone(),
+                    actual: kernel_mr,
+                    leaves: kernel_start..report.num_kernels,
+                });
+                return Ok(report);
+            }
+
+            let output_start = report.num_outputs;
+            while report.num_outputs < header.output_mmr_size {
+                let (output_hash, witness_hash) = match outputs.next() {
+                    Some(PrunedOutput::Pruned {
+                        output_hash,
+                        witness_hash,
+                    }) => (output_hash, witness_hash),
+                    Some(PrunedOutput::NotPruned { output }) => (output.hash(), output.witness_hash()),
+                    None => break,
+                };
+                output_mmr.push(output_hash)?;
+                witness_mmr.push(witness_hash)?;
+                report.num_outputs += 1;
+            }
+            if report.num_outputs != header.output_mmr_size {
+                report.divergence = Some(Divergence::LeafCount {
+                    tree: MmrTree::Utxo,
+                    height: header.height,
+                    expected: header.output_mmr_size,
+                    actual: report.num_outputs,
+                });
+                return Ok(report);
+            }
+            let witness_mr = witness_mmr.get_merkle_root()?;
+            if witness_mr != header.witness_mr {
+                report.divergence = Some(Divergence::Root {
+                    tree: MmrTree::Witness,
+                    height: header.height,
+                    expected: header.witness_mr.clone(),
+                    actual: witness_mr,
+                    leaves: output_start..report.num_outputs,
+                });
+                return Ok(report);
+            }
+        }
+
+        height = batch_end + 1;
+        on_progress(batch_end, tip_height);
+    }
+
+    if let Some(index) = deleted.maximum() {
+        if u64::from(index) >= report.num_outputs {
+            report.divergence = Some(Divergence::DeletedLeaf {
+                index,
+                leaf_count: report.num_outputs,
+            });
+            return Ok(report);
+        }
+    }
+
+    output_mmr.compress();
+    let output_mr = output_mmr.get_merkle_root()?;
+    if output_mr != tip.header().output_mr {
+        report.divergence = Some(Divergence::Root {
+            tree: MmrTree::Utxo,
+            height: tip_height,
+            expected: tip.header().output_mr.clone(),
+            actual: output_mr,
+            leaves: 0..report.num_outputs,
+        });
+    }
+
+    Ok(report)
+}
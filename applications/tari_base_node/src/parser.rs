@@ -21,7 +21,16 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::LOG_TARGET;
-use crate::command_handler::{CommandHandler, Format, StatusOutput};
+use crate::{
+    command_handler::{CommandHandler, Format, StatusOutput, PROVE_OUTPUT_MAX_HEADERS},
+    console_aliases::ConsoleAliases,
+    discovery_stats::DISCOVERY_STATS_FILE_NAME,
+    event_journal::parse_since,
+    hash_rate::{DEFAULT_HASH_RATE_NUM_WINDOWS, DEFAULT_HASH_RATE_WINDOW, MAX_HASH_RATE_NUM_WINDOWS},
+    peer_db::{self, PurgeCriteria},
+    status_line::StatusTicker,
+};
+use chrono::Utc;
 use futures::future::Either;
 use log::*;
 use rustyline::{
@@ -32,57 +41,105 @@ use rustyline::{
     Context,
 };
 use rustyline_derive::{Helper, Highlighter, Validator};
-use std::{str::FromStr, string::ToString, sync::Arc, time::Duration};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    string::ToString,
+    sync::Arc,
+    time::Duration,
+};
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
-use tari_app_utilities::utilities::{
-    either_to_node_id,
-    parse_emoji_id_or_public_key,
-    parse_emoji_id_or_public_key_or_node_id,
+use tari_app_utilities::{
+    message_signature::MessageSignature,
+    utilities::{either_to_node_id, parse_emoji_id_or_public_key, parse_emoji_id_or_public_key_or_node_id},
 };
+use tari_common::SyncMode;
 use tari_common_types::types::{Commitment, PrivateKey, PublicKey, Signature};
+use tari_comms::connection_manager::AccessRule;
 use tari_core::{
+    consensus::LinearWeights,
     crypto::tari_utilities::hex::from_hex,
     proof_of_work::PowAlgorithm,
-    tari_utilities::{hex::Hex, ByteArray},
+    tari_utilities::{hex::Hex, message_format::MessageFormat, ByteArray},
+    transactions::transaction::Transaction,
+};
+use tari_crypto::{
+    script::{ExecutionStack, TariScript},
+    tari_utilities::hex,
 };
-use tari_crypto::tari_utilities::hex;
 use tari_shutdown::Shutdown;
 
+/// The maximum number of pings sent by `ping-peer --count`
+const MAX_PING_COUNT: usize = 100;
+
 /// Enum representing commands used by the basenode
 #[derive(Clone, Copy, PartialEq, Debug, Display, EnumIter, EnumString)]
 #[strum(serialize_all = "kebab_case")]
 pub enum BaseNodeCommand {
     Help,
+    Alias,
     Version,
     CheckForUpdates,
+    ReloadConfig,
+    SetLogLevel,
+    SetSyncMode,
+    GenerateDebugBundle,
+    Events,
     Status,
     GetChainMetadata,
     GetDbStats,
+    OrphanStats,
+    ChainMonitor,
     GetPeer,
     ListPeers,
     DialPeer,
+    #[strum(to_string = "ping-peer", serialize = "ping")]
     PingPeer,
+    PingStats,
     ResetOfflinePeers,
+    PurgePeers,
+    ExportPeers,
+    ImportPeers,
     RewindBlockchain,
     BanPeer,
     UnbanPeer,
     UnbanAllPeers,
     ListBannedPeers,
+    BlockAddress,
+    AllowAddress,
     ListConnections,
+    RpcSessions,
     ListHeaders,
     CheckDb,
+    VerifyUtxoRoot,
+    RevalidateBlocks,
+    UtxoStats,
     PeriodStats,
     HeaderStats,
+    WeightAudit,
+    DebugScript,
+    ValidateTx,
     BlockTiming,
     CalcTiming,
     DiscoverPeer,
     GetBlock,
+    GetHeader,
+    GetHeaderByHash,
+    GetNetworkHashrate,
+    MiningDistribution,
     SearchUtxo,
     SearchKernel,
+    ProveOutput,
     GetMempoolStats,
     GetMempoolState,
+    GrpcStats,
+    NodeStats,
     Whoami,
+    RotateIdentity,
+    SignMessage,
+    VerifyMessage,
     GetStateInfo,
     Quit,
     Exit,
@@ -92,6 +149,7 @@ pub enum BaseNodeCommand {
 #[derive(Helper, Validator, Highlighter)]
 pub struct Parser {
     commands: Vec<String>,
+    aliases: ConsoleAliases,
     hinter: HistoryHinter,
     command_handler: Arc<CommandHandler>,
 }
@@ -104,8 +162,10 @@ impl Completer for Parser {
         let completions = self
             .commands
             .iter()
+            .map(String::as_str)
+            .chain(self.aliases.all().into_keys())
             .filter(|cmd| cmd.starts_with(line))
-            .cloned()
+            .map(ToString::to_string)
             .collect();
 
         Ok((pos, completions))
@@ -125,9 +185,10 @@ impl Hinter for Parser {
 
 impl Parser {
     /// creates a new parser struct
-    pub fn new(command_handler: Arc<CommandHandler>) -> Self {
+    pub fn new(command_handler: Arc<CommandHandler>, aliases: ConsoleAliases) -> Self {
         Parser {
             commands: BaseNodeCommand::iter().map(|x| x.to_string()).collect(),
+            aliases,
             hinter: HistoryHinter {},
             command_handler,
         }
@@ -138,20 +199,33 @@ impl Parser {
         self.commands.clone()
     }
 
-    /// This will parse the provided command and execute the task
-    pub fn handle_command(&mut self, command_str: &str, shutdown: &mut Shutdown) {
+    /// This will parse the provided command and execute the task. Returns false if the command is not valid.
+    pub fn handle_command(&mut self, command_str: &str, shutdown: &mut Shutdown) -> bool {
         if command_str.trim().is_empty() {
-            return;
+            return true;
         }
 
+        let mut is_valid = true;
+        for command in self.aliases.expand(command_str) {
+            if shutdown.is_triggered() {
+                break;
+            }
+            is_valid &= self.handle_single_command(&command, shutdown);
+        }
+        is_valid
+    }
+
+    fn handle_single_command(&mut self, command_str: &str, shutdown: &mut Shutdown) -> bool {
         let mut args = command_str.split_whitespace();
         match args.next().unwrap_or("help").parse() {
             Ok(command) => {
                 self.process_command(command, args, shutdown);
+                true
             },
             Err(_) => {
                 println!("{} is not a valid command, please enter a valid command", command_str);
                 println!("Enter help or press tab for available commands");
+                false
             },
         }
     }
@@ -176,8 +250,16 @@ impl Parser {
                         .unwrap_or(BaseNodeCommand::Help),
                 );
             },
-            Status => {
-                self.command_handler.status(StatusOutput::Full);
+            Alias => {
+                self.process_alias(args);
+            },
+            Status => match args.next() {
+                Some("--watch") => self.process_status_watch(args),
+                Some(arg) => {
+                    println!("Unknown argument '{}'", arg);
+                    self.print_help(command);
+                },
+                None => self.command_handler.status(StatusOutput::Full),
             },
             GetStateInfo => {
                 self.command_handler.state_info();
@@ -185,8 +267,26 @@ impl Parser {
             Version => {
                 self.command_handler.print_version();
             },
-            CheckForUpdates => {
-                self.command_handler.check_for_updates();
+            CheckForUpdates => match args.next() {
+                Some("--install") => self.command_handler.install_update(),
+                Some("--rollback") => self.command_handler.rollback_update(),
+                _ => self.command_handler.check_for_updates(),
+            },
+            ReloadConfig => {
+                self.command_handler.reload_config();
+            },
+            SetLogLevel => {
+                self.process_set_log_level(args);
+            },
+            SetSyncMode => {
+                self.process_set_sync_mode(args);
+            },
+            GenerateDebugBundle => {
+                self.command_handler
+                    .generate_debug_bundle(args.next().map(PathBuf::from));
+            },
+            Events => {
+                self.process_events(args);
             },
             GetChainMetadata => {
                 self.command_handler.get_chain_meta();
@@ -194,12 +294,21 @@ impl Parser {
             GetDbStats => {
                 self.command_handler.get_blockchain_db_stats();
             },
+            OrphanStats => {
+                self.command_handler.orphan_stats();
+            },
+            ChainMonitor => {
+                self.command_handler.chain_monitor_status();
+            },
             DialPeer => {
                 self.process_dial_peer(args);
             },
             PingPeer => {
                 self.process_ping_peer(args);
             },
+            PingStats => {
+                self.process_ping_stats(args);
+            },
             DiscoverPeer => {
                 self.process_discover_peer(args);
             },
@@ -212,18 +321,49 @@ impl Parser {
             ResetOfflinePeers => {
                 self.command_handler.reset_offline_peers();
             },
+            PurgePeers => {
+                self.process_purge_peers(args);
+            },
+            ExportPeers => {
+                self.command_handler.export_peers(args.next().map(PathBuf::from));
+            },
+            ImportPeers => match args.next() {
+                Some(path) => self.command_handler.import_peers(PathBuf::from(path)),
+                None => {
+                    println!("Please enter the path of the file to import");
+                    println!("import-peers <path>");
+                },
+            },
             RewindBlockchain => {
                 self.process_rewind_blockchain(args);
             },
             CheckDb => {
                 self.command_handler.check_db();
             },
+            VerifyUtxoRoot => {
+                self.command_handler.verify_utxo_root();
+            },
+            RevalidateBlocks => {
+                self.process_revalidate_blocks(args);
+            },
+            UtxoStats => {
+                self.process_utxo_stats(args);
+            },
             PeriodStats => {
                 self.process_period_stats(args);
             },
             HeaderStats => {
                 self.process_header_stats(args);
             },
+            WeightAudit => {
+                self.process_weight_audit(args);
+            },
+            DebugScript => {
+                self.process_debug_script(args);
+            },
+            ValidateTx => {
+                self.process_validate_tx(args);
+            },
             BanPeer => {
                 self.process_ban_peer(args, true);
             },
@@ -236,9 +376,18 @@ impl Parser {
             ListBannedPeers => {
                 self.command_handler.list_banned_peers();
             },
+            BlockAddress => {
+                self.process_access_rule(args, true);
+            },
+            AllowAddress => {
+                self.process_access_rule(args, false);
+            },
             ListConnections => {
                 self.command_handler.list_connections();
             },
+            RpcSessions => {
+                self.command_handler.list_rpc_sessions();
+            },
             ListHeaders => {
                 self.process_list_headers(args);
             },
@@ -248,21 +397,52 @@ impl Parser {
             GetBlock => {
                 self.process_get_block(args);
             },
+            GetHeader | GetHeaderByHash => {
+                self.process_get_header(command, args);
+            },
+            GetNetworkHashrate => {
+                self.process_get_network_hashrate(args);
+            },
+            MiningDistribution => {
+                self.command_handler.mining_distribution();
+            },
             SearchUtxo => {
                 self.process_search_utxo(args);
             },
             SearchKernel => {
                 self.process_search_kernel(args);
             },
+            ProveOutput => {
+                self.process_prove_output(args);
+            },
             GetMempoolStats => {
                 self.command_handler.get_mempool_stats();
             },
             GetMempoolState => {
                 self.command_handler.get_mempool_state();
             },
+            GrpcStats => {
+                self.command_handler.grpc_stats();
+            },
+            NodeStats => {
+                self.command_handler.node_stats();
+            },
             Whoami => {
                 self.command_handler.whoami();
             },
+            RotateIdentity => match args.next() {
+                Some("--confirm") => self.command_handler.rotate_identity(),
+                _ => {
+                    println!("This replaces the node identity and onion address, and restarts the node");
+                    println!("Run `rotate-identity --confirm` to continue");
+                },
+            },
+            SignMessage => {
+                self.process_sign_message(args);
+            },
+            VerifyMessage => {
+                self.process_verify_message(args);
+            },
             Exit | Quit => {
                 println!("Shutting down...");
                 info!(
@@ -283,8 +463,20 @@ impl Parser {
                 let joined = self.commands.join(", ");
                 println!("{}", joined);
             },
+            Alias => {
+                println!("Lists, adds or removes console aliases. An alias expands to one or more commands separated");
+                println!("by ';' and the arguments after an alias are appended to its last command.");
+                println!("alias [<name> = <command>; <command>...]");
+                println!("alias --remove <name>");
+                println!("e.g. alias st = get-state-info; get-chain-metadata");
+            },
             Status => {
                 println!("Prints out the status of this node");
+                println!("status --watch [seconds|off|default]");
+                println!(
+                    "Prints the status line every given number of seconds, turns it off or restores the default \
+                     interval"
+                );
             },
             GetStateInfo => {
                 println!("Prints out the status of the base node state machine");
@@ -294,6 +486,57 @@ impl Parser {
             },
             CheckForUpdates => {
                 println!("Checks for software updates if auto update is enabled");
+                println!("check-for-updates [--install | --rollback]");
+                println!(
+                    "--install downloads and verifies the update, installs it and restarts the node. The previous \
+                     version is kept"
+                );
+                println!("--rollback restores the version replaced by the last installed update and restarts the node");
+            },
+            ReloadConfig => {
+                println!("Re-reads the configuration file and applies the settings that can change without a restart");
+                println!("The node also reloads its configuration when it receives SIGHUP");
+                println!(
+                    "Applied: mempool.unconfirmed_pool settings and base_node.<network>.flood_ban_max_msg_count. Log \
+                     levels are reloaded from the logging configuration file according to its refresh_rate"
+                );
+            },
+            GenerateDebugBundle => {
+                println!(
+                    "Writes the configuration with secrets masked, recent log lines, chain metadata, peer counts, \
+                     state machine events and database stats to a zip file to attach to bug reports"
+                );
+                println!("generate-debug-bundle [path]");
+                println!("The bundle is written to the data directory if no path is given");
+            },
+            Events => {
+                println!(
+                    "Lists the state changes, reorgs, peer bans, fatal errors and software update notices recorded in \
+                     the event journal. The journal is kept in the data directory, so it includes events from before \
+                     the node was last restarted."
+                );
+                println!("events [--since <time>]");
+                println!(
+                    "<time> is a time (2021-09-30T12:00:00Z), a date (2021-09-30) or a period before now (30m, 12h, \
+                     7d). Defaults to 24h."
+                );
+            },
+            SetLogLevel => {
+                println!("Changes the level of a log target, and the targets below it, while the node is running");
+                println!("set-log-level [<target> <off|error|warn|info|debug|trace>]");
+                println!(
+                    "Use `root` as the target to change the default level. Without arguments the changed levels are \
+                     listed"
+                );
+            },
+            SetSyncMode => {
+                println!("Sets the strategy used to sync the blockchain. The node must be restarted to apply it");
+                println!("set-sync-mode [archival|pruned|headers-first]");
+                println!(
+                    "archival syncs and keeps every block and requires a pruning horizon of 0. pruned syncs the \
+                     horizon state and then the blocks above it. headers-first syncs every block and prunes them \
+                     afterwards. Both require a pruning horizon. Without arguments the active mode is shown"
+                );
             },
             GetChainMetadata => {
                 println!("Gets your base node chain meta data");
@@ -301,14 +544,41 @@ impl Parser {
             GetDbStats => {
                 println!("Gets your base node database stats");
             },
+            OrphanStats => {
+                println!("Shows the number, heights and size of the blocks in the orphan pool");
+            },
+            ChainMonitor => {
+                println!(
+                    "Shows how the local chain tip compared with the chain_monitor_reference_nodes at the last check"
+                );
+            },
             DialPeer => {
                 println!("Attempt to connect to a known peer");
             },
             PingPeer => {
-                println!("Send a ping to a known peer and wait for a pong reply");
+                println!("Send pings to a known peer and wait for the pong replies");
+                println!("ping-peer [hex public key or emoji id] [--count <n>]");
+                println!("`ping` is a shorter name for this command. Pings are sent one at a time, one by default");
+            },
+            PingStats => {
+                println!(
+                    "Shows the round trip times, lost pings and path quality of the peers this node has pinged, with \
+                     percentiles estimated from a round trip time histogram"
+                );
+                println!("ping-stats [hex public key, emoji id or node id]");
+                println!("Given a peer, shows its round trip time histogram");
             },
             DiscoverPeer => {
-                println!("Attempt to discover a peer on the Tari network");
+                println!(
+                    "Attempt to discover a peer on the Tari network and dial it, showing the time taken to send the \
+                     discovery, receive the response, dial the peer and exchange identities"
+                );
+                println!("discover-peer [hex public key or emoji id | --stats]");
+                println!(
+                    "--stats shows the aggregate timings of previous discoveries, which are saved to {} in the data \
+                     directory",
+                    DISCOVERY_STATS_FILE_NAME
+                );
             },
             GetPeer => {
                 println!("Get all available info about peer");
@@ -319,6 +589,30 @@ impl Parser {
             ResetOfflinePeers => {
                 println!("Clear offline flag from all peers");
             },
+            PurgePeers => {
+                println!(
+                    "Deletes peers that have been offline, or were never seen, for longer than the given number of \
+                     days from the peer database. Connected peers are kept"
+                );
+                println!("purge-peers [--banned] [--stale-days <days>]");
+                println!(
+                    "--banned also deletes banned peers, which allows them to connect again. The default is {} days",
+                    peer_db::DEFAULT_STALE_AFTER_DAYS
+                );
+            },
+            ExportPeers => {
+                println!("Writes the peer database to a JSON file");
+                println!("export-peers [path]");
+                println!("The peers are written to peers.json in the data directory if no path is given");
+            },
+            ImportPeers => {
+                println!("Adds the peers in a JSON file, such as one written by export-peers, to the peer database");
+                println!("import-peers <path>");
+                println!(
+                    "Besides peers, the file may contain strings in the `<public key>::<address>` format of the \
+                     peer_seeds setting. Known peers keep their state and gain the imported addresses"
+                );
+            },
             RewindBlockchain => {
                 println!("Rewinds the blockchain to the given height.");
                 println!("Usage: {} [new_height]", command);
@@ -336,9 +630,50 @@ impl Parser {
             ListBannedPeers => {
                 println!("Lists peers that have been banned by the node or wallet");
             },
+            BlockAddress => {
+                println!("Blocks connections to and from an IP address, CIDR range, onion address or public key");
+                println!("block-address [--remove] <address>");
+                println!(
+                    "Connected peers that match the address are disconnected. Lists the access rules if no address is \
+                     given"
+                );
+            },
+            AllowAddress => {
+                println!("Allows connections to and from an IP address, CIDR range, onion address or public key");
+                println!("allow-address [--remove] <address>");
+                println!(
+                    "Once an address is allowed, only peers matching an allowed address may connect and other \
+                     connected peers are disconnected. Lists the access rules if no address is given"
+                );
+            },
             CheckDb => {
                 println!("Checks the blockchain database for missing blocks and headers");
             },
+            VerifyUtxoRoot => {
+                println!(
+                    "Rebuilds the output, kernel and witness MMRs from the database and checks their roots against \
+                     the headers up to the tip, reporting the first divergent block. This can take a long time and \
+                     holds all the MMR leaves in memory"
+                );
+            },
+            RevalidateBlocks => {
+                println!(
+                    "Re-runs the validation of the main chain blocks in the range and prints the time taken by each \
+                     stage (proof of work, signatures, range proofs, scripts, balance and MMR roots). Failed blocks \
+                     are reported at the first stage that failed. Blocks with pruned outputs cannot be revalidated."
+                );
+                println!("revalidate-blocks <start height> <end height> [number of blocks to validate in parallel]");
+                println!("The output MMR root is not checked for historical blocks, use verify-utxo-root to check it");
+            },
+            UtxoStats => {
+                println!(
+                    "Counts the unspent outputs by type (standard, coinbase, one-sided or custom script), by the \
+                     number of blocks until they can be spent and by size. Output values are confidential and are not \
+                     included. The outputs found are cached, so later runs only scan the blocks added since."
+                );
+                println!("utxo-stats [--rescan]");
+                println!("--rescan Discards the cache and scans the UTXO set from genesis");
+            },
             HeaderStats => {
                 println!(
                     "Prints out certain stats to of the block chain in csv format for easy copy, use as follows: "
@@ -348,6 +683,38 @@ impl Parser {
                 println!("header-stats 0 1000");
                 println!("header-stats 0 1000 sample2.csv");
                 println!("header-stats 0 1000 monero-sample.csv monero");
+                println!("header-stats 0 1000 sample.parquet");
+                println!(
+                    "Files ending in .parquet are written as Parquet if the node is built with the parquet feature"
+                );
+            },
+            WeightAudit => {
+                println!(
+                    "Recomputes the weights of a range of blocks under alternative weights and reports the impact on \
+                     block fullness and fee revenue compared to the current consensus weights:"
+                );
+                println!("weight-audit [start height] [end height] [kernel weight:input weight:output weight]");
+                println!("e.g.");
+                println!("weight-audit 0 1000 3:1:13");
+            },
+            DebugScript => {
+                println!(
+                    "Executes a TariScript against an input stack and prints the script, its inputs, the execution \
+                     context and the result. Failed script validations are logged in the same format at debug level."
+                );
+                println!("debug-script [script hex] [input stack hex or '-' for an empty stack] [block height]");
+            },
+            ValidateTx => {
+                println!(
+                    "Runs the mempool validation rules against a transaction without submitting or broadcasting it, \
+                     reporting each rule checked and the first failure. Inputs, outputs and timelocks are checked \
+                     against the current tip."
+                );
+                println!("validate-tx <file or hex>");
+                println!(
+                    "The file contains the transaction as JSON or as hex encoded binary. The transaction can also be \
+                     given as hex directly."
+                );
             },
             PeriodStats => {
                 println!(
@@ -356,12 +723,22 @@ impl Parser {
                 );
                 println!(
                     "Period-stats [start time in unix timestamp] [end time in unix timestamp] [interval period time \
-                     in unix timestamp]"
+                     in unix timestamp] (dump_file)"
+                );
+                println!(
+                    "The stats are written to the file instead of the console if one is given, as Parquet if its name \
+                     ends in .parquet and the node is built with the parquet feature, otherwise as CSV"
                 );
             },
             ListConnections => {
                 println!("Lists the peer connections currently held by this node");
             },
+            RpcSessions => {
+                println!(
+                    "Lists the RPC sessions currently being served by this node, with the data sent and average \
+                     throughput of each session"
+                );
+            },
             ListHeaders => {
                 println!("List the amount of headers, can be called in the following two ways: ");
                 println!("list-headers [first header height] [last header height]");
@@ -383,20 +760,71 @@ impl Parser {
                     "[format] Optional. Supported options are 'json' and 'text'. 'text' is the default if omitted."
                 );
             },
+            GetHeader => {
+                println!("Display a header of the main chain by height, with its proof of work and MMR roots:");
+                println!("get-header [height] [format]");
+                println!(
+                    "[format] Optional. Supported options are 'json' and 'text'. 'text' is the default if omitted."
+                );
+            },
+            GetHeaderByHash => {
+                println!("Display a header by hash, with its proof of work and MMR roots:");
+                println!("get-header-by-hash [hash] [format]");
+                println!(
+                    "[format] Optional. Supported options are 'json' and 'text'. 'text' is the default if omitted."
+                );
+            },
+            GetNetworkHashrate => {
+                println!("Estimates the network hash rate per PoW algorithm from the difficulty and timestamps of the");
+                println!("headers, over consecutive windows of blocks that end at the tip:");
+                println!("get-network-hashrate [window] [number of windows]");
+                println!(
+                    "[window] Optional. The number of blocks in a window, {} if omitted.",
+                    DEFAULT_HASH_RATE_WINDOW
+                );
+                println!(
+                    "[number of windows] Optional. The number of windows to chart, {} if omitted.",
+                    DEFAULT_HASH_RATE_NUM_WINDOWS
+                );
+            },
+            MiningDistribution => {
+                println!("Shows the share of the recent blocks of each PoW algorithm mined by each miner signature.");
+                println!("Blocks do not identify their miner, so a signature only hints at the entity behind it.");
+            },
             SearchUtxo => {
                 println!(
-                    "This will search the main chain for the utxo. If the utxo is found, it will print out the block \
-                     it was found in."
+                    "Searches the UTXO set for outputs by commitment and prints the height and hash of the block each \
+                     output was mined in and whether it is spent. Spent outputs are not indexed by commitment and are \
+                     not found."
                 );
-                println!("search-utxo [hex of commitment of the utxo]");
+                println!("search-utxo <commitment> [commitment ...]");
+                println!("search-utxo --file <path>");
+                println!("Commitments are given in hex or base58. The file contains one commitment per line.");
             },
             SearchKernel => {
                 println!(
-                    "This will search the main chain for the kernel. If the kernel is found, it will print out the \
-                     block it was found in."
+                    "Searches the main chain for kernels by excess signature and prints the height and hash of the \
+                     block each kernel was mined in."
+                );
+                println!("search-kernel <public nonce> <signature> [<public nonce> <signature> ...]");
+                println!("search-kernel --file <path>");
+                println!(
+                    "Nonces and signatures are given in hex or base58. The file contains one `<public nonce> \
+                     <signature>` pair per line."
+                );
+            },
+            ProveOutput => {
+                println!(
+                    "Writes a proof that an unspent output was mined to a JSON file. The proof contains the output, a \
+                     Merkle proof of its inclusion in the block it was mined in and the headers from that block up to \
+                     the tip (at most {}). It can be verified offline with the console wallet's verify-output-proof \
+                     command.",
+                    PROVE_OUTPUT_MAX_HEADERS
+                );
+                println!("prove-output <commitment> [file]");
+                println!(
+                    "The commitment is given in hex or base58. The file defaults to output_proof_<commitment>.json"
                 );
-                println!("This searches for the kernel via the excess signature");
-                println!("search-kernel [hex of nonce] [Hex of signature]");
             },
             GetMempoolStats => {
                 println!("Retrieves your mempools stats");
@@ -404,18 +832,112 @@ impl Parser {
             GetMempoolState => {
                 println!("Retrieves your mempools state");
             },
+            GrpcStats => {
+                println!(
+                    "Shows the number of gRPC calls, rate limited calls and slow calls, and the call latency by \
+                     method since the node started"
+                );
+            },
+            NodeStats => {
+                println!(
+                    "Shows the uptime, memory use, open files, disk IO rates and runtime load of the node, how much \
+                     of the blockchain database map is used, and how far the local clock is from NTP servers and peers"
+                );
+            },
             Whoami => {
                 println!(
                     "Display identity information about this node, including: public key, node ID and the public \
                      address"
                 );
             },
+            RotateIdentity => {
+                println!(
+                    "Replaces the node identity with a new one and, when using the tor transport, creates a new onion \
+                     address. The node restarts and announces its new identity to the network"
+                );
+                println!("rotate-identity --confirm");
+                println!("The previous identity files are kept with a .bak extension");
+            },
+            SignMessage => {
+                println!(
+                    "Signs a message with the node identity key, to prove that you control this node. The signature \
+                     can be checked by anyone with verify-message and the node's public key"
+                );
+                println!("sign-message <message>");
+                println!("The message is the rest of the line, with words separated by single spaces");
+            },
+            VerifyMessage => {
+                println!("Checks that a message was signed with sign-message by the owner of a public key");
+                println!("verify-message <public key> <signature> <message>");
+                println!("The public key can be given in hex or as an emoji id");
+            },
             Exit | Quit => {
                 println!("Exits the base node");
             },
         }
     }
 
+    /// Function to process the alias command
+    fn process_alias<'a, I: Iterator<Item = &'a str>>(&mut self, args: I) {
+        let args = args.collect::<Vec<_>>();
+        match args.as_slice() {
+            [] => {
+                let aliases = self.aliases.all();
+                if aliases.is_empty() {
+                    println!("No aliases defined");
+                    return;
+                }
+                for (name, commands) in aliases {
+                    println!("{} = {}", name, commands.join("; "));
+                }
+            },
+            ["--remove", name] => match self.aliases.remove(name) {
+                Ok(true) => println!("Alias '{}' removed", name),
+                Ok(false) if self.aliases.is_configured(name) => {
+                    println!(
+                        "Alias '{}' is defined in the config file and can only be removed there",
+                        name
+                    )
+                },
+                Ok(false) => println!("No alias named '{}'", name),
+                Err(err) => println!("Failed to remove alias: {}", err),
+            },
+            _ => {
+                let definition = args.join(" ");
+                let name = definition.split('=').next().unwrap_or_default().trim();
+                if BaseNodeCommand::from_str(name).is_ok() {
+                    println!("'{}' is a command and can not be used as an alias", name);
+                    return;
+                }
+                match self.aliases.set(&definition) {
+                    Ok(name) => println!("Alias '{}' saved to {}", name, self.aliases.file().display()),
+                    Err(err) => {
+                        println!("{}", err);
+                        self.print_help(BaseNodeCommand::Alias);
+                    },
+                }
+            },
+        }
+    }
+
+    /// Function to process the status --watch command
+    fn process_status_watch<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let ticker = match args.next() {
+            None | Some("default") => StatusTicker::Default,
+            Some("off") => StatusTicker::Off,
+            Some(secs) => match secs.parse::<u64>() {
+                Ok(0) => StatusTicker::Off,
+                Ok(secs) => StatusTicker::Every(Duration::from_secs(secs)),
+                Err(_) => {
+                    println!("Interval must be a number of seconds, 'off' or 'default'");
+                    self.print_help(BaseNodeCommand::Status);
+                    return;
+                },
+            },
+        };
+        self.command_handler.set_status_ticker(ticker);
+    }
+
     /// Function to process the get-block command
     fn process_get_block<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
         let height_or_hash = match args.next() {
@@ -451,67 +973,233 @@ impl Parser {
         };
     }
 
-    /// Function to process the search utxo command
-    fn process_search_utxo<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
-        // let command_arg = args.take(4).collect::<Vec<&str>>();
-        let hex = args.next();
-        if hex.is_none() {
-            self.print_help(BaseNodeCommand::SearchUtxo);
-            return;
+    /// Function to process the get-header and get-header-by-hash commands
+    fn process_get_header<'a, I: Iterator<Item = &'a str>>(&self, command: BaseNodeCommand, mut args: I) {
+        let arg = match args.next() {
+            Some(arg) => arg,
+            None => {
+                self.print_help(command);
+                return;
+            },
+        };
+
+        let format = match args.next() {
+            Some(v) if v.to_ascii_lowercase() == "json" => Format::Json,
+            Some(v) if v.to_ascii_lowercase() == "text" => Format::Text,
+            None => Format::Text,
+            Some(_) => {
+                println!("Unrecognized format specifier");
+                self.print_help(command);
+                return;
+            },
+        };
+
+        if command == BaseNodeCommand::GetHeaderByHash {
+            match from_hex(arg) {
+                Ok(hash) => self.command_handler.get_header_by_hash(hash, format),
+                Err(_) => {
+                    println!("Invalid header hash provided. The hash must be hex encoded.");
+                    self.print_help(command);
+                },
+            }
+        } else {
+            match arg.parse::<u64>() {
+                Ok(height) => self.command_handler.get_header(height, format),
+                Err(_) => {
+                    println!("Invalid header height provided. Height must be an integer.");
+                    self.print_help(command);
+                },
+            }
         }
-        let commitment = match Commitment::from_hex(&hex.unwrap().to_string()) {
-            Ok(v) => v,
+    }
+
+    /// Function to process the get-network-hashrate command
+    fn process_get_network_hashrate<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let window = match args.next().map(u64::from_str) {
+            None => DEFAULT_HASH_RATE_WINDOW,
+            Some(Ok(window)) if window > 0 => window,
+            Some(_) => {
+                println!("The window must be a number of blocks greater than zero");
+                self.print_help(BaseNodeCommand::GetNetworkHashrate);
+                return;
+            },
+        };
+        let num_windows = match args.next().map(u64::from_str) {
+            None => DEFAULT_HASH_RATE_NUM_WINDOWS,
+            Some(Ok(num_windows)) if num_windows > 0 && num_windows <= MAX_HASH_RATE_NUM_WINDOWS => num_windows,
+            Some(_) => {
+                println!(
+                    "The number of windows must be between 1 and {}",
+                    MAX_HASH_RATE_NUM_WINDOWS
+                );
+                self.print_help(BaseNodeCommand::GetNetworkHashrate);
+                return;
+            },
+        };
+        self.command_handler.get_network_hash_rate(window, num_windows);
+    }
+
+    /// Function to process the utxo-stats command
+    /// Function to process the events command
+    fn process_events<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let since = match (args.next(), args.next()) {
+            (None, _) => "24h",
+            (Some("--since"), Some(since)) => since,
             _ => {
-                println!("Invalid commitment provided.");
-                self.print_help(BaseNodeCommand::SearchUtxo);
+                self.print_help(BaseNodeCommand::Events);
                 return;
             },
         };
-        self.command_handler.search_utxo(commitment)
+        let since = try_or_print!(parse_since(since, Utc::now()), "{error}");
+        self.command_handler.events(since);
     }
 
-    /// Function to process the search kernel command
-    fn process_search_kernel<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
-        // let command_arg = args.take(4).collect::<Vec<&str>>();
-        let hex = args.next();
-        if hex.is_none() {
-            self.print_help(BaseNodeCommand::SearchKernel);
+    fn process_utxo_stats<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let rescan = match args.next() {
+            None => false,
+            Some("--rescan") => true,
+            Some(_) => {
+                self.print_help(BaseNodeCommand::UtxoStats);
+                return;
+            },
+        };
+        self.command_handler.utxo_stats(rescan);
+    }
+
+    /// Function to process the search utxo command
+    fn process_search_utxo<'a, I: Iterator<Item = &'a str>>(&self, args: I) {
+        let values = match read_search_values(args) {
+            Ok(values) => values,
+            Err(err) => {
+                println!("{}", err);
+                return;
+            },
+        };
+        if values.is_empty() {
+            self.print_help(BaseNodeCommand::SearchUtxo);
             return;
         }
-        let public_nonce = match PublicKey::from_hex(&hex.unwrap().to_string()) {
-            Ok(v) => v,
-            _ => {
-                println!("Invalid public nonce provided.");
-                self.print_help(BaseNodeCommand::SearchKernel);
+        let mut commitments = Vec::with_capacity(values.len());
+        for value in values {
+            match parse_hex_or_base58::<Commitment>(&value) {
+                Some(commitment) => commitments.push(commitment),
+                None => {
+                    println!("Invalid commitment '{}' provided.", value);
+                    self.print_help(BaseNodeCommand::SearchUtxo);
+                    return;
+                },
+            }
+        }
+        self.command_handler.search_utxos(commitments)
+    }
+
+    /// Function to process the prove output command
+    fn process_prove_output<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let commitment = match args.next().and_then(parse_hex_or_base58::<Commitment>) {
+            Some(commitment) => commitment,
+            None => {
+                println!("Please provide a valid commitment");
+                self.print_help(BaseNodeCommand::ProveOutput);
                 return;
             },
         };
+        let filename = args
+            .next()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| format!("output_proof_{}.json", commitment.to_hex()));
+        self.command_handler.prove_output(commitment, filename)
+    }
 
-        let hex = args.next();
-        if hex.is_none() {
-            self.print_help(BaseNodeCommand::SearchKernel);
+    /// Function to process the sign-message command
+    fn process_sign_message<'a, I: Iterator<Item = &'a str>>(&self, args: I) {
+        let message = args.collect::<Vec<_>>().join(" ");
+        if message.is_empty() {
+            println!("Please provide a message to sign");
+            self.print_help(BaseNodeCommand::SignMessage);
             return;
         }
-        let signature = match PrivateKey::from_hex(&hex.unwrap().to_string()) {
-            Ok(v) => v,
-            _ => {
-                println!("Invalid signature provided.");
-                self.print_help(BaseNodeCommand::SearchKernel);
+        self.command_handler.sign_message(message)
+    }
+
+    /// Function to process the verify-message command
+    fn process_verify_message<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let public_key = match args.next().and_then(parse_emoji_id_or_public_key) {
+            Some(public_key) => public_key,
+            None => {
+                println!("Please provide a valid public key");
+                self.print_help(BaseNodeCommand::VerifyMessage);
                 return;
             },
         };
-        let kernel_sig = Signature::new(public_nonce, signature);
+        let signature = match args.next().map(str::parse::<MessageSignature>) {
+            Some(Ok(signature)) => signature,
+            Some(Err(err)) => {
+                println!("{}", err);
+                self.print_help(BaseNodeCommand::VerifyMessage);
+                return;
+            },
+            None => {
+                println!("Please provide a signature");
+                self.print_help(BaseNodeCommand::VerifyMessage);
+                return;
+            },
+        };
+        let message = args.collect::<Vec<_>>().join(" ");
+        self.command_handler.verify_message(&public_key, &signature, &message)
+    }
 
-        self.command_handler.search_kernel(kernel_sig)
+    /// Function to process the search kernel command
+    fn process_search_kernel<'a, I: Iterator<Item = &'a str>>(&self, args: I) {
+        let values = match read_search_values(args) {
+            Ok(values) => values,
+            Err(err) => {
+                println!("{}", err);
+                return;
+            },
+        };
+        let parts = values.iter().flat_map(|v| v.split_whitespace()).collect::<Vec<_>>();
+        if parts.is_empty() || parts.len() % 2 != 0 {
+            self.print_help(BaseNodeCommand::SearchKernel);
+            return;
+        }
+        let mut excess_sigs = Vec::with_capacity(parts.len() / 2);
+        for pair in parts.chunks(2) {
+            let public_nonce = match parse_hex_or_base58::<PublicKey>(pair[0]) {
+                Some(v) => v,
+                None => {
+                    println!("Invalid public nonce '{}' provided.", pair[0]);
+                    self.print_help(BaseNodeCommand::SearchKernel);
+                    return;
+                },
+            };
+            let signature = match parse_hex_or_base58::<PrivateKey>(pair[1]) {
+                Some(v) => v,
+                None => {
+                    println!("Invalid signature '{}' provided.", pair[1]);
+                    self.print_help(BaseNodeCommand::SearchKernel);
+                    return;
+                },
+            };
+            excess_sigs.push(Signature::new(public_nonce, signature));
+        }
+        self.command_handler.search_kernels(excess_sigs)
     }
 
     /// Function to process the discover-peer command
     fn process_discover_peer<'a, I: Iterator<Item = &'a str>>(&mut self, mut args: I) {
-        let dest_pubkey = match args.next().and_then(parse_emoji_id_or_public_key) {
-            Some(v) => Box::new(v),
+        let dest_pubkey = match args.next() {
+            Some("--stats") => {
+                self.command_handler.discovery_stats();
+                return;
+            },
+            Some(arg) => parse_emoji_id_or_public_key(arg).map(Box::new),
+            None => None,
+        };
+        let dest_pubkey = match dest_pubkey {
+            Some(v) => v,
             None => {
                 println!("Please enter a valid destination public key or emoji id");
-                println!("discover-peer [hex public key or emoji id]");
+                println!("discover-peer [hex public key or emoji id | --stats]");
                 return;
             },
         };
@@ -578,12 +1266,43 @@ impl Parser {
             Some(n) => n,
             None => {
                 println!("Please enter a valid destination public key or emoji id");
-                println!("ping-peer [hex public key or emoji id]");
+                println!("ping-peer [hex public key or emoji id] [--count <n>]");
+                return;
+            },
+        };
+        let count = match (args.next(), args.next()) {
+            (None, _) => 1,
+            (Some("--count"), Some(count)) => match count.parse::<usize>() {
+                Ok(count) if (1..=MAX_PING_COUNT).contains(&count) => count,
+                _ => {
+                    println!("The ping count must be between 1 and {}", MAX_PING_COUNT);
+                    return;
+                },
+            },
+            _ => {
+                println!("ping-peer [hex public key or emoji id] [--count <n>]");
                 return;
             },
         };
 
-        self.command_handler.ping_peer(dest_node_id)
+        self.command_handler.ping_peer(dest_node_id, count)
+    }
+
+    /// Function to process the ping-stats command
+    fn process_ping_stats<'a, I: Iterator<Item = &'a str>>(&mut self, mut args: I) {
+        let node_id = match args.next() {
+            Some(arg) => match parse_emoji_id_or_public_key_or_node_id(arg).map(either_to_node_id) {
+                Some(node_id) => Some(node_id),
+                None => {
+                    println!("Please enter a valid public key, emoji id or node id");
+                    println!("ping-stats [hex public key, emoji id or node id]");
+                    return;
+                },
+            },
+            None => None,
+        };
+
+        self.command_handler.ping_stats(node_id)
     }
 
     /// Function to process the ban-peer command
@@ -645,13 +1364,9 @@ impl Parser {
     }
 
     fn process_period_stats<'a, I: Iterator<Item = &'a str>>(&self, args: I) {
-        let command_arg = args.map(|arg| arg.to_string()).take(3).collect::<Vec<String>>();
-        if command_arg.len() != 3 {
-            println!("Prints out certain stats to of the block chain, use as follows: ");
-            println!(
-                "Period-stats [start time in unix timestamp] [end time in unix timestamp] [interval period time in \
-                 unix timestamp]"
-            );
+        let command_arg = args.map(|arg| arg.to_string()).take(4).collect::<Vec<String>>();
+        if command_arg.len() < 3 {
+            self.print_help(BaseNodeCommand::PeriodStats);
             return;
         }
         let period_end = match u64::from_str(&command_arg[0]) {
@@ -675,7 +1390,9 @@ impl Parser {
                 return;
             },
         };
-        self.command_handler.period_stats(period_end, period_ticker_end, period)
+        let filename = command_arg.get(3).cloned();
+        self.command_handler
+            .period_stats(period_end, period_ticker_end, period, filename)
     }
 
     fn process_header_stats<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
@@ -707,6 +1424,158 @@ impl Parser {
             .save_header_stats(start_height, end_height, filename, algo)
     }
 
+    fn process_revalidate_blocks<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let mut next_height = |name: &str| {
+            args.next()
+                .ok_or_else(|| {
+                    self.print_help(BaseNodeCommand::RevalidateBlocks);
+                    format!("No {} provided", name)
+                })
+                .and_then(|arg| u64::from_str(arg).map_err(|err| format!("Invalid {}: {}", name, err)))
+        };
+        let start_height = try_or_print!(next_height("start height"));
+        let end_height = try_or_print!(next_height("end height"));
+        let parallelism = match args.next() {
+            Some(arg) => try_or_print!(usize::from_str(arg)
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or("The number of blocks to validate in parallel must be a positive number")),
+            None => 1,
+        };
+        self.command_handler
+            .revalidate_blocks(start_height, end_height, parallelism)
+    }
+
+    fn process_weight_audit<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let mut next_arg = |name: &str| {
+            args.next().ok_or_else(|| {
+                self.print_help(BaseNodeCommand::WeightAudit);
+                format!("No {} provided", name)
+            })
+        };
+        let start_height =
+            try_or_print!(next_arg("start height").and_then(|arg| u64::from_str(arg).map_err(|err| err.to_string())));
+        let end_height =
+            try_or_print!(next_arg("end height").and_then(|arg| u64::from_str(arg).map_err(|err| err.to_string())));
+        let weights = try_or_print!(next_arg("weights").and_then(LinearWeights::from_str));
+        if end_height < start_height {
+            println!("End height must be greater than or equal to the start height");
+            return;
+        }
+        self.command_handler.weight_audit(start_height, end_height, weights)
+    }
+
+    fn process_debug_script<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let script = match args.next() {
+            Some(arg) => try_or_print!(TariScript::from_hex(arg).map_err(|err| format!("Invalid script: {}", err))),
+            None => {
+                self.print_help(BaseNodeCommand::DebugScript);
+                return;
+            },
+        };
+        let input_data = match args.next() {
+            Some("-") | None => ExecutionStack::default(),
+            Some(arg) => {
+                try_or_print!(ExecutionStack::from_hex(arg).map_err(|err| format!("Invalid input stack: {}", err)))
+            },
+        };
+        let height = match args.next() {
+            Some(arg) => try_or_print!(u64::from_str(arg).map_err(|err| format!("Invalid block height: {}", err))),
+            None => 0,
+        };
+        self.command_handler.debug_script(script, input_data, height)
+    }
+
+    fn process_validate_tx<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let arg = match args.next() {
+            Some(arg) => arg,
+            None => {
+                self.print_help(BaseNodeCommand::ValidateTx);
+                return;
+            },
+        };
+        let contents = if Path::new(arg).is_file() {
+            try_or_print!(fs::read_to_string(arg), "Could not read '{}': {error}", arg)
+        } else {
+            arg.to_string()
+        };
+        let contents = contents.trim();
+        let tx = if contents.starts_with('{') {
+            try_or_print!(Transaction::from_json(contents), "Invalid transaction JSON: {error}")
+        } else {
+            let bytes = try_or_print!(from_hex(contents), "Expected a file, JSON or hex: {error}");
+            try_or_print!(Transaction::from_binary(&bytes), "Invalid transaction: {error}")
+        };
+        self.command_handler.validate_tx(tx)
+    }
+
+    fn process_set_log_level<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let target = match args.next() {
+            Some(target) => target,
+            None => {
+                self.command_handler.list_log_levels();
+                return;
+            },
+        };
+        let level = try_or_print!(args.next().ok_or("level argument required").and_then(|s| {
+            LevelFilter::from_str(s).map_err(|_| "level must be one of off, error, warn, info, debug or trace")
+        }));
+        self.command_handler.set_log_level(target, level);
+    }
+
+    fn process_set_sync_mode<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let mode = match args.next() {
+            Some(mode) => Some(try_or_print!(SyncMode::from_str(mode))),
+            None => None,
+        };
+        self.command_handler.set_sync_mode(mode);
+    }
+
+    fn process_purge_peers<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let mut criteria = PurgeCriteria::default();
+        while let Some(arg) = args.next() {
+            match arg {
+                "--banned" => criteria.banned = true,
+                "--stale-days" => {
+                    let days = try_or_print!(args
+                        .next()
+                        .ok_or("--stale-days requires the number of days")
+                        .and_then(|s| u32::from_str(s).map_err(|_| "the number of days must be an integer")));
+                    criteria.stale_after = chrono::Duration::days(i64::from(days));
+                },
+                _ => {
+                    println!("Unknown argument: {}", arg);
+                    println!("purge-peers [--banned] [--stale-days <days>]");
+                    return;
+                },
+            }
+        }
+        self.command_handler.purge_peers(criteria);
+    }
+
+    fn process_access_rule<'a, I: Iterator<Item = &'a str>>(&self, mut args: I, block: bool) {
+        let (remove, rule) = match args.next() {
+            Some("--remove") => (true, args.next()),
+            rule => (false, rule),
+        };
+        let rule = match rule {
+            Some(rule) => try_or_print!(AccessRule::from_str(rule)),
+            None if remove => {
+                println!("Please enter the address to remove");
+                return;
+            },
+            None => {
+                self.command_handler.list_access_rules();
+                return;
+            },
+        };
+        match (remove, block) {
+            (true, _) => self.command_handler.remove_access_rule(rule),
+            (false, true) => self.command_handler.block_address(rule),
+            (false, false) => self.command_handler.allow_address(rule),
+        }
+    }
+
     fn process_rewind_blockchain<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
         let new_height = try_or_print!(args
             .next()
@@ -715,3 +1584,31 @@ impl Parser {
         self.command_handler.rewind_blockchain(new_height);
     }
 }
+
+/// Parses a hex or base58 encoded value
+fn parse_hex_or_base58<T: ByteArray>(value: &str) -> Option<T> {
+    T::from_hex(value).ok().or_else(|| {
+        bs58::decode(value)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| T::from_bytes(&bytes).ok())
+    })
+}
+
+/// Reads the values given to a search command, either from the arguments or from the file given with `--file`, one
+/// value per line. Empty lines and lines starting with `#` are skipped.
+fn read_search_values<'a, I: Iterator<Item = &'a str>>(args: I) -> Result<Vec<String>, String> {
+    let mut args = args.peekable();
+    if args.peek() == Some(&"--file") {
+        args.next();
+        let path = args.next().ok_or_else(|| "Missing file path".to_string())?;
+        let contents = fs::read_to_string(path).map_err(|err| format!("Could not read '{}': {}", path, err))?;
+        return Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(ToString::to_string)
+            .collect());
+    }
+    Ok(args.map(ToString::to_string).collect())
+}
@@ -0,0 +1,223 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Peer database maintenance: purging stale and banned peers, and exporting the peers to and importing them from a
+//! JSON file.
+//!
+//! An export is a JSON array of peers. An import also accepts peers in the `<public key>::<address>` format of the
+//! `peer_seeds` setting, so a curated list can be written by hand.
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde::Deserialize;
+use std::{collections::HashSet, fs, io, path::Path, str::FromStr};
+use tari_comms::peer_manager::{NodeId, Peer, PeerManager, PeerManagerError};
+use tari_p2p::peer_seeds::SeedPeer;
+use thiserror::Error;
+
+/// Peers that have been offline for this many days are stale
+pub const DEFAULT_STALE_AFTER_DAYS: i64 = 7;
+
+#[derive(Debug, Error)]
+pub enum PeerDbError {
+    #[error("Peer manager error: {0}")]
+    PeerManagerError(#[from] PeerManagerError),
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Invalid peer file: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Invalid peer at position {position}: {details}")]
+    InvalidPeer { position: usize, details: String },
+}
+
+/// The peers to purge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PurgeCriteria {
+    /// Purge banned peers. They are able to connect again once purged.
+    pub banned: bool,
+    /// Purge peers that have been offline, or were never seen since they were added, for longer than this
+    pub stale_after: Duration,
+}
+
+impl Default for PurgeCriteria {
+    fn default() -> Self {
+        Self {
+            banned: false,
+            stale_after: Duration::days(DEFAULT_STALE_AFTER_DAYS),
+        }
+    }
+}
+
+impl PurgeCriteria {
+    /// Whether the peer matches the criteria, as of `now`
+    pub fn matches(&self, peer: &Peer, now: NaiveDateTime) -> bool {
+        if peer.is_banned() {
+            return self.banned;
+        }
+        let stale_since = match (peer.offline_at, peer.last_seen()) {
+            (Some(offline_at), _) => offline_at,
+            (None, None) => peer.added_at,
+            (None, Some(_)) => return false,
+        };
+        now - stale_since > self.stale_after
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PurgeStats {
+    pub purged: usize,
+    pub remaining: usize,
+}
+
+/// Deletes the peers that match the criteria, other than the connected peers
+pub async fn purge_peers(
+    peer_manager: &PeerManager,
+    criteria: PurgeCriteria,
+    connected: &HashSet<NodeId>,
+) -> Result<PurgeStats, PeerDbError> {
+    let now = Utc::now().naive_utc();
+    let mut stats = PurgeStats::default();
+    for peer in peer_manager.all().await? {
+        if !connected.contains(&peer.node_id) && criteria.matches(&peer, now) {
+            peer_manager.delete_peer(&peer.node_id).await?;
+            stats.purged += 1;
+        } else {
+            stats.remaining += 1;
+        }
+    }
+    Ok(stats)
+}
+
+/// Writes all the peers to the file as JSON, returns the number of peers written
+pub async fn export_peers(peer_manager: &PeerManager, path: &Path) -> Result<usize, PeerDbError> {
+    let peers = peer_manager.all().await?;
+    fs::write(path, serde_json::to_vec_pretty(&peers)?)?;
+    Ok(peers.len())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PeerEntry {
+    Seed(String),
+    Peer(Box<Peer>),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportStats {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+/// Adds the peers in the file to the peer database. The addresses of peers that are already known are added to the
+/// stored peer, which keeps its ban and connection state. This node's own identity is skipped.
+pub async fn import_peers(
+    peer_manager: &PeerManager,
+    own_node_id: &NodeId,
+    path: &Path,
+) -> Result<ImportStats, PeerDbError> {
+    let peers = parse_peers(&fs::read_to_string(path)?)?;
+    let mut stats = ImportStats::default();
+    for peer in peers {
+        if &peer.node_id == own_node_id {
+            stats.skipped += 1;
+            continue;
+        }
+        if peer_manager.exists(&peer.public_key).await {
+            for address in peer.addresses.iter() {
+                peer_manager.add_net_address(&peer.node_id, address).await?;
+            }
+            stats.updated += 1;
+        } else {
+            peer_manager.add_peer(peer).await?;
+            stats.added += 1;
+        }
+    }
+    Ok(stats)
+}
+
+fn parse_peers(json: &str) -> Result<Vec<Peer>, PeerDbError> {
+    serde_json::from_str::<Vec<PeerEntry>>(json)?
+        .into_iter()
+        .enumerate()
+        .map(|(position, entry)| match entry {
+            PeerEntry::Peer(peer) => Ok(*peer),
+            PeerEntry::Seed(seed) => {
+                SeedPeer::from_str(&seed)
+                    .map(Peer::from)
+                    .map_err(|err| PeerDbError::InvalidPeer {
+                        position,
+                        details: err.to_string(),
+                    })
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SEED: &str = "e856839057aac496b9e25f10821116d02b58f20129e9b9ba681b830568e47c4d::/ip4/127.0.0.1/tcp/18189";
+
+    fn peer() -> Peer {
+        parse_peers(&format!("[\"{}\"]", SEED)).unwrap().remove(0)
+    }
+
+    #[test]
+    fn it_parses_exported_and_seed_peers() {
+        let exported = serde_json::to_string(&vec![peer()]).unwrap();
+        let peers = parse_peers(&exported).unwrap();
+        assert_eq!(peers, vec![peer()]);
+        assert_eq!(peers[0].addresses.len(), 1);
+
+        assert!(matches!(
+            parse_peers("[\"not a peer\"]"),
+            Err(PeerDbError::InvalidPeer { position: 0, .. })
+        ));
+        assert!(matches!(parse_peers("{}"), Err(PeerDbError::JsonError(_))));
+    }
+
+    #[test]
+    fn it_matches_stale_and_banned_peers() {
+        let now = Utc::now().naive_utc();
+        let criteria = PurgeCriteria::default();
+
+        let mut peer = peer();
+        assert!(!criteria.matches(&peer, now));
+        assert!(criteria.matches(&peer, now + Duration::days(8)));
+
+        peer.offline_at = Some(now - Duration::days(1));
+        assert!(!criteria.matches(&peer, now));
+        peer.offline_at = Some(now - Duration::days(10));
+        assert!(criteria.matches(&peer, now));
+
+        peer.offline_at = None;
+        peer.banned_until = Some(now + Duration::days(1));
+        assert!(!criteria.matches(&peer, now));
+        assert!(PurgeCriteria {
+            banned: true,
+            ..criteria
+        }
+        .matches(&peer, now));
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Integration with the service managers that supervise the base node. Under systemd (a unit with `Type=notify`) the
+//! node reports its state and readiness over `sd_notify` and pings the watchdog. Under the Windows Service Control
+//! Manager the node registers start and stop handlers.
+
+#[cfg(target_os = "linux")]
+mod systemd;
+#[cfg(windows)]
+pub mod windows;
+
+use futures::future;
+use tari_core::base_node::state_machine_service::states::StatusInfo;
+use tari_shutdown::{OptionalShutdownSignal, ShutdownSignal};
+use tokio::sync::watch;
+
+/// The connection between the base node and the service manager running it, if any
+#[derive(Clone, Default)]
+pub struct ServiceIntegration {
+    stop_signal: Option<ShutdownSignal>,
+}
+
+impl ServiceIntegration {
+    /// Integration with a service manager that is detected from the environment, such as systemd
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Integration with a service manager that asks the node to stop by triggering the given signal
+    pub fn with_stop_signal(stop_signal: ShutdownSignal) -> Self {
+        Self {
+            stop_signal: Some(stop_signal),
+        }
+    }
+
+    /// Resolves once the service manager asks the node to stop. Never resolves when the service manager stops the
+    /// node by other means, such as a signal.
+    pub fn stop_requested(&self) -> OptionalShutdownSignal {
+        self.stop_signal.clone().into()
+    }
+
+    /// Resolves when either the given shutdown signal resolves or the service manager asks the node to stop
+    pub async fn wait_for_shutdown(&self, shutdown_signal: ShutdownSignal) {
+        future::select(shutdown_signal, self.stop_requested()).await;
+    }
+
+    /// Reports the state of the node to the service manager until `shutdown_signal` resolves
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    pub fn spawn_state_notifier(&self, state_info: watch::Receiver<StatusInfo>, shutdown_signal: ShutdownSignal) {
+        #[cfg(target_os = "linux")]
+        if let Some(notifier) = systemd::Notifier::from_environment() {
+            tokio::task::spawn(notifier.run(state_info, shutdown_signal));
+        }
+    }
+}
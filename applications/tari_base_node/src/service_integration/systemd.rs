@@ -0,0 +1,171 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! The `sd_notify` protocol: the node sends newline separated `KEY=VALUE` assignments as datagrams to the unix socket
+//! systemd passes in `NOTIFY_SOCKET`.
+
+use futures::future;
+use log::*;
+use std::{env, ffi::OsString, io, os::unix::net::UnixDatagram, path::PathBuf, process, time::Duration};
+use tari_core::base_node::state_machine_service::states::StatusInfo;
+use tari_shutdown::ShutdownSignal;
+use tokio::{
+    sync::watch,
+    time::{self, Interval},
+};
+
+const LOG_TARGET: &str = "base_node::app::systemd";
+
+/// Sends state notifications to systemd
+pub struct Notifier {
+    socket: UnixDatagram,
+    socket_path: PathBuf,
+    watchdog_interval: Option<Duration>,
+}
+
+impl Notifier {
+    /// Returns a notifier when the node was started by systemd as a `Type=notify` service. The watchdog is pinged at
+    /// half the interval systemd expects, if it is enabled for this process.
+    pub fn from_environment() -> Option<Self> {
+        let socket_path = env::var_os("NOTIFY_SOCKET")?;
+        let watchdog_interval = watchdog_interval(
+            env::var("WATCHDOG_USEC").ok().as_deref(),
+            env::var("WATCHDOG_PID").ok().as_deref(),
+        );
+        match Self::connect(socket_path, watchdog_interval) {
+            Ok(notifier) => Some(notifier),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Could not set up systemd notifications: {}", err);
+                None
+            },
+        }
+    }
+
+    fn connect(socket_path: OsString, watchdog_interval: Option<Duration>) -> io::Result<Self> {
+        if socket_path.to_string_lossy().starts_with('@') {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "abstract notification sockets are not supported",
+            ));
+        }
+        Ok(Self {
+            socket: UnixDatagram::unbound()?,
+            socket_path: socket_path.into(),
+            watchdog_interval,
+        })
+    }
+
+    fn notify(&self, state: &str) {
+        if let Err(err) = self.socket.send_to(state.as_bytes(), &self.socket_path) {
+            warn!(target: LOG_TARGET, "Failed to notify systemd of '{}': {}", state, err);
+        }
+    }
+
+    /// Reports the state of the node to systemd, and that the node is ready once it has synced, until the shutdown
+    /// signal resolves.
+    pub async fn run(self, mut state_info: watch::Receiver<StatusInfo>, mut shutdown_signal: ShutdownSignal) {
+        let mut watchdog = self.watchdog_interval.map(time::interval);
+        let mut is_ready = false;
+        loop {
+            tokio::select! {
+                Ok(_) = state_info.changed() => {
+                    let state = state_message(&state_info.borrow(), &mut is_ready);
+                    self.notify(&state);
+                },
+                _ = tick(&mut watchdog) => {
+                    self.notify("WATCHDOG=1");
+                },
+                _ = shutdown_signal.wait() => {
+                    self.notify("STOPPING=1");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Waits for the next tick of the watchdog interval, forever if the watchdog is disabled
+async fn tick(watchdog: &mut Option<Interval>) {
+    match watchdog {
+        Some(interval) => {
+            interval.tick().await;
+        },
+        None => future::pending().await,
+    }
+}
+
+/// The notification for the given status. The node is ready for the first time once it has synced, after which
+/// systemd considers the service started.
+fn state_message(status: &StatusInfo, is_ready: &mut bool) -> String {
+    let status_line = format!("STATUS={}", status.state_info.short_desc());
+    if !*is_ready && status.bootstrapped && status.state_info.is_synced() {
+        *is_ready = true;
+        format!("READY=1\n{}", status_line)
+    } else {
+        status_line
+    }
+}
+
+/// Half the watchdog timeout, if the watchdog is enabled for this process
+fn watchdog_interval(watchdog_usec: Option<&str>, watchdog_pid: Option<&str>) -> Option<Duration> {
+    if let Some(pid) = watchdog_pid {
+        if pid.parse::<u32>().ok()? != process::id() {
+            return None;
+        }
+    }
+    let usec = watchdog_usec?.parse::<u64>().ok().filter(|usec| *usec > 0)?;
+    Some(Duration::from_micros(usec / 2))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn it_sends_notifications_to_the_socket() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("notify.sock");
+        let systemd = UnixDatagram::bind(&socket_path).unwrap();
+        let notifier = Notifier::connect(socket_path.into_os_string(), None).unwrap();
+
+        notifier.notify("READY=1\nSTATUS=Listening");
+        let mut buf = [0u8; 64];
+        let len = systemd.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1\nSTATUS=Listening");
+    }
+
+    #[test]
+    fn it_derives_the_watchdog_interval() {
+        assert_eq!(watchdog_interval(None, None), None);
+        assert_eq!(watchdog_interval(Some("0"), None), None);
+        assert_eq!(watchdog_interval(Some("30000000"), None), Some(Duration::from_secs(15)));
+        let pid = process::id().to_string();
+        assert_eq!(
+            watchdog_interval(Some("30000000"), Some(&pid)),
+            Some(Duration::from_secs(15))
+        );
+        let other_pid = (process::id() + 1).to_string();
+        assert_eq!(watchdog_interval(Some("30000000"), Some(&other_pid)), None);
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Running the base node as a Windows service. The service is installed with
+//! `sc.exe create tari_base_node binPath= "<path>\tari_base_node.exe --windows-service --base-path <path>"`, after
+//! which the Service Control Manager starts the node and asks it to stop through the handler registered here.
+
+use crate::{cli::Cli, service_integration::ServiceIntegration};
+use log::*;
+use std::{ffi::OsString, time::Duration};
+use structopt::StructOpt;
+use tari_app_utilities::utilities::ExitError;
+use tari_shutdown::Shutdown;
+use windows_service::{
+    define_windows_service,
+    service::{ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus, ServiceType},
+    service_control_handler::{self, ServiceControlHandlerResult, ServiceStatusHandle},
+    service_dispatcher,
+};
+
+const LOG_TARGET: &str = "base_node::app::windows_service";
+
+pub const SERVICE_NAME: &str = "tari_base_node";
+const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
+
+define_windows_service!(ffi_service_main, service_main);
+
+/// Hands the process over to the Service Control Manager, which runs the node on a thread of its own. Returns once
+/// the service has stopped.
+pub fn run_as_service() -> windows_service::Result<()> {
+    service_dispatcher::start(SERVICE_NAME, ffi_service_main)
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    if let Err(err) = run_service() {
+        error!(target: LOG_TARGET, "Windows service failed: {}", err);
+    }
+}
+
+fn run_service() -> windows_service::Result<()> {
+    let mut stop = Shutdown::new();
+    let stop_signal = stop.to_signal();
+    let status_handle = service_control_handler::register(SERVICE_NAME, move |control| match control {
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            info!(target: LOG_TARGET, "Stop requested by the Service Control Manager");
+            stop.trigger();
+            ServiceControlHandlerResult::NoError
+        },
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    })?;
+
+    // Syncing can take hours, longer than the Service Control Manager waits for a service to start, so the service
+    // is running as soon as the node is.
+    set_status(
+        &status_handle,
+        ServiceState::Running,
+        ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN,
+        ServiceExitCode::Win32(0),
+    )?;
+
+    // The service is started with the arguments it was installed with
+    let mut cli = Cli::from_args();
+    cli.bootstrap.non_interactive_mode = true;
+    let exit_code = match crate::main_inner(cli, ServiceIntegration::with_stop_signal(stop_signal)) {
+        Ok(_) => ServiceExitCode::Win32(0),
+        Err(err) => {
+            error!(target: LOG_TARGET, "Exiting with code ({}): {}", err.as_i32(), err);
+            service_exit_code(&err)
+        },
+    };
+
+    set_status(
+        &status_handle,
+        ServiceState::Stopped,
+        ServiceControlAccept::empty(),
+        exit_code,
+    )
+}
+
+fn set_status(
+    status_handle: &ServiceStatusHandle,
+    current_state: ServiceState,
+    controls_accepted: ServiceControlAccept,
+    exit_code: ServiceExitCode,
+) -> windows_service::Result<()> {
+    status_handle.set_service_status(ServiceStatus {
+        service_type: SERVICE_TYPE,
+        current_state,
+        controls_accepted,
+        exit_code,
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    })
+}
+
+/// The base node exit codes are reported as service specific error codes
+fn service_exit_code(err: &ExitError) -> ServiceExitCode {
+    ServiceExitCode::ServiceSpecific(err.as_i32() as u32)
+}
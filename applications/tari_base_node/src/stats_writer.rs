@@ -0,0 +1,404 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Output files for the `header-stats` and `period-stats` commands. The format is picked by the file extension:
+//! `.parquet` files are written as Parquet, which needs the base node to be built with the `parquet` feature, and any
+//! other file as CSV. Rows are buffered until `flush`, which the commands call after each batch of blocks, so a Parquet
+//! file gets a row group per batch and long ranges are never held in memory.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StatsWriterError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Parquet output needs the base node to be built with the `parquet` feature")]
+    ParquetNotEnabled,
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("Expected a {expected:?} value for column '{column}' but got {value}")]
+    InvalidValue {
+        column: &'static str,
+        expected: ColumnType,
+        value: StatsValue,
+    },
+    #[error("Expected {expected} values in the row but got {actual}")]
+    InvalidRowLength { expected: usize, actual: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Int,
+    UInt,
+    Float,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsValue {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Text(String),
+}
+
+impl StatsValue {
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            StatsValue::Int(_) => ColumnType::Int,
+            StatsValue::UInt(_) => ColumnType::UInt,
+            StatsValue::Float(_) => ColumnType::Float,
+            StatsValue::Text(_) => ColumnType::Text,
+        }
+    }
+}
+
+impl fmt::Display for StatsValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsValue::Int(v) => write!(f, "{}", v),
+            StatsValue::UInt(v) => write!(f, "{}", v),
+            StatsValue::Float(v) => write!(f, "{}", v),
+            StatsValue::Text(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl From<i64> for StatsValue {
+    fn from(v: i64) -> Self {
+        StatsValue::Int(v)
+    }
+}
+
+impl From<u64> for StatsValue {
+    fn from(v: u64) -> Self {
+        StatsValue::UInt(v)
+    }
+}
+
+impl From<usize> for StatsValue {
+    fn from(v: usize) -> Self {
+        StatsValue::UInt(v as u64)
+    }
+}
+
+impl From<f64> for StatsValue {
+    fn from(v: f64) -> Self {
+        StatsValue::Float(v)
+    }
+}
+
+impl From<String> for StatsValue {
+    fn from(v: String) -> Self {
+        StatsValue::Text(v)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+    Csv,
+    Parquet,
+}
+
+impl StatsFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("parquet") => StatsFormat::Parquet,
+            _ => StatsFormat::Csv,
+        }
+    }
+}
+
+/// The name and type of each column, in order
+pub type Columns = &'static [(&'static str, ColumnType)];
+
+pub struct StatsWriter {
+    columns: Columns,
+    output: Output,
+}
+
+enum Output {
+    Csv(BufWriter<File>),
+    #[cfg(feature = "parquet")]
+    Parquet(parquet_output::ParquetOutput),
+}
+
+impl StatsWriter {
+    /// Creates the file, in the format given by its extension, and writes the CSV header
+    pub fn create<P: AsRef<Path>>(path: P, columns: Columns) -> Result<Self, StatsWriterError> {
+        let output = match StatsFormat::from_path(&path) {
+            StatsFormat::Csv => {
+                let mut output = BufWriter::new(File::create(path)?);
+                let names = columns.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+                writeln!(output, "{}", names.join(","))?;
+                Output::Csv(output)
+            },
+            #[cfg(feature = "parquet")]
+            StatsFormat::Parquet => Output::Parquet(parquet_output::ParquetOutput::create(path, columns)?),
+            #[cfg(not(feature = "parquet"))]
+            StatsFormat::Parquet => return Err(StatsWriterError::ParquetNotEnabled),
+        };
+        Ok(Self { columns, output })
+    }
+
+    pub fn write_row(&mut self, row: Vec<StatsValue>) -> Result<(), StatsWriterError> {
+        if row.len() != self.columns.len() {
+            return Err(StatsWriterError::InvalidRowLength {
+                expected: self.columns.len(),
+                actual: row.len(),
+            });
+        }
+        for ((column, expected), value) in self.columns.iter().zip(&row) {
+            if value.column_type() != *expected {
+                return Err(StatsWriterError::InvalidValue {
+                    column: *column,
+                    expected: *expected,
+                    value: value.clone(),
+                });
+            }
+        }
+
+        match &mut self.output {
+            Output::Csv(output) => {
+                let values = row.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                writeln!(output, "{}", values.join(","))?;
+            },
+            #[cfg(feature = "parquet")]
+            Output::Parquet(output) => output.push(row),
+        }
+        Ok(())
+    }
+
+    /// Writes out the rows buffered since the last flush
+    pub fn flush(&mut self) -> Result<(), StatsWriterError> {
+        match &mut self.output {
+            Output::Csv(output) => output.flush()?,
+            #[cfg(feature = "parquet")]
+            Output::Parquet(output) => output.write_row_group()?,
+        }
+        Ok(())
+    }
+
+    /// Flushes the buffered rows and, for Parquet, writes the file footer. A Parquet file that is not finished cannot
+    /// be read.
+    pub fn finish(mut self) -> Result<(), StatsWriterError> {
+        self.flush()?;
+        match self.output {
+            Output::Csv(_) => {},
+            #[cfg(feature = "parquet")]
+            Output::Parquet(output) => output.close()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_output {
+    use super::{ColumnType, Columns, StatsValue};
+    use parquet::{
+        basic::{ConvertedType, Repetition, Type as PhysicalType},
+        column::writer::ColumnWriter,
+        data_type::ByteArray,
+        errors::ParquetError,
+        file::{
+            properties::WriterProperties,
+            writer::{FileWriter, RowGroupWriter, SerializedFileWriter},
+        },
+        schema::types::Type,
+    };
+    use std::{fs::File, path::Path, sync::Arc};
+
+    pub struct ParquetOutput {
+        writer: SerializedFileWriter<File>,
+        rows: Vec<Vec<StatsValue>>,
+    }
+
+    impl ParquetOutput {
+        pub fn create<P: AsRef<Path>>(path: P, columns: Columns) -> Result<Self, ParquetError> {
+            let mut fields = columns
+                .iter()
+                .map(|(name, column_type)| {
+                    let (physical_type, converted_type) = match column_type {
+                        ColumnType::Int => (PhysicalType::INT64, ConvertedType::INT_64),
+                        ColumnType::UInt => (PhysicalType::INT64, ConvertedType::UINT_64),
+                        ColumnType::Float => (PhysicalType::DOUBLE, ConvertedType::NONE),
+                        ColumnType::Text => (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8),
+                    };
+                    Type::primitive_type_builder(name, physical_type)
+                        .with_converted_type(converted_type)
+                        .with_repetition(Repetition::REQUIRED)
+                        .build()
+                        .map(Arc::new)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let schema = Type::group_type_builder("stats").with_fields(&mut fields).build()?;
+            let file = File::create(path)?;
+            let writer =
+                SerializedFileWriter::new(file, Arc::new(schema), Arc::new(WriterProperties::builder().build()))?;
+            Ok(Self {
+                writer,
+                rows: Vec::new(),
+            })
+        }
+
+        pub fn push(&mut self, row: Vec<StatsValue>) {
+            self.rows.push(row);
+        }
+
+        /// Writes the buffered rows as a row group. The values have been checked against the column types.
+        pub fn write_row_group(&mut self) -> Result<(), ParquetError> {
+            if self.rows.is_empty() {
+                return Ok(());
+            }
+            let mut row_group = self.writer.next_row_group()?;
+            let mut index = 0;
+            while let Some(mut column) = row_group.next_column()? {
+                let values = self.rows.iter().map(|row| &row[index]);
+                match column {
+                    ColumnWriter::Int64ColumnWriter(ref mut writer) => {
+                        let values = values
+                            .map(|value| match value {
+                                StatsValue::Int(v) => *v,
+                                // Stored as the bit pattern, which readers interpret as unsigned through the
+                                // UINT_64 annotation
+                                StatsValue::UInt(v) => *v as i64,
+                                _ => 0,
+                            })
+                            .collect::<Vec<_>>();
+                        writer.write_batch(&values, None, None)?;
+                    },
+                    ColumnWriter::DoubleColumnWriter(ref mut writer) => {
+                        let values = values
+                            .map(|value| match value {
+                                StatsValue::Float(v) => *v,
+                                _ => 0.0,
+                            })
+                            .collect::<Vec<_>>();
+                        writer.write_batch(&values, None, None)?;
+                    },
+                    ColumnWriter::ByteArrayColumnWriter(ref mut writer) => {
+                        let values = values
+                            .map(|value| ByteArray::from(value.to_string().into_bytes()))
+                            .collect::<Vec<_>>();
+                        writer.write_batch(&values, None, None)?;
+                    },
+                    _ => return Err(ParquetError::General("Unexpected column type".to_string())),
+                }
+                row_group.close_column(column)?;
+                index += 1;
+            }
+            self.writer.close_row_group(row_group)?;
+            self.rows.clear();
+            Ok(())
+        }
+
+        pub fn close(mut self) -> Result<(), ParquetError> {
+            self.writer.close()?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const COLUMNS: Columns = &[("Height", ColumnType::UInt), ("Algo", ColumnType::Text)];
+
+    #[test]
+    fn it_picks_the_format_from_the_extension() {
+        assert_eq!(StatsFormat::from_path("stats.parquet"), StatsFormat::Parquet);
+        assert_eq!(StatsFormat::from_path("stats.PARQUET"), StatsFormat::Parquet);
+        assert_eq!(StatsFormat::from_path("stats.csv"), StatsFormat::Csv);
+        assert_eq!(StatsFormat::from_path("stats"), StatsFormat::Csv);
+    }
+
+    #[test]
+    fn it_writes_csv_rows() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("stats.csv");
+        let mut writer = StatsWriter::create(&path, COLUMNS).unwrap();
+        writer.write_row(vec![1u64.into(), "Sha3".to_string().into()]).unwrap();
+        writer.flush().unwrap();
+        writer
+            .write_row(vec![2u64.into(), "Monero".to_string().into()])
+            .unwrap();
+        writer.finish().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Height,Algo\n1,Sha3\n2,Monero\n");
+    }
+
+    #[test]
+    fn it_rejects_rows_that_do_not_match_the_columns() {
+        let dir = tempdir().unwrap();
+        let mut writer = StatsWriter::create(dir.path().join("stats.csv"), COLUMNS).unwrap();
+        assert!(matches!(
+            writer.write_row(vec![1u64.into()]),
+            Err(StatsWriterError::InvalidRowLength { expected: 2, actual: 1 })
+        ));
+        assert!(matches!(
+            writer.write_row(vec![1i64.into(), "Sha3".to_string().into()]),
+            Err(StatsWriterError::InvalidValue { column: "Height", .. })
+        ));
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    #[test]
+    fn it_requires_the_parquet_feature() {
+        let dir = tempdir().unwrap();
+        assert!(matches!(
+            StatsWriter::create(dir.path().join("stats.parquet"), COLUMNS),
+            Err(StatsWriterError::ParquetNotEnabled)
+        ));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn it_writes_a_row_group_per_flush() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("stats.parquet");
+        let mut writer = StatsWriter::create(&path, COLUMNS).unwrap();
+        writer.write_row(vec![1u64.into(), "Sha3".to_string().into()]).unwrap();
+        writer.flush().unwrap();
+        writer
+            .write_row(vec![2u64.into(), "Monero".to_string().into()])
+            .unwrap();
+        writer.write_row(vec![3u64.into(), "Sha3".to_string().into()]).unwrap();
+        writer.finish().unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 2);
+        assert_eq!(metadata.file_metadata().num_rows(), 3);
+        assert_eq!(metadata.file_metadata().schema_descr().num_columns(), 2);
+    }
+}
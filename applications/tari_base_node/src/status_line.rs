@@ -21,7 +21,59 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use chrono::Local;
-use std::{fmt, fmt::Display};
+use std::{fmt, fmt::Display, time::Duration};
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// The fields that can be selected for the status line, see `status_line_fields` in the config
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, EnumIter, EnumString)]
+#[strum(serialize_all = "kebab_case")]
+pub enum StatusLineField {
+    Version,
+    Network,
+    State,
+    Tip,
+    Hashrate,
+    BlockTiming,
+    MiningDistribution,
+    Mempool,
+    Connections,
+    Churn,
+    Banned,
+    Messages,
+    Rpc,
+    #[strum(serialize = "randomx")]
+    RandomX,
+}
+
+/// How often the status line is printed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StatusTicker {
+    /// Every 5 seconds while the node starts up and every 30 seconds after that
+    Default,
+    Every(Duration),
+    Off,
+}
+
+impl StatusTicker {
+    /// `None` keeps the default schedule and a zero interval turns the ticker off
+    pub fn from_interval(interval: Option<Duration>) -> Self {
+        match interval {
+            None => StatusTicker::Default,
+            Some(interval) if interval.as_secs() == 0 => StatusTicker::Off,
+            Some(interval) => StatusTicker::Every(interval),
+        }
+    }
+
+    /// The time until the next status line given how long the node has been running, or `None` if the ticker is off
+    pub fn interval(&self, uptime: Duration) -> Option<Duration> {
+        match self {
+            StatusTicker::Default if uptime.as_secs() <= 120 => Some(Duration::from_secs(5)),
+            StatusTicker::Default => Some(Duration::from_secs(30)),
+            StatusTicker::Every(interval) => Some(*interval),
+            StatusTicker::Off => None,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct StatusLine {
@@ -48,6 +100,18 @@ impl Display for StatusLine {
     }
 }
 
+/// Formats a hash rate with the largest unit that keeps the value above 1, e.g. `12.34 MH/s`
+pub fn format_hash_rate(hashes_per_sec: f64) -> String {
+    const UNITS: [&str; 6] = ["H/s", "kH/s", "MH/s", "GH/s", "TH/s", "PH/s"];
+    let mut rate = hashes_per_sec;
+    let mut unit = 0;
+    while rate >= 1000.0 && unit < UNITS.len() - 1 {
+        rate /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", rate, UNITS[unit])
+}
+
 fn format(k: &&str, v: &str) -> String {
     if k.is_empty() {
         v.to_string()
@@ -58,7 +122,8 @@ fn format(k: &&str, v: &str) -> String {
 
 #[cfg(test)]
 mod test {
-    use super::StatusLine;
+    use super::{format_hash_rate, StatusLine, StatusLineField, StatusTicker};
+    use std::time::Duration;
 
     #[test]
     fn test_do_not_display_empty_keys() {
@@ -74,4 +139,32 @@ mod test {
         assert!(display.contains("val"));
         assert_eq!(display.matches(':').count(), 1);
     }
+
+    #[test]
+    fn test_status_ticker() {
+        let ticker = StatusTicker::from_interval(None);
+        assert_eq!(ticker.interval(Duration::from_secs(10)), Some(Duration::from_secs(5)));
+        assert_eq!(ticker.interval(Duration::from_secs(600)), Some(Duration::from_secs(30)));
+        let ticker = StatusTicker::from_interval(Some(Duration::from_secs(2)));
+        assert_eq!(ticker.interval(Duration::from_secs(600)), Some(Duration::from_secs(2)));
+        let ticker = StatusTicker::from_interval(Some(Duration::from_secs(0)));
+        assert_eq!(ticker.interval(Duration::from_secs(600)), None);
+    }
+
+    #[test]
+    fn test_status_line_fields() {
+        assert_eq!("randomx".parse::<StatusLineField>().unwrap(), StatusLineField::RandomX);
+        assert_eq!(
+            "hashrate".parse::<StatusLineField>().unwrap(),
+            StatusLineField::Hashrate
+        );
+        assert!("height".parse::<StatusLineField>().is_err());
+    }
+
+    #[test]
+    fn test_format_hash_rate() {
+        assert_eq!(format_hash_rate(12.0), "12.00 H/s");
+        assert_eq!(format_hash_rate(12_345_678.0), "12.35 MH/s");
+        assert_eq!(format_hash_rate(2.5e18), "2500.00 PH/s");
+    }
 }
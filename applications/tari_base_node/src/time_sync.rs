@@ -0,0 +1,557 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Checks the local clock against NTP servers and the clocks of peers. Headers too far ahead of the local clock are
+//! rejected and block templates with a timestamp outside of the network's window are rejected by peers, so a mis-set
+//! clock causes confusing header rejections. The monitor measures the clock skew at startup and periodically, and
+//! warns when it exceeds the configured limit.
+
+use crate::alerts::{AlertKind, Alerter};
+use chrono::{DateTime, Utc};
+use log::*;
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    fmt,
+    io,
+    net::{Ipv6Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+use tari_comms::peer_manager::NodeId;
+use tari_p2p::services::liveness::{LivenessEvent, LivenessEventReceiver};
+use tari_shutdown::ShutdownSignal;
+use thiserror::Error;
+use tokio::{
+    net::{self, UdpSocket},
+    sync::{broadcast::error::RecvError, watch},
+    time,
+};
+
+const LOG_TARGET: &str = "base_node::time_sync";
+
+const NTP_PORT: u16 = 123;
+const NTP_PACKET_SIZE: usize = 48;
+/// Seconds from the NTP epoch (1900-01-01) to the unix epoch
+const NTP_UNIX_EPOCH_OFFSET: i64 = 2_208_988_800;
+const NTP_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// The number of peers whose clocks are needed before the peer median is used
+const MIN_PEER_SAMPLES: usize = 3;
+/// Peer clock samples older than this are discarded
+const PEER_SAMPLE_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Error)]
+pub enum TimeSyncError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Could not resolve '{0}'")]
+    UnresolvedServer(String),
+    #[error("The server did not respond within {0:.0?}")]
+    Timeout(Duration),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Converts milliseconds since the unix epoch to an NTP timestamp: seconds since 1900 in the high 32 bits and the
+/// fraction of a second in the low 32 bits
+fn to_ntp_timestamp(unix_millis: i64) -> u64 {
+    let millis = unix_millis + NTP_UNIX_EPOCH_OFFSET * 1000;
+    let secs = millis.div_euclid(1000) as u64;
+    // Rounded up so that converting back to milliseconds is exact
+    let fraction = (millis.rem_euclid(1000) as u64 * (1 << 32) + 999) / 1000;
+    (secs << 32) | fraction
+}
+
+/// Converts an NTP timestamp to milliseconds since the unix epoch
+fn from_ntp_timestamp(timestamp: u64) -> i64 {
+    let secs = (timestamp >> 32) as i64 - NTP_UNIX_EPOCH_OFFSET;
+    let millis = ((timestamp & 0xffff_ffff) * 1000) >> 32;
+    secs * 1000 + millis as i64
+}
+
+fn read_ntp_timestamp(packet: &[u8], offset: usize) -> u64 {
+    u64::from_be_bytes(packet[offset..offset + 8].try_into().expect("slice is 8 bytes"))
+}
+
+/// An SNTP client request (version 3, client mode) sent at the given time
+pub fn ntp_request(unix_millis: i64) -> [u8; NTP_PACKET_SIZE] {
+    let mut packet = [0u8; NTP_PACKET_SIZE];
+    packet[0] = 0x1b;
+    packet[40..48].copy_from_slice(&to_ntp_timestamp(unix_millis).to_be_bytes());
+    packet
+}
+
+/// Parses the response to `request` received at `received_at` (milliseconds since the unix epoch), and returns the
+/// offset of the server's clock from the local clock in milliseconds. The offset is positive when the local clock is
+/// behind the server's.
+pub fn parse_ntp_response(
+    response: &[u8],
+    request: &[u8; NTP_PACKET_SIZE],
+    received_at: i64,
+) -> Result<i64, TimeSyncError> {
+    if response.len() < NTP_PACKET_SIZE {
+        return Err(TimeSyncError::InvalidResponse(format!(
+            "expected at least {} bytes, got {}",
+            NTP_PACKET_SIZE,
+            response.len()
+        )));
+    }
+    let mode = response[0] & 0x07;
+    if mode != 4 {
+        return Err(TimeSyncError::InvalidResponse(format!("unexpected mode {}", mode)));
+    }
+    if response[0] >> 6 == 3 || response[1] == 0 {
+        return Err(TimeSyncError::InvalidResponse(
+            "the server is not synchronized".to_string(),
+        ));
+    }
+    let sent = read_ntp_timestamp(request, 40);
+    if read_ntp_timestamp(response, 24) != sent {
+        return Err(TimeSyncError::InvalidResponse(
+            "the response does not match the request".to_string(),
+        ));
+    }
+    let sent = from_ntp_timestamp(sent);
+    let server_received = from_ntp_timestamp(read_ntp_timestamp(response, 32));
+    let server_sent = from_ntp_timestamp(read_ntp_timestamp(response, 40));
+    Ok(((server_received - sent) + (server_sent - received_at)) / 2)
+}
+
+/// Adds the NTP port to a server address that does not have one
+fn with_default_port(server: &str) -> String {
+    if server.parse::<SocketAddr>().is_ok() {
+        return server.to_string();
+    }
+    if server.parse::<Ipv6Addr>().is_ok() {
+        return format!("[{}]:{}", server, NTP_PORT);
+    }
+    match server.rsplit_once(':') {
+        Some((_, port)) if port.parse::<u16>().is_ok() => server.to_string(),
+        _ => format!("{}:{}", server, NTP_PORT),
+    }
+}
+
+/// Queries an NTP server and returns the offset of its clock from the local clock in milliseconds
+pub async fn query_ntp_server(server: &str) -> Result<i64, TimeSyncError> {
+    let address = net::lookup_host(with_default_port(server))
+        .await?
+        .next()
+        .ok_or_else(|| TimeSyncError::UnresolvedServer(server.to_string()))?;
+    let socket = if address.is_ipv4() {
+        UdpSocket::bind("0.0.0.0:0").await?
+    } else {
+        UdpSocket::bind("[::]:0").await?
+    };
+    socket.connect(address).await?;
+    let request = ntp_request(Utc::now().timestamp_millis());
+    socket.send(&request).await?;
+    let mut response = [0u8; 128];
+    let len = time::timeout(NTP_QUERY_TIMEOUT, socket.recv(&mut response))
+        .await
+        .map_err(|_| TimeSyncError::Timeout(NTP_QUERY_TIMEOUT))??;
+    parse_ntp_response(&response[..len], &request, Utc::now().timestamp_millis())
+}
+
+fn median(values: &mut [i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        Some((values[mid - 1] + values[mid]) / 2)
+    } else {
+        Some(values[mid])
+    }
+}
+
+/// The most recent clock offset of each peer, measured from the timestamps in their liveness pongs
+#[derive(Debug, Default)]
+pub struct PeerClockSamples {
+    samples: HashMap<NodeId, (i64, Instant)>,
+}
+
+impl PeerClockSamples {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records the clock of a peer that sent a pong at `peer_timestamp` (milliseconds since the unix epoch), which
+    /// was received `latency` milliseconds after the ping was sent and at `received_at`. The pong is assumed to have
+    /// been sent halfway through the round trip.
+    pub fn add(&mut self, node_id: NodeId, peer_timestamp: u64, latency: u32, received_at: i64) {
+        let offset = peer_timestamp as i64 + i64::from(latency / 2) - received_at;
+        self.samples.insert(node_id, (offset, Instant::now()));
+    }
+
+    /// Discards the samples older than `ttl`
+    pub fn prune(&mut self, ttl: Duration) {
+        self.samples.retain(|_, (_, at)| at.elapsed() <= ttl);
+    }
+
+    pub fn num_peers(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// The median offset of the peers' clocks from the local clock in milliseconds, if enough peers were sampled
+    pub fn median_offset(&self) -> Option<i64> {
+        if self.samples.len() < MIN_PEER_SAMPLES {
+            return None;
+        }
+        let mut offsets = self.samples.values().map(|(offset, _)| *offset).collect::<Vec<_>>();
+        median(&mut offsets)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimeSyncConfig {
+    pub ntp_servers: Vec<String>,
+    pub interval: Duration,
+    pub max_clock_skew: Duration,
+    pub refuse_mining_on_clock_skew: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClockSkewStatus {
+    /// The median offset of the NTP servers' clocks from the local clock in milliseconds
+    pub ntp_offset: Option<i64>,
+    pub num_ntp_servers: usize,
+    /// The median offset of the peers' clocks from the local clock in milliseconds
+    pub peer_offset: Option<i64>,
+    pub num_peers: usize,
+    pub last_checked: Option<DateTime<Utc>>,
+    pub max_clock_skew: Duration,
+}
+
+impl ClockSkewStatus {
+    /// How far the local clock is behind (positive) or ahead (negative) of the network in milliseconds. NTP servers
+    /// are preferred over peers, since peers may share the local node's problem.
+    pub fn offset(&self) -> Option<i64> {
+        self.ntp_offset.or(self.peer_offset)
+    }
+
+    pub fn is_skewed(&self) -> bool {
+        self.exceeds_max_skew(self.offset())
+    }
+
+    /// Whether the skew measured against NTP servers exceeds the limit. Node ids cost nothing to create, so a Sybil
+    /// attacker connecting enough peers controls the peer median. A skew seen only by peers is warned about but is not
+    /// trusted to act on.
+    pub fn is_skewed_by_ntp(&self) -> bool {
+        self.exceeds_max_skew(self.ntp_offset)
+    }
+
+    fn exceeds_max_skew(&self, offset: Option<i64>) -> bool {
+        offset
+            .map(|offset| offset.unsigned_abs() > self.max_clock_skew.as_millis() as u64)
+            .unwrap_or(false)
+    }
+}
+
+impl fmt::Display for ClockSkewStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (offset, source) = match (self.ntp_offset, self.peer_offset) {
+            (Some(offset), _) => (offset, format!("{} NTP server(s)", self.num_ntp_servers)),
+            (None, Some(offset)) => (offset, format!("{} peer(s)", self.num_peers)),
+            (None, None) => return f.write_str("unknown"),
+        };
+        let direction = if offset > 0 { "behind" } else { "ahead" };
+        write!(
+            f,
+            "{:.3}s {} (measured against {})",
+            offset.unsigned_abs() as f64 / 1000.0,
+            direction,
+            source
+        )?;
+        if self.is_skewed() {
+            write!(f, " (exceeds {}s)", self.max_clock_skew.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+/// Handle to the latest clock skew measurement
+#[derive(Clone)]
+pub struct TimeSyncHandle {
+    status: watch::Receiver<ClockSkewStatus>,
+    is_enabled: bool,
+    refuse_mining_on_clock_skew: bool,
+}
+
+impl TimeSyncHandle {
+    /// A handle for when the time sync interval is set to 0
+    pub fn disabled() -> Self {
+        let (_, status) = watch::channel(ClockSkewStatus::default());
+        Self {
+            status,
+            is_enabled: false,
+            refuse_mining_on_clock_skew: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn status(&self) -> ClockSkewStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Returns the clock skew status if block templates should not be handed out because the local clock is skewed
+    /// according to the NTP servers. Peer clocks alone never refuse block templates, see
+    /// [ClockSkewStatus::is_skewed_by_ntp].
+    pub fn refuses_mining(&self) -> Option<ClockSkewStatus> {
+        if !self.is_enabled || !self.refuse_mining_on_clock_skew {
+            return None;
+        }
+        Some(self.status()).filter(|status| status.is_skewed_by_ntp())
+    }
+}
+
+/// Measures the local clock skew at startup and periodically against NTP servers, and continuously against the
+/// timestamps in the liveness pongs of peers
+pub struct TimeSyncMonitor {
+    config: TimeSyncConfig,
+    liveness_events: LivenessEventReceiver,
+    peer_clocks: PeerClockSamples,
+    alerter: Alerter,
+    status: watch::Sender<ClockSkewStatus>,
+    is_skewed: bool,
+}
+
+impl TimeSyncMonitor {
+    pub fn new(
+        config: TimeSyncConfig,
+        liveness_events: LivenessEventReceiver,
+        alerter: Alerter,
+    ) -> (Self, TimeSyncHandle) {
+        let (status, status_rx) = watch::channel(ClockSkewStatus {
+            max_clock_skew: config.max_clock_skew,
+            ..Default::default()
+        });
+        let handle = TimeSyncHandle {
+            status: status_rx,
+            is_enabled: true,
+            refuse_mining_on_clock_skew: config.refuse_mining_on_clock_skew,
+        };
+        let monitor = Self {
+            config,
+            liveness_events,
+            peer_clocks: PeerClockSamples::new(),
+            alerter,
+            status,
+            is_skewed: false,
+        };
+        (monitor, handle)
+    }
+
+    pub async fn run(mut self, mut shutdown_signal: ShutdownSignal) {
+        let mut interval = time::interval(self.config.interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    self.check().await;
+                },
+                event = self.liveness_events.recv() => match event {
+                    Ok(event) => self.handle_liveness_event(&*event),
+                    Err(RecvError::Lagged(n)) => {
+                        trace!(target: LOG_TARGET, "Missed {} liveness event(s)", n);
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown_signal.wait() => {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn handle_liveness_event(&mut self, event: &LivenessEvent) {
+        if let LivenessEvent::ReceivedPong(pong) = event {
+            if let (Some(timestamp), Some(latency)) = (pong.metadata.timestamp_millis(), pong.latency) {
+                self.peer_clocks
+                    .add(pong.node_id.clone(), timestamp, latency, Utc::now().timestamp_millis());
+                let mut status = self.status.borrow().clone();
+                status.peer_offset = self.peer_clocks.median_offset();
+                status.num_peers = self.peer_clocks.num_peers();
+                let _ = self.status.send(status);
+            }
+        }
+    }
+
+    async fn check(&mut self) {
+        let mut offsets = Vec::with_capacity(self.config.ntp_servers.len());
+        for server in &self.config.ntp_servers {
+            match query_ntp_server(server).await {
+                Ok(offset) => {
+                    debug!(target: LOG_TARGET, "Clock offset from '{}' is {}ms", server, offset);
+                    offsets.push(offset);
+                },
+                Err(err) => {
+                    debug!(target: LOG_TARGET, "Could not query NTP server '{}': {}", server, err);
+                },
+            }
+        }
+        if offsets.is_empty() && !self.config.ntp_servers.is_empty() {
+            warn!(
+                target: LOG_TARGET,
+                "None of the {} NTP server(s) responded, the clock skew is estimated from peers",
+                self.config.ntp_servers.len()
+            );
+        }
+        self.peer_clocks.prune(PEER_SAMPLE_TTL);
+
+        let mut status = self.status.borrow().clone();
+        status.num_ntp_servers = offsets.len();
+        status.ntp_offset = median(&mut offsets);
+        status.peer_offset = self.peer_clocks.median_offset();
+        status.num_peers = self.peer_clocks.num_peers();
+        status.last_checked = Some(Utc::now());
+        self.report(&status);
+        let _ = self.status.send(status);
+    }
+
+    /// Warns when the clock becomes skewed, and logs when it recovers
+    fn report(&mut self, status: &ClockSkewStatus) {
+        if status.offset().is_none() {
+            return;
+        }
+        let is_skewed = status.is_skewed();
+        if is_skewed && !self.is_skewed {
+            let text = format!(
+                "The local clock is {}. Headers may be rejected until the system clock is corrected.",
+                status
+            );
+            warn!(target: LOG_TARGET, "{}", text);
+            println!("WARNING: {}", text);
+            self.alerter.notify(AlertKind::ClockSkew, text);
+        } else if !is_skewed && self.is_skewed {
+            info!(target: LOG_TARGET, "The local clock is within the limit again: {}", status);
+        } else {
+            debug!(target: LOG_TARGET, "Clock skew: {}", status);
+        }
+        self.is_skewed = is_skewed;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn node_id(byte: u8) -> NodeId {
+        NodeId::try_from(&[byte; NodeId::BYTE_SIZE][..]).unwrap()
+    }
+
+    fn ntp_response(request: &[u8; NTP_PACKET_SIZE], server_received: i64, server_sent: i64) -> Vec<u8> {
+        let mut response = vec![0u8; NTP_PACKET_SIZE];
+        response[0] = 0x1c;
+        response[1] = 2;
+        response[24..32].copy_from_slice(&request[40..48]);
+        response[32..40].copy_from_slice(&to_ntp_timestamp(server_received).to_be_bytes());
+        response[40..48].copy_from_slice(&to_ntp_timestamp(server_sent).to_be_bytes());
+        response
+    }
+
+    #[test]
+    fn ntp_timestamp_round_trip() {
+        let millis = 1_634_567_890_123;
+        assert_eq!(from_ntp_timestamp(to_ntp_timestamp(millis)), millis);
+        assert_eq!(to_ntp_timestamp(0) >> 32, NTP_UNIX_EPOCH_OFFSET as u64);
+    }
+
+    #[test]
+    fn it_computes_the_clock_offset() {
+        let sent = 1_634_567_890_000;
+        let request = ntp_request(sent);
+        // The server's clock is 5s ahead and each leg of the round trip takes 100ms
+        let response = ntp_response(&request, sent + 5_100, sent + 5_110);
+        let offset = parse_ntp_response(&response, &request, sent + 210).unwrap();
+        assert_eq!(offset, 5_000);
+
+        // The server's clock is 2s behind
+        let response = ntp_response(&request, sent - 1_900, sent - 1_890);
+        let offset = parse_ntp_response(&response, &request, sent + 210).unwrap();
+        assert_eq!(offset, -2_000);
+    }
+
+    #[test]
+    fn it_rejects_invalid_responses() {
+        let request = ntp_request(1_634_567_890_000);
+        let response = ntp_response(&request, 1_634_567_890_000, 1_634_567_890_000);
+        assert!(parse_ntp_response(&response[..40], &request, 0).is_err());
+
+        let mut unsynchronized = response.clone();
+        unsynchronized[1] = 0;
+        assert!(parse_ntp_response(&unsynchronized, &request, 0).is_err());
+
+        let other_request = ntp_request(1_634_567_891_000);
+        assert!(parse_ntp_response(&response, &other_request, 0).is_err());
+    }
+
+    #[test]
+    fn it_adds_the_default_port() {
+        assert_eq!(with_default_port("pool.ntp.org"), "pool.ntp.org:123");
+        assert_eq!(with_default_port("pool.ntp.org:1123"), "pool.ntp.org:1123");
+        assert_eq!(with_default_port("127.0.0.1"), "127.0.0.1:123");
+        assert_eq!(with_default_port("::1"), "[::1]:123");
+        assert_eq!(with_default_port("[::1]:1123"), "[::1]:1123");
+    }
+
+    #[test]
+    fn it_takes_the_median_peer_offset() {
+        let mut peers = PeerClockSamples::new();
+        let now = 1_634_567_890_000;
+        peers.add(node_id(1), (now + 1_000) as u64, 200, now);
+        peers.add(node_id(2), (now - 500) as u64, 0, now);
+        assert_eq!(peers.median_offset(), None);
+
+        peers.add(node_id(3), (now + 60_000) as u64, 0, now);
+        assert_eq!(peers.num_peers(), 3);
+        assert_eq!(peers.median_offset(), Some(1_100));
+
+        // A new sample replaces the peer's previous one
+        peers.add(node_id(3), (now - 60_000) as u64, 0, now);
+        assert_eq!(peers.num_peers(), 3);
+        assert_eq!(peers.median_offset(), Some(-500));
+    }
+
+    #[test]
+    fn it_detects_skew() {
+        let mut status = ClockSkewStatus {
+            max_clock_skew: Duration::from_secs(30),
+            ..Default::default()
+        };
+        assert!(!status.is_skewed());
+        status.peer_offset = Some(-45_000);
+        assert!(status.is_skewed());
+        // Peers alone are not trusted to refuse block templates
+        assert!(!status.is_skewed_by_ntp());
+        // NTP servers are preferred over peers
+        status.ntp_offset = Some(1_000);
+        status.num_ntp_servers = 2;
+        assert!(!status.is_skewed());
+        assert_eq!(status.to_string(), "1.000s behind (measured against 2 NTP server(s))");
+        status.ntp_offset = Some(31_000);
+        assert!(status.is_skewed_by_ntp());
+    }
+}
@@ -0,0 +1,303 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Statistics about the unspent output set. The unspent outputs seen by a scan are kept in a cache, so that the next
+//! scan only loads the blocks added since and drops the outputs spent since.
+
+use std::{
+    cmp,
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+use strum_macros::{Display, EnumIter};
+use tari_common_types::types::{HashOutput, PublicKey};
+use tari_core::{
+    chain_storage::{async_db::AsyncBlockchainDb, ChainStorageError, LMDBDatabase, PrunedOutput},
+    transactions::transaction::{OutputFlags, TransactionOutput},
+};
+use tari_crypto::{script, tari_utilities::Hashable};
+
+/// The number of blocks that are loaded from the database at a time
+const BATCH_SIZE: u64 = 100;
+/// The width in bytes of the buckets of the size histogram
+pub const SIZE_BUCKET_WIDTH: u64 = 128;
+/// The upper bounds, in blocks until the output can be spent, of the maturity buckets
+const MATURITY_BUCKETS: [u64; 4] = [0, 10, 100, 1000];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumIter)]
+pub enum OutputKind {
+    Standard,
+    Coinbase,
+    #[strum(serialize = "One-sided")]
+    OneSided,
+    #[strum(serialize = "Custom script")]
+    CustomScript,
+}
+
+impl OutputKind {
+    /// Coinbase outputs are flagged. Standard outputs have a `Nop` script and one-sided payments a script that only
+    /// pushes the recipient's public key.
+    pub fn of(output: &TransactionOutput) -> Self {
+        if output.features.flags.contains(OutputFlags::COINBASE_OUTPUT) {
+            return OutputKind::Coinbase;
+        }
+        let bytes = output.script.as_bytes();
+        if bytes == script!(Nop).as_bytes() {
+            return OutputKind::Standard;
+        }
+        let one_sided = script!(PushPubKey(Box::new(PublicKey::default()))).as_bytes();
+        if bytes.len() == one_sided.len() && bytes.first() == one_sided.first() {
+            return OutputKind::OneSided;
+        }
+        OutputKind::CustomScript
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OutputSummary {
+    kind: OutputKind,
+    mined_height: u64,
+    maturity: u64,
+    size: u64,
+}
+
+impl OutputSummary {
+    fn new(output: &TransactionOutput, mined_height: u64) -> Self {
+        Self {
+            kind: OutputKind::of(output),
+            mined_height,
+            maturity: output.features.maturity,
+            size: bincode::serialized_size(output).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KindStats {
+    pub count: usize,
+    pub total_size: u64,
+    pub total_age: u64,
+}
+
+impl KindStats {
+    fn add(&mut self, output: &OutputSummary, tip_height: u64) {
+        self.count += 1;
+        self.total_size += output.size;
+        self.total_age += tip_height.saturating_sub(output.mined_height);
+    }
+
+    pub fn average_size(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.total_size as f64 / self.count as f64
+    }
+
+    /// The average number of blocks since the outputs were mined
+    pub fn average_age(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.total_age as f64 / self.count as f64
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UtxoStats {
+    pub tip_height: u64,
+    pub total: KindStats,
+    pub by_kind: BTreeMap<OutputKind, KindStats>,
+    /// The number of outputs by the upper bound of the number of blocks until they can be spent, `None` for outputs
+    /// that mature later than the last bucket
+    pub maturity: BTreeMap<Option<u64>, usize>,
+    /// The number of outputs by the lower bound of their size bucket
+    pub sizes: BTreeMap<u64, usize>,
+}
+
+/// The unspent outputs found by the last scan
+#[derive(Debug, Default)]
+pub struct UtxoStatsCache {
+    tip: Option<(u64, HashOutput)>,
+    num_outputs: u64,
+    outputs: HashMap<u32, OutputSummary>,
+}
+
+impl UtxoStatsCache {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The height of the tip at the last scan
+    pub fn tip_height(&self) -> Option<u64> {
+        self.tip.as_ref().map(|(height, _)| *height)
+    }
+
+    /// Brings the cache up to the current tip. Outputs spent since the last scan are dropped and the outputs of the
+    /// blocks added since are loaded. If the last scanned block is no longer in the chain, the UTXO set is scanned
+    /// from genesis. `on_progress` is called with the height reached and the tip height after every batch of blocks.
+    /// Returns the number of blocks that were scanned.
+    pub async fn update<F>(
+        &mut self,
+        db: &AsyncBlockchainDb<LMDBDatabase>,
+        mut on_progress: F,
+    ) -> Result<u64, ChainStorageError>
+    where
+        F: FnMut(u64, u64),
+    {
+        let tip = db.fetch_tip_header().await?;
+        let tip_height = tip.height();
+        if let Some((height, hash)) = self.tip.clone() {
+            let is_in_chain = height <= tip_height &&
+                db.fetch_header(height)
+                    .await?
+                    .map(|header| header.hash() == hash)
+                    .unwrap_or(false);
+            if !is_in_chain {
+                self.clear();
+            }
+        }
+
+        let deleted = db
+            .fetch_complete_deleted_bitmap_at(tip.hash().clone())
+            .await?
+            .into_bitmap();
+        self.outputs.retain(|position, _| !deleted.contains(*position));
+        let deleted = Arc::new(deleted);
+
+        let start = self.tip_height().map(|height| height + 1).unwrap_or(0);
+        let mut height = start;
+        while height <= tip_height {
+            let batch_end = cmp::min(height + BATCH_SIZE - 1, tip_height);
+            let headers = db.fetch_headers(height..=batch_end).await?;
+            let last = headers.last().ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockHeader",
+                field: "height",
+                value: height.to_string(),
+            })?;
+            let outputs = if last.output_mmr_size > self.num_outputs {
+                db.fetch_utxos_by_mmr_position(self.num_outputs, last.output_mmr_size - 1, deleted.clone())
+                    .await?
+                    .0
+            } else {
+                Vec::new()
+            };
+            let mut outputs = outputs.into_iter();
+            for header in &headers {
+                while self.num_outputs < header.output_mmr_size {
+                    let output = match outputs.next() {
+                        Some(output) => output,
+                        None => break,
+                    };
+                    let position = self.num_outputs as u32;
+                    self.num_outputs += 1;
+                    // Spent outputs are returned pruned
+                    if let PrunedOutput::NotPruned { output } = output {
+                        self.outputs
+                            .insert(position, OutputSummary::new(&output, header.height));
+                    }
+                }
+            }
+            on_progress(batch_end, tip_height);
+            height = batch_end + 1;
+        }
+        self.tip = Some((tip_height, tip.hash().clone()));
+        Ok(tip_height + 1 - start)
+    }
+
+    pub fn stats(&self) -> UtxoStats {
+        let tip_height = self.tip_height().unwrap_or_default();
+        let mut stats = UtxoStats {
+            tip_height,
+            ..Default::default()
+        };
+        for output in self.outputs.values() {
+            stats.total.add(output, tip_height);
+            stats.by_kind.entry(output.kind).or_default().add(output, tip_height);
+            *stats
+                .maturity
+                .entry(maturity_bucket(output.maturity, tip_height))
+                .or_default() += 1;
+            *stats
+                .sizes
+                .entry(output.size / SIZE_BUCKET_WIDTH * SIZE_BUCKET_WIDTH)
+                .or_default() += 1;
+        }
+        stats
+    }
+}
+
+/// The maturity bucket of an output, by the number of blocks after the tip until the output can be spent
+fn maturity_bucket(maturity: u64, tip_height: u64) -> Option<u64> {
+    let blocks_until_spendable = maturity.saturating_sub(tip_height + 1);
+    MATURITY_BUCKETS
+        .iter()
+        .find(|bound| blocks_until_spendable <= **bound)
+        .copied()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn summary(kind: OutputKind, mined_height: u64, maturity: u64, size: u64) -> OutputSummary {
+        OutputSummary {
+            kind,
+            mined_height,
+            maturity,
+            size,
+        }
+    }
+
+    #[test]
+    fn maturity_buckets() {
+        assert_eq!(maturity_bucket(0, 100), Some(0));
+        assert_eq!(maturity_bucket(101, 100), Some(0));
+        assert_eq!(maturity_bucket(102, 100), Some(10));
+        assert_eq!(maturity_bucket(201, 100), Some(100));
+        assert_eq!(maturity_bucket(1101, 100), Some(1000));
+        assert_eq!(maturity_bucket(1102, 100), None);
+    }
+
+    #[test]
+    fn stats() {
+        let mut cache = UtxoStatsCache {
+            tip: Some((100, vec![0; 32])),
+            ..Default::default()
+        };
+        cache.outputs.insert(0, summary(OutputKind::Coinbase, 10, 1010, 900));
+        cache.outputs.insert(1, summary(OutputKind::Standard, 50, 0, 950));
+        cache.outputs.insert(2, summary(OutputKind::Standard, 90, 0, 1050));
+
+        let stats = cache.stats();
+        assert_eq!(stats.total.count, 3);
+        assert_eq!(stats.by_kind[&OutputKind::Standard].count, 2);
+        assert_eq!(stats.by_kind[&OutputKind::Standard].average_size(), 1000.0);
+        assert_eq!(stats.by_kind[&OutputKind::Standard].average_age(), 30.0);
+        assert_eq!(stats.by_kind[&OutputKind::Coinbase].count, 1);
+        assert!(!stats.by_kind.contains_key(&OutputKind::OneSided));
+        assert_eq!(stats.maturity[&Some(0)], 2);
+        assert_eq!(stats.maturity[&Some(1000)], 1);
+        assert_eq!(stats.sizes[&896], 2);
+        assert_eq!(stats.sizes[&1024], 1);
+    }
+}
@@ -29,11 +29,15 @@ use std::{
     fmt::{Display, Formatter},
     str::FromStr,
 };
-use tari_app_utilities::utilities::parse_emoji_id_or_public_key;
+use tari_app_utilities::{message_signature::MessageSignature, utilities::parse_emoji_id_or_public_key};
 use tari_comms::multiaddr::Multiaddr;
 
-use tari_common_types::types::PublicKey;
+use tari_common_types::{stealth_address::StealthAddress, types::PublicKey};
 use tari_core::transactions::tari_amount::MicroTari;
+use tari_crypto::tari_utilities::hex::Hex;
+
+/// The number of days shown by `balance-history` when `--days` is not given
+const DEFAULT_BALANCE_HISTORY_DAYS: u64 = 30;
 
 #[derive(Debug)]
 pub struct ParsedCommand {
@@ -46,15 +50,44 @@ impl Display for ParsedCommand {
         use WalletCommand::*;
         let command = match self.command {
             GetBalance => "get-balance",
+            BalanceHistory => "balance-history",
             SendTari => "send-tari",
             SendOneSided => "send-one-sided",
+            SendStealth => "send-stealth",
+            InitSwap => "init-swap",
+            ClaimSwap => "claim-swap",
+            RefundSwap => "refund-swap",
+            BurnTari => "burn-tari",
+            CancelTransaction => "cancel-transaction",
+            BumpFee => "bump-fee",
+            CreateAccount => "create-account",
+            ListAccounts => "list-accounts",
+            TransferBetweenAccounts => "transfer-between-accounts",
+            ExportUnsignedTx => "export-unsigned-tx",
+            SignOfflineTx => "sign-offline-tx",
+            BroadcastSignedTx => "broadcast-signed-tx",
+            ExportWatchOnlyKeys => "export-watch-only-keys",
+            MultisigCreate => "multisig-create",
+            MultisigAccept => "multisig-accept",
+            MultisigFund => "multisig-fund",
+            MultisigSign => "multisig-sign",
+            MultisigSpend => "multisig-spend",
+            MultisigApprove => "multisig-approve",
+            MultisigFinalize => "multisig-finalize",
             MakeItRain => "make-it-rain",
             CoinSplit => "coin-split",
             DiscoverPeer => "discover-peer",
             Whois => "whois",
+            StealthAddress => "stealth-address",
             ExportUtxos => "export-utxos",
             ExportSpentUtxos => "export-spent-utxos",
+            ImportUtxo => "import-utxo",
             CountUtxos => "count-utxos",
+            AuditKeys => "audit-keys",
+            VerifyOutputProof => "verify-output-proof",
+            SignMessage => "sign-message",
+            VerifyMessage => "verify-message",
+            RotateEncryption => "rotate-encryption",
             SetBaseNode => "set-base-node",
             SetCustomBaseNode => "set-custom-base-node",
             ClearCustomBaseNode => "clear-custom-base-node",
@@ -75,6 +108,7 @@ impl Display for ParsedCommand {
 pub enum ParsedArgument {
     Amount(MicroTari),
     PublicKey(PublicKey),
+    StealthAddress(StealthAddress),
     Text(String),
     Float(f64),
     Int(u64),
@@ -83,6 +117,8 @@ pub enum ParsedArgument {
     CSVFileName(String),
     Address(Multiaddr),
     Negotiated(bool),
+    Hash(Vec<u8>),
+    QrCodes(bool),
 }
 
 impl Display for ParsedArgument {
@@ -91,6 +127,7 @@ impl Display for ParsedArgument {
         match self {
             Amount(v) => write!(f, "{}", v.to_string()),
             PublicKey(v) => write!(f, "{}", v.to_string()),
+            StealthAddress(v) => write!(f, "{}", v.to_string()),
             Text(v) => write!(f, "{}", v.to_string()),
             Float(v) => write!(f, "{}", v.to_string()),
             Int(v) => write!(f, "{}", v.to_string()),
@@ -99,6 +136,8 @@ impl Display for ParsedArgument {
             CSVFileName(v) => write!(f, "{}", v.to_string()),
             Address(v) => write!(f, "{}", v.to_string()),
             Negotiated(v) => write!(f, "{}", v.to_string()),
+            Hash(v) => write!(f, "{}", v.to_hex()),
+            QrCodes(v) => write!(f, "{}", if *v { "--qr" } else { "" }),
         }
     }
 }
@@ -113,15 +152,44 @@ pub fn parse_command(command: &str) -> Result<ParsedCommand, ParseError> {
     use WalletCommand::*;
     let args = match command {
         GetBalance => Vec::new(),
+        BalanceHistory => parse_balance_history(args)?,
         SendTari => parse_send_tari(args)?,
         SendOneSided => parse_send_tari(args)?,
+        SendStealth => parse_send_stealth(args)?,
+        InitSwap => parse_init_swap(args)?,
+        ClaimSwap => parse_claim_swap(args)?,
+        RefundSwap => parse_refund_swap(args)?,
+        BurnTari => parse_burn_tari(args)?,
+        CancelTransaction => parse_cancel_transaction(args)?,
+        BumpFee => parse_bump_fee(args)?,
+        CreateAccount => parse_create_account(args)?,
+        ListAccounts => Vec::new(),
+        TransferBetweenAccounts => parse_transfer_between_accounts(args)?,
+        ExportUnsignedTx => parse_export_unsigned_tx(args)?,
+        SignOfflineTx => parse_sign_offline_tx(args)?,
+        BroadcastSignedTx => parse_broadcast_signed_tx(args)?,
+        ExportWatchOnlyKeys => parse_export_watch_only_keys(args)?,
+        MultisigCreate => parse_multisig_create(args)?,
+        MultisigAccept => parse_session_id(args)?,
+        MultisigFund => parse_multisig_fund(args)?,
+        MultisigSign => parse_multisig_sign(args)?,
+        MultisigSpend => parse_multisig_spend(args)?,
+        MultisigApprove => parse_signing_id(args)?,
+        MultisigFinalize => parse_signing_id(args)?,
         MakeItRain => parse_make_it_rain(args)?,
         CoinSplit => parse_coin_split(args)?,
         DiscoverPeer => parse_public_key(args)?,
         Whois => parse_whois(args)?,
+        StealthAddress => Vec::new(),
         ExportUtxos => parse_export_utxos(args)?, // todo: only show X number of utxos
         ExportSpentUtxos => parse_export_spent_utxos(args)?, // todo: only show X number of utxos
+        ImportUtxo => parse_import_utxo(args)?,
         CountUtxos => Vec::new(),
+        AuditKeys => parse_audit_keys(args)?,
+        VerifyOutputProof => parse_verify_output_proof(args)?,
+        SignMessage => parse_sign_message(args)?,
+        VerifyMessage => parse_verify_message(args)?,
+        RotateEncryption => Vec::new(),
         SetBaseNode => parse_public_key_and_address(args)?,
         SetCustomBaseNode => parse_public_key_and_address(args)?,
         ClearCustomBaseNode => Vec::new(),
@@ -130,6 +198,25 @@ pub fn parse_command(command: &str) -> Result<ParsedCommand, ParseError> {
     Ok(ParsedCommand { command, args })
 }
 
+fn parse_balance_history(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let usage = "\n  Usage:\n    balance-history\n    balance-history --days <number of days>";
+    let days = match args.next() {
+        None => DEFAULT_BALANCE_HISTORY_DAYS,
+        Some("--days") => {
+            let days = args
+                .next()
+                .ok_or_else(|| ParseError::Empty(format!("number of days{}", usage)))?;
+            days.parse::<u64>()?
+        },
+        Some(_) => return Err(ParseError::Empty(format!("'--days' qualifier{}", usage))),
+    };
+    if days == 0 {
+        return Err(ParseError::Invalid("the number of days must be at least 1".to_string()));
+    }
+
+    Ok(vec![ParsedArgument::Int(days)])
+}
+
 fn parse_whois(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
     let mut parsed_args = Vec::new();
 
@@ -276,6 +363,356 @@ fn parse_send_tari(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, Par
     Ok(parsed_args)
 }
 
+fn parse_send_stealth(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // amount
+    let amount = args.next().ok_or_else(|| ParseError::Empty("amount".to_string()))?;
+    let amount = MicroTari::from_str(amount)?;
+    parsed_args.push(ParsedArgument::Amount(amount));
+
+    // stealth address
+    let address = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("stealth address".to_string()))?;
+    let address = StealthAddress::from_str(address).map_err(|_| ParseError::StealthAddress)?;
+    parsed_args.push(ParsedArgument::StealthAddress(address));
+
+    // message
+    let message = args.collect::<Vec<&str>>().join(" ");
+    parsed_args.push(ParsedArgument::Text(message));
+
+    Ok(parsed_args)
+}
+
+fn parse_init_swap(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // amount
+    let amount = args.next().ok_or_else(|| ParseError::Empty("amount".to_string()))?;
+    let amount = MicroTari::from_str(amount)?;
+    parsed_args.push(ParsedArgument::Amount(amount));
+
+    // public key/emoji id
+    let pubkey = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("public key or emoji id".to_string()))?;
+    let pubkey = parse_emoji_id_or_public_key(pubkey).ok_or(ParseError::PublicKey)?;
+    parsed_args.push(ParsedArgument::PublicKey(pubkey));
+
+    // number of blocks until the swap can be refunded
+    let lock_blocks = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("number of lock blocks".to_string()))?;
+    let lock_blocks = lock_blocks.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(lock_blocks));
+
+    // message
+    let message = args.collect::<Vec<&str>>().join(" ");
+    parsed_args.push(ParsedArgument::Text(message));
+
+    Ok(parsed_args)
+}
+
+fn parse_claim_swap(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // output hash
+    let hash = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("output hash".to_string()))?;
+    let hash = Vec::<u8>::from_hex(hash).map_err(|_| ParseError::Hash)?;
+    parsed_args.push(ParsedArgument::Hash(hash));
+
+    // pre-image
+    let pre_image = args.next().ok_or_else(|| ParseError::Empty("pre-image".to_string()))?;
+    let pre_image = PublicKey::from_hex(pre_image).map_err(|_| ParseError::PublicKey)?;
+    parsed_args.push(ParsedArgument::PublicKey(pre_image));
+
+    Ok(parsed_args)
+}
+
+fn parse_refund_swap(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // tx id of the atomic swap transaction
+    let tx_id = args.next().ok_or_else(|| ParseError::Empty("tx_id".to_string()))?;
+    let tx_id = tx_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(tx_id));
+
+    Ok(parsed_args)
+}
+
+fn parse_cancel_transaction(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // tx id of the transaction to cancel
+    let tx_id = args.next().ok_or_else(|| ParseError::Empty("tx_id".to_string()))?;
+    let tx_id = tx_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(tx_id));
+
+    // reason
+    let reason = args.collect::<Vec<&str>>().join(" ");
+    parsed_args.push(ParsedArgument::Text(reason));
+
+    Ok(parsed_args)
+}
+
+fn parse_bump_fee(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // tx id of the stuck transaction
+    let tx_id = args.next().ok_or_else(|| ParseError::Empty("tx_id".to_string()))?;
+    let tx_id = tx_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(tx_id));
+
+    // fee per gram that the stuck and fee bump transactions pay together
+    let fee_per_gram = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("fee per gram".to_string()))?;
+    let fee_per_gram = MicroTari::from_str(fee_per_gram)?;
+    parsed_args.push(ParsedArgument::Amount(fee_per_gram));
+
+    Ok(parsed_args)
+}
+
+fn parse_create_account(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // account name
+    let name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("account name".to_string()))?;
+    parsed_args.push(ParsedArgument::Text(name.to_string()));
+
+    Ok(parsed_args)
+}
+
+fn parse_transfer_between_accounts(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // source and destination account names
+    let from = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("source account".to_string()))?;
+    parsed_args.push(ParsedArgument::Text(from.to_string()));
+    let to = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("destination account".to_string()))?;
+    parsed_args.push(ParsedArgument::Text(to.to_string()));
+
+    // amount
+    let amount = args.next().ok_or_else(|| ParseError::Empty("amount".to_string()))?;
+    let amount = MicroTari::from_str(amount)?;
+    parsed_args.push(ParsedArgument::Amount(amount));
+
+    // message
+    let message = args.collect::<Vec<&str>>().join(" ");
+    parsed_args.push(ParsedArgument::Text(message));
+
+    Ok(parsed_args)
+}
+
+fn parse_burn_tari(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // amount to burn
+    let amount = args.next().ok_or_else(|| ParseError::Empty("amount".to_string()))?;
+    let amount = MicroTari::from_str(amount)?;
+    parsed_args.push(ParsedArgument::Amount(amount));
+
+    // message
+    let message = args.collect::<Vec<&str>>().join(" ");
+    parsed_args.push(ParsedArgument::Text(message));
+
+    Ok(parsed_args)
+}
+
+/// Parse the optional `--qr` flag that writes a package as QR code chunks rather than JSON
+fn parse_qr_flag(args: &mut SplitWhitespace) -> ParsedArgument {
+    let qr_codes = args.clone().next() == Some("--qr");
+    if qr_codes {
+        args.next();
+    }
+    ParsedArgument::QrCodes(qr_codes)
+}
+
+fn parse_export_unsigned_tx(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let usage = "\n  Usage:\n    export-unsigned-tx [--qr] <file name> <amount> <public key or emoji id> [message]";
+    let mut parsed_args = vec![parse_qr_flag(&mut args)];
+
+    // package file
+    let file_name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty(format!("file name{}", usage)))?;
+    parsed_args.push(ParsedArgument::Text(file_name.to_string()));
+
+    // amount, public key/emoji id and message
+    parsed_args.extend(parse_send_tari(args)?);
+
+    Ok(parsed_args)
+}
+
+fn parse_sign_offline_tx(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let usage = "\n  Usage:\n    sign-offline-tx [--qr] <unsigned file name> <signed file name>";
+    let mut parsed_args = vec![parse_qr_flag(&mut args)];
+
+    // unsigned package file
+    let file_name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty(format!("unsigned file name{}", usage)))?;
+    parsed_args.push(ParsedArgument::Text(file_name.to_string()));
+
+    // signed package file
+    let file_name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty(format!("signed file name{}", usage)))?;
+    parsed_args.push(ParsedArgument::Text(file_name.to_string()));
+
+    Ok(parsed_args)
+}
+
+fn parse_broadcast_signed_tx(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let file_name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("file name\n  Usage:\n    broadcast-signed-tx <file name>".to_string()))?;
+    Ok(vec![ParsedArgument::Text(file_name.to_string())])
+}
+
+fn parse_export_watch_only_keys(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let file_name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("file name\n  Usage:\n    export-watch-only-keys <file name>".to_string()))?;
+    Ok(vec![ParsedArgument::Text(file_name.to_string())])
+}
+
+fn parse_import_utxo(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // file holding the unblinded output as JSON
+    let file_name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("file name\n  Usage:\n    import-utxo <file name> [message]".to_string()))?;
+    parsed_args.push(ParsedArgument::Text(file_name.to_string()));
+
+    // message
+    let message = args.collect::<Vec<&str>>().join(" ");
+    parsed_args.push(ParsedArgument::Text(message));
+
+    Ok(parsed_args)
+}
+
+fn parse_multisig_create(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // number of signatures required
+    let threshold = args.next().ok_or_else(|| ParseError::Empty("threshold".to_string()))?;
+    let threshold = threshold.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(threshold));
+
+    // public keys/emoji ids of the other participants
+    parsed_args.extend(parse_public_key_list(args, "participant public keys or emoji ids")?);
+
+    Ok(parsed_args)
+}
+
+fn parse_multisig_fund(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // multisig session id
+    let session_id = args.next().ok_or_else(|| ParseError::Empty("session id".to_string()))?;
+    let session_id = session_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(session_id));
+
+    // amount to lock in the multisig output
+    let amount = args.next().ok_or_else(|| ParseError::Empty("amount".to_string()))?;
+    let amount = MicroTari::from_str(amount)?;
+    parsed_args.push(ParsedArgument::Amount(amount));
+
+    Ok(parsed_args)
+}
+
+fn parse_multisig_spend(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // multisig session id
+    let session_id = args.next().ok_or_else(|| ParseError::Empty("session id".to_string()))?;
+    let session_id = session_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(session_id));
+
+    // commitment of the multisig output
+    let commitment = args.next().ok_or_else(|| ParseError::Empty("commitment".to_string()))?;
+    let commitment = Vec::<u8>::from_hex(commitment).map_err(|_| ParseError::Hash)?;
+    parsed_args.push(ParsedArgument::Hash(commitment));
+
+    // destination public key/emoji id
+    let destination = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("destination".to_string()))?;
+    let destination = parse_emoji_id_or_public_key(destination).ok_or(ParseError::PublicKey)?;
+    parsed_args.push(ParsedArgument::PublicKey(destination));
+
+    // public keys/emoji ids of the other signers
+    parsed_args.extend(parse_public_key_list(args, "signer public keys or emoji ids")?);
+
+    Ok(parsed_args)
+}
+
+fn parse_multisig_sign(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // multisig session id
+    let session_id = args.next().ok_or_else(|| ParseError::Empty("session id".to_string()))?;
+    let session_id = session_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(session_id));
+
+    // message to sign
+    let message = args.next().ok_or_else(|| ParseError::Empty("message".to_string()))?;
+    let message = Vec::<u8>::from_hex(message).map_err(|_| ParseError::Hash)?;
+    parsed_args.push(ParsedArgument::Hash(message));
+
+    // public keys/emoji ids of the other signers
+    parsed_args.extend(parse_public_key_list(args, "signer public keys or emoji ids")?);
+
+    Ok(parsed_args)
+}
+
+fn parse_session_id(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // multisig session id
+    let session_id = args.next().ok_or_else(|| ParseError::Empty("session id".to_string()))?;
+    let session_id = session_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(session_id));
+
+    Ok(parsed_args)
+}
+
+fn parse_signing_id(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // multisig signing id
+    let signing_id = args.next().ok_or_else(|| ParseError::Empty("signing id".to_string()))?;
+    let signing_id = signing_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(signing_id));
+
+    Ok(parsed_args)
+}
+
+fn parse_public_key_list(args: SplitWhitespace, name: &str) -> Result<Vec<ParsedArgument>, ParseError> {
+    let pubkeys = args
+        .map(|k| {
+            parse_emoji_id_or_public_key(k)
+                .map(ParsedArgument::PublicKey)
+                .ok_or(ParseError::PublicKey)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if pubkeys.is_empty() {
+        return Err(ParseError::Empty(name.to_string()));
+    }
+    Ok(pubkeys)
+}
+
 fn parse_export_utxos(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
     let mut parsed_args = Vec::new();
 
@@ -299,6 +736,65 @@ fn parse_export_utxos(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>,
     Ok(parsed_args)
 }
 
+fn parse_audit_keys(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    if let Some(v) = args.next() {
+        if v == "--csv-file" {
+            let file_name = args.next().ok_or_else(|| {
+                ParseError::Empty(
+                    "file name\n  Usage:\n    audit-keys\n    audit-keys --csv-file <file name>".to_string(),
+                )
+            })?;
+            parsed_args.push(ParsedArgument::OutputToCSVFile("--csv-file".to_string()));
+            parsed_args.push(ParsedArgument::CSVFileName(file_name.to_string()));
+        } else {
+            return Err(ParseError::Empty(
+                "'--csv-file' qualifier\n  Usage:\n    audit-keys\n    audit-keys --csv-file <file name>".to_string(),
+            ));
+        }
+    };
+
+    Ok(parsed_args)
+}
+
+fn parse_verify_output_proof(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let file_name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("file name\n  Usage:\n    verify-output-proof <file name>".to_string()))?;
+    Ok(vec![ParsedArgument::Text(file_name.to_string())])
+}
+
+fn parse_sign_message(args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let message = args.collect::<Vec<_>>().join(" ");
+    if message.is_empty() {
+        return Err(ParseError::Empty(
+            "message\n  Usage:\n    sign-message <message>".to_string(),
+        ));
+    }
+    Ok(vec![ParsedArgument::Text(message)])
+}
+
+fn parse_verify_message(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let usage = "\n  Usage:\n    verify-message <public key or emoji id> <signature> <message>";
+    let pubkey = args
+        .next()
+        .ok_or_else(|| ParseError::Empty(format!("public key or emoji id{}", usage)))?;
+    let pubkey = parse_emoji_id_or_public_key(pubkey).ok_or(ParseError::PublicKey)?;
+    let signature = args
+        .next()
+        .ok_or_else(|| ParseError::Empty(format!("signature{}", usage)))?;
+    // Checked here so that a malformed signature is reported before the wallet starts
+    signature.parse::<MessageSignature>()?;
+    let message = args.collect::<Vec<_>>().join(" ");
+
+    Ok(vec![
+        ParsedArgument::PublicKey(pubkey),
+        ParsedArgument::Text(signature.to_string()),
+        ParsedArgument::Text(message),
+    ])
+}
+
 fn parse_export_spent_utxos(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
     let mut parsed_args = Vec::new();
 
@@ -349,9 +845,10 @@ mod test {
     };
     use rand::rngs::OsRng;
     use std::str::FromStr;
+    use tari_app_utilities::message_signature::MessageSignature;
     use tari_common_types::types::PublicKey;
     use tari_core::transactions::tari_amount::MicroTari;
-    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+    use tari_crypto::{keys::PublicKey as PublicKeyTrait, tari_utilities::hex::Hex};
 
     #[test]
     fn test_parse_command() {
@@ -419,6 +916,158 @@ mod test {
             panic!("Parsed public key is not the same as provided.");
         }
 
+        let command_str = format!("init-swap 999T {} 100 swap msg", public_key);
+        let parsed = parse_command(&command_str).unwrap();
+
+        if let ParsedArgument::PublicKey(pk) = parsed.args[1].clone() {
+            assert_eq!(pk, public_key);
+        } else {
+            panic!("Parsed public key is not the same as provided.");
+        }
+        if let ParsedArgument::Int(lock_blocks) = parsed.args[2].clone() {
+            assert_eq!(lock_blocks, 100);
+        } else {
+            panic!("Parsed lock blocks is not the same as provided.");
+        }
+
+        let hash = vec![7u8; 32];
+        let command_str = format!("claim-swap {} {}", hash.to_hex(), public_key.to_hex());
+        let parsed = parse_command(&command_str).unwrap();
+
+        if let ParsedArgument::Hash(h) = parsed.args[0].clone() {
+            assert_eq!(h, hash);
+        } else {
+            panic!("Parsed output hash is not the same as provided.");
+        }
+        if let ParsedArgument::PublicKey(pk) = parsed.args[1].clone() {
+            assert_eq!(pk, public_key);
+        } else {
+            panic!("Parsed pre-image is not the same as provided.");
+        }
+
+        let command_str = "claim-swap not_hex".to_string();
+        let parsed = parse_command(&command_str);
+        assert!(parsed.is_err());
+
+        let command_str = "refund-swap 12345".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+
+        if let ParsedArgument::Int(tx_id) = parsed.args[0].clone() {
+            assert_eq!(tx_id, 12345);
+        } else {
+            panic!("Parsed tx_id is not the same as provided.");
+        }
+
+        let command_str = "cancel-transaction 12345 fee was too low".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::Int(12345)));
+        assert!(matches!(parsed.args[1].clone(), ParsedArgument::Text(r) if r == "fee was too low"));
+
+        let command_str = "cancel-transaction not_a_tx_id".to_string();
+        assert!(parse_command(&command_str).is_err());
+
+        let command_str = "bump-fee 12345 100".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::Int(12345)));
+        assert!(matches!(parsed.args[1], ParsedArgument::Amount(fee) if fee == MicroTari::from(100)));
+
+        let command_str = "bump-fee 12345".to_string();
+        assert!(parse_command(&command_str).is_err());
+
+        let parsed = parse_command("balance-history").unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::Int(30)));
+        let parsed = parse_command("balance-history --days 7").unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::Int(7)));
+        assert!(parse_command("balance-history --days").is_err());
+        assert!(parse_command("balance-history --days 0").is_err());
+        assert!(parse_command("balance-history 7").is_err());
+
+        let command_str = "import-utxo faucet.json from the faucet".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0].clone(), ParsedArgument::Text(file) if file == "faucet.json"));
+        assert!(matches!(parsed.args[1].clone(), ParsedArgument::Text(m) if m == "from the faucet"));
+
+        let command_str = "import-utxo".to_string();
+        assert!(parse_command(&command_str).is_err());
+
+        let command_str = "create-account savings".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0].clone(), ParsedArgument::Text(name) if name == "savings"));
+
+        let command_str = "create-account".to_string();
+        assert!(parse_command(&command_str).is_err());
+
+        let command_str = "transfer-between-accounts default savings 1T rainy day".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0].clone(), ParsedArgument::Text(from) if from == "default"));
+        assert!(matches!(parsed.args[1].clone(), ParsedArgument::Text(to) if to == "savings"));
+        assert!(matches!(parsed.args[2], ParsedArgument::Amount(amount) if amount == MicroTari::from(1_000_000)));
+        assert!(matches!(parsed.args[3].clone(), ParsedArgument::Text(m) if m == "rainy day"));
+
+        let command_str = "transfer-between-accounts default savings".to_string();
+        assert!(parse_command(&command_str).is_err());
+
+        let command_str = format!("multisig-create 2 {} {}", public_key, public_key);
+        let parsed = parse_command(&command_str).unwrap();
+
+        if let ParsedArgument::Int(threshold) = parsed.args[0].clone() {
+            assert_eq!(threshold, 2);
+        } else {
+            panic!("Parsed threshold is not the same as provided.");
+        }
+        assert_eq!(parsed.args.len(), 3);
+
+        let command_str = "multisig-create 2".to_string();
+        let parsed = parse_command(&command_str);
+        assert!(parsed.is_err());
+
+        let command_str = "multisig-accept 12345".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::Int(12345)));
+
+        let command_str = "multisig-accept".to_string();
+        assert!(parse_command(&command_str).is_err());
+
+        let command_str = "multisig-fund 12345 1T".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::Int(12345)));
+        assert!(matches!(parsed.args[1], ParsedArgument::Amount(amount) if amount == MicroTari::from(1_000_000)));
+
+        let command_str = "multisig-fund 12345".to_string();
+        assert!(parse_command(&command_str).is_err());
+
+        let command_str = format!("multisig-spend 12345 {} {} {}", hash.to_hex(), public_key, public_key);
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[1].clone(), ParsedArgument::Hash(commitment) if commitment == hash));
+        assert!(matches!(parsed.args[2].clone(), ParsedArgument::PublicKey(pk) if pk == public_key));
+        assert_eq!(parsed.args.len(), 4);
+
+        let command_str = format!("multisig-spend 12345 {}", hash.to_hex());
+        assert!(parse_command(&command_str).is_err());
+
+        let command_str = format!("multisig-sign 12345 {} {}", hash.to_hex(), public_key);
+        let parsed = parse_command(&command_str).unwrap();
+
+        if let ParsedArgument::Hash(message) = parsed.args[1].clone() {
+            assert_eq!(message, hash);
+        } else {
+            panic!("Parsed message is not the same as provided.");
+        }
+        if let ParsedArgument::PublicKey(pk) = parsed.args[2].clone() {
+            assert_eq!(pk, public_key);
+        } else {
+            panic!("Parsed signer is not the same as provided.");
+        }
+
+        let command_str = "multisig-finalize 12345".to_string();
+        let parsed = parse_command(&command_str).unwrap();
+
+        if let ParsedArgument::Int(signing_id) = parsed.args[0].clone() {
+            assert_eq!(signing_id, 12345);
+        } else {
+            panic!("Parsed signing id is not the same as provided.");
+        }
+
         let command_str = "export-utxos --csv-file utxo_list.csv".to_string();
         let parsed = parse_command(&command_str).unwrap();
 
@@ -481,4 +1130,55 @@ mod test {
             },
         }
     }
+
+    #[test]
+    fn test_parse_offline_signing_commands() {
+        let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+
+        let command_str = format!("export-unsigned-tx --qr unsigned.txt 1T {} cold storage", public_key);
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::QrCodes(true)));
+        assert!(matches!(parsed.args[1].clone(), ParsedArgument::Text(f) if f == "unsigned.txt"));
+        assert!(matches!(parsed.args[2], ParsedArgument::Amount(_)));
+        assert!(matches!(parsed.args[3].clone(), ParsedArgument::PublicKey(pk) if pk == public_key));
+        assert!(matches!(parsed.args[4].clone(), ParsedArgument::Text(m) if m == "cold storage"));
+
+        let command_str = format!("export-unsigned-tx 1T {}", public_key);
+        assert!(parse_command(&command_str).is_err());
+
+        let parsed = parse_command("sign-offline-tx unsigned.json signed.json").unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::QrCodes(false)));
+        assert!(matches!(parsed.args[2].clone(), ParsedArgument::Text(f) if f == "signed.json"));
+        assert!(parse_command("sign-offline-tx --qr unsigned.json").is_err());
+
+        let parsed = parse_command("broadcast-signed-tx signed.json").unwrap();
+        assert!(matches!(parsed.args[0].clone(), ParsedArgument::Text(f) if f == "signed.json"));
+        assert!(parse_command("broadcast-signed-tx").is_err());
+
+        let parsed = parse_command("export-watch-only-keys keys.txt").unwrap();
+        assert!(matches!(parsed.args[0].clone(), ParsedArgument::Text(f) if f == "keys.txt"));
+        assert!(parse_command("export-watch-only-keys").is_err());
+    }
+
+    #[test]
+    fn test_parse_message_signing_commands() {
+        let (secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+
+        let parsed = parse_command("sign-message I own   this wallet").unwrap();
+        assert!(matches!(parsed.args[0].clone(), ParsedArgument::Text(m) if m == "I own this wallet"));
+        assert!(parse_command("sign-message").is_err());
+
+        let signature = MessageSignature::sign(&secret_key, "I own this wallet").unwrap();
+        let command_str = format!("verify-message {} {} I own this wallet", public_key, signature);
+        let parsed = parse_command(&command_str).unwrap();
+        assert!(matches!(parsed.args[0].clone(), ParsedArgument::PublicKey(pk) if pk == public_key));
+        assert!(matches!(parsed.args[1].clone(), ParsedArgument::Text(s) if s == signature.to_string()));
+        assert!(matches!(parsed.args[2].clone(), ParsedArgument::Text(m) if m == "I own this wallet"));
+
+        let command_str = format!("verify-message {} v9:00:00 I own this wallet", public_key);
+        assert!(matches!(
+            parse_command(&command_str),
+            Err(ParseError::MessageSignature(_))
+        ));
+    }
 }
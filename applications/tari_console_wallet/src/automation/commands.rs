@@ -23,7 +23,8 @@
 use super::error::CommandError;
 use log::*;
 use std::{
-    fs::File,
+    convert::TryFrom,
+    fs::{self, File},
     io::{LineWriter, Write},
     str::FromStr,
     time::{Duration, Instant},
@@ -31,15 +32,22 @@ use std::{
 
 use chrono::{DateTime, Utc};
 use futures::FutureExt;
+use qrcode::{render::unicode, QrCode};
+use serde_json::json;
 use strum_macros::{Display, EnumIter, EnumString};
 use tari_crypto::ristretto::pedersen::PedersenCommitmentFactory;
 
 use crate::{
     automation::command_parser::{ParsedArgument, ParsedCommand},
+    init::{prompt_new_password, prompt_password},
     utils::db::{CUSTOM_BASE_NODE_ADDRESS_KEY, CUSTOM_BASE_NODE_PUBLIC_KEY_KEY},
 };
+use tari_app_utilities::message_signature::MessageSignature;
 use tari_common::GlobalConfig;
-use tari_common_types::{emoji::EmojiId, types::PublicKey};
+use tari_common_types::{
+    emoji::EmojiId,
+    types::{Commitment, HashOutput, PublicKey},
+};
 use tari_comms::{
     connectivity::{ConnectivityEvent, ConnectivityRequester},
     multiaddr::Multiaddr,
@@ -47,15 +55,32 @@ use tari_comms::{
 };
 use tari_comms_dht::{envelope::NodeDestination, DhtDiscoveryRequester};
 use tari_core::{
-    tari_utilities::hex::Hex,
+    blocks::OutputProof,
+    proto::base_node::FetchMatchingUtxos,
+    tari_utilities::{hex::Hex, ByteArray, Hashable},
     transactions::{
         tari_amount::{uT, MicroTari, Tari},
-        transaction::UnblindedOutput,
+        transaction::{TransactionOutput, UnblindedOutput},
+        CryptoFactories,
     },
 };
 use tari_wallet::{
-    output_manager_service::{handle::OutputManagerHandle, TxId},
-    transaction_service::handle::{TransactionEvent, TransactionServiceHandle},
+    base_node_service::handle::BaseNodeServiceHandle,
+    connectivity_service::WalletConnectivityHandle,
+    multisig_service::handle::MultisigServiceHandle,
+    output_manager_service::{
+        handle::OutputManagerHandle,
+        key_audit::KeyAuditReport,
+        storage::models::WalletAccount,
+        TxId,
+    },
+    transaction_service::{
+        balance_history::BalanceSnapshot,
+        handle::{TransactionEvent, TransactionServiceHandle},
+        offline_signing::{OfflinePackage, SignedTransactionPackage, UnsignedTransactionPackage, DEFAULT_CHUNK_SIZE},
+        storage::models::TransactionCancellation,
+    },
+    wallet::EncryptionRotationProgress,
     WalletSqlite,
 };
 use tokio::{
@@ -65,20 +90,52 @@ use tokio::{
 
 pub const LOG_TARGET: &str = "wallet::automation::commands";
 
+/// How long to wait for the base node service to learn the chain tip before giving up
+const CHAIN_TIP_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Enum representing commands used by the wallet
 #[derive(Clone, PartialEq, Debug, Display, EnumIter, EnumString)]
 #[strum(serialize_all = "kebab_case")]
 pub enum WalletCommand {
     GetBalance,
+    BalanceHistory,
     SendTari,
     SendOneSided,
+    SendStealth,
+    InitSwap,
+    ClaimSwap,
+    RefundSwap,
+    BurnTari,
+    CancelTransaction,
+    BumpFee,
+    CreateAccount,
+    ListAccounts,
+    TransferBetweenAccounts,
+    ExportUnsignedTx,
+    SignOfflineTx,
+    BroadcastSignedTx,
+    ExportWatchOnlyKeys,
+    MultisigCreate,
+    MultisigAccept,
+    MultisigFund,
+    MultisigSign,
+    MultisigSpend,
+    MultisigApprove,
+    MultisigFinalize,
     MakeItRain,
     CoinSplit,
     DiscoverPeer,
     Whois,
+    StealthAddress,
     ExportUtxos,
     ExportSpentUtxos,
+    ImportUtxo,
     CountUtxos,
+    AuditKeys,
+    VerifyOutputProof,
+    SignMessage,
+    VerifyMessage,
+    RotateEncryption,
     SetBaseNode,
     SetCustomBaseNode,
     ClearCustomBaseNode,
@@ -101,6 +158,15 @@ pub struct SentTransaction {
     stage: TransactionStage,
 }
 
+/// The details of an atomic swap initiated by this wallet
+#[derive(Debug)]
+pub struct InitiatedSwap {
+    pub tx_id: TxId,
+    pub pre_image: PublicKey,
+    pub output_hash: HashOutput,
+    pub refund_height: u64,
+}
+
 fn get_transaction_parameters(
     args: Vec<ParsedArgument>,
 ) -> Result<(MicroTari, MicroTari, PublicKey, String), CommandError> {
@@ -127,6 +193,43 @@ fn get_transaction_parameters(
 }
 
 /// Send a normal negotiated transaction to a recipient
+/// Returns the balance at the end of each of the requested number of days, up to and including today
+pub async fn balance_history(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<Vec<BalanceSnapshot>, CommandError> {
+    let days = match args[0] {
+        ParsedArgument::Int(days) => Ok(u32::try_from(days).unwrap_or(u32::MAX)),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let transactions = wallet_transaction_service.get_completed_transactions().await?;
+    Ok(tari_wallet::transaction_service::balance_history::balance_history(
+        transactions.values(),
+        Utc::now().naive_utc().date(),
+        days,
+    ))
+}
+
+/// Prints the balance history as a bar chart, one day per line
+fn print_balance_history(history: &[BalanceSnapshot]) {
+    const BAR_WIDTH: u64 = 50;
+    let max = history.iter().map(|s| u64::from(s.balance)).max().unwrap_or_default();
+    for snapshot in history {
+        let bar = match max {
+            0 => 0,
+            max => (u128::from(u64::from(snapshot.balance)) * u128::from(BAR_WIDTH) / u128::from(max)) as usize,
+        };
+        println!(
+            "{} |{:<width$}| {}",
+            snapshot.date.format("%Y-%m-%d"),
+            "#".repeat(bar),
+            snapshot.balance,
+            width = BAR_WIDTH as usize
+        );
+    }
+}
+
 pub async fn send_tari(
     mut wallet_transaction_service: TransactionServiceHandle,
     args: Vec<ParsedArgument>,
@@ -150,6 +253,616 @@ pub async fn send_one_sided(
         .map_err(CommandError::TransactionServiceError)
 }
 
+/// Send a one-sided transaction to a stealth address
+pub async fn send_stealth(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    // TODO: Consolidate "fee per gram" in codebase
+    let fee_per_gram = 25 * uT;
+
+    use ParsedArgument::*;
+    let amount = match args[0] {
+        Amount(amount) => Ok(amount),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let address = match args[1].clone() {
+        StealthAddress(address) => Ok(address),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let message = match args[2].clone() {
+        Text(msg) => Ok(msg),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    wallet_transaction_service
+        .send_stealth_transaction(address, amount, fee_per_gram, message)
+        .await
+        .map_err(CommandError::TransactionServiceError)
+}
+
+/// Send a hash time locked contract (HTLC) transaction to a recipient, for use in an atomic swap. The funds can be
+/// refunded once the given number of blocks have been mined.
+pub async fn init_swap(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    mut base_node_service: BaseNodeServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<InitiatedSwap, CommandError> {
+    // TODO: Consolidate "fee per gram" in codebase
+    let fee_per_gram = 25 * uT;
+
+    use ParsedArgument::*;
+    let amount = match args[0].clone() {
+        Amount(mtari) => Ok(mtari),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let dest_pubkey = match args[1].clone() {
+        PublicKey(key) => Ok(key),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let lock_blocks = match args[2].clone() {
+        Int(n) => Ok(n),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let message = match args[3].clone() {
+        Text(msg) => Ok(msg),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let tip_height = wait_for_chain_tip(&mut base_node_service).await?;
+    let refund_height = tip_height + lock_blocks;
+
+    let (tx_id, pre_image, output_hash) = wallet_transaction_service
+        .send_sha_atomic_swap_transaction(dest_pubkey, amount, fee_per_gram, refund_height, message)
+        .await
+        .map_err(CommandError::TransactionServiceError)?;
+
+    Ok(InitiatedSwap {
+        tx_id,
+        pre_image,
+        output_hash,
+        refund_height,
+    })
+}
+
+/// Returns the height of the chain tip once the base node service has received it from the base node. A freshly
+/// started wallet will not know the chain tip until the first base node monitor round has completed.
+async fn wait_for_chain_tip(base_node_service: &mut BaseNodeServiceHandle) -> Result<u64, CommandError> {
+    let started = Instant::now();
+    loop {
+        let metadata = base_node_service
+            .get_chain_metadata()
+            .await
+            .map_err(|e| CommandError::Comms(e.to_string()))?;
+        if let Some(metadata) = metadata {
+            return Ok(metadata.height_of_longest_chain());
+        }
+        if started.elapsed() > CHAIN_TIP_TIMEOUT {
+            return Err(CommandError::Comms(
+                "Chain tip is not known, is the base node online?".to_string(),
+            ));
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Claim the funds of a hash time locked contract (HTLC) output sent to this wallet using its pre-image
+pub async fn claim_swap(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    mut wallet_connectivity: WalletConnectivityHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    // TODO: Consolidate "fee per gram" in codebase
+    let fee_per_gram = 25 * uT;
+
+    use ParsedArgument::*;
+    let output_hash = match args[0].clone() {
+        Hash(hash) => Ok(hash),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let pre_image = match args[1].clone() {
+        PublicKey(key) => Ok(key),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let mut client = wallet_connectivity
+        .obtain_base_node_wallet_rpc_client()
+        .await
+        .ok_or_else(|| CommandError::Comms("Could not connect to the base node".to_string()))?;
+    let response = client
+        .fetch_matching_utxos(FetchMatchingUtxos {
+            output_hashes: vec![output_hash.clone()],
+        })
+        .await
+        .map_err(|e| CommandError::Comms(e.to_string()))?;
+    let output = response.outputs.into_iter().next().ok_or_else(|| {
+        CommandError::AtomicSwap(format!("Output {} not found in the UTXO set", output_hash.to_hex()))
+    })?;
+    let output = TransactionOutput::try_from(output).map_err(CommandError::AtomicSwap)?;
+
+    let tx_id = wallet_transaction_service
+        .claim_sha_atomic_swap_transaction(output, pre_image, fee_per_gram)
+        .await
+        .map_err(CommandError::TransactionServiceError)?;
+
+    Ok(tx_id)
+}
+
+/// Reclaim the funds of a hash time locked contract (HTLC) transaction sent by this wallet, once its time lock has
+/// expired
+pub async fn refund_swap(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    // TODO: Consolidate "fee per gram" in codebase
+    let fee_per_gram = 25 * uT;
+
+    let htlc_tx_id = match args[0] {
+        ParsedArgument::Int(tx_id) => Ok(tx_id),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let tx_id = wallet_transaction_service
+        .refund_sha_atomic_swap_transaction(htlc_tx_id, fee_per_gram)
+        .await
+        .map_err(CommandError::TransactionServiceError)?;
+
+    Ok(tx_id)
+}
+
+/// Cancels a pending or unmined outbound transaction and returns the cancellation recorded for it
+pub async fn cancel_transaction(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<Option<TransactionCancellation>, CommandError> {
+    use ParsedArgument::*;
+    let tx_id = match args[0] {
+        Int(tx_id) => Ok(tx_id),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let reason = match args[1].clone() {
+        Text(reason) => Ok(Some(reason).filter(|r| !r.is_empty())),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    wallet_transaction_service
+        .cancel_transaction_with_reason(tx_id, reason)
+        .await
+        .map_err(CommandError::TransactionServiceError)?;
+
+    let cancellation = wallet_transaction_service
+        .get_transaction_cancellation(tx_id)
+        .await
+        .map_err(CommandError::TransactionServiceError)?;
+
+    Ok(cancellation)
+}
+
+pub async fn bump_fee(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    use ParsedArgument::*;
+    let tx_id = match args[0] {
+        Int(tx_id) => Ok(tx_id),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let fee_per_gram = match args[1] {
+        Amount(fee_per_gram) => Ok(fee_per_gram),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    wallet_transaction_service
+        .bump_fee(tx_id, fee_per_gram)
+        .await
+        .map_err(CommandError::TransactionServiceError)
+}
+
+pub async fn create_account(
+    mut output_service: OutputManagerHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<WalletAccount, CommandError> {
+    let name = match args[0].clone() {
+        ParsedArgument::Text(name) => Ok(name),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    Ok(output_service.create_account(name).await?)
+}
+
+/// Prints every account of the wallet along with its balance
+pub async fn list_accounts(mut output_service: OutputManagerHandle) -> Result<(), CommandError> {
+    let accounts = output_service.get_accounts().await?;
+    for account in accounts {
+        let balance = output_service.get_account_balance(account.id).await?;
+        println!("{} created {}", account, account.created_at);
+        println!("{}\n", balance);
+    }
+
+    Ok(())
+}
+
+pub async fn transfer_between_accounts(
+    mut output_service: OutputManagerHandle,
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    use ParsedArgument::*;
+    // TODO: Consolidate "fee per gram" in codebase
+    let fee_per_gram = 25 * uT;
+
+    let from = match args[0].clone() {
+        Text(from) => Ok(from),
+        _ => Err(CommandError::Argument),
+    }?;
+    let to = match args[1].clone() {
+        Text(to) => Ok(to),
+        _ => Err(CommandError::Argument),
+    }?;
+    let amount = match args[2] {
+        Amount(amount) => Ok(amount),
+        _ => Err(CommandError::Argument),
+    }?;
+    let message = match args[3].clone() {
+        Text(message) => Ok(message),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let from = output_service.find_account(&from).await?;
+    let to = output_service.find_account(&to).await?;
+    wallet_transaction_service
+        .transfer_between_accounts(from.id, to.id, amount, fee_per_gram, message)
+        .await
+        .map_err(CommandError::TransactionServiceError)
+}
+
+pub async fn burn_tari(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    // TODO: Consolidate "fee per gram" in codebase
+    let fee_per_gram = 25 * uT;
+
+    use ParsedArgument::*;
+    let amount = match args[0] {
+        Amount(amount) => Ok(amount),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let message = match args[1].clone() {
+        Text(msg) => Ok(msg),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let tx_id = wallet_transaction_service
+        .burn_tari(amount, fee_per_gram, message)
+        .await
+        .map_err(CommandError::TransactionServiceError)?;
+
+    Ok(tx_id)
+}
+
+/// Reserve the inputs of a one-sided payment and write them to a package for an offline wallet to sign
+pub async fn export_unsigned_tx(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<UnsignedTransactionPackage, CommandError> {
+    let (qr_codes, path) = get_package_parameters(&args)?;
+    let (fee_per_gram, amount, dest_pubkey, message) = get_transaction_parameters(args[2..].to_vec())?;
+
+    let package = wallet_transaction_service
+        .export_unsigned_transaction(dest_pubkey, amount, fee_per_gram, message)
+        .await?;
+    write_package(&package, &path, qr_codes)?;
+    println!(
+        "Reserved {} input(s) worth {} for TxId: {}",
+        package.inputs.len(),
+        package.input_value(),
+        package.tx_id
+    );
+    println!("Unsigned transaction written to {}", path);
+
+    Ok(package)
+}
+
+/// Sign a package exported by an online wallet and write the signed transaction to a package for it to broadcast
+pub async fn sign_offline_tx(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<SignedTransactionPackage, CommandError> {
+    let (qr_codes, unsigned_path) = get_package_parameters(&args)?;
+    let signed_path = match args[2].clone() {
+        ParsedArgument::Text(path) => Ok(path),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let unsigned = read_package::<UnsignedTransactionPackage>(&unsigned_path)?;
+    println!("TxId       : {}", unsigned.tx_id);
+    println!("Destination: {}", unsigned.destination);
+    println!("Amount     : {}", unsigned.amount);
+    println!(
+        "Inputs     : {} worth {}",
+        unsigned.inputs.len(),
+        unsigned.input_value()
+    );
+    let package = wallet_transaction_service.sign_offline_transaction(unsigned).await?;
+    write_package(&package, &signed_path, qr_codes)?;
+    println!("Fee        : {}", package.fee);
+    println!("Signed transaction written to {}", signed_path);
+
+    Ok(package)
+}
+
+/// Broadcast a package signed by an offline wallet
+pub async fn broadcast_signed_tx(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    let path = match args[0].clone() {
+        ParsedArgument::Text(path) => Ok(path),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let package = read_package::<SignedTransactionPackage>(&path)?;
+    let tx_id = wallet_transaction_service.broadcast_signed_transaction(package).await?;
+
+    Ok(tx_id)
+}
+
+/// Write the rewind keys and the stealth view key of the wallet to a file, from which a watch-only wallet can be created
+/// to reserve the inputs of offline signed transactions
+pub async fn export_watch_only_keys(
+    mut output_service: OutputManagerHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<(), CommandError> {
+    let path = match args[0].clone() {
+        ParsedArgument::Text(path) => Ok(path),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let keys = output_service.get_watch_only_keys().await?;
+    fs::write(&path, keys.to_hex())
+        .map_err(|e| CommandError::OfflinePackage(format!("could not write '{}': {}", path, e)))?;
+    println!("Watch-only keys written to {}", path);
+
+    Ok(())
+}
+
+fn get_package_parameters(args: &[ParsedArgument]) -> Result<(bool, String), CommandError> {
+    match (args.get(0), args.get(1)) {
+        (Some(ParsedArgument::QrCodes(qr_codes)), Some(ParsedArgument::Text(path))) => Ok((*qr_codes, path.clone())),
+        _ => Err(CommandError::Argument),
+    }
+}
+
+/// Write the package to `path` as JSON or, with `qr_codes`, as chunks that are also printed as QR codes
+fn write_package<P: OfflinePackage>(package: &P, path: &str, qr_codes: bool) -> Result<(), CommandError> {
+    let contents = if qr_codes {
+        let chunks = package
+            .to_chunks(DEFAULT_CHUNK_SIZE)
+            .map_err(|e| CommandError::OfflinePackage(e.to_string()))?;
+        for (i, chunk) in chunks.iter().enumerate() {
+            let code = QrCode::new(chunk).map_err(|e| CommandError::OfflinePackage(e.to_string()))?;
+            println!("QR code {} of {}", i + 1, chunks.len());
+            println!(
+                "{}",
+                code.render::<unicode::Dense1x2>()
+                    .dark_color(unicode::Dense1x2::Dark)
+                    .light_color(unicode::Dense1x2::Light)
+                    .build()
+            );
+        }
+        chunks.join("\n")
+    } else {
+        package
+            .to_json()
+            .map_err(|e| CommandError::OfflinePackage(e.to_string()))?
+    };
+    fs::write(path, contents).map_err(|e| CommandError::OfflinePackage(format!("could not write '{}': {}", path, e)))
+}
+
+/// Read a package from `path`, which holds either its JSON or its chunks, one per line
+fn read_package<P: OfflinePackage>(path: &str) -> Result<P, CommandError> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| CommandError::OfflinePackage(format!("could not read '{}': {}", path, e)))?;
+    P::read(&text).map_err(|e| CommandError::OfflinePackage(format!("could not parse '{}': {}", path, e)))
+}
+
+/// Imports the external UTXO held as JSON in a file, once the base node confirms that it is in the UTXO set
+pub async fn import_utxo(wallet: &mut WalletSqlite, args: Vec<ParsedArgument>) -> Result<TxId, CommandError> {
+    let path = match args[0].clone() {
+        ParsedArgument::Text(path) => Ok(path),
+        _ => Err(CommandError::Argument),
+    }?;
+    let message = match args[1].clone() {
+        ParsedArgument::Text(message) if !message.is_empty() => Ok(message),
+        ParsedArgument::Text(_) => Ok(format!("Imported external UTXO from {}", path)),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let text =
+        fs::read_to_string(&path).map_err(|e| CommandError::ImportUtxo(format!("could not read '{}': {}", path, e)))?;
+    let output: UnblindedOutput = serde_json::from_str(&text)
+        .map_err(|e| CommandError::ImportUtxo(format!("could not parse '{}': {}", path, e)))?;
+    let tx_id = wallet
+        .import_external_utxo(output, &CommsPublicKey::default(), message)
+        .await?;
+
+    Ok(tx_id)
+}
+
+/// Create an m-of-n multisig session between this wallet and the provided participants
+pub async fn multisig_create(
+    mut multisig_service: MultisigServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<u64, CommandError> {
+    let threshold = match args[0] {
+        ParsedArgument::Int(threshold) => Ok(threshold as usize),
+        _ => Err(CommandError::Argument),
+    }?;
+    let participants = get_public_keys(&args[1..])?;
+
+    let session = multisig_service.create_session(threshold, participants).await?;
+    println!(
+        "Multisig session {} created ({}-of-{})",
+        session.session_id,
+        session.threshold,
+        session.participants.len()
+    );
+    println!("The group public key is available once every participant has dealt its share of the key");
+
+    Ok(session.session_id)
+}
+
+/// Join a multisig session that another participant invited this wallet to
+pub async fn multisig_accept(
+    mut multisig_service: MultisigServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<(), CommandError> {
+    let session_id = match args[0] {
+        ParsedArgument::Int(session_id) => Ok(session_id),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let session = multisig_service.accept_session(session_id).await?;
+    println!(
+        "Multisig session {} accepted ({}-of-{})",
+        session.session_id,
+        session.threshold,
+        session.participants.len()
+    );
+
+    Ok(())
+}
+
+/// Fund the group key of a multisig session with a multisig output
+pub async fn multisig_fund(
+    mut multisig_service: MultisigServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    let session_id = match args[0] {
+        ParsedArgument::Int(session_id) => Ok(session_id),
+        _ => Err(CommandError::Argument),
+    }?;
+    let amount = match args[1] {
+        ParsedArgument::Amount(amount) => Ok(amount),
+        _ => Err(CommandError::Argument),
+    }?;
+    let fee_per_gram = 25 * uT;
+
+    let (tx_id, commitment) = multisig_service.fund_session(session_id, amount, fee_per_gram).await?;
+    println!("Multisig output {} sent in TxId: {}", commitment.to_hex(), tx_id);
+
+    Ok(tx_id)
+}
+
+/// Start a signing round of a message with the provided signers of a multisig session
+pub async fn multisig_sign(
+    mut multisig_service: MultisigServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<u64, CommandError> {
+    let session_id = match args[0] {
+        ParsedArgument::Int(session_id) => Ok(session_id),
+        _ => Err(CommandError::Argument),
+    }?;
+    let message = match args[1].clone() {
+        ParsedArgument::Hash(message) => Ok(message),
+        _ => Err(CommandError::Argument),
+    }?;
+    let signers = get_public_keys(&args[2..])?;
+
+    let signing_id = multisig_service.start_signing(session_id, message, signers).await?;
+    println!("Multisig signing round {} started", signing_id);
+
+    Ok(signing_id)
+}
+
+/// Start a signing round that spends a multisig output of a session to a destination as a one-sided payment
+pub async fn multisig_spend(
+    mut multisig_service: MultisigServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<u64, CommandError> {
+    let session_id = match args[0] {
+        ParsedArgument::Int(session_id) => Ok(session_id),
+        _ => Err(CommandError::Argument),
+    }?;
+    let commitment = match args[1].clone() {
+        ParsedArgument::Hash(commitment) => Commitment::from_bytes(&commitment).map_err(|_| CommandError::Argument),
+        _ => Err(CommandError::Argument),
+    }?;
+    let destination = match args[2].clone() {
+        ParsedArgument::PublicKey(destination) => Ok(destination),
+        _ => Err(CommandError::Argument),
+    }?;
+    let signers = get_public_keys(&args[3..])?;
+    let fee_per_gram = 25 * uT;
+
+    let signing_id = multisig_service
+        .start_spend(session_id, commitment, destination, fee_per_gram, signers)
+        .await?;
+    println!(
+        "Multisig spend signing round {} started, the transaction is submitted once every signer approved it",
+        signing_id
+    );
+
+    Ok(signing_id)
+}
+
+/// Approve a signing round that was requested by another participant of a multisig session
+pub async fn multisig_approve(
+    mut multisig_service: MultisigServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<(), CommandError> {
+    let signing_id = match args[0] {
+        ParsedArgument::Int(signing_id) => Ok(signing_id),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let round = multisig_service.get_signing_round(signing_id).await?;
+    println!(
+        "Approving signing of {} for multisig session {}",
+        round.request, round.session_id
+    );
+    multisig_service.approve_signing(signing_id).await?;
+    println!("Multisig signing round {} approved", signing_id);
+
+    Ok(())
+}
+
+/// Print the aggregated signature of a completed signing round
+pub async fn multisig_finalize(
+    mut multisig_service: MultisigServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<(), CommandError> {
+    let signing_id = match args[0] {
+        ParsedArgument::Int(signing_id) => Ok(signing_id),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let signature = multisig_service.finalize_signing(signing_id).await?;
+    println!("Public nonce: {}", signature.get_public_nonce().to_hex());
+    println!("Signature   : {}", signature.get_signature().to_hex());
+
+    Ok(())
+}
+
+fn get_public_keys(args: &[ParsedArgument]) -> Result<Vec<PublicKey>, CommandError> {
+    args.iter()
+        .map(|a| match a {
+            ParsedArgument::PublicKey(key) => Ok(key.clone()),
+            _ => Err(CommandError::Argument),
+        })
+        .collect()
+}
+
 pub async fn coin_split(
     args: &[ParsedArgument],
     output_service: &mut OutputManagerHandle,
@@ -177,8 +890,15 @@ pub async fn coin_split(
 }
 
 async fn wait_for_comms(connectivity_requester: &ConnectivityRequester) -> Result<(), CommandError> {
-    let mut connectivity = connectivity_requester.get_event_subscription();
     print!("Waiting for connectivity... ");
+    let result = wait_for_base_node_connection(connectivity_requester).await;
+    println!("{}", if result.is_ok() { "✅" } else { "❌" });
+    result
+}
+
+/// Waits up to 30 seconds for the first connection to a base node
+async fn wait_for_base_node_connection(connectivity_requester: &ConnectivityRequester) -> Result<(), CommandError> {
+    let mut connectivity = connectivity_requester.get_event_subscription();
     let timeout = sleep(Duration::from_secs(30));
     tokio::pin!(timeout);
     let mut timeout = timeout.fuse();
@@ -187,12 +907,10 @@ async fn wait_for_comms(connectivity_requester: &ConnectivityRequester) -> Resul
             // Wait for the first base node connection
             Ok(ConnectivityEvent::PeerConnected(conn)) = connectivity.recv() => {
                 if conn.peer_features().is_node() {
-                    println!("✅");
                     return Ok(());
                 }
             },
             () = &mut timeout => {
-                println!("❌");
                 return Err(CommandError::Comms("Timed out".to_string()));
             }
         }
@@ -437,11 +1155,6 @@ pub async fn monitor_transactions(
     let mut event_stream = transaction_service.get_event_stream();
     let mut results = Vec::new();
     debug!(target: LOG_TARGET, "monitor transactions wait_stage: {:?}", wait_stage);
-    println!(
-        "Monitoring {} sent transactions to {:?} stage...",
-        tx_ids.len(),
-        wait_stage
-    );
 
     loop {
         match event_stream.recv().await {
@@ -576,6 +1289,10 @@ pub async fn command_runner(
                 },
                 Err(e) => eprintln!("GetBalance error! {}", e),
             },
+            BalanceHistory => {
+                let history = balance_history(transaction_service.clone(), parsed.args).await?;
+                print_balance_history(&history);
+            },
             DiscoverPeer => {
                 if !online {
                     wait_for_comms(&connectivity_requester).await?;
@@ -593,6 +1310,149 @@ pub async fn command_runner(
                 debug!(target: LOG_TARGET, "send-one-sided tx_id {}", tx_id);
                 tx_ids.push(tx_id);
             },
+            SendStealth => {
+                let tx_id = send_stealth(transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "send-stealth tx_id {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            InitSwap => {
+                let swap = init_swap(
+                    transaction_service.clone(),
+                    wallet.base_node_service.clone(),
+                    parsed.args,
+                )
+                .await?;
+                debug!(target: LOG_TARGET, "init-swap tx_id {}", swap.tx_id);
+                println!("Atomic swap transaction sent");
+                println!("TxId         : {}", swap.tx_id);
+                println!("Pre-image    : {}", swap.pre_image.to_hex());
+                println!("Output hash  : {}", swap.output_hash.to_hex());
+                println!("Refund height: {}", swap.refund_height);
+                tx_ids.push(swap.tx_id);
+            },
+            ClaimSwap => {
+                let tx_id = claim_swap(
+                    transaction_service.clone(),
+                    wallet.wallet_connectivity.clone(),
+                    parsed.args,
+                )
+                .await?;
+                debug!(target: LOG_TARGET, "claim-swap tx_id {}", tx_id);
+                println!("Atomic swap claimed in TxId: {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            RefundSwap => {
+                let tx_id = refund_swap(transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "refund-swap tx_id {}", tx_id);
+                println!("Atomic swap refunded in TxId: {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            BurnTari => {
+                let tx_id = burn_tari(transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "burn-tari tx_id {}", tx_id);
+                println!("Burn transaction submitted in TxId: {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            CancelTransaction => match cancel_transaction(transaction_service.clone(), parsed.args).await? {
+                Some(cancellation) => {
+                    println!(
+                        "Transaction {} cancelled at {}: {}",
+                        cancellation.tx_id, cancellation.cancelled_at, cancellation.reason
+                    );
+                    if cancellation.outputs_released {
+                        println!("The spent outputs have been released.");
+                    } else {
+                        println!(
+                            "The transaction may already be known to the network, so the spent outputs stay \
+                             encumbered until it is confirmed that it will not be mined."
+                        );
+                    }
+                },
+                None => println!("Transaction cancelled."),
+            },
+            BumpFee => {
+                let tx_id = bump_fee(transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "bump-fee tx_id {}", tx_id);
+                println!("Fee bump transaction submitted in TxId: {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            CreateAccount => {
+                let account = create_account(output_service.clone(), parsed.args).await?;
+                println!("Created account {}", account);
+            },
+            ListAccounts => list_accounts(output_service.clone()).await?,
+            TransferBetweenAccounts => {
+                let tx_id =
+                    transfer_between_accounts(output_service.clone(), transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "transfer-between-accounts tx_id {}", tx_id);
+                println!("Transfer between accounts submitted in TxId: {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            ExportUnsignedTx => {
+                let package = export_unsigned_tx(transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "export-unsigned-tx tx_id {}", package.tx_id);
+            },
+            SignOfflineTx => {
+                let package = sign_offline_tx(transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "sign-offline-tx tx_id {}", package.tx_id);
+            },
+            BroadcastSignedTx => {
+                let tx_id = broadcast_signed_tx(transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "broadcast-signed-tx tx_id {}", tx_id);
+                println!("Offline signed transaction submitted in TxId: {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            ExportWatchOnlyKeys => export_watch_only_keys(output_service.clone(), parsed.args).await?,
+            MultisigCreate => {
+                if !online {
+                    wait_for_comms(&connectivity_requester).await?;
+                    online = true;
+                }
+                let session_id = multisig_create(wallet.multisig_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "multisig-create session_id {}", session_id);
+            },
+            MultisigAccept => {
+                if !online {
+                    wait_for_comms(&connectivity_requester).await?;
+                    online = true;
+                }
+                multisig_accept(wallet.multisig_service.clone(), parsed.args).await?;
+            },
+            MultisigFund => {
+                if !online {
+                    wait_for_comms(&connectivity_requester).await?;
+                    online = true;
+                }
+                let tx_id = multisig_fund(wallet.multisig_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "multisig-fund tx_id {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            MultisigSign => {
+                if !online {
+                    wait_for_comms(&connectivity_requester).await?;
+                    online = true;
+                }
+                let signing_id = multisig_sign(wallet.multisig_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "multisig-sign signing_id {}", signing_id);
+            },
+            MultisigSpend => {
+                if !online {
+                    wait_for_comms(&connectivity_requester).await?;
+                    online = true;
+                }
+                let signing_id = multisig_spend(wallet.multisig_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "multisig-spend signing_id {}", signing_id);
+            },
+            MultisigApprove => {
+                if !online {
+                    wait_for_comms(&connectivity_requester).await?;
+                    online = true;
+                }
+                multisig_approve(wallet.multisig_service.clone(), parsed.args).await?;
+            },
+            MultisigFinalize => {
+                multisig_finalize(wallet.multisig_service.clone(), parsed.args).await?;
+            },
             MakeItRain => {
                 make_it_rain(transaction_service.clone(), parsed.args).await?;
             },
@@ -611,6 +1471,11 @@ pub async fn command_runner(
                 println!("Public Key: {}", public_key.to_hex());
                 println!("Emoji ID  : {}", emoji_id);
             },
+            StealthAddress => {
+                let address = output_service.get_stealth_address().await?;
+                println!("Stealth Address: {}", address);
+                println!("Hex            : {}", address.to_hex());
+            },
             ExportUtxos => {
                 let utxos = output_service.get_unspent_outputs().await?;
                 let count = utxos.len();
@@ -639,6 +1504,15 @@ pub async fn command_runner(
                 println!("Total number of UTXOs: {}", count);
                 println!("Total value of UTXOs: {}", sum);
             },
+            ImportUtxo => {
+                if !online {
+                    wait_for_comms(&connectivity_requester).await?;
+                    online = true;
+                }
+                let tx_id = import_utxo(&mut wallet.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "import-utxo tx_id {}", tx_id);
+                println!("UTXO imported in TxId: {}", tx_id);
+            },
             CountUtxos => {
                 let utxos = output_service.get_unspent_outputs().await?;
                 let count = utxos.len();
@@ -658,6 +1532,42 @@ pub async fn command_runner(
                     println!("Maximum value UTXO   : {}", max);
                 }
             },
+            AuditKeys => {
+                let report = output_service.get_key_audit_report().await?;
+                if parsed.args.is_empty() {
+                    print_key_audit_report(&report);
+                } else if let ParsedArgument::CSVFileName(file) = parsed.args[1].clone() {
+                    write_key_audit_to_csv_file(&report, file)?;
+                    println!("Audited the keys of {} outputs", report.outputs.len());
+                }
+            },
+            VerifyOutputProof => {
+                let path = match parsed.args[0].clone() {
+                    ParsedArgument::Text(path) => Ok(path),
+                    _ => Err(CommandError::Argument),
+                }?;
+                verify_output_proof(&path)?;
+            },
+            SignMessage => {
+                let signature = sign_message(&wallet, &parsed.args)?;
+                println!("Public key: {}", wallet.comms.node_identity().public_key());
+                println!("Signature : {}", signature);
+            },
+            VerifyMessage => {
+                let (public_key, is_valid) = verify_message(&parsed.args)?;
+                if is_valid {
+                    println!("The signature is valid: the message was signed by {}", public_key);
+                } else {
+                    println!("The signature is NOT valid for this public key and message");
+                }
+            },
+            RotateEncryption => {
+                let current_password =
+                    prompt_password("Current wallet password: ").map_err(|e| CommandError::Password(e.to_string()))?;
+                let new_password = prompt_new_password().map_err(|e| CommandError::Password(e.to_string()))?;
+                rotate_encryption(wallet.clone(), current_password, new_password).await?;
+                println!("Wallet encryption rotated successfully.");
+            },
             SetBaseNode => {
                 set_base_node_peer(wallet.clone(), &parsed.args).await?;
             },
@@ -694,6 +1604,11 @@ pub async fn command_runner(
             target: LOG_TARGET,
             "wallet monitor_transactions timeout duration {:?}", duration
         );
+        println!(
+            "Monitoring {} sent transactions to {:?} stage...",
+            tx_ids.len(),
+            wait_stage
+        );
         match timeout(
             duration,
             monitor_transactions(transaction_service.clone(), tx_ids, wait_stage.clone()),
@@ -725,6 +1640,365 @@ pub async fn command_runner(
     Ok(())
 }
 
+/// Runs a single command in headless mode and prints its result to stdout as JSON. Transactions are monitored to the
+/// configured wait stage and the stage they reached is included in the result.
+pub async fn headless_runner(
+    parsed: ParsedCommand,
+    wallet: WalletSqlite,
+    config: GlobalConfig,
+) -> Result<(), CommandError> {
+    let wait_stage = TransactionStage::from_str(&config.wallet_command_send_wait_stage)
+        .map_err(|e| CommandError::Config(e.to_string()))?;
+    let transaction_service = wallet.transaction_service.clone();
+
+    use WalletCommand::*;
+    // Commands that create a transaction are followed until it reaches the wait stage, which needs a base node. The
+    // wait is silent because stdout only carries the JSON result.
+    if matches!(
+        parsed.command,
+        ImportUtxo |
+            SendTari |
+            SendOneSided |
+            SendStealth |
+            InitSwap |
+            ClaimSwap |
+            RefundSwap |
+            BurnTari |
+            BumpFee |
+            TransferBetweenAccounts
+    ) {
+        wait_for_base_node_connection(&wallet.comms.connectivity()).await?;
+    }
+    let (tx_id, mut output) = match parsed.command {
+        GetBalance => {
+            let balance = wallet.output_manager_service.clone().get_balance().await?;
+            let output = json!({
+                "available_balance": u64::from(balance.available_balance),
+                "time_locked_balance": balance.time_locked_balance.map(u64::from),
+                "pending_incoming_balance": u64::from(balance.pending_incoming_balance),
+                "pending_outgoing_balance": u64::from(balance.pending_outgoing_balance),
+            });
+            println!("{}", output);
+            return Ok(());
+        },
+        ImportUtxo => {
+            let tx_id = import_utxo(&mut wallet.clone(), parsed.args).await?;
+            println!("{}", json!({ "tx_id": tx_id }));
+            return Ok(());
+        },
+        CreateAccount => {
+            let account = create_account(wallet.output_manager_service.clone(), parsed.args).await?;
+            let output = json!({
+                "id": account.id,
+                "name": account.name,
+            });
+            println!("{}", output);
+            return Ok(());
+        },
+        BalanceHistory => {
+            let history = balance_history(transaction_service.clone(), parsed.args).await?;
+            let output = history
+                .iter()
+                .map(|snapshot| {
+                    json!({
+                        "date": snapshot.date.format("%Y-%m-%d").to_string(),
+                        "balance": u64::from(snapshot.balance),
+                    })
+                })
+                .collect::<Vec<_>>();
+            println!("{}", json!(output));
+            return Ok(());
+        },
+        StealthAddress => {
+            let address = wallet.output_manager_service.clone().get_stealth_address().await?;
+            let output = json!({
+                "stealth_address": address.to_string(),
+                "scan_key": address.scan_key.to_hex(),
+                "spend_key": address.spend_key.to_hex(),
+            });
+            println!("{}", output);
+            return Ok(());
+        },
+        SignMessage => {
+            let signature = sign_message(&wallet, &parsed.args)?;
+            let output = json!({
+                "public_key": wallet.comms.node_identity().public_key().to_hex(),
+                "signature": signature.to_string(),
+            });
+            println!("{}", output);
+            return Ok(());
+        },
+        VerifyMessage => {
+            let (public_key, is_valid) = verify_message(&parsed.args)?;
+            let output = json!({
+                "public_key": public_key.to_hex(),
+                "valid": is_valid,
+            });
+            println!("{}", output);
+            return Ok(());
+        },
+        AuditKeys => {
+            let report = wallet.output_manager_service.clone().get_key_audit_report().await?;
+            let outputs = report
+                .outputs
+                .iter()
+                .map(|o| {
+                    json!({
+                        "commitment": o.commitment.to_hex(),
+                        "value": u64::from(o.value),
+                        "status": format!("{:?}", o.status),
+                        "is_coinbase": o.is_coinbase,
+                        "spending_key_index": o.spending_key_index,
+                        "script_key_index": o.script_key_index,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let gaps = report
+                .index_gaps()
+                .into_iter()
+                .map(|gap| json!({ "start": gap.start(), "end": gap.end() }))
+                .collect::<Vec<_>>();
+            let output = json!({
+                "current_key_index": report.current_key_index,
+                "outputs": outputs,
+                "index_gaps": gaps,
+            });
+            println!("{}", output);
+            return Ok(());
+        },
+        SendTari => {
+            let tx_id = send_tari(transaction_service.clone(), parsed.args).await?;
+            (tx_id, json!({ "tx_id": tx_id }))
+        },
+        SendOneSided => {
+            let tx_id = send_one_sided(transaction_service.clone(), parsed.args).await?;
+            (tx_id, json!({ "tx_id": tx_id }))
+        },
+        SendStealth => {
+            let tx_id = send_stealth(transaction_service.clone(), parsed.args).await?;
+            (tx_id, json!({ "tx_id": tx_id }))
+        },
+        InitSwap => {
+            let swap = init_swap(
+                transaction_service.clone(),
+                wallet.base_node_service.clone(),
+                parsed.args,
+            )
+            .await?;
+            let output = json!({
+                "tx_id": swap.tx_id,
+                "pre_image": swap.pre_image.to_hex(),
+                "output_hash": swap.output_hash.to_hex(),
+                "refund_height": swap.refund_height,
+            });
+            (swap.tx_id, output)
+        },
+        ClaimSwap => {
+            let tx_id = claim_swap(
+                transaction_service.clone(),
+                wallet.wallet_connectivity.clone(),
+                parsed.args,
+            )
+            .await?;
+            (tx_id, json!({ "tx_id": tx_id }))
+        },
+        RefundSwap => {
+            let tx_id = refund_swap(transaction_service.clone(), parsed.args).await?;
+            (tx_id, json!({ "tx_id": tx_id }))
+        },
+        BurnTari => {
+            let tx_id = burn_tari(transaction_service.clone(), parsed.args).await?;
+            (tx_id, json!({ "tx_id": tx_id }))
+        },
+        BumpFee => {
+            let tx_id = bump_fee(transaction_service.clone(), parsed.args).await?;
+            (tx_id, json!({ "tx_id": tx_id }))
+        },
+        TransferBetweenAccounts => {
+            let tx_id = transfer_between_accounts(
+                wallet.output_manager_service.clone(),
+                transaction_service.clone(),
+                parsed.args,
+            )
+            .await?;
+            (tx_id, json!({ "tx_id": tx_id }))
+        },
+        command => return Err(CommandError::HeadlessUnsupported(command.to_string())),
+    };
+
+    let duration = Duration::from_secs(config.wallet_command_send_wait_timeout);
+    let stage = match timeout(
+        duration,
+        monitor_transactions(transaction_service, vec![tx_id], wait_stage.clone()),
+    )
+    .await
+    {
+        Ok(_) => wait_stage,
+        Err(_) => TransactionStage::Timedout,
+    };
+    output["stage"] = json!(format!("{:?}", stage));
+    println!("{}", output);
+
+    if stage == TransactionStage::Timedout {
+        return Err(CommandError::Timeout(format!(
+            "transaction {} did not reach the {} stage within {:?}",
+            tx_id, config.wallet_command_send_wait_stage, duration
+        )));
+    }
+
+    Ok(())
+}
+
+/// Re-encrypts the wallet database with the new password on a background task, printing the progress of each stage
+pub async fn rotate_encryption(
+    wallet: WalletSqlite,
+    current_password: String,
+    new_password: String,
+) -> Result<(), CommandError> {
+    let (progress_tx, mut progress_rx) = mpsc::channel(10);
+    let rotation = wallet.rotate_encryption(current_password, new_password, Some(progress_tx));
+
+    while let Some(progress) = progress_rx.recv().await {
+        match progress {
+            EncryptionRotationProgress::Started(stage) => println!("Re-encrypting {}...", stage),
+            EncryptionRotationProgress::Completed { stage, rows } => {
+                println!("Re-encrypted {} {} row(s)", rows, stage)
+            },
+            EncryptionRotationProgress::Committed { rows } => println!("Saved {} re-encrypted row(s)", rows),
+        }
+    }
+    rotation.await??;
+
+    Ok(())
+}
+
+/// Verifies an output proof file produced by a base node's `prove-output` command. No connection to a base node is
+/// needed; the tip header hash is printed so that it can be compared with a trusted node.
+fn verify_output_proof(path: &str) -> Result<(), CommandError> {
+    let json =
+        fs::read_to_string(path).map_err(|e| CommandError::OutputProof(format!("could not read '{}': {}", path, e)))?;
+    let proof = serde_json::from_str::<OutputProof>(&json)
+        .map_err(|e| CommandError::OutputProof(format!("could not parse '{}': {}", path, e)))?;
+    proof
+        .verify(&CryptoFactories::default().range_proof)
+        .map_err(|e| CommandError::OutputProof(e.to_string()))?;
+
+    // Verification fails if the proof has no headers
+    if let (Some(mined), Some(tip)) = (proof.mined_header(), proof.tip_header()) {
+        println!("Output {} is valid", proof.output.commitment.to_hex());
+        println!("Mined in block    : #{} ({})", mined.height, mined.hash().to_hex());
+        println!("Confirmations     : {}", proof.confirmations());
+        println!("Last header       : #{} ({})", tip.height, tip.hash().to_hex());
+        println!("Check that the last header is in the chain of a base node you trust.");
+    }
+    Ok(())
+}
+
+/// Signs a message with the wallet identity key, which is the key behind the wallet's public key and emoji id
+fn sign_message(wallet: &WalletSqlite, args: &[ParsedArgument]) -> Result<MessageSignature, CommandError> {
+    let message = match args.get(0) {
+        Some(ParsedArgument::Text(message)) => Ok(message),
+        _ => Err(CommandError::Argument),
+    }?;
+    Ok(MessageSignature::sign(
+        wallet.comms.node_identity().secret_key(),
+        message,
+    )?)
+}
+
+/// Returns the public key, and whether the signature over the message was made by its owner
+fn verify_message(args: &[ParsedArgument]) -> Result<(PublicKey, bool), CommandError> {
+    match (args.get(0), args.get(1), args.get(2)) {
+        (
+            Some(ParsedArgument::PublicKey(public_key)),
+            Some(ParsedArgument::Text(signature)),
+            Some(ParsedArgument::Text(message)),
+        ) => {
+            let signature = signature.parse::<MessageSignature>()?;
+            Ok((public_key.clone(), signature.verify(public_key, message)))
+        },
+        _ => Err(CommandError::Argument),
+    }
+}
+
+fn format_key_index(index: Option<u64>) -> String {
+    index.map_or_else(|| "not derived".to_string(), |i| i.to_string())
+}
+
+fn print_key_audit_report(report: &KeyAuditReport) {
+    for (i, o) in report.outputs.iter().enumerate() {
+        println!(
+            "{}. {} Value: {} Status: {:?}{} Spending key index: {} Script key index: {}",
+            i + 1,
+            o.commitment.to_hex(),
+            o.value,
+            o.status,
+            if o.is_coinbase { " (coinbase)" } else { "" },
+            format_key_index(o.spending_key_index),
+            format_key_index(o.script_key_index),
+        );
+    }
+    println!("Current key index: {}", report.current_key_index);
+    println!("Key indexes in use: {}", report.used_indexes().len());
+
+    let gaps = report.index_gaps();
+    if gaps.is_empty() {
+        println!("No key index gaps found");
+    } else {
+        let gaps = gaps
+            .iter()
+            .map(|gap| {
+                if gap.start() == gap.end() {
+                    gap.start().to_string()
+                } else {
+                    format!("{}-{}", gap.start(), gap.end())
+                }
+            })
+            .collect::<Vec<_>>();
+        println!("Unused key indexes: {}", gaps.join(", "));
+    }
+
+    for o in report.outputs_beyond_current_index() {
+        println!(
+            "WARNING: Output {} uses key index {} which is beyond the current key index",
+            o.commitment.to_hex(),
+            format_key_index(o.spending_key_index)
+        );
+    }
+    let underived = report.underived_outputs().len();
+    if underived > 0 {
+        println!(
+            "{} output(s) use keys not derived by this wallet, e.g. one-sided payments or imported outputs",
+            underived
+        );
+    }
+}
+
+fn write_key_audit_to_csv_file(report: &KeyAuditReport, file_path: String) -> Result<(), CommandError> {
+    let file = File::create(file_path).map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    let mut csv_file = LineWriter::new(file);
+    writeln!(
+        csv_file,
+        r##""index","commitment","value","status","is_coinbase","spending_key_index","script_key_index""##
+    )
+    .map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    for (i, o) in report.outputs.iter().enumerate() {
+        writeln!(
+            csv_file,
+            r##""{}","{}","{}","{:?}","{}","{}","{}""##,
+            i + 1,
+            o.commitment.to_hex(),
+            o.value.0,
+            o.status,
+            o.is_coinbase,
+            o.spending_key_index.map(|i| i.to_string()).unwrap_or_default(),
+            o.script_key_index.map(|i| i.to_string()).unwrap_or_default(),
+        )
+        .map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    }
+    Ok(())
+}
+
 fn write_utxos_to_csv_file(utxos: Vec<UnblindedOutput>, file_path: String) -> Result<(), CommandError> {
     let factory = PedersenCommitmentFactory::default();
     let file = File::create(file_path).map_err(|e| CommandError::CSVFile(e.to_string()))?;
@@ -24,10 +24,11 @@ use std::num::{ParseFloatError, ParseIntError};
 
 use chrono_english::DateError;
 use log::*;
-use tari_app_utilities::utilities::ExitCodes;
+use tari_app_utilities::{message_signature::MessageSignatureError, utilities::ExitCodes};
 use tari_core::transactions::{tari_amount::MicroTariError, transaction::TransactionError};
 use tari_wallet::{
     error::{WalletError, WalletStorageError},
+    multisig_service::error::MultisigServiceError,
     output_manager_service::error::OutputManagerError,
     transaction_service::error::TransactionServiceError,
 };
@@ -53,18 +54,45 @@ pub enum CommandError {
     Config(String),
     #[error("Comms error `{0}`")]
     Comms(String),
+    #[error("Atomic swap error `{0}`")]
+    AtomicSwap(String),
+    #[error("Multisig service error `{0}`")]
+    MultisigServiceError(#[from] MultisigServiceError),
     #[error("CSV file error `{0}`")]
     CSVFile(String),
     #[error("Wallet error `{0}`")]
     WalletError(#[from] WalletError),
     #[error("Wallet storage error `{0}`")]
     WalletStorageError(#[from] WalletStorageError),
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    #[error("The `{0}` command is not supported in headless mode")]
+    HeadlessUnsupported(String),
+    #[error("Password error: {0}")]
+    Password(String),
+    #[error("Invalid output proof: {0}")]
+    OutputProof(String),
+    #[error("Offline transaction package error: {0}")]
+    OfflinePackage(String),
+    #[error("UTXO import error: {0}")]
+    ImportUtxo(String),
+    #[error("Message signature error: {0}")]
+    MessageSignature(#[from] MessageSignatureError),
 }
 
 impl From<CommandError> for ExitCodes {
     fn from(err: CommandError) -> Self {
         error!(target: LOG_TARGET, "{}", err);
-        Self::CommandError(err.to_string())
+        match err {
+            CommandError::Argument |
+            CommandError::HeadlessUnsupported(_) |
+            CommandError::Password(_) |
+            CommandError::MessageSignature(_) => Self::InputError(err.to_string()),
+            CommandError::Config(_) => Self::ConfigError(err.to_string()),
+            CommandError::Comms(_) => Self::NetworkError(err.to_string()),
+            CommandError::WalletError(_) | CommandError::WalletStorageError(_) => Self::WalletError(err.to_string()),
+            _ => Self::CommandError(err.to_string()),
+        }
     }
 }
 
@@ -76,6 +104,8 @@ pub enum ParseError {
     MicroTariAmount(#[from] MicroTariError),
     #[error("Failed to parse public key or emoji id.")]
     PublicKey,
+    #[error("Failed to parse stealth address.")]
+    StealthAddress,
     #[error("Failed to parse a missing {0}")]
     Empty(String),
     #[error("Failed to parse float.")]
@@ -84,8 +114,12 @@ pub enum ParseError {
     Int(#[from] ParseIntError),
     #[error("Failed to parse date. {0}")]
     Date(#[from] DateError),
+    #[error("Failed to parse hash.")]
+    Hash,
     #[error("Failed to parse a net address.")]
     Address,
+    #[error("Failed to parse message signature. {0}")]
+    MessageSignature(#[from] MessageSignatureError),
     #[error("Invalid combination of arguments ({0}).")]
     Invalid(String),
     #[error("Parsing not yet implemented for {0}.")]
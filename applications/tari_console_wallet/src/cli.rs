@@ -0,0 +1,318 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::automation::{
+    command_parser::{ParsedArgument, ParsedCommand},
+    commands::WalletCommand,
+    error::ParseError,
+};
+use structopt::StructOpt;
+use tari_app_utilities::utilities::parse_emoji_id_or_public_key;
+use tari_common::ConfigBootstrap;
+use tari_common_types::{stealth_address::StealthAddress, types::PublicKey};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_crypto::tari_utilities::hex::Hex;
+
+/// The console wallet command-line arguments: the bootstrap arguments shared by all the Tari applications, optionally
+/// followed by a single headless command.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "tari_console_wallet")]
+pub struct Cli {
+    #[structopt(flatten)]
+    pub bootstrap: ConfigBootstrap,
+    #[structopt(subcommand)]
+    pub command: Option<HeadlessCommand>,
+}
+
+/// A single wallet operation run in headless mode. The wallet prints the result to stdout as JSON and exits.
+#[derive(StructOpt, Debug, Clone, PartialEq)]
+#[structopt(rename_all = "kebab-case")]
+pub enum HeadlessCommand {
+    /// Print the wallet balance
+    GetBalance,
+    /// Print the wallet balance at the end of each of the last days
+    BalanceHistory {
+        /// The number of days up to and including today
+        #[structopt(long, default_value = "30")]
+        days: u64,
+    },
+    /// Print which outputs map to which key derivation indexes, and any gaps in the key indexes in use
+    AuditKeys,
+    /// Send Tari to a recipient
+    SendTari {
+        /// The amount to send, e.g. 1.5T or 1500000uT
+        #[structopt(long)]
+        amount: MicroTari,
+        /// The public key or emoji id of the recipient
+        #[structopt(long, parse(try_from_str = parse_public_key))]
+        destination: PublicKey,
+        /// The transaction message
+        #[structopt(long, default_value = "")]
+        message: String,
+        /// Send a one-sided transaction, which does not need the recipient to be online
+        #[structopt(long)]
+        one_sided: bool,
+    },
+    /// Send a one-sided transaction to a stealth address
+    SendStealth {
+        /// The amount to send, e.g. 1.5T or 1500000uT
+        #[structopt(long)]
+        amount: MicroTari,
+        /// The stealth address of the recipient, as emoji or hex
+        #[structopt(long, parse(try_from_str = parse_stealth_address))]
+        destination: StealthAddress,
+        /// The transaction message
+        #[structopt(long, default_value = "")]
+        message: String,
+    },
+    /// Print the stealth address of this wallet
+    StealthAddress,
+    /// Send a hash time locked contract (HTLC) transaction to a recipient, for use in an atomic swap
+    InitHtlc {
+        /// The amount to send, e.g. 1.5T or 1500000uT
+        #[structopt(long)]
+        amount: MicroTari,
+        /// The public key or emoji id of the recipient
+        #[structopt(long, parse(try_from_str = parse_public_key))]
+        destination: PublicKey,
+        /// The number of blocks after which the funds can be refunded
+        #[structopt(long)]
+        lock_blocks: u64,
+        /// The transaction message
+        #[structopt(long, default_value = "")]
+        message: String,
+    },
+    /// Claim an HTLC output sent to this wallet using its pre-image
+    ClaimHtlc {
+        /// The hash of the HTLC output, in hex
+        #[structopt(long, parse(try_from_str = parse_hash))]
+        output_hash: Vec<u8>,
+        /// The pre-image of the HTLC, in hex
+        #[structopt(long, parse(try_from_str = parse_pre_image))]
+        pre_image: PublicKey,
+    },
+    /// Refund an HTLC transaction sent by this wallet once its time lock has expired
+    RefundHtlc {
+        /// The transaction id of the HTLC transaction
+        #[structopt(long)]
+        tx_id: u64,
+    },
+    /// Burn Tari, permanently removing it from the circulating supply
+    BurnTari {
+        /// The amount to burn, e.g. 1.5T or 1500000uT
+        #[structopt(long)]
+        amount: MicroTari,
+        /// The transaction message
+        #[structopt(long, default_value = "")]
+        message: String,
+    },
+    /// Sign a message with the wallet identity key, to prove that you control this wallet
+    SignMessage {
+        /// The message to sign
+        #[structopt(long)]
+        message: String,
+    },
+    /// Check that a message was signed by the owner of a public key
+    VerifyMessage {
+        /// The public key or emoji id of the signer
+        #[structopt(long, parse(try_from_str = parse_public_key))]
+        public_key: PublicKey,
+        /// The signature produced by `sign-message`
+        #[structopt(long)]
+        signature: String,
+        /// The signed message
+        #[structopt(long)]
+        message: String,
+    },
+}
+
+impl From<HeadlessCommand> for ParsedCommand {
+    fn from(command: HeadlessCommand) -> Self {
+        use ParsedArgument::*;
+        let (command, args) = match command {
+            HeadlessCommand::GetBalance => (WalletCommand::GetBalance, vec![]),
+            HeadlessCommand::BalanceHistory { days } => (WalletCommand::BalanceHistory, vec![Int(days)]),
+            HeadlessCommand::AuditKeys => (WalletCommand::AuditKeys, vec![]),
+            HeadlessCommand::SendTari {
+                amount,
+                destination,
+                message,
+                one_sided,
+            } => {
+                let command = if one_sided {
+                    WalletCommand::SendOneSided
+                } else {
+                    WalletCommand::SendTari
+                };
+                (command, vec![Amount(amount), PublicKey(destination), Text(message)])
+            },
+            HeadlessCommand::SendStealth {
+                amount,
+                destination,
+                message,
+            } => (WalletCommand::SendStealth, vec![
+                Amount(amount),
+                StealthAddress(destination),
+                Text(message),
+            ]),
+            HeadlessCommand::StealthAddress => (WalletCommand::StealthAddress, vec![]),
+            HeadlessCommand::InitHtlc {
+                amount,
+                destination,
+                lock_blocks,
+                message,
+            } => (WalletCommand::InitSwap, vec![
+                Amount(amount),
+                PublicKey(destination),
+                Int(lock_blocks),
+                Text(message),
+            ]),
+            HeadlessCommand::ClaimHtlc { output_hash, pre_image } => {
+                (WalletCommand::ClaimSwap, vec![Hash(output_hash), PublicKey(pre_image)])
+            },
+            HeadlessCommand::RefundHtlc { tx_id } => (WalletCommand::RefundSwap, vec![Int(tx_id)]),
+            HeadlessCommand::BurnTari { amount, message } => {
+                (WalletCommand::BurnTari, vec![Amount(amount), Text(message)])
+            },
+            HeadlessCommand::SignMessage { message } => (WalletCommand::SignMessage, vec![Text(message)]),
+            HeadlessCommand::VerifyMessage {
+                public_key,
+                signature,
+                message,
+            } => (WalletCommand::VerifyMessage, vec![
+                PublicKey(public_key),
+                Text(signature),
+                Text(message),
+            ]),
+        };
+        ParsedCommand { command, args }
+    }
+}
+
+fn parse_public_key(s: &str) -> Result<PublicKey, ParseError> {
+    parse_emoji_id_or_public_key(s).ok_or(ParseError::PublicKey)
+}
+
+fn parse_stealth_address(s: &str) -> Result<StealthAddress, ParseError> {
+    s.parse().map_err(|_| ParseError::StealthAddress)
+}
+
+fn parse_pre_image(s: &str) -> Result<PublicKey, ParseError> {
+    PublicKey::from_hex(s).map_err(|_| ParseError::PublicKey)
+}
+
+fn parse_hash(s: &str) -> Result<Vec<u8>, ParseError> {
+    Vec::<u8>::from_hex(s).map_err(|_| ParseError::Hash)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        automation::{command_parser::ParsedCommand, commands::WalletCommand},
+        cli::{Cli, HeadlessCommand},
+    };
+    use rand::rngs::OsRng;
+    use std::str::FromStr;
+    use structopt::StructOpt;
+    use tari_common_types::types::PublicKey;
+    use tari_core::transactions::tari_amount::MicroTari;
+    use tari_crypto::{keys::PublicKey as PublicKeyTrait, tari_utilities::hex::Hex};
+
+    #[test]
+    fn test_parse_headless_command() {
+        let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+
+        let cli = Cli::from_iter_safe(&["tari_console_wallet"]).unwrap();
+        assert!(cli.command.is_none());
+
+        let cli = Cli::from_iter_safe(&["tari_console_wallet", "--password", "secret", "get-balance"]).unwrap();
+        assert_eq!(cli.bootstrap.password, Some("secret".to_string()));
+        assert_eq!(cli.command, Some(HeadlessCommand::GetBalance));
+
+        let cli = Cli::from_iter_safe(&[
+            "tari_console_wallet",
+            "send-tari",
+            "--amount",
+            "1T",
+            "--destination",
+            public_key.to_hex().as_str(),
+            "--one-sided",
+        ])
+        .unwrap();
+        let parsed = ParsedCommand::from(cli.command.unwrap());
+        assert_eq!(parsed.command, WalletCommand::SendOneSided);
+        assert_eq!(parsed.args.len(), 3);
+
+        let cli = Cli::from_iter_safe(&[
+            "tari_console_wallet",
+            "send-tari",
+            "--amount",
+            "1T",
+            "--destination",
+            public_key.to_hex().as_str(),
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.command,
+            Some(HeadlessCommand::SendTari {
+                amount: MicroTari::from_str("1T").unwrap(),
+                destination: public_key.clone(),
+                message: "".to_string(),
+                one_sided: false,
+            })
+        );
+
+        let result = Cli::from_iter_safe(&["tari_console_wallet", "send-tari", "--amount", "1T"]);
+        assert!(result.is_err());
+
+        let result = Cli::from_iter_safe(&[
+            "tari_console_wallet",
+            "send-tari",
+            "--amount",
+            "1T",
+            "--destination",
+            "not a key",
+        ]);
+        assert!(result.is_err());
+
+        let cli = Cli::from_iter_safe(&[
+            "tari_console_wallet",
+            "claim-htlc",
+            "--output-hash",
+            "deadbeef",
+            "--pre-image",
+            public_key.to_hex().as_str(),
+        ])
+        .unwrap();
+        let parsed = ParsedCommand::from(cli.command.unwrap());
+        assert_eq!(parsed.command, WalletCommand::ClaimSwap);
+
+        let result = Cli::from_iter_safe(&["tari_console_wallet", "refund-htlc", "--tx-id", "abc"]);
+        assert!(result.is_err());
+
+        let cli = Cli::from_iter_safe(&["tari_console_wallet", "balance-history"]).unwrap();
+        assert_eq!(cli.command, Some(HeadlessCommand::BalanceHistory { days: 30 }));
+        let cli = Cli::from_iter_safe(&["tari_console_wallet", "balance-history", "--days", "7"]).unwrap();
+        let parsed = ParsedCommand::from(cli.command.unwrap());
+        assert_eq!(parsed.command, WalletCommand::BalanceHistory);
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use log::*;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tari_app_grpc::tari_rpc::{faucet_server, FaucetRequest, FaucetResponse};
+use tari_common::GlobalConfig;
+use tari_comms::types::CommsPublicKey;
+use tari_core::{tari_utilities::hex::Hex, transactions::tari_amount::MicroTari};
+use tari_wallet::WalletSqlite;
+use tonic::{Request, Response, Status};
+
+const LOG_TARGET: &str = "wallet::ui::grpc::faucet";
+const FAUCET_PAYOUT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone)]
+pub struct FaucetConfig {
+    /// The amount sent to each requesting address
+    pub amount: MicroTari,
+    pub fee_per_gram: MicroTari,
+    /// The time before the same address may request coins again
+    pub cooldown: Duration,
+    /// The maximum number of payouts across all addresses in any hour
+    pub max_payouts_per_hour: usize,
+}
+
+impl FaucetConfig {
+    pub fn from_global_config(config: &GlobalConfig) -> Self {
+        Self {
+            amount: config.console_wallet_faucet_amount.into(),
+            fee_per_gram: config.console_wallet_faucet_fee_per_gram.into(),
+            cooldown: config.console_wallet_faucet_cooldown,
+            max_payouts_per_hour: config.console_wallet_faucet_max_payouts_per_hour as usize,
+        }
+    }
+}
+
+/// Checks the token presented with a faucet request. This is the hook for captchas or other proof that a request was
+/// made by a person.
+pub trait FaucetTokenValidator: Send + Sync + 'static {
+    fn validate(&self, address: &CommsPublicKey, token: &str) -> Result<(), String>;
+}
+
+/// Accepts every request, for faucets that rely on cooldowns alone
+pub struct NoTokenRequired;
+
+impl FaucetTokenValidator for NoTokenRequired {
+    fn validate(&self, _: &CommsPublicKey, _: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Accepts requests that present one of a fixed set of tokens, e.g. handed out to workshop participants
+pub struct StaticTokens(HashSet<String>);
+
+impl StaticTokens {
+    pub fn new<I: IntoIterator<Item = String>>(tokens: I) -> Self {
+        Self(tokens.into_iter().collect())
+    }
+}
+
+impl FaucetTokenValidator for StaticTokens {
+    fn validate(&self, _: &CommsPublicKey, token: &str) -> Result<(), String> {
+        if self.0.contains(token) {
+            Ok(())
+        } else {
+            Err("Invalid faucet token".to_string())
+        }
+    }
+}
+
+/// Tracks the payouts made by the faucet to enforce the per-address cooldown and the hourly payout cap
+#[derive(Debug, Default)]
+struct FaucetLimiter {
+    last_payouts: HashMap<CommsPublicKey, Instant>,
+    recent_payouts: VecDeque<Instant>,
+}
+
+impl FaucetLimiter {
+    /// Reserves a payout to the address, or returns the reason it is not allowed
+    fn try_reserve(&mut self, config: &FaucetConfig, address: &CommsPublicKey, now: Instant) -> Result<(), Status> {
+        self.last_payouts
+            .retain(|_, last| now.saturating_duration_since(*last) < config.cooldown);
+        while let Some(oldest) = self.recent_payouts.front() {
+            if now.saturating_duration_since(*oldest) < FAUCET_PAYOUT_WINDOW {
+                break;
+            }
+            self.recent_payouts.pop_front();
+        }
+
+        if let Some(last) = self.last_payouts.get(address) {
+            return Err(Status::resource_exhausted(format!(
+                "This address was paid recently, try again in {:.0?}",
+                config.cooldown.saturating_sub(now.saturating_duration_since(*last))
+            )));
+        }
+        if self.recent_payouts.len() >= config.max_payouts_per_hour {
+            return Err(Status::resource_exhausted(
+                "The faucet has reached its hourly payout limit, try again later",
+            ));
+        }
+
+        self.last_payouts.insert(address.clone(), now);
+        self.recent_payouts.push_back(now);
+        Ok(())
+    }
+
+    /// Releases a reservation for a payout that could not be sent
+    fn release(&mut self, address: &CommsPublicKey, reserved_at: Instant) {
+        if self.last_payouts.get(address) == Some(&reserved_at) {
+            self.last_payouts.remove(address);
+        }
+        if let Some(pos) = self.recent_payouts.iter().rposition(|t| *t == reserved_at) {
+            self.recent_payouts.remove(pos);
+        }
+    }
+}
+
+pub struct FaucetGrpcServer {
+    wallet: WalletSqlite,
+    config: FaucetConfig,
+    token_validator: Box<dyn FaucetTokenValidator>,
+    limiter: Mutex<FaucetLimiter>,
+}
+
+impl FaucetGrpcServer {
+    pub fn new(wallet: WalletSqlite, config: FaucetConfig, token_validator: Box<dyn FaucetTokenValidator>) -> Self {
+        Self {
+            wallet,
+            config,
+            token_validator,
+            limiter: Mutex::new(FaucetLimiter::default()),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl faucet_server::Faucet for FaucetGrpcServer {
+    async fn request_coins(&self, request: Request<FaucetRequest>) -> Result<Response<FaucetResponse>, Status> {
+        let message = request.into_inner();
+        let address =
+            CommsPublicKey::from_hex(&message.address).map_err(|_| Status::invalid_argument("Address is malformed"))?;
+        self.token_validator
+            .validate(&address, &message.token)
+            .map_err(Status::permission_denied)?;
+
+        let reserved_at = Instant::now();
+        self.limiter
+            .lock()
+            .unwrap()
+            .try_reserve(&self.config, &address, reserved_at)?;
+
+        let mut transaction_service = self.wallet.transaction_service.clone();
+        let result = transaction_service
+            .send_transaction(
+                address.clone(),
+                self.config.amount,
+                self.config.fee_per_gram,
+                "Faucet payout".to_string(),
+            )
+            .await;
+        match result {
+            Ok(tx_id) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Faucet sent {} to {} (TxId: {})", self.config.amount, message.address, tx_id
+                );
+                Ok(Response::new(FaucetResponse {
+                    transaction_id: tx_id,
+                    amount: self.config.amount.into(),
+                }))
+            },
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Faucet payout to {} failed: {}", message.address, err);
+                self.limiter.lock().unwrap().release(&address, reserved_at);
+                Err(Status::unavailable(format!(
+                    "The faucet could not send a payout: {}",
+                    err
+                )))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_core::crypto::keys::PublicKey;
+
+    fn config() -> FaucetConfig {
+        FaucetConfig {
+            amount: 1000.into(),
+            fee_per_gram: 5.into(),
+            cooldown: Duration::from_secs(60),
+            max_payouts_per_hour: 2,
+        }
+    }
+
+    fn random_address() -> CommsPublicKey {
+        CommsPublicKey::random_keypair(&mut rand::rngs::OsRng).1
+    }
+
+    #[test]
+    fn it_enforces_the_address_cooldown() {
+        let config = config();
+        let mut limiter = FaucetLimiter::default();
+        let address = random_address();
+        let now = Instant::now();
+
+        limiter.try_reserve(&config, &address, now).unwrap();
+        assert!(limiter
+            .try_reserve(&config, &address, now + Duration::from_secs(59))
+            .is_err());
+        limiter
+            .try_reserve(&config, &address, now + Duration::from_secs(60))
+            .unwrap();
+    }
+
+    #[test]
+    fn it_enforces_the_hourly_payout_limit() {
+        let config = config();
+        let mut limiter = FaucetLimiter::default();
+        let now = Instant::now();
+
+        limiter.try_reserve(&config, &random_address(), now).unwrap();
+        limiter.try_reserve(&config, &random_address(), now).unwrap();
+        assert!(limiter.try_reserve(&config, &random_address(), now).is_err());
+        limiter
+            .try_reserve(&config, &random_address(), now + FAUCET_PAYOUT_WINDOW)
+            .unwrap();
+    }
+
+    #[test]
+    fn it_releases_failed_payouts() {
+        let config = config();
+        let mut limiter = FaucetLimiter::default();
+        let address = random_address();
+        let now = Instant::now();
+
+        limiter.try_reserve(&config, &address, now).unwrap();
+        limiter.release(&address, now);
+        limiter.try_reserve(&config, &address, now).unwrap();
+    }
+
+    #[test]
+    fn static_tokens_validate() {
+        let validator = StaticTokens::new(vec!["abc".to_string()]);
+        let address = random_address();
+        assert!(validator.validate(&address, "abc").is_ok());
+        assert!(validator.validate(&address, "abd").is_err());
+        assert!(NoTokenRequired.validate(&address, "").is_ok());
+    }
+}
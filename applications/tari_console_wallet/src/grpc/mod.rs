@@ -1,3 +1,4 @@
+mod faucet_grpc_server;
 mod wallet_grpc_server;
 
-pub use self::wallet_grpc_server::*;
+pub use self::{faucet_grpc_server::*, wallet_grpc_server::*};
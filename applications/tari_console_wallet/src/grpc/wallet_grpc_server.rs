@@ -1,14 +1,21 @@
+use chrono::Utc;
 use futures::{channel::mpsc, future, SinkExt};
 use log::*;
 use std::convert::TryFrom;
 use tari_app_grpc::{
     conversions::naive_datetime_to_timestamp,
+    paging::PageLimits,
     tari_rpc,
     tari_rpc::{
         payment_recipient::PaymentType,
         wallet_server,
+        BalanceSnapshot as BalanceSnapshotMessage,
         CoinSplitRequest,
         CoinSplitResponse,
+        CreateAccountRequest,
+        CreateAccountResponse,
+        GetBalanceHistoryRequest,
+        GetBalanceHistoryResponse,
         GetBalanceRequest,
         GetBalanceResponse,
         GetCoinbaseRequest,
@@ -21,14 +28,21 @@ use tari_app_grpc::{
         GetTransactionInfoResponse,
         GetVersionRequest,
         GetVersionResponse,
+        ImportUtxoRequest,
+        ImportUtxoResponse,
         ImportUtxosRequest,
         ImportUtxosResponse,
+        ListAccountsResponse,
+        StreamTransactionEventsRequest,
+        StreamTransactionEventsResponse,
         TransactionDirection,
+        TransactionEventType,
         TransactionInfo,
         TransactionStatus,
         TransferRequest,
         TransferResponse,
         TransferResult,
+        WalletAccount as WalletAccountMessage,
     },
 };
 use tari_common_types::types::Signature;
@@ -38,14 +52,31 @@ use tari_core::{
     transactions::{tari_amount::MicroTari, transaction::UnblindedOutput},
 };
 use tari_wallet::{
-    output_manager_service::handle::OutputManagerHandle,
-    transaction_service::{handle::TransactionServiceHandle, storage::models},
+    error::WalletError,
+    output_manager_service::{
+        handle::OutputManagerHandle,
+        storage::models::{WalletAccount, DEFAULT_ACCOUNT_ID},
+        TxId,
+    },
+    transaction_service::{
+        balance_history::balance_history,
+        handle::{TransactionEvent, TransactionServiceHandle},
+        storage::models,
+    },
     WalletSqlite,
 };
-use tokio::task;
+use tokio::{sync::broadcast::error::RecvError, task};
 use tonic::{Request, Response, Status};
 
 const LOG_TARGET: &str = "wallet::ui::grpc";
+/// The maximum number of transactions in a page of GetCompletedTransactions
+const GET_COMPLETED_TRANSACTIONS_MAX_PAGE_SIZE: usize = 1_000;
+/// The maximum total size of a page of GetCompletedTransactions
+const GET_COMPLETED_TRANSACTIONS_MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+/// The number of days returned by GetBalanceHistory when none are requested
+const DEFAULT_BALANCE_HISTORY_DAYS: u32 = 30;
+/// The maximum number of days returned by GetBalanceHistory
+const MAX_BALANCE_HISTORY_DAYS: u32 = 10 * 366;
 
 pub struct WalletGrpcServer {
     wallet: WalletSqlite,
@@ -56,6 +87,18 @@ impl WalletGrpcServer {
         Self { wallet }
     }
 
+    /// Resolves an account name given in a request. An empty name selects no account.
+    async fn resolve_account(&self, name: &str) -> Result<Option<WalletAccount>, Status> {
+        if name.trim().is_empty() {
+            return Ok(None);
+        }
+        self.get_output_manager_service()
+            .find_account(name.trim())
+            .await
+            .map(Some)
+            .map_err(|err| Status::not_found(err.to_string()))
+    }
+
     fn get_transaction_service(&self) -> TransactionServiceHandle {
         self.wallet.transaction_service.clone()
     }
@@ -72,6 +115,7 @@ impl WalletGrpcServer {
 #[tonic::async_trait]
 impl wallet_server::Wallet for WalletGrpcServer {
     type GetCompletedTransactionsStream = mpsc::Receiver<Result<GetCompletedTransactionsResponse, Status>>;
+    type StreamTransactionEventsStream = mpsc::Receiver<Result<StreamTransactionEventsResponse, Status>>;
 
     async fn get_version(&self, _: Request<GetVersionRequest>) -> Result<Response<GetVersionResponse>, Status> {
         Ok(Response::new(GetVersionResponse {
@@ -104,10 +148,15 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }))
     }
 
-    async fn get_balance(&self, _request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+    async fn get_balance(&self, request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+        let account = self.resolve_account(&request.into_inner().account).await?;
         let mut output_service = self.get_output_manager_service();
         let balance;
-        match output_service.get_balance().await {
+        let result = match account {
+            Some(account) => output_service.get_account_balance(account.id).await,
+            None => output_service.get_balance().await,
+        };
+        match result {
             Ok(b) => balance = b,
             Err(e) => return Err(Status::not_found(format!("GetBalance error! {}", e))),
         }
@@ -118,6 +167,30 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }))
     }
 
+    async fn get_balance_history(
+        &self,
+        request: Request<GetBalanceHistoryRequest>,
+    ) -> Result<Response<GetBalanceHistoryResponse>, Status> {
+        let days = match request.into_inner().days {
+            0 => DEFAULT_BALANCE_HISTORY_DAYS,
+            days => days.min(MAX_BALANCE_HISTORY_DAYS),
+        };
+        let mut transaction_service = self.get_transaction_service();
+        let transactions = transaction_service
+            .get_completed_transactions()
+            .await
+            .map_err(|err| Status::unknown(err.to_string()))?;
+        let snapshots = balance_history(transactions.values(), Utc::now().naive_utc().date(), days)
+            .into_iter()
+            .map(|snapshot| BalanceSnapshotMessage {
+                date: snapshot.date.format("%Y-%m-%d").to_string(),
+                balance: snapshot.balance.into(),
+            })
+            .collect();
+
+        Ok(Response::new(GetBalanceHistoryResponse { snapshots }))
+    }
+
     async fn get_coinbase(
         &self,
         request: Request<GetCoinbaseRequest>,
@@ -139,6 +212,11 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
     async fn transfer(&self, request: Request<TransferRequest>) -> Result<Response<TransferResponse>, Status> {
         let message = request.into_inner();
+        let account_id = self
+            .resolve_account(&message.account)
+            .await?
+            .map(|account| account.id)
+            .unwrap_or(DEFAULT_ACCOUNT_ID);
         let recipients = message
             .recipients
             .into_iter()
@@ -167,7 +245,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     (
                         address,
                         transaction_service
-                            .send_transaction(pk, amount.into(), fee_per_gram.into(), message)
+                            .send_transaction_from_account(account_id, pk, amount.into(), fee_per_gram.into(), message)
                             .await,
                     )
                 });
@@ -176,7 +254,13 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     (
                         address,
                         transaction_service
-                            .send_one_sided_transaction(pk, amount.into(), fee_per_gram.into(), message)
+                            .send_one_sided_transaction_from_account(
+                                account_id,
+                                pk,
+                                amount.into(),
+                                fee_per_gram.into(),
+                                message,
+                            )
                             .await,
                     )
                 });
@@ -249,21 +333,50 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
     async fn get_completed_transactions(
         &self,
-        _request: Request<GetCompletedTransactionsRequest>,
+        request: Request<GetCompletedTransactionsRequest>,
     ) -> Result<Response<Self::GetCompletedTransactionsStream>, Status> {
+        let GetCompletedTransactionsRequest { page, account } = request.into_inner();
+        let account = self.resolve_account(&account).await?;
         debug!(
             target: LOG_TARGET,
-            "Incoming GRPC request for GetAllCompletedTransactions"
+            "Incoming GRPC request for GetAllCompletedTransactions, page: {:?}", page
         );
+        let limits = page.as_ref().map(|page| {
+            PageLimits::negotiate(
+                Some(page),
+                GET_COMPLETED_TRANSACTIONS_MAX_PAGE_SIZE,
+                GET_COMPLETED_TRANSACTIONS_MAX_RESPONSE_BYTES,
+            )
+        });
+        let after_tx_id = match page.as_ref().map(|page| page.cursor.as_str()) {
+            Some(cursor) if !cursor.is_empty() => Some(
+                cursor
+                    .parse::<TxId>()
+                    .map_err(|_| Status::invalid_argument("Invalid cursor: expected a transaction id"))?,
+            ),
+            _ => None,
+        };
+
         let mut transaction_service = self.get_transaction_service();
-        let transactions = transaction_service
-            .get_completed_transactions()
-            .await
-            .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?;
+        let completed_transactions = match account {
+            Some(account) => transaction_service.get_account_completed_transactions(account.id).await,
+            None => transaction_service.get_completed_transactions().await,
+        };
+        let mut transactions = completed_transactions
+            .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?
+            .into_iter()
+            .map(|(_, txn)| txn)
+            .filter(|txn| after_tx_id.map(|tx_id| txn.tx_id > tx_id).unwrap_or(true))
+            .collect::<Vec<_>>();
+        transactions.sort_by_key(|txn| txn.tx_id);
+        if let Some(limits) = limits {
+            transactions.truncate(limits.limit);
+        }
 
         let (mut sender, receiver) = mpsc::channel(transactions.len());
         task::spawn(async move {
-            for (_, txn) in transactions {
+            let mut budget = limits.map(PageLimits::budget);
+            for txn in transactions {
                 let response = GetCompletedTransactionsResponse {
                     transaction: Some(TransactionInfo {
                         tx_id: txn.tx_id,
@@ -285,6 +398,11 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         valid: txn.valid,
                     }),
                 };
+                if let Some(ref mut budget) = budget {
+                    if !budget.try_add(&response) {
+                        return;
+                    }
+                }
                 match sender.send(Ok(response)).await {
                     Ok(_) => (),
                     Err(err) => {
@@ -301,7 +419,11 @@ impl wallet_server::Wallet for WalletGrpcServer {
             }
         });
 
-        Ok(Response::new(receiver))
+        let mut response = Response::new(receiver);
+        if let Some(limits) = limits {
+            limits.add_to_metadata(&mut response);
+        }
+        Ok(response)
     }
 
     async fn coin_split(&self, request: Request<CoinSplitRequest>) -> Result<Response<CoinSplitResponse>, Status> {
@@ -357,6 +479,40 @@ impl wallet_server::Wallet for WalletGrpcServer {
         Ok(Response::new(ImportUtxosResponse { tx_ids }))
     }
 
+    async fn import_utxo(&self, request: Request<ImportUtxoRequest>) -> Result<Response<ImportUtxoResponse>, Status> {
+        let message = request.into_inner();
+
+        let output = message
+            .output
+            .ok_or_else(|| Status::invalid_argument("Output is missing"))
+            .and_then(|o| UnblindedOutput::try_from(o).map_err(Status::invalid_argument))?;
+        let source_public_key = if message.source_public_key.is_empty() {
+            CommsPublicKey::default()
+        } else {
+            CommsPublicKey::from_bytes(&message.source_public_key)
+                .map_err(|_| Status::invalid_argument("Source public key is malformed"))?
+        };
+        let import_message = if message.message.is_empty() {
+            "Imported external UTXO via gRPC".to_string()
+        } else {
+            message.message
+        };
+
+        let mut wallet = self.wallet.clone();
+        let tx_id = wallet
+            .import_external_utxo(output, &source_public_key, import_message)
+            .await
+            .map_err(|e| match e {
+                WalletError::BaseNodeNotConnected | WalletError::BaseNodeNotSynced => {
+                    Status::unavailable(e.to_string())
+                },
+                WalletError::UtxoNotFoundOnChain(_) => Status::not_found(e.to_string()),
+                e => Status::internal(e.to_string()),
+            })?;
+
+        Ok(Response::new(ImportUtxoResponse { tx_id }))
+    }
+
     async fn get_network_status(
         &self,
         _: Request<tari_rpc::Empty>,
@@ -411,6 +567,33 @@ impl wallet_server::Wallet for WalletGrpcServer {
         Ok(Response::new(resp))
     }
 
+    async fn get_connected_base_node(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::GetConnectedBaseNodeResponse>, Status> {
+        let mut base_node_service = self.wallet.base_node_service.clone();
+        let base_node = base_node_service
+            .get_connected_base_node()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+        let latency_ms = match base_node {
+            Some(_) => base_node_service
+                .get_base_node_latency()
+                .await
+                .map_err(|err| Status::internal(err.to_string()))?
+                .map(|d| u32::try_from(d.as_millis()).unwrap_or(u32::MAX))
+                .unwrap_or_default(),
+            None => 0,
+        };
+
+        let resp = tari_rpc::GetConnectedBaseNodeResponse {
+            base_node: base_node.map(Into::into),
+            latency_ms,
+        };
+
+        Ok(Response::new(resp))
+    }
+
     async fn cancel_transaction(
         &self,
         request: Request<tari_rpc::CancelTransactionRequest>,
@@ -421,8 +604,12 @@ impl wallet_server::Wallet for WalletGrpcServer {
             "Incoming gRPC request to Cancel Transaction (TxId: {})", message.tx_id,
         );
         let mut transaction_service = self.get_transaction_service();
+        let reason = Some(message.reason).filter(|r| !r.trim().is_empty());
 
-        match transaction_service.cancel_transaction(message.tx_id).await {
+        match transaction_service
+            .cancel_transaction_with_reason(message.tx_id, reason)
+            .await
+        {
             Ok(_) => {
                 return Ok(Response::new(tari_rpc::CancelTransactionResponse {
                     is_success: true,
@@ -437,6 +624,141 @@ impl wallet_server::Wallet for WalletGrpcServer {
             },
         }
     }
+
+    async fn stream_transaction_events(
+        &self,
+        request: Request<StreamTransactionEventsRequest>,
+    ) -> Result<Response<Self::StreamTransactionEventsStream>, Status> {
+        let message = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming gRPC request to stream transaction events for {} transaction(s)",
+            if message.tx_ids.is_empty() {
+                "all".to_string()
+            } else {
+                message.tx_ids.len().to_string()
+            }
+        );
+        let mut transaction_service = self.get_transaction_service();
+        let mut event_stream = transaction_service.get_event_stream();
+        let wallet_pk = self.wallet.comms.node_identity_ref().public_key().clone();
+
+        let (mut sender, receiver) = mpsc::channel(100);
+        task::spawn(async move {
+            loop {
+                let event = match event_stream.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Transaction event stream lagged, {} event(s) were not sent to the gRPC client", n
+                        );
+                        continue;
+                    },
+                    Err(RecvError::Closed) => {
+                        debug!(target: LOG_TARGET, "Transaction event stream closed");
+                        return;
+                    },
+                };
+                let (event, tx_id, confirmations) = match convert_transaction_event(&event) {
+                    Some(converted) => converted,
+                    None => continue,
+                };
+                if !is_requested_transaction(&message.tx_ids, tx_id) {
+                    continue;
+                }
+
+                let transaction = match transaction_service.get_any_transaction(tx_id).await {
+                    Ok(tx) => tx.map(|tx| convert_wallet_transaction_into_transaction_info(tx, &wallet_pk)),
+                    Err(err) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Error fetching transaction {} for the gRPC event stream: {}", tx_id, err
+                        );
+                        None
+                    },
+                };
+                let response = StreamTransactionEventsResponse {
+                    event: event as i32,
+                    tx_id,
+                    confirmations,
+                    transaction,
+                };
+                if let Err(err) = sender.send(Ok(response)).await {
+                    debug!(
+                        target: LOG_TARGET,
+                        "gRPC client stopped listening for transaction events: {}", err
+                    );
+                    return;
+                }
+            }
+        });
+
+        Ok(Response::new(receiver))
+    }
+
+    async fn create_account(
+        &self,
+        request: Request<CreateAccountRequest>,
+    ) -> Result<Response<CreateAccountResponse>, Status> {
+        let message = request.into_inner();
+        debug!(target: LOG_TARGET, "Incoming gRPC request to create account `{}`", message.name);
+        let mut output_service = self.get_output_manager_service();
+        let account = output_service
+            .create_account(message.name)
+            .await
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+
+        Ok(Response::new(CreateAccountResponse {
+            account: Some(convert_wallet_account(account)),
+        }))
+    }
+
+    async fn list_accounts(&self, _: Request<tari_rpc::Empty>) -> Result<Response<ListAccountsResponse>, Status> {
+        let mut output_service = self.get_output_manager_service();
+        let accounts = output_service
+            .get_accounts()
+            .await
+            .map_err(|err| Status::unknown(err.to_string()))?
+            .into_iter()
+            .map(convert_wallet_account)
+            .collect();
+
+        Ok(Response::new(ListAccountsResponse { accounts }))
+    }
+}
+
+fn convert_wallet_account(account: WalletAccount) -> WalletAccountMessage {
+    WalletAccountMessage {
+        id: account.id,
+        name: account.name,
+        created_at: Some(naive_datetime_to_timestamp(account.created_at)),
+    }
+}
+
+/// Converts a transaction service event into the event type, transaction id and number of confirmations streamed to
+/// gRPC clients. Events that are not about a single transaction are not streamed.
+fn convert_transaction_event(event: &TransactionEvent) -> Option<(TransactionEventType, TxId, u64)> {
+    use TransactionEvent::*;
+    match event {
+        ReceivedTransaction(tx_id) => Some((TransactionEventType::Received, *tx_id, 0)),
+        ReceivedTransactionReply(tx_id) => Some((TransactionEventType::ReplyReceived, *tx_id, 0)),
+        ReceivedFinalizedTransaction(tx_id) => Some((TransactionEventType::Finalized, *tx_id, 0)),
+        TransactionBroadcast(tx_id) => Some((TransactionEventType::Broadcast, *tx_id, 0)),
+        TransactionMinedUnconfirmed(tx_id, confirmations) => {
+            Some((TransactionEventType::MinedUnconfirmed, *tx_id, *confirmations))
+        },
+        TransactionMined(tx_id) => Some((TransactionEventType::Mined, *tx_id, 0)),
+        TransactionCancelled(tx_id) => Some((TransactionEventType::Cancelled, *tx_id, 0)),
+        TransactionImported(tx_id) => Some((TransactionEventType::Imported, *tx_id, 0)),
+        _ => None,
+    }
+}
+
+/// Whether events for the transaction are streamed to a client that asked for the given transactions, where no
+/// transactions means all of them
+fn is_requested_transaction(requested_tx_ids: &[TxId], tx_id: TxId) -> bool {
+    requested_tx_ids.is_empty() || requested_tx_ids.contains(&tx_id)
 }
 
 fn convert_wallet_transaction_into_transaction_info(
@@ -493,3 +815,49 @@ fn convert_wallet_transaction_into_transaction_info(
         },
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_converts_transaction_events() {
+        use TransactionEvent::*;
+        let cases = vec![
+            (ReceivedTransaction(1), TransactionEventType::Received),
+            (ReceivedTransactionReply(1), TransactionEventType::ReplyReceived),
+            (ReceivedFinalizedTransaction(1), TransactionEventType::Finalized),
+            (TransactionBroadcast(1), TransactionEventType::Broadcast),
+            (TransactionMined(1), TransactionEventType::Mined),
+            (TransactionCancelled(1), TransactionEventType::Cancelled),
+            (TransactionImported(1), TransactionEventType::Imported),
+        ];
+        for (event, expected) in cases {
+            assert_eq!(convert_transaction_event(&event), Some((expected, 1, 0)));
+        }
+        assert_eq!(
+            convert_transaction_event(&TransactionMinedUnconfirmed(2, 3)),
+            Some((TransactionEventType::MinedUnconfirmed, 2, 3))
+        );
+        // Events that are not about a single transaction are not streamed
+        assert_eq!(convert_transaction_event(&TransactionValidationSuccess(1)), None);
+        assert_eq!(convert_transaction_event(&Error("error".to_string())), None);
+    }
+
+    #[test]
+    fn it_never_streams_the_unspecified_event_type() {
+        assert_eq!(TransactionEventType::Unspecified as i32, 0);
+        assert_eq!(
+            TransactionEventType::from_i32(0),
+            Some(TransactionEventType::Unspecified)
+        );
+        assert_ne!(TransactionEventType::Received as i32, 0);
+    }
+
+    #[test]
+    fn it_filters_requested_transactions() {
+        assert!(is_requested_transaction(&[], 1));
+        assert!(is_requested_transaction(&[1, 2], 2));
+        assert!(!is_requested_transaction(&[1, 2], 3));
+    }
+}
@@ -26,11 +26,11 @@ use log::*;
 use rpassword::prompt_password_stdout;
 use rustyline::Editor;
 
-use tari_app_utilities::utilities::{create_transport_type, ExitCodes};
+use tari_app_utilities::utilities::{create_autoupdate_socks_config, create_transport_type, ExitCodes};
 use tari_common::{ConfigBootstrap, GlobalConfig};
 use tari_common_types::types::PrivateKey;
 use tari_comms::{
-    peer_manager::{Peer, PeerFeatures},
+    peer_manager::{Peer, PeerCapabilities, PeerFeatures},
     types::CommsSecretKey,
     NodeIdentity,
 };
@@ -47,7 +47,7 @@ use tari_shutdown::ShutdownSignal;
 use tari_wallet::{
     base_node_service::config::BaseNodeServiceConfig,
     error::{WalletError, WalletStorageError},
-    output_manager_service::{config::OutputManagerServiceConfig, TxoValidationType},
+    output_manager_service::{config::OutputManagerServiceConfig, watch_only::WatchOnlyKeys, TxoValidationType},
     storage::{database::WalletDatabase, sqlite_utilities::initialize_sqlite_database_backends},
     transaction_service::{
         config::{TransactionRoutingMechanism, TransactionServiceConfig},
@@ -60,6 +60,8 @@ use tari_wallet::{
 };
 
 use crate::{
+    automation::commands::rotate_encryption,
+    cli::HeadlessCommand,
     utils::db::get_custom_base_node_peer_from_db,
     wallet_modes::{PeerConfig, WalletMode},
 };
@@ -69,11 +71,31 @@ pub const LOG_TARGET: &str = "wallet::console_wallet::init";
 const BASE_NODE_BUFFER_MIN_SIZE: usize = 30;
 const TARI_WALLET_PASSWORD: &str = "TARI_WALLET_PASSWORD";
 
+/// Determines the master key of the wallet when the wallet database does not contain one yet
+pub enum WalletSeed {
+    /// The wallet generates a random master key, whose seed words are shown to the user
+    Random,
+    /// The master key was recovered from seed words without a seed words passphrase
+    Recovered(PrivateKey),
+    /// A wallet protected by a seed words passphrase, either new or `recovered` from its seed words. The seed words
+    /// encode `seed_key`, while the wallet uses `master_key`, which is derived from the seed key and the passphrase.
+    /// The seed key is kept in the wallet database so that the seed words can be shown again later.
+    Passphrase {
+        seed_key: PrivateKey,
+        master_key: PrivateKey,
+        recovered: bool,
+    },
+    /// A watch-only wallet that finds the outputs of the wallet the keys were exported from, with a random master key
+    /// that is only used for its comms identity
+    WatchOnly(WatchOnlyKeys),
+}
+
 #[derive(Clone, Copy)]
 pub enum WalletBoot {
     New,
     Existing,
     Recovery,
+    WatchOnly,
 }
 
 /// Gets the password provided by command line argument or environment variable if available.
@@ -104,7 +126,7 @@ pub fn get_or_prompt_password(
     Ok(Some(password))
 }
 
-fn prompt_password(prompt: &str) -> Result<String, ExitCodes> {
+pub fn prompt_password(prompt: &str) -> Result<String, ExitCodes> {
     let password = loop {
         let pass = prompt_password_stdout(prompt).map_err(|e| ExitCodes::IOError(e.to_string()))?;
         if pass.is_empty() {
@@ -118,14 +140,8 @@ fn prompt_password(prompt: &str) -> Result<String, ExitCodes> {
     Ok(password)
 }
 
-/// Allows the user to change the password of the wallet.
-pub async fn change_password(
-    config: &GlobalConfig,
-    arg_password: Option<String>,
-    shutdown_signal: ShutdownSignal,
-) -> Result<(), ExitCodes> {
-    let mut wallet = init_wallet(config, arg_password, None, None, shutdown_signal).await?;
-
+/// Prompts for a new password to be typed in twice
+pub fn prompt_new_password() -> Result<String, ExitCodes> {
     let passphrase = prompt_password("New wallet password: ")?;
     let confirmed = prompt_password("Confirm new password: ")?;
 
@@ -133,15 +149,51 @@ pub async fn change_password(
         return Err(ExitCodes::InputError("Passwords don't match!".to_string()));
     }
 
-    wallet
-        .remove_encryption()
-        .await
-        .map_err(|e| ExitCodes::WalletError(e.to_string()))?;
+    Ok(passphrase)
+}
 
-    wallet
-        .apply_encryption(passphrase)
-        .await
-        .map_err(|e| ExitCodes::WalletError(e.to_string()))?;
+/// Returns true if the wallet database exists and is encrypted
+fn is_wallet_encrypted(config: &GlobalConfig) -> Result<bool, ExitCodes> {
+    if !config.console_wallet_db_file.exists() {
+        return Ok(false);
+    }
+    match initialize_sqlite_database_backends(config.console_wallet_db_file.clone(), None) {
+        Ok(_) => Ok(false),
+        Err(WalletStorageError::NoPasswordError) => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Allows the user to change the password of the wallet. The wallet database of an encrypted wallet is re-encrypted
+/// with the new password, so it is never decrypted to plaintext in the process.
+pub async fn change_password(
+    config: &GlobalConfig,
+    arg_password: Option<String>,
+    shutdown_signal: ShutdownSignal,
+) -> Result<(), ExitCodes> {
+    let current_password = if is_wallet_encrypted(config)? {
+        get_or_prompt_password(arg_password, config.console_wallet_password.clone())?
+    } else {
+        None
+    };
+    let mut wallet = init_wallet(
+        config,
+        current_password.clone(),
+        None,
+        WalletSeed::Random,
+        shutdown_signal,
+    )
+    .await?;
+
+    let passphrase = prompt_new_password()?;
+
+    match current_password {
+        Some(current_password) => rotate_encryption(wallet, current_password, passphrase).await?,
+        None => wallet
+            .apply_encryption(passphrase)
+            .await
+            .map_err(|e| ExitCodes::WalletError(e.to_string()))?,
+    }
 
     println!("Wallet password changed successfully.");
 
@@ -184,7 +236,23 @@ pub async fn get_base_node_peer_config(
 }
 
 /// Determines which mode the wallet should run in.
-pub fn wallet_mode(bootstrap: &ConfigBootstrap, boot_mode: WalletBoot) -> WalletMode {
+pub fn wallet_mode(
+    bootstrap: &ConfigBootstrap,
+    boot_mode: WalletBoot,
+    headless_command: Option<HeadlessCommand>,
+) -> WalletMode {
+    // Headless mode runs a single command and cannot be combined with any of the other modes
+    if let Some(command) = headless_command {
+        if matches!(boot_mode, WalletBoot::Recovery) ||
+            bootstrap.non_interactive_mode ||
+            bootstrap.input_file.is_some() ||
+            bootstrap.command.is_some()
+        {
+            return WalletMode::Invalid;
+        }
+        return WalletMode::Headless(command);
+    }
+
     // Recovery mode
     if matches!(boot_mode, WalletBoot::Recovery) {
         if bootstrap.non_interactive_mode {
@@ -258,7 +326,7 @@ pub async fn init_wallet(
     config: &GlobalConfig,
     arg_password: Option<String>,
     seed_words_file_name: Option<PathBuf>,
-    recovery_master_key: Option<PrivateKey>,
+    wallet_seed: WalletSeed,
     shutdown_signal: ShutdownSignal,
 ) -> Result<WalletSqlite, ExitCodes> {
     fs::create_dir_all(
@@ -293,8 +361,17 @@ pub async fn init_wallet(
             return Err(e.into());
         },
     };
-    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend) = backends;
+    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend, multisig_backend) = backends;
     let wallet_db = WalletDatabase::new(wallet_backend);
+    if let WalletSeed::WatchOnly(keys) = &wallet_seed {
+        wallet_db.set_watch_only_keys(keys.clone()).await?;
+    }
+    if let WalletSeed::Passphrase { seed_key, .. } = &wallet_seed {
+        // Only for a new wallet, starting an existing wallet with a different master key fails below
+        if wallet_db.get_master_secret_key().await?.is_none() {
+            wallet_db.set_seed_words_key(seed_key.clone()).await?;
+        }
+    }
 
     debug!(
         target: LOG_TARGET,
@@ -330,6 +407,7 @@ pub async fn init_wallet(
         network: config.network,
         node_identity,
         user_agent: format!("tari/wallet/{}", env!("CARGO_PKG_VERSION")),
+        capabilities: PeerCapabilities::NONE,
         transport_type,
         auxilary_tcp_listener_address: None,
         datastore_path: config.console_wallet_peer_db_path.clone(),
@@ -348,11 +426,14 @@ pub async fn init_wallet(
         // This should be false unless testing locally
         allow_test_addresses: config.allow_test_addresses,
         listener_liveness_allowlist_cidrs: Vec::new(),
+        allowed_addresses: Vec::new(),
+        blocked_addresses: Vec::new(),
         listener_liveness_max_sessions: 0,
         dns_seeds_name_server: DEFAULT_DNS_NAME_SERVER.parse().unwrap(),
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: true,
+        peer_snapshot_path: None,
     };
 
     let base_node_service_config = BaseNodeServiceConfig::new(
@@ -368,6 +449,7 @@ pub async fn init_wallet(
         download_base_url: "https://tari-binaries.s3.amazonaws.com/latest".to_string(),
         hashes_url: config.autoupdate_hashes_url.clone(),
         hashes_sig_url: config.autoupdate_hashes_sig_url.clone(),
+        socks_proxy: create_autoupdate_socks_config(config),
     };
 
     let factories = CryptoFactories::default();
@@ -383,6 +465,7 @@ pub async fn init_wallet(
                 config.transaction_routing_mechanism.clone(),
             ),
             num_confirmations_required: config.transaction_num_confirmations_required,
+            cancelled_output_release_timeout: config.transaction_cancelled_output_release_timeout,
             transaction_event_channel_size: config.transaction_event_channel_size,
             ..Default::default()
         }),
@@ -411,8 +494,12 @@ pub async fn init_wallet(
         transaction_backend,
         output_manager_backend,
         contacts_backend,
+        multisig_backend,
         shutdown_signal,
-        recovery_master_key.clone(),
+        match &wallet_seed {
+            WalletSeed::Random | WalletSeed::WatchOnly(_) => None,
+            WalletSeed::Recovered(master_key) | WalletSeed::Passphrase { master_key, .. } => Some(master_key.clone()),
+        },
     )
     .await
     .map_err(|e| {
@@ -454,8 +541,14 @@ pub async fn init_wallet(
 
         debug!(target: LOG_TARGET, "Wallet encrypted.");
 
-        if interactive && recovery_master_key.is_none() {
-            match confirm_seed_words(&mut wallet).await {
+        // A watch-only wallet has no seed words, and the user already has the seed words of a recovered wallet
+        let has_new_seed_words = matches!(
+            wallet_seed,
+            WalletSeed::Random | WalletSeed::Passphrase { recovered: false, .. }
+        );
+        if interactive && has_new_seed_words {
+            let has_passphrase = matches!(wallet_seed, WalletSeed::Passphrase { .. });
+            match confirm_seed_words(&mut wallet, has_passphrase).await {
                 Ok(()) => {
                     print!("\x1Bc"); // Clear the screen
                 },
@@ -464,7 +557,7 @@ pub async fn init_wallet(
                 },
             };
         }
-        if let Some(file_name) = seed_words_file_name {
+        if let Some(file_name) = seed_words_file_name.filter(|_| !matches!(wallet_seed, WalletSeed::WatchOnly(_))) {
             let seed_words = wallet.output_manager_service.get_seed_words().await?.join(" ");
             let _ = fs::write(file_name, seed_words)
                 .map_err(|e| ExitCodes::WalletError(format!("Problem writing seed words to file: {}", e)));
@@ -474,10 +567,12 @@ pub async fn init_wallet(
     Ok(wallet)
 }
 
-/// Starts the wallet by setting the base node peer, and restarting the transaction and broadcast protocols.
+/// Starts the wallet by setting the base node peer and the base nodes to fail over to, and restarting the transaction
+/// and broadcast protocols.
 pub async fn start_wallet(
     wallet: &mut WalletSqlite,
     base_node: &Peer,
+    base_node_peers: Vec<Peer>,
     wallet_mode: &WalletMode,
 ) -> Result<(), ExitCodes> {
     // TODO gRPC interfaces for setting base node
@@ -494,9 +589,22 @@ pub async fn start_wallet(
         .await
         .map_err(|e| ExitCodes::WalletError(format!("Error setting wallet base node peer. {}", e)))?;
 
-    // Restart transaction protocols if not running in script or command modes
+    debug!(
+        target: LOG_TARGET,
+        "Setting {} base node peers for failover",
+        base_node_peers.len()
+    );
+    wallet
+        .set_base_node_peers(base_node_peers)
+        .await
+        .map_err(|e| ExitCodes::WalletError(format!("Error setting wallet base node peers. {}", e)))?;
 
-    if !matches!(wallet_mode, WalletMode::Command(_)) && !matches!(wallet_mode, WalletMode::Script(_)) {
+    // Restart transaction protocols if not running in script, command or headless modes
+
+    if !matches!(
+        wallet_mode,
+        WalletMode::Command(_) | WalletMode::Script(_) | WalletMode::Headless(_)
+    ) {
         if let Err(e) = wallet.transaction_service.restart_transaction_protocols().await {
             error!(target: LOG_TARGET, "Problem restarting transaction protocols: {}", e);
         }
@@ -556,7 +664,7 @@ async fn validate_txos(wallet: &mut WalletSqlite) -> Result<(), ExitCodes> {
     Ok(())
 }
 
-async fn confirm_seed_words(wallet: &mut WalletSqlite) -> Result<(), ExitCodes> {
+async fn confirm_seed_words(wallet: &mut WalletSqlite, has_passphrase: bool) -> Result<(), ExitCodes> {
     let seed_words = wallet.output_manager_service.get_seed_words().await?;
 
     println!();
@@ -570,6 +678,11 @@ async fn confirm_seed_words(wallet: &mut WalletSqlite) -> Result<(), ExitCodes>
     println!("=========================");
     println!("{}", seed_words.join(" "));
     println!("=========================");
+    if has_passphrase {
+        println!("This wallet is protected by your seed words passphrase.");
+        println!("You will need BOTH these seed words and the passphrase to recover it.");
+        println!("=========================");
+    }
     println!("\x07"); // beep!
 
     let mut rl = Editor::<()>::new();
@@ -638,6 +751,7 @@ pub(crate) fn boot(bootstrap: &ConfigBootstrap, config: &GlobalConfig) -> Result
         loop {
             println!("1. Create a new wallet.");
             println!("2. Recover wallet from seed words.");
+            println!("3. Create a watch-only wallet from exported watch-only keys.");
             let readline = rl.readline(">> ");
             match readline {
                 Ok(line) => {
@@ -650,6 +764,10 @@ pub(crate) fn boot(bootstrap: &ConfigBootstrap, config: &GlobalConfig) -> Result
                             // recover wallet
                             return Ok(WalletBoot::Recovery);
                         },
+                        "3" | "w" | "watch" => {
+                            // watch-only wallet
+                            return Ok(WalletBoot::WatchOnly);
+                        },
                         _ => continue,
                     }
                 },
@@ -660,3 +778,23 @@ pub(crate) fn boot(bootstrap: &ConfigBootstrap, config: &GlobalConfig) -> Result
         }
     }
 }
+
+/// Prompts the user for the keys written by `export-watch-only-keys` on the wallet to watch.
+pub fn prompt_watch_only_keys() -> Result<WatchOnlyKeys, ExitCodes> {
+    debug!(target: LOG_TARGET, "Prompting for watch-only keys.");
+    let mut rl = Editor::<()>::new();
+
+    loop {
+        println!("Paste the watch-only keys exported from the wallet to watch.");
+        let input = rl.readline(">> ").map_err(|e| ExitCodes::IOError(e.to_string()))?;
+
+        match WatchOnlyKeys::from_hex(input.trim()) {
+            Ok(keys) => break Ok(keys),
+            Err(e) => {
+                debug!(target: LOG_TARGET, "Error parsing watch-only keys: {}", e);
+                println!("Failed to parse the watch-only keys! Did you paste all of them?");
+                continue;
+            },
+        }
+    }
+}
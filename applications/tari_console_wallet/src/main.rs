@@ -6,32 +6,47 @@
 #![deny(unreachable_patterns)]
 #![deny(unknown_lints)]
 #![recursion_limit = "1024"]
-use crate::{recovery::get_private_key_from_seed_words, wallet_modes::WalletModeConfig};
+use crate::{
+    cli::Cli,
+    recovery::{
+        apply_passphrase,
+        get_private_key_from_seed_words,
+        prompt_new_seed_words_passphrase,
+        prompt_seed_words_passphrase,
+    },
+    wallet_modes::WalletModeConfig,
+};
 use init::{
     boot,
     change_password,
     get_base_node_peer_config,
     get_notify_script,
     init_wallet,
+    prompt_watch_only_keys,
     start_wallet,
     tari_splash_screen,
     wallet_mode,
     WalletBoot,
+    WalletSeed,
 };
 use log::*;
 use opentelemetry::{self, global, KeyValue};
+use rand::rngs::OsRng;
 use recovery::prompt_private_key_from_seed_words;
 use std::{env, process};
-use tari_app_utilities::{consts, initialization::init_configuration, utilities::ExitCodes};
+use structopt::StructOpt;
+use tari_app_utilities::{consts, initialization::init_configuration_from_bootstrap, telemetry, utilities::ExitCodes};
 use tari_common::{configuration::bootstrap::ApplicationType, ConfigBootstrap};
 use tari_common_types::types::PrivateKey;
+use tari_crypto::keys::SecretKey;
 use tari_shutdown::Shutdown;
 use tracing_subscriber::{layer::SubscriberExt, Registry};
-use wallet_modes::{command_mode, grpc_mode, recovery_mode, script_mode, tui_mode, WalletMode};
+use wallet_modes::{command_mode, grpc_mode, headless_mode, recovery_mode, script_mode, tui_mode, WalletMode};
 
 pub const LOG_TARGET: &str = "wallet::console_wallet::main";
 
 mod automation;
+mod cli;
 mod grpc;
 mod init;
 mod notifier;
@@ -63,7 +78,10 @@ fn main_inner() -> Result<(), ExitCodes> {
         .build()
         .expect("Failed to build a runtime!");
 
-    let (bootstrap, global_config, _) = init_configuration(ApplicationType::ConsoleWallet)?;
+    let cli = Cli::from_args();
+    let headless_command = cli.command;
+    let (bootstrap, global_config, _) =
+        init_configuration_from_bootstrap(ApplicationType::ConsoleWallet, cli.bootstrap)?;
 
     info!(
         target: LOG_TARGET,
@@ -78,14 +96,14 @@ fn main_inner() -> Result<(), ExitCodes> {
     // get command line password if provided
     let arg_password = bootstrap.password.clone();
 
-    if arg_password.is_none() {
+    if arg_password.is_none() && headless_command.is_none() {
         tari_splash_screen("Console Wallet");
     }
 
     // check for recovery based on existence of wallet file
     let mut boot_mode = boot(&bootstrap, &global_config)?;
 
-    let recovery_master_key: Option<PrivateKey> = get_recovery_master_key(boot_mode, &bootstrap)?;
+    let wallet_seed = get_wallet_seed(boot_mode, &bootstrap, headless_command.is_some())?;
 
     if bootstrap.init {
         info!(target: LOG_TARGET, "Default configuration created. Done.");
@@ -109,7 +127,7 @@ fn main_inner() -> Result<(), ExitCodes> {
         &global_config,
         arg_password,
         seed_words_file_name,
-        recovery_master_key,
+        wallet_seed,
         shutdown_signal,
     ))?;
 
@@ -123,10 +141,17 @@ fn main_inner() -> Result<(), ExitCodes> {
     let base_node_config = runtime.block_on(get_base_node_peer_config(&global_config, &mut wallet))?;
     let base_node_selected = base_node_config.get_base_node_peer()?;
 
-    let wallet_mode = wallet_mode(&bootstrap, boot_mode);
+    let is_headless = headless_command.is_some();
+    let wallet_mode = wallet_mode(&bootstrap, boot_mode, headless_command);
 
     // start wallet
-    runtime.block_on(start_wallet(&mut wallet, &base_node_selected, &wallet_mode))?;
+    let base_node_peers = base_node_config.get_failover_peers(&base_node_selected);
+    runtime.block_on(start_wallet(
+        &mut wallet,
+        &base_node_selected,
+        base_node_peers,
+        &wallet_mode,
+    ))?;
 
     // optional path to notify script
     let notify_script = get_notify_script(&bootstrap, &global_config)?;
@@ -148,45 +173,93 @@ fn main_inner() -> Result<(), ExitCodes> {
         WalletMode::Grpc => grpc_mode(config, wallet.clone()),
         WalletMode::Script(path) => script_mode(config, wallet.clone(), path),
         WalletMode::Command(command) => command_mode(config, wallet.clone(), command),
+        WalletMode::Headless(command) => headless_mode(config, wallet.clone(), command),
         WalletMode::RecoveryDaemon | WalletMode::RecoveryTui => recovery_mode(config, wallet.clone()),
         WalletMode::Invalid => Err(ExitCodes::InputError(
             "Invalid wallet mode - are you trying too many command options at once?".to_string(),
         )),
     };
 
-    print!("\nShutting down wallet... ");
+    // Only the command result is printed in headless mode
+    if !is_headless {
+        print!("\nShutting down wallet... ");
+    }
     shutdown.trigger();
     runtime.block_on(wallet.wait_until_shutdown());
-    println!("Done.");
+    if !is_headless {
+        println!("Done.");
+    }
 
     result
 }
 
-fn get_recovery_master_key(
+/// Determines the master key for a wallet that is being recovered, or a new wallet protected by a seed words
+/// passphrase. The passphrase is taken from the command line, or prompted for when the wallet is set up interactively.
+fn get_wallet_seed(
     boot_mode: WalletBoot,
     bootstrap: &ConfigBootstrap,
-) -> Result<Option<PrivateKey>, ExitCodes> {
-    if matches!(boot_mode, WalletBoot::Recovery) {
-        let private_key = if bootstrap.seed_words.is_some() {
-            let seed_words: Vec<String> = bootstrap
-                .seed_words
-                .clone()
-                .unwrap()
-                .split_whitespace()
-                .map(|v| v.to_string())
-                .collect();
-            get_private_key_from_seed_words(seed_words)?
-        } else {
-            prompt_private_key_from_seed_words()?
-        };
-        Ok(Some(private_key))
-    } else {
-        Ok(None)
+    headless: bool,
+) -> Result<WalletSeed, ExitCodes> {
+    match boot_mode {
+        WalletBoot::Recovery => {
+            let (seed_key, passphrase) = if bootstrap.seed_words.is_some() {
+                let seed_words: Vec<String> = bootstrap
+                    .seed_words
+                    .clone()
+                    .unwrap()
+                    .split_whitespace()
+                    .map(|v| v.to_string())
+                    .collect();
+                let seed_key = get_private_key_from_seed_words(seed_words)?;
+                (seed_key, bootstrap.seed_words_passphrase.clone().unwrap_or_default())
+            } else {
+                let seed_key = prompt_private_key_from_seed_words()?;
+                let passphrase = match bootstrap.seed_words_passphrase.clone() {
+                    Some(passphrase) => passphrase,
+                    None => prompt_seed_words_passphrase()?,
+                };
+                (seed_key, passphrase)
+            };
+            if passphrase.is_empty() {
+                return Ok(WalletSeed::Recovered(seed_key));
+            }
+            let master_key = apply_passphrase(seed_key.clone(), &passphrase)?;
+            Ok(WalletSeed::Passphrase {
+                seed_key,
+                master_key,
+                recovered: true,
+            })
+        },
+        WalletBoot::New => {
+            let passphrase = match bootstrap.seed_words_passphrase.clone() {
+                Some(passphrase) => passphrase,
+                // The wallet is set up non-interactively when the password is provided on the command line
+                None if bootstrap.password.is_none() && !headless => prompt_new_seed_words_passphrase()?,
+                None => String::new(),
+            };
+            if passphrase.is_empty() {
+                return Ok(WalletSeed::Random);
+            }
+            let seed_key = PrivateKey::random(&mut OsRng);
+            let master_key = apply_passphrase(seed_key.clone(), &passphrase)?;
+            Ok(WalletSeed::Passphrase {
+                seed_key,
+                master_key,
+                recovered: false,
+            })
+        },
+        WalletBoot::WatchOnly => Ok(WalletSeed::WatchOnly(prompt_watch_only_keys()?)),
+        WalletBoot::Existing => Ok(WalletSeed::Random),
     }
 }
 
 fn enable_tracing_if_specified(bootstrap: &ConfigBootstrap) {
-    if bootstrap.tracing_enabled {
+    if cfg!(feature = "tokio-console") {
+        if bootstrap.tracing_enabled {
+            warn!(target: LOG_TARGET, "Tracing is not available when built with the tokio-console feature");
+        }
+        telemetry::enable_tokio_console();
+    } else if bootstrap.tracing_enabled {
         // To run: docker run -d -p6831:6831/udp -p6832:6832/udp -p16686:16686 -p14268:14268 \
         // jaegertracing/all-in-one:latest
         global::set_text_map_propagator(opentelemetry_jaeger::Propagator::new());
@@ -23,13 +23,15 @@
 use chrono::offset::Local;
 use futures::FutureExt;
 use log::*;
+use rpassword::prompt_password_stdout;
 use rustyline::Editor;
 use tari_app_utilities::utilities::ExitCodes;
 use tari_common_types::types::PrivateKey;
-use tari_key_manager::mnemonic::to_secretkey;
+use tari_key_manager::{key_manager::apply_seed_words_passphrase, mnemonic::to_secretkey};
 use tari_shutdown::Shutdown;
 use tari_wallet::{
     storage::sqlite_db::WalletSqliteDatabase,
+    types::KeyDigest,
     utxo_scanner_service::{handle::UtxoScannerEvent, utxo_scanning::UtxoScannerService},
     WalletSqlite,
 };
@@ -75,6 +77,59 @@ pub fn get_private_key_from_seed_words(seed_words: Vec<String>) -> Result<Privat
     }
 }
 
+/// Prompt the user for the optional seed words passphrase of the wallet being recovered.
+pub fn prompt_seed_words_passphrase() -> Result<String, ExitCodes> {
+    debug!(target: LOG_TARGET, "Prompting for seed words passphrase.");
+    println!();
+    println!("If your wallet was created with a seed words passphrase, enter it now. Otherwise leave it empty.");
+    println!("WARNING: A wrong passphrase recovers a different, empty wallet without any error.");
+    prompt_password_stdout("Seed words passphrase: ").map_err(|e| ExitCodes::IOError(e.to_string()))
+}
+
+/// Ask the user whether the new wallet should be protected by a seed words passphrase, and prompt for it if so.
+/// Returns an empty passphrase if the user declines.
+pub fn prompt_new_seed_words_passphrase() -> Result<String, ExitCodes> {
+    debug!(target: LOG_TARGET, "Prompting for new seed words passphrase.");
+    let mut rl = Editor::<()>::new();
+    println!();
+    println!("Would you like to protect your seed words with an additional passphrase? (y/N)");
+    let readline = rl.readline(">> ").map_err(|e| ExitCodes::IOError(e.to_string()))?;
+    if !matches!(readline.trim().to_lowercase().as_str(), "y" | "yes") {
+        return Ok(String::new());
+    }
+
+    println!();
+    println!("=========================");
+    println!("        WARNING!         ");
+    println!("=========================");
+    println!("The passphrase is NOT part of your seed words and is not stored anywhere.");
+    println!("You will need BOTH your seed words and this exact passphrase to recover your wallet.");
+    println!("Recovering with a different passphrase produces a different, empty wallet without any error.");
+    println!("IF YOU FORGET THE PASSPHRASE YOUR FUNDS ARE LOST.");
+    println!("=========================");
+    loop {
+        let passphrase =
+            prompt_password_stdout("Seed words passphrase: ").map_err(|e| ExitCodes::IOError(e.to_string()))?;
+        if passphrase.is_empty() {
+            println!("Passphrase cannot be empty!");
+            continue;
+        }
+        let confirmed =
+            prompt_password_stdout("Confirm seed words passphrase: ").map_err(|e| ExitCodes::IOError(e.to_string()))?;
+        if passphrase != confirmed {
+            println!("Passphrases don't match!");
+            continue;
+        }
+        break Ok(passphrase);
+    }
+}
+
+/// Combine the secret key encoded by the seed words with the seed words passphrase, producing the wallet master key.
+pub fn apply_passphrase(seed_key: PrivateKey, passphrase: &str) -> Result<PrivateKey, ExitCodes> {
+    apply_seed_words_passphrase::<PrivateKey, KeyDigest>(seed_key, passphrase)
+        .map_err(|e| ExitCodes::WalletError(format!("Error applying the seed words passphrase: {}", e)))
+}
+
 /// Recovers wallet funds by connecting to a given base node peer, downloading the transaction outputs stored in the
 /// blockchain, and attempting to rewind them. Any outputs that are successfully rewound are then imported into the
 /// wallet.
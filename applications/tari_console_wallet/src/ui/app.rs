@@ -24,7 +24,9 @@ use crate::{
     notifier::Notifier,
     ui::{
         components::{
+            balance_history_tab::BalanceHistoryTab,
             base_node::BaseNode,
+            chain_monitor_tab::ChainMonitorTab,
             log_tab::LogTab,
             menu::Menu,
             network_tab::NetworkTab,
@@ -88,6 +90,8 @@ impl<B: Backend> App<B> {
             .add("Send".into(), Box::new(SendTab::new()))
             .add("Receive".into(), Box::new(ReceiveTab::new()))
             .add("Network".into(), Box::new(NetworkTab::new(base_node_selected)))
+            .add("Chain".into(), Box::new(ChainMonitorTab::new()))
+            .add("History".into(), Box::new(BalanceHistoryTab::new()))
             .add("Log".into(), Box::new(LogTab::new()))
             .add("Notifications".into(), Box::new(NotificationTab::new()));
 
@@ -0,0 +1,140 @@
+use crate::ui::{components::Component, state::AppState};
+use chrono::Utc;
+use tari_core::transactions::tari_amount::{MicroTari, Tari};
+use tari_wallet::transaction_service::balance_history::{balance_history, BalanceSnapshot};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Wrap},
+    Frame,
+};
+
+/// The periods, in days, that the chart can show. `d` switches to the next one.
+const PERIODS: [u32; 4] = [7, 30, 90, 365];
+
+/// Charts the balance of the wallet at the end of each day, derived from its mined and imported transactions
+pub struct BalanceHistoryTab {
+    period: usize,
+}
+
+impl BalanceHistoryTab {
+    pub fn new() -> Self {
+        Self { period: 1 }
+    }
+
+    fn days(&self) -> u32 {
+        PERIODS[self.period]
+    }
+
+    fn draw_summary<B>(&self, f: &mut Frame<B>, area: Rect, history: &[BalanceSnapshot])
+    where B: Backend {
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            format!("Balance History ({} days)", self.days()),
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        ));
+        let (first, last) = match (history.first(), history.last()) {
+            (Some(first), Some(last)) => (first.balance, last.balance),
+            _ => (MicroTari::from(0), MicroTari::from(0)),
+        };
+        let (change_color, change) = if last >= first {
+            (Color::Green, format!("+{}", last - first))
+        } else {
+            (Color::Red, format!("-{}", first - last))
+        };
+        let max = history.iter().map(|s| s.balance).max().unwrap_or_default();
+        let min = history.iter().map(|s| s.balance).min().unwrap_or_default();
+        let lines = vec![
+            Spans::from(vec![
+                Span::styled("Balance:", Style::default().fg(Color::Magenta)),
+                Span::raw(format!(" {}   ", last)),
+                Span::styled("Change:", Style::default().fg(Color::Magenta)),
+                Span::styled(format!(" {}   ", change), Style::default().fg(change_color)),
+                Span::styled("Low:", Style::default().fg(Color::Magenta)),
+                Span::raw(format!(" {}   ", min)),
+                Span::styled("High:", Style::default().fg(Color::Magenta)),
+                Span::raw(format!(" {}", max)),
+            ]),
+            Spans::from(vec![
+                Span::raw("Only mined and imported transactions are counted. Press "),
+                Span::styled("D", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" to change the period."),
+            ]),
+        ];
+        let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+    }
+
+    fn draw_chart<B>(&self, f: &mut Frame<B>, area: Rect, history: &[BalanceSnapshot])
+    where B: Backend {
+        let data = history
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i as f64, f64::from(Tari::from(s.balance))))
+            .collect::<Vec<_>>();
+        let max = data.iter().map(|(_, balance)| *balance).fold(0.0, f64::max);
+        // Keep a flat line off the top of the chart
+        let top = if max > 0.0 { max * 1.1 } else { 1.0 };
+        let date_label = |i: usize| {
+            history
+                .get(i)
+                .map(|s| s.date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        };
+        let last = history.len().saturating_sub(1);
+
+        let datasets = vec![Dataset::default()
+            .name("Balance (T)")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&data)];
+        let chart = Chart::new(datasets)
+            .block(Block::default().borders(Borders::ALL))
+            .x_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds([0.0, last.max(1) as f64])
+                    .labels(vec![
+                        Span::raw(date_label(0)),
+                        Span::raw(date_label(last / 2)),
+                        Span::raw(date_label(last)),
+                    ]),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds([0.0, top])
+                    .labels(vec![
+                        Span::raw("0"),
+                        Span::raw(format!("{:.2}", top / 2.0)),
+                        Span::raw(format!("{:.2}", top)),
+                    ]),
+            );
+        f.render_widget(chart, area);
+    }
+}
+
+impl<B: Backend> Component<B> for BalanceHistoryTab {
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect, app_state: &AppState) {
+        let areas = Layout::default()
+            .constraints([Constraint::Length(4), Constraint::Min(10)].as_ref())
+            .split(area);
+
+        let history = balance_history(
+            app_state.get_completed_txs(),
+            Utc::now().naive_utc().date(),
+            self.days(),
+        );
+        self.draw_summary(f, areas[0], &history);
+        self.draw_chart(f, areas[1], &history);
+    }
+
+    fn on_key(&mut self, _app_state: &mut AppState, c: char) {
+        if c == 'd' || c == 'D' {
+            self.period = (self.period + 1) % PERIODS.len();
+        }
+    }
+}
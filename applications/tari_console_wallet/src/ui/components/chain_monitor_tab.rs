@@ -0,0 +1,242 @@
+use crate::ui::{
+    components::{network_congestion::NetworkCongestion, Component},
+    state::AppState,
+    widgets::MultiColumnList,
+    MAX_WIDTH,
+};
+use chrono::Utc;
+use tari_core::blocks::BlockHeader;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_wallet::{connectivity_service::OnlineStatus, transaction_service::storage::models::TransactionStatus};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+/// Blocks are expected every two minutes, a chain tip older than this is highlighted as possibly stalled
+const STALLED_TIP_AGE_SECS: u64 = 10 * 60;
+
+/// Shows the chain tip and sync state of the connected base node, its mempool congestion and the most recent blocks,
+/// so that it is possible to see why a transaction is not being mined without a base node console.
+pub struct ChainMonitorTab {
+    network_congestion: NetworkCongestion,
+}
+
+impl ChainMonitorTab {
+    pub fn new() -> Self {
+        Self {
+            network_congestion: NetworkCongestion::new(),
+        }
+    }
+
+    fn draw_chain_status<B>(&self, f: &mut Frame<B>, area: Rect, app_state: &AppState)
+    where B: Backend {
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            "Base Node Chain",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        ));
+        f.render_widget(block, area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .margin(1)
+            .split(area);
+
+        let base_node_state = app_state.get_base_node_state();
+        let mut chain_lines = Vec::new();
+        match (
+            app_state.get_wallet_connectivity().get_connectivity_status(),
+            base_node_state.chain_metadata.as_ref(),
+        ) {
+            (OnlineStatus::Online, Some(metadata)) => {
+                let (sync_color, sync_text) = if base_node_state.is_synced.unwrap_or_default() {
+                    (Color::Green, "Synced")
+                } else {
+                    (Color::Yellow, "Syncing...")
+                };
+                chain_lines.push(label_line(
+                    "Tip Height:",
+                    Span::raw(format!("{}", metadata.height_of_longest_chain())),
+                ));
+                chain_lines.push(label_line("Tip Hash:", Span::raw(metadata.best_block().to_hex())));
+                chain_lines.push(label_line(
+                    "Sync State:",
+                    Span::styled(sync_text, Style::default().fg(sync_color)),
+                ));
+                if let Some(tip) = app_state.get_recent_block_headers().first() {
+                    let age = tip_age_secs(tip, Utc::now().timestamp() as u64);
+                    let age_color = if age > STALLED_TIP_AGE_SECS {
+                        Color::Red
+                    } else {
+                        Color::Reset
+                    };
+                    chain_lines.push(label_line(
+                        "Last Block:",
+                        Span::styled(format!("{} ago", format_age(age)), Style::default().fg(age_color)),
+                    ));
+                }
+            },
+            (OnlineStatus::Online, None) => {
+                chain_lines.push(label_line("Tip Height:", Span::raw("Waiting for chain metadata...")));
+            },
+            (OnlineStatus::Connecting, _) => {
+                chain_lines.push(label_line("Tip Height:", Span::raw("Connecting...")));
+            },
+            (OnlineStatus::Offline, _) => {
+                chain_lines.push(label_line(
+                    "Tip Height:",
+                    Span::styled("Offline", Style::default().fg(Color::Red)),
+                ));
+            },
+        }
+        let paragraph = Paragraph::new(chain_lines).wrap(Wrap { trim: true });
+        f.render_widget(paragraph, columns[0]);
+
+        let pending_txs = app_state.get_pending_txs();
+        let count_with_status = |status: TransactionStatus| pending_txs.iter().filter(|tx| tx.status == status).count();
+        let wallet_lines = vec![
+            label_line("Pending Transactions:", Span::raw(format!("{}", pending_txs.len()))),
+            label_line(
+                "Waiting in Mempool:",
+                Span::raw(format!("{}", count_with_status(TransactionStatus::Broadcast))),
+            ),
+            label_line(
+                "Mined, Awaiting Confirmations:",
+                Span::raw(format!("{}", count_with_status(TransactionStatus::MinedUnconfirmed))),
+            ),
+            label_line(
+                "Mempool Weight:",
+                Span::raw(match app_state.get_fee_per_gram_stats() {
+                    Some(stats) => format!("{} grams", stats.iter().map(|s| s.total_weight).sum::<u64>()),
+                    None => "Unavailable".to_string(),
+                }),
+            ),
+        ];
+        let paragraph = Paragraph::new(wallet_lines).wrap(Wrap { trim: true });
+        f.render_widget(paragraph, columns[1]);
+    }
+
+    fn draw_recent_blocks<B>(&self, f: &mut Frame<B>, area: Rect, app_state: &AppState)
+    where B: Backend {
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            "Recent Blocks",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        ));
+        f.render_widget(block, area);
+        let list_area = Layout::default()
+            .constraints([Constraint::Min(1)].as_ref())
+            .margin(1)
+            .split(area);
+
+        let headers = app_state.get_recent_block_headers();
+        if headers.is_empty() {
+            let paragraph = Paragraph::new(Spans::from(Span::styled(
+                "Recent blocks are not available from the base node",
+                Style::default().fg(Color::DarkGray),
+            )));
+            f.render_widget(paragraph, list_area[0]);
+            return;
+        }
+
+        let now = Utc::now().timestamp() as u64;
+        let mut column0_items = Vec::new();
+        let mut column1_items = Vec::new();
+        let mut column2_items = Vec::new();
+        let mut column3_items = Vec::new();
+        let mut column4_items = Vec::new();
+        let mut column5_items = Vec::new();
+        for (i, header) in headers.iter().enumerate() {
+            let contents = block_contents(header, headers.get(i + 1));
+            let count_or_dash = |count: Option<u64>| count.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string());
+            column0_items.push(ListItem::new(Span::raw(format!("{}", header.height))));
+            column1_items.push(ListItem::new(Span::raw(header.hash().to_hex())));
+            column2_items.push(ListItem::new(Span::raw(format_age(tip_age_secs(header, now)))));
+            column3_items.push(ListItem::new(Span::raw(format!("{}", header.pow.pow_algo))));
+            column4_items.push(ListItem::new(Span::raw(count_or_dash(contents.map(|c| c.0)))));
+            column5_items.push(ListItem::new(Span::raw(count_or_dash(contents.map(|c| c.1)))));
+        }
+        let column_list = MultiColumnList::new()
+            .heading_style(Style::default().fg(Color::Magenta))
+            .max_width(MAX_WIDTH)
+            .add_column(Some("Height"), Some(10), column0_items)
+            .add_column(Some("Hash"), Some(66), column1_items)
+            .add_column(Some("Age"), Some(10), column2_items)
+            .add_column(Some("PoW"), Some(8), column3_items)
+            .add_column(Some("Kernels"), Some(9), column4_items)
+            .add_column(Some("Outputs"), None, column5_items);
+        column_list.render(f, list_area[0], &mut ListState::default());
+    }
+}
+
+fn label_line<'a>(label: &'a str, value: Span<'a>) -> Spans<'a> {
+    Spans::from(vec![
+        Span::styled(label, Style::default().fg(Color::Magenta)),
+        Span::raw(" "),
+        value,
+    ])
+}
+
+/// Returns the number of kernels and outputs added by the block, derived from the MMR sizes of the block and its
+/// parent. None is returned if the parent header is not known.
+pub fn block_contents(header: &BlockHeader, parent: Option<&BlockHeader>) -> Option<(u64, u64)> {
+    parent.map(|parent| {
+        (
+            header.kernel_mmr_size.saturating_sub(parent.kernel_mmr_size),
+            header.output_mmr_size.saturating_sub(parent.output_mmr_size),
+        )
+    })
+}
+
+fn tip_age_secs(header: &BlockHeader, now: u64) -> u64 {
+    now.saturating_sub(header.timestamp.as_u64())
+}
+
+/// Formats a number of seconds as a short human readable age, e.g. `3m 20s`
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+impl<B: Backend> Component<B> for ChainMonitorTab {
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect, app_state: &AppState) {
+        let areas = Layout::default()
+            .constraints([Constraint::Length(7), Constraint::Length(8), Constraint::Min(8)].as_ref())
+            .split(area);
+
+        self.draw_chain_status(f, areas[0], app_state);
+        self.network_congestion.draw(f, areas[1], app_state);
+        self.draw_recent_blocks(f, areas[2], app_state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_block_contents() {
+        let mut parent = BlockHeader::new(0);
+        parent.kernel_mmr_size = 10;
+        parent.output_mmr_size = 20;
+        let mut header = BlockHeader::from_previous(&parent);
+        header.kernel_mmr_size = 13;
+        header.output_mmr_size = 27;
+
+        assert_eq!(block_contents(&header, Some(&parent)), Some((3, 7)));
+        assert_eq!(block_contents(&header, None), None);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(42), "42s");
+        assert_eq!(format_age(200), "3m 20s");
+        assert_eq!(format_age(3 * 3600 + 5 * 60 + 1), "3h 5m");
+    }
+}
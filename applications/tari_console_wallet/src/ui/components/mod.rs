@@ -21,10 +21,13 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod balance;
+pub mod balance_history_tab;
 pub mod base_node;
+pub mod chain_monitor_tab;
 mod component;
 pub mod log_tab;
 pub(crate) mod menu;
+pub mod network_congestion;
 pub mod network_tab;
 pub mod notification_tab;
 pub mod receive_tab;
@@ -0,0 +1,180 @@
+use crate::ui::{components::Component, state::AppState};
+use tari_core::{mempool::FeePerGramStat, transactions::tari_amount::MicroTari};
+use tari_wallet::types::DEFAULT_FEE_PER_GRAM;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{BarChart, Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+/// Displays the fee per gram distribution of the transactions waiting in the mempool of the connected base node,
+/// grouped into the blocks they are expected to be mined in, and suggests a fee per gram.
+pub struct NetworkCongestion {
+    fee_per_gram: Option<MicroTari>,
+}
+
+impl NetworkCongestion {
+    pub fn new() -> Self {
+        Self { fee_per_gram: None }
+    }
+
+    /// Set the fee per gram of the transaction being composed, used to estimate the block it will be mined in
+    pub fn set_fee_per_gram(&mut self, fee_per_gram: Option<MicroTari>) {
+        self.fee_per_gram = fee_per_gram;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CongestionLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl CongestionLevel {
+    /// The congestion level is determined by the number of blocks needed to clear the mempool
+    pub fn from_stats(stats: &[FeePerGramStat]) -> Self {
+        match stats.len() {
+            0 | 1 => CongestionLevel::Low,
+            2 | 3 => CongestionLevel::Medium,
+            _ => CongestionLevel::High,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            CongestionLevel::Low => Color::Green,
+            CongestionLevel::Medium => Color::Yellow,
+            CongestionLevel::High => Color::Red,
+        }
+    }
+}
+
+/// Suggests a fee per gram that will get a transaction included in the next block. When the mempool fits into a
+/// single block the default fee per gram is sufficient.
+pub fn suggested_fee_per_gram(stats: &[FeePerGramStat]) -> MicroTari {
+    match stats.first() {
+        Some(next_block) if stats.len() > 1 => {
+            std::cmp::max(next_block.min_fee_per_gram + MicroTari::from(1), DEFAULT_FEE_PER_GRAM)
+        },
+        _ => DEFAULT_FEE_PER_GRAM,
+    }
+}
+
+/// Returns the number of blocks until a transaction with the given fee per gram is expected to be mined, or None if
+/// it will not be mined within the blocks covered by the stats
+pub fn expected_blocks_until_mined(stats: &[FeePerGramStat], fee_per_gram: MicroTari) -> Option<u64> {
+    if stats.len() <= 1 {
+        return Some(1);
+    }
+    stats
+        .iter()
+        .find(|stat| fee_per_gram >= stat.min_fee_per_gram)
+        .map(|stat| stat.order + 1)
+}
+
+impl<B: Backend> Component<B> for NetworkCongestion {
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect, app_state: &AppState)
+    where B: Backend {
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            "Network Congestion",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        ));
+        f.render_widget(block, area);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+            .margin(1)
+            .split(area);
+
+        let stats = match app_state.get_fee_per_gram_stats() {
+            Some(stats) => stats,
+            None => {
+                let paragraph = Paragraph::new(Spans::from(Span::styled(
+                    "Mempool stats are not available from the base node",
+                    Style::default().fg(Color::DarkGray),
+                )));
+                f.render_widget(paragraph, columns[0]);
+                return;
+            },
+        };
+
+        let level = CongestionLevel::from_stats(stats);
+        let mut lines = vec![
+            Spans::from(vec![
+                Span::styled("Congestion:", Style::default().fg(Color::Magenta)),
+                Span::raw(" "),
+                Span::styled(format!("{:?}", level), Style::default().fg(level.color())),
+            ]),
+            Spans::from(vec![
+                Span::styled("Suggested Fee-per-gram:", Style::default().fg(Color::Magenta)),
+                Span::raw(" "),
+                Span::raw(format!("{}", suggested_fee_per_gram(stats))),
+            ]),
+        ];
+        if let Some(fee_per_gram) = self.fee_per_gram {
+            let estimate = match expected_blocks_until_mined(stats, fee_per_gram) {
+                Some(blocks) => format!("~{} block(s)", blocks),
+                None => format!("more than {} blocks", stats.len()),
+            };
+            lines.push(Spans::from(vec![
+                Span::styled("Expected to be mined in:", Style::default().fg(Color::Magenta)),
+                Span::raw(" "),
+                Span::raw(estimate),
+            ]));
+        }
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: true });
+        f.render_widget(paragraph, columns[0]);
+
+        let labels = stats
+            .iter()
+            .map(|stat| format!("+{}", stat.order + 1))
+            .collect::<Vec<_>>();
+        let data = stats
+            .iter()
+            .zip(labels.iter())
+            .map(|(stat, label)| (label.as_str(), u64::from(stat.avg_fee_per_gram)))
+            .collect::<Vec<_>>();
+        let bar_chart = BarChart::default()
+            .block(Block::default().title("Avg fee-per-gram (uT) by block"))
+            .data(&data)
+            .bar_width(6)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(level.color()))
+            .value_style(Style::default().fg(Color::Black).bg(level.color()));
+        f.render_widget(bar_chart, columns[1]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn stat(order: u64, min_fee_per_gram: u64) -> FeePerGramStat {
+        FeePerGramStat {
+            order,
+            min_fee_per_gram: min_fee_per_gram.into(),
+            avg_fee_per_gram: min_fee_per_gram.into(),
+            max_fee_per_gram: min_fee_per_gram.into(),
+            total_weight: 0,
+        }
+    }
+
+    #[test]
+    fn test_fee_suggestion() {
+        let stats = vec![stat(0, 100)];
+        assert_eq!(CongestionLevel::from_stats(&stats), CongestionLevel::Low);
+        assert_eq!(suggested_fee_per_gram(&stats), DEFAULT_FEE_PER_GRAM);
+        assert_eq!(expected_blocks_until_mined(&stats, MicroTari::from(1)), Some(1));
+
+        let stats = vec![stat(0, 100), stat(1, 50), stat(2, 20), stat(3, 10)];
+        assert_eq!(CongestionLevel::from_stats(&stats), CongestionLevel::High);
+        assert_eq!(suggested_fee_per_gram(&stats), MicroTari::from(101));
+        assert_eq!(expected_blocks_until_mined(&stats, MicroTari::from(60)), Some(2));
+        assert_eq!(expected_blocks_until_mined(&stats, MicroTari::from(5)), None);
+    }
+}
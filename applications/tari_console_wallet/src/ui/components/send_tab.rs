@@ -1,6 +1,6 @@
 use crate::{
     ui::{
-        components::{balance::Balance, Component, KeyHandled},
+        components::{balance::Balance, network_congestion::NetworkCongestion, Component, KeyHandled},
         state::{AppState, UiTransactionSendStatus},
         widgets::{centered_rect_absolute, draw_dialog, MultiColumnList, WindowedListState},
         MAX_WIDTH,
@@ -22,6 +22,7 @@ use unicode_width::UnicodeWidthStr;
 
 pub struct SendTab {
     balance: Balance,
+    network_congestion: NetworkCongestion,
     send_input_mode: SendInputMode,
     edit_contact_mode: ContactInputMode,
     show_contacts: bool,
@@ -43,6 +44,7 @@ impl SendTab {
     pub fn new() -> Self {
         Self {
             balance: Balance::new(),
+            network_congestion: NetworkCongestion::new(),
             send_input_mode: SendInputMode::None,
             edit_contact_mode: ContactInputMode::None,
             show_contacts: false,
@@ -527,6 +529,7 @@ impl<B: Backend> Component<B> for SendTab {
                 [
                     Constraint::Length(3),
                     Constraint::Length(14),
+                    Constraint::Length(8),
                     Constraint::Min(42),
                     Constraint::Length(1),
                 ]
@@ -536,9 +539,12 @@ impl<B: Backend> Component<B> for SendTab {
 
         self.balance.draw(f, areas[0], app_state);
         self.draw_send_form(f, areas[1], app_state);
+        self.network_congestion
+            .set_fee_per_gram(self.fee_field.parse::<u64>().ok().map(MicroTari::from));
+        self.network_congestion.draw(f, areas[2], app_state);
 
         if self.show_contacts {
-            self.draw_contacts(f, areas[2], app_state);
+            self.draw_contacts(f, areas[3], app_state);
             if self.show_edit_contact {
                 self.draw_edit_contact(f, area, app_state);
             }
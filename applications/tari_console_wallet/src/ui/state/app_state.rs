@@ -46,10 +46,17 @@ use tari_comms::{
     types::CommsPublicKey,
     NodeIdentity,
 };
-use tari_core::transactions::tari_amount::{uT, MicroTari};
+use tari_core::{
+    blocks::BlockHeader,
+    mempool::FeePerGramStat,
+    transactions::tari_amount::{uT, MicroTari},
+};
 use tari_shutdown::ShutdownSignal;
 use tari_wallet::{
-    base_node_service::{handle::BaseNodeEventReceiver, service::BaseNodeState},
+    base_node_service::{
+        handle::{BaseNodeEventReceiver, BaseNodeServiceHandle},
+        service::BaseNodeState,
+    },
     connectivity_service::WalletConnectivityHandle,
     contacts_service::storage::database::Contact,
     output_manager_service::{handle::OutputManagerEventReceiver, service::Balance, TxId, TxoValidationType},
@@ -384,6 +391,16 @@ impl AppState {
         self.wallet_connectivity.clone()
     }
 
+    /// Returns the mempool fee per gram stats of the connected base node, or None if they could not be obtained
+    pub fn get_fee_per_gram_stats(&self) -> Option<&Vec<FeePerGramStat>> {
+        self.cached_data.fee_per_gram_stats.as_ref()
+    }
+
+    /// Returns the most recent block headers of the connected base node, ordered from the chain tip downwards
+    pub fn get_recent_block_headers(&self) -> &[BlockHeader] {
+        &self.cached_data.recent_block_headers
+    }
+
     pub fn get_selected_base_node(&self) -> &Peer {
         &self.cached_data.base_node_selected
     }
@@ -677,6 +694,25 @@ impl AppStateInner {
         Ok(())
     }
 
+    pub async fn refresh_fee_per_gram_stats(&mut self, stats: Option<Vec<FeePerGramStat>>) -> Result<(), UiError> {
+        self.data.fee_per_gram_stats = stats;
+        self.updated = true;
+
+        Ok(())
+    }
+
+    pub async fn refresh_recent_block_headers(&mut self, headers: Vec<BlockHeader>) -> Result<(), UiError> {
+        self.data.recent_block_headers = headers;
+        self.updated = true;
+
+        Ok(())
+    }
+
+    /// Returns the height of the newest block header that has been fetched, if any
+    pub fn get_recent_block_headers_tip(&self) -> Option<u64> {
+        self.data.recent_block_headers.first().map(|header| header.height)
+    }
+
     pub async fn refresh_base_node_peer(&mut self, peer: Peer) -> Result<(), UiError> {
         self.data.base_node_selected = peer;
         self.updated = true;
@@ -708,6 +744,10 @@ impl AppStateInner {
         self.wallet.base_node_service.get_event_stream()
     }
 
+    pub fn get_base_node_service(&self) -> BaseNodeServiceHandle {
+        self.wallet.base_node_service.clone()
+    }
+
     pub async fn set_base_node_peer(&mut self, peer: Peer) -> Result<(), UiError> {
         self.wallet
             .set_base_node_peer(
@@ -862,6 +902,8 @@ struct AppStateData {
     connected_peers: Vec<Peer>,
     balance: Balance,
     base_node_state: BaseNodeState,
+    fee_per_gram_stats: Option<Vec<FeePerGramStat>>,
+    recent_block_headers: Vec<BlockHeader>,
     base_node_selected: Peer,
     base_node_previous: Peer,
     base_node_list: Vec<(String, Peer)>,
@@ -928,6 +970,8 @@ impl AppStateData {
             connected_peers: Vec::new(),
             balance: Balance::zero(),
             base_node_state: BaseNodeState::default(),
+            fee_per_gram_stats: None,
+            recent_block_headers: Vec::new(),
             base_node_selected,
             base_node_previous,
             base_node_list,
@@ -32,6 +32,10 @@ use tari_wallet::{
 use tokio::sync::{broadcast, RwLock};
 
 const LOG_TARGET: &str = "wallet::console_wallet::wallet_event_monitor";
+/// The number of upcoming blocks for which the mempool fee per gram stats are requested from the base node
+const FEE_PER_GRAM_STATS_BLOCK_COUNT: u64 = 5;
+/// The number of recent blocks shown in the chain monitor tab
+const RECENT_BLOCK_HEADERS_COUNT: u64 = 10;
 
 pub struct WalletEventMonitor {
     app_state_inner: Arc<RwLock<AppStateInner>>,
@@ -172,12 +176,22 @@ impl WalletEventMonitor {
                                 trace!(target: LOG_TARGET, "Wallet Event Monitor received base node event {:?}", msg);
                                 match (*msg).clone() {
                                     BaseNodeEvent::BaseNodeStateChanged(state) => {
+                                        let tip_height = state.chain_metadata.as_ref().map(|m| m.height_of_longest_chain());
                                         self.trigger_base_node_state_refresh(state).await;
+                                        self.trigger_fee_per_gram_stats_refresh().await;
+                                        self.trigger_recent_block_headers_refresh(tip_height).await;
                                     }
                                     BaseNodeEvent::BaseNodePeerSet(peer) => {
                                         self.trigger_base_node_peer_refresh(*peer).await;
                                         self.trigger_balance_refresh();
                                     }
+                                    BaseNodeEvent::BaseNodeFailover { peer, reason } => {
+                                        self.add_notification(format!(
+                                            "Failed over to base node {}: {}",
+                                            peer.public_key, reason
+                                        ))
+                                        .await;
+                                    }
                                 }
                             },
                             Err(broadcast::error::RecvError::Lagged(n)) => {
@@ -256,6 +270,49 @@ impl WalletEventMonitor {
         }
     }
 
+    async fn trigger_fee_per_gram_stats_refresh(&mut self) {
+        // Query the base node without holding the app state lock
+        let mut base_node_service = self.app_state_inner.read().await.get_base_node_service();
+        let stats = match base_node_service
+            .get_mempool_fee_per_gram_stats(FEE_PER_GRAM_STATS_BLOCK_COUNT)
+            .await
+        {
+            Ok(stats) => Some(stats),
+            Err(e) => {
+                debug!(target: LOG_TARGET, "Could not obtain mempool fee per gram stats: {}", e);
+                None
+            },
+        };
+
+        let mut inner = self.app_state_inner.write().await;
+        if let Err(e) = inner.refresh_fee_per_gram_stats(stats).await {
+            warn!(target: LOG_TARGET, "Error refresh app_state: {}", e);
+        }
+    }
+
+    async fn trigger_recent_block_headers_refresh(&mut self, tip_height: Option<u64>) {
+        // The headers only need to be fetched again once the chain tip has moved
+        if tip_height.is_none() || self.app_state_inner.read().await.get_recent_block_headers_tip() == tip_height {
+            return;
+        }
+        let mut base_node_service = self.app_state_inner.read().await.get_base_node_service();
+        let headers = match base_node_service
+            .get_recent_block_headers(RECENT_BLOCK_HEADERS_COUNT)
+            .await
+        {
+            Ok(headers) => headers,
+            Err(e) => {
+                debug!(target: LOG_TARGET, "Could not obtain recent block headers: {}", e);
+                return;
+            },
+        };
+
+        let mut inner = self.app_state_inner.write().await;
+        if let Err(e) = inner.refresh_recent_block_headers(headers).await {
+            warn!(target: LOG_TARGET, "Error refresh app_state: {}", e);
+        }
+    }
+
     async fn trigger_base_node_peer_refresh(&mut self, peer: Peer) {
         let mut inner = self.app_state_inner.write().await;
 
@@ -20,8 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::{
-    automation::{command_parser::parse_command, commands::command_runner},
-    grpc::WalletGrpcServer,
+    automation::{
+        command_parser::{parse_command, ParsedCommand},
+        commands::{command_runner, headless_runner},
+    },
+    cli::HeadlessCommand,
+    grpc::{FaucetConfig, FaucetGrpcServer, FaucetTokenValidator, NoTokenRequired, StaticTokens, WalletGrpcServer},
     notifier::Notifier,
     recovery::wallet_recovery,
     ui,
@@ -31,8 +35,9 @@ use crate::{
 use log::*;
 use rand::{rngs::OsRng, seq::SliceRandom};
 use std::{fs, io::Stdout, net::SocketAddr, path::PathBuf};
+use tari_app_grpc::instrumentation::{GrpcLimits, GrpcMetrics, RateLimit};
 use tari_app_utilities::utilities::ExitCodes;
-use tari_common::{ConfigBootstrap, GlobalConfig};
+use tari_common::{configuration::Network, ConfigBootstrap, GlobalConfig};
 use tari_comms::peer_manager::Peer;
 use tari_wallet::WalletSqlite;
 use tokio::runtime::Handle;
@@ -47,6 +52,7 @@ pub enum WalletMode {
     Grpc,
     Script(PathBuf),
     Command(String),
+    Headless(HeadlessCommand),
     RecoveryDaemon,
     RecoveryTui,
     Invalid,
@@ -107,6 +113,18 @@ impl PeerConfig {
         }
     }
 
+    /// Returns the base node peers the wallet can fail over to, in order of priority: the selected base node followed
+    /// by all the other peers from the PeerConfig.
+    pub fn get_failover_peers(&self, base_node_selected: &Peer) -> Vec<Peer> {
+        let mut peers = vec![base_node_selected.clone()];
+        for peer in self.get_all_peers() {
+            if peers.iter().all(|p| p.node_id != peer.node_id) {
+                peers.push(peer);
+            }
+        }
+        peers
+    }
+
     /// Returns all the peers from the PeerConfig.
     /// In order: Custom base node, service peers, peer seeds.
     pub fn get_all_peers(&self) -> Vec<Peer> {
@@ -141,6 +159,23 @@ pub fn command_mode(config: WalletModeConfig, wallet: WalletSqlite, command: Str
     wallet_or_exit(config, wallet)
 }
 
+/// Runs a single command and exits, without any of the interactive wallet output, so that the result can be consumed
+/// by scripts.
+pub fn headless_mode(
+    config: WalletModeConfig,
+    wallet: WalletSqlite,
+    command: HeadlessCommand,
+) -> Result<(), ExitCodes> {
+    let WalletModeConfig {
+        global_config, handle, ..
+    } = config;
+    info!(target: LOG_TARGET, "Starting wallet headless mode");
+    handle.block_on(headless_runner(ParsedCommand::from(command), wallet, global_config))?;
+    info!(target: LOG_TARGET, "Completed wallet headless mode");
+
+    Ok(())
+}
+
 pub fn script_mode(config: WalletModeConfig, wallet: WalletSqlite, path: PathBuf) -> Result<(), ExitCodes> {
     let WalletModeConfig {
         global_config, handle, ..
@@ -214,7 +249,12 @@ pub fn tui_mode(config: WalletModeConfig, mut wallet: WalletSqlite) -> Result<()
         ..
     } = config;
     let grpc = WalletGrpcServer::new(wallet.clone());
-    handle.spawn(run_grpc(grpc, global_config.grpc_console_wallet_address));
+    handle.spawn(run_grpc(
+        grpc,
+        faucet_server(&global_config, &wallet),
+        global_config.grpc_console_wallet_address,
+        grpc_metrics(&global_config),
+    ));
 
     let notifier = Notifier::new(notify_script, handle.clone(), wallet.clone());
 
@@ -287,19 +327,66 @@ pub fn grpc_mode(config: WalletModeConfig, wallet: WalletSqlite) -> Result<(), E
         global_config, handle, ..
     } = config;
     println!("Starting grpc server");
+    let faucet = faucet_server(&global_config, &wallet);
     let grpc = WalletGrpcServer::new(wallet);
     handle
-        .block_on(run_grpc(grpc, global_config.grpc_console_wallet_address))
+        .block_on(run_grpc(
+            grpc,
+            faucet,
+            global_config.grpc_console_wallet_address,
+            grpc_metrics(&global_config),
+        ))
         .map_err(ExitCodes::GrpcError)?;
     println!("Shutting down");
     Ok(())
 }
 
-async fn run_grpc(grpc: WalletGrpcServer, grpc_console_wallet_address: SocketAddr) -> Result<(), String> {
+/// Creates the gRPC call metrics and limits for the wallet gRPC server from the config
+fn grpc_metrics(config: &GlobalConfig) -> GrpcMetrics {
+    GrpcMetrics::new(GrpcLimits {
+        slow_request_threshold: config.console_wallet_grpc_slow_request_threshold,
+        rate_limit: RateLimit::new(
+            config.console_wallet_grpc_rate_limit,
+            config.console_wallet_grpc_rate_limit_burst,
+        ),
+    })
+}
+
+/// Creates the faucet gRPC service if it is enabled. The faucet never runs on mainnet.
+fn faucet_server(config: &GlobalConfig, wallet: &WalletSqlite) -> Option<FaucetGrpcServer> {
+    if !config.console_wallet_faucet_enabled {
+        return None;
+    }
+    if config.network == Network::MainNet {
+        warn!(target: LOG_TARGET, "The faucet is only available on test networks and will not be started");
+        return None;
+    }
+    let token_validator: Box<dyn FaucetTokenValidator> = if config.console_wallet_faucet_tokens.is_empty() {
+        Box::new(NoTokenRequired)
+    } else {
+        Box::new(StaticTokens::new(config.console_wallet_faucet_tokens.clone()))
+    };
+    let faucet_config = FaucetConfig::from_global_config(config);
+    info!(
+        target: LOG_TARGET,
+        "Starting faucet, paying {} per address every {:.0?}", faucet_config.amount, faucet_config.cooldown
+    );
+    Some(FaucetGrpcServer::new(wallet.clone(), faucet_config, token_validator))
+}
+
+async fn run_grpc(
+    grpc: WalletGrpcServer,
+    faucet: Option<FaucetGrpcServer>,
+    grpc_console_wallet_address: SocketAddr,
+    metrics: GrpcMetrics,
+) -> Result<(), String> {
     info!(target: LOG_TARGET, "Starting GRPC on {}", grpc_console_wallet_address);
 
     Server::builder()
-        .add_service(tari_app_grpc::tari_rpc::wallet_server::WalletServer::new(grpc))
+        .add_service(metrics.layer(tari_app_grpc::tari_rpc::wallet_server::WalletServer::new(grpc)))
+        .add_optional_service(
+            faucet.map(|faucet| metrics.layer(tari_app_grpc::tari_rpc::faucet_server::FaucetServer::new(faucet))),
+        )
         .serve(grpc_console_wallet_address)
         .await
         .map_err(|e| format!("GRPC server returned error:{}", e))?;
@@ -0,0 +1,4 @@
+DROP TABLE watched_outputs;
+DROP TABLE outputs;
+DROP TABLE kernels;
+DROP TABLE headers;
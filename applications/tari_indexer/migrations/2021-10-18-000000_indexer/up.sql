@@ -0,0 +1,52 @@
+CREATE TABLE headers (
+    height BIGINT PRIMARY KEY NOT NULL,
+    hash BLOB UNIQUE NOT NULL,
+    prev_hash BLOB NOT NULL,
+    version INTEGER NOT NULL,
+    timestamp BIGINT NOT NULL,
+    nonce BIGINT NOT NULL,
+    pow_algo BIGINT NOT NULL,
+    num_inputs INTEGER NOT NULL,
+    num_outputs INTEGER NOT NULL,
+    num_kernels INTEGER NOT NULL
+);
+
+CREATE TABLE kernels (
+    hash BLOB PRIMARY KEY NOT NULL,
+    block_height BIGINT NOT NULL,
+    features INTEGER NOT NULL,
+    fee BIGINT NOT NULL,
+    lock_height BIGINT NOT NULL,
+    excess BLOB NOT NULL,
+    excess_sig_nonce BLOB NOT NULL,
+    excess_sig BLOB NOT NULL,
+    FOREIGN KEY(block_height) REFERENCES headers(height)
+);
+
+CREATE INDEX kernels_block_height_index ON kernels (block_height);
+CREATE INDEX kernels_excess_index ON kernels (excess);
+CREATE INDEX kernels_excess_sig_nonce_index ON kernels (excess_sig_nonce);
+
+CREATE TABLE outputs (
+    hash BLOB PRIMARY KEY NOT NULL,
+    commitment BLOB NOT NULL,
+    block_height BIGINT NOT NULL,
+    flags INTEGER NOT NULL,
+    maturity BIGINT NOT NULL,
+    script BLOB NOT NULL,
+    spent_height BIGINT NULL,
+    FOREIGN KEY(block_height) REFERENCES headers(height)
+);
+
+CREATE INDEX outputs_block_height_index ON outputs (block_height);
+CREATE INDEX outputs_commitment_index ON outputs (commitment);
+CREATE INDEX outputs_spent_height_index ON outputs (spent_height);
+
+CREATE TABLE watched_outputs (
+    output_hash BLOB NOT NULL,
+    public_key BLOB NOT NULL,
+    PRIMARY KEY (output_hash, public_key),
+    FOREIGN KEY(output_hash) REFERENCES outputs(hash)
+);
+
+CREATE INDEX watched_outputs_public_key_index ON watched_outputs (public_key);
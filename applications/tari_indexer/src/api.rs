@@ -0,0 +1,203 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! Read only HTTP query API over the index. All responses are JSON with binary fields hex encoded.
+//!
+//! - `GET /tip` - the highest indexed header
+//! - `GET /headers?from=<height>&limit=<n>` - headers in ascending height order
+//! - `GET /blocks/<height or hash>` - a header together with its kernels and outputs
+//! - `GET /kernels/<excess or excess signature nonce>` - matching kernels
+//! - `GET /outputs/<commitment>` - outputs with the commitment, including when they were spent
+//! - `GET /watched/<public key>/outputs` - outputs whose script contains one of the `watched_public_keys`
+
+use crate::{
+    error::IndexerError,
+    storage::{HeaderKey, IndexerBackend, IndexerDatabase, KernelKey, OutputKey},
+};
+use hyper::{
+    header,
+    service::{make_service_fn, service_fn},
+    Body,
+    Method,
+    Request,
+    Response,
+    Server,
+    StatusCode,
+};
+use log::*;
+use serde::Serialize;
+use serde_json::json;
+use std::{convert::Infallible, net::SocketAddr};
+
+const LOG_TARGET: &str = "tari_indexer::api";
+
+const DEFAULT_HEADERS_LIMIT: u64 = 20;
+const MAX_HEADERS_LIMIT: u64 = 100;
+
+pub async fn serve<T: IndexerBackend>(addr: SocketAddr, db: IndexerDatabase<T>) -> Result<(), hyper::Error> {
+    let service = make_service_fn(move |_| {
+        let db = db.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let db = db.clone();
+                async move { Ok::<_, Infallible>(handle(db, request).await) }
+            }))
+        }
+    });
+    info!(target: LOG_TARGET, "Query API listening on {}", addr);
+    Server::try_bind(&addr)?.serve(service).await
+}
+
+async fn handle<T: IndexerBackend>(db: IndexerDatabase<T>, request: Request<Body>) -> Response<Body> {
+    if request.method() != Method::GET {
+        return error_response(StatusCode::METHOD_NOT_ALLOWED, "Only GET requests are supported");
+    }
+    let segments = request
+        .uri()
+        .path()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    let result = match segments.as_slice() {
+        ["tip"] => db.fetch_tip().await.and_then(|tip| json_response(&tip)),
+        ["headers"] => {
+            let (from, limit) = headers_query(request.uri().query());
+            db.fetch_headers(from, limit)
+                .await
+                .and_then(|headers| json_response(&headers))
+        },
+        ["blocks", key] => match parse_header_key(key) {
+            Ok(key) => get_block(&db, key).await,
+            Err(err) => Err(err),
+        },
+        ["kernels", excess] => match parse_hex(excess) {
+            Ok(excess) => db
+                .fetch_kernels(KernelKey::Excess(excess))
+                .await
+                .and_then(|kernels| json_response(&kernels)),
+            Err(err) => Err(err),
+        },
+        ["outputs", commitment] => match parse_hex(commitment) {
+            Ok(commitment) => db
+                .fetch_outputs(OutputKey::Commitment(commitment))
+                .await
+                .and_then(|outputs| json_response(&outputs)),
+            Err(err) => Err(err),
+        },
+        ["watched", public_key, "outputs"] => match parse_hex(public_key) {
+            Ok(public_key) => db
+                .fetch_outputs(OutputKey::WatchedPublicKey(public_key))
+                .await
+                .and_then(|outputs| json_response(&outputs)),
+            Err(err) => Err(err),
+        },
+        _ => return error_response(StatusCode::NOT_FOUND, "Not found"),
+    };
+    match result {
+        Ok(response) => response,
+        Err(IndexerError::InvalidHex(value)) => {
+            error_response(StatusCode::BAD_REQUEST, &format!("Invalid hex '{}'", value))
+        },
+        Err(err) => {
+            error!(target: LOG_TARGET, "Failed to handle '{}': {}", request.uri(), err);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, &err.to_string())
+        },
+    }
+}
+
+async fn get_block<T: IndexerBackend>(db: &IndexerDatabase<T>, key: HeaderKey) -> Result<Response<Body>, IndexerError> {
+    let header = match db.fetch_header(key).await? {
+        Some(header) => header,
+        None => return Ok(error_response(StatusCode::NOT_FOUND, "Block not found")),
+    };
+    let kernels = db.fetch_kernels(KernelKey::BlockHeight(header.height)).await?;
+    let outputs = db.fetch_outputs(OutputKey::BlockHeight(header.height)).await?;
+    json_response(&json!({
+        "header": header,
+        "kernels": kernels,
+        "outputs": outputs,
+    }))
+}
+
+/// Heights are decimal, anything else is treated as a hex block hash
+fn parse_header_key(key: &str) -> Result<HeaderKey, IndexerError> {
+    match key.parse::<u64>() {
+        Ok(height) => Ok(HeaderKey::Height(height)),
+        Err(_) => parse_hex(key).map(HeaderKey::Hash),
+    }
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, IndexerError> {
+    hex::decode(value).map_err(|_| IndexerError::InvalidHex(value.to_string()))
+}
+
+fn headers_query(query: Option<&str>) -> (u64, u64) {
+    let mut from = 0;
+    let mut limit = DEFAULT_HEADERS_LIMIT;
+    for (name, value) in query.unwrap_or_default().split('&').filter_map(|p| {
+        let mut parts = p.splitn(2, '=');
+        Some((parts.next()?, parts.next()?))
+    }) {
+        match name {
+            "from" => from = value.parse().unwrap_or(from),
+            "limit" => limit = value.parse().unwrap_or(limit),
+            _ => {},
+        }
+    }
+    (from, limit.min(MAX_HEADERS_LIMIT))
+}
+
+fn json_response<S: Serialize>(body: &S) -> Result<Response<Body>, IndexerError> {
+    let body = serde_json::to_vec(body)?;
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/json")
+        .status(StatusCode::OK)
+        .body(body.into())
+        .map_err(Into::into)
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/json")
+        .status(status)
+        .body(json!({ "error": message }).to_string().into())
+        .expect("static response parts are valid")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_parses_query_parameters() {
+        assert_eq!(headers_query(None), (0, DEFAULT_HEADERS_LIMIT));
+        assert_eq!(headers_query(Some("from=10&limit=5")), (10, 5));
+        assert_eq!(headers_query(Some("limit=1000&other")), (0, MAX_HEADERS_LIMIT));
+    }
+
+    #[test]
+    fn it_parses_header_keys() {
+        assert_eq!(parse_header_key("42").unwrap(), HeaderKey::Height(42));
+        assert_eq!(parse_header_key("0a0b").unwrap(), HeaderKey::Hash(vec![10, 11]));
+        assert!(parse_header_key("xyz").is_err());
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! Indexer specific configuration
+//!
+//! The indexer derives all configuration management from the [tari_common] crate, extended with a few specific
+//! options:
+//! - base_node_grpc_address - is IPv4/IPv6 address including port number, by which the Tari Base Node can be found
+//! - database_path - path to the sqlite database holding the index, defaults to `indexer/indexer.sqlite` in the data
+//! directory
+//! - http_listener_address - the address the query API listens on
+//! - sync_interval_sec - how often to poll the base node for new blocks
+//! - sync_batch_size - the number of blocks requested from the base node at a time
+//! - watched_public_keys - hex encoded public keys of interest, outputs with a script containing one of these keys
+//! are recorded so they can be queried by key
+//! All indexer options are configured under the `[indexer]` section of Tari's `config.toml`.
+
+use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+use tari_common::{GlobalConfig, NetworkConfigPath};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IndexerConfig {
+    pub base_node_grpc_address: Option<String>,
+    pub database_path: Option<PathBuf>,
+    pub http_listener_address: SocketAddr,
+    pub sync_interval_sec: u64,
+    pub sync_batch_size: u64,
+    pub watched_public_keys: Vec<String>,
+}
+
+impl NetworkConfigPath for IndexerConfig {
+    fn main_key_prefix() -> &'static str {
+        "indexer"
+    }
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            base_node_grpc_address: None,
+            database_path: None,
+            http_listener_address: ([127, 0, 0, 1], 18150).into(),
+            sync_interval_sec: 10,
+            sync_batch_size: 100,
+            watched_public_keys: vec![],
+        }
+    }
+}
+
+impl IndexerConfig {
+    pub fn base_node_addr(&self, global: &GlobalConfig) -> String {
+        self.base_node_grpc_address
+            .clone()
+            .unwrap_or_else(|| format!("http://{}", global.grpc_base_node_address))
+    }
+
+    pub fn database_path(&self, global: &GlobalConfig) -> PathBuf {
+        self.database_path
+            .clone()
+            .unwrap_or_else(|| global.data_dir.join("indexer").join("indexer.sqlite"))
+    }
+
+    pub fn sync_interval(&self) -> Duration {
+        Duration::from_secs(self.sync_interval_sec)
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+use diesel::{result::Error as DieselError, ConnectionError};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error("GRPC error: {0}")]
+    GrpcStatus(#[from] tonic::Status),
+    #[error("Connection error: {0}")]
+    GrpcConnection(#[from] tonic::transport::Error),
+    #[error("Database error: {0}")]
+    Database(#[from] DieselError),
+    #[error("Database connection error: {0}")]
+    DatabaseConnection(#[from] ConnectionError),
+    #[error("Database migration failed: {0}")]
+    DatabaseMigration(String),
+    #[error("The database path is not valid unicode")]
+    InvalidUnicodePath,
+    #[error("Blocking task spawn error: {0}")]
+    BlockingTaskSpawn(String),
+    #[error("Expected non empty {0}")]
+    EmptyObject(String),
+    #[error("Invalid hex '{0}'")]
+    InvalidHex(String),
+    #[error("Base node did not return the block at height {0}")]
+    MissingBlock(u64),
+    #[error("HTTP error: {0}")]
+    Http(#[from] hyper::http::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub fn err_empty(name: &str) -> IndexerError {
+    IndexerError::EmptyObject(name.to_string())
+}
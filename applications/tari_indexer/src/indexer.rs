@@ -0,0 +1,309 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! Keeps the index in step with the base node. Blocks are requested in batches from the last indexed height up to
+//! the tip of the base node. Before every sync the indexed tip is compared against the base node and blocks that are
+//! no longer on the best chain are rewound, so the index follows reorgs.
+
+use crate::{
+    config::IndexerConfig,
+    error::{err_empty, IndexerError},
+    storage::{IndexedBlock, IndexedHeader, IndexedKernel, IndexedOutput, IndexerBackend, IndexerDatabase},
+};
+use log::*;
+use std::{cmp, time::Duration};
+use tari_app_grpc::tari_rpc as grpc;
+use tokio::time::sleep;
+use tonic::transport::Channel;
+
+const LOG_TARGET: &str = "tari_indexer::indexer";
+
+pub struct BlockchainIndexer<T> {
+    db: IndexerDatabase<T>,
+    base_node: grpc::base_node_client::BaseNodeClient<Channel>,
+    watched_public_keys: Vec<Vec<u8>>,
+    sync_interval: Duration,
+    batch_size: u64,
+}
+
+impl<T> BlockchainIndexer<T>
+where T: IndexerBackend
+{
+    pub fn new(
+        db: IndexerDatabase<T>,
+        base_node: grpc::base_node_client::BaseNodeClient<Channel>,
+        config: &IndexerConfig,
+    ) -> Result<Self, IndexerError> {
+        let watched_public_keys = config
+            .watched_public_keys
+            .iter()
+            .map(|key| hex::decode(key.trim()).map_err(|_| IndexerError::InvalidHex(key.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            db,
+            base_node,
+            watched_public_keys,
+            sync_interval: config.sync_interval(),
+            batch_size: cmp::max(config.sync_batch_size, 1),
+        })
+    }
+
+    pub async fn run(mut self) {
+        loop {
+            match self.sync().await {
+                Ok(0) => {},
+                Ok(num_blocks) => info!(target: LOG_TARGET, "Indexed {} new block(s)", num_blocks),
+                Err(err) => warn!(target: LOG_TARGET, "Failed to sync with the base node: {}", err),
+            }
+            sleep(self.sync_interval).await;
+        }
+    }
+
+    /// Index the blocks between the indexed tip and the base node tip, returning the number of blocks indexed
+    async fn sync(&mut self) -> Result<u64, IndexerError> {
+        let tip_info = self
+            .base_node
+            .get_tip_info(grpc::Empty {})
+            .await?
+            .into_inner()
+            .metadata
+            .ok_or_else(|| err_empty("metadata"))?;
+        let tip_height = tip_info.height_of_longest_chain;
+
+        self.rewind_reorged_blocks().await?;
+        let tip = self.db.fetch_tip().await?;
+        let mut next_height = tip.as_ref().map(|h| h.height + 1).unwrap_or(0);
+        let mut tip_hash = tip.map(|h| h.hash);
+        let mut num_blocks = 0;
+        while next_height <= tip_height {
+            let end_height = cmp::min(next_height + self.batch_size - 1, tip_height);
+            for block in self.fetch_blocks(next_height, end_height).await? {
+                let block = indexed_block(block, &self.watched_public_keys)?;
+                if tip_hash.as_ref().map_or(false, |hash| *hash != block.header.prev_hash) {
+                    // The base node reorged while we were syncing, the next sync will rewind
+                    debug!(
+                        target: LOG_TARGET,
+                        "Block {} does not build on the indexed tip, stopping sync", block.header.height
+                    );
+                    return Ok(num_blocks);
+                }
+                tip_hash = Some(block.header.hash.clone());
+                self.db.insert_block(block).await?;
+                num_blocks += 1;
+            }
+            next_height = end_height + 1;
+        }
+        Ok(num_blocks)
+    }
+
+    /// Walk the indexed tip back until it matches the block the base node has at the same height
+    async fn rewind_reorged_blocks(&mut self) -> Result<(), IndexerError> {
+        while let Some(tip) = self.db.fetch_tip().await? {
+            let header = self.fetch_header(tip.height).await?;
+            if header.map(|h| h.hash) == Some(tip.hash) {
+                return Ok(());
+            }
+            warn!(
+                target: LOG_TARGET,
+                "Indexed block {} is no longer on the best chain, rewinding", tip.height
+            );
+            self.db.rewind_to_height(tip.height).await?;
+        }
+        Ok(())
+    }
+
+    async fn fetch_header(&mut self, height: u64) -> Result<Option<grpc::BlockHeader>, IndexerError> {
+        let mut stream = self
+            .base_node
+            .list_headers(grpc::ListHeadersRequest {
+                from_height: height,
+                num_headers: 1,
+                sorting: grpc::Sorting::Asc.into(),
+            })
+            .await?
+            .into_inner();
+        let header = stream.message().await?;
+        Ok(header.filter(|h| h.height == height))
+    }
+
+    async fn fetch_blocks(&mut self, from_height: u64, to_height: u64) -> Result<Vec<grpc::Block>, IndexerError> {
+        let mut stream = self
+            .base_node
+            .get_blocks(grpc::GetBlocksRequest {
+                heights: (from_height..=to_height).collect(),
+                // Range proofs are not indexed
+                field_mask: Some(grpc::BlockFieldMask {
+                    skip_range_proofs: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await?
+            .into_inner();
+        let mut blocks = Vec::new();
+        while let Some(historical_block) = stream.message().await? {
+            blocks.push(historical_block.block.ok_or_else(|| err_empty("block"))?);
+        }
+        blocks.sort_by_key(|b| b.header.as_ref().map(|h| h.height));
+        // The base node stops streaming early if it cannot provide a block, only index a contiguous range
+        for (expected_height, block) in (from_height..=to_height).zip(blocks.iter()) {
+            if block.header.as_ref().map(|h| h.height) != Some(expected_height) {
+                return Err(IndexerError::MissingBlock(expected_height));
+            }
+        }
+        if blocks.is_empty() {
+            return Err(IndexerError::MissingBlock(from_height));
+        }
+        Ok(blocks)
+    }
+}
+
+fn indexed_block(block: grpc::Block, watched_public_keys: &[Vec<u8>]) -> Result<IndexedBlock, IndexerError> {
+    let header = block.header.ok_or_else(|| err_empty("header"))?;
+    let body = block.body.unwrap_or_default();
+    let height = header.height;
+
+    let kernels = body
+        .kernels
+        .into_iter()
+        .map(|k| {
+            let excess_sig = k.excess_sig.ok_or_else(|| err_empty("excess_sig"))?;
+            Ok(IndexedKernel {
+                hash: k.hash,
+                block_height: height,
+                features: k.features,
+                fee: k.fee,
+                lock_height: k.lock_height,
+                excess: k.excess,
+                excess_sig_nonce: excess_sig.public_nonce,
+                excess_sig: excess_sig.signature,
+            })
+        })
+        .collect::<Result<Vec<_>, IndexerError>>()?;
+
+    let mut watched_outputs = Vec::new();
+    let outputs = body
+        .outputs
+        .into_iter()
+        .map(|o| {
+            for key in watched_keys_in_script(&o.script, watched_public_keys) {
+                watched_outputs.push((o.hash.clone(), key.clone()));
+            }
+            let features = o.features.unwrap_or_default();
+            IndexedOutput {
+                hash: o.hash,
+                commitment: o.commitment,
+                block_height: height,
+                flags: features.flags,
+                maturity: features.maturity,
+                script: o.script,
+                spent_height: None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let header = IndexedHeader {
+        height,
+        hash: header.hash,
+        prev_hash: header.prev_hash,
+        version: header.version,
+        timestamp: header.timestamp.map(|t| t.seconds as u64).unwrap_or_default(),
+        nonce: header.nonce,
+        pow_algo: header.pow.map(|p| p.pow_algo).unwrap_or_default(),
+        num_inputs: body.inputs.len() as u32,
+        num_outputs: outputs.len() as u32,
+        num_kernels: kernels.len() as u32,
+    };
+
+    Ok(IndexedBlock {
+        header,
+        kernels,
+        outputs,
+        spent_commitments: body.inputs.into_iter().map(|i| i.commitment).collect(),
+        watched_outputs,
+    })
+}
+
+/// The watched public keys that appear in the serialized script, e.g. the `PushPubKey` of a one-sided payment
+fn watched_keys_in_script<'a>(
+    script: &'a [u8],
+    watched_public_keys: &'a [Vec<u8>],
+) -> impl Iterator<Item = &'a Vec<u8>> {
+    watched_public_keys
+        .iter()
+        .filter(move |key| !key.is_empty() && script.windows(key.len()).any(|w| w == key.as_slice()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_finds_watched_keys_in_scripts() {
+        let watched = vec![vec![1u8; 32], vec![2u8; 32]];
+        let mut script = vec![0x7e];
+        script.extend_from_slice(&[2u8; 32]);
+        let found = watched_keys_in_script(&script, &watched).collect::<Vec<_>>();
+        assert_eq!(found, vec![&vec![2u8; 32]]);
+        assert_eq!(watched_keys_in_script(&[0x73], &watched).count(), 0);
+    }
+
+    #[test]
+    fn it_converts_grpc_blocks() {
+        let block = grpc::Block {
+            header: Some(grpc::BlockHeader {
+                hash: vec![1; 32],
+                height: 5,
+                prev_hash: vec![0; 32],
+                ..Default::default()
+            }),
+            body: Some(grpc::AggregateBody {
+                inputs: vec![grpc::TransactionInput {
+                    commitment: vec![3; 32],
+                    ..Default::default()
+                }],
+                outputs: vec![grpc::TransactionOutput {
+                    hash: vec![4; 32],
+                    commitment: vec![5; 32],
+                    script: vec![6; 33],
+                    ..Default::default()
+                }],
+                kernels: vec![grpc::TransactionKernel {
+                    hash: vec![7; 32],
+                    fee: 100,
+                    excess_sig: Some(grpc::Signature {
+                        public_nonce: vec![8; 32],
+                        signature: vec![9; 32],
+                    }),
+                    ..Default::default()
+                }],
+            }),
+        };
+        let indexed = indexed_block(block, &[vec![6; 32]]).unwrap();
+        assert_eq!(indexed.header.height, 5);
+        assert_eq!(indexed.header.num_inputs, 1);
+        assert_eq!(indexed.spent_commitments, vec![vec![3; 32]]);
+        assert_eq!(indexed.kernels[0].excess_sig_nonce, vec![8; 32]);
+        assert_eq!(indexed.outputs[0].block_height, 5);
+        assert_eq!(indexed.watched_outputs, vec![(vec![4; 32], vec![6; 32])]);
+    }
+}
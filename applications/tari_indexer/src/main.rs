@@ -0,0 +1,78 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+#![cfg_attr(not(debug_assertions), deny(unused_variables))]
+#![cfg_attr(not(debug_assertions), deny(unused_imports))]
+#![cfg_attr(not(debug_assertions), deny(dead_code))]
+#![cfg_attr(not(debug_assertions), deny(unused_extern_crates))]
+#![deny(unused_must_use)]
+#![deny(unreachable_patterns)]
+#![deny(unknown_lints)]
+
+#[macro_use]
+extern crate diesel;
+#[macro_use]
+extern crate diesel_migrations;
+
+mod api;
+mod config;
+mod error;
+mod indexer;
+mod storage;
+
+use crate::{
+    config::IndexerConfig,
+    indexer::BlockchainIndexer,
+    storage::{sqlite_db::SqliteIndexerBackend, IndexerDatabase},
+};
+use log::*;
+use std::fs;
+use tari_app_grpc::tari_rpc as grpc;
+use tari_app_utilities::initialization::init_configuration;
+use tari_common::{configuration::bootstrap::ApplicationType, DefaultConfigLoader};
+
+const LOG_TARGET: &str = "tari_indexer";
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let (bootstrap, global, cfg) = init_configuration(ApplicationType::Indexer)?;
+    let config = <IndexerConfig as DefaultConfigLoader>::load_from(&cfg)?;
+    debug!(target: LOG_TARGET, "{:?}", bootstrap);
+    debug!(target: LOG_TARGET, "{:?}", config);
+
+    let db_path = config.database_path(&global);
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    println!("Opening index database at {}", db_path.display());
+    let db = IndexerDatabase::new(SqliteIndexerBackend::connect(&db_path)?);
+
+    let base_node_addr = config.base_node_addr(&global);
+    println!("Connecting to base node at {}", base_node_addr);
+    let base_node = grpc::base_node_client::BaseNodeClient::connect(base_node_addr).await?;
+    let indexer = BlockchainIndexer::new(db.clone(), base_node, &config)?;
+    tokio::spawn(indexer.run());
+
+    println!("Query API listening on {}", config.http_listener_address);
+    api::serve(config.http_listener_address, db).await?;
+    Ok(())
+}
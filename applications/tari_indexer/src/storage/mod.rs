@@ -0,0 +1,184 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! Storage of the index. The [IndexerBackend] trait is implemented by each supported database, currently only
+//! sqlite, and [IndexerDatabase] runs the blocking backend calls off the async runtime.
+
+mod schema;
+pub mod sqlite_db;
+
+use crate::error::IndexerError;
+use serde::{Serialize, Serializer};
+use std::sync::Arc;
+use tokio::task;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexedHeader {
+    pub height: u64,
+    #[serde(serialize_with = "as_hex")]
+    pub hash: Vec<u8>,
+    #[serde(serialize_with = "as_hex")]
+    pub prev_hash: Vec<u8>,
+    pub version: u32,
+    pub timestamp: u64,
+    pub nonce: u64,
+    pub pow_algo: u64,
+    pub num_inputs: u32,
+    pub num_outputs: u32,
+    pub num_kernels: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexedKernel {
+    #[serde(serialize_with = "as_hex")]
+    pub hash: Vec<u8>,
+    pub block_height: u64,
+    pub features: u32,
+    pub fee: u64,
+    pub lock_height: u64,
+    #[serde(serialize_with = "as_hex")]
+    pub excess: Vec<u8>,
+    #[serde(serialize_with = "as_hex")]
+    pub excess_sig_nonce: Vec<u8>,
+    #[serde(serialize_with = "as_hex")]
+    pub excess_sig: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexedOutput {
+    #[serde(serialize_with = "as_hex")]
+    pub hash: Vec<u8>,
+    #[serde(serialize_with = "as_hex")]
+    pub commitment: Vec<u8>,
+    pub block_height: u64,
+    pub flags: u32,
+    pub maturity: u64,
+    #[serde(serialize_with = "as_hex")]
+    pub script: Vec<u8>,
+    pub spent_height: Option<u64>,
+}
+
+/// Everything the index records about a single block
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedBlock {
+    pub header: IndexedHeader,
+    pub kernels: Vec<IndexedKernel>,
+    pub outputs: Vec<IndexedOutput>,
+    /// Commitments of the outputs spent by the inputs of the block
+    pub spent_commitments: Vec<Vec<u8>>,
+    /// Output hashes paired with the watched public key found in their script
+    pub watched_outputs: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderKey {
+    Height(u64),
+    Hash(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KernelKey {
+    BlockHeight(u64),
+    /// Matches the kernel excess or the public nonce of the excess signature
+    Excess(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputKey {
+    BlockHeight(u64),
+    Commitment(Vec<u8>),
+    WatchedPublicKey(Vec<u8>),
+}
+
+pub trait IndexerBackend: Send + Sync + 'static {
+    /// The highest indexed header, or None if nothing has been indexed yet
+    fn fetch_tip(&self) -> Result<Option<IndexedHeader>, IndexerError>;
+    /// Atomically add a block on top of the current tip, marking the outputs spent by its inputs
+    fn insert_block(&self, block: IndexedBlock) -> Result<(), IndexerError>;
+    /// Remove every block at or above `height` and restore the outputs they spent
+    fn rewind_to_height(&self, height: u64) -> Result<(), IndexerError>;
+    fn fetch_header(&self, key: HeaderKey) -> Result<Option<IndexedHeader>, IndexerError>;
+    fn fetch_headers(&self, from_height: u64, limit: u64) -> Result<Vec<IndexedHeader>, IndexerError>;
+    fn fetch_kernels(&self, key: KernelKey) -> Result<Vec<IndexedKernel>, IndexerError>;
+    fn fetch_outputs(&self, key: OutputKey) -> Result<Vec<IndexedOutput>, IndexerError>;
+}
+
+pub struct IndexerDatabase<T> {
+    db: Arc<T>,
+}
+
+impl<T> Clone for IndexerDatabase<T> {
+    fn clone(&self) -> Self {
+        Self { db: self.db.clone() }
+    }
+}
+
+impl<T> IndexerDatabase<T>
+where T: IndexerBackend
+{
+    pub fn new(db: T) -> Self {
+        Self { db: Arc::new(db) }
+    }
+
+    pub async fn fetch_tip(&self) -> Result<Option<IndexedHeader>, IndexerError> {
+        self.run(|db| db.fetch_tip()).await
+    }
+
+    pub async fn insert_block(&self, block: IndexedBlock) -> Result<(), IndexerError> {
+        self.run(move |db| db.insert_block(block)).await
+    }
+
+    pub async fn rewind_to_height(&self, height: u64) -> Result<(), IndexerError> {
+        self.run(move |db| db.rewind_to_height(height)).await
+    }
+
+    pub async fn fetch_header(&self, key: HeaderKey) -> Result<Option<IndexedHeader>, IndexerError> {
+        self.run(move |db| db.fetch_header(key)).await
+    }
+
+    pub async fn fetch_headers(&self, from_height: u64, limit: u64) -> Result<Vec<IndexedHeader>, IndexerError> {
+        self.run(move |db| db.fetch_headers(from_height, limit)).await
+    }
+
+    pub async fn fetch_kernels(&self, key: KernelKey) -> Result<Vec<IndexedKernel>, IndexerError> {
+        self.run(move |db| db.fetch_kernels(key)).await
+    }
+
+    pub async fn fetch_outputs(&self, key: OutputKey) -> Result<Vec<IndexedOutput>, IndexerError> {
+        self.run(move |db| db.fetch_outputs(key)).await
+    }
+
+    async fn run<F, R>(&self, f: F) -> Result<R, IndexerError>
+    where
+        F: FnOnce(&T) -> Result<R, IndexerError> + Send + 'static,
+        R: Send + 'static,
+    {
+        let db = self.db.clone();
+        task::spawn_blocking(move || f(&db))
+            .await
+            .map_err(|err| IndexerError::BlockingTaskSpawn(err.to_string()))?
+    }
+}
+
+fn as_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(bytes))
+}
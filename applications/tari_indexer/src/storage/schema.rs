@@ -0,0 +1,52 @@
+table! {
+    headers (height) {
+        height -> BigInt,
+        hash -> Binary,
+        prev_hash -> Binary,
+        version -> Integer,
+        timestamp -> BigInt,
+        nonce -> BigInt,
+        pow_algo -> BigInt,
+        num_inputs -> Integer,
+        num_outputs -> Integer,
+        num_kernels -> Integer,
+    }
+}
+
+table! {
+    kernels (hash) {
+        hash -> Binary,
+        block_height -> BigInt,
+        features -> Integer,
+        fee -> BigInt,
+        lock_height -> BigInt,
+        excess -> Binary,
+        excess_sig_nonce -> Binary,
+        excess_sig -> Binary,
+    }
+}
+
+table! {
+    outputs (hash) {
+        hash -> Binary,
+        commitment -> Binary,
+        block_height -> BigInt,
+        flags -> Integer,
+        maturity -> BigInt,
+        script -> Binary,
+        spent_height -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    watched_outputs (output_hash, public_key) {
+        output_hash -> Binary,
+        public_key -> Binary,
+    }
+}
+
+joinable!(kernels -> headers (block_height));
+joinable!(outputs -> headers (block_height));
+joinable!(watched_outputs -> outputs (output_hash));
+
+allow_tables_to_appear_in_same_query!(headers, kernels, outputs, watched_outputs,);
@@ -0,0 +1,399 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+use crate::{
+    error::IndexerError,
+    storage::{
+        schema::{headers, kernels, outputs, watched_outputs},
+        HeaderKey,
+        IndexedBlock,
+        IndexedHeader,
+        IndexedKernel,
+        IndexedOutput,
+        IndexerBackend,
+        KernelKey,
+        OutputKey,
+    },
+};
+use diesel::{prelude::*, SqliteConnection};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+embed_migrations!("./migrations");
+
+/// Sqlite implementation of the [IndexerBackend]
+#[derive(Clone)]
+pub struct SqliteIndexerBackend {
+    conn: Arc<Mutex<SqliteConnection>>,
+}
+
+impl SqliteIndexerBackend {
+    /// Open (or create) the database at the given path and bring its schema up to date
+    pub fn connect<P: AsRef<Path>>(db_path: P) -> Result<Self, IndexerError> {
+        let path_str = db_path.as_ref().to_str().ok_or(IndexerError::InvalidUnicodePath)?;
+        let conn = SqliteConnection::establish(path_str)?;
+        conn.execute("PRAGMA foreign_keys = ON; PRAGMA busy_timeout = 60000;")?;
+        embedded_migrations::run(&conn).map_err(|err| IndexerError::DatabaseMigration(err.to_string()))?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    fn with_conn<F, R>(&self, f: F) -> Result<R, IndexerError>
+    where F: FnOnce(&SqliteConnection) -> Result<R, IndexerError> {
+        let conn = self.conn.lock().expect("the sqlite connection mutex was poisoned");
+        f(&conn)
+    }
+}
+
+impl IndexerBackend for SqliteIndexerBackend {
+    fn fetch_tip(&self) -> Result<Option<IndexedHeader>, IndexerError> {
+        self.with_conn(|conn| {
+            let header = headers::table
+                .order(headers::height.desc())
+                .first::<HeaderSql>(conn)
+                .optional()?;
+            Ok(header.map(Into::into))
+        })
+    }
+
+    fn insert_block(&self, block: IndexedBlock) -> Result<(), IndexerError> {
+        self.with_conn(|conn| {
+            conn.transaction::<_, IndexerError, _>(|| {
+                let height = block.header.height as i64;
+                diesel::insert_into(headers::table)
+                    .values(HeaderSql::from(block.header))
+                    .execute(conn)?;
+                let kernels = block.kernels.into_iter().map(KernelSql::from).collect::<Vec<_>>();
+                diesel::insert_into(kernels::table).values(&kernels).execute(conn)?;
+                let outputs = block.outputs.into_iter().map(OutputSql::from).collect::<Vec<_>>();
+                diesel::insert_into(outputs::table).values(&outputs).execute(conn)?;
+                for commitment in block.spent_commitments {
+                    diesel::update(
+                        outputs::table
+                            .filter(outputs::commitment.eq(commitment))
+                            .filter(outputs::spent_height.is_null()),
+                    )
+                    .set(outputs::spent_height.eq(Some(height)))
+                    .execute(conn)?;
+                }
+                let watched = block
+                    .watched_outputs
+                    .into_iter()
+                    .map(|(output_hash, public_key)| WatchedOutputSql {
+                        output_hash,
+                        public_key,
+                    })
+                    .collect::<Vec<_>>();
+                diesel::insert_into(watched_outputs::table)
+                    .values(&watched)
+                    .execute(conn)?;
+                Ok(())
+            })
+        })
+    }
+
+    fn rewind_to_height(&self, height: u64) -> Result<(), IndexerError> {
+        let height = height as i64;
+        self.with_conn(|conn| {
+            conn.transaction::<_, IndexerError, _>(|| {
+                diesel::update(outputs::table.filter(outputs::spent_height.ge(height)))
+                    .set(outputs::spent_height.eq(None::<i64>))
+                    .execute(conn)?;
+                let removed_outputs = outputs::table
+                    .select(outputs::hash)
+                    .filter(outputs::block_height.ge(height));
+                diesel::delete(watched_outputs::table.filter(watched_outputs::output_hash.eq_any(removed_outputs)))
+                    .execute(conn)?;
+                diesel::delete(outputs::table.filter(outputs::block_height.ge(height))).execute(conn)?;
+                diesel::delete(kernels::table.filter(kernels::block_height.ge(height))).execute(conn)?;
+                diesel::delete(headers::table.filter(headers::height.ge(height))).execute(conn)?;
+                Ok(())
+            })
+        })
+    }
+
+    fn fetch_header(&self, key: HeaderKey) -> Result<Option<IndexedHeader>, IndexerError> {
+        self.with_conn(|conn| {
+            let query = match key {
+                HeaderKey::Height(height) => headers::table.filter(headers::height.eq(height as i64)).into_boxed(),
+                HeaderKey::Hash(hash) => headers::table.filter(headers::hash.eq(hash)).into_boxed(),
+            };
+            let header = query.first::<HeaderSql>(conn).optional()?;
+            Ok(header.map(Into::into))
+        })
+    }
+
+    fn fetch_headers(&self, from_height: u64, limit: u64) -> Result<Vec<IndexedHeader>, IndexerError> {
+        self.with_conn(|conn| {
+            let headers = headers::table
+                .filter(headers::height.ge(from_height as i64))
+                .order(headers::height.asc())
+                .limit(limit as i64)
+                .load::<HeaderSql>(conn)?;
+            Ok(headers.into_iter().map(Into::into).collect())
+        })
+    }
+
+    fn fetch_kernels(&self, key: KernelKey) -> Result<Vec<IndexedKernel>, IndexerError> {
+        self.with_conn(|conn| {
+            let query = match key {
+                KernelKey::BlockHeight(height) => kernels::table
+                    .filter(kernels::block_height.eq(height as i64))
+                    .into_boxed(),
+                KernelKey::Excess(excess) => kernels::table
+                    .filter(
+                        kernels::excess
+                            .eq(excess.clone())
+                            .or(kernels::excess_sig_nonce.eq(excess)),
+                    )
+                    .into_boxed(),
+            };
+            let kernels = query.order(kernels::block_height.asc()).load::<KernelSql>(conn)?;
+            Ok(kernels.into_iter().map(Into::into).collect())
+        })
+    }
+
+    fn fetch_outputs(&self, key: OutputKey) -> Result<Vec<IndexedOutput>, IndexerError> {
+        self.with_conn(|conn| {
+            let outputs = match key {
+                OutputKey::BlockHeight(height) => outputs::table
+                    .filter(outputs::block_height.eq(height as i64))
+                    .load::<OutputSql>(conn)?,
+                OutputKey::Commitment(commitment) => outputs::table
+                    .filter(outputs::commitment.eq(commitment))
+                    .order(outputs::block_height.asc())
+                    .load::<OutputSql>(conn)?,
+                OutputKey::WatchedPublicKey(public_key) => outputs::table
+                    .inner_join(watched_outputs::table)
+                    .filter(watched_outputs::public_key.eq(public_key))
+                    .select(outputs::all_columns)
+                    .order(outputs::block_height.asc())
+                    .load::<OutputSql>(conn)?,
+            };
+            Ok(outputs.into_iter().map(Into::into).collect())
+        })
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name = "headers"]
+struct HeaderSql {
+    height: i64,
+    hash: Vec<u8>,
+    prev_hash: Vec<u8>,
+    version: i32,
+    timestamp: i64,
+    nonce: i64,
+    pow_algo: i64,
+    num_inputs: i32,
+    num_outputs: i32,
+    num_kernels: i32,
+}
+
+impl From<IndexedHeader> for HeaderSql {
+    fn from(h: IndexedHeader) -> Self {
+        Self {
+            height: h.height as i64,
+            hash: h.hash,
+            prev_hash: h.prev_hash,
+            version: h.version as i32,
+            timestamp: h.timestamp as i64,
+            nonce: h.nonce as i64,
+            pow_algo: h.pow_algo as i64,
+            num_inputs: h.num_inputs as i32,
+            num_outputs: h.num_outputs as i32,
+            num_kernels: h.num_kernels as i32,
+        }
+    }
+}
+
+impl From<HeaderSql> for IndexedHeader {
+    fn from(h: HeaderSql) -> Self {
+        Self {
+            height: h.height as u64,
+            hash: h.hash,
+            prev_hash: h.prev_hash,
+            version: h.version as u32,
+            timestamp: h.timestamp as u64,
+            nonce: h.nonce as u64,
+            pow_algo: h.pow_algo as u64,
+            num_inputs: h.num_inputs as u32,
+            num_outputs: h.num_outputs as u32,
+            num_kernels: h.num_kernels as u32,
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name = "kernels"]
+struct KernelSql {
+    hash: Vec<u8>,
+    block_height: i64,
+    features: i32,
+    fee: i64,
+    lock_height: i64,
+    excess: Vec<u8>,
+    excess_sig_nonce: Vec<u8>,
+    excess_sig: Vec<u8>,
+}
+
+impl From<IndexedKernel> for KernelSql {
+    fn from(k: IndexedKernel) -> Self {
+        Self {
+            hash: k.hash,
+            block_height: k.block_height as i64,
+            features: k.features as i32,
+            fee: k.fee as i64,
+            lock_height: k.lock_height as i64,
+            excess: k.excess,
+            excess_sig_nonce: k.excess_sig_nonce,
+            excess_sig: k.excess_sig,
+        }
+    }
+}
+
+impl From<KernelSql> for IndexedKernel {
+    fn from(k: KernelSql) -> Self {
+        Self {
+            hash: k.hash,
+            block_height: k.block_height as u64,
+            features: k.features as u32,
+            fee: k.fee as u64,
+            lock_height: k.lock_height as u64,
+            excess: k.excess,
+            excess_sig_nonce: k.excess_sig_nonce,
+            excess_sig: k.excess_sig,
+        }
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name = "outputs"]
+struct OutputSql {
+    hash: Vec<u8>,
+    commitment: Vec<u8>,
+    block_height: i64,
+    flags: i32,
+    maturity: i64,
+    script: Vec<u8>,
+    spent_height: Option<i64>,
+}
+
+impl From<IndexedOutput> for OutputSql {
+    fn from(o: IndexedOutput) -> Self {
+        Self {
+            hash: o.hash,
+            commitment: o.commitment,
+            block_height: o.block_height as i64,
+            flags: o.flags as i32,
+            maturity: o.maturity as i64,
+            script: o.script,
+            spent_height: o.spent_height.map(|h| h as i64),
+        }
+    }
+}
+
+impl From<OutputSql> for IndexedOutput {
+    fn from(o: OutputSql) -> Self {
+        Self {
+            hash: o.hash,
+            commitment: o.commitment,
+            block_height: o.block_height as u64,
+            flags: o.flags as u32,
+            maturity: o.maturity as u64,
+            script: o.script,
+            spent_height: o.spent_height.map(|h| h as u64),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "watched_outputs"]
+struct WatchedOutputSql {
+    output_hash: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn block(height: u64, outputs: Vec<&[u8]>, spent_commitments: Vec<&[u8]>) -> IndexedBlock {
+        let header = IndexedHeader {
+            height,
+            hash: vec![height as u8; 32],
+            prev_hash: vec![height.saturating_sub(1) as u8; 32],
+            version: 1,
+            timestamp: 1_600_000_000 + height,
+            nonce: height,
+            pow_algo: 1,
+            num_inputs: spent_commitments.len() as u32,
+            num_outputs: outputs.len() as u32,
+            num_kernels: 0,
+        };
+        let outputs = outputs
+            .into_iter()
+            .map(|commitment| IndexedOutput {
+                hash: commitment.to_vec(),
+                commitment: commitment.to_vec(),
+                block_height: height,
+                flags: 0,
+                maturity: 0,
+                script: vec![],
+                spent_height: None,
+            })
+            .collect::<Vec<_>>();
+        let watched_outputs = outputs.iter().map(|o| (o.hash.clone(), vec![9u8; 32])).collect();
+        IndexedBlock {
+            header,
+            kernels: vec![],
+            outputs,
+            spent_commitments: spent_commitments.into_iter().map(|c| c.to_vec()).collect(),
+            watched_outputs,
+        }
+    }
+
+    #[test]
+    fn it_indexes_and_rewinds_blocks() {
+        let db = SqliteIndexerBackend::connect(":memory:").unwrap();
+        assert_eq!(db.fetch_tip().unwrap(), None);
+
+        db.insert_block(block(0, vec![b"a"], vec![])).unwrap();
+        db.insert_block(block(1, vec![b"b"], vec![b"a"])).unwrap();
+        assert_eq!(db.fetch_tip().unwrap().unwrap().height, 1);
+        let spent = db.fetch_outputs(OutputKey::Commitment(b"a".to_vec())).unwrap();
+        assert_eq!(spent[0].spent_height, Some(1));
+        let watched = db.fetch_outputs(OutputKey::WatchedPublicKey(vec![9u8; 32])).unwrap();
+        assert_eq!(watched.len(), 2);
+
+        db.rewind_to_height(1).unwrap();
+        assert_eq!(db.fetch_tip().unwrap().unwrap().height, 0);
+        assert!(db.fetch_header(HeaderKey::Hash(vec![1u8; 32])).unwrap().is_none());
+        let unspent = db.fetch_outputs(OutputKey::Commitment(b"a".to_vec())).unwrap();
+        assert_eq!(unspent[0].spent_height, None);
+        let watched = db.fetch_outputs(OutputKey::WatchedPublicKey(vec![9u8; 32])).unwrap();
+        assert_eq!(watched.len(), 1);
+    }
+}
@@ -27,19 +27,19 @@ use crate::{
 };
 use log::*;
 use std::cmp;
-use tari_app_grpc::tari_rpc as grpc;
+use tari_app_grpc::{authentication::BaseNodeAdminClient, tari_rpc as grpc};
 use tari_core::proof_of_work::{monero_rx, monero_rx::FixedByteArray, Difficulty};
 
 const LOG_TARGET: &str = "tari_mm_proxy::proxy::block_template_protocol";
 
 pub struct BlockTemplateProtocol<'a> {
-    base_node_client: &'a mut grpc::base_node_client::BaseNodeClient<tonic::transport::Channel>,
+    base_node_client: &'a mut BaseNodeAdminClient,
     wallet_client: &'a mut grpc::wallet_client::WalletClient<tonic::transport::Channel>,
 }
 
 impl<'a> BlockTemplateProtocol<'a> {
     pub fn new(
-        base_node_client: &'a mut grpc::base_node_client::BaseNodeClient<tonic::transport::Channel>,
+        base_node_client: &'a mut BaseNodeAdminClient,
         wallet_client: &'a mut grpc::wallet_client::WalletClient<tonic::transport::Channel>,
     ) -> Self {
         Self {
@@ -55,6 +55,8 @@ pub enum MmProxyError {
     InvalidMonerodResponse(String),
     #[error("Failed to send request to monerod: {0}")]
     MonerodRequestFailed(reqwest::Error),
+    #[error("No monerod upstream is configured")]
+    NoMonerodUpstream,
     #[error("GRPC request failed with `{status}` {details}")]
     GrpcRequestError {
         #[source]
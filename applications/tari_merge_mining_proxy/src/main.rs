@@ -31,20 +31,28 @@ mod block_template_data;
 mod block_template_protocol;
 mod common;
 mod error;
+mod metrics;
+mod monerod_upstreams;
 mod proxy;
 
 #[cfg(test)]
 mod test;
 
-use crate::{block_template_data::BlockTemplateRepository, error::MmProxyError};
+use crate::{
+    block_template_data::BlockTemplateRepository,
+    error::MmProxyError,
+    metrics::ProxyMetrics,
+    monerod_upstreams::MonerodUpstreams,
+};
 use futures::future;
-use hyper::{service::make_service_fn, Server};
+use hyper::{server::conn::AddrStream, service::make_service_fn, Server};
 use proxy::{MergeMiningProxyConfig, MergeMiningProxyService};
 use std::convert::Infallible;
-use tari_app_grpc::tari_rpc as grpc;
+use tari_app_grpc::{authentication::ClientAuthInterceptor, tari_rpc as grpc};
 use tari_app_utilities::initialization::init_configuration;
 use tari_common::configuration::bootstrap::ApplicationType;
 use tokio::time::Duration;
+use tonic::transport::Endpoint;
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
@@ -58,9 +66,24 @@ async fn main() -> Result<(), anyhow::Error> {
         .pool_max_idle_per_host(25)
         .build()
         .map_err(MmProxyError::ReqwestError)?;
+    let monerod_upstreams = MonerodUpstreams::new(config.monerod_url.clone());
+    if monerod_upstreams.active().await.is_none() {
+        return Err(MmProxyError::NoMonerodUpstream.into());
+    }
+    tokio::spawn(
+        monerod_upstreams
+            .clone()
+            .run_health_checks(client.clone(), config.clone()),
+    );
     println!("Connecting to base node at {}", config.grpc_base_node_address);
-    let base_node_client =
-        grpc::base_node_client::BaseNodeClient::connect(format!("http://{}", config.grpc_base_node_address)).await?;
+    // Block templates and block submission are admin methods of the base node
+    let auth = ClientAuthInterceptor::new(config.grpc_base_node_admin_token.as_deref())?;
+    let base_node_client = grpc::base_node_client::BaseNodeClient::with_interceptor(
+        Endpoint::new(format!("http://{}", config.grpc_base_node_address))?
+            .connect()
+            .await?,
+        auth,
+    );
     println!("Connecting to wallet at {}", config.grpc_console_wallet_address);
     let wallet_client =
         grpc::wallet_client::WalletClient::connect(format!("http://{}", config.grpc_console_wallet_address)).await?;
@@ -70,8 +93,14 @@ async fn main() -> Result<(), anyhow::Error> {
         base_node_client,
         wallet_client,
         BlockTemplateRepository::new(),
+        monerod_upstreams,
+        ProxyMetrics::new(),
     );
-    let service = make_service_fn(|_conn| future::ready(Result::<_, Infallible>::Ok(xmrig_service.clone())));
+    let service = make_service_fn(|conn: &AddrStream| {
+        future::ready(Result::<_, Infallible>::Ok(
+            xmrig_service.for_connection(conn.remote_addr()),
+        ))
+    });
 
     match Server::try_bind(&addr) {
         Ok(builder) => {
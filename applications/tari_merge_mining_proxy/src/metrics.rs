@@ -0,0 +1,267 @@
+//  Copyright 2020, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use chrono::{DateTime, Utc};
+use json::json;
+use serde_json as json;
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+
+/// Upstream services whose request latency is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Upstream {
+    Monerod,
+    TariBaseNode,
+}
+
+impl Upstream {
+    fn label(self) -> &'static str {
+        match self {
+            Upstream::Monerod => "monerod",
+            Upstream::TariBaseNode => "tari_base_node",
+        }
+    }
+}
+
+/// Chains that mined blocks are submitted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Chain {
+    Monero,
+    Tari,
+}
+
+impl Chain {
+    fn label(self) -> &'static str {
+        match self {
+            Chain::Monero => "xmr",
+            Chain::Tari => "xtr",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct SubmittedBlocks {
+    accepted: u64,
+    rejected: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Latency {
+    count: u64,
+    total: Duration,
+    last: Duration,
+    max: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct WorkerStats {
+    shares: u64,
+    last_share: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct MetricsState {
+    started: Instant,
+    template_requests: u64,
+    submitted_blocks: HashMap<Chain, SubmittedBlocks>,
+    latencies: HashMap<Upstream, Latency>,
+    workers: HashMap<String, WorkerStats>,
+}
+
+/// Counters of the proxy activity, served by the `/stats` (JSON) and `/metrics` (Prometheus) endpoints
+#[derive(Debug, Clone)]
+pub struct ProxyMetrics {
+    inner: Arc<RwLock<MetricsState>>,
+}
+
+impl ProxyMetrics {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(MetricsState {
+                started: Instant::now(),
+                template_requests: 0,
+                submitted_blocks: HashMap::new(),
+                latencies: HashMap::new(),
+                workers: HashMap::new(),
+            })),
+        }
+    }
+
+    pub async fn record_template_request(&self) {
+        self.inner.write().await.template_requests += 1;
+    }
+
+    pub async fn record_submitted_block(&self, chain: Chain, accepted: bool) {
+        let mut state = self.inner.write().await;
+        let blocks = state.submitted_blocks.entry(chain).or_default();
+        if accepted {
+            blocks.accepted += 1;
+        } else {
+            blocks.rejected += 1;
+        }
+    }
+
+    pub async fn record_latency(&self, upstream: Upstream, elapsed: Duration) {
+        let mut state = self.inner.write().await;
+        let latency = state.latencies.entry(upstream).or_default();
+        latency.count += 1;
+        latency.total += elapsed;
+        latency.last = elapsed;
+        latency.max = latency.max.max(elapsed);
+    }
+
+    pub async fn record_share(&self, worker: &str) {
+        let mut state = self.inner.write().await;
+        let stats = state.workers.entry(worker.to_string()).or_insert(WorkerStats {
+            shares: 0,
+            last_share: Utc::now(),
+        });
+        stats.shares += 1;
+        stats.last_share = Utc::now();
+    }
+
+    pub async fn to_json(&self) -> json::Value {
+        let state = self.inner.read().await;
+        let blocks = [Chain::Tari, Chain::Monero]
+            .iter()
+            .map(|chain| {
+                let blocks = state.submitted_blocks.get(chain).copied().unwrap_or_default();
+                (
+                    chain.label().to_string(),
+                    json!({ "accepted": blocks.accepted, "rejected": blocks.rejected }),
+                )
+            })
+            .collect::<json::Map<_, _>>();
+        let latencies = state
+            .latencies
+            .iter()
+            .map(|(upstream, latency)| {
+                let average = latency.total.checked_div(latency.count as u32).unwrap_or_default();
+                (
+                    upstream.label().to_string(),
+                    json!({
+                        "requests": latency.count,
+                        "average_ms": average.as_millis() as u64,
+                        "last_ms": latency.last.as_millis() as u64,
+                        "max_ms": latency.max.as_millis() as u64,
+                    }),
+                )
+            })
+            .collect::<json::Map<_, _>>();
+        let workers = state
+            .workers
+            .iter()
+            .map(|(worker, stats)| {
+                (
+                    worker.clone(),
+                    json!({ "shares": stats.shares, "last_share": stats.last_share.to_rfc3339() }),
+                )
+            })
+            .collect::<json::Map<_, _>>();
+        json!({
+            "uptime_secs": state.started.elapsed().as_secs(),
+            "template_requests": state.template_requests,
+            "submitted_blocks": blocks,
+            "upstream_latency": latencies,
+            "workers": workers,
+        })
+    }
+
+    /// The metrics in the Prometheus text exposition format
+    pub async fn to_prometheus(&self) -> String {
+        let state = self.inner.read().await;
+        let mut out = String::new();
+        // Writing to a String cannot fail
+        let _ = writeln!(out, "# HELP mmproxy_uptime_seconds Time since the proxy started");
+        let _ = writeln!(out, "# TYPE mmproxy_uptime_seconds gauge");
+        let _ = writeln!(out, "mmproxy_uptime_seconds {}", state.started.elapsed().as_secs());
+
+        let _ = writeln!(
+            out,
+            "# HELP mmproxy_template_requests_total Block template requests from miners"
+        );
+        let _ = writeln!(out, "# TYPE mmproxy_template_requests_total counter");
+        let _ = writeln!(out, "mmproxy_template_requests_total {}", state.template_requests);
+
+        let _ = writeln!(
+            out,
+            "# HELP mmproxy_submitted_blocks_total Blocks submitted to each chain"
+        );
+        let _ = writeln!(out, "# TYPE mmproxy_submitted_blocks_total counter");
+        for chain in &[Chain::Tari, Chain::Monero] {
+            let blocks = state.submitted_blocks.get(chain).copied().unwrap_or_default();
+            for (result, count) in &[("accepted", blocks.accepted), ("rejected", blocks.rejected)] {
+                let _ = writeln!(
+                    out,
+                    "mmproxy_submitted_blocks_total{{chain=\"{}\",result=\"{}\"}} {}",
+                    chain.label(),
+                    result,
+                    count
+                );
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mmproxy_upstream_latency_seconds Latency of requests to upstream services"
+        );
+        let _ = writeln!(out, "# TYPE mmproxy_upstream_latency_seconds summary");
+        for (upstream, latency) in &state.latencies {
+            let _ = writeln!(
+                out,
+                "mmproxy_upstream_latency_seconds_sum{{upstream=\"{}\"}} {}",
+                upstream.label(),
+                latency.total.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "mmproxy_upstream_latency_seconds_count{{upstream=\"{}\"}} {}",
+                upstream.label(),
+                latency.count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mmproxy_worker_shares_total Shares submitted by each worker"
+        );
+        let _ = writeln!(out, "# TYPE mmproxy_worker_shares_total counter");
+        for (worker, stats) in &state.workers {
+            let _ = writeln!(
+                out,
+                "mmproxy_worker_shares_total{{worker=\"{}\"}} {}",
+                escape_label(worker),
+                stats.shares
+            );
+        }
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
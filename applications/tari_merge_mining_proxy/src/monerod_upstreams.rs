@@ -0,0 +1,219 @@
+//  Copyright 2020, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::proxy::MergeMiningProxyConfig;
+use json::json;
+use serde_json as json;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{sync::RwLock, time};
+use tracing::{debug, info, warn};
+
+pub const LOG_TARGET: &str = "tari_mm_proxy::monerod";
+
+/// How long a monerod upstream is skipped after it failed, unless all upstreams are failing
+const FAILOVER_COOLDOWN: Duration = Duration::from_secs(60);
+/// How often all monerod upstreams are health checked
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The configured monerod upstreams. Requests are sent to the active upstream, which is kept for as long as it
+/// responds. When it fails, requests fail over to the next upstream that is not cooling down from a failure.
+#[derive(Debug, Clone)]
+pub struct MonerodUpstreams {
+    inner: Arc<RwLock<UpstreamsState>>,
+}
+
+#[derive(Debug)]
+struct UpstreamsState {
+    upstreams: Vec<Upstream>,
+    active: usize,
+}
+
+#[derive(Debug)]
+struct Upstream {
+    url: String,
+    healthy: bool,
+    failures: u64,
+    cooldown_until: Option<Instant>,
+}
+
+impl Upstream {
+    fn is_cooling_down(&self, now: Instant) -> bool {
+        self.cooldown_until.map(|until| until > now).unwrap_or(false)
+    }
+}
+
+impl MonerodUpstreams {
+    pub fn new(urls: Vec<String>) -> Self {
+        let upstreams = urls
+            .into_iter()
+            .filter(|url| !url.is_empty())
+            .map(|url| Upstream {
+                url,
+                healthy: true,
+                failures: 0,
+                cooldown_until: None,
+            })
+            .collect();
+        Self {
+            inner: Arc::new(RwLock::new(UpstreamsState { upstreams, active: 0 })),
+        }
+    }
+
+    /// The URL of the active upstream
+    pub async fn active(&self) -> Option<String> {
+        let state = self.inner.read().await;
+        state.upstreams.get(state.active).map(|upstream| upstream.url.clone())
+    }
+
+    /// The upstream URLs in the order they should be tried: the active upstream followed by the others in configured
+    /// order. Upstreams that are cooling down come last, so they are only used when everything else fails.
+    pub async fn candidates(&self) -> Vec<String> {
+        let state = self.inner.read().await;
+        let now = Instant::now();
+        let len = state.upstreams.len();
+        let (mut available, cooling_down): (Vec<_>, Vec<_>) = (0..len)
+            .map(|i| &state.upstreams[(state.active + i) % len])
+            .partition(|upstream| !upstream.is_cooling_down(now));
+        available.extend(cooling_down);
+        available.into_iter().map(|upstream| upstream.url.clone()).collect()
+    }
+
+    /// Marks the upstream as healthy and makes it the active upstream if the active one has failed
+    pub async fn report_success(&self, url: &str) {
+        let mut state = self.inner.write().await;
+        let index = match state.upstreams.iter().position(|upstream| upstream.url == url) {
+            Some(index) => index,
+            None => return,
+        };
+        state.upstreams[index].healthy = true;
+        if index != state.active && !state.upstreams[state.active].healthy {
+            info!(
+                target: LOG_TARGET,
+                "Switching monerod upstream from {} to {}", state.upstreams[state.active].url, url
+            );
+            state.active = index;
+        }
+    }
+
+    /// Counts the failure and puts the upstream into cooldown. A failing active upstream is replaced by the next
+    /// upstream that is not cooling down.
+    pub async fn report_failure(&self, url: &str) {
+        let mut state = self.inner.write().await;
+        let index = match state.upstreams.iter().position(|upstream| upstream.url == url) {
+            Some(index) => index,
+            None => return,
+        };
+        let now = Instant::now();
+        let upstream = &mut state.upstreams[index];
+        upstream.healthy = false;
+        upstream.failures += 1;
+        upstream.cooldown_until = Some(now + FAILOVER_COOLDOWN);
+        warn!(
+            target: LOG_TARGET,
+            "Monerod upstream {} failed ({} failures in total)", upstream.url, upstream.failures
+        );
+        if index != state.active {
+            return;
+        }
+        let len = state.upstreams.len();
+        let next = (1..len)
+            .map(|i| (state.active + i) % len)
+            .find(|i| state.upstreams[*i].healthy && !state.upstreams[*i].is_cooling_down(now));
+        if let Some(next) = next {
+            info!(
+                target: LOG_TARGET,
+                "Failing over from monerod upstream {} to {}", url, state.upstreams[next].url
+            );
+            state.active = next;
+        }
+    }
+
+    /// Status of all upstreams for the status endpoint
+    pub async fn status(&self) -> json::Value {
+        let state = self.inner.read().await;
+        let now = Instant::now();
+        let upstreams = state
+            .upstreams
+            .iter()
+            .map(|upstream| {
+                json!({
+                    "url": upstream.url,
+                    "healthy": upstream.healthy,
+                    "failures": upstream.failures,
+                    "cooldown_secs": upstream
+                        .cooldown_until
+                        .map(|until| until.saturating_duration_since(now).as_secs())
+                        .unwrap_or_default(),
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "active": state.upstreams.get(state.active).map(|upstream| upstream.url.clone()),
+            "upstreams": upstreams,
+        })
+    }
+
+    /// Periodically checks that every upstream responds to `get_height`, so that a failing active upstream is
+    /// replaced before a miner request fails, and recovered upstreams become available again
+    pub async fn run_health_checks(self, http_client: reqwest::Client, config: MergeMiningProxyConfig) {
+        let mut interval = time::interval(HEALTH_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let urls = {
+                let state = self.inner.read().await;
+                state
+                    .upstreams
+                    .iter()
+                    .map(|upstream| upstream.url.clone())
+                    .collect::<Vec<_>>()
+            };
+            for url in urls {
+                let mut builder = http_client.get(format!("{}/get_height", url.trim_end_matches('/')));
+                if config.monerod_use_auth {
+                    builder = builder.basic_auth(&config.monerod_username, Some(&config.monerod_password));
+                }
+                match builder.send().await {
+                    Ok(resp) if resp.status().is_success() => {
+                        debug!(target: LOG_TARGET, "Monerod upstream {} is healthy", url);
+                        self.report_success(&url).await;
+                    },
+                    Ok(resp) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Monerod upstream {} health check returned {}",
+                            url,
+                            resp.status()
+                        );
+                        self.report_failure(&url).await;
+                    },
+                    Err(err) => {
+                        debug!(target: LOG_TARGET, "Monerod upstream {} health check failed: {}", url, err);
+                        self.report_failure(&url).await;
+                    },
+                }
+            }
+        }
+    }
+}
@@ -25,9 +25,11 @@ use crate::{
     block_template_protocol::{BlockTemplateProtocol, MoneroMiningData},
     common::{json_rpc, monero_rpc::CoreRpcErrorCode, proxy, proxy::convert_json_to_hyper_json_response},
     error::MmProxyError,
+    metrics::{Chain, ProxyMetrics, Upstream},
+    monerod_upstreams::MonerodUpstreams,
 };
 use bytes::Bytes;
-use hyper::{header::HeaderValue, service::Service, Body, Method, Request, Response, StatusCode, Uri};
+use hyper::{header, header::HeaderValue, service::Service, Body, Method, Request, Response, StatusCode, Uri};
 use json::json;
 use jsonrpc::error::StandardError;
 use reqwest::{ResponseBuilderExt, Url};
@@ -44,7 +46,7 @@ use std::{
     task::{Context, Poll},
     time::Instant,
 };
-use tari_app_grpc::tari_rpc as grpc;
+use tari_app_grpc::{authentication::BaseNodeAdminClient, tari_rpc as grpc};
 use tari_common::{configuration::Network, GlobalConfig};
 use tari_core::proof_of_work::{monero_rx, monero_rx::FixedByteArray};
 use tari_utilities::hex::Hex;
@@ -55,15 +57,22 @@ const LOG_TARGET: &str = "tari_mm_proxy::proxy";
 pub(crate) const MMPROXY_AUX_KEY_NAME: &str = "_aux";
 /// The identifier used to identify the tari aux chain data
 const TARI_CHAIN_ID: &str = "xtr";
+/// The path of the status endpoint, which is served by the proxy itself instead of being proxied to monerod
+const STATUS_PATH: &str = "/mmproxy_status";
+/// The path of the JSON statistics endpoint
+const STATS_PATH: &str = "/stats";
+/// The path of the Prometheus metrics endpoint
+const METRICS_PATH: &str = "/metrics";
 
 #[derive(Debug, Clone)]
 pub struct MergeMiningProxyConfig {
     pub network: Network,
-    pub monerod_url: String,
+    pub monerod_url: Vec<String>,
     pub monerod_username: String,
     pub monerod_password: String,
     pub monerod_use_auth: bool,
     pub grpc_base_node_address: SocketAddr,
+    pub grpc_base_node_admin_token: Option<String>,
     pub grpc_console_wallet_address: SocketAddr,
     pub proxy_host_address: SocketAddr,
     pub proxy_submit_to_origin: bool,
@@ -79,6 +88,7 @@ impl From<GlobalConfig> for MergeMiningProxyConfig {
             monerod_password: config.monerod_password,
             monerod_use_auth: config.monerod_use_auth,
             grpc_base_node_address: config.grpc_base_node_address,
+            grpc_base_node_admin_token: config.grpc_base_node_admin_token,
             grpc_console_wallet_address: config.grpc_console_wallet_address,
             proxy_host_address: config.proxy_host_address,
             proxy_submit_to_origin: config.proxy_submit_to_origin,
@@ -96,14 +106,19 @@ impl MergeMiningProxyService {
     pub fn new(
         config: MergeMiningProxyConfig,
         http_client: reqwest::Client,
-        base_node_client: grpc::base_node_client::BaseNodeClient<tonic::transport::Channel>,
+        base_node_client: BaseNodeAdminClient,
         wallet_client: grpc::wallet_client::WalletClient<tonic::transport::Channel>,
         block_templates: BlockTemplateRepository,
+        monerod_upstreams: MonerodUpstreams,
+        metrics: ProxyMetrics,
     ) -> Self {
         Self {
             inner: InnerService {
                 config,
                 block_templates,
+                monerod_upstreams,
+                metrics,
+                remote_addr: None,
                 http_client,
                 base_node_client,
                 wallet_client,
@@ -111,6 +126,13 @@ impl MergeMiningProxyService {
             },
         }
     }
+
+    /// A service for a miner connection, the remote address identifies the worker in the statistics
+    pub fn for_connection(&self, remote_addr: SocketAddr) -> Self {
+        let mut service = self.clone();
+        service.inner.remote_addr = Some(remote_addr);
+        service
+    }
 }
 
 #[allow(clippy::type_complexity)]
@@ -170,8 +192,11 @@ impl Service<Request<Body>> for MergeMiningProxyService {
 struct InnerService {
     config: MergeMiningProxyConfig,
     block_templates: BlockTemplateRepository,
+    monerod_upstreams: MonerodUpstreams,
+    metrics: ProxyMetrics,
+    remote_addr: Option<SocketAddr>,
     http_client: reqwest::Client,
-    base_node_client: grpc::base_node_client::BaseNodeClient<tonic::transport::Channel>,
+    base_node_client: BaseNodeAdminClient,
     wallet_client: grpc::wallet_client::WalletClient<tonic::transport::Channel>,
     initial_sync_achieved: Arc<AtomicBool>,
 }
@@ -191,6 +216,7 @@ impl InnerService {
         let mut base_node_client = self.base_node_client.clone();
         trace!(target: LOG_TARGET, "Successful connection to base node GRPC");
 
+        let start = Instant::now();
         let result =
             base_node_client
                 .get_tip_info(grpc::Empty {})
@@ -199,6 +225,9 @@ impl InnerService {
                     status: err,
                     details: "get_tip_info failed".to_string(),
                 })?;
+        self.metrics
+            .record_latency(Upstream::TariBaseNode, start.elapsed())
+            .await;
         let height = result
             .get_ref()
             .metadata
@@ -234,6 +263,10 @@ impl InnerService {
 
         debug!(target: LOG_TARGET, "handle_submit_block: submit request #{}", request);
         debug!(target: LOG_TARGET, "Params received: #{:?}", request["params"]);
+        if self.config.proxy_submit_to_origin {
+            let accepted = json_resp["error"].is_null() && json_resp["result"]["status"].as_str() == Some("OK");
+            self.metrics.record_submitted_block(Chain::Monero, accepted).await;
+        }
         let params = match request["params"].as_array() {
             Some(v) => v,
             None => {
@@ -250,6 +283,7 @@ impl InnerService {
         };
 
         for param in params.iter().filter_map(|p| p.as_str()) {
+            self.metrics.record_share(&self.worker_name()).await;
             let monero_block = monero_rx::deserialize_monero_block_from_hex(param)?;
             debug!(target: LOG_TARGET, "Monero block: {}", monero_block);
             let hash = monero_rx::extract_tari_hash(&monero_block)
@@ -284,7 +318,12 @@ impl InnerService {
 
             let mut base_node_client = self.base_node_client.clone();
             let start = Instant::now();
-            match base_node_client.submit_block(block_data.tari_block).await {
+            let result = base_node_client.submit_block(block_data.tari_block).await;
+            self.metrics
+                .record_latency(Upstream::TariBaseNode, start.elapsed())
+                .await;
+            self.metrics.record_submitted_block(Chain::Tari, result.is_ok()).await;
+            match result {
                 Ok(resp) => {
                     if !self.config.proxy_submit_to_origin {
                         // self-select related, do not change.
@@ -340,6 +379,7 @@ impl InnerService {
         &self,
         monerod_resp: Response<json::Value>,
     ) -> Result<Response<Body>, MmProxyError> {
+        self.metrics.record_template_request().await;
         let (parts, mut monerod_resp) = monerod_resp.into_parts();
         debug!(
             target: LOG_TARGET,
@@ -576,17 +616,68 @@ impl InnerService {
         Ok(proxy::into_response(parts, &resp))
     }
 
-    fn get_fully_qualified_monerod_url(&self, uri: &Uri) -> Result<Url, MmProxyError> {
-        let uri = format!("{}{}", self.config.monerod_url, uri.path()).parse::<Url>()?;
+    fn get_fully_qualified_monerod_url(&self, monerod_url: &str, uri: &Uri) -> Result<Url, MmProxyError> {
+        let uri = format!("{}{}", monerod_url, uri.path()).parse::<Url>()?;
         Ok(uri)
     }
 
-    /// Proxy a request received by this server to Monerod
-    async fn proxy_request_to_monerod(
+    async fn handle_get_status(&self) -> Result<Response<Body>, MmProxyError> {
+        let status = json!({
+            "monerod": self.monerod_upstreams.status().await,
+            "initial_sync_achieved": self.initial_sync_achieved.load(Ordering::Relaxed),
+        });
+        proxy::json_response(StatusCode::OK, &status)
+    }
+
+    async fn handle_get_stats(&self) -> Result<Response<Body>, MmProxyError> {
+        proxy::json_response(StatusCode::OK, &self.metrics.to_json().await)
+    }
+
+    async fn handle_get_metrics(&self) -> Result<Response<Body>, MmProxyError> {
+        Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .status(StatusCode::OK)
+            .body(self.metrics.to_prometheus().await.into())
+            .map_err(Into::into)
+    }
+
+    /// Miners are identified by their IP address, the port changes with every connection
+    fn worker_name(&self) -> String {
+        self.remote_addr
+            .map(|addr| addr.ip().to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Send the request to the monerod upstreams in turn until one of them responds
+    async fn send_to_monerod_with_failover(
         &self,
-        request: Request<Bytes>,
-    ) -> Result<(Request<Bytes>, Response<json::Value>), MmProxyError> {
-        let monerod_uri = self.get_fully_qualified_monerod_url(request.uri())?;
+        request: &Request<Bytes>,
+    ) -> Result<Response<json::Value>, MmProxyError> {
+        let mut result = Err(MmProxyError::NoMonerodUpstream);
+        for monerod_url in self.monerod_upstreams.candidates().await {
+            let start = Instant::now();
+            result = self.send_to_monerod(&monerod_url, request).await;
+            match result {
+                Ok(_) => {
+                    self.metrics.record_latency(Upstream::Monerod, start.elapsed()).await;
+                    self.monerod_upstreams.report_success(&monerod_url).await;
+                    break;
+                },
+                Err(ref err) => {
+                    warn!(target: LOG_TARGET, "[monerod] request to {} failed: {}", monerod_url, err);
+                    self.monerod_upstreams.report_failure(&monerod_url).await;
+                },
+            }
+        }
+        result
+    }
+
+    async fn send_to_monerod(
+        &self,
+        monerod_url: &str,
+        request: &Request<Bytes>,
+    ) -> Result<Response<json::Value>, MmProxyError> {
+        let monerod_uri = self.get_fully_qualified_monerod_url(monerod_url, request.uri())?;
 
         let mut headers = request.headers().clone();
         // Some public monerod setups (e.g. those that are reverse proxied by nginx) require the Host header.
@@ -619,6 +710,20 @@ impl InnerService {
             monerod_uri,
         );
 
+        let resp = builder
+            // This is a cheap clone of the request body
+            .body(request.body().clone())
+            .send()
+            .await
+            .map_err(MmProxyError::MonerodRequestFailed)?;
+        convert_reqwest_response_to_hyper_json_response(resp).await
+    }
+
+    /// Proxy a request received by this server to Monerod
+    async fn proxy_request_to_monerod(
+        &self,
+        request: Request<Bytes>,
+    ) -> Result<(Request<Bytes>, Response<json::Value>), MmProxyError> {
         let mut submit_block = false;
         let body: Bytes = request.body().clone();
         let json = json::from_slice::<json::Value>(&body[..]).unwrap_or_default();
@@ -658,16 +763,15 @@ impl InnerService {
             // NB!: This is by design, do not change this without understanding
             // it's implications.
             let accept_response = json_rpc::default_block_accept_response(json["id"].as_i64());
-            json_response =
-                convert_json_to_hyper_json_response(accept_response, StatusCode::OK, monerod_uri.clone()).await?;
-        } else {
-            let resp = builder
-                // This is a cheap clone of the request body
-                .body(body)
-                .send()
+            let monerod_url = self
+                .monerod_upstreams
+                .active()
                 .await
-                .map_err(MmProxyError::MonerodRequestFailed)?;
-            json_response = convert_reqwest_response_to_hyper_json_response(resp).await?
+                .ok_or(MmProxyError::NoMonerodUpstream)?;
+            let monerod_uri = self.get_fully_qualified_monerod_url(&monerod_url, request.uri())?;
+            json_response = convert_json_to_hyper_json_response(accept_response, StatusCode::OK, monerod_uri).await?;
+        } else {
+            json_response = self.send_to_monerod_with_failover(&request).await?
         };
 
         let rpc_status = if json_response.body()["error"].is_null() {
@@ -738,6 +842,15 @@ impl InnerService {
                 .join(","),
         );
 
+        if request.method() == Method::GET {
+            match request.uri().path() {
+                STATUS_PATH => return self.handle_get_status().await,
+                STATS_PATH => return self.handle_get_stats().await,
+                METRICS_PATH => return self.handle_get_metrics().await,
+                _ => {},
+            }
+        }
+
         let (request, monerod_resp) = self.proxy_request_to_monerod(request).await?;
         // Any failed (!= 200 OK) responses from Monero are immediately returned to the requester
         let monerod_status = monerod_resp.status();
@@ -68,3 +68,88 @@ mod append_aux_chain_data {
         ]);
     }
 }
+
+mod monerod_upstreams {
+    use crate::monerod_upstreams::MonerodUpstreams;
+
+    fn upstreams() -> MonerodUpstreams {
+        MonerodUpstreams::new(vec![
+            "http://a:18081".to_string(),
+            "http://b:18081".to_string(),
+            "http://c:18081".to_string(),
+        ])
+    }
+
+    #[tokio::test]
+    async fn it_fails_over_to_the_next_upstream() {
+        let upstreams = upstreams();
+        assert_eq!(upstreams.active().await.unwrap(), "http://a:18081");
+
+        upstreams.report_failure("http://a:18081").await;
+        assert_eq!(upstreams.active().await.unwrap(), "http://b:18081");
+        assert_eq!(upstreams.candidates().await, vec![
+            "http://b:18081",
+            "http://c:18081",
+            "http://a:18081"
+        ]);
+
+        // The active upstream is sticky, a recovered upstream does not take over
+        upstreams.report_success("http://a:18081").await;
+        assert_eq!(upstreams.active().await.unwrap(), "http://b:18081");
+
+        let status = upstreams.status().await;
+        assert_eq!(status["active"].as_str().unwrap(), "http://b:18081");
+        assert_eq!(status["upstreams"][0]["failures"].as_u64().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_keeps_trying_when_all_upstreams_fail() {
+        let upstreams = upstreams();
+        upstreams.report_failure("http://a:18081").await;
+        upstreams.report_failure("http://b:18081").await;
+        upstreams.report_failure("http://c:18081").await;
+        assert_eq!(upstreams.active().await.unwrap(), "http://c:18081");
+        assert_eq!(upstreams.candidates().await.len(), 3);
+
+        upstreams.report_success("http://a:18081").await;
+        assert_eq!(upstreams.active().await.unwrap(), "http://a:18081");
+    }
+}
+
+mod metrics {
+    use crate::metrics::{Chain, ProxyMetrics, Upstream};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn it_reports_stats_and_prometheus_metrics() {
+        let metrics = ProxyMetrics::new();
+        metrics.record_template_request().await;
+        metrics.record_submitted_block(Chain::Tari, true).await;
+        metrics.record_submitted_block(Chain::Monero, false).await;
+        metrics
+            .record_latency(Upstream::Monerod, Duration::from_millis(100))
+            .await;
+        metrics
+            .record_latency(Upstream::Monerod, Duration::from_millis(300))
+            .await;
+        metrics.record_share("127.0.0.1").await;
+        metrics.record_share("127.0.0.1").await;
+
+        let stats = metrics.to_json().await;
+        assert_eq!(stats["template_requests"].as_u64().unwrap(), 1);
+        assert_eq!(stats["submitted_blocks"]["xtr"]["accepted"].as_u64().unwrap(), 1);
+        assert_eq!(stats["submitted_blocks"]["xmr"]["rejected"].as_u64().unwrap(), 1);
+        assert_eq!(
+            stats["upstream_latency"]["monerod"]["average_ms"].as_u64().unwrap(),
+            200
+        );
+        assert_eq!(stats["upstream_latency"]["monerod"]["max_ms"].as_u64().unwrap(), 300);
+        assert_eq!(stats["workers"]["127.0.0.1"]["shares"].as_u64().unwrap(), 2);
+
+        let prometheus = metrics.to_prometheus().await;
+        assert!(prometheus.contains("mmproxy_template_requests_total 1\n"));
+        assert!(prometheus.contains("mmproxy_submitted_blocks_total{chain=\"xtr\",result=\"accepted\"} 1\n"));
+        assert!(prometheus.contains("mmproxy_upstream_latency_seconds_count{upstream=\"monerod\"} 2\n"));
+        assert!(prometheus.contains("mmproxy_worker_shares_total{worker=\"127.0.0.1\"} 2\n"));
+    }
+}
@@ -33,6 +33,12 @@
 //! - mine_on_tip_only - will start mining only when node is reporting bootstrapped state
 //! - validate_tip_timeout_sec - will check tip with node every N seconds to validate that still
 //! mining on a tip
+//! - mine_with_gpu - will also mine on the OpenCL GPU devices, requires the `gpu` feature
+//! - gpu_devices - indexes of the GPU devices to mine on, defaults to all devices
+//! - gpu_work_sizes - number of GPU threads per kernel launch for each device in `gpu_devices`
+//! - mining_active_hours - daily local time window to mine in, e.g. `22:00-06:00`, defaults to always
+//! - max_temperature - will pause mining while the CPU or a GPU device is at or above this temperature in
+//! degrees Celsius
 //! All miner options configured under `[mining_node]` section of
 //! Tari's `config.toml`.
 
@@ -52,6 +58,11 @@ pub struct MinerConfig {
     pub mining_pool_address: String,
     pub mining_wallet_address: String,
     pub mining_worker_name: String,
+    pub mine_with_gpu: bool,
+    pub gpu_devices: Vec<usize>,
+    pub gpu_work_sizes: Vec<usize>,
+    pub mining_active_hours: Option<String>,
+    pub max_temperature: Option<f32>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -77,6 +88,11 @@ impl Default for MinerConfig {
             mining_pool_address: "".to_string(),
             mining_wallet_address: "".to_string(),
             mining_worker_name: "".to_string(),
+            mine_with_gpu: false,
+            gpu_devices: vec![],
+            gpu_work_sizes: vec![],
+            mining_active_hours: None,
+            max_temperature: None,
         }
     }
 }
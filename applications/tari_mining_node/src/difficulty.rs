@@ -75,6 +75,32 @@ impl BlockHeaderSha3 {
             .chain(self.header.total_script_offset.as_bytes())
     }
 
+    /// The serialized header fields that are hashed before the nonce, for hashing backends that cannot use the
+    /// intermediate hash state, e.g. GPUs
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    pub fn bytes_before_nonce(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(256);
+        bytes.extend_from_slice(&(self.header.version as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.header.height.to_le_bytes());
+        bytes.extend_from_slice(self.header.prev_hash.as_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes.extend_from_slice(self.header.input_mr.as_bytes());
+        bytes.extend_from_slice(self.header.output_mr.as_bytes());
+        bytes.extend_from_slice(&self.header.output_mmr_size.to_le_bytes());
+        bytes.extend_from_slice(self.header.witness_mr.as_bytes());
+        bytes.extend_from_slice(self.header.kernel_mr.as_bytes());
+        bytes.extend_from_slice(&self.header.kernel_mmr_size.to_le_bytes());
+        bytes.extend_from_slice(self.header.total_kernel_offset.as_bytes());
+        bytes.extend_from_slice(self.header.total_script_offset.as_bytes());
+        bytes
+    }
+
+    /// The serialized proof of work that is hashed after the nonce
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    pub fn pow_bytes(&self) -> &[u8] {
+        &self.pow_bytes
+    }
+
     /// This function will update the timestamp of the header, but only if the new timestamp is greater than the current
     /// one.
     pub fn set_forward_timestamp(&mut self, timestamp: u64) {
@@ -129,6 +155,16 @@ fn big_endian_difficulty(hash: &[u8]) -> Difficulty {
     result.low_u64()
 }
 
+/// The largest big endian hash that meets the difficulty. Comparing hashes against the target is cheaper than
+/// calculating the difficulty of every hash.
+#[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+pub fn big_endian_target(difficulty: Difficulty) -> [u8; 32] {
+    let target = U256::MAX / U256::from(difficulty.max(1));
+    let mut bytes = [0u8; 32];
+    target.to_big_endian(&mut bytes);
+    bytes
+}
+
 #[cfg(test)]
 pub mod test {
     use super::*;
@@ -161,6 +197,26 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn validate_bytes_before_nonce() {
+        let (mut header, _) = get_header();
+        header.nonce = 1;
+        let mut hasher = BlockHeaderSha3::new(header).unwrap();
+        for _ in 0..100 {
+            let mut bytes = hasher.bytes_before_nonce();
+            bytes.extend_from_slice(&hasher.nonce.to_le_bytes());
+            bytes.extend_from_slice(hasher.pow_bytes());
+            let hash = Sha3_256::digest(&Sha3_256::digest(&bytes));
+            let difficulty = big_endian_difficulty(&hash);
+            assert_eq!(difficulty, hasher.difficulty());
+            assert!(
+                hash.as_slice() <= &big_endian_target(difficulty)[..],
+                "hash must meet the target of its own difficulty"
+            );
+            hasher.inc_nonce();
+        }
+    }
+
     #[test]
     fn validate_timestamp_difficulty() {
         let (mut header, mut core_header) = get_header();
@@ -28,6 +28,8 @@ pub enum MinerError {
     GrpcStatus(#[from] tonic::Status),
     #[error("Connection error: {0}")]
     GrpcConnection(#[from] tonic::transport::Error),
+    #[error("Invalid gRPC admin token: {0}")]
+    InvalidAdminToken(#[from] tonic::metadata::errors::InvalidMetadataValue),
     #[error("Node not ready")]
     NodeNotReady,
     #[error("Blockchain reached specified height {0}, mining will be stopped")]
@@ -40,6 +42,13 @@ pub enum MinerError {
     BlockHeader(String),
     #[error("Conversion error: {0}")]
     Conversion(String),
+    #[error("Invalid mining active hours '{0}', expected HH:MM-HH:MM")]
+    InvalidActiveHours(String),
+    #[error("Mining {0}")]
+    MiningPaused(String),
+    #[error("GPU error: {0}")]
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    Gpu(String),
 }
 
 pub fn err_empty(name: &str) -> MinerError {
@@ -0,0 +1,61 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+use crate::{config::MinerConfig, miner::MiningReport};
+use crossbeam::channel::Sender;
+use log::*;
+use std::{fmt, task::Waker};
+use tari_app_grpc::tari_rpc::BlockHeader;
+
+/// The miner was compiled without the `gpu` feature so there are no GPU devices to mine on
+#[derive(Clone)]
+pub enum GpuDevice {}
+
+impl GpuDevice {
+    pub fn temperature(&self) -> Option<f32> {
+        match *self {}
+    }
+}
+
+impl fmt::Display for GpuDevice {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {}
+    }
+}
+
+pub fn init_devices(config: &MinerConfig) -> Vec<GpuDevice> {
+    if config.mine_with_gpu {
+        warn!("GPU mining is enabled but the miner was compiled without the `gpu` feature, mining on the CPU only");
+    }
+    Vec::new()
+}
+
+pub fn mining_task(
+    device: GpuDevice,
+    _header: BlockHeader,
+    _target_difficulty: u64,
+    _sender: Sender<MiningReport>,
+    _waker: Waker,
+    _miner: usize,
+) {
+    match device {}
+}
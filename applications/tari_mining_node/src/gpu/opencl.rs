@@ -0,0 +1,316 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+use crate::{
+    config::MinerConfig,
+    difficulty::{big_endian_target, BlockHeaderSha3},
+    errors::MinerError,
+    miner::MiningReport,
+};
+use crossbeam::channel::{Sender, TrySendError};
+use log::*;
+use ocl::{flags, Buffer, Device, Platform, ProQue};
+use std::{
+    collections::HashMap,
+    fmt,
+    fs,
+    process::Command,
+    task::Waker,
+    time::{Duration, Instant},
+};
+use tari_app_grpc::{conversions::timestamp, tari_rpc::BlockHeader};
+
+const KERNEL_SOURCE: &str = include_str!("sha3.cl");
+// Must match MAX_MESSAGE_LEN in the kernel
+const MAX_MESSAGE_LEN: usize = 512;
+// Number of nonces hashed by a single kernel launch unless configured otherwise
+const DEFAULT_WORK_SIZE: usize = 1 << 20;
+// How often GPU mining threads are reporting / checking context
+const REPORTING_INTERVAL: Duration = Duration::from_secs(2);
+// Querying the temperature is slow, so it is refreshed less often
+const TEMPERATURE_INTERVAL: Duration = Duration::from_secs(30);
+
+impl From<ocl::Error> for MinerError {
+    fn from(err: ocl::Error) -> Self {
+        MinerError::Gpu(err.to_string())
+    }
+}
+
+/// OpenCL GPU device with the mining kernel compiled for it
+#[derive(Clone)]
+pub struct GpuDevice {
+    index: usize,
+    name: String,
+    vendor: String,
+    /// Index of the device among the devices of the same vendor, used to look up its temperature
+    vendor_index: usize,
+    work_size: usize,
+    pro_que: ProQue,
+}
+
+impl GpuDevice {
+    /// Device temperature in degrees Celsius, when exposed by the driver (nvidia-smi for NVIDIA, hwmon for AMD)
+    pub fn temperature(&self) -> Option<f32> {
+        let vendor = self.vendor.to_lowercase();
+        if vendor.contains("nvidia") {
+            let output = Command::new("nvidia-smi")
+                .args(&[
+                    "--query-gpu=temperature.gpu",
+                    "--format=csv,noheader,nounits",
+                    "-i",
+                    &self.vendor_index.to_string(),
+                ])
+                .output()
+                .ok()?;
+            String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+        } else if vendor.contains("amd") || vendor.contains("advanced micro devices") {
+            let hwmon = fs::read_dir(format!("/sys/class/drm/card{}/device/hwmon", self.vendor_index))
+                .ok()?
+                .filter_map(Result::ok)
+                .next()?;
+            let millidegrees: f32 = fs::read_to_string(hwmon.path().join("temp1_input"))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()?;
+            Some(millidegrees / 1000.0)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for GpuDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GPU {} ({})", self.index, self.name)
+    }
+}
+
+/// Enumerates the GPU devices of all OpenCL platforms and compiles the mining kernel for the configured ones. Returns
+/// no devices when GPU mining is disabled or no usable device is found, so the miner falls back to the CPU.
+pub fn init_devices(config: &MinerConfig) -> Vec<GpuDevice> {
+    if !config.mine_with_gpu {
+        return Vec::new();
+    }
+    let mut vendor_counts = HashMap::<String, usize>::new();
+    let mut devices = Vec::new();
+    let available = Platform::list().into_iter().flat_map(|platform| {
+        Device::list(platform, Some(flags::DEVICE_TYPE_GPU))
+            .unwrap_or_default()
+            .into_iter()
+            .map(move |device| (platform, device))
+    });
+    for (index, (platform, device)) in available.enumerate() {
+        let name = device.name().unwrap_or_else(|_| "unknown".to_string());
+        let vendor = device.vendor().unwrap_or_default();
+        let vendor_count = vendor_counts.entry(vendor.clone()).or_insert(0);
+        let vendor_index = *vendor_count;
+        *vendor_count += 1;
+        info!("Found GPU {}: {} ({})", index, name, vendor);
+
+        let position = if config.gpu_devices.is_empty() {
+            Some(index)
+        } else {
+            config.gpu_devices.iter().position(|i| *i == index)
+        };
+        let position = match position {
+            Some(position) => position,
+            None => continue,
+        };
+        let work_size = config
+            .gpu_work_sizes
+            .get(position)
+            .copied()
+            .unwrap_or(DEFAULT_WORK_SIZE);
+        match ProQue::builder()
+            .platform(platform)
+            .device(device)
+            .src(KERNEL_SOURCE)
+            .dims(work_size)
+            .build()
+        {
+            Ok(pro_que) => {
+                info!("Mining on GPU {} with work size {}", index, work_size);
+                devices.push(GpuDevice {
+                    index,
+                    name,
+                    vendor,
+                    vendor_index,
+                    work_size,
+                    pro_que,
+                });
+            },
+            Err(err) => warn!("Failed to compile mining kernel for GPU {}: {}", index, err),
+        }
+    }
+    if devices.is_empty() {
+        warn!("No GPU devices available for mining, mining on the CPU only");
+    }
+    devices
+}
+
+/// GPU miner starts with a random nonce and hashes consecutive batches of nonces on the device until it finds a header
+/// hash that meets the desired target. Reports are sent the same way as from the CPU mining threads.
+pub fn mining_task(
+    device: GpuDevice,
+    header: BlockHeader,
+    target_difficulty: u64,
+    sender: Sender<MiningReport>,
+    waker: Waker,
+    miner: usize,
+) {
+    info!("Mining thread {} started on {}", miner, device);
+    if let Err(err) = mine(&device, header, target_difficulty, &sender, &waker, miner) {
+        error!("Mining thread {} on {} failed: {}", miner, device, err);
+    }
+    info!("Mining thread {} stopped", miner);
+}
+
+fn mine(
+    device: &GpuDevice,
+    header: BlockHeader,
+    target_difficulty: u64,
+    sender: &Sender<MiningReport>,
+    waker: &Waker,
+    miner: usize,
+) -> Result<(), MinerError> {
+    let start = Instant::now();
+    let mut hasher = BlockHeaderSha3::new(header)?;
+    hasher.random_nonce();
+    let (message, nonce_offset) = header_message(&hasher)?;
+    let target = big_endian_target(target_difficulty);
+
+    let queue = device.pro_que.queue().clone();
+    let message_buffer = Buffer::<u8>::builder()
+        .queue(queue.clone())
+        .flags(flags::MEM_READ_ONLY)
+        .len(message.len())
+        .copy_host_slice(&message)
+        .build()?;
+    let target_buffer = Buffer::<u8>::builder()
+        .queue(queue.clone())
+        .flags(flags::MEM_READ_ONLY)
+        .len(target.len())
+        .copy_host_slice(&target)
+        .build()?;
+    let result_buffer = Buffer::<u64>::builder().queue(queue).len(2).fill_val(0u64).build()?;
+    let kernel = device
+        .pro_que
+        .kernel_builder("sha3_mine")
+        .arg(&message_buffer)
+        .arg(message.len() as u32)
+        .arg(nonce_offset as u32)
+        .arg(hasher.nonce)
+        .arg(&target_buffer)
+        .arg(&result_buffer)
+        .build()?;
+
+    let mut result = [0u64; 2];
+    let mut last_report = Instant::now();
+    let mut temperature = device.temperature();
+    let mut last_temperature = Instant::now();
+    loop {
+        kernel.set_arg(3, hasher.nonce)?;
+        unsafe {
+            kernel.enq()?;
+        }
+        result_buffer.read(&mut result[..]).enq()?;
+        hasher.hashes = hasher.hashes.saturating_add(device.work_size as u64);
+        if result[0] != 0 {
+            hasher.nonce = result[1];
+            let difficulty = hasher.difficulty();
+            if difficulty >= target_difficulty {
+                debug!(
+                    "Miner {} found nonce {} with matching difficulty {}",
+                    miner, hasher.nonce, difficulty
+                );
+                if let Err(err) = sender.try_send(MiningReport {
+                    miner,
+                    difficulty,
+                    hashes: hasher.hashes,
+                    elapsed: start.elapsed(),
+                    height: hasher.height(),
+                    last_nonce: hasher.nonce,
+                    header: Some(hasher.into_header()),
+                    target_difficulty,
+                    device: Some(device.to_string()),
+                    temperature,
+                }) {
+                    error!("Miner {} failed to send report: {}", miner, err);
+                }
+                waker.wake_by_ref();
+                return Ok(());
+            }
+            warn!(
+                "Miner {} on {} returned nonce {} with difficulty {} below target {}",
+                miner, device, hasher.nonce, difficulty, target_difficulty
+            );
+            result = [0u64; 2];
+            result_buffer.write(&result[..]).enq()?;
+        }
+        hasher.nonce = hasher.nonce.wrapping_add(device.work_size as u64);
+
+        if last_report.elapsed() >= REPORTING_INTERVAL {
+            last_report = Instant::now();
+            if last_temperature.elapsed() >= TEMPERATURE_INTERVAL {
+                last_temperature = Instant::now();
+                temperature = device.temperature();
+            }
+            let res = sender.try_send(MiningReport {
+                miner,
+                difficulty: 0,
+                hashes: hasher.hashes,
+                elapsed: start.elapsed(),
+                header: None,
+                last_nonce: hasher.nonce,
+                height: hasher.height(),
+                target_difficulty,
+                device: Some(device.to_string()),
+                temperature,
+            });
+            waker.wake_by_ref();
+            trace!("Reporting from {} result {:?}", miner, res);
+            if let Err(TrySendError::Disconnected(_)) = res {
+                info!("Mining thread {} disconnected", miner);
+                return Ok(());
+            }
+            hasher.set_forward_timestamp(timestamp().seconds as u64);
+            let (message, _) = header_message(&hasher)?;
+            message_buffer.write(&message[..]).enq()?;
+        }
+    }
+}
+
+/// Header hash pre-image with a zero nonce, along with the offset of the nonce
+fn header_message(hasher: &BlockHeaderSha3) -> Result<(Vec<u8>, usize), MinerError> {
+    let mut message = hasher.bytes_before_nonce();
+    let nonce_offset = message.len();
+    message.extend_from_slice(&[0u8; 8]);
+    message.extend_from_slice(hasher.pow_bytes());
+    if message.len() > MAX_MESSAGE_LEN {
+        return Err(MinerError::Gpu(format!(
+            "Header of {} bytes is too long for the mining kernel",
+            message.len()
+        )));
+    }
+    Ok((message, nonce_offset))
+}
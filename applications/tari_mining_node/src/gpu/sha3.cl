@@ -0,0 +1,161 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+// Tari SHA3 proof of work: sha3_256(sha3_256(header bytes before nonce || nonce || pow bytes)). Every work item hashes
+// one nonce and records it if the big endian hash is less than or equal to the target.
+
+#define SHA3_256_RATE 136
+#define SHA3_256_LEN 32
+// Must match MAX_MESSAGE_LEN in opencl.rs
+#define MAX_MESSAGE_LEN 512
+
+__constant ulong KECCAK_ROUND_CONSTANTS[24] = {
+    0x0000000000000001UL, 0x0000000000008082UL, 0x800000000000808aUL, 0x8000000080008000UL,
+    0x000000000000808bUL, 0x0000000080000001UL, 0x8000000080008081UL, 0x8000000000008009UL,
+    0x000000000000008aUL, 0x0000000000000088UL, 0x0000000080008009UL, 0x000000008000000aUL,
+    0x000000008000808bUL, 0x800000000000008bUL, 0x8000000000008089UL, 0x8000000000008003UL,
+    0x8000000000008002UL, 0x8000000000000080UL, 0x000000000000800aUL, 0x800000008000000aUL,
+    0x8000000080008081UL, 0x8000000000008080UL, 0x0000000080000001UL, 0x8000000080008008UL,
+};
+
+__constant uint KECCAK_ROTATIONS[24] = {
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+};
+
+__constant uint KECCAK_PI_LANES[24] = {
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+};
+
+void keccak_f1600(ulong state[25]) {
+    ulong c[5];
+    ulong t;
+    for (uint round = 0; round < 24; round++) {
+        // Theta
+        for (uint i = 0; i < 5; i++) {
+            c[i] = state[i] ^ state[i + 5] ^ state[i + 10] ^ state[i + 15] ^ state[i + 20];
+        }
+        for (uint i = 0; i < 5; i++) {
+            t = c[(i + 4) % 5] ^ rotate(c[(i + 1) % 5], (ulong)1);
+            for (uint j = 0; j < 25; j += 5) {
+                state[j + i] ^= t;
+            }
+        }
+        // Rho and pi
+        t = state[1];
+        for (uint i = 0; i < 24; i++) {
+            uint j = KECCAK_PI_LANES[i];
+            c[0] = state[j];
+            state[j] = rotate(t, (ulong)KECCAK_ROTATIONS[i]);
+            t = c[0];
+        }
+        // Chi
+        for (uint j = 0; j < 25; j += 5) {
+            for (uint i = 0; i < 5; i++) {
+                c[i] = state[j + i];
+            }
+            for (uint i = 0; i < 5; i++) {
+                state[j + i] ^= (~c[(i + 1) % 5]) & c[(i + 2) % 5];
+            }
+        }
+        // Iota
+        state[0] ^= KECCAK_ROUND_CONSTANTS[round];
+    }
+}
+
+ulong load_le64(const uchar *bytes) {
+    ulong value = 0;
+    for (uint i = 0; i < 8; i++) {
+        value |= ((ulong)bytes[i]) << (8 * i);
+    }
+    return value;
+}
+
+void sha3_256(const uchar *message, uint len, uchar *hash) {
+    ulong state[25];
+    for (uint i = 0; i < 25; i++) {
+        state[i] = 0;
+    }
+
+    uint offset = 0;
+    while (len - offset >= SHA3_256_RATE) {
+        for (uint i = 0; i < SHA3_256_RATE / 8; i++) {
+            state[i] ^= load_le64(message + offset + 8 * i);
+        }
+        keccak_f1600(state);
+        offset += SHA3_256_RATE;
+    }
+
+    // The last block is padded with the SHA3 domain separator
+    uchar block[SHA3_256_RATE];
+    for (uint i = 0; i < SHA3_256_RATE; i++) {
+        block[i] = 0;
+    }
+    for (uint i = 0; i < len - offset; i++) {
+        block[i] = message[offset + i];
+    }
+    block[len - offset] ^= 0x06;
+    block[SHA3_256_RATE - 1] ^= 0x80;
+    for (uint i = 0; i < SHA3_256_RATE / 8; i++) {
+        state[i] ^= load_le64(block + 8 * i);
+    }
+    keccak_f1600(state);
+
+    for (uint i = 0; i < SHA3_256_LEN; i++) {
+        hash[i] = (uchar)(state[i / 8] >> (8 * (i % 8)));
+    }
+}
+
+__kernel void sha3_mine(
+    __global const uchar *message,
+    const uint message_len,
+    const uint nonce_offset,
+    const ulong start_nonce,
+    __global const uchar *target,
+    __global ulong *result
+) {
+    ulong nonce = start_nonce + get_global_id(0);
+
+    uchar input[MAX_MESSAGE_LEN];
+    for (uint i = 0; i < message_len; i++) {
+        input[i] = message[i];
+    }
+    for (uint i = 0; i < 8; i++) {
+        input[nonce_offset + i] = (uchar)(nonce >> (8 * i));
+    }
+
+    uchar hash[SHA3_256_LEN];
+    sha3_256(input, message_len, hash);
+    uchar pow_hash[SHA3_256_LEN];
+    sha3_256(hash, SHA3_256_LEN, pow_hash);
+
+    for (uint i = 0; i < SHA3_256_LEN; i++) {
+        if (pow_hash[i] < target[i]) {
+            break;
+        }
+        if (pow_hash[i] > target[i]) {
+            return;
+        }
+    }
+    // Any nonce that meets the target will do, the host verifies it
+    result[1] = nonce;
+    result[0] = 1;
+}
@@ -27,12 +27,16 @@ use log::*;
 mod config;
 mod difficulty;
 mod errors;
+mod gpu;
 mod miner;
+mod schedule;
 mod stratum;
 mod utils;
 
 use crate::{
+    gpu::GpuDevice,
     miner::MiningReport,
+    schedule::{MiningSchedule, MiningState},
     stratum::{stratum_controller::controller::Controller, stratum_miner::miner::StratumMiner},
 };
 use errors::{err_empty, MinerError};
@@ -46,12 +50,15 @@ use std::{
     thread,
     time::Instant,
 };
-use tari_app_grpc::tari_rpc::{base_node_client::BaseNodeClient, wallet_client::WalletClient};
+use tari_app_grpc::{
+    authentication::{BaseNodeAdminClient, ClientAuthInterceptor},
+    tari_rpc::{base_node_client::BaseNodeClient, wallet_client::WalletClient},
+};
 use tari_app_utilities::{initialization::init_configuration, utilities::ExitCodes};
 use tari_common::{configuration::bootstrap::ApplicationType, ConfigBootstrap, DefaultConfigLoader, GlobalConfig};
 use tari_core::blocks::BlockHeader;
 use tokio::{runtime::Runtime, time::sleep};
-use tonic::transport::Channel;
+use tonic::transport::{Channel, Endpoint};
 use utils::{coinbase_request, extract_outputs_and_kernels};
 
 /// Application entry point
@@ -134,11 +141,22 @@ async fn main_inner() -> Result<(), ExitCodes> {
         debug!("mine_on_tip_only is {}", config.mine_on_tip_only);
 
         let (mut node_conn, mut wallet_conn) = connect(&config, &global).await.map_err(ExitCodes::grpc)?;
+        let gpu_devices = gpu::init_devices(&config);
+        let mut schedule = MiningSchedule::new(&config).map_err(|err| ExitCodes::ConfigError(err.to_string()))?;
 
         let mut blocks_found: u64 = 0;
         loop {
             debug!("Starting new mining cycle");
-            match mining_cycle(&mut node_conn, &mut wallet_conn, &config, &bootstrap).await {
+            match mining_cycle(
+                &mut node_conn,
+                &mut wallet_conn,
+                &config,
+                &bootstrap,
+                &gpu_devices,
+                &mut schedule,
+            )
+            .await
+            {
                 err @ Err(MinerError::GrpcConnection(_)) | err @ Err(MinerError::GrpcStatus(_)) => {
                     // Any GRPC error we will try to reconnect with a standard delay
                     error!("Connection error: {:?}", err);
@@ -162,6 +180,10 @@ async fn main_inner() -> Result<(), ExitCodes> {
                     info!("Prescribed blockchain height {} reached. Aborting ...", h);
                     return Ok(());
                 },
+                Err(MinerError::MiningPaused(_)) => {
+                    // State changes are logged by the schedule
+                    sleep(config.wait_timeout()).await;
+                },
                 Err(MinerError::MinerLostBlock(h)) => {
                     info!("Height {} already mined by other node. Restarting ...", h);
                 },
@@ -188,10 +210,12 @@ async fn main_inner() -> Result<(), ExitCodes> {
 async fn connect(
     config: &MinerConfig,
     global: &GlobalConfig,
-) -> Result<(BaseNodeClient<Channel>, WalletClient<Channel>), MinerError> {
+) -> Result<(BaseNodeAdminClient, WalletClient<Channel>), MinerError> {
     let base_node_addr = config.base_node_addr(global);
     info!("Connecting to base node at {}", base_node_addr);
-    let node_conn = BaseNodeClient::connect(base_node_addr.clone()).await?;
+    // Block templates and block submission are admin methods of the base node
+    let auth = ClientAuthInterceptor::new(global.grpc_base_node_admin_token.as_deref())?;
+    let node_conn = BaseNodeClient::with_interceptor(Endpoint::new(base_node_addr)?.connect().await?, auth);
     let wallet_addr = config.wallet_addr(global);
     info!("Connecting to wallet at {}", wallet_addr);
     let wallet_conn = WalletClient::connect(wallet_addr.clone()).await?;
@@ -200,11 +224,15 @@ async fn connect(
 }
 
 async fn mining_cycle(
-    node_conn: &mut BaseNodeClient<Channel>,
+    node_conn: &mut BaseNodeAdminClient,
     wallet_conn: &mut WalletClient<Channel>,
     config: &MinerConfig,
     bootstrap: &ConfigBootstrap,
+    gpu_devices: &[GpuDevice],
+    schedule: &mut MiningSchedule,
 ) -> Result<bool, MinerError> {
+    check_schedule(node_conn, config, gpu_devices, schedule).await?;
+
     // 1. Receive new block template
     let template = node_conn
         .get_new_block_template(config.pow_algo_request())
@@ -246,7 +274,12 @@ async fn mining_cycle(
     let header = block.clone().header.ok_or_else(|| err_empty("block.header"))?;
 
     // 4. Initialize miner and start receiving mining statuses in the loop
-    let mut reports = Miner::init_mining(header.clone(), target_difficulty, config.num_mining_threads);
+    let mut reports = Miner::init_mining(
+        header.clone(),
+        target_difficulty,
+        config.num_mining_threads,
+        gpu_devices.to_vec(),
+    );
     let mut reporting_timeout = Instant::now();
     let mut block_submitted = false;
     while let Some(report) = reports.next().await {
@@ -289,8 +322,11 @@ async fn mining_cycle(
         } else {
             display_report(&report, config).await;
         }
-        if config.mine_on_tip_only && reporting_timeout.elapsed() > config.validate_tip_timeout_sec() {
-            validate_tip(node_conn, report.height, bootstrap.mine_until_height).await?;
+        if reporting_timeout.elapsed() > config.validate_tip_timeout_sec() {
+            if config.mine_on_tip_only {
+                validate_tip(node_conn, report.height, bootstrap.mine_until_height).await?;
+            }
+            check_schedule(node_conn, config, gpu_devices, schedule).await?;
             reporting_timeout = Instant::now();
         }
     }
@@ -301,6 +337,17 @@ async fn mining_cycle(
 
 async fn display_report(report: &MiningReport, config: &MinerConfig) {
     let hashrate = report.hashes as f64 / report.elapsed.as_micros() as f64;
+    if let Some(device) = &report.device {
+        let temperature = report
+            .temperature
+            .map(|t| format!(" at {:.0}°C", t))
+            .unwrap_or_default();
+        debug!(
+            "Miner {} on {} reported {:.2}MH/s{}. Height: {}. Target: {})",
+            report.miner, device, hashrate, temperature, report.height, report.target_difficulty,
+        );
+        return;
+    }
     debug!(
         "Miner {} reported {:.2}MH/s with total {:.2}MH/s over {} threads. Height: {}. Target: {})",
         report.miner,
@@ -312,9 +359,32 @@ async fn display_report(report: &MiningReport, config: &MinerConfig) {
     );
 }
 
+/// Returns an error while the schedule doesn't allow mining. The base node sync state is only taken into account when
+/// mining on tip only.
+async fn check_schedule(
+    node_conn: &mut BaseNodeAdminClient,
+    config: &MinerConfig,
+    gpu_devices: &[GpuDevice],
+    schedule: &mut MiningSchedule,
+) -> Result<(), MinerError> {
+    let node_synced = if config.mine_on_tip_only {
+        node_conn
+            .get_tip_info(tari_app_grpc::tari_rpc::Empty {})
+            .await?
+            .into_inner()
+            .initial_sync_achieved
+    } else {
+        true
+    };
+    match schedule.update(node_synced, gpu_devices) {
+        MiningState::Active => Ok(()),
+        state => Err(MinerError::MiningPaused(state.to_string())),
+    }
+}
+
 /// If config
 async fn validate_tip(
-    node_conn: &mut BaseNodeClient<Channel>,
+    node_conn: &mut BaseNodeAdminClient,
     height: u64,
     mine_until_height: Option<u64>,
 ) -> Result<(), MinerError> {
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
-use super::difficulty::BlockHeaderSha3;
+use super::{difficulty::BlockHeaderSha3, gpu, gpu::GpuDevice};
 use crossbeam::channel::{bounded, Select, Sender, TrySendError};
 use futures::Stream;
 use log::*;
@@ -52,6 +52,10 @@ pub struct MiningReport {
     pub header: Option<BlockHeader>,
     pub height: u64,
     pub last_nonce: u64,
+    /// GPU device of the reporting thread, not set for CPU threads
+    pub device: Option<String>,
+    /// GPU device temperature in degrees Celsius, when available
+    pub temperature: Option<f32>,
 }
 
 /// Miner is starting number of mining threads and implements Stream for async reports polling
@@ -60,21 +64,32 @@ pub struct Miner {
     threads: Vec<JoinHandle<()>>,
     channels: Vec<crossbeam::channel::Receiver<MiningReport>>,
     num_threads: usize,
+    gpu_devices: Vec<GpuDevice>,
     header: BlockHeader,
     target_difficulty: u64,
 }
 
 impl Miner {
-    pub fn init_mining(header: BlockHeader, target_difficulty: u64, num_threads: usize) -> Self {
+    pub fn init_mining(
+        header: BlockHeader,
+        target_difficulty: u64,
+        num_threads: usize,
+        gpu_devices: Vec<GpuDevice>,
+    ) -> Self {
         Self {
             threads: vec![],
             channels: vec![],
             header,
             num_threads,
+            gpu_devices,
             target_difficulty,
         }
     }
 
+    fn total_threads(&self) -> usize {
+        self.num_threads + self.gpu_devices.len()
+    }
+
     // Start mining threads with async context waker
     fn start_threads(&mut self, ctx: &Context<'_>) {
         let miners = (0..self.num_threads)
@@ -96,8 +111,24 @@ impl Miner {
                     .expect("Failed to create mining thread");
                 (handle, rx)
             });
+        let (mut threads, mut channels): (Vec<_>, Vec<_>) = miners.unzip();
+
+        // GPU threads are mostly waiting on the device, so they get the default stack size
+        for (i, device) in self.gpu_devices.iter().enumerate() {
+            let (tx, rx) = bounded(1);
+            let header = self.header.clone();
+            let waker = ctx.waker().clone();
+            let difficulty = self.target_difficulty;
+            let device = device.clone();
+            let miner = self.num_threads + i;
+            let handle = thread::Builder::new()
+                .name(format!("gpu-miner-{}", i))
+                .spawn(move || gpu::mining_task(device, header, difficulty, tx, waker, miner))
+                .expect("Failed to create mining thread");
+            threads.push(handle);
+            channels.push(rx);
+        }
 
-        let (threads, channels) = miners.unzip();
         self.threads = threads;
         self.channels = channels;
     }
@@ -109,14 +140,15 @@ impl Stream for Miner {
     fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         trace!("Polling Miner");
         // First poll would start all the threads passing async context waker
-        if self.threads.is_empty() && self.num_threads > 0 {
+        if self.threads.is_empty() && self.total_threads() > 0 {
             debug!(
                 "Starting {} mining threads for target difficulty {}",
-                self.num_threads, self.target_difficulty
+                self.total_threads(),
+                self.target_difficulty
             );
             self.start_threads(ctx);
             return Poll::Pending;
-        } else if self.num_threads == 0 {
+        } else if self.total_threads() == 0 {
             error!("Cannot mine: no mining threads");
             return Poll::Ready(None);
         } else if self.channels.is_empty() {
@@ -185,6 +217,8 @@ pub fn mining_task(
                 last_nonce: hasher.nonce,
                 header: Some(hasher.into_header()),
                 target_difficulty,
+                device: None,
+                temperature: None,
             }) {
                 error!("Miner {} failed to send report: {}", miner, err);
             }
@@ -202,6 +236,8 @@ pub fn mining_task(
                 last_nonce: hasher.nonce,
                 height: hasher.height(),
                 target_difficulty,
+                device: None,
+                temperature: None,
             });
             waker.clone().wake();
             trace!("Reporting from {} result {:?}", miner, res);
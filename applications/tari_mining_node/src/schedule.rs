@@ -0,0 +1,212 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! Mining schedule
+//!
+//! Decides whether the miner should be mining, based on the sync state of the base node, the configured daily active
+//! hours and the temperature of the CPU and GPU devices. Mining state changes are logged.
+
+use crate::{config::MinerConfig, errors::MinerError, gpu::GpuDevice};
+use chrono::{Local, NaiveTime};
+use log::*;
+use std::{fmt, fs, mem, str::FromStr};
+
+// Mining resumes once the temperature is this many degrees below the limit, so it doesn't flap around the limit
+const TEMPERATURE_HYSTERESIS: f32 = 5.0;
+const THERMAL_ZONES_PATH: &str = "/sys/class/thermal";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MiningState {
+    Active,
+    NodeSyncing,
+    OutsideActiveHours,
+    Overheated(f32),
+}
+
+impl fmt::Display for MiningState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MiningState::Active => write!(f, "active"),
+            MiningState::NodeSyncing => write!(f, "paused, base node is syncing"),
+            MiningState::OutsideActiveHours => write!(f, "paused, outside of active hours"),
+            MiningState::Overheated(temperature) => write!(f, "paused, temperature is {:.0}°C", temperature),
+        }
+    }
+}
+
+/// Daily window in local time during which mining is allowed, e.g. `22:00-06:00`. The window wraps around midnight
+/// when it ends before it starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl ActiveHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for ActiveHours {
+    type Err = MinerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || MinerError::InvalidActiveHours(s.to_string());
+        let (start, end) = s.split_once('-').ok_or_else(err)?;
+        let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").map_err(|_| err())?;
+        let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").map_err(|_| err())?;
+        Ok(Self { start, end })
+    }
+}
+
+pub struct MiningSchedule {
+    active_hours: Option<ActiveHours>,
+    max_temperature: Option<f32>,
+    state: MiningState,
+}
+
+impl MiningSchedule {
+    pub fn new(config: &MinerConfig) -> Result<Self, MinerError> {
+        let active_hours = config
+            .mining_active_hours
+            .as_deref()
+            .map(ActiveHours::from_str)
+            .transpose()?;
+        Ok(Self {
+            active_hours,
+            max_temperature: config.max_temperature,
+            state: MiningState::Active,
+        })
+    }
+
+    /// Evaluates the schedule for the current local time and device temperatures, logging when the state changes
+    pub fn update(&mut self, node_synced: bool, gpu_devices: &[GpuDevice]) -> MiningState {
+        let temperature = self.max_temperature.and_then(|_| max_temperature(gpu_devices));
+        let state = self.evaluate(node_synced, Local::now().time(), temperature);
+        if mem::discriminant(&state) != mem::discriminant(&self.state) {
+            match state {
+                MiningState::Active => info!("Mining resumed"),
+                _ => info!("Mining {}", state),
+            }
+        }
+        self.state = state;
+        state
+    }
+
+    fn evaluate(&self, node_synced: bool, time: NaiveTime, temperature: Option<f32>) -> MiningState {
+        if !node_synced {
+            return MiningState::NodeSyncing;
+        }
+        if let Some(active_hours) = self.active_hours {
+            if !active_hours.contains(time) {
+                return MiningState::OutsideActiveHours;
+            }
+        }
+        if let (Some(max_temperature), Some(temperature)) = (self.max_temperature, temperature) {
+            let limit = match self.state {
+                MiningState::Overheated(_) => max_temperature - TEMPERATURE_HYSTERESIS,
+                _ => max_temperature,
+            };
+            if temperature >= limit {
+                return MiningState::Overheated(temperature);
+            }
+        }
+        MiningState::Active
+    }
+}
+
+/// Highest temperature of the CPU thermal zones and the GPU devices, when exposed by the system
+fn max_temperature(gpu_devices: &[GpuDevice]) -> Option<f32> {
+    gpu_devices
+        .iter()
+        .filter_map(GpuDevice::temperature)
+        .chain(cpu_temperature())
+        .fold(None, |max: Option<f32>, t| Some(max.map_or(t, |max| max.max(t))))
+}
+
+fn cpu_temperature() -> Option<f32> {
+    fs::read_dir(THERMAL_ZONES_PATH)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|entry| fs::read_to_string(entry.path().join("temp")).ok())
+        .filter_map(|millidegrees| millidegrees.trim().parse::<f32>().ok())
+        .map(|millidegrees| millidegrees / 1000.0)
+        .fold(None, |max: Option<f32>, t| Some(max.map_or(t, |max| max.max(t))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn time(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms(hour, min, 0)
+    }
+
+    #[test]
+    fn active_hours() {
+        let hours = ActiveHours::from_str("08:30-17:00").unwrap();
+        assert!(!hours.contains(time(8, 0)));
+        assert!(hours.contains(time(8, 30)));
+        assert!(!hours.contains(time(17, 0)));
+
+        let hours = ActiveHours::from_str("22:00 - 06:00").unwrap();
+        assert!(hours.contains(time(23, 0)));
+        assert!(hours.contains(time(5, 59)));
+        assert!(!hours.contains(time(12, 0)));
+
+        assert!(ActiveHours::from_str("22:00").is_err());
+        assert!(ActiveHours::from_str("25:00-06:00").is_err());
+    }
+
+    #[test]
+    fn schedule_state() {
+        let mut schedule = MiningSchedule {
+            active_hours: Some(ActiveHours::from_str("22:00-06:00").unwrap()),
+            max_temperature: Some(80.0),
+            state: MiningState::Active,
+        };
+        assert_eq!(schedule.evaluate(false, time(23, 0), None), MiningState::NodeSyncing);
+        assert_eq!(
+            schedule.evaluate(true, time(12, 0), None),
+            MiningState::OutsideActiveHours
+        );
+        assert_eq!(schedule.evaluate(true, time(23, 0), Some(70.0)), MiningState::Active);
+        assert_eq!(
+            schedule.evaluate(true, time(23, 0), Some(82.0)),
+            MiningState::Overheated(82.0)
+        );
+
+        // Mining resumes only once cooled down below the hysteresis
+        schedule.state = MiningState::Overheated(82.0);
+        assert_eq!(
+            schedule.evaluate(true, time(23, 0), Some(77.0)),
+            MiningState::Overheated(77.0)
+        );
+        assert_eq!(schedule.evaluate(true, time(23, 0), Some(74.0)), MiningState::Active);
+    }
+}
@@ -25,11 +25,17 @@ use crate::stratum::{error::Error, stratum_types as types, stream::Stream};
 use log::*;
 use std::{
     self,
+    cmp,
     io::{BufRead, ErrorKind, Write},
     sync::mpsc,
     thread,
 };
 
+/// The initial number of seconds to wait before reconnecting to the stratum server
+const MIN_SERVER_RETRY_INTERVAL: i64 = 5;
+/// The reconnection interval doubles on every failed attempt up to this number of seconds
+const MAX_SERVER_RETRY_INTERVAL: i64 = 60;
+
 pub struct Controller {
     server_url: String,
     server_login: Option<String>,
@@ -40,6 +46,7 @@ pub struct Controller {
     pub tx: mpsc::Sender<types::client_message::ClientMessage>,
     miner_tx: mpsc::Sender<types::miner_message::MinerMessage>,
     last_request_id: String,
+    miner_paused: bool,
 }
 
 // fn invalid_error_response() -> types::RpcError {
@@ -68,6 +75,7 @@ impl Controller {
             rx,
             miner_tx,
             last_request_id: "".to_string(),
+            miner_paused: true,
         })
     }
 
@@ -103,14 +111,16 @@ impl Controller {
     }
 
     fn send_message(&mut self, message: &str) -> Result<(), Error> {
-        if self.stream.is_none() {
-            return Err(Error::Connection(String::from("No server connection")));
-        }
+        let stream = self
+            .stream
+            .as_mut()
+            .ok_or_else(|| Error::Connection(String::from("No server connection")))?;
         debug!("sending request: {}", message);
-        let _ = self.stream.as_mut().unwrap().write(message.as_bytes());
-        let _ = self.stream.as_mut().unwrap().write(b"\n");
-        let _ = self.stream.as_mut().unwrap().flush();
-        Ok(())
+        stream
+            .write_all(message.as_bytes())
+            .and_then(|_| stream.write_all(b"\n"))
+            .and_then(|_| stream.flush())
+            .map_err(|e| Error::Connection(format!("Failed to send message to stratum server: {}", e)))
     }
 
     fn send_message_get_job_template(&mut self) -> Result<(), Error> {
@@ -191,22 +201,32 @@ impl Controller {
     }
 
     fn send_miner_job(&mut self, job: types::job_params::JobParams) -> Result<(), Error> {
-        let miner_message = types::miner_message::MinerMessage::ReceivedJob(
-            job.height,
-            job.job_id.parse::<u64>().unwrap(),
-            job.target.parse::<u64>().unwrap(),
-            job.blob,
-        );
-        self.miner_tx.send(miner_message).map_err(|e| e.into())
+        let job_id = job
+            .job_id
+            .parse::<u64>()
+            .map_err(|e| Error::Json(format!("Invalid job id '{}': {}", job.job_id, e)))?;
+        let target = job
+            .target
+            .parse::<u64>()
+            .map_err(|e| Error::Json(format!("Invalid job target '{}': {}", job.target, e)))?;
+        let miner_message = types::miner_message::MinerMessage::ReceivedJob(job.height, job_id, target, job.blob);
+        self.miner_tx.send(miner_message)?;
+        // The solvers are paused while disconnected and only resume once a fresh job was received from the server
+        if self.miner_paused {
+            self.send_miner_resume()?;
+        }
+        Ok(())
     }
 
     fn send_miner_stop(&mut self) -> Result<(), Error> {
         let miner_message = types::miner_message::MinerMessage::StopJob;
+        self.miner_paused = true;
         self.miner_tx.send(miner_message).map_err(|e| e.into())
     }
 
     fn send_miner_resume(&mut self) -> Result<(), Error> {
         let miner_message = types::miner_message::MinerMessage::ResumeJob;
+        self.miner_paused = false;
         self.miner_tx.send(miner_message).map_err(|e| e.into())
     }
 
@@ -236,8 +256,7 @@ impl Controller {
                 if let Ok(st) = login_response {
                     info!("Successful login to server, worker identifier is {}", st.id);
                     self.last_request_id = st.id;
-                    let _ = self.send_miner_job(st.job);
-                    return Ok(());
+                    return self.send_miner_job(st.job);
                 };
                 let job_response = serde_json::from_value::<types::job_params::JobParams>(result.clone());
                 if let Ok(st) = job_response {
@@ -245,8 +264,7 @@ impl Controller {
                         "Got a new job for height {} with target difficulty {}",
                         st.height, st.target
                     );
-                    let _ = self.send_miner_job(st);
-                    return Ok(());
+                    return self.send_miner_job(st);
                 };
                 let rpc_response = serde_json::from_value::<types::rpc_response::RpcResponse>(result);
                 if let Ok(st) = rpc_response {
@@ -275,7 +293,7 @@ impl Controller {
     #[allow(clippy::cognitive_complexity)]
     pub fn run(mut self) {
         let server_read_interval = 1;
-        let server_retry_interval = 5;
+        let mut server_retry_interval = MIN_SERVER_RETRY_INTERVAL;
         let mut next_server_read = time::get_time().sec + server_read_interval;
         let mut next_server_retry = time::get_time().sec;
         // Request the first job template
@@ -291,7 +309,7 @@ impl Controller {
                 if time::get_time().sec > next_server_retry {
                     if self.try_connect().is_err() {
                         let status = format!(
-                            "Connection Status: Can't establish server connection to {}. Will retry every {} seconds",
+                            "Connection Status: Can't establish server connection to {}. Will retry in {} seconds",
                             self.server_url, server_retry_interval
                         );
                         warn!("{}", status);
@@ -299,9 +317,11 @@ impl Controller {
                     } else {
                         let status = format!("Connection Status: Connected to server at {}.", self.server_url);
                         info!("{}", status);
+                        server_retry_interval = MIN_SERVER_RETRY_INTERVAL;
                     }
                     next_server_retry = time::get_time().sec + server_retry_interval;
                     if self.stream.is_none() {
+                        server_retry_interval = cmp::min(server_retry_interval * 2, MAX_SERVER_RETRY_INTERVAL);
                         thread::sleep(std::time::Duration::from_secs(1));
                         continue;
                     }
@@ -310,8 +330,11 @@ impl Controller {
                 // get new job template
                 if was_disconnected {
                     was_disconnected = false;
-                    let _ = self.send_login();
-                    let _ = self.send_miner_resume();
+                    if let Err(e) = self.send_login() {
+                        error!("Error logging in to stratum server: {:?}", e);
+                        self.stream = None;
+                        continue;
+                    }
                 }
                 // read messages from server
                 if time::get_time().sec > next_server_read {
@@ -25,7 +25,15 @@ use crate::{
     stratum::{stratum_miner::miner::StratumMiner, stratum_types as types},
 };
 use log::*;
-use std::{self, sync::mpsc, thread, time::SystemTime};
+use std::{
+    self,
+    sync::mpsc,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// The interval at which the hashrate of each mining thread is reported
+const HASHRATE_REPORT_INTERVAL: Duration = Duration::from_secs(60);
 
 pub struct Controller {
     rx: mpsc::Receiver<types::miner_message::MinerMessage>,
@@ -35,6 +43,7 @@ pub struct Controller {
     current_job_id: u64,
     current_blob: String,
     keep_alive_time: SystemTime,
+    hashrate_report_time: SystemTime,
 }
 
 impl Controller {
@@ -48,6 +57,7 @@ impl Controller {
             current_job_id: 0,
             current_blob: "".to_string(),
             keep_alive_time: SystemTime::now(),
+            hashrate_report_time: SystemTime::now(),
         })
     }
 
@@ -111,7 +121,23 @@ impl Controller {
                     .unwrap()
                     .send(types::client_message::ClientMessage::KeepAlive);
             }
+            if self.hashrate_report_time.elapsed().unwrap_or_default() >= HASHRATE_REPORT_INTERVAL {
+                self.hashrate_report_time = SystemTime::now();
+                display_hashrate_report(&miner.hashrate_report(), self.current_height);
+            }
             thread::sleep(std::time::Duration::from_millis(100));
         }
     }
 }
+
+fn display_hashrate_report(hashrates: &[f64], height: u64) {
+    for (i, hashrate) in hashrates.iter().enumerate() {
+        info!("Miner {} reported {:.2}MH/s. Height: {}", i, hashrate, height);
+    }
+    info!(
+        "Total hashrate {:.2}MH/s over {} threads. Height: {}",
+        hashrates.iter().sum::<f64>(),
+        hashrates.len(),
+        height
+    );
+}
@@ -34,9 +34,15 @@ use crate::{
 use log::*;
 use std::{
     convert::TryFrom,
-    sync::{mpsc, Arc, RwLock},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc,
+        Arc,
+        RwLock,
+    },
     thread,
     time,
+    time::Instant,
 };
 use tari_core::{
     blocks::BlockHeader,
@@ -49,6 +55,9 @@ pub struct StratumMiner {
     control_txs: Vec<mpsc::Sender<ControlMessage>>,
     solver_loop_txs: Vec<mpsc::Sender<ControlMessage>>,
     solver_stopped_rxs: Vec<mpsc::Receiver<ControlMessage>>,
+    /// The number of hashes computed by each solver since the last hashrate report
+    hash_counters: Arc<Vec<AtomicU64>>,
+    last_hashrate_report: Instant,
 }
 
 impl StratumMiner {
@@ -60,6 +69,8 @@ impl StratumMiner {
             control_txs: vec![],
             solver_loop_txs: vec![],
             solver_stopped_rxs: vec![],
+            hash_counters: Arc::new((0..threads).map(|_| AtomicU64::new(0)).collect()),
+            last_hashrate_report: Instant::now(),
         }
     }
 
@@ -67,6 +78,7 @@ impl StratumMiner {
         mut solver: SolverInstance,
         instance: usize,
         shared_data: JobSharedDataType,
+        hash_counters: Arc<Vec<AtomicU64>>,
         control_rx: mpsc::Receiver<ControlMessage>,
         solver_loop_rx: mpsc::Receiver<ControlMessage>,
         solver_stopped_tx: mpsc::Sender<ControlMessage>,
@@ -129,6 +141,7 @@ impl StratumMiner {
                     }
 
                     let difficulty = hasher.difficulty();
+                    hash_counters[instance].fetch_add(1, Ordering::Relaxed);
                     if difficulty >= target_difficulty {
                         let block_header: BlockHeader = BlockHeader::try_from(hasher.into_header()).unwrap();
                         info!(
@@ -144,7 +157,12 @@ impl StratumMiner {
                             block_header
                         );
 
-                        let still_valid = { height == shared_data.read().unwrap().height };
+                        // Shares found for a previous job, or with a difficulty below a share target raised by the
+                        // pool in the meantime, would be rejected
+                        let still_valid = {
+                            let sd = shared_data.read().unwrap();
+                            height == sd.height && job_id == sd.job_id && difficulty >= sd.difficulty
+                        };
                         if still_valid {
                             let mut s = shared_data.write().unwrap();
                             s.solutions.push(Solution {
@@ -177,6 +195,7 @@ impl StratumMiner {
         }
         for (i, s) in solvers.into_iter().enumerate() {
             let sd = self.shared_data.clone();
+            let hash_counters = self.hash_counters.clone();
             let (control_tx, control_rx) = mpsc::channel::<ControlMessage>();
             let (solver_tx, solver_rx) = mpsc::channel::<ControlMessage>();
             let (solver_stopped_tx, solver_stopped_rx) = mpsc::channel::<ControlMessage>();
@@ -184,7 +203,7 @@ impl StratumMiner {
             self.solver_loop_txs.push(solver_tx);
             self.solver_stopped_rxs.push(solver_stopped_rx);
             thread::spawn(move || {
-                StratumMiner::solver_thread(s, i, sd, control_rx, solver_rx, solver_stopped_tx);
+                StratumMiner::solver_thread(s, i, sd, hash_counters, control_rx, solver_rx, solver_stopped_tx);
             });
         }
         Ok(())
@@ -208,6 +227,13 @@ impl StratumMiner {
             self.pause_solvers();
             true
         } else {
+            if difficulty != sd.difficulty {
+                // Variable difficulty: the pool adjusts the share target to the hashrate of this miner
+                info!(
+                    "Share difficulty for height {} adjusted from {} to {}",
+                    height, sd.difficulty, difficulty
+                );
+            }
             false
         };
 
@@ -221,6 +247,16 @@ impl StratumMiner {
         Ok(())
     }
 
+    /// Returns the hashrate of each solver in MH/s since the previous report and resets the hash counters
+    pub fn hashrate_report(&mut self) -> Vec<f64> {
+        let elapsed = self.last_hashrate_report.elapsed().as_micros().max(1) as f64;
+        self.last_hashrate_report = Instant::now();
+        self.hash_counters
+            .iter()
+            .map(|counter| counter.swap(0, Ordering::Relaxed) as f64 / elapsed)
+            .collect()
+    }
+
     pub fn get_solutions(&self) -> Option<Solution> {
         {
             let mut s = self.shared_data.write().unwrap();
@@ -25,7 +25,7 @@ use std::io;
 use tari_common::{ConfigError, ConfigurationError};
 use tari_core::transactions::CoinbaseBuildError;
 use thiserror::Error;
-use tonic::transport;
+use tonic::{metadata::errors::InvalidMetadataValue, transport};
 
 #[derive(Debug, Error)]
 pub enum StratumTranscoderProxyError {
@@ -41,6 +41,8 @@ pub enum StratumTranscoderProxyError {
     IoError(#[from] io::Error),
     #[error("Tonic transport error: {0}")]
     TonicTransportError(#[from] transport::Error),
+    #[error("Invalid gRPC admin token: {0}")]
+    InvalidAdminToken(#[from] InvalidMetadataValue),
     #[error("GRPC response did not contain the expected field: `{0}`")]
     GrpcResponseMissingField(&'static str),
     #[error("Hyper error: {0}")]
@@ -65,6 +67,12 @@ pub enum StratumTranscoderProxyError {
     CoinbaseBuilderError(#[from] CoinbaseBuildError),
     #[error("Unexpected Tari base node response: {0}")]
     UnexpectedTariBaseNodeResponse(String),
+    #[error("Invalid login configuration: {0}")]
+    InvalidLogin(String),
+    #[error("Missing login credentials")]
+    Unauthorized,
+    #[error("Unknown login `{0}` or invalid access token")]
+    UnknownLogin(String),
 }
 
 impl From<tonic::Status> for StratumTranscoderProxyError {
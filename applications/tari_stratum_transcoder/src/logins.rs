@@ -0,0 +1,183 @@
+//  Copyright 2020, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::error::StratumTranscoderProxyError;
+use hyper::{header, HeaderMap};
+use std::{collections::HashMap, net::SocketAddr, str::FromStr};
+use tari_app_grpc::tari_rpc as grpc;
+use tonic::transport::Channel;
+
+/// A login that is allowed to use the transcoder, in the `login:access_token[:wallet_grpc_address]` format. Block
+/// rewards of the login are paid to the wallet at the given GRPC address, or to the default console wallet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Login {
+    pub name: String,
+    access_token: String,
+    pub wallet_grpc_address: Option<SocketAddr>,
+}
+
+impl FromStr for Login {
+    type Err = StratumTranscoderProxyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let name = parts.next().unwrap_or_default().trim();
+        let access_token = parts.next().unwrap_or_default().trim();
+        if name.is_empty() || access_token.is_empty() {
+            return Err(StratumTranscoderProxyError::InvalidLogin(format!(
+                "`{}` must be in the `login:access_token[:wallet_grpc_address]` format",
+                name
+            )));
+        }
+        let wallet_grpc_address = parts
+            .next()
+            .map(|addr| {
+                addr.trim().parse::<SocketAddr>().map_err(|e| {
+                    StratumTranscoderProxyError::InvalidLogin(format!("invalid wallet address for `{}`: {}", name, e))
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            name: name.to_string(),
+            access_token: access_token.to_string(),
+            wallet_grpc_address,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LoginAccount {
+    login: Login,
+    wallet_client: grpc::wallet_client::WalletClient<Channel>,
+}
+
+/// The logins that may use the transcoder, with the wallet client of each login. When no logins are configured the
+/// transcoder is open to everyone and pays all block rewards to the default console wallet.
+#[derive(Debug, Clone, Default)]
+pub struct LoginRegistry {
+    accounts: HashMap<String, LoginAccount>,
+}
+
+impl LoginRegistry {
+    /// Parses the configured logins and connects to the wallet of each login
+    pub async fn connect(
+        logins: &[String],
+        default_wallet_client: &grpc::wallet_client::WalletClient<Channel>,
+    ) -> Result<Self, StratumTranscoderProxyError> {
+        let mut accounts = HashMap::new();
+        for login in logins.iter().filter(|login| !login.trim().is_empty()) {
+            let login = Login::from_str(login)?;
+            let wallet_client = match login.wallet_grpc_address {
+                Some(addr) => grpc::wallet_client::WalletClient::connect(format!("http://{}", addr)).await?,
+                None => default_wallet_client.clone(),
+            };
+            if accounts.contains_key(&login.name) {
+                return Err(StratumTranscoderProxyError::InvalidLogin(format!(
+                    "`{}` is configured more than once",
+                    login.name
+                )));
+            }
+            accounts.insert(login.name.clone(), LoginAccount { login, wallet_client });
+        }
+        Ok(Self { accounts })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.accounts.is_empty()
+    }
+
+    /// Authenticates the request with the HTTP basic auth credentials, returning the login name and its wallet client
+    pub fn authenticate(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<(String, grpc::wallet_client::WalletClient<Channel>), StratumTranscoderProxyError> {
+        let (name, access_token) = basic_auth_credentials(headers).ok_or(StratumTranscoderProxyError::Unauthorized)?;
+        match self.accounts.get(&name) {
+            Some(account) if tokens_match(&account.login.access_token, &access_token) => {
+                Ok((name, account.wallet_client.clone()))
+            },
+            _ => Err(StratumTranscoderProxyError::UnknownLogin(name)),
+        }
+    }
+}
+
+fn basic_auth_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let encoded = value.strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(base64::decode(encoded.trim()).ok()?).ok()?;
+    let (name, access_token) = decoded.split_once(':')?;
+    Some((name.to_string(), access_token.to_string()))
+}
+
+/// Compares the access tokens in constant time, so the comparison does not reveal how much of a token is correct
+fn tokens_match(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len() &&
+        expected
+            .bytes()
+            .zip(actual.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b)) ==
+            0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn auth_headers(credentials: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let value = format!("Basic {}", base64::encode(credentials));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(&value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn it_parses_logins() {
+        let login = Login::from_str("pool1:secret:127.0.0.1:18143").unwrap();
+        assert_eq!(login.name, "pool1");
+        assert_eq!(login.access_token, "secret");
+        assert_eq!(login.wallet_grpc_address, Some("127.0.0.1:18143".parse().unwrap()));
+
+        let login = Login::from_str("pool2:secret").unwrap();
+        assert_eq!(login.wallet_grpc_address, None);
+
+        assert!(Login::from_str("pool3").is_err());
+        assert!(Login::from_str("pool4:secret:not-an-address").is_err());
+    }
+
+    #[test]
+    fn it_reads_basic_auth_credentials() {
+        let headers = auth_headers("pool1:secret:with:colons");
+        assert_eq!(
+            basic_auth_credentials(&headers),
+            Some(("pool1".to_string(), "secret:with:colons".to_string()))
+        );
+        assert_eq!(basic_auth_credentials(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn it_compares_tokens() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret1"));
+    }
+}
@@ -29,17 +29,19 @@
 
 mod common;
 mod error;
+mod logins;
 mod proxy;
 
-use crate::error::StratumTranscoderProxyError;
+use crate::{error::StratumTranscoderProxyError, logins::LoginRegistry};
 use futures::future;
 use hyper::{service::make_service_fn, Server};
 use proxy::{StratumTranscoderProxyConfig, StratumTranscoderProxyService};
 use std::convert::Infallible;
 use structopt::StructOpt;
-use tari_app_grpc::tari_rpc as grpc;
+use tari_app_grpc::{authentication::ClientAuthInterceptor, tari_rpc as grpc};
 use tari_common::{configuration::bootstrap::ApplicationType, ConfigBootstrap, GlobalConfig};
 use tokio::time::Duration;
+use tonic::transport::Endpoint;
 
 #[tokio::main]
 async fn main() -> Result<(), StratumTranscoderProxyError> {
@@ -53,11 +55,24 @@ async fn main() -> Result<(), StratumTranscoderProxyError> {
         .pool_max_idle_per_host(25)
         .build()
         .map_err(StratumTranscoderProxyError::ReqwestError)?;
-    let base_node_client =
-        grpc::base_node_client::BaseNodeClient::connect(format!("http://{}", config.grpc_base_node_address)).await?;
+    // Block templates and block submission are admin methods of the base node
+    let auth = ClientAuthInterceptor::new(config.grpc_base_node_admin_token.as_deref())?;
+    let base_node_client = grpc::base_node_client::BaseNodeClient::with_interceptor(
+        Endpoint::new(format!("http://{}", config.grpc_base_node_address))?
+            .connect()
+            .await?,
+        auth,
+    );
     let wallet_client =
         grpc::wallet_client::WalletClient::connect(format!("http://{}", config.grpc_console_wallet_address)).await?;
-    let miningcore_service = StratumTranscoderProxyService::new(config, client, base_node_client, wallet_client);
+    let logins = LoginRegistry::connect(&config.logins, &wallet_client).await?;
+    if logins.is_enabled() {
+        println!("Accepting {} configured logins", config.logins.len());
+    } else {
+        println!("No logins configured, accepting all requests");
+    }
+    let miningcore_service =
+        StratumTranscoderProxyService::new(config, client, base_node_client, wallet_client, logins);
     let service = make_service_fn(|_conn| future::ready(Result::<_, Infallible>::Ok(miningcore_service.clone())));
 
     match Server::try_bind(&addr) {
@@ -28,9 +28,18 @@ use crate::{
         proxy,
     },
     error::StratumTranscoderProxyError,
+    logins::LoginRegistry,
 };
 use bytes::Bytes;
-use hyper::{service::Service, Body, Method, Request, Response, StatusCode};
+use hyper::{
+    header::{self, HeaderValue},
+    service::Service,
+    Body,
+    Method,
+    Request,
+    Response,
+    StatusCode,
+};
 use json::json;
 use jsonrpc::error::StandardError;
 use serde_json as json;
@@ -42,11 +51,11 @@ use std::{
     task::{Context, Poll},
     time::Instant,
 };
-use tari_app_grpc::{tari_rpc as grpc, tari_rpc::GetCoinbaseRequest};
+use tari_app_grpc::{authentication::BaseNodeAdminClient, tari_rpc as grpc, tari_rpc::GetCoinbaseRequest};
 use tari_common::{configuration::Network, GlobalConfig};
 use tari_core::blocks::{Block, NewBlockTemplate};
 use tari_utilities::{hex::Hex, message_format::MessageFormat};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 const LOG_TARGET: &str = "tari_stratum_transcoder::transcoder";
 
@@ -54,8 +63,10 @@ const LOG_TARGET: &str = "tari_stratum_transcoder::transcoder";
 pub struct StratumTranscoderProxyConfig {
     pub network: Network,
     pub grpc_base_node_address: SocketAddr,
+    pub grpc_base_node_admin_token: Option<String>,
     pub grpc_console_wallet_address: SocketAddr,
     pub transcoder_host_address: SocketAddr,
+    pub logins: Vec<String>,
 }
 
 impl From<GlobalConfig> for StratumTranscoderProxyConfig {
@@ -63,8 +74,10 @@ impl From<GlobalConfig> for StratumTranscoderProxyConfig {
         Self {
             network: config.network,
             grpc_base_node_address: config.grpc_base_node_address,
+            grpc_base_node_admin_token: config.grpc_base_node_admin_token,
             grpc_console_wallet_address: config.grpc_console_wallet_address,
             transcoder_host_address: config.transcoder_host_address,
+            logins: config.transcoder_logins,
         }
     }
 }
@@ -78,8 +91,9 @@ impl StratumTranscoderProxyService {
     pub fn new(
         config: StratumTranscoderProxyConfig,
         http_client: reqwest::Client,
-        base_node_client: grpc::base_node_client::BaseNodeClient<tonic::transport::Channel>,
+        base_node_client: BaseNodeAdminClient,
         wallet_client: grpc::wallet_client::WalletClient<tonic::transport::Channel>,
+        logins: LoginRegistry,
     ) -> Self {
         Self {
             inner: InnerService {
@@ -87,6 +101,7 @@ impl StratumTranscoderProxyService {
                 http_client,
                 base_node_client,
                 wallet_client,
+                logins,
             },
         }
     }
@@ -131,8 +146,9 @@ impl Service<Request<Body>> for StratumTranscoderProxyService {
 struct InnerService {
     config: StratumTranscoderProxyConfig,
     http_client: reqwest::Client,
-    base_node_client: grpc::base_node_client::BaseNodeClient<tonic::transport::Channel>,
+    base_node_client: BaseNodeAdminClient,
     wallet_client: grpc::wallet_client::WalletClient<tonic::transport::Channel>,
+    logins: LoginRegistry,
 }
 
 impl InnerService {
@@ -350,7 +366,14 @@ impl InnerService {
         }
         let mut client = self.base_node_client.clone();
         let mut resp = client
-            .get_blocks(grpc::GetBlocksRequest { heights: vec![height] })
+            .get_blocks(grpc::GetBlocksRequest {
+                heights: vec![height],
+                field_mask: Some(grpc::BlockFieldMask {
+                    headers_only: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
             .await?
             .into_inner();
         let message = resp.message().await?;
@@ -461,7 +484,10 @@ impl InnerService {
         let request = request.body();
         let request_id = request["id"].as_i64();
         let mut client = self.wallet_client.clone();
-        let balances = client.get_balance(grpc::GetBalanceRequest {}).await?.into_inner();
+        let balances = client
+            .get_balance(grpc::GetBalanceRequest::default())
+            .await?
+            .into_inner();
 
         let json_response = json!({
              "id": request_id.unwrap_or(-1),
@@ -520,6 +546,7 @@ impl InnerService {
         let transfer_results = client
             .transfer(grpc::TransferRequest {
                 recipients: grpc_payments,
+                account: String::new(),
             })
             .await?
             .into_inner();
@@ -597,7 +624,19 @@ impl InnerService {
         }
     }
 
-    async fn handle(self, mut request: Request<Body>) -> Result<Response<Body>, StratumTranscoderProxyError> {
+    /// Rejects the request unless it was made by a configured login, in which case the block rewards and wallet
+    /// requests are routed to the wallet of the login
+    fn authenticate(&mut self, request: &Request<Bytes>) -> Result<(), StratumTranscoderProxyError> {
+        if !self.logins.is_enabled() {
+            return Ok(());
+        }
+        let (login, wallet_client) = self.logins.authenticate(request.headers())?;
+        debug!(target: LOG_TARGET, "Request authenticated for login `{}`", login);
+        self.wallet_client = wallet_client;
+        Ok(())
+    }
+
+    async fn handle(mut self, mut request: Request<Body>) -> Result<Response<Body>, StratumTranscoderProxyError> {
         let start = Instant::now();
         let bytes = proxy::read_body_until_end(request.body_mut()).await?;
         let request = request.map(|_| bytes.freeze());
@@ -629,6 +668,24 @@ impl InnerService {
                 .join(","),
         );
 
+        if let Err(err) = self.authenticate(&request) {
+            warn!(target: LOG_TARGET, "Rejected request: {}", err);
+            println!("Method: {}, Rejected: {}", method_name, err);
+            let mut response = proxy::json_response(
+                StatusCode::UNAUTHORIZED,
+                &standard_error_response(
+                    None,
+                    StandardError::InvalidRequest,
+                    Some(json!({"details": err.to_string()})),
+                ),
+            )?;
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                HeaderValue::from_static("Basic realm=\"tari_stratum_transcoder\""),
+            );
+            return Ok(response);
+        }
+
         let response = self.get_proxy_response(request).await?;
         println!(
             "Method: {}, Proxy Status: {}, Response Time: {}ms",
@@ -115,6 +115,7 @@ async fn write_keys(mut rx: mpsc::Receiver<(TransactionOutput, PrivateKey, Micro
     let kernel = TransactionKernel {
         features: KernelFeatures::empty(),
         fee: MicroTari::from(0),
+        burn_amount: MicroTari::from(0),
         lock_height: 0,
         excess,
         excess_sig: sig,
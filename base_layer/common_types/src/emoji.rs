@@ -86,6 +86,11 @@ pub const fn emoji_set() -> [char; 256] {
     EMOJI
 }
 
+/// Returns the index of the emoji in the emoji set, or None if it is not part of the set
+pub(crate) fn emoji_index(c: char) -> Option<usize> {
+    REVERSE_EMOJI.get(&c).copied()
+}
+
 impl EmojiId {
     /// Construct an Emoji ID from the given pubkey.
     pub fn from_pubkey(key: &PublicKey) -> Self {
@@ -23,6 +23,7 @@
 pub mod chain_metadata;
 pub mod emoji;
 pub mod luhn;
+pub mod stealth_address;
 pub mod types;
 pub mod waiting_requests;
 
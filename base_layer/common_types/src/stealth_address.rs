@@ -0,0 +1,160 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{
+    emoji::{emoji_index, emoji_set},
+    luhn::{checksum, is_valid},
+    types::PublicKey,
+};
+use std::{
+    fmt::{Display, Error, Formatter},
+    str::FromStr,
+};
+use tari_crypto::tari_utilities::{hex::Hex, ByteArray};
+
+const KEY_LENGTH: usize = 32;
+const ADDRESS_LENGTH: usize = 2 * KEY_LENGTH;
+
+/// A stealth address is the pair of public keys that a wallet publishes to receive one-sided payments that cannot be
+/// linked to the address, or to each other, by anyone but the receiver.
+///
+/// The scan key lets its owner detect and read the value of payments to the address, while spending them also
+/// requires the private key of the spend key. Handing out only the private scan key therefore enables watch-only
+/// scanning.
+///
+/// Stealth addresses are displayed as 65 emoji: the emoji encoding of the scan key and spend key bytes followed by a
+/// Luhn mod 256 checksum, as for [EmojiId](crate::emoji::EmojiId). They can also be parsed from the 128 character
+/// hex encoding of the two keys.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StealthAddress {
+    pub scan_key: PublicKey,
+    pub spend_key: PublicKey,
+}
+
+impl StealthAddress {
+    pub fn new(scan_key: PublicKey, spend_key: PublicKey) -> Self {
+        Self { scan_key, spend_key }
+    }
+
+    /// The scan key bytes followed by the spend key bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.scan_key.as_bytes().to_vec();
+        bytes.extend_from_slice(self.spend_key.as_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StealthAddressError> {
+        if bytes.len() != ADDRESS_LENGTH {
+            return Err(StealthAddressError);
+        }
+        let scan_key = PublicKey::from_bytes(&bytes[..KEY_LENGTH]).map_err(|_| StealthAddressError)?;
+        let spend_key = PublicKey::from_bytes(&bytes[KEY_LENGTH..]).map_err(|_| StealthAddressError)?;
+        Ok(Self { scan_key, spend_key })
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.to_bytes().to_hex()
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Self, StealthAddressError> {
+        let bytes = Vec::<u8>::from_hex(hex).map_err(|_| StealthAddressError)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Return the 65 character emoji string for this address
+    pub fn to_emoji_string(&self) -> String {
+        let mut indices = self.to_bytes().into_iter().map(usize::from).collect::<Vec<_>>();
+        indices.push(checksum(&indices, 256));
+        let emoji = emoji_set();
+        indices.into_iter().map(|i| emoji[i]).collect()
+    }
+
+    pub fn from_emoji_string(s: &str) -> Result<Self, StealthAddressError> {
+        let indices = s
+            .chars()
+            .map(|c| emoji_index(c).ok_or(StealthAddressError))
+            .collect::<Result<Vec<_>, _>>()?;
+        if indices.len() != ADDRESS_LENGTH + 1 || !is_valid(&indices, 256) {
+            return Err(StealthAddressError);
+        }
+        let bytes = indices[..ADDRESS_LENGTH].iter().map(|i| *i as u8).collect::<Vec<_>>();
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl Display for StealthAddress {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        fmt.write_str(&self.to_emoji_string())
+    }
+}
+
+impl FromStr for StealthAddress {
+    type Err = StealthAddressError;
+
+    /// Parse a stealth address from either its emoji or its hex encoding
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_emoji_string(s).or_else(|_| Self::from_hex(s))
+    }
+}
+
+#[derive(Debug)]
+pub struct StealthAddressError;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    fn random_address() -> StealthAddress {
+        let (_, scan_key) = PublicKey::random_keypair(&mut OsRng);
+        let (_, spend_key) = PublicKey::random_keypair(&mut OsRng);
+        StealthAddress::new(scan_key, spend_key)
+    }
+
+    #[test]
+    fn it_round_trips_the_encodings() {
+        let address = random_address();
+        let emoji = address.to_string();
+        assert_eq!(emoji.chars().count(), ADDRESS_LENGTH + 1);
+        assert_eq!(emoji.parse::<StealthAddress>().unwrap(), address);
+        assert_eq!(address.to_hex().parse::<StealthAddress>().unwrap(), address);
+    }
+
+    #[test]
+    fn it_rejects_invalid_addresses() {
+        let address = random_address();
+        let emoji = address.to_string();
+        // Missing checksum
+        let truncated = emoji.chars().take(ADDRESS_LENGTH).collect::<String>();
+        assert!(truncated.parse::<StealthAddress>().is_err());
+        // Wrong checksum
+        let mut chars = emoji.chars().collect::<Vec<_>>();
+        let last = chars.pop().unwrap();
+        let other = emoji_set().iter().copied().find(|c| *c != last).unwrap();
+        chars.push(other);
+        assert!(chars.into_iter().collect::<String>().parse::<StealthAddress>().is_err());
+        // A single key is not an address
+        assert!(address.scan_key.to_hex().parse::<StealthAddress>().is_err());
+    }
+}
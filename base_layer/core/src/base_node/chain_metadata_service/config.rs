@@ -0,0 +1,50 @@
+// Copyright 2019, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+/// Configuration for the chain metadata service.
+#[derive(Debug, Clone, Copy)]
+pub struct ChainMetadataServiceConfig {
+    /// The interval at which chain metadata is broadcast while new blocks are arriving or while this node is behind
+    /// its peers.
+    pub min_broadcast_interval: Duration,
+    /// The interval that broadcasts slow down to while this node is synced and no new blocks are arriving. The
+    /// broadcast interval is not adapted if this is not greater than `min_broadcast_interval`.
+    pub max_broadcast_interval: Duration,
+}
+
+impl ChainMetadataServiceConfig {
+    /// Returns true if the broadcast interval should be adapted to network conditions
+    pub fn is_adaptive(&self) -> bool {
+        self.max_broadcast_interval > self.min_broadcast_interval
+    }
+}
+
+impl Default for ChainMetadataServiceConfig {
+    fn default() -> Self {
+        Self {
+            min_broadcast_interval: Duration::from_secs(30),
+            max_broadcast_interval: Duration::from_secs(30),
+        }
+    }
+}
@@ -21,13 +21,24 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::service::ChainMetadataService;
-use crate::base_node::{chain_metadata_service::handle::ChainMetadataHandle, comms_interface::LocalNodeCommsInterface};
+use crate::base_node::{
+    chain_metadata_service::{handle::ChainMetadataHandle, ChainMetadataServiceConfig},
+    comms_interface::LocalNodeCommsInterface,
+};
 use tari_comms::connectivity::ConnectivityRequester;
 use tari_p2p::services::liveness::LivenessHandle;
 use tari_service_framework::{async_trait, ServiceInitializationError, ServiceInitializer, ServiceInitializerContext};
 use tokio::sync::broadcast;
 
-pub struct ChainMetadataServiceInitializer;
+pub struct ChainMetadataServiceInitializer {
+    config: ChainMetadataServiceConfig,
+}
+
+impl ChainMetadataServiceInitializer {
+    pub fn new(config: ChainMetadataServiceConfig) -> Self {
+        Self { config }
+    }
+}
 
 #[async_trait]
 impl ServiceInitializer for ChainMetadataServiceInitializer {
@@ -38,12 +49,13 @@ impl ServiceInitializer for ChainMetadataServiceInitializer {
         let handle = ChainMetadataHandle::new(publisher.clone());
         context.register_handle(handle);
 
-        context.spawn_until_shutdown(|handles| {
+        let config = self.config;
+        context.spawn_until_shutdown(move |handles| {
             let liveness = handles.expect_handle::<LivenessHandle>();
             let base_node = handles.expect_handle::<LocalNodeCommsInterface>();
             let connectivity = handles.expect_handle::<ConnectivityRequester>();
 
-            ChainMetadataService::new(liveness, base_node, connectivity, publisher).run()
+            ChainMetadataService::new(config, liveness, base_node, connectivity, publisher).run()
         });
 
         Ok(())
@@ -22,11 +22,13 @@
 
 const LOG_TARGET: &str = "c::bn::chain_state_sync_service";
 
+mod config;
 mod error;
 mod handle;
 mod initializer;
 mod service;
 
 // Public re-exports
+pub use config::ChainMetadataServiceConfig;
 pub use handle::{ChainMetadataEvent, ChainMetadataHandle, PeerChainMetadata};
 pub use initializer::ChainMetadataServiceInitializer;
@@ -23,7 +23,10 @@
 use super::{error::ChainMetadataSyncError, LOG_TARGET};
 use crate::{
     base_node::{
-        chain_metadata_service::handle::{ChainMetadataEvent, PeerChainMetadata},
+        chain_metadata_service::{
+            handle::{ChainMetadataEvent, PeerChainMetadata},
+            ChainMetadataServiceConfig,
+        },
         comms_interface::{BlockEvent, LocalNodeCommsInterface},
     },
     chain_storage::BlockAddResult,
@@ -32,7 +35,7 @@ use crate::{
 use log::*;
 use num_format::{Locale, ToFormattedString};
 use prost::Message;
-use std::{convert::TryFrom, sync::Arc};
+use std::{cmp, convert::TryFrom, sync::Arc, time::Duration};
 use tari_common::log_if_error;
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_comms::{
@@ -46,33 +49,43 @@ use tokio::sync::broadcast;
 const NUM_ROUNDS_NETWORK_SILENCE: u16 = 3;
 
 pub(super) struct ChainMetadataService {
+    config: ChainMetadataServiceConfig,
     liveness: LivenessHandle,
     base_node: LocalNodeCommsInterface,
     peer_chain_metadata: Vec<PeerChainMetadata>,
     connectivity: ConnectivityRequester,
     event_publisher: broadcast::Sender<Arc<ChainMetadataEvent>>,
     number_of_rounds_no_pings: u16,
+    local_chain_metadata: Option<ChainMetadata>,
+    broadcast_interval: Duration,
+    block_added_since_last_round: bool,
 }
 
 impl ChainMetadataService {
     /// Create a new ChainMetadataService
     ///
     /// ## Arguments
+    /// `config` - the chain metadata service config
     /// `liveness` - the liveness service handle
     /// `base_node` - the base node service handle
     pub fn new(
+        config: ChainMetadataServiceConfig,
         liveness: LivenessHandle,
         base_node: LocalNodeCommsInterface,
         connectivity: ConnectivityRequester,
         event_publisher: broadcast::Sender<Arc<ChainMetadataEvent>>,
     ) -> Self {
         Self {
+            config,
             liveness,
             base_node,
             peer_chain_metadata: Vec::new(),
             connectivity,
             event_publisher,
             number_of_rounds_no_pings: 0,
+            local_chain_metadata: None,
+            broadcast_interval: config.min_broadcast_interval,
+            block_added_since_last_round: false,
         }
     }
 
@@ -137,6 +150,8 @@ impl ChainMetadataService {
             BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { .. }, _) |
             BlockEvent::BlockSyncComplete(_) => {
                 self.update_liveness_chain_metadata().await?;
+                self.block_added_since_last_round = true;
+                self.reset_broadcast_interval().await?;
             },
             _ => {},
         }
@@ -147,6 +162,7 @@ impl ChainMetadataService {
     /// Tack this node's metadata on to ping/pongs sent by the liveness service
     async fn update_liveness_chain_metadata(&mut self) -> Result<(), ChainMetadataSyncError> {
         let chain_metadata = self.base_node.get_metadata().await?;
+        self.local_chain_metadata = Some(chain_metadata.clone());
         let bytes = proto::ChainMetadata::from(chain_metadata).to_encoded_bytes();
         self.liveness
             .set_metadata_entry(MetadataKey::ChainMetadata, bytes)
@@ -166,6 +182,9 @@ impl ChainMetadataService {
                 self.number_of_rounds_no_pings = 0;
                 self.collect_chain_state_from_ping(&event.node_id, &event.metadata)?;
                 self.send_chain_metadata_to_event_publisher().await?;
+                if self.is_behind_peers() {
+                    self.reset_broadcast_interval().await?;
+                }
             },
             // Received a pong, check if our neighbour sent it and it contains ChainMetadata
             LivenessEvent::ReceivedPong(event) => {
@@ -177,6 +196,9 @@ impl ChainMetadataService {
                 self.number_of_rounds_no_pings = 0;
                 self.collect_chain_state_from_pong(&event.node_id, &event.metadata)?;
                 self.send_chain_metadata_to_event_publisher().await?;
+                if self.is_behind_peers() {
+                    self.reset_broadcast_interval().await?;
+                }
             },
             // New ping round has begun
             LivenessEvent::PingRoundBroadcast(num_peers) => {
@@ -196,12 +218,56 @@ impl ChainMetadataService {
                 // and have allocated space for their replies
 
                 self.resize_chainstate_buffer(*num_peers);
+
+                // Keep broadcasting quickly while blocks are arriving or we are catching up, otherwise slow down
+                if self.block_added_since_last_round || self.is_behind_peers() {
+                    self.reset_broadcast_interval().await?;
+                } else {
+                    self.back_off_broadcast_interval().await?;
+                }
+                self.block_added_since_last_round = false;
             },
         }
 
         Ok(())
     }
 
+    /// Returns true if any peer has reported a chain with more accumulated difficulty than ours, or if our own chain
+    /// metadata is not yet known
+    fn is_behind_peers(&self) -> bool {
+        match self.local_chain_metadata {
+            Some(ref local) => self
+                .peer_chain_metadata
+                .iter()
+                .any(|p| p.chain_metadata.accumulated_difficulty() > local.accumulated_difficulty()),
+            None => true,
+        }
+    }
+
+    /// Broadcast chain metadata at the minimum interval
+    async fn reset_broadcast_interval(&mut self) -> Result<(), ChainMetadataSyncError> {
+        self.set_broadcast_interval(self.config.min_broadcast_interval).await
+    }
+
+    /// Double the chain metadata broadcast interval, up to the maximum interval
+    async fn back_off_broadcast_interval(&mut self) -> Result<(), ChainMetadataSyncError> {
+        let interval = cmp::min(self.broadcast_interval * 2, self.config.max_broadcast_interval);
+        self.set_broadcast_interval(interval).await
+    }
+
+    async fn set_broadcast_interval(&mut self, interval: Duration) -> Result<(), ChainMetadataSyncError> {
+        if !self.config.is_adaptive() || interval == self.broadcast_interval {
+            return Ok(());
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Changing chain metadata broadcast interval from {:.0?} to {:.0?}", self.broadcast_interval, interval
+        );
+        self.liveness.set_auto_ping_interval(interval).await?;
+        self.broadcast_interval = interval;
+        Ok(())
+    }
+
     async fn send_network_silence(&mut self) -> Result<(), ChainMetadataSyncError> {
         let _ = self.event_publisher.send(Arc::new(ChainMetadataEvent::NetworkSilence));
         Ok(())
@@ -341,6 +407,17 @@ mod test {
         LivenessMockState,
         ConnectivityManagerMockState,
         reply_channel::TryReceiver<NodeCommsRequest, NodeCommsResponse, CommsInterfaceError>,
+    ) {
+        setup_with_config(Default::default())
+    }
+
+    fn setup_with_config(
+        config: ChainMetadataServiceConfig,
+    ) -> (
+        ChainMetadataService,
+        LivenessMockState,
+        ConnectivityManagerMockState,
+        reply_channel::TryReceiver<NodeCommsRequest, NodeCommsResponse, CommsInterfaceError>,
     ) {
         let (liveness_handle, mock, _) = create_p2p_liveness_mock(1);
         let liveness_mock_state = mock.get_mock_state();
@@ -353,7 +430,7 @@ mod test {
         let connectivity_mock_state = mock.get_shared_state();
         task::spawn(mock.run());
 
-        let service = ChainMetadataService::new(liveness_handle, base_node, connectivity, publisher);
+        let service = ChainMetadataService::new(config, liveness_handle, base_node, connectivity, publisher);
 
         (
             service,
@@ -488,4 +565,86 @@ mod test {
         unpack_enum!(ChainMetadataSyncError::DecodeError(_err) = err);
         assert_eq!(service.peer_chain_metadata.len(), 0);
     }
+
+    fn adaptive_config() -> ChainMetadataServiceConfig {
+        ChainMetadataServiceConfig {
+            min_broadcast_interval: Duration::from_secs(30),
+            max_broadcast_interval: Duration::from_secs(90),
+        }
+    }
+
+    fn take_ping_interval_calls(liveness_mock_state: &LivenessMockState) -> Vec<Duration> {
+        liveness_mock_state
+            .take_calls()
+            .into_iter()
+            .filter_map(|call| match call {
+                LivenessRequest::SetAutoPingInterval(interval) => Some(interval),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn broadcast_interval_backs_off_when_synced() {
+        let (mut service, liveness_mock_state, _, _) = setup_with_config(adaptive_config());
+        service.local_chain_metadata = Some(create_sample_proto_chain_metadata().try_into().unwrap());
+
+        for _ in 0..3 {
+            service
+                .handle_liveness_event(&LivenessEvent::PingRoundBroadcast(1))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(service.broadcast_interval, Duration::from_secs(90));
+        // Only changes to the interval are sent to the liveness service
+        assert_eq!(take_ping_interval_calls(&liveness_mock_state), vec![
+            Duration::from_secs(60),
+            Duration::from_secs(90)
+        ]);
+    }
+
+    #[tokio::test]
+    async fn broadcast_interval_resets_when_behind_peers() {
+        let (mut service, liveness_mock_state, _, _) = setup_with_config(adaptive_config());
+        service.local_chain_metadata = Some(create_sample_proto_chain_metadata().try_into().unwrap());
+        service.broadcast_interval = Duration::from_secs(90);
+
+        let mut proto_chain_metadata = create_sample_proto_chain_metadata();
+        proto_chain_metadata.accumulated_difficulty = 2u128.to_be_bytes().to_vec();
+        let mut metadata = Metadata::new();
+        metadata.insert(MetadataKey::ChainMetadata, proto_chain_metadata.to_encoded_bytes());
+        let pong_event = PingPongEvent {
+            metadata,
+            node_id: NodeId::new(),
+            latency: None,
+        };
+        service
+            .handle_liveness_event(&LivenessEvent::ReceivedPong(Box::new(pong_event)))
+            .await
+            .unwrap();
+        assert_eq!(service.broadcast_interval, Duration::from_secs(30));
+
+        // Stays at the minimum interval while the peer is ahead
+        service
+            .handle_liveness_event(&LivenessEvent::PingRoundBroadcast(1))
+            .await
+            .unwrap();
+        assert_eq!(service.broadcast_interval, Duration::from_secs(30));
+        assert_eq!(take_ping_interval_calls(&liveness_mock_state), vec![
+            Duration::from_secs(30)
+        ]);
+    }
+
+    #[tokio::test]
+    async fn broadcast_interval_not_adapted_by_default() {
+        let (mut service, liveness_mock_state, _, _) = setup();
+        service.local_chain_metadata = Some(create_sample_proto_chain_metadata().try_into().unwrap());
+
+        service
+            .handle_liveness_event(&LivenessEvent::PingRoundBroadcast(1))
+            .await
+            .unwrap();
+        assert!(take_ping_interval_calls(&liveness_mock_state).is_empty());
+    }
 }
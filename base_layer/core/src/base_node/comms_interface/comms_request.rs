@@ -20,10 +20,14 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{blocks::NewBlockTemplate, chain_storage::MmrTree, proof_of_work::PowAlgorithm};
+use crate::{
+    blocks::{NewBlockTemplate, ShortKernelId},
+    chain_storage::MmrTree,
+    proof_of_work::PowAlgorithm,
+};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Error, Formatter};
-use tari_common_types::types::{Commitment, HashOutput, Signature};
+use tari_common_types::types::{BlockHash, Commitment, HashOutput, Signature};
 use tari_crypto::tari_utilities::hex::Hex;
 
 /// A container for the parameters required for a FetchMmrState request.
@@ -52,6 +56,10 @@ pub enum NodeCommsRequest {
     GetNewBlockTemplate(GetNewBlockTemplateRequest),
     GetNewBlock(NewBlockTemplate),
     FetchKernelByExcessSig(Signature),
+    FetchMempoolTransactionsByShortIds {
+        block_hash: BlockHash,
+        short_ids: Vec<ShortKernelId>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -84,6 +92,12 @@ impl Display for NodeCommsRequest {
                 s.get_public_nonce().to_hex(),
                 s.get_signature().to_hex()
             ),
+            FetchMempoolTransactionsByShortIds { block_hash, short_ids } => write!(
+                f,
+                "FetchMempoolTransactionsByShortIds (block={}, n={})",
+                block_hash.to_hex(),
+                short_ids.len()
+            ),
         }
     }
 }
@@ -24,7 +24,7 @@ use crate::{
     blocks::{block_header::BlockHeader, Block, NewBlockTemplate},
     chain_storage::HistoricalBlock,
     proof_of_work::Difficulty,
-    transactions::transaction::{TransactionKernel, TransactionOutput},
+    transactions::transaction::{Transaction, TransactionKernel, TransactionOutput},
 };
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display, Formatter};
@@ -49,6 +49,7 @@ pub enum NodeCommsResponse {
     TargetDifficulty(Difficulty),
     FetchHeadersAfterResponse(Vec<BlockHeader>),
     MmrNodes(Vec<HashOutput>, Vec<u8>),
+    Transactions(Vec<Transaction>),
 }
 
 impl Display for NodeCommsResponse {
@@ -76,6 +77,7 @@ impl Display for NodeCommsResponse {
             TargetDifficulty(_) => write!(f, "TargetDifficulty"),
             FetchHeadersAfterResponse(_) => write!(f, "FetchHeadersAfterResponse"),
             MmrNodes(_, _) => write!(f, "MmrNodes"),
+            Transactions(_) => write!(f, "Transactions"),
         }
     }
 }
@@ -24,13 +24,21 @@ use crate::{
         comms_interface::{
             error::CommsInterfaceError,
             local_interface::BlockEventSender,
+            propagated_blocks::PropagatedBlocks,
             NodeCommsRequest,
             NodeCommsResponse,
         },
         OutboundNodeCommsInterface,
     },
-    blocks::{block_header::BlockHeader, Block, NewBlock, NewBlockTemplate},
-    chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend, ChainBlock, PrunedOutput},
+    blocks::{block_header::BlockHeader, Block, CompactBlock, NewBlock, NewBlockTemplate},
+    chain_storage::{
+        async_db::AsyncBlockchainDb,
+        BlockAddResult,
+        BlockchainBackend,
+        ChainBlock,
+        ChainStorageError,
+        PrunedOutput,
+    },
     consensus::{ConsensusConstants, ConsensusManager},
     mempool::{async_mempool, Mempool},
     proof_of_work::{Difficulty, PowAlgorithm},
@@ -49,6 +57,10 @@ use tokio::sync::Semaphore;
 
 const LOG_TARGET: &str = "c::bn::comms_interface::inbound_handler";
 const MAX_HEADERS_PER_RESPONSE: u32 = 100;
+/// The maximum number of missing ancestors of an orphan block that are requested from the peer that provided it
+const MAX_ORPHAN_ANCESTOR_REQUESTS: usize = 10;
+/// The maximum number of transactions of a compact block that can be requested from a peer at once
+const MAX_SHORT_IDS_PER_REQUEST: usize = 1000;
 
 /// Events that can be published on the Validated Block Event Stream
 /// Broadcast is to notify subscribers if this is a valid propagated block event
@@ -98,6 +110,7 @@ pub struct InboundNodeCommsHandlers<T> {
     consensus_manager: ConsensusManager,
     new_block_request_semaphore: Arc<Semaphore>,
     outbound_nci: OutboundNodeCommsInterface,
+    propagated_blocks: PropagatedBlocks,
 }
 
 impl<T> InboundNodeCommsHandlers<T>
@@ -118,9 +131,31 @@ where T: BlockchainBackend + 'static
             consensus_manager,
             new_block_request_semaphore: Arc::new(Semaphore::new(1)),
             outbound_nci,
+            propagated_blocks: PropagatedBlocks::default(),
         }
     }
 
+    /// Handle an inbound node comms request from a remote peer. The transactions of a compact block are only returned to
+    /// peers that this node recently propagated the block to.
+    pub async fn handle_peer_request(
+        &self,
+        request: NodeCommsRequest,
+        source_peer: &NodeId,
+    ) -> Result<NodeCommsResponse, CommsInterfaceError> {
+        if let NodeCommsRequest::FetchMempoolTransactionsByShortIds { block_hash, .. } = &request {
+            if !self.propagated_blocks.was_propagated_to(block_hash, source_peer) {
+                debug!(
+                    target: LOG_TARGET,
+                    "Peer `{}` requested transactions of compact block `{}` that was not propagated to it",
+                    source_peer.short_str(),
+                    block_hash.to_hex()
+                );
+                return Ok(NodeCommsResponse::Transactions(Vec::new()));
+            }
+        }
+        self.handle_request(request).await
+    }
+
     /// Handle inbound node comms requests from remote nodes and local services.
     pub async fn handle_request(&self, request: NodeCommsRequest) -> Result<NodeCommsResponse, CommsInterfaceError> {
         debug!(target: LOG_TARGET, "Handling remote request {}", request);
@@ -416,6 +451,23 @@ where T: BlockchainBackend + 'static
 
                 Ok(NodeCommsResponse::TransactionKernels(kernels))
             },
+            NodeCommsRequest::FetchMempoolTransactionsByShortIds { block_hash, short_ids } => {
+                if short_ids.len() > MAX_SHORT_IDS_PER_REQUEST {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Not fetching {} transaction(s) of compact block `{}`, the limit is {}",
+                        short_ids.len(),
+                        block_hash.to_hex(),
+                        MAX_SHORT_IDS_PER_REQUEST
+                    );
+                    return Ok(NodeCommsResponse::Transactions(Vec::new()));
+                }
+                let transactions =
+                    async_mempool::retrieve_by_short_kernel_ids(self.mempool.clone(), block_hash, short_ids).await?;
+                Ok(NodeCommsResponse::Transactions(
+                    transactions.iter().map(|tx| (**tx).clone()).collect(),
+                ))
+            },
         }
     }
 
@@ -428,7 +480,10 @@ where T: BlockchainBackend + 'static
         new_block: NewBlock,
         source_peer: NodeId,
     ) -> Result<(), CommsInterfaceError> {
-        let NewBlock { block_hash } = new_block;
+        let NewBlock {
+            block_hash,
+            compact_block,
+        } = new_block;
 
         // Only a single block request can complete at a time.
         // As multiple NewBlock requests arrive from propagation, this semaphore prevents multiple requests to nodes for
@@ -445,6 +500,23 @@ where T: BlockchainBackend + 'static
             return Ok(());
         }
 
+        if let Some(compact_block) = compact_block {
+            match self.reconstruct_compact_block(compact_block, &source_peer).await {
+                Ok(Some(block)) => {
+                    let block = Arc::new(block);
+                    self.handle_block(block, true.into(), Some(source_peer)).await?;
+                    return Ok(());
+                },
+                Ok(None) => {},
+                Err(err) => debug!(
+                    target: LOG_TARGET,
+                    "Could not reconstruct compact block `{}`: {}",
+                    block_hash.to_hex(),
+                    err
+                ),
+            }
+        }
+
         debug!(
             target: LOG_TARGET,
             "Block with hash `{}` is unknown. Requesting it from peer `{}`.",
@@ -458,8 +530,10 @@ where T: BlockchainBackend + 'static
 
         match block.pop() {
             Some(block) => {
-                self.handle_block(Arc::new(block.try_into_block()?), true.into(), Some(source_peer))
+                let block = Arc::new(block.try_into_block()?);
+                self.handle_block(block.clone(), true.into(), Some(source_peer.clone()))
                     .await?;
+                self.request_missing_ancestors(&block, source_peer).await;
                 Ok(())
             },
             None => {
@@ -477,6 +551,162 @@ where T: BlockchainBackend + 'static
         }
     }
 
+    /// Rebuilds a compact block from the transactions in the local mempool, requesting the transactions that are not in
+    /// the mempool from the peer that announced the block. Returns None if the block could not be rebuilt, in which case
+    /// the full block should be requested.
+    async fn reconstruct_compact_block(
+        &mut self,
+        compact_block: CompactBlock,
+        source_peer: &NodeId,
+    ) -> Result<Option<Block>, CommsInterfaceError> {
+        let block_hash = compact_block.hash();
+        // The MMR roots of the rebuilt block can only be checked when it builds on the current tip
+        let tip_header = self.blockchain_db.fetch_tip_header().await?;
+        if tip_header.hash() != &compact_block.header.prev_hash {
+            debug!(
+                target: LOG_TARGET,
+                "Compact block `{}` does not build on the tip",
+                block_hash.to_hex()
+            );
+            return Ok(None);
+        }
+
+        let mut transactions = async_mempool::retrieve_by_short_kernel_ids(
+            self.mempool.clone(),
+            block_hash.clone(),
+            compact_block.short_ids.clone(),
+        )
+        .await?;
+        let missing = compact_block.missing_short_ids(&transactions);
+        if missing.len() > MAX_SHORT_IDS_PER_REQUEST {
+            debug!(
+                target: LOG_TARGET,
+                "Compact block `{}` is missing {} transaction(s), more than can be requested from a peer",
+                block_hash.to_hex(),
+                missing.len()
+            );
+            return Ok(None);
+        }
+        if !missing.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "Requesting {} of {} transaction(s) of compact block `{}` from peer `{}`",
+                missing.len(),
+                compact_block.short_ids.len(),
+                block_hash.to_hex(),
+                source_peer.short_str()
+            );
+            let fetched = self
+                .outbound_nci
+                .request_mempool_transactions_from_peer(block_hash.clone(), missing, Some(source_peer.clone()))
+                .await?;
+            transactions.extend(fetched.into_iter().map(Arc::new));
+        }
+
+        let block = match compact_block.reconstruct(&transactions) {
+            Some(block) => block,
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Transactions of compact block `{}` could not be matched",
+                    block_hash.to_hex()
+                );
+                return Ok(None);
+            },
+        };
+        let (block, roots) = self.blockchain_db.calculate_mmr_roots(block).await?;
+        let header = &block.header;
+        if header.kernel_mr != roots.kernel_mr ||
+            header.kernel_mmr_size != roots.kernel_mmr_size ||
+            header.input_mr != roots.input_mr ||
+            header.output_mr != roots.output_mr ||
+            header.witness_mr != roots.witness_mr ||
+            header.output_mmr_size != roots.output_mmr_size
+        {
+            debug!(
+                target: LOG_TARGET,
+                "Rebuilt compact block `{}` does not match the MMR roots in its header",
+                block_hash.to_hex()
+            );
+            return Ok(None);
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Rebuilt compact block `{}` with {} transaction(s) from the mempool",
+            block_hash.to_hex(),
+            transactions.len()
+        );
+        Ok(Some(block))
+    }
+
+    /// If the block was added as an orphan, requests its missing ancestors from the peer that provided it, so that
+    /// the orphan chain can be connected without waiting for the state machine to notice that the node is lagging.
+    /// At most `MAX_ORPHAN_ANCESTOR_REQUESTS` ancestors are requested; deeper forks are left to block sync.
+    async fn request_missing_ancestors(&mut self, block: &Block, source_peer: NodeId) {
+        let mut prev_hash = block.header.prev_hash.clone();
+        for _ in 0..MAX_ORPHAN_ANCESTOR_REQUESTS {
+            match self.blockchain_db.block_exists(prev_hash.clone()).await {
+                Ok(true) => return,
+                Ok(false) => {},
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "Could not check for orphan ancestor: {}", err);
+                    return;
+                },
+            }
+
+            debug!(
+                target: LOG_TARGET,
+                "Requesting orphan ancestor `{}` from peer `{}`",
+                prev_hash.to_hex(),
+                source_peer.short_str()
+            );
+            let ancestor = match self
+                .outbound_nci
+                .request_blocks_with_hashes_from_peer(vec![prev_hash.clone()], Some(source_peer.clone()))
+                .await
+            {
+                Ok(mut blocks) => blocks.pop(),
+                Err(err) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Peer `{}` did not provide orphan ancestor `{}`: {}",
+                        source_peer.short_str(),
+                        prev_hash.to_hex(),
+                        err
+                    );
+                    return;
+                },
+            };
+            let ancestor = match ancestor.map(|b| b.try_into_block()) {
+                Some(Ok(ancestor)) if ancestor.hash() == prev_hash => Arc::new(ancestor),
+                _ => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Peer `{}` did not provide orphan ancestor `{}`",
+                        source_peer.short_str(),
+                        prev_hash.to_hex()
+                    );
+                    return;
+                },
+            };
+
+            prev_hash = ancestor.header.prev_hash.clone();
+            // Ancestors are not propagated, the peer that provided them already has them
+            if let Err(err) = self
+                .handle_block(ancestor, false.into(), Some(source_peer.clone()))
+                .await
+            {
+                debug!(target: LOG_TARGET, "Orphan ancestor was not added: {}", err);
+                return;
+            }
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Orphan ancestor limit reached for block #{}, leaving the rest to block sync",
+            block.header.height
+        );
+    }
+
     /// Handle inbound blocks from remote nodes and local services.
     pub async fn handle_block(
         &self,
@@ -497,6 +727,15 @@ where T: BlockchainBackend + 'static
                 .unwrap_or_else(|| "local services".to_string())
         );
         trace!(target: LOG_TARGET, "Block: {}", block);
+        if self.blockchain_db.inner().is_read_only() {
+            warn!(
+                target: LOG_TARGET,
+                "Block #{} ({}) not added because the blockchain database is in read-only mode",
+                block_height,
+                block_hash.to_hex(),
+            );
+            return Err(CommsInterfaceError::ChainStorageError(ChainStorageError::ReadOnly));
+        }
         let add_block_result = self.blockchain_db.add_block(block.clone()).await;
         // Create block event on block event stream
         match add_block_result {
@@ -512,7 +751,7 @@ where T: BlockchainBackend + 'static
 
                 self.blockchain_db.cleanup_orphans().await?;
 
-                self.publish_block_event(BlockEvent::ValidBlockAdded(block, block_add_result, broadcast));
+                self.publish_block_event(BlockEvent::ValidBlockAdded(block.clone(), block_add_result, broadcast));
 
                 if should_propagate && broadcast.is_true() {
                     info!(
@@ -520,8 +759,9 @@ where T: BlockchainBackend + 'static
                         "Propagate block ({}) to network.",
                         block_hash.to_hex()
                     );
-                    let exclude_peers = source_peer.into_iter().collect();
-                    let new_block = NewBlock::new(block_hash.clone());
+                    let exclude_peers: Vec<NodeId> = source_peer.into_iter().collect();
+                    self.propagated_blocks.insert(block_hash.clone(), exclude_peers.clone());
+                    let new_block = NewBlock::from(block.as_ref());
                     self.outbound_nci.propagate_block(new_block, exclude_peers).await?;
                 }
                 Ok(block_hash)
@@ -575,6 +815,7 @@ impl<T> Clone for InboundNodeCommsHandlers<T> {
             consensus_manager: self.consensus_manager.clone(),
             new_block_request_semaphore: self.new_block_request_semaphore.clone(),
             outbound_nci: self.outbound_nci.clone(),
+            propagated_blocks: self.propagated_blocks.clone(),
         }
     }
 }
@@ -37,3 +37,5 @@ pub use local_interface::{BlockEventReceiver, BlockEventSender, LocalNodeCommsIn
 
 mod outbound_interface;
 pub use outbound_interface::OutboundNodeCommsInterface;
+
+mod propagated_blocks;
@@ -22,9 +22,9 @@
 
 use crate::{
     base_node::comms_interface::{error::CommsInterfaceError, NodeCommsRequest, NodeCommsResponse},
-    blocks::{block_header::BlockHeader, NewBlock},
+    blocks::{block_header::BlockHeader, NewBlock, ShortKernelId},
     chain_storage::HistoricalBlock,
-    transactions::transaction::TransactionOutput,
+    transactions::transaction::{Transaction, TransactionOutput},
 };
 use log::*;
 use tari_common_types::{
@@ -231,6 +231,27 @@ impl OutboundNodeCommsInterface {
         }
     }
 
+    /// Fetch the transactions matching the short kernel ids of a compact block from the mempool of a specific base node.
+    pub async fn request_mempool_transactions_from_peer(
+        &mut self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortKernelId>,
+        node_id: Option<NodeId>,
+    ) -> Result<Vec<Transaction>, CommsInterfaceError> {
+        if let NodeCommsResponse::Transactions(transactions) = self
+            .request_sender
+            .call((
+                NodeCommsRequest::FetchMempoolTransactionsByShortIds { block_hash, short_ids },
+                node_id,
+            ))
+            .await??
+        {
+            Ok(transactions)
+        } else {
+            Err(CommsInterfaceError::UnexpectedApiResponse)
+        }
+    }
+
     /// Transmit a block to remote base nodes, excluding the provided peers.
     pub async fn propagate_block(
         &self,
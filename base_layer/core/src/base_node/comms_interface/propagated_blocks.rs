@@ -0,0 +1,113 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use tari_common_types::types::BlockHash;
+use tari_comms::peer_manager::NodeId;
+
+/// The number of recently propagated blocks whose transactions peers may request by short kernel id
+const MAX_PROPAGATED_BLOCKS: usize = 10;
+/// How long after a block was propagated its transactions may be requested by short kernel id
+const PROPAGATED_BLOCK_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Remembers the blocks this node recently propagated, so that requests for the transactions of a compact block are
+/// only answered for peers the block was actually sent to. Blocks are flooded to all connected peers except the
+/// excluded ones, so those are all that is recorded.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PropagatedBlocks {
+    inner: Arc<Mutex<VecDeque<PropagatedBlock>>>,
+}
+
+#[derive(Debug)]
+struct PropagatedBlock {
+    block_hash: BlockHash,
+    excluded_peers: Vec<NodeId>,
+    propagated_at: Instant,
+}
+
+impl PropagatedBlocks {
+    /// Records that the block was propagated to every connected peer except `excluded_peers`
+    pub fn insert(&self, block_hash: BlockHash, excluded_peers: Vec<NodeId>) {
+        let mut blocks = acquire(&self.inner);
+        blocks.retain(|b| b.block_hash != block_hash);
+        if blocks.len() >= MAX_PROPAGATED_BLOCKS {
+            blocks.pop_front();
+        }
+        blocks.push_back(PropagatedBlock {
+            block_hash,
+            excluded_peers,
+            propagated_at: Instant::now(),
+        });
+    }
+
+    /// Returns true if the block was recently propagated to the peer
+    pub fn was_propagated_to(&self, block_hash: &[u8], peer: &NodeId) -> bool {
+        acquire(&self.inner).iter().any(|b| {
+            b.block_hash == block_hash &&
+                b.propagated_at.elapsed() < PROPAGATED_BLOCK_TTL &&
+                !b.excluded_peers.contains(peer)
+        })
+    }
+}
+
+fn acquire(inner: &Mutex<VecDeque<PropagatedBlock>>) -> MutexGuard<'_, VecDeque<PropagatedBlock>> {
+    // Losing track of a propagation only means a peer falls back to requesting the full block
+    inner.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_common_types::types::{PrivateKey, PublicKey};
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+    fn random_node_id() -> NodeId {
+        NodeId::from_public_key(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)))
+    }
+
+    #[test]
+    fn it_only_matches_peers_the_block_was_sent_to() {
+        let blocks = PropagatedBlocks::default();
+        let source = random_node_id();
+        let peer = random_node_id();
+        blocks.insert(vec![1; 32], vec![source.clone()]);
+        assert!(blocks.was_propagated_to(&[1; 32], &peer));
+        assert!(!blocks.was_propagated_to(&[1; 32], &source));
+        assert!(!blocks.was_propagated_to(&[2; 32], &peer));
+    }
+
+    #[test]
+    fn it_forgets_the_oldest_block() {
+        let blocks = PropagatedBlocks::default();
+        let peer = random_node_id();
+        for i in 0..=MAX_PROPAGATED_BLOCKS {
+            blocks.insert(vec![i as u8; 32], vec![]);
+        }
+        assert!(!blocks.was_propagated_to(&[0; 32], &peer));
+        assert!(blocks.was_propagated_to(&[MAX_PROPAGATED_BLOCKS as u8; 32], &peer));
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{
+    chain_storage::{MmrMembershipProof, MmrTree},
+    proto::base_node as proto,
+};
+use std::convert::{TryFrom, TryInto};
+use tari_mmr::MerkleProof;
+
+impl From<MmrMembershipProof> for proto::MmrMembershipProof {
+    fn from(proof: MmrMembershipProof) -> Self {
+        Self {
+            header: Some(proof.header.into()),
+            tree: proto::MmrTree::from(proof.tree) as i32,
+            leaf_index: proof.leaf_index,
+            leaf_hash: proof.leaf_hash,
+            mmr_size: proof.merkle_proof.mmr_size() as u64,
+            path: proof.merkle_proof.path().to_vec(),
+            peaks: proof.merkle_proof.peaks().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<proto::MmrMembershipProof> for MmrMembershipProof {
+    type Error = String;
+
+    fn try_from(proof: proto::MmrMembershipProof) -> Result<Self, Self::Error> {
+        let tree = proto::MmrTree::from_i32(proof.tree).ok_or("Invalid MmrTree")?;
+        Ok(Self {
+            header: proof.header.map(TryInto::try_into).ok_or("header not provided")??,
+            tree: MmrTree::try_from(tree)?,
+            leaf_index: proof.leaf_index,
+            leaf_hash: proof.leaf_hash,
+            merkle_proof: MerkleProof::from_parts(proof.mmr_size as usize, proof.path, proof.peaks),
+        })
+    }
+}
@@ -23,6 +23,8 @@
 mod chain_metadata;
 pub mod wallet_rpc;
 
+#[cfg(feature = "base_node")]
+mod light_client;
 #[cfg(feature = "base_node")]
 mod mmr_tree;
 #[cfg(feature = "base_node")]
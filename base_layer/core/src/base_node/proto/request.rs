@@ -30,6 +30,7 @@ use crate::{
             BlockHeights,
             FetchHeadersAfter as ProtoFetchHeadersAfter,
             HashOutputs,
+            ShortKernelIds,
         },
     },
 };
@@ -82,6 +83,10 @@ impl TryInto<ci::NodeCommsRequest> for ProtoNodeCommsRequest {
             FetchKernelByExcessSig(sig) => ci::NodeCommsRequest::FetchKernelByExcessSig(
                 Signature::try_from(sig).map_err(|err: ByteArrayError| err.to_string())?,
             ),
+            FetchMempoolTransactionsByShortIds(request) => ci::NodeCommsRequest::FetchMempoolTransactionsByShortIds {
+                block_hash: request.block_hash,
+                short_ids: request.short_ids,
+            },
         };
         Ok(request)
     }
@@ -119,6 +124,9 @@ impl From<ci::NodeCommsRequest> for ProtoNodeCommsRequest {
             },
             GetNewBlock(block_template) => ProtoNodeCommsRequest::GetNewBlock(block_template.into()),
             FetchKernelByExcessSig(signature) => ProtoNodeCommsRequest::FetchKernelByExcessSig(signature.into()),
+            FetchMempoolTransactionsByShortIds { block_hash, short_ids } => {
+                ProtoNodeCommsRequest::FetchMempoolTransactionsByShortIds(ShortKernelIds { block_hash, short_ids })
+            },
         }
     }
 }
@@ -36,6 +36,7 @@ use crate::{
             NewBlockResponse as ProtoNewBlockResponse,
             TransactionKernels as ProtoTransactionKernels,
             TransactionOutputs as ProtoTransactionOutputs,
+            Transactions as ProtoTransactions,
         },
         core as core_proto_types,
     },
@@ -86,6 +87,10 @@ impl TryInto<ci::NodeCommsResponse> for ProtoNodeCommsResponse {
             },
             TargetDifficulty(difficulty) => ci::NodeCommsResponse::TargetDifficulty(Difficulty::from(difficulty)),
             MmrNodes(response) => ci::NodeCommsResponse::MmrNodes(response.added, response.deleted),
+            Transactions(transactions) => {
+                let transactions = try_convert_all(transactions.transactions)?;
+                ci::NodeCommsResponse::Transactions(transactions)
+            },
         };
 
         Ok(response)
@@ -127,6 +132,9 @@ impl From<ci::NodeCommsResponse> for ProtoNodeCommsResponse {
             }),
             TargetDifficulty(difficulty) => ProtoNodeCommsResponse::TargetDifficulty(difficulty.as_u64()),
             MmrNodes(added, deleted) => ProtoNodeCommsResponse::MmrNodes(ProtoMmrNodes { added, deleted }),
+            Transactions(transactions) => ProtoNodeCommsResponse::Transactions(ProtoTransactions {
+                transactions: transactions.into_iter().map(Into::into).collect(),
+            }),
         }
     }
 }
@@ -0,0 +1,233 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{
+    base_node::rpc::BalanceAuditService,
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, PrunedOutput},
+    proto::base_node::{AuditedOutput, BalanceAuditRequest, BalanceAuditResponse},
+    transactions::CryptoFactories,
+};
+use log::*;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tari_common_types::types::PublicKey;
+use tari_comms::{
+    peer_manager::NodeId,
+    protocol::rpc::{Request, Response, RpcStatus},
+};
+use tari_crypto::tari_utilities::{hex::Hex, ByteArray, Hashable};
+use tokio::sync::Semaphore;
+
+const LOG_TARGET: &str = "c::base_node::rpc::balance_audit";
+/// The number of headers fetched at a time while scanning
+const SCAN_HEADER_BATCH_SIZE: u64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct BalanceAuditConfig {
+    /// The minimum time between audits requested by the same peer
+    pub min_interval: Duration,
+    /// The maximum number of audits that are scanned at the same time
+    pub max_concurrent_audits: usize,
+}
+
+impl Default for BalanceAuditConfig {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_secs(600),
+            max_concurrent_audits: 2,
+        }
+    }
+}
+
+pub struct BalanceAuditRpcService<B> {
+    db: AsyncBlockchainDb<B>,
+    factories: CryptoFactories,
+    config: BalanceAuditConfig,
+    last_audits: Mutex<HashMap<NodeId, Instant>>,
+    audit_permits: Arc<Semaphore>,
+}
+
+impl<B: BlockchainBackend + 'static> BalanceAuditRpcService<B> {
+    pub fn new(db: AsyncBlockchainDb<B>, config: BalanceAuditConfig) -> Self {
+        Self {
+            db,
+            factories: CryptoFactories::default(),
+            audit_permits: Arc::new(Semaphore::new(config.max_concurrent_audits)),
+            config,
+            last_audits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[inline]
+    fn db(&self) -> AsyncBlockchainDb<B> {
+        self.db.clone()
+    }
+
+    /// Records an audit for the peer, or returns an error if the peer's previous audit was too recent
+    fn check_rate_limit(&self, node_id: &NodeId) -> Result<(), RpcStatus> {
+        let mut last_audits = self
+            .last_audits
+            .lock()
+            .map_err(|_| RpcStatus::general("Balance audit rate limiter lock poisoned"))?;
+        let min_interval = self.config.min_interval;
+        last_audits.retain(|_, last| last.elapsed() < min_interval);
+        if let Some(last) = last_audits.get(node_id) {
+            return Err(RpcStatus::forbidden(format!(
+                "Balance audits are limited to one every {:.0?}, try again in {:.0?}",
+                min_interval,
+                min_interval.saturating_sub(last.elapsed())
+            )));
+        }
+        last_audits.insert(node_id.clone(), Instant::now());
+        Ok(())
+    }
+
+    async fn scan(
+        &self,
+        rewind_public_key: PublicKey,
+        rewind_blinding_public_key: PublicKey,
+        start_height: u64,
+    ) -> Result<BalanceAuditResponse, RpcStatus> {
+        let db = self.db();
+        let tip_header = db
+            .fetch_tip_header()
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+            .into_header();
+        let tip_hash = tip_header.hash();
+        let mut response = BalanceAuditResponse {
+            scanned_height: tip_header.height,
+            scanned_hash: tip_hash.clone(),
+            ..Default::default()
+        };
+        if start_height > tip_header.height {
+            return Ok(response);
+        }
+
+        // Spent status is reported as at the tip
+        let deleted = Arc::new(
+            db.fetch_complete_deleted_bitmap_at(tip_hash)
+                .await
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+                .into_bitmap(),
+        );
+        let mut prev_mmr_size = match start_height.checked_sub(1) {
+            Some(height) => db
+                .fetch_header(height)
+                .await
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+                .map(|h| h.output_mmr_size)
+                .ok_or_else(|| RpcStatus::not_found(format!("Header not found at height {}", height)))?,
+            None => 0,
+        };
+
+        let mut batch_start = start_height;
+        while batch_start <= tip_header.height {
+            let batch_end = (batch_start + SCAN_HEADER_BATCH_SIZE - 1).min(tip_header.height);
+            let headers = db
+                .fetch_headers(batch_start..=batch_end)
+                .await
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+            for header in headers {
+                if header.output_mmr_size <= prev_mmr_size {
+                    continue;
+                }
+                let (outputs, _) = db
+                    .fetch_utxos_by_mmr_position(prev_mmr_size, header.output_mmr_size - 1, deleted.clone())
+                    .await
+                    .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+                response.num_scanned_outputs += outputs.len() as u64;
+                for (mmr_position, output) in (prev_mmr_size..).zip(outputs) {
+                    // Pruned outputs are spent and can no longer be rewound
+                    let output = match output {
+                        PrunedOutput::NotPruned { output } => output,
+                        PrunedOutput::Pruned { .. } => continue,
+                    };
+                    let rewound = match output.rewind_range_proof_value_only(
+                        &self.factories.range_proof,
+                        &rewind_public_key,
+                        &rewind_blinding_public_key,
+                    ) {
+                        Ok(rewound) => rewound,
+                        Err(_) => continue,
+                    };
+                    let is_spent = deleted.contains(mmr_position as u32);
+                    if !is_spent {
+                        response.balance += u64::from(rewound.committed_value);
+                    }
+                    response.outputs.push(AuditedOutput {
+                        commitment: output.commitment.to_vec(),
+                        output_hash: output.hash(),
+                        value: rewound.committed_value.into(),
+                        mined_height: header.height,
+                        is_spent,
+                    });
+                }
+                prev_mmr_size = header.output_mmr_size;
+            }
+            batch_start = batch_end + 1;
+        }
+
+        Ok(response)
+    }
+}
+
+#[tari_comms::async_trait]
+impl<B: BlockchainBackend + 'static> BalanceAuditService for BalanceAuditRpcService<B> {
+    async fn audit_balance(
+        &self,
+        request: Request<BalanceAuditRequest>,
+    ) -> Result<Response<BalanceAuditResponse>, RpcStatus> {
+        let node_id = request.context().peer_node_id().clone();
+        let message = request.into_message();
+        let rewind_public_key = PublicKey::from_bytes(&message.rewind_public_key)
+            .map_err(|_| RpcStatus::bad_request("Invalid rewind public key"))?;
+        let rewind_blinding_public_key = PublicKey::from_bytes(&message.rewind_blinding_public_key)
+            .map_err(|_| RpcStatus::bad_request("Invalid rewind blinding public key"))?;
+
+        self.check_rate_limit(&node_id)?;
+        let _permit = self.audit_permits.try_acquire().map_err(|_| {
+            RpcStatus::general("The maximum number of balance audits are already running, try again later")
+        })?;
+
+        let timer = Instant::now();
+        let response = self
+            .scan(rewind_public_key, rewind_blinding_public_key, message.start_height)
+            .await?;
+        debug!(
+            target: LOG_TARGET,
+            "Balance audit for peer {} scanned {} output(s) from height {} to {} ({}) in {:.2?} and found {}",
+            node_id,
+            response.num_scanned_outputs,
+            message.start_height,
+            response.scanned_height,
+            response.scanned_hash.to_hex(),
+            timer.elapsed(),
+            response.outputs.len()
+        );
+        Ok(Response::new(response))
+    }
+}
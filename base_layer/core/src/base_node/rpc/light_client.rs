@@ -0,0 +1,127 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{
+    base_node::rpc::LightClientService,
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
+    proto,
+    proto::{
+        base_node::{LightClientHeaders, LightClientHeadersRequest, MmrMembershipProof, OutputMembershipProofRequest},
+        types::Signature as SignatureProto,
+    },
+};
+use log::*;
+use std::{cmp, convert::TryFrom};
+use tari_common_types::types::Signature;
+use tari_comms::protocol::rpc::{Request, Response, RpcStatus};
+use tari_crypto::tari_utilities::hex::Hex;
+
+const LOG_TARGET: &str = "c::base_node::rpc::light_client";
+/// The maximum number of headers that can be requested in a single `get_headers` call
+const MAX_HEADERS_PER_REQUEST: u64 = 500;
+
+pub struct LightClientRpcService<B> {
+    db: AsyncBlockchainDb<B>,
+}
+
+impl<B: BlockchainBackend + 'static> LightClientRpcService<B> {
+    pub fn new(db: AsyncBlockchainDb<B>) -> Self {
+        Self { db }
+    }
+
+    #[inline]
+    fn db(&self) -> AsyncBlockchainDb<B> {
+        self.db.clone()
+    }
+}
+
+#[tari_comms::async_trait]
+impl<B: BlockchainBackend + 'static> LightClientService for LightClientRpcService<B> {
+    async fn get_tip_header(&self, _: Request<()>) -> Result<Response<proto::core::BlockHeader>, RpcStatus> {
+        let header = self
+            .db()
+            .fetch_tip_header()
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+        Ok(Response::new(header.into_header().into()))
+    }
+
+    async fn get_headers(
+        &self,
+        request: Request<LightClientHeadersRequest>,
+    ) -> Result<Response<LightClientHeaders>, RpcStatus> {
+        let message = request.into_message();
+        if message.count == 0 {
+            return Ok(Response::new(LightClientHeaders { headers: vec![] }));
+        }
+        let count = cmp::min(message.count, MAX_HEADERS_PER_REQUEST);
+        let end_height = message.start_height.saturating_add(count - 1);
+        debug!(
+            target: LOG_TARGET,
+            "Light client requested headers #{} to #{}", message.start_height, end_height
+        );
+
+        let headers = self
+            .db()
+            .fetch_headers(message.start_height..=end_height)
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+        Ok(Response::new(LightClientHeaders {
+            headers: headers.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_kernel_proof(
+        &self,
+        request: Request<SignatureProto>,
+    ) -> Result<Response<MmrMembershipProof>, RpcStatus> {
+        let excess_sig =
+            Signature::try_from(request.into_message()).map_err(|_| RpcStatus::bad_request("Signature was invalid"))?;
+        let proof = self
+            .db()
+            .fetch_kernel_membership_proof(excess_sig.clone())
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+            .ok_or_else(|| {
+                RpcStatus::not_found(format!(
+                    "Kernel with excess signature {} not found",
+                    excess_sig.get_signature().to_hex()
+                ))
+            })?;
+        Ok(Response::new(proof.into()))
+    }
+
+    async fn get_output_proof(
+        &self,
+        request: Request<OutputMembershipProofRequest>,
+    ) -> Result<Response<MmrMembershipProof>, RpcStatus> {
+        let output_hash = request.into_message().output_hash;
+        let proof = self
+            .db()
+            .fetch_output_membership_proof(output_hash.clone())
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+            .ok_or_else(|| RpcStatus::not_found(format!("Output {} not found", output_hash.to_hex())))?;
+        Ok(Response::new(proof.into()))
+    }
+}
@@ -20,6 +20,16 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+#[cfg(feature = "base_node")]
+mod balance_audit;
+#[cfg(feature = "base_node")]
+pub use balance_audit::{BalanceAuditConfig, BalanceAuditRpcService};
+
+#[cfg(feature = "base_node")]
+mod light_client;
+#[cfg(feature = "base_node")]
+pub use light_client::LightClientRpcService;
+
 #[cfg(feature = "base_node")]
 mod service;
 #[cfg(feature = "base_node")]
@@ -36,8 +46,16 @@ use crate::{
     proto,
     proto::{
         base_node::{
+            BalanceAuditRequest,
+            BalanceAuditResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
+            GetMempoolFeePerGramStatsRequest,
+            GetMempoolFeePerGramStatsResponse,
+            LightClientHeaders,
+            LightClientHeadersRequest,
+            MmrMembershipProof,
+            OutputMembershipProofRequest,
             Signatures,
             TipInfoResponse,
             TxQueryBatchResponses,
@@ -79,6 +97,46 @@ pub trait BaseNodeWalletService: Send + Sync + 'static {
 
     #[rpc(method = 6)]
     async fn get_header(&self, request: Request<u64>) -> Result<Response<proto::core::BlockHeader>, RpcStatus>;
+
+    #[rpc(method = 7)]
+    async fn get_mempool_fee_per_gram_stats(
+        &self,
+        request: Request<GetMempoolFeePerGramStatsRequest>,
+    ) -> Result<Response<GetMempoolFeePerGramStatsResponse>, RpcStatus>;
+}
+
+/// Serves block headers and MMR membership proofs so that light clients can verify that kernels and outputs were
+/// mined without trusting the base node.
+#[tari_rpc(protocol_name = b"t/bnlight/1", server_struct = LightClientRpcServer, client_struct = LightClientRpcClient)]
+pub trait LightClientService: Send + Sync + 'static {
+    #[rpc(method = 1)]
+    async fn get_tip_header(&self, request: Request<()>) -> Result<Response<proto::core::BlockHeader>, RpcStatus>;
+
+    #[rpc(method = 2)]
+    async fn get_headers(
+        &self,
+        request: Request<LightClientHeadersRequest>,
+    ) -> Result<Response<LightClientHeaders>, RpcStatus>;
+
+    #[rpc(method = 3)]
+    async fn get_kernel_proof(&self, request: Request<Signature>) -> Result<Response<MmrMembershipProof>, RpcStatus>;
+
+    #[rpc(method = 4)]
+    async fn get_output_proof(
+        &self,
+        request: Request<OutputMembershipProofRequest>,
+    ) -> Result<Response<MmrMembershipProof>, RpcStatus>;
+}
+
+/// Scans the chain on behalf of a wallet owner (e.g. an exchange) using its view (rewind) keys and reports the outputs
+/// it owns and its balance. This service is opt-in and rate limited, since every request is a full chain scan.
+#[tari_rpc(protocol_name = b"t/bnaudit/1", server_struct = BalanceAuditRpcServer, client_struct = BalanceAuditRpcClient)]
+pub trait BalanceAuditService: Send + Sync + 'static {
+    #[rpc(method = 1)]
+    async fn audit_balance(
+        &self,
+        request: Request<BalanceAuditRequest>,
+    ) -> Result<Response<BalanceAuditResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
@@ -89,3 +147,18 @@ pub fn create_base_node_wallet_rpc_service<B: BlockchainBackend + 'static>(
 ) -> BaseNodeWalletRpcServer<BaseNodeWalletRpcService<B>> {
     BaseNodeWalletRpcServer::new(BaseNodeWalletRpcService::new(db, mempool, state_machine))
 }
+
+#[cfg(feature = "base_node")]
+pub fn create_light_client_rpc_service<B: BlockchainBackend + 'static>(
+    db: AsyncBlockchainDb<B>,
+) -> LightClientRpcServer<LightClientRpcService<B>> {
+    LightClientRpcServer::new(LightClientRpcService::new(db))
+}
+
+#[cfg(feature = "base_node")]
+pub fn create_balance_audit_rpc_service<B: BlockchainBackend + 'static>(
+    db: AsyncBlockchainDb<B>,
+    config: BalanceAuditConfig,
+) -> BalanceAuditRpcServer<BalanceAuditRpcService<B>> {
+    BalanceAuditRpcServer::new(BalanceAuditRpcService::new(db, config))
+}
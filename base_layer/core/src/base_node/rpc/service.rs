@@ -29,6 +29,9 @@ use crate::{
         base_node::{
             FetchMatchingUtxos,
             FetchUtxosResponse,
+            GetMempoolFeePerGramStatsRequest,
+            GetMempoolFeePerGramStatsResponse,
+            MempoolFeePerGramStat,
             Signatures as SignaturesProto,
             TipInfoResponse,
             TxLocation,
@@ -47,6 +50,8 @@ use tari_common_types::types::Signature;
 use tari_comms::protocol::rpc::{Request, Response, RpcStatus};
 
 const LOG_TARGET: &str = "c::base_node::rpc";
+/// The maximum number of future blocks for which mempool fee per gram stats can be requested
+const MAX_FEE_PER_GRAM_STATS_COUNT: u64 = 20;
 
 pub struct BaseNodeWalletRpcService<B> {
     db: AsyncBlockchainDb<B>,
@@ -341,4 +346,43 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
 
         Ok(Response::new(header.into()))
     }
+
+    async fn get_mempool_fee_per_gram_stats(
+        &self,
+        request: Request<GetMempoolFeePerGramStatsRequest>,
+    ) -> Result<Response<GetMempoolFeePerGramStatsResponse>, RpcStatus> {
+        let count = request.into_message().count;
+        if count > MAX_FEE_PER_GRAM_STATS_COUNT {
+            return Err(RpcStatus::bad_request(format!(
+                "Cannot request fee per gram stats for more than {} blocks",
+                MAX_FEE_PER_GRAM_STATS_COUNT
+            )));
+        }
+
+        let max_block_weight = self
+            .db
+            .inner()
+            .consensus_constants()
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+            .get_max_block_weight_excluding_coinbase();
+        let response = self
+            .mempool()
+            .get_fee_per_gram_stats(count, max_block_weight)
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+
+        let stats = response
+            .stats
+            .into_iter()
+            .map(|stat| MempoolFeePerGramStat {
+                order: stat.order,
+                min_fee_per_gram: stat.min_fee_per_gram.into(),
+                avg_fee_per_gram: stat.avg_fee_per_gram.into(),
+                max_fee_per_gram: stat.max_fee_per_gram.into(),
+                total_weight: stat.total_weight,
+            })
+            .collect();
+
+        Ok(Response::new(GetMempoolFeePerGramStatsResponse { stats }))
+    }
 }
@@ -380,6 +380,7 @@ async fn handle_incoming_request<B: BlockchainBackend + 'static>(
     state_machine_handle: StateMachineHandle,
     domain_request_msg: DomainMessage<proto::BaseNodeServiceRequest>,
 ) -> Result<(), BaseNodeServiceError> {
+    let source_peer = domain_request_msg.source_peer.node_id.clone();
     let (origin_public_key, inner_msg) = domain_request_msg.into_origin_and_inner();
 
     // Convert proto::BaseNodeServiceRequest to a BaseNodeServiceRequest
@@ -388,7 +389,10 @@ async fn handle_incoming_request<B: BlockchainBackend + 'static>(
         .ok_or_else(|| BaseNodeServiceError::InvalidRequest("Received invalid base node request".to_string()))?;
 
     let response = inbound_nch
-        .handle_request(request.try_into().map_err(BaseNodeServiceError::InvalidRequest)?)
+        .handle_peer_request(
+            request.try_into().map_err(BaseNodeServiceError::InvalidRequest)?,
+            &source_peer,
+        )
         .await?;
 
     // Determine if we are synced
@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::base_node::state_machine_service::states::{StateEvent, StatusInfo};
+use crate::base_node::state_machine_service::{
+    observer::{StateTransitionObserver, StateTransitionObservers},
+    states::{StateEvent, StatusInfo, WarmupReport},
+};
 use std::sync::Arc;
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::{broadcast, watch};
@@ -28,19 +31,28 @@ use tokio::sync::{broadcast, watch};
 #[derive(Clone)]
 pub struct StateMachineHandle {
     state_change_event_subscriber: broadcast::Sender<Arc<StateEvent>>,
+    status_event_sender: Arc<watch::Sender<StatusInfo>>,
     status_event_receiver: watch::Receiver<StatusInfo>,
+    warmup_report_receiver: watch::Receiver<Option<WarmupReport>>,
+    transition_observers: StateTransitionObservers,
     shutdown_signal: ShutdownSignal,
 }
 
 impl StateMachineHandle {
     pub fn new(
         state_change_event_subscriber: broadcast::Sender<Arc<StateEvent>>,
+        status_event_sender: Arc<watch::Sender<StatusInfo>>,
         status_event_receiver: watch::Receiver<StatusInfo>,
+        warmup_report_receiver: watch::Receiver<Option<WarmupReport>>,
+        transition_observers: StateTransitionObservers,
         shutdown_signal: ShutdownSignal,
     ) -> Self {
         Self {
             state_change_event_subscriber,
+            status_event_sender,
             status_event_receiver,
+            warmup_report_receiver,
+            transition_observers,
             shutdown_signal,
         }
     }
@@ -59,6 +71,28 @@ impl StateMachineHandle {
         self.status_event_receiver.clone()
     }
 
+    /// Returns a watch of the report of the most recent connection warm-up, which is `None` until the first warm-up
+    /// completes
+    pub fn get_warmup_report_watch(&self) -> watch::Receiver<Option<WarmupReport>> {
+        self.warmup_report_receiver.clone()
+    }
+
+    /// Republishes the current status info with the read-only flag set. This should be called after the blockchain
+    /// database has been switched in or out of read-only mode so that subscribers do not have to wait for the next
+    /// state change to see it.
+    pub fn publish_read_only_status(&self, read_only: bool) {
+        let mut status = self.status_event_receiver.borrow().clone();
+        if status.read_only != read_only {
+            status.read_only = read_only;
+            let _ = self.status_event_sender.send(status);
+        }
+    }
+
+    /// Registers an observer that is called with every state transition of the state machine
+    pub fn register_transition_observer<T: StateTransitionObserver>(&self, observer: T) {
+        self.transition_observers.register(observer);
+    }
+
     pub fn shutdown_signal(&self) -> ShutdownSignal {
         self.shutdown_signal.clone()
     }
@@ -33,6 +33,7 @@ use crate::{
         chain_metadata_service::ChainMetadataHandle,
         state_machine_service::{
             handle::StateMachineHandle,
+            observer::StateTransitionObservers,
             state_machine::{BaseNodeStateMachine, BaseNodeStateMachineConfig},
             states::StatusInfo,
         },
@@ -81,10 +82,16 @@ where B: BlockchainBackend + 'static
         trace!(target: LOG_TARGET, "init of base_node");
         let (state_event_publisher, _) = broadcast::channel(500);
         let (status_event_sender, status_event_receiver) = watch::channel(StatusInfo::new());
+        let status_event_sender = Arc::new(status_event_sender);
+        let (warmup_report_sender, warmup_report_receiver) = watch::channel(None);
+        let transition_observers = StateTransitionObservers::new();
 
         let handle = StateMachineHandle::new(
             state_event_publisher.clone(),
+            status_event_sender.clone(),
             status_event_receiver,
+            warmup_report_receiver,
+            transition_observers.clone(),
             context.get_shutdown_signal(),
         );
         context.register_handle(handle);
@@ -120,7 +127,9 @@ where B: BlockchainBackend + 'static
                 config,
                 sync_validators,
                 status_event_sender,
+                warmup_report_sender,
                 state_event_publisher,
+                transition_observers,
                 RandomXFactory::new(max_randomx_vms),
                 rules,
                 handles.get_shutdown_signal(),
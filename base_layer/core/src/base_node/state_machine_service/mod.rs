@@ -25,6 +25,9 @@ pub use handle::StateMachineHandle;
 
 pub mod initializer;
 
+mod observer;
+pub use observer::{StateTransitionObserver, StateTransitionObservers};
+
 mod state_machine;
 pub use state_machine::{BaseNodeStateMachine, BaseNodeStateMachineConfig};
 
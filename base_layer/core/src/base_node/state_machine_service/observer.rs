@@ -0,0 +1,129 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::base_node::state_machine_service::states::{BaseNodeState, StateEvent};
+use async_trait::async_trait;
+use log::*;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::time;
+
+const LOG_TARGET: &str = "c::bn::state_machine_service::observer";
+
+/// The time an observer has to handle a transition before the state machine moves on without it
+const OBSERVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Receives the state transitions of the base node state machine, so that integrations (metrics, webhooks, etc.) can
+/// react to them without changing the state machine.
+///
+/// Observers are called in the order they were registered, and the state machine waits for each of them before
+/// running the next state. Observers that need to do slow work (e.g. network requests) should spawn it.
+#[async_trait]
+pub trait StateTransitionObserver: Send + Sync + 'static {
+    async fn on_transition(&self, from: &BaseNodeState, event: &StateEvent, to: &BaseNodeState);
+}
+
+/// The observers registered with the state machine. Observers can be registered while the state machine is running,
+/// and receive every transition after they are registered.
+#[derive(Clone, Default)]
+pub struct StateTransitionObservers {
+    observers: Arc<RwLock<Vec<Arc<dyn StateTransitionObserver>>>>,
+}
+
+impl StateTransitionObservers {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn register<T: StateTransitionObserver>(&self, observer: T) {
+        self.observers
+            .write()
+            .expect("observer lock poisoned")
+            .push(Arc::new(observer));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.read().expect("observer lock poisoned").is_empty()
+    }
+
+    pub(super) async fn notify(&self, from: &BaseNodeState, event: &StateEvent, to: &BaseNodeState) {
+        let observers = self.observers.read().expect("observer lock poisoned").clone();
+        for observer in observers {
+            if time::timeout(OBSERVER_TIMEOUT, observer.on_transition(from, event, to))
+                .await
+                .is_err()
+            {
+                warn!(
+                    target: LOG_TARGET,
+                    "A state transition observer took longer than {:.0?} to handle the transition from {} to {}",
+                    OBSERVER_TIMEOUT,
+                    from,
+                    to
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::base_node::state_machine_service::states::{Listening, Starting};
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct RecordingObserver {
+        transitions: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl StateTransitionObserver for RecordingObserver {
+        async fn on_transition(&self, from: &BaseNodeState, event: &StateEvent, to: &BaseNodeState) {
+            self.transitions
+                .lock()
+                .unwrap()
+                .push(format!("{} -> {} -> {}", from, event, to));
+        }
+    }
+
+    #[tokio::test]
+    async fn it_notifies_registered_observers() {
+        let observers = StateTransitionObservers::new();
+        assert!(observers.is_empty());
+        let from = BaseNodeState::Starting(Starting);
+        let to = BaseNodeState::Listening(Listening::new());
+        observers.notify(&from, &StateEvent::Initialized, &to).await;
+
+        let observer = RecordingObserver::default();
+        observers.register(observer.clone());
+        observers.clone().register(observer.clone());
+        assert!(!observers.is_empty());
+        observers.notify(&from, &StateEvent::Initialized, &to).await;
+
+        let transitions = observer.transitions.lock().unwrap();
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0], "Initializing -> Initialized -> Listening");
+    }
+}
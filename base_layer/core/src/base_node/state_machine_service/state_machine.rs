@@ -24,8 +24,19 @@ use crate::{
         chain_metadata_service::ChainMetadataEvent,
         comms_interface::{LocalNodeCommsInterface, OutboundNodeCommsInterface},
         state_machine_service::{
+            observer::StateTransitionObservers,
             states,
-            states::{BaseNodeState, HorizonSyncConfig, StateEvent, StateInfo, StatusInfo, SyncPeerConfig, SyncStatus},
+            states::{
+                BaseNodeState,
+                HorizonSyncConfig,
+                StateEvent,
+                StateInfo,
+                StatusInfo,
+                SyncPeerConfig,
+                SyncStatus,
+                WarmupConfig,
+                WarmupReport,
+            },
         },
         sync::{BlockSyncConfig, SyncValidators},
     },
@@ -37,6 +48,7 @@ use futures::{future, future::Either};
 use log::*;
 use randomx_rs::RandomXFlag;
 use std::{future::Future, sync::Arc};
+use tari_common::configuration::global::SyncMode;
 use tari_comms::{connectivity::ConnectivityRequester, PeerManager};
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::{broadcast, watch};
@@ -49,8 +61,11 @@ pub struct BaseNodeStateMachineConfig {
     pub block_sync_config: BlockSyncConfig,
     pub horizon_sync_config: HorizonSyncConfig,
     pub sync_peer_config: SyncPeerConfig,
+    pub warmup_config: WarmupConfig,
     pub orphan_db_clean_out_threshold: usize,
     pub pruning_horizon: u64,
+    /// The sync strategy, which must be compatible with `pruning_horizon`
+    pub sync_mode: SyncMode,
     pub max_randomx_vms: usize,
     pub blocks_behind_before_considered_lagging: u64,
     pub bypass_range_proof_verification: bool,
@@ -63,8 +78,10 @@ impl Default for BaseNodeStateMachineConfig {
             block_sync_config: Default::default(),
             horizon_sync_config: Default::default(),
             sync_peer_config: Default::default(),
+            warmup_config: Default::default(),
             orphan_db_clean_out_threshold: 0,
             pruning_horizon: 0,
+            sync_mode: SyncMode::Archival,
             max_randomx_vms: 0,
             blocks_behind_before_considered_lagging: 0,
             bypass_range_proof_verification: false,
@@ -92,9 +109,11 @@ pub struct BaseNodeStateMachine<B: BlockchainBackend> {
     pub(super) sync_validators: SyncValidators<B>,
     pub(super) consensus_rules: ConsensusManager,
     pub(super) status_event_sender: Arc<watch::Sender<StatusInfo>>,
+    warmup_report_sender: watch::Sender<Option<WarmupReport>>,
     pub(super) randomx_factory: RandomXFactory,
     is_bootstrapped: bool,
     event_publisher: broadcast::Sender<Arc<StateEvent>>,
+    transition_observers: StateTransitionObservers,
     interrupt_signal: ShutdownSignal,
 }
 
@@ -110,8 +129,10 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
         metadata_event_stream: broadcast::Receiver<Arc<ChainMetadataEvent>>,
         config: BaseNodeStateMachineConfig,
         sync_validators: SyncValidators<B>,
-        status_event_sender: watch::Sender<StatusInfo>,
+        status_event_sender: Arc<watch::Sender<StatusInfo>>,
+        warmup_report_sender: watch::Sender<Option<WarmupReport>>,
         event_publisher: broadcast::Sender<Arc<StateEvent>>,
+        transition_observers: StateTransitionObservers,
         randomx_factory: RandomXFactory,
         consensus_rules: ConsensusManager,
         interrupt_signal: ShutdownSignal,
@@ -126,7 +147,9 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
             config,
             info: StateInfo::StartUp,
             event_publisher,
-            status_event_sender: Arc::new(status_event_sender),
+            transition_observers,
+            status_event_sender,
+            warmup_report_sender,
             sync_validators,
             randomx_factory,
             is_bootstrapped: false,
@@ -142,12 +165,9 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
         match (state, event) {
             (Starting(s), Initialized) => Listening(s.into()),
             (Listening(s), InitialSync) => HeaderSync(s.into()),
-            (HeaderSync(_), HeadersSynchronized(conn)) => {
-                if self.config.pruning_horizon > 0 {
-                    HorizonStateSync(states::HorizonStateSync::with_peer(conn))
-                } else {
-                    BlockSync(states::BlockSync::with_peer(conn))
-                }
+            (HeaderSync(_), HeadersSynchronized(conn)) => match self.config.sync_mode {
+                SyncMode::Pruned => HorizonStateSync(states::HorizonStateSync::with_peer(conn)),
+                SyncMode::Archival | SyncMode::HeadersFirst => BlockSync(states::BlockSync::with_peer(conn)),
             },
             (HeaderSync(s), HeaderSyncFailed) => Waiting(s.into()),
             (HeaderSync(s), NetworkSilence) => Listening(s.into()),
@@ -155,8 +175,9 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
             (HorizonStateSync(s), HorizonStateSyncFailure) => Waiting(s.into()),
             (BlockSync(s), BlocksSynchronized) => Listening(s.into()),
             (BlockSync(s), BlockSyncFailed) => Waiting(s.into()),
-            (Listening(_), FallenBehind(Lagging(_, sync_peers))) => HeaderSync(sync_peers.into()),
-            (Listening(_), FallenBehind(LaggingBehindHorizon(_, sync_peers))) => HeaderSync(sync_peers.into()),
+            (BlockSync(_), HeaderTipForked) => HeaderSync(states::HeaderSync::new(Vec::new())),
+            (Listening(_), FallenBehind(Lagging { sync_peers, .. })) => HeaderSync(sync_peers.into()),
+            (Listening(_), FallenBehind(LaggingBehindHorizon { sync_peers, .. })) => HeaderSync(sync_peers.into()),
             (Waiting(s), Continue) => Listening(s.into()),
             (_, FatalError(s)) => Shutdown(states::Shutdown::with_reason(s)),
             (_, UserQuit) => Shutdown(states::Shutdown::with_reason("Shutdown initiated by user".to_string())),
@@ -177,6 +198,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
             state_info: self.info.clone(),
             randomx_vm_cnt: self.randomx_factory.get_count(),
             randomx_vm_flags: self.randomx_factory.get_flags(),
+            read_only: self.db.inner().is_read_only(),
         };
 
         if let Err(e) = self.status_event_sender.send(status) {
@@ -194,6 +216,22 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
         self.publish_event_info();
     }
 
+    /// Dials likely sync peers before the node starts relying on chain metadata from its connections, and publishes
+    /// the resulting report.
+    pub(super) async fn warm_up_connections(&mut self) {
+        let config = self.config.warmup_config;
+        if !config.is_enabled() {
+            return;
+        }
+        match states::warm_up_connections(&mut self.connectivity, &self.peer_manager, &config).await {
+            Ok(report) => {
+                info!(target: LOG_TARGET, "Connection warm-up: {}", report);
+                let _ = self.warmup_report_sender.send(Some(report));
+            },
+            Err(err) => warn!(target: LOG_TARGET, "Connection warm-up failed: {}", err),
+        }
+    }
+
     pub fn is_bootstrapped(&self) -> bool {
         self.is_bootstrapped
     }
@@ -232,7 +270,13 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
                 state,
                 next_event
             );
-            state = self.transition(state, next_event);
+            if self.transition_observers.is_empty() {
+                state = self.transition(state, next_event);
+            } else {
+                let from = state.clone();
+                state = self.transition(state, next_event.clone());
+                self.transition_observers.notify(&from, &next_event, &state).await;
+            }
         }
     }
 
@@ -24,19 +24,19 @@ use crate::{
     base_node::{
         comms_interface::BlockEvent,
         state_machine_service::states::{BlockSyncInfo, HorizonStateSync, StateEvent, StateInfo, StatusInfo},
-        sync::BlockSynchronizer,
+        sync::{BlockSyncError, BlockSynchronizer, HeaderTipTracker},
         BaseNodeStateMachine,
     },
-    chain_storage::{BlockAddResult, BlockchainBackend},
+    chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend},
 };
 use log::*;
 use randomx_rs::RandomXFlag;
-use std::time::Instant;
+use std::{cmp, sync::atomic::Ordering, time::Instant};
 use tari_comms::PeerConnection;
 
 const LOG_TARGET: &str = "c::bn::block_sync";
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct BlockSync {
     sync_peer: Option<PeerConnection>,
     is_synced: bool,
@@ -68,15 +68,28 @@ impl BlockSync {
 
         let status_event_sender = shared.status_event_sender.clone();
         let bootstrapped = shared.is_bootstrapped();
+        let read_only = shared.db.inner().is_read_only();
         let _ = status_event_sender.send(StatusInfo {
             bootstrapped,
             state_info: StateInfo::BlockSyncStarting,
             randomx_vm_cnt: 0,
             randomx_vm_flags: RandomXFlag::FLAG_DEFAULT,
+            read_only,
         });
+        // Headers keep being synced at the network tip while the block bodies are downloaded
+        let tip_tracker = HeaderTipTracker::new(
+            shared.config.block_sync_config.clone(),
+            shared.db.clone(),
+            shared.consensus_rules.clone(),
+            shared.connectivity.clone(),
+            shared.randomx_factory.clone(),
+        );
+        let header_tip_height = tip_tracker.header_tip_height();
+
         let local_nci = shared.local_node_interface.clone();
         let randomx_vm_cnt = shared.get_randomx_vm_cnt();
         let randomx_vm_flags = shared.get_randomx_vm_flags();
+        let sync_mode = shared.config.sync_mode;
         synchronizer.on_progress(move |block, remote_tip_height, sync_peers| {
             let local_height = block.height();
             local_nci.publish_block_event(BlockEvent::ValidBlockAdded(
@@ -88,12 +101,14 @@ impl BlockSync {
             let _ = status_event_sender.send(StatusInfo {
                 bootstrapped,
                 state_info: StateInfo::BlockSync(BlockSyncInfo {
-                    tip_height: remote_tip_height,
+                    tip_height: cmp::max(remote_tip_height, header_tip_height.load(Ordering::SeqCst)),
                     local_height,
                     sync_peers: sync_peers.to_vec(),
+                    sync_mode,
                 }),
                 randomx_vm_cnt,
                 randomx_vm_flags,
+                read_only,
             });
         });
 
@@ -103,15 +118,29 @@ impl BlockSync {
         });
 
         let timer = Instant::now();
-        match synchronizer.synchronize().await {
-            Ok(()) => {
-                info!(target: LOG_TARGET, "Blocks synchronized in {:.0?}", timer.elapsed());
-                self.is_synced = true;
-                StateEvent::BlocksSynchronized
+        let db = shared.db.clone();
+        tokio::select! {
+            result = sync_to_header_tip(&mut synchronizer, &db) => match result {
+                Ok(()) => {
+                    info!(target: LOG_TARGET, "Blocks synchronized in {:.0?}", timer.elapsed());
+                    self.is_synced = true;
+                    StateEvent::BlocksSynchronized
+                },
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "Block sync failed: {}", err);
+                    StateEvent::BlockSyncFailed
+                },
             },
-            Err(err) => {
-                warn!(target: LOG_TARGET, "Block sync failed: {}", err);
-                StateEvent::BlockSyncFailed
+            steps_back = tip_tracker.run(&mut shared.metadata_event_stream) => {
+                // Block sync was interrupted, so flush the blocks it committed without flushing
+                if let Err(err) = db.set_durable_commits(true).await {
+                    error!(target: LOG_TARGET, "Failed to flush the synced blocks to disk: {}", err);
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "Block sync stopped because a stronger chain forks {} header(s) below the header tip", steps_back
+                );
+                StateEvent::HeaderTipForked
             },
         }
     }
@@ -121,6 +150,26 @@ impl BlockSync {
     }
 }
 
+/// Syncs the block bodies up to the header tip, including the headers the tip tracker appends during the sync
+async fn sync_to_header_tip<B: BlockchainBackend + 'static>(
+    synchronizer: &mut BlockSynchronizer<B>,
+    db: &AsyncBlockchainDb<B>,
+) -> Result<(), BlockSyncError> {
+    loop {
+        let start_height = db.get_chain_metadata().await?.height_of_longest_chain();
+        synchronizer.synchronize().await?;
+        let best_height = db.get_chain_metadata().await?.height_of_longest_chain();
+        let header_tip_height = db.fetch_last_header().await?.height;
+        if header_tip_height <= best_height || best_height == start_height {
+            return Ok(());
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Headers advanced to #{} during block sync, syncing blocks from #{}", header_tip_height, best_height
+        );
+    }
+}
+
 impl From<HorizonStateSync> for BlockSync {
     fn from(_: HorizonStateSync) -> Self {
         BlockSync::new()
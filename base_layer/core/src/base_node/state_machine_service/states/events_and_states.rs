@@ -35,10 +35,11 @@ use crate::base_node::{
 };
 use randomx_rs::RandomXFlag;
 use std::fmt::{Display, Error, Formatter};
+use tari_common::configuration::global::SyncMode;
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_comms::{peer_manager::NodeId, PeerConnection};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum BaseNodeState {
     Starting(Starting),
     HeaderSync(HeaderSync),
@@ -61,6 +62,8 @@ pub enum StateEvent {
     HorizonStateSyncFailure,
     BlocksSynchronized,
     BlockSyncFailed,
+    /// A peer's stronger chain forks from the header chain that block sync is following
+    HeaderTipForked,
     FallenBehind(SyncStatus),
     NetworkSilence,
     FatalError(String),
@@ -78,12 +81,22 @@ impl<E: std::error::Error> From<E> for StateEvent {
 /// blockchain the local node is. It can either be very far behind (`LaggingBehindHorizon`), in which case we will just
 /// synchronise against the pruning horizon; we're somewhat behind (`Lagging`) and need to download the missing
 /// blocks to catch up, or we are `UpToDate`.
+/// `num_unsuitable_peers` counts the peers with the best chain that have pruned the history we need, which are not
+/// selected as sync peers.
 #[derive(Debug, Clone, PartialEq)]
 pub enum SyncStatus {
     // We are behind the chain tip.
-    Lagging(ChainMetadata, SyncPeers),
+    Lagging {
+        network: ChainMetadata,
+        sync_peers: SyncPeers,
+        num_unsuitable_peers: usize,
+    },
     // We are behind the pruning horizon.
-    LaggingBehindHorizon(ChainMetadata, SyncPeers),
+    LaggingBehindHorizon {
+        network: ChainMetadata,
+        sync_peers: SyncPeers,
+        num_unsuitable_peers: usize,
+    },
     UpToDate,
 }
 
@@ -101,25 +114,46 @@ impl Display for SyncStatus {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         use SyncStatus::*;
         match self {
-            Lagging(m, v) => write!(
-                f,
-                "Lagging behind {} peers (#{}, Difficulty: {})",
-                v.len(),
-                m.height_of_longest_chain(),
-                m.accumulated_difficulty(),
-            ),
-            LaggingBehindHorizon(m, v) => write!(
-                f,
-                "Lagging behind pruning horizon ({} peer(s), Network height: #{}, Difficulty: {})",
-                v.len(),
-                m.height_of_longest_chain(),
-                m.accumulated_difficulty(),
-            ),
+            Lagging {
+                network,
+                sync_peers,
+                num_unsuitable_peers,
+            } => {
+                write!(
+                    f,
+                    "Lagging behind {} peers (#{}, Difficulty: {})",
+                    sync_peers.len(),
+                    network.height_of_longest_chain(),
+                    network.accumulated_difficulty(),
+                )?;
+                write_unsuitable_peers(f, *num_unsuitable_peers)
+            },
+            LaggingBehindHorizon {
+                network,
+                sync_peers,
+                num_unsuitable_peers,
+            } => {
+                write!(
+                    f,
+                    "Lagging behind pruning horizon ({} peer(s), Network height: #{}, Difficulty: {})",
+                    sync_peers.len(),
+                    network.height_of_longest_chain(),
+                    network.accumulated_difficulty(),
+                )?;
+                write_unsuitable_peers(f, *num_unsuitable_peers)
+            },
             UpToDate => f.write_str("UpToDate"),
         }
     }
 }
 
+fn write_unsuitable_peers(f: &mut Formatter<'_>, num_unsuitable_peers: usize) -> Result<(), Error> {
+    if num_unsuitable_peers > 0 {
+        write!(f, ", {} peer(s) ahead but unsuitable (pruned)", num_unsuitable_peers)?;
+    }
+    Ok(())
+}
+
 impl Display for StateEvent {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         use StateEvent::*;
@@ -132,6 +166,7 @@ impl Display for StateEvent {
             HorizonStateSynchronized => f.write_str("Horizon State Synchronized"),
             HorizonStateSyncFailure => f.write_str("Horizon State Synchronization Failed"),
             BlockSyncFailed => f.write_str("Block Synchronization Failed"),
+            HeaderTipForked => f.write_str("Header Tip Forked"),
             FallenBehind(s) => write!(f, "Fallen behind main chain - {}", s),
             NetworkSilence => f.write_str("Network Silence"),
             Continue => f.write_str("Continuing"),
@@ -174,7 +209,7 @@ impl StateInfo {
         match self {
             StartUp => "Starting up".to_string(),
             HeaderSync(None) => "Starting header sync".to_string(),
-            HeaderSync(Some(info)) => format!("Syncing headers: {}", info.sync_progress_string()),
+            HeaderSync(Some(info)) => format!("Syncing headers ({}): {}", info.sync_mode, info.sync_progress_string()),
             HorizonSync(info) => match info.status {
                 HorizonSyncStatus::Starting => "Starting horizon sync".to_string(),
                 HorizonSyncStatus::Kernels(current, total) => format!(
@@ -192,12 +227,15 @@ impl StateInfo {
                 HorizonSyncStatus::Finalizing => "Finalizing horizon sync".to_string(),
             },
             BlockSync(info) => format!(
-                "Syncing blocks: ({}) {}",
+                "Syncing blocks ({}): ({}) headers at #{}, blocks at #{} ({:.0}%)",
+                info.sync_mode,
                 info.sync_peers
                     .first()
                     .map(|n| n.short_str())
                     .unwrap_or_else(|| "".to_string()),
-                info.sync_progress_string()
+                info.tip_height,
+                info.local_height,
+                info.local_height as f64 / info.tip_height as f64 * 100.0
             ),
             Listening(_) => "Listening".to_string(),
             BlockSyncStarting => "Starting block sync".to_string(),
@@ -242,6 +280,8 @@ pub struct StatusInfo {
     pub state_info: StateInfo,
     pub randomx_vm_cnt: usize,
     pub randomx_vm_flags: RandomXFlag,
+    /// True when the blockchain database has been switched to read-only mode, e.g. because disk space is running out
+    pub read_only: bool,
 }
 
 impl StatusInfo {
@@ -251,6 +291,7 @@ impl StatusInfo {
             state_info: StateInfo::StartUp,
             randomx_vm_cnt: 0,
             randomx_vm_flags: RandomXFlag::FLAG_DEFAULT,
+            read_only: false,
         }
     }
 }
@@ -263,7 +304,11 @@ impl Default for StatusInfo {
 
 impl Display for StatusInfo {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        write!(f, "Bootstrapped: {}, {}", self.bootstrapped, self.state_info)
+        write!(f, "Bootstrapped: {}, {}", self.bootstrapped, self.state_info)?;
+        if self.read_only {
+            write!(f, ", READ-ONLY")?;
+        }
+        Ok(())
     }
 }
 
@@ -273,15 +318,17 @@ pub struct BlockSyncInfo {
     pub tip_height: u64,
     pub local_height: u64,
     pub sync_peers: Vec<NodeId>,
+    pub sync_mode: SyncMode,
 }
 
 impl BlockSyncInfo {
     /// Creates a new blockSyncInfo
-    pub fn new(tip_height: u64, local_height: u64, sync_peers: Vec<NodeId>) -> BlockSyncInfo {
+    pub fn new(tip_height: u64, local_height: u64, sync_peers: Vec<NodeId>, sync_mode: SyncMode) -> BlockSyncInfo {
         BlockSyncInfo {
             tip_height,
             local_height,
             sync_peers,
+            sync_mode,
         }
     }
 
@@ -297,6 +344,7 @@ impl BlockSyncInfo {
 
 impl Display for BlockSyncInfo {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        writeln!(f, "Sync mode: {}", self.sync_mode)?;
         writeln!(f, "Syncing from the following peers:")?;
         for peer in &self.sync_peers {
             writeln!(f, "{}", peer)?;
@@ -74,19 +74,23 @@ impl HeaderSync {
 
         let status_event_sender = shared.status_event_sender.clone();
         let bootstrapped = shared.is_bootstrapped();
+        let read_only = shared.db.inner().is_read_only();
         let randomx_vm_cnt = shared.get_randomx_vm_cnt();
         let randomx_vm_flags = shared.get_randomx_vm_flags();
+        let sync_mode = shared.config.sync_mode;
         synchronizer.on_progress(move |details, sync_peers| {
             let details = details.map(|(current_height, remote_tip_height)| BlockSyncInfo {
                 tip_height: remote_tip_height,
                 local_height: current_height,
                 sync_peers: sync_peers.to_vec(),
+                sync_mode,
             });
             let _ = status_event_sender.send(StatusInfo {
                 bootstrapped,
                 state_info: StateInfo::HeaderSync(details),
                 randomx_vm_cnt,
                 randomx_vm_flags,
+                read_only,
             });
         });
 
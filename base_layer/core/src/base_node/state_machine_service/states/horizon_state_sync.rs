@@ -74,8 +74,9 @@ impl HorizonStateSync {
             return StateEvent::HorizonStateSynchronized;
         }
 
+        let pruning_policy = shared.db.inner().pruning_policy().clone();
         let horizon_sync_height = match shared.db.fetch_last_header().await {
-            Ok(header) => header.height.saturating_sub(local_metadata.pruning_horizon()),
+            Ok(header) => pruning_policy.horizon_height(header.height),
             Err(err) => return StateEvent::FatalError(err.to_string()),
         };
 
@@ -83,6 +84,16 @@ impl HorizonStateSync {
             return StateEvent::HorizonStateSynchronized;
         }
 
+        if pruning_policy.has_retained_commitments() {
+            warn!(
+                target: LOG_TARGET,
+                "{} retained commitment(s) are configured. Retained outputs that were spent below the horizon height \
+                 #{} cannot be recovered from sync peers.",
+                pruning_policy.num_retained_commitments(),
+                horizon_sync_height
+            );
+        }
+
         let info = HorizonSyncInfo::new(vec![self.sync_peer.peer_node_id().clone()], HorizonSyncStatus::Starting);
         shared.set_state_info(StateInfo::HorizonSync(info));
 
@@ -49,7 +49,7 @@ use std::{
     sync::Arc,
 };
 use tari_common_types::types::{HashDigest, RangeProofService};
-use tari_comms::PeerConnection;
+use tari_comms::{protocol::rpc::RpcCompression, PeerConnection};
 use tari_crypto::{
     commitment::HomomorphicCommitment,
     tari_utilities::{hex::Hex, Hashable},
@@ -97,7 +97,10 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
             }
         })?;
 
-        let mut client = self.sync_peer.connect_rpc::<rpc::BaseNodeSyncRpcClient>().await?;
+        let mut client = self
+            .sync_peer
+            .connect_rpc_using_builder(rpc::BaseNodeSyncRpcClient::builder().with_compression(RpcCompression::Zstd))
+            .await?;
 
         match self.begin_sync(&mut client, &header).await {
             Ok(_) => match self.finalize_horizon_sync().await {
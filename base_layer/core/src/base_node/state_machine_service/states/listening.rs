@@ -24,7 +24,16 @@ use crate::{
     base_node::{
         chain_metadata_service::{ChainMetadataEvent, PeerChainMetadata},
         state_machine_service::{
-            states::{BlockSync, HeaderSync, StateEvent, StateEvent::FatalError, StateInfo, SyncStatus, Waiting},
+            states::{
+                warmup::SleepDetector,
+                BlockSync,
+                HeaderSync,
+                StateEvent,
+                StateEvent::FatalError,
+                StateInfo,
+                SyncStatus,
+                Waiting,
+            },
             BaseNodeStateMachine,
         },
         sync::SyncPeers,
@@ -38,6 +47,7 @@ use std::{
     fmt::{Display, Formatter},
     ops::Deref,
 };
+use tari_common::configuration::global::SyncMode;
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_crypto::tari_utilities::epoch_time::EpochTime;
 use tokio::sync::broadcast;
@@ -117,8 +127,18 @@ impl Listening {
 
         info!(target: LOG_TARGET, "Listening for chain metadata updates");
         shared.set_state_info(StateInfo::Listening(ListeningInfo::new(self.is_synced)));
+        let mut sleep_detector = SleepDetector::new(shared.config.warmup_config.sleep_threshold);
         loop {
             let metadata_event = shared.metadata_event_stream.recv().await;
+            if let Some(slept) = sleep_detector.check() {
+                info!(
+                    target: LOG_TARGET,
+                    "Host appears to have been asleep for {:.0?}, warming up connections",
+                    slept
+                );
+                shared.warm_up_connections().await;
+                sleep_detector.reset();
+            }
             match metadata_event.as_ref().map(|v| v.deref()) {
                 Ok(ChainMetadataEvent::NetworkSilence) => {
                     debug!("NetworkSilence event received");
@@ -187,21 +207,42 @@ impl Listening {
                         },
                     };
 
-                    let local_tip_height = local.height_of_longest_chain();
                     // If we have configured sync peers, they are already filtered at this point
-                    let sync_peers = if configured_sync_peers.is_empty() {
-                        select_sync_peers(local_tip_height, &best_metadata, &peer_metadata_list)
+                    let (sync_peers, num_unsuitable_peers) = if configured_sync_peers.is_empty() {
+                        let can_horizon_sync = shared.config.sync_mode == SyncMode::Pruned;
+                        let required_height = required_sync_height(&local, &best_metadata, can_horizon_sync);
+                        select_sync_peers(required_height, &best_metadata, &peer_metadata_list)
                     } else {
-                        peer_metadata_list
+                        (peer_metadata_list, 0)
                     };
+                    let num_sync_peers = sync_peers.len();
                     let sync_mode = determine_sync_mode(
                         shared.config.blocks_behind_before_considered_lagging,
                         &local,
                         best_metadata,
                         sync_peers,
+                        num_unsuitable_peers,
                     );
 
                     if sync_mode.is_lagging() {
+                        if num_sync_peers == 0 {
+                            // Syncing from peers that do not have the blocks we need only ends in a sync failure, so
+                            // wait for a suitable peer to come along
+                            warn!(
+                                target: LOG_TARGET,
+                                "{} peer(s) ahead but unsuitable (pruned). Continuing in listening state.",
+                                num_unsuitable_peers
+                            );
+                            continue;
+                        }
+
+                        if shared.db.inner().is_read_only() {
+                            debug!(
+                                target: LOG_TARGET,
+                                "Not syncing because the blockchain database is in read-only mode"
+                            );
+                            continue;
+                        }
                         return StateEvent::FallenBehind(sync_mode);
                     }
 
@@ -251,22 +292,42 @@ impl From<BlockSync> for Listening {
     }
 }
 
+/// The height from which a sync peer must still have the full blocks to update the local node. This is the local tip,
+/// unless the local node can sync the horizon state and has fallen behind the network's pruning horizon, in which case
+/// it only needs blocks from the horizon onwards.
+fn required_sync_height(local: &ChainMetadata, network: &ChainMetadata, can_horizon_sync: bool) -> u64 {
+    let local_tip_height = local.height_of_longest_chain();
+    let network_horizon_block = local.horizon_block(network.height_of_longest_chain());
+    if can_horizon_sync && local_tip_height < network_horizon_block {
+        network_horizon_block
+    } else {
+        local_tip_height
+    }
+}
+
 // Finds the set of sync peers that have the best tip on their main chain and have all the data required to update the
-// local node.
+// local node. Also returns the number of peers with the best tip that were excluded because they have pruned the
+// blocks above `required_height`.
 fn select_sync_peers(
-    local_tip_height: u64,
+    required_height: u64,
     best_metadata: &ChainMetadata,
     peer_metadata_list: &[PeerChainMetadata],
-) -> Vec<PeerChainMetadata> {
-    peer_metadata_list
+) -> (Vec<PeerChainMetadata>, usize) {
+    let (sync_peers, unsuitable_peers) = peer_metadata_list
         .iter()
-        // Check if the peer can provide blocks higher than the local tip height
-        .filter(|peer| {
-                let peer_horizon_height = peer.chain_metadata.pruned_height();
-                local_tip_height >= peer_horizon_height && peer.chain_metadata.best_block() == best_metadata.best_block()
-        })
+        .filter(|peer| peer.chain_metadata.best_block() == best_metadata.best_block())
         .cloned()
-        .collect()
+        // Check if the peer can provide the blocks from the required height
+        .partition::<Vec<_>, _>(|peer| required_height >= peer.chain_metadata.pruned_height());
+    if !unsuitable_peers.is_empty() {
+        debug!(
+            target: LOG_TARGET,
+            "{} peer(s) ahead but pruned above the required height #{}",
+            unsuitable_peers.len(),
+            required_height
+        );
+    }
+    (sync_peers, unsuitable_peers.len())
 }
 
 /// Determine the best metadata from a set of metadata received from the network.
@@ -289,6 +350,7 @@ fn determine_sync_mode(
     local: &ChainMetadata,
     network: ChainMetadata,
     sync_peers: SyncPeers,
+    num_unsuitable_peers: usize,
 ) -> SyncStatus {
     use SyncStatus::*;
     let network_tip_accum_difficulty = network.accumulated_difficulty();
@@ -327,10 +389,18 @@ fn determine_sync_mode(
                 "Lagging behind horizon ({} sync peer(s))",
                 sync_peers.len()
             );
-            LaggingBehindHorizon(network, sync_peers)
+            LaggingBehindHorizon {
+                network,
+                sync_peers,
+                num_unsuitable_peers,
+            }
         } else {
             debug!(target: LOG_TARGET, "Lagging ({} sync peer(s))", sync_peers.len());
-            Lagging(network, sync_peers)
+            Lagging {
+                network,
+                sync_peers,
+                num_unsuitable_peers,
+            }
         }
     } else {
         info!(
@@ -372,8 +442,10 @@ mod test {
         assert!(best_network_metadata.is_none());
         let best_network_metadata = ChainMetadata::empty();
         assert_eq!(best_network_metadata, ChainMetadata::new(0, Vec::new(), 0, 0, 0));
-        let sync_peers = select_sync_peers(local_tip_height, &best_network_metadata, &peer_metadata_list);
+        let (sync_peers, num_unsuitable_peers) =
+            select_sync_peers(local_tip_height, &best_network_metadata, &peer_metadata_list);
         assert_eq!(sync_peers.len(), 0);
+        assert_eq!(num_unsuitable_peers, 0);
 
         let node_id1 = random_node_id();
         let node_id2 = random_node_id();
@@ -434,8 +506,11 @@ mod test {
         assert_eq!(best_network_metadata.height_of_longest_chain(), network_tip_height);
         assert_eq!(best_network_metadata.best_block(), &block_hash1);
         assert_eq!(best_network_metadata.accumulated_difficulty(), accumulated_difficulty1);
-        let sync_peers = select_sync_peers(local_tip_height, best_network_metadata, &peer_metadata_list);
+        let (sync_peers, num_unsuitable_peers) =
+            select_sync_peers(local_tip_height, best_network_metadata, &peer_metadata_list);
         assert_eq!(sync_peers.len(), 3);
+        // Only peer2 is on the best chain but pruned above the local tip
+        assert_eq!(num_unsuitable_peers, 1);
         sync_peers.iter().find(|p| p.node_id == node_id1).unwrap();
         sync_peers.iter().find(|p| p.node_id == node_id3).unwrap();
         sync_peers.iter().find(|p| p.node_id == node_id5).unwrap();
@@ -444,42 +519,77 @@ mod test {
     #[test]
     fn sync_mode_selection() {
         let local = ChainMetadata::new(0, Vec::new(), 0, 0, 500_000);
-        match determine_sync_mode(0, &local, local.clone(), vec![]) {
+        match determine_sync_mode(0, &local, local.clone(), vec![], 0) {
             SyncStatus::UpToDate => {},
             _ => panic!(),
         }
 
         let network = ChainMetadata::new(0, Vec::new(), 0, 0, 499_000);
-        match determine_sync_mode(0, &local, network, vec![]) {
+        match determine_sync_mode(0, &local, network, vec![], 0) {
             SyncStatus::UpToDate => {},
             _ => panic!(),
         }
 
         let network = ChainMetadata::new(0, Vec::new(), 0, 0, 500_001);
-        match determine_sync_mode(0, &local, network.clone(), vec![]) {
-            SyncStatus::Lagging(n, _) => assert_eq!(n, network),
+        match determine_sync_mode(0, &local, network.clone(), vec![], 0) {
+            SyncStatus::Lagging { network: n, .. } => assert_eq!(n, network),
             _ => panic!(),
         }
 
         let local = ChainMetadata::new(100, Vec::new(), 50, 50, 500_000);
         let network = ChainMetadata::new(150, Vec::new(), 0, 0, 500_001);
-        match determine_sync_mode(0, &local, network.clone(), vec![]) {
-            SyncStatus::Lagging(n, _) => assert_eq!(n, network),
+        match determine_sync_mode(0, &local, network.clone(), vec![], 0) {
+            SyncStatus::Lagging { network: n, .. } => assert_eq!(n, network),
             _ => panic!(),
         }
 
         let local = ChainMetadata::new(0, Vec::new(), 50, 50, 500_000);
         let network = ChainMetadata::new(100, Vec::new(), 0, 0, 500_001);
-        match determine_sync_mode(0, &local, network.clone(), vec![]) {
-            SyncStatus::LaggingBehindHorizon(n, _) => assert_eq!(n, network),
+        match determine_sync_mode(0, &local, network.clone(), vec![], 0) {
+            SyncStatus::LaggingBehindHorizon { network: n, .. } => assert_eq!(n, network),
             _ => panic!(),
         }
 
         let local = ChainMetadata::new(99, Vec::new(), 50, 50, 500_000);
         let network = ChainMetadata::new(150, Vec::new(), 0, 0, 500_001);
-        match determine_sync_mode(0, &local, network.clone(), vec![]) {
-            SyncStatus::LaggingBehindHorizon(n, _) => assert_eq!(n, network),
+        match determine_sync_mode(0, &local, network.clone(), vec![], 0) {
+            SyncStatus::LaggingBehindHorizon { network: n, .. } => assert_eq!(n, network),
             _ => panic!(),
         }
     }
+
+    #[test]
+    fn required_sync_height_for_pruned_node() {
+        let network = ChainMetadata::new(5000, Vec::new(), 0, 0, 500_001);
+        // Archival node needs everything above its tip
+        let local = ChainMetadata::new(1000, Vec::new(), 0, 0, 500_000);
+        assert_eq!(required_sync_height(&local, &network, false), 1000);
+        // Pruned node within the horizon needs everything above its tip
+        let local = ChainMetadata::new(4000, Vec::new(), 2880, 4000 - 2880, 500_000);
+        assert_eq!(required_sync_height(&local, &network, true), 4000);
+        // Pruned node behind the horizon only needs blocks from the network horizon onwards
+        let local = ChainMetadata::new(1000, Vec::new(), 2880, 0, 500_000);
+        assert_eq!(required_sync_height(&local, &network, true), 5000 - 2880);
+        // A headers-first node syncs every block from its tip
+        assert_eq!(required_sync_height(&local, &network, false), 1000);
+    }
+
+    #[test]
+    fn sync_status_display_unsuitable_peers() {
+        let network = ChainMetadata::new(150, Vec::new(), 0, 0, 500_001);
+        let status = SyncStatus::Lagging {
+            network: network.clone(),
+            sync_peers: vec![],
+            num_unsuitable_peers: 0,
+        };
+        assert!(!status.to_string().contains("unsuitable"));
+        let status = SyncStatus::LaggingBehindHorizon {
+            network,
+            sync_peers: vec![],
+            num_unsuitable_peers: 2,
+        };
+        assert!(status
+            .to_string()
+            .ends_with(", 2 peer(s) ahead but unsuitable (pruned)"));
+    }
 }
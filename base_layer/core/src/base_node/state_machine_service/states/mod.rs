@@ -87,3 +87,7 @@ pub use starting_state::Starting;
 
 mod waiting;
 pub use waiting::Waiting;
+
+mod warmup;
+pub(super) use warmup::warm_up_connections;
+pub use warmup::{WarmupConfig, WarmupReport};
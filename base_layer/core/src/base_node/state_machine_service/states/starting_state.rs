@@ -36,8 +36,14 @@ const LOG_TARGET: &str = "c::bn::state_machine_service::states::starting_state";
 pub struct Starting;
 
 impl Starting {
-    pub async fn next_event<B: BlockchainBackend>(&mut self, _shared: &BaseNodeStateMachine<B>) -> StateEvent {
+    pub async fn next_event<B: BlockchainBackend + 'static>(
+        &mut self,
+        shared: &mut BaseNodeStateMachine<B>,
+    ) -> StateEvent {
         info!(target: LOG_TARGET, "Starting node.");
+        // Connections to likely sync peers are made up front, so that falling behind is detected from the first round
+        // of chain metadata rather than after the connectivity manager has gradually built up its pool
+        shared.warm_up_connections().await;
         StateEvent::Initialized
     }
 }
@@ -0,0 +1,316 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::base_node::state_machine_service::states::{helpers::BaseNodeRequestError, PeerMetadata};
+use futures::StreamExt;
+use log::*;
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    time::{Duration, Instant, SystemTime},
+};
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, Peer},
+    PeerManager,
+};
+use tokio::time;
+
+const LOG_TARGET: &str = "c::bn::state_machine_service::states::warmup";
+
+/// The peer metadata key under which the listening state stores the last chain metadata received from a peer
+const PEER_METADATA_KEY: u8 = 1;
+
+/// Configuration for the connection warm-up that runs before the node starts listening for chain metadata.
+#[derive(Clone, Copy, Debug)]
+pub struct WarmupConfig {
+    /// The number of most recently connected base nodes to dial
+    pub num_recent_peers: usize,
+    /// The number of base nodes with the strongest known chain to dial
+    pub num_sync_candidates: usize,
+    /// The maximum time to wait for the warm-up dials to complete
+    pub timeout: Duration,
+    /// The warm-up is run again when the wall clock has moved this much further than the monotonic clock between two
+    /// chain metadata events, i.e. when the host has been asleep. Zero disables the check.
+    pub sleep_threshold: Duration,
+}
+
+impl WarmupConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.num_recent_peers > 0 || self.num_sync_candidates > 0
+    }
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            num_recent_peers: 8,
+            num_sync_candidates: 4,
+            timeout: Duration::from_secs(30),
+            sleep_threshold: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// The outcome of a connection warm-up
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WarmupReport {
+    /// The number of recently connected peers selected
+    pub recent_peers: usize,
+    /// The number of sync candidates selected
+    pub sync_candidates: usize,
+    /// The number of selected peers that were already connected and were not dialed
+    pub already_connected: usize,
+    pub connected: usize,
+    pub failed: usize,
+    /// The number of dials that had not completed when the warm-up timed out
+    pub timed_out: usize,
+    pub elapsed: Duration,
+}
+
+impl WarmupReport {
+    /// The number of selected peers that are connected after the warm-up
+    pub fn num_connected(&self) -> usize {
+        self.already_connected + self.connected
+    }
+}
+
+impl Display for WarmupReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} recent peer(s) and {} sync candidate(s): {} connected, {} already connected, {} failed, {} timed out \
+             in {:.2?}",
+            self.recent_peers,
+            self.sync_candidates,
+            self.connected,
+            self.already_connected,
+            self.failed,
+            self.timed_out,
+            self.elapsed
+        )
+    }
+}
+
+/// Dials the most recently connected base nodes and the base nodes with the strongest known chain in parallel, so
+/// that connections to likely sync peers exist by the time the first chain metadata arrives. Dials that do not
+/// complete within the configured timeout are left to finish in the background.
+pub async fn warm_up_connections(
+    connectivity: &mut ConnectivityRequester,
+    peer_manager: &PeerManager,
+    config: &WarmupConfig,
+) -> Result<WarmupReport, BaseNodeRequestError> {
+    let timer = Instant::now();
+    let (sync_candidates, recent_peers) = select_warmup_peers(peer_manager.all().await?, config);
+    let mut report = WarmupReport {
+        recent_peers: recent_peers.len(),
+        sync_candidates: sync_candidates.len(),
+        ..Default::default()
+    };
+
+    let active = connectivity
+        .get_active_connections()
+        .await?
+        .into_iter()
+        .map(|conn| conn.peer_node_id().clone())
+        .collect::<HashSet<_>>();
+    let to_dial = sync_candidates
+        .into_iter()
+        .chain(recent_peers)
+        .filter(|node_id| !active.contains(node_id))
+        .collect::<Vec<_>>();
+    report.already_connected = report.recent_peers + report.sync_candidates - to_dial.len();
+
+    let deadline = time::Instant::now() + config.timeout;
+    let mut pending = to_dial.len();
+    let mut dials = connectivity.dial_many_peers(to_dial);
+    while let Ok(Some(result)) = time::timeout_at(deadline, dials.next()).await {
+        pending -= 1;
+        match result {
+            Ok(_) => report.connected += 1,
+            Err(err) => {
+                debug!(target: LOG_TARGET, "Warm-up dial failed: {}", err);
+                report.failed += 1;
+            },
+        }
+    }
+    report.timed_out = pending;
+    report.elapsed = timer.elapsed();
+    Ok(report)
+}
+
+/// Selects the sync candidates and recently connected peers to dial, in that order. Only base nodes that are not
+/// banned or marked offline are selected, and a peer is only selected once.
+fn select_warmup_peers(peers: Vec<Peer>, config: &WarmupConfig) -> (Vec<NodeId>, Vec<NodeId>) {
+    let mut peers = peers
+        .into_iter()
+        .filter(|peer| peer.features.is_node() && !peer.is_banned() && !peer.is_offline())
+        .collect::<Vec<_>>();
+
+    let mut candidates = peers
+        .iter()
+        .filter_map(|peer| {
+            let metadata = peer
+                .get_metadata(PEER_METADATA_KEY)
+                .and_then(|v| bincode::deserialize::<PeerMetadata>(v).ok())?;
+            Some((metadata.metadata.accumulated_difficulty(), peer.node_id.clone()))
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(difficulty, _)| Reverse(*difficulty));
+    let candidates = candidates
+        .into_iter()
+        .take(config.num_sync_candidates)
+        .map(|(_, node_id)| node_id)
+        .collect::<Vec<_>>();
+
+    peers.retain(|peer| peer.connection_stats.last_connected_at.is_some() && !candidates.contains(&peer.node_id));
+    peers.sort_by_key(|peer| Reverse(peer.connection_stats.last_connected_at));
+    let recent = peers
+        .into_iter()
+        .take(config.num_recent_peers)
+        .map(|peer| peer.node_id)
+        .collect();
+
+    (candidates, recent)
+}
+
+/// Detects that the host has been asleep (e.g. a suspended laptop) by comparing the wall clock, which keeps running
+/// while suspended, with the monotonic clock, which does not.
+pub(super) struct SleepDetector {
+    monotonic: Instant,
+    wall: SystemTime,
+    threshold: Duration,
+}
+
+impl SleepDetector {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            monotonic: Instant::now(),
+            wall: SystemTime::now(),
+            threshold,
+        }
+    }
+
+    /// Returns the time the host has been asleep since the previous check, if it is longer than the threshold
+    pub fn check(&mut self) -> Option<Duration> {
+        let monotonic = self.monotonic.elapsed();
+        // The wall clock going backwards is not sleep
+        let wall = self.wall.elapsed().unwrap_or_default();
+        self.reset();
+        if self.threshold == Duration::from_secs(0) {
+            return None;
+        }
+        wall.checked_sub(monotonic).filter(|slept| *slept >= self.threshold)
+    }
+
+    /// Restarts the measurement from now
+    pub fn reset(&mut self) {
+        self.monotonic = Instant::now();
+        self.wall = SystemTime::now();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{Duration as ChronoDuration, Utc};
+    use rand::rngs::OsRng;
+    use tari_common_types::chain_metadata::ChainMetadata;
+    use tari_comms::{
+        peer_manager::{PeerFeatures, PeerFlags},
+        types::CommsPublicKey,
+    };
+    use tari_crypto::{keys::PublicKey, tari_utilities::epoch_time::EpochTime};
+
+    fn create_peer(features: PeerFeatures, connected_mins_ago: Option<i64>, difficulty: Option<u128>) -> Peer {
+        let (_secret_key, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let node_id = NodeId::from_key(&public_key);
+        let mut peer = Peer::new(
+            public_key,
+            node_id,
+            Default::default(),
+            PeerFlags::empty(),
+            features,
+            Vec::new(),
+            String::new(),
+        );
+        peer.connection_stats.last_connected_at =
+            connected_mins_ago.map(|mins| Utc::now().naive_utc() - ChronoDuration::minutes(mins));
+        if let Some(difficulty) = difficulty {
+            let metadata = PeerMetadata {
+                metadata: ChainMetadata::new(100, Vec::new(), 0, 0, difficulty),
+                last_updated: EpochTime::now(),
+            };
+            peer.set_metadata(PEER_METADATA_KEY, metadata.to_bytes());
+        }
+        peer
+    }
+
+    #[test]
+    fn it_selects_the_strongest_chains_and_most_recent_peers() {
+        let peers = vec![
+            create_peer(PeerFeatures::COMMUNICATION_NODE, Some(30), None),
+            create_peer(PeerFeatures::COMMUNICATION_NODE, Some(5), Some(100)),
+            create_peer(PeerFeatures::COMMUNICATION_NODE, None, Some(300)),
+            create_peer(PeerFeatures::COMMUNICATION_NODE, Some(10), None),
+            create_peer(PeerFeatures::COMMUNICATION_NODE, None, None),
+            create_peer(PeerFeatures::COMMUNICATION_CLIENT, Some(1), Some(500)),
+        ];
+        let node_ids = peers.iter().map(|p| p.node_id.clone()).collect::<Vec<_>>();
+        let config = WarmupConfig {
+            num_recent_peers: 2,
+            num_sync_candidates: 1,
+            ..Default::default()
+        };
+
+        let (candidates, recent) = select_warmup_peers(peers, &config);
+        assert_eq!(candidates, vec![node_ids[2].clone()]);
+        assert_eq!(recent, vec![node_ids[1].clone(), node_ids[3].clone()]);
+    }
+
+    #[test]
+    fn it_skips_banned_and_offline_peers() {
+        let mut banned = create_peer(PeerFeatures::COMMUNICATION_NODE, Some(1), Some(100));
+        banned.ban_for(Duration::from_secs(60), "test".to_string());
+        let mut offline = create_peer(PeerFeatures::COMMUNICATION_NODE, Some(1), Some(100));
+        offline.set_offline(true);
+
+        let (candidates, recent) = select_warmup_peers(vec![banned, offline], &Default::default());
+        assert!(candidates.is_empty());
+        assert!(recent.is_empty());
+    }
+
+    #[test]
+    fn it_detects_sleep_from_a_clock_gap() {
+        let mut detector = SleepDetector::new(Duration::from_secs(60));
+        assert_eq!(detector.check(), None);
+        detector.wall -= Duration::from_secs(120);
+        assert!(detector.check().unwrap() >= Duration::from_secs(119));
+        assert_eq!(detector.check(), None);
+
+        let mut detector = SleepDetector::new(Duration::from_secs(0));
+        detector.wall -= Duration::from_secs(120);
+        assert_eq!(detector.check(), None);
+    }
+}
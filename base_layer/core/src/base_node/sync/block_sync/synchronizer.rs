@@ -44,6 +44,7 @@ use std::{
 use tari_comms::{
     connectivity::{ConnectivityRequester, ConnectivitySelection},
     peer_manager::NodeId,
+    protocol::rpc::RpcCompression,
     PeerConnection,
 };
 use tracing;
@@ -126,10 +127,28 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
 
     async fn attempt_block_sync(&mut self, mut conn: PeerConnection) -> Result<(), BlockSyncError> {
         let mut client = conn
-            .connect_rpc_using_builder(rpc::BaseNodeSyncRpcClient::builder().with_deadline(Duration::from_secs(60)))
+            .connect_rpc_using_builder(
+                rpc::BaseNodeSyncRpcClient::builder()
+                    .with_deadline(Duration::from_secs(60))
+                    .with_compression(RpcCompression::Zstd),
+            )
             .await?;
-        self.synchronize_blocks(conn.peer_node_id(), &mut client).await?;
-        Ok(())
+
+        // Every block is committed as soon as it is validated, because the next block is validated against it, but
+        // the commits are only flushed to disk once per batch. A failed sync leaves the blocks that were committed in
+        // place, they are valid and are flushed when durable commits are turned back on.
+        let coalesce_commits = self.config.commit_batch_size > 1;
+        if coalesce_commits {
+            self.db.set_durable_commits(false).await?;
+        }
+        let result = self.synchronize_blocks(conn.peer_node_id(), &mut client).await;
+        if coalesce_commits {
+            if let Err(err) = self.db.set_durable_commits(true).await {
+                error!(target: LOG_TARGET, "Failed to flush the synced blocks to disk: {}", err);
+                return Err(err.into());
+            }
+        }
+        result
     }
 
     async fn synchronize_blocks(
@@ -171,6 +190,8 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         let mut block_stream = client.sync_blocks(request).await?;
         let mut prev_hash = best_full_block_hash;
         let mut current_block = None;
+        let coalesce_commits = self.config.commit_batch_size > 1;
+        let mut blocks_since_flush = 0;
         while let Some(block) = block_stream.next().await {
             let block = block?;
 
@@ -238,6 +259,13 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
                 .commit()
                 .await?;
 
+            blocks_since_flush += 1;
+            if coalesce_commits && blocks_since_flush >= self.config.commit_batch_size {
+                self.db.flush().await?;
+                blocks_since_flush = 0;
+                debug!(target: LOG_TARGET, "Flushed block bodies up to #{} to disk", block.height());
+            }
+
             self.hooks
                 .call_on_progress_block_hooks(block.clone(), tip_height, &[peer.clone()]);
 
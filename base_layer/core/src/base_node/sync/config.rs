@@ -28,6 +28,8 @@ pub struct BlockSyncConfig {
     pub ban_period: Duration,
     pub short_ban_period: Duration,
     pub sync_peers: Vec<NodeId>,
+    /// The number of blocks written between flushes to disk during block sync. 1 flushes every block.
+    pub commit_batch_size: usize,
 }
 
 impl Default for BlockSyncConfig {
@@ -36,6 +38,7 @@ impl Default for BlockSyncConfig {
             ban_period: Duration::from_secs(30 * 60),
             short_ban_period: Duration::from_secs(60),
             sync_peers: Default::default(),
+            commit_batch_size: 50,
         }
     }
 }
@@ -61,6 +61,8 @@ pub enum BlockHeaderSyncError {
     NetworkSilence,
     #[error("Invalid protocol response: {0}")]
     InvalidProtocolResponse(String),
+    #[error("The peer's stronger chain forks {0} header(s) below the local header tip, but reorgs are not allowed")]
+    ReorgNotAllowed(u64),
     #[error("Headers did not form a chain. Expected {actual} to equal the previous hash {expected}")]
     ChainLinkBroken { actual: String, expected: String },
 }
@@ -26,4 +26,7 @@ pub use error::BlockHeaderSyncError;
 mod synchronizer;
 pub use synchronizer::HeaderSynchronizer;
 
+mod tip_tracker;
+pub use tip_tracker::HeaderTipTracker;
+
 mod validator;
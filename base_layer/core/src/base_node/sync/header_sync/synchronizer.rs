@@ -45,7 +45,7 @@ use tari_common_types::types::HashOutput;
 use tari_comms::{
     connectivity::{ConnectivityError, ConnectivityRequester, ConnectivitySelection},
     peer_manager::NodeId,
-    protocol::rpc::{RpcError, RpcHandshakeError},
+    protocol::rpc::{RpcCompression, RpcError, RpcHandshakeError},
     PeerConnection,
 };
 use tracing;
@@ -61,6 +61,7 @@ pub struct HeaderSynchronizer<'a, B> {
     connectivity: ConnectivityRequester,
     sync_peers: &'a [NodeId],
     hooks: Hooks,
+    allow_reorgs: bool,
 }
 
 impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
@@ -79,9 +80,16 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             connectivity,
             sync_peers,
             hooks: Default::default(),
+            allow_reorgs: true,
         }
     }
 
+    /// If reorgs are not allowed, syncing from a peer whose stronger chain forks from the local header chain fails with
+    /// `ReorgNotAllowed` instead of rewinding the local chain
+    pub fn set_allow_reorgs(&mut self, allow_reorgs: bool) {
+        self.allow_reorgs = allow_reorgs;
+    }
+
     pub fn on_progress<H>(&mut self, hook: H)
     where H: FnMut(Option<(u64, u64)>, &[NodeId]) + Send + Sync + 'static {
         self.hooks.add_on_progress_header_hook(hook);
@@ -110,6 +118,7 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             );
             match self.attempt_sync(peer_conn.clone()).await {
                 Ok(()) => return Ok(peer_conn),
+                Err(err @ BlockHeaderSyncError::ReorgNotAllowed(_)) => return Err(err),
                 // Try another peer
                 Err(err @ BlockHeaderSyncError::NotInSync) => {
                     warn!(target: LOG_TARGET, "{}", err);
@@ -250,7 +259,9 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
     #[tracing::instrument(skip(self, conn), err)]
     async fn attempt_sync(&mut self, mut conn: PeerConnection) -> Result<(), BlockHeaderSyncError> {
         let peer = conn.peer_node_id().clone();
-        let mut client = conn.connect_rpc::<rpc::BaseNodeSyncRpcClient>().await?;
+        let mut client = conn
+            .connect_rpc_using_builder(rpc::BaseNodeSyncRpcClient::builder().with_compression(RpcCompression::Zstd))
+            .await?;
         let latency = client.get_last_request_latency().await?;
         debug!(
             target: LOG_TARGET,
@@ -265,6 +276,9 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             // We're ahead of this peer, try another peer if possible
             SyncStatus::Ahead => Err(BlockHeaderSyncError::NotInSync),
             SyncStatus::Lagging(split_info) => {
+                if !self.allow_reorgs && split_info.reorg_steps_back > 0 {
+                    return Err(BlockHeaderSyncError::ReorgNotAllowed(split_info.reorg_steps_back));
+                }
                 self.hooks.call_on_progress_header_hooks(
                     Some((split_info.local_tip_header.height(), split_info.remote_tip_height)),
                     self.sync_peers,
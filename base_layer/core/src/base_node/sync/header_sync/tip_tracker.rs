@@ -0,0 +1,146 @@
+//  Copyright 2020, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::{BlockHeaderSyncError, HeaderSynchronizer};
+use crate::{
+    base_node::{chain_metadata_service::ChainMetadataEvent, sync::BlockSyncConfig},
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
+    consensus::ConsensusManager,
+    proof_of_work::randomx_factory::RandomXFactory,
+};
+use futures::future;
+use log::*;
+use std::{
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tari_comms::connectivity::ConnectivityRequester;
+use tokio::sync::broadcast;
+
+const LOG_TARGET: &str = "c::bn::header_sync::tip_tracker";
+
+/// Keeps the header chain at the network tip while the block bodies are synced. Whenever a peer reports a chain with
+/// more accumulated difficulty than the local header chain, the new headers are synced from that peer. Headers are
+/// only ever appended: if a peer's stronger chain forks from the local header chain, the tracker stops so that the
+/// reorg can be handled by a full header sync.
+pub struct HeaderTipTracker<B> {
+    config: BlockSyncConfig,
+    db: AsyncBlockchainDb<B>,
+    consensus_rules: ConsensusManager,
+    connectivity: ConnectivityRequester,
+    randomx_factory: RandomXFactory,
+    header_tip_height: Arc<AtomicU64>,
+}
+
+impl<B: BlockchainBackend + 'static> HeaderTipTracker<B> {
+    pub fn new(
+        config: BlockSyncConfig,
+        db: AsyncBlockchainDb<B>,
+        consensus_rules: ConsensusManager,
+        connectivity: ConnectivityRequester,
+        randomx_factory: RandomXFactory,
+    ) -> Self {
+        Self {
+            config,
+            db,
+            consensus_rules,
+            connectivity,
+            randomx_factory,
+            header_tip_height: Default::default(),
+        }
+    }
+
+    /// The height of the local header chain, updated as the tracker appends headers
+    pub fn header_tip_height(&self) -> Arc<AtomicU64> {
+        self.header_tip_height.clone()
+    }
+
+    /// Tracks the network tip until a peer presents a stronger chain that forks from the local header chain, and
+    /// returns the number of local headers that chain would replace. Never returns if the event stream closes.
+    pub async fn run(self, metadata_events: &mut broadcast::Receiver<Arc<ChainMetadataEvent>>) -> u64 {
+        loop {
+            match self.db.fetch_last_header().await {
+                Ok(header) => self.header_tip_height.store(header.height, Ordering::SeqCst),
+                Err(err) => warn!(target: LOG_TARGET, "Failed to fetch the header tip: {}", err),
+            }
+
+            let peers = match metadata_events.recv().await.as_ref().map(|e| e.deref()) {
+                Ok(ChainMetadataEvent::PeerChainMetadataReceived(peers)) => peers.clone(),
+                Ok(ChainMetadataEvent::NetworkSilence) => continue,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    debug!(target: LOG_TARGET, "Metadata event subscriber lagged by {} item(s)", n);
+                    continue;
+                },
+                Err(broadcast::error::RecvError::Closed) => return future::pending().await,
+            };
+
+            let local_accumulated_difficulty = match self.db.fetch_tip_header().await {
+                Ok(header) => header.accumulated_data().total_accumulated_difficulty,
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "Failed to fetch the header tip: {}", err);
+                    continue;
+                },
+            };
+            let best_peer = peers
+                .iter()
+                .filter(|p| p.chain_metadata.accumulated_difficulty() > local_accumulated_difficulty)
+                .max_by_key(|p| p.chain_metadata.accumulated_difficulty());
+            let best_peer = match best_peer {
+                Some(peer) => peer.node_id.clone(),
+                None => continue,
+            };
+
+            debug!(
+                target: LOG_TARGET,
+                "Peer `{}` has a stronger chain, syncing the new headers", best_peer
+            );
+            let sync_peers = [best_peer];
+            let mut synchronizer = HeaderSynchronizer::new(
+                self.config.clone(),
+                self.db.clone(),
+                self.consensus_rules.clone(),
+                self.connectivity.clone(),
+                &sync_peers,
+                self.randomx_factory.clone(),
+            );
+            synchronizer.set_allow_reorgs(false);
+            match synchronizer.synchronize().await {
+                Ok(_) => {},
+                Err(BlockHeaderSyncError::ReorgNotAllowed(steps_back)) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Peer `{}` has a stronger chain that forks {} header(s) below the header tip",
+                        sync_peers[0],
+                        steps_back
+                    );
+                    return steps_back;
+                },
+                Err(err) => {
+                    debug!(target: LOG_TARGET, "Failed to sync the new headers: {}", err);
+                },
+            }
+        }
+    }
+}
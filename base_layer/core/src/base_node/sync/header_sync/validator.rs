@@ -36,6 +36,7 @@ use crate::{
     proof_of_work::{randomx_factory::RandomXFactory, PowAlgorithm},
     tari_utilities::{epoch_time::EpochTime, hash::Hashable, hex::Hex},
     validation::helpers::{
+        check_checkpoint,
         check_header_timestamp_greater_than_median,
         check_pow_data,
         check_target_difficulty,
@@ -135,6 +136,7 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
             });
         }
         check_timestamp_ftl(&header, &self.consensus_rules)?;
+        check_checkpoint(&header, &self.consensus_rules)?;
 
         check_header_timestamp_greater_than_median(&header, &state.timestamps)?;
 
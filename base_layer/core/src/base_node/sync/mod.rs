@@ -33,7 +33,7 @@ pub use block_sync::{BlockSyncError, BlockSynchronizer};
 #[cfg(feature = "base_node")]
 mod header_sync;
 #[cfg(feature = "base_node")]
-pub use header_sync::{BlockHeaderSyncError, HeaderSynchronizer};
+pub use header_sync::{BlockHeaderSyncError, HeaderSynchronizer, HeaderTipTracker};
 
 #[cfg(feature = "base_node")]
 mod hooks;
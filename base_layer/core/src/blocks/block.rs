@@ -36,7 +36,7 @@ use thiserror::Error;
 use tari_common_types::types::BlockHash;
 
 use crate::{
-    blocks::BlockHeader,
+    blocks::{BlockHeader, CompactBlock},
     chain_storage::MmrTree,
     consensus::ConsensusConstants,
     proof_of_work::ProofOfWork,
@@ -257,13 +257,19 @@ impl Hashable for Block {
 }
 
 //---------------------------------- NewBlock --------------------------------------------//
+/// A new block announcement. When the compact block is included, peers can rebuild the block from their mempool
+/// instead of requesting the full block.
 pub struct NewBlock {
     pub block_hash: BlockHash,
+    pub compact_block: Option<CompactBlock>,
 }
 
 impl NewBlock {
     pub fn new(block_hash: BlockHash) -> Self {
-        Self { block_hash }
+        Self {
+            block_hash,
+            compact_block: None,
+        }
     }
 }
 
@@ -271,6 +277,7 @@ impl From<&Block> for NewBlock {
     fn from(block: &Block) -> Self {
         Self {
             block_hash: block.hash(),
+            compact_block: Some(CompactBlock::from(block)),
         }
     }
 }
@@ -0,0 +1,158 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{
+    blocks::{Block, BlockHeader},
+    transactions::{
+        aggregated_body::AggregateBody,
+        transaction::{Transaction, TransactionKernel, TransactionOutput},
+    },
+};
+use digest::Digest;
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    fmt::{Display, Error, Formatter},
+    sync::Arc,
+};
+use tari_common_types::types::{BlockHash, HashDigest};
+use tari_crypto::tari_utilities::{ByteArray, Hashable};
+
+/// A short identifier for a transaction kernel in a compact block.
+pub type ShortKernelId = u64;
+
+/// Calculates the short id of a kernel for the block with the given hash. The id is keyed by the block hash so that
+/// colliding ids cannot be precomputed for every block.
+pub fn short_kernel_id(block_hash: &[u8], kernel: &TransactionKernel) -> ShortKernelId {
+    let hash = HashDigest::new()
+        .chain(block_hash)
+        .chain(kernel.excess_sig.get_public_nonce().as_bytes())
+        .chain(kernel.excess_sig.get_signature().as_bytes())
+        .finalize();
+    u64::from_le_bytes(hash[..8].try_into().expect("hash is at least 8 bytes"))
+}
+
+/// A block announced as its header, its coinbase and the short ids of the remaining kernels. The receiver rebuilds the
+/// block from the transactions in its mempool, so that only the transactions it has not seen need to be relayed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactBlock {
+    pub header: BlockHeader,
+    pub coinbase_outputs: Vec<TransactionOutput>,
+    pub coinbase_kernels: Vec<TransactionKernel>,
+    pub short_ids: Vec<ShortKernelId>,
+}
+
+impl CompactBlock {
+    pub fn hash(&self) -> BlockHash {
+        self.header.hash()
+    }
+
+    /// Returns the short ids that are not matched by a kernel of the given transactions.
+    pub fn missing_short_ids(&self, transactions: &[Arc<Transaction>]) -> Vec<ShortKernelId> {
+        let block_hash = self.hash();
+        let found = transactions
+            .iter()
+            .flat_map(|tx| tx.body.kernels())
+            .map(|kernel| short_kernel_id(&block_hash, kernel))
+            .collect::<HashSet<_>>();
+        self.short_ids
+            .iter()
+            .filter(|id| !found.contains(id))
+            .copied()
+            .collect()
+    }
+
+    /// Rebuilds the block from the given transactions, ignoring transactions that are not part of the block. Returns
+    /// None if the transactions do not account for exactly the announced kernels, in which case the full block must be
+    /// requested.
+    pub fn reconstruct(&self, transactions: &[Arc<Transaction>]) -> Option<Block> {
+        let block_hash = self.hash();
+        let mut remaining = self.short_ids.iter().copied().collect::<HashSet<_>>();
+        if remaining.len() != self.short_ids.len() {
+            // Duplicate (colliding) short ids cannot be resolved
+            return None;
+        }
+        let mut body = AggregateBody::new(Vec::new(), self.coinbase_outputs.clone(), self.coinbase_kernels.clone());
+        for tx in transactions {
+            let ids = tx
+                .body
+                .kernels()
+                .iter()
+                .map(|kernel| short_kernel_id(&block_hash, kernel))
+                .collect::<Vec<_>>();
+            if ids.iter().all(|id| !remaining.contains(id)) {
+                continue;
+            }
+            // A transaction that is only partially contained in the block cannot be split
+            if !ids.iter().all(|id| remaining.remove(id)) {
+                return None;
+            }
+            body.add_inputs(&mut tx.body.inputs().clone());
+            body.add_outputs(&mut tx.body.outputs().clone());
+            body.add_kernels(&mut tx.body.kernels().clone());
+        }
+        if !remaining.is_empty() {
+            return None;
+        }
+        body.sort(self.header.version);
+        Some(Block::new(self.header.clone(), body))
+    }
+}
+
+impl From<&Block> for CompactBlock {
+    fn from(block: &Block) -> Self {
+        let block_hash = block.hash();
+        let (coinbase_kernels, kernels) = block
+            .body
+            .kernels()
+            .iter()
+            .cloned()
+            .partition::<Vec<_>, _>(|kernel| kernel.is_coinbase());
+        Self {
+            header: block.header.clone(),
+            coinbase_outputs: block
+                .body
+                .outputs()
+                .iter()
+                .filter(|output| output.is_coinbase())
+                .cloned()
+                .collect(),
+            coinbase_kernels,
+            short_ids: kernels
+                .iter()
+                .map(|kernel| short_kernel_id(&block_hash, kernel))
+                .collect(),
+        }
+    }
+}
+
+impl Display for CompactBlock {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(
+            f,
+            "CompactBlock #{} ({} short ids)",
+            self.header.height,
+            self.short_ids.len()
+        )
+    }
+}
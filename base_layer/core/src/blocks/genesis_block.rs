@@ -135,6 +135,7 @@ pub fn get_stibbons_genesis_block_raw() -> Block {
             features: OutputFeatures {
                 flags: OutputFlags::COINBASE_OUTPUT,
                 maturity: 60,
+                encrypted_memo: Vec::new(),
             },
             commitment: Commitment::from_hex(
                 "fadafb12de96d90042dcbf839985aadb7ae88baa3446d5c6a17937ef2b36783e",
@@ -151,6 +152,7 @@ pub fn get_stibbons_genesis_block_raw() -> Block {
         vec![TransactionKernel {
             features: KernelFeatures::COINBASE_KERNEL,
             fee: MicroTari(0),
+            burn_amount: MicroTari(0),
             lock_height: 0,
             excess: Commitment::from_hex(
                 "f472cc347a1006b7390f9c93b3c62fba334fd99f6c9c1daf9302646cd4781f61",
@@ -202,6 +204,7 @@ pub fn get_weatherwax_genesis_block_raw() -> Block {
             features: OutputFeatures {
                 flags: OutputFlags::COINBASE_OUTPUT,
                 maturity: 60,
+                encrypted_memo: Vec::new(),
             },
             commitment: Commitment::from_hex(
                 "fadafb12de96d90042dcbf839985aadb7ae88baa3446d5c6a17937ef2b36783e",
@@ -218,6 +221,7 @@ pub fn get_weatherwax_genesis_block_raw() -> Block {
         vec![TransactionKernel {
             features: KernelFeatures::COINBASE_KERNEL,
             fee: MicroTari(0),
+            burn_amount: MicroTari(0),
             lock_height: 0,
             excess: Commitment::from_hex(
                 "f472cc347a1006b7390f9c93b3c62fba334fd99f6c9c1daf9302646cd4781f61",
@@ -312,6 +316,7 @@ pub fn get_ridcully_genesis_block_raw() -> Block {
             features: OutputFeatures {
                 flags: OutputFlags::COINBASE_OUTPUT,
                 maturity: 60,
+                encrypted_memo: Vec::new(),
             },
             commitment: Commitment::from_hex(
                 "fadafb12de96d90042dcbf839985aadb7ae88baa3446d5c6a17937ef2b36783e",
@@ -328,6 +333,7 @@ pub fn get_ridcully_genesis_block_raw() -> Block {
         vec![TransactionKernel {
             features: KernelFeatures::COINBASE_KERNEL,
             fee: MicroTari(0),
+            burn_amount: MicroTari(0),
             lock_height: 0,
             excess: Commitment::from_hex(
                 "f472cc347a1006b7390f9c93b3c62fba334fd99f6c9c1daf9302646cd4781f61",
@@ -395,6 +401,7 @@ pub fn get_igor_genesis_block_raw() -> Block {
             features: OutputFeatures {
                 flags: OutputFlags::COINBASE_OUTPUT,
                 maturity: 60,
+                encrypted_memo: Vec::new(),
             },
             commitment: Commitment::from_hex(
                 "fadafb12de96d90042dcbf839985aadb7ae88baa3446d5c6a17937ef2b36783e",
@@ -411,6 +418,7 @@ pub fn get_igor_genesis_block_raw() -> Block {
         vec![TransactionKernel {
             features: KernelFeatures::COINBASE_KERNEL,
             fee: MicroTari(0),
+            burn_amount: MicroTari(0),
             lock_height: 0,
             excess: Commitment::from_hex(
                 "f472cc347a1006b7390f9c93b3c62fba334fd99f6c9c1daf9302646cd4781f61",
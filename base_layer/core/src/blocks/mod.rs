@@ -25,6 +25,10 @@ mod block;
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 pub mod block_header;
 
+#[cfg(feature = "base_node")]
+mod compact_block;
+#[cfg(feature = "base_node")]
+pub use compact_block::{short_kernel_id, CompactBlock, ShortKernelId};
 #[cfg(feature = "base_node")]
 pub mod genesis_block;
 #[cfg(feature = "base_node")]
@@ -39,3 +43,8 @@ pub use block_header::{BlockHeader, BlockHeaderValidationError};
 pub use new_block_template::NewBlockTemplate;
 #[cfg(feature = "base_node")]
 pub use new_blockheader_template::NewBlockHeaderTemplate;
+
+#[cfg(all(feature = "tari_mmr", feature = "transactions"))]
+mod output_proof;
+#[cfg(all(feature = "tari_mmr", feature = "transactions"))]
+pub use output_proof::{OutputProof, OutputProofError};
@@ -0,0 +1,94 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{blocks::BlockHeader, transactions::transaction::TransactionOutput};
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{HashDigest, RangeProofService};
+use tari_crypto::tari_utilities::Hashable;
+use tari_mmr::{MerkleProof, MerkleProofError};
+use thiserror::Error;
+
+/// A portable proof that an output was mined, which can be verified offline. It contains the output, a Merkle proof
+/// that the output's witness hash is in the witness MMR of the block it was mined in and the chain of headers from that
+/// block up to the tip of the producing node's chain. The verifier is responsible for checking that the last header is
+/// part of the chain they trust, e.g. by comparing its hash with their own node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputProof {
+    pub output: TransactionOutput,
+    /// The leaf index of the output in the witness MMR
+    pub leaf_index: u64,
+    pub merkle_proof: MerkleProof,
+    /// The header of the block the output was mined in, followed by each header that builds on it
+    pub headers: Vec<BlockHeader>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum OutputProofError {
+    #[error("The proof does not contain any headers")]
+    NoHeaders,
+    #[error("Header #{height} does not build on the previous header in the proof")]
+    BrokenHeaderChain { height: u64 },
+    #[error("The range proof of the output is invalid")]
+    InvalidRangeProof,
+    #[error("Merkle proof error: {0}")]
+    MerkleProofError(#[from] MerkleProofError),
+}
+
+impl OutputProof {
+    /// The header of the block the output was mined in
+    pub fn mined_header(&self) -> Option<&BlockHeader> {
+        self.headers.first()
+    }
+
+    /// The tip of the chain at the time the proof was created
+    pub fn tip_header(&self) -> Option<&BlockHeader> {
+        self.headers.last()
+    }
+
+    /// The number of confirmations of the output, including the block it was mined in
+    pub fn confirmations(&self) -> u64 {
+        self.headers.len() as u64
+    }
+
+    /// Verifies that the output is committed to by the witness MMR root of the mined header and that each header
+    /// builds on the one before it.
+    pub fn verify(&self, range_proof_service: &RangeProofService) -> Result<(), OutputProofError> {
+        let mined_header = self.mined_header().ok_or(OutputProofError::NoHeaders)?;
+        for pair in self.headers.windows(2) {
+            if pair[1].prev_hash != pair[0].hash() || pair[1].height != pair[0].height + 1 {
+                return Err(OutputProofError::BrokenHeaderChain { height: pair[1].height });
+            }
+        }
+
+        if !self.output.verify_range_proof(range_proof_service).unwrap_or(false) {
+            return Err(OutputProofError::InvalidRangeProof);
+        }
+
+        self.merkle_proof.verify_leaf::<HashDigest>(
+            &mined_header.witness_mr,
+            &self.output.witness_hash(),
+            self.leaf_index as usize,
+        )?;
+        Ok(())
+    }
+}
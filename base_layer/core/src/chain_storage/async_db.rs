@@ -38,14 +38,20 @@ use crate::{
         DbTransaction,
         HistoricalBlock,
         HorizonData,
+        MmrMembershipProof,
         MmrTree,
+        OrphanPoolStats,
         PrunedOutput,
         TargetDifficulties,
+        WitnessPruningStats,
     },
     common::rolling_vec::RollingVec,
     proof_of_work::{PowAlgorithm, TargetDifficultyWindow},
     tari_utilities::epoch_time::EpochTime,
-    transactions::transaction::{TransactionKernel, TransactionOutput},
+    transactions::{
+        tari_amount::MicroTari,
+        transaction::{TransactionKernel, TransactionOutput},
+    },
 };
 use croaring::Bitmap;
 use log::*;
@@ -131,6 +137,20 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
     pub fn inner(&self) -> &BlockchainDatabase<B> {
         &self.db
     }
+
+    /// Returns the hash of the unspent output with the given commitment
+    pub async fn fetch_unspent_output_by_commitment(
+        &self,
+        commitment: Commitment,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            trace_log("fetch_unspent_output_by_commitment", move || {
+                db.fetch_unspent_output_by_commitment(&commitment)
+            })
+        })
+        .await?
+    }
 }
 
 impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
@@ -144,6 +164,8 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
     //---------------------------------- TXO --------------------------------------------//
     make_async_fn!(fetch_utxo(hash: HashOutput) -> Option<PrunedOutput>, "fetch_utxo");
 
+    make_async_fn!(fetch_output(hash: HashOutput) -> Option<(PrunedOutput, u32, u64)>, "fetch_output");
+
     make_async_fn!(fetch_utxos(hashes: Vec<HashOutput>) -> Vec<Option<(PrunedOutput, bool)>>, "fetch_utxos");
 
     make_async_fn!(fetch_utxos_by_mmr_position(start: u64, end: u64, deleted: Arc<Bitmap>) -> (Vec<PrunedOutput>, Bitmap), "fetch_utxos_by_mmr_position");
@@ -151,6 +173,12 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
     //---------------------------------- Kernel --------------------------------------------//
     make_async_fn!(fetch_kernel_by_excess_sig(excess_sig: Signature) -> Option<(TransactionKernel, HashOutput)>, "fetch_kernel_by_excess_sig");
 
+    make_async_fn!(fetch_kernel_membership_proof(excess_sig: Signature) -> Option<MmrMembershipProof>, "fetch_kernel_membership_proof");
+
+    make_async_fn!(fetch_output_membership_proof(output_hash: HashOutput) -> Option<MmrMembershipProof>, "fetch_output_membership_proof");
+
+    make_async_fn!(fetch_total_burnt(height: u64) -> MicroTari, "fetch_total_burnt");
+
     make_async_fn!(fetch_kernels_by_mmr_position(start: u64, end: u64) -> Vec<TransactionKernel>, "fetch_kernels_by_mmr_position");
 
     //---------------------------------- MMR --------------------------------------------//
@@ -198,6 +226,10 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(cleanup_all_orphans() -> (), "cleanup_all_orphans");
 
+    make_async_fn!(fetch_orphan_stats() -> OrphanPoolStats, "fetch_orphan_stats");
+
+    make_async_fn!(prune_spent_witness_data(max_blocks: u64) -> WitnessPruningStats, "prune_spent_witness_data");
+
     make_async_fn!(block_exists(block_hash: BlockHash) -> bool, "block_exists");
 
     make_async_fn!(fetch_block(height: u64) -> HistoricalBlock, "fetch_block");
@@ -235,6 +267,10 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
     make_async_fn!(get_stats() -> DbBasicStats, "get_stats");
 
     make_async_fn!(fetch_total_size_stats() -> DbTotalSizeStats, "fetch_total_size_stats");
+
+    make_async_fn!(flush() -> (), "flush");
+
+    make_async_fn!(set_durable_commits(durable: bool) -> (), "set_durable_commits");
 }
 
 impl<B: BlockchainBackend + 'static> From<BlockchainDatabase<B>> for AsyncBlockchainDb<B> {
@@ -15,8 +15,14 @@ use crate::{
         DbValue,
         HorizonData,
         MmrTree,
+        OrphanPoolStats,
+        PruningPolicy,
+        WitnessPruningStats,
+    },
+    transactions::{
+        tari_amount::MicroTari,
+        transaction::{TransactionInput, TransactionKernel},
     },
-    transactions::transaction::{TransactionInput, TransactionKernel},
 };
 use croaring::Bitmap;
 use tari_common_types::{
@@ -94,6 +100,9 @@ pub trait BlockchainBackend: Send + Sync {
         excess_sig: &Signature,
     ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>;
 
+    /// Returns the total value burnt by burn kernels in all blocks up to and including the given height
+    fn fetch_total_burnt(&self, height: u64) -> Result<MicroTari, ChainStorageError>;
+
     /// Fetch kernels by MMR position
     fn fetch_kernels_by_mmr_position(&self, start: u64, end: u64) -> Result<Vec<TransactionKernel>, ChainStorageError>;
 
@@ -156,6 +165,18 @@ pub trait BlockchainBackend: Send + Sync {
         orphan_storage_capacity: usize,
     ) -> Result<(), ChainStorageError>;
 
+    /// Returns a summary of the orphan pool. The capacity is not known to the backend and is left as 0.
+    fn fetch_orphan_stats(&self) -> Result<OrphanPoolStats, ChainStorageError>;
+
+    /// Removes the witness data of outputs that were spent below the pruned height, along with the inputs of those
+    /// blocks, for at most `max_blocks` blocks following the last block that was processed. Outputs retained by the
+    /// pruning policy, and the inputs spending them, are kept.
+    fn prune_spent_witness_data(
+        &mut self,
+        max_blocks: u64,
+        policy: &PruningPolicy,
+    ) -> Result<WitnessPruningStats, ChainStorageError>;
+
     /// This gets the monero seed_height. This will return 0, if the seed is unkown
     fn fetch_monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError>;
 
@@ -167,4 +188,10 @@ pub trait BlockchainBackend: Send + Sync {
     /// Returns total size information about each internal database. This call may be very slow and will obtain a read
     /// lock for the duration.
     fn fetch_total_size_stats(&self) -> Result<DbTotalSizeStats, ChainStorageError>;
+    /// Writes all committed transactions that are still buffered to disk
+    fn flush(&self) -> Result<(), ChainStorageError>;
+    /// Turns durable commits on or off. While they are off, committed transactions are visible to later reads but are
+    /// only guaranteed to be on disk after a call to `flush`. Turning durable commits back on flushes the buffered
+    /// transactions.
+    fn set_durable_commits(&mut self, durable: bool) -> Result<(), ChainStorageError>;
 }
@@ -24,12 +24,14 @@ use crate::{
     chain_storage::{
         accumulated_data::{BlockAccumulatedData, BlockHeaderAccumulatedData, CompleteDeletedBitmap},
         consts::{
+            BLOCKCHAIN_DATABASE_HEADER_CACHE_SIZE,
             BLOCKCHAIN_DATABASE_ORPHAN_STORAGE_CAPACITY,
             BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL,
             BLOCKCHAIN_DATABASE_PRUNING_HORIZON,
         },
-        db_transaction::{DbKey, DbTransaction, DbValue},
+        db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation},
         error::ChainStorageError,
+        header_cache::HeaderCache,
         pruned_output::PrunedOutput,
         BlockAddResult,
         BlockchainBackend,
@@ -37,18 +39,23 @@ use crate::{
         ChainHeader,
         DbBasicStats,
         DbTotalSizeStats,
+        HeaderCacheStats,
         HistoricalBlock,
         HorizonData,
+        MmrMembershipProof,
         MmrTree,
         Optional,
         OrNotFound,
+        OrphanPoolStats,
+        PruningPolicy,
         TargetDifficulties,
+        WitnessPruningStats,
     },
     common::rolling_vec::RollingVec,
     consensus::{chain_strength_comparer::ChainStrengthComparer, ConsensusConstants, ConsensusManager},
     proof_of_work::{monero_rx::MoneroPowData, PowAlgorithm, TargetDifficultyWindow},
     tari_utilities::epoch_time::EpochTime,
-    transactions::transaction::TransactionKernel,
+    transactions::{tari_amount::MicroTari, transaction::TransactionKernel},
     validation::{
         helpers::calc_median_timestamp,
         DifficultyCalculator,
@@ -67,7 +74,13 @@ use std::{
     convert::TryFrom,
     mem,
     ops::{Bound, RangeBounds},
-    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        RwLock,
+        RwLockReadGuard,
+        RwLockWriteGuard,
+    },
     time::Instant,
 };
 use tari_common_types::{
@@ -75,7 +88,7 @@ use tari_common_types::{
     types::{BlockHash, Commitment, HashDigest, HashOutput, Signature},
 };
 use tari_crypto::tari_utilities::{hex::Hex, ByteArray, Hashable};
-use tari_mmr::{pruned_hashset::PrunedHashSet, MerkleMountainRange, MutableMmr};
+use tari_mmr::{pruned_hashset::PrunedHashSet, MerkleMountainRange, MerkleProof, MerkleProofError, MutableMmr};
 
 const LOG_TARGET: &str = "c::cs::database";
 
@@ -85,6 +98,8 @@ pub struct BlockchainDatabaseConfig {
     pub orphan_storage_capacity: usize,
     pub pruning_horizon: u64,
     pub pruning_interval: u64,
+    /// The number of main chain headers to keep in memory for lookups by height and hash. 0 disables the cache.
+    pub header_cache_size: usize,
 }
 
 impl Default for BlockchainDatabaseConfig {
@@ -93,6 +108,7 @@ impl Default for BlockchainDatabaseConfig {
             orphan_storage_capacity: BLOCKCHAIN_DATABASE_ORPHAN_STORAGE_CAPACITY,
             pruning_horizon: BLOCKCHAIN_DATABASE_PRUNING_HORIZON,
             pruning_interval: BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL,
+            header_cache_size: BLOCKCHAIN_DATABASE_HEADER_CACHE_SIZE,
         }
     }
 }
@@ -174,6 +190,9 @@ pub struct BlockchainDatabase<B> {
     config: BlockchainDatabaseConfig,
     consensus_manager: ConsensusManager,
     difficulty_calculator: Arc<DifficultyCalculator>,
+    read_only: Arc<AtomicBool>,
+    header_cache: Arc<HeaderCache>,
+    pruning_policy: PruningPolicy,
 }
 
 #[allow(clippy::ptr_arg)]
@@ -197,6 +216,9 @@ where B: BlockchainBackend
             config,
             consensus_manager,
             difficulty_calculator: Arc::new(difficulty_calculator),
+            read_only: Arc::new(AtomicBool::new(false)),
+            header_cache: Arc::new(HeaderCache::new(config.header_cache_size)),
+            pruning_policy: PruningPolicy::new(config.pruning_horizon, config.pruning_interval),
         };
         if is_empty {
             info!(target: LOG_TARGET, "Blockchain db is empty. Adding genesis block.");
@@ -225,6 +247,17 @@ where B: BlockchainBackend
         Ok(blockchain_db)
     }
 
+    /// Keeps the outputs with the given commitments, and the inputs spending them, when pruning. This should be called
+    /// before the database is shared.
+    pub fn with_retained_commitments<I: IntoIterator<Item = Commitment>>(mut self, commitments: I) -> Self {
+        self.pruning_policy = self.pruning_policy.with_retained_commitments(commitments);
+        self
+    }
+
+    pub fn pruning_policy(&self) -> &PruningPolicy {
+        &self.pruning_policy
+    }
+
     /// Returns a reference to the consensus cosntants at the current height
     pub fn consensus_constants(&self) -> Result<&ConsensusConstants, ChainStorageError> {
         let height = self.get_height()?;
@@ -264,11 +297,58 @@ where B: BlockchainBackend
         })
     }
 
+    /// Switches the database in or out of read-only mode. While in read-only mode, operations that grow the database
+    /// (adding blocks and headers or committing transactions) fail with [ChainStorageError::ReadOnly]. Operations that
+    /// free space, such as orphan cleanup and witness pruning, are still permitted.
+    pub fn set_read_only(&self, read_only: bool) {
+        let was_read_only = self.read_only.swap(read_only, Ordering::SeqCst);
+        if was_read_only != read_only {
+            warn!(
+                target: LOG_TARGET,
+                "Blockchain database is {}",
+                if read_only { "now read-only" } else { "writable again" }
+            );
+        }
+    }
+
+    /// Returns true if the database is in read-only mode
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    fn check_writable(&self) -> Result<(), ChainStorageError> {
+        if self.is_read_only() {
+            return Err(ChainStorageError::ReadOnly);
+        }
+        Ok(())
+    }
+
     pub fn write(&self, transaction: DbTransaction) -> Result<(), ChainStorageError> {
+        self.check_writable()?;
         let mut db = self.db_write_access()?;
+        self.invalidate_deleted_headers(&transaction);
         db.write(transaction)
     }
 
+    /// Returns the hit and miss counts of the header cache
+    pub fn header_cache_stats(&self) -> HeaderCacheStats {
+        self.header_cache.stats()
+    }
+
+    fn invalidate_deleted_headers(&self, transaction: &DbTransaction) {
+        let lowest_deleted = transaction
+            .operations()
+            .iter()
+            .filter_map(|op| match op {
+                WriteOperation::DeleteHeader(height) => Some(*height),
+                _ => None,
+            })
+            .min();
+        if let Some(height) = lowest_deleted {
+            self.header_cache.invalidate_from(height);
+        }
+    }
+
     /// Returns the height of the current longest chain. This method will only fail if there's a fairly serious
     /// synchronisation problem on the database. You can try calling [BlockchainDatabase::try_recover_metadata] in
     /// that case to re-sync the metadata; or else just exit the program.
@@ -296,6 +376,12 @@ where B: BlockchainBackend
         Ok(db.fetch_output(&hash)?.map(|(out, _index, _)| out))
     }
 
+    /// Returns the output with the given hash, its position in the output MMR and the height it was mined at
+    pub fn fetch_output(&self, hash: HashOutput) -> Result<Option<(PrunedOutput, u32, u64)>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_output(&hash)
+    }
+
     pub fn fetch_unspent_output_by_commitment(
         &self,
         commitment: &Commitment,
@@ -335,6 +421,32 @@ where B: BlockchainBackend
         db.fetch_kernel_by_excess_sig(&excess_sig)
     }
 
+    /// Returns a proof that the kernel with the given excess signature is committed to by the kernel MMR root of the
+    /// block it was mined in, or None if the kernel is not in the main chain.
+    pub fn fetch_kernel_membership_proof(
+        &self,
+        excess_sig: Signature,
+    ) -> Result<Option<MmrMembershipProof>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        fetch_kernel_membership_proof(&*db, &excess_sig)
+    }
+
+    /// Returns a proof that the output with the given hash is committed to by the witness MMR root of the block it was
+    /// mined in, or None if the output is not in the main chain. The proof does not say whether the output is spent.
+    pub fn fetch_output_membership_proof(
+        &self,
+        output_hash: HashOutput,
+    ) -> Result<Option<MmrMembershipProof>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        fetch_output_membership_proof(&*db, &output_hash)
+    }
+
+    /// Returns the total value burnt on the main chain up to and including the given height
+    pub fn fetch_total_burnt(&self, height: u64) -> Result<MicroTari, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_total_burnt(height)
+    }
+
     pub fn fetch_kernels_by_mmr_position(
         &self,
         start: u64,
@@ -356,9 +468,8 @@ where B: BlockchainBackend
 
     /// Returns the block header at the given block height.
     pub fn fetch_header(&self, height: u64) -> Result<Option<BlockHeader>, ChainStorageError> {
-        let db = self.db_read_access()?;
-        match fetch_header(&*db, height) {
-            Ok(header) => Ok(Some(header)),
+        match self.fetch_chain_header(height) {
+            Ok(chain_header) => Ok(Some(chain_header.into_header())),
             Err(err) if err.is_value_not_found() => Ok(None),
             Err(err) => Err(err),
         }
@@ -366,8 +477,13 @@ where B: BlockchainBackend
 
     /// Returns the block header at the given block height.
     pub fn fetch_chain_header(&self, height: u64) -> Result<ChainHeader, ChainStorageError> {
+        if let Some(chain_header) = self.header_cache.get_by_height(height) {
+            return Ok(chain_header);
+        }
         let db = self.db_read_access()?;
         let chain_header = db.fetch_chain_header_by_height(height)?;
+        // Populated while the read lock is held so that a concurrent rewind cannot invalidate the cache first
+        self.header_cache.insert(chain_header.clone());
         Ok(chain_header)
     }
 
@@ -461,6 +577,7 @@ where B: BlockchainBackend
     /// Store the provided headers. This function does not do any validation and assumes the inserted header has already
     /// been validated.
     pub fn insert_valid_headers(&self, headers: Vec<ChainHeader>) -> Result<(), ChainStorageError> {
+        self.check_writable()?;
         let mut db = self.db_write_access()?;
         insert_headers(&mut *db, headers)
     }
@@ -497,12 +614,15 @@ where B: BlockchainBackend
 
     /// Returns the block header corresponding to the provided BlockHash
     pub fn fetch_header_by_block_hash(&self, hash: HashOutput) -> Result<Option<BlockHeader>, ChainStorageError> {
-        let db = self.db_read_access()?;
-        fetch_header_by_block_hash(&*db, hash)
+        let chain_header = self.fetch_chain_header_by_block_hash(hash)?;
+        Ok(chain_header.map(ChainHeader::into_header))
     }
 
     /// Returns a connected header in the main chain by block hash
     pub fn fetch_chain_header_by_block_hash(&self, hash: HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        if let Some(chain_header) = self.header_cache.get_by_hash(&hash) {
+            return Ok(Some(chain_header));
+        }
         let db = self.db_read_access()?;
 
         if let Some(header) = fetch_header_by_block_hash(&*db, hash.clone())? {
@@ -526,6 +646,7 @@ where B: BlockchainBackend
                     ),
                 }
             })?;
+            self.header_cache.insert(header.clone());
             Ok(Some(header))
         } else {
             Ok(None)
@@ -723,6 +844,7 @@ where B: BlockchainBackend
     ///
     /// If an error does occur while writing the new block parts, all changes are reverted before returning.
     pub fn add_block(&self, block: Arc<Block>) -> Result<BlockAddResult, ChainStorageError> {
+        self.check_writable()?;
         let new_height = block.header.height;
         // Perform orphan block validation.
         if let Err(e) = self.validators.orphan.validate(&block) {
@@ -749,11 +871,28 @@ where B: BlockchainBackend
             self.consensus_manager.chain_strength_comparer(),
             &self.difficulty_calculator,
             block,
-        )?;
+        );
+        let block_add_result = match block_add_result {
+            Ok(block_add_result) => block_add_result,
+            Err(err) => {
+                // A failed reorg may have partially rewound and restored the chain
+                self.header_cache.clear();
+                return Err(err);
+            },
+        };
+        match &block_add_result {
+            BlockAddResult::Ok(block) => self.header_cache.invalidate_from(block.height()),
+            BlockAddResult::ChainReorg { added, removed } => {
+                if let Some(height) = removed.iter().chain(added.iter()).map(|b| b.height()).min() {
+                    self.header_cache.invalidate_from(height);
+                }
+            },
+            BlockAddResult::BlockExists | BlockAddResult::OrphanBlock => {},
+        }
 
         if block_add_result.was_chain_modified() {
             // If blocks were added and the node is in pruned mode, perform pruning
-            prune_database_if_needed(&mut *db, self.config.pruning_horizon, self.config.pruning_interval)?
+            prune_database_if_needed(&mut *db, &self.pruning_policy)?
         }
 
         info!(
@@ -776,6 +915,21 @@ where B: BlockchainBackend
         Ok(())
     }
 
+    /// Removes the witness data of spent outputs below the pruned height for at most `max_blocks` blocks. See
+    /// [WitnessGarbageCollector](crate::chain_storage::WitnessGarbageCollector).
+    pub fn prune_spent_witness_data(&self, max_blocks: u64) -> Result<WitnessPruningStats, ChainStorageError> {
+        let mut db = self.db_write_access()?;
+        db.prune_spent_witness_data(max_blocks, &self.pruning_policy)
+    }
+
+    /// Returns a summary of the orphan pool
+    pub fn fetch_orphan_stats(&self) -> Result<OrphanPoolStats, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let mut stats = db.fetch_orphan_stats()?;
+        stats.capacity = self.config.orphan_storage_capacity;
+        Ok(stats)
+    }
+
     /// Clean out the entire orphan pool
     pub fn cleanup_all_orphans(&self) -> Result<(), ChainStorageError> {
         let mut db = self.db_write_access()?;
@@ -874,7 +1028,9 @@ where B: BlockchainBackend
 
     /// Atomically commit the provided transaction to the database backend. This function does not update the metadata.
     pub fn commit(&self, txn: DbTransaction) -> Result<(), ChainStorageError> {
+        self.check_writable()?;
         let mut db = self.db_write_access()?;
+        self.invalidate_deleted_headers(&txn);
         db.write(txn)
     }
 
@@ -884,6 +1040,8 @@ where B: BlockchainBackend
     /// * The block height is in the future
     pub fn rewind_to_height(&self, height: u64) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
         let mut db = self.db_write_access()?;
+        // Only headers above the given height are removed
+        self.header_cache.invalidate_from(height.saturating_add(1));
         rewind_to_height(&mut *db, height)
     }
 
@@ -895,7 +1053,9 @@ where B: BlockchainBackend
     /// * The block hash is before the horizon block height determined by the pruning horizon
     pub fn rewind_to_hash(&self, hash: BlockHash) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
         let mut db = self.db_write_access()?;
-        rewind_to_hash(&mut *db, hash)
+        let removed_blocks = rewind_to_hash(&mut *db, hash);
+        self.header_cache.clear();
+        removed_blocks
     }
 
     pub fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
@@ -934,6 +1094,20 @@ where B: BlockchainBackend
         lock.get_stats()
     }
 
+    /// Writes buffered database transactions to disk. This waits for writes that are in progress to complete and
+    /// should be called before the process exits.
+    pub fn flush(&self) -> Result<(), ChainStorageError> {
+        let lock = self.db_write_access()?;
+        lock.flush()
+    }
+
+    /// Turns durable commits on or off, see [BlockchainBackend::set_durable_commits]. Block sync turns them off to
+    /// write a batch of blocks with a single flush.
+    pub fn set_durable_commits(&self, durable: bool) -> Result<(), ChainStorageError> {
+        let mut db = self.db_write_access()?;
+        db.set_durable_commits(durable)
+    }
+
     /// Returns total size information about each internal database. This call may be very slow and will obtain a read
     /// lock for the duration.
     pub fn fetch_total_size_stats(&self) -> Result<DbTotalSizeStats, ChainStorageError> {
@@ -1133,6 +1307,143 @@ fn fetch_header_by_block_hash<T: BlockchainBackend>(
     try_fetch!(db, hash, BlockHash)
 }
 
+fn fetch_kernel_membership_proof<T: BlockchainBackend>(
+    db: &T,
+    excess_sig: &Signature,
+) -> Result<Option<MmrMembershipProof>, ChainStorageError> {
+    let (kernel, header_hash) = match db.fetch_kernel_by_excess_sig(excess_sig)? {
+        Some(kernel) => kernel,
+        None => return Ok(None),
+    };
+    let header =
+        fetch_header_by_block_hash(db, header_hash.clone())?.ok_or_else(|| ChainStorageError::ValueNotFound {
+            entity: "BlockHeader",
+            field: "hash",
+            value: header_hash.to_hex(),
+        })?;
+    let kernel_hash = kernel.hash();
+    let kernel_hashes = db
+        .fetch_kernels_in_block(&header_hash)?
+        .iter()
+        .map(|k| k.hash())
+        .collect::<Vec<_>>();
+    let block_index = kernel_hashes.iter().position(|h| *h == kernel_hash).ok_or_else(|| {
+        ChainStorageError::DataInconsistencyDetected {
+            function: "fetch_kernel_membership_proof",
+            details: format!(
+                "Kernel {} was not found in block #{}",
+                kernel_hash.to_hex(),
+                header.height
+            ),
+        }
+    })?;
+    let proof = generate_block_membership_proof(db, header, MmrTree::Kernel, kernel_hashes, block_index)?;
+    Ok(Some(proof))
+}
+
+fn fetch_output_membership_proof<T: BlockchainBackend>(
+    db: &T,
+    output_hash: &HashOutput,
+) -> Result<Option<MmrMembershipProof>, ChainStorageError> {
+    let mined_height = match db.fetch_output(output_hash)? {
+        Some((_, _, mined_height)) => mined_height,
+        None => return Ok(None),
+    };
+    let header = db.fetch_chain_header_by_height(mined_height)?.into_header();
+    let outputs = db.fetch_outputs_in_block(&header.hash())?;
+    let block_index = outputs
+        .iter()
+        .position(|o| match o {
+            PrunedOutput::Pruned { output_hash: hash, .. } => hash == output_hash,
+            PrunedOutput::NotPruned { output } => output.hash() == *output_hash,
+        })
+        .ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+            function: "fetch_output_membership_proof",
+            details: format!(
+                "Output {} was not found in block #{}",
+                output_hash.to_hex(),
+                header.height
+            ),
+        })?;
+    let witness_hashes = outputs
+        .into_iter()
+        .map(|o| match o {
+            PrunedOutput::Pruned { witness_hash, .. } => witness_hash,
+            PrunedOutput::NotPruned { output } => output.witness_hash(),
+        })
+        .collect();
+    let proof = generate_block_membership_proof(db, header, MmrTree::Witness, witness_hashes, block_index)?;
+    Ok(Some(proof))
+}
+
+/// Builds a membership proof for a leaf that was added to the given MMR in the given block. Only the peaks of each MMR
+/// are stored per block, but these are all that is needed to prove leaves added on top of them.
+fn generate_block_membership_proof<T: BlockchainBackend>(
+    db: &T,
+    header: BlockHeader,
+    tree: MmrTree,
+    block_leaf_hashes: Vec<HashOutput>,
+    block_index: usize,
+) -> Result<MmrMembershipProof, ChainStorageError> {
+    let (mmr_size, expected_root) = match tree {
+        MmrTree::Kernel => (header.kernel_mmr_size, &header.kernel_mr),
+        MmrTree::Witness => (header.output_mmr_size, &header.witness_mr),
+        MmrTree::Utxo => {
+            return Err(ChainStorageError::InvalidArguments {
+                func: "generate_block_membership_proof",
+                arg: "tree",
+                message: "Membership proofs are not supported for the UTXO MMR".to_string(),
+            })
+        },
+    };
+
+    let base = if header.height == 0 {
+        PrunedHashSet::default()
+    } else {
+        let (kernels, _, range_proofs, _) = db
+            .fetch_block_accumulated_data(&header.prev_hash)?
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockAccumulatedData",
+                field: "header_hash",
+                value: header.prev_hash.to_hex(),
+            })?
+            .dissolve();
+        match tree {
+            MmrTree::Kernel => kernels,
+            _ => range_proofs,
+        }
+    };
+
+    let num_block_leaves = block_leaf_hashes.len();
+    let mut mmr = MerkleMountainRange::<HashDigest, _>::new(base);
+    for hash in block_leaf_hashes {
+        mmr.push(hash)?;
+    }
+    let leaf_count = mmr.get_leaf_count()?;
+    if leaf_count as u64 != mmr_size || mmr.get_merkle_root()? != *expected_root {
+        return Err(ChainStorageError::DataInconsistencyDetected {
+            function: "generate_block_membership_proof",
+            details: format!(
+                "{} MMR rebuilt for block #{} does not match the header ({} leaves, expected {})",
+                tree, header.height, leaf_count, mmr_size
+            ),
+        });
+    }
+
+    let leaf_index = leaf_count - num_block_leaves + block_index;
+    let merkle_proof = MerkleProof::for_leaf_node(&mmr, leaf_index)?;
+    let leaf_hash = mmr
+        .get_leaf_hash(leaf_index)?
+        .ok_or(MerkleProofError::HashNotFound(leaf_index))?;
+    Ok(MmrMembershipProof {
+        header,
+        tree,
+        leaf_index: leaf_index as u64,
+        leaf_hash,
+        merkle_proof,
+    })
+}
+
 fn fetch_orphan<T: BlockchainBackend>(db: &T, hash: BlockHash) -> Result<Block, ChainStorageError> {
     fetch!(db, hash, OrphanBlock)
 }
@@ -1924,27 +2235,23 @@ fn cleanup_orphans<T: BlockchainBackend>(db: &mut T, orphan_storage_capacity: us
 
     db.delete_oldest_orphans(horizon_height, orphan_storage_capacity)
 }
-fn prune_database_if_needed<T: BlockchainBackend>(
-    db: &mut T,
-    pruning_horizon: u64,
-    pruning_interval: u64,
-) -> Result<(), ChainStorageError> {
+fn prune_database_if_needed<T: BlockchainBackend>(db: &mut T, policy: &PruningPolicy) -> Result<(), ChainStorageError> {
     let metadata = db.fetch_chain_metadata()?;
     if !metadata.is_pruned_node() {
         return Ok(());
     }
 
     let db_height = metadata.height_of_longest_chain();
-    let abs_pruning_horizon = db_height.saturating_sub(pruning_horizon);
+    let abs_pruning_horizon = policy.horizon_height(db_height);
 
     debug!(
         target: LOG_TARGET,
         "Current pruned height is: {}, pruning horizon is: {}, while the pruning interval is: {}",
         metadata.pruned_height(),
         abs_pruning_horizon,
-        pruning_interval,
+        policy.pruning_interval(),
     );
-    if metadata.pruned_height() < abs_pruning_horizon.saturating_sub(pruning_interval) {
+    if policy.should_prune(metadata.pruned_height(), db_height) {
         let last_pruned = metadata.pruned_height();
         info!(
             target: LOG_TARGET,
@@ -1967,7 +2274,7 @@ fn prune_database_if_needed<T: BlockchainBackend>(
             let inputs_to_prune = curr_block.deleted.bitmap().clone() - last_block.deleted.bitmap();
             last_block = curr_block;
 
-            txn.prune_outputs_and_update_horizon(inputs_to_prune.to_vec(), block_to_prune);
+            txn.prune_outputs_and_update_horizon(inputs_to_prune.to_vec(), block_to_prune, policy.clone());
         }
 
         db.write(txn)?;
@@ -1994,6 +2301,9 @@ impl<T> Clone for BlockchainDatabase<T> {
             config: self.config,
             consensus_manager: self.consensus_manager.clone(),
             difficulty_calculator: self.difficulty_calculator.clone(),
+            read_only: self.read_only.clone(),
+            header_cache: self.header_cache.clone(),
+            pruning_policy: self.pruning_policy.clone(),
         }
     }
 }
@@ -2071,6 +2381,65 @@ mod test {
         }
     }
 
+    #[test]
+    fn read_only_mode() {
+        let db = create_new_blockchain();
+        let (_, chain) = create_main_chain(&db, &[("A->GB", 1, 120)]);
+        let (_, blocks) = create_chained_blocks(&[("B->A", 1, 120)], chain.get("A").unwrap().clone());
+        let block = blocks.get("B").unwrap().to_arc_block();
+
+        db.set_read_only(true);
+        assert!(db.is_read_only());
+        assert!(db.add_block(block.clone()).unwrap_err().is_read_only());
+        assert!(db.commit(DbTransaction::new()).unwrap_err().is_read_only());
+        db.cleanup_all_orphans().unwrap();
+        assert_eq!(db.get_height().unwrap(), 1);
+
+        // Clones share the read-only flag
+        let db_clone = db.clone();
+        db_clone.set_read_only(false);
+        assert!(!db.is_read_only());
+        db.add_block(block).unwrap().assert_added();
+        assert_eq!(db.get_height().unwrap(), 2);
+    }
+
+    #[test]
+    fn coalesced_commits() {
+        let db = create_new_blockchain();
+        db.set_durable_commits(false).unwrap();
+        let (_, chain) = create_main_chain(&db, &[("A->GB", 1, 120), ("B->A", 1, 120)]);
+        // Commits are visible before they are flushed
+        assert_eq!(db.get_height().unwrap(), 2);
+        db.flush().unwrap();
+        db.set_durable_commits(true).unwrap();
+        let (_, blocks) = create_chained_blocks(&[("C->B", 1, 120)], chain.get("B").unwrap().clone());
+        db.add_block(blocks.get("C").unwrap().to_arc_block())
+            .unwrap()
+            .assert_added();
+        assert_eq!(db.get_height().unwrap(), 3);
+    }
+
+    #[test]
+    fn header_cache_is_invalidated_on_rewind() {
+        let db = create_new_blockchain();
+        let (_, chain) = create_main_chain(&db, &[("A->GB", 1, 120), ("B->A", 1, 120)]);
+        let header_b = db.fetch_chain_header(2).unwrap();
+        assert_eq!(header_b.hash(), chain.get("B").unwrap().hash());
+        assert_eq!(db.fetch_chain_header(2).unwrap(), header_b);
+        assert_eq!(db.header_cache_stats().hits, 1);
+
+        db.rewind_to_height(1).unwrap();
+        assert!(db.fetch_header(2).unwrap().is_none());
+        assert!(db
+            .fetch_header_by_block_hash(header_b.hash().clone())
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            db.fetch_header(1).unwrap().unwrap().hash(),
+            *chain.get("A").unwrap().hash()
+        );
+    }
+
     mod get_orphan_link_main_chain {
         use super::*;
 
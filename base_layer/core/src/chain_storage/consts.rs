@@ -26,3 +26,5 @@ pub const BLOCKCHAIN_DATABASE_ORPHAN_STORAGE_CAPACITY: usize = 720;
 pub const BLOCKCHAIN_DATABASE_PRUNING_HORIZON: u64 = 0;
 /// The chain height interval used to determine when a pruned node should perform pruning.
 pub const BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL: u64 = 50;
+/// The number of main chain headers kept in the header cache of the blockchain db.
+pub const BLOCKCHAIN_DATABASE_HEADER_CACHE_SIZE: usize = 1000;
@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::{
     blocks::{Block, BlockHeader},
-    chain_storage::{error::ChainStorageError, ChainBlock, ChainHeader, MmrTree},
+    chain_storage::{error::ChainStorageError, ChainBlock, ChainHeader, MmrTree, PruningPolicy},
     transactions::transaction::{TransactionKernel, TransactionOutput},
 };
 use croaring::Bitmap;
@@ -156,10 +156,16 @@ impl DbTransaction {
         self
     }
 
-    pub fn prune_outputs_and_update_horizon(&mut self, output_mmr_positions: Vec<u32>, horizon: u64) -> &mut Self {
+    pub fn prune_outputs_and_update_horizon(
+        &mut self,
+        output_mmr_positions: Vec<u32>,
+        horizon: u64,
+        policy: PruningPolicy,
+    ) -> &mut Self {
         self.operations.push(WriteOperation::PruneOutputsAndUpdateHorizon {
             output_positions: output_mmr_positions,
             horizon,
+            policy,
         });
         self
     }
@@ -313,6 +319,7 @@ pub enum WriteOperation {
     PruneOutputsAndUpdateHorizon {
         output_positions: Vec<u32>,
         horizon: u64,
+        policy: PruningPolicy,
     },
     UpdateKernelSum {
         header_hash: HashOutput,
@@ -408,6 +415,7 @@ impl fmt::Display for WriteOperation {
             PruneOutputsAndUpdateHorizon {
                 output_positions,
                 horizon,
+                ..
             } => write!(
                 f,
                 "Prune {} outputs and set horizon to {}",
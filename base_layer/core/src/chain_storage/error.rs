@@ -114,6 +114,8 @@ pub enum ChainStorageError {
     DbResizeRequired,
     #[error("DB transaction was too large ({0} operations)")]
     DbTransactionTooLarge(usize),
+    #[error("The blockchain database is in read-only mode because the node is running out of disk space")]
+    ReadOnly,
 }
 
 impl ChainStorageError {
@@ -124,6 +126,10 @@ impl ChainStorageError {
     pub fn is_key_exist_error(&self) -> bool {
         matches!(self, ChainStorageError::KeyExists { .. })
     }
+
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, ChainStorageError::ReadOnly)
+    }
 }
 
 impl From<task::JoinError> for ChainStorageError {
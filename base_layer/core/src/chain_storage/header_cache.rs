@@ -0,0 +1,250 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::chain_storage::ChainHeader;
+use lru::LruCache;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+        MutexGuard,
+    },
+};
+use tari_common_types::types::HashOutput;
+
+/// Hit and miss counts of the header cache
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of cached headers
+    pub size: usize,
+    pub capacity: usize,
+}
+
+impl HeaderCacheStats {
+    /// The fraction of lookups that were served from the cache, or 0 if there were no lookups
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl fmt::Display for HeaderCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} headers, {} hits, {} misses ({:.1}% hit rate)",
+            self.size,
+            self.capacity,
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+struct CachedHeaders {
+    by_hash: LruCache<HashOutput, ChainHeader>,
+    by_height: LruCache<u64, HashOutput>,
+}
+
+/// An LRU cache of the headers of the main chain, indexed by height and by hash. The cache must be invalidated from
+/// the lowest height at which headers are removed from the main chain, e.g. by a reorg or rewind.
+pub(crate) struct HeaderCache {
+    headers: Option<Mutex<CachedHeaders>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl HeaderCache {
+    /// Creates a cache of up to `capacity` headers. A capacity of 0 disables the cache.
+    pub fn new(capacity: usize) -> Self {
+        let headers = if capacity == 0 {
+            None
+        } else {
+            Some(Mutex::new(CachedHeaders {
+                by_hash: LruCache::new(capacity),
+                by_height: LruCache::new(capacity),
+            }))
+        };
+        Self {
+            headers,
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get_by_height(&self, height: u64) -> Option<ChainHeader> {
+        let headers = self.headers.as_ref()?;
+        let mut headers = acquire(headers);
+        let header = match headers.by_height.get(&height).cloned() {
+            Some(hash) => headers.by_hash.get(&hash).cloned(),
+            None => None,
+        };
+        self.record(header.is_some());
+        header
+    }
+
+    pub fn get_by_hash(&self, hash: &HashOutput) -> Option<ChainHeader> {
+        let headers = self.headers.as_ref()?;
+        let header = acquire(headers).by_hash.get(hash).cloned();
+        self.record(header.is_some());
+        header
+    }
+
+    pub fn insert(&self, header: ChainHeader) {
+        if let Some(headers) = self.headers.as_ref() {
+            let mut headers = acquire(headers);
+            headers.by_height.put(header.height(), header.hash().clone());
+            headers.by_hash.put(header.hash().clone(), header);
+        }
+    }
+
+    /// Removes the headers at and above `height`
+    pub fn invalidate_from(&self, height: u64) {
+        if let Some(headers) = self.headers.as_ref() {
+            let mut headers = acquire(headers);
+            let heights = headers
+                .by_height
+                .iter()
+                .filter(|(h, _)| **h >= height)
+                .map(|(h, _)| *h)
+                .collect::<Vec<_>>();
+            for h in heights {
+                headers.by_height.pop(&h);
+            }
+            let hashes = headers
+                .by_hash
+                .iter()
+                .filter(|(_, header)| header.height() >= height)
+                .map(|(hash, _)| hash.clone())
+                .collect::<Vec<_>>();
+            for hash in hashes {
+                headers.by_hash.pop(&hash);
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Some(headers) = self.headers.as_ref() {
+            let mut headers = acquire(headers);
+            headers.by_height.clear();
+            headers.by_hash.clear();
+        }
+    }
+
+    pub fn stats(&self) -> HeaderCacheStats {
+        HeaderCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            size: self
+                .headers
+                .as_ref()
+                .map(|headers| acquire(headers).by_hash.len())
+                .unwrap_or(0),
+            capacity: self.capacity,
+        }
+    }
+
+    fn record(&self, hit: bool) {
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn acquire(headers: &Mutex<CachedHeaders>) -> MutexGuard<'_, CachedHeaders> {
+    // The cache only holds copies of committed headers, so a poisoned lock is not a reason to fail
+    headers.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{blocks::BlockHeader, chain_storage::BlockHeaderAccumulatedData};
+    use tari_crypto::tari_utilities::Hashable;
+
+    fn chain_header(height: u64) -> ChainHeader {
+        let mut header = BlockHeader::new(0);
+        header.height = height;
+        let accumulated_data = BlockHeaderAccumulatedData {
+            hash: header.hash(),
+            ..Default::default()
+        };
+        ChainHeader::try_construct(header, accumulated_data).unwrap()
+    }
+
+    #[test]
+    fn cache_lookups() {
+        let cache = HeaderCache::new(2);
+        let headers = (0..3).map(chain_header).collect::<Vec<_>>();
+        assert!(cache.get_by_height(0).is_none());
+        for header in &headers {
+            cache.insert(header.clone());
+        }
+        // The first header was evicted
+        assert!(cache.get_by_hash(headers[0].hash()).is_none());
+        assert_eq!(cache.get_by_height(1).unwrap().hash(), headers[1].hash());
+        assert_eq!(cache.get_by_hash(headers[2].hash()).unwrap().height(), 2);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.size, 2);
+        assert!((stats.hit_rate() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn invalidation() {
+        let cache = HeaderCache::new(10);
+        let headers = (0..5).map(chain_header).collect::<Vec<_>>();
+        for header in &headers {
+            cache.insert(header.clone());
+        }
+        cache.invalidate_from(3);
+        assert!(cache.get_by_height(2).is_some());
+        assert!(cache.get_by_height(3).is_none());
+        assert!(cache.get_by_hash(headers[4].hash()).is_none());
+        assert_eq!(cache.stats().size, 3);
+        cache.clear();
+        assert_eq!(cache.stats().size, 0);
+    }
+
+    #[test]
+    fn disabled_cache() {
+        let cache = HeaderCache::new(0);
+        cache.insert(chain_header(1));
+        assert!(cache.get_by_height(1).is_none());
+        assert_eq!(cache.stats(), HeaderCacheStats::default());
+    }
+}
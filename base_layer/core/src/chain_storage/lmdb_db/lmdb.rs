@@ -397,6 +397,43 @@ where
     Ok(result)
 }
 
+/// Deserializes every value in the database as `V` and overwrites it with the value returned by `f`. This is used to
+/// upgrade rows to a new format and must not be used on `DUPSORT` databases. Returns the number of values rewritten.
+pub fn lmdb_migrate_values<F, V, R>(txn: &WriteTransaction<'_>, db: &Database, f: F) -> Result<usize, ChainStorageError>
+where
+    F: Fn(V) -> Result<R, ChainStorageError>,
+    V: DeserializeOwned,
+    R: Serialize,
+{
+    let mut access = txn.access();
+    let mut cursor = txn.cursor(db).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not get write cursor from lmdb: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })?;
+
+    let mut num_migrated = 0;
+    let mut row = cursor
+        .first::<[u8], [u8]>(&access)
+        .to_opt()?
+        .map(|(k, v)| (k.to_vec(), v.to_vec()));
+    while let Some((key, val)) = row {
+        let val = serialize(&f(deserialize::<V>(&val)?)?)?;
+        cursor.overwrite(&mut access, key.as_slice(), val.as_slice(), put::Flags::empty())?;
+        num_migrated += 1;
+        row = cursor
+            .next::<[u8], [u8]>(&access)
+            .to_opt()?
+            .map(|(k, v)| (k.to_vec(), v.to_vec()));
+    }
+    Ok(num_migrated)
+}
+
+/// Deletes all values in the database
+pub fn lmdb_clear(txn: &WriteTransaction<'_>, db: &Database) -> Result<(), ChainStorageError> {
+    txn.access().clear_db(db)?;
+    Ok(())
+}
+
 /// Fetches all the size of all key/values in the given DB. Returns the number of entries, the total size of all the
 /// keys and values in bytes.
 pub fn fetch_db_entry_sizes(txn: &ConstTransaction<'_>, db: &Database) -> Result<(u64, u64, u64), ChainStorageError> {
@@ -33,6 +33,7 @@ use crate::{
         lmdb_db::{
             lmdb::{
                 fetch_db_entry_sizes,
+                lmdb_clear,
                 lmdb_delete,
                 lmdb_delete_key_value,
                 lmdb_delete_keys_starting_with,
@@ -46,13 +47,22 @@ use crate::{
                 lmdb_insert_dup,
                 lmdb_last,
                 lmdb_len,
+                lmdb_migrate_values,
                 lmdb_replace,
             },
+            migrations::{
+                TransactionInputRowDataV1,
+                TransactionKernelRowDataV0,
+                TransactionOutputRowDataV1,
+                MIGRATION_VERSION,
+            },
+            BurntKernelRowData,
             TransactionInputRowData,
             TransactionKernelRowData,
             TransactionOutputRowData,
             LMDB_DB_BLOCK_ACCUMULATED_DATA,
             LMDB_DB_BLOCK_HASHES,
+            LMDB_DB_BURNT_KERNELS,
             LMDB_DB_HEADERS,
             LMDB_DB_HEADER_ACCUMULATED_DATA,
             LMDB_DB_INPUTS,
@@ -71,6 +81,7 @@ use crate::{
             LMDB_DB_UTXO_COMMITMENT_INDEX,
             LMDB_DB_UTXO_MMR_SIZE_INDEX,
         },
+        orphan_pool::{select_orphans_to_evict, EvictionCandidate, OrphanUsage},
         stats::DbTotalSizeStats,
         BlockchainBackend,
         ChainBlock,
@@ -79,20 +90,24 @@ use crate::{
         DbSize,
         HorizonData,
         MmrTree,
+        OrphanPoolStats,
         PrunedOutput,
+        PruningPolicy,
+        WitnessPruningStats,
     },
     crypto::tari_utilities::hex::to_hex,
     transactions::{
         aggregated_body::AggregateBody,
+        tari_amount::MicroTari,
         transaction::{TransactionInput, TransactionKernel, TransactionOutput},
     },
 };
 use croaring::Bitmap;
 use fs2::FileExt;
-use lmdb_zero::{ConstTransaction, Database, Environment, ReadTransaction, WriteTransaction};
+use lmdb_zero::{open, ConstTransaction, Database, Environment, ReadTransaction, WriteTransaction};
 use log::*;
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, fmt, fs, fs::File, ops::Deref, path::Path, sync::Arc, time::Instant};
+use std::{cmp, convert::TryFrom, fmt, fs, fs::File, ops::Deref, path::Path, sync::Arc, time::Instant};
 use tari_common_types::{
     chain_metadata::ChainMetadata,
     types::{BlockHash, Commitment, HashDigest, HashOutput, Signature, BLOCK_HASH_LENGTH},
@@ -146,6 +161,8 @@ pub struct LMDBDatabase {
     orphan_header_accumulated_data_db: DatabaseRef,
     orphan_chain_tips_db: DatabaseRef,
     orphan_parent_map_index: DatabaseRef,
+    burnt_kernels_db: DatabaseRef,
+    orphan_usage: OrphanUsage,
     _file_lock: Arc<File>,
 }
 
@@ -173,14 +190,67 @@ impl LMDBDatabase {
             monero_seed_height_db: get_database(&store, LMDB_DB_MONERO_SEED_HEIGHT)?,
             orphan_chain_tips_db: get_database(&store, LMDB_DB_ORPHAN_CHAIN_TIPS)?,
             orphan_parent_map_index: get_database(&store, LMDB_DB_ORPHAN_PARENT_MAP_INDEX)?,
+            burnt_kernels_db: get_database(&store, LMDB_DB_BURNT_KERNELS)?,
+            orphan_usage: OrphanUsage::default(),
             env,
             env_config: store.env_config(),
             _file_lock: Arc::new(file_lock),
         };
+        res.run_migrations()?;
 
         Ok(res)
     }
 
+    /// Upgrades the rows of a database written by an earlier version to the current format. Orphan blocks are
+    /// discarded rather than upgraded, since they can be fetched from peers again.
+    fn run_migrations(&self) -> Result<(), ChainStorageError> {
+        let txn = self.write_transaction()?;
+        let version = fetch_migration_version(&txn, &self.metadata_db)?;
+        if version >= MIGRATION_VERSION {
+            return Ok(());
+        }
+        info!(
+            target: LOG_TARGET,
+            "Migrating blockchain database from version {} to {}", version, MIGRATION_VERSION
+        );
+
+        if version < 1 {
+            let num_kernels = lmdb_migrate_values(&txn, &self.kernels_db, |row: TransactionKernelRowDataV0| {
+                Ok(TransactionKernelRowData::from(row))
+            })?;
+            info!(target: LOG_TARGET, "Migrated {} kernels to version 1", num_kernels);
+        }
+        if version < 2 {
+            let num_outputs = lmdb_migrate_values(&txn, &self.utxos_db, |row: TransactionOutputRowDataV1| {
+                Ok(TransactionOutputRowData::from(row))
+            })?;
+            let num_inputs = lmdb_migrate_values(&txn, &self.inputs_db, |row: TransactionInputRowDataV1| {
+                Ok(TransactionInputRowData::from(row))
+            })?;
+            info!(
+                target: LOG_TARGET,
+                "Migrated {} outputs and {} inputs to version 2", num_outputs, num_inputs
+            );
+        }
+        self.clear_orphans(&txn)?;
+
+        self.set_metadata(
+            &txn,
+            MetadataKey::MigrationVersion,
+            MetadataValue::MigrationVersion(MIGRATION_VERSION),
+        )?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn clear_orphans(&self, txn: &WriteTransaction<'_>) -> Result<(), ChainStorageError> {
+        lmdb_clear(txn, &self.orphans_db)?;
+        lmdb_clear(txn, &self.orphan_header_accumulated_data_db)?;
+        lmdb_clear(txn, &self.orphan_chain_tips_db)?;
+        lmdb_clear(txn, &self.orphan_parent_map_index)?;
+        Ok(())
+    }
+
     /// Try to establish a read lock on the LMDB database. If an exclusive write lock has been previously acquired, this
     /// method will block until that lock is released.
     fn read_transaction(&self) -> Result<ReadTransaction<'_>, ChainStorageError> {
@@ -294,8 +364,9 @@ impl LMDBDatabase {
                 PruneOutputsAndUpdateHorizon {
                     output_positions,
                     horizon,
+                    policy,
                 } => {
-                    self.prune_outputs_and_update_horizon(&write_txn, output_positions, *horizon)?;
+                    self.prune_outputs_and_update_horizon(&write_txn, output_positions, *horizon, policy)?;
                 },
                 UpdateKernelSum {
                     header_hash,
@@ -376,7 +447,7 @@ impl LMDBDatabase {
         Ok(())
     }
 
-    fn all_dbs(&self) -> [(&'static str, &DatabaseRef); 19] {
+    fn all_dbs(&self) -> [(&'static str, &DatabaseRef); 20] {
         [
             ("metadata_db", &self.metadata_db),
             ("headers_db", &self.headers_db),
@@ -400,6 +471,7 @@ impl LMDBDatabase {
             ("monero_seed_height_db", &self.monero_seed_height_db),
             ("orphan_chain_tips_db", &self.orphan_chain_tips_db),
             ("orphan_parent_map_index", &self.orphan_parent_map_index),
+            ("burnt_kernels_db", &self.burnt_kernels_db),
         ]
     }
 
@@ -539,6 +611,25 @@ impl LMDBDatabase {
             "kernel_excess_sig_index",
         )?;
 
+        if kernel.is_burn() {
+            let height = self.fetch_height_from_hash(txn, header_hash).or_not_found(
+                "BlockHash",
+                "hash",
+                header_hash.to_hex(),
+            )?;
+            lmdb_insert(
+                txn,
+                &*self.burnt_kernels_db,
+                key.as_str(),
+                &BurntKernelRowData {
+                    header_hash: header_hash.clone(),
+                    height,
+                    burn_amount: kernel.burn_amount,
+                },
+                "burnt_kernels_db",
+            )?;
+        }
+
         lmdb_insert(
             txn,
             &*self.kernels_db,
@@ -597,6 +688,9 @@ impl LMDBDatabase {
         let k = block.hash();
         lmdb_insert_dup(txn, &self.orphan_parent_map_index, &block.header.prev_hash, &k)?;
         lmdb_insert(txn, &self.orphans_db, k.as_slice(), &block, "orphans_db")?;
+        // A new child keeps the parent orphan from being evicted
+        self.orphan_usage.touch(&k);
+        self.orphan_usage.touch(&block.header.prev_hash);
 
         Ok(())
     }
@@ -865,6 +959,7 @@ impl LMDBDatabase {
 
     fn delete_block_kernels(&self, txn: &WriteTransaction<'_>, hash: &str) -> Result<(), ChainStorageError> {
         let kernels = lmdb_delete_keys_starting_with::<TransactionKernelRowData>(txn, &self.kernels_db, hash)?;
+        lmdb_delete_keys_starting_with::<BurntKernelRowData>(txn, &self.burnt_kernels_db, hash)?;
         debug!(target: LOG_TARGET, "Deleted {} kernels...", kernels.len());
         for kernel in kernels {
             trace!(
@@ -937,6 +1032,7 @@ impl LMDBDatabase {
                 )?;
             }
             lmdb_delete(txn, &self.orphans_db, hash.as_slice(), "orphans_db")?;
+            self.orphan_usage.remove(hash);
         }
         Ok(())
     }
@@ -1177,6 +1273,7 @@ impl LMDBDatabase {
         write_txn: &WriteTransaction<'_>,
         output_positions: &[u32],
         horizon: u64,
+        policy: &PruningPolicy,
     ) -> Result<(), ChainStorageError> {
         for pos in output_positions {
             let (_height, hash) = lmdb_first_after::<_, (u64, Vec<u8>)>(
@@ -1186,6 +1283,10 @@ impl LMDBDatabase {
             )
             .or_not_found("BlockHeader", "mmr_position", pos.to_string())?;
             let key = OutputKey::new(&hash, *pos);
+            if self.is_retained_output(write_txn, &key, policy)? {
+                debug!(target: LOG_TARGET, "Retaining output: {}", key.get_key());
+                continue;
+            }
             debug!(target: LOG_TARGET, "Pruning output: {}", key.get_key());
             self.prune_output(write_txn, &key)?;
         }
@@ -1199,6 +1300,22 @@ impl LMDBDatabase {
         Ok(())
     }
 
+    fn is_retained_output(
+        &self,
+        txn: &ConstTransaction<'_>,
+        key: &OutputKey,
+        policy: &PruningPolicy,
+    ) -> Result<bool, ChainStorageError> {
+        if !policy.has_retained_commitments() {
+            return Ok(false);
+        }
+        let row: Option<TransactionOutputRowData> = lmdb_get(txn, &self.utxos_db, key.get_key().as_str())?;
+        Ok(row
+            .and_then(|row| row.output)
+            .map(|output| policy.retains(&output.commitment))
+            .unwrap_or(false))
+    }
+
     #[allow(clippy::ptr_arg)]
     fn fetch_mmr_leaf_index(
         &self,
@@ -1260,7 +1377,7 @@ pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Resu
     let lmdb_store = LMDBBuilder::new()
         .set_path(path)
         .set_env_config(config)
-        .set_max_number_of_databases(20)
+        .set_max_number_of_databases(21)
         .add_database(LMDB_DB_METADATA, flags | db::INTEGERKEY)
         .add_database(LMDB_DB_HEADERS, flags | db::INTEGERKEY)
         .add_database(LMDB_DB_HEADER_ACCUMULATED_DATA, flags | db::INTEGERKEY)
@@ -1280,6 +1397,7 @@ pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Resu
         .add_database(LMDB_DB_MONERO_SEED_HEIGHT, flags)
         .add_database(LMDB_DB_ORPHAN_CHAIN_TIPS, flags)
         .add_database(LMDB_DB_ORPHAN_PARENT_MAP_INDEX, flags | db::DUPSORT)
+        .add_database(LMDB_DB_BURNT_KERNELS, flags)
         .build()
         .map_err(|err| ChainStorageError::CriticalError(format!("Could not create LMDB store:{}", err)))?;
     LMDBDatabase::new(lmdb_store, file_lock)
@@ -1325,6 +1443,12 @@ impl BlockchainBackend for LMDBDatabase {
         }
 
         let mark = Instant::now();
+        // Grow the map ahead of time once its utilization passes the resize threshold, rather than waiting for the
+        // transaction to fail with MDB_MAP_FULL.
+        // SAFETY: See below, writers are paused by the exclusive write lock held by the caller.
+        unsafe {
+            LMDBStore::resize_if_required(&self.env, &self.env_config)?;
+        }
         // Resize this many times before assuming something is not right
         const MAX_RESIZES: usize = 5;
         for i in 0..MAX_RESIZES {
@@ -1402,9 +1526,13 @@ impl BlockchainBackend for LMDBDatabase {
                     },
                 }
             },
-            DbKey::OrphanBlock(k) => self
-                .fetch_orphan(&txn, k)?
-                .map(|val| DbValue::OrphanBlock(Box::new(val))),
+            DbKey::OrphanBlock(k) => {
+                let orphan = self.fetch_orphan(&txn, k)?;
+                if orphan.is_some() {
+                    self.orphan_usage.touch(k);
+                }
+                orphan.map(|val| DbValue::OrphanBlock(Box::new(val)))
+            },
         };
         Ok(res)
     }
@@ -1637,6 +1765,24 @@ impl BlockchainBackend for LMDBDatabase {
         }
     }
 
+    fn fetch_total_burnt(&self, height: u64) -> Result<MicroTari, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let burnt = lmdb_filter_map_values(&txn, &self.burnt_kernels_db, |row: BurntKernelRowData| {
+            Ok(if row.height <= height {
+                Some(row.burn_amount)
+            } else {
+                None
+            })
+        })?;
+        burnt
+            .into_iter()
+            .try_fold(MicroTari(0), |total, amount| total.checked_add(amount))
+            .ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+                function: "fetch_total_burnt",
+                details: format!("The total amount burnt up to height {} overflowed", height),
+            })
+    }
+
     fn fetch_kernels_by_mmr_position(&self, start: u64, end: u64) -> Result<Vec<TransactionKernel>, ChainStorageError> {
         let txn = self.read_transaction()?;
         if let Some(start_height) = lmdb_first_after(&txn, &self.kernel_mmr_size_index, &(start + 1).to_be_bytes())? {
@@ -2059,35 +2205,165 @@ impl BlockchainBackend for LMDBDatabase {
             num_over_limit,
         );
 
-        let mut orphans;
-
-        {
+        let candidates = {
             let read_txn = self.read_transaction()?;
-
-            orphans = lmdb_filter_map_values(&read_txn, &self.orphans_db, |block: Block| {
+            let orphans = lmdb_filter_map_values(&read_txn, &self.orphans_db, |block: Block| {
                 Ok(Some((block.header.height, block.hash())))
             })?;
-        }
+            orphans
+                .into_iter()
+                .map(|(height, hash)| {
+                    let total_work = lmdb_get::<_, BlockHeaderAccumulatedData>(
+                        &read_txn,
+                        &self.orphan_header_accumulated_data_db,
+                        hash.as_slice(),
+                    )?
+                    .map(|accum| accum.total_accumulated_difficulty)
+                    .unwrap_or(0);
+                    Ok(EvictionCandidate {
+                        last_used: self.orphan_usage.last_used(&hash),
+                        hash,
+                        height,
+                        total_work,
+                    })
+                })
+                .collect::<Result<Vec<_>, ChainStorageError>>()?
+        };
+        self.orphan_usage
+            .retain(&candidates.iter().map(|c| c.hash.clone()).collect());
 
-        orphans.sort_by(|a, b| a.0.cmp(&b.0));
         let mut txn = DbTransaction::new();
-        for (removed_count, (height, block_hash)) in orphans.into_iter().enumerate() {
-            if height > horizon_height && removed_count >= num_over_limit {
-                break;
-            }
+        for orphan in select_orphans_to_evict(candidates, horizon_height, orphan_storage_capacity) {
             debug!(
                 target: LOG_TARGET,
-                "Discarding orphan block #{} ({}).",
-                height,
-                block_hash.to_hex()
+                "Discarding orphan block #{} ({}) with total work {}.",
+                orphan.height,
+                orphan.hash.to_hex(),
+                orphan.total_work
             );
-            txn.delete_orphan(block_hash.clone());
+            txn.delete_orphan(orphan.hash);
         }
         self.write(txn)?;
 
         Ok(())
     }
 
+    fn fetch_orphan_stats(&self) -> Result<OrphanPoolStats, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let orphans = lmdb_filter_map_values(&txn, &self.orphans_db, |block: Block| {
+            Ok(Some((block.header.height, serialized_size(&block)?)))
+        })?;
+        let best_total_accumulated_difficulty = lmdb_filter_map_values(
+            &txn,
+            &self.orphan_header_accumulated_data_db,
+            |accum: BlockHeaderAccumulatedData| Ok(Some(accum.total_accumulated_difficulty)),
+        )?
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+        let min_height = orphans.iter().map(|(height, _)| *height).min();
+        let max_height = orphans.iter().map(|(height, _)| *height).max();
+
+        Ok(OrphanPoolStats {
+            num_orphans: orphans.len(),
+            capacity: 0,
+            num_linked: lmdb_len(&txn, &self.orphan_header_accumulated_data_db)?,
+            num_chain_tips: lmdb_len(&txn, &self.orphan_chain_tips_db)?,
+            height_range: min_height.zip(max_height),
+            best_total_accumulated_difficulty,
+            size_bytes: orphans.iter().map(|(_, size)| size).sum(),
+        })
+    }
+
+    fn prune_spent_witness_data(
+        &mut self,
+        max_blocks: u64,
+        policy: &PruningPolicy,
+    ) -> Result<WitnessPruningStats, ChainStorageError> {
+        let write_txn = self.write_transaction()?;
+        let metadata = fetch_metadata(&write_txn, &self.metadata_db)?;
+        let start_height = fetch_witness_pruned_height(&write_txn, &self.metadata_db)?;
+        // Blocks below the pruned height can no longer be reorged out, so their spent outputs and inputs are not needed
+        let end_height = cmp::min(start_height.saturating_add(max_blocks), metadata.pruned_height());
+        if !metadata.is_pruned_node() || end_height <= start_height {
+            return Ok(WitnessPruningStats {
+                start_height,
+                end_height: start_height,
+                ..Default::default()
+            });
+        }
+
+        // Only outputs that were spent below the pruned height may be pruned
+        let mut spent = fetch_deleted_bitmap(&write_txn, &self.metadata_db)?.into_bitmap();
+        for height in metadata.pruned_height()..=metadata.height_of_longest_chain() {
+            let accum_data = self.fetch_block_accumulated_data(&write_txn, height).or_not_found(
+                "BlockAccumulatedData",
+                "height",
+                height.to_string(),
+            )?;
+            spent.andnot_inplace(accum_data.deleted());
+        }
+
+        let mut stats = WitnessPruningStats {
+            start_height,
+            end_height,
+            ..Default::default()
+        };
+        for height in start_height..end_height {
+            let header_hash =
+                lmdb_get::<_, BlockHeaderAccumulatedData>(&write_txn, &self.header_accumulated_data_db, &height)
+                    .or_not_found("BlockHeaderAccumulatedData", "height", height.to_string())?
+                    .hash;
+            let hash_hex = header_hash.to_hex();
+
+            let rows: Vec<TransactionOutputRowData> =
+                lmdb_fetch_keys_starting_with(hash_hex.as_str(), &write_txn, &self.utxos_db)?;
+            for mut row in rows {
+                if !spent.contains(row.mmr_position) {
+                    continue;
+                }
+                if row
+                    .output
+                    .as_ref()
+                    .map(|o| policy.retains(&o.commitment))
+                    .unwrap_or(false)
+                {
+                    stats.outputs_retained += 1;
+                    continue;
+                }
+                if let Some(output) = row.output.take() {
+                    stats.bytes_reclaimed += serialized_size(&output)?;
+                    let key = OutputKey::new(&header_hash, row.mmr_position);
+                    lmdb_replace(&write_txn, &self.utxos_db, key.get_key().as_str(), &row)?;
+                    stats.outputs_pruned += 1;
+                }
+            }
+
+            let inputs =
+                lmdb_delete_keys_starting_with::<TransactionInputRowData>(&write_txn, &self.inputs_db, &hash_hex)?;
+            for input in inputs {
+                if policy.retains(input.input.commitment()) {
+                    // Put back the inputs that spend retained outputs
+                    let key = format!("{}-{:010}-{}", hash_hex, input.mmr_position, input.hash.to_hex());
+                    lmdb_insert(&write_txn, &*self.inputs_db, key.as_str(), &input, "inputs_db")?;
+                    continue;
+                }
+                stats.bytes_reclaimed += serialized_size(&input)?;
+                stats.inputs_removed += 1;
+            }
+        }
+
+        self.set_metadata(
+            &write_txn,
+            MetadataKey::WitnessPrunedHeight,
+            MetadataValue::WitnessPrunedHeight(end_height),
+        )?;
+        write_txn.commit()?;
+        debug!(target: LOG_TARGET, "Pruned spent witness data for {}", stats);
+
+        Ok(stats)
+    }
+
     fn fetch_monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError> {
         let txn = self.read_transaction()?;
         Ok(lmdb_get(&txn, &self.monero_seed_height_db, seed)?.unwrap_or(0))
@@ -2125,6 +2401,31 @@ impl BlockchainBackend for LMDBDatabase {
             })
             .collect()
     }
+
+    fn flush(&self) -> Result<(), ChainStorageError> {
+        self.env.sync(true)?;
+        debug!(target: LOG_TARGET, "LMDB buffers have been flushed");
+        Ok(())
+    }
+
+    fn set_durable_commits(&mut self, durable: bool) -> Result<(), ChainStorageError> {
+        // MDB_NOSYNC, see LMDBBuilder::build for why the named flag is not used
+        let no_sync = open::Flags::from_bits(0x0001_0000).expect("LMDB open::Flag is correct");
+        // SAFETY: `LmdbDatabase` is wrapped in an exclusive write lock in BlockchainDatabase, so no transactions are
+        // open while the flags change.
+        unsafe {
+            self.env.set_flags(no_sync, !durable)?;
+        }
+        if durable {
+            self.env.sync(true)?;
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Durable commits turned {}",
+            if durable { "on" } else { "off" }
+        );
+        Ok(())
+    }
 }
 
 // Fetch the chain metadata
@@ -2161,6 +2462,20 @@ fn fetch_pruned_height(txn: &ConstTransaction<'_>, db: &Database) -> Result<u64,
         _ => Ok(0),
     }
 }
+// Fetches the height up to which spent witness data has been removed from the provided metadata db.
+fn fetch_witness_pruned_height(txn: &ConstTransaction<'_>, db: &Database) -> Result<u64, ChainStorageError> {
+    let k = MetadataKey::WitnessPrunedHeight;
+    let val: Option<MetadataValue> = lmdb_get(txn, db, &k.as_u32())?;
+    match val {
+        Some(MetadataValue::WitnessPrunedHeight(height)) => Ok(height),
+        _ => Ok(0),
+    }
+}
+
+fn serialized_size<T: Serialize>(value: &T) -> Result<u64, ChainStorageError> {
+    bincode::serialized_size(value).map_err(|e| ChainStorageError::AccessError(e.to_string()))
+}
+
 // Fetches the best block hash from the provided metadata db.
 fn fetch_horizon_data(txn: &ConstTransaction<'_>, db: &Database) -> Result<Option<HorizonData>, ChainStorageError> {
     let k = MetadataKey::HorizonData;
@@ -2203,6 +2518,21 @@ fn fetch_accumulated_work(txn: &ConstTransaction<'_>, db: &Database) -> Result<u
     }
 }
 
+// Fetches the migration version from the provided metadata db. Databases without a version are at version 0.
+fn fetch_migration_version(txn: &ConstTransaction<'_>, db: &Database) -> Result<u64, ChainStorageError> {
+    let k = MetadataKey::MigrationVersion.as_u32();
+    let val: Option<MetadataValue> = lmdb_get(txn, db, &k)?;
+    match val {
+        Some(MetadataValue::MigrationVersion(version)) => Ok(version),
+        None => Ok(0),
+        _ => Err(ChainStorageError::ValueNotFound {
+            entity: "ChainMetadata",
+            field: "MigrationVersion",
+            value: "".to_string(),
+        }),
+    }
+}
+
 // Fetches the deleted bitmap from the provided metadata db.
 fn fetch_deleted_bitmap(txn: &ConstTransaction<'_>, db: &Database) -> Result<DeletedBitmap, ChainStorageError> {
     let k = MetadataKey::DeletedBitmap.as_u32();
@@ -2244,6 +2574,8 @@ enum MetadataKey {
     PrunedHeight,
     HorizonData,
     DeletedBitmap,
+    WitnessPrunedHeight,
+    MigrationVersion,
 }
 
 impl MetadataKey {
@@ -2263,6 +2595,8 @@ impl fmt::Display for MetadataKey {
             MetadataKey::BestBlock => f.write_str("Chain tip block hash"),
             MetadataKey::HorizonData => f.write_str("Database info"),
             MetadataKey::DeletedBitmap => f.write_str("Deleted bitmap"),
+            MetadataKey::WitnessPrunedHeight => f.write_str("Witness pruned height"),
+            MetadataKey::MigrationVersion => f.write_str("Database migration version"),
         }
     }
 }
@@ -2277,6 +2611,8 @@ enum MetadataValue {
     PrunedHeight(u64),
     HorizonData(HorizonData),
     DeletedBitmap(DeletedBitmap),
+    WitnessPrunedHeight(u64),
+    MigrationVersion(u64),
 }
 
 impl fmt::Display for MetadataValue {
@@ -2291,6 +2627,8 @@ impl fmt::Display for MetadataValue {
             MetadataValue::DeletedBitmap(deleted) => {
                 write!(f, "Deleted Bitmap ({} indexes)", deleted.bitmap().cardinality())
             },
+            MetadataValue::WitnessPrunedHeight(height) => write!(f, "Witness pruned height is {}", height),
+            MetadataValue::MigrationVersion(version) => write!(f, "Database migration version is {}", version),
         }
     }
 }
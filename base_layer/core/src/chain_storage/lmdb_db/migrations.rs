@@ -0,0 +1,248 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Row formats of earlier database versions, used to upgrade an existing database to the current format when it is
+//! opened. Rows are bincode encoded, so any change to a stored type needs a migration.
+
+use crate::{
+    chain_storage::lmdb_db::{TransactionInputRowData, TransactionKernelRowData, TransactionOutputRowData},
+    transactions::{
+        tari_amount::MicroTari,
+        transaction::{
+            KernelFeatures,
+            OutputFeatures,
+            OutputFlags,
+            TransactionInput,
+            TransactionKernel,
+            TransactionOutput,
+        },
+    },
+};
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{ComSignature, Commitment, HashOutput, PublicKey, RangeProof, Signature};
+use tari_crypto::script::{ExecutionStack, TariScript};
+
+/// The version of the row formats written by this version of the database. Databases without a stored version are at
+/// version 0.
+pub(super) const MIGRATION_VERSION: u64 = 2;
+
+/// A kernel as stored before burn amounts were added (version 0)
+#[derive(Serialize, Deserialize)]
+struct TransactionKernelV0 {
+    features: KernelFeatures,
+    fee: MicroTari,
+    lock_height: u64,
+    excess: Commitment,
+    excess_sig: Signature,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct TransactionKernelRowDataV0 {
+    kernel: TransactionKernelV0,
+    header_hash: HashOutput,
+    mmr_position: u32,
+    hash: HashOutput,
+}
+
+impl From<TransactionKernelRowDataV0> for TransactionKernelRowData {
+    fn from(row: TransactionKernelRowDataV0) -> Self {
+        let TransactionKernelV0 {
+            features,
+            fee,
+            lock_height,
+            excess,
+            excess_sig,
+        } = row.kernel;
+        Self {
+            kernel: TransactionKernel {
+                features,
+                fee,
+                // Burn kernels did not exist before version 1
+                burn_amount: MicroTari(0),
+                lock_height,
+                excess,
+                excess_sig,
+            },
+            header_hash: row.header_hash,
+            mmr_position: row.mmr_position,
+            hash: row.hash,
+        }
+    }
+}
+
+/// Output features as stored before encrypted memos were added (up to version 1)
+#[derive(Serialize, Deserialize)]
+struct OutputFeaturesV1 {
+    flags: OutputFlags,
+    maturity: u64,
+}
+
+impl From<OutputFeaturesV1> for OutputFeatures {
+    fn from(features: OutputFeaturesV1) -> Self {
+        Self {
+            flags: features.flags,
+            maturity: features.maturity,
+            // Output memos did not exist before version 2
+            encrypted_memo: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TransactionOutputV1 {
+    features: OutputFeaturesV1,
+    commitment: Commitment,
+    proof: RangeProof,
+    script: TariScript,
+    sender_offset_public_key: PublicKey,
+    metadata_signature: ComSignature,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct TransactionOutputRowDataV1 {
+    output: Option<TransactionOutputV1>,
+    header_hash: HashOutput,
+    mmr_position: u32,
+    hash: HashOutput,
+    witness_hash: HashOutput,
+    mined_height: u64,
+}
+
+impl From<TransactionOutputRowDataV1> for TransactionOutputRowData {
+    fn from(row: TransactionOutputRowDataV1) -> Self {
+        Self {
+            output: row.output.map(|output| TransactionOutput {
+                features: output.features.into(),
+                commitment: output.commitment,
+                proof: output.proof,
+                script: output.script,
+                sender_offset_public_key: output.sender_offset_public_key,
+                metadata_signature: output.metadata_signature,
+            }),
+            header_hash: row.header_hash,
+            mmr_position: row.mmr_position,
+            hash: row.hash,
+            witness_hash: row.witness_hash,
+            mined_height: row.mined_height,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TransactionInputV1 {
+    features: OutputFeaturesV1,
+    commitment: Commitment,
+    script: TariScript,
+    input_data: ExecutionStack,
+    script_signature: ComSignature,
+    sender_offset_public_key: PublicKey,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct TransactionInputRowDataV1 {
+    input: TransactionInputV1,
+    header_hash: HashOutput,
+    mmr_position: u32,
+    hash: HashOutput,
+}
+
+impl From<TransactionInputRowDataV1> for TransactionInputRowData {
+    fn from(row: TransactionInputRowDataV1) -> Self {
+        let input = row.input;
+        Self {
+            input: TransactionInput {
+                features: input.features.into(),
+                commitment: input.commitment,
+                script: input.script,
+                input_data: input.input_data,
+                script_signature: input.script_signature,
+                sender_offset_public_key: input.sender_offset_public_key,
+            },
+            header_hash: row.header_hash,
+            mmr_position: row.mmr_position,
+            hash: row.hash,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{chain_storage::lmdb_db::lmdb::deserialize, transactions::helpers::create_test_kernel};
+
+    #[test]
+    fn it_migrates_kernels_from_version_0() {
+        let kernel = create_test_kernel(100.into(), 5);
+        let row = TransactionKernelRowDataV0 {
+            kernel: TransactionKernelV0 {
+                features: kernel.features,
+                fee: kernel.fee,
+                lock_height: kernel.lock_height,
+                excess: kernel.excess.clone(),
+                excess_sig: kernel.excess_sig.clone(),
+            },
+            header_hash: vec![1; 32],
+            mmr_position: 7,
+            hash: vec![2; 32],
+        };
+        let buf = bincode::serialize(&row).unwrap();
+
+        let migrated = TransactionKernelRowData::from(deserialize::<TransactionKernelRowDataV0>(&buf).unwrap());
+        assert_eq!(migrated.kernel, kernel);
+        assert_eq!(migrated.header_hash, vec![1; 32]);
+        assert_eq!(migrated.mmr_position, 7);
+        assert_eq!(migrated.hash, vec![2; 32]);
+    }
+
+    #[test]
+    fn it_migrates_inputs_from_version_1() {
+        let input = TransactionInput::new(
+            OutputFeatures::with_maturity(42),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        let row = TransactionInputRowDataV1 {
+            input: TransactionInputV1 {
+                features: OutputFeaturesV1 {
+                    flags: input.features.flags,
+                    maturity: input.features.maturity,
+                },
+                commitment: input.commitment.clone(),
+                script: input.script.clone(),
+                input_data: input.input_data.clone(),
+                script_signature: input.script_signature.clone(),
+                sender_offset_public_key: input.sender_offset_public_key.clone(),
+            },
+            header_hash: vec![1; 32],
+            mmr_position: 7,
+            hash: vec![2; 32],
+        };
+        let buf = bincode::serialize(&row).unwrap();
+
+        let migrated = TransactionInputRowData::from(deserialize::<TransactionInputRowDataV1>(&buf).unwrap());
+        assert_eq!(migrated.input, input);
+        assert_eq!(migrated.mmr_position, 7);
+    }
+}
@@ -23,8 +23,12 @@
 mod lmdb;
 #[allow(clippy::module_inception)]
 mod lmdb_db;
+mod migrations;
 
-use crate::transactions::transaction::{TransactionInput, TransactionKernel, TransactionOutput};
+use crate::transactions::{
+    tari_amount::MicroTari,
+    transaction::{TransactionInput, TransactionKernel, TransactionOutput},
+};
 pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, LMDBDatabase};
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::HashOutput;
@@ -48,6 +52,7 @@ pub const LMDB_DB_MONERO_SEED_HEIGHT: &str = "monero_seed_height";
 pub const LMDB_DB_ORPHAN_HEADER_ACCUMULATED_DATA: &str = "orphan_accumulated_data";
 pub const LMDB_DB_ORPHAN_CHAIN_TIPS: &str = "orphan_chain_tips";
 pub const LMDB_DB_ORPHAN_PARENT_MAP_INDEX: &str = "orphan_parent_map_index";
+pub const LMDB_DB_BURNT_KERNELS: &str = "burnt_kernels";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct TransactionOutputRowData {
@@ -74,3 +79,10 @@ pub(crate) struct TransactionKernelRowData {
     pub mmr_position: u32,
     pub hash: HashOutput,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct BurntKernelRowData {
+    pub header_hash: HashOutput,
+    pub height: u64,
+    pub burn_amount: MicroTari,
+}
@@ -0,0 +1,74 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{blocks::BlockHeader, chain_storage::MmrTree};
+use std::fmt::{Display, Error, Formatter};
+use tari_common_types::types::{HashDigest, HashOutput};
+use tari_crypto::tari_utilities::{hex::Hex, Hashable};
+use tari_mmr::{MerkleProof, MerkleProofError};
+
+/// Proves that a leaf (a kernel hash or an output witness hash) is committed to by the MMR root in a block header.
+/// This allows light clients that only track the header chain to verify that a kernel or output was mined without
+/// having to trust the node that provided the proof.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MmrMembershipProof {
+    pub header: BlockHeader,
+    pub tree: MmrTree,
+    pub leaf_index: u64,
+    pub leaf_hash: HashOutput,
+    pub merkle_proof: MerkleProof,
+}
+
+impl MmrMembershipProof {
+    /// The MMR root in the header that the proof is verified against
+    pub fn root(&self) -> Option<&HashOutput> {
+        match self.tree {
+            MmrTree::Kernel => Some(&self.header.kernel_mr),
+            MmrTree::Witness => Some(&self.header.witness_mr),
+            // The UTXO MMR root commits to the deleted bitmap, which is not included in the proof
+            MmrTree::Utxo => None,
+        }
+    }
+
+    /// Verifies that the leaf hash is included at `leaf_index` in the MMR committed to by the header. The caller is
+    /// responsible for checking that the header is part of the chain they are following.
+    pub fn verify(&self) -> Result<(), MerkleProofError> {
+        let root = self.root().ok_or(MerkleProofError::Unexpected)?;
+        self.merkle_proof
+            .verify_leaf::<HashDigest>(root, &self.leaf_hash, self.leaf_index as usize)
+    }
+}
+
+impl Display for MmrMembershipProof {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "{} leaf #{} ({}) in block #{} ({})",
+            self.tree,
+            self.leaf_index,
+            self.leaf_hash.to_hex(),
+            self.header.height,
+            self.header.hash().to_hex()
+        )
+    }
+}
@@ -64,18 +64,30 @@ mod consts;
 mod db_transaction;
 pub use db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation};
 
+mod membership_proof;
+pub use membership_proof::MmrMembershipProof;
+
 mod mmr_tree;
 pub use mmr_tree::*;
 
 mod error;
 pub use error::{ChainStorageError, Optional, OrNotFound};
 
+mod header_cache;
+pub use header_cache::HeaderCacheStats;
+
 mod historical_block;
 pub use historical_block::HistoricalBlock;
 
 mod horizon_data;
 pub use horizon_data::HorizonData;
 
+mod orphan_pool;
+pub use orphan_pool::OrphanPoolStats;
+
+mod pruning_policy;
+pub use pruning_policy::PruningPolicy;
+
 mod pruned_output;
 pub use pruned_output::PrunedOutput;
 
@@ -98,3 +110,6 @@ pub use stats::{DbBasicStats, DbSize, DbStat, DbTotalSizeStats};
 
 mod target_difficulties;
 pub use target_difficulties::TargetDifficulties;
+
+mod witness_gc;
+pub use witness_gc::{WitnessGarbageCollector, WitnessGcConfig, WitnessPruningStats};
@@ -0,0 +1,204 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tari_common_types::types::HashOutput;
+
+/// Summary of the blocks held in the orphan pool
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrphanPoolStats {
+    /// The number of orphan blocks in the pool
+    pub num_orphans: usize,
+    /// The maximum number of orphan blocks the pool may hold before orphans are evicted
+    pub capacity: usize,
+    /// The number of orphans that link to the main chain, i.e. that have known accumulated data
+    pub num_linked: usize,
+    /// The number of tips of orphan chains that link to the main chain
+    pub num_chain_tips: usize,
+    /// The lowest and highest orphan heights
+    pub height_range: Option<(u64, u64)>,
+    /// The total accumulated difficulty of the strongest orphan chain tip
+    pub best_total_accumulated_difficulty: u128,
+    /// The serialized size of the orphan blocks
+    pub size_bytes: u64,
+}
+
+impl fmt::Display for OrphanPoolStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} orphans ({} linked, {} chain tips)",
+            self.num_orphans, self.capacity, self.num_linked, self.num_chain_tips
+        )?;
+        if let Some((min, max)) = self.height_range {
+            write!(f, " at heights {}-{}", min, max)?;
+        }
+        Ok(())
+    }
+}
+
+/// An orphan that may be evicted from the pool
+#[derive(Debug, Clone)]
+pub(crate) struct EvictionCandidate {
+    pub hash: HashOutput,
+    pub height: u64,
+    /// The total accumulated difficulty of the orphan if it links to the main chain, otherwise 0
+    pub total_work: u128,
+    /// The value of the usage counter when the orphan was last used, 0 if it is unknown
+    pub last_used: u64,
+}
+
+/// Returns the orphans that have to be evicted for the pool to hold at most `capacity` orphans. Orphans at or below
+/// the horizon height are always evicted, followed by the orphans with the least total work and, of those, the least
+/// recently used.
+pub(crate) fn select_orphans_to_evict(
+    mut candidates: Vec<EvictionCandidate>,
+    horizon_height: u64,
+    capacity: usize,
+) -> Vec<EvictionCandidate> {
+    let num_over_limit = candidates.len().saturating_sub(capacity);
+    if num_over_limit == 0 {
+        return Vec::new();
+    }
+
+    candidates.sort_by(|a, b| {
+        let a_expired = a.height <= horizon_height;
+        let b_expired = b.height <= horizon_height;
+        match (a_expired, b_expired) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => a
+                .total_work
+                .cmp(&b.total_work)
+                .then_with(|| a.last_used.cmp(&b.last_used)),
+        }
+    });
+    let num_expired = candidates.iter().filter(|c| c.height <= horizon_height).count();
+    candidates.truncate(std::cmp::max(num_over_limit, num_expired));
+    candidates
+}
+
+/// Tracks when orphans were last used so that the least recently used orphans can be evicted first. This is held in
+/// memory, so after a restart all orphans are considered equally old until they are used again.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OrphanUsage {
+    inner: Arc<Mutex<OrphanUsageInner>>,
+}
+
+#[derive(Debug, Default)]
+struct OrphanUsageInner {
+    counter: u64,
+    last_used: HashMap<HashOutput, u64>,
+}
+
+impl OrphanUsage {
+    /// Marks the orphan as used
+    pub fn touch(&self, hash: &[u8]) {
+        let mut inner = acquire(&self.inner);
+        inner.counter += 1;
+        let counter = inner.counter;
+        inner.last_used.insert(hash.to_vec(), counter);
+    }
+
+    pub fn last_used(&self, hash: &[u8]) -> u64 {
+        acquire(&self.inner).last_used.get(hash).copied().unwrap_or(0)
+    }
+
+    pub fn remove(&self, hash: &[u8]) {
+        acquire(&self.inner).last_used.remove(hash);
+    }
+
+    /// Removes the usage of orphans that are no longer in the pool
+    pub fn retain(&self, orphans: &HashSet<HashOutput>) {
+        acquire(&self.inner).last_used.retain(|hash, _| orphans.contains(hash));
+    }
+}
+
+fn acquire(inner: &Mutex<OrphanUsageInner>) -> std::sync::MutexGuard<'_, OrphanUsageInner> {
+    // The usage is only a hint for eviction, so a poisoned lock is not a reason to fail
+    inner.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn candidate(n: u8, height: u64, total_work: u128, last_used: u64) -> EvictionCandidate {
+        EvictionCandidate {
+            hash: vec![n],
+            height,
+            total_work,
+            last_used,
+        }
+    }
+
+    fn evicted(candidates: Vec<EvictionCandidate>, horizon_height: u64, capacity: usize) -> Vec<u8> {
+        select_orphans_to_evict(candidates, horizon_height, capacity)
+            .into_iter()
+            .map(|c| c.hash[0])
+            .collect()
+    }
+
+    #[test]
+    fn it_evicts_nothing_within_capacity() {
+        let candidates = vec![candidate(1, 1, 0, 0), candidate(2, 2, 0, 0)];
+        assert!(evicted(candidates, 10, 2).is_empty());
+    }
+
+    #[test]
+    fn it_evicts_the_least_work_then_least_recently_used() {
+        let candidates = vec![
+            candidate(1, 11, 100, 1),
+            candidate(2, 12, 0, 5),
+            candidate(3, 13, 0, 2),
+            candidate(4, 14, 50, 3),
+        ];
+        assert_eq!(evicted(candidates.clone(), 10, 3), vec![3]);
+        assert_eq!(evicted(candidates, 10, 1), vec![3, 2, 4]);
+    }
+
+    #[test]
+    fn it_evicts_all_orphans_below_the_horizon() {
+        let candidates = vec![candidate(1, 5, 100, 9), candidate(2, 12, 0, 1), candidate(3, 6, 100, 9)];
+        assert_eq!(evicted(candidates, 10, 2), vec![1, 3]);
+    }
+
+    #[test]
+    fn it_tracks_usage() {
+        let usage = OrphanUsage::default();
+        usage.touch(&[1]);
+        usage.touch(&[2]);
+        assert!(usage.last_used(&[2]) > usage.last_used(&[1]));
+        usage.touch(&[1]);
+        assert!(usage.last_used(&[1]) > usage.last_used(&[2]));
+        usage.retain(&vec![vec![1]].into_iter().collect());
+        assert_eq!(usage.last_used(&[2]), 0);
+        usage.remove(&[1]);
+        assert_eq!(usage.last_used(&[1]), 0);
+    }
+}
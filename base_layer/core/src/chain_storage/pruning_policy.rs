@@ -0,0 +1,128 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{collections::HashSet, sync::Arc};
+use tari_common_types::types::Commitment;
+
+/// Decides which data a pruned node keeps. Full blocks are kept for the last `pruning_horizon` blocks and older
+/// block bodies are pruned, except for outputs with a retained commitment (e.g. the operator's own outputs), which
+/// are kept along with the inputs that spend them. A pruning horizon of 0 keeps all blocks (archival mode).
+#[derive(Debug, Clone, Default)]
+pub struct PruningPolicy {
+    pruning_horizon: u64,
+    pruning_interval: u64,
+    retained_commitments: Arc<HashSet<Commitment>>,
+}
+
+impl PruningPolicy {
+    pub fn new(pruning_horizon: u64, pruning_interval: u64) -> Self {
+        Self {
+            pruning_horizon,
+            pruning_interval,
+            retained_commitments: Default::default(),
+        }
+    }
+
+    /// A policy that never prunes
+    pub fn archival() -> Self {
+        Self::default()
+    }
+
+    pub fn with_retained_commitments<I: IntoIterator<Item = Commitment>>(mut self, commitments: I) -> Self {
+        self.retained_commitments = Arc::new(commitments.into_iter().collect());
+        self
+    }
+
+    pub fn is_pruned(&self) -> bool {
+        self.pruning_horizon > 0
+    }
+
+    /// The number of recent blocks for which full blocks are kept
+    pub fn pruning_horizon(&self) -> u64 {
+        self.pruning_horizon
+    }
+
+    /// The number of blocks the pruned height may fall behind the horizon height before pruning is performed
+    pub fn pruning_interval(&self) -> u64 {
+        self.pruning_interval
+    }
+
+    /// Returns the height below which block bodies are pruned for a chain with the given tip height, or 0 for an
+    /// archival node
+    pub fn horizon_height(&self, tip_height: u64) -> u64 {
+        if self.is_pruned() {
+            tip_height.saturating_sub(self.pruning_horizon)
+        } else {
+            0
+        }
+    }
+
+    /// Returns true if pruning is due for a node that has pruned up to `pruned_height`
+    pub fn should_prune(&self, pruned_height: u64, tip_height: u64) -> bool {
+        self.is_pruned() && pruned_height < self.horizon_height(tip_height).saturating_sub(self.pruning_interval)
+    }
+
+    /// Returns true if outputs with this commitment must not be pruned
+    pub fn retains(&self, commitment: &Commitment) -> bool {
+        self.retained_commitments.contains(commitment)
+    }
+
+    pub fn has_retained_commitments(&self) -> bool {
+        !self.retained_commitments.is_empty()
+    }
+
+    pub fn num_retained_commitments(&self) -> usize {
+        self.retained_commitments.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn horizon_and_interval() {
+        let policy = PruningPolicy::archival();
+        assert!(!policy.is_pruned());
+        assert_eq!(policy.horizon_height(1000), 0);
+        assert!(!policy.should_prune(0, 1000));
+
+        let policy = PruningPolicy::new(100, 50);
+        assert!(policy.is_pruned());
+        assert_eq!(policy.horizon_height(50), 0);
+        assert_eq!(policy.horizon_height(1000), 900);
+        assert!(policy.should_prune(849, 1000));
+        assert!(!policy.should_prune(850, 1000));
+    }
+
+    #[test]
+    fn retained_commitments() {
+        let retained = Commitment::default();
+        let policy = PruningPolicy::new(100, 50);
+        assert!(!policy.retains(&retained));
+        let policy = policy.with_retained_commitments(vec![retained.clone()]);
+        assert!(policy.has_retained_commitments());
+        assert!(policy.retains(&retained));
+        assert_eq!(policy.num_retained_commitments(), 1);
+    }
+}
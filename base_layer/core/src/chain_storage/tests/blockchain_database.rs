@@ -410,6 +410,85 @@ mod add_block {
     }
 }
 
+mod fetch_membership_proofs {
+    use super::*;
+    use crate::{
+        blocks::{OutputProof, OutputProofError},
+        chain_storage::MmrTree,
+        transactions::CryptoFactories,
+    };
+
+    #[test]
+    fn it_proves_kernels_and_outputs_in_each_block() {
+        let db = setup();
+        add_many_chained_blocks(5, &db);
+        for height in 0..=5 {
+            let block = db.fetch_block(height).unwrap().try_into_block().unwrap();
+
+            let kernel = &block.body.kernels()[0];
+            let proof = db
+                .fetch_kernel_membership_proof(kernel.excess_sig.clone())
+                .unwrap()
+                .unwrap();
+            assert_eq!(proof.tree, MmrTree::Kernel);
+            assert_eq!(proof.header, block.header);
+            assert_eq!(proof.leaf_hash, kernel.hash());
+            proof.verify().unwrap();
+
+            let output = &block.body.outputs()[0];
+            let proof = db.fetch_output_membership_proof(output.hash()).unwrap().unwrap();
+            assert_eq!(proof.tree, MmrTree::Witness);
+            assert_eq!(proof.header, block.header);
+            assert_eq!(proof.leaf_hash, output.witness_hash());
+            proof.verify().unwrap();
+        }
+    }
+
+    #[test]
+    fn it_returns_none_for_an_unknown_output() {
+        let db = setup();
+        add_many_chained_blocks(1, &db);
+        assert!(db.fetch_output_membership_proof(vec![0u8; 32]).unwrap().is_none());
+    }
+
+    #[test]
+    fn it_fails_to_verify_against_another_header() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(2, &db);
+        let kernel = &blocks[0].body.kernels()[0];
+        let mut proof = db
+            .fetch_kernel_membership_proof(kernel.excess_sig.clone())
+            .unwrap()
+            .unwrap();
+        proof.header = blocks[1].header.clone();
+        assert!(proof.verify().is_err());
+    }
+
+    #[test]
+    fn it_creates_a_verifiable_output_proof() {
+        let db = setup();
+        let (blocks, _) = add_many_chained_blocks(4, &db);
+        let output = blocks[1].body.outputs()[0].clone();
+        let membership_proof = db.fetch_output_membership_proof(output.hash()).unwrap().unwrap();
+        let mut proof = OutputProof {
+            output,
+            leaf_index: membership_proof.leaf_index,
+            merkle_proof: membership_proof.merkle_proof,
+            headers: db.fetch_headers(2..).unwrap(),
+        };
+        let factories = CryptoFactories::default();
+        proof.verify(&factories.range_proof).unwrap();
+        assert_eq!(proof.confirmations(), 3);
+        assert_eq!(proof.tip_header().unwrap().height, 4);
+
+        proof.headers.remove(1);
+        unpack_enum!(
+            OutputProofError::BrokenHeaderChain { height } = proof.verify(&factories.range_proof).unwrap_err()
+        );
+        assert_eq!(height, 4);
+    }
+}
+
 mod get_stats {
     use super::*;
 
@@ -0,0 +1,163 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainStorageError};
+use log::*;
+use std::{fmt, time::Duration};
+use tari_shutdown::ShutdownSignal;
+use tokio::time;
+
+const LOG_TARGET: &str = "c::cs::witness_gc";
+
+/// The result of removing the witness data of spent outputs for a range of blocks below the pruned height
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WitnessPruningStats {
+    /// The first block height that was processed
+    pub start_height: u64,
+    /// The block height after the last block that was processed
+    pub end_height: u64,
+    /// The number of spent outputs that had their full output data removed
+    pub outputs_pruned: usize,
+    /// The number of transaction inputs that were removed
+    pub inputs_removed: usize,
+    /// The number of spent outputs that were kept because the pruning policy retains their commitment
+    pub outputs_retained: usize,
+    /// The size of the serialized data that was removed
+    pub bytes_reclaimed: u64,
+}
+
+impl WitnessPruningStats {
+    pub fn num_blocks(&self) -> u64 {
+        self.end_height.saturating_sub(self.start_height)
+    }
+
+    fn merge(&mut self, other: &WitnessPruningStats) {
+        self.end_height = other.end_height;
+        self.outputs_pruned += other.outputs_pruned;
+        self.inputs_removed += other.inputs_removed;
+        self.outputs_retained += other.outputs_retained;
+        self.bytes_reclaimed += other.bytes_reclaimed;
+    }
+}
+
+impl fmt::Display for WitnessPruningStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "blocks {}..{}: {} output(s) pruned, {} output(s) retained, {} input(s) removed, {} bytes reclaimed",
+            self.start_height,
+            self.end_height,
+            self.outputs_pruned,
+            self.outputs_retained,
+            self.inputs_removed,
+            self.bytes_reclaimed
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WitnessGcConfig {
+    /// The maximum number of blocks processed in a single database transaction
+    pub batch_size: u64,
+    /// The time to wait between batches, limiting the IO load the collector puts on the node
+    pub batch_delay: Duration,
+    /// The time to wait between collection runs
+    pub interval: Duration,
+}
+
+impl Default for WitnessGcConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            batch_delay: Duration::from_millis(500),
+            interval: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// Periodically removes the witness data (range proofs, scripts and input data) of outputs that were spent below the
+/// pruned height, along with the inputs of those blocks. Pruned nodes only prune outputs as they are spent and keep
+/// all inputs, so without this they retain far more data than the pruning horizon implies.
+pub struct WitnessGarbageCollector<B> {
+    db: AsyncBlockchainDb<B>,
+    config: WitnessGcConfig,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl<B: BlockchainBackend + 'static> WitnessGarbageCollector<B> {
+    pub fn new(db: AsyncBlockchainDb<B>, config: WitnessGcConfig, shutdown_signal: ShutdownSignal) -> Self {
+        Self {
+            db,
+            config,
+            shutdown_signal,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut interval = time::interval(self.config.interval);
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    match self.collect().await {
+                        Ok(stats) if stats.num_blocks() > 0 => {
+                            info!(target: LOG_TARGET, "Witness garbage collection complete for {}", stats);
+                        },
+                        Ok(_) => {
+                            debug!(target: LOG_TARGET, "No witness data to collect");
+                        },
+                        Err(err) => {
+                            error!(target: LOG_TARGET, "Witness garbage collection failed: {}", err);
+                        },
+                    }
+                },
+                _ = self.shutdown_signal.wait() => {
+                    info!(target: LOG_TARGET, "Witness garbage collector shutting down");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Processes batches of blocks until the pruned height is reached or shutdown is signalled
+    async fn collect(&mut self) -> Result<WitnessPruningStats, ChainStorageError> {
+        let mut total: Option<WitnessPruningStats> = None;
+        loop {
+            let stats = self.db.prune_spent_witness_data(self.config.batch_size).await?;
+            if stats.num_blocks() == 0 {
+                break;
+            }
+            debug!(target: LOG_TARGET, "Collected witness data for {}", stats);
+            match total.as_mut() {
+                Some(total) => total.merge(&stats),
+                None => total = Some(stats),
+            }
+            tokio::select! {
+                _ = time::sleep(self.config.batch_delay) => {},
+                _ = self.shutdown_signal.wait() => break,
+            }
+        }
+
+        Ok(total.unwrap_or_default())
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+use tari_common_types::types::HashOutput;
+use tari_crypto::tari_utilities::hex::{from_hex, to_hex};
+
+/// Hard checkpoints are block hashes that the chain must contain at the given heights. Block bodies that are buried
+/// under a checkpoint and below the assume-valid height are assumed valid during sync, which skips their range proof
+/// and script verification.
+#[derive(Debug, Clone, Default)]
+pub struct Checkpoints {
+    checkpoints: BTreeMap<u64, HashOutput>,
+    assume_valid_before_height: u64,
+}
+
+impl Checkpoints {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoints.insert(checkpoint.height, checkpoint.hash);
+        self
+    }
+
+    /// Block bodies below this height that are buried under a checkpoint are assumed valid
+    pub fn with_assume_valid_before_height(mut self, height: u64) -> Self {
+        self.assume_valid_before_height = height;
+        self
+    }
+
+    /// The checkpointed hash at the given height, if any
+    pub fn get(&self, height: u64) -> Option<&HashOutput> {
+        self.checkpoints.get(&height)
+    }
+
+    /// The height of the highest checkpoint, if any
+    pub fn highest_height(&self) -> Option<u64> {
+        self.checkpoints.keys().next_back().copied()
+    }
+
+    /// Block bodies below this height are assumed valid. This is the configured assume-valid height, limited to the
+    /// blocks at or below the highest checkpoint.
+    pub fn assume_valid_height(&self) -> u64 {
+        match self.highest_height() {
+            Some(height) => self.assume_valid_before_height.min(height + 1),
+            None => 0,
+        }
+    }
+
+    /// The lowest checkpoint at or above the given height, if a block body at that height may be assumed valid. The
+    /// block may only be assumed valid if it is an ancestor of the returned checkpoint.
+    pub fn assume_valid_checkpoint(&self, height: u64) -> Option<(u64, &HashOutput)> {
+        if height >= self.assume_valid_height() {
+            return None;
+        }
+        self.checkpoints
+            .range(height..)
+            .next()
+            .map(|(height, hash)| (*height, hash))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &HashOutput)> {
+        self.checkpoints.iter().map(|(height, hash)| (*height, hash))
+    }
+
+    pub fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.is_empty()
+    }
+}
+
+/// A block hash at a height, written as `<height>:<hash hex>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub height: u64,
+    pub hash: HashOutput,
+}
+
+impl FromStr for Checkpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (height, hash) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Checkpoint '{}' is not in the `<height>:<hash>` format", s))?;
+        let height = height
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid checkpoint height '{}'", height))?;
+        let hash = from_hex(hash.trim()).map_err(|_| format!("Invalid checkpoint hash '{}'", hash))?;
+        if hash.len() != 32 {
+            return Err(format!("Checkpoint hash '{}' is not 32 bytes", to_hex(&hash)));
+        }
+        Ok(Self { height, hash })
+    }
+}
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.height, to_hex(&self.hash))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checkpoint(height: u64) -> Checkpoint {
+        Checkpoint {
+            height,
+            hash: vec![height as u8; 32],
+        }
+    }
+
+    #[test]
+    fn it_parses_checkpoints() {
+        let cp = checkpoint(1234);
+        assert_eq!(cp.to_string().parse::<Checkpoint>().unwrap(), cp);
+        assert!("1234".parse::<Checkpoint>().is_err());
+        assert!("abc:00".parse::<Checkpoint>().is_err());
+        assert!("1234:0011".parse::<Checkpoint>().is_err());
+    }
+
+    #[test]
+    fn it_limits_assumed_valid_blocks_to_checkpoints() {
+        let checkpoints = Checkpoints::new().with_assume_valid_before_height(1000);
+        assert!(checkpoints.assume_valid_checkpoint(0).is_none());
+
+        let checkpoints = checkpoints
+            .with_checkpoint(checkpoint(100))
+            .with_checkpoint(checkpoint(50));
+        assert_eq!(checkpoints.highest_height(), Some(100));
+        assert_eq!(checkpoints.assume_valid_checkpoint(10), Some((50, &vec![50u8; 32])));
+        assert_eq!(checkpoints.assume_valid_checkpoint(50), Some((50, &vec![50u8; 32])));
+        assert_eq!(checkpoints.assume_valid_checkpoint(51), Some((100, &vec![100u8; 32])));
+        assert_eq!(checkpoints.assume_valid_checkpoint(100), Some((100, &vec![100u8; 32])));
+        assert!(checkpoints.assume_valid_checkpoint(101).is_none());
+
+        let checkpoints = checkpoints.with_assume_valid_before_height(60);
+        assert!(checkpoints.assume_valid_checkpoint(59).is_some());
+        assert!(checkpoints.assume_valid_checkpoint(60).is_none());
+        assert_eq!(checkpoints.get(50), Some(&vec![50u8; 32]));
+    }
+}
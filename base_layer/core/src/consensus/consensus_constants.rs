@@ -23,7 +23,10 @@
 use crate::{
     consensus::{network::NetworkConsensus, KERNEL_WEIGHT, WEIGHT_PER_OUTPUT},
     proof_of_work::{Difficulty, PowAlgorithm},
-    transactions::tari_amount::{uT, MicroTari, T},
+    transactions::{
+        tari_amount::{uT, MicroTari, T},
+        transaction::MAX_OUTPUT_MEMO_SIZE,
+    },
 };
 use chrono::{DateTime, Duration, Utc};
 use std::{collections::HashMap, ops::Add};
@@ -62,6 +65,12 @@ pub struct ConsensusConstants {
     proof_of_work: HashMap<PowAlgorithm, PowAlgorithmConstants>,
     /// This is to keep track of the value inside of the genesis block
     faucet_value: MicroTari,
+    /// Whether blocks from this height may contain burn kernels. Burn kernels are enabled on a network by adding
+    /// constants with this set from the activation height.
+    burn_kernels_enabled: bool,
+    /// The maximum size in bytes of an encrypted output memo. Output memos are not allowed when this is zero, and are
+    /// enabled on a network by adding constants with a non-zero size from the activation height.
+    max_output_memo_size: usize,
 }
 
 /// This is just a convenience  wrapper to put all the info into a hashmap per diff algo
@@ -183,6 +192,16 @@ impl ConsensusConstants {
         self.max_randomx_seed_height
     }
 
+    /// Whether burn kernels are valid in blocks that these constants apply to
+    pub fn burn_kernels_enabled(&self) -> bool {
+        self.burn_kernels_enabled
+    }
+
+    /// The maximum size in bytes of an encrypted output memo, zero if output memos are not allowed
+    pub fn max_output_memo_size(&self) -> usize {
+        self.max_output_memo_size
+    }
+
     pub fn localnet() -> Vec<Self> {
         let difficulty_block_window = 90;
         let mut algos = HashMap::new();
@@ -212,6 +231,8 @@ impl ConsensusConstants {
             max_randomx_seed_height: u64::MAX,
             proof_of_work: algos,
             faucet_value: (5000 * 4000) * T,
+            burn_kernels_enabled: true,
+            max_output_memo_size: MAX_OUTPUT_MEMO_SIZE,
         }]
     }
 
@@ -245,6 +266,8 @@ impl ConsensusConstants {
             max_randomx_seed_height: u64::MAX,
             proof_of_work: algos,
             faucet_value: (5000 * 4000) * T,
+            burn_kernels_enabled: false,
+            max_output_memo_size: 0,
         }]
     }
 
@@ -305,6 +328,8 @@ impl ConsensusConstants {
                 max_randomx_seed_height: u64::MAX,
                 proof_of_work: algos,
                 faucet_value: (5000 * 4000) * T,
+                burn_kernels_enabled: false,
+                max_output_memo_size: 0,
             },
             ConsensusConstants {
                 effective_from_height: 1400,
@@ -320,6 +345,8 @@ impl ConsensusConstants {
                 max_randomx_seed_height: u64::MAX,
                 proof_of_work: algos2,
                 faucet_value: (5000 * 4000) * T,
+                burn_kernels_enabled: false,
+                max_output_memo_size: 0,
             },
         ]
     }
@@ -353,6 +380,8 @@ impl ConsensusConstants {
             max_randomx_seed_height: u64::MAX,
             proof_of_work: algos,
             faucet_value: (5000 * 4000) * T,
+            burn_kernels_enabled: false,
+            max_output_memo_size: 0,
         }]
     }
 
@@ -385,6 +414,8 @@ impl ConsensusConstants {
             max_randomx_seed_height: u64::MAX,
             proof_of_work: algos,
             faucet_value: (5000 * 4000) * T,
+            burn_kernels_enabled: false,
+            max_output_memo_size: 0,
         }]
     }
 
@@ -418,6 +449,8 @@ impl ConsensusConstants {
             max_randomx_seed_height: u64::MAX,
             proof_of_work: algos,
             faucet_value: MicroTari::from(0),
+            burn_kernels_enabled: false,
+            max_output_memo_size: 0,
         }]
     }
 }
@@ -470,6 +503,16 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_burn_kernels_enabled(mut self, enabled: bool) -> Self {
+        self.consensus.burn_kernels_enabled = enabled;
+        self
+    }
+
+    pub fn with_max_output_memo_size(mut self, size: usize) -> Self {
+        self.consensus.max_output_memo_size = size;
+        self
+    }
+
     pub fn with_faucet_value(mut self, value: MicroTari) -> Self {
         self.consensus.faucet_value = value;
         self
@@ -31,6 +31,7 @@ use crate::{
     chain_storage::{ChainBlock, ChainStorageError},
     consensus::{
         chain_strength_comparer::{strongest_chain, ChainStrengthComparer},
+        checkpoints::Checkpoints,
         emission::{Emission, EmissionSchedule},
         ConsensusConstants,
         NetworkConsensus,
@@ -140,6 +141,11 @@ impl ConsensusManager {
     pub fn network(&self) -> NetworkConsensus {
         self.inner.network
     }
+
+    /// The hard checkpoints that the chain must contain
+    pub fn checkpoints(&self) -> &Checkpoints {
+        &self.inner.checkpoints
+    }
 }
 
 /// This is the used to control all consensus values.
@@ -155,6 +161,8 @@ struct ConsensusManagerInner {
     pub gen_block: Option<ChainBlock>,
     /// The comparer used to determine which chain is stronger for reorgs.
     pub chain_strength_comparer: Box<dyn ChainStrengthComparer + Send + Sync>,
+    /// The hard checkpoints that the chain must contain
+    pub checkpoints: Checkpoints,
 }
 
 /// Constructor for the consensus manager struct
@@ -163,6 +171,7 @@ pub struct ConsensusManagerBuilder {
     network: NetworkConsensus,
     gen_block: Option<ChainBlock>,
     chain_strength_comparer: Option<Box<dyn ChainStrengthComparer + Send + Sync>>,
+    checkpoints: Checkpoints,
 }
 
 impl ConsensusManagerBuilder {
@@ -173,6 +182,7 @@ impl ConsensusManagerBuilder {
             network: network.into(),
             gen_block: None,
             chain_strength_comparer: None,
+            checkpoints: Checkpoints::default(),
         }
     }
 
@@ -193,6 +203,12 @@ impl ConsensusManagerBuilder {
         self
     }
 
+    /// Adds hard checkpoints that the chain must contain
+    pub fn with_checkpoints(mut self, checkpoints: Checkpoints) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
     /// Builds a consensus manager
     pub fn build(mut self) -> ConsensusManager {
         if self.consensus_constants.is_empty() {
@@ -221,6 +237,7 @@ impl ConsensusManagerBuilder {
                     .by_sha3_difficulty()
                     .build()
             }),
+            checkpoints: self.checkpoints,
         };
         ConsensusManager { inner: Arc::new(inner) }
     }
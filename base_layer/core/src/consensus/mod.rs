@@ -22,6 +22,8 @@
 
 #[cfg(feature = "base_node")]
 pub(crate) mod chain_strength_comparer;
+#[cfg(feature = "base_node")]
+mod checkpoints;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub mod consensus_constants;
 #[cfg(feature = "base_node")]
@@ -30,6 +32,8 @@ mod consensus_manager;
 pub mod emission;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 mod network;
+#[cfg(feature = "base_node")]
+mod weight_audit;
 
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub const WEIGHT_PER_INPUT: u64 = 1;
@@ -37,10 +41,16 @@ pub const WEIGHT_PER_INPUT: u64 = 1;
 pub const WEIGHT_PER_OUTPUT: u64 = 13;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub const KERNEL_WEIGHT: u64 = 3; // Constant weight per transaction; covers kernel and part of header.
+#[cfg(any(feature = "base_node", feature = "transactions"))]
+pub const MEMO_BYTES_PER_GRAM: u64 = 64; // Encrypted output memos weigh one gram per 64 bytes (or part thereof).
 
+#[cfg(feature = "base_node")]
+pub use checkpoints::{Checkpoint, Checkpoints};
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub use consensus_constants::{ConsensusConstants, ConsensusConstantsBuilder};
 #[cfg(feature = "base_node")]
 pub use consensus_manager::{ConsensusManager, ConsensusManagerBuilder, ConsensusManagerError};
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub use network::NetworkConsensus;
+#[cfg(feature = "base_node")]
+pub use weight_audit::{LinearWeights, WeightAudit, WeightAuditReport, WeightFunction, WeightSummary};
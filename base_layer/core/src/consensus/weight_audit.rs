@@ -0,0 +1,325 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{
+    blocks::Block,
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainStorageError},
+    consensus::{ConsensusManager, KERNEL_WEIGHT, WEIGHT_PER_INPUT, WEIGHT_PER_OUTPUT},
+    transactions::{aggregated_body::AggregateBody, fee::Fee, tari_amount::MicroTari},
+};
+use std::{cmp, fmt, ops::RangeInclusive, str::FromStr};
+
+/// Computes the weight of a block body. Implementations allow alternative weighting rules to be evaluated against
+/// historical blocks before a consensus change.
+pub trait WeightFunction: Send + Sync {
+    fn body_weight(&self, body: &AggregateBody) -> u64;
+}
+
+/// A weight that is linear in the number of kernels, inputs and outputs, written as `<kernel>:<input>:<output>`.
+/// Output memos are always weighed by size as in consensus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinearWeights {
+    pub kernel: u64,
+    pub input: u64,
+    pub output: u64,
+}
+
+impl LinearWeights {
+    /// The weights used by the current consensus rules
+    pub fn consensus() -> Self {
+        Self {
+            kernel: KERNEL_WEIGHT,
+            input: WEIGHT_PER_INPUT,
+            output: WEIGHT_PER_OUTPUT,
+        }
+    }
+}
+
+impl WeightFunction for LinearWeights {
+    fn body_weight(&self, body: &AggregateBody) -> u64 {
+        self.kernel * body.kernels().len() as u64 +
+            self.input * body.inputs().len() as u64 +
+            self.output * body.outputs().len() as u64 +
+            Fee::memo_weight(body.total_memo_size())
+    }
+}
+
+impl FromStr for LinearWeights {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split(':')
+            .map(|part| {
+                part.trim()
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid weight '{}'", part))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        match parts.as_slice() {
+            [kernel, input, output] => Ok(Self {
+                kernel: *kernel,
+                input: *input,
+                output: *output,
+            }),
+            _ => Err(format!(
+                "Weights '{}' are not in the `<kernel>:<input>:<output>` format",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LinearWeights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.kernel, self.input, self.output)
+    }
+}
+
+/// Block weight totals under a single weight function
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WeightSummary {
+    pub total_weight: u64,
+    /// The weight of the heaviest block
+    pub max_block_weight: u64,
+    /// The sum of each block's weight as a fraction of the maximum block weight
+    pub total_fullness: f64,
+    /// The number of blocks that would exceed the maximum block weight
+    pub overweight_blocks: u64,
+    /// The fees that would have been paid had each block's transactions paid the same fee per gram under this weight
+    /// function as under the baseline
+    pub projected_fees: MicroTari,
+}
+
+impl WeightSummary {
+    fn add(&mut self, weight: u64, max_weight: u64, projected_fees: MicroTari) {
+        self.total_weight += weight;
+        self.max_block_weight = cmp::max(self.max_block_weight, weight);
+        if max_weight > 0 {
+            self.total_fullness += weight as f64 / max_weight as f64;
+        }
+        if weight > max_weight {
+            self.overweight_blocks += 1;
+        }
+        self.projected_fees += projected_fees;
+    }
+}
+
+/// The impact of an alternative weight function on a range of historical blocks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WeightAuditReport {
+    pub start_height: u64,
+    pub end_height: u64,
+    pub num_blocks: u64,
+    /// The fees that were paid in the audited blocks
+    pub total_fees: MicroTari,
+    pub baseline: WeightSummary,
+    pub alternative: WeightSummary,
+}
+
+impl WeightAuditReport {
+    /// The mean block fullness under the baseline and alternative weights
+    pub fn mean_fullness(&self) -> (f64, f64) {
+        if self.num_blocks == 0 {
+            return (0.0, 0.0);
+        }
+        (
+            self.baseline.total_fullness / self.num_blocks as f64,
+            self.alternative.total_fullness / self.num_blocks as f64,
+        )
+    }
+
+    /// The relative change in fee revenue under the alternative weights, e.g. 0.1 for a 10% increase
+    pub fn fee_revenue_change(&self) -> f64 {
+        let baseline = u64::from(self.baseline.projected_fees);
+        if baseline == 0 {
+            return 0.0;
+        }
+        u64::from(self.alternative.projected_fees) as f64 / baseline as f64 - 1.0
+    }
+}
+
+impl fmt::Display for WeightAuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (baseline_fullness, alternative_fullness) = self.mean_fullness();
+        writeln!(
+            f,
+            "Blocks #{} - #{} ({} blocks), fees paid: {}",
+            self.start_height, self.end_height, self.num_blocks, self.total_fees
+        )?;
+        writeln!(
+            f,
+            "Total weight: {} -> {}",
+            self.baseline.total_weight, self.alternative.total_weight
+        )?;
+        writeln!(
+            f,
+            "Heaviest block: {} -> {}",
+            self.baseline.max_block_weight, self.alternative.max_block_weight
+        )?;
+        writeln!(
+            f,
+            "Mean block fullness: {:.2}% -> {:.2}%",
+            baseline_fullness * 100.0,
+            alternative_fullness * 100.0
+        )?;
+        writeln!(
+            f,
+            "Overweight blocks: {} -> {}",
+            self.baseline.overweight_blocks, self.alternative.overweight_blocks
+        )?;
+        write!(
+            f,
+            "Fee revenue at the same fee per gram: {} -> {} ({:+.2}%)",
+            self.baseline.projected_fees,
+            self.alternative.projected_fees,
+            self.fee_revenue_change() * 100.0
+        )
+    }
+}
+
+/// Recomputes block weights under an alternative weight function and compares them to a baseline
+pub struct WeightAudit<B, A> {
+    baseline: B,
+    alternative: A,
+    report: Option<WeightAuditReport>,
+}
+
+impl<B: WeightFunction, A: WeightFunction> WeightAudit<B, A> {
+    pub fn new(baseline: B, alternative: A) -> Self {
+        Self {
+            baseline,
+            alternative,
+            report: None,
+        }
+    }
+
+    /// Adds a block to the audit. `max_weight` is the maximum block weight allowed by consensus at the block's height.
+    pub fn add_block(&mut self, block: &Block, max_weight: u64) {
+        let height = block.header.height;
+        let report = self.report.get_or_insert_with(|| WeightAuditReport {
+            start_height: height,
+            end_height: height,
+            ..Default::default()
+        });
+        let fees = block.body.get_total_fee();
+        let baseline_weight = self.baseline.body_weight(&block.body);
+        let alternative_weight = self.alternative.body_weight(&block.body);
+        let alternative_fees = if baseline_weight == 0 {
+            fees
+        } else {
+            let fees = u128::from(u64::from(fees)) * u128::from(alternative_weight) / u128::from(baseline_weight);
+            MicroTari::from(fees as u64)
+        };
+
+        report.start_height = cmp::min(report.start_height, height);
+        report.end_height = cmp::max(report.end_height, height);
+        report.num_blocks += 1;
+        report.total_fees += fees;
+        report.baseline.add(baseline_weight, max_weight, fees);
+        report.alternative.add(alternative_weight, max_weight, alternative_fees);
+    }
+
+    pub fn finish(self) -> WeightAuditReport {
+        self.report.unwrap_or_default()
+    }
+
+    /// Audits the main chain blocks in `heights`, fetching `batch_size` blocks at a time. The database is only read.
+    pub async fn audit_chain<T: BlockchainBackend + 'static>(
+        mut self,
+        db: &AsyncBlockchainDb<T>,
+        rules: &ConsensusManager,
+        heights: RangeInclusive<u64>,
+        batch_size: u64,
+    ) -> Result<WeightAuditReport, ChainStorageError> {
+        let batch_size = cmp::max(batch_size, 1);
+        let (mut start, end) = heights.into_inner();
+        while start <= end {
+            let batch_end = cmp::min(start.saturating_add(batch_size - 1), end);
+            for block in db.fetch_blocks(start..=batch_end).await? {
+                let block = block.block();
+                let max_weight = rules
+                    .consensus_constants(block.header.height)
+                    .get_max_block_transaction_weight();
+                self.add_block(block, max_weight);
+            }
+            if batch_end == u64::MAX {
+                break;
+            }
+            start = batch_end + 1;
+        }
+        Ok(self.finish())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        blocks::BlockHeader,
+        transactions::{helpers::create_test_kernel, tari_amount::uT},
+    };
+
+    fn block_with_kernels(height: u64, fees: &[u64]) -> Block {
+        let kernels = fees.iter().map(|fee| create_test_kernel((*fee).into(), 0)).collect();
+        let mut header = BlockHeader::new(0);
+        header.height = height;
+        Block::new(header, AggregateBody::new(vec![], vec![], kernels))
+    }
+
+    #[test]
+    fn parse_linear_weights() {
+        let weights = "3:1:13".parse::<LinearWeights>().unwrap();
+        assert_eq!(weights, LinearWeights::consensus());
+        assert_eq!(weights.to_string(), "3:1:13");
+        assert!("3:1".parse::<LinearWeights>().is_err());
+        assert!("3:a:13".parse::<LinearWeights>().is_err());
+    }
+
+    #[test]
+    fn it_compares_weights() {
+        let alternative = LinearWeights {
+            kernel: 6,
+            input: 1,
+            output: 13,
+        };
+        let mut audit = WeightAudit::new(LinearWeights::consensus(), alternative);
+        audit.add_block(&block_with_kernels(10, &[100, 200]), 10);
+        audit.add_block(&block_with_kernels(11, &[]), 10);
+        let report = audit.finish();
+
+        assert_eq!(report.start_height, 10);
+        assert_eq!(report.end_height, 11);
+        assert_eq!(report.num_blocks, 2);
+        assert_eq!(report.total_fees, 300 * uT);
+        assert_eq!(report.baseline.total_weight, 6);
+        assert_eq!(report.alternative.total_weight, 12);
+        assert_eq!(report.baseline.overweight_blocks, 0);
+        assert_eq!(report.alternative.overweight_blocks, 1);
+        assert_eq!(report.alternative.projected_fees, 600 * uT);
+        assert!((report.fee_revenue_change() - 1.0).abs() < f64::EPSILON);
+        let (baseline, alternative) = report.mean_fullness();
+        assert!((baseline - 0.3).abs() < f64::EPSILON);
+        assert!((alternative - 0.6).abs() < f64::EPSILON);
+    }
+}
@@ -21,12 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
-    mempool::{error::MempoolError, Mempool, StateResponse, StatsResponse, TxStorageResponse},
+    blocks::{Block, ShortKernelId},
+    mempool::{error::MempoolError, FeePerGramStatsResponse, Mempool, StateResponse, StatsResponse, TxStorageResponse},
     transactions::transaction::Transaction,
 };
 use std::sync::Arc;
-use tari_common_types::types::Signature;
+use tari_common_types::types::{BlockHash, Signature};
 
 macro_rules! make_async {
     ($fn:ident($($param1:ident:$ptype1:ty,$param2:ident:$ptype2:ty),+) -> $rtype:ty) => {
@@ -65,5 +65,7 @@ make_async!(process_reorg(removed_blocks: Vec<Arc<Block>>, new_blocks: Vec<Arc<B
 make_async!(snapshot() -> Vec<Arc<Transaction>>);
 make_async!(retrieve(total_weight: u64) -> Vec<Arc<Transaction>>);
 make_async!(has_tx_with_excess_sig(excess_sig: Signature) -> TxStorageResponse);
+make_async!(retrieve_by_short_kernel_ids(block_hash: BlockHash, short_ids: Vec<ShortKernelId>) -> Vec<Arc<Transaction>>);
 make_async!(stats() -> StatsResponse);
 make_async!(state() -> StateResponse);
+make_async!(get_fee_per_gram_stats(count: usize, max_block_weight: u64) -> FeePerGramStatsResponse);
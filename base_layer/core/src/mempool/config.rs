@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::mempool::{consts, reorg_pool::ReorgPoolConfig, unconfirmed_pool::UnconfirmedPoolConfig};
+use crate::{
+    mempool::{consts, reorg_pool::ReorgPoolConfig, unconfirmed_pool::UnconfirmedPoolConfig},
+    transactions::tari_amount::MicroTari,
+};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tari_common::{configuration::seconds, NetworkConfigPath};
@@ -58,6 +61,16 @@ pub struct MempoolServiceConfig {
     pub initial_sync_num_peers: usize,
     /// The maximum number of transactions to sync in a single sync session Default: 10_000
     pub initial_sync_max_transactions: usize,
+    /// Transactions with a lower fee-per-gram are not accepted from peers or relayed. The value is advertised to peers
+    /// so that they do not send such transactions. Default: 0 (relay all transactions)
+    pub min_relay_fee_per_gram: MicroTari,
+    /// The number of workers validating transactions received from peers. Default: 4
+    pub ingress_workers: usize,
+    /// The maximum number of transactions from peers waiting to be validated. Further transactions are dropped.
+    /// Default: 1_000
+    pub ingress_queue_size: usize,
+    /// The maximum number of transactions from a single peer waiting to be validated. Default: 100
+    pub ingress_max_queued_per_peer: usize,
 }
 
 impl Default for MempoolServiceConfig {
@@ -66,6 +79,10 @@ impl Default for MempoolServiceConfig {
             request_timeout: consts::MEMPOOL_SERVICE_REQUEST_TIMEOUT,
             initial_sync_num_peers: 2,
             initial_sync_max_transactions: 10_000,
+            min_relay_fee_per_gram: MicroTari(0),
+            ingress_workers: 4,
+            ingress_queue_size: 1_000,
+            ingress_max_queued_per_peer: 100,
         }
     }
 }
@@ -21,10 +21,11 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortKernelId},
     mempool::{
         error::MempoolError,
         mempool_storage::MempoolStorage,
+        FeePerGramStatsResponse,
         MempoolConfig,
         StateResponse,
         StatsResponse,
@@ -34,7 +35,7 @@ use crate::{
     validation::MempoolTransactionValidation,
 };
 use std::sync::{Arc, RwLock};
-use tari_common_types::types::Signature;
+use tari_common_types::types::{BlockHash, Signature};
 
 /// The Mempool consists of an Unconfirmed Transaction Pool, Pending Pool, Orphan Pool and Reorg Pool and is responsible
 /// for managing and maintaining all unconfirmed transactions have not yet been included in a block, and transactions
@@ -52,6 +53,16 @@ impl Mempool {
         }
     }
 
+    /// Apply a new configuration to the running Mempool. Only the UnconfirmedPool settings are applied, the ReorgPool
+    /// keeps the configuration it was created with.
+    pub fn update_config(&self, config: MempoolConfig) -> Result<(), MempoolError> {
+        self.pool_storage
+            .write()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .update_config(config);
+        Ok(())
+    }
+
     /// Insert an unconfirmed transaction into the Mempool. The transaction *MUST* have passed through the validation
     /// pipeline already and will thus always be internally consistent by this stage
     pub fn insert(&self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolError> {
@@ -108,6 +119,19 @@ impl Mempool {
             .has_tx_with_excess_sig(excess_sig)
     }
 
+    /// Returns the transactions, including recently mined transactions, that match the short kernel ids of the
+    /// compact block with the given hash.
+    pub fn retrieve_by_short_kernel_ids(
+        &self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortKernelId>,
+    ) -> Result<Vec<Arc<Transaction>>, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .retrieve_by_short_kernel_ids(block_hash, short_ids)
+    }
+
     /// Gathers and returns the stats of the Mempool.
     pub fn stats(&self) -> Result<StatsResponse, MempoolError> {
         self.pool_storage
@@ -116,6 +140,18 @@ impl Mempool {
             .stats()
     }
 
+    /// Returns the fee per gram stats of the transactions expected to be included in each of the next `count` blocks.
+    pub fn get_fee_per_gram_stats(
+        &self,
+        count: usize,
+        max_block_weight: u64,
+    ) -> Result<FeePerGramStatsResponse, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .get_fee_per_gram_stats(count, max_block_weight)
+    }
+
     /// Gathers and returns a breakdown of all the transaction in the Mempool.
     pub fn state(&self) -> Result<StateResponse, MempoolError> {
         self.pool_storage
@@ -21,11 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{short_kernel_id, Block, ShortKernelId},
     mempool::{
         error::MempoolError,
         reorg_pool::ReorgPool,
         unconfirmed_pool::UnconfirmedPool,
+        FeePerGramStatsResponse,
         MempoolConfig,
         StateResponse,
         StatsResponse,
@@ -35,8 +36,8 @@ use crate::{
     validation::{MempoolTransactionValidation, ValidationError},
 };
 use log::*;
-use std::sync::Arc;
-use tari_common_types::types::Signature;
+use std::{collections::HashSet, sync::Arc};
+use tari_common_types::types::{BlockHash, Signature};
 use tari_crypto::tari_utilities::{hex::Hex, Hashable};
 
 pub const LOG_TARGET: &str = "c::mp::mempool_storage";
@@ -60,6 +61,12 @@ impl MempoolStorage {
         }
     }
 
+    /// Apply a new configuration to the running Mempool. Only the UnconfirmedPool settings are applied, the ReorgPool
+    /// keeps the configuration it was created with.
+    pub fn update_config(&mut self, config: MempoolConfig) {
+        self.unconfirmed_pool.update_config(config.unconfirmed_pool);
+    }
+
     /// Insert an unconfirmed transaction into the Mempool. The transaction *MUST* have passed through the validation
     /// pipeline already and will thus always be internally consistent by this stage
     pub fn insert(&mut self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolError> {
@@ -214,6 +221,29 @@ impl MempoolStorage {
         }
     }
 
+    /// Returns the transactions in the Unconfirmed and Reorg pools that have a kernel matching one of the short ids of
+    /// the compact block with the given hash.
+    pub fn retrieve_by_short_kernel_ids(
+        &self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortKernelId>,
+    ) -> Result<Vec<Arc<Transaction>>, MempoolError> {
+        let short_ids = short_ids.into_iter().collect::<HashSet<_>>();
+        let txs = self
+            .unconfirmed_pool
+            .snapshot()
+            .into_iter()
+            .chain(self.reorg_pool.snapshot()?)
+            .filter(|tx| {
+                tx.body
+                    .kernels()
+                    .iter()
+                    .any(|kernel| short_ids.contains(&short_kernel_id(&block_hash, kernel)))
+            })
+            .collect();
+        Ok(txs)
+    }
+
     // Returns the total number of transactions in the Mempool.
     fn len(&self) -> Result<usize, MempoolError> {
         Ok(self.unconfirmed_pool.len())
@@ -229,6 +259,16 @@ impl MempoolStorage {
         })
     }
 
+    /// Returns the fee per gram stats of the transactions expected to be included in each of the next `count` blocks.
+    pub fn get_fee_per_gram_stats(
+        &self,
+        count: usize,
+        max_block_weight: u64,
+    ) -> Result<FeePerGramStatsResponse, MempoolError> {
+        let stats = self.unconfirmed_pool.get_fee_per_gram_stats(count, max_block_weight)?;
+        Ok(FeePerGramStatsResponse { stats })
+    }
+
     /// Gathers and returns a breakdown of all the transaction in the Mempool.
     pub fn state(&self) -> Result<StateResponse, MempoolError> {
         let unconfirmed_pool = self
@@ -72,7 +72,7 @@ mod sync_protocol;
 #[cfg(feature = "base_node")]
 pub use sync_protocol::MempoolSyncInitializer;
 
-use crate::transactions::transaction::Transaction;
+use crate::transactions::{tari_amount::MicroTari, transaction::Transaction};
 use core::fmt::{Display, Error, Formatter};
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::Signature;
@@ -96,6 +96,38 @@ impl Display for StatsResponse {
     }
 }
 
+/// The fee per gram statistics of the mempool transactions that are expected to be included in a future block, assuming
+/// that the highest priority transactions are mined first
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeePerGramStat {
+    /// The position of the block, starting at 0 for the next block
+    pub order: u64,
+    pub min_fee_per_gram: MicroTari,
+    pub avg_fee_per_gram: MicroTari,
+    pub max_fee_per_gram: MicroTari,
+    /// The total weight of the transactions expected to be included in the block
+    pub total_weight: u64,
+}
+
+impl Display for FeePerGramStat {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            fmt,
+            "Block +{}: Min fee per gram: {}, Avg fee per gram: {}, Max fee per gram: {}, Total Weight: {}",
+            self.order + 1,
+            self.min_fee_per_gram,
+            self.avg_fee_per_gram,
+            self.max_fee_per_gram,
+            self.total_weight
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeePerGramStatsResponse {
+    pub stats: Vec<FeePerGramStat>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct StateResponse {
     pub unconfirmed_pool: Vec<Transaction>,
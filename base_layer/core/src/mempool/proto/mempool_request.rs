@@ -22,6 +22,7 @@
 
 use super::mempool::{
     mempool_service_request::Request as ProtoMempoolRequest,
+    GetFeePerGramStats as ProtoGetFeePerGramStats,
     MempoolServiceRequest as ProtoMempoolServiceRequest,
 };
 use crate::mempool::service::{MempoolRequest, MempoolServiceRequest};
@@ -41,6 +42,10 @@ impl TryInto<MempoolRequest> for ProtoMempoolRequest {
                 excess_sig.try_into().map_err(|err: ByteArrayError| err.to_string())?,
             ),
             SubmitTransaction(tx) => MempoolRequest::SubmitTransaction(tx.try_into()?),
+            GetFeePerGramStats(request) => MempoolRequest::GetFeePerGramStats {
+                count: request.count,
+                max_block_weight: request.max_block_weight,
+            },
         };
         Ok(request)
     }
@@ -54,6 +59,13 @@ impl From<MempoolRequest> for ProtoMempoolRequest {
             GetState => ProtoMempoolRequest::GetState(true),
             GetTxStateByExcessSig(excess_sig) => ProtoMempoolRequest::GetTxStateByExcessSig(excess_sig.into()),
             SubmitTransaction(tx) => ProtoMempoolRequest::SubmitTransaction(tx.into()),
+            GetFeePerGramStats {
+                count,
+                max_block_weight,
+            } => ProtoMempoolRequest::GetFeePerGramStats(ProtoGetFeePerGramStats {
+                count,
+                max_block_weight,
+            }),
         }
     }
 }
@@ -20,7 +20,16 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::mempool::{proto::mempool::StatsResponse as ProtoStatsResponse, StatsResponse};
+use crate::mempool::{
+    proto::mempool::{
+        FeePerGramStat as ProtoFeePerGramStat,
+        FeePerGramStatsResponse as ProtoFeePerGramStatsResponse,
+        StatsResponse as ProtoStatsResponse,
+    },
+    FeePerGramStat,
+    FeePerGramStatsResponse,
+    StatsResponse,
+};
 use std::convert::TryFrom;
 
 impl TryFrom<ProtoStatsResponse> for StatsResponse {
@@ -46,3 +55,45 @@ impl From<StatsResponse> for ProtoStatsResponse {
         }
     }
 }
+
+impl TryFrom<ProtoFeePerGramStatsResponse> for FeePerGramStatsResponse {
+    type Error = String;
+
+    fn try_from(response: ProtoFeePerGramStatsResponse) -> Result<Self, Self::Error> {
+        Ok(Self {
+            stats: response.stats.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl From<FeePerGramStatsResponse> for ProtoFeePerGramStatsResponse {
+    fn from(response: FeePerGramStatsResponse) -> Self {
+        Self {
+            stats: response.stats.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ProtoFeePerGramStat> for FeePerGramStat {
+    fn from(stat: ProtoFeePerGramStat) -> Self {
+        Self {
+            order: stat.order,
+            min_fee_per_gram: stat.min_fee_per_gram.into(),
+            avg_fee_per_gram: stat.avg_fee_per_gram.into(),
+            max_fee_per_gram: stat.max_fee_per_gram.into(),
+            total_weight: stat.total_weight,
+        }
+    }
+}
+
+impl From<FeePerGramStat> for ProtoFeePerGramStat {
+    fn from(stat: FeePerGramStat) -> Self {
+        Self {
+            order: stat.order,
+            min_fee_per_gram: stat.min_fee_per_gram.into(),
+            avg_fee_per_gram: stat.avg_fee_per_gram.into(),
+            max_fee_per_gram: stat.max_fee_per_gram.into(),
+            total_weight: stat.total_weight,
+        }
+    }
+}
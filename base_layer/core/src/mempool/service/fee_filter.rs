@@ -0,0 +1,130 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::transactions::{tari_amount::MicroTari, transaction::Transaction};
+use std::{collections::HashMap, convert::TryInto};
+use tari_comms::peer_manager::NodeId;
+use tari_p2p::services::liveness::{Metadata, MetadataKey};
+
+/// Encodes a fee filter for the liveness metadata
+pub fn encode_fee_filter(min_fee_per_gram: MicroTari) -> Vec<u8> {
+    min_fee_per_gram.as_u64().to_le_bytes().to_vec()
+}
+
+/// Decodes a fee filter from the liveness metadata, returning None if the value is malformed
+pub fn decode_fee_filter(bytes: &[u8]) -> Option<MicroTari> {
+    let bytes = bytes.try_into().ok()?;
+    Some(MicroTari::from(u64::from_le_bytes(bytes)))
+}
+
+/// The minimum relay fee-per-gram advertised by peers in their liveness pings and pongs. Transactions are not
+/// propagated to peers that will not relay them.
+#[derive(Debug, Default)]
+pub struct PeerFeeFilters {
+    filters: HashMap<NodeId, MicroTari>,
+}
+
+impl PeerFeeFilters {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records the fee filter in the liveness metadata of a peer. Peers that do not advertise a fee filter relay all
+    /// transactions.
+    pub fn update(&mut self, node_id: &NodeId, metadata: &Metadata) {
+        match metadata
+            .get(MetadataKey::MinRelayFeePerGram)
+            .and_then(|bytes| decode_fee_filter(bytes))
+        {
+            Some(min_fee_per_gram) if min_fee_per_gram > MicroTari::from(0) => {
+                self.filters.insert(node_id.clone(), min_fee_per_gram);
+            },
+            _ => {
+                self.filters.remove(node_id);
+            },
+        }
+    }
+
+    pub fn get(&self, node_id: &NodeId) -> Option<MicroTari> {
+        self.filters.get(node_id).copied()
+    }
+
+    /// Returns the peers whose fee filter rejects the given transaction
+    pub fn peers_rejecting(&self, tx: &Transaction) -> Vec<NodeId> {
+        let fee_per_gram = tx.calculate_ave_fee_per_gram();
+        self.filters
+            .iter()
+            .filter(|(_, min_fee_per_gram)| fee_per_gram < min_fee_per_gram.as_u64() as f64)
+            .map(|(node_id, _)| node_id.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tx;
+    use tari_common_types::types::PublicKey;
+
+    fn metadata_with_filter(min_fee_per_gram: Option<MicroTari>) -> Metadata {
+        let mut metadata = Metadata::new();
+        if let Some(min_fee_per_gram) = min_fee_per_gram {
+            metadata.insert(MetadataKey::MinRelayFeePerGram, encode_fee_filter(min_fee_per_gram));
+        }
+        metadata
+    }
+
+    #[test]
+    fn encode_decode() {
+        let fee = MicroTari(25);
+        assert_eq!(decode_fee_filter(&encode_fee_filter(fee)), Some(fee));
+        assert_eq!(decode_fee_filter(&[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn update_and_filter_peers() {
+        let tx = tx!(MicroTari(10_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+        let fee_per_gram = tx.calculate_ave_fee_per_gram() as u64;
+        let low = NodeId::default();
+        let high = NodeId::from_key(&PublicKey::default());
+
+        let mut filters = PeerFeeFilters::new();
+        filters.update(&low, &metadata_with_filter(Some(MicroTari(1))));
+        filters.update(&high, &metadata_with_filter(Some(MicroTari(fee_per_gram + 1))));
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters.peers_rejecting(&tx), vec![high.clone()]);
+
+        // A peer that stops advertising a fee filter relays all transactions
+        filters.update(&high, &metadata_with_filter(None));
+        assert_eq!(filters.get(&high), None);
+        assert!(filters.peers_rejecting(&tx).is_empty());
+    }
+}
@@ -23,6 +23,7 @@
 use crate::{
     mempool::{
         service::{MempoolRequest, MempoolResponse},
+        FeePerGramStatsResponse,
         MempoolServiceError,
         StateResponse,
         StatsResponse,
@@ -80,4 +81,22 @@ impl MempoolHandle {
             _ => panic!("Incorrect response"),
         }
     }
+
+    pub async fn get_fee_per_gram_stats(
+        &mut self,
+        count: u64,
+        max_block_weight: u64,
+    ) -> Result<FeePerGramStatsResponse, MempoolServiceError> {
+        match self
+            .inner
+            .call(MempoolRequest::GetFeePerGramStats {
+                count,
+                max_block_weight,
+            })
+            .await??
+        {
+            MempoolResponse::FeePerGramStats(resp) => Ok(resp),
+            _ => panic!("Incorrect response"),
+        }
+    }
 }
@@ -85,6 +85,12 @@ impl MempoolInboundHandlers {
                 );
                 Ok(MempoolResponse::TxStorage(self.submit_transaction(tx, vec![]).await?))
             },
+            GetFeePerGramStats {
+                count,
+                max_block_weight,
+            } => Ok(MempoolResponse::FeePerGramStats(
+                async_mempool::get_fee_per_gram_stats(self.mempool.clone(), count as usize, max_block_weight).await?,
+            )),
         }
     }
 
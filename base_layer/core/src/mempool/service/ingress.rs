@@ -0,0 +1,298 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::transactions::transaction::Transaction;
+use log::*;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tari_comms::peer_manager::NodeId;
+use tari_p2p::domain_message::DomainMessage;
+use tokio::sync::Notify;
+
+const LOG_TARGET: &str = "c::mempool::service::ingress";
+
+/// Weight given to the latest sample in the moving average of the validation time
+const LATENCY_SMOOTHING: f64 = 0.1;
+
+/// A bounded queue for transactions received from peers, waiting to be validated by the ingress workers.
+///
+/// Each peer has its own queue and the workers take transactions from the peers in turn, so a peer flooding the node
+/// with transactions does not delay the transactions of other peers. Transactions are dropped once the queue, or the
+/// share of the queue of a single peer, is full.
+pub struct TransactionIngressQueue<T = DomainMessage<Transaction>> {
+    state: Arc<Mutex<QueueState<T>>>,
+    notify: Arc<Notify>,
+    capacity: usize,
+    max_per_peer: usize,
+}
+
+// Derived Clone would require T: Clone
+impl<T> Clone for TransactionIngressQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            notify: self.notify.clone(),
+            capacity: self.capacity,
+            max_per_peer: self.max_per_peer,
+        }
+    }
+}
+
+struct QueueState<T> {
+    queues: HashMap<NodeId, VecDeque<T>>,
+    /// The peers with queued transactions, in the order they are served
+    turns: VecDeque<NodeId>,
+    len: usize,
+    is_closed: bool,
+    num_validated: u64,
+    num_dropped: u64,
+    avg_validation_time: Option<Duration>,
+    max_validation_time: Duration,
+}
+
+impl<T> TransactionIngressQueue<T> {
+    pub fn new(capacity: usize, max_per_peer: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(QueueState {
+                queues: HashMap::new(),
+                turns: VecDeque::new(),
+                len: 0,
+                is_closed: false,
+                num_validated: 0,
+                num_dropped: 0,
+                avg_validation_time: None,
+                max_validation_time: Duration::from_secs(0),
+            })),
+            notify: Arc::new(Notify::new()),
+            capacity: capacity.max(1),
+            max_per_peer: max_per_peer.max(1),
+        }
+    }
+
+    /// Queues a transaction from the given peer. Returns false if the transaction was dropped because the queue is full
+    /// or the peer already has its share of the queue.
+    pub fn push(&self, peer: &NodeId, item: T) -> bool {
+        {
+            let mut state = self.lock_state();
+            if state.is_closed {
+                return false;
+            }
+            let peer_len = state.queues.get(peer).map(|q| q.len()).unwrap_or(0);
+            if state.len >= self.capacity || peer_len >= self.max_per_peer {
+                state.num_dropped += 1;
+                debug!(
+                    target: LOG_TARGET,
+                    "Dropped transaction from peer `{}` ({} queued from this peer, {}/{} queued in total)",
+                    peer.short_str(),
+                    peer_len,
+                    state.len,
+                    self.capacity
+                );
+                return false;
+            }
+            if peer_len == 0 {
+                state.turns.push_back(peer.clone());
+            }
+            state.queues.entry(peer.clone()).or_default().push_back(item);
+            state.len += 1;
+        }
+        self.notify.notify_one();
+        true
+    }
+
+    /// Takes the next transaction, from the peer whose turn it is, without waiting
+    pub fn try_pop(&self) -> Option<T> {
+        let mut state = self.lock_state();
+        let peer = state.turns.pop_front()?;
+        let queue = state.queues.get_mut(&peer)?;
+        let item = queue.pop_front();
+        if queue.is_empty() {
+            state.queues.remove(&peer);
+        } else {
+            state.turns.push_back(peer);
+        }
+        if item.is_some() {
+            state.len -= 1;
+        }
+        item
+    }
+
+    /// Waits for the next transaction. Returns None once the queue is closed.
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            if self.lock_state().is_closed {
+                // Pass the wake-up on to a worker that was not yet waiting when the queue was closed
+                self.notify.notify_one();
+                return None;
+            }
+            if let Some(item) = self.try_pop() {
+                return Some(item);
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Closes the queue, dropping the queued transactions and stopping the workers
+    pub fn close(&self) {
+        {
+            let mut state = self.lock_state();
+            state.is_closed = true;
+            state.queues.clear();
+            state.turns.clear();
+            state.len = 0;
+        }
+        self.notify.notify_waiters();
+    }
+
+    /// Records the time it took to validate a transaction taken from the queue
+    pub fn record_validation(&self, elapsed: Duration) {
+        let mut state = self.lock_state();
+        state.num_validated += 1;
+        state.max_validation_time = state.max_validation_time.max(elapsed);
+        state.avg_validation_time = Some(match state.avg_validation_time {
+            Some(avg) => avg.mul_f64(1.0 - LATENCY_SMOOTHING) + elapsed.mul_f64(LATENCY_SMOOTHING),
+            None => elapsed,
+        });
+    }
+
+    pub fn stats(&self) -> IngressStats {
+        let state = self.lock_state();
+        IngressStats {
+            queue_depth: state.len,
+            capacity: self.capacity,
+            num_queued_peers: state.turns.len(),
+            num_validated: state.num_validated,
+            num_dropped: state.num_dropped,
+            avg_validation_time: state.avg_validation_time.unwrap_or_default(),
+            max_validation_time: state.max_validation_time,
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, QueueState<T>> {
+        self.state.lock().expect("ingress queue lock poisoned")
+    }
+}
+
+/// A snapshot of the mempool ingress queue and validation times
+#[derive(Debug, Clone, Default)]
+pub struct IngressStats {
+    pub queue_depth: usize,
+    pub capacity: usize,
+    pub num_queued_peers: usize,
+    pub num_validated: u64,
+    pub num_dropped: u64,
+    /// Moving average of the time taken to validate a transaction
+    pub avg_validation_time: Duration,
+    pub max_validation_time: Duration,
+}
+
+impl fmt::Display for IngressStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Mempool ingress: Queued: {}/{} from {} peer(s), Validated: {}, Dropped: {}, Validation time: avg {:.2?}, \
+             max {:.2?}",
+            self.queue_depth,
+            self.capacity,
+            self.num_queued_peers,
+            self.num_validated,
+            self.num_dropped,
+            self.avg_validation_time,
+            self.max_validation_time
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_common_types::types::PublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    fn random_node_id() -> NodeId {
+        let (_, pk) = PublicKey::random_keypair(&mut rand::rngs::OsRng);
+        NodeId::from_key(&pk)
+    }
+
+    #[test]
+    fn serves_peers_in_turn() {
+        let queue = TransactionIngressQueue::new(10, 10);
+        let flooder = random_node_id();
+        let other = random_node_id();
+        for i in 0..3 {
+            assert!(queue.push(&flooder, i));
+        }
+        assert!(queue.push(&other, 100));
+
+        assert_eq!(queue.try_pop(), Some(0));
+        assert_eq!(queue.try_pop(), Some(100));
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.try_pop(), Some(2));
+        assert_eq!(queue.try_pop(), None);
+        assert_eq!(queue.stats().queue_depth, 0);
+    }
+
+    #[test]
+    fn drops_when_full() {
+        let queue = TransactionIngressQueue::new(3, 2);
+        let flooder = random_node_id();
+        let other = random_node_id();
+        assert!(queue.push(&flooder, 1));
+        assert!(queue.push(&flooder, 2));
+        // The peer has used up its share
+        assert!(!queue.push(&flooder, 3));
+        assert!(queue.push(&other, 4));
+        // The queue is full
+        assert!(!queue.push(&other, 5));
+
+        let stats = queue.stats();
+        assert_eq!(stats.queue_depth, 3);
+        assert_eq!(stats.num_queued_peers, 2);
+        assert_eq!(stats.num_dropped, 2);
+    }
+
+    #[test]
+    fn records_validation_time() {
+        let queue = TransactionIngressQueue::<()>::new(1, 1);
+        queue.record_validation(Duration::from_millis(100));
+        queue.record_validation(Duration::from_millis(200));
+        let stats = queue.stats();
+        assert_eq!(stats.num_validated, 2);
+        assert_eq!(stats.max_validation_time, Duration::from_millis(200));
+        assert!((stats.avg_validation_time.as_secs_f64() - 0.11).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn pop_returns_none_once_closed() {
+        let queue = TransactionIngressQueue::<u32>::new(1, 1);
+        let worker = queue.clone();
+        let handle = tokio::spawn(async move { worker.pop().await });
+        queue.close();
+        assert_eq!(handle.await.unwrap(), None);
+        assert!(!queue.push(&random_node_id(), 1));
+    }
+}
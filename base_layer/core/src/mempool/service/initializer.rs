@@ -27,11 +27,13 @@ use crate::{
         proto as mempool_proto,
         service::{
             inbound_handlers::MempoolInboundHandlers,
+            ingress::TransactionIngressQueue,
             local_service::LocalMempoolService,
             outbound_interface::OutboundMempoolServiceInterface,
             service::{MempoolService, MempoolStreams},
             MempoolHandle,
         },
+        MempoolConfig,
         MempoolServiceConfig,
     },
     proto,
@@ -44,7 +46,10 @@ use tari_comms_dht::Dht;
 use tari_p2p::{
     comms_connector::{PeerMessage, SubscriptionFactory},
     domain_message::DomainMessage,
-    services::utils::{map_decode, ok_or_skip_result},
+    services::{
+        liveness::LivenessHandle,
+        utils::{map_decode, ok_or_skip_result},
+    },
     tari_message::TariMessageType,
 };
 use tari_service_framework::{
@@ -54,7 +59,7 @@ use tari_service_framework::{
     ServiceInitializer,
     ServiceInitializerContext,
 };
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, watch};
 
 const LOG_TARGET: &str = "c::bn::mempool_service::initializer";
 const SUBSCRIPTION_LABEL: &str = "Mempool";
@@ -64,6 +69,7 @@ pub struct MempoolServiceInitializer {
     inbound_message_subscription_factory: Arc<SubscriptionFactory>,
     mempool: Mempool,
     config: MempoolServiceConfig,
+    config_updates: Option<watch::Receiver<MempoolConfig>>,
 }
 
 impl MempoolServiceInitializer {
@@ -77,9 +83,16 @@ impl MempoolServiceInitializer {
             inbound_message_subscription_factory,
             mempool,
             config,
+            config_updates: None,
         }
     }
 
+    /// Apply the configurations sent on `config_updates` to the running Mempool
+    pub fn with_config_updates(mut self, config_updates: watch::Receiver<MempoolConfig>) -> Self {
+        self.config_updates = Some(config_updates);
+        self
+    }
+
     /// Get a stream for inbound Mempool service request messages
     fn inbound_request_stream(&self) -> impl Stream<Item = DomainMessage<mempool_proto::MempoolServiceRequest>> {
         self.inbound_message_subscription_factory
@@ -154,9 +167,13 @@ impl ServiceInitializer for MempoolServiceInitializer {
         let (mempool_state_event_publisher, _) = broadcast::channel(100);
         let outbound_mp_interface =
             OutboundMempoolServiceInterface::new(outbound_request_sender_service, outbound_tx_sender);
-        let local_mp_interface =
-            LocalMempoolService::new(local_request_sender_service, mempool_state_event_publisher.clone());
         let config = self.config;
+        let ingress_queue = TransactionIngressQueue::new(config.ingress_queue_size, config.ingress_max_queued_per_peer);
+        let local_mp_interface = LocalMempoolService::new(
+            local_request_sender_service,
+            mempool_state_event_publisher.clone(),
+            ingress_queue.clone(),
+        );
         let inbound_handlers = MempoolInboundHandlers::new(
             mempool_state_event_publisher,
             self.mempool.clone(),
@@ -167,10 +184,23 @@ impl ServiceInitializer for MempoolServiceInitializer {
         context.register_handle(outbound_mp_interface);
         context.register_handle(local_mp_interface);
 
+        if let Some(mut config_updates) = self.config_updates.take() {
+            let mempool = self.mempool.clone();
+            context.clone().spawn_until_shutdown(move |_| async move {
+                while config_updates.changed().await.is_ok() {
+                    let config = *config_updates.borrow();
+                    if let Err(err) = mempool.update_config(config) {
+                        warn!(target: LOG_TARGET, "Failed to apply the mempool configuration: {}", err);
+                    }
+                }
+            });
+        }
+
         context.spawn_until_shutdown(move |handles| {
             let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
             let state_machine = handles.expect_handle::<StateMachineHandle>();
             let base_node = handles.expect_handle::<LocalNodeCommsInterface>();
+            let liveness = handles.expect_handle::<LivenessHandle>();
 
             let streams = MempoolStreams {
                 outbound_request_stream,
@@ -180,9 +210,18 @@ impl ServiceInitializer for MempoolServiceInitializer {
                 inbound_transaction_stream,
                 local_request_stream,
                 block_event_stream: base_node.get_block_event_stream(),
+                liveness_event_stream: liveness.get_event_stream(),
                 request_receiver,
             };
-            MempoolService::new(outbound_message_service, inbound_handlers, config, state_machine).start(streams)
+            MempoolService::new(
+                outbound_message_service,
+                inbound_handlers,
+                config,
+                state_machine,
+                liveness,
+                ingress_queue,
+            )
+            .start(streams)
         });
 
         Ok(())
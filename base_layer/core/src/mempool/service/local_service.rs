@@ -22,7 +22,8 @@
 
 use crate::{
     mempool::{
-        service::{MempoolRequest, MempoolResponse, MempoolServiceError},
+        service::{IngressStats, MempoolRequest, MempoolResponse, MempoolServiceError, TransactionIngressQueue},
+        FeePerGramStatsResponse,
         MempoolStateEvent,
         StateResponse,
         StatsResponse,
@@ -47,6 +48,7 @@ pub type LocalMempoolRequester = SenderService<MempoolRequest, Result<MempoolRes
 pub struct LocalMempoolService {
     request_sender: LocalMempoolRequester,
     mempool_state_event_stream: broadcast::Sender<MempoolStateEvent>,
+    ingress_queue: TransactionIngressQueue,
 }
 
 impl LocalMempoolService {
@@ -59,13 +61,20 @@ impl LocalMempoolService {
     pub fn new(
         request_sender: LocalMempoolRequester,
         mempool_state_event_stream: broadcast::Sender<MempoolStateEvent>,
+        ingress_queue: TransactionIngressQueue,
     ) -> Self {
         LocalMempoolService {
             request_sender,
             mempool_state_event_stream,
+            ingress_queue,
         }
     }
 
+    /// Returns the depth of the queue of transactions received from peers and the time taken to validate them
+    pub fn get_ingress_stats(&self) -> IngressStats {
+        self.ingress_queue.stats()
+    }
+
     pub fn get_mempool_state_event_stream(&self) -> broadcast::Receiver<MempoolStateEvent> {
         self.mempool_state_event_stream.subscribe()
     }
@@ -112,12 +121,31 @@ impl LocalMempoolService {
             _ => Err(MempoolServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Returns the fee per gram stats of the transactions expected to be included in each of the next `count` blocks
+    pub async fn get_fee_per_gram_stats(
+        &mut self,
+        count: u64,
+        max_block_weight: u64,
+    ) -> Result<FeePerGramStatsResponse, MempoolServiceError> {
+        match self
+            .request_sender
+            .call(MempoolRequest::GetFeePerGramStats {
+                count,
+                max_block_weight,
+            })
+            .await??
+        {
+            MempoolResponse::FeePerGramStats(s) => Ok(s),
+            _ => Err(MempoolServiceError::UnexpectedApiResponse),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::mempool::{
-        service::{local_service::LocalMempoolService, MempoolRequest, MempoolResponse},
+        service::{local_service::LocalMempoolService, MempoolRequest, MempoolResponse, TransactionIngressQueue},
         MempoolServiceError,
         StatsResponse,
     };
@@ -151,7 +179,7 @@ mod test {
     async fn mempool_stats() {
         let (event_publisher, _) = broadcast::channel(100);
         let (tx, rx) = unbounded();
-        let mut service = LocalMempoolService::new(tx, event_publisher);
+        let mut service = LocalMempoolService::new(tx, event_publisher, TransactionIngressQueue::new(1, 1));
         task::spawn(mock_handler(rx));
         let stats = service.get_mempool_stats().await;
         let stats = stats.expect("get_mempool_stats should have succeeded");
@@ -162,7 +190,7 @@ mod test {
     async fn mempool_stats_from_multiple() {
         let (event_publisher, _) = broadcast::channel(100);
         let (tx, rx) = unbounded();
-        let mut service = LocalMempoolService::new(tx, event_publisher);
+        let mut service = LocalMempoolService::new(tx, event_publisher, TransactionIngressQueue::new(1, 1));
         let mut service2 = service.clone();
         task::spawn(mock_handler(rx));
         let stats = service.get_mempool_stats().await;
@@ -25,9 +25,19 @@ mod error;
 #[cfg(feature = "base_node")]
 pub use error::MempoolServiceError;
 
+#[cfg(feature = "base_node")]
+mod fee_filter;
+#[cfg(feature = "base_node")]
+pub use fee_filter::{decode_fee_filter, encode_fee_filter, PeerFeeFilters};
+
 #[cfg(feature = "base_node")]
 mod inbound_handlers;
 
+#[cfg(feature = "base_node")]
+mod ingress;
+#[cfg(feature = "base_node")]
+pub use ingress::{IngressStats, TransactionIngressQueue};
+
 #[cfg(feature = "base_node")]
 mod initializer;
 #[cfg(feature = "base_node")]
@@ -34,6 +34,7 @@ pub enum MempoolRequest {
     GetState,
     GetTxStateByExcessSig(Signature),
     SubmitTransaction(Transaction),
+    GetFeePerGramStats { count: u64, max_block_weight: u64 },
 }
 
 impl Display for MempoolRequest {
@@ -48,6 +49,13 @@ impl Display for MempoolRequest {
                 "SubmitTransaction ({})",
                 tx.body.kernels()[0].excess_sig.get_signature().to_hex()
             )),
+            MempoolRequest::GetFeePerGramStats {
+                count,
+                max_block_weight,
+            } => f.write_str(&format!(
+                "GetFeePerGramStats (count: {}, max_block_weight: {})",
+                count, max_block_weight
+            )),
         }
     }
 }
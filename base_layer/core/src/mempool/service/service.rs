@@ -29,7 +29,9 @@ use crate::{
         proto as mempool_proto,
         service::{
             error::MempoolServiceError,
+            fee_filter::{encode_fee_filter, PeerFeeFilters},
             inbound_handlers::MempoolInboundHandlers,
+            ingress::TransactionIngressQueue,
             MempoolRequest,
             MempoolResponse,
         },
@@ -41,7 +43,11 @@ use crate::{
 use futures::{pin_mut, stream::StreamExt, Stream};
 use log::*;
 use rand::rngs::OsRng;
-use std::{convert::TryInto, sync::Arc, time::Duration};
+use std::{
+    convert::TryInto,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tari_common_types::waiting_requests::{generate_request_key, RequestKey, WaitingRequests};
 use tari_comms::peer_manager::NodeId;
 use tari_comms_dht::{
@@ -50,7 +56,11 @@ use tari_comms_dht::{
     outbound::{DhtOutboundError, OutboundEncryption, OutboundMessageRequester},
 };
 use tari_crypto::tari_utilities::hex::Hex;
-use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
+use tari_p2p::{
+    domain_message::DomainMessage,
+    services::liveness::{LivenessEvent, LivenessEventReceiver, LivenessHandle, MetadataKey},
+    tari_message::TariMessageType,
+};
 use tari_service_framework::{reply_channel, reply_channel::RequestContext};
 use tokio::{
     sync::{mpsc, oneshot::Sender as OneshotSender},
@@ -68,6 +78,7 @@ pub struct MempoolStreams<SOutReq, SInReq, SInRes, STxIn, SLocalReq> {
     pub inbound_transaction_stream: STxIn,
     pub local_request_stream: SLocalReq,
     pub block_event_stream: BlockEventReceiver,
+    pub liveness_event_stream: LivenessEventReceiver,
     pub request_receiver: reply_channel::TryReceiver<MempoolRequest, MempoolResponse, MempoolServiceError>,
 }
 
@@ -81,6 +92,9 @@ pub struct MempoolService {
     timeout_receiver_stream: Option<mpsc::Receiver<RequestKey>>,
     config: MempoolServiceConfig,
     state_machine: StateMachineHandle,
+    liveness: LivenessHandle,
+    peer_fee_filters: PeerFeeFilters,
+    ingress_queue: TransactionIngressQueue,
 }
 
 impl MempoolService {
@@ -89,6 +103,8 @@ impl MempoolService {
         inbound_handlers: MempoolInboundHandlers,
         config: MempoolServiceConfig,
         state_machine: StateMachineHandle,
+        liveness: LivenessHandle,
+        ingress_queue: TransactionIngressQueue,
     ) -> Self {
         let (timeout_sender, timeout_receiver) = mpsc::channel(100);
         Self {
@@ -99,6 +115,9 @@ impl MempoolService {
             timeout_receiver_stream: Some(timeout_receiver),
             config,
             state_machine,
+            liveness,
+            peer_fee_filters: PeerFeeFilters::new(),
+            ingress_queue,
         }
     }
 
@@ -130,6 +149,21 @@ impl MempoolService {
             .take()
             .expect("Mempool Service initialized without timeout_receiver_stream");
         let mut request_receiver = streams.request_receiver;
+        let mut liveness_event_stream = streams.liveness_event_stream;
+
+        // Advertise the fee filter to peers in liveness pings and pongs
+        if let Err(err) = self
+            .liveness
+            .set_metadata_entry(
+                MetadataKey::MinRelayFeePerGram,
+                encode_fee_filter(self.config.min_relay_fee_per_gram),
+            )
+            .await
+        {
+            warn!(target: LOG_TARGET, "Failed to advertise the relay fee filter: {}", err);
+        }
+
+        self.spawn_ingress_workers();
 
         loop {
             tokio::select! {
@@ -176,6 +210,11 @@ impl MempoolService {
                     }
                 },
 
+                // Fee filters advertised by peers
+                Ok(event) = liveness_event_stream.recv() => {
+                    self.handle_liveness_event(&*event);
+                },
+
                 // Timeout events for waiting requests
                 Some(timeout_request_key) = timeout_receiver_stream.recv() => {
                     self.spawn_handle_request_timeout(timeout_request_key);
@@ -188,6 +227,7 @@ impl MempoolService {
             }
         }
 
+        self.ingress_queue.close();
         Ok(())
     }
 
@@ -222,7 +262,18 @@ impl MempoolService {
         });
     }
 
-    fn spawn_handle_outbound_tx(&self, tx: Transaction, excluded_peers: Vec<NodeId>) {
+    fn handle_liveness_event(&mut self, event: &LivenessEvent) {
+        match event {
+            LivenessEvent::ReceivedPing(event) | LivenessEvent::ReceivedPong(event) => {
+                self.peer_fee_filters.update(&event.node_id, &event.metadata);
+            },
+            LivenessEvent::PingRoundBroadcast(_) => {},
+        }
+    }
+
+    fn spawn_handle_outbound_tx(&self, tx: Transaction, mut excluded_peers: Vec<NodeId>) {
+        // Peers do not relay transactions below their fee filter
+        excluded_peers.extend(self.peer_fee_filters.peers_rejecting(&tx));
         let outbound_message_service = self.outbound_message_service.clone();
         task::spawn(async move {
             let result = handle_outbound_tx(outbound_message_service, tx, excluded_peers).await;
@@ -271,16 +322,41 @@ impl MempoolService {
             );
             return;
         }
-        let inbound_handlers = self.inbound_handlers.clone();
-        task::spawn(async move {
-            let result = handle_incoming_tx(inbound_handlers, tx_msg).await;
-            if let Err(e) = result {
-                error!(
-                    target: LOG_TARGET,
-                    "Failed to handle incoming transaction message: {:?}", e
-                );
-            }
-        });
+        let fee_per_gram = tx_msg.inner.calculate_ave_fee_per_gram();
+        if fee_per_gram < self.config.min_relay_fee_per_gram.as_u64() as f64 {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction from peer `{}` with fee-per-gram {:.2} is below the relay fee filter of {}",
+                tx_msg.source_peer.node_id.short_str(),
+                fee_per_gram,
+                self.config.min_relay_fee_per_gram
+            );
+            return;
+        }
+        let source_peer = tx_msg.source_peer.node_id.clone();
+        self.ingress_queue.push(&source_peer, tx_msg);
+    }
+
+    /// Validates the transactions received from peers on a fixed number of workers, so that a burst of transactions
+    /// cannot starve the handling of blocks and requests
+    fn spawn_ingress_workers(&self) {
+        for _ in 0..self.config.ingress_workers.max(1) {
+            let inbound_handlers = self.inbound_handlers.clone();
+            let ingress_queue = self.ingress_queue.clone();
+            task::spawn(async move {
+                while let Some(tx_msg) = ingress_queue.pop().await {
+                    let timer = Instant::now();
+                    let result = handle_incoming_tx(inbound_handlers.clone(), tx_msg).await;
+                    ingress_queue.record_validation(timer.elapsed());
+                    if let Err(e) = result {
+                        error!(
+                            target: LOG_TARGET,
+                            "Failed to handle incoming transaction message: {:?}", e
+                        );
+                    }
+                }
+            });
+        }
     }
 
     fn spawn_handle_local_request(
@@ -22,6 +22,7 @@
 
 use crate::mempool::{
     service::{MempoolHandle, MempoolRequest, MempoolResponse},
+    FeePerGramStatsResponse,
     MempoolServiceError,
     StateResponse,
     StatsResponse,
@@ -49,6 +50,7 @@ pub struct MempoolMockState {
     get_state: Arc<Mutex<StateResponse>>,
     get_tx_state_by_excess_sig: Arc<Mutex<TxStorageResponse>>,
     submit_transaction: Arc<Mutex<TxStorageResponse>>,
+    get_fee_per_gram_stats: Arc<Mutex<FeePerGramStatsResponse>>,
     calls: Arc<AtomicUsize>,
 }
 
@@ -67,6 +69,7 @@ impl Default for MempoolMockState {
             })),
             get_tx_state_by_excess_sig: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
             submit_transaction: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
+            get_fee_per_gram_stats: Arc::new(Mutex::new(FeePerGramStatsResponse { stats: vec![] })),
             calls: Arc::new(Default::default()),
         }
     }
@@ -89,6 +92,10 @@ impl MempoolMockState {
         *self.submit_transaction.lock().await = resp;
     }
 
+    pub async fn set_get_fee_per_gram_stats_response(&self, resp: FeePerGramStatsResponse) {
+        *self.get_fee_per_gram_stats.lock().await = resp;
+    }
+
     fn inc_call_count(&self) {
         self.calls.fetch_add(1, Ordering::SeqCst);
     }
@@ -135,6 +142,9 @@ impl MempoolServiceMock {
             SubmitTransaction(_) => Ok(MempoolResponse::TxStorage(
                 self.state.submit_transaction.lock().await.clone(),
             )),
+            GetFeePerGramStats { .. } => Ok(MempoolResponse::FeePerGramStats(
+                self.state.get_fee_per_gram_stats.lock().await.clone(),
+            )),
         }
     }
 }
@@ -35,8 +35,9 @@ use crate::{
         consts::{MEMPOOL_UNCONFIRMED_POOL_STORAGE_CAPACITY, MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT},
         priority::{FeePriority, PrioritizedTransaction},
         unconfirmed_pool::UnconfirmedPoolError,
+        FeePerGramStat,
     },
-    transactions::transaction::Transaction,
+    transactions::{tari_amount::MicroTari, transaction::Transaction},
 };
 use tari_common_types::types::{HashOutput, Signature};
 
@@ -93,6 +94,15 @@ impl UnconfirmedPool {
         }
     }
 
+    /// Replace the configuration of the pool. The lowest priority transactions are removed when the pool holds more
+    /// transactions than the new storage capacity allows.
+    pub fn update_config(&mut self, config: UnconfirmedPoolConfig) {
+        self.config = config;
+        while self.txs_by_signature.len() > self.config.storage_capacity && !self.txs_by_priority.is_empty() {
+            self.remove_lowest_priority_tx();
+        }
+    }
+
     fn lowest_priority(&self) -> &FeePriority {
         self.txs_by_priority.iter().next().unwrap().0
     }
@@ -460,6 +470,57 @@ impl UnconfirmedPool {
             .fold(0, |weight, (_, ptx)| weight + ptx.transaction.calculate_weight())
     }
 
+    /// Returns the fee per gram stats of the transactions expected to be included in each of the next `count` blocks.
+    /// The transactions are assigned to blocks in order of priority, filling each block up to `max_block_weight`.
+    pub fn get_fee_per_gram_stats(
+        &self,
+        count: usize,
+        max_block_weight: u64,
+    ) -> Result<Vec<FeePerGramStat>, UnconfirmedPoolError> {
+        let mut stats = Vec::with_capacity(count);
+        let mut fees_per_gram = Vec::new();
+        let mut block_fee = 0;
+        let mut block_weight = 0;
+        for (_, tx_key) in self.txs_by_priority.iter().rev() {
+            if stats.len() >= count {
+                break;
+            }
+            let transaction = &self
+                .txs_by_signature
+                .get(tx_key)
+                .ok_or(UnconfirmedPoolError::StorageOutofSync)?
+                .transaction;
+            let weight = transaction.calculate_weight();
+            if block_weight + weight > max_block_weight && !fees_per_gram.is_empty() {
+                stats.push(create_fee_per_gram_stat(
+                    stats.len() as u64,
+                    &fees_per_gram,
+                    block_fee,
+                    block_weight,
+                ));
+                fees_per_gram.clear();
+                block_fee = 0;
+                block_weight = 0;
+                if stats.len() >= count {
+                    break;
+                }
+            }
+            let fee = transaction.body.get_total_fee().as_u64();
+            fees_per_gram.push(fee / weight.max(1));
+            block_fee += fee;
+            block_weight += weight;
+        }
+        if !fees_per_gram.is_empty() && stats.len() < count {
+            stats.push(create_fee_per_gram_stat(
+                stats.len() as u64,
+                &fees_per_gram,
+                block_fee,
+                block_weight,
+            ));
+        }
+        Ok(stats)
+    }
+
     #[cfg(test)]
     /// Returns false if there are any inconsistencies in the internal mempool state, otherwise true
     fn check_status(&self) -> bool {
@@ -472,6 +533,16 @@ impl UnconfirmedPool {
     }
 }
 
+fn create_fee_per_gram_stat(order: u64, fees_per_gram: &[u64], total_fee: u64, total_weight: u64) -> FeePerGramStat {
+    FeePerGramStat {
+        order,
+        min_fee_per_gram: MicroTari::from(fees_per_gram.iter().copied().min().unwrap_or_default()),
+        avg_fee_per_gram: MicroTari::from(total_fee / total_weight.max(1)),
+        max_fee_per_gram: MicroTari::from(fees_per_gram.iter().copied().max().unwrap_or_default()),
+        total_weight,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use tari_common::configuration::Network;
@@ -553,6 +624,32 @@ mod test {
         assert!(unconfirmed_pool.check_status());
     }
 
+    #[test]
+    fn test_update_config() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 4, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 5, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 3,
+            weight_tx_skip_count: 3,
+        });
+        unconfirmed_pool
+            .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone()])
+            .unwrap();
+        assert_eq!(unconfirmed_pool.len(), 3);
+
+        // Shrinking the pool removes the lowest priority transactions
+        unconfirmed_pool.update_config(UnconfirmedPoolConfig {
+            storage_capacity: 2,
+            weight_tx_skip_count: 3,
+        });
+        assert_eq!(unconfirmed_pool.len(), 2);
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx1.body.kernels()[0].excess_sig));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx2.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx3.body.kernels()[0].excess_sig));
+    }
+
     #[test]
     fn test_double_spend_inputs() {
         let (tx1, _, _) = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1);
@@ -573,7 +670,7 @@ mod test {
         let double_spend_utxo = tx2.body.inputs().first().unwrap().clone();
         let double_spend_input = inputs.first().unwrap().clone();
 
-        let estimated_fee = Fee::calculate(20.into(), 1, 1, 1);
+        let estimated_fee = Fee::calculate(20.into(), 1, 1, 1, 0);
 
         let utxo = test_params.create_unblinded_output(UtxoTestParams {
             value: INPUT_AMOUNT - estimated_fee,
@@ -586,7 +683,9 @@ mod test {
 
         let factories = CryptoFactories::default();
         let mut stx_protocol = stx_builder.build::<HashDigest>(&factories).unwrap();
-        stx_protocol.finalize(KernelFeatures::empty(), &factories).unwrap();
+        stx_protocol
+            .finalize(KernelFeatures::empty(), &factories, None, None)
+            .unwrap();
 
         let tx3 = stx_protocol.get_transaction().unwrap().clone();
 
@@ -700,6 +799,47 @@ mod test {
         assert!(unconfirmed_pool.check_status());
     }
 
+    #[test]
+    fn test_get_fee_per_gram_stats() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(30), inputs: 2, outputs: 1).0);
+        let tx4 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+        });
+        assert!(unconfirmed_pool.get_fee_per_gram_stats(3, 19_500).unwrap().is_empty());
+
+        unconfirmed_pool
+            .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone()])
+            .unwrap();
+        let tx_weight = tx1.calculate_weight();
+
+        // Two transactions fit into each block
+        let stats = unconfirmed_pool.get_fee_per_gram_stats(3, tx_weight * 2).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].order, 0);
+        assert_eq!(stats[1].order, 1);
+        assert_eq!(stats[0].total_weight, tx_weight * 2);
+        assert_eq!(stats[1].total_weight, tx_weight * 2);
+        for stat in &stats {
+            assert!(stat.min_fee_per_gram <= stat.avg_fee_per_gram);
+            assert!(stat.avg_fee_per_gram <= stat.max_fee_per_gram);
+        }
+        // The highest priority transactions are expected in the next block
+        assert!(stats[0].min_fee_per_gram >= stats[1].max_fee_per_gram);
+
+        let stats = unconfirmed_pool.get_fee_per_gram_stats(1, tx_weight * 2).unwrap();
+        assert_eq!(stats.len(), 1);
+
+        // All the transactions fit into the next block
+        let stats = unconfirmed_pool.get_fee_per_gram_stats(3, tx_weight * 10).unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].total_weight, tx_weight * 4);
+    }
+
     #[test]
     fn test_multiple_transactions_with_same_outputs_in_mempool() {
         let (tx1, _, _) = tx!(MicroTari(150_000), fee: MicroTari(50), inputs:5, outputs:5);
@@ -96,6 +96,21 @@ pub fn extract_tari_hash(monero: &monero::Block) -> Option<&monero::Hash> {
     None
 }
 
+/// Returns the extra nonce of the Monero coinbase transaction, which mining pools commonly use to tag the blocks they
+/// mine
+pub fn extract_extra_nonce(monero_data: &MoneroPowData) -> Option<&[u8]> {
+    monero_data
+        .coinbase_tx
+        .prefix
+        .extra
+        .0
+        .iter()
+        .find_map(|item| match item {
+            SubField::Nonce(nonce) => Some(nonce.as_slice()),
+            _ => None,
+        })
+}
+
 pub fn deserialize_monero_block_from_hex<T>(data: T) -> Result<monero::Block, MergeMineError>
 where T: AsRef<[u8]> {
     let bytes = hex::decode(data).map_err(|_| HexError::HexConversionError)?;
@@ -29,6 +29,7 @@ pub use helpers::{
     create_blockhashing_blob_from_block,
     create_ordered_transaction_hashes_from_block,
     deserialize_monero_block_from_hex,
+    extract_extra_nonce,
     extract_tari_hash,
     monero_difficulty,
     serialize_monero_block_to_hex,
@@ -72,6 +72,7 @@ impl TryFrom<proto::types::TransactionKernel> for TransactionKernel {
             excess,
             excess_sig,
             fee: MicroTari::from(kernel.fee),
+            burn_amount: MicroTari::from(kernel.burn_amount),
             lock_height: kernel.lock_height,
         })
     }
@@ -84,6 +85,7 @@ impl From<TransactionKernel> for proto::types::TransactionKernel {
             excess: Some(kernel.excess.into()),
             excess_sig: Some(kernel.excess_sig.into()),
             fee: kernel.fee.into(),
+            burn_amount: kernel.burn_amount.into(),
             lock_height: kernel.lock_height,
         }
     }
@@ -201,6 +203,7 @@ impl TryFrom<proto::types::OutputFeatures> for OutputFeatures {
             flags: OutputFlags::from_bits(features.flags as u8)
                 .ok_or_else(|| "Invalid or unrecognised output flags".to_string())?,
             maturity: features.maturity,
+            encrypted_memo: features.encrypted_memo,
         })
     }
 }
@@ -210,6 +213,7 @@ impl From<OutputFeatures> for proto::types::OutputFeatures {
         Self {
             flags: features.flags.bits() as u32,
             maturity: features.maturity,
+            encrypted_memo: features.encrypted_memo,
         }
     }
 }
@@ -42,14 +42,18 @@ use crate::{
         HorizonData,
         LMDBDatabase,
         MmrTree,
+        OrphanPoolStats,
         PrunedOutput,
+        PruningPolicy,
         Validators,
+        WitnessPruningStats,
     },
     consensus::{chain_strength_comparer::ChainStrengthComparerBuilder, ConsensusConstantsBuilder, ConsensusManager},
     crypto::tari_utilities::Hashable,
     proof_of_work::{AchievedTargetDifficulty, Difficulty, PowAlgorithm},
     test_helpers::BlockSpec,
     transactions::{
+        tari_amount::MicroTari,
         transaction::{TransactionInput, TransactionKernel, UnblindedOutput},
         CryptoFactories,
     },
@@ -256,6 +260,10 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_kernel_by_excess_sig(excess_sig)
     }
 
+    fn fetch_total_burnt(&self, height: u64) -> Result<MicroTari, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_total_burnt(height)
+    }
+
     fn fetch_kernels_by_mmr_position(&self, start: u64, end: u64) -> Result<Vec<TransactionKernel>, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_kernels_by_mmr_position(start, end)
     }
@@ -353,6 +361,18 @@ impl BlockchainBackend for TempDatabase {
             .delete_oldest_orphans(horizon_height, orphan_storage_capacity)
     }
 
+    fn fetch_orphan_stats(&self) -> Result<OrphanPoolStats, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_orphan_stats()
+    }
+
+    fn prune_spent_witness_data(
+        &mut self,
+        max_blocks: u64,
+        policy: &PruningPolicy,
+    ) -> Result<WitnessPruningStats, ChainStorageError> {
+        self.db.as_mut().unwrap().prune_spent_witness_data(max_blocks, policy)
+    }
+
     fn fetch_monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_monero_seed_first_seen_height(seed)
     }
@@ -368,6 +388,14 @@ impl BlockchainBackend for TempDatabase {
     fn fetch_total_size_stats(&self) -> Result<DbTotalSizeStats, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_total_size_stats()
     }
+
+    fn flush(&self) -> Result<(), ChainStorageError> {
+        self.db.as_ref().unwrap().flush()
+    }
+
+    fn set_durable_commits(&mut self, durable: bool) -> Result<(), ChainStorageError> {
+        self.db.as_mut().unwrap().set_durable_commits(durable)
+    }
 }
 
 pub fn create_chained_blocks(
@@ -19,17 +19,26 @@
 // SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-use crate::transactions::{crypto_factories::CryptoFactories, fee::Fee, tari_amount::*, transaction::*};
+use crate::transactions::{
+    crypto_factories::CryptoFactories,
+    fee::Fee,
+    script_debug::ScriptDebugReport,
+    tari_amount::*,
+    transaction::*,
+    verification_cache::VerificationCache,
+};
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::max,
+    convert::TryInto,
     fmt::{Display, Error, Formatter},
 };
 use tari_common_types::types::{
     BlindingFactor,
     Commitment,
     CommitmentFactory,
+    HashOutput,
     PrivateKey,
     PublicKey,
     RangeProofService,
@@ -38,6 +47,7 @@ use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
     keys::PublicKey as PublicKeyTrait,
     ristretto::pedersen::PedersenCommitment,
+    script::ScriptContext,
     tari_utilities::hex::Hex,
 };
 
@@ -226,6 +236,18 @@ impl AggregateBody {
         Ok(())
     }
 
+    /// Verify that the burn amounts of all kernels contained in this aggregate body match their kernel features
+    pub fn verify_burn_kernels(&self) -> Result<(), TransactionError> {
+        trace!(target: LOG_TARGET, "Checking burn kernels");
+        for kernel in self.kernels.iter() {
+            kernel.verify_burn().map_err(|e| {
+                warn!(target: LOG_TARGET, "Kernel ({}) burn check failed {:?}.", kernel, e);
+                e
+            })?;
+        }
+        Ok(())
+    }
+
     pub fn get_total_fee(&self) -> MicroTari {
         let mut fee = MicroTari::from(0);
         for kernel in &self.kernels {
@@ -234,6 +256,14 @@ impl AggregateBody {
         fee
     }
 
+    /// The total amount burnt by the kernels in this aggregate body
+    pub fn get_total_burnt(&self) -> Result<MicroTari, TransactionError> {
+        self.kernels
+            .iter()
+            .try_fold(MicroTari(0), |total, k| total.checked_add(k.burn_amount))
+            .ok_or(TransactionError::KernelSumOverflow)
+    }
+
     /// This function will check spent kernel rules like tx lock height etc
     pub fn check_kernel_rules(&self, height: u64) -> Result<(), TransactionError> {
         for kernel in self.kernels() {
@@ -332,19 +362,79 @@ impl AggregateBody {
         bypass_range_proof_verification: bool,
         total_reward: MicroTari,
         factories: &CryptoFactories,
+        prev_header: Option<HashOutput>,
+        height: Option<u64>,
     ) -> Result<(), TransactionError> {
-        self.verify_kernel_signatures()?;
+        self.validate_internal_consistency_with_cache(
+            tx_offset,
+            script_offset,
+            bypass_range_proof_verification,
+            total_reward,
+            factories,
+            prev_header,
+            height,
+            None,
+        )
+    }
+
+    /// Same as [validate_internal_consistency](AggregateBody::validate_internal_consistency), except that kernel
+    /// signatures, range proofs, metadata signatures and script signatures found in the verification cache are not
+    /// verified again. Components that pass are added to the cache.
+    #[allow(clippy::too_many_arguments)]
+    pub fn validate_internal_consistency_with_cache(
+        &self,
+        tx_offset: &BlindingFactor,
+        script_offset: &BlindingFactor,
+        bypass_range_proof_verification: bool,
+        total_reward: MicroTari,
+        factories: &CryptoFactories,
+        prev_header: Option<HashOutput>,
+        height: Option<u64>,
+        verification_cache: Option<&VerificationCache>,
+    ) -> Result<(), TransactionError> {
+        match verification_cache {
+            Some(cache) => {
+                for kernel in &self.kernels {
+                    cache.verify_kernel_signature(kernel)?;
+                }
+            },
+            None => self.verify_kernel_signatures()?,
+        }
+        self.verify_burn_kernels()?;
 
         let total_offset = factories.commitment.commit_value(tx_offset, total_reward.0);
         self.validate_kernel_sum(total_offset, &factories.commitment)?;
 
+        let unverified_outputs = match verification_cache {
+            Some(cache) => cache.unverified_outputs(&self.outputs),
+            None => self.outputs.iter().collect(),
+        };
         if !bypass_range_proof_verification {
-            self.validate_range_proofs(&factories.range_proof)?;
+            trace!(target: LOG_TARGET, "Checking range proofs");
+            TransactionOutput::verify_range_proofs_in_parallel(&factories.range_proof, &unverified_outputs)?;
+        }
+        trace!(target: LOG_TARGET, "Checking sender signatures");
+        for output in &unverified_outputs {
+            output.verify_metadata_signature()?;
         }
-        self.verify_metadata_signatures()?;
+        self.verify_output_memos()?;
 
         let script_offset_g = PublicKey::from_secret_key(script_offset);
-        self.validate_script_offset(script_offset_g, &factories.commitment)
+        self.validate_script_offset(
+            script_offset_g,
+            &factories.commitment,
+            prev_header,
+            height,
+            verification_cache,
+        )?;
+
+        // Outputs are only cached once their range proofs have been checked as well
+        if let Some(cache) = verification_cache {
+            if !bypass_range_proof_verification {
+                cache.set_outputs_verified(unverified_outputs);
+            }
+        }
+        Ok(())
     }
 
     pub fn dissolve(self) -> (Vec<TransactionInput>, Vec<TransactionOutput>, Vec<TransactionKernel>) {
@@ -358,17 +448,28 @@ impl AggregateBody {
         sum_outputs - sum_inputs
     }
 
-    /// Calculate the sum of the kernels, taking into account the provided offset, and their constituent fees
-    fn sum_kernels(&self, offset_with_fee: PedersenCommitment) -> KernelSum {
-        // Sum all kernel excesses and fees
-        self.kernels.iter().fold(
+    /// Calculate the sum of the kernels, taking into account the provided offset, and their constituent fees and
+    /// burnt amounts
+    fn sum_kernels(&self, offset_with_fee: PedersenCommitment) -> Result<KernelSum, TransactionError> {
+        // Sum all kernel excesses, fees and burnt amounts
+        self.kernels.iter().try_fold(
             KernelSum {
                 fees: MicroTari(0),
+                burnt: MicroTari(0),
                 sum: offset_with_fee,
             },
-            |acc, val| KernelSum {
-                fees: acc.fees + val.fee,
-                sum: &acc.sum + &val.excess,
+            |acc, val| {
+                Ok(KernelSum {
+                    fees: acc
+                        .fees
+                        .checked_add(val.fee)
+                        .ok_or(TransactionError::KernelSumOverflow)?,
+                    burnt: acc
+                        .burnt
+                        .checked_add(val.burn_amount)
+                        .ok_or(TransactionError::KernelSumOverflow)?,
+                    sum: &acc.sum + &val.excess,
+                })
             },
         )
     }
@@ -377,16 +478,22 @@ impl AggregateBody {
     ///
     /// The offset_and_reward commitment includes the offset & the total coinbase reward (block reward + fees for
     /// block balances, or zero for transaction balances)
-    fn validate_kernel_sum(
+    pub(crate) fn validate_kernel_sum(
         &self,
         offset_and_reward: Commitment,
         factory: &CommitmentFactory,
     ) -> Result<(), TransactionError> {
         trace!(target: LOG_TARGET, "Checking kernel total");
-        let KernelSum { sum: excess, fees } = self.sum_kernels(offset_and_reward);
+        let KernelSum {
+            sum: excess,
+            fees,
+            burnt,
+        } = self.sum_kernels(offset_and_reward)?;
         let sum_io = self.sum_commitments();
         trace!(target: LOG_TARGET, "Total outputs - inputs:{}", sum_io.to_hex());
-        let fees = factory.commit_value(&PrivateKey::default(), fees.into());
+        // Burnt value leaves the outputs in the same way as the fee, but is not claimed by the coinbase
+        let fees_and_burnt = fees.checked_add(burnt).ok_or(TransactionError::KernelSumOverflow)?;
+        let fees = factory.commit_value(&PrivateKey::default(), fees_and_burnt.into());
         trace!(
             target: LOG_TARGET,
             "Comparing sum.  excess:{} == sum {} + fees {}",
@@ -404,16 +511,46 @@ impl AggregateBody {
     }
 
     /// this will validate the script offset of the aggregate body.
-    fn validate_script_offset(
+    pub(crate) fn validate_script_offset(
         &self,
         script_offset: PublicKey,
         factory: &CommitmentFactory,
+        prev_header: Option<HashOutput>,
+        height: Option<u64>,
+        verification_cache: Option<&VerificationCache>,
     ) -> Result<(), TransactionError> {
         trace!(target: LOG_TARGET, "Checking script offset");
+        // The script context is built from the previous block hash and the height of the block the inputs are spent in
+        let prev_hash: [u8; 32] = prev_header.unwrap_or_default().as_slice().try_into().unwrap_or([0; 32]);
+        let height = height.unwrap_or_default();
         // lets count up the input script public keys
         let mut input_keys = PublicKey::default();
         for input in &self.inputs {
-            input_keys = input_keys + input.run_and_verify_script(factory)?;
+            let context = ScriptContext::new(height, &prev_hash, &input.commitment);
+            let input_key = match verification_cache {
+                Some(cache) => cache.run_and_verify_script(input, factory, context),
+                None => input.run_and_verify_script(factory, Some(context)),
+            };
+            let input_key = input_key.map_err(|err| {
+                if log_enabled!(target: LOG_TARGET, Level::Debug) {
+                    let report = ScriptDebugReport::execute(
+                        &input.script,
+                        &input.input_data,
+                        height,
+                        prev_hash,
+                        &input.commitment,
+                    );
+                    debug!(
+                        target: LOG_TARGET,
+                        "Script validation failed for input {}: {}\n{}",
+                        input.commitment.to_hex(),
+                        err,
+                        report
+                    );
+                }
+                err
+            })?;
+            input_keys = input_keys + input_key;
         }
 
         // Now lets gather the output public keys and hashes.
@@ -431,29 +568,42 @@ impl AggregateBody {
         Ok(())
     }
 
-    fn validate_range_proofs(&self, range_proof_service: &RangeProofService) -> Result<(), TransactionError> {
+    pub(crate) fn validate_range_proofs(
+        &self,
+        range_proof_service: &RangeProofService,
+    ) -> Result<(), TransactionError> {
         trace!(target: LOG_TARGET, "Checking range proofs");
+        TransactionOutput::verify_range_proofs_in_parallel(range_proof_service, &self.outputs)
+    }
+
+    pub(crate) fn verify_metadata_signatures(&self) -> Result<(), TransactionError> {
+        trace!(target: LOG_TARGET, "Checking sender signatures");
         for o in &self.outputs {
-            if !o.verify_range_proof(range_proof_service)? {
-                return Err(TransactionError::ValidationError(
-                    "Range proof could not be verified".into(),
-                ));
-            }
+            o.verify_metadata_signature()?;
         }
         Ok(())
     }
 
-    fn verify_metadata_signatures(&self) -> Result<(), TransactionError> {
-        trace!(target: LOG_TARGET, "Checking sender signatures");
+    fn verify_output_memos(&self) -> Result<(), TransactionError> {
         for o in &self.outputs {
-            o.verify_metadata_signature()?;
+            o.features.verify_memo_size(MAX_OUTPUT_MEMO_SIZE)?;
         }
         Ok(())
     }
 
     /// Returns the byte size or weight of a body
     pub fn calculate_weight(&self) -> u64 {
-        Fee::calculate_weight(self.kernels().len(), self.inputs().len(), self.outputs().len())
+        Fee::calculate_weight(
+            self.kernels().len(),
+            self.inputs().len(),
+            self.outputs().len(),
+            self.total_memo_size(),
+        )
+    }
+
+    /// Returns the total size in bytes of the encrypted memos on the outputs of this body
+    pub fn total_memo_size(&self) -> usize {
+        self.outputs().iter().map(|o| o.features.encrypted_memo.len()).sum()
     }
 
     pub fn is_sorted(&self) -> bool {
@@ -527,7 +527,9 @@ mod test {
                 &PrivateKey::default(),
                 false,
                 block_reward,
-                &factories
+                &factories,
+                None,
+                None
             ),
             Ok(())
         );
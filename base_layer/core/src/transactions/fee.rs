@@ -21,16 +21,23 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    consensus::{KERNEL_WEIGHT, WEIGHT_PER_INPUT, WEIGHT_PER_OUTPUT},
+    consensus::{KERNEL_WEIGHT, MEMO_BYTES_PER_GRAM, WEIGHT_PER_INPUT, WEIGHT_PER_OUTPUT},
     transactions::{tari_amount::*, transaction::MINIMUM_TRANSACTION_FEE},
 };
 
 pub struct Fee {}
 
 impl Fee {
-    /// Computes the absolute transaction fee given the fee-per-gram, and the size of the transaction
-    pub fn calculate(fee_per_gram: MicroTari, num_kernels: usize, num_inputs: usize, num_outputs: usize) -> MicroTari {
-        (Fee::calculate_weight(num_kernels, num_inputs, num_outputs) * u64::from(fee_per_gram)).into()
+    /// Computes the absolute transaction fee given the fee-per-gram, and the size of the transaction. `memo_size` is
+    /// the total length in bytes of the encrypted memos on the transaction's outputs.
+    pub fn calculate(
+        fee_per_gram: MicroTari,
+        num_kernels: usize,
+        num_inputs: usize,
+        num_outputs: usize,
+        memo_size: usize,
+    ) -> MicroTari {
+        (Fee::calculate_weight(num_kernels, num_inputs, num_outputs, memo_size) * u64::from(fee_per_gram)).into()
     }
 
     /// Computes the absolute transaction fee using `calculate`, but the resulting fee will always be at least the
@@ -40,8 +47,9 @@ impl Fee {
         num_kernels: usize,
         num_inputs: usize,
         num_outputs: usize,
+        memo_size: usize,
     ) -> MicroTari {
-        let fee = Fee::calculate(fee_per_gram, num_kernels, num_inputs, num_outputs);
+        let fee = Fee::calculate(fee_per_gram, num_kernels, num_inputs, num_outputs, memo_size);
         if fee < MINIMUM_TRANSACTION_FEE {
             MINIMUM_TRANSACTION_FEE
         } else {
@@ -49,10 +57,42 @@ impl Fee {
         }
     }
 
-    /// Calculate the weight of a transaction based on the number of inputs and outputs
-    pub fn calculate_weight(num_kernels: usize, num_inputs: usize, num_outputs: usize) -> u64 {
+    /// Calculate the weight of a transaction based on the number of inputs and outputs, and the total size of the
+    /// encrypted memos on its outputs
+    pub fn calculate_weight(num_kernels: usize, num_inputs: usize, num_outputs: usize, memo_size: usize) -> u64 {
         KERNEL_WEIGHT * num_kernels as u64 +
             WEIGHT_PER_INPUT * num_inputs as u64 +
-            WEIGHT_PER_OUTPUT * num_outputs as u64
+            WEIGHT_PER_OUTPUT * num_outputs as u64 +
+            Fee::memo_weight(memo_size)
+    }
+
+    /// The weight of `memo_size` bytes of encrypted output memos, rounded up to a whole gram
+    pub fn memo_weight(memo_size: usize) -> u64 {
+        (memo_size as u64 + MEMO_BYTES_PER_GRAM - 1) / MEMO_BYTES_PER_GRAM
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transactions::transaction::MAX_OUTPUT_MEMO_SIZE;
+
+    #[test]
+    fn memo_weight_rounds_up() {
+        assert_eq!(Fee::memo_weight(0), 0);
+        assert_eq!(Fee::memo_weight(1), 1);
+        assert_eq!(Fee::memo_weight(MEMO_BYTES_PER_GRAM as usize), 1);
+        assert_eq!(Fee::memo_weight(MEMO_BYTES_PER_GRAM as usize + 1), 2);
+        assert_eq!(Fee::memo_weight(MAX_OUTPUT_MEMO_SIZE), 4);
+    }
+
+    #[test]
+    fn memo_raises_minimum_fee() {
+        // Large enough that the fee without a memo is already above the network minimum
+        let fee_per_gram = MicroTari(100_000);
+        let without_memo = Fee::calculate_with_minimum(fee_per_gram, 1, 1, 2, 0);
+        let with_memo = Fee::calculate_with_minimum(fee_per_gram, 1, 1, 2, MAX_OUTPUT_MEMO_SIZE);
+        assert!(without_memo > MINIMUM_TRANSACTION_FEE);
+        assert_eq!(with_memo, without_memo + MicroTari(4 * 100_000));
     }
 }
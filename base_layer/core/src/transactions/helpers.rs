@@ -194,7 +194,11 @@ pub fn create_random_signature(fee: MicroTari, lock_height: u64) -> (PublicKey,
 /// Generate a random transaction signature, returning the public key (excess) and the signature.
 pub fn create_signature(k: PrivateKey, fee: MicroTari, lock_height: u64) -> Signature {
     let r = PrivateKey::random(&mut OsRng);
-    let tx_meta = TransactionMetadata { fee, lock_height };
+    let tx_meta = TransactionMetadata {
+        fee,
+        lock_height,
+        ..Default::default()
+    };
     let e = build_challenge(&PublicKey::from_secret_key(&r), &tx_meta);
     Signature::sign(k, r, &e).unwrap()
 }
@@ -208,7 +212,11 @@ pub fn create_random_signature_from_s_key(
     let _rng = rand::thread_rng();
     let r = PrivateKey::random(&mut OsRng);
     let p = PK::from_secret_key(&s_key);
-    let tx_meta = TransactionMetadata { fee, lock_height };
+    let tx_meta = TransactionMetadata {
+        fee,
+        lock_height,
+        ..Default::default()
+    };
     let e = build_challenge(&PublicKey::from_secret_key(&r), &tx_meta);
     (p, Signature::sign(s_key, r, &e).unwrap())
 }
@@ -341,7 +349,7 @@ pub fn create_unblinded_txos(
     output_count: usize,
     fee_per_gram: MicroTari,
 ) -> (Vec<UnblindedOutput>, Vec<(UnblindedOutput, PrivateKey)>) {
-    let estimated_fee = Fee::calculate(fee_per_gram, 1, input_count, output_count);
+    let estimated_fee = Fee::calculate(fee_per_gram, 1, input_count, output_count, 0);
     let amount_per_output = (amount - estimated_fee) / output_count as u64;
     let amount_for_last_output = (amount - estimated_fee) - amount_per_output * (output_count as u64 - 1);
 
@@ -416,7 +424,9 @@ pub fn create_transaction_with(
     });
 
     let mut stx_protocol = stx_builder.build::<Blake256>(&factories).unwrap();
-    stx_protocol.finalize(KernelFeatures::empty(), &factories).unwrap();
+    stx_protocol
+        .finalize(KernelFeatures::empty(), &factories, None, None)
+        .unwrap();
     stx_protocol.take_transaction().unwrap()
 }
 
@@ -508,7 +518,9 @@ pub fn spend_utxos(schema: TransactionSchema) -> (Transaction, Vec<UnblindedOutp
         metadata_sig,
     );
     outputs.push(change_output);
-    stx_protocol.finalize(KernelFeatures::empty(), &factories).unwrap();
+    stx_protocol
+        .finalize(KernelFeatures::empty(), &factories, None, None)
+        .unwrap();
     let txn = stx_protocol.get_transaction().unwrap().clone();
     (txn, outputs, test_params_change_and_txn)
 }
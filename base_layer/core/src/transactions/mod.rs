@@ -1,10 +1,12 @@
 pub mod aggregated_body;
 mod crypto_factories;
 pub mod fee;
+pub mod script_debug;
 pub mod tari_amount;
 pub mod transaction;
 #[allow(clippy::op_ref)]
 pub mod transaction_protocol;
+pub mod verification_cache;
 
 pub use crypto_factories::*;
 
@@ -0,0 +1,136 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Diagnostics for TariScript execution. A [ScriptDebugReport] captures the script, its input stack and the
+//! execution context together with the outcome, in a form that can be replayed with the base node `debug-script`
+//! command.
+
+use std::fmt;
+use tari_common_types::types::{Commitment, PublicKey};
+use tari_crypto::{
+    script::{ExecutionStack, ScriptContext, StackItem, TariScript},
+    tari_utilities::hex::Hex,
+};
+
+/// The outcome of executing a script along with everything that is needed to reproduce the execution
+#[derive(Debug, Clone)]
+pub struct ScriptDebugReport {
+    pub script: TariScript,
+    pub input_data: ExecutionStack,
+    pub height: u64,
+    pub prev_block_hash: [u8; 32],
+    pub commitment: Commitment,
+    pub result: Result<StackItem, String>,
+}
+
+impl ScriptDebugReport {
+    /// Execute `script` against `input_data` in the context given by the remaining parameters
+    pub fn execute(
+        script: &TariScript,
+        input_data: &ExecutionStack,
+        height: u64,
+        prev_block_hash: [u8; 32],
+        commitment: &Commitment,
+    ) -> Self {
+        let context = ScriptContext::new(height, &prev_block_hash, commitment);
+        let result = script
+            .execute_with_context(input_data, &context)
+            .map_err(|err| err.to_string());
+        Self {
+            script: script.clone(),
+            input_data: input_data.clone(),
+            height,
+            prev_block_hash,
+            commitment: commitment.clone(),
+            result,
+        }
+    }
+
+    /// Returns true if the script executed successfully and left a public key on the stack
+    pub fn is_success(&self) -> bool {
+        self.public_key().is_some()
+    }
+
+    /// The public key left on the stack, if the script executed successfully
+    pub fn public_key(&self) -> Option<&PublicKey> {
+        match &self.result {
+            Ok(StackItem::PublicKey(key)) => Some(key),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ScriptDebugReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Script      : {}", self.script.to_hex())?;
+        writeln!(f, "Opcodes     : {:?}", self.script)?;
+        writeln!(f, "Input stack : {}", self.input_data.to_hex())?;
+        writeln!(f, "Input items : {:?}", self.input_data)?;
+        writeln!(
+            f,
+            "Context     : height {}, previous block {}, commitment {}",
+            self.height,
+            self.prev_block_hash.to_hex(),
+            self.commitment.to_hex()
+        )?;
+        match &self.result {
+            Ok(StackItem::PublicKey(key)) => write!(f, "Result      : public key {}", key.to_hex()),
+            Ok(item) => write!(
+                f,
+                "Result      : {:?} (the script must leave a public key on the stack)",
+                item
+            ),
+            Err(err) => write!(f, "Result      : failed with {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_crypto::{inputs, keys::PublicKey as PublicKeyTrait, script};
+
+    #[test]
+    fn it_reports_successful_execution() {
+        let (_, key) = PublicKey::random_keypair(&mut OsRng);
+        let script = script!(Nop);
+        let report = ScriptDebugReport::execute(&script, &inputs!(key.clone()), 5, [0; 32], &Commitment::default());
+        assert!(report.is_success());
+        assert_eq!(report.public_key(), Some(&key));
+        assert!(report.to_string().contains(&key.to_hex()));
+    }
+
+    #[test]
+    fn it_reports_failed_execution() {
+        let (_, key) = PublicKey::random_keypair(&mut OsRng);
+        let script = script!(CheckHeightVerify(10) PushPubKey(Box::new(key)));
+        let report =
+            ScriptDebugReport::execute(&script, &ExecutionStack::default(), 5, [0; 32], &Commitment::default());
+        assert!(!report.is_success());
+        assert!(report.result.is_err());
+        let output = report.to_string();
+        assert!(output.contains(&script.to_hex()));
+        assert!(output.contains("failed"));
+    }
+}
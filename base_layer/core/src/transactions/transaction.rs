@@ -24,6 +24,7 @@
 // Version 2.0, available at http://www.apache.org/licenses/LICENSE-2.0.
 
 use std::{
+    borrow::Borrow,
     cmp::{max, min, Ordering},
     fmt,
     fmt::{Display, Formatter},
@@ -33,6 +34,7 @@ use std::{
 
 use blake2::Digest;
 use rand::rngs::OsRng;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
@@ -45,7 +47,7 @@ use tari_crypto::{
         REWIND_USER_MESSAGE_LENGTH,
     },
     ristretto::pedersen::PedersenCommitmentFactory,
-    script::{ExecutionStack, ScriptError, StackItem, TariScript},
+    script::{ExecutionStack, ScriptContext, ScriptError, StackItem, TariScript},
     signatures::CommitmentSignatureError,
     tari_utilities::{hex::Hex, message_format::MessageFormat, ByteArray, Hashable},
 };
@@ -56,6 +58,7 @@ use crate::transactions::{
     crypto_factories::CryptoFactories,
     tari_amount::{uT, MicroTari},
     transaction_protocol::{build_challenge, RewindData, TransactionMetadata},
+    verification_cache::VerificationCache,
 };
 use std::ops::Shl;
 use tari_common_types::types::{
@@ -65,6 +68,7 @@ use tari_common_types::types::{
     Commitment,
     CommitmentFactory,
     HashDigest,
+    HashOutput,
     MessageHash,
     PrivateKey,
     PublicKey,
@@ -78,6 +82,14 @@ pub const MAX_TRANSACTION_INPUTS: usize = 12_500;
 pub const MAX_TRANSACTION_OUTPUTS: usize = 500;
 pub const MAX_TRANSACTION_RECIPIENTS: usize = 15;
 pub const MINIMUM_TRANSACTION_FEE: MicroTari = MicroTari(100);
+/// The maximum size in bytes of the encrypted memo that may be attached to an output. Consensus constants may set a
+/// lower limit, e.g. before output memos are enabled on a network.
+pub const MAX_OUTPUT_MEMO_SIZE: usize = 256;
+/// The most a kernel may burn. This is above the total emission, so no valid kernel can burn more, and it keeps the
+/// sum of the fees and burnt amounts of a block far from overflowing.
+pub const MAX_BURN_AMOUNT: MicroTari = MicroTari(21_000_000_000 * 1_000_000);
+/// The number of range proofs one rayon task verifies in turn when the range proofs of a block are verified in parallel
+pub const RANGE_PROOF_CHUNK_SIZE: usize = 32;
 
 //--------------------------------------        Output features   --------------------------------------------------//
 
@@ -88,6 +100,8 @@ bitflags! {
     pub struct KernelFeatures: u8 {
         /// Coinbase transaction
         const COINBASE_KERNEL = 1u8;
+        /// Burn transaction. The burnt amount is removed from the circulating supply.
+        const BURN_KERNEL = 2u8;
     }
 }
 
@@ -95,6 +109,10 @@ impl KernelFeatures {
     pub fn create_coinbase() -> KernelFeatures {
         KernelFeatures::COINBASE_KERNEL
     }
+
+    pub fn create_burn() -> KernelFeatures {
+        KernelFeatures::BURN_KERNEL
+    }
 }
 
 /// Options for UTXO's
@@ -105,12 +123,21 @@ pub struct OutputFeatures {
     /// the maturity of the specific UTXO. This is the min lock height at which an UTXO can be spent. Coinbase UTXO
     /// require a min maturity of the Coinbase_lock_height, this should be checked on receiving new blocks.
     pub maturity: u64,
+    /// An optional memo, e.g. a payment reference, encrypted to the receiver of the output. Consensus only limits its
+    /// size.
+    #[serde(default)]
+    pub encrypted_memo: Vec<u8>,
 }
 
 impl OutputFeatures {
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        bincode::serialize_into(&mut buf, self).unwrap(); // this should not fail
+        // Features without a memo serialize as they did before the memo was added, which keeps existing output hashes
+        // and metadata signatures valid
+        bincode::serialize_into(&mut buf, &(self.flags, self.maturity)).unwrap(); // this should not fail
+        if !self.encrypted_memo.is_empty() {
+            bincode::serialize_into(&mut buf, &self.encrypted_memo).unwrap(); // this should not fail
+        }
         buf
     }
 
@@ -118,9 +145,29 @@ impl OutputFeatures {
         OutputFeatures {
             flags: OutputFlags::COINBASE_OUTPUT,
             maturity: maturity_height,
+            encrypted_memo: Vec::new(),
+        }
+    }
+
+    /// Create an `OutputFeatures` carrying the given encrypted memo and all other values at their default setting
+    pub fn with_encrypted_memo(encrypted_memo: Vec<u8>) -> OutputFeatures {
+        OutputFeatures {
+            encrypted_memo,
+            ..OutputFeatures::default()
         }
     }
 
+    /// Check that the encrypted memo does not exceed `max_size` bytes
+    pub fn verify_memo_size(&self, max_size: usize) -> Result<(), TransactionError> {
+        if self.encrypted_memo.len() > max_size {
+            return Err(TransactionError::OutputMemoTooLarge {
+                size: self.encrypted_memo.len(),
+                max: max_size,
+            });
+        }
+        Ok(())
+    }
+
     /// Create an `OutputFeatures` with the given maturity and all other values at their default setting
     pub fn with_maturity(maturity: u64) -> OutputFeatures {
         OutputFeatures {
@@ -135,6 +182,7 @@ impl Default for OutputFeatures {
         OutputFeatures {
             flags: OutputFlags::empty(),
             maturity: 0,
+            encrypted_memo: Vec::new(),
         }
     }
 }
@@ -155,8 +203,10 @@ impl Display for OutputFeatures {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "OutputFeatures: Flags = {:?}, Maturity = {}",
-            self.flags, self.maturity
+            "OutputFeatures: Flags = {:?}, Maturity = {}, Memo = {} bytes",
+            self.flags,
+            self.maturity,
+            self.encrypted_memo.len()
         )
     }
 }
@@ -203,6 +253,12 @@ pub enum TransactionError {
     ScriptOffset,
     #[error("Error executing script: {0}")]
     ScriptExecutionError(String),
+    #[error("Invalid burn kernel: {0}")]
+    InvalidBurnKernel(String),
+    #[error("The total of the kernel fees and burnt amounts overflowed")]
+    KernelSumOverflow,
+    #[error("The output memo is {size} bytes, which exceeds the maximum of {max} bytes")]
+    OutputMemoTooLarge { size: usize, max: usize },
 }
 
 //-----------------------------------------     UnblindedOutput   ----------------------------------------------------//
@@ -446,9 +502,10 @@ impl TransactionInput {
     }
 
     /// This will run the script contained in the TransactionInput, returning either a script error or the resulting
-    /// public key.
-    pub fn run_script(&self) -> Result<PublicKey, TransactionError> {
-        match self.script.execute(&self.input_data)? {
+    /// public key. If no context is provided, the script is executed against a default (empty) context.
+    pub fn run_script(&self, context: Option<ScriptContext>) -> Result<PublicKey, TransactionError> {
+        let context = context.unwrap_or_default();
+        match self.script.execute_with_context(&self.input_data, &context)? {
             StackItem::PublicKey(pubkey) => Ok(pubkey),
             _ => Err(TransactionError::ScriptExecutionError(
                 "The script executed successfully but it did not leave a public key on the stack".to_string(),
@@ -482,8 +539,12 @@ impl TransactionInput {
 
     /// This will run the script and verify the script signature. If its valid, it will return the resulting public key
     /// from the script.
-    pub fn run_and_verify_script(&self, factory: &CommitmentFactory) -> Result<PublicKey, TransactionError> {
-        let key = self.run_script()?;
+    pub fn run_and_verify_script(
+        &self,
+        factory: &CommitmentFactory,
+        context: Option<ScriptContext>,
+    ) -> Result<PublicKey, TransactionError> {
+        let key = self.run_script(context)?;
         self.validate_script_signature(&key, factory)?;
         Ok(key)
     }
@@ -604,6 +665,36 @@ impl TransactionOutput {
         Ok(prover.verify(&self.proof.0, &self.commitment))
     }
 
+    /// Verify the range proofs of all the given outputs in parallel. The outputs are split into chunks of
+    /// `RANGE_PROOF_CHUNK_SIZE` and the chunks are verified on the rayon thread pool. Each proof is still verified on
+    /// its own, this is not batch verification.
+    pub fn verify_range_proofs_in_parallel<T: Borrow<TransactionOutput> + Sync>(
+        prover: &RangeProofService,
+        outputs: &[T],
+    ) -> Result<(), TransactionError> {
+        outputs
+            .par_chunks(RANGE_PROOF_CHUNK_SIZE)
+            .try_for_each(|chunk| Self::verify_range_proof_chunk(prover, chunk))
+    }
+
+    fn verify_range_proof_chunk<T: Borrow<TransactionOutput>>(
+        prover: &RangeProofService,
+        chunk: &[T],
+    ) -> Result<(), TransactionError> {
+        // The range proof service can only verify one proof at a time, so the proofs in a chunk are checked in turn
+        match chunk
+            .iter()
+            .map(Borrow::borrow)
+            .find(|output: &&TransactionOutput| !prover.verify(&output.proof.0, &output.commitment))
+        {
+            Some(output) => Err(TransactionError::ValidationError(format!(
+                "Range proof could not be verified for output {}",
+                output.hash().to_hex()
+            ))),
+            None => Ok(()),
+        }
+    }
+
     /// Verify that the metadata signature is valid
     pub fn verify_metadata_signature(&self) -> Result<(), TransactionError> {
         let challenge = TransactionOutput::build_metadata_signature_challenge(
@@ -925,13 +1016,16 @@ impl From<CryptoFullRewindResult<BlindingFactor>> for FullRewindResult {
 /// why it is necessary, refer to the
 /// [Mimblewimble TLU post](https://tlu.tarilabs.com/protocols/mimblewimble-1/sources/PITCHME.link.html?highlight=mimblewimble#mimblewimble).
 /// The kernel also tracks other transaction metadata, such as the lock height for the transaction (i.e. the earliest
-/// this transaction can be mined) and the transaction fee, in cleartext.
+/// this transaction can be mined), the transaction fee and the amount burnt, in cleartext.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TransactionKernel {
     /// Options for a kernel's structure or use
     pub features: KernelFeatures,
     /// Fee originally included in the transaction this proof is for.
     pub fee: MicroTari,
+    /// The amount removed from the supply by this transaction. Only burn kernels have a non-zero burn amount.
+    #[serde(default)]
+    pub burn_amount: MicroTari,
     /// This kernel is not valid earlier than lock_height blocks
     /// The max lock_height of all *inputs* to this transaction
     pub lock_height: u64,
@@ -948,12 +1042,44 @@ impl TransactionKernel {
         self.features.contains(KernelFeatures::COINBASE_KERNEL)
     }
 
+    pub fn is_burn(&self) -> bool {
+        self.features.contains(KernelFeatures::BURN_KERNEL)
+    }
+
+    /// The commitment to the burnt amount with a zero blinding factor. Burnt value leaves the outputs of a
+    /// transaction without being claimed by the coinbase, so it balances in the same way as the fee.
+    pub fn burn_commitment(&self, factory: &CommitmentFactory) -> Commitment {
+        factory.commit_value(&PrivateKey::default(), self.burn_amount.into())
+    }
+
+    /// Checks that the burn amount is consistent with the kernel features. Only burn kernels may burn value, they must
+    /// burn a non-zero amount of at most `MAX_BURN_AMOUNT` and cannot also be coinbase kernels.
+    pub fn verify_burn(&self) -> Result<(), TransactionError> {
+        match (self.is_burn(), self.burn_amount) {
+            (true, MicroTari(0)) => Err(TransactionError::InvalidBurnKernel(
+                "A burn kernel must burn a non-zero amount".to_string(),
+            )),
+            (true, amount) if amount > MAX_BURN_AMOUNT => Err(TransactionError::InvalidBurnKernel(format!(
+                "A burn kernel may burn at most {}",
+                MAX_BURN_AMOUNT
+            ))),
+            (true, _) if self.is_coinbase() => Err(TransactionError::InvalidBurnKernel(
+                "A coinbase kernel cannot be a burn kernel".to_string(),
+            )),
+            (false, amount) if amount > MicroTari(0) => Err(TransactionError::InvalidBurnKernel(
+                "Only burn kernels may have a burn amount".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
     pub fn verify_signature(&self) -> Result<(), TransactionError> {
         let excess = self.excess.as_public_key();
         let r = self.excess_sig.get_public_nonce();
         let m = TransactionMetadata {
             lock_height: self.lock_height,
             fee: self.fee,
+            burn_amount: self.burn_amount,
         };
         let c = build_challenge(r, &m);
         if self.excess_sig.verify_challenge(excess, &c) {
@@ -979,24 +1105,32 @@ impl TransactionKernel {
 impl Hashable for TransactionKernel {
     /// Produce a canonical hash for a transaction kernel. The hash is given by
     /// $$ H(feature_bits | fee | lock_height | P_excess | R_sum | s_sum)
+    /// Burn kernels append the burn amount, so that the hashes of all other kernels are unchanged.
     fn hash(&self) -> Vec<u8> {
-        HashDigest::new()
+        let hasher = HashDigest::new()
             .chain(&[self.features.bits])
             .chain(u64::from(self.fee).to_le_bytes())
             .chain(self.lock_height.to_le_bytes())
             .chain(self.excess.as_bytes())
             .chain(self.excess_sig.get_public_nonce().as_bytes())
-            .chain(self.excess_sig.get_signature().as_bytes())
-            .finalize()
-            .to_vec()
+            .chain(self.excess_sig.get_signature().as_bytes());
+        if self.is_burn() {
+            hasher
+                .chain(u64::from(self.burn_amount).to_le_bytes())
+                .finalize()
+                .to_vec()
+        } else {
+            hasher.finalize().to_vec()
+        }
     }
 }
 
 impl Display for TransactionKernel {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
         let msg = format!(
-            "Fee: {}\nLock height: {}\nFeatures: {:?}\nExcess: {}\nExcess signature: {}\n",
+            "Fee: {}\nBurnt: {}\nLock height: {}\nFeatures: {:?}\nExcess: {}\nExcess signature: {}\n",
             self.fee,
+            self.burn_amount,
             self.lock_height,
             self.features,
             self.excess.to_hex(),
@@ -1024,6 +1158,7 @@ impl Ord for TransactionKernel {
 pub struct KernelBuilder {
     features: KernelFeatures,
     fee: MicroTari,
+    burn_amount: MicroTari,
     lock_height: u64,
     excess: Option<Commitment>,
     excess_sig: Option<Signature>,
@@ -1048,6 +1183,13 @@ impl KernelBuilder {
         self
     }
 
+    /// Build a transaction kernel that burns the provided amount. This also sets the burn kernel feature.
+    pub fn with_burn_amount(mut self, burn_amount: MicroTari) -> KernelBuilder {
+        self.burn_amount = burn_amount;
+        self.features |= KernelFeatures::BURN_KERNEL;
+        self
+    }
+
     /// Build a transaction kernel with the provided lock height
     pub fn with_lock_height(mut self, lock_height: u64) -> KernelBuilder {
         self.lock_height = lock_height;
@@ -1073,6 +1215,7 @@ impl KernelBuilder {
         Ok(TransactionKernel {
             features: self.features,
             fee: self.fee,
+            burn_amount: self.burn_amount,
             lock_height: self.lock_height,
             excess: self.excess.unwrap(),
             excess_sig: self.excess_sig.unwrap(),
@@ -1085,6 +1228,7 @@ impl Default for KernelBuilder {
         KernelBuilder {
             features: KernelFeatures::empty(),
             fee: MicroTari::from(0),
+            burn_amount: MicroTari::from(0),
             lock_height: 0,
             excess: None,
             excess_sig: None,
@@ -1093,11 +1237,12 @@ impl Default for KernelBuilder {
 }
 
 /// This struct holds the result of calculating the sum of the kernels in a Transaction
-/// and returns the summed commitments and the total fees
+/// and returns the summed commitments, the total fees and the total amount burnt
 #[derive(Default)]
 pub struct KernelSum {
     pub sum: Commitment,
     pub fees: MicroTari,
+    pub burnt: MicroTari,
 }
 
 //----------------------------------------      Transaction       ----------------------------------------------------//
@@ -1141,20 +1286,46 @@ impl Transaction {
     /// 1. Range proofs of the outputs are valid
     ///
     /// This function does NOT check that inputs come from the UTXO set
-    #[allow(clippy::erasing_op)] // This is for 0 * uT
     pub fn validate_internal_consistency(
         &self,
         bypass_range_proof_verification: bool,
         factories: &CryptoFactories,
         reward: Option<MicroTari>,
+        prev_header: Option<HashOutput>,
+        height: Option<u64>,
+    ) -> Result<(), TransactionError> {
+        self.validate_internal_consistency_with_cache(
+            bypass_range_proof_verification,
+            factories,
+            reward,
+            prev_header,
+            height,
+            None,
+        )
+    }
+
+    /// Same as [validate_internal_consistency](Transaction::validate_internal_consistency), using the verification
+    /// cache to skip the checks of components that have been verified before
+    #[allow(clippy::erasing_op)] // This is for 0 * uT
+    pub fn validate_internal_consistency_with_cache(
+        &self,
+        bypass_range_proof_verification: bool,
+        factories: &CryptoFactories,
+        reward: Option<MicroTari>,
+        prev_header: Option<HashOutput>,
+        height: Option<u64>,
+        verification_cache: Option<&VerificationCache>,
     ) -> Result<(), TransactionError> {
         let reward = reward.unwrap_or_else(|| 0 * uT);
-        self.body.validate_internal_consistency(
+        self.body.validate_internal_consistency_with_cache(
             &self.offset,
             &self.script_offset,
             bypass_range_proof_verification,
             reward,
             factories,
+            prev_header,
+            height,
+            verification_cache,
         )
     }
 
@@ -1302,7 +1473,7 @@ impl TransactionBuilder {
         if let (Some(script_offset), Some(offset)) = (self.script_offset, self.offset) {
             let (i, o, k) = self.body.dissolve();
             let tx = Transaction::new(i, o, k, offset, script_offset);
-            tx.validate_internal_consistency(true, factories, self.reward)?;
+            tx.validate_internal_consistency(true, factories, self.reward, None, None)?;
             Ok(tx)
         } else {
             Err(TransactionError::ValidationError(
@@ -1378,6 +1549,29 @@ mod test {
         assert_eq!(features.flags, OutputFlags::empty());
     }
 
+    #[test]
+    fn encrypted_memo() {
+        let features = OutputFeatures::with_maturity(42);
+        let mut expected = Vec::new();
+        bincode::serialize_into(&mut expected, &(OutputFlags::empty(), 42u64)).unwrap();
+        assert_eq!(features.to_bytes(), expected);
+
+        let features = OutputFeatures::with_encrypted_memo(vec![1; MAX_OUTPUT_MEMO_SIZE]);
+        assert_ne!(features.to_bytes(), OutputFeatures::default().to_bytes());
+        assert!(features.verify_memo_size(MAX_OUTPUT_MEMO_SIZE).is_ok());
+        assert!(matches!(
+            features.verify_memo_size(0),
+            Err(TransactionError::OutputMemoTooLarge { size, max: 0 }) if size == MAX_OUTPUT_MEMO_SIZE
+        ));
+        assert!(OutputFeatures::default().verify_memo_size(0).is_ok());
+
+        let features = OutputFeatures::with_encrypted_memo(vec![1; MAX_OUTPUT_MEMO_SIZE + 1]);
+        assert!(matches!(
+            features.verify_memo_size(MAX_OUTPUT_MEMO_SIZE),
+            Err(TransactionError::OutputMemoTooLarge { size, .. }) if size == MAX_OUTPUT_MEMO_SIZE + 1
+        ));
+    }
+
     #[test]
     fn range_proof_verification() {
         let factories = CryptoFactories::new(32);
@@ -1555,7 +1749,9 @@ mod test {
         let (tx, _, _) = helpers::create_tx(5000.into(), 15.into(), 1, 2, 1, 4);
 
         let factories = CryptoFactories::default();
-        assert!(tx.validate_internal_consistency(false, &factories, None).is_ok());
+        assert!(tx
+            .validate_internal_consistency(false, &factories, None, None, None)
+            .is_ok());
     }
 
     #[test]
@@ -1568,7 +1764,9 @@ mod test {
         assert_eq!(tx.body.kernels().len(), 1);
 
         let factories = CryptoFactories::default();
-        assert!(tx.validate_internal_consistency(false, &factories, None).is_ok());
+        assert!(tx
+            .validate_internal_consistency(false, &factories, None, None, None)
+            .is_ok());
 
         let schema = txn_schema!(from: vec![outputs[1].clone()], to: vec![1 * T, 2 * T]);
         let (tx2, _outputs, _) = helpers::spend_utxos(schema);
@@ -1599,11 +1797,13 @@ mod test {
         }
 
         // Validate basis transaction where cut-through has not been applied.
-        assert!(tx3.validate_internal_consistency(false, &factories, None).is_ok());
+        assert!(tx3
+            .validate_internal_consistency(false, &factories, None, None, None)
+            .is_ok());
 
         // tx3_cut_through has manual cut-through, it should not be possible so this should fail
         assert!(tx3_cut_through
-            .validate_internal_consistency(false, &factories, None)
+            .validate_internal_consistency(false, &factories, None, None, None)
             .is_err());
     }
 
@@ -1641,7 +1841,9 @@ mod test {
         tx.body.inputs_mut()[0].input_data = stack;
 
         let factories = CryptoFactories::default();
-        let err = tx.validate_internal_consistency(false, &factories, None).unwrap_err();
+        let err = tx
+            .validate_internal_consistency(false, &factories, None, None, None)
+            .unwrap_err();
         assert!(matches!(err, TransactionError::InvalidSignatureError(_)));
     }
 
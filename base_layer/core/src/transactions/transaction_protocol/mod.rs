@@ -128,13 +128,16 @@ pub enum TransactionProtocolError {
     ScriptOffsetPrivateKeyNotFound,
 }
 
-/// Transaction metadata, including the fee and lock height
+/// Transaction metadata, including the fee, lock height and burnt amount
 #[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
 pub struct TransactionMetadata {
     /// The absolute fee for the transaction
     pub fee: MicroTari,
     /// The earliest block this transaction can be mined
     pub lock_height: u64,
+    /// The amount burnt by the transaction
+    #[serde(default)]
+    pub burn_amount: MicroTari,
 }
 
 #[derive(Debug, Clone)]
@@ -146,10 +149,17 @@ pub struct RewindData {
 
 /// Convenience function that calculates the challenge for the Schnorr signatures
 pub fn build_challenge(sum_public_nonces: &PublicKey, metadata: &TransactionMetadata) -> MessageHash {
-    Challenge::new()
+    let challenge = Challenge::new()
         .chain(sum_public_nonces.as_bytes())
         .chain(&u64::from(metadata.fee).to_le_bytes())
-        .chain(&metadata.lock_height.to_le_bytes())
-        .finalize()
-        .to_vec()
+        .chain(&metadata.lock_height.to_le_bytes());
+    // The burn amount is only committed to when it is set, so that signatures of all other transactions are unchanged
+    if metadata.burn_amount > MicroTari(0) {
+        challenge
+            .chain(&u64::from(metadata.burn_amount).to_le_bytes())
+            .finalize()
+            .to_vec()
+    } else {
+        challenge.finalize().to_vec()
+    }
 }
@@ -236,6 +236,7 @@ mod test {
         let m = TransactionMetadata {
             fee: MicroTari(125),
             lock_height: 0,
+            ..Default::default()
         };
         let script = TariScript::default();
         let features = OutputFeatures::default();
@@ -284,6 +285,7 @@ mod test {
         let m = TransactionMetadata {
             fee: MicroTari(125),
             lock_height: 0,
+            ..Default::default()
         };
         let script = TariScript::default();
         let features = OutputFeatures::default();
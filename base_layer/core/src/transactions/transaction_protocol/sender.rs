@@ -47,7 +47,15 @@ use crate::transactions::{
 use digest::Digest;
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use tari_common_types::types::{BlindingFactor, ComSignature, PrivateKey, PublicKey, RangeProofService, Signature};
+use tari_common_types::types::{
+    BlindingFactor,
+    ComSignature,
+    HashOutput,
+    PrivateKey,
+    PublicKey,
+    RangeProofService,
+    Signature,
+};
 use tari_crypto::{
     keys::PublicKey as PublicKeyTrait,
     ristretto::pedersen::{PedersenCommitment, PedersenCommitmentFactory},
@@ -476,13 +484,16 @@ impl SenderTransactionProtocol {
         let mut s_agg = info.signatures[0].clone();
         info.signatures.iter().skip(1).for_each(|s| s_agg = &s_agg + s);
         let excess = PedersenCommitment::from_public_key(&info.public_excess);
-        let kernel = KernelBuilder::new()
+        let mut kernel_builder = KernelBuilder::new()
             .with_fee(info.metadata.fee)
             .with_features(features)
             .with_lock_height(info.metadata.lock_height)
             .with_excess(&excess)
-            .with_signature(&s_agg)
-            .build()?;
+            .with_signature(&s_agg);
+        if info.metadata.burn_amount > MicroTari(0) {
+            kernel_builder = kernel_builder.with_burn_amount(info.metadata.burn_amount);
+        }
+        let kernel = kernel_builder.build()?;
         tx_builder.with_kernel(kernel);
         tx_builder.build(factories).map_err(TPE::from)
     }
@@ -540,7 +551,16 @@ impl SenderTransactionProtocol {
     /// formally validate the transaction terms (no inflation, signature matches etc). If any step fails,
     /// the transaction protocol moves to Failed state and we are done; you can't rescue the situation. The function
     /// returns `Ok(false)` in this instance.
-    pub fn finalize(&mut self, features: KernelFeatures, factories: &CryptoFactories) -> Result<(), TPE> {
+    ///
+    /// The optional `prev_header` and `height` are used as the context when executing the input scripts, and should be
+    /// provided when any of the inputs' scripts depend on the block height (e.g. time locked contracts).
+    pub fn finalize(
+        &mut self,
+        features: KernelFeatures,
+        factories: &CryptoFactories,
+        prev_header: Option<HashOutput>,
+        height: Option<u64>,
+    ) -> Result<(), TPE> {
         // Create the final aggregated signature, moving to the Failed state if anything goes wrong
         match &mut self.state {
             SenderState::Finalizing(_) => {
@@ -563,7 +583,7 @@ impl SenderTransactionProtocol {
                 }
                 let transaction = result.unwrap();
                 let result = transaction
-                    .validate_internal_consistency(true, factories, None)
+                    .validate_internal_consistency(true, factories, None, prev_header, height)
                     .map_err(TPE::TransactionBuildError);
                 if let Err(e) = result {
                     self.state = SenderState::Failed(e.clone());
@@ -827,7 +847,7 @@ mod test {
         let mut sender = builder.build::<Blake256>(&factories).unwrap();
         assert!(!sender.is_failed());
         assert!(sender.is_finalizing());
-        match sender.finalize(KernelFeatures::empty(), &factories) {
+        match sender.finalize(KernelFeatures::empty(), &factories, None, None) {
             Ok(_) => (),
             Err(e) => panic!("{:?}", e),
         }
@@ -835,6 +855,41 @@ mod test {
         assert_eq!(tx.offset, p1.offset + p2.offset);
     }
 
+    #[test]
+    fn burn_with_change() {
+        let factories = CryptoFactories::default();
+        let p = TestParams::new();
+        let (utxo, input) = create_test_input(MicroTari(1200), 0, &factories.commitment);
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(MicroTari(10))
+            .with_burn_amount(MicroTari(300))
+            .with_offset(p.offset.clone())
+            .with_private_nonce(p.nonce.clone())
+            .with_change_secret(p.change_spend_key.clone())
+            .with_change_script(script!(Nop), ExecutionStack::default(), PrivateKey::random(&mut OsRng))
+            .with_input(utxo, input);
+        let mut sender = builder.build::<Blake256>(&factories).unwrap();
+        assert!(sender.is_finalizing());
+        sender
+            .finalize(KernelFeatures::empty(), &factories, None, None)
+            .unwrap();
+        let tx = sender.get_transaction().unwrap();
+        let kernel = &tx.body.kernels()[0];
+        assert!(kernel.is_burn());
+        assert_eq!(kernel.burn_amount, MicroTari(300));
+        assert_eq!(tx.body.get_total_burnt().unwrap(), MicroTari(300));
+        assert_eq!(tx.body.outputs().len(), 1);
+        assert_eq!(
+            tx.body.outputs()[0].commitment,
+            factories.commitment.commit_value(
+                &p.change_spend_key,
+                (MicroTari(1200) - MicroTari(300) - kernel.fee).as_u64()
+            )
+        );
+    }
+
     #[test]
     fn single_recipient_no_change() {
         let factories = CryptoFactories::default();
@@ -845,7 +900,7 @@ mod test {
         let (utxo, input) = create_test_input(MicroTari(1200), 0, &factories.commitment);
         let script = script!(Nop);
         let mut builder = SenderTransactionProtocol::builder(1);
-        let fee = Fee::calculate(MicroTari(20), 1, 1, 1);
+        let fee = Fee::calculate(MicroTari(20), 1, 1, 1, 0);
         let features = OutputFeatures::default();
         builder
             .with_lock_height(0)
@@ -875,7 +930,7 @@ mod test {
             .unwrap();
         // Transaction should be complete
         assert!(alice.is_finalizing());
-        match alice.finalize(KernelFeatures::empty(), &factories) {
+        match alice.finalize(KernelFeatures::empty(), &factories, None, None) {
             Ok(_) => (),
             Err(e) => panic!("{:?}", e),
         };
@@ -904,7 +959,7 @@ mod test {
         let (utxo, input) = create_test_input(MicroTari(25000), 0, &factories.commitment);
         let mut builder = SenderTransactionProtocol::builder(1);
         let script = script!(Nop);
-        let fee = Fee::calculate(MicroTari(20), 1, 1, 2);
+        let fee = Fee::calculate(MicroTari(20), 1, 1, 2, 0);
         let features = OutputFeatures::default();
         builder
             .with_lock_height(0)
@@ -956,7 +1011,7 @@ mod test {
             .unwrap();
         // Transaction should be complete
         assert!(alice.is_finalizing());
-        match alice.finalize(KernelFeatures::empty(), &factories) {
+        match alice.finalize(KernelFeatures::empty(), &factories, None, None) {
             Ok(_) => (),
             Err(e) => panic!("{:?}", e),
         };
@@ -970,7 +1025,7 @@ mod test {
         assert_eq!(tx.body.outputs().len(), 2);
         assert!(tx
             .clone()
-            .validate_internal_consistency(false, &factories, None)
+            .validate_internal_consistency(false, &factories, None, None, None)
             .is_ok());
     }
 
@@ -1160,7 +1215,7 @@ mod test {
             .unwrap();
         // Transaction should be complete
         assert!(alice.is_finalizing());
-        match alice.finalize(KernelFeatures::empty(), &factories) {
+        match alice.finalize(KernelFeatures::empty(), &factories, None, None) {
             Ok(_) => (),
             Err(e) => panic!("{:?}", e),
         };
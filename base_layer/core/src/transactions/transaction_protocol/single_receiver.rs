@@ -186,6 +186,7 @@ mod test {
         let m = TransactionMetadata {
             fee: MicroTari(100),
             lock_height: 0,
+            ..Default::default()
         };
         let script_offset_secret_key = PrivateKey::random(&mut OsRng);
         let sender_offset_public_key = PublicKey::from_secret_key(&script_offset_secret_key);
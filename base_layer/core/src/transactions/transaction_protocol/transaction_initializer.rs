@@ -74,6 +74,7 @@ pub struct SenderTransactionInitializer {
     amounts: FixedSet<MicroTari>,
     lock_height: Option<u64>,
     fee_per_gram: Option<MicroTari>,
+    burn_amount: MicroTari,
     inputs: Vec<TransactionInput>,
     unblinded_inputs: Vec<UnblindedOutput>,
     sender_custom_outputs: Vec<UnblindedOutput>,
@@ -114,6 +115,7 @@ impl SenderTransactionInitializer {
             amounts: FixedSet::new(num_recipients),
             lock_height: None,
             fee_per_gram: None,
+            burn_amount: MicroTari(0),
             inputs: Vec::new(),
             unblinded_inputs: Vec::new(),
             sender_custom_outputs: Vec::new(),
@@ -144,6 +146,13 @@ impl SenderTransactionInitializer {
         self
     }
 
+    /// Set the amount that the transaction burns. The burnt amount is removed from the supply and the transaction kernel
+    /// will be a burn kernel.
+    pub fn with_burn_amount(&mut self, burn_amount: MicroTari) -> &mut Self {
+        self.burn_amount = burn_amount;
+        self
+    }
+
     /// Set the amount to pay to the ith recipient. This method will silently fail if `receiver_index` >= num_receivers.
     pub fn with_amount(&mut self, receiver_index: usize, amount: MicroTari) -> &mut Self {
         self.amounts.set_item(receiver_index, amount);
@@ -278,11 +287,25 @@ impl SenderTransactionInitializer {
         let total_to_self = self.sender_custom_outputs.iter().map(|o| o.value).sum::<MicroTari>();
         let total_amount = self.amounts.sum().ok_or("Not all amounts have been provided")?;
         let fee_per_gram = self.fee_per_gram.ok_or("Fee per gram was not provided")?;
-        let fee_without_change = Fee::calculate(fee_per_gram, 1, num_inputs, num_outputs);
-        let fee_with_change = Fee::calculate(fee_per_gram, 1, num_inputs, num_outputs + 1);
+        // The change output never carries a memo, so only the recipient and custom outputs add to the memo weight
+        let memo_size = self
+            .recipient_output_features
+            .clone()
+            .into_vec()
+            .iter()
+            .chain(self.sender_custom_outputs.iter().map(|o| &o.features))
+            .map(|f| f.encrypted_memo.len())
+            .sum();
+        let fee_without_change = Fee::calculate(fee_per_gram, 1, num_inputs, num_outputs, memo_size);
+        let fee_with_change = Fee::calculate(fee_per_gram, 1, num_inputs, num_outputs + 1, memo_size);
         let extra_fee = fee_with_change - fee_without_change;
         // Subtract with a check on going negative
-        let change_amount = total_being_spent.checked_sub(total_to_self + total_amount + fee_without_change);
+        let total_spent = total_to_self
+            .checked_add(total_amount)
+            .and_then(|total| total.checked_add(self.burn_amount))
+            .and_then(|total| total.checked_add(fee_without_change))
+            .ok_or("The amounts to spend overflowed")?;
+        let change_amount = total_being_spent.checked_sub(total_spent);
         match change_amount {
             None => Err("You are spending more than you're providing".into()),
             Some(MicroTari(0)) => Ok((fee_without_change, MicroTari(0), None)),
@@ -547,6 +570,7 @@ impl SenderTransactionInitializer {
             metadata: TransactionMetadata {
                 fee: total_fee,
                 lock_height: self.lock_height.unwrap(),
+                burn_amount: self.burn_amount,
             },
             inputs: self.inputs,
             outputs,
@@ -642,7 +666,7 @@ mod test {
                 PrivateKey::random(&mut OsRng),
             )
             .with_change_script(script, ExecutionStack::default(), PrivateKey::default());
-        let expected_fee = Fee::calculate(MicroTari(20), 1, 1, 2);
+        let expected_fee = Fee::calculate(MicroTari(20), 1, 1, 2, 0);
         // We needed a change input, so this should fail
         let err = builder.build::<Blake256>(&factories).unwrap_err();
         assert_eq!(err.message, "Change spending key was not provided");
@@ -671,7 +695,7 @@ mod test {
         let factories = CryptoFactories::default();
         let p = TestParams::new();
         let (utxo, input) = create_test_input(MicroTari(500), 0, &factories.commitment);
-        let expected_fee = Fee::calculate(MicroTari(20), 1, 1, 1);
+        let expected_fee = Fee::calculate(MicroTari(20), 1, 1, 1, 0);
 
         let output = create_unblinded_output(
             TariScript::default(),
@@ -894,7 +918,7 @@ mod test {
         let weight = MicroTari(30);
 
         let script = script!(Nop);
-        let expected_fee = Fee::calculate(weight, 1, 2, 3);
+        let expected_fee = Fee::calculate(weight, 1, 2, 3, 0);
         let output = create_unblinded_output(
             script.clone(),
             OutputFeatures::default(),
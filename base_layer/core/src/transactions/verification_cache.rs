@@ -0,0 +1,307 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::transactions::transaction::{TransactionError, TransactionInput, TransactionKernel, TransactionOutput};
+use blake2::Digest;
+use lru::LruCache;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+        MutexGuard,
+    },
+};
+use tari_common_types::types::{CommitmentFactory, HashDigest, HashOutput, PublicKey};
+use tari_crypto::{
+    script::ScriptContext,
+    tari_utilities::{ByteArray, Hashable},
+};
+
+/// Hit and miss counts of the verification cache
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerificationCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of cached kernels, outputs and script signatures
+    pub size: usize,
+    pub capacity: usize,
+}
+
+impl VerificationCacheStats {
+    /// The fraction of lookups that were served from the cache, or 0 if there were no lookups
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl fmt::Display for VerificationCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} entries, {} hits, {} misses ({:.1}% hit rate)",
+            self.size,
+            self.capacity,
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+struct VerifiedEntries {
+    kernels: LruCache<HashOutput, ()>,
+    outputs: LruCache<HashOutput, ()>,
+    script_signatures: LruCache<HashOutput, ()>,
+}
+
+/// An LRU cache of the kernels, outputs and input script signatures that have already passed their cryptographic
+/// checks, so that the components of a transaction that was verified when it entered the mempool are not verified
+/// again when the block containing it arrives.
+///
+/// Each entry is keyed by a hash over all the data its check covers, so a cached result holds for that component on
+/// any chain. Checks that depend on the chain, i.e. script execution, UTXO membership and maturity, are never cached,
+/// which is why nothing has to be invalidated when the chain reorgs.
+pub struct VerificationCache {
+    entries: Option<Mutex<VerifiedEntries>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl VerificationCache {
+    /// Creates a cache of up to `capacity` kernels, outputs and script signatures each. A capacity of 0 disables the
+    /// cache.
+    pub fn new(capacity: usize) -> Self {
+        let entries = if capacity == 0 {
+            None
+        } else {
+            Some(Mutex::new(VerifiedEntries {
+                kernels: LruCache::new(capacity),
+                outputs: LruCache::new(capacity),
+                script_signatures: LruCache::new(capacity),
+            }))
+        };
+        Self {
+            entries,
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Verifies the excess signature of the kernel, unless it has been verified before
+    pub fn verify_kernel_signature(&self, kernel: &TransactionKernel) -> Result<(), TransactionError> {
+        let entries = match self.entries.as_ref() {
+            Some(entries) => entries,
+            None => return kernel.verify_signature(),
+        };
+        let key = kernel.hash();
+        if self.lookup(&mut acquire(entries).kernels, &key) {
+            return Ok(());
+        }
+        kernel.verify_signature()?;
+        acquire(entries).kernels.put(key, ());
+        Ok(())
+    }
+
+    /// Returns the outputs of which the range proof and metadata signature have not been verified before
+    pub fn unverified_outputs<'a>(&self, outputs: &'a [TransactionOutput]) -> Vec<&'a TransactionOutput> {
+        let entries = match self.entries.as_ref() {
+            Some(entries) => entries,
+            None => return outputs.iter().collect(),
+        };
+        let mut entries = acquire(entries);
+        outputs
+            .iter()
+            .filter(|output| !self.lookup(&mut entries.outputs, &output_key(output)))
+            .collect()
+    }
+
+    /// Records that the range proofs and metadata signatures of the outputs are valid
+    pub fn set_outputs_verified<'a, I: IntoIterator<Item = &'a TransactionOutput>>(&self, outputs: I) {
+        if let Some(entries) = self.entries.as_ref() {
+            let mut entries = acquire(entries);
+            for output in outputs {
+                entries.outputs.put(output_key(output), ());
+            }
+        }
+    }
+
+    /// Runs the script of the input and verifies its script signature, unless the signature has been verified before
+    /// for the same script public key. The script is always executed because its result depends on the context.
+    pub fn run_and_verify_script(
+        &self,
+        input: &TransactionInput,
+        factory: &CommitmentFactory,
+        context: ScriptContext,
+    ) -> Result<PublicKey, TransactionError> {
+        let entries = match self.entries.as_ref() {
+            Some(entries) => entries,
+            None => return input.run_and_verify_script(factory, Some(context)),
+        };
+        let script_key = input.run_script(Some(context))?;
+        let key = script_signature_key(input, &script_key);
+        if !self.lookup(&mut acquire(entries).script_signatures, &key) {
+            input.validate_script_signature(&script_key, factory)?;
+            acquire(entries).script_signatures.put(key, ());
+        }
+        Ok(script_key)
+    }
+
+    pub fn clear(&self) {
+        if let Some(entries) = self.entries.as_ref() {
+            let mut entries = acquire(entries);
+            entries.kernels.clear();
+            entries.outputs.clear();
+            entries.script_signatures.clear();
+        }
+    }
+
+    pub fn stats(&self) -> VerificationCacheStats {
+        VerificationCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            size: self
+                .entries
+                .as_ref()
+                .map(|entries| {
+                    let entries = acquire(entries);
+                    entries.kernels.len() + entries.outputs.len() + entries.script_signatures.len()
+                })
+                .unwrap_or(0),
+            capacity: self.capacity * 3,
+        }
+    }
+
+    fn lookup(&self, cache: &mut LruCache<HashOutput, ()>, key: &HashOutput) -> bool {
+        let hit = cache.get(key).is_some();
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        hit
+    }
+}
+
+/// The output hash does not cover the range proof, metadata signature or sender offset public key, so they are added
+/// to the key
+fn output_key(output: &TransactionOutput) -> HashOutput {
+    HashDigest::new()
+        .chain(output.hash())
+        .chain(output.witness_hash())
+        .chain(output.sender_offset_public_key.as_bytes())
+        .finalize()
+        .to_vec()
+}
+
+fn script_signature_key(input: &TransactionInput, script_key: &PublicKey) -> HashOutput {
+    HashDigest::new()
+        .chain(input.hash())
+        .chain(script_key.as_bytes())
+        .finalize()
+        .to_vec()
+}
+
+fn acquire(entries: &Mutex<VerifiedEntries>) -> MutexGuard<'_, VerifiedEntries> {
+    // The cache only holds the results of checks that passed, so a poisoned lock is not a reason to fail
+    entries.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transactions::{helpers, CryptoFactories};
+
+    #[test]
+    fn it_caches_verified_components() {
+        let factories = CryptoFactories::default();
+        let (tx, _, _) = helpers::create_tx(5000.into(), 15.into(), 1, 2, 1, 4);
+        let cache = VerificationCache::new(100);
+
+        tx.validate_internal_consistency_with_cache(false, &factories, None, None, None, Some(&cache))
+            .unwrap();
+        let num_components = tx.body.kernels().len() + tx.body.outputs().len() + tx.body.inputs().len();
+        assert_eq!(cache.stats().size, num_components);
+        assert_eq!(cache.stats().hits, 0);
+        assert!(cache.unverified_outputs(tx.body.outputs()).is_empty());
+
+        tx.validate_internal_consistency_with_cache(false, &factories, None, None, None, Some(&cache))
+            .unwrap();
+        assert_eq!(cache.stats().size, num_components);
+        // The unverified_outputs call above accounts for the extra output hits
+        assert_eq!(cache.stats().hits as usize, num_components + tx.body.outputs().len());
+    }
+
+    #[test]
+    fn it_does_not_cache_outputs_when_range_proofs_are_bypassed() {
+        let factories = CryptoFactories::default();
+        let (tx, _, _) = helpers::create_tx(5000.into(), 15.into(), 1, 2, 1, 4);
+        let cache = VerificationCache::new(100);
+
+        tx.validate_internal_consistency_with_cache(true, &factories, None, None, None, Some(&cache))
+            .unwrap();
+        assert_eq!(
+            cache.unverified_outputs(tx.body.outputs()).len(),
+            tx.body.outputs().len()
+        );
+    }
+
+    #[test]
+    fn it_verifies_changed_outputs_again() {
+        let factories = CryptoFactories::default();
+        let (tx, _, _) = helpers::create_tx(5000.into(), 15.into(), 1, 2, 1, 4);
+        let cache = VerificationCache::new(100);
+        tx.validate_internal_consistency_with_cache(false, &factories, None, None, None, Some(&cache))
+            .unwrap();
+
+        let mut tampered = tx.clone();
+        let proof = tampered.body.outputs()[1].proof.clone();
+        tampered.body.outputs_mut()[0].proof = proof;
+        assert_eq!(cache.unverified_outputs(tampered.body.outputs()).len(), 1);
+        tampered
+            .validate_internal_consistency_with_cache(false, &factories, None, None, None, Some(&cache))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn it_is_disabled_with_zero_capacity() {
+        let factories = CryptoFactories::default();
+        let (tx, _, _) = helpers::create_tx(5000.into(), 15.into(), 1, 2, 1, 4);
+        let cache = VerificationCache::new(0);
+
+        tx.validate_internal_consistency_with_cache(false, &factories, None, None, None, Some(&cache))
+            .unwrap();
+        assert_eq!(cache.stats(), VerificationCacheStats::default());
+        assert_eq!(
+            cache.unverified_outputs(tx.body.outputs()).len(),
+            tx.body.outputs().len()
+        );
+    }
+}
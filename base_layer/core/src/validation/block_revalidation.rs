@@ -0,0 +1,302 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use croaring::Bitmap;
+use log::*;
+use tari_common_types::types::{HashDigest, HashOutput, PublicKey};
+use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::PublicKey as PublicKeyTrait};
+use tari_mmr::{pruned_hashset::PrunedHashSet, MerkleMountainRange, MutableMmr};
+
+use crate::{
+    blocks::Block,
+    chain_storage::{BlockchainBackend, BlockchainDatabase, ChainStorageError},
+    consensus::ConsensusManager,
+    crypto::tari_utilities::{hex::Hex, Hashable},
+    proof_of_work::randomx_factory::RandomXFactory,
+    transactions::CryptoFactories,
+    validation::{helpers, ValidationError},
+};
+
+const LOG_TARGET: &str = "c::val::block_revalidation";
+
+/// A stage of block validation that is timed separately when revalidating historical blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RevalidationStage {
+    /// The proof of work data and achieved difficulty of the header
+    ProofOfWork,
+    /// Kernel excess and output metadata signatures
+    Signatures,
+    RangeProofs,
+    /// Input script execution and the script offset
+    Scripts,
+    /// The kernel sum, including the coinbase and fees
+    Balance,
+    /// The kernel, witness and input MMR roots and the MMR sizes
+    MmrRoots,
+}
+
+impl RevalidationStage {
+    pub const ALL: [RevalidationStage; 6] = [
+        RevalidationStage::ProofOfWork,
+        RevalidationStage::Signatures,
+        RevalidationStage::RangeProofs,
+        RevalidationStage::Scripts,
+        RevalidationStage::Balance,
+        RevalidationStage::MmrRoots,
+    ];
+}
+
+impl fmt::Display for RevalidationStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use RevalidationStage::*;
+        match self {
+            ProofOfWork => write!(f, "Proof of work"),
+            Signatures => write!(f, "Signatures"),
+            RangeProofs => write!(f, "Range proofs"),
+            Scripts => write!(f, "Scripts"),
+            Balance => write!(f, "Balance"),
+            MmrRoots => write!(f, "MMR roots"),
+        }
+    }
+}
+
+/// The time taken by each validation stage of a block and the first stage that failed, if any
+#[derive(Debug)]
+pub struct BlockRevalidation {
+    pub height: u64,
+    pub hash: HashOutput,
+    pub timings: Vec<(RevalidationStage, Duration)>,
+    pub failure: Option<(RevalidationStage, ValidationError)>,
+}
+
+impl BlockRevalidation {
+    pub fn is_valid(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Re-runs the validation of blocks that are already in the main chain, e.g. to audit the chain or to measure
+/// validation performance.
+///
+/// Stateful checks are made against the chain as it was before each block: the MMR roots are rebuilt from the
+/// previous block's accumulated data. The output MMR root commits to the spent outputs at the time of the block, which
+/// are not stored per block, so only the output MMR size is checked. Use `verify_mmr_roots` for the output root at the
+/// tip. The genesis block only has its signatures, range proofs and MMR roots checked. Blocks with pruned outputs
+/// cannot be revalidated.
+pub struct BlockRevalidator<B> {
+    db: BlockchainDatabase<B>,
+    rules: ConsensusManager,
+    factories: CryptoFactories,
+    randomx_factory: RandomXFactory,
+}
+
+impl<B: BlockchainBackend> BlockRevalidator<B> {
+    pub fn new(db: BlockchainDatabase<B>, rules: ConsensusManager, randomx_factory: RandomXFactory) -> Self {
+        Self {
+            db,
+            rules,
+            factories: CryptoFactories::default(),
+            randomx_factory,
+        }
+    }
+
+    /// Revalidates the main chain block at `height`, stopping at the first stage that fails
+    pub fn revalidate(&self, height: u64) -> Result<BlockRevalidation, ChainStorageError> {
+        let historical_block = self.db.fetch_block(height)?;
+        let hash = historical_block.hash().clone();
+        let target_difficulty = historical_block.accumulated_data.target_difficulty;
+        let block = historical_block.try_into_block()?;
+        let (prev_kernels, prev_witnesses) = if height == 0 {
+            (PrunedHashSet::default(), PrunedHashSet::default())
+        } else {
+            let (kernels, _, range_proofs, _) = self
+                .db
+                .fetch_block_accumulated_data(block.header.prev_hash.clone())?
+                .dissolve();
+            (kernels, range_proofs)
+        };
+
+        let mut stages: Vec<(RevalidationStage, Box<dyn FnOnce() -> Result<(), ValidationError> + '_>)> = Vec::new();
+        if height > 0 {
+            stages.push((
+                RevalidationStage::ProofOfWork,
+                Box::new(|| {
+                    let db = self.db.db_read_access()?;
+                    helpers::check_pow_data(&block.header, &self.rules, &*db)?;
+                    helpers::check_target_difficulty(&block.header, target_difficulty, &self.randomx_factory)?;
+                    Ok(())
+                }),
+            ));
+        }
+        stages.push((
+            RevalidationStage::Signatures,
+            Box::new(|| {
+                block.body.verify_kernel_signatures()?;
+                block.body.verify_burn_kernels()?;
+                block.body.verify_metadata_signatures()?;
+                Ok(())
+            }),
+        ));
+        stages.push((
+            RevalidationStage::RangeProofs,
+            Box::new(|| Ok(block.body.validate_range_proofs(&self.factories.range_proof)?)),
+        ));
+        if height > 0 {
+            stages.push((
+                RevalidationStage::Scripts,
+                Box::new(|| {
+                    let script_offset = PublicKey::from_secret_key(&block.header.total_script_offset);
+                    block.body.validate_script_offset(
+                        script_offset,
+                        &self.factories.commitment,
+                        Some(block.header.prev_hash.clone()),
+                        Some(height),
+                        None,
+                    )?;
+                    Ok(())
+                }),
+            ));
+            stages.push((
+                RevalidationStage::Balance,
+                Box::new(|| {
+                    let total_coinbase = self.rules.calculate_coinbase_and_fees(height, block.body.kernels());
+                    let offset_and_reward = self
+                        .factories
+                        .commitment
+                        .commit_value(&block.header.total_kernel_offset, total_coinbase.0);
+                    block
+                        .body
+                        .validate_kernel_sum(offset_and_reward, &self.factories.commitment)?;
+                    Ok(())
+                }),
+            ));
+        }
+        stages.push((
+            RevalidationStage::MmrRoots,
+            Box::new(|| check_historical_mmr_roots(&block, prev_kernels, prev_witnesses)),
+        ));
+
+        let mut revalidation = BlockRevalidation {
+            height,
+            hash,
+            timings: Vec::with_capacity(stages.len()),
+            failure: None,
+        };
+        for (stage, check) in stages {
+            let timer = Instant::now();
+            let result = check();
+            revalidation.timings.push((stage, timer.elapsed()));
+            if let Err(err) = result {
+                warn!(
+                    target: LOG_TARGET,
+                    "Revalidation of block #{} ({}) failed at stage '{}': {}",
+                    height,
+                    revalidation.hash.to_hex(),
+                    stage,
+                    err
+                );
+                revalidation.failure = Some((stage, err));
+                break;
+            }
+        }
+        Ok(revalidation)
+    }
+}
+
+/// Checks the kernel, witness and input MMR roots and the MMR sizes of a block, given the kernel and witness MMRs of
+/// the previous block
+fn check_historical_mmr_roots(
+    block: &Block,
+    prev_kernels: PrunedHashSet,
+    prev_witnesses: PrunedHashSet,
+) -> Result<(), ValidationError> {
+    let header = &block.header;
+    let mut kernel_mmr = MerkleMountainRange::<HashDigest, _>::new(prev_kernels);
+    let mut witness_mmr = MerkleMountainRange::<HashDigest, _>::new(prev_witnesses);
+    let mut input_mmr = MerkleMountainRange::<HashDigest, _>::new(PrunedHashSet::default());
+    for kernel in block.body.kernels() {
+        kernel_mmr.push(kernel.hash()).map_err(ValidationError::custom_error)?;
+    }
+    for output in block.body.outputs() {
+        witness_mmr
+            .push(output.witness_hash())
+            .map_err(ValidationError::custom_error)?;
+    }
+    for input in block.body.inputs() {
+        input_mmr.push(input.hash()).map_err(ValidationError::custom_error)?;
+    }
+    // Version 1 headers commit to the inputs as a mutable MMR, see `calculate_mmr_roots`
+    let input_mr = if header.version == 1 {
+        let hashes = input_mmr.get_pruned_hash_set().map_err(ValidationError::custom_error)?;
+        MutableMmr::<HashDigest, _>::new(hashes, Bitmap::create())
+            .and_then(|mmr| mmr.get_merkle_root())
+            .map_err(ValidationError::custom_error)?
+    } else {
+        input_mmr.get_merkle_root().map_err(ValidationError::custom_error)?
+    };
+
+    let roots = [
+        (
+            "Kernel",
+            &header.kernel_mr,
+            kernel_mmr.get_merkle_root().map_err(ValidationError::custom_error)?,
+        ),
+        (
+            "Witness",
+            &header.witness_mr,
+            witness_mmr.get_merkle_root().map_err(ValidationError::custom_error)?,
+        ),
+        ("Input", &header.input_mr, input_mr),
+    ];
+    for (tree, expected, actual) in roots {
+        if *expected != actual {
+            return Err(ValidationError::CustomError(format!(
+                "{} MMR root does not match the header (expected {}, got {})",
+                tree,
+                expected.to_hex(),
+                actual.to_hex()
+            )));
+        }
+    }
+
+    let kernel_mmr_size = kernel_mmr.get_leaf_count().map_err(ValidationError::custom_error)? as u64;
+    let output_mmr_size = witness_mmr.get_leaf_count().map_err(ValidationError::custom_error)? as u64;
+    let sizes = [
+        ("Kernel", header.kernel_mmr_size, kernel_mmr_size),
+        ("Output", header.output_mmr_size, output_mmr_size),
+    ];
+    for (tree, expected, actual) in sizes {
+        if expected != actual {
+            return Err(ValidationError::CustomError(format!(
+                "{} MMR size does not match the header (expected {}, got {})",
+                tree, expected, actual
+            )));
+        }
+    }
+    Ok(())
+}
@@ -28,7 +28,14 @@ use crate::{
     iterators::NonOverlappingIntegerPairIter,
     transactions::{
         aggregated_body::AggregateBody,
-        transaction::{KernelSum, TransactionError, TransactionInput, TransactionKernel, TransactionOutput},
+        transaction::{
+            KernelSum,
+            TransactionError,
+            TransactionInput,
+            TransactionKernel,
+            TransactionOutput,
+            RANGE_PROOF_CHUNK_SIZE,
+        },
         CryptoFactories,
     },
     validation::{
@@ -41,9 +48,9 @@ use crate::{
 use async_trait::async_trait;
 use futures::{stream::FuturesUnordered, StreamExt};
 use log::*;
-use std::{cmp, cmp::Ordering, thread, time::Instant};
+use std::{cmp, cmp::Ordering, convert::TryInto, thread, time::Instant};
 use tari_common_types::types::{Commitment, HashOutput, PublicKey};
-use tari_crypto::commitment::HomomorphicCommitmentFactory;
+use tari_crypto::{commitment::HomomorphicCommitmentFactory, script::ScriptContext};
 use tokio::task;
 
 /// This validator checks whether a block satisfies consensus rules.
@@ -81,19 +88,63 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
         Ok(block)
     }
 
+    /// A block is only assumed valid if it is below the assume-valid height and it is an ancestor of the checkpoint
+    /// that covers it. Since the header chain is linked by hash, this holds when the stored main chain header at the
+    /// checkpoint height matches the checkpoint and the stored header at the block height matches the block.
+    async fn is_assumed_valid(&self, header: &BlockHeader) -> Result<bool, ValidationError> {
+        let (checkpoint_height, checkpoint_hash) = match self.rules.checkpoints().assume_valid_checkpoint(header.height)
+        {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(false),
+        };
+        let is_checkpoint_in_chain = self
+            .db
+            .fetch_header(checkpoint_height)
+            .await?
+            .map(|h| h.hash() == *checkpoint_hash)
+            .unwrap_or(false);
+        if !is_checkpoint_in_chain {
+            return Ok(false);
+        }
+        let is_block_in_chain = self
+            .db
+            .fetch_header(header.height)
+            .await?
+            .map(|h| h.hash() == header.hash())
+            .unwrap_or(false);
+        Ok(is_block_in_chain)
+    }
+
     pub async fn validate_block_body(&self, block: Block) -> Result<Block, ValidationError> {
         let (valid_header, inputs, outputs, kernels) = block.dissolve();
+        // Blocks buried under a checkpoint are part of the checkpointed chain, so their range proofs and scripts are
+        // not verified
+        let is_assumed_valid = self.is_assumed_valid(&valid_header).await?;
+        if is_assumed_valid {
+            debug!(
+                target: LOG_TARGET,
+                "Block #{} is assumed valid, range proofs and scripts will not be verified", valid_header.height
+            );
+        }
+
+        let constants = self.rules.consensus_constants(valid_header.height);
+        helpers::check_burn_kernels(&kernels, constants)?;
+        helpers::check_output_memos(&outputs, constants)?;
 
         // Start all validation tasks concurrently
         let kernels_task = self.start_kernel_validation(&valid_header, kernels);
-        let inputs_task =
-            self.start_input_validation(&valid_header, outputs.iter().map(|o| o.hash()).collect(), inputs);
+        let inputs_task = self.start_input_validation(
+            &valid_header,
+            outputs.iter().map(|o| o.hash()).collect(),
+            inputs,
+            is_assumed_valid,
+        );
 
         // Output order cannot be checked concurrently so it is checked here first
         if !helpers::is_all_unique_and_sorted(&outputs) {
             return Err(ValidationError::UnsortedOrDuplicateOutput);
         }
-        let outputs_task = self.start_output_validation(&valid_header, outputs);
+        let outputs_task = self.start_output_validation(&valid_header, outputs, is_assumed_valid);
 
         // Wait for them to complete
         let outputs_result = outputs_task.await??;
@@ -110,11 +161,13 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
             outputs_result.coinbase(),
         )?;
 
-        helpers::check_script_offset(
-            &valid_header,
-            &outputs_result.aggregate_offset_pubkey,
-            &inputs_result.aggregate_input_key,
-        )?;
+        if !is_assumed_valid {
+            helpers::check_script_offset(
+                &valid_header,
+                &outputs_result.aggregate_offset_pubkey,
+                &inputs_result.aggregate_input_key,
+            )?;
+        }
 
         helpers::check_kernel_sum(
             &self.factories.commitment,
@@ -174,6 +227,7 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                 }
 
                 kernel.verify_signature()?;
+                kernel.verify_burn()?;
 
                 if kernel.is_coinbase() {
                     if coinbase_index.is_some() {
@@ -187,7 +241,14 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                 }
 
                 max_kernel_timelock = cmp::max(max_kernel_timelock, kernel.lock_height);
-                kernel_sum.fees += kernel.fee;
+                kernel_sum.fees = kernel_sum
+                    .fees
+                    .checked_add(kernel.fee)
+                    .ok_or(TransactionError::KernelSumOverflow)?;
+                kernel_sum.burnt = kernel_sum
+                    .burnt
+                    .checked_add(kernel.burn_amount)
+                    .ok_or(TransactionError::KernelSumOverflow)?;
                 kernel_sum.sum = &kernel_sum.sum + &kernel.excess;
             }
 
@@ -225,8 +286,10 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
         header: &BlockHeader,
         output_hashes: Vec<HashOutput>,
         inputs: Vec<TransactionInput>,
+        is_assumed_valid: bool,
     ) -> AbortOnDropJoinHandle<Result<InputValidationData, ValidationError>> {
         let block_height = header.height;
+        let prev_hash: [u8; 32] = header.prev_hash.as_slice().try_into().unwrap_or([0; 32]);
         let commitment_factory = self.factories.commitment.clone();
         let db = self.db.inner().clone();
         task::spawn_blocking(move || {
@@ -269,7 +332,11 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                 // the tari script
                 if not_found_inputs.is_empty() {
                     // lets count up the input script public keys
-                    aggregate_input_key = aggregate_input_key + input.run_and_verify_script(&commitment_factory)?;
+                    if !is_assumed_valid {
+                        let context = ScriptContext::new(block_height, &prev_hash, &input.commitment);
+                        aggregate_input_key =
+                            aggregate_input_key + input.run_and_verify_script(&commitment_factory, Some(context))?;
+                    }
                     commitment_sum = &commitment_sum + &input.commitment;
                 }
             }
@@ -297,13 +364,14 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
         &self,
         header: &BlockHeader,
         outputs: Vec<TransactionOutput>,
+        is_assumed_valid: bool,
     ) -> AbortOnDropJoinHandle<Result<OutputValidationData, ValidationError>> {
         let height = header.height;
         let num_outputs = outputs.len();
         let concurrency = cmp::min(self.concurrency, num_outputs);
         let output_chunks = into_enumerated_batches(outputs, concurrency);
-        let bypass_range_proof_verification = self.bypass_range_proof_verification;
-        if bypass_range_proof_verification {
+        let bypass_range_proof_verification = self.bypass_range_proof_verification || is_assumed_valid;
+        if self.bypass_range_proof_verification {
             warn!(target: LOG_TARGET, "Range proof verification will be bypassed!")
         }
 
@@ -347,14 +415,31 @@ impl<B: BlockchainBackend + 'static> BlockValidator<B> {
                         }
 
                         output.verify_metadata_signature()?;
-                        if !bypass_range_proof_verification {
-                            output.verify_range_proof(&range_proof_prover)?;
-                        }
 
                         helpers::check_not_duplicate_txo(&*db, output)?;
                         commitment_sum = &commitment_sum + &output.commitment;
                     }
 
+                    // Range proofs are the most expensive part of validating a block, so the range proofs of the
+                    // worker's outputs are spread over the rayon thread pool
+                    if !bypass_range_proof_verification {
+                        let timer = Instant::now();
+                        let outputs = outputs.iter().map(|(_, output)| output).collect::<Vec<_>>();
+                        TransactionOutput::verify_range_proofs_in_parallel(&range_proof_prover, &outputs).map_err(
+                            |err| {
+                                warn!(target: LOG_TARGET, "Block #{} failed to validate: {}", height, err);
+                                err
+                            },
+                        )?;
+                        debug!(
+                            target: LOG_TARGET,
+                            "Verified {} range proof(s) in parallel in chunks of {} in {:.2?}",
+                            outputs.len(),
+                            RANGE_PROOF_CHUNK_SIZE,
+                            timer.elapsed()
+                        );
+                    }
+
                     Ok((outputs, aggregate_sender_offset, commitment_sum, coinbase_index))
                 })
             })
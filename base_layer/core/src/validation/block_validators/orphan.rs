@@ -23,7 +23,7 @@ use super::LOG_TARGET;
 use crate::{
     blocks::Block,
     consensus::ConsensusManager,
-    transactions::CryptoFactories,
+    transactions::{verification_cache::VerificationCache, CryptoFactories},
     validation::{
         helpers,
         helpers::{check_accounting_balance, check_block_weight, check_coinbase_output, check_sorting_and_duplicates},
@@ -32,6 +32,7 @@ use crate::{
     },
 };
 use log::*;
+use std::sync::Arc;
 use tari_crypto::tari_utilities::{hash::Hashable, hex::Hex};
 
 /// This validator tests whether a candidate block is internally consistent
@@ -40,6 +41,7 @@ pub struct OrphanBlockValidator {
     rules: ConsensusManager,
     bypass_range_proof_verification: bool,
     factories: CryptoFactories,
+    verification_cache: Option<Arc<VerificationCache>>,
 }
 
 impl OrphanBlockValidator {
@@ -48,8 +50,15 @@ impl OrphanBlockValidator {
             rules,
             bypass_range_proof_verification,
             factories,
+            verification_cache: None,
         }
     }
+
+    /// Skips the checks of kernels and outputs that were already verified, e.g. by the mempool
+    pub fn with_verification_cache(mut self, verification_cache: Arc<VerificationCache>) -> Self {
+        self.verification_cache = Some(verification_cache);
+        self
+    }
 }
 
 impl OrphanValidation for OrphanBlockValidator {
@@ -91,6 +100,8 @@ impl OrphanValidation for OrphanBlockValidator {
         // Check that the inputs are are allowed to be spent
         helpers::check_maturity(height, block.body.inputs())?;
         helpers::check_kernel_lock_height(height, block.body.kernels())?;
+        helpers::check_burn_kernels(block.body.kernels(), self.rules.consensus_constants(height))?;
+        helpers::check_output_memos(block.body.outputs(), self.rules.consensus_constants(height))?;
         trace!(target: LOG_TARGET, "SV - Output constraints are ok for {} ", &block_id);
         check_coinbase_output(block, &self.rules, &self.factories)?;
         trace!(target: LOG_TARGET, "SV - Coinbase output is ok for {} ", &block_id);
@@ -99,6 +110,7 @@ impl OrphanValidation for OrphanBlockValidator {
             &self.rules,
             self.bypass_range_proof_verification,
             &self.factories,
+            self.verification_cache.as_deref(),
         )?;
         trace!(target: LOG_TARGET, "SV - accounting balance correct for {}", &block_id);
         debug!(
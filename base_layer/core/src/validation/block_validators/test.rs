@@ -21,7 +21,8 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    consensus::{ConsensusConstantsBuilder, ConsensusManager},
+    chain_storage::ChainBlock,
+    consensus::{Checkpoint, Checkpoints, ConsensusConstantsBuilder, ConsensusManager},
     test_helpers::{
         blockchain::{TempDatabase, TestBlockchain},
         BlockSpec,
@@ -39,6 +40,7 @@ use crate::{
 };
 use std::sync::Arc;
 use tari_common::configuration::Network;
+use tari_common_types::types::RangeProof;
 use tari_test_utils::unpack_enum;
 
 fn setup_with_rules(rules: ConsensusManager) -> (TestBlockchain, BlockValidator<TempDatabase>) {
@@ -57,6 +59,38 @@ fn setup() -> (TestBlockchain, BlockValidator<TempDatabase>) {
     setup_with_rules(ConsensusManager::builder(Network::LocalNet).build())
 }
 
+fn create_validator_with_checkpoint(
+    blockchain: &TestBlockchain,
+    height: u64,
+    hash: Vec<u8>,
+) -> BlockValidator<TempDatabase> {
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .with_checkpoints(
+            Checkpoints::new()
+                .with_checkpoint(Checkpoint { height, hash })
+                .with_assume_valid_before_height(height + 1),
+        )
+        .build();
+    BlockValidator::new(
+        blockchain.db().clone().into(),
+        rules,
+        CryptoFactories::default(),
+        false,
+        2,
+    )
+}
+
+fn create_block_with_invalid_range_proof(blockchain: &TestBlockchain, parent_name: &'static str) -> Arc<ChainBlock> {
+    let (mut block, _) = blockchain.create_unmined_block(parent_name, BlockSpec::new());
+    block.body.outputs_mut()[0].proof = RangeProof(vec![0u8; 32]);
+    blockchain.mine_block(parent_name, block, 1.into())
+}
+
+fn assert_range_proof_error(err: ValidationError) {
+    unpack_enum!(ValidationError::TransactionError(TransactionError::ValidationError(msg)) = err);
+    assert!(msg.starts_with("Range proof could not be verified"));
+}
+
 #[tokio::test]
 async fn it_passes_if_block_is_valid() {
     let (blockchain, validator) = setup();
@@ -187,3 +221,50 @@ async fn it_checks_txo_sort_order() {
     let err = validator.validate_block_body(block.block().clone()).await.unwrap_err();
     assert!(matches!(err, ValidationError::UnsortedOrDuplicateOutput));
 }
+
+#[tokio::test]
+async fn it_checks_range_proofs() {
+    let (blockchain, validator) = setup();
+
+    let block = create_block_with_invalid_range_proof(&blockchain, "GB");
+    let err = validator.validate_block_body(block.block().clone()).await.unwrap_err();
+    assert_range_proof_error(err);
+}
+
+#[tokio::test]
+async fn it_assumes_blocks_under_a_checkpoint_are_valid() {
+    let (mut blockchain, _) = setup();
+
+    let block = create_block_with_invalid_range_proof(&blockchain, "GB");
+    blockchain.append_block("A", block.clone());
+    let (checkpoint, _) = blockchain.add_next_tip("B", BlockSpec::default());
+
+    let validator = create_validator_with_checkpoint(&blockchain, 2, checkpoint.hash().clone());
+    validator.validate_block_body(block.block().clone()).await.unwrap();
+}
+
+#[tokio::test]
+async fn it_fully_validates_blocks_that_are_not_under_the_checkpoint() {
+    let (mut blockchain, _) = setup();
+
+    blockchain.add_next_tip("A", BlockSpec::default());
+    let (checkpoint, _) = blockchain.add_next_tip("B", BlockSpec::default());
+    let validator = create_validator_with_checkpoint(&blockchain, 2, checkpoint.hash().clone());
+
+    // A block on a fork below the checkpoint height
+    let fork_block = create_block_with_invalid_range_proof(&blockchain, "GB");
+    let err = validator
+        .validate_block_body(fork_block.block().clone())
+        .await
+        .unwrap_err();
+    assert_range_proof_error(err);
+
+    // A stored chain that does not contain the checkpoint
+    let (mut blockchain, _) = setup();
+    let block = create_block_with_invalid_range_proof(&blockchain, "GB");
+    blockchain.append_block("A", block.clone());
+    blockchain.add_next_tip("B", BlockSpec::default());
+    let validator = create_validator_with_checkpoint(&blockchain, 2, vec![0u8; 32]);
+    let err = validator.validate_block_body(block.block().clone()).await.unwrap_err();
+    assert_range_proof_error(err);
+}
@@ -60,7 +60,8 @@ impl<B: BlockchainBackend> FinalHorizonStateValidation<B> for ChainBalanceValida
         total_kernel_sum: &Commitment,
         backend: &B,
     ) -> Result<(), ValidationError> {
-        let emission_h = self.get_emission_commitment_at(height);
+        let total_burnt = backend.fetch_total_burnt(height)?;
+        let emission_h = self.get_emission_commitment_at(height, total_burnt);
         let total_offset = self.fetch_total_offset_commitment(height, backend)?;
 
         debug!(
@@ -89,14 +90,16 @@ impl<B: BlockchainBackend> ChainBalanceValidator<B> {
         Ok(self.factories.commitment.commit(offset, &0u64.into()))
     }
 
-    fn get_emission_commitment_at(&self, height: u64) -> Commitment {
+    /// Commits to the value that should remain in the UTXO set, i.e. the total emission less the value that has been
+    /// burnt.
+    fn get_emission_commitment_at(&self, height: u64, total_burnt: MicroTari) -> Commitment {
         let total_supply =
             self.rules.get_total_emission_at(height) + self.rules.consensus_constants(height).faucet_value();
         debug!(
             target: LOG_TARGET,
-            "Expected emission at height {} is {}", height, total_supply
+            "Expected emission at height {} is {} ({} burnt)", height, total_supply, total_burnt
         );
-        self.commit_value(total_supply)
+        self.commit_value(total_supply.saturating_sub(total_burnt))
     }
 
     #[inline]
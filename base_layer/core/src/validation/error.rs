@@ -85,6 +85,12 @@ pub enum ValidationError {
     IncorrectNextTipHeight { expected: u64, block_height: u64 },
     #[error("Expected block previous hash to be {expected}, but was {block_hash}")]
     IncorrectPreviousHash { expected: String, block_hash: String },
+    #[error("Block hash at checkpoint height {height} is {actual}, expected {expected}")]
+    CheckpointMismatch {
+        height: u64,
+        expected: String,
+        actual: String,
+    },
     #[error("Async validation task failed: {0}")]
     AsyncTaskFailed(#[from] task::JoinError),
 }
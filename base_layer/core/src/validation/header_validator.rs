@@ -4,7 +4,7 @@ use crate::{
     consensus::ConsensusManager,
     proof_of_work::AchievedTargetDifficulty,
     validation::{
-        helpers::{check_header_timestamp_greater_than_median, check_pow_data, check_timestamp_ftl},
+        helpers::{check_checkpoint, check_header_timestamp_greater_than_median, check_pow_data, check_timestamp_ftl},
         DifficultyCalculator,
         HeaderValidation,
         ValidationError,
@@ -54,6 +54,7 @@ impl HeaderValidator {
 impl<TBackend: BlockchainBackend> HeaderValidation<TBackend> for HeaderValidator {
     /// The consensus checks that are done (in order of cheapest to verify to most expensive):
     /// 1. Is the block timestamp within the Future Time Limit (FTL)?
+    /// 1. Does the block hash match the checkpoint at this height, if any?
     /// 1. Is the Proof of Work valid?
     /// 1. Is the achieved difficulty of this block >= the target difficulty for this block?
 
@@ -64,6 +65,7 @@ impl<TBackend: BlockchainBackend> HeaderValidation<TBackend> for HeaderValidator
         difficulty_calculator: &DifficultyCalculator,
     ) -> Result<AchievedTargetDifficulty, ValidationError> {
         check_timestamp_ftl(header, &self.rules)?;
+        check_checkpoint(header, &self.rules)?;
         let header_id = format!("header #{} ({})", header.height, header.hash().to_hex());
         trace!(
             target: LOG_TARGET,
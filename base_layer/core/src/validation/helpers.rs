@@ -46,6 +46,7 @@ use crate::{
     transactions::{
         tari_amount::MicroTari,
         transaction::{KernelSum, TransactionError, TransactionInput, TransactionKernel, TransactionOutput},
+        verification_cache::VerificationCache,
         CryptoFactories,
     },
     validation::ValidationError,
@@ -74,6 +75,30 @@ pub fn check_timestamp_ftl(
     Ok(())
 }
 
+/// This function tests that the block hash matches the configured checkpoint, if there is one at the block's height
+pub fn check_checkpoint(
+    block_header: &BlockHeader,
+    consensus_manager: &ConsensusManager,
+) -> Result<(), ValidationError> {
+    if let Some(expected) = consensus_manager.checkpoints().get(block_header.height) {
+        let hash = block_header.hash();
+        if hash != *expected {
+            warn!(
+                target: LOG_TARGET,
+                "Block #{} ({}) does not match the checkpoint",
+                block_header.height,
+                hash.to_hex()
+            );
+            return Err(ValidationError::CheckpointMismatch {
+                height: block_header.height,
+                expected: expected.to_hex(),
+                actual: hash.to_hex(),
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Returns the median timestamp for the provided timestamps.
 pub fn calc_median_timestamp(timestamps: &[EpochTime]) -> EpochTime {
     assert!(
@@ -217,6 +242,7 @@ pub fn check_accounting_balance(
     rules: &ConsensusManager,
     bypass_range_proof_verification: bool,
     factories: &CryptoFactories,
+    verification_cache: Option<&VerificationCache>,
 ) -> Result<(), ValidationError> {
     if block.header.height == 0 {
         // Gen block does not need to be checked for this.
@@ -227,12 +253,15 @@ pub fn check_accounting_balance(
     let total_coinbase = rules.calculate_coinbase_and_fees(block.header.height, block.body.kernels());
     block
         .body
-        .validate_internal_consistency(
+        .validate_internal_consistency_with_cache(
             offset,
             script_offset,
             bypass_range_proof_verification,
             total_coinbase,
             factories,
+            Some(block.header.prev_hash.clone()),
+            Some(block.header.height),
+            verification_cache,
         )
         .map_err(|err| {
             warn!(
@@ -527,9 +556,14 @@ pub fn check_kernel_sum(
     output_commitment_sum: &Commitment,
     input_commitment_sum: &Commitment,
 ) -> Result<(), ValidationError> {
-    let KernelSum { sum: excess, fees } = kernel_sum;
+    let KernelSum {
+        sum: excess,
+        fees,
+        burnt,
+    } = kernel_sum;
     let sum_io = output_commitment_sum - input_commitment_sum;
-    let fees = factory.commit_value(&Default::default(), fees.as_u64());
+    let fees_and_burnt = fees.checked_add(*burnt).ok_or(TransactionError::KernelSumOverflow)?;
+    let fees = factory.commit_value(&Default::default(), fees_and_burnt.as_u64());
     if *excess != &sum_io + &fees {
         return Err(TransactionError::ValidationError(
             "Sum of inputs and outputs did not equal sum of kernels with fees".into(),
@@ -560,6 +594,32 @@ pub fn check_kernel_lock_height(height: u64, kernels: &[TransactionKernel]) -> R
     Ok(())
 }
 
+/// Checks that the kernels only burn value if burn kernels are enabled by the consensus constants
+pub fn check_burn_kernels(
+    kernels: &[TransactionKernel],
+    consensus_constants: &ConsensusConstants,
+) -> Result<(), TransactionError> {
+    if !consensus_constants.burn_kernels_enabled() && kernels.iter().any(|k| k.is_burn()) {
+        return Err(TransactionError::InvalidBurnKernel(
+            "Burn kernels are not enabled at this height".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the encrypted memos of the outputs do not exceed the size allowed by the consensus constants
+pub fn check_output_memos(
+    outputs: &[TransactionOutput],
+    consensus_constants: &ConsensusConstants,
+) -> Result<(), TransactionError> {
+    for output in outputs {
+        output
+            .features
+            .verify_memo_size(consensus_constants.max_output_memo_size())?;
+    }
+    Ok(())
+}
+
 /// Checks that all inputs have matured at the given height
 pub fn check_maturity(height: u64, inputs: &[TransactionInput]) -> Result<(), TransactionError> {
     if let Some(input) = inputs.iter().find(|input| !input.is_mature_at(height)) {
@@ -659,6 +719,71 @@ mod test {
         }
     }
 
+    mod check_burn_kernels {
+        use super::*;
+        use crate::{
+            consensus::ConsensusConstantsBuilder,
+            transactions::{
+                helpers,
+                tari_amount::MicroTari,
+                transaction::{KernelFeatures, MAX_BURN_AMOUNT},
+            },
+        };
+        use tari_common::configuration::Network;
+
+        #[test]
+        fn it_only_allows_burn_kernels_when_enabled() {
+            let mut kernel = helpers::create_test_kernel(0.into(), 0);
+            kernel.features = KernelFeatures::create_burn();
+            kernel.burn_amount = 100.into();
+            let disabled = ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_burn_kernels_enabled(false)
+                .build();
+            let enabled = ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_burn_kernels_enabled(true)
+                .build();
+
+            assert!(matches!(
+                check_burn_kernels(&[kernel.clone()], &disabled),
+                Err(TransactionError::InvalidBurnKernel(_))
+            ));
+            assert_eq!(check_burn_kernels(&[kernel], &enabled), Ok(()));
+            assert_eq!(
+                check_burn_kernels(&[helpers::create_test_kernel(0.into(), 0)], &disabled),
+                Ok(())
+            );
+        }
+
+        #[test]
+        fn it_rejects_burn_amounts_that_would_overflow_the_kernel_sum() {
+            let mut kernel = helpers::create_test_kernel(1000.into(), 0);
+            kernel.features = KernelFeatures::create_burn();
+            kernel.burn_amount = MicroTari(u64::MAX - 900);
+            assert!(matches!(
+                kernel.verify_burn(),
+                Err(TransactionError::InvalidBurnKernel(_))
+            ));
+            kernel.burn_amount = MAX_BURN_AMOUNT;
+            assert_eq!(kernel.verify_burn(), Ok(()));
+
+            // Without the cap the fee and burnt amount would wrap around to 100 µT
+            let kernel_sum = KernelSum {
+                sum: Commitment::default(),
+                fees: MicroTari(1000),
+                burnt: MicroTari(u64::MAX - 900),
+            };
+            assert!(matches!(
+                check_kernel_sum(
+                    &CommitmentFactory::default(),
+                    &kernel_sum,
+                    &Commitment::default(),
+                    &Commitment::default()
+                ),
+                Err(ValidationError::TransactionError(TransactionError::KernelSumOverflow))
+            ));
+        }
+    }
+
     mod check_maturity {
         use super::*;
 
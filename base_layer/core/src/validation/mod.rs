@@ -42,6 +42,7 @@ pub use traits::{
     PostOrphanBodyValidation,
 };
 
+pub mod block_revalidation;
 pub mod block_validators;
 mod difficulty_calculator;
 pub use difficulty_calculator::*;
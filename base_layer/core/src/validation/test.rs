@@ -22,7 +22,7 @@
 
 use std::sync::Arc;
 
-use tari_crypto::{commitment::HomomorphicCommitment, script};
+use tari_crypto::{commitment::HomomorphicCommitment, keys::SecretKey, script};
 
 use tari_common::configuration::Network;
 
@@ -32,16 +32,26 @@ use crate::{
     consensus::{ConsensusConstantsBuilder, ConsensusManagerBuilder},
     crypto::tari_utilities::Hashable,
     proof_of_work::AchievedTargetDifficulty,
-    test_helpers::{blockchain::create_store_with_consensus, create_chain_header},
+    test_helpers::{
+        blockchain::{create_new_blockchain, create_store_with_consensus},
+        create_chain_header,
+    },
     transactions::{
-        helpers::{create_random_signature_from_s_key, create_utxo},
+        helpers::{create_random_signature_from_s_key, create_tx, create_utxo},
         tari_amount::{uT, MicroTari},
         transaction::{KernelBuilder, KernelFeatures, OutputFeatures, TransactionKernel},
         CryptoFactories,
     },
-    validation::{header_iter::HeaderIter, ChainBalanceValidator, FinalHorizonStateValidation},
+    validation::{
+        header_iter::HeaderIter,
+        transaction_validators::TxValidationReport,
+        ChainBalanceValidator,
+        FinalHorizonStateValidation,
+        ValidationError,
+    },
 };
-use tari_common_types::types::Commitment;
+use rand::rngs::OsRng;
+use tari_common_types::types::{Commitment, PrivateKey};
 
 #[test]
 fn header_iter_empty_and_invalid_height() {
@@ -103,6 +113,7 @@ fn chain_balance_validation() {
     let kernel = TransactionKernel {
         features: KernelFeatures::empty(),
         fee: MicroTari::from(0),
+        burn_amount: MicroTari::from(0),
         lock_height: 0,
         excess,
         excess_sig: sig,
@@ -234,3 +245,26 @@ fn chain_balance_validation() {
         .validate(2, &utxo_sum, &kernel_sum, &*db.db_read_access().unwrap())
         .unwrap_err();
 }
+
+#[test]
+fn tx_validation_report_stops_at_the_first_failure() {
+    let db = create_new_blockchain();
+    let factories = CryptoFactories::default();
+
+    // The inputs are well formed but do not exist in the UTXO set
+    let (tx, _, _) = create_tx(5000.into(), 15.into(), 0, 2, 0, 4);
+    let report = TxValidationReport::run(db.clone(), factories.clone(), &tx);
+    assert!(!report.is_valid());
+    let (rule, err) = report.first_failure().unwrap();
+    assert!(rule.starts_with("Inputs are unspent"));
+    assert!(matches!(err, ValidationError::UnknownInputs(hashes) if hashes.len() == 2));
+    assert_eq!(report.to_string().matches("OK").count(), 2);
+
+    // A broken balance fails the first rule and the others are not run
+    let (mut tx, _, _) = create_tx(5000.into(), 15.into(), 0, 2, 0, 4);
+    tx.offset = PrivateKey::random(&mut OsRng);
+    let report = TxValidationReport::run(db, factories, &tx);
+    let (rule, _) = report.first_failure().unwrap();
+    assert!(rule.starts_with("Internal consistency"));
+    assert!(!report.to_string().contains("OK"));
+}
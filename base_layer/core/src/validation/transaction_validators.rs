@@ -21,12 +21,13 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use log::*;
+use std::{fmt, sync::Arc};
 
 use crate::{
     chain_storage::{BlockchainBackend, BlockchainDatabase},
-    transactions::{transaction::Transaction, CryptoFactories},
+    transactions::{transaction::Transaction, verification_cache::VerificationCache, CryptoFactories},
     validation::{
-        helpers::{check_inputs_are_utxos, check_not_duplicate_txos},
+        helpers::{check_burn_kernels, check_inputs_are_utxos, check_not_duplicate_txos, check_output_memos},
         MempoolTransactionValidation,
         ValidationError,
     },
@@ -41,24 +42,44 @@ pub const LOG_TARGET: &str = "c::val::transaction_validators";
 /// 1. Range proofs of the outputs are valid
 ///
 /// This function does NOT check that inputs come from the UTXO set
-pub struct TxInternalConsistencyValidator {
+/// The scripts of the inputs are executed in the context of the next block to be mined on the current tip.
+pub struct TxInternalConsistencyValidator<B> {
+    db: BlockchainDatabase<B>,
     factories: CryptoFactories,
     bypass_range_proof_verification: bool,
+    verification_cache: Option<Arc<VerificationCache>>,
 }
 
-impl TxInternalConsistencyValidator {
-    pub fn new(factories: CryptoFactories, bypass_range_proof_verification: bool) -> Self {
+impl<B: BlockchainBackend> TxInternalConsistencyValidator<B> {
+    pub fn new(db: BlockchainDatabase<B>, factories: CryptoFactories, bypass_range_proof_verification: bool) -> Self {
         Self {
+            db,
             factories,
             bypass_range_proof_verification,
+            verification_cache: None,
         }
     }
+
+    /// Records the components of valid transactions in the cache, so that they are not verified again when they are
+    /// mined
+    pub fn with_verification_cache(mut self, verification_cache: Arc<VerificationCache>) -> Self {
+        self.verification_cache = Some(verification_cache);
+        self
+    }
 }
 
-impl MempoolTransactionValidation for TxInternalConsistencyValidator {
+impl<B: BlockchainBackend> MempoolTransactionValidation for TxInternalConsistencyValidator<B> {
     fn validate(&self, tx: &Transaction) -> Result<(), ValidationError> {
-        tx.validate_internal_consistency(self.bypass_range_proof_verification, &self.factories, None)
-            .map_err(ValidationError::TransactionError)?;
+        let tip = self.db.get_chain_metadata()?;
+        tx.validate_internal_consistency_with_cache(
+            self.bypass_range_proof_verification,
+            &self.factories,
+            None,
+            Some(tip.best_block().clone()),
+            Some(tip.height_of_longest_chain() + 1),
+            self.verification_cache.as_deref(),
+        )
+        .map_err(ValidationError::TransactionError)?;
         Ok(())
     }
 }
@@ -84,6 +105,8 @@ impl<B: BlockchainBackend> MempoolTransactionValidation for TxConsensusValidator
         if tx.calculate_weight() > consensus_constants.get_max_block_weight_excluding_coinbase() {
             return Err(ValidationError::MaxTransactionWeightExceeded);
         }
+        check_burn_kernels(tx.body.kernels(), consensus_constants)?;
+        check_output_memos(tx.body.outputs(), consensus_constants)?;
 
         Ok(())
     }
@@ -159,3 +182,71 @@ impl MempoolTransactionValidation for MempoolValidator {
         Ok(())
     }
 }
+
+/// The result of running each mempool validation rule against a transaction in turn, without submitting it. Rules
+/// are run in the order the mempool runs them and stop at the first failure, since later rules assume the earlier ones
+/// passed.
+pub struct TxValidationReport {
+    checks: Vec<(&'static str, Result<(), ValidationError>)>,
+}
+
+impl TxValidationReport {
+    pub fn run<B: BlockchainBackend + 'static>(
+        db: BlockchainDatabase<B>,
+        factories: CryptoFactories,
+        tx: &Transaction,
+    ) -> Self {
+        let rules: Vec<(&'static str, Box<dyn MempoolTransactionValidation>)> = vec![
+            (
+                "Internal consistency (balance, signatures, range proofs and scripts)",
+                Box::new(TxInternalConsistencyValidator::new(db.clone(), factories, false)),
+            ),
+            (
+                "Consensus rules (maximum weight)",
+                Box::new(TxConsensusValidator::new(db.clone())),
+            ),
+            (
+                "Inputs are unspent, outputs are new and timelocks have passed",
+                Box::new(TxInputAndMaturityValidator::new(db)),
+            ),
+        ];
+
+        let mut checks = Vec::with_capacity(rules.len());
+        for (name, rule) in rules {
+            let result = rule.validate(tx);
+            let failed = result.is_err();
+            checks.push((name, result));
+            if failed {
+                break;
+            }
+        }
+        Self { checks }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.first_failure().is_none()
+    }
+
+    /// The name and error of the rule that failed, if any
+    pub fn first_failure(&self) -> Option<(&'static str, &ValidationError)> {
+        self.checks
+            .iter()
+            .find_map(|(name, result)| result.as_ref().err().map(|err| (*name, err)))
+    }
+}
+
+impl fmt::Display for TxValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, result) in &self.checks {
+            match result {
+                Ok(_) => writeln!(f, "OK    : {}", name)?,
+                Err(err) => writeln!(f, "FAILED: {} ({})", name, err)?,
+            }
+        }
+        if self.is_valid() {
+            write!(f, "The transaction is valid against the current tip")
+        } else {
+            write!(f, "The transaction is invalid, later rules were not checked")
+        }
+    }
+}
@@ -42,13 +42,14 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::convert::TryFrom;
+use std::{convert::TryFrom, time::Duration};
 
 use tempfile::{tempdir, TempDir};
 
 use randomx_rs::RandomXFlag;
 use tari_common::configuration::Network;
-use tari_comms::protocol::rpc::mock::RpcRequestMock;
+use tari_common_types::types::PublicKey;
+use tari_comms::protocol::rpc::{mock::RpcRequestMock, RpcStatusCode};
 use tari_core::{
     base_node::{
         comms_interface::Broadcast,
@@ -59,14 +60,23 @@ use tari_core::{
             TxSubmissionRejectionReason,
             TxSubmissionResponse,
         },
-        rpc::{BaseNodeWalletRpcService, BaseNodeWalletService},
+        rpc::{
+            BalanceAuditConfig,
+            BalanceAuditRpcService,
+            BalanceAuditService,
+            BaseNodeWalletRpcService,
+            BaseNodeWalletService,
+        },
         state_machine_service::states::{ListeningInfo, StateInfo, StatusInfo},
     },
     chain_storage::ChainBlock,
     consensus::{ConsensusManager, ConsensusManagerBuilder, NetworkConsensus},
-    crypto::tari_utilities::Hashable,
+    crypto::{
+        keys::PublicKey as PublicKeyTrait,
+        tari_utilities::{ByteArray, Hashable},
+    },
     proto::{
-        base_node::{FetchMatchingUtxos, Signatures as SignaturesProto},
+        base_node::{BalanceAuditRequest, FetchMatchingUtxos, Signatures as SignaturesProto},
         types::{Signature as SignatureProto, Transaction as TransactionProto},
     },
     test_helpers::blockchain::TempDatabase,
@@ -115,6 +125,7 @@ async fn setup() -> (
         state_info: StateInfo::Listening(ListeningInfo::new(true)),
         randomx_vm_cnt: 0,
         randomx_vm_flags: RandomXFlag::FLAG_DEFAULT,
+        read_only: false,
     });
 
     let request_mock = RpcRequestMock::new(base_node.comms.peer_manager());
@@ -290,3 +301,43 @@ async fn test_base_node_wallet_rpc() {
             .any(|u| u.as_transaction_output(&factories).unwrap().commitment == output.commitment));
     }
 }
+
+#[tokio::test]
+async fn test_balance_audit_rpc() {
+    let (_service, base_node, request_mock, _consensus_manager, block0, _utxo0, _temp_dir) = setup().await;
+    let service = BalanceAuditRpcService::new(base_node.blockchain_db.clone().into(), BalanceAuditConfig {
+        min_interval: Duration::from_secs(60),
+        max_concurrent_audits: 1,
+    });
+
+    // Malformed keys are rejected without counting towards the rate limit
+    let msg = BalanceAuditRequest {
+        rewind_public_key: vec![1, 2, 3],
+        rewind_blinding_public_key: vec![],
+        start_height: 0,
+    };
+    let req = request_mock.request_with_context(Default::default(), msg);
+    let err = service.audit_balance(req).await.unwrap_err();
+    assert_eq!(err.status_code(), RpcStatusCode::BadRequest);
+
+    // Keys that own nothing scan the whole chain and find no outputs
+    let (_, rewind_public_key) = PublicKey::random_keypair(&mut rand::thread_rng());
+    let (_, rewind_blinding_public_key) = PublicKey::random_keypair(&mut rand::thread_rng());
+    let msg = BalanceAuditRequest {
+        rewind_public_key: rewind_public_key.to_vec(),
+        rewind_blinding_public_key: rewind_blinding_public_key.to_vec(),
+        start_height: 0,
+    };
+    let req = request_mock.request_with_context(Default::default(), msg.clone());
+    let resp = service.audit_balance(req).await.unwrap().into_message();
+    assert!(resp.outputs.is_empty());
+    assert_eq!(resp.balance, 0);
+    assert_eq!(resp.scanned_height, 0);
+    assert_eq!(&resp.scanned_hash, block0.hash());
+    assert_eq!(resp.num_scanned_outputs, block0.block().body.outputs().len() as u64);
+
+    // A second audit by the same peer within the interval is refused
+    let req = request_mock.request_with_context(Default::default(), msg);
+    let err = service.audit_balance(req).await.unwrap_err();
+    assert_eq!(err.status_code(), RpcStatusCode::Forbidden);
+}
@@ -38,7 +38,7 @@ use tari_core::{
         Validators,
     },
     consensus::{emission::Emission, ConsensusConstantsBuilder, ConsensusManagerBuilder},
-    proof_of_work::Difficulty,
+    proof_of_work::{randomx_factory::RandomXFactory, Difficulty},
     test_helpers::blockchain::{
         create_store_with_consensus,
         create_store_with_consensus_and_validators,
@@ -52,7 +52,12 @@ use tari_core::{
     },
     tx,
     txn_schema,
-    validation::{mocks::MockValidator, DifficultyCalculator, ValidationError},
+    validation::{
+        block_revalidation::{BlockRevalidator, RevalidationStage},
+        mocks::MockValidator,
+        DifficultyCalculator,
+        ValidationError,
+    },
 };
 use tari_storage::lmdb_store::LMDBConfig;
 use tari_test_utils::{paths::create_temporary_data_path, unpack_enum};
@@ -256,6 +261,7 @@ fn rewind_past_horizon_height() {
         orphan_storage_capacity: 3,
         pruning_horizon: 2,
         pruning_interval: 2,
+        ..Default::default()
     };
     let store = BlockchainDatabase::new(
         db,
@@ -954,10 +960,10 @@ fn handle_reorg_failure_recovery() {
     // Block B3 (Incorrect height)
     let double_spend_block = {
         let schemas = vec![
-                txn_schema!(from: vec![orphan1_outputs[1][3].clone()], to: vec![3 * T]),
-                // Double spend
-                //txn_schema!(from: vec![orphan1_outputs[1][3].clone()], to: vec![3 * T]),
-            ];
+            txn_schema!(from: vec![orphan1_outputs[1][3].clone()], to: vec![3 * T]),
+            // Double spend
+            // txn_schema!(from: vec![orphan1_outputs[1][3].clone()], to: vec![3 * T]),
+        ];
         let mut txns = Vec::new();
         let mut block_utxos = Vec::new();
         for schema in schemas {
@@ -1270,6 +1276,7 @@ fn orphan_cleanup_on_block_add() {
         orphan_storage_capacity: 3,
         pruning_horizon: 0,
         pruning_interval: 50,
+        ..Default::default()
     };
     let store = BlockchainDatabase::new(
         db,
@@ -1311,6 +1318,9 @@ fn orphan_cleanup_on_block_add() {
         store.add_block(orphan7.clone().into()).unwrap(),
         BlockAddResult::OrphanBlock
     );
+    // None of the orphans link to the main chain, so the least recently used orphans are discarded
+    store.fetch_orphan(orphan1_hash.clone()).unwrap();
+    store.fetch_orphan(orphan4_hash.clone()).unwrap();
 
     store.cleanup_orphans().unwrap();
     assert_eq!(store.db_read_access().unwrap().orphan_count().unwrap(), 3);
@@ -1339,6 +1349,7 @@ fn horizon_height_orphan_cleanup() {
         orphan_storage_capacity: 3,
         pruning_horizon: 2,
         pruning_interval: 50,
+        ..Default::default()
     };
     let store = BlockchainDatabase::new(
         db,
@@ -1404,6 +1415,7 @@ fn orphan_cleanup_on_reorg() {
         orphan_storage_capacity: 3,
         pruning_horizon: 0,
         pruning_interval: 50,
+        ..Default::default()
     };
     let mut store = BlockchainDatabase::new(
         db,
@@ -1542,6 +1554,7 @@ fn orphan_cleanup_delete_all_orphans() {
         orphan_storage_capacity: 5,
         pruning_horizon: 0,
         pruning_interval: 50,
+        ..Default::default()
     };
     // Test cleanup during runtime
     {
@@ -1652,6 +1665,7 @@ fn fails_validation() {
         orphan_storage_capacity: 3,
         pruning_horizon: 0,
         pruning_interval: 50,
+        ..Default::default()
     };
     let mut store = BlockchainDatabase::new(
         db,
@@ -1697,6 +1711,7 @@ fn pruned_mode_cleanup_and_fetch_block() {
         orphan_storage_capacity: 3,
         pruning_horizon: 3,
         pruning_interval: 1,
+        ..Default::default()
     };
     let store = BlockchainDatabase::new(
         db,
@@ -1723,6 +1738,89 @@ fn pruned_mode_cleanup_and_fetch_block() {
     assert_eq!(metadata.pruning_horizon(), 3);
 }
 
+#[test]
+fn pruned_mode_witness_gc() {
+    let network = Network::LocalNet;
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let config = BlockchainDatabaseConfig {
+        orphan_storage_capacity: 3,
+        pruning_horizon: 2,
+        pruning_interval: 1,
+        ..Default::default()
+    };
+    let (mut store, mut blocks, mut outputs, consensus_manager) =
+        create_new_blockchain_lmdb(network, validators, config);
+    // Block 1 spends the genesis output
+    let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![10 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    for _ in 0..4 {
+        generate_new_block(&mut store, &mut blocks, &mut outputs, vec![], &consensus_manager).unwrap();
+    }
+    let metadata = store.get_chain_metadata().unwrap();
+    assert!(metadata.pruned_height() > 1);
+    assert_eq!(store.fetch_block(1).unwrap().block().body.inputs().len(), 1);
+
+    let stats = store.prune_spent_witness_data(1).unwrap();
+    assert_eq!(stats.start_height, 0);
+    assert_eq!(stats.end_height, 1);
+    assert_eq!(stats.inputs_removed, 0);
+
+    let stats = store.prune_spent_witness_data(100).unwrap();
+    assert_eq!(stats.start_height, 1);
+    assert_eq!(stats.end_height, metadata.pruned_height());
+    assert_eq!(stats.inputs_removed, 1);
+    assert!(stats.bytes_reclaimed > 0);
+    assert!(store.fetch_block(1).unwrap().block().body.inputs().is_empty());
+
+    let stats = store.prune_spent_witness_data(100).unwrap();
+    assert_eq!(stats.num_blocks(), 0);
+}
+
+#[test]
+fn pruned_mode_retained_commitments() {
+    let network = Network::LocalNet;
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let config = BlockchainDatabaseConfig {
+        orphan_storage_capacity: 3,
+        pruning_horizon: 2,
+        pruning_interval: 1,
+        ..Default::default()
+    };
+    let (store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain_lmdb(network, validators, config);
+    let retained = blocks[0]
+        .block()
+        .body
+        .outputs()
+        .iter()
+        .map(|o| o.commitment.clone())
+        .collect::<Vec<_>>();
+    let mut store = store.with_retained_commitments(retained);
+    assert!(store.pruning_policy().has_retained_commitments());
+    // Block 1 spends the genesis output
+    let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![10 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    for _ in 0..4 {
+        generate_new_block(&mut store, &mut blocks, &mut outputs, vec![], &consensus_manager).unwrap();
+    }
+    let metadata = store.get_chain_metadata().unwrap();
+    assert!(metadata.pruned_height() > 1);
+
+    let stats = store.prune_spent_witness_data(100).unwrap();
+    assert_eq!(stats.end_height, metadata.pruned_height());
+    assert!(stats.outputs_retained > 0);
+    assert_eq!(stats.inputs_removed, 0);
+    // The input spending the retained output is kept
+    assert_eq!(store.fetch_block(1).unwrap().block().body.inputs().len(), 1);
+}
+
 #[test]
 fn input_malleability() {
     let mut blockchain = TestBlockchain::with_genesis("GB");
@@ -1759,3 +1857,32 @@ fn input_malleability() {
     let mod_block_hash = mod_block.hash();
     assert_ne!(*block_hash, mod_block_hash);
 }
+
+#[test]
+#[allow(clippy::identity_op)]
+fn revalidate_historical_blocks() {
+    let network = Network::LocalNet;
+    let (mut db, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let schema = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![6 * T, 3 * T])];
+    generate_new_block(&mut db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+    let schema = vec![txn_schema!(from: vec![outputs[1][0].clone()], to: vec![3 * T, 1 * T])];
+    generate_new_block(&mut db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+
+    let revalidator = BlockRevalidator::new(db.clone(), consensus_manager, RandomXFactory::default());
+    for height in 1..=2 {
+        let revalidation = revalidator.revalidate(height).unwrap();
+        assert!(revalidation.is_valid(), "{:?}", revalidation.failure);
+        assert_eq!(&revalidation.hash, blocks[height as usize].hash());
+        let stages = revalidation.timings.iter().map(|(stage, _)| *stage).collect::<Vec<_>>();
+        assert_eq!(stages, RevalidationStage::ALL);
+    }
+
+    // The genesis block has no proof of work, scripts or balance to check
+    let revalidation = revalidator.revalidate(0).unwrap();
+    assert!(!revalidation
+        .timings
+        .iter()
+        .any(|(stage, _)| *stage == RevalidationStage::ProofOfWork));
+
+    assert!(revalidator.revalidate(3).is_err());
+}
@@ -20,13 +20,17 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use tari_core::base_node::{state_machine_service::states::StatusInfo, StateMachineHandle};
+use std::sync::Arc;
+use tari_core::base_node::{
+    state_machine_service::{states::StatusInfo, StateTransitionObservers},
+    StateMachineHandle,
+};
 use tari_service_framework::{async_trait, ServiceInitializationError, ServiceInitializer, ServiceInitializerContext};
 use tokio::sync::{broadcast, watch};
 
 pub struct MockBaseNodeStateMachine {
     status_receiver: watch::Receiver<StatusInfo>,
-    status_sender: watch::Sender<StatusInfo>,
+    status_sender: Arc<watch::Sender<StatusInfo>>,
 }
 
 #[allow(dead_code)]
@@ -35,7 +39,7 @@ impl MockBaseNodeStateMachine {
         let (status_sender, status_receiver) = tokio::sync::watch::channel(StatusInfo::new());
         Self {
             status_receiver,
-            status_sender,
+            status_sender: Arc::new(status_sender),
         }
     }
 
@@ -45,12 +49,14 @@ impl MockBaseNodeStateMachine {
 
     pub fn get_initializer(&self) -> MockBaseNodeStateMachineInitializer {
         MockBaseNodeStateMachineInitializer {
+            status_sender: self.status_sender.clone(),
             status_receiver: self.status_receiver.clone(),
         }
     }
 }
 
 pub struct MockBaseNodeStateMachineInitializer {
+    status_sender: Arc<watch::Sender<StatusInfo>>,
     status_receiver: watch::Receiver<StatusInfo>,
 }
 
@@ -61,7 +67,10 @@ impl ServiceInitializer for MockBaseNodeStateMachineInitializer {
 
         let handle = StateMachineHandle::new(
             state_event_publisher,
+            self.status_sender.clone(),
             self.status_receiver.clone(),
+            watch::channel(None).1,
+            StateTransitionObservers::new(),
             context.get_shutdown_signal(),
         );
         context.register_handle(handle);
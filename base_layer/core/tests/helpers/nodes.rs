@@ -427,7 +427,7 @@ async fn setup_base_node_services(
             subscription_factory,
         ))
         .add_initializer(mock_state_machine.get_initializer())
-        .add_initializer(ChainMetadataServiceInitializer)
+        .add_initializer(ChainMetadataServiceInitializer::new(Default::default()))
         .build()
         .await
         .unwrap();
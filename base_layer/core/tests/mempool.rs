@@ -23,7 +23,7 @@
 // use crate::helpers::database::create_store;
 use std::{ops::Deref, sync::Arc, time::Duration};
 
-use tari_crypto::{keys::PublicKey as PublicKeyTrait, script};
+use tari_crypto::{keys::PublicKey as PublicKeyTrait, script, tari_utilities::Hashable};
 use tempfile::tempdir;
 
 use helpers::{
@@ -48,6 +48,7 @@ use tari_core::{
         service::BaseNodeServiceConfig,
         state_machine_service::states::{ListeningInfo, StateInfo, StatusInfo},
     },
+    blocks::{short_kernel_id, CompactBlock},
     consensus::{ConsensusConstantsBuilder, ConsensusManager, NetworkConsensus},
     mempool::{Mempool, MempoolConfig, MempoolServiceConfig, MempoolServiceError, TxStorageResponse},
     proof_of_work::Difficulty,
@@ -332,6 +333,56 @@ async fn test_retrieve() {
     assert!(retrieved_txs.contains(&tx2[1]));
 }
 
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_compact_block_reconstruction() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
+    let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![1 * T, 1 * T, 1 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).unwrap();
+
+    let txs = vec![
+        txn_schema!(from: vec![outputs[1][0].clone()], to: vec![], fee: 20*uT, lock: 0, features: OutputFeatures::default()),
+        txn_schema!(from: vec![outputs[1][1].clone()], to: vec![], fee: 20*uT, lock: 0, features: OutputFeatures::default()),
+        txn_schema!(from: vec![outputs[1][2].clone()], to: vec![], fee: 20*uT, lock: 0, features: OutputFeatures::default()),
+    ];
+    let (tx, _) = schema_to_transaction(&txs);
+    // The last transaction of the block is not in the local mempool
+    mempool.insert(tx[0].clone()).unwrap();
+    mempool.insert(tx[1].clone()).unwrap();
+    generate_block(
+        &store,
+        &mut blocks,
+        vec![tx[0].deref().clone(), tx[2].deref().clone()],
+        &consensus_manager,
+    )
+    .unwrap();
+    let block = blocks[2].block();
+    let block_hash = block.hash();
+    let compact_block = CompactBlock::from(block);
+    assert_eq!(compact_block.coinbase_kernels.len(), 1);
+    assert_eq!(compact_block.short_ids.len(), 2);
+
+    let mut found = mempool
+        .retrieve_by_short_kernel_ids(block_hash.clone(), compact_block.short_ids.clone())
+        .unwrap();
+    assert_eq!(found, vec![tx[0].clone()]);
+    assert_eq!(compact_block.missing_short_ids(&found), vec![short_kernel_id(
+        &block_hash,
+        &tx[2].body.kernels()[0]
+    )]);
+    assert!(compact_block.reconstruct(&found).is_none());
+
+    // Transactions that are not part of the block are ignored
+    found.push(tx[1].clone());
+    found.push(tx[2].clone());
+    assert!(compact_block.missing_short_ids(&found).is_empty());
+    assert_eq!(compact_block.reconstruct(&found).as_ref(), Some(block));
+}
+
 #[tokio::test]
 #[allow(clippy::identity_op)]
 async fn test_zero_conf() {
@@ -863,18 +914,21 @@ async fn receive_and_propagate_transaction() {
         state_info: StateInfo::Listening(ListeningInfo::new(true)),
         randomx_vm_cnt: 0,
         randomx_vm_flags: RandomXFlag::FLAG_DEFAULT,
+        read_only: false,
     });
     bob_node.mock_base_node_state_machine.publish_status(StatusInfo {
         bootstrapped: true,
         state_info: StateInfo::Listening(ListeningInfo::new(true)),
         randomx_vm_cnt: 0,
         randomx_vm_flags: RandomXFlag::FLAG_DEFAULT,
+        read_only: false,
     });
     carol_node.mock_base_node_state_machine.publish_status(StatusInfo {
         bootstrapped: true,
         state_info: StateInfo::Listening(ListeningInfo::new(true)),
         randomx_vm_cnt: 0,
         randomx_vm_flags: RandomXFlag::FLAG_DEFAULT,
+        read_only: false,
     });
 
     let (tx, _, _) = spend_utxos(txn_schema!(from: vec![utxo], to: vec![2 * T, 2 * T, 2 * T]));
@@ -966,7 +1020,7 @@ async fn consensus_validation_large_tx() {
     let mut script_offset_pvt = outputs[1][0].script_private_key.clone();
     let inputs = vec![input.as_transaction_input(&factories.commitment).unwrap()];
 
-    let fee = Fee::calculate(fee_per_gram.into(), 1, input_count, output_count);
+    let fee = Fee::calculate(fee_per_gram.into(), 1, input_count, output_count, 0);
     let amount_per_output = (amount - fee) / output_count as u64;
     let amount_for_last_output = (amount - fee) - amount_per_output * (output_count as u64 - 1);
     let mut unblinded_outputs = Vec::with_capacity(output_count);
@@ -1005,7 +1059,11 @@ async fn consensus_validation_large_tx() {
         .collect::<Result<Vec<TransactionOutput>, _>>()
         .unwrap();
 
-    let tx_meta = TransactionMetadata { fee, lock_height: 0 };
+    let tx_meta = TransactionMetadata {
+        fee,
+        lock_height: 0,
+        ..Default::default()
+    };
 
     let public_nonce = PublicKey::from_secret_key(&nonce);
     let offset_blinding_factor = &excess_blinding_factor - &offset;
@@ -1027,7 +1085,9 @@ async fn consensus_validation_large_tx() {
 
     // make sure the tx was correctly made and is valid
     let factories = CryptoFactories::default();
-    assert!(tx.validate_internal_consistency(true, &factories, None).is_ok());
+    assert!(tx
+        .validate_internal_consistency(true, &factories, None, None, None)
+        .is_ok());
     let weight = tx.calculate_weight();
 
     let height = blocks.len() as u64;
@@ -1101,6 +1161,7 @@ async fn block_event_and_reorg_event_handling() {
         state_info: StateInfo::Listening(ListeningInfo::new(true)),
         randomx_vm_cnt: 0,
         randomx_vm_flags: RandomXFlag::FLAG_DEFAULT,
+        read_only: false,
     });
 
     // Bob creates Block 1 and sends it to Alice. Alice adds it to her chain and creates a block event that the Mempool
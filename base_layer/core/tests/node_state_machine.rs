@@ -25,7 +25,7 @@ use helpers::{
     chain_metadata::{random_peer_metadata, MockChainMetadata},
     nodes::{create_network_with_2_base_nodes_with_config, wait_until_online, BaseNodeBuilder},
 };
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tari_common::configuration::Network;
 use tari_core::{
     base_node::{
@@ -33,9 +33,11 @@ use tari_core::{
         comms_interface::Broadcast,
         service::BaseNodeServiceConfig,
         state_machine_service::{
-            states::{Listening, StateEvent, StatusInfo},
+            states::{BaseNodeState, Listening, StateEvent, StatusInfo},
             BaseNodeStateMachine,
             BaseNodeStateMachineConfig,
+            StateTransitionObserver,
+            StateTransitionObservers,
         },
         SyncValidators,
     },
@@ -47,10 +49,11 @@ use tari_core::{
     validation::mocks::MockValidator,
 };
 use tari_p2p::services::liveness::LivenessConfig;
+use tari_service_framework::async_trait;
 use tari_shutdown::Shutdown;
 use tempfile::tempdir;
 use tokio::{
-    sync::{broadcast, watch},
+    sync::{broadcast, mpsc, watch},
     task,
     time,
 };
@@ -95,8 +98,10 @@ async fn test_listening_lagging() {
         alice_node.chain_metadata_handle.get_event_stream(),
         BaseNodeStateMachineConfig::default(),
         SyncValidators::new(MockValidator::new(true), MockValidator::new(true)),
-        status_event_sender,
+        Arc::new(status_event_sender),
+        watch::channel(None).0,
         state_change_event_publisher,
+        StateTransitionObservers::new(),
         RandomXFactory::default(),
         consensus_manager.clone(),
         shutdown.to_signal(),
@@ -145,6 +150,9 @@ async fn test_event_channel() {
     let mut mock = MockChainMetadata::new();
     let (state_change_event_publisher, mut state_change_event_subscriber) = broadcast::channel(10);
     let (status_event_sender, _status_event_receiver) = tokio::sync::watch::channel(StatusInfo::new());
+    let transition_observers = StateTransitionObservers::new();
+    let (transition_tx, mut transition_rx) = mpsc::unbounded_channel();
+    transition_observers.register(ForwardingObserver(transition_tx));
     let state_machine = BaseNodeStateMachine::new(
         db.into(),
         node.local_nci.clone(),
@@ -154,8 +162,10 @@ async fn test_event_channel() {
         mock.subscription(),
         BaseNodeStateMachineConfig::default(),
         SyncValidators::new(MockValidator::new(true), MockValidator::new(true)),
-        status_event_sender,
+        Arc::new(status_event_sender),
+        watch::channel(None).0,
         state_change_event_publisher,
+        transition_observers.clone(),
         RandomXFactory::default(),
         consensus_manager,
         shutdown.to_signal(),
@@ -178,4 +188,15 @@ async fn test_event_channel() {
         StateEvent::InitialSync => (),
         _ => panic!("Unexpected state was found:{:?}", event),
     }
+    let transition = transition_rx.recv().await.unwrap();
+    assert_eq!(transition, "Initializing -> Initialized -> Listening");
+}
+
+struct ForwardingObserver(mpsc::UnboundedSender<String>);
+
+#[async_trait]
+impl StateTransitionObserver for ForwardingObserver {
+    async fn on_transition(&self, from: &BaseNodeState, event: &StateEvent, to: &BaseNodeState) {
+        let _ = self.0.send(format!("{} -> {} -> {}", from, event, to));
+    }
 }
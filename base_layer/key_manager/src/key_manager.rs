@@ -28,10 +28,16 @@ use serde_derive::{Deserialize, Serialize};
 use std::marker::PhantomData;
 use tari_crypto::{
     keys::SecretKey,
-    tari_utilities::{byte_array::ByteArrayError, hex::Hex},
+    tari_utilities::{
+        byte_array::{ByteArray, ByteArrayError},
+        hex::Hex,
+    },
 };
 use thiserror::Error;
 
+/// Domain separation label used when combining a seed key with a seed words passphrase
+const SEED_WORDS_PASSPHRASE_LABEL: &str = "com.tari.key_manager.seed_words_passphrase";
+
 #[derive(Debug, Error, PartialEq)]
 pub enum KeyManagerError {
     #[error("Could not convert into byte array: `{0}`")]
@@ -116,6 +122,24 @@ where
         }
     }
 
+    /// Creates a KeyManager from the provided sequence of mnemonic words and an additional seed words passphrase (the
+    /// so called 25th word). Every passphrase produces a distinct master key, an empty passphrase produces the same
+    /// master key as `from_mnemonic`.
+    pub fn from_mnemonic_with_passphrase(
+        mnemonic_seq: &[String],
+        passphrase: &str,
+        branch_seed: String,
+        primary_key_index: u64,
+    ) -> Result<KeyManager<K, D>, KeyManagerError> {
+        let seed_key = K::from_mnemonic(mnemonic_seq)?;
+        Ok(KeyManager {
+            master_key: apply_seed_words_passphrase::<K, D>(seed_key, passphrase)?,
+            branch_seed,
+            primary_key_index,
+            digest_type: PhantomData,
+        })
+    }
+
     /// Derive a new private key from master key: derived_key=SHA256(master_key||branch_seed||index)
     pub fn derive_key(&self, key_index: u64) -> Result<DerivedKey<K>, ByteArrayError> {
         let concatenated = format!("{}{}", self.master_key.to_hex(), key_index.to_string());
@@ -144,6 +168,25 @@ where
     }
 }
 
+/// Combines the secret key encoded by a set of seed words with a seed words passphrase to produce the master key of the
+/// wallet: master_key=H(label||seed_key||passphrase). An empty passphrase leaves the seed key unchanged so that wallets
+/// created without a passphrase are unaffected.
+pub fn apply_seed_words_passphrase<K, D>(seed_key: K, passphrase: &str) -> Result<K, KeyManagerError>
+where
+    K: SecretKey,
+    D: Digest,
+{
+    if passphrase.is_empty() {
+        return Ok(seed_key);
+    }
+    let digest = D::new()
+        .chain(SEED_WORDS_PASSPHRASE_LABEL.as_bytes())
+        .chain(seed_key.as_bytes())
+        .chain(passphrase.as_bytes())
+        .finalize();
+    Ok(K::from_bytes(digest.as_slice())?)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{file_backup::*, key_manager::*};
@@ -202,6 +245,54 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_from_mnemonic_with_passphrase() {
+        let mnemonic_seq = vec![
+            "clever", "jaguar", "bus", "engage", "oil", "august", "media", "high", "trick", "remove", "tiny", "join",
+            "item", "tobacco", "orange", "pony", "tomorrow", "also", "dignity", "giraffe", "little", "board", "army",
+            "scale",
+        ]
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>();
+        let branch_seed = "".to_string();
+        let km =
+            KeyManager::<RistrettoSecretKey, Sha256>::from_mnemonic(&mnemonic_seq, branch_seed.clone(), 0).unwrap();
+        let km_empty = KeyManager::<RistrettoSecretKey, Sha256>::from_mnemonic_with_passphrase(
+            &mnemonic_seq,
+            "",
+            branch_seed.clone(),
+            0,
+        )
+        .unwrap();
+        let km1 = KeyManager::<RistrettoSecretKey, Sha256>::from_mnemonic_with_passphrase(
+            &mnemonic_seq,
+            "passphrase",
+            branch_seed.clone(),
+            0,
+        )
+        .unwrap();
+        let km1_again = KeyManager::<RistrettoSecretKey, Sha256>::from_mnemonic_with_passphrase(
+            &mnemonic_seq,
+            "passphrase",
+            branch_seed.clone(),
+            0,
+        )
+        .unwrap();
+        let km2 = KeyManager::<RistrettoSecretKey, Sha256>::from_mnemonic_with_passphrase(
+            &mnemonic_seq,
+            "another passphrase",
+            branch_seed,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(km.master_key, km_empty.master_key);
+        assert_eq!(km1.master_key, km1_again.master_key);
+        assert_ne!(km.master_key, km1.master_key);
+        assert_ne!(km1.master_key, km2.master_key);
+    }
+
     #[test]
     fn test_derive_and_next_key() {
         let mut km = KeyManager::<RistrettoSecretKey, Sha256>::new(&mut OsRng);
@@ -77,6 +77,26 @@ impl Default for MerkleProof {
 }
 
 impl MerkleProof {
+    /// Reconstructs a Merkle proof from its parts, e.g. after the proof has been received over the wire.
+    pub fn from_parts(mmr_size: usize, path: Vec<Hash>, peaks: Vec<Hash>) -> MerkleProof {
+        MerkleProof { mmr_size, path, peaks }
+    }
+
+    /// The size of the MMR at the time the proof was created
+    pub fn mmr_size(&self) -> usize {
+        self.mmr_size
+    }
+
+    /// The sibling hashes from the candidate leaf up to its local peak
+    pub fn path(&self) -> &[Hash] {
+        &self.path
+    }
+
+    /// The MMR peaks, excluding the local peak of the candidate leaf
+    pub fn peaks(&self) -> &[Hash] {
+        &self.peaks
+    }
+
     /// Build a Merkle Proof the given MMR at the given *leaf* position. This is usually the version you'll want to
     /// call, since you'll know the leaf index more often than the MMR index.
     ///
@@ -28,6 +28,7 @@ use support::{create_mmr, int_to_hash, Hasher};
 use tari_crypto::tari_utilities::hex::{self, Hex};
 use tari_mmr::{
     common::{is_leaf, node_index},
+    functions::prune_mmr,
     MerkleProof,
     MerkleProofError,
 };
@@ -94,6 +95,34 @@ fn for_leaf_node() {
     assert!(proof.verify_leaf::<Hasher>(&root, &hash, leaf_pos).is_ok())
 }
 
+#[test]
+fn for_leaf_added_to_pruned_mmr() {
+    let full = create_mmr(40);
+    let root = full.get_merkle_root().unwrap();
+    let mut pruned = prune_mmr(&create_mmr(27)).unwrap();
+    for i in 27..40 {
+        pruned.push(int_to_hash(i)).unwrap();
+    }
+    assert_eq!(pruned.get_merkle_root().unwrap(), root);
+    // Leaves added after pruning only require the pruned peaks to build a proof
+    for leaf_pos in 27..40 {
+        let proof = MerkleProof::for_leaf_node(&pruned, leaf_pos).unwrap();
+        assert_eq!(proof, MerkleProof::for_leaf_node(&full, leaf_pos).unwrap());
+        assert!(proof
+            .verify_leaf::<Hasher>(&root, &int_to_hash(leaf_pos), leaf_pos)
+            .is_ok());
+    }
+    assert!(MerkleProof::for_leaf_node(&pruned, 3).is_err());
+}
+
+#[test]
+fn from_parts() {
+    let mmr = create_mmr(13);
+    let proof = MerkleProof::for_leaf_node(&mmr, 9).unwrap();
+    let rebuilt = MerkleProof::from_parts(proof.mmr_size(), proof.path().to_vec(), proof.peaks().to_vec());
+    assert_eq!(rebuilt, proof);
+}
+
 const JSON_PROOF: &str = r#"{"mmr_size":8,"path":["e88b43fded6323ef02ffeffbd8c40846ee09bf316271bd22369659c959dd733a","8bdd601372fd4d8242591e4b42815bc35826b0209ce5b78eb06609110b002b9d"],"peaks":["e96760d274653a39b429a87ebaae9d3aa4fdf58b9096cf0bebc7c4e5a4c2ed8d"]}"#;
 const BINCODE_PROOF: &str = "080000000000000002000000000000002000000000000000e88b43fded6323ef02ffeffbd8c40846ee09bf316271bd22369659c959dd733a20000000000000008bdd601372fd4d8242591e4b42815bc35826b0209ce5b78eb06609110b002b9d01000000000000002000000000000000e96760d274653a39b429a87ebaae9d3aa4fdf58b9096cf0bebc7c4e5a4c2ed8d";
 
@@ -0,0 +1,151 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Update deltas rebuild a new binary from the installed one, so that only the changed parts of the binary are
+//! downloaded. A delta starts with [DELTA_MAGIC], followed by a sequence of operations:
+//! - `0x00 <offset: u64 LE> <length: u64 LE>` copies `length` bytes at `offset` of the installed binary
+//! - `0x01 <length: u64 LE> <bytes>` inserts `length` new bytes
+//!
+//! A delta is not trusted: the binary it produces is only installed if its hash is listed in the signed manifest. A
+//! copy can repeat any part of the installed binary any number of times, so the size of the binary a delta may produce
+//! is capped before anything is copied.
+
+use super::error::AutoUpdateError;
+use std::convert::TryInto;
+
+pub const DELTA_MAGIC: &[u8] = b"TARIDLT1";
+
+const OP_COPY: u8 = 0x00;
+const OP_INSERT: u8 = 0x01;
+
+/// Applies `delta` to the installed binary `old` and returns the new binary, which may be at most `max_size` bytes
+pub fn apply_delta(old: &[u8], delta: &[u8], max_size: usize) -> Result<Vec<u8>, AutoUpdateError> {
+    let mut ops = delta
+        .strip_prefix(DELTA_MAGIC)
+        .ok_or_else(|| invalid("missing header"))?;
+    let mut new = Vec::with_capacity(old.len());
+    while let Some((&op, rest)) = ops.split_first() {
+        ops = rest;
+        match op {
+            OP_COPY => {
+                let offset = read_u64(&mut ops)?;
+                let length = read_u64(&mut ops)?;
+                let end = offset.checked_add(length).ok_or_else(|| invalid("copy overflows"))?;
+                let bytes = old
+                    .get(offset as usize..end as usize)
+                    .ok_or_else(|| invalid("copy is out of range of the installed binary"))?;
+                check_size(&new, bytes.len(), max_size)?;
+                new.extend_from_slice(bytes);
+            },
+            OP_INSERT => {
+                let length = read_u64(&mut ops)? as usize;
+                if ops.len() < length {
+                    return Err(invalid("insert is truncated"));
+                }
+                let (bytes, rest) = ops.split_at(length);
+                check_size(&new, bytes.len(), max_size)?;
+                new.extend_from_slice(bytes);
+                ops = rest;
+            },
+            op => return Err(invalid(&format!("unknown operation {:#04x}", op))),
+        }
+    }
+    Ok(new)
+}
+
+fn check_size(new: &[u8], length: usize, max_size: usize) -> Result<(), AutoUpdateError> {
+    if new.len() + length > max_size {
+        return Err(invalid(&format!(
+            "the new binary would exceed the maximum size of {} bytes",
+            max_size
+        )));
+    }
+    Ok(())
+}
+
+fn read_u64(ops: &mut &[u8]) -> Result<u64, AutoUpdateError> {
+    if ops.len() < 8 {
+        return Err(invalid("operation is truncated"));
+    }
+    let (bytes, rest) = ops.split_at(8);
+    *ops = rest;
+    Ok(u64::from_le_bytes(bytes.try_into().expect("slice is 8 bytes")))
+}
+
+fn invalid(reason: &str) -> AutoUpdateError {
+    AutoUpdateError::InvalidDelta(reason.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn copy(offset: u64, length: u64) -> Vec<u8> {
+        let mut op = vec![OP_COPY];
+        op.extend_from_slice(&offset.to_le_bytes());
+        op.extend_from_slice(&length.to_le_bytes());
+        op
+    }
+
+    fn insert(bytes: &[u8]) -> Vec<u8> {
+        let mut op = vec![OP_INSERT];
+        op.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        op.extend_from_slice(bytes);
+        op
+    }
+
+    #[test]
+    fn it_rebuilds_the_new_binary() {
+        let old = b"tari base node v1";
+        let delta = [DELTA_MAGIC.to_vec(), copy(0, 15), insert(b"v2 (patched)")].concat();
+        assert_eq!(apply_delta(old, &delta, 100).unwrap(), b"tari base node v2 (patched)");
+    }
+
+    #[test]
+    fn it_rejects_malformed_deltas() {
+        let old = b"tari base node v1";
+        let invalid = |delta: Vec<u8>| matches!(apply_delta(old, &delta, 100), Err(AutoUpdateError::InvalidDelta(_)));
+        assert!(invalid(copy(0, 4)));
+        assert!(invalid([DELTA_MAGIC.to_vec(), copy(10, 100)].concat()));
+        assert!(invalid([DELTA_MAGIC.to_vec(), copy(u64::MAX, 2)].concat()));
+        assert!(invalid([DELTA_MAGIC.to_vec(), insert(b"v2")[..5].to_vec()].concat()));
+        assert!(invalid([DELTA_MAGIC.to_vec(), vec![0x02]].concat()));
+    }
+
+    #[test]
+    fn it_caps_the_size_of_the_new_binary() {
+        let old = vec![0xAB; 1000];
+        // Each 17 byte copy appends the whole installed binary again
+        let delta = [DELTA_MAGIC.to_vec(), copy(0, 1000).repeat(1000)].concat();
+        assert!(matches!(
+            apply_delta(&old, &delta, 10_000),
+            Err(AutoUpdateError::InvalidDelta(_))
+        ));
+        let delta = [DELTA_MAGIC.to_vec(), copy(0, 1000).repeat(10)].concat();
+        assert_eq!(apply_delta(&old, &delta, 10_000).unwrap().len(), 10_000);
+        let delta = [DELTA_MAGIC.to_vec(), copy(0, 1000).repeat(10), insert(b"!")].concat();
+        assert!(matches!(
+            apply_delta(&old, &delta, 10_000),
+            Err(AutoUpdateError::InvalidDelta(_))
+        ));
+    }
+}
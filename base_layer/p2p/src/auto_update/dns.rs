@@ -240,6 +240,7 @@ mod test {
                     hashes_sig_url:
                         "https://raw.githubusercontent.com/tari-project/tari/development/meta/hashes.txt.sig"
                             .to_string(),
+                    socks_proxy: None,
                 }
             }
         }
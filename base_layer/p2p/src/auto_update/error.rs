@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::dns::DnsClientError;
+use std::io;
 
 #[derive(Debug, thiserror::Error)]
 pub enum AutoUpdateError {
@@ -30,4 +31,26 @@ pub enum AutoUpdateError {
     DownloadError(#[from] reqwest::Error),
     #[error("Failed to verify signature: {0}")]
     SignatureError(#[from] pgp::errors::Error),
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Download URL '{0}' does not name a file")]
+    InvalidDownloadUrl(String),
+    #[error("Downloaded update has hash {actual} but {expected} was expected")]
+    HashMismatch { expected: String, actual: String },
+    #[error("Downloaded update from '{0}' is not signed by a maintainer")]
+    InvalidSignature(String),
+    #[error("No previous version found at '{0}'")]
+    NoPreviousVersion(String),
+    #[error("Invalid SOCKS5 proxy address '{0}'")]
+    InvalidProxyAddress(String),
+    #[error("Failed to read the update archive: {0}")]
+    ArchiveError(#[from] zip::result::ZipError),
+    #[error("The update archive does not contain '{0}'")]
+    BinaryNotFound(String),
+    #[error("The signed manifest has no hash for '{0}'")]
+    NotInManifest(String),
+    #[error("Invalid update delta: {0}")]
+    InvalidDelta(String),
+    #[error("The download from '{url}' exceeds the maximum size of {max} bytes")]
+    DownloadTooLarge { url: String, max: u64 },
 }
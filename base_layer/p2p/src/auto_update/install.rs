@@ -0,0 +1,396 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::{
+    delta::apply_delta,
+    download_signature_file,
+    error::AutoUpdateError,
+    http_client,
+    maintainers,
+    signature::SignedMessageVerifier,
+    SoftwareUpdate,
+};
+use reqwest::{header, StatusCode};
+use sha2::{Digest, Sha256};
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use tari_utilities::hex::Hex;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+const LOG_TARGET: &str = "p2p::auto_update::install";
+/// The largest update archive, delta or rebuilt binary that is downloaded or built. Neither the archive nor the delta
+/// can be checked before it is downloaded, so this bounds the disk space and memory a hostile mirror can use up.
+const MAX_UPDATE_SIZE: u64 = 512 * 1024 * 1024;
+
+/// A binary extracted from a verified update, ready to be installed
+#[derive(Debug, Clone)]
+pub struct StagedUpdate {
+    path: PathBuf,
+    hash: Vec<u8>,
+}
+
+impl StagedUpdate {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Downloads the update of the binary at `installed` into `staging_dir`. A delta against the installed binary is
+/// used if the update publishes one, otherwise the update archive is downloaded, resuming a previously interrupted
+/// download, and the binary is extracted from it. The archive is only used once its SHA-256 hash matches the update
+/// and it carries a detached maintainer signature, published next to it with a `.sig` suffix. Either way the binary
+/// is only staged if its hash is the one the signed manifest lists for it.
+pub async fn download_update(
+    update: &SoftwareUpdate,
+    staging_dir: &Path,
+    installed: &Path,
+) -> Result<StagedUpdate, AutoUpdateError> {
+    let url = update.download_url();
+    let archive_name = url
+        .rsplit('/')
+        .next()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AutoUpdateError::InvalidDownloadUrl(url.to_string()))?;
+    let binary_name = installed
+        .file_name()
+        .and_then(OsStr::to_str)
+        .ok_or_else(|| AutoUpdateError::BinaryNotFound(installed.display().to_string()))?;
+    let manifest_name = format!("{}/{}", archive_name, binary_name);
+    let expected = update
+        .manifest_hash(&manifest_name)
+        .ok_or_else(|| AutoUpdateError::NotInManifest(manifest_name.clone()))?;
+    fs::create_dir_all(staging_dir)?;
+
+    let client = http_client(update.socks_proxy.as_ref())?;
+    let binary = match download_delta(&client, url, installed, &expected).await {
+        Ok(Some(binary)) => binary,
+        Ok(None) => download_archive(&client, update, staging_dir, archive_name, binary_name).await?,
+        Err(err) => {
+            log::warn!(
+                target: LOG_TARGET,
+                "Could not apply the update delta, downloading the full update: {}",
+                err
+            );
+            download_archive(&client, update, staging_dir, archive_name, binary_name).await?
+        },
+    };
+    verify_hash(&binary, &expected)?;
+
+    let staged_path = staging_dir.join(binary_name);
+    fs::write(&staged_path, &binary)?;
+    set_executable(&staged_path)?;
+    log::info!(
+        target: LOG_TARGET,
+        "Update {} verified and staged at {}",
+        update.version(),
+        staged_path.display()
+    );
+    Ok(StagedUpdate {
+        path: staged_path,
+        hash: expected,
+    })
+}
+
+/// Replaces the binary at `target` with the staged update. The staged binary is checked against the signed manifest
+/// again, in case it was changed after it was staged. The current binary is kept next to it (see [backup_path]) so
+/// that the update can be rolled back.
+pub fn install_update(staged: &StagedUpdate, target: &Path) -> Result<(), AutoUpdateError> {
+    if let Err(err) = verify_hash(&fs::read(&staged.path)?, &staged.hash) {
+        fs::remove_file(&staged.path)?;
+        return Err(err);
+    }
+    let backup = backup_path(target);
+    swap_in(&staged.path, target, &backup)?;
+    fs::remove_file(&staged.path)?;
+    log::info!(
+        target: LOG_TARGET,
+        "Installed update at {}, previous version kept at {}",
+        target.display(),
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Restores the binary that was replaced by the last [install_update]. The binary that is rolled back is kept as the
+/// backup in turn, so no version is lost.
+pub fn rollback_update(target: &Path) -> Result<(), AutoUpdateError> {
+    let backup = backup_path(target);
+    if !backup.exists() {
+        return Err(AutoUpdateError::NoPreviousVersion(backup.display().to_string()));
+    }
+    swap_in(&backup, target, &backup)?;
+    log::info!(target: LOG_TARGET, "Rolled back {} to the previous version", target.display());
+    Ok(())
+}
+
+/// The path the previous version of the binary at `target` is kept at after an update
+pub fn backup_path(target: &Path) -> PathBuf {
+    with_suffix(target, ".previous")
+}
+
+/// Installs a copy of `source` at `target`, moving the binary at `target` to `backup`. `source` may be `backup`.
+fn swap_in(source: &Path, target: &Path, backup: &Path) -> Result<(), AutoUpdateError> {
+    // Copy next to the target first so that swapping in the new binary is a rename on the same filesystem. The
+    // running binary is only ever renamed, never overwritten, which Windows does not allow.
+    let incoming = with_suffix(target, ".new");
+    fs::copy(source, &incoming)?;
+    fs::rename(target, backup)?;
+    if let Err(err) = fs::rename(&incoming, target) {
+        fs::rename(backup, target)?;
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// Downloads the delta that rebuilds the update from the installed binary, if the update publishes one. Deltas are
+/// published next to the update archive with the hex SHA-256 hash of the binary they apply to and a `.delta` suffix.
+async fn download_delta(
+    client: &reqwest::Client,
+    url: &str,
+    installed: &Path,
+    expected: &[u8],
+) -> Result<Option<Vec<u8>>, AutoUpdateError> {
+    let current = tokio::fs::read(installed).await?;
+    let delta_url = format!("{}.{}.delta", url, Sha256::digest(&current).to_vec().to_hex());
+    let resp = client.get(&delta_url).send().await?;
+    if resp.status() == StatusCode::NOT_FOUND {
+        log::debug!(target: LOG_TARGET, "No update delta published at {}", delta_url);
+        return Ok(None);
+    }
+    let delta = download_limited(resp.error_for_status()?, &delta_url).await?;
+    let binary = apply_delta(&current, &delta, MAX_UPDATE_SIZE as usize)?;
+    verify_hash(&binary, expected)?;
+    log::info!(
+        target: LOG_TARGET,
+        "Update rebuilt from a {} byte delta instead of downloading the full update",
+        delta.len()
+    );
+    Ok(Some(binary))
+}
+
+/// Downloads and verifies the update archive, and extracts `binary_name` from it
+async fn download_archive(
+    client: &reqwest::Client,
+    update: &SoftwareUpdate,
+    staging_dir: &Path,
+    archive_name: &str,
+    binary_name: &str,
+) -> Result<Vec<u8>, AutoUpdateError> {
+    let url = update.download_url();
+    let partial_path = with_suffix(&staging_dir.join(archive_name), ".part");
+    let signature = download_signature_file(client, format!("{}.sig", url)).await?;
+    resume_download(client, url, &partial_path).await?;
+
+    let contents = tokio::fs::read(&partial_path).await?;
+    // Start from scratch next time if the archive does not check out, the partial file is corrupt or the update was
+    // replaced
+    fs::remove_file(&partial_path)?;
+    let hash = Sha256::digest(&contents).to_vec();
+    if hash != update.hash() {
+        return Err(AutoUpdateError::HashMismatch {
+            expected: update.to_hash_hex(),
+            actual: hash.to_hex(),
+        });
+    }
+    let verifier = SignedMessageVerifier::new(maintainers().collect());
+    if verifier.verify_signed_file(&signature, &contents).is_none() {
+        return Err(AutoUpdateError::InvalidSignature(url.to_string()));
+    }
+    extract_binary(&contents, binary_name)
+}
+
+/// Extracts the file named `binary_name` from a zip archive, wherever it is in the archive
+fn extract_binary(archive: &[u8], binary_name: &str) -> Result<Vec<u8>, AutoUpdateError> {
+    let mut archive = zip::ZipArchive::new(io::Cursor::new(archive))?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if !file.is_dir() && Path::new(file.name()).file_name() == Some(OsStr::new(binary_name)) {
+            let mut binary = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut binary)?;
+            return Ok(binary);
+        }
+    }
+    Err(AutoUpdateError::BinaryNotFound(binary_name.to_string()))
+}
+
+fn verify_hash(binary: &[u8], expected: &[u8]) -> Result<(), AutoUpdateError> {
+    let hash = Sha256::digest(binary).to_vec();
+    if hash != expected {
+        return Err(AutoUpdateError::HashMismatch {
+            expected: expected.to_hex(),
+            actual: hash.to_hex(),
+        });
+    }
+    Ok(())
+}
+
+/// Reads the body of `resp`, failing as soon as it exceeds [MAX_UPDATE_SIZE]
+async fn download_limited(mut resp: reqwest::Response, url: &str) -> Result<Vec<u8>, AutoUpdateError> {
+    let too_large = || AutoUpdateError::DownloadTooLarge {
+        url: url.to_string(),
+        max: MAX_UPDATE_SIZE,
+    };
+    if resp.content_length().map(|len| len > MAX_UPDATE_SIZE).unwrap_or(false) {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if (body.len() + chunk.len()) as u64 > MAX_UPDATE_SIZE {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+async fn resume_download(client: &reqwest::Client, url: &str, path: &Path) -> Result<(), AutoUpdateError> {
+    let offset = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let mut request = client.get(url);
+    if offset > 0 {
+        log::debug!(target: LOG_TARGET, "Resuming download of {} at byte {}", url, offset);
+        request = request.header(header::RANGE, format!("bytes={}-", offset));
+    }
+    let resp = request.send().await?;
+    if offset > 0 && resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The previous download had already completed
+        return Ok(());
+    }
+    let mut resp = resp.error_for_status()?;
+    // Servers that do not support range requests send the whole file again
+    let append = resp.status() == StatusCode::PARTIAL_CONTENT;
+    let mut size = if append { offset } else { 0 };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .await?;
+    while let Some(chunk) = resp.chunk().await? {
+        size += chunk.len() as u64;
+        if size > MAX_UPDATE_SIZE {
+            drop(file);
+            fs::remove_file(path)?;
+            return Err(AutoUpdateError::DownloadTooLarge {
+                url: url.to_string(),
+                max: MAX_UPDATE_SIZE,
+            });
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    fn stage(dir: &Path, contents: &[u8]) -> StagedUpdate {
+        let path = dir.join("staged");
+        fs::write(&path, contents).unwrap();
+        StagedUpdate {
+            path,
+            hash: Sha256::digest(contents).to_vec(),
+        }
+    }
+
+    #[test]
+    fn it_installs_and_rolls_back_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("tari_base_node");
+        fs::write(&target, b"v1").unwrap();
+        assert!(matches!(
+            rollback_update(&target),
+            Err(AutoUpdateError::NoPreviousVersion(_))
+        ));
+
+        let staged = stage(dir.path(), b"v2");
+        install_update(&staged, &target).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"v2");
+        assert_eq!(fs::read(backup_path(&target)).unwrap(), b"v1");
+        assert!(!staged.path().exists());
+
+        rollback_update(&target).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"v1");
+        assert_eq!(fs::read(backup_path(&target)).unwrap(), b"v2");
+    }
+
+    #[test]
+    fn it_refuses_to_install_a_changed_staged_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("tari_base_node");
+        fs::write(&target, b"v1").unwrap();
+        let staged = stage(dir.path(), b"v2");
+        fs::write(staged.path(), b"v2 tampered").unwrap();
+
+        assert!(matches!(
+            install_update(&staged, &target),
+            Err(AutoUpdateError::HashMismatch { .. })
+        ));
+        assert_eq!(fs::read(&target).unwrap(), b"v1");
+        assert!(!backup_path(&target).exists());
+    }
+
+    #[test]
+    fn it_extracts_the_binary_from_the_archive() {
+        let mut archive = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        archive.start_file("tari_base_node-linux/README.md", options).unwrap();
+        archive.write_all(b"readme").unwrap();
+        archive
+            .start_file("tari_base_node-linux/tari_base_node", options)
+            .unwrap();
+        archive.write_all(b"v2").unwrap();
+        let archive = archive.finish().unwrap().into_inner();
+
+        assert_eq!(extract_binary(&archive, "tari_base_node").unwrap(), b"v2");
+        assert!(matches!(
+            extract_binary(&archive, "tari_console_wallet"),
+            Err(AutoUpdateError::BinaryNotFound(_))
+        ));
+    }
+}
@@ -20,9 +20,13 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod delta;
 mod dns;
 mod signature;
 
+mod install;
+pub use install::{backup_path, download_update, install_update, rollback_update, StagedUpdate};
+
 mod service;
 pub use service::{SoftwareUpdaterHandle, SoftwareUpdaterService};
 
@@ -33,7 +37,10 @@ pub use error::AutoUpdateError;
 pub use semver::Version;
 pub use trust_dns_client::rr::dnssec::TrustAnchor;
 
-use crate::auto_update::{dns::UpdateSpec, signature::SignedMessageVerifier};
+use crate::auto_update::{
+    dns::UpdateSpec,
+    signature::{manifest_entries, SignedMessageVerifier},
+};
 use futures::future;
 use pgp::Deserializable;
 use reqwest::IntoUrl;
@@ -44,6 +51,11 @@ use std::{
     net::SocketAddr,
 };
 use tari_common::configuration::bootstrap::ApplicationType;
+use tari_comms::{
+    multiaddr::{Multiaddr, Protocol},
+    socks,
+    transports::SocksConfig,
+};
 use tari_utilities::hex::Hex;
 
 const LOG_TARGET: &str = "p2p::auto_update";
@@ -56,6 +68,9 @@ pub struct AutoUpdateConfig {
     pub download_base_url: String,
     pub hashes_url: String,
     pub hashes_sig_url: String,
+    /// Update files are downloaded through this SOCKS5 proxy, independently of the proxy used by the p2p transport.
+    /// If this is None, they are downloaded directly.
+    pub socks_proxy: Option<SocksConfig>,
 }
 
 impl AutoUpdateConfig {
@@ -73,6 +88,8 @@ pub async fn check_for_updates(
     let download_base_url = config.download_base_url.clone();
     let hashes_url = config.hashes_url.clone();
     let hashes_sig_url = config.hashes_sig_url.clone();
+    let socks_proxy = config.socks_proxy.clone();
+    let client = http_client(socks_proxy.as_ref())?;
     let dns_update = dns::DnsSoftwareUpdate::connect(config).await?;
 
     match dns_update.check_for_updates(app, arch, version).await? {
@@ -83,8 +100,8 @@ pub async fn check_for_updates(
                 update_spec
             );
             let (hashes, sig) = future::join(
-                download_hashes_file(&hashes_url),
-                download_hashes_sig_file(&hashes_sig_url),
+                download_hashes_file(&client, &hashes_url),
+                download_signature_file(&client, &hashes_sig_url),
             )
             .await;
             let hashes = hashes?;
@@ -98,6 +115,8 @@ pub async fn check_for_updates(
                     Ok(SoftwareUpdate {
                         spec: update_spec,
                         download_url,
+                        manifest: hashes.clone(),
+                        socks_proxy,
                     })
                 })
                 .transpose()
@@ -113,6 +132,11 @@ pub async fn check_for_updates(
 pub struct SoftwareUpdate {
     spec: UpdateSpec,
     download_url: String,
+    /// The maintainer signed hashes file the update was verified against. It also lists the hashes of the binaries in
+    /// the update archive, as `<archive file name>/<binary file name>`.
+    manifest: String,
+    /// The proxy the update was found through, which is also used to download it
+    socks_proxy: Option<SocksConfig>,
 }
 
 impl SoftwareUpdate {
@@ -129,6 +153,13 @@ impl SoftwareUpdate {
         self.spec.hash.to_hex()
     }
 
+    /// The SHA-256 hash the signed manifest lists for `file_name`
+    pub fn manifest_hash(&self, file_name: &str) -> Option<Vec<u8>> {
+        manifest_entries(&self.manifest)
+            .find(|(_, name)| name == file_name)
+            .map(|(hash, _)| hash)
+    }
+
     pub fn version(&self) -> &Version {
         &self.spec.version
     }
@@ -144,25 +175,59 @@ impl Display for SoftwareUpdate {
     }
 }
 
-async fn download_hashes_file<T: IntoUrl>(url: T) -> Result<String, AutoUpdateError> {
-    let resp = http_download(url).await?;
+async fn download_hashes_file<T: IntoUrl>(client: &reqwest::Client, url: T) -> Result<String, AutoUpdateError> {
+    let resp = http_download(client, url).await?;
     let txt = resp.text().await?;
     Ok(txt)
 }
 
-async fn download_hashes_sig_file<T: IntoUrl>(url: T) -> Result<pgp::StandaloneSignature, AutoUpdateError> {
-    let resp = http_download(url).await?;
+async fn download_signature_file<T: IntoUrl>(
+    client: &reqwest::Client,
+    url: T,
+) -> Result<pgp::StandaloneSignature, AutoUpdateError> {
+    let resp = http_download(client, url).await?;
     let sig_bytes = resp.bytes().await?;
     let cursor = io::Cursor::new(&sig_bytes);
     let sig = pgp::StandaloneSignature::from_bytes(cursor).map_err(AutoUpdateError::SignatureError)?;
     Ok(sig)
 }
 
-async fn http_download<T: IntoUrl>(url: T) -> Result<reqwest::Response, AutoUpdateError> {
-    let resp = reqwest::get(url).await?.error_for_status()?;
+async fn http_download<T: IntoUrl>(client: &reqwest::Client, url: T) -> Result<reqwest::Response, AutoUpdateError> {
+    let resp = client.get(url).send().await?.error_for_status()?;
     Ok(resp)
 }
 
+/// Builds the HTTP client for update downloads, connecting through the SOCKS5 proxy if one is given
+fn http_client(socks_proxy: Option<&SocksConfig>) -> Result<reqwest::Client, AutoUpdateError> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(socks_config) = socks_proxy {
+        let url = socks_proxy_url(&socks_config.proxy_address)
+            .ok_or_else(|| AutoUpdateError::InvalidProxyAddress(socks_config.proxy_address.to_string()))?;
+        let mut proxy = reqwest::Proxy::all(url.as_str())?;
+        if let socks::Authentication::Password(username, password) = &socks_config.authentication {
+            proxy = proxy.basic_auth(username, password);
+        }
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build()?)
+}
+
+/// Converts a proxy address such as `/ip4/127.0.0.1/tcp/1080` to a `socks5h` URL, so that DNS names are resolved by the
+/// proxy
+fn socks_proxy_url(address: &Multiaddr) -> Option<String> {
+    let mut protocols = address.iter();
+    let host = match protocols.next()? {
+        Protocol::Ip4(ip) => ip.to_string(),
+        Protocol::Ip6(ip) => format!("[{}]", ip),
+        Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => name.to_string(),
+        _ => return None,
+    };
+    match protocols.next()? {
+        Protocol::Tcp(port) => Some(format!("socks5h://{}:{}", host, port)),
+        _ => None,
+    }
+}
+
 const MAINTAINERS: &[&str] = &[
     include_str!("../../../../meta/gpg_keys/CjS77.asc"),
     include_str!("../../../../meta/gpg_keys/hansieodendaal.asc"),
@@ -186,4 +251,19 @@ mod test {
     fn all_maintainers_well_formed() {
         assert_eq!(maintainers().count(), MAINTAINERS.len());
     }
+
+    #[test]
+    fn socks_proxy_urls() {
+        let url = |addr: &str| socks_proxy_url(&addr.parse().unwrap());
+        assert_eq!(
+            url("/ip4/127.0.0.1/tcp/1080").as_deref(),
+            Some("socks5h://127.0.0.1:1080")
+        );
+        assert_eq!(url("/ip6/::1/tcp/1080").as_deref(), Some("socks5h://[::1]:1080"));
+        assert_eq!(
+            url("/dns4/proxy.example.com/tcp/1080").as_deref(),
+            Some("socks5h://proxy.example.com:1080")
+        );
+        assert_eq!(url("/ip4/127.0.0.1/udp/1080"), None);
+    }
 }
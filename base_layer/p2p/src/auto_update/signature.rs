@@ -38,23 +38,24 @@ impl SignedMessageVerifier {
         hashes: &str,
         update: &UpdateSpec,
     ) -> Option<(Vec<u8>, String)> {
-        self.verify_signature(signature, hashes)?;
+        self.verify_signature(signature, hashes.as_bytes())?;
 
-        hashes
-            .lines()
-            .filter_map(|line| {
-                let mut parts = line.splitn(2, ' ');
-                let hash = parts.next().map(|s| s.trim()).map(from_hex)?.ok()?;
-                let filename = parts.next()?;
-                Some((hash, filename.trim().to_string()))
-            })
-            .find(|(hash, _)| update.hash == *hash)
+        manifest_entries(hashes).find(|(hash, _)| update.hash == *hash)
     }
 
-    fn verify_signature(&self, signature: &pgp::StandaloneSignature, message: &str) -> Option<&pgp::SignedPublicKey> {
+    /// Verifies a detached maintainer signature over the contents of a downloaded file, returning the signer
+    pub fn verify_signed_file(
+        &self,
+        signature: &pgp::StandaloneSignature,
+        contents: &[u8],
+    ) -> Option<&pgp::SignedPublicKey> {
+        self.verify_signature(signature, contents)
+    }
+
+    fn verify_signature(&self, signature: &pgp::StandaloneSignature, message: &[u8]) -> Option<&pgp::SignedPublicKey> {
         // TODO: When pgp has SignedMessage support, implement that here
         self.maintainers.iter().find_map(|pk| {
-            if signature.verify(pk, message.as_bytes()).is_ok() {
+            if signature.verify(pk, message).is_ok() {
                 Some(pk)
             } else {
                 None
@@ -63,6 +64,16 @@ impl SignedMessageVerifier {
     }
 }
 
+/// The `(hash, file name)` entries of a hashes file, one `<hex hash> <file name>` pair per line
+pub fn manifest_entries(hashes: &str) -> impl Iterator<Item = (Vec<u8>, String)> + '_ {
+    hashes.lines().filter_map(|line| {
+        let mut parts = line.splitn(2, ' ');
+        let hash = parts.next().map(|s| s.trim()).map(from_hex)?.ok()?;
+        let filename = parts.next()?;
+        Some((hash, filename.trim().to_string()))
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -91,7 +102,7 @@ l9smp8LtJcXkw4cNgE4MB9VKdx+NhdbvWemt7ccldeL22hmyS24=
     fn it_verifies_signed_message() {
         let (sig, _) = pgp::StandaloneSignature::from_string(VALID_SIGNATURE.trim()).unwrap();
         let verifier = SignedMessageVerifier::new(maintainers().collect());
-        let signer = verifier.verify_signature(&sig, MESSAGE).unwrap();
+        let signer = verifier.verify_signature(&sig, MESSAGE.as_bytes()).unwrap();
 
         let (maintainer, _) = pgp::SignedPublicKey::from_string(MAINTAINERS[3]).unwrap();
         assert_eq!(*signer, maintainer);
@@ -101,6 +112,6 @@ l9smp8LtJcXkw4cNgE4MB9VKdx+NhdbvWemt7ccldeL22hmyS24=
     fn it_does_not_validate_with_tampered_message() {
         let (sig, _) = pgp::StandaloneSignature::from_string(VALID_SIGNATURE.trim()).unwrap();
         let verifier = SignedMessageVerifier::new(maintainers().collect());
-        assert!(verifier.verify_signature(&sig, "Zilip R. Phimmermann").is_none());
+        assert!(verifier.verify_signature(&sig, b"Zilip R. Phimmermann").is_none());
     }
 }
@@ -24,6 +24,7 @@
 use crate::{
     comms_connector::{InboundDomainConnector, PubsubDomainConnector},
     peer_seeds::{DnsSeedResolver, SeedPeer},
+    peer_snapshot::{self, PeerSnapshot},
     transport::{TorConfig, TransportType},
     MAJOR_NETWORK_VERSION,
     MINOR_NETWORK_VERSION,
@@ -44,8 +45,9 @@ use std::{
 use tari_common::configuration::Network;
 use tari_comms::{
     backoff::ConstantBackoff,
+    connection_manager::{AccessList, AccessRule},
     multiaddr::Multiaddr,
-    peer_manager::{NodeIdentity, Peer, PeerFeatures, PeerManagerError},
+    peer_manager::{NodeIdentity, Peer, PeerCapabilities, PeerFeatures, PeerManagerError},
     pipeline,
     protocol::{
         messaging::{MessagingEventSender, MessagingProtocolExtension},
@@ -87,6 +89,8 @@ pub enum CommsInitializationError {
     HiddenServiceBuilderError(#[from] tor::HiddenServiceBuilderError),
     #[error("Invalid liveness CIDRs error: `{0}`")]
     InvalidLivenessCidrs(String),
+    #[error("Invalid access rule: `{0}`")]
+    InvalidAccessRule(String),
     #[error("Could not add seed peers to comms layer: `{0}`")]
     FailedToAddSeedPeer(#[from] PeerManagerError),
     #[error("Cannot acquire exclusive file lock, another instance of the application is already running")]
@@ -139,8 +143,15 @@ pub struct P2pConfig {
     pub listener_liveness_max_sessions: usize,
     /// CIDR for addresses allowed to enter into liveness check mode on the listener.
     pub listener_liveness_allowlist_cidrs: Vec<String>,
+    /// Unparsed IP addresses, CIDR ranges, onion addresses or public keys. If not empty, only matching peers may
+    /// connect or be dialed.
+    pub allowed_addresses: Vec<String>,
+    /// Unparsed IP addresses, CIDR ranges, onion addresses or public keys of peers that may not connect or be dialed
+    pub blocked_addresses: Vec<String>,
     /// User agent string for this node
     pub user_agent: String,
+    /// The optional protocol capabilities that this node advertises to its peers
+    pub capabilities: PeerCapabilities,
     /// Unparsed peer seeds
     pub peer_seeds: Vec<String>,
     /// DNS seeds hosts. The DNS TXT records are queried from these hosts and the resulting peers added to the comms
@@ -150,6 +161,9 @@ pub struct P2pConfig {
     pub dns_seeds_name_server: SocketAddr,
     /// All DNS seed records must pass DNSSEC validation
     pub dns_seeds_use_dnssec: bool,
+    /// The file in which the recently connected base nodes are kept. If none of the DNS seeds can be resolved, the
+    /// node bootstraps from these peers. If this is set to None, no snapshot is kept.
+    pub peer_snapshot_path: Option<PathBuf>,
     /// The address to bind on using the TCP transport _in addition to_ the primary transport. This is typically useful
     /// for direct comms between a wallet and base node. If this is set to None, no listener will be bound.
     /// Default: None
@@ -333,10 +347,15 @@ async fn configure_comms_and_dht(
 
     let listener_liveness_allowlist_cidrs = parse_cidrs(&config.listener_liveness_allowlist_cidrs)
         .map_err(CommsInitializationError::InvalidLivenessCidrs)?;
+    let access_list = AccessList::new(
+        parse_access_rules(&config.allowed_addresses)?,
+        parse_access_rules(&config.blocked_addresses)?,
+    );
 
     let builder = builder
         .with_listener_liveness_max_sessions(config.listener_liveness_max_sessions)
         .with_listener_liveness_allowlist_cidrs(listener_liveness_allowlist_cidrs)
+        .with_access_list(access_list)
         .with_dial_backoff(ConstantBackoff::new(Duration::from_millis(500)))
         .with_peer_storage(peer_database, Some(file_lock));
 
@@ -416,6 +435,13 @@ pub fn acquire_exclusive_file_lock(db_path: &Path) -> Result<File, CommsInitiali
     Ok(file)
 }
 
+fn parse_access_rules(rules: &[String]) -> Result<Vec<AccessRule>, CommsInitializationError> {
+    rules
+        .iter()
+        .map(|rule| rule.parse().map_err(CommsInitializationError::InvalidAccessRule))
+        .collect()
+}
+
 /// Adds a new peer to the base node
 /// ## Parameters
 /// `comms_node` - A reference to the comms node. This is the communications stack
@@ -450,6 +476,7 @@ async fn add_all_peers(
 pub struct P2pInitializer {
     config: P2pConfig,
     connector: Option<PubsubDomainConnector>,
+    shutdown_signal: Option<ShutdownSignal>,
 }
 
 impl P2pInitializer {
@@ -457,9 +484,17 @@ impl P2pInitializer {
         Self {
             config,
             connector: Some(connector),
+            shutdown_signal: None,
         }
     }
 
+    /// Shut down comms with the given signal instead of the shutdown signal of the service stack, so that comms can be
+    /// closed after the other services have stopped
+    pub fn with_shutdown_signal(mut self, shutdown_signal: ShutdownSignal) -> Self {
+        self.shutdown_signal = Some(shutdown_signal);
+        self
+    }
+
     // Following are inlined due to Rust ICE: https://github.com/rust-lang/rust/issues/73537
     #[inline(always)]
     fn try_parse_seed_peers(peer_seeds_str: &[String]) -> Result<Vec<Peer>, ServiceInitializationError> {
@@ -528,6 +563,47 @@ impl P2pInitializer {
 
         Ok(peers)
     }
+
+    /// Returns the last-known-good peers to bootstrap from when none of the DNS seeds could be resolved
+    fn load_peer_snapshot(path: Option<&Path>) -> Vec<Peer> {
+        let path = match path {
+            Some(path) => path,
+            None => {
+                warn!(target: LOG_TARGET, "None of the DNS seeds could be resolved");
+                return Vec::new();
+            },
+        };
+        match PeerSnapshot::load(path) {
+            Ok(snapshot) if snapshot.is_empty() => {
+                warn!(
+                    target: LOG_TARGET,
+                    "None of the DNS seeds could be resolved and there are no last-known-good peers in {}",
+                    path.display()
+                );
+                Vec::new()
+            },
+            Ok(snapshot) => {
+                let peers = snapshot.to_peers();
+                info!(
+                    target: LOG_TARGET,
+                    "None of the DNS seeds could be resolved, bootstrapping from {} last-known-good peer(s) in {}",
+                    peers.len(),
+                    path.display()
+                );
+                peers
+            },
+            Err(err) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "None of the DNS seeds could be resolved and the last-known-good peers in {} could not be \
+                     loaded: {}",
+                    path.display(),
+                    err
+                );
+                Vec::new()
+            },
+        }
+    }
 }
 
 #[async_trait]
@@ -535,15 +611,20 @@ impl ServiceInitializer for P2pInitializer {
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
         let config = self.config.clone();
         let connector = self.connector.take().expect("P2pInitializer called more than once");
+        let shutdown_signal = self
+            .shutdown_signal
+            .take()
+            .unwrap_or_else(|| context.get_shutdown_signal());
 
         let mut builder = CommsBuilder::new()
-            .with_shutdown_signal(context.get_shutdown_signal())
+            .with_shutdown_signal(shutdown_signal)
             .with_node_identity(config.node_identity.clone())
             .with_node_info(NodeNetworkInfo {
                 major_version: MAJOR_NETWORK_VERSION,
                 minor_version: MINOR_NETWORK_VERSION,
                 network_byte: config.network.as_byte(),
                 user_agent: config.user_agent.clone(),
+                capabilities: config.capabilities,
             });
 
         if config.allow_test_addresses {
@@ -557,14 +638,33 @@ impl ServiceInitializer for P2pInitializer {
         let node_identity = comms.node_identity();
         add_all_peers(&peer_manager, &node_identity, peers).await?;
 
-        let peers = Self::try_resolve_dns_seeds(
+        let peers = match Self::try_resolve_dns_seeds(
             config.dns_seeds_name_server,
             &config.dns_seeds,
             config.dns_seeds_use_dnssec,
         )
-        .await?;
+        .await
+        {
+            Ok(peers) if !peers.is_empty() || config.dns_seeds.is_empty() => peers,
+            result => {
+                let snapshot_peers = Self::load_peer_snapshot(config.peer_snapshot_path.as_deref());
+                if snapshot_peers.is_empty() {
+                    result?
+                } else {
+                    snapshot_peers
+                }
+            },
+        };
         add_all_peers(&peer_manager, &node_identity, peers).await?;
 
+        if let Some(path) = config.peer_snapshot_path.clone() {
+            let connectivity = comms.connectivity();
+            let peer_manager = peer_manager.clone();
+            context
+                .clone()
+                .spawn_until_shutdown(move |_| peer_snapshot::run_peer_snapshot(path, connectivity, peer_manager));
+        }
+
         context.register_handle(comms.connectivity());
         context.register_handle(peer_manager);
         context.register_handle(comms);
@@ -39,6 +39,7 @@ pub mod domain_message;
 pub mod initialization;
 pub mod peer;
 pub mod peer_seeds;
+pub mod peer_snapshot;
 pub mod proto;
 pub mod services;
 pub mod tari_message;
@@ -0,0 +1,271 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A rolling snapshot of the base nodes this node recently connected to, stored in a small JSON file. When none of the
+//! DNS seeds can be resolved on startup, the node bootstraps from these last-known-good peers instead.
+
+use crate::peer_seeds::SeedPeer;
+use chrono::{DateTime, Utc};
+use log::*;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{Peer, PeerManager},
+};
+use tari_utilities::hex::Hex;
+use thiserror::Error;
+use tokio::time;
+
+const LOG_TARGET: &str = "p2p::peer_snapshot";
+
+/// The maximum number of peers kept in the snapshot
+pub const PEER_SNAPSHOT_MAX_PEERS: usize = 32;
+/// The interval at which the connected peers are added to the snapshot
+pub const PEER_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Error)]
+pub enum PeerSnapshotError {
+    #[error("Could not access the peer snapshot file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Could not parse the peer snapshot file: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotPeer {
+    /// Hex encoded public key of the peer
+    pub public_key: String,
+    pub addresses: Vec<String>,
+    /// The last time the peer was seen connected
+    pub last_connected: DateTime<Utc>,
+}
+
+/// The base nodes this node was recently connected to, most recently connected first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerSnapshot {
+    pub peers: Vec<SnapshotPeer>,
+}
+
+impl PeerSnapshot {
+    /// Loads the snapshot from the file, returning an empty snapshot if the file does not exist
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PeerSnapshotError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes the snapshot to a temporary file and moves it into place, so that an interrupted write does not lose the
+    /// previous snapshot
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), PeerSnapshotError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Records that the given peers were connected at `now`. Peers already in the snapshot are moved to the front and
+    /// the least recently connected peers are dropped beyond `max_peers`.
+    pub fn record<'a, I: IntoIterator<Item = &'a Peer>>(&mut self, peers: I, now: DateTime<Utc>, max_peers: usize) {
+        for peer in peers {
+            let public_key = peer.public_key.to_hex();
+            self.peers.retain(|p| p.public_key != public_key);
+            self.peers.push(SnapshotPeer {
+                public_key,
+                addresses: peer.addresses.iter().map(|a| a.to_string()).collect(),
+                last_connected: now,
+            });
+        }
+        self.peers.sort_by(|a, b| b.last_connected.cmp(&a.last_connected));
+        self.peers.truncate(max_peers);
+    }
+
+    /// Returns the peers in the snapshot, skipping any that cannot be parsed
+    pub fn to_peers(&self) -> Vec<Peer> {
+        self.peers
+            .iter()
+            .filter_map(|p| {
+                let seed = format!("{}::{}", p.public_key, p.addresses.join("::"));
+                match SeedPeer::from_str(&seed) {
+                    Ok(seed) => Some(seed.into()),
+                    Err(err) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Ignoring invalid peer `{}` in the peer snapshot: {}", p.public_key, err
+                        );
+                        None
+                    },
+                }
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+/// Periodically adds the base nodes that this node is connected to to the snapshot at `path`
+pub async fn run_peer_snapshot(path: PathBuf, mut connectivity: ConnectivityRequester, peer_manager: Arc<PeerManager>) {
+    let mut snapshot = PeerSnapshot::load(&path).unwrap_or_else(|err| {
+        warn!(
+            target: LOG_TARGET,
+            "Discarding the peer snapshot in {}: {}",
+            path.display(),
+            err
+        );
+        PeerSnapshot::default()
+    });
+    let mut interval = time::interval(PEER_SNAPSHOT_INTERVAL);
+    // The first tick completes immediately, when there are no connections yet
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let conns = match connectivity.get_active_connections().await {
+            Ok(conns) => conns,
+            Err(err) => {
+                debug!(target: LOG_TARGET, "Peer snapshot stopped: {}", err);
+                break;
+            },
+        };
+        let mut peers = Vec::with_capacity(conns.len());
+        for conn in conns.iter().filter(|c| c.peer_features().is_node()) {
+            match peer_manager.find_by_node_id(conn.peer_node_id()).await {
+                Ok(peer) => peers.push(peer),
+                Err(err) => debug!(target: LOG_TARGET, "Connected peer not found for the peer snapshot: {}", err),
+            }
+        }
+        if peers.is_empty() {
+            continue;
+        }
+        snapshot.record(&peers, Utc::now(), PEER_SNAPSHOT_MAX_PEERS);
+        match snapshot.save(&path) {
+            Ok(_) => trace!(
+                target: LOG_TARGET,
+                "Saved {} peer(s) to the peer snapshot in {}",
+                snapshot.len(),
+                path.display()
+            ),
+            Err(err) => warn!(
+                target: LOG_TARGET,
+                "Failed to save the peer snapshot in {}: {}",
+                path.display(),
+                err
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use rand::rngs::OsRng;
+    use tari_comms::{
+        multiaddr::Multiaddr,
+        peer_manager::{NodeId, PeerFeatures},
+        types::CommsPublicKey,
+    };
+    use tari_crypto::keys::PublicKey;
+
+    fn random_peer() -> Peer {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let address = Multiaddr::from_str("/ip4/127.0.0.1/tcp/18141").unwrap();
+        Peer::new(
+            public_key.clone(),
+            NodeId::from_public_key(&public_key),
+            vec![address].into(),
+            Default::default(),
+            PeerFeatures::COMMUNICATION_NODE,
+            Default::default(),
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn record_keeps_most_recent_peers() {
+        let now = Utc::now();
+        let peers = (0..3).map(|_| random_peer()).collect::<Vec<_>>();
+        let mut snapshot = PeerSnapshot::default();
+        snapshot.record(&peers[..2], now - ChronoDuration::minutes(10), 2);
+        snapshot.record(&peers[2..], now, 2);
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot.peers[0].public_key, peers[2].public_key.to_hex());
+
+        // Reconnecting to a peer moves it to the front without duplicating it
+        snapshot.record(&peers[1..2], now + ChronoDuration::minutes(1), 2);
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot.peers[0].public_key, peers[1].public_key.to_hex());
+        assert_eq!(snapshot.peers[1].public_key, peers[2].public_key.to_hex());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("peer_snapshot.json");
+        assert!(PeerSnapshot::load(&path).unwrap().is_empty());
+
+        let peer = random_peer();
+        let mut snapshot = PeerSnapshot::default();
+        snapshot.record(&[peer.clone()], Utc::now(), PEER_SNAPSHOT_MAX_PEERS);
+        snapshot.save(&path).unwrap();
+
+        let loaded = PeerSnapshot::load(&path).unwrap();
+        assert_eq!(loaded, snapshot);
+        let peers = loaded.to_peers();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].node_id, peer.node_id);
+        assert_eq!(
+            peers[0].addresses.iter().collect::<Vec<_>>(),
+            peer.addresses.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn to_peers_skips_invalid_entries() {
+        let snapshot = PeerSnapshot {
+            peers: vec![SnapshotPeer {
+                public_key: "not a key".to_string(),
+                addresses: vec!["/ip4/127.0.0.1/tcp/18141".to_string()],
+                last_connected: Utc::now(),
+            }],
+        };
+        assert!(snapshot.to_peers().is_empty());
+    }
+}
@@ -20,9 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use super::{error::LivenessError, state::Metadata};
+use super::{
+    error::LivenessError,
+    state::{Metadata, PeerPingStats},
+};
 use crate::proto::liveness::MetadataKey;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tari_comms::peer_manager::NodeId;
 use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
@@ -41,8 +44,12 @@ pub enum LivenessRequest {
     GetAvgLatency(NodeId),
     /// Get average latency for all connected nodes
     GetNetworkAvgLatency,
+    /// Get the round trip statistics of the peers that were pinged
+    GetPeerPingStats,
     /// Set the metadata attached to each ping/pong message
     SetMetadataEntry(MetadataKey, Vec<u8>),
+    /// Change the interval between ping rounds. The next round starts one interval from now.
+    SetAutoPingInterval(Duration),
 }
 
 /// Response type for `LivenessService`
@@ -56,6 +63,8 @@ pub enum LivenessResponse {
     AvgLatency(Option<u32>),
     /// The number of active neighbouring peers
     NumActiveNeighbours(usize),
+    /// Response for GetPeerPingStats
+    PeerPingStats(HashMap<NodeId, PeerPingStats>),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -151,6 +160,18 @@ impl LivenessHandle {
         }
     }
 
+    /// Change the interval between ping rounds, the next round starts one interval from now
+    pub async fn set_auto_ping_interval(&mut self, interval: Duration) -> Result<(), LivenessError> {
+        match self
+            .handle
+            .call(LivenessRequest::SetAutoPingInterval(interval))
+            .await??
+        {
+            LivenessResponse::Ok => Ok(()),
+            _ => Err(LivenessError::UnexpectedApiResponse),
+        }
+    }
+
     /// Retrieve the average latency for a given node
     pub async fn get_avg_latency(&mut self, node_id: NodeId) -> Result<Option<u32>, LivenessError> {
         match self.handle.call(LivenessRequest::GetAvgLatency(node_id)).await?? {
@@ -159,6 +180,14 @@ impl LivenessHandle {
         }
     }
 
+    /// Retrieve the round trip time histograms, losses and path quality of the peers that were pinged
+    pub async fn get_peer_ping_stats(&mut self) -> Result<HashMap<NodeId, PeerPingStats>, LivenessError> {
+        match self.handle.call(LivenessRequest::GetPeerPingStats).await?? {
+            LivenessResponse::PeerPingStats(stats) => Ok(stats),
+            _ => Err(LivenessError::UnexpectedApiResponse),
+        }
+    }
+
     /// Retrieve the mean average latency for all connected nodes
     pub async fn get_network_avg_latency(&mut self) -> Result<Option<u32>, LivenessError> {
         match self.handle.call(LivenessRequest::GetNetworkAvgLatency).await?? {
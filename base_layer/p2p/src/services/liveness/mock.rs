@@ -138,7 +138,12 @@ impl LivenessMock {
             GetNetworkAvgLatency => {
                 reply.send(Ok(LivenessResponse::AvgLatency(None))).unwrap();
             },
-            SetMetadataEntry(_, _) => {
+            GetPeerPingStats => {
+                reply
+                    .send(Ok(LivenessResponse::PeerPingStats(Default::default())))
+                    .unwrap();
+            },
+            SetMetadataEntry(_, _) | SetAutoPingInterval(_) => {
                 reply.send(Ok(LivenessResponse::Ok)).unwrap();
             },
         }
@@ -43,6 +43,7 @@ pub mod error;
 mod handle;
 pub use handle::{
     LivenessEvent,
+    LivenessEventReceiver,
     LivenessEventSender,
     LivenessHandle,
     LivenessRequest,
@@ -54,7 +55,7 @@ mod message;
 mod service;
 
 mod state;
-pub use state::Metadata;
+pub use state::{Metadata, PeerPingStats, RttHistogram, RTT_BUCKET_BOUNDS_MS};
 
 #[cfg(feature = "test-mocks")]
 pub mod mock;
@@ -31,12 +31,17 @@ use super::{
 };
 use crate::{
     domain_message::DomainMessage,
-    services::liveness::{handle::LivenessEventSender, LivenessEvent, PingPongEvent},
+    services::liveness::{handle::LivenessEventSender, LivenessEvent, Metadata, PingPongEvent},
     tari_message::TariMessageType,
 };
+use chrono::Utc;
 use futures::{future::Either, pin_mut, stream::StreamExt, Stream};
 use log::*;
-use std::{iter, sync::Arc, time::Instant};
+use std::{
+    iter,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tari_comms::{
     connectivity::{ConnectivityRequester, ConnectivitySelection},
     peer_manager::NodeId,
@@ -61,6 +66,7 @@ pub struct LivenessService<THandleStream, TPingStream> {
     outbound_messaging: OutboundMessageRequester,
     event_publisher: LivenessEventSender,
     shutdown_signal: ShutdownSignal,
+    ping_interval_changed: bool,
 }
 
 impl<TRequestStream, TPingStream> LivenessService<TRequestStream, TPingStream>
@@ -88,6 +94,7 @@ where
             event_publisher,
             shutdown_signal,
             config,
+            ping_interval_changed: false,
         }
     }
 
@@ -100,14 +107,7 @@ where
         let request_stream = self.request_rx.take().expect("ping_stream cannot be None").fuse();
         pin_mut!(request_stream);
 
-        let mut ping_tick = match self.config.auto_ping_interval {
-            Some(interval) => {
-                let mut interval = time::interval_at((Instant::now() + interval).into(), interval);
-                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
-                Either::Left(wrappers::IntervalStream::new(interval))
-            },
-            None => Either::Right(futures::stream::iter(iter::empty())),
-        };
+        let mut ping_tick = ping_tick_stream(self.config.auto_ping_interval);
 
         loop {
             tokio::select! {
@@ -115,6 +115,10 @@ where
                 Some(request_context) = request_stream.next() => {
                     let (request, reply_tx) = request_context.split();
                     let _ = reply_tx.send(self.handle_request(request).await);
+                    if self.ping_interval_changed {
+                        self.ping_interval_changed = false;
+                        ping_tick = ping_tick_stream(self.config.auto_ping_interval);
+                    }
                 },
 
                 // Tick events
@@ -197,7 +201,7 @@ where
     }
 
     async fn send_ping(&mut self, node_id: NodeId) -> Result<(), LivenessError> {
-        let msg = PingPongMessage::ping_with_metadata(self.state.metadata().clone());
+        let msg = PingPongMessage::ping_with_metadata(self.outbound_metadata());
         self.state.add_inflight_ping(msg.nonce, node_id.clone());
         debug!(target: LOG_TARGET, "Sending ping to peer '{}'", node_id.short_str(),);
 
@@ -210,7 +214,7 @@ where
     }
 
     async fn send_pong(&mut self, nonce: u64, dest: CommsPublicKey) -> Result<(), LivenessError> {
-        let msg = PingPongMessage::pong_with_metadata(nonce, self.state.metadata().clone());
+        let msg = PingPongMessage::pong_with_metadata(nonce, self.outbound_metadata());
         self.outbound_messaging
             .send_direct(dest, OutboundDomainMessage::new(TariMessageType::PingPong, msg))
             .await
@@ -242,10 +246,17 @@ where
                 let latency = self.state.get_network_avg_latency();
                 Ok(LivenessResponse::AvgLatency(latency))
             },
+            GetPeerPingStats => Ok(LivenessResponse::PeerPingStats(self.state.peer_ping_stats().clone())),
             SetMetadataEntry(key, value) => {
                 self.state.set_metadata_entry(key, value);
                 Ok(LivenessResponse::Ok)
             },
+            SetAutoPingInterval(interval) => {
+                debug!(target: LOG_TARGET, "Auto ping interval set to {:.2?}", interval);
+                self.config.auto_ping_interval = Some(interval);
+                self.ping_interval_changed = true;
+                Ok(LivenessResponse::Ok)
+            },
         }
     }
 
@@ -273,7 +284,7 @@ where
         debug!(target: LOG_TARGET, "Sending liveness ping to {} peer(s)", len_peers);
 
         for peer in selected_peers {
-            let msg = PingPongMessage::ping_with_metadata(self.state.metadata().clone());
+            let msg = PingPongMessage::ping_with_metadata(self.outbound_metadata());
             self.state.add_inflight_ping(msg.nonce, peer.clone());
             self.outbound_messaging
                 .send_direct_node_id(peer, OutboundDomainMessage::new(TariMessageType::PingPong, msg))
@@ -285,6 +296,13 @@ where
         Ok(())
     }
 
+    /// The local metadata with the current time, which lets peers estimate how far their clock is from ours
+    fn outbound_metadata(&self) -> Metadata {
+        let mut metadata = self.state.metadata().clone();
+        metadata.set_timestamp_millis(Utc::now().timestamp_millis().max(0) as u64);
+        metadata
+    }
+
     fn publish_event(&mut self, event: LivenessEvent) {
         let _ = self.event_publisher.send(Arc::new(event)).map_err(|_| {
             trace!(
@@ -303,6 +321,20 @@ where
     }
 }
 
+/// Ticks every `interval` starting one interval from now, or never if auto pinging is disabled
+fn ping_tick_stream(
+    interval: Option<Duration>,
+) -> Either<wrappers::IntervalStream, futures::stream::Iter<iter::Empty<time::Instant>>> {
+    match interval {
+        Some(interval) => {
+            let mut interval = time::interval_at((Instant::now() + interval).into(), interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            Either::Left(wrappers::IntervalStream::new(interval))
+        },
+        None => Either::Right(futures::stream::iter(iter::empty())),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
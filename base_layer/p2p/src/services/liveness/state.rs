@@ -24,12 +24,18 @@ use crate::proto::liveness::MetadataKey;
 use chrono::{NaiveDateTime, Utc};
 use std::{
     collections::{hash_map::RandomState, HashMap},
+    convert::TryInto,
     time::Duration,
 };
 use tari_comms::peer_manager::NodeId;
 
 const LATENCY_SAMPLE_WINDOW_SIZE: usize = 25;
 const MAX_INFLIGHT_TTL: Duration = Duration::from_secs(20);
+/// The upper bounds, in milliseconds, of the round trip time histogram buckets. Round trips of at least the last bound
+/// are counted in an extra bucket.
+pub const RTT_BUCKET_BOUNDS_MS: [u32; 7] = [50, 100, 250, 500, 1000, 2500, 5000];
+/// The round trip time at which the latency half of the path quality is 0.5
+const PATH_QUALITY_REFERENCE_RTT_MS: f64 = 500.0;
 
 /// Represents metadata in a ping/pong message.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -49,6 +55,18 @@ impl Metadata {
     pub fn get(&self, key: MetadataKey) -> Option<&Vec<u8>> {
         self.inner.get(&(key as i32))
     }
+
+    /// Sets the sender's clock, in milliseconds since the unix epoch
+    pub fn set_timestamp_millis(&mut self, timestamp: u64) {
+        self.insert(MetadataKey::Timestamp, timestamp.to_le_bytes().to_vec());
+    }
+
+    /// The sender's clock when the message was sent, in milliseconds since the unix epoch. Returns None if the sender
+    /// did not include its clock or the value is malformed.
+    pub fn timestamp_millis(&self) -> Option<u64> {
+        let bytes = self.get(MetadataKey::Timestamp)?.as_slice().try_into().ok()?;
+        Some(u64::from_le_bytes(bytes))
+    }
 }
 
 impl From<HashMap<i32, Vec<u8>>> for Metadata {
@@ -68,6 +86,7 @@ impl From<Metadata> for HashMap<i32, Vec<u8>, RandomState> {
 pub struct LivenessState {
     inflight_pings: HashMap<u64, (NodeId, NaiveDateTime)>,
     peer_latency: HashMap<NodeId, AverageLatency>,
+    peer_ping_stats: HashMap<NodeId, PeerPingStats>,
 
     pings_received: usize,
     pongs_received: usize,
@@ -130,17 +149,22 @@ impl LivenessState {
     /// Adds a ping to the inflight ping list, while noting the current time that a ping was sent.
     pub fn add_inflight_ping(&mut self, nonce: u64, node_id: NodeId) {
         let now = Utc::now().naive_utc();
+        self.peer_ping_stats.entry(node_id.clone()).or_default().pings_sent += 1;
         self.inflight_pings.insert(nonce, (node_id, now));
         self.clear_stale_inflight_pings();
     }
 
-    /// Clears inflight ping requests which have not responded
+    /// Clears inflight ping requests which have not responded, counting them as lost
     fn clear_stale_inflight_pings(&mut self) {
-        self.inflight_pings = self
-            .inflight_pings
-            .drain()
-            .filter(|(_, (_, time))| convert_to_std_duration(Utc::now().naive_utc() - *time) <= MAX_INFLIGHT_TTL)
-            .collect();
+        let now = Utc::now().naive_utc();
+        let peer_ping_stats = &mut self.peer_ping_stats;
+        self.inflight_pings.retain(|_, (node_id, time)| {
+            let is_stale = convert_to_std_duration(now - *time) > MAX_INFLIGHT_TTL;
+            if is_stale {
+                peer_ping_stats.entry(node_id.clone()).or_default().pings_lost += 1;
+            }
+            !is_stale
+        });
     }
 
     /// Returns true if the nonce is inflight, otherwise false
@@ -156,9 +180,12 @@ impl LivenessState {
         match self.inflight_pings.remove_entry(&nonce) {
             Some((_, (node_id, sent_time))) => {
                 let now = Utc::now().naive_utc();
-                let latency = self
-                    .add_latency_sample(node_id, convert_to_std_duration(now - sent_time))
-                    .calc_average();
+                let rtt = convert_to_std_duration(now - sent_time);
+                self.peer_ping_stats
+                    .entry(node_id.clone())
+                    .or_default()
+                    .add_pong(rtt, now);
+                let latency = self.add_latency_sample(node_id, rtt).calc_average();
                 Some(latency)
             },
             None => None,
@@ -179,6 +206,11 @@ impl LivenessState {
         self.peer_latency.get(node_id).map(|latency| latency.calc_average())
     }
 
+    /// Returns the round trip statistics of the peers that were pinged
+    pub fn peer_ping_stats(&self) -> &HashMap<NodeId, PeerPingStats> {
+        &self.peer_ping_stats
+    }
+
     pub fn get_network_avg_latency(&self) -> Option<u32> {
         let num_peers = self.peer_latency.len();
         self.peer_latency
@@ -233,6 +265,114 @@ impl AverageLatency {
     }
 }
 
+/// A histogram of round trip times with the buckets in [RTT_BUCKET_BOUNDS_MS](self::RTT_BUCKET_BOUNDS_MS). Unlike the
+/// average latency, the histogram keeps every sample since the node started, so that occasional slow round trips are
+/// visible without dominating the result.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RttHistogram {
+    buckets: [u64; RTT_BUCKET_BOUNDS_MS.len() + 1],
+    count: u64,
+    sum_ms: u64,
+    min_ms: u32,
+    max_ms: u32,
+}
+
+impl RttHistogram {
+    pub fn add_sample(&mut self, rtt: Duration) {
+        let ms = rtt.as_millis().min(u128::from(u32::MAX)) as u32;
+        let bucket = RTT_BUCKET_BOUNDS_MS
+            .iter()
+            .position(|bound| ms < *bound)
+            .unwrap_or(RTT_BUCKET_BOUNDS_MS.len());
+        self.buckets[bucket] += 1;
+        self.min_ms = if self.count == 0 { ms } else { self.min_ms.min(ms) };
+        self.max_ms = self.max_ms.max(ms);
+        self.count += 1;
+        self.sum_ms += u64::from(ms);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The number of samples in each bucket, the last bucket holds the samples of at least the last bound
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    pub fn min_ms(&self) -> Option<u32> {
+        Some(self.min_ms).filter(|_| self.count > 0)
+    }
+
+    pub fn max_ms(&self) -> Option<u32> {
+        Some(self.max_ms).filter(|_| self.count > 0)
+    }
+
+    pub fn mean_ms(&self) -> Option<u32> {
+        if self.count == 0 {
+            return None;
+        }
+        Some((self.sum_ms / self.count) as u32)
+    }
+
+    /// An estimate of the given percentile (0.0 to 1.0): the upper bound of the bucket holding it, limited to the
+    /// largest sample
+    pub fn percentile_ms(&self, percentile: f64) -> Option<u32> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((self.count as f64 * percentile.max(0.0).min(1.0)).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = RTT_BUCKET_BOUNDS_MS.get(i).copied().unwrap_or(self.max_ms);
+                return Some(bound.min(self.max_ms));
+            }
+        }
+        Some(self.max_ms)
+    }
+}
+
+/// The round trip times and answered pings of a peer
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerPingStats {
+    pub rtt: RttHistogram,
+    pub pings_sent: u64,
+    pub pongs_received: u64,
+    /// Pings that were not answered within the inflight ping TTL
+    pub pings_lost: u64,
+    pub last_rtt_ms: Option<u32>,
+    pub last_pong: Option<NaiveDateTime>,
+}
+
+impl PeerPingStats {
+    fn add_pong(&mut self, rtt: Duration, received_at: NaiveDateTime) {
+        self.rtt.add_sample(rtt);
+        self.pongs_received += 1;
+        self.last_rtt_ms = Some(rtt.as_millis().min(u128::from(u32::MAX)) as u32);
+        self.last_pong = Some(received_at);
+    }
+
+    /// The fraction of the pings that were answered or timed out that timed out
+    pub fn loss_rate(&self) -> f64 {
+        let total = self.pongs_received + self.pings_lost;
+        if total == 0 {
+            return 0.0;
+        }
+        self.pings_lost as f64 / total as f64
+    }
+
+    /// A score from 0.0 to 1.0 for the path to the peer, combining the fraction of pings answered with the 90th
+    /// percentile round trip time. A path without losses and a 90th percentile of 500ms scores 0.5. Returns None
+    /// until the peer has answered a ping.
+    pub fn path_quality(&self) -> Option<f64> {
+        let p90 = self.rtt.percentile_ms(0.9)?;
+        let latency_score = PATH_QUALITY_REFERENCE_RTT_MS / (PATH_QUALITY_REFERENCE_RTT_MS + f64::from(p90));
+        Some((1.0 - self.loss_rate()) * latency_score)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -305,4 +445,53 @@ mod test {
         state.set_metadata_entry(MetadataKey::ChainMetadata, b"dummy-data".to_vec());
         assert_eq!(state.metadata().get(MetadataKey::ChainMetadata).unwrap(), b"dummy-data");
     }
+
+    #[test]
+    fn rtt_histogram() {
+        let mut histogram = RttHistogram::default();
+        assert_eq!(histogram.percentile_ms(0.5), None);
+        for ms in &[10, 20, 30, 80, 120, 300, 6000] {
+            histogram.add_sample(Duration::from_millis(*ms));
+        }
+        assert_eq!(histogram.count(), 7);
+        assert_eq!(histogram.buckets(), &[3, 1, 1, 1, 0, 0, 0, 1]);
+        assert_eq!(histogram.min_ms(), Some(10));
+        assert_eq!(histogram.max_ms(), Some(6000));
+        assert_eq!(histogram.mean_ms(), Some(937));
+        assert_eq!(histogram.percentile_ms(0.5), Some(100));
+        assert_eq!(histogram.percentile_ms(0.4), Some(50));
+        assert_eq!(histogram.percentile_ms(1.0), Some(6000));
+    }
+
+    #[test]
+    fn peer_ping_stats() {
+        let mut state = LivenessState::new();
+        let node_id = NodeId::default();
+        state.add_inflight_ping(1, node_id.clone());
+        state.add_inflight_ping(2, node_id.clone());
+        state.record_pong(1);
+        // A ping that is not answered within the TTL is lost
+        let (_, sent_time) = state.inflight_pings.get_mut(&2).unwrap();
+        *sent_time -= chrono::Duration::seconds(MAX_INFLIGHT_TTL.as_secs() as i64 + 1);
+        state.add_inflight_ping(3, node_id.clone());
+
+        let stats = state.peer_ping_stats().get(&node_id).unwrap();
+        assert_eq!(stats.pings_sent, 3);
+        assert_eq!(stats.pongs_received, 1);
+        assert_eq!(stats.pings_lost, 1);
+        assert!((stats.loss_rate() - 0.5).abs() < f64::EPSILON);
+        // The pong was answered immediately, so only the loss reduces the path quality
+        let quality = stats.path_quality().unwrap();
+        assert!(quality > 0.45 && quality <= 0.5);
+    }
+
+    #[test]
+    fn metadata_timestamp() {
+        let mut metadata = Metadata::new();
+        assert_eq!(metadata.timestamp_millis(), None);
+        metadata.set_timestamp_millis(1_634_000_000_123);
+        assert_eq!(metadata.timestamp_millis(), Some(1_634_000_000_123));
+        metadata.insert(MetadataKey::Timestamp, vec![1, 2, 3]);
+        assert_eq!(metadata.timestamp_millis(), None);
+    }
 }
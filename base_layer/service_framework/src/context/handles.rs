@@ -49,6 +49,7 @@ pub(crate) fn create_context_notifier_pair(shutdown_signal: ShutdownSignal) -> (
 pub struct ServiceInitializerContext {
     inner: ServiceHandles,
     ready_signal: ShutdownSignal,
+    task_name: &'static str,
 }
 
 impl ServiceInitializerContext {
@@ -62,9 +63,16 @@ impl ServiceInitializerContext {
         Self {
             inner: ServiceHandles::new(shutdown_signal),
             ready_signal,
+            task_name: "service",
         }
     }
 
+    /// Set the name given to tasks spawned from this context
+    pub(crate) fn with_task_name(mut self, task_name: &'static str) -> Self {
+        self.task_name = task_name;
+        self
+    }
+
     /// Insert a service handle with the given name
     pub fn register_handle<H>(&self, handle: H)
     where H: Any + Send {
@@ -84,7 +92,7 @@ impl ServiceInitializerContext {
         Fut: Future + Send + 'static,
         Fut::Output: Send,
     {
-        task::spawn(self.wait_ready().then(f))
+        spawn_named(self.task_name, self.wait_ready().then(f))
     }
 
     /// Spawn a task once handles are ready. The resolved handles are passed into this closure.
@@ -95,7 +103,7 @@ impl ServiceInitializerContext {
         Fut: Future + Send + 'static,
         Fut::Output: Send + 'static,
     {
-        task::spawn(async move {
+        spawn_named(self.task_name, async move {
             let shutdown_signal = self.get_shutdown_signal();
             let _ = self.ready_signal.await;
             let fut = f(self.inner);
@@ -124,6 +132,24 @@ impl ServiceInitializerContext {
     }
 }
 
+/// Spawn a task with a name that is shown in tokio-console, when built with the `tokio-console` feature and
+/// `RUSTFLAGS="--cfg tokio_unstable"`
+fn spawn_named<F>(name: &str, future: F) -> task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tokio-console"))]
+    {
+        task::Builder::new().name(name).spawn(future)
+    }
+    #[cfg(not(all(tokio_unstable, feature = "tokio-console")))]
+    {
+        let _ = name;
+        task::spawn(future)
+    }
+}
+
 /// This macro unlocks a Mutex or RwLock. If the lock is
 /// poisoned (i.e. panic while unlocked) the last value
 /// before the panic is used.
@@ -22,6 +22,7 @@
 
 use crate::context::ServiceInitializerContext;
 use async_trait::async_trait;
+use std::any;
 
 pub type ServiceInitializationError = anyhow::Error;
 
@@ -33,6 +34,17 @@ type Output = Result<(), ServiceInitializationError>;
 pub trait ServiceInitializer {
     /// Async initialization code for a service
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Output;
+
+    /// The name of the service, used to name the tasks spawned from its `ServiceInitializerContext`. Defaults to the
+    /// name of the initializer type without its module path or generic parameters.
+    fn name(&self) -> &'static str {
+        short_type_name(any::type_name::<Self>())
+    }
+}
+
+fn short_type_name(type_name: &'static str) -> &'static str {
+    let without_generics = type_name.split('<').next().unwrap_or(type_name);
+    without_generics.rsplit("::").next().unwrap_or(without_generics)
 }
 
 /// Implementation of ServiceInitializer for any function matching the signature of `ServiceInitializer::initialize`
@@ -73,3 +85,21 @@ where TFunc: FnOnce(ServiceInitializerContext) -> Output + Send
         (f)(context)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn short_type_name_strips_path_and_generics() {
+        assert_eq!(
+            short_type_name("tari_p2p::services::LivenessInitializer"),
+            "LivenessInitializer"
+        );
+        assert_eq!(
+            short_type_name("tari_core::mempool::MempoolServiceInitializer<tari_core::Foo<u8>>"),
+            "MempoolServiceInitializer"
+        );
+        assert_eq!(short_type_name("Dummy"), "Dummy");
+    }
+}
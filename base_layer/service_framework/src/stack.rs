@@ -76,7 +76,10 @@ impl StackBuilder {
         let (mut notifier, context) = create_context_notifier_pair(shutdown_signal);
 
         // Collect all the initialization futures
-        let init_futures = initializers.iter_mut().map(|init| init.initialize(context.clone()));
+        let init_futures = initializers.iter_mut().map(|init| {
+            let name = init.name();
+            init.initialize(context.clone().with_task_name(name))
+        });
 
         // Run all the initializers concurrently and check each Result returning an error
         // on the first one that failed.
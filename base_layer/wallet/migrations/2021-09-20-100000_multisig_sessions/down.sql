@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS multisig_signing_rounds;
+DROP TABLE IF EXISTS multisig_sessions;
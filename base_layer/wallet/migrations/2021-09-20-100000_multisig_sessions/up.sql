@@ -0,0 +1,13 @@
+CREATE TABLE multisig_sessions (
+    session_id BIGINT PRIMARY KEY NOT NULL,
+    threshold INTEGER NOT NULL,
+    participants BLOB NOT NULL,
+    timestamp DATETIME NOT NULL
+);
+
+CREATE TABLE multisig_signing_rounds (
+    signing_id BIGINT PRIMARY KEY NOT NULL,
+    session_id BIGINT NOT NULL,
+    signing_round TEXT NOT NULL,
+    FOREIGN KEY(session_id) REFERENCES multisig_sessions(session_id)
+);
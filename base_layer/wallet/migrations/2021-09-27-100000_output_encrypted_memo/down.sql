@@ -0,0 +1,27 @@
+PRAGMA foreign_keys=off;
+ALTER TABLE outputs RENAME TO outputs_old;
+CREATE TABLE outputs (
+    id INTEGER NOT NULL PRIMARY KEY,
+    commitment BLOB NOT NULL,
+    spending_key BLOB NOT NULL,
+    value INTEGER NOT NULL,
+    flags INTEGER NOT NULL,
+    maturity INTEGER NOT NULL,
+    status INTEGER NOT NULL,
+    tx_id INTEGER NULL,
+    hash BLOB NOT NULL,
+    script BLOB NOT NULL,
+    input_data BLOB NOT NULL,
+    script_private_key BLOB NOT NULL,
+    sender_offset_public_key BLOB NOT NULL,
+    metadata_signature_nonce BLOB NOT NULL,
+    metadata_signature_u_key BLOB NOT NULL,
+    metadata_signature_v_key BLOB NOT NULL,
+    CONSTRAINT unique_commitment UNIQUE (commitment)
+);
+
+INSERT INTO outputs (id, commitment, spending_key, value, flags, maturity, status, tx_id, hash, script, input_data, script_private_key, sender_offset_public_key, metadata_signature_nonce, metadata_signature_u_key, metadata_signature_v_key)
+SELECT id, commitment, spending_key, value, flags, maturity, status, tx_id, hash, script, input_data, script_private_key, sender_offset_public_key, metadata_signature_nonce, metadata_signature_u_key, metadata_signature_v_key
+FROM outputs_old;
+DROP TABLE outputs_old;
+PRAGMA foreign_keys=on;
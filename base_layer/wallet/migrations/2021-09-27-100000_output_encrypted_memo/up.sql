@@ -0,0 +1 @@
+ALTER TABLE outputs ADD COLUMN encrypted_memo BLOB NULL;
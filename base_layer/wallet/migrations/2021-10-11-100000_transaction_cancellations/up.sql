@@ -0,0 +1,6 @@
+CREATE TABLE transaction_cancellations (
+    tx_id BIGINT PRIMARY KEY NOT NULL,
+    reason TEXT NOT NULL,
+    cancelled_at DATETIME NOT NULL,
+    outputs_released INTEGER NOT NULL
+);
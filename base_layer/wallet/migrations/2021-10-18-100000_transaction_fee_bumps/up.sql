@@ -0,0 +1,6 @@
+CREATE TABLE transaction_fee_bumps (
+    tx_id BIGINT PRIMARY KEY NOT NULL,
+    parent_tx_id BIGINT NOT NULL,
+    fee_per_gram BIGINT NOT NULL,
+    timestamp DATETIME NOT NULL
+);
@@ -0,0 +1,14 @@
+CREATE TABLE wallet_accounts (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT UNIQUE NOT NULL,
+    key_index BIGINT NOT NULL,
+    created_at DATETIME NOT NULL
+);
+-- The default account derives its keys from the primary key chain, so its key index is not used
+INSERT INTO wallet_accounts (id, name, key_index, created_at) VALUES (0, 'default', 0, CURRENT_TIMESTAMP);
+ALTER TABLE outputs ADD COLUMN account_id BIGINT NOT NULL DEFAULT 0;
+CREATE TABLE transaction_accounts (
+    tx_id BIGINT NOT NULL,
+    account_id BIGINT NOT NULL,
+    PRIMARY KEY (tx_id, account_id)
+);
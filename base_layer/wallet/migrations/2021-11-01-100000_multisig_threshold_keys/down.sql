@@ -0,0 +1,5 @@
+DROP TABLE multisig_outputs;
+DROP TABLE multisig_signing_rounds;
+DROP TABLE multisig_sessions;
+ALTER TABLE multisig_legacy_sessions RENAME TO multisig_sessions;
+ALTER TABLE multisig_legacy_signing_rounds RENAME TO multisig_signing_rounds;
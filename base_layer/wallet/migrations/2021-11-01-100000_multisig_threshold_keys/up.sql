@@ -0,0 +1,27 @@
+-- Sessions with aggregated keys can not be used for threshold signing. They are kept with their signing rounds in
+-- legacy tables rather than deleted, and the new sessions start from empty tables.
+ALTER TABLE multisig_signing_rounds RENAME TO multisig_legacy_signing_rounds;
+ALTER TABLE multisig_sessions RENAME TO multisig_legacy_sessions;
+
+CREATE TABLE multisig_sessions (
+    session_id BIGINT PRIMARY KEY NOT NULL,
+    threshold INTEGER NOT NULL,
+    participants BLOB NOT NULL,
+    timestamp DATETIME NOT NULL,
+    dealings TEXT NOT NULL DEFAULT '[]',
+    accepted BOOLEAN NOT NULL DEFAULT 1
+);
+
+CREATE TABLE multisig_signing_rounds (
+    signing_id BIGINT PRIMARY KEY NOT NULL,
+    session_id BIGINT NOT NULL,
+    signing_round TEXT NOT NULL,
+    FOREIGN KEY(session_id) REFERENCES multisig_sessions(session_id)
+);
+
+CREATE TABLE multisig_outputs (
+    commitment BLOB PRIMARY KEY NOT NULL,
+    session_id BIGINT NOT NULL,
+    multisig_output TEXT NOT NULL,
+    FOREIGN KEY(session_id) REFERENCES multisig_sessions(session_id)
+);
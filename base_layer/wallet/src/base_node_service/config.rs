@@ -31,6 +31,12 @@ pub struct BaseNodeServiceConfig {
     pub base_node_rpc_pool_size: usize,
     pub request_max_age: Duration,
     pub event_channel_size: usize,
+    /// The maximum time a base node may take to respond to a health check before it is considered unhealthy
+    pub base_node_max_latency: Duration,
+    /// The maximum time to wait for a connection to the base node before it is considered unhealthy
+    pub base_node_connect_timeout: Duration,
+    /// The number of consecutive failed health checks after which the wallet fails over to the next base node
+    pub base_node_failover_threshold: usize,
 }
 
 impl Default for BaseNodeServiceConfig {
@@ -40,6 +46,9 @@ impl Default for BaseNodeServiceConfig {
             base_node_rpc_pool_size: 10,
             request_max_age: Duration::from_secs(60),
             event_channel_size: 250,
+            base_node_max_latency: Duration::from_secs(10),
+            base_node_connect_timeout: Duration::from_secs(60),
+            base_node_failover_threshold: 3,
         }
     }
 }
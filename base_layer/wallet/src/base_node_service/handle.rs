@@ -24,6 +24,7 @@ use super::{error::BaseNodeServiceError, service::BaseNodeState};
 use std::{sync::Arc, time::Duration};
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_comms::peer_manager::Peer;
+use tari_core::{blocks::BlockHeader, mempool::FeePerGramStat};
 use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
 use tower::Service;
@@ -36,7 +37,11 @@ pub enum BaseNodeServiceRequest {
     GetChainMetadata,
     SetBaseNodePeer(Box<Peer>),
     GetBaseNodePeer,
+    SetBaseNodePeers(Vec<Peer>),
+    GetConnectedBaseNode,
     GetBaseNodeLatency,
+    GetMempoolFeePerGramStats(u64),
+    GetRecentBlockHeaders(u64),
 }
 /// API Response enum
 #[derive(Debug)]
@@ -44,12 +49,20 @@ pub enum BaseNodeServiceResponse {
     ChainMetadata(Option<ChainMetadata>),
     BaseNodePeerSet,
     BaseNodePeer(Option<Box<Peer>>),
+    BaseNodePeersSet,
     Latency(Option<Duration>),
+    MempoolFeePerGramStats(Vec<FeePerGramStat>),
+    BlockHeaders(Vec<BlockHeader>),
 }
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum BaseNodeEvent {
     BaseNodeStateChanged(BaseNodeState),
     BaseNodePeerSet(Box<Peer>),
+    /// The current base node failed its health checks and the wallet switched to the given base node
+    BaseNodeFailover {
+        peer: Box<Peer>,
+        reason: String,
+    },
 }
 
 /// The Base Node Service Handle is a struct that contains the interfaces used to communicate with a running
@@ -100,10 +113,58 @@ impl BaseNodeServiceHandle {
         }
     }
 
+    /// Set the prioritized list of base nodes that the wallet fails over to when the current base node is unhealthy
+    pub async fn set_base_node_peers(&mut self, peers: Vec<Peer>) -> Result<(), BaseNodeServiceError> {
+        match self
+            .handle
+            .call(BaseNodeServiceRequest::SetBaseNodePeers(peers))
+            .await??
+        {
+            BaseNodeServiceResponse::BaseNodePeersSet => Ok(()),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the base node the wallet is currently connected to, or None if the wallet is not online
+    pub async fn get_connected_base_node(&mut self) -> Result<Option<Peer>, BaseNodeServiceError> {
+        match self.handle.call(BaseNodeServiceRequest::GetConnectedBaseNode).await?? {
+            BaseNodeServiceResponse::BaseNodePeer(peer) => Ok(peer.map(|p| *p)),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_base_node_latency(&mut self) -> Result<Option<Duration>, BaseNodeServiceError> {
         match self.handle.call(BaseNodeServiceRequest::GetBaseNodeLatency).await?? {
             BaseNodeServiceResponse::Latency(latency) => Ok(latency),
             _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Requests the fee per gram stats of the transactions in the base node mempool, grouped into the next `count`
+    /// blocks
+    pub async fn get_mempool_fee_per_gram_stats(
+        &mut self,
+        count: u64,
+    ) -> Result<Vec<FeePerGramStat>, BaseNodeServiceError> {
+        match self
+            .handle
+            .call(BaseNodeServiceRequest::GetMempoolFeePerGramStats(count))
+            .await??
+        {
+            BaseNodeServiceResponse::MempoolFeePerGramStats(stats) => Ok(stats),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Requests the headers of the last `count` blocks of the base node chain, ordered from the chain tip downwards
+    pub async fn get_recent_block_headers(&mut self, count: u64) -> Result<Vec<BlockHeader>, BaseNodeServiceError> {
+        match self
+            .handle
+            .call(BaseNodeServiceRequest::GetRecentBlockHeaders(count))
+            .await??
+        {
+            BaseNodeServiceResponse::BlockHeaders(headers) => Ok(headers),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -121,10 +121,19 @@ impl MockBaseNodeService {
                 let peer = self.base_node_peer.clone();
                 Ok(BaseNodeServiceResponse::BaseNodePeer(peer.map(Box::new)))
             },
+            BaseNodeServiceRequest::SetBaseNodePeers(_) => Ok(BaseNodeServiceResponse::BaseNodePeersSet),
+            BaseNodeServiceRequest::GetConnectedBaseNode => {
+                let peer = self.base_node_peer.clone();
+                Ok(BaseNodeServiceResponse::BaseNodePeer(peer.map(Box::new)))
+            },
             BaseNodeServiceRequest::GetChainMetadata => Ok(BaseNodeServiceResponse::ChainMetadata(
                 self.state.chain_metadata.clone(),
             )),
             BaseNodeServiceRequest::GetBaseNodeLatency => Ok(BaseNodeServiceResponse::Latency(None)),
+            BaseNodeServiceRequest::GetMempoolFeePerGramStats(_) => {
+                Ok(BaseNodeServiceResponse::MempoolFeePerGramStats(Vec::new()))
+            },
+            BaseNodeServiceRequest::GetRecentBlockHeaders(_) => Ok(BaseNodeServiceResponse::BlockHeaders(Vec::new())),
         }
     }
 }
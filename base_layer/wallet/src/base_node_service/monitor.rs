@@ -22,6 +22,7 @@
 
 use crate::{
     base_node_service::{
+        config::BaseNodeServiceConfig,
         handle::{BaseNodeEvent, BaseNodeEventSender},
         service::BaseNodeState,
     },
@@ -43,27 +44,29 @@ use tokio::{sync::RwLock, time};
 const LOG_TARGET: &str = "wallet::base_node_service::chain_metadata_monitor";
 
 pub struct BaseNodeMonitor<T> {
-    interval: Duration,
+    config: BaseNodeServiceConfig,
     state: Arc<RwLock<BaseNodeState>>,
     db: WalletDatabase<T>,
     wallet_connectivity: WalletConnectivityHandle,
     event_publisher: BaseNodeEventSender,
+    failed_checks: usize,
 }
 
 impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
     pub fn new(
-        interval: Duration,
+        config: BaseNodeServiceConfig,
         state: Arc<RwLock<BaseNodeState>>,
         db: WalletDatabase<T>,
         wallet_connectivity: WalletConnectivityHandle,
         event_publisher: BaseNodeEventSender,
     ) -> Self {
         Self {
-            interval,
+            config,
             state,
             db,
             wallet_connectivity,
             event_publisher,
+            failed_checks: 0,
         }
     }
 
@@ -80,7 +83,7 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
                     );
                     break;
                 },
-                Err(e @ BaseNodeMonitorError::RpcFailed(_)) => {
+                Err(e @ BaseNodeMonitorError::RpcFailed(_)) | Err(e @ BaseNodeMonitorError::Unreachable(_)) => {
                     warn!(target: LOG_TARGET, "Connectivity failure to base node: {}", e);
                     self.map_state(move |_| BaseNodeState {
                         chain_metadata: None,
@@ -89,6 +92,7 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
                         latency: None,
                     })
                     .await;
+                    self.record_failed_check(e.to_string());
                     continue;
                 },
                 Err(e @ BaseNodeMonitorError::InvalidBaseNodeResponse(_)) |
@@ -107,11 +111,13 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
     async fn monitor_node(&mut self) -> Result<(), BaseNodeMonitorError> {
         loop {
             let start = Instant::now();
-            let mut client = self
-                .wallet_connectivity
-                .obtain_base_node_wallet_rpc_client()
-                .await
-                .ok_or(BaseNodeMonitorError::NodeShuttingDown)?;
+            let mut client = time::timeout(
+                self.config.base_node_connect_timeout,
+                self.wallet_connectivity.obtain_base_node_wallet_rpc_client(),
+            )
+            .await
+            .map_err(|_| BaseNodeMonitorError::Unreachable(self.config.base_node_connect_timeout))?
+            .ok_or(BaseNodeMonitorError::NodeShuttingDown)?;
             trace!(
                 target: LOG_TARGET,
                 "Obtain RPC client {} ms",
@@ -143,6 +149,19 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
                 latency.as_millis()
             );
 
+            if !is_synced {
+                self.record_failed_check(format!("Base node {} is not synced", base_node_id));
+            } else if latency > self.config.base_node_max_latency {
+                self.record_failed_check(format!(
+                    "Base node {} latency of {} ms exceeds the maximum of {} ms",
+                    base_node_id,
+                    latency.as_millis(),
+                    self.config.base_node_max_latency.as_millis()
+                ));
+            } else {
+                self.failed_checks = 0;
+            }
+
             let start = Instant::now();
             self.db.set_chain_metadata(chain_metadata.clone()).await?;
             trace!(
@@ -161,7 +180,7 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
             .await;
             trace!(target: LOG_TARGET, "Publish event {} ms", start.elapsed().as_millis());
 
-            time::sleep(self.interval).await
+            time::sleep(self.config.base_node_monitor_refresh_interval).await
         }
 
         // loop only exits on shutdown/error
@@ -169,6 +188,43 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
         Ok(())
     }
 
+    /// Record a failed health check of the current base node. Once the number of consecutive failed health checks
+    /// reaches the failover threshold the wallet switches to the next base node in its prioritized list.
+    fn record_failed_check(&mut self, reason: String) {
+        self.failed_checks += 1;
+        if self.failed_checks < self.config.base_node_failover_threshold {
+            debug!(
+                target: LOG_TARGET,
+                "Base node health check failed ({}/{}): {}",
+                self.failed_checks,
+                self.config.base_node_failover_threshold,
+                reason
+            );
+            return;
+        }
+        self.failed_checks = 0;
+
+        match self.wallet_connectivity.failover_base_node() {
+            Some(peer) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "{}. Failing over to base node {}", reason, peer.node_id
+                );
+                self.publish_event(BaseNodeEvent::BaseNodeFailover {
+                    peer: Box::new(peer.clone()),
+                    reason,
+                });
+                self.publish_event(BaseNodeEvent::BaseNodePeerSet(Box::new(peer)));
+            },
+            None => {
+                warn!(
+                    target: LOG_TARGET,
+                    "{}. There is no other base node to fail over to", reason
+                );
+            },
+        }
+    }
+
     async fn map_state<F>(&self, transform: F)
     where F: FnOnce(&BaseNodeState) -> BaseNodeState {
         let new_state = {
@@ -191,6 +247,8 @@ enum BaseNodeMonitorError {
     NodeShuttingDown,
     #[error("Rpc error: {0}")]
     RpcFailed(#[from] RpcError),
+    #[error("Base node could not be reached within {0:.0?}")]
+    Unreachable(Duration),
     #[error("Invalid base node response: {0}")]
     InvalidBaseNodeResponse(String),
     #[error("Wallet storage error: {0}")]
@@ -27,20 +27,27 @@ use super::{
 };
 use crate::{
     base_node_service::monitor::BaseNodeMonitor,
-    connectivity_service::WalletConnectivityHandle,
+    connectivity_service::{OnlineStatus, WalletConnectivityHandle},
     storage::database::{WalletBackend, WalletDatabase},
 };
 use chrono::NaiveDateTime;
 use futures::{future, StreamExt};
 use log::*;
-use std::{sync::Arc, time::Duration};
+use std::{convert::TryFrom, sync::Arc, time::Duration};
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_comms::peer_manager::Peer;
+use tari_core::{
+    base_node::proto::wallet_rpc::GetMempoolFeePerGramStatsRequest,
+    blocks::BlockHeader,
+    mempool::FeePerGramStat,
+};
 use tari_service_framework::reply_channel::Receiver;
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::RwLock;
 
 const LOG_TARGET: &str = "wallet::base_node_service::service";
+/// The maximum number of recent block headers that can be requested from the base node at once
+const MAX_RECENT_BLOCK_HEADERS: u64 = 50;
 
 /// State determined from Base Node Service Requests
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -110,7 +117,7 @@ where T: WalletBackend + 'static
             .expect("Wallet Base Node Service initialized without shutdown signal");
 
         let monitor = BaseNodeMonitor::new(
-            self.config.base_node_monitor_refresh_interval,
+            self.config.clone(),
             self.state.clone(),
             self.db.clone(),
             self.wallet_connectivity.clone(),
@@ -177,6 +184,17 @@ where T: WalletBackend + 'static
                 let peer = self.wallet_connectivity.get_current_base_node_peer().map(Box::new);
                 Ok(BaseNodeServiceResponse::BaseNodePeer(peer))
             },
+            BaseNodeServiceRequest::SetBaseNodePeers(peers) => {
+                self.wallet_connectivity.set_base_node_peers(peers);
+                Ok(BaseNodeServiceResponse::BaseNodePeersSet)
+            },
+            BaseNodeServiceRequest::GetConnectedBaseNode => {
+                let peer = match self.wallet_connectivity.get_connectivity_status() {
+                    OnlineStatus::Online => self.wallet_connectivity.get_current_base_node_peer().map(Box::new),
+                    _ => None,
+                };
+                Ok(BaseNodeServiceResponse::BaseNodePeer(peer))
+            },
             BaseNodeServiceRequest::GetChainMetadata => match self.get_state().await.chain_metadata.clone() {
                 Some(metadata) => Ok(BaseNodeServiceResponse::ChainMetadata(Some(metadata))),
                 None => {
@@ -188,7 +206,69 @@ where T: WalletBackend + 'static
             BaseNodeServiceRequest::GetBaseNodeLatency => {
                 Ok(BaseNodeServiceResponse::Latency(self.state.read().await.latency))
             },
+            BaseNodeServiceRequest::GetMempoolFeePerGramStats(count) => Ok(
+                BaseNodeServiceResponse::MempoolFeePerGramStats(self.get_mempool_fee_per_gram_stats(count).await?),
+            ),
+            BaseNodeServiceRequest::GetRecentBlockHeaders(count) => Ok(BaseNodeServiceResponse::BlockHeaders(
+                self.get_recent_block_headers(count).await?,
+            )),
+        }
+    }
+
+    async fn get_mempool_fee_per_gram_stats(
+        &mut self,
+        count: u64,
+    ) -> Result<Vec<FeePerGramStat>, BaseNodeServiceError> {
+        if self.wallet_connectivity.get_connectivity_status() != OnlineStatus::Online {
+            return Err(BaseNodeServiceError::NoBaseNodePeer);
         }
+        let mut client = self
+            .wallet_connectivity
+            .obtain_base_node_wallet_rpc_client()
+            .await
+            .ok_or(BaseNodeServiceError::NoBaseNodePeer)?;
+        let response = client
+            .get_mempool_fee_per_gram_stats(GetMempoolFeePerGramStatsRequest { count })
+            .await?;
+
+        Ok(response
+            .stats
+            .into_iter()
+            .map(|stat| FeePerGramStat {
+                order: stat.order,
+                min_fee_per_gram: stat.min_fee_per_gram.into(),
+                avg_fee_per_gram: stat.avg_fee_per_gram.into(),
+                max_fee_per_gram: stat.max_fee_per_gram.into(),
+                total_weight: stat.total_weight,
+            })
+            .collect())
+    }
+
+    async fn get_recent_block_headers(&mut self, count: u64) -> Result<Vec<BlockHeader>, BaseNodeServiceError> {
+        if self.wallet_connectivity.get_connectivity_status() != OnlineStatus::Online {
+            return Err(BaseNodeServiceError::NoBaseNodePeer);
+        }
+        let tip_height = self
+            .get_state()
+            .await
+            .chain_metadata
+            .map(|metadata| metadata.height_of_longest_chain())
+            .ok_or(BaseNodeServiceError::NoChainMetadata)?;
+        let mut client = self
+            .wallet_connectivity
+            .obtain_base_node_wallet_rpc_client()
+            .await
+            .ok_or(BaseNodeServiceError::NoBaseNodePeer)?;
+
+        let count = count.min(MAX_RECENT_BLOCK_HEADERS).min(tip_height + 1);
+        let mut headers = Vec::with_capacity(count as usize);
+        for height in (tip_height + 1 - count..=tip_height).rev() {
+            let header = client.get_header(height).await?;
+            let header = BlockHeader::try_from(header).map_err(BaseNodeServiceError::InvalidBaseNodeResponse)?;
+            headers.push(header);
+        }
+
+        Ok(headers)
     }
 
     fn publish_event(&self, event: BaseNodeEvent) {
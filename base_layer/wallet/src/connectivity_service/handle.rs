@@ -38,6 +38,7 @@ pub enum WalletConnectivityRequest {
 pub struct WalletConnectivityHandle {
     sender: mpsc::Sender<WalletConnectivityRequest>,
     base_node_watch: Watch<Option<Peer>>,
+    base_node_peers: Watch<Vec<Peer>>,
     online_status_rx: watch::Receiver<OnlineStatus>,
 }
 
@@ -45,11 +46,13 @@ impl WalletConnectivityHandle {
     pub(super) fn new(
         sender: mpsc::Sender<WalletConnectivityRequest>,
         base_node_watch: Watch<Option<Peer>>,
+        base_node_peers: Watch<Vec<Peer>>,
         online_status_rx: watch::Receiver<OnlineStatus>,
     ) -> Self {
         Self {
             sender,
             base_node_watch,
+            base_node_peers,
             online_status_rx,
         }
     }
@@ -59,6 +62,40 @@ impl WalletConnectivityHandle {
         Ok(())
     }
 
+    /// Set the prioritized list of base nodes that the wallet can fail over to. This does not change the current base
+    /// node.
+    pub fn set_base_node_peers(&mut self, base_node_peers: Vec<Peer>) {
+        self.base_node_peers.broadcast(base_node_peers);
+    }
+
+    pub fn get_base_node_peers(&self) -> Vec<Peer> {
+        self.base_node_peers.borrow().clone()
+    }
+
+    /// Switch to the base node that follows the current base node in the prioritized list of base nodes, wrapping
+    /// around at the end of the list. If the current base node is not in the list, the first base node in the list is
+    /// selected. Returns the newly selected base node, or None if there is no other base node to fail over to.
+    pub fn failover_base_node(&mut self) -> Option<Peer> {
+        let next = {
+            let peers = self.base_node_peers.borrow();
+            let current = self.get_current_base_node_id();
+            let start = current
+                .as_ref()
+                .and_then(|node_id| peers.iter().position(|p| p.node_id == *node_id))
+                .map(|pos| pos + 1)
+                .unwrap_or(0);
+            peers
+                .iter()
+                .cycle()
+                .skip(start)
+                .take(peers.len())
+                .find(|p| Some(&p.node_id) != current.as_ref())
+                .cloned()
+        }?;
+        self.base_node_watch.broadcast(Some(next.clone()));
+        Some(next)
+    }
+
     /// Obtain a BaseNodeWalletRpcClient.
     ///
     /// This can be relied on to obtain a pooled BaseNodeWalletRpcClient rpc session from a currently selected base
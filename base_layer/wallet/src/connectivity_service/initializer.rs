@@ -48,10 +48,12 @@ impl ServiceInitializer for WalletConnectivityInitializer {
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
         let (sender, receiver) = mpsc::channel(5);
         let base_node_watch = Watch::new(None);
+        let base_node_peers = Watch::new(Vec::new());
         let online_status_watch = Watch::new(OnlineStatus::Offline);
         context.register_handle(WalletConnectivityHandle::new(
             sender,
             base_node_watch.clone(),
+            base_node_peers,
             online_status_watch.get_receiver(),
         ));
 
@@ -52,7 +52,12 @@ async fn setup() -> (
     let (tx, rx) = mpsc::channel(1);
     let base_node_watch = Watch::new(None);
     let online_status_watch = Watch::new(OnlineStatus::Offline);
-    let handle = WalletConnectivityHandle::new(tx, base_node_watch.clone(), online_status_watch.get_receiver());
+    let handle = WalletConnectivityHandle::new(
+        tx,
+        base_node_watch.clone(),
+        Watch::new(Vec::new()),
+        online_status_watch.get_receiver(),
+    );
     let (connectivity, mock) = create_connectivity_mock();
     let mock_state = mock.spawn();
     // let peer_manager = create_peer_manager(tempdir().unwrap());
@@ -234,3 +239,42 @@ async fn it_gracefully_handles_multiple_connection_failures() {
     // Still able to get a base node rpc client
     pending_request.await.unwrap();
 }
+
+#[tokio::test]
+async fn it_fails_over_to_the_next_base_node() {
+    let (mut handle, mock_server, mock_state, _shutdown) = setup().await;
+    let base_node_peer1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let base_node_peer2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let base_node_peer3 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    // Nothing to fail over to
+    assert!(handle.failover_base_node().is_none());
+
+    handle.set_base_node_peers(vec![
+        base_node_peer1.to_peer(),
+        base_node_peer2.to_peer(),
+        base_node_peer3.to_peer(),
+    ]);
+    handle.set_base_node(base_node_peer2.to_peer()).await.unwrap();
+
+    let next = handle.failover_base_node().unwrap();
+    assert_eq!(next.node_id, *base_node_peer3.node_id());
+    assert_eq!(handle.get_current_base_node_id().unwrap(), *base_node_peer3.node_id());
+
+    // Wraps around to the start of the list
+    let next = handle.failover_base_node().unwrap();
+    assert_eq!(next.node_id, *base_node_peer1.node_id());
+
+    // The service connects to the selected base node
+    let conn = mock_server.create_mockimpl_connection(base_node_peer1.to_peer()).await;
+    mock_state.add_active_connection(conn).await;
+    let rpc_client = handle.obtain_base_node_wallet_rpc_client().await.unwrap();
+    assert!(rpc_client.is_connected());
+
+    // A base node that is not in the list fails over to the first base node
+    handle.set_base_node(base_node_peer2.to_peer()).await.unwrap();
+    handle.set_base_node_peers(vec![base_node_peer1.to_peer()]);
+    let next = handle.failover_base_node().unwrap();
+    assert_eq!(next.node_id, *base_node_peer1.node_id());
+    assert!(handle.failover_base_node().is_none());
+}
@@ -23,8 +23,9 @@
 use crate::{
     base_node_service::error::BaseNodeServiceError,
     contacts_service::error::ContactsServiceError,
+    multisig_service::error::{MultisigError, MultisigServiceError},
     output_manager_service::error::OutputManagerError,
-    storage::database::DbKey,
+    storage::database::{DbKey, EncryptionRotationStage},
     transaction_service::error::TransactionServiceError,
     utxo_scanner_service::error::UtxoScannerError,
 };
@@ -35,6 +36,7 @@ use tari_comms::{
     connectivity::ConnectivityError,
     multiaddr,
     peer_manager::{node_id::NodeIdError, PeerManagerError},
+    protocol::rpc::RpcError,
 };
 use tari_comms_dht::store_forward::StoreAndForwardError;
 use tari_core::transactions::transaction::TransactionError;
@@ -63,6 +65,10 @@ pub enum WalletError {
     ContactsServiceError(#[from] ContactsServiceError),
     #[error("Liveness service error: `{0}`")]
     LivenessServiceError(#[from] LivenessError),
+    #[error("Multisig error: `{0}`")]
+    MultisigError(#[from] MultisigError),
+    #[error("Multisig service error: `{0}`")]
+    MultisigServiceError(#[from] MultisigServiceError),
     #[error("Store and forward error: `{0}`")]
     StoreAndForwardError(#[from] StoreAndForwardError),
     #[error("Connectivity error: `{0}`")]
@@ -83,6 +89,14 @@ pub enum WalletError {
     ByteArrayError(#[from] tari_crypto::tari_utilities::ByteArrayError),
     #[error("Utxo Scanner Error: {0}")]
     UtxoScannerError(#[from] UtxoScannerError),
+    #[error("RPC error: `{0}`")]
+    RpcError(#[from] RpcError),
+    #[error("Could not connect to a base node to validate the UTXO")]
+    BaseNodeNotConnected,
+    #[error("The base node is not synced so the UTXO cannot be validated")]
+    BaseNodeNotSynced,
+    #[error("UTXO (Commitment: {0}) was not found in the UTXO set of the base node")]
+    UtxoNotFoundOnChain(String),
 }
 
 #[derive(Debug, Error)]
@@ -127,6 +141,10 @@ pub enum WalletStorageError {
     AeadError(String),
     #[error("Wallet db is already encrypted and cannot be encrypted until the previous encryption is removed")]
     AlreadyEncrypted,
+    #[error("Wallet db is not encrypted so its encryption cannot be rotated")]
+    NotEncrypted,
+    #[error("Could not re-encrypt the {0}: `{1}`")]
+    EncryptionRotationError(EncryptionRotationStage, String),
     #[error("Byte array error: `{0}`")]
     ByteArrayError(#[from] ByteArrayError),
     #[error("Cannot acquire exclusive file lock, another instance of the application is already running")]
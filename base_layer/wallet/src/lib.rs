@@ -13,6 +13,7 @@ pub mod base_node_service;
 pub mod connectivity_service;
 pub mod contacts_service;
 pub mod error;
+pub mod multisig_service;
 pub mod output_manager_service;
 pub mod storage;
 pub mod test_utils;
@@ -35,6 +36,7 @@ pub use wallet::Wallet;
 
 use crate::{
     contacts_service::storage::sqlite_db::ContactsServiceSqliteDatabase,
+    multisig_service::storage::sqlite_db::MultisigServiceSqliteDatabase,
     output_manager_service::storage::sqlite_db::OutputManagerSqliteDatabase,
     storage::sqlite_db::WalletSqliteDatabase,
     transaction_service::storage::sqlite_db::TransactionServiceSqliteDatabase,
@@ -45,4 +47,5 @@ pub type WalletSqlite = Wallet<
     TransactionServiceSqliteDatabase,
     OutputManagerSqliteDatabase,
     ContactsServiceSqliteDatabase,
+    MultisigServiceSqliteDatabase,
 >;
@@ -0,0 +1,614 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Threshold Schnorr signatures for the multisig service.
+//!
+//! The participants of an m-of-n session run a distributed key generation. Every participant `i` deals a polynomial
+//! `f_i` of degree `m - 1`: it publishes the commitments `C_ij = a_ij·G` to the coefficients, with a proof of knowledge
+//! of `a_i0`, and privately sends the share `f_i(j)` to participant `j`, who checks it against the commitments. The
+//! secret share of participant `j` is `s_j = sum_i(f_i(j))` and the group key is `X = sum_i(C_i0)`, whose private key
+//! is never known to anyone.
+//!
+//! Any `m` participants sign for `X` in two rounds, as in FROST. Every signer first commits to a random nonce pair
+//! `(D_i, E_i)`. The group nonce is then `R = sum(D_i + ρ_i·E_i)`, where the binding factor `ρ_i` is hashed from all
+//! the nonce commitments and from what is signed, and the partial signatures `z_i = d_i + ρ_i·e_i + λ_i·s_i·c` add up
+//! to a Schnorr signature for `X`, where `λ_i` is the Lagrange coefficient of the signer within the signer set. The
+//! binding factors stop a signer from steering the group nonce of concurrent signing rounds.
+//!
+//! A multisig output is locked with a script that leaves `X` on the stack, so the script is the same size for any `m`
+//! and `n`.
+
+use crate::{
+    multisig_service::error::MultisigError,
+    types::{HashDigest, KeyDigest},
+};
+use digest::Digest;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{PrivateKey, PublicKey, Signature};
+use tari_crypto::{
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+    script,
+    script::TariScript,
+    tari_utilities::ByteArray,
+};
+use tari_key_manager::key_manager::KeyManager;
+
+const KEY_MANAGER_MULTISIG_BRANCH_KEY: &str = "multisig";
+
+/// The order of the Ristretto group minus two, in little endian byte order. Raising a scalar to this power inverts it.
+const GROUP_ORDER_MINUS_TWO: [u8; 32] = [
+    0xeb, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+/// This wallet's multisig key. It is derived from the master key on its own key manager branch, so it is independent
+/// of the comms identity and is recovered with the seed words.
+#[derive(Clone)]
+pub struct MultisigKey {
+    secret_key: PrivateKey,
+}
+
+impl MultisigKey {
+    pub fn from_master_key(master_key: PrivateKey) -> Result<Self, MultisigError> {
+        let secret_key =
+            KeyManager::<PrivateKey, KeyDigest>::from(master_key, KEY_MANAGER_MULTISIG_BRANCH_KEY.to_string(), 0)
+                .derive_key(0)
+                .map_err(|e| MultisigError::ConversionError(e.to_string()))?
+                .k;
+        Ok(Self { secret_key })
+    }
+
+    /// The coefficients of the polynomial this wallet deals in the key generation of a session. They are derived from
+    /// the multisig key, so the same dealing is sent again if the key generation has to be repeated.
+    pub fn polynomial(&self, session_id: u64, threshold: usize) -> Result<Vec<PrivateKey>, MultisigError> {
+        (0..threshold as u64)
+            .map(|i| {
+                hash_to_scalar(
+                    HashDigest::new()
+                        .chain(b"tari_multisig_polynomial")
+                        .chain(self.secret_key.as_bytes())
+                        .chain(session_id.to_le_bytes())
+                        .chain(i.to_le_bytes()),
+                )
+            })
+            .collect()
+    }
+
+    /// Blind the share dealt by `dealer` for storage, so that the database on its own does not reveal the share
+    pub fn seal_share(
+        &self,
+        session_id: u64,
+        dealer: &PublicKey,
+        share: &PrivateKey,
+    ) -> Result<PrivateKey, MultisigError> {
+        Ok(share + &self.share_mask(session_id, dealer)?)
+    }
+
+    pub fn unseal_share(
+        &self,
+        session_id: u64,
+        dealer: &PublicKey,
+        sealed_share: &PrivateKey,
+    ) -> Result<PrivateKey, MultisigError> {
+        Ok(sealed_share - &self.share_mask(session_id, dealer)?)
+    }
+
+    fn share_mask(&self, session_id: u64, dealer: &PublicKey) -> Result<PrivateKey, MultisigError> {
+        hash_to_scalar(
+            HashDigest::new()
+                .chain(b"tari_multisig_share_mask")
+                .chain(self.secret_key.as_bytes())
+                .chain(session_id.to_le_bytes())
+                .chain(dealer.as_bytes()),
+        )
+    }
+}
+
+/// Returns the keys sorted in canonical (byte) order, with duplicates removed
+pub fn sorted_keys(keys: &[PublicKey]) -> Vec<PublicKey> {
+    let mut sorted = keys.to_vec();
+    sorted.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+    sorted.dedup();
+    sorted
+}
+
+/// The index of `key` among the participants, starting at 1. A participant's share of every polynomial is the
+/// polynomial evaluated at its index.
+pub fn participant_index(participants: &[PublicKey], key: &PublicKey) -> Result<u64, MultisigError> {
+    sorted_keys(participants)
+        .iter()
+        .position(|p| p == key)
+        .map(|i| i as u64 + 1)
+        .ok_or_else(|| MultisigError::InvalidParticipants(format!("{} is not a participant", key)))
+}
+
+/// Evaluate the polynomial with the given coefficients at `index`
+pub fn evaluate_polynomial(coefficients: &[PrivateKey], index: u64) -> PrivateKey {
+    let x = PrivateKey::from(index);
+    coefficients
+        .iter()
+        .rev()
+        .fold(PrivateKey::default(), |acc, c| &(&acc * &x) + c)
+}
+
+/// The public commitments `a_j·G` to the coefficients of a polynomial
+pub fn polynomial_commitments(coefficients: &[PrivateKey]) -> Vec<PublicKey> {
+    coefficients.iter().map(PublicKey::from_secret_key).collect()
+}
+
+/// Evaluate the committed polynomial at `index`, which gives the public key of the share at `index`
+pub fn evaluate_commitments(commitments: &[PublicKey], index: u64) -> PublicKey {
+    let x = PrivateKey::from(index);
+    commitments
+        .iter()
+        .rev()
+        .fold(PublicKey::default(), |acc, c| &(&x * &acc) + c)
+}
+
+/// Check a dealt share against the commitments of the dealer's polynomial
+pub fn verify_share(share: &PrivateKey, commitments: &[PublicKey], index: u64) -> bool {
+    PublicKey::from_secret_key(share) == evaluate_commitments(commitments, index)
+}
+
+/// Prove knowledge of the constant term of a dealt polynomial, which stops a dealer from choosing its commitment to
+/// cancel out the commitments of the other dealers
+pub fn dealing_proof(secret: &PrivateKey, session_id: u64, dealer: &PublicKey) -> Result<Signature, MultisigError> {
+    let nonce = PrivateKey::random(&mut OsRng);
+    let challenge = dealing_proof_challenge(
+        &PublicKey::from_secret_key(&nonce),
+        &PublicKey::from_secret_key(secret),
+        session_id,
+        dealer,
+    );
+    Signature::sign(secret.clone(), nonce, &challenge).map_err(|e| MultisigError::ConversionError(e.to_string()))
+}
+
+pub fn verify_dealing_proof(proof: &Signature, commitment: &PublicKey, session_id: u64, dealer: &PublicKey) -> bool {
+    let challenge = dealing_proof_challenge(proof.get_public_nonce(), commitment, session_id, dealer);
+    proof.verify_challenge(commitment, &challenge)
+}
+
+fn dealing_proof_challenge(nonce: &PublicKey, commitment: &PublicKey, session_id: u64, dealer: &PublicKey) -> Vec<u8> {
+    HashDigest::new()
+        .chain(b"tari_multisig_dealing")
+        .chain(nonce.as_bytes())
+        .chain(commitment.as_bytes())
+        .chain(session_id.to_le_bytes())
+        .chain(dealer.as_bytes())
+        .finalize()
+        .to_vec()
+}
+
+/// The Lagrange coefficient `λ_i = prod_j(j / (j - i))` of the signer with index `i` within the signer set, which
+/// interpolates the signers' shares at zero
+pub fn lagrange_coefficient(index: u64, signer_indexes: &[u64]) -> Result<PrivateKey, MultisigError> {
+    let i = PrivateKey::from(index);
+    let mut numerator = PrivateKey::from(1u64);
+    let mut denominator = PrivateKey::from(1u64);
+    for j in signer_indexes.iter().filter(|j| **j != index) {
+        let j = PrivateKey::from(*j);
+        numerator = &numerator * &j;
+        denominator = &denominator * &(&j - &i);
+    }
+    if denominator == PrivateKey::default() {
+        return Err(MultisigError::InvalidParticipants("Duplicate signer index".to_string()));
+    }
+    Ok(&numerator * &invert(&denominator))
+}
+
+/// Invert a non-zero scalar by raising it to the power `l - 2`, with `l` the group order
+fn invert(scalar: &PrivateKey) -> PrivateKey {
+    let mut result = PrivateKey::from(1u64);
+    for byte in GROUP_ORDER_MINUS_TWO.iter().rev() {
+        for bit in (0..8).rev() {
+            result = &result * &result;
+            if (byte >> bit) & 1 == 1 {
+                result = &result * scalar;
+            }
+        }
+    }
+    result
+}
+
+/// A signer's secret nonce pair for one signature. The nonces are random and only ever kept in memory: a nonce that
+/// was stored could be used a second time after the database is restored from a backup.
+pub struct SigningNonces {
+    hiding: PrivateKey,
+    binding: PrivateKey,
+}
+
+impl SigningNonces {
+    pub fn random() -> Self {
+        Self {
+            hiding: PrivateKey::random(&mut OsRng),
+            binding: PrivateKey::random(&mut OsRng),
+        }
+    }
+
+    pub fn commitment(&self) -> NonceCommitment {
+        NonceCommitment {
+            hiding: PublicKey::from_secret_key(&self.hiding),
+            binding: PublicKey::from_secret_key(&self.binding),
+        }
+    }
+}
+
+/// The public commitment `(D_i, E_i)` to a signer's nonce pair
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NonceCommitment {
+    pub hiding: PublicKey,
+    pub binding: PublicKey,
+}
+
+/// The binding factor `ρ_i` of a signer for one signature, which commits to the nonce commitments of all the signers
+/// and to the context of the signature
+pub fn binding_factor(
+    signer: &PublicKey,
+    group_public_key: &PublicKey,
+    commitments: &[(PublicKey, NonceCommitment)],
+    context: &[u8],
+) -> Result<PrivateKey, MultisigError> {
+    let mut commitments = commitments.to_vec();
+    commitments.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+    let mut hasher = HashDigest::new()
+        .chain(b"tari_multisig_binding")
+        .chain(signer.as_bytes())
+        .chain(group_public_key.as_bytes())
+        .chain(context);
+    for (signer, commitment) in &commitments {
+        hasher = hasher
+            .chain(signer.as_bytes())
+            .chain(commitment.hiding.as_bytes())
+            .chain(commitment.binding.as_bytes());
+    }
+    hash_to_scalar(hasher)
+}
+
+/// The group nonce `R = sum(D_i + ρ_i·E_i)` of one signature
+pub fn group_nonce(
+    group_public_key: &PublicKey,
+    commitments: &[(PublicKey, NonceCommitment)],
+    context: &[u8],
+) -> Result<PublicKey, MultisigError> {
+    let mut nonce = PublicKey::default();
+    for (signer, commitment) in commitments {
+        let rho = binding_factor(signer, group_public_key, commitments, context)?;
+        nonce = &(&nonce + &commitment.hiding) + &(&rho * &commitment.binding);
+    }
+    Ok(nonce)
+}
+
+/// Create this signer's partial signature `z_i = d_i + ρ_i·e_i + λ_i·s_i·c` for the challenge `c`
+pub fn partial_sign(
+    nonces: &SigningNonces,
+    binding_factor: &PrivateKey,
+    lagrange_coefficient: &PrivateKey,
+    secret_share: &PrivateKey,
+    challenge: &PrivateKey,
+) -> PrivateKey {
+    &(&nonces.hiding + &(binding_factor * &nonces.binding)) + &(challenge * &(lagrange_coefficient * secret_share))
+}
+
+/// Verify a partial signature against the public key `Y_i = s_i·G` of the signer's share, i.e.
+/// `z_i·G == D_i + ρ_i·E_i + λ_i·c·Y_i`
+pub fn verify_partial_signature(
+    partial_signature: &PrivateKey,
+    commitment: &NonceCommitment,
+    binding_factor: &PrivateKey,
+    lagrange_coefficient: &PrivateKey,
+    verification_share: &PublicKey,
+    challenge: &PrivateKey,
+) -> bool {
+    let lhs = PublicKey::from_secret_key(partial_signature);
+    let rhs = &(&commitment.hiding + &(binding_factor * &commitment.binding)) +
+        &(&(challenge * lagrange_coefficient) * verification_share);
+    lhs == rhs
+}
+
+/// The challenge of a Schnorr signature of `message` for the group key
+pub fn signing_challenge(group_nonce: &PublicKey, group_public_key: &PublicKey, message: &[u8]) -> Vec<u8> {
+    HashDigest::new()
+        .chain(group_nonce.as_bytes())
+        .chain(group_public_key.as_bytes())
+        .chain(message)
+        .finalize()
+        .to_vec()
+}
+
+/// Convert a challenge hash into the scalar it represents in a signature
+pub fn challenge_scalar(challenge: &[u8]) -> Result<PrivateKey, MultisigError> {
+    PrivateKey::from_bytes(challenge).map_err(|e| MultisigError::ConversionError(e.to_string()))
+}
+
+/// A signer's Diffie-Hellman share `s_i·P` with a public key `P`, with a proof that it was made with the same secret
+/// share as the signer's verification share `s_i·G`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DhShare {
+    pub share: PublicKey,
+    nonce_g: PublicKey,
+    nonce_p: PublicKey,
+    proof: PrivateKey,
+}
+
+impl DhShare {
+    pub fn new(secret_share: &PrivateKey, point: &PublicKey) -> Result<Self, MultisigError> {
+        let share = secret_share * point;
+        let nonce = PrivateKey::random(&mut OsRng);
+        let nonce_g = PublicKey::from_secret_key(&nonce);
+        let nonce_p = &nonce * point;
+        let challenge = dh_share_challenge(
+            &PublicKey::from_secret_key(secret_share),
+            point,
+            &share,
+            &nonce_g,
+            &nonce_p,
+        )?;
+        Ok(Self {
+            share,
+            nonce_g,
+            nonce_p,
+            proof: &nonce + &(&challenge * secret_share),
+        })
+    }
+
+    /// Check that the share and the verification share have the same discrete logarithm
+    pub fn verify(&self, verification_share: &PublicKey, point: &PublicKey) -> Result<bool, MultisigError> {
+        let challenge = dh_share_challenge(verification_share, point, &self.share, &self.nonce_g, &self.nonce_p)?;
+        Ok(
+            PublicKey::from_secret_key(&self.proof) == &self.nonce_g + &(&challenge * verification_share) &&
+                &self.proof * point == &self.nonce_p + &(&challenge * &self.share),
+        )
+    }
+}
+
+fn dh_share_challenge(
+    verification_share: &PublicKey,
+    point: &PublicKey,
+    share: &PublicKey,
+    nonce_g: &PublicKey,
+    nonce_p: &PublicKey,
+) -> Result<PrivateKey, MultisigError> {
+    hash_to_scalar(
+        HashDigest::new()
+            .chain(b"tari_multisig_dh_share")
+            .chain(verification_share.as_bytes())
+            .chain(point.as_bytes())
+            .chain(share.as_bytes())
+            .chain(nonce_g.as_bytes())
+            .chain(nonce_p.as_bytes()),
+    )
+}
+
+/// Combine the signers' Diffie-Hellman shares `s_i·P`, given with the signer indexes, into `x·P`, where `x` is the
+/// private key of the group
+pub fn combine_dh_shares(shares: &[(u64, PublicKey)]) -> Result<PublicKey, MultisigError> {
+    let indexes = shares.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+    let mut combined = PublicKey::default();
+    for (index, share) in shares {
+        combined = &combined + &(&lagrange_coefficient(*index, &indexes)? * share);
+    }
+    Ok(combined)
+}
+
+/// The script that locks an output to the group key of a multisig session
+pub fn multisig_script(group_public_key: &PublicKey) -> TariScript {
+    script!(PushPubKey(Box::new(group_public_key.clone())))
+}
+
+fn hash_to_scalar(hasher: HashDigest) -> Result<PrivateKey, MultisigError> {
+    PrivateKey::from_bytes(hasher.finalize().as_slice()).map_err(|e| MultisigError::ConversionError(e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_crypto::script::{ExecutionStack, StackItem};
+
+    /// The outcome of a key generation between `n` participants with random polynomials
+    struct KeyGeneration {
+        participants: Vec<PublicKey>,
+        shares: Vec<PrivateKey>,
+        group_secret: PrivateKey,
+        group_public_key: PublicKey,
+    }
+
+    fn key_generation(n: usize, threshold: usize) -> KeyGeneration {
+        let participants = sorted_keys(
+            &(0..n)
+                .map(|_| PublicKey::random_keypair(&mut OsRng).1)
+                .collect::<Vec<_>>(),
+        );
+        let polynomials = (0..n)
+            .map(|_| {
+                (0..threshold)
+                    .map(|_| PrivateKey::random(&mut OsRng))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut shares = Vec::new();
+        for index in 1..=n as u64 {
+            let mut share = PrivateKey::default();
+            for polynomial in &polynomials {
+                let dealt = evaluate_polynomial(polynomial, index);
+                assert!(verify_share(&dealt, &polynomial_commitments(polynomial), index));
+                share = &share + &dealt;
+            }
+            shares.push(share);
+        }
+        let group_secret = polynomials.iter().fold(PrivateKey::default(), |acc, p| &acc + &p[0]);
+        let group_public_key = polynomials
+            .iter()
+            .fold(PublicKey::default(), |acc, p| &acc + &polynomial_commitments(p)[0]);
+        KeyGeneration {
+            participants,
+            shares,
+            group_secret,
+            group_public_key,
+        }
+    }
+
+    /// Sign `message` with the participants at the given positions, returning the signature
+    fn threshold_sign(keys: &KeyGeneration, signers: &[usize], message: &[u8]) -> Signature {
+        let indexes = signers.iter().map(|i| *i as u64 + 1).collect::<Vec<_>>();
+        let nonces = signers.iter().map(|_| SigningNonces::random()).collect::<Vec<_>>();
+        let commitments = signers
+            .iter()
+            .zip(nonces.iter())
+            .map(|(i, n)| (keys.participants[*i].clone(), n.commitment()))
+            .collect::<Vec<_>>();
+        let nonce = group_nonce(&keys.group_public_key, &commitments, message).unwrap();
+        let challenge = challenge_scalar(&signing_challenge(&nonce, &keys.group_public_key, message)).unwrap();
+
+        let mut s = PrivateKey::default();
+        for (position, signer) in signers.iter().enumerate() {
+            let participant = &keys.participants[*signer];
+            let rho = binding_factor(participant, &keys.group_public_key, &commitments, message).unwrap();
+            let lambda = lagrange_coefficient(indexes[position], &indexes).unwrap();
+            let z = partial_sign(&nonces[position], &rho, &lambda, &keys.shares[*signer], &challenge);
+            assert!(verify_partial_signature(
+                &z,
+                &commitments[position].1,
+                &rho,
+                &lambda,
+                &PublicKey::from_secret_key(&keys.shares[*signer]),
+                &challenge
+            ));
+            // A partial signature does not verify for another signer's share
+            let other = (*signer + 1) % keys.shares.len();
+            assert!(!verify_partial_signature(
+                &z,
+                &commitments[position].1,
+                &rho,
+                &lambda,
+                &PublicKey::from_secret_key(&keys.shares[other]),
+                &challenge
+            ));
+            s = &s + &z;
+        }
+        Signature::new(nonce, s)
+    }
+
+    #[test]
+    fn it_inverts_scalars() {
+        let scalar = PrivateKey::random(&mut OsRng);
+        assert_eq!(&scalar * &invert(&scalar), PrivateKey::from(1u64));
+        assert_eq!(invert(&PrivateKey::from(1u64)), PrivateKey::from(1u64));
+    }
+
+    #[test]
+    fn it_indexes_participants_independent_of_order() {
+        let keys = (0..3)
+            .map(|_| PublicKey::random_keypair(&mut OsRng).1)
+            .collect::<Vec<_>>();
+        let mut reversed = keys.clone();
+        reversed.reverse();
+        for key in &keys {
+            let index = participant_index(&keys, key).unwrap();
+            assert!((1..=3).contains(&index));
+            assert_eq!(participant_index(&reversed, key).unwrap(), index);
+        }
+        assert!(participant_index(&keys, &PublicKey::random_keypair(&mut OsRng).1).is_err());
+    }
+
+    #[test]
+    fn it_derives_a_deterministic_polynomial_and_seals_shares() {
+        let key = MultisigKey::from_master_key(PrivateKey::random(&mut OsRng)).unwrap();
+        let polynomial = key.polynomial(1, 3).unwrap();
+        assert_eq!(polynomial.len(), 3);
+        assert_eq!(key.polynomial(1, 3).unwrap(), polynomial);
+        assert_ne!(key.polynomial(2, 3).unwrap(), polynomial);
+
+        let dealer = PublicKey::random_keypair(&mut OsRng).1;
+        let share = PrivateKey::random(&mut OsRng);
+        let sealed = key.seal_share(1, &dealer, &share).unwrap();
+        assert_ne!(sealed, share);
+        assert_eq!(key.unseal_share(1, &dealer, &sealed).unwrap(), share);
+    }
+
+    #[test]
+    fn it_verifies_dealings() {
+        let dealer = PublicKey::random_keypair(&mut OsRng).1;
+        let polynomial = (0..2).map(|_| PrivateKey::random(&mut OsRng)).collect::<Vec<_>>();
+        let commitments = polynomial_commitments(&polynomial);
+
+        let proof = dealing_proof(&polynomial[0], 1, &dealer).unwrap();
+        assert!(verify_dealing_proof(&proof, &commitments[0], 1, &dealer));
+        assert!(!verify_dealing_proof(&proof, &commitments[0], 2, &dealer));
+        assert!(!verify_dealing_proof(&proof, &commitments[1], 1, &dealer));
+
+        let share = evaluate_polynomial(&polynomial, 2);
+        assert!(verify_share(&share, &commitments, 2));
+        assert!(!verify_share(&share, &commitments, 3));
+    }
+
+    #[test]
+    fn it_creates_a_threshold_signature_with_any_signer_subset() {
+        let keys = key_generation(3, 2);
+        assert_eq!(keys.group_public_key, PublicKey::from_secret_key(&keys.group_secret));
+        let message = b"multisig message";
+        for signers in &[[0, 1], [0, 2], [1, 2]] {
+            let signature = threshold_sign(&keys, signers, message);
+            let challenge = signing_challenge(signature.get_public_nonce(), &keys.group_public_key, message);
+            assert!(signature.verify_challenge(&keys.group_public_key, &challenge));
+        }
+
+        // Fewer signers than the threshold can not sign
+        let signature = threshold_sign(&keys, &[1], message);
+        let challenge = signing_challenge(signature.get_public_nonce(), &keys.group_public_key, message);
+        assert!(!signature.verify_challenge(&keys.group_public_key, &challenge));
+    }
+
+    #[test]
+    fn it_combines_dh_shares_of_any_signer_subset() {
+        let keys = key_generation(4, 3);
+        let point = PublicKey::random_keypair(&mut OsRng).1;
+        let expected = &keys.group_secret * &point;
+        for signers in &[[0, 1, 2], [0, 1, 3], [1, 2, 3]] {
+            let shares = signers
+                .iter()
+                .map(|i| (*i as u64 + 1, &keys.shares[*i] * &point))
+                .collect::<Vec<_>>();
+            assert_eq!(combine_dh_shares(&shares).unwrap(), expected);
+        }
+        let dh_share = DhShare::new(&keys.shares[0], &point).unwrap();
+        assert!(dh_share
+            .verify(&PublicKey::from_secret_key(&keys.shares[0]), &point)
+            .unwrap());
+        assert!(!dh_share
+            .verify(&PublicKey::from_secret_key(&keys.shares[1]), &point)
+            .unwrap());
+        let shares = [0usize, 1]
+            .iter()
+            .map(|i| (*i as u64 + 1, &keys.shares[*i] * &point))
+            .collect::<Vec<_>>();
+        assert_ne!(combine_dh_shares(&shares).unwrap(), expected);
+    }
+
+    #[test]
+    fn it_locks_outputs_to_the_group_key() {
+        let keys = key_generation(3, 2);
+        let script = multisig_script(&keys.group_public_key);
+        let result = script.execute(&ExecutionStack::new(vec![])).unwrap();
+        assert_eq!(result, StackItem::PublicKey(keys.group_public_key));
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{multisig_service::storage::database::DbKey, transaction_service::error::TransactionServiceError};
+use diesel::result::Error as DieselError;
+use tari_comms_dht::outbound::DhtOutboundError;
+use tari_core::transactions::transaction::TransactionError;
+use tari_service_framework::reply_channel::TransportChannelError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MultisigError {
+    #[error("Invalid multisig threshold {0} for {1} participants")]
+    InvalidThreshold(usize, usize),
+    #[error("Invalid multisig participants: `{0}`")]
+    InvalidParticipants(String),
+    #[error("This wallet is not a signer in signing round `{0}`")]
+    NotASigner(u64),
+    #[error("Signing round `{0}` has already been signed by this wallet")]
+    AlreadySigned(u64),
+    #[error("Signing round `{0}` is not complete")]
+    SigningIncomplete(u64),
+    #[error("The threshold signature is not valid")]
+    InvalidSignature,
+    #[error("Not every participant of multisig session `{0}` has dealt its share of the key yet")]
+    KeyGenerationIncomplete(u64),
+    #[error("Invalid multisig dealing: `{0}`")]
+    InvalidDealing(String),
+    #[error("The signing nonces of signing round `{0}` were lost, the round has to be started again")]
+    NoncesLost(u64),
+    #[error("Invalid multisig spend: `{0}`")]
+    InvalidSpend(String),
+    #[error("Transaction error: `{0}`")]
+    TransactionError(#[from] TransactionError),
+    #[error("Conversion error: `{0}`")]
+    ConversionError(String),
+}
+
+#[derive(Debug, Error)]
+pub enum MultisigServiceError {
+    #[error("Multisig error: `{0}`")]
+    MultisigError(#[from] MultisigError),
+    #[error("Received incorrect response from service request")]
+    UnexpectedApiResponse,
+    #[error("Invalid multisig message: `{0}`")]
+    InvalidMessage(String),
+    #[error("Multisig storage error: `{0}`")]
+    MultisigStorageError(#[from] MultisigStorageError),
+    #[error("DHT outbound error: `{0}`")]
+    DhtOutboundError(#[from] DhtOutboundError),
+    #[error("Transport channel error: `{0}`")]
+    TransportChannelError(#[from] TransportChannelError),
+    #[error("Transaction service error: `{0}`")]
+    TransactionServiceError(#[from] TransactionServiceError),
+}
+
+#[derive(Debug, Error)]
+pub enum MultisigStorageError {
+    #[error("This write operation is not supported for provided DbKey")]
+    OperationNotSupported,
+    #[error("Error converting a type: `{0}`")]
+    ConversionError(String),
+    #[error("Value not found error: `{0}`")]
+    ValueNotFound(DbKey),
+    #[error("Unexpected result error: `{0}`")]
+    UnexpectedResult(String),
+    #[error("Diesel error: `{0}`")]
+    DieselError(#[from] DieselError),
+    #[error("Json error: `{0}`")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Blocking task spawn error: `{0}`")]
+    BlockingTaskSpawnError(String),
+    #[error("Aead error: `{0}`")]
+    AeadError(String),
+    #[error("Wallet db is already encrypted and cannot be encrypted until the previous encryption is removed")]
+    AlreadyEncrypted,
+}
@@ -0,0 +1,272 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    multisig_service::{
+        error::MultisigServiceError,
+        models::{MultisigOutput, MultisigSession, MultisigSigningRound},
+    },
+    output_manager_service::TxId,
+};
+use aes_gcm::Aes256Gcm;
+use std::fmt;
+use tari_common_types::types::{Commitment, PublicKey, Signature};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_service_framework::reply_channel::SenderService;
+use tower::Service;
+
+pub enum MultisigServiceRequest {
+    CreateSession(usize, Vec<PublicKey>),
+    AcceptSession(u64),
+    GetSessions,
+    FundSession(u64, MicroTari, MicroTari),
+    GetOutputs,
+    StartSigning(u64, Vec<u8>, Vec<PublicKey>),
+    StartSpend(u64, Commitment, PublicKey, MicroTari, Vec<PublicKey>),
+    ApproveSigning(u64),
+    GetSigningRound(u64),
+    GetSigningRounds,
+    FinalizeSigning(u64),
+    ApplyEncryption(Box<Aes256Gcm>),
+    RemoveEncryption,
+}
+
+impl fmt::Display for MultisigServiceRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::CreateSession(threshold, participants) => {
+                write!(f, "CreateSession({}-of-{})", threshold, participants.len() + 1)
+            },
+            Self::AcceptSession(session_id) => write!(f, "AcceptSession({})", session_id),
+            Self::GetSessions => write!(f, "GetSessions"),
+            Self::FundSession(session_id, amount, _) => write!(f, "FundSession({}, {})", session_id, amount),
+            Self::GetOutputs => write!(f, "GetOutputs"),
+            Self::StartSigning(session_id, _, _) => write!(f, "StartSigning({})", session_id),
+            Self::StartSpend(session_id, commitment, _, _, _) => {
+                write!(f, "StartSpend({}, {})", session_id, commitment.to_hex())
+            },
+            Self::ApproveSigning(signing_id) => write!(f, "ApproveSigning({})", signing_id),
+            Self::GetSigningRound(signing_id) => write!(f, "GetSigningRound({})", signing_id),
+            Self::GetSigningRounds => write!(f, "GetSigningRounds"),
+            Self::FinalizeSigning(signing_id) => write!(f, "FinalizeSigning({})", signing_id),
+            Self::ApplyEncryption(_) => write!(f, "ApplyEncryption"),
+            Self::RemoveEncryption => write!(f, "RemoveEncryption"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MultisigServiceResponse {
+    SessionCreated(Box<MultisigSession>),
+    SessionAccepted(Box<MultisigSession>),
+    Sessions(Vec<MultisigSession>),
+    FundingSent(TxId, Box<Commitment>),
+    Outputs(Vec<MultisigOutput>),
+    SigningStarted(u64),
+    SigningApproved,
+    SigningRound(Box<MultisigSigningRound>),
+    SigningRounds(Vec<MultisigSigningRound>),
+    Signature(Box<Signature>),
+    EncryptionApplied,
+    EncryptionRemoved,
+}
+
+#[derive(Clone)]
+pub struct MultisigServiceHandle {
+    handle: SenderService<MultisigServiceRequest, Result<MultisigServiceResponse, MultisigServiceError>>,
+}
+
+impl MultisigServiceHandle {
+    pub fn new(
+        handle: SenderService<MultisigServiceRequest, Result<MultisigServiceResponse, MultisigServiceError>>,
+    ) -> Self {
+        Self { handle }
+    }
+
+    /// Create an m-of-n multisig session between this wallet and the provided participants and start its key
+    /// generation. The group key is available once every participant has dealt its share.
+    pub async fn create_session(
+        &mut self,
+        threshold: usize,
+        participants: Vec<PublicKey>,
+    ) -> Result<MultisigSession, MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::CreateSession(threshold, participants))
+            .await??
+        {
+            MultisigServiceResponse::SessionCreated(s) => Ok(*s),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Accept the invitation to a session set up by another participant, and deal this wallet's share of its key
+    /// generation
+    pub async fn accept_session(&mut self, session_id: u64) -> Result<MultisigSession, MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::AcceptSession(session_id))
+            .await??
+        {
+            MultisigServiceResponse::SessionAccepted(s) => Ok(*s),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_sessions(&mut self) -> Result<Vec<MultisigSession>, MultisigServiceError> {
+        match self.handle.call(MultisigServiceRequest::GetSessions).await?? {
+            MultisigServiceResponse::Sessions(s) => Ok(s),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Fund the group key of a session with `amount`. The other participants are sent the multisig output. Returns the
+    /// funding transaction id and the commitment of the multisig output.
+    pub async fn fund_session(
+        &mut self,
+        session_id: u64,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, Commitment), MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::FundSession(session_id, amount, fee_per_gram))
+            .await??
+        {
+            MultisigServiceResponse::FundingSent(tx_id, commitment) => Ok((tx_id, *commitment)),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_outputs(&mut self) -> Result<Vec<MultisigOutput>, MultisigServiceError> {
+        match self.handle.call(MultisigServiceRequest::GetOutputs).await?? {
+            MultisigServiceResponse::Outputs(o) => Ok(o),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Start a signing round for `message` with exactly `threshold` signers of the session, this wallet included.
+    /// Returns the signing id.
+    pub async fn start_signing(
+        &mut self,
+        session_id: u64,
+        message: Vec<u8>,
+        signers: Vec<PublicKey>,
+    ) -> Result<u64, MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::StartSigning(session_id, message, signers))
+            .await??
+        {
+            MultisigServiceResponse::SigningStarted(id) => Ok(id),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Start a signing round that spends a multisig output of the session to `destination` as a one-sided payment,
+    /// with exactly `threshold` signers, this wallet included. This wallet coordinates the spend and submits the
+    /// transaction once all the signers have signed. Returns the signing id.
+    pub async fn start_spend(
+        &mut self,
+        session_id: u64,
+        commitment: Commitment,
+        destination: PublicKey,
+        fee_per_gram: MicroTari,
+        signers: Vec<PublicKey>,
+    ) -> Result<u64, MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::StartSpend(
+                session_id,
+                commitment,
+                destination,
+                fee_per_gram,
+                signers,
+            ))
+            .await??
+        {
+            MultisigServiceResponse::SigningStarted(id) => Ok(id),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Approve a signing round requested by another participant
+    pub async fn approve_signing(&mut self, signing_id: u64) -> Result<(), MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::ApproveSigning(signing_id))
+            .await??
+        {
+            MultisigServiceResponse::SigningApproved => Ok(()),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_signing_round(&mut self, signing_id: u64) -> Result<MultisigSigningRound, MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::GetSigningRound(signing_id))
+            .await??
+        {
+            MultisigServiceResponse::SigningRound(r) => Ok(*r),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_signing_rounds(&mut self) -> Result<Vec<MultisigSigningRound>, MultisigServiceError> {
+        match self.handle.call(MultisigServiceRequest::GetSigningRounds).await?? {
+            MultisigServiceResponse::SigningRounds(r) => Ok(r),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Get the signature of a completed signing round of a message
+    pub async fn finalize_signing(&mut self, signing_id: u64) -> Result<Signature, MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::FinalizeSigning(signing_id))
+            .await??
+        {
+            MultisigServiceResponse::Signature(s) => Ok(*s),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn apply_encryption(&mut self, cipher: Aes256Gcm) -> Result<(), MultisigServiceError> {
+        match self
+            .handle
+            .call(MultisigServiceRequest::ApplyEncryption(Box::new(cipher)))
+            .await??
+        {
+            MultisigServiceResponse::EncryptionApplied => Ok(()),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn remove_encryption(&mut self) -> Result<(), MultisigServiceError> {
+        match self.handle.call(MultisigServiceRequest::RemoveEncryption).await?? {
+            MultisigServiceResponse::EncryptionRemoved => Ok(()),
+            _ => Err(MultisigServiceError::UnexpectedApiResponse),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The multisig service lets a group of wallets set up an m-of-n multisig session, in which every participant deals
+//! shares of a group key to the others, so that any `m` of them can cooperatively produce Schnorr signatures for the
+//! group key. Each participant only ever holds its own share of the group key. The group key can be funded with a
+//! multisig output and any `m` participants can spend that output.
+
+pub mod crypto;
+pub mod error;
+pub mod handle;
+pub mod models;
+pub mod proto;
+pub mod service;
+pub mod spend;
+pub mod storage;
+
+use crate::{
+    multisig_service::{
+        crypto::MultisigKey,
+        handle::MultisigServiceHandle,
+        service::MultisigService,
+        storage::database::{MultisigBackend, MultisigDatabase},
+    },
+    transaction_service::handle::TransactionServiceHandle,
+};
+use futures::{Stream, StreamExt};
+use log::*;
+use std::sync::Arc;
+use tari_comms::peer_manager::NodeIdentity;
+use tari_comms_dht::Dht;
+use tari_core::transactions::CryptoFactories;
+use tari_p2p::{
+    comms_connector::SubscriptionFactory,
+    domain_message::DomainMessage,
+    services::utils::{map_decode, ok_or_skip_result},
+    tari_message::TariMessageType,
+};
+use tari_service_framework::{
+    async_trait,
+    reply_channel,
+    ServiceInitializationError,
+    ServiceInitializer,
+    ServiceInitializerContext,
+};
+
+const LOG_TARGET: &str = "wallet::multisig_service::initializer";
+const SUBSCRIPTION_LABEL: &str = "Multisig Service";
+
+pub struct MultisigServiceInitializer<T>
+where T: MultisigBackend
+{
+    backend: Option<T>,
+    subscription_factory: Arc<SubscriptionFactory>,
+    node_identity: Arc<NodeIdentity>,
+    multisig_key: MultisigKey,
+    factories: CryptoFactories,
+}
+
+impl<T> MultisigServiceInitializer<T>
+where T: MultisigBackend
+{
+    pub fn new(
+        backend: T,
+        subscription_factory: Arc<SubscriptionFactory>,
+        node_identity: Arc<NodeIdentity>,
+        multisig_key: MultisigKey,
+        factories: CryptoFactories,
+    ) -> Self {
+        Self {
+            backend: Some(backend),
+            subscription_factory,
+            node_identity,
+            multisig_key,
+            factories,
+        }
+    }
+
+    /// Get a stream of inbound Multisig messages
+    fn multisig_message_stream(&self) -> impl Stream<Item = DomainMessage<proto::MultisigMessage>> {
+        trace!(
+            target: LOG_TARGET,
+            "Subscription '{}' for topic '{:?}' created.",
+            SUBSCRIPTION_LABEL,
+            TariMessageType::MultisigMessage
+        );
+        self.subscription_factory
+            .get_subscription(TariMessageType::MultisigMessage, SUBSCRIPTION_LABEL)
+            .map(map_decode::<proto::MultisigMessage>)
+            .filter_map(ok_or_skip_result)
+    }
+}
+
+#[async_trait]
+impl<T> ServiceInitializer for MultisigServiceInitializer<T>
+where T: MultisigBackend + 'static
+{
+    async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
+        let (sender, receiver) = reply_channel::unbounded();
+        let message_stream = self.multisig_message_stream();
+
+        let multisig_handle = MultisigServiceHandle::new(sender);
+
+        // Register handle before waiting for handles to be ready
+        context.register_handle(multisig_handle);
+
+        let backend = self
+            .backend
+            .take()
+            .expect("Cannot start Multisig Service without setting a storage backend");
+        let node_identity = self.node_identity.clone();
+        let multisig_key = self.multisig_key.clone();
+        let factories = self.factories.clone();
+
+        context.spawn_when_ready(move |handles| async move {
+            let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
+            let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
+
+            let result = MultisigService::new(
+                receiver,
+                message_stream,
+                MultisigDatabase::new(backend),
+                outbound_message_service,
+                transaction_service,
+                node_identity,
+                multisig_key,
+                factories,
+                handles.get_shutdown_signal(),
+            )
+            .start()
+            .await;
+
+            if let Err(e) = result {
+                error!(target: LOG_TARGET, "Multisig Service error: {}", e);
+            }
+            info!(target: LOG_TARGET, "Multisig Service shutdown");
+        });
+
+        Ok(())
+    }
+}
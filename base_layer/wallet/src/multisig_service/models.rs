@@ -0,0 +1,411 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    multisig_service::{
+        crypto::{self, DhShare, MultisigKey, NonceCommitment},
+        error::MultisigError,
+        spend::MultisigSpendTemplate,
+    },
+    output_manager_service::TxId,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tari_common_types::types::{Commitment, PrivateKey, PublicKey, Signature};
+use tari_core::transactions::{tari_amount::MicroTari, transaction::TransactionOutput};
+use tari_crypto::{script::TariScript, tari_utilities::hex::Hex};
+
+/// A participant's dealing in the key generation of a multisig session
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultisigDealing {
+    pub dealer: PublicKey,
+    /// The commitments to the coefficients of the dealer's polynomial
+    pub commitments: Vec<PublicKey>,
+    /// This wallet's share of the dealer's polynomial, sealed with this wallet's multisig key
+    pub sealed_share: PrivateKey,
+}
+
+/// An m-of-n threshold key shared between a number of wallets
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisigSession {
+    pub session_id: u64,
+    pub threshold: usize,
+    /// The public keys of all the participants, in canonical order
+    pub participants: Vec<PublicKey>,
+    /// The dealings of the key generation received so far, this wallet's own included
+    pub dealings: Vec<MultisigDealing>,
+    pub timestamp: NaiveDateTime,
+    /// Whether this wallet has joined the session. A session set up by another participant is only an invitation
+    /// until it is accepted, and this wallet does not deal its share before then.
+    pub accepted: bool,
+}
+
+impl MultisigSession {
+    pub fn new(
+        session_id: u64,
+        threshold: usize,
+        participants: Vec<PublicKey>,
+        timestamp: NaiveDateTime,
+    ) -> Result<Self, MultisigError> {
+        let participants = crypto::sorted_keys(&participants);
+        if participants.len() < 2 {
+            return Err(MultisigError::InvalidParticipants(
+                "A multisig session needs at least two participants".to_string(),
+            ));
+        }
+        if threshold == 0 || threshold > participants.len() {
+            return Err(MultisigError::InvalidThreshold(threshold, participants.len()));
+        }
+        Ok(Self {
+            session_id,
+            threshold,
+            participants,
+            dealings: Vec::new(),
+            timestamp,
+            accepted: true,
+        })
+    }
+
+    pub fn is_participant(&self, public_key: &PublicKey) -> bool {
+        self.participants.contains(public_key)
+    }
+
+    /// The index of a participant, at which the dealt polynomials are evaluated for its share
+    pub fn participant_index(&self, public_key: &PublicKey) -> Result<u64, MultisigError> {
+        crypto::participant_index(&self.participants, public_key)
+    }
+
+    pub fn dealing(&self, dealer: &PublicKey) -> Option<&MultisigDealing> {
+        self.dealings.iter().find(|d| &d.dealer == dealer)
+    }
+
+    pub fn add_dealing(&mut self, dealing: MultisigDealing) -> Result<(), MultisigError> {
+        if !self.is_participant(&dealing.dealer) {
+            return Err(MultisigError::InvalidDealing(format!(
+                "{} is not a participant of multisig session {}",
+                dealing.dealer, self.session_id
+            )));
+        }
+        if dealing.commitments.len() != self.threshold {
+            return Err(MultisigError::InvalidDealing(format!(
+                "{} commitments dealt for a threshold of {}",
+                dealing.commitments.len(),
+                self.threshold
+            )));
+        }
+        if self.dealing(&dealing.dealer).is_none() {
+            self.dealings.push(dealing);
+        }
+        Ok(())
+    }
+
+    /// The key generation is complete once every participant has dealt
+    pub fn is_ready(&self) -> bool {
+        self.participants.iter().all(|p| self.dealing(p).is_some())
+    }
+
+    /// The group public key, which no participant knows the private key of
+    pub fn group_public_key(&self) -> Result<PublicKey, MultisigError> {
+        self.check_ready()?;
+        Ok(self
+            .dealings
+            .iter()
+            .fold(PublicKey::default(), |acc, d| &acc + &d.commitments[0]))
+    }
+
+    /// The public key of a participant's share of the group key, which its partial signatures are verified against
+    pub fn verification_share(&self, participant: &PublicKey) -> Result<PublicKey, MultisigError> {
+        self.check_ready()?;
+        let index = self.participant_index(participant)?;
+        Ok(self.dealings.iter().fold(PublicKey::default(), |acc, d| {
+            &acc + &crypto::evaluate_commitments(&d.commitments, index)
+        }))
+    }
+
+    /// This wallet's share of the group key
+    pub fn secret_share(&self, key: &MultisigKey) -> Result<PrivateKey, MultisigError> {
+        self.check_ready()?;
+        let mut share = PrivateKey::default();
+        for dealing in &self.dealings {
+            share = &share + &key.unseal_share(self.session_id, &dealing.dealer, &dealing.sealed_share)?;
+        }
+        Ok(share)
+    }
+
+    /// The script that locks an output to the group key
+    pub fn script(&self) -> Result<TariScript, MultisigError> {
+        Ok(crypto::multisig_script(&self.group_public_key()?))
+    }
+
+    fn check_ready(&self) -> Result<(), MultisigError> {
+        if self.is_ready() {
+            Ok(())
+        } else {
+            Err(MultisigError::KeyGenerationIncomplete(self.session_id))
+        }
+    }
+}
+
+/// An output locked to the group key of a multisig session
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultisigOutput {
+    pub session_id: u64,
+    pub output: TransactionOutput,
+    pub value: MicroTari,
+    /// The spending key of the output is known to all the participants, the output can only be spent with a script
+    /// signature for the group key
+    pub spending_key: PrivateKey,
+    pub spent: bool,
+}
+
+/// What the signers of a signing round sign
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MultisigSigningRequest {
+    /// Sign a message with the group key
+    Message(Vec<u8>),
+    /// Spend a multisig output by paying its value, minus the fee, to `destination` as a one-sided payment. The
+    /// coordinator builds and submits the transaction.
+    Spend {
+        commitment: Commitment,
+        destination: PublicKey,
+        fee_per_gram: MicroTari,
+        coordinator: PublicKey,
+    },
+}
+
+impl MultisigSigningRequest {
+    /// The number of signatures the signers produce together. A spend signs the script of its input and the metadata
+    /// of its output.
+    pub fn num_signatures(&self) -> usize {
+        match self {
+            MultisigSigningRequest::Message(_) => 1,
+            MultisigSigningRequest::Spend { .. } => 2,
+        }
+    }
+}
+
+impl fmt::Display for MultisigSigningRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultisigSigningRequest::Message(message) => write!(f, "Sign message {}", message.to_hex()),
+            MultisigSigningRequest::Spend {
+                commitment,
+                destination,
+                fee_per_gram,
+                ..
+            } => write!(
+                f,
+                "Spend output {} to {} at {} per gram",
+                commitment.to_hex(),
+                destination,
+                fee_per_gram
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MultisigSigningStatus {
+    /// A signing request was received and this wallet has not approved it yet
+    AwaitingApproval,
+    /// Waiting for the nonce commitments of all the signers, and for the transaction of a spend
+    CollectingNonces,
+    /// Waiting for the partial signatures of all the signers
+    CollectingSignatures,
+    /// The signature is complete and valid, or the spend was submitted
+    Completed,
+    /// This wallet lost its nonces before it signed, e.g. in a restart, so the round can never complete
+    Abandoned,
+}
+
+impl fmt::Display for MultisigSigningStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultisigSigningStatus::AwaitingApproval => write!(f, "Awaiting approval"),
+            MultisigSigningStatus::CollectingNonces => write!(f, "Collecting nonces"),
+            MultisigSigningStatus::CollectingSignatures => write!(f, "Collecting signatures"),
+            MultisigSigningStatus::Completed => write!(f, "Completed"),
+            MultisigSigningStatus::Abandoned => write!(f, "Abandoned"),
+        }
+    }
+}
+
+/// A cooperative signing by `threshold` participants of a multisig session
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultisigSigningRound {
+    pub signing_id: u64,
+    pub session_id: u64,
+    pub request: MultisigSigningRequest,
+    pub signers: Vec<PublicKey>,
+    pub status: MultisigSigningStatus,
+    /// The signers' nonce commitments, one for every signature
+    pub nonce_commitments: Vec<(PublicKey, Vec<NonceCommitment>)>,
+    /// The signers' Diffie-Hellman shares with the destination of a spend
+    pub dh_shares: Vec<(PublicKey, DhShare)>,
+    /// The transaction that the signers of a spend sign, provided by the coordinator
+    pub spend_template: Option<MultisigSpendTemplate>,
+    /// The signers' partial signatures, one for every signature
+    pub partial_signatures: Vec<(PublicKey, Vec<PrivateKey>)>,
+    /// The signature of a message
+    pub signature: Option<Signature>,
+    /// The transaction of a spend, if this wallet submitted it
+    pub tx_id: Option<TxId>,
+}
+
+impl MultisigSigningRound {
+    pub fn new(signing_id: u64, session_id: u64, request: MultisigSigningRequest, signers: Vec<PublicKey>) -> Self {
+        Self {
+            signing_id,
+            session_id,
+            request,
+            signers: crypto::sorted_keys(&signers),
+            status: MultisigSigningStatus::AwaitingApproval,
+            nonce_commitments: Vec::new(),
+            dh_shares: Vec::new(),
+            spend_template: None,
+            partial_signatures: Vec::new(),
+            signature: None,
+            tx_id: None,
+        }
+    }
+
+    pub fn is_signer(&self, public_key: &PublicKey) -> bool {
+        self.signers.contains(public_key)
+    }
+
+    pub fn is_coordinator(&self, public_key: &PublicKey) -> bool {
+        match &self.request {
+            MultisigSigningRequest::Message(_) => false,
+            MultisigSigningRequest::Spend { coordinator, .. } => coordinator == public_key,
+        }
+    }
+
+    pub fn nonce_commitments(&self, signer: &PublicKey) -> Option<&Vec<NonceCommitment>> {
+        self.nonce_commitments.iter().find(|(k, _)| k == signer).map(|(_, n)| n)
+    }
+
+    pub fn add_nonce_commitments(
+        &mut self,
+        signer: PublicKey,
+        commitments: Vec<NonceCommitment>,
+    ) -> Result<(), MultisigError> {
+        if commitments.len() != self.request.num_signatures() {
+            return Err(MultisigError::ConversionError(format!(
+                "{} nonce commitments for {} signatures",
+                commitments.len(),
+                self.request.num_signatures()
+            )));
+        }
+        if self.nonce_commitments(&signer).is_none() {
+            self.nonce_commitments.push((signer, commitments));
+        }
+        Ok(())
+    }
+
+    pub fn dh_share(&self, signer: &PublicKey) -> Option<&DhShare> {
+        self.dh_shares.iter().find(|(k, _)| k == signer).map(|(_, s)| s)
+    }
+
+    pub fn add_dh_share(&mut self, signer: PublicKey, dh_share: DhShare) {
+        if self.dh_share(&signer).is_none() {
+            self.dh_shares.push((signer, dh_share));
+        }
+    }
+
+    pub fn partial_signatures(&self, signer: &PublicKey) -> Option<&Vec<PrivateKey>> {
+        self.partial_signatures
+            .iter()
+            .find(|(k, _)| k == signer)
+            .map(|(_, s)| s)
+    }
+
+    pub fn add_partial_signatures(
+        &mut self,
+        signer: PublicKey,
+        partial_signatures: Vec<PrivateKey>,
+    ) -> Result<(), MultisigError> {
+        if partial_signatures.len() != self.request.num_signatures() {
+            return Err(MultisigError::ConversionError(format!(
+                "{} partial signatures for {} signatures",
+                partial_signatures.len(),
+                self.request.num_signatures()
+            )));
+        }
+        if self.partial_signatures(&signer).is_none() {
+            self.partial_signatures.push((signer, partial_signatures));
+        }
+        Ok(())
+    }
+
+    pub fn has_all_nonce_commitments(&self) -> bool {
+        self.signers.iter().all(|s| self.nonce_commitments(s).is_some())
+    }
+
+    /// Whether the signers have everything they need to sign: all the nonce commitments, and for a spend all the
+    /// Diffie-Hellman shares and the transaction
+    pub fn is_ready_to_sign(&self) -> bool {
+        match self.request {
+            MultisigSigningRequest::Message(_) => self.has_all_nonce_commitments(),
+            MultisigSigningRequest::Spend { .. } => {
+                self.has_all_nonce_commitments() && self.has_all_dh_shares() && self.spend_template.is_some()
+            },
+        }
+    }
+
+    pub fn has_all_dh_shares(&self) -> bool {
+        self.signers.iter().all(|s| self.dh_share(s).is_some())
+    }
+
+    pub fn has_all_partial_signatures(&self) -> bool {
+        self.signers.iter().all(|s| self.partial_signatures(s).is_some())
+    }
+
+    /// The signers' nonce commitments for one of the signatures
+    pub fn signature_nonce_commitments(&self, signature: usize) -> Vec<(PublicKey, NonceCommitment)> {
+        self.nonce_commitments
+            .iter()
+            .filter_map(|(k, n)| n.get(signature).map(|n| (k.clone(), n.clone())))
+            .collect()
+    }
+
+    /// What the binding factors of one of the signatures commit to, besides the nonce commitments
+    pub fn binding_context(&self, signature: usize) -> Vec<u8> {
+        let mut context = Vec::new();
+        context.extend_from_slice(&self.signing_id.to_le_bytes());
+        context.extend_from_slice(&(signature as u64).to_le_bytes());
+        match &self.request {
+            MultisigSigningRequest::Message(message) => context.extend_from_slice(message),
+            MultisigSigningRequest::Spend { .. } => {
+                if let Some(template) = &self.spend_template {
+                    context.extend_from_slice(&template.hash());
+                }
+            },
+        }
+        context
+    }
+
+    /// The participant indexes of the signers
+    pub fn signer_indexes(&self, session: &MultisigSession) -> Result<Vec<u64>, MultisigError> {
+        self.signers.iter().map(|s| session.participant_index(s)).collect()
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Imports of code generated from protobuf files
+
+use crate::multisig_service::{
+    crypto::{self, DhShare},
+    models::{MultisigOutput, MultisigSession, MultisigSigningRequest, MultisigSigningRound},
+    spend::MultisigSpendTemplate,
+};
+use chrono::Utc;
+use std::convert::TryFrom;
+use tari_common_types::types::{PrivateKey, PublicKey, Signature};
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_crypto::tari_utilities::ByteArray;
+
+include!(concat!(env!("OUT_DIR"), "/tari.wallet.multisig.rs"));
+
+impl MultisigDealing {
+    /// Construct the dealing this wallet sends to one of the other participants of the session
+    pub fn new(session: &MultisigSession, commitments: &[PublicKey], proof: &Signature, share: &PrivateKey) -> Self {
+        Self {
+            session_id: session.session_id,
+            threshold: session.threshold as u32,
+            participants: session.participants.iter().map(|p| p.to_vec()).collect(),
+            commitments: commitments.iter().map(|c| c.to_vec()).collect(),
+            proof_public_nonce: proof.get_public_nonce().to_vec(),
+            proof_signature: proof.get_signature().to_vec(),
+            share: share.to_vec(),
+        }
+    }
+
+    /// The session the dealing is for, without any dealings
+    pub fn session(&self) -> Result<MultisigSession, String> {
+        MultisigSession::new(
+            self.session_id,
+            self.threshold as usize,
+            public_keys(&self.participants)?,
+            Utc::now().naive_utc(),
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn commitment_keys(&self) -> Result<Vec<PublicKey>, String> {
+        public_keys(&self.commitments)
+    }
+
+    pub fn proof(&self) -> Result<Signature, String> {
+        Ok(Signature::new(
+            PublicKey::from_bytes(&self.proof_public_nonce).map_err(|e| e.to_string())?,
+            PrivateKey::from_bytes(&self.proof_signature).map_err(|e| e.to_string())?,
+        ))
+    }
+
+    pub fn share_key(&self) -> Result<PrivateKey, String> {
+        PrivateKey::from_bytes(&self.share).map_err(|e| e.to_string())
+    }
+}
+
+impl MultisigSigning {
+    /// Construct the message this signer sends to the other signers of the signing round
+    pub fn new(
+        round: &MultisigSigningRound,
+        nonce_commitments: &[crypto::NonceCommitment],
+        dh_share: Option<&DhShare>,
+        spend_template: Option<&MultisigSpendTemplate>,
+        partial_signatures: Option<&Vec<PrivateKey>>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            session_id: round.session_id,
+            signing_id: round.signing_id,
+            request: serde_json::to_vec(&round.request).map_err(|e| e.to_string())?,
+            signers: round.signers.iter().map(|s| s.to_vec()).collect(),
+            nonce_commitments: nonce_commitments
+                .iter()
+                .map(|n| NonceCommitment {
+                    hiding: n.hiding.to_vec(),
+                    binding: n.binding.to_vec(),
+                })
+                .collect(),
+            dh_share: dh_share
+                .map(serde_json::to_vec)
+                .transpose()
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default(),
+            spend_template: spend_template
+                .map(serde_json::to_vec)
+                .transpose()
+                .map_err(|e| e.to_string())?
+                .unwrap_or_default(),
+            partial_signatures: partial_signatures
+                .map(|s| s.iter().map(|s| s.to_vec()).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn signing_request(&self) -> Result<MultisigSigningRequest, String> {
+        serde_json::from_slice(&self.request).map_err(|e| e.to_string())
+    }
+
+    pub fn signer_keys(&self) -> Result<Vec<PublicKey>, String> {
+        public_keys(&self.signers)
+    }
+
+    pub fn nonce_commitment_keys(&self) -> Result<Vec<crypto::NonceCommitment>, String> {
+        self.nonce_commitments
+            .iter()
+            .map(|n| {
+                Ok(crypto::NonceCommitment {
+                    hiding: PublicKey::from_bytes(&n.hiding).map_err(|e| e.to_string())?,
+                    binding: PublicKey::from_bytes(&n.binding).map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+
+    pub fn dh_share_value(&self) -> Result<Option<DhShare>, String> {
+        if self.dh_share.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_slice(&self.dh_share)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn spend_template_value(&self) -> Result<Option<MultisigSpendTemplate>, String> {
+        if self.spend_template.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_slice(&self.spend_template)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn partial_signature_keys(&self) -> Result<Option<Vec<PrivateKey>>, String> {
+        if self.partial_signatures.is_empty() {
+            return Ok(None);
+        }
+        self.partial_signatures
+            .iter()
+            .map(|s| PrivateKey::from_bytes(s))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl TryFrom<MultisigOutput> for MultisigFunding {
+    type Error = String;
+
+    fn try_from(output: MultisigOutput) -> Result<Self, Self::Error> {
+        Ok(Self {
+            session_id: output.session_id,
+            output: serde_json::to_vec(&output.output).map_err(|e| e.to_string())?,
+            value: output.value.into(),
+            spending_key: output.spending_key.to_vec(),
+        })
+    }
+}
+
+impl TryFrom<MultisigFunding> for MultisigOutput {
+    type Error = String;
+
+    fn try_from(funding: MultisigFunding) -> Result<Self, Self::Error> {
+        Ok(Self {
+            session_id: funding.session_id,
+            output: serde_json::from_slice(&funding.output).map_err(|e| e.to_string())?,
+            value: MicroTari::from(funding.value),
+            spending_key: PrivateKey::from_bytes(&funding.spending_key).map_err(|e| e.to_string())?,
+            spent: false,
+        })
+    }
+}
+
+fn public_keys(keys: &[Vec<u8>]) -> Result<Vec<PublicKey>, String> {
+    keys.iter()
+        .map(|k| PublicKey::from_bytes(k))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,1042 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    multisig_service::{
+        crypto::{self, DhShare, MultisigKey, SigningNonces},
+        error::{MultisigError, MultisigServiceError, MultisigStorageError},
+        handle::{MultisigServiceRequest, MultisigServiceResponse},
+        models::{
+            MultisigDealing,
+            MultisigOutput,
+            MultisigSession,
+            MultisigSigningRequest,
+            MultisigSigningRound,
+            MultisigSigningStatus,
+        },
+        proto,
+        spend::{self, SpendNonces},
+        storage::database::{MultisigBackend, MultisigDatabase},
+    },
+    output_manager_service::TxId,
+    transaction_service::handle::TransactionServiceHandle,
+};
+use chrono::Utc;
+use futures::{pin_mut, Stream, StreamExt};
+use log::*;
+use rand::{rngs::OsRng, RngCore};
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+use tari_common_types::types::{Commitment, PrivateKey, PublicKey, Signature};
+use tari_comms::peer_manager::NodeIdentity;
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
+};
+use tari_core::transactions::{tari_amount::MicroTari, CryptoFactories};
+use tari_crypto::commitment::HomomorphicCommitmentFactory;
+use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
+use tari_service_framework::reply_channel;
+use tari_shutdown::ShutdownSignal;
+
+const LOG_TARGET: &str = "wallet::multisig_service::service";
+/// The number of invitations to sessions set up by other wallets that are kept until they are accepted
+const MAX_PENDING_SESSIONS: usize = 16;
+
+pub struct MultisigService<T, TMsgStream>
+where T: MultisigBackend + 'static
+{
+    db: MultisigDatabase<T>,
+    request_stream:
+        Option<reply_channel::Receiver<MultisigServiceRequest, Result<MultisigServiceResponse, MultisigServiceError>>>,
+    message_stream: Option<TMsgStream>,
+    outbound_message_service: OutboundMessageRequester,
+    transaction_service: TransactionServiceHandle,
+    node_identity: Arc<NodeIdentity>,
+    multisig_key: MultisigKey,
+    factories: CryptoFactories,
+    /// This wallet's nonces for the signing rounds it has not signed yet. They are never persisted, so they can not be
+    /// used twice after a restart or after the database is restored from a backup.
+    signing_nonces: HashMap<u64, Vec<SigningNonces>>,
+    /// The nonces of the spends this wallet coordinates and has not submitted yet, kept in memory for the same reason
+    spend_nonces: HashMap<u64, SpendNonces>,
+    shutdown_signal: Option<ShutdownSignal>,
+}
+
+impl<T, TMsgStream> MultisigService<T, TMsgStream>
+where
+    T: MultisigBackend + 'static,
+    TMsgStream: Stream<Item = DomainMessage<proto::MultisigMessage>>,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        request_stream: reply_channel::Receiver<
+            MultisigServiceRequest,
+            Result<MultisigServiceResponse, MultisigServiceError>,
+        >,
+        message_stream: TMsgStream,
+        db: MultisigDatabase<T>,
+        outbound_message_service: OutboundMessageRequester,
+        transaction_service: TransactionServiceHandle,
+        node_identity: Arc<NodeIdentity>,
+        multisig_key: MultisigKey,
+        factories: CryptoFactories,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            db,
+            request_stream: Some(request_stream),
+            message_stream: Some(message_stream),
+            outbound_message_service,
+            transaction_service,
+            node_identity,
+            multisig_key,
+            factories,
+            signing_nonces: HashMap::new(),
+            spend_nonces: HashMap::new(),
+            shutdown_signal: Some(shutdown_signal),
+        }
+    }
+
+    pub async fn start(mut self) -> Result<(), MultisigServiceError> {
+        let request_stream = self
+            .request_stream
+            .take()
+            .expect("Multisig Service initialized without request_stream")
+            .fuse();
+        pin_mut!(request_stream);
+
+        let message_stream = self
+            .message_stream
+            .take()
+            .expect("Multisig Service initialized without message_stream")
+            .fuse();
+        pin_mut!(message_stream);
+
+        let shutdown = self
+            .shutdown_signal
+            .take()
+            .expect("Multisig Service initialized without shutdown signal");
+        pin_mut!(shutdown);
+
+        self.abandon_unsigned_rounds().await?;
+
+        info!(target: LOG_TARGET, "Multisig Service started");
+        loop {
+            tokio::select! {
+                Some(request_context) = request_stream.next() => {
+                    let (request, reply_tx) = request_context.split();
+                    trace!(target: LOG_TARGET, "Handling Service API Request ({})", request);
+                    let response = self.handle_request(request).await.map_err(|e| {
+                        error!(target: LOG_TARGET, "Error handling request: {:?}", e);
+                        e
+                    });
+                    let _ = reply_tx.send(response).map_err(|e| {
+                        error!(target: LOG_TARGET, "Failed to send reply");
+                        e
+                    });
+                },
+                Some(msg) = message_stream.next() => {
+                    let (origin_public_key, inner_msg) = msg.clone().into_origin_and_inner();
+                    trace!(target: LOG_TARGET, "Handling Multisig Message, Trace: {}", msg.dht_header.message_tag);
+                    if let Err(e) = self.handle_multisig_message(origin_public_key, inner_msg).await {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Failed to handle incoming Multisig message: {}, Trace: {}", e, msg.dht_header.message_tag
+                        );
+                    }
+                },
+                _ = shutdown.wait() => {
+                    info!(target: LOG_TARGET, "Multisig service shutting down because it received the shutdown signal");
+                    break;
+                }
+            }
+        }
+        info!(target: LOG_TARGET, "Multisig Service ended");
+        Ok(())
+    }
+
+    async fn handle_request(
+        &mut self,
+        request: MultisigServiceRequest,
+    ) -> Result<MultisigServiceResponse, MultisigServiceError> {
+        match request {
+            MultisigServiceRequest::CreateSession(threshold, participants) => self
+                .create_session(threshold, participants)
+                .await
+                .map(|s| MultisigServiceResponse::SessionCreated(Box::new(s))),
+            MultisigServiceRequest::AcceptSession(session_id) => self
+                .accept_session(session_id)
+                .await
+                .map(|s| MultisigServiceResponse::SessionAccepted(Box::new(s))),
+            MultisigServiceRequest::GetSessions => {
+                Ok(self.db.get_sessions().await.map(MultisigServiceResponse::Sessions)?)
+            },
+            MultisigServiceRequest::FundSession(session_id, amount, fee_per_gram) => self
+                .fund_session(session_id, amount, fee_per_gram)
+                .await
+                .map(|(tx_id, commitment)| MultisigServiceResponse::FundingSent(tx_id, Box::new(commitment))),
+            MultisigServiceRequest::GetOutputs => {
+                Ok(self.db.get_outputs().await.map(MultisigServiceResponse::Outputs)?)
+            },
+            MultisigServiceRequest::StartSigning(session_id, message, signers) => self
+                .start_signing(session_id, MultisigSigningRequest::Message(message), signers)
+                .await
+                .map(MultisigServiceResponse::SigningStarted),
+            MultisigServiceRequest::StartSpend(session_id, commitment, destination, fee_per_gram, signers) => self
+                .start_spend(session_id, commitment, destination, fee_per_gram, signers)
+                .await
+                .map(MultisigServiceResponse::SigningStarted),
+            MultisigServiceRequest::ApproveSigning(signing_id) => self
+                .approve_signing(signing_id)
+                .await
+                .map(|_| MultisigServiceResponse::SigningApproved),
+            MultisigServiceRequest::GetSigningRound(signing_id) => Ok(self
+                .db
+                .get_signing_round(signing_id)
+                .await
+                .map(|r| MultisigServiceResponse::SigningRound(Box::new(r)))?),
+            MultisigServiceRequest::GetSigningRounds => Ok(self
+                .db
+                .get_signing_rounds()
+                .await
+                .map(MultisigServiceResponse::SigningRounds)?),
+            MultisigServiceRequest::FinalizeSigning(signing_id) => {
+                let round = self.db.get_signing_round(signing_id).await?;
+                match round.signature {
+                    Some(signature) if round.status == MultisigSigningStatus::Completed => {
+                        Ok(MultisigServiceResponse::Signature(Box::new(signature)))
+                    },
+                    _ => Err(MultisigError::SigningIncomplete(signing_id).into()),
+                }
+            },
+            MultisigServiceRequest::ApplyEncryption(cipher) => Ok(self
+                .db
+                .apply_encryption(*cipher)
+                .await
+                .map(|_| MultisigServiceResponse::EncryptionApplied)?),
+            MultisigServiceRequest::RemoveEncryption => Ok(self
+                .db
+                .remove_encryption()
+                .await
+                .map(|_| MultisigServiceResponse::EncryptionRemoved)?),
+        }
+    }
+
+    /// The nonces of the rounds this wallet has not signed yet, and of the spends it coordinates, did not survive the
+    /// restart, so those rounds can never complete
+    async fn abandon_unsigned_rounds(&mut self) -> Result<(), MultisigServiceError> {
+        let own_public_key = self.node_identity.public_key().clone();
+        for mut round in self.db.get_signing_rounds().await? {
+            let nonces_lost = match round.status {
+                MultisigSigningStatus::CollectingNonces => true,
+                MultisigSigningStatus::CollectingSignatures => round.is_coordinator(&own_public_key),
+                _ => false,
+            };
+            if nonces_lost {
+                warn!(
+                    target: LOG_TARGET,
+                    "Abandoning signing round {}, its nonces were lost in a restart", round.signing_id
+                );
+                round.status = MultisigSigningStatus::Abandoned;
+                self.db.upsert_signing_round(round).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn create_session(
+        &mut self,
+        threshold: usize,
+        mut participants: Vec<PublicKey>,
+    ) -> Result<MultisigSession, MultisigServiceError> {
+        let own_public_key = self.node_identity.public_key().clone();
+        if !participants.contains(&own_public_key) {
+            participants.push(own_public_key);
+        }
+        let session = MultisigSession::new(OsRng.next_u64(), threshold, participants, Utc::now().naive_utc())?;
+        let session = self.deal(session).await?;
+        info!(
+            target: LOG_TARGET,
+            "Created {}-of-{} multisig session {}",
+            session.threshold,
+            session.participants.len(),
+            session.session_id
+        );
+        Ok(session)
+    }
+
+    /// Accept the invitation to a session set up by another participant and deal this wallet's share
+    async fn accept_session(&mut self, session_id: u64) -> Result<MultisigSession, MultisigServiceError> {
+        let mut session = self.db.get_session(session_id).await?;
+        if session.accepted {
+            return Ok(session);
+        }
+        session.accepted = true;
+        let session = self.deal(session).await?;
+        info!(
+            target: LOG_TARGET,
+            "Joined {}-of-{} multisig session {}",
+            session.threshold,
+            session.participants.len(),
+            session.session_id
+        );
+        Ok(session)
+    }
+
+    /// Deal this wallet's polynomial for the key generation of the session: keep this wallet's own share and send
+    /// every other participant its share
+    async fn deal(&mut self, mut session: MultisigSession) -> Result<MultisigSession, MultisigServiceError> {
+        let own_public_key = self.node_identity.public_key().clone();
+        let session_id = session.session_id;
+        let polynomial = self.multisig_key.polynomial(session_id, session.threshold)?;
+        let commitments = crypto::polynomial_commitments(&polynomial);
+        let proof = crypto::dealing_proof(&polynomial[0], session_id, &own_public_key)?;
+
+        let own_share = crypto::evaluate_polynomial(&polynomial, session.participant_index(&own_public_key)?);
+        session.add_dealing(MultisigDealing {
+            dealer: own_public_key.clone(),
+            commitments: commitments.clone(),
+            sealed_share: self.multisig_key.seal_share(session_id, &own_public_key, &own_share)?,
+        })?;
+        self.db.upsert_session(session.clone()).await?;
+
+        for participant in session.participants.iter().filter(|p| **p != own_public_key) {
+            let share = crypto::evaluate_polynomial(&polynomial, session.participant_index(participant)?);
+            let dealing = proto::MultisigDealing::new(&session, &commitments, &proof, &share);
+            self.send_message(participant.clone(), proto::multisig_message::Message::Dealing(dealing))
+                .await?;
+        }
+        Ok(session)
+    }
+
+    async fn fund_session(
+        &mut self,
+        session_id: u64,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, Commitment), MultisigServiceError> {
+        let session = self.db.get_session(session_id).await?;
+        let (tx_id, output, spending_key) = self
+            .transaction_service
+            .send_multisig_funding_transaction(session.group_public_key()?, amount, fee_per_gram)
+            .await?;
+        let output = MultisigOutput {
+            session_id,
+            output,
+            value: amount,
+            spending_key,
+            spent: false,
+        };
+        self.db.upsert_output(output.clone()).await?;
+
+        let funding = proto::MultisigFunding::try_from(output.clone()).map_err(MultisigError::ConversionError)?;
+        let own_public_key = self.node_identity.public_key().clone();
+        for participant in session.participants.iter().filter(|p| **p != own_public_key) {
+            self.send_message(
+                participant.clone(),
+                proto::multisig_message::Message::Funding(funding.clone()),
+            )
+            .await?;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Funded multisig session {} with {} in transaction {}", session_id, amount, tx_id
+        );
+        Ok((tx_id, output.output.commitment))
+    }
+
+    async fn start_spend(
+        &mut self,
+        session_id: u64,
+        commitment: Commitment,
+        destination: PublicKey,
+        fee_per_gram: MicroTari,
+        signers: Vec<PublicKey>,
+    ) -> Result<u64, MultisigServiceError> {
+        let output = self.db.get_output(commitment.clone()).await?;
+        check_spendable(&output, session_id)?;
+        let request = MultisigSigningRequest::Spend {
+            commitment,
+            destination,
+            fee_per_gram,
+            coordinator: self.node_identity.public_key().clone(),
+        };
+        self.start_signing(session_id, request, signers).await
+    }
+
+    async fn start_signing(
+        &mut self,
+        session_id: u64,
+        request: MultisigSigningRequest,
+        mut signers: Vec<PublicKey>,
+    ) -> Result<u64, MultisigServiceError> {
+        let session = self.db.get_session(session_id).await?;
+        let own_public_key = self.node_identity.public_key().clone();
+        if !signers.contains(&own_public_key) {
+            signers.push(own_public_key);
+        }
+        validate_signers(&session, &signers)?;
+
+        let mut round = MultisigSigningRound::new(OsRng.next_u64(), session_id, request, signers);
+        let signing_id = round.signing_id;
+        self.add_own_nonces(&session, &mut round)?;
+        self.progress_signing_round(&session, round, true).await?;
+        info!(
+            target: LOG_TARGET,
+            "Started signing round {} for multisig session {}", signing_id, session_id
+        );
+        Ok(signing_id)
+    }
+
+    async fn approve_signing(&mut self, signing_id: u64) -> Result<(), MultisigServiceError> {
+        let mut round = self.db.get_signing_round(signing_id).await?;
+        if round.status != MultisigSigningStatus::AwaitingApproval {
+            return Ok(());
+        }
+        let session = self.db.get_session(round.session_id).await?;
+        self.add_own_nonces(&session, &mut round)?;
+        self.progress_signing_round(&session, round, true).await?;
+        info!(target: LOG_TARGET, "Approved signing round {}", signing_id);
+        Ok(())
+    }
+
+    async fn handle_multisig_message(
+        &mut self,
+        source_pubkey: PublicKey,
+        message: proto::MultisigMessage,
+    ) -> Result<(), MultisigServiceError> {
+        match message.message {
+            Some(proto::multisig_message::Message::Dealing(dealing)) => {
+                self.handle_dealing(source_pubkey, dealing).await
+            },
+            Some(proto::multisig_message::Message::Signing(signing)) => {
+                self.handle_signing(source_pubkey, signing).await
+            },
+            Some(proto::multisig_message::Message::Funding(funding)) => {
+                self.handle_funding(source_pubkey, funding).await
+            },
+            None => Err(MultisigServiceError::InvalidMessage(
+                "Empty multisig message".to_string(),
+            )),
+        }
+    }
+
+    async fn handle_dealing(
+        &mut self,
+        source_pubkey: PublicKey,
+        dealing: proto::MultisigDealing,
+    ) -> Result<(), MultisigServiceError> {
+        let own_public_key = self.node_identity.public_key().clone();
+        let setup = dealing.session().map_err(MultisigServiceError::InvalidMessage)?;
+        if !setup.is_participant(&source_pubkey) || !setup.is_participant(&own_public_key) {
+            return Err(MultisigServiceError::InvalidMessage(format!(
+                "Not a participant of multisig session {}",
+                setup.session_id
+            )));
+        }
+
+        // The first dealing received for a session this wallet does not know is an invitation to it. The dealings are
+        // kept, but this wallet only deals its own share once the invitation is accepted.
+        let mut session = match self.db.get_session(setup.session_id).await {
+            Ok(session) => {
+                if session.threshold != setup.threshold || session.participants != setup.participants {
+                    return Err(MultisigServiceError::InvalidMessage(format!(
+                        "Dealing does not match multisig session {}",
+                        session.session_id
+                    )));
+                }
+                session
+            },
+            Err(MultisigStorageError::ValueNotFound(_)) => {
+                let pending = self.db.get_sessions().await?.iter().filter(|s| !s.accepted).count();
+                if pending >= MAX_PENDING_SESSIONS {
+                    return Err(MultisigServiceError::InvalidMessage(format!(
+                        "Too many pending multisig invitations, ignoring session {}",
+                        setup.session_id
+                    )));
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "Received an invitation to {}-of-{} multisig session {} from {}",
+                    setup.threshold,
+                    setup.participants.len(),
+                    setup.session_id,
+                    source_pubkey
+                );
+                MultisigSession {
+                    accepted: false,
+                    ..setup
+                }
+            },
+            Err(e) => return Err(e.into()),
+        };
+        if session.dealing(&source_pubkey).is_some() {
+            trace!(
+                target: LOG_TARGET,
+                "Already received the dealing of {} for multisig session {}",
+                source_pubkey,
+                session.session_id
+            );
+            return Ok(());
+        }
+
+        let commitments = dealing
+            .commitment_keys()
+            .map_err(MultisigServiceError::InvalidMessage)?;
+        let proof = dealing.proof().map_err(MultisigServiceError::InvalidMessage)?;
+        let share = dealing.share_key().map_err(MultisigServiceError::InvalidMessage)?;
+        if commitments.len() != session.threshold ||
+            !crypto::verify_dealing_proof(&proof, &commitments[0], session.session_id, &source_pubkey)
+        {
+            return Err(MultisigError::InvalidDealing("Invalid polynomial commitments".to_string()).into());
+        }
+        if !crypto::verify_share(&share, &commitments, session.participant_index(&own_public_key)?) {
+            return Err(MultisigError::InvalidDealing("The share does not match the commitments".to_string()).into());
+        }
+        let sealed_share = self
+            .multisig_key
+            .seal_share(session.session_id, &source_pubkey, &share)?;
+        session.add_dealing(MultisigDealing {
+            dealer: source_pubkey,
+            commitments,
+            sealed_share,
+        })?;
+
+        self.db.upsert_session(session.clone()).await?;
+        if session.is_ready() {
+            info!(
+                target: LOG_TARGET,
+                "Key generation of multisig session {} complete", session.session_id
+            );
+        }
+        Ok(())
+    }
+
+    async fn handle_funding(
+        &mut self,
+        source_pubkey: PublicKey,
+        funding: proto::MultisigFunding,
+    ) -> Result<(), MultisigServiceError> {
+        let output = MultisigOutput::try_from(funding).map_err(MultisigServiceError::InvalidMessage)?;
+        let session = self.db.get_session(output.session_id).await?;
+        if !session.is_participant(&source_pubkey) {
+            return Err(MultisigServiceError::InvalidMessage(format!(
+                "Sender is not a participant of multisig session {}",
+                session.session_id
+            )));
+        }
+        if output.output.script != session.script()? ||
+            output.output.commitment !=
+                self.factories
+                    .commitment
+                    .commit_value(&output.spending_key, output.value.into())
+        {
+            return Err(MultisigServiceError::InvalidMessage(format!(
+                "Output is not a multisig output of session {}",
+                session.session_id
+            )));
+        }
+        match self.db.get_output(output.output.commitment.clone()).await {
+            Ok(_) => Ok(()),
+            Err(MultisigStorageError::ValueNotFound(_)) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Multisig session {} received an output of {}", session.session_id, output.value
+                );
+                Ok(self.db.upsert_output(output).await?)
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn handle_signing(
+        &mut self,
+        source_pubkey: PublicKey,
+        signing: proto::MultisigSigning,
+    ) -> Result<(), MultisigServiceError> {
+        let signers = crypto::sorted_keys(&signing.signer_keys().map_err(MultisigServiceError::InvalidMessage)?);
+        let request = signing
+            .signing_request()
+            .map_err(MultisigServiceError::InvalidMessage)?;
+        let session = self.db.get_session(signing.session_id).await?;
+
+        let mut round = match self.db.get_signing_round(signing.signing_id).await {
+            Ok(round) => {
+                if round.session_id != signing.session_id || round.request != request || round.signers != signers {
+                    return Err(MultisigServiceError::InvalidMessage(format!(
+                        "Signing round {} does not match the known signing round",
+                        signing.signing_id
+                    )));
+                }
+                round
+            },
+            Err(MultisigStorageError::ValueNotFound(_)) => {
+                validate_signers(&session, &signers)?;
+                if let MultisigSigningRequest::Spend {
+                    commitment,
+                    coordinator,
+                    ..
+                } = &request
+                {
+                    check_spendable(&self.db.get_output(commitment.clone()).await?, session.session_id)?;
+                    if !signers.contains(coordinator) {
+                        return Err(MultisigServiceError::InvalidMessage(
+                            "The coordinator of a spend must be a signer".to_string(),
+                        ));
+                    }
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "Received signing request {} for multisig session {}: {}",
+                    signing.signing_id,
+                    signing.session_id,
+                    request
+                );
+                MultisigSigningRound::new(signing.signing_id, signing.session_id, request, signers)
+            },
+            Err(e) => return Err(e.into()),
+        };
+
+        if !round.is_signer(&source_pubkey) {
+            return Err(MultisigServiceError::InvalidMessage(format!(
+                "Sender is not a signer of signing round {}",
+                round.signing_id
+            )));
+        }
+        let nonce_commitments = signing
+            .nonce_commitment_keys()
+            .map_err(MultisigServiceError::InvalidMessage)?;
+        round.add_nonce_commitments(source_pubkey.clone(), nonce_commitments)?;
+        if let Some(dh_share) = signing.dh_share_value().map_err(MultisigServiceError::InvalidMessage)? {
+            let destination = spend_destination(&round.request)?;
+            if !dh_share.verify(&session.verification_share(&source_pubkey)?, destination)? {
+                return Err(MultisigServiceError::InvalidMessage(format!(
+                    "Invalid Diffie-Hellman share for signing round {}",
+                    round.signing_id
+                )));
+            }
+            round.add_dh_share(source_pubkey.clone(), dh_share);
+        }
+        if let Some(template) = signing
+            .spend_template_value()
+            .map_err(MultisigServiceError::InvalidMessage)?
+        {
+            // The template is checked before this wallet signs it, once all the Diffie-Hellman shares are known
+            if round.is_coordinator(&source_pubkey) && round.spend_template.is_none() {
+                round.spend_template = Some(template);
+            }
+        }
+        if let Some(partial_signatures) = signing
+            .partial_signature_keys()
+            .map_err(MultisigServiceError::InvalidMessage)?
+        {
+            round.add_partial_signatures(source_pubkey, partial_signatures)?;
+        }
+        self.progress_signing_round(&session, round, false).await
+    }
+
+    /// Create this wallet's nonces for the signing round, and its Diffie-Hellman share for a spend
+    fn add_own_nonces(
+        &mut self,
+        session: &MultisigSession,
+        round: &mut MultisigSigningRound,
+    ) -> Result<(), MultisigServiceError> {
+        let own_public_key = self.node_identity.public_key().clone();
+        if !round.is_signer(&own_public_key) {
+            return Err(MultisigError::NotASigner(round.signing_id).into());
+        }
+        let nonces = (0..round.request.num_signatures())
+            .map(|_| SigningNonces::random())
+            .collect::<Vec<_>>();
+        round.add_nonce_commitments(own_public_key.clone(), nonces.iter().map(|n| n.commitment()).collect())?;
+        if let MultisigSigningRequest::Spend { destination, .. } = &round.request {
+            let dh_share = DhShare::new(&session.secret_share(&self.multisig_key)?, destination)?;
+            round.add_dh_share(own_public_key, dh_share);
+        }
+        self.signing_nonces.insert(round.signing_id, nonces);
+        round.status = MultisigSigningStatus::CollectingNonces;
+        Ok(())
+    }
+
+    /// Move the signing round forward as far as the collected nonces and partial signatures allow, then store it. If
+    /// `announce` is set this wallet's contribution is sent to the other signers even if it can't sign yet.
+    async fn progress_signing_round(
+        &mut self,
+        session: &MultisigSession,
+        mut round: MultisigSigningRound,
+        announce: bool,
+    ) -> Result<(), MultisigServiceError> {
+        let own_public_key = self.node_identity.public_key().clone();
+
+        if round.status == MultisigSigningStatus::CollectingNonces {
+            if round.is_coordinator(&own_public_key) &&
+                round.spend_template.is_none() &&
+                round.has_all_nonce_commitments() &&
+                round.has_all_dh_shares()
+            {
+                let input = self.spend_input(&round).await?;
+                let (template, nonces) = spend::build_spend_template(
+                    &input,
+                    &session.group_public_key()?,
+                    &round.request,
+                    &group_dh(session, &round)?,
+                    &self.factories,
+                )?;
+                self.spend_nonces.insert(round.signing_id, nonces);
+                round.spend_template = Some(template);
+            }
+
+            if round.is_ready_to_sign() && !self.signing_nonces.contains_key(&round.signing_id) {
+                round.status = MultisigSigningStatus::Abandoned;
+                self.db.upsert_signing_round(round.clone()).await?;
+                return Err(MultisigError::NoncesLost(round.signing_id).into());
+            }
+            let signed = if round.is_ready_to_sign() {
+                let partial_signatures = self.partial_sign(session, &round).await?;
+                // The nonces are only ever used for a single partial signature
+                self.signing_nonces.remove(&round.signing_id);
+                round.add_partial_signatures(own_public_key.clone(), partial_signatures)?;
+                round.status = MultisigSigningStatus::CollectingSignatures;
+                true
+            } else {
+                false
+            };
+            // Persist before sending so that a restart can never produce a second partial signature
+            self.db.upsert_signing_round(round.clone()).await?;
+            if announce || signed {
+                self.send_to_signers(&round).await?;
+            }
+        }
+
+        if round.status == MultisigSigningStatus::CollectingSignatures && round.has_all_partial_signatures() {
+            self.complete_signing_round(session, &mut round).await?;
+        }
+
+        Ok(self.db.upsert_signing_round(round).await?)
+    }
+
+    /// This wallet's partial signatures for the signing round
+    async fn partial_sign(
+        &mut self,
+        session: &MultisigSession,
+        round: &MultisigSigningRound,
+    ) -> Result<Vec<PrivateKey>, MultisigServiceError> {
+        let own_public_key = self.node_identity.public_key().clone();
+        let group_public_key = session.group_public_key()?;
+        let input = match &round.request {
+            MultisigSigningRequest::Message(_) => None,
+            MultisigSigningRequest::Spend { .. } => {
+                let input = self.spend_input(round).await?;
+                let template = round
+                    .spend_template
+                    .as_ref()
+                    .ok_or(MultisigError::SigningIncomplete(round.signing_id))?;
+                spend::verify_spend_template(
+                    template,
+                    &input,
+                    &group_public_key,
+                    &round.request,
+                    &group_dh(session, round)?,
+                    &self.factories,
+                )?;
+                Some(input)
+            },
+        };
+        let nonces = match self.signing_nonces.get(&round.signing_id) {
+            Some(nonces) => nonces,
+            None => return Err(MultisigError::NoncesLost(round.signing_id).into()),
+        };
+
+        let secret_share = session.secret_share(&self.multisig_key)?;
+        let lagrange_coefficient = crypto::lagrange_coefficient(
+            session.participant_index(&own_public_key)?,
+            &round.signer_indexes(session)?,
+        )?;
+        let mut partial_signatures = Vec::with_capacity(nonces.len());
+        for (signature, nonces) in nonces.iter().enumerate() {
+            let commitments = round.signature_nonce_commitments(signature);
+            let context = round.binding_context(signature);
+            let group_nonce = crypto::group_nonce(&group_public_key, &commitments, &context)?;
+            let binding_factor = crypto::binding_factor(&own_public_key, &group_public_key, &commitments, &context)?;
+            let challenge = signature_challenge(round, input.as_ref(), &group_public_key, signature, &group_nonce)?;
+            partial_signatures.push(crypto::partial_sign(
+                nonces,
+                &binding_factor,
+                &lagrange_coefficient,
+                &secret_share,
+                &challenge,
+            ));
+        }
+        Ok(partial_signatures)
+    }
+
+    /// Verify and combine all the partial signatures. A completed message signature is stored with the round, a
+    /// completed spend is submitted by its coordinator.
+    async fn complete_signing_round(
+        &mut self,
+        session: &MultisigSession,
+        round: &mut MultisigSigningRound,
+    ) -> Result<(), MultisigServiceError> {
+        let group_public_key = session.group_public_key()?;
+        let input = match &round.request {
+            MultisigSigningRequest::Message(_) => None,
+            MultisigSigningRequest::Spend { .. } => Some(self.spend_input(round).await?),
+        };
+        let indexes = round.signer_indexes(session)?;
+
+        let mut group_nonces = Vec::new();
+        let mut signatures = Vec::new();
+        for signature in 0..round.request.num_signatures() {
+            let commitments = round.signature_nonce_commitments(signature);
+            let context = round.binding_context(signature);
+            let group_nonce = crypto::group_nonce(&group_public_key, &commitments, &context)?;
+            let challenge = signature_challenge(round, input.as_ref(), &group_public_key, signature, &group_nonce)?;
+            let mut sum = PrivateKey::default();
+            for (signer, index) in round.signers.iter().zip(indexes.iter()) {
+                let (nonce_commitment, partial_signature) = match (
+                    round.nonce_commitments(signer).and_then(|n| n.get(signature)),
+                    round.partial_signatures(signer).and_then(|s| s.get(signature)),
+                ) {
+                    (Some(n), Some(s)) => (n, s),
+                    _ => return Err(MultisigError::SigningIncomplete(round.signing_id).into()),
+                };
+                if !crypto::verify_partial_signature(
+                    partial_signature,
+                    nonce_commitment,
+                    &crypto::binding_factor(signer, &group_public_key, &commitments, &context)?,
+                    &crypto::lagrange_coefficient(*index, &indexes)?,
+                    &session.verification_share(signer)?,
+                    &challenge,
+                ) {
+                    return Err(MultisigError::InvalidSignature.into());
+                }
+                sum = &sum + partial_signature;
+            }
+            group_nonces.push(group_nonce);
+            signatures.push(sum);
+        }
+
+        match input {
+            None => {
+                round.signature = Some(Signature::new(group_nonces[0].clone(), signatures[0].clone()));
+            },
+            Some(mut input) => {
+                if round.is_coordinator(self.node_identity.public_key()) {
+                    round.tx_id = Some(
+                        self.submit_spend(session, round, &input, &group_nonces, &signatures)
+                            .await?,
+                    );
+                }
+                input.spent = true;
+                self.db.upsert_output(input).await?;
+            },
+        }
+        round.status = MultisigSigningStatus::Completed;
+        info!(target: LOG_TARGET, "Signing round {} completed", round.signing_id);
+        Ok(())
+    }
+
+    async fn submit_spend(
+        &mut self,
+        session: &MultisigSession,
+        round: &MultisigSigningRound,
+        input: &MultisigOutput,
+        group_nonces: &[PublicKey],
+        signatures: &[PrivateKey],
+    ) -> Result<TxId, MultisigServiceError> {
+        let template = round
+            .spend_template
+            .as_ref()
+            .ok_or(MultisigError::SigningIncomplete(round.signing_id))?;
+        let nonces = self
+            .spend_nonces
+            .remove(&round.signing_id)
+            .ok_or(MultisigError::NoncesLost(round.signing_id))?;
+        let tx = spend::finalize_spend(
+            template,
+            nonces,
+            input,
+            &session.group_public_key()?,
+            &round.request,
+            &group_dh(session, round)?,
+            group_nonces,
+            signatures,
+            &self.factories,
+        )?;
+        let tx_id = OsRng.next_u64();
+        self.transaction_service
+            .submit_transaction(
+                tx_id,
+                tx,
+                template.fee,
+                input.value - template.fee,
+                format!("Multisig spend from session {}", session.session_id),
+            )
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "Submitted multisig spend of signing round {} in transaction {}", round.signing_id, tx_id
+        );
+        Ok(tx_id)
+    }
+
+    async fn spend_input(&self, round: &MultisigSigningRound) -> Result<MultisigOutput, MultisigServiceError> {
+        match &round.request {
+            MultisigSigningRequest::Spend { commitment, .. } => Ok(self.db.get_output(commitment.clone()).await?),
+            MultisigSigningRequest::Message(_) => {
+                Err(MultisigError::InvalidSpend("Not a spend request".to_string()).into())
+            },
+        }
+    }
+
+    /// Send this wallet's nonce commitments, Diffie-Hellman share, spend template and partial signatures, as far as it
+    /// has them, to the other signers
+    async fn send_to_signers(&mut self, round: &MultisigSigningRound) -> Result<(), MultisigServiceError> {
+        let own_public_key = self.node_identity.public_key().clone();
+        let nonce_commitments = round
+            .nonce_commitments(&own_public_key)
+            .ok_or(MultisigError::NotASigner(round.signing_id))?;
+        let spend_template = if round.is_coordinator(&own_public_key) {
+            round.spend_template.as_ref()
+        } else {
+            None
+        };
+        let signing = proto::MultisigSigning::new(
+            round,
+            nonce_commitments,
+            round.dh_share(&own_public_key),
+            spend_template,
+            round.partial_signatures(&own_public_key),
+        )
+        .map_err(MultisigError::ConversionError)?;
+        for signer in round.signers.iter().filter(|s| **s != own_public_key) {
+            self.send_message(
+                signer.clone(),
+                proto::multisig_message::Message::Signing(signing.clone()),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Multisig messages carry secret shares and the spending keys of multisig outputs, so they are always encrypted
+    /// for the recipient
+    async fn send_message(
+        &mut self,
+        destination: PublicKey,
+        message: proto::multisig_message::Message,
+    ) -> Result<(), MultisigServiceError> {
+        let _ = self
+            .outbound_message_service
+            .send_message(
+                SendMessageParams::new()
+                    .direct_public_key(destination.clone())
+                    .with_encryption(OutboundEncryption::EncryptFor(Box::new(destination)))
+                    .with_discovery(true)
+                    .finish(),
+                OutboundDomainMessage::new(TariMessageType::MultisigMessage, proto::MultisigMessage {
+                    message: Some(message),
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// The signers of a round must be exactly `threshold` distinct participants of a session whose key generation is
+/// complete
+fn validate_signers(session: &MultisigSession, signers: &[PublicKey]) -> Result<(), MultisigError> {
+    if !session.is_ready() {
+        return Err(MultisigError::KeyGenerationIncomplete(session.session_id));
+    }
+    let signers = crypto::sorted_keys(signers);
+    if signers.len() != session.threshold {
+        return Err(MultisigError::InvalidParticipants(format!(
+            "{} signers provided for a {}-of-{} multisig",
+            signers.len(),
+            session.threshold,
+            session.participants.len()
+        )));
+    }
+    if let Some(s) = signers.iter().find(|s| !session.is_participant(s)) {
+        return Err(MultisigError::InvalidParticipants(format!(
+            "{} is not a participant of multisig session {}",
+            s, session.session_id
+        )));
+    }
+    Ok(())
+}
+
+fn check_spendable(output: &MultisigOutput, session_id: u64) -> Result<(), MultisigError> {
+    if output.session_id != session_id {
+        return Err(MultisigError::InvalidSpend(format!(
+            "The output does not belong to multisig session {}",
+            session_id
+        )));
+    }
+    if output.spent {
+        return Err(MultisigError::InvalidSpend("The output is already spent".to_string()));
+    }
+    Ok(())
+}
+
+fn spend_destination(request: &MultisigSigningRequest) -> Result<&PublicKey, MultisigError> {
+    match request {
+        MultisigSigningRequest::Spend { destination, .. } => Ok(destination),
+        MultisigSigningRequest::Message(_) => Err(MultisigError::InvalidSpend("Not a spend request".to_string())),
+    }
+}
+
+/// `x·D`, combined from the signers' Diffie-Hellman shares with the destination of a spend
+fn group_dh(session: &MultisigSession, round: &MultisigSigningRound) -> Result<PublicKey, MultisigError> {
+    let shares = round
+        .signers
+        .iter()
+        .map(|signer| {
+            let share = round
+                .dh_share(signer)
+                .ok_or(MultisigError::SigningIncomplete(round.signing_id))?;
+            Ok((session.participant_index(signer)?, share.share.clone()))
+        })
+        .collect::<Result<Vec<_>, MultisigError>>()?;
+    crypto::combine_dh_shares(&shares)
+}
+
+/// The challenge of one of the signatures of a signing round, given the group nonce of the signers for it
+fn signature_challenge(
+    round: &MultisigSigningRound,
+    input: Option<&MultisigOutput>,
+    group_public_key: &PublicKey,
+    signature: usize,
+    group_nonce: &PublicKey,
+) -> Result<PrivateKey, MultisigError> {
+    let challenge = match (&round.request, input, &round.spend_template) {
+        (MultisigSigningRequest::Message(message), _, _) => {
+            crypto::signing_challenge(group_nonce, group_public_key, message)
+        },
+        (MultisigSigningRequest::Spend { .. }, Some(input), Some(template)) => {
+            spend::spend_challenge(template, input, group_public_key, signature, group_nonce)
+        },
+        _ => return Err(MultisigError::SigningIncomplete(round.signing_id)),
+    };
+    crypto::challenge_scalar(&challenge)
+}
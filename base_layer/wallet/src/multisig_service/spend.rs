@@ -0,0 +1,619 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Spending a multisig output.
+//!
+//! A multisig output is locked with a script that leaves the group key `X` on the stack, so the script signature of
+//! the input has to be made with `x`, the private key of the group. The value of the output is paid, minus the fee,
+//! to a destination `D` as a one-sided payment, which the recipient finds by its script and whose spending key the
+//! recipient derives as `d·K_O` from the sender offset public key `K_O`.
+//!
+//! The coordinator of the spend sets `K_O = X + r·G` for a random `r`, which makes the script offset of the
+//! transaction `-r` without anyone knowing `x`. The spending key of the output is `k = x·D + r·D`, where `x·D` is
+//! combined from the signers' Diffie-Hellman shares. The metadata signature of the output is then also made with `x`.
+//! Both signatures are commitment signatures whose nonce and `k`-part the coordinator completes with the group nonce and
+//! the threshold signature of the signers.
+
+use crate::{
+    multisig_service::{
+        crypto,
+        error::MultisigError,
+        models::{MultisigOutput, MultisigSigningRequest},
+    },
+    types::HashDigest,
+};
+use digest::Digest;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{ComSignature, Commitment, PrivateKey, PublicKey, Signature};
+use tari_core::transactions::{
+    fee::Fee,
+    tari_amount::MicroTari,
+    transaction::{
+        KernelBuilder,
+        OutputFeatures,
+        Transaction,
+        TransactionBuilder,
+        TransactionInput,
+        TransactionOutput,
+        UnblindedOutput,
+    },
+    transaction_protocol::{build_challenge, RewindData, TransactionMetadata},
+    CryptoFactories,
+};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+    script,
+    script::ExecutionStack,
+    tari_utilities::{ByteArray, Hashable},
+};
+
+/// The index of the input script signature among the signatures of a spend
+pub const SCRIPT_SIGNATURE: usize = 0;
+/// The index of the output metadata signature among the signatures of a spend
+pub const METADATA_SIGNATURE: usize = 1;
+
+/// The transaction of a spend as the coordinator built it. The signers check it against the spend request before they
+/// sign it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultisigSpendTemplate {
+    pub fee: MicroTari,
+    /// The output paying the destination, without its metadata signature
+    pub output: TransactionOutput,
+    /// The sender offset public key of the output is `X + r·G` for this `r`
+    pub sender_offset_tweak: PrivateKey,
+    /// The coordinator's commitment nonce of the input script signature
+    pub script_nonce: Commitment,
+    /// The coordinator's commitment nonce of the output metadata signature
+    pub metadata_nonce: Commitment,
+}
+
+impl MultisigSpendTemplate {
+    pub fn hash(&self) -> Vec<u8> {
+        HashDigest::new()
+            .chain(u64::from(self.fee).to_le_bytes())
+            .chain(self.output.hash())
+            .chain(self.output.sender_offset_public_key.as_bytes())
+            .chain(self.sender_offset_tweak.as_bytes())
+            .chain(self.script_nonce.as_bytes())
+            .chain(self.metadata_nonce.as_bytes())
+            .finalize()
+            .to_vec()
+    }
+}
+
+/// The coordinator's secret nonces of the commitment signatures of a spend, as (value nonce, blinding nonce) pairs.
+/// Like the signing nonces they are only ever kept in memory.
+pub struct SpendNonces {
+    script: (PrivateKey, PrivateKey),
+    metadata: (PrivateKey, PrivateKey),
+}
+
+impl SpendNonces {
+    fn random() -> Self {
+        Self {
+            script: (PrivateKey::random(&mut OsRng), PrivateKey::random(&mut OsRng)),
+            metadata: (PrivateKey::random(&mut OsRng), PrivateKey::random(&mut OsRng)),
+        }
+    }
+}
+
+/// The fee of a spend, which has one kernel, one input and one output
+pub fn spend_fee(fee_per_gram: MicroTari) -> MicroTari {
+    Fee::calculate(fee_per_gram, 1, 1, 1, 0)
+}
+
+/// Build the transaction of a spend. `group_dh` is `x·D`, combined from the signers' Diffie-Hellman shares.
+pub fn build_spend_template(
+    input: &MultisigOutput,
+    group_public_key: &PublicKey,
+    request: &MultisigSigningRequest,
+    group_dh: &PublicKey,
+    factories: &CryptoFactories,
+) -> Result<(MultisigSpendTemplate, SpendNonces), MultisigError> {
+    let (destination, fee_per_gram) = spend_request(request)?;
+    let fee = spend_fee(fee_per_gram);
+    let value = output_value(input, fee)?;
+    let sender_offset_tweak = PrivateKey::random(&mut OsRng);
+    let sender_offset_public_key = group_public_key + &PublicKey::from_secret_key(&sender_offset_tweak);
+    let spending_key = output_spending_key(group_dh, &sender_offset_tweak, destination)?;
+
+    let output = UnblindedOutput::new(
+        value,
+        spending_key.clone(),
+        OutputFeatures::default(),
+        script!(PushPubKey(Box::new(destination.clone()))),
+        ExecutionStack::default(),
+        PrivateKey::default(),
+        sender_offset_public_key,
+        Default::default(),
+    )
+    .as_rewindable_transaction_output(factories, &rewind_data(&spending_key)?)?;
+
+    let nonces = SpendNonces::random();
+    let template = MultisigSpendTemplate {
+        fee,
+        output,
+        sender_offset_tweak,
+        script_nonce: factories.commitment.commit(&nonces.script.1, &nonces.script.0),
+        metadata_nonce: factories.commitment.commit(&nonces.metadata.1, &nonces.metadata.0),
+    };
+    Ok((template, nonces))
+}
+
+/// Check that the coordinator's transaction pays the value of the input, minus the fee of the request, to the
+/// destination of the request, with an output that the destination can spend
+pub fn verify_spend_template(
+    template: &MultisigSpendTemplate,
+    input: &MultisigOutput,
+    group_public_key: &PublicKey,
+    request: &MultisigSigningRequest,
+    group_dh: &PublicKey,
+    factories: &CryptoFactories,
+) -> Result<(), MultisigError> {
+    let (destination, fee_per_gram) = spend_request(request)?;
+    if template.fee != spend_fee(fee_per_gram) {
+        return Err(MultisigError::InvalidSpend(format!("Unexpected fee {}", template.fee)));
+    }
+    let value = output_value(input, template.fee)?;
+    let output = &template.output;
+    if output.script != script!(PushPubKey(Box::new(destination.clone()))) ||
+        output.features != OutputFeatures::default()
+    {
+        return Err(MultisigError::InvalidSpend(
+            "The output is not a payment to the destination".to_string(),
+        ));
+    }
+    if output.sender_offset_public_key != group_public_key + &PublicKey::from_secret_key(&template.sender_offset_tweak)
+    {
+        return Err(MultisigError::InvalidSpend(
+            "Unexpected sender offset public key".to_string(),
+        ));
+    }
+    let spending_key = output_spending_key(group_dh, &template.sender_offset_tweak, destination)?;
+    if output.commitment != factories.commitment.commit_value(&spending_key, value.into()) ||
+        !output.verify_range_proof(&factories.range_proof)?
+    {
+        return Err(MultisigError::InvalidSpend(
+            "The destination can not spend the output".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The challenge of one of the signatures of a spend, given the group nonce of the signers for it
+pub fn spend_challenge(
+    template: &MultisigSpendTemplate,
+    input: &MultisigOutput,
+    group_public_key: &PublicKey,
+    signature: usize,
+    group_nonce: &PublicKey,
+) -> Vec<u8> {
+    if signature == SCRIPT_SIGNATURE {
+        TransactionInput::build_script_challenge(
+            &(&template.script_nonce + group_nonce),
+            &input.output.script,
+            &ExecutionStack::default(),
+            group_public_key,
+            &input.output.commitment,
+        )
+    } else {
+        TransactionOutput::build_metadata_signature_challenge(
+            &template.output.script,
+            &template.output.features,
+            &template.output.sender_offset_public_key,
+            &(&template.metadata_nonce + group_nonce),
+            &template.output.commitment,
+        )
+        .to_vec()
+    }
+}
+
+/// Complete the transaction of a spend. `group_nonces` and `signatures` are the group nonces and threshold signatures
+/// of the signers for the script and the metadata signature.
+#[allow(clippy::too_many_arguments)]
+pub fn finalize_spend(
+    template: &MultisigSpendTemplate,
+    nonces: SpendNonces,
+    input: &MultisigOutput,
+    group_public_key: &PublicKey,
+    request: &MultisigSigningRequest,
+    group_dh: &PublicKey,
+    group_nonces: &[PublicKey],
+    signatures: &[PrivateKey],
+    factories: &CryptoFactories,
+) -> Result<Transaction, MultisigError> {
+    let (destination, _) = spend_request(request)?;
+    if group_nonces.len() != 2 || signatures.len() != 2 {
+        return Err(MultisigError::InvalidSpend("A spend needs two signatures".to_string()));
+    }
+    let value = output_value(input, template.fee)?;
+    let spending_key = output_spending_key(group_dh, &template.sender_offset_tweak, destination)?;
+
+    // The commitment signatures are made for the keys this wallet knows, then completed with the group nonce and the
+    // threshold signature for `x`
+    let challenge = spend_challenge(
+        template,
+        input,
+        group_public_key,
+        SCRIPT_SIGNATURE,
+        &group_nonces[SCRIPT_SIGNATURE],
+    );
+    let script_signature = complete_commitment_signature(
+        ComSignature::sign(
+            PrivateKey::from(u64::from(input.value)),
+            input.spending_key.clone(),
+            nonces.script.0,
+            nonces.script.1,
+            &challenge,
+            &factories.commitment,
+        )
+        .map_err(|e| MultisigError::InvalidSpend(e.to_string()))?,
+        &group_nonces[SCRIPT_SIGNATURE],
+        &signatures[SCRIPT_SIGNATURE],
+    );
+    if !script_signature.verify_challenge(
+        &(&input.output.commitment + group_public_key),
+        &challenge,
+        &factories.commitment,
+    ) {
+        return Err(MultisigError::InvalidSignature);
+    }
+
+    let challenge = spend_challenge(
+        template,
+        input,
+        group_public_key,
+        METADATA_SIGNATURE,
+        &group_nonces[METADATA_SIGNATURE],
+    );
+    let metadata_signature = complete_commitment_signature(
+        ComSignature::sign(
+            PrivateKey::from(u64::from(value)),
+            &spending_key + &template.sender_offset_tweak,
+            nonces.metadata.0,
+            nonces.metadata.1,
+            &challenge,
+            &factories.commitment,
+        )
+        .map_err(|e| MultisigError::InvalidSpend(e.to_string()))?,
+        &group_nonces[METADATA_SIGNATURE],
+        &signatures[METADATA_SIGNATURE],
+    );
+    let mut output = template.output.clone();
+    output.metadata_signature = metadata_signature;
+    output.verify_metadata_signature()?;
+
+    let tx_input = TransactionInput::new(
+        input.output.features.clone(),
+        input.output.commitment.clone(),
+        input.output.script.clone(),
+        ExecutionStack::default(),
+        script_signature,
+        input.output.sender_offset_public_key.clone(),
+    );
+
+    // The kernel only involves the spending keys of the input and the output, which the coordinator knows
+    let offset = PrivateKey::random(&mut OsRng);
+    let excess = &(&spending_key - &input.spending_key) - &offset;
+    let kernel_nonce = PrivateKey::random(&mut OsRng);
+    let challenge = build_challenge(&PublicKey::from_secret_key(&kernel_nonce), &TransactionMetadata {
+        fee: template.fee,
+        ..Default::default()
+    });
+    let excess_signature = Signature::sign(excess.clone(), kernel_nonce, &challenge)
+        .map_err(|e| MultisigError::InvalidSpend(e.to_string()))?;
+    let kernel = KernelBuilder::new()
+        .with_fee(template.fee)
+        .with_lock_height(0)
+        .with_excess(&Commitment::from_public_key(&PublicKey::from_secret_key(&excess)))
+        .with_signature(&excess_signature)
+        .build()?;
+
+    let mut builder = TransactionBuilder::new();
+    builder
+        .add_input(tx_input)
+        .add_output(output)
+        .add_offset(offset)
+        .add_script_offset(&PrivateKey::default() - &template.sender_offset_tweak)
+        .with_kernel(kernel);
+    Ok(builder.build(factories)?)
+}
+
+/// Add the threshold signature of the signers to a commitment signature, the group nonce to its public nonce and the
+/// signature to its blinding factor part
+fn complete_commitment_signature(
+    signature: ComSignature,
+    group_nonce: &PublicKey,
+    threshold_signature: &PrivateKey,
+) -> ComSignature {
+    let (public_nonce, u, v) = signature.complete_signature_tuple();
+    ComSignature::new(public_nonce + group_nonce, u + threshold_signature, v.clone())
+}
+
+fn spend_request(request: &MultisigSigningRequest) -> Result<(&PublicKey, MicroTari), MultisigError> {
+    match request {
+        MultisigSigningRequest::Spend {
+            destination,
+            fee_per_gram,
+            ..
+        } => Ok((destination, *fee_per_gram)),
+        MultisigSigningRequest::Message(_) => Err(MultisigError::InvalidSpend("Not a spend request".to_string())),
+    }
+}
+
+fn output_value(input: &MultisigOutput, fee: MicroTari) -> Result<MicroTari, MultisigError> {
+    input
+        .value
+        .checked_sub(fee)
+        .filter(|v| *v > MicroTari(0))
+        .ok_or_else(|| {
+            MultisigError::InvalidSpend(format!(
+                "The output value {} does not cover the fee {}",
+                input.value, fee
+            ))
+        })
+}
+
+/// The spending key of the output, which the recipient derives as `d·(X + r·G)`
+fn output_spending_key(
+    group_dh: &PublicKey,
+    sender_offset_tweak: &PrivateKey,
+    destination: &PublicKey,
+) -> Result<PrivateKey, MultisigError> {
+    PrivateKey::from_bytes((group_dh + &(sender_offset_tweak * destination)).as_bytes())
+        .map_err(|e| MultisigError::ConversionError(e.to_string()))
+}
+
+/// The rewind keys of the output, derived from its spending key in the same way as for any one-sided payment
+fn rewind_data(spending_key: &PrivateKey) -> Result<RewindData, MultisigError> {
+    let rewind_key = PrivateKey::from_bytes(&hash_secret_key(spending_key))
+        .map_err(|e| MultisigError::ConversionError(e.to_string()))?;
+    let rewind_blinding_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_key))
+        .map_err(|e| MultisigError::ConversionError(e.to_string()))?;
+    Ok(RewindData {
+        rewind_key,
+        rewind_blinding_key,
+        proof_message: [0u8; 21],
+    })
+}
+
+fn hash_secret_key(key: &PrivateKey) -> Vec<u8> {
+    HashDigest::new().chain(key.as_bytes()).finalize().to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::multisig_service::{
+        crypto::{
+            binding_factor,
+            challenge_scalar,
+            combine_dh_shares,
+            evaluate_polynomial,
+            group_nonce,
+            lagrange_coefficient,
+            multisig_script,
+            partial_sign,
+            polynomial_commitments,
+            verify_partial_signature,
+            DhShare,
+            MultisigKey,
+            SigningNonces,
+        },
+        models::{MultisigDealing, MultisigSession, MultisigSigningRound},
+    };
+    use chrono::Utc;
+    use tari_crypto::keys::DiffieHellmanSharedSecret;
+
+    /// Run the key generation of a session between `n` wallets, returning every wallet's key and view of the session
+    fn key_generation(n: usize, threshold: usize) -> Vec<(PublicKey, MultisigKey, MultisigSession)> {
+        let wallets = (0..n)
+            .map(|_| {
+                (
+                    PublicKey::random_keypair(&mut OsRng).1,
+                    MultisigKey::from_master_key(PrivateKey::random(&mut OsRng)).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let session = MultisigSession::new(
+            1,
+            threshold,
+            wallets.iter().map(|(p, _)| p.clone()).collect(),
+            Utc::now().naive_utc(),
+        )
+        .unwrap();
+        wallets
+            .iter()
+            .map(|(receiver, receiver_key)| {
+                let mut session = session.clone();
+                let index = session.participant_index(receiver).unwrap();
+                for (dealer, dealer_key) in &wallets {
+                    let polynomial = dealer_key.polynomial(1, threshold).unwrap();
+                    let share = evaluate_polynomial(&polynomial, index);
+                    session
+                        .add_dealing(MultisigDealing {
+                            dealer: dealer.clone(),
+                            commitments: polynomial_commitments(&polynomial),
+                            sealed_share: receiver_key.seal_share(1, dealer, &share).unwrap(),
+                        })
+                        .unwrap();
+                }
+                (receiver.clone(), receiver_key.clone(), session)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_spends_a_multisig_output_to_a_one_sided_recipient() {
+        let factories = CryptoFactories::default();
+        let wallets = key_generation(3, 2);
+        let session = &wallets[0].2;
+        let group_public_key = session.group_public_key().unwrap();
+        for (_, _, s) in &wallets {
+            assert_eq!(s.group_public_key().unwrap(), group_public_key);
+        }
+
+        // Fund the group key
+        let value = MicroTari(100_000);
+        let funding_key = PrivateKey::random(&mut OsRng);
+        let input = MultisigOutput {
+            session_id: 1,
+            output: UnblindedOutput::new(
+                value,
+                funding_key.clone(),
+                OutputFeatures::default(),
+                multisig_script(&group_public_key),
+                ExecutionStack::default(),
+                PrivateKey::default(),
+                PublicKey::random_keypair(&mut OsRng).1,
+                Default::default(),
+            )
+            .as_transaction_output(&factories)
+            .unwrap(),
+            value,
+            spending_key: funding_key,
+            spent: false,
+        };
+
+        // Any two of the three participants spend it, the first one coordinating
+        let signers = [&wallets[0], &wallets[2]];
+        let (destination_key, destination) = PublicKey::random_keypair(&mut OsRng);
+        let request = MultisigSigningRequest::Spend {
+            commitment: input.output.commitment.clone(),
+            destination: destination.clone(),
+            fee_per_gram: MicroTari(5),
+            coordinator: wallets[0].0.clone(),
+        };
+        let mut round = MultisigSigningRound::new(
+            1,
+            1,
+            request.clone(),
+            signers.iter().map(|(p, _, _)| p.clone()).collect(),
+        );
+
+        let mut dh_shares = Vec::new();
+        let mut signing_nonces = Vec::new();
+        for (public_key, key, session) in &signers {
+            let dh_share = DhShare::new(&session.secret_share(key).unwrap(), &destination).unwrap();
+            assert!(dh_share
+                .verify(&session.verification_share(public_key).unwrap(), &destination)
+                .unwrap());
+            dh_shares.push((session.participant_index(public_key).unwrap(), dh_share.share));
+            let nonces = vec![SigningNonces::random(), SigningNonces::random()];
+            round
+                .add_nonce_commitments(public_key.clone(), nonces.iter().map(|n| n.commitment()).collect())
+                .unwrap();
+            signing_nonces.push(nonces);
+        }
+        let group_dh = combine_dh_shares(&dh_shares).unwrap();
+
+        let (template, spend_nonces) =
+            build_spend_template(&input, &group_public_key, &request, &group_dh, &factories).unwrap();
+        verify_spend_template(&template, &input, &group_public_key, &request, &group_dh, &factories).unwrap();
+        let other_destination = MultisigSigningRequest::Spend {
+            commitment: input.output.commitment.clone(),
+            destination: PublicKey::random_keypair(&mut OsRng).1,
+            fee_per_gram: MicroTari(5),
+            coordinator: wallets[0].0.clone(),
+        };
+        assert!(verify_spend_template(
+            &template,
+            &input,
+            &group_public_key,
+            &other_destination,
+            &group_dh,
+            &factories
+        )
+        .is_err());
+        round.spend_template = Some(template.clone());
+
+        let indexes = round.signer_indexes(session).unwrap();
+        let mut group_nonces = Vec::new();
+        let mut signatures = Vec::new();
+        for signature in 0..2 {
+            let commitments = round.signature_nonce_commitments(signature);
+            let context = round.binding_context(signature);
+            let nonce = group_nonce(&group_public_key, &commitments, &context).unwrap();
+            let challenge = challenge_scalar(&spend_challenge(
+                &template,
+                &input,
+                &group_public_key,
+                signature,
+                &nonce,
+            ))
+            .unwrap();
+            let mut sum = PrivateKey::default();
+            for (i, (public_key, key, session)) in signers.iter().enumerate() {
+                let rho = binding_factor(public_key, &group_public_key, &commitments, &context).unwrap();
+                let lambda = lagrange_coefficient(indexes[i], &indexes).unwrap();
+                let z = partial_sign(
+                    &signing_nonces[i][signature],
+                    &rho,
+                    &lambda,
+                    &session.secret_share(key).unwrap(),
+                    &challenge,
+                );
+                assert!(verify_partial_signature(
+                    &z,
+                    &round.nonce_commitments(public_key).unwrap()[signature],
+                    &rho,
+                    &lambda,
+                    &session.verification_share(public_key).unwrap(),
+                    &challenge
+                ));
+                sum = &sum + &z;
+            }
+            group_nonces.push(nonce);
+            signatures.push(sum);
+        }
+
+        let tx = finalize_spend(
+            &template,
+            spend_nonces,
+            &input,
+            &group_public_key,
+            &request,
+            &group_dh,
+            &group_nonces,
+            &signatures,
+            &factories,
+        )
+        .unwrap();
+        assert_eq!(tx.body.inputs().len(), 1);
+        assert_eq!(tx.body.outputs().len(), 1);
+
+        // The recipient finds the output in the same way as any one-sided payment
+        let output = &tx.body.outputs()[0];
+        let spending_key = PrivateKey::from_bytes(
+            PublicKey::shared_secret(&destination_key, &output.sender_offset_public_key).as_bytes(),
+        )
+        .unwrap();
+        let rewind = rewind_data(&spending_key).unwrap();
+        let rewound = output
+            .full_rewind_range_proof(&factories.range_proof, &rewind.rewind_key, &rewind.rewind_blinding_key)
+            .unwrap();
+        assert_eq!(rewound.committed_value, value - template.fee);
+        assert_eq!(rewound.blinding_factor, spending_key);
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::multisig_service::{
+    error::MultisigStorageError,
+    models::{MultisigOutput, MultisigSession, MultisigSigningRound},
+};
+use aes_gcm::Aes256Gcm;
+use log::*;
+use std::{
+    fmt::{Display, Error, Formatter},
+    sync::Arc,
+};
+use tari_common_types::types::Commitment;
+use tari_crypto::tari_utilities::hex::Hex;
+
+const LOG_TARGET: &str = "wallet::multisig_service::database";
+
+/// This trait defines the functionality that a database backend need to provide for the Multisig Service
+pub trait MultisigBackend: Send + Sync + Clone {
+    /// Retrieve the record associated with the provided DbKey
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, MultisigStorageError>;
+    /// Modify the state the of the backend with a write operation
+    fn write(&self, op: WriteOperation) -> Result<Option<DbValue>, MultisigStorageError>;
+    /// Apply encryption to the backend.
+    fn apply_encryption(&self, cipher: Aes256Gcm) -> Result<(), MultisigStorageError>;
+    /// Remove encryption from the backend.
+    fn remove_encryption(&self) -> Result<(), MultisigStorageError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DbKey {
+    Session(u64),
+    Sessions,
+    SigningRound(u64),
+    SigningRounds,
+    Output(Commitment),
+    Outputs,
+}
+
+pub enum DbValue {
+    Session(Box<MultisigSession>),
+    Sessions(Vec<MultisigSession>),
+    SigningRound(Box<MultisigSigningRound>),
+    SigningRounds(Vec<MultisigSigningRound>),
+    Output(Box<MultisigOutput>),
+    Outputs(Vec<MultisigOutput>),
+}
+
+pub enum DbKeyValuePair {
+    Session(u64, Box<MultisigSession>),
+    SigningRound(u64, Box<MultisigSigningRound>),
+    Output(Commitment, Box<MultisigOutput>),
+}
+
+pub enum WriteOperation {
+    Upsert(DbKeyValuePair),
+    Remove(DbKey),
+}
+
+// Private macro that pulls out all the boiler plate of extracting a DB query result from its variants
+macro_rules! fetch {
+    ($db:ident, $key_val:expr, $key_var:ident) => {{
+        let key = DbKey::$key_var($key_val);
+        match $db.fetch(&key) {
+            Ok(None) => Err(MultisigStorageError::ValueNotFound(key)),
+            Ok(Some(DbValue::$key_var(k))) => Ok(*k),
+            Ok(Some(other)) => unexpected_result(key, other),
+            Err(e) => log_error(key, e),
+        }
+    }};
+}
+
+pub struct MultisigDatabase<T>
+where T: MultisigBackend
+{
+    db: Arc<T>,
+}
+
+impl<T> MultisigDatabase<T>
+where T: MultisigBackend + 'static
+{
+    pub fn new(db: T) -> Self {
+        Self { db: Arc::new(db) }
+    }
+
+    pub async fn get_session(&self, session_id: u64) -> Result<MultisigSession, MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || fetch!(db_clone, session_id, Session))
+            .await
+            .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn get_sessions(&self) -> Result<Vec<MultisigSession>, MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::Sessions) {
+            Ok(None) => Ok(Vec::new()),
+            Ok(Some(DbValue::Sessions(s))) => Ok(s),
+            Ok(Some(other)) => unexpected_result(DbKey::Sessions, other),
+            Err(e) => log_error(DbKey::Sessions, e),
+        })
+        .await
+        .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn upsert_session(&self, session: MultisigSession) -> Result<(), MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Upsert(DbKeyValuePair::Session(
+                session.session_id,
+                Box::new(session),
+            )))
+        })
+        .await
+        .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn get_signing_round(&self, signing_id: u64) -> Result<MultisigSigningRound, MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || fetch!(db_clone, signing_id, SigningRound))
+            .await
+            .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn get_signing_rounds(&self) -> Result<Vec<MultisigSigningRound>, MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::SigningRounds) {
+            Ok(None) => Ok(Vec::new()),
+            Ok(Some(DbValue::SigningRounds(s))) => Ok(s),
+            Ok(Some(other)) => unexpected_result(DbKey::SigningRounds, other),
+            Err(e) => log_error(DbKey::SigningRounds, e),
+        })
+        .await
+        .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn upsert_signing_round(&self, signing_round: MultisigSigningRound) -> Result<(), MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Upsert(DbKeyValuePair::SigningRound(
+                signing_round.signing_id,
+                Box::new(signing_round),
+            )))
+        })
+        .await
+        .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn get_output(&self, commitment: Commitment) -> Result<MultisigOutput, MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || fetch!(db_clone, commitment, Output))
+            .await
+            .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn get_outputs(&self) -> Result<Vec<MultisigOutput>, MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::Outputs) {
+            Ok(None) => Ok(Vec::new()),
+            Ok(Some(DbValue::Outputs(o))) => Ok(o),
+            Ok(Some(other)) => unexpected_result(DbKey::Outputs, other),
+            Err(e) => log_error(DbKey::Outputs, e),
+        })
+        .await
+        .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn upsert_output(&self, output: MultisigOutput) -> Result<(), MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Upsert(DbKeyValuePair::Output(
+                output.output.commitment.clone(),
+                Box::new(output),
+            )))
+        })
+        .await
+        .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn apply_encryption(&self, cipher: Aes256Gcm) -> Result<(), MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.apply_encryption(cipher))
+            .await
+            .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn remove_encryption(&self) -> Result<(), MultisigStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.remove_encryption())
+            .await
+            .map_err(|err| MultisigStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+}
+
+fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, MultisigStorageError> {
+    let msg = format!("Unexpected result for database query {}. Response: {}", req, res);
+    error!(target: LOG_TARGET, "{}", msg);
+    Err(MultisigStorageError::UnexpectedResult(msg))
+}
+
+impl Display for DbKey {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            DbKey::Session(id) => f.write_str(&format!("Multisig session: {}", id)),
+            DbKey::Sessions => f.write_str("Multisig sessions"),
+            DbKey::SigningRound(id) => f.write_str(&format!("Multisig signing round: {}", id)),
+            DbKey::SigningRounds => f.write_str("Multisig signing rounds"),
+            DbKey::Output(c) => f.write_str(&format!("Multisig output: {}", c.to_hex())),
+            DbKey::Outputs => f.write_str("Multisig outputs"),
+        }
+    }
+}
+
+impl Display for DbValue {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            DbValue::Session(_) => f.write_str("Multisig session"),
+            DbValue::Sessions(_) => f.write_str("Multisig sessions"),
+            DbValue::SigningRound(_) => f.write_str("Multisig signing round"),
+            DbValue::SigningRounds(_) => f.write_str("Multisig signing rounds"),
+            DbValue::Output(_) => f.write_str("Multisig output"),
+            DbValue::Outputs(_) => f.write_str("Multisig outputs"),
+        }
+    }
+}
+
+fn log_error<T>(req: DbKey, err: MultisigStorageError) -> Result<T, MultisigStorageError> {
+    error!(
+        target: LOG_TARGET,
+        "Database access error on request: {}: {}",
+        req,
+        err.to_string()
+    );
+    Err(err)
+}
@@ -0,0 +1,726 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    multisig_service::{
+        error::MultisigStorageError,
+        models::{MultisigDealing, MultisigOutput, MultisigSession, MultisigSigningRound},
+        storage::database::{DbKey, DbKeyValuePair, DbValue, MultisigBackend, WriteOperation},
+    },
+    schema::{multisig_outputs, multisig_sessions, multisig_signing_rounds},
+    storage::sqlite_utilities::WalletDbConnection,
+    util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
+};
+use aes_gcm::{Aes256Gcm, Error as AeadError};
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, result::Error as DieselError, SqliteConnection};
+use log::*;
+use std::{
+    convert::TryFrom,
+    str::from_utf8,
+    sync::{Arc, RwLock},
+};
+use tari_common_types::types::{Commitment, PublicKey};
+use tari_crypto::tari_utilities::{
+    hex::{from_hex, Hex},
+    ByteArray,
+};
+
+const LOG_TARGET: &str = "wallet::multisig_service::database::sqlite_db";
+
+const PUBLIC_KEY_LENGTH: usize = 32;
+
+/// A Sqlite backend for the Multisig Service. The Backend is accessed via a connection pool to the Sqlite file.
+#[derive(Clone)]
+pub struct MultisigServiceSqliteDatabase {
+    database_connection: WalletDbConnection,
+    cipher: Arc<RwLock<Option<Aes256Gcm>>>,
+}
+
+impl MultisigServiceSqliteDatabase {
+    pub fn new(database_connection: WalletDbConnection, cipher: Option<Aes256Gcm>) -> Self {
+        Self {
+            database_connection,
+            cipher: Arc::new(RwLock::new(cipher)),
+        }
+    }
+
+    /// The cipher of this backend, which is shared by all of its clones
+    pub(crate) fn cipher(&self) -> Arc<RwLock<Option<Aes256Gcm>>> {
+        self.cipher.clone()
+    }
+
+    fn encrypt_if_necessary<T: Encryptable<Aes256Gcm>>(&self, o: &mut T) -> Result<(), MultisigStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if let Some(cipher) = cipher.as_ref() {
+            o.encrypt(cipher)
+                .map_err(|_| MultisigStorageError::AeadError("Encryption Error".to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn decrypt_if_necessary<T: Encryptable<Aes256Gcm>>(&self, o: &mut T) -> Result<(), MultisigStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if let Some(cipher) = cipher.as_ref() {
+            o.decrypt(cipher)
+                .map_err(|_| MultisigStorageError::AeadError("Decryption Error".to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+impl MultisigBackend for MultisigServiceSqliteDatabase {
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, MultisigStorageError> {
+        let conn = self.database_connection.acquire_lock();
+
+        let result = match key {
+            DbKey::Session(id) => match MultisigSessionSql::find(*id, &(*conn)) {
+                Ok(mut s) => {
+                    self.decrypt_if_necessary(&mut s)?;
+                    Some(DbValue::Session(Box::new(MultisigSession::try_from(s)?)))
+                },
+                Err(MultisigStorageError::DieselError(DieselError::NotFound)) => None,
+                Err(e) => return Err(e),
+            },
+            DbKey::Sessions => Some(DbValue::Sessions(
+                MultisigSessionSql::index(&conn)?
+                    .into_iter()
+                    .map(|mut s| {
+                        self.decrypt_if_necessary(&mut s)?;
+                        MultisigSession::try_from(s)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            DbKey::SigningRound(id) => match MultisigSigningRoundSql::find(*id, &(*conn)) {
+                Ok(s) => Some(DbValue::SigningRound(Box::new(MultisigSigningRound::try_from(s)?))),
+                Err(MultisigStorageError::DieselError(DieselError::NotFound)) => None,
+                Err(e) => return Err(e),
+            },
+            DbKey::SigningRounds => Some(DbValue::SigningRounds(
+                MultisigSigningRoundSql::index(&conn)?
+                    .into_iter()
+                    .map(MultisigSigningRound::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            DbKey::Output(commitment) => match MultisigOutputSql::find(commitment, &(*conn)) {
+                Ok(mut o) => {
+                    self.decrypt_if_necessary(&mut o)?;
+                    Some(DbValue::Output(Box::new(MultisigOutput::try_from(o)?)))
+                },
+                Err(MultisigStorageError::DieselError(DieselError::NotFound)) => None,
+                Err(e) => return Err(e),
+            },
+            DbKey::Outputs => Some(DbValue::Outputs(
+                MultisigOutputSql::index(&conn)?
+                    .into_iter()
+                    .map(|mut o| {
+                        self.decrypt_if_necessary(&mut o)?;
+                        MultisigOutput::try_from(o)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+        };
+
+        Ok(result)
+    }
+
+    fn write(&self, op: WriteOperation) -> Result<Option<DbValue>, MultisigStorageError> {
+        let conn = self.database_connection.acquire_lock();
+
+        match op {
+            WriteOperation::Upsert(kvp) => match kvp {
+                DbKeyValuePair::Session(id, s) => {
+                    let mut session = MultisigSessionSql::try_from(*s)?;
+                    self.encrypt_if_necessary(&mut session)?;
+                    match MultisigSessionSql::find(id, &(*conn)) {
+                        Ok(_) => session.update(&conn)?,
+                        Err(_) => session.commit(&conn)?,
+                    }
+                },
+                DbKeyValuePair::SigningRound(id, s) => {
+                    let round = MultisigSigningRoundSql::try_from(*s)?;
+                    match MultisigSigningRoundSql::find(id, &(*conn)) {
+                        Ok(_) => round.update(&conn)?,
+                        Err(_) => round.commit(&conn)?,
+                    }
+                },
+                DbKeyValuePair::Output(commitment, o) => {
+                    let mut output = MultisigOutputSql::try_from(*o)?;
+                    self.encrypt_if_necessary(&mut output)?;
+                    match MultisigOutputSql::find(&commitment, &(*conn)) {
+                        Ok(_) => output.update(&conn)?,
+                        Err(_) => output.commit(&conn)?,
+                    }
+                },
+            },
+            WriteOperation::Remove(k) => match k {
+                DbKey::SigningRound(id) => match MultisigSigningRoundSql::find(id, &(*conn)) {
+                    Ok(s) => {
+                        s.delete(&conn)?;
+                        return Ok(Some(DbValue::SigningRound(Box::new(MultisigSigningRound::try_from(
+                            s,
+                        )?))));
+                    },
+                    Err(MultisigStorageError::DieselError(DieselError::NotFound)) => (),
+                    Err(e) => return Err(e),
+                },
+                DbKey::Session(_) | DbKey::Sessions | DbKey::SigningRounds | DbKey::Output(_) | DbKey::Outputs => {
+                    return Err(MultisigStorageError::OperationNotSupported)
+                },
+            },
+        }
+
+        Ok(None)
+    }
+
+    fn apply_encryption(&self, cipher: Aes256Gcm) -> Result<(), MultisigStorageError> {
+        let mut current_cipher = acquire_write_lock!(self.cipher);
+
+        if (*current_cipher).is_some() {
+            return Err(MultisigStorageError::AlreadyEncrypted);
+        }
+
+        let conn = self.database_connection.acquire_lock();
+
+        let mut sessions = MultisigSessionSql::index(&conn)?;
+        for s in sessions.iter_mut() {
+            // Test if this session is encrypted or not to avoid a double encryption.
+            let _ = MultisigSession::try_from(s.clone()).map_err(|_| {
+                error!(
+                    target: LOG_TARGET,
+                    "Could not convert Multisig Session from database version, it might already be encrypted"
+                );
+                MultisigStorageError::AlreadyEncrypted
+            })?;
+            s.encrypt(&cipher)
+                .map_err(|_| MultisigStorageError::AeadError("Encryption Error".to_string()))?;
+            s.update(&conn)?;
+        }
+
+        let mut outputs = MultisigOutputSql::index(&conn)?;
+        for o in outputs.iter_mut() {
+            // Test if this output is encrypted or not to avoid a double encryption.
+            let _ = MultisigOutput::try_from(o.clone()).map_err(|_| {
+                error!(
+                    target: LOG_TARGET,
+                    "Could not convert Multisig Output from database version, it might already be encrypted"
+                );
+                MultisigStorageError::AlreadyEncrypted
+            })?;
+            o.encrypt(&cipher)
+                .map_err(|_| MultisigStorageError::AeadError("Encryption Error".to_string()))?;
+            o.update(&conn)?;
+        }
+
+        (*current_cipher) = Some(cipher);
+
+        Ok(())
+    }
+
+    fn remove_encryption(&self) -> Result<(), MultisigStorageError> {
+        let mut current_cipher = acquire_write_lock!(self.cipher);
+        let cipher = if let Some(cipher) = (*current_cipher).clone().take() {
+            cipher
+        } else {
+            return Ok(());
+        };
+        let conn = self.database_connection.acquire_lock();
+
+        let mut sessions = MultisigSessionSql::index(&conn)?;
+        for s in sessions.iter_mut() {
+            s.decrypt(&cipher)
+                .map_err(|_| MultisigStorageError::AeadError("Decryption Error".to_string()))?;
+            s.update(&conn)?;
+        }
+
+        let mut outputs = MultisigOutputSql::index(&conn)?;
+        for o in outputs.iter_mut() {
+            o.decrypt(&cipher)
+                .map_err(|_| MultisigStorageError::AeadError("Decryption Error".to_string()))?;
+            o.update(&conn)?;
+        }
+
+        // Now that all the decryption has been completed we can safely remove the cipher fully
+        let _ = (*current_cipher).take();
+
+        Ok(())
+    }
+}
+
+/// Re-encrypt the multisig sessions and outputs with a new cipher, returning the number of rows that were
+/// re-encrypted. The caller is expected to hold a database transaction on the connection.
+pub(crate) fn rotate_encryption(
+    conn: &SqliteConnection,
+    current_cipher: &Aes256Gcm,
+    new_cipher: &Aes256Gcm,
+) -> Result<usize, MultisigStorageError> {
+    let mut rows = 0;
+    let mut sessions = MultisigSessionSql::index(conn)?;
+    for s in sessions.iter_mut() {
+        s.decrypt(current_cipher)
+            .map_err(|_| MultisigStorageError::AeadError("Decryption Error".to_string()))?;
+        s.encrypt(new_cipher)
+            .map_err(|_| MultisigStorageError::AeadError("Encryption Error".to_string()))?;
+        s.update(conn)?;
+        rows += 1;
+    }
+
+    let mut outputs = MultisigOutputSql::index(conn)?;
+    for o in outputs.iter_mut() {
+        o.decrypt(current_cipher)
+            .map_err(|_| MultisigStorageError::AeadError("Decryption Error".to_string()))?;
+        o.encrypt(new_cipher)
+            .map_err(|_| MultisigStorageError::AeadError("Encryption Error".to_string()))?;
+        o.update(conn)?;
+        rows += 1;
+    }
+
+    Ok(rows)
+}
+
+/// A Sql version of the MultisigSession struct, the dealings are stored as JSON
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "multisig_sessions"]
+struct MultisigSessionSql {
+    session_id: i64,
+    threshold: i32,
+    participants: Vec<u8>,
+    timestamp: NaiveDateTime,
+    dealings: String,
+    accepted: bool,
+}
+
+impl MultisigSessionSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), MultisigStorageError> {
+        diesel::insert_into(multisig_sessions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Return all multisig sessions
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<MultisigSessionSql>, MultisigStorageError> {
+        Ok(multisig_sessions::table.load::<MultisigSessionSql>(conn)?)
+    }
+
+    /// Find a particular multisig session, if it exists
+    pub fn find(session_id: u64, conn: &SqliteConnection) -> Result<MultisigSessionSql, MultisigStorageError> {
+        Ok(multisig_sessions::table
+            .filter(multisig_sessions::session_id.eq(session_id as i64))
+            .first::<MultisigSessionSql>(conn)?)
+    }
+
+    /// The participants of a session never change, only its dealings and whether it was accepted are updated
+    pub fn update(&self, conn: &SqliteConnection) -> Result<(), MultisigStorageError> {
+        let num_updated =
+            diesel::update(multisig_sessions::table.filter(multisig_sessions::session_id.eq(&self.session_id)))
+                .set((
+                    multisig_sessions::dealings.eq(&self.dealings),
+                    multisig_sessions::accepted.eq(self.accepted),
+                ))
+                .execute(conn)?;
+
+        if num_updated == 0 {
+            return Err(MultisigStorageError::UnexpectedResult(
+                "Database update error".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Encryptable<Aes256Gcm> for MultisigSessionSql {
+    fn encrypt(&mut self, cipher: &Aes256Gcm) -> Result<(), AeadError> {
+        let encrypted_dealings = encrypt_bytes_integral_nonce(cipher, self.dealings.as_bytes().to_vec())?;
+        self.dealings = encrypted_dealings.to_hex();
+        Ok(())
+    }
+
+    fn decrypt(&mut self, cipher: &Aes256Gcm) -> Result<(), AeadError> {
+        let decrypted_dealings =
+            decrypt_bytes_integral_nonce(cipher, from_hex(self.dealings.as_str()).map_err(|_| aes_gcm::Error)?)?;
+        self.dealings = from_utf8(decrypted_dealings.as_slice())
+            .map_err(|_| aes_gcm::Error)?
+            .to_string();
+        Ok(())
+    }
+}
+
+impl TryFrom<MultisigSession> for MultisigSessionSql {
+    type Error = MultisigStorageError;
+
+    fn try_from(s: MultisigSession) -> Result<Self, Self::Error> {
+        Ok(Self {
+            session_id: s.session_id as i64,
+            threshold: s.threshold as i32,
+            participants: s.participants.iter().flat_map(|p| p.to_vec()).collect(),
+            timestamp: s.timestamp,
+            dealings: serde_json::to_string(&s.dealings)?,
+            accepted: s.accepted,
+        })
+    }
+}
+
+impl TryFrom<MultisigSessionSql> for MultisigSession {
+    type Error = MultisigStorageError;
+
+    fn try_from(s: MultisigSessionSql) -> Result<Self, Self::Error> {
+        let participants = s
+            .participants
+            .chunks(PUBLIC_KEY_LENGTH)
+            .map(PublicKey::from_bytes)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| MultisigStorageError::ConversionError(e.to_string()))?;
+        let mut session = MultisigSession::new(s.session_id as u64, s.threshold as usize, participants, s.timestamp)
+            .map_err(|e| MultisigStorageError::ConversionError(e.to_string()))?;
+        session.accepted = s.accepted;
+        for dealing in serde_json::from_str::<Vec<MultisigDealing>>(&s.dealings)? {
+            session
+                .add_dealing(dealing)
+                .map_err(|e| MultisigStorageError::ConversionError(e.to_string()))?;
+        }
+        Ok(session)
+    }
+}
+
+/// A Sql version of the MultisigSigningRound struct, the round itself is stored as JSON
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "multisig_signing_rounds"]
+struct MultisigSigningRoundSql {
+    signing_id: i64,
+    session_id: i64,
+    signing_round: String,
+}
+
+impl MultisigSigningRoundSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), MultisigStorageError> {
+        diesel::insert_into(multisig_signing_rounds::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Return all multisig signing rounds
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<MultisigSigningRoundSql>, MultisigStorageError> {
+        Ok(multisig_signing_rounds::table.load::<MultisigSigningRoundSql>(conn)?)
+    }
+
+    /// Find a particular multisig signing round, if it exists
+    pub fn find(signing_id: u64, conn: &SqliteConnection) -> Result<MultisigSigningRoundSql, MultisigStorageError> {
+        Ok(multisig_signing_rounds::table
+            .filter(multisig_signing_rounds::signing_id.eq(signing_id as i64))
+            .first::<MultisigSigningRoundSql>(conn)?)
+    }
+
+    pub fn update(&self, conn: &SqliteConnection) -> Result<(), MultisigStorageError> {
+        let num_updated = diesel::update(
+            multisig_signing_rounds::table.filter(multisig_signing_rounds::signing_id.eq(&self.signing_id)),
+        )
+        .set(multisig_signing_rounds::signing_round.eq(&self.signing_round))
+        .execute(conn)?;
+
+        if num_updated == 0 {
+            return Err(MultisigStorageError::UnexpectedResult(
+                "Database update error".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn delete(&self, conn: &SqliteConnection) -> Result<(), MultisigStorageError> {
+        diesel::delete(multisig_signing_rounds::table.filter(multisig_signing_rounds::signing_id.eq(&self.signing_id)))
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+impl TryFrom<MultisigSigningRound> for MultisigSigningRoundSql {
+    type Error = MultisigStorageError;
+
+    fn try_from(s: MultisigSigningRound) -> Result<Self, Self::Error> {
+        Ok(Self {
+            signing_id: s.signing_id as i64,
+            session_id: s.session_id as i64,
+            signing_round: serde_json::to_string(&s)?,
+        })
+    }
+}
+
+impl TryFrom<MultisigSigningRoundSql> for MultisigSigningRound {
+    type Error = MultisigStorageError;
+
+    fn try_from(s: MultisigSigningRoundSql) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(&s.signing_round)?)
+    }
+}
+
+/// A Sql version of the MultisigOutput struct, the output itself is stored as JSON
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "multisig_outputs"]
+struct MultisigOutputSql {
+    commitment: Vec<u8>,
+    session_id: i64,
+    multisig_output: String,
+}
+
+impl MultisigOutputSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), MultisigStorageError> {
+        diesel::insert_into(multisig_outputs::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Return all multisig outputs
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<MultisigOutputSql>, MultisigStorageError> {
+        Ok(multisig_outputs::table.load::<MultisigOutputSql>(conn)?)
+    }
+
+    /// Find a particular multisig output, if it exists
+    pub fn find(commitment: &Commitment, conn: &SqliteConnection) -> Result<MultisigOutputSql, MultisigStorageError> {
+        Ok(multisig_outputs::table
+            .filter(multisig_outputs::commitment.eq(commitment.to_vec()))
+            .first::<MultisigOutputSql>(conn)?)
+    }
+
+    pub fn update(&self, conn: &SqliteConnection) -> Result<(), MultisigStorageError> {
+        let num_updated =
+            diesel::update(multisig_outputs::table.filter(multisig_outputs::commitment.eq(&self.commitment)))
+                .set(multisig_outputs::multisig_output.eq(&self.multisig_output))
+                .execute(conn)?;
+
+        if num_updated == 0 {
+            return Err(MultisigStorageError::UnexpectedResult(
+                "Database update error".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Encryptable<Aes256Gcm> for MultisigOutputSql {
+    fn encrypt(&mut self, cipher: &Aes256Gcm) -> Result<(), AeadError> {
+        let encrypted_output = encrypt_bytes_integral_nonce(cipher, self.multisig_output.as_bytes().to_vec())?;
+        self.multisig_output = encrypted_output.to_hex();
+        Ok(())
+    }
+
+    fn decrypt(&mut self, cipher: &Aes256Gcm) -> Result<(), AeadError> {
+        let decrypted_output = decrypt_bytes_integral_nonce(
+            cipher,
+            from_hex(self.multisig_output.as_str()).map_err(|_| aes_gcm::Error)?,
+        )?;
+        self.multisig_output = from_utf8(decrypted_output.as_slice())
+            .map_err(|_| aes_gcm::Error)?
+            .to_string();
+        Ok(())
+    }
+}
+
+impl TryFrom<MultisigOutput> for MultisigOutputSql {
+    type Error = MultisigStorageError;
+
+    fn try_from(o: MultisigOutput) -> Result<Self, Self::Error> {
+        Ok(Self {
+            commitment: o.output.commitment.to_vec(),
+            session_id: o.session_id as i64,
+            multisig_output: serde_json::to_string(&o)?,
+        })
+    }
+}
+
+impl TryFrom<MultisigOutputSql> for MultisigOutput {
+    type Error = MultisigStorageError;
+
+    fn try_from(o: MultisigOutputSql) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(&o.multisig_output)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::multisig_service::{
+        crypto::{polynomial_commitments, MultisigKey, NonceCommitment},
+        error::MultisigStorageError,
+        models::{MultisigDealing, MultisigOutput, MultisigSession, MultisigSigningRequest, MultisigSigningRound},
+        storage::{
+            database::MultisigDatabase,
+            sqlite_db::{MultisigOutputSql, MultisigServiceSqliteDatabase},
+        },
+    };
+    use aes_gcm::{
+        aead::{generic_array::GenericArray, NewAead},
+        Aes256Gcm,
+    };
+    use chrono::Utc;
+    use rand::rngs::OsRng;
+    use std::convert::TryFrom;
+    use tari_common_types::types::{PrivateKey, PublicKey};
+    use tari_core::transactions::{
+        tari_amount::MicroTari,
+        transaction::{OutputFeatures, UnblindedOutput},
+        CryptoFactories,
+    };
+    use tari_crypto::{
+        keys::{PublicKey as PublicKeyTrait, SecretKey},
+        script,
+        script::ExecutionStack,
+    };
+    use tari_test_utils::random::string;
+    use tempfile::tempdir;
+
+    use crate::storage::sqlite_utilities::run_migration_and_create_sqlite_connection;
+
+    #[tokio::test]
+    async fn test_crud() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join(db_name);
+        let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+        let db = MultisigDatabase::new(MultisigServiceSqliteDatabase::new(connection, None));
+
+        let participants = (0..3)
+            .map(|_| PublicKey::random_keypair(&mut OsRng).1)
+            .collect::<Vec<_>>();
+        let mut session = MultisigSession::new(1, 2, participants.clone(), Utc::now().naive_utc()).unwrap();
+        session.accepted = false;
+        db.upsert_session(session.clone()).await.unwrap();
+        assert_eq!(db.get_session(1).await.unwrap(), session);
+        assert_eq!(db.get_sessions().await.unwrap().len(), 1);
+        assert!(db.get_session(2).await.is_err());
+
+        // The dealings of a stored session are updated, and so is its acceptance
+        session.accepted = true;
+        let key = MultisigKey::from_master_key(PrivateKey::random(&mut OsRng)).unwrap();
+        for participant in &participants {
+            session
+                .add_dealing(MultisigDealing {
+                    dealer: participant.clone(),
+                    commitments: polynomial_commitments(&key.polynomial(1, 2).unwrap()),
+                    sealed_share: PrivateKey::random(&mut OsRng),
+                })
+                .unwrap();
+        }
+        db.upsert_session(session.clone()).await.unwrap();
+        let fetched = db.get_session(1).await.unwrap();
+        assert_eq!(fetched, session);
+        assert!(fetched.is_ready());
+
+        let mut round = MultisigSigningRound::new(
+            10,
+            1,
+            MultisigSigningRequest::Message(b"message".to_vec()),
+            participants[..2].to_vec(),
+        );
+        db.upsert_signing_round(round.clone()).await.unwrap();
+        let nonce_commitment = NonceCommitment {
+            hiding: participants[2].clone(),
+            binding: participants[1].clone(),
+        };
+        round
+            .add_nonce_commitments(participants[0].clone(), vec![nonce_commitment.clone()])
+            .unwrap();
+        db.upsert_signing_round(round.clone()).await.unwrap();
+
+        let fetched = db.get_signing_round(10).await.unwrap();
+        assert_eq!(fetched.signers, round.signers);
+        assert_eq!(
+            fetched.nonce_commitments(&participants[0]),
+            Some(&vec![nonce_commitment])
+        );
+        assert_eq!(db.get_signing_rounds().await.unwrap().len(), 1);
+
+        let mut output = create_output(1);
+        db.upsert_output(output.clone()).await.unwrap();
+        output.spent = true;
+        db.upsert_output(output.clone()).await.unwrap();
+        assert_eq!(db.get_output(output.output.commitment.clone()).await.unwrap(), output);
+        assert_eq!(db.get_outputs().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_apply_and_remove_encryption() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join(db_name);
+        let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+        let db = MultisigDatabase::new(MultisigServiceSqliteDatabase::new(connection.clone(), None));
+
+        let participants = (0..3)
+            .map(|_| PublicKey::random_keypair(&mut OsRng).1)
+            .collect::<Vec<_>>();
+        let session = MultisigSession::new(1, 2, participants, Utc::now().naive_utc()).unwrap();
+        db.upsert_session(session.clone()).await.unwrap();
+        let output = create_output(1);
+        db.upsert_output(output.clone()).await.unwrap();
+
+        let cipher = Aes256Gcm::new(GenericArray::from_slice(b"an example very very secret key."));
+        db.apply_encryption(cipher.clone()).await.unwrap();
+        assert!(matches!(
+            db.apply_encryption(cipher.clone()).await,
+            Err(MultisigStorageError::AlreadyEncrypted)
+        ));
+
+        // The spending key of the output is no longer stored in plaintext
+        let stored = MultisigOutputSql::index(&connection.acquire_lock()).unwrap();
+        assert!(MultisigOutput::try_from(stored[0].clone()).is_err());
+        assert_eq!(db.get_output(output.output.commitment.clone()).await.unwrap(), output);
+        assert_eq!(db.get_session(1).await.unwrap(), session);
+
+        // A second backend with the same cipher can read what the first one wrote
+        let db2 = MultisigDatabase::new(MultisigServiceSqliteDatabase::new(connection.clone(), Some(cipher)));
+        assert_eq!(db2.get_outputs().await.unwrap(), vec![output.clone()]);
+
+        db.remove_encryption().await.unwrap();
+        let stored = MultisigOutputSql::index(&connection.acquire_lock()).unwrap();
+        assert_eq!(MultisigOutput::try_from(stored[0].clone()).unwrap(), output);
+    }
+
+    fn create_output(session_id: u64) -> MultisigOutput {
+        let factories = CryptoFactories::default();
+        let spending_key = PrivateKey::random(&mut OsRng);
+        MultisigOutput {
+            session_id,
+            output: UnblindedOutput::new(
+                MicroTari(1000),
+                spending_key.clone(),
+                OutputFeatures::default(),
+                script!(Nop),
+                ExecutionStack::default(),
+                PrivateKey::default(),
+                PublicKey::default(),
+                Default::default(),
+            )
+            .as_transaction_output(&factories)
+            .unwrap(),
+            value: MicroTari(1000),
+            spending_key,
+            spent: false,
+        }
+    }
+}
@@ -63,6 +63,8 @@ pub enum OutputManagerError {
     IncompleteTransaction(&'static str),
     #[error("Not enough funds to fulfil transaction")]
     NotEnoughFunds,
+    #[error("The input `{0}` cannot be spent by this wallet")]
+    UnknownInput(String),
     #[error("Funds are still pending. Unable to fulfil transaction right now.")]
     FundsPending,
     #[error("Output already exists")]
@@ -109,6 +111,14 @@ pub enum OutputManagerError {
     MasterSecretKeyMismatch,
     #[error("Private Key is not found in the current Key Chain")]
     KeyNotFoundInKeyChain,
+    #[error("Transaction `{0}` has no pending change output that can be spent to bump its fee")]
+    NoFeeBumpOutput(u64),
+    #[error("Burn transactions are not enabled on this network")]
+    BurnKernelsNotEnabled,
+    #[error("Account `{0}` does not exist")]
+    AccountNotFound(String),
+    #[error("A watch-only wallet cannot derive keys or sign transactions")]
+    WatchOnlyWallet,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -119,6 +129,8 @@ pub enum OutputManagerStorageError {
         "Tried to insert an pending transaction encumberance for a transaction ID that already exists in the database"
     )]
     DuplicateTransaction,
+    #[error("An account named `{0}` already exists")]
+    DuplicateAccount(String),
     #[error("Value not found")]
     ValueNotFound,
     #[error("Unexpected result: `{0}`")]
@@ -23,20 +23,29 @@
 use crate::{
     output_manager_service::{
         error::OutputManagerError,
+        key_audit::KeyAuditReport,
         service::Balance,
-        storage::{database::PendingTransactionOutputs, models::KnownOneSidedPaymentScript},
+        stealth::StealthViewKey,
+        storage::{
+            database::PendingTransactionOutputs,
+            models::{AccountId, KnownOneSidedPaymentScript, WalletAccount, DEFAULT_ACCOUNT_ID},
+        },
         tasks::TxoValidationType,
+        watch_only::WatchOnlyKeys,
         TxId,
     },
     types::ValidationRetryStrategy,
 };
 use aes_gcm::Aes256Gcm;
 use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
-use tari_common_types::types::PublicKey;
+use tari_common_types::{
+    stealth_address::StealthAddress,
+    types::{PrivateKey, PublicKey},
+};
 use tari_comms::types::CommsPublicKey;
 use tari_core::transactions::{
     tari_amount::MicroTari,
-    transaction::{Transaction, TransactionInput, TransactionOutput, UnblindedOutput},
+    transaction::{OutputFeatures, Transaction, TransactionInput, TransactionOutput, UnblindedOutput},
     transaction_protocol::sender::TransactionSenderMessage,
     ReceiverTransactionProtocol,
     SenderTransactionProtocol,
@@ -56,8 +65,35 @@ pub enum OutputManagerRequest {
     GetCoinbaseTransaction((u64, MicroTari, MicroTari, u64)),
     ConfirmPendingTransaction(u64),
     ConfirmTransaction((u64, Vec<TransactionInput>, Vec<TransactionOutput>)),
-    PrepareToSendTransaction((TxId, MicroTari, MicroTari, Option<u64>, String, TariScript)),
+    PrepareToSendTransaction(
+        (
+            TxId,
+            MicroTari,
+            MicroTari,
+            Option<u64>,
+            String,
+            TariScript,
+            OutputFeatures,
+            Option<PrivateKey>,
+            AccountId,
+        ),
+    ),
+    PrepareOfflineTransaction(
+        Box<(
+            TxId,
+            Vec<TransactionOutput>,
+            MicroTari,
+            MicroTari,
+            String,
+            TariScript,
+            OutputFeatures,
+            Option<PrivateKey>,
+        )>,
+    ),
+    ReserveOutputsForOfflineTransaction((TxId, MicroTari, MicroTari)),
+    AddOfflineChangeOutputs((TxId, Vec<TransactionOutput>)),
     CreatePayToSelfTransaction((TxId, MicroTari, MicroTari, Option<u64>, String)),
+    CreateAccountTransferTransaction((TxId, AccountId, AccountId, MicroTari, MicroTari, String)),
     CancelTransaction(u64),
     TimeoutTransactions(Duration),
     GetPendingTransactions,
@@ -68,14 +104,46 @@ pub enum OutputManagerRequest {
     SetBaseNodePublicKey(CommsPublicKey),
     ValidateUtxos(TxoValidationType, ValidationRetryStrategy),
     CreateCoinSplit((MicroTari, usize, MicroTari, Option<u64>)),
+    CreateBurnTransaction((MicroTari, MicroTari, Option<u64>)),
     ApplyEncryption(Box<Aes256Gcm>),
     RemoveEncryption,
     GetPublicRewindKeys,
+    GetWatchOnlyKeys,
+    GetStealthAddress,
+    GetStealthViewKey,
     FeeEstimate((MicroTari, MicroTari, u64, u64)),
     ScanForRecoverableOutputs(Vec<TransactionOutput>),
     ScanOutputs(Vec<TransactionOutput>),
     AddKnownOneSidedPaymentScript(KnownOneSidedPaymentScript),
     ReinstateCancelledInboundTx(TxId),
+    AddRefundableHtlcOutput((TxId, Box<UnblindedOutput>)),
+    CreateHtlcClaimTransaction((Box<UnblindedOutput>, MicroTari)),
+    CreateHtlcRefundTransaction((TxId, MicroTari)),
+    CreateFeeBumpTransaction((TxId, MicroTari, u64, MicroTari)),
+    CancelFeeBumpTransaction((TxId, TxId)),
+    GetKeyAuditReport,
+    CreateAccount(String),
+    GetAccounts,
+    GetAccountBalance(AccountId),
+}
+
+impl OutputManagerRequest {
+    /// Whether the request signs for outputs of the wallet, which a watch-only wallet cannot do
+    pub fn spends_outputs(&self) -> bool {
+        use OutputManagerRequest::*;
+        matches!(
+            self,
+            PrepareToSendTransaction(_) |
+                PrepareOfflineTransaction(_) |
+                CreatePayToSelfTransaction(_) |
+                CreateAccountTransferTransaction(_) |
+                CreateCoinSplit(_) |
+                CreateBurnTransaction(_) |
+                CreateHtlcClaimTransaction(_) |
+                CreateHtlcRefundTransaction(_) |
+                CreateFeeBumpTransaction(_)
+        )
+    }
 }
 
 impl fmt::Display for OutputManagerRequest {
@@ -95,8 +163,18 @@ impl fmt::Display for OutputManagerRequest {
             GetRecipientTransaction(_) => write!(f, "GetRecipientTransaction"),
             ConfirmTransaction(v) => write!(f, "ConfirmTransaction ({})", v.0),
             ConfirmPendingTransaction(v) => write!(f, "ConfirmPendingTransaction ({})", v),
-            PrepareToSendTransaction((_, _, _, _, msg, _)) => write!(f, "PrepareToSendTransaction ({})", msg),
+            PrepareToSendTransaction((_, _, _, _, msg, _, _, _, account)) => {
+                write!(f, "PrepareToSendTransaction ({}, account {})", msg, account)
+            },
+            PrepareOfflineTransaction(v) => write!(f, "PrepareOfflineTransaction ({}: {} inputs)", v.0, v.1.len()),
+            ReserveOutputsForOfflineTransaction((t, v, _)) => {
+                write!(f, "ReserveOutputsForOfflineTransaction ({}: {})", t, v)
+            },
+            AddOfflineChangeOutputs((t, _)) => write!(f, "AddOfflineChangeOutputs ({})", t),
             CreatePayToSelfTransaction((_, _, _, _, msg)) => write!(f, "CreatePayToSelfTransaction ({})", msg),
+            CreateAccountTransferTransaction((_, from, to, v, _, _)) => {
+                write!(f, "CreateAccountTransferTransaction ({} from {} to {})", v, from, to)
+            },
             CancelTransaction(v) => write!(f, "CancelTransaction ({})", v),
             TimeoutTransactions(d) => write!(f, "TimeoutTransactions ({}s)", d.as_secs()),
             GetPendingTransactions => write!(f, "GetPendingTransactions"),
@@ -107,15 +185,28 @@ impl fmt::Display for OutputManagerRequest {
             SetBaseNodePublicKey(k) => write!(f, "SetBaseNodePublicKey ({})", k),
             ValidateUtxos(validation_type, retry) => write!(f, "{} ({:?})", validation_type, retry),
             CreateCoinSplit(v) => write!(f, "CreateCoinSplit ({})", v.0),
+            CreateBurnTransaction(v) => write!(f, "CreateBurnTransaction ({})", v.0),
             ApplyEncryption(_) => write!(f, "ApplyEncryption"),
             RemoveEncryption => write!(f, "RemoveEncryption"),
             GetCoinbaseTransaction(_) => write!(f, "GetCoinbaseTransaction"),
             GetPublicRewindKeys => write!(f, "GetPublicRewindKeys"),
+            GetWatchOnlyKeys => write!(f, "GetWatchOnlyKeys"),
+            GetStealthAddress => write!(f, "GetStealthAddress"),
+            GetStealthViewKey => write!(f, "GetStealthViewKey"),
             FeeEstimate(_) => write!(f, "FeeEstimate"),
             ScanForRecoverableOutputs(_) => write!(f, "ScanForRecoverableOutputs"),
             ScanOutputs(_) => write!(f, "ScanOutputs"),
             AddKnownOneSidedPaymentScript(_) => write!(f, "AddKnownOneSidedPaymentScript"),
             ReinstateCancelledInboundTx(_) => write!(f, "ReinstateCancelledInboundTx"),
+            AddRefundableHtlcOutput((t, v)) => write!(f, "AddRefundableHtlcOutput ({}: {})", t, v.value),
+            CreateHtlcClaimTransaction((v, _)) => write!(f, "CreateHtlcClaimTransaction ({})", v.value),
+            CreateHtlcRefundTransaction((t, _)) => write!(f, "CreateHtlcRefundTransaction ({})", t),
+            CreateFeeBumpTransaction((t, _, _, _)) => write!(f, "CreateFeeBumpTransaction ({})", t),
+            CancelFeeBumpTransaction((t, p)) => write!(f, "CancelFeeBumpTransaction ({} of {})", t, p),
+            GetKeyAuditReport => write!(f, "GetKeyAuditReport"),
+            CreateAccount(name) => write!(f, "CreateAccount ({})", name),
+            GetAccounts => write!(f, "GetAccounts"),
+            GetAccountBalance(account) => write!(f, "GetAccountBalance ({})", account),
         }
     }
 }
@@ -133,6 +224,7 @@ pub enum OutputManagerResponse {
     PayToSelfTransaction((MicroTari, Transaction)),
     TransactionConfirmed,
     TransactionToSend(SenderTransactionProtocol),
+    OutputsReserved(Vec<(TransactionOutput, MicroTari)>),
     TransactionCancelled,
     TransactionsTimedOut,
     PendingTransactions(HashMap<u64, PendingTransactionOutputs>),
@@ -146,11 +238,17 @@ pub enum OutputManagerResponse {
     EncryptionApplied,
     EncryptionRemoved,
     PublicRewindKeys(Box<PublicRewindKeys>),
+    WatchOnlyKeys(Box<WatchOnlyKeys>),
+    StealthAddress(Box<StealthAddress>),
+    StealthViewKey(Box<StealthViewKey>),
     FeeEstimate(MicroTari),
     RewoundOutputs(Vec<UnblindedOutput>),
     ScanOutputs(Vec<UnblindedOutput>),
     AddKnownOneSidedPaymentScript,
     ReinstatedCancelledInboundTx,
+    KeyAuditReport(Box<KeyAuditReport>),
+    Account(WalletAccount),
+    Accounts(Vec<WalletAccount>),
 }
 
 pub type OutputManagerEventSender = broadcast::Sender<Arc<OutputManagerEvent>>;
@@ -220,6 +318,105 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Persist the hash time locked contract output sent in the given transaction so that it can be refunded once its
+    /// time lock has expired.
+    pub async fn add_refundable_htlc_output(
+        &mut self,
+        tx_id: TxId,
+        output: UnblindedOutput,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::AddRefundableHtlcOutput((tx_id, Box::new(output))))
+            .await??
+        {
+            OutputManagerResponse::OutputAdded => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a transaction that claims a hash time locked contract output into this wallet. The provided output must
+    /// contain the pre-image as its input data. Returns (tx_id, transaction, fee, amount).
+    pub async fn create_htlc_claim_transaction(
+        &mut self,
+        htlc_output: UnblindedOutput,
+        fee_per_gram: MicroTari,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateHtlcClaimTransaction((
+                Box::new(htlc_output),
+                fee_per_gram,
+            )))
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a transaction that refunds the hash time locked contract output sent in the given transaction back into
+    /// this wallet. Returns (tx_id, transaction, fee, amount).
+    pub async fn create_htlc_refund_transaction(
+        &mut self,
+        htlc_tx_id: TxId,
+        fee_per_gram: MicroTari,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateHtlcRefundTransaction((
+                htlc_tx_id,
+                fee_per_gram,
+            )))
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a child-pays-for-parent transaction that spends the pending change of a stuck transaction back into this
+    /// wallet. The fee is chosen so that the parent, of the given fee and weight, and the new transaction together pay
+    /// `fee_per_gram`. Returns (tx_id, transaction, fee, amount).
+    pub async fn create_fee_bump_transaction(
+        &mut self,
+        parent_tx_id: TxId,
+        parent_fee: MicroTari,
+        parent_weight: u64,
+        fee_per_gram: MicroTari,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateFeeBumpTransaction((
+                parent_tx_id,
+                parent_fee,
+                parent_weight,
+                fee_per_gram,
+            )))
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Cancel a fee bump transaction. The change output it spends goes back to its parent transaction if that is still
+    /// pending, otherwise it is returned to the unspent pool.
+    pub async fn cancel_fee_bump_transaction(
+        &mut self,
+        tx_id: TxId,
+        parent_tx_id: TxId,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CancelFeeBumpTransaction((tx_id, parent_tx_id)))
+            .await??
+        {
+            OutputManagerResponse::TransactionCancelled => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn update_output_metadata_signature(
         &mut self,
         output: TransactionOutput,
@@ -234,6 +431,7 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Get the balance of the whole wallet, across all of its accounts
     pub async fn get_balance(&mut self) -> Result<Balance, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::GetBalance).await?? {
             OutputManagerResponse::Balance(b) => Ok(b),
@@ -241,6 +439,43 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Get the balance of the outputs that belong to the given account
+    pub async fn get_account_balance(&mut self, account_id: AccountId) -> Result<Balance, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAccountBalance(account_id))
+            .await??
+        {
+            OutputManagerResponse::Balance(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a new account, whose keys are derived from a new branch of the wallet's seed
+    pub async fn create_account(&mut self, name: String) -> Result<WalletAccount, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::CreateAccount(name)).await?? {
+            OutputManagerResponse::Account(account) => Ok(account),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Get all the accounts of the wallet, starting with the default account
+    pub async fn get_accounts(&mut self) -> Result<Vec<WalletAccount>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetAccounts).await?? {
+            OutputManagerResponse::Accounts(accounts) => Ok(accounts),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Find the account with the given name
+    pub async fn find_account(&mut self, name: &str) -> Result<WalletAccount, OutputManagerError> {
+        self.get_accounts()
+            .await?
+            .into_iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| OutputManagerError::AccountNotFound(name.to_string()))
+    }
+
     pub async fn get_recipient_transaction(
         &mut self,
         sender_message: TransactionSenderMessage,
@@ -285,6 +520,36 @@ impl OutputManagerHandle {
         lock_height: Option<u64>,
         message: String,
         recipient_script: TariScript,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        self.prepare_transaction_to_send_with_features(
+            DEFAULT_ACCOUNT_ID,
+            tx_id,
+            amount,
+            fee_per_gram,
+            lock_height,
+            message,
+            recipient_script,
+            OutputFeatures::default(),
+            None,
+        )
+        .await
+    }
+
+    /// Prepare a transaction to send from the given account where the recipient's output has the given features, e.g.
+    /// an encrypted memo. The recipient's sender offset private key is random unless one is given, which is needed
+    /// when the recipient's script depends on it as for stealth payments.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn prepare_transaction_to_send_with_features(
+        &mut self,
+        account_id: AccountId,
+        tx_id: TxId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        recipient_script: TariScript,
+        recipient_features: OutputFeatures,
+        recipient_sender_offset_private_key: Option<PrivateKey>,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         match self
             .handle
@@ -295,6 +560,9 @@ impl OutputManagerHandle {
                 lock_height,
                 message,
                 recipient_script,
+                recipient_features,
+                recipient_sender_offset_private_key,
+                account_id,
             )))
             .await??
         {
@@ -303,6 +571,80 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Prepare a transaction for an offline signing workflow that spends exactly the given outputs, which are rewound
+    /// with the keys of this wallet rather than looked up in its database. Neither the inputs nor any change output
+    /// are stored, as the online wallet that reserved the inputs keeps track of them.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn prepare_offline_transaction(
+        &mut self,
+        tx_id: TxId,
+        inputs: Vec<TransactionOutput>,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        recipient_script: TariScript,
+        recipient_features: OutputFeatures,
+        recipient_sender_offset_private_key: Option<PrivateKey>,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::PrepareOfflineTransaction(Box::new((
+                tx_id,
+                inputs,
+                amount,
+                fee_per_gram,
+                message,
+                recipient_script,
+                recipient_features,
+                recipient_sender_offset_private_key,
+            ))))
+            .await??
+        {
+            OutputManagerResponse::TransactionToSend(stp) => Ok(stp),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Select and encumber the outputs that pay `amount` without building a transaction, so that it can be signed by
+    /// an offline wallet. Returns each reserved output, with a range proof that the offline wallet can rewind, and its
+    /// value.
+    pub async fn reserve_outputs_for_offline_transaction(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+    ) -> Result<Vec<(TransactionOutput, MicroTari)>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ReserveOutputsForOfflineTransaction((
+                tx_id,
+                amount,
+                fee_per_gram,
+            )))
+            .await??
+        {
+            OutputManagerResponse::OutputsReserved(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Add the outputs of a transaction signed by an offline wallet that pay change to this wallet to the outputs
+    /// reserved against `tx_id`. Returns the change outputs.
+    pub async fn add_offline_change_outputs(
+        &mut self,
+        tx_id: TxId,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::AddOfflineChangeOutputs((tx_id, outputs)))
+            .await??
+        {
+            OutputManagerResponse::RewoundOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     /// Get a fee estimate for an amount of MicroTari, at a specified fee per gram and given number of kernels and
     /// outputs.
     pub async fn fee_estimate(
@@ -418,6 +760,14 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Returns a report of which outputs map to which key derivation indexes
+    pub async fn get_key_audit_report(&mut self) -> Result<KeyAuditReport, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetKeyAuditReport).await?? {
+            OutputManagerResponse::KeyAuditReport(report) => Ok(*report),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_rewind_public_keys(&mut self) -> Result<PublicRewindKeys, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::GetPublicRewindKeys).await?? {
             OutputManagerResponse::PublicRewindKeys(rk) => Ok(*rk),
@@ -425,6 +775,31 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Return the keys that a watch-only wallet needs to find the outputs of this wallet
+    pub async fn get_watch_only_keys(&mut self) -> Result<WatchOnlyKeys, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetWatchOnlyKeys).await?? {
+            OutputManagerResponse::WatchOnlyKeys(keys) => Ok(*keys),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Return the stealth address that this wallet receives stealth one-sided payments on
+    pub async fn get_stealth_address(&mut self) -> Result<StealthAddress, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetStealthAddress).await?? {
+            OutputManagerResponse::StealthAddress(address) => Ok(*address),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Return the view key of this wallet's stealth address, which a watch-only wallet can use to detect incoming
+    /// stealth payments
+    pub async fn get_stealth_view_key(&mut self) -> Result<StealthViewKey, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetStealthViewKey).await?? {
+            OutputManagerResponse::StealthViewKey(view_key) => Ok(*view_key),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn set_base_node_public_key(&mut self, public_key: CommsPublicKey) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -475,6 +850,28 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Create a transaction that burns the given amount, returning any change to this wallet.
+    /// Returns (tx_id, tx, fee, utxos_total_value).
+    pub async fn create_burn_transaction(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateBurnTransaction((
+                amount,
+                fee_per_gram,
+                lock_height,
+            )))
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn apply_encryption(&mut self, cipher: Aes256Gcm) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -552,6 +949,34 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Create a transaction that moves `amount` from one account of this wallet to another. Returns the fee and the
+    /// transaction.
+    pub async fn create_account_transfer_transaction(
+        &mut self,
+        tx_id: TxId,
+        from_account: AccountId,
+        to_account: AccountId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateAccountTransferTransaction((
+                tx_id,
+                from_account,
+                to_account,
+                amount,
+                fee_per_gram,
+                message,
+            )))
+            .await??
+        {
+            OutputManagerResponse::PayToSelfTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn reinstate_cancelled_inbound_transaction(&mut self, tx_id: TxId) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -0,0 +1,123 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::output_manager_service::storage::models::OutputStatus;
+use std::{collections::BTreeSet, ops::RangeInclusive};
+use tari_common_types::types::Commitment;
+use tari_core::transactions::tari_amount::MicroTari;
+
+/// The derivation indexes of the keys of a single output known to the wallet
+#[derive(Debug, Clone)]
+pub struct OutputKeyAudit {
+    pub commitment: Commitment,
+    pub value: MicroTari,
+    pub status: OutputStatus,
+    /// Coinbase output keys are derived from the coinbase key chains, where the spending key index is the block height
+    pub is_coinbase: bool,
+    /// The index of the spending key in its key chain, or None if the key was not derived by this wallet's key chains,
+    /// e.g. for one-sided payments and imported outputs
+    pub spending_key_index: Option<u64>,
+    /// The index of the script private key in its key chain, or None if it was not derived by this wallet
+    pub script_key_index: Option<u64>,
+}
+
+/// A report of which outputs map to which key derivation indexes, used to debug recovery mismatches
+#[derive(Debug, Clone)]
+pub struct KeyAuditReport {
+    /// The current index of the spending and script key chains
+    pub current_key_index: u64,
+    pub outputs: Vec<OutputKeyAudit>,
+}
+
+impl KeyAuditReport {
+    pub fn new(current_key_index: u64, outputs: Vec<OutputKeyAudit>) -> Self {
+        Self {
+            current_key_index,
+            outputs,
+        }
+    }
+
+    /// The spending key chain indexes used by the outputs in the wallet, excluding coinbase outputs
+    pub fn used_indexes(&self) -> BTreeSet<u64> {
+        self.outputs
+            .iter()
+            .filter(|o| !o.is_coinbase)
+            .filter_map(|o| o.spending_key_index)
+            .collect()
+    }
+
+    /// The ranges of spending key chain indexes up to the current key index that are not used by any output. Gaps are
+    /// expected for keys used in cancelled transactions, but large gaps can cause outputs to be missed during recovery.
+    pub fn index_gaps(&self) -> Vec<RangeInclusive<u64>> {
+        find_index_gaps(&self.used_indexes(), self.current_key_index)
+    }
+
+    /// Outputs using a spending key index beyond the current key index. The key chain index is out of sync and these
+    /// keys will be reused for new outputs.
+    pub fn outputs_beyond_current_index(&self) -> Vec<&OutputKeyAudit> {
+        self.outputs
+            .iter()
+            .filter(|o| !o.is_coinbase && o.spending_key_index.map_or(false, |i| i > self.current_key_index))
+            .collect()
+    }
+
+    /// Outputs whose spending key was not derived by this wallet's key chains
+    pub fn underived_outputs(&self) -> Vec<&OutputKeyAudit> {
+        self.outputs.iter().filter(|o| o.spending_key_index.is_none()).collect()
+    }
+}
+
+/// Returns the ranges of indexes in `1..=current_index` that are not in `used`. Key chain indexes start at 1 because
+/// the key index is incremented before a key is derived.
+fn find_index_gaps(used: &BTreeSet<u64>, current_index: u64) -> Vec<RangeInclusive<u64>> {
+    let mut gaps = Vec::new();
+    let mut next_expected = 1;
+    for &index in used.range(1..=current_index) {
+        if index > next_expected {
+            gaps.push(next_expected..=index - 1);
+        }
+        next_expected = index + 1;
+    }
+    if next_expected <= current_index {
+        gaps.push(next_expected..=current_index);
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod test {
+    use super::find_index_gaps;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_find_index_gaps() {
+        assert!(find_index_gaps(&BTreeSet::new(), 0).is_empty());
+        assert_eq!(find_index_gaps(&BTreeSet::new(), 3), vec![1..=3]);
+
+        let used = vec![1, 2, 3].into_iter().collect::<BTreeSet<_>>();
+        assert!(find_index_gaps(&used, 3).is_empty());
+        assert_eq!(find_index_gaps(&used, 5), vec![4..=5]);
+
+        let used = vec![2, 3, 6, 9, 12].into_iter().collect::<BTreeSet<_>>();
+        assert_eq!(find_index_gaps(&used, 10), vec![1..=1, 4..=5, 7..=8, 10..=10]);
+    }
+}
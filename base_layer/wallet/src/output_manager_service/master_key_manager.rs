@@ -22,17 +22,31 @@
 
 use crate::{
     output_manager_service::{
-        error::OutputManagerError,
+        error::{OutputManagerError, OutputManagerStorageError},
         handle::PublicRewindKeys,
-        storage::database::{KeyManagerState, OutputManagerBackend, OutputManagerDatabase},
+        key_audit::{KeyAuditReport, OutputKeyAudit},
+        stealth::StealthViewKey,
+        storage::{
+            database::{KeyManagerState, OutputManagerBackend, OutputManagerDatabase},
+            models::{AccountId, DbUnblindedOutput, OutputStatus, DEFAULT_ACCOUNT_ID},
+        },
+        watch_only::{OutputManagerKeys, WatchOnlyKeys},
     },
     types::KeyDigest,
 };
 use futures::lock::Mutex;
 use log::*;
-use tari_common_types::types::{PrivateKey, PublicKey};
-use tari_core::transactions::transaction_protocol::RewindData;
-use tari_crypto::{keys::PublicKey as PublicKeyTrait, range_proof::REWIND_USER_MESSAGE_LENGTH};
+use std::collections::HashMap;
+use tari_common_types::{
+    stealth_address::StealthAddress,
+    types::{PrivateKey, PublicKey},
+};
+use tari_core::transactions::{transaction::OutputFlags, transaction_protocol::RewindData};
+use tari_crypto::{
+    keys::PublicKey as PublicKeyTrait,
+    range_proof::REWIND_USER_MESSAGE_LENGTH,
+    tari_utilities::ByteArray,
+};
 use tari_key_manager::{
     key_manager::KeyManager,
     mnemonic::{from_secret_key, MnemonicLanguage},
@@ -45,15 +59,38 @@ const KEY_MANAGER_COINBASE_SCRIPT_BRANCH_KEY: &str = "coinbase_script";
 const KEY_MANAGER_SCRIPT_BRANCH_KEY: &str = "script";
 const KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY: &str = "recovery_viewonly";
 const KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY: &str = "recovery_blinding";
+const KEY_MANAGER_STEALTH_SCAN_BRANCH_KEY: &str = "stealth_scan";
+const KEY_MANAGER_STEALTH_SPEND_BRANCH_KEY: &str = "stealth_spend";
+const KEY_MANAGER_ACCOUNT_BRANCH_KEY: &str = "account";
 const KEY_MANAGER_MAX_SEARCH_DEPTH: u64 = 1_000_000;
+/// The number of key indexes beyond the current key index that are searched when auditing the output keys
+const KEY_AUDIT_SEARCH_MARGIN: u64 = 1_000;
+/// The number of key indexes beyond the key index of an account that are searched for the key of a recovered output
+const ACCOUNT_KEY_SEARCH_DEPTH: u64 = 10_000;
+/// The number of accounts beyond the highest known account whose key branches are searched for the key of a recovered
+/// output, so that accounts without any unspent outputs between them do not stop the search
+const ACCOUNT_RECOVERY_GAP: AccountId = 10;
 
-pub(crate) struct MasterKeyManager<TBackend>
-where TBackend: OutputManagerBackend + 'static
-{
+/// The key chains derived from the master secret key, which a watch-only wallet does not have
+struct KeyChains {
     utxo_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
     utxo_script_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
     coinbase_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
     coinbase_script_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
+    stealth_spend_key: PrivateKey,
+    /// The key that the seed words of the wallet encode, which is the master secret key unless the wallet is protected
+    /// by a seed words passphrase
+    seed_words_key: PrivateKey,
+}
+
+pub(crate) struct MasterKeyManager<TBackend>
+where TBackend: OutputManagerBackend + 'static
+{
+    key_chains: Option<KeyChains>,
+    /// The view key of the stealth address of the wallet, which a watch-only wallet only has if it was exported with it
+    stealth_view_key: Option<StealthViewKey>,
+    /// Serializes the derivation of account keys, whose key indexes are only kept in the database
+    account_key_lock: Mutex<()>,
     rewind_data: RewindData,
     db: OutputManagerDatabase<TBackend>,
 }
@@ -61,10 +98,20 @@ where TBackend: OutputManagerBackend + 'static
 impl<TBackend> MasterKeyManager<TBackend>
 where TBackend: OutputManagerBackend + 'static
 {
-    pub async fn new(
-        master_secret_key: PrivateKey,
-        db: OutputManagerDatabase<TBackend>,
-    ) -> Result<Self, OutputManagerError> {
+    pub async fn new(keys: OutputManagerKeys, db: OutputManagerDatabase<TBackend>) -> Result<Self, OutputManagerError> {
+        let (master_secret_key, seed_words_key) = match keys {
+            OutputManagerKeys::MasterKey(master_secret_key) => (master_secret_key.clone(), master_secret_key),
+            OutputManagerKeys::Passphrase { master_key, seed_key } => (master_key, seed_key),
+            OutputManagerKeys::WatchOnly(keys) => {
+                return Ok(Self {
+                    key_chains: None,
+                    stealth_view_key: keys.stealth_view_key.clone(),
+                    account_key_lock: Mutex::new(()),
+                    rewind_data: keys.rewind_data(),
+                    db,
+                })
+            },
+        };
         // Check to see if there is any persisted state. If there is confirm that the provided master secret key matches
         let key_manager_state = match db.get_key_manager_state().await? {
             None => {
@@ -116,7 +163,7 @@ where TBackend: OutputManagerBackend + 'static
         let rewind_key = rewind_key_manager.derive_key(0)?.k;
 
         let rewind_blinding_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key,
+            key_manager_state.master_key.clone(),
             KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY.to_string(),
             0,
         );
@@ -128,35 +175,121 @@ where TBackend: OutputManagerBackend + 'static
             proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
         };
 
+        let stealth_scan_key = KeyManager::<PrivateKey, KeyDigest>::from(
+            key_manager_state.master_key.clone(),
+            KEY_MANAGER_STEALTH_SCAN_BRANCH_KEY.to_string(),
+            0,
+        )
+        .derive_key(0)?
+        .k;
+
+        let stealth_spend_key = KeyManager::<PrivateKey, KeyDigest>::from(
+            key_manager_state.master_key,
+            KEY_MANAGER_STEALTH_SPEND_BRANCH_KEY.to_string(),
+            0,
+        )
+        .derive_key(0)?
+        .k;
+        let stealth_view_key = StealthViewKey::new(stealth_scan_key, PublicKey::from_secret_key(&stealth_spend_key));
+
         Ok(Self {
-            utxo_key_manager: Mutex::new(utxo_key_manager),
-            utxo_script_key_manager: Mutex::new(utxo_script_key_manager),
-            coinbase_key_manager: Mutex::new(coinbase_key_manager),
-            coinbase_script_key_manager: Mutex::new(coinbase_script_key_manager),
+            key_chains: Some(KeyChains {
+                utxo_key_manager: Mutex::new(utxo_key_manager),
+                utxo_script_key_manager: Mutex::new(utxo_script_key_manager),
+                coinbase_key_manager: Mutex::new(coinbase_key_manager),
+                coinbase_script_key_manager: Mutex::new(coinbase_script_key_manager),
+                stealth_spend_key,
+                seed_words_key,
+            }),
+            stealth_view_key: Some(stealth_view_key),
+            account_key_lock: Mutex::new(()),
             rewind_data,
             db,
         })
     }
 
+    fn key_chains(&self) -> Result<&KeyChains, OutputManagerError> {
+        self.key_chains.as_ref().ok_or(OutputManagerError::WatchOnlyWallet)
+    }
+
+    /// Whether this is the key manager of a watch-only wallet, which cannot derive spending or script keys
+    pub fn is_watch_only(&self) -> bool {
+        self.key_chains.is_none()
+    }
+
     pub fn rewind_data(&self) -> &RewindData {
         &self.rewind_data
     }
 
+    /// Return the keys that a watch-only wallet needs to find the outputs of this wallet, including its stealth
+    /// payments
+    pub fn get_watch_only_keys(&self) -> WatchOnlyKeys {
+        WatchOnlyKeys {
+            rewind_key: self.rewind_data.rewind_key.clone(),
+            rewind_blinding_key: self.rewind_data.rewind_blinding_key.clone(),
+            stealth_view_key: self.stealth_view_key.clone(),
+        }
+    }
+
     /// Return the next pair of (spending_key, script_private_key) from the key managers. These will always be generated
     /// in tandem and at corresponding increments
     pub async fn get_next_spend_and_script_key(&self) -> Result<(PrivateKey, PrivateKey), OutputManagerError> {
-        let mut km = self.utxo_key_manager.lock().await;
+        let key_chains = self.key_chains()?;
+        let mut km = key_chains.utxo_key_manager.lock().await;
         let key = km.next_key()?;
 
-        let mut skm = self.utxo_script_key_manager.lock().await;
+        let mut skm = key_chains.utxo_script_key_manager.lock().await;
         let script_key = skm.next_key()?;
 
         self.db.increment_key_index().await?;
         Ok((key.k, script_key.k))
     }
 
+    /// Return the next pair of (spending_key, script_private_key) of the given account. The keys of the default account
+    /// are those of the primary key chain, every other account has its own spending and script key branches.
+    pub async fn get_next_spend_and_script_key_for_account(
+        &self,
+        account_id: AccountId,
+    ) -> Result<(PrivateKey, PrivateKey), OutputManagerError> {
+        if account_id == DEFAULT_ACCOUNT_ID {
+            return self.get_next_spend_and_script_key().await;
+        }
+
+        let key_chains = self.key_chains()?;
+        let _guard = self.account_key_lock.lock().await;
+        let key_index = self
+            .db
+            .increment_account_key_index(account_id)
+            .await
+            .map_err(|e| match e {
+                OutputManagerStorageError::ValueNotFound => OutputManagerError::AccountNotFound(account_id.to_string()),
+                e => e.into(),
+            })?;
+        let master_key = key_chains.utxo_key_manager.lock().await.master_key().clone();
+        let key = KeyManager::<PrivateKey, KeyDigest>::from(master_key.clone(), account_branch(account_id), 0)
+            .derive_key(key_index)?;
+        let script_key = KeyManager::<PrivateKey, KeyDigest>::from(master_key, account_script_branch(account_id), 0)
+            .derive_key(key_index)?;
+        Ok((key.k, script_key.k))
+    }
+
+    /// Return the script private key at the given index of the script key branch of the given account
+    pub async fn get_account_script_key_at_index(
+        &self,
+        account_id: AccountId,
+        index: u64,
+    ) -> Result<PrivateKey, OutputManagerError> {
+        if account_id == DEFAULT_ACCOUNT_ID {
+            return self.get_script_key_at_index(index).await;
+        }
+        let master_key = self.key_chains()?.utxo_key_manager.lock().await.master_key().clone();
+        let script_key = KeyManager::<PrivateKey, KeyDigest>::from(master_key, account_script_branch(account_id), 0)
+            .derive_key(index)?;
+        Ok(script_key.k)
+    }
+
     pub async fn get_script_key_at_index(&self, index: u64) -> Result<PrivateKey, OutputManagerError> {
-        let skm = self.utxo_script_key_manager.lock().await;
+        let skm = self.key_chains()?.utxo_script_key_manager.lock().await;
         let script_key = skm.derive_key(index)?;
         Ok(script_key.k)
     }
@@ -165,20 +298,19 @@ where TBackend: OutputManagerBackend + 'static
         &self,
         height: u64,
     ) -> Result<(PrivateKey, PrivateKey), OutputManagerError> {
-        let km = self.coinbase_key_manager.lock().await;
+        let key_chains = self.key_chains()?;
+        let km = key_chains.coinbase_key_manager.lock().await;
         let spending_key = km.derive_key(height)?;
 
-        let mut skm = self.coinbase_script_key_manager.lock().await;
+        let mut skm = key_chains.coinbase_script_key_manager.lock().await;
         let script_key = skm.next_key()?;
         Ok((spending_key.k, script_key.k))
     }
 
-    /// Return the Seed words for the current Master Key set in the Key Manager
-    pub async fn get_seed_words(&self, language: &MnemonicLanguage) -> Result<Vec<String>, OutputManagerError> {
-        Ok(from_secret_key(
-            self.utxo_key_manager.lock().await.master_key(),
-            language,
-        )?)
+    /// Return the seed words of the wallet. The seed words of a wallet protected by a seed words passphrase encode the
+    /// seed key rather than the master key, so that the wallet is recovered from the seed words and the passphrase.
+    pub fn get_seed_words(&self, language: &MnemonicLanguage) -> Result<Vec<String>, OutputManagerError> {
+        Ok(from_secret_key(&self.key_chains()?.seed_words_key, language)?)
     }
 
     /// Return the public rewind keys
@@ -189,9 +321,24 @@ where TBackend: OutputManagerBackend + 'static
         }
     }
 
+    /// Return the stealth address that this wallet receives stealth one-sided payments on
+    pub fn get_stealth_address(&self) -> Result<StealthAddress, OutputManagerError> {
+        Ok(self.get_stealth_view_key()?.address())
+    }
+
+    /// Return the view key that detects payments to this wallet's stealth address without being able to spend them
+    pub fn get_stealth_view_key(&self) -> Result<StealthViewKey, OutputManagerError> {
+        self.stealth_view_key.clone().ok_or(OutputManagerError::WatchOnlyWallet)
+    }
+
+    /// Return the private key of the one-time script key of a stealth output with the given tweak
+    pub fn get_stealth_script_private_key(&self, tweak: &PrivateKey) -> Result<PrivateKey, OutputManagerError> {
+        Ok(&self.key_chains()?.stealth_spend_key + tweak)
+    }
+
     /// Search the current key manager key chain to find the index of the specified key.
     pub async fn find_utxo_key_index(&self, key: PrivateKey) -> Result<u64, OutputManagerError> {
-        let utxo_key_manager = self.utxo_key_manager.lock().await;
+        let utxo_key_manager = self.key_chains()?.utxo_key_manager.lock().await;
         let current_index = (*utxo_key_manager).key_index();
 
         for i in 0u64..current_index + KEY_MANAGER_MAX_SEARCH_DEPTH {
@@ -204,10 +351,110 @@ where TBackend: OutputManagerBackend + 'static
         Err(OutputManagerError::KeyNotFoundInKeyChain)
     }
 
+    /// Search the key branches of the accounts other than the default account for the specified key, returning the
+    /// account and the index of the key in its branch. The accounts up to `ACCOUNT_RECOVERY_GAP` beyond the highest
+    /// known account are searched too, since a recovering wallet does not know its accounts yet.
+    pub async fn find_account_key_index(&self, key: &PrivateKey) -> Result<(AccountId, u64), OutputManagerError> {
+        let master_key = self.key_chains()?.utxo_key_manager.lock().await.master_key().clone();
+        let accounts = self.db.get_accounts().await?;
+        let highest_account_id = accounts.iter().map(|a| a.id).max().unwrap_or(DEFAULT_ACCOUNT_ID);
+
+        for account_id in DEFAULT_ACCOUNT_ID + 1..=highest_account_id + ACCOUNT_RECOVERY_GAP {
+            let key_index = accounts
+                .iter()
+                .find(|a| a.id == account_id)
+                .map(|a| a.key_index)
+                .unwrap_or(0);
+            let key_manager =
+                KeyManager::<PrivateKey, KeyDigest>::from(master_key.clone(), account_branch(account_id), 0);
+            // Account keys are derived from index 1 onwards
+            for i in 1..=key_index + ACCOUNT_KEY_SEARCH_DEPTH {
+                if key_manager.derive_key(i)?.k == *key {
+                    trace!(target: LOG_TARGET, "Key found in account {} at index {}", account_id, i);
+                    return Ok((account_id, i));
+                }
+            }
+        }
+
+        Err(OutputManagerError::KeyNotFoundInKeyChain)
+    }
+
+    /// Recreate an account whose key was found by `find_account_key_index`, making sure that the account does not
+    /// derive the found key again
+    pub async fn restore_account(&self, account_id: AccountId, key_index: u64) -> Result<(), OutputManagerError> {
+        let _guard = self.account_key_lock.lock().await;
+        let account = self.db.restore_account(account_id, key_index).await?;
+        trace!(target: LOG_TARGET, "Restored account {} with key index {}", account, account.key_index);
+        Ok(())
+    }
+
+    /// Map each of the provided outputs to the derivation indexes of its spending and script keys. Keys are searched
+    /// up to `KEY_AUDIT_SEARCH_MARGIN` indexes beyond the current key index to detect a key chain that is out of sync.
+    pub async fn audit_keys(
+        &self,
+        outputs: Vec<(DbUnblindedOutput, OutputStatus)>,
+        coinbase_lock_height: u64,
+    ) -> Result<KeyAuditReport, OutputManagerError> {
+        let key_chains = self.key_chains()?;
+        let current_key_index = key_chains.utxo_key_manager.lock().await.key_index();
+        let max_index = current_key_index + KEY_AUDIT_SEARCH_MARGIN;
+        let spending_key_indexes = derive_key_indexes(&*key_chains.utxo_key_manager.lock().await, max_index)?;
+        let script_key_indexes = derive_key_indexes(&*key_chains.utxo_script_key_manager.lock().await, max_index)?;
+        // The coinbase script key index is not persisted, so only the start of the key chain is searched
+        let coinbase_script_key_indexes = derive_key_indexes(
+            &*key_chains.coinbase_script_key_manager.lock().await,
+            KEY_AUDIT_SEARCH_MARGIN,
+        )?;
+        let coinbase_key_manager = key_chains.coinbase_key_manager.lock().await;
+
+        let mut audits = Vec::with_capacity(outputs.len());
+        for (output, status) in outputs {
+            let uo = &output.unblinded_output;
+            let is_coinbase = uo.features.flags.contains(OutputFlags::COINBASE_OUTPUT);
+            let (spending_key_index, script_key_index) = if is_coinbase {
+                // Coinbase spending keys are derived at the height of the block they were mined in
+                let height = uo.features.maturity.saturating_sub(coinbase_lock_height);
+                let spending_key_index = if coinbase_key_manager.derive_key(height)?.k == uo.spending_key {
+                    Some(height)
+                } else {
+                    None
+                };
+                (
+                    spending_key_index,
+                    coinbase_script_key_indexes
+                        .get(uo.script_private_key.as_bytes())
+                        .copied(),
+                )
+            } else {
+                (
+                    spending_key_indexes.get(uo.spending_key.as_bytes()).copied(),
+                    script_key_indexes.get(uo.script_private_key.as_bytes()).copied(),
+                )
+            };
+            audits.push(OutputKeyAudit {
+                commitment: output.commitment,
+                value: uo.value,
+                status,
+                is_coinbase,
+                spending_key_index,
+                script_key_index,
+            });
+        }
+        trace!(
+            target: LOG_TARGET,
+            "Audited the keys of {} outputs up to key index {}",
+            audits.len(),
+            max_index
+        );
+
+        Ok(KeyAuditReport::new(current_key_index, audits))
+    }
+
     /// If the supplied index is higher than the current UTXO key chain indices then they will be updated.
     pub async fn update_current_index_if_higher(&self, index: u64) -> Result<(), OutputManagerError> {
-        let mut utxo_key_manager = self.utxo_key_manager.lock().await;
-        let mut utxo_script_key_manager = self.utxo_script_key_manager.lock().await;
+        let key_chains = self.key_chains()?;
+        let mut utxo_key_manager = key_chains.utxo_key_manager.lock().await;
+        let mut utxo_script_key_manager = key_chains.utxo_script_key_manager.lock().await;
         let current_index = (*utxo_key_manager).key_index();
         if index > current_index {
             (*utxo_key_manager).update_key_index(index);
@@ -218,3 +465,23 @@ where TBackend: OutputManagerBackend + 'static
         Ok(())
     }
 }
+
+fn account_branch(account_id: AccountId) -> String {
+    format!("{}_{}", KEY_MANAGER_ACCOUNT_BRANCH_KEY, account_id)
+}
+
+fn account_script_branch(account_id: AccountId) -> String {
+    format!("{}_{}_script", KEY_MANAGER_ACCOUNT_BRANCH_KEY, account_id)
+}
+
+/// Derive the keys of the key chain up to and including `max_index`, mapping each key to its index
+fn derive_key_indexes(
+    key_manager: &KeyManager<PrivateKey, KeyDigest>,
+    max_index: u64,
+) -> Result<HashMap<Vec<u8>, u64>, OutputManagerError> {
+    let mut indexes = HashMap::with_capacity(max_index as usize + 1);
+    for i in 0..=max_index {
+        indexes.insert(key_manager.derive_key(i)?.k.as_bytes().to_vec(), i);
+    }
+    Ok(indexes)
+}
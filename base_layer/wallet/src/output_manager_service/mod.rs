@@ -25,7 +25,7 @@ use log::*;
 use tokio::sync::broadcast;
 
 pub(crate) use master_key_manager::MasterKeyManager;
-use tari_comms::{connectivity::ConnectivityRequester, types::CommsSecretKey};
+use tari_comms::connectivity::ConnectivityRequester;
 use tari_core::{
     consensus::{ConsensusConstantsBuilder, NetworkConsensus},
     transactions::CryptoFactories,
@@ -46,6 +46,7 @@ use crate::{
         handle::OutputManagerHandle,
         service::OutputManagerService,
         storage::database::{OutputManagerBackend, OutputManagerDatabase},
+        watch_only::OutputManagerKeys,
     },
     transaction_service::handle::TransactionServiceHandle,
 };
@@ -53,13 +54,16 @@ use crate::{
 pub mod config;
 pub mod error;
 pub mod handle;
+pub mod key_audit;
 mod master_key_manager;
 mod recovery;
 pub mod resources;
 #[allow(unused_assignments)]
 pub mod service;
+pub mod stealth;
 pub mod storage;
 mod tasks;
+pub mod watch_only;
 
 const LOG_TARGET: &str = "wallet::output_manager_service::initializer";
 
@@ -72,7 +76,7 @@ where T: OutputManagerBackend
     backend: Option<T>,
     factories: CryptoFactories,
     network: NetworkConsensus,
-    master_secret_key: CommsSecretKey,
+    keys: OutputManagerKeys,
 }
 
 impl<T> OutputManagerServiceInitializer<T>
@@ -83,14 +87,14 @@ where T: OutputManagerBackend + 'static
         backend: T,
         factories: CryptoFactories,
         network: NetworkConsensus,
-        master_secret_key: CommsSecretKey,
+        keys: OutputManagerKeys,
     ) -> Self {
         Self {
             config,
             backend: Some(backend),
             factories,
             network,
-            master_secret_key,
+            keys,
         }
     }
 }
@@ -120,7 +124,7 @@ where T: OutputManagerBackend + 'static
         let factories = self.factories.clone();
         let config = self.config.clone();
         let constants = ConsensusConstantsBuilder::new(self.network.as_network()).build();
-        let master_secret_key = self.master_secret_key.clone();
+        let keys = self.keys.clone();
         context.spawn_when_ready(move |handles| async move {
             let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
             let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
@@ -137,7 +141,7 @@ where T: OutputManagerBackend + 'static
                 handles.get_shutdown_signal(),
                 base_node_service_handle,
                 connectivity_manager,
-                master_secret_key,
+                keys,
             )
             .await
             .expect("Could not initialize Output Manager Service")
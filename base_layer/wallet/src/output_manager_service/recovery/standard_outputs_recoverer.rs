@@ -23,9 +23,9 @@
 use std::sync::Arc;
 
 use log::*;
-use tari_crypto::{inputs, keys::PublicKey as PublicKeyTrait, tari_utilities::hex::Hex};
+use tari_crypto::{inputs, keys::PublicKey as PublicKeyTrait, script::ExecutionStack, tari_utilities::hex::Hex};
 
-use tari_common_types::types::PublicKey;
+use tari_common_types::types::{PrivateKey, PublicKey};
 use tari_core::transactions::{
     transaction::{TransactionOutput, UnblindedOutput},
     CryptoFactories,
@@ -35,7 +35,7 @@ use crate::output_manager_service::{
     error::OutputManagerError,
     storage::{
         database::{OutputManagerBackend, OutputManagerDatabase},
-        models::DbUnblindedOutput,
+        models::{AccountId, DbUnblindedOutput, DEFAULT_ACCOUNT_ID},
     },
     MasterKeyManager,
 };
@@ -64,12 +64,55 @@ where TBackend: OutputManagerBackend + 'static
     }
 
     /// Attempt to rewind all of the given transaction outputs into unblinded outputs. If they can be rewound then add
-    /// them to the database and increment the key manager index
+    /// them to the database, in the account whose key branch they were found in, and increment the key manager index
     pub async fn scan_and_recover_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
     ) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
-        let mut rewound_outputs: Vec<UnblindedOutput> = outputs
+        let rewound_outputs = self.rewind_outputs_into_accounts(outputs).await?;
+
+        for (output, account_id) in rewound_outputs.iter() {
+            let db_output =
+                DbUnblindedOutput::from_unblinded_output(output.clone(), &self.factories)?.with_account(*account_id);
+            self.db.add_unspent_output(db_output).await?;
+
+            trace!(
+                target: LOG_TARGET,
+                "Output {} with value {} with {} recovered",
+                output
+                    .as_transaction_input(&self.factories.commitment)?
+                    .commitment
+                    .to_hex(),
+                output.value,
+                output.features,
+            );
+        }
+
+        Ok(rewound_outputs.into_iter().map(|(output, _)| output).collect())
+    }
+
+    /// Attempt to rewind all of the given transaction outputs into unblinded outputs, incrementing the key manager
+    /// index for those that can be rewound. The rewound outputs are not added to the database. A watch-only wallet
+    /// cannot derive the script private keys, so its rewound outputs are left without them.
+    pub async fn rewind_outputs(
+        &mut self,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
+        Ok(self
+            .rewind_outputs_into_accounts(outputs)
+            .await?
+            .into_iter()
+            .map(|(output, _)| output)
+            .collect())
+    }
+
+    /// Rewind the given transaction outputs like `rewind_outputs`, pairing each rewound output with the account that
+    /// its spending key belongs to. Accounts found this way are recreated in the database.
+    async fn rewind_outputs_into_accounts(
+        &mut self,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<(UnblindedOutput, AccountId)>, OutputManagerError> {
+        let rewound_outputs: Vec<UnblindedOutput> = outputs
             .into_iter()
             .filter_map(|output| {
                 output
@@ -105,47 +148,59 @@ where TBackend: OutputManagerBackend + 'static
             )
             .collect();
 
-        for output in rewound_outputs.iter_mut() {
-            self.update_outputs_script_private_key_and_update_key_manager_index(output)
+        let mut rewound_outputs_with_accounts = Vec::with_capacity(rewound_outputs.len());
+        for mut output in rewound_outputs {
+            if self.master_key_manager.is_watch_only() {
+                output.input_data = ExecutionStack::default();
+                output.script_private_key = PrivateKey::default();
+                rewound_outputs_with_accounts.push((output, DEFAULT_ACCOUNT_ID));
+                continue;
+            }
+            let account_id = self
+                .update_outputs_script_private_key_and_update_key_manager_index(&mut output)
                 .await?;
-
-            let db_output = DbUnblindedOutput::from_unblinded_output(output.clone(), &self.factories)?;
-            self.db.add_unspent_output(db_output).await?;
-
-            trace!(
-                target: LOG_TARGET,
-                "Output {} with value {} with {} recovered",
-                output
-                    .as_transaction_input(&self.factories.commitment)?
-                    .commitment
-                    .to_hex(),
-                output.value,
-                output.features,
-            );
+            rewound_outputs_with_accounts.push((output, account_id));
         }
 
-        Ok(rewound_outputs)
+        Ok(rewound_outputs_with_accounts)
     }
 
-    /// Find the key manager index that corresponds to the spending key in the rewound output, if found then modify
-    /// output to contain correct associated script private key and update the key manager to the highest index it has
-    /// seen so far.
+    /// Find the key manager index that corresponds to the spending key in the rewound output, searching the primary
+    /// key chain first and then the key branches of the accounts. If found then modify output to contain correct
+    /// associated script private key and update the key manager, or recreate the account, to the highest index it has
+    /// seen so far. Returns the account that the output belongs to.
     async fn update_outputs_script_private_key_and_update_key_manager_index(
         &mut self,
         output: &mut UnblindedOutput,
-    ) -> Result<(), OutputManagerError> {
-        let found_index = self
+    ) -> Result<AccountId, OutputManagerError> {
+        let (account_id, found_index) = match self
             .master_key_manager
             .find_utxo_key_index(output.spending_key.clone())
-            .await?;
+            .await
+        {
+            Ok(found_index) => {
+                self.master_key_manager
+                    .update_current_index_if_higher(found_index)
+                    .await?;
+                (DEFAULT_ACCOUNT_ID, found_index)
+            },
+            Err(OutputManagerError::KeyNotFoundInKeyChain) => {
+                let (account_id, found_index) = self
+                    .master_key_manager
+                    .find_account_key_index(&output.spending_key)
+                    .await?;
+                self.master_key_manager.restore_account(account_id, found_index).await?;
+                (account_id, found_index)
+            },
+            Err(e) => return Err(e),
+        };
 
-        self.master_key_manager
-            .update_current_index_if_higher(found_index)
+        let script_private_key = self
+            .master_key_manager
+            .get_account_script_key_at_index(account_id, found_index)
             .await?;
-
-        let script_private_key = self.master_key_manager.get_script_key_at_index(found_index).await?;
         output.input_data = inputs!(PublicKey::from_secret_key(&script_private_key));
         output.script_private_key = script_private_key;
-        Ok(())
+        Ok(account_id)
     }
 }
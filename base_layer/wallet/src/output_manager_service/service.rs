@@ -22,7 +22,7 @@
 
 use std::{
     cmp::Ordering,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::{self, Display},
     sync::Arc,
     time::Duration,
@@ -38,16 +38,13 @@ use tari_crypto::{
     inputs,
     keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait, SecretKey},
     script,
-    script::TariScript,
+    script::{ExecutionStack, TariScript},
     tari_utilities::{hex::Hex, ByteArray},
 };
 use tokio::sync::broadcast;
 
 use tari_common_types::types::{PrivateKey, PublicKey};
-use tari_comms::{
-    connectivity::ConnectivityRequester,
-    types::{CommsPublicKey, CommsSecretKey},
-};
+use tari_comms::{connectivity::ConnectivityRequester, types::CommsPublicKey};
 use tari_core::{
     consensus::ConsensusConstants,
     transactions::{
@@ -77,13 +74,22 @@ use crate::{
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerProtocolError, OutputManagerStorageError},
         handle::{OutputManagerEventSender, OutputManagerRequest, OutputManagerResponse},
+        key_audit::KeyAuditReport,
         recovery::StandardUtxoRecoverer,
         resources::OutputManagerResources,
         storage::{
             database::{OutputManagerBackend, OutputManagerDatabase, PendingTransactionOutputs},
-            models::{DbUnblindedOutput, KnownOneSidedPaymentScript},
+            models::{
+                AccountId,
+                DbUnblindedOutput,
+                KnownOneSidedPaymentScript,
+                OutputStatus,
+                WalletAccount,
+                DEFAULT_ACCOUNT_ID,
+            },
         },
         tasks::{TxoValidationTask, TxoValidationType},
+        watch_only::OutputManagerKeys,
         MasterKeyManager,
         TxId,
     },
@@ -126,13 +132,13 @@ where TBackend: OutputManagerBackend + 'static
         shutdown_signal: ShutdownSignal,
         base_node_service: BaseNodeServiceHandle,
         connectivity_manager: ConnectivityRequester,
-        master_secret_key: CommsSecretKey,
+        keys: OutputManagerKeys,
     ) -> Result<OutputManagerService<TBackend>, OutputManagerError> {
         // Clear any encumberances for transactions that were being negotiated but did not complete to become official
         // Pending Transactions.
         db.clear_short_term_encumberances().await?;
 
-        let master_key_manager = MasterKeyManager::new(master_secret_key, db.clone()).await?;
+        let master_key_manager = MasterKeyManager::new(keys, db.clone()).await?;
 
         let resources = OutputManagerResources {
             config,
@@ -199,6 +205,9 @@ where TBackend: OutputManagerBackend + 'static
         request: OutputManagerRequest,
     ) -> Result<OutputManagerResponse, OutputManagerError> {
         trace!(target: LOG_TARGET, "Handling Service Request: {}", request);
+        if request.spends_outputs() && self.resources.master_key_manager.is_watch_only() {
+            return Err(OutputManagerError::WatchOnlyWallet);
+        }
         match request {
             OutputManagerRequest::AddOutput(uo) => self
                 .add_output(None, *uo)
@@ -236,15 +245,71 @@ where TBackend: OutputManagerBackend + 'static
                 lock_height,
                 message,
                 recipient_script,
+                recipient_features,
+                recipient_sender_offset_private_key,
+                account_id,
             )) => self
-                .prepare_transaction_to_send(tx_id, amount, fee_per_gram, lock_height, message, recipient_script)
+                .prepare_transaction_to_send(
+                    account_id,
+                    tx_id,
+                    amount,
+                    fee_per_gram,
+                    lock_height,
+                    message,
+                    recipient_script,
+                    recipient_features,
+                    recipient_sender_offset_private_key,
+                )
                 .await
                 .map(OutputManagerResponse::TransactionToSend),
+            OutputManagerRequest::PrepareOfflineTransaction(request) => {
+                let (
+                    tx_id,
+                    inputs,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    recipient_script,
+                    recipient_features,
+                    recipient_sender_offset_private_key,
+                ) = *request;
+                self.prepare_offline_transaction(
+                    tx_id,
+                    inputs,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    recipient_script,
+                    recipient_features,
+                    recipient_sender_offset_private_key,
+                )
+                .await
+                .map(OutputManagerResponse::TransactionToSend)
+            },
+            OutputManagerRequest::ReserveOutputsForOfflineTransaction((tx_id, amount, fee_per_gram)) => self
+                .reserve_outputs_for_offline_transaction(tx_id, amount, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::OutputsReserved),
+            OutputManagerRequest::AddOfflineChangeOutputs((tx_id, outputs)) => self
+                .add_offline_change_outputs(tx_id, outputs)
+                .await
+                .map(OutputManagerResponse::RewoundOutputs),
             OutputManagerRequest::CreatePayToSelfTransaction((tx_id, amount, fee_per_gram, lock_height, message)) => {
                 self.create_pay_to_self_transaction(tx_id, amount, fee_per_gram, lock_height, message)
                     .await
                     .map(OutputManagerResponse::PayToSelfTransaction)
             },
+            OutputManagerRequest::CreateAccountTransferTransaction((
+                tx_id,
+                from_account,
+                to_account,
+                amount,
+                fee_per_gram,
+                message,
+            )) => self
+                .create_account_transfer_transaction(tx_id, from_account, to_account, amount, fee_per_gram, message)
+                .await
+                .map(OutputManagerResponse::PayToSelfTransaction),
             OutputManagerRequest::FeeEstimate((amount, fee_per_gram, num_kernels, num_outputs)) => self
                 .fee_estimate(amount, fee_per_gram, num_kernels, num_outputs)
                 .await
@@ -287,11 +352,14 @@ where TBackend: OutputManagerBackend + 'static
                     .collect();
                 Ok(OutputManagerResponse::UnspentOutputs(outputs))
             },
+            OutputManagerRequest::GetKeyAuditReport => self
+                .get_key_audit_report()
+                .await
+                .map(|report| OutputManagerResponse::KeyAuditReport(Box::new(report))),
             OutputManagerRequest::GetSeedWords => self
                 .resources
                 .master_key_manager
                 .get_seed_words(&self.resources.config.seed_word_language)
-                .await
                 .map(OutputManagerResponse::SeedWords),
             OutputManagerRequest::SetBaseNodePublicKey(pk) => self
                 .set_base_node_public_key(pk)
@@ -313,6 +381,10 @@ where TBackend: OutputManagerBackend + 'static
                 .create_coin_split(amount_per_split, split_count, fee_per_gram, lock_height)
                 .await
                 .map(OutputManagerResponse::Transaction),
+            OutputManagerRequest::CreateBurnTransaction((amount, fee_per_gram, lock_height)) => self
+                .create_burn_transaction(amount, fee_per_gram, lock_height)
+                .await
+                .map(OutputManagerResponse::Transaction),
             OutputManagerRequest::ApplyEncryption(cipher) => self
                 .resources
                 .db
@@ -331,6 +403,19 @@ where TBackend: OutputManagerBackend + 'static
             OutputManagerRequest::GetPublicRewindKeys => Ok(OutputManagerResponse::PublicRewindKeys(Box::new(
                 self.resources.master_key_manager.get_rewind_public_keys(),
             ))),
+            OutputManagerRequest::GetWatchOnlyKeys => Ok(OutputManagerResponse::WatchOnlyKeys(Box::new(
+                self.resources.master_key_manager.get_watch_only_keys(),
+            ))),
+            OutputManagerRequest::GetStealthAddress => self
+                .resources
+                .master_key_manager
+                .get_stealth_address()
+                .map(|address| OutputManagerResponse::StealthAddress(Box::new(address))),
+            OutputManagerRequest::GetStealthViewKey => self
+                .resources
+                .master_key_manager
+                .get_stealth_view_key()
+                .map(|view_key| OutputManagerResponse::StealthViewKey(Box::new(view_key))),
             OutputManagerRequest::ScanForRecoverableOutputs(outputs) => StandardUtxoRecoverer::new(
                 self.resources.master_key_manager.clone(),
                 self.resources.factories.clone(),
@@ -351,6 +436,49 @@ where TBackend: OutputManagerBackend + 'static
                 .reinstate_cancelled_inbound_transaction(tx_id)
                 .await
                 .map(|_| OutputManagerResponse::ReinstatedCancelledInboundTx),
+            OutputManagerRequest::AddRefundableHtlcOutput((tx_id, uo)) => self
+                .add_refundable_htlc_output(tx_id, *uo)
+                .await
+                .map(|_| OutputManagerResponse::OutputAdded),
+            OutputManagerRequest::CreateHtlcClaimTransaction((uo, fee_per_gram)) => {
+                let output = DbUnblindedOutput::from_unblinded_output(*uo, &self.resources.factories)?;
+                self.create_htlc_spend_transaction(output, false, fee_per_gram)
+                    .await
+                    .map(OutputManagerResponse::Transaction)
+            },
+            OutputManagerRequest::CreateHtlcRefundTransaction((tx_id, fee_per_gram)) => {
+                let output = self.resources.db.fetch_refundable_htlc_output(tx_id).await?;
+                self.create_htlc_spend_transaction(output, true, fee_per_gram)
+                    .await
+                    .map(OutputManagerResponse::Transaction)
+            },
+            OutputManagerRequest::CreateFeeBumpTransaction((parent_tx_id, parent_fee, parent_weight, fee_per_gram)) => {
+                self.create_fee_bump_transaction(parent_tx_id, parent_fee, parent_weight, fee_per_gram)
+                    .await
+                    .map(OutputManagerResponse::Transaction)
+            },
+            OutputManagerRequest::CancelFeeBumpTransaction((tx_id, parent_tx_id)) => self
+                .resources
+                .db
+                .cancel_fee_bump_transaction_outputs(tx_id, parent_tx_id)
+                .await
+                .map(|_| OutputManagerResponse::TransactionCancelled)
+                .map_err(OutputManagerError::OutputManagerStorageError),
+            OutputManagerRequest::CreateAccount(name) => {
+                self.create_account(name).await.map(OutputManagerResponse::Account)
+            },
+            OutputManagerRequest::GetAccounts => {
+                Ok(OutputManagerResponse::Accounts(self.resources.db.get_accounts().await?))
+            },
+            OutputManagerRequest::GetAccountBalance(account_id) => {
+                let current_chain_tip = match self.base_node_service.get_chain_metadata().await {
+                    Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()),
+                    Err(_) => None,
+                };
+                self.get_account_balance(account_id, current_chain_tip)
+                    .await
+                    .map(OutputManagerResponse::Balance)
+            },
         }
     }
 
@@ -424,6 +552,43 @@ where TBackend: OutputManagerBackend + 'static
         Ok(balance)
     }
 
+    async fn get_account_balance(
+        &self,
+        account_id: AccountId,
+        current_chain_tip: Option<u64>,
+    ) -> Result<Balance, OutputManagerError> {
+        self.get_account(account_id).await?;
+        let balance = self
+            .resources
+            .db
+            .get_account_balance(account_id, current_chain_tip)
+            .await?;
+        trace!(target: LOG_TARGET, "Balance of account {}: {:?}", account_id, balance);
+        Ok(balance)
+    }
+
+    async fn create_account(&self, name: String) -> Result<WalletAccount, OutputManagerError> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(OutputManagerError::BuildError(
+                "An account name cannot be empty".to_string(),
+            ));
+        }
+        let account = self.resources.db.create_account(name).await?;
+        info!(target: LOG_TARGET, "Created account {}", account);
+        Ok(account)
+    }
+
+    async fn get_account(&self, account_id: AccountId) -> Result<WalletAccount, OutputManagerError> {
+        self.resources
+            .db
+            .get_accounts()
+            .await?
+            .into_iter()
+            .find(|a| a.id == account_id)
+            .ok_or_else(|| OutputManagerError::AccountNotFound(account_id.to_string()))
+    }
+
     /// Request a receiver transaction be generated from the supplied Sender Message
     async fn get_recipient_transaction(
         &mut self,
@@ -548,11 +713,11 @@ where TBackend: OutputManagerBackend + 'static
         );
 
         let (utxos, _, _) = self
-            .select_utxos(amount, fee_per_gram, num_outputs as usize, None)
+            .select_utxos(amount, fee_per_gram, num_outputs as usize, 0, None, DEFAULT_ACCOUNT_ID)
             .await?;
         debug!(target: LOG_TARGET, "{} utxos selected.", utxos.len());
 
-        let fee = Fee::calculate_with_minimum(fee_per_gram, num_kernels as usize, utxos.len(), num_outputs as usize);
+        let fee = Fee::calculate_with_minimum(fee_per_gram, num_kernels as usize, utxos.len(), num_outputs as usize, 0);
 
         debug!(target: LOG_TARGET, "Fee calculated: {}", fee);
         Ok(fee)
@@ -560,21 +725,250 @@ where TBackend: OutputManagerBackend + 'static
 
     /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
     /// will be produced.
+    #[allow(clippy::too_many_arguments)]
     pub async fn prepare_transaction_to_send(
         &mut self,
+        account_id: AccountId,
         tx_id: TxId,
         amount: MicroTari,
         fee_per_gram: MicroTari,
         lock_height: Option<u64>,
         message: String,
         recipient_script: TariScript,
+        recipient_features: OutputFeatures,
+        recipient_sender_offset_private_key: Option<PrivateKey>,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        debug!(
+            target: LOG_TARGET,
+            "Preparing to send transaction. Amount: {}. Fee per gram: {}. Account: {}", amount, fee_per_gram, account_id,
+        );
+        let (outputs, _, _) = self
+            .select_utxos(
+                amount,
+                fee_per_gram,
+                1,
+                recipient_features.encrypted_memo.len(),
+                None,
+                account_id,
+            )
+            .await?;
+
+        self.build_transaction_to_send(
+            tx_id,
+            outputs,
+            amount,
+            fee_per_gram,
+            lock_height,
+            message,
+            recipient_script,
+            recipient_features,
+            recipient_sender_offset_private_key,
+        )
+        .await
+    }
+
+    /// Prepare a Sender Transaction Protocol for an offline signing workflow that spends exactly the given outputs, as
+    /// reserved by the online wallet. The outputs are rewound with the keys of this wallet rather than looked up in its
+    /// database, and neither they nor the change output are stored: the online wallet keeps track of both.
+    #[allow(clippy::too_many_arguments)]
+    async fn prepare_offline_transaction(
+        &mut self,
+        tx_id: TxId,
+        inputs: Vec<TransactionOutput>,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        recipient_script: TariScript,
+        recipient_features: OutputFeatures,
+        recipient_sender_offset_private_key: Option<PrivateKey>,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         debug!(
             target: LOG_TARGET,
-            "Preparing to send transaction. Amount: {}. Fee per gram: {}. ", amount, fee_per_gram,
+            "Preparing offline transaction from {} given inputs. Amount: {}. Fee per gram: {}. ",
+            inputs.len(),
+            amount,
+            fee_per_gram,
+        );
+        let commitments = inputs.iter().map(|i| i.commitment.clone()).collect::<Vec<_>>();
+        let rewound_outputs = StandardUtxoRecoverer::new(
+            self.resources.master_key_manager.clone(),
+            self.resources.factories.clone(),
+            self.resources.db.clone(),
+        )
+        .rewind_outputs(inputs)
+        .await?
+        .into_iter()
+        .map(|o| DbUnblindedOutput::from_unblinded_output(o, &self.resources.factories))
+        .collect::<Result<Vec<_>, _>>()?;
+        if let Some(commitment) = commitments
+            .iter()
+            .find(|c| !rewound_outputs.iter().any(|o| &o.commitment == *c))
+        {
+            return Err(OutputManagerError::UnknownInput(commitment.to_hex()));
+        }
+
+        let (stp, _) = self
+            .build_sender_protocol(
+                tx_id,
+                &rewound_outputs,
+                amount,
+                fee_per_gram,
+                None,
+                message,
+                recipient_script,
+                recipient_features,
+                recipient_sender_offset_private_key,
+            )
+            .await?;
+        debug!(target: LOG_TARGET, "Prepared offline transaction (TxId: {})", tx_id);
+
+        Ok(stp)
+    }
+
+    /// Select the unspent outputs to pay `amount` to a single recipient and encumber them against `tx_id` without
+    /// building a transaction, so that they can be signed by an offline wallet holding the same keys. The outputs stay
+    /// encumbered until the signed transaction is broadcast and mined, or the pending transaction times out. Each
+    /// output is returned with a fresh range proof that the offline wallet can rewind to recover its spending key.
+    async fn reserve_outputs_for_offline_transaction(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+    ) -> Result<Vec<(TransactionOutput, MicroTari)>, OutputManagerError> {
+        let (outputs, _, _) = self
+            .select_utxos(amount, fee_per_gram, 1, 0, None, DEFAULT_ACCOUNT_ID)
+            .await?;
+        let reserved = outputs
+            .iter()
+            .map(|o| {
+                let output = o.unblinded_output.as_rewindable_transaction_output(
+                    &self.resources.factories,
+                    self.resources.master_key_manager.rewind_data(),
+                )?;
+                Ok((output, o.unblinded_output.value))
+            })
+            .collect::<Result<Vec<_>, OutputManagerError>>()?;
+        self.resources.db.encumber_outputs(tx_id, outputs, Vec::new()).await?;
+        self.resources.db.confirm_encumbered_outputs(tx_id).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Reserved outputs for offline transaction (TxId: {})", tx_id
+        );
+
+        Ok(reserved)
+    }
+
+    /// Add the change outputs of a transaction signed by an offline wallet to the outputs reserved against `tx_id`, so
+    /// that they are received once the transaction is mined. The change outputs are those of `outputs` that can be
+    /// rewound with the keys of this wallet.
+    async fn add_offline_change_outputs(
+        &mut self,
+        tx_id: TxId,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
+        let change_outputs = StandardUtxoRecoverer::new(
+            self.resources.master_key_manager.clone(),
+            self.resources.factories.clone(),
+            self.resources.db.clone(),
+        )
+        .rewind_outputs(outputs)
+        .await?;
+        if change_outputs.is_empty() {
+            return Ok(change_outputs);
+        }
+        let db_change_outputs = change_outputs
+            .iter()
+            .map(|o| DbUnblindedOutput::from_unblinded_output(o.clone(), &self.resources.factories))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The reservation is replaced by one that also receives the change outputs
+        let pending_transaction = self.resources.db.fetch_pending_transaction_outputs(tx_id).await?;
+        self.resources.db.cancel_pending_transaction_outputs(tx_id).await?;
+        self.resources
+            .db
+            .encumber_outputs(tx_id, pending_transaction.outputs_to_be_spent, db_change_outputs)
+            .await?;
+        self.resources.db.confirm_encumbered_outputs(tx_id).await?;
+
+        Ok(change_outputs)
+    }
+
+    /// Build the Sender Transaction Protocol spending the given outputs and encumber them, along with any change
+    /// output, against `tx_id`
+    #[allow(clippy::too_many_arguments)]
+    async fn build_transaction_to_send(
+        &mut self,
+        tx_id: TxId,
+        outputs: Vec<DbUnblindedOutput>,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        recipient_script: TariScript,
+        recipient_features: OutputFeatures,
+        recipient_sender_offset_private_key: Option<PrivateKey>,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        let (stp, change_output) = self
+            .build_sender_protocol(
+                tx_id,
+                &outputs,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+                recipient_script,
+                recipient_features,
+                recipient_sender_offset_private_key,
+            )
+            .await?;
+
+        // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
+        // store them until the transaction times out OR is confirmed
+        self.resources
+            .db
+            .encumber_outputs(tx_id, outputs, change_output.into_iter().collect())
+            .await?;
+
+        debug!(target: LOG_TARGET, "Prepared transaction (TxId: {}) to send", tx_id);
+        debug!(
+            target: LOG_TARGET_STRESS,
+            "Prepared transaction (TxId: {}) to send", tx_id
         );
-        let (outputs, _, total) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
 
+        Ok(stp)
+    }
+
+    /// Build the Sender Transaction Protocol spending the given outputs. Returns the protocol and the change output,
+    /// if one is needed.
+    #[allow(clippy::too_many_arguments)]
+    async fn build_sender_protocol(
+        &mut self,
+        tx_id: TxId,
+        outputs: &[DbUnblindedOutput],
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        recipient_script: TariScript,
+        mut recipient_features: OutputFeatures,
+        recipient_sender_offset_private_key: Option<PrivateKey>,
+    ) -> Result<(SenderTransactionProtocol, Option<DbUnblindedOutput>), OutputManagerError> {
+        // The memo is left off if the network does not allow it yet, rather than building an invalid transaction
+        let max_memo_size = self.resources.consensus_constants.max_output_memo_size();
+        if recipient_features.encrypted_memo.len() > max_memo_size {
+            warn!(
+                target: LOG_TARGET,
+                "Output memo of {} bytes exceeds the maximum of {} bytes on this network and will not be sent (TxId: {})",
+                recipient_features.encrypted_memo.len(),
+                max_memo_size,
+                tx_id
+            );
+            recipient_features.encrypted_memo.clear();
+        }
+        let memo_size = recipient_features.encrypted_memo.len();
+        let total = outputs.iter().map(|o| o.unblinded_output.value).sum::<MicroTari>();
+        // Change is returned to the account that the inputs are spent from
+        let account_id = outputs.first().map(|o| o.account_id).unwrap_or(DEFAULT_ACCOUNT_ID);
         let offset = PrivateKey::random(&mut OsRng);
         let nonce = PrivateKey::random(&mut OsRng);
 
@@ -588,8 +982,8 @@ where TBackend: OutputManagerBackend + 'static
             .with_recipient_data(
                 0,
                 recipient_script,
-                PrivateKey::random(&mut OsRng),
-                Default::default(),
+                recipient_sender_offset_private_key.unwrap_or_else(|| PrivateKey::random(&mut OsRng)),
+                recipient_features,
                 PrivateKey::random(&mut OsRng),
             )
             .with_message(message)
@@ -609,14 +1003,14 @@ where TBackend: OutputManagerBackend + 'static
             amount,
             outputs.len()
         );
-        let fee_without_change = Fee::calculate(fee_per_gram, 1, outputs.len(), 1);
+        let fee_without_change = Fee::calculate(fee_per_gram, 1, outputs.len(), 1, memo_size);
         // If the input values > the amount to be sent + fee_without_change then we will need to include a change
         // output
         if total > amount + fee_without_change {
             let (spending_key, script_private_key) = self
                 .resources
                 .master_key_manager
-                .get_next_spend_and_script_key()
+                .get_next_spend_and_script_key_for_account(account_id)
                 .await?;
             builder.with_change_secret(spending_key);
             builder.with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone());
@@ -631,34 +1025,21 @@ where TBackend: OutputManagerBackend + 'static
             .build::<HashDigest>(&self.resources.factories)
             .map_err(|e| OutputManagerError::BuildError(e.message))?;
 
-        // If a change output was created add it to the pending_outputs list.
-        let mut change_output = Vec::<DbUnblindedOutput>::new();
-        if total > amount + fee_without_change {
+        let change_output = if total > amount + fee_without_change {
             let unblinded_output = stp.get_change_unblinded_output()?.ok_or_else(|| {
                 OutputManagerError::BuildError(
                     "There should be a change output metadata signature available".to_string(),
                 )
             })?;
-            change_output.push(DbUnblindedOutput::from_unblinded_output(
-                unblinded_output,
-                &self.resources.factories,
-            )?);
-        }
-
-        // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
-        // store them until the transaction times out OR is confirmed
-        self.resources
-            .db
-            .encumber_outputs(tx_id, outputs, change_output)
-            .await?;
-
-        debug!(target: LOG_TARGET, "Prepared transaction (TxId: {}) to send", tx_id);
-        debug!(
-            target: LOG_TARGET_STRESS,
-            "Prepared transaction (TxId: {}) to send", tx_id
-        );
+            Some(
+                DbUnblindedOutput::from_unblinded_output(unblinded_output, &self.resources.factories)?
+                    .with_account(account_id),
+            )
+        } else {
+            None
+        };
 
-        Ok(stp)
+        Ok((stp, change_output))
     }
 
     /// Request a Coinbase transaction for a specific block height. All existing pending transactions with
@@ -733,7 +1114,49 @@ where TBackend: OutputManagerBackend + 'static
         lock_height: Option<u64>,
         message: String,
     ) -> Result<(MicroTari, Transaction), OutputManagerError> {
-        let (inputs, _, total) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
+        self.create_transaction_between_accounts(
+            tx_id,
+            DEFAULT_ACCOUNT_ID,
+            DEFAULT_ACCOUNT_ID,
+            amount,
+            fee_per_gram,
+            lock_height,
+            message,
+        )
+        .await
+    }
+
+    async fn create_account_transfer_transaction(
+        &mut self,
+        tx_id: TxId,
+        from_account: AccountId,
+        to_account: AccountId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        self.get_account(from_account).await?;
+        self.get_account(to_account).await?;
+        self.create_transaction_between_accounts(tx_id, from_account, to_account, amount, fee_per_gram, None, message)
+            .await
+    }
+
+    /// Create a transaction that spends outputs of `from_account` to an output of `to_account`, with any change
+    /// returned to `from_account`
+    #[allow(clippy::too_many_arguments)]
+    async fn create_transaction_between_accounts(
+        &mut self,
+        tx_id: TxId,
+        from_account: AccountId,
+        to_account: AccountId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<(MicroTari, Transaction), OutputManagerError> {
+        let (inputs, _, total) = self
+            .select_utxos(amount, fee_per_gram, 1, 0, None, from_account)
+            .await?;
 
         let offset = PrivateKey::random(&mut OsRng);
         let nonce = PrivateKey::random(&mut OsRng);
@@ -763,7 +1186,7 @@ where TBackend: OutputManagerBackend + 'static
         let (spending_key, script_private_key) = self
             .resources
             .master_key_manager
-            .get_next_spend_and_script_key()
+            .get_next_spend_and_script_key_for_account(to_account)
             .await?;
         let metadata_signature = TransactionOutput::create_final_metadata_signature(
             &amount,
@@ -784,23 +1207,25 @@ where TBackend: OutputManagerBackend + 'static
                 metadata_signature,
             ),
             &self.resources.factories,
-        )?;
+        )?
+        .with_account(to_account);
         builder
             .with_output(utxo.unblinded_output.clone(), sender_offset_private_key.clone())
             .map_err(|e| OutputManagerError::BuildError(e.message))?;
 
+        // The output to the other account is rewindable too, so that it is found by recovery
+        builder.with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone());
         let mut outputs = vec![utxo];
 
-        let fee = Fee::calculate(fee_per_gram, 1, inputs.len(), 1);
+        let fee = Fee::calculate(fee_per_gram, 1, inputs.len(), 1, 0);
         let change_value = total.saturating_sub(amount).saturating_sub(fee);
         if change_value > 0.into() {
             let (spending_key, script_private_key) = self
                 .resources
                 .master_key_manager
-                .get_next_spend_and_script_key()
+                .get_next_spend_and_script_key_for_account(from_account)
                 .await?;
             builder.with_change_secret(spending_key);
-            builder.with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone());
             builder.with_change_script(
                 script!(Nop),
                 inputs!(PublicKey::from_secret_key(&script_private_key)),
@@ -819,7 +1244,8 @@ where TBackend: OutputManagerBackend + 'static
                     "There should be a change output metadata signature available".to_string(),
                 )
             })?;
-            let change_output = DbUnblindedOutput::from_unblinded_output(unblinded_output, &self.resources.factories)?;
+            let change_output = DbUnblindedOutput::from_unblinded_output(unblinded_output, &self.resources.factories)?
+                .with_account(from_account);
 
             outputs.push(change_output);
         }
@@ -833,7 +1259,7 @@ where TBackend: OutputManagerBackend + 'static
         self.confirm_encumberance(tx_id).await?;
         let fee = stp.get_fee_amount()?;
         trace!(target: LOG_TARGET, "Finalize send-to-self transaction ({}).", tx_id);
-        stp.finalize(KernelFeatures::empty(), &factories)?;
+        stp.finalize(KernelFeatures::empty(), &factories, None, None)?;
         let tx = stp.take_transaction()?;
 
         Ok((fee, tx))
@@ -939,22 +1365,34 @@ where TBackend: OutputManagerBackend + 'static
         amount: MicroTari,
         fee_per_gram: MicroTari,
         output_count: usize,
+        memo_size: usize,
         strategy: Option<UTXOSelectionStrategy>,
+        account_id: AccountId,
     ) -> Result<(Vec<DbUnblindedOutput>, bool, MicroTari), OutputManagerError> {
         debug!(
             target: LOG_TARGET,
-            "select_utxos amount: {}, fee_per_gram: {}, output_count: {}, strategy: {:?}",
+            "select_utxos amount: {}, fee_per_gram: {}, output_count: {}, memo_size: {}, strategy: {:?}, account: {}",
             amount,
             fee_per_gram,
             output_count,
-            strategy
+            memo_size,
+            strategy,
+            account_id
         );
         let mut utxos = Vec::new();
         let mut utxos_total_value = MicroTari::from(0);
         let mut fee_without_change = MicroTari::from(0);
         let mut fee_with_change = MicroTari::from(0);
 
-        let uo = self.resources.db.fetch_sorted_unspent_outputs().await?;
+        // Only the outputs of the account being spent from can be selected
+        let uo = self
+            .resources
+            .db
+            .fetch_sorted_unspent_outputs()
+            .await?
+            .into_iter()
+            .filter(|u| u.account_id == account_id)
+            .collect::<Vec<_>>();
 
         // Attempt to get the chain tip height
         let chain_metadata = self.base_node_service.get_chain_metadata().await?;
@@ -1033,11 +1471,11 @@ where TBackend: OutputManagerBackend + 'static
             utxos.push(o.clone());
             utxos_total_value += o.unblinded_output.value;
             // The assumption here is that the only output will be the payment output and change if required
-            fee_without_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count);
+            fee_without_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count, memo_size);
             if utxos_total_value == amount + fee_without_change {
                 break;
             }
-            fee_with_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count + 1);
+            fee_with_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count + 1, memo_size);
             if utxos_total_value > amount + fee_with_change {
                 require_change_output = true;
                 break;
@@ -1103,6 +1541,50 @@ where TBackend: OutputManagerBackend + 'static
         Ok(self.resources.db.get_invalid_outputs().await?)
     }
 
+    /// Map all the outputs known to the wallet to the derivation indexes of their keys
+    async fn get_key_audit_report(&self) -> Result<KeyAuditReport, OutputManagerError> {
+        let mut outputs = Vec::new();
+        outputs.extend(
+            self.fetch_unspent_outputs()
+                .await?
+                .into_iter()
+                .map(|o| (o, OutputStatus::Unspent)),
+        );
+        outputs.extend(
+            self.fetch_spent_outputs()
+                .await?
+                .into_iter()
+                .map(|o| (o, OutputStatus::Spent)),
+        );
+        outputs.extend(
+            self.fetch_invalid_outputs()
+                .await?
+                .into_iter()
+                .map(|o| (o, OutputStatus::Invalid)),
+        );
+        for pending in self.fetch_pending_transaction_outputs().await?.into_values() {
+            outputs.extend(
+                pending
+                    .outputs_to_be_spent
+                    .into_iter()
+                    .map(|o| (o, OutputStatus::EncumberedToBeSpent)),
+            );
+            outputs.extend(
+                pending
+                    .outputs_to_be_received
+                    .into_iter()
+                    .map(|o| (o, OutputStatus::EncumberedToBeReceived)),
+            );
+        }
+        let mut seen = HashSet::new();
+        outputs.retain(|(o, _)| seen.insert(o.commitment.as_bytes().to_vec()));
+
+        self.resources
+            .master_key_manager
+            .audit_keys(outputs, self.resources.consensus_constants.coinbase_lock_height())
+            .await
+    }
+
     async fn create_coin_split(
         &mut self,
         amount_per_split: MicroTari,
@@ -1121,14 +1603,16 @@ where TBackend: OutputManagerBackend + 'static
                 total_split_amount,
                 fee_per_gram,
                 output_count,
+                0,
                 Some(UTXOSelectionStrategy::Largest),
+                DEFAULT_ACCOUNT_ID,
             )
             .await?;
         let input_count = inputs.len();
         if require_change_output {
             output_count = split_count + 1
         };
-        let fee = Fee::calculate(fee_per_gram, 1, input_count, output_count);
+        let fee = Fee::calculate(fee_per_gram, 1, input_count, output_count, 0);
 
         trace!(target: LOG_TARGET, "Construct coin split transaction.");
         let offset = PrivateKey::random(&mut OsRng);
@@ -1215,11 +1699,279 @@ where TBackend: OutputManagerBackend + 'static
         self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
         self.confirm_encumberance(tx_id).await?;
         trace!(target: LOG_TARGET, "Finalize coin split transaction ({}).", tx_id);
-        stp.finalize(KernelFeatures::empty(), &factories)?;
+        stp.finalize(KernelFeatures::empty(), &factories, None, None)?;
+        let tx = stp.take_transaction()?;
+        Ok((tx_id, tx, fee, utxos_total_value))
+    }
+
+    async fn create_burn_transaction(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
+        if !self.resources.consensus_constants.burn_kernels_enabled() {
+            return Err(OutputManagerError::BurnKernelsNotEnabled);
+        }
+        trace!(target: LOG_TARGET, "Select UTXOs and estimate burn transaction fee.");
+        let (inputs, require_change_output, utxos_total_value) = self
+            .select_utxos(amount, fee_per_gram, 0, 0, None, DEFAULT_ACCOUNT_ID)
+            .await?;
+        let output_count = if require_change_output { 1 } else { 0 };
+        let fee = Fee::calculate(fee_per_gram, 1, inputs.len(), output_count, 0);
+
+        trace!(target: LOG_TARGET, "Construct burn transaction.");
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(lock_height.unwrap_or(0))
+            .with_fee_per_gram(fee_per_gram)
+            .with_burn_amount(amount)
+            .with_offset(offset.clone())
+            .with_private_nonce(nonce.clone())
+            .with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone());
+        for uo in inputs.iter() {
+            builder.with_input(
+                uo.unblinded_output
+                    .as_transaction_input(&self.resources.factories.commitment)?,
+                uo.unblinded_output.clone(),
+            );
+        }
+
+        let mut outputs: Vec<DbUnblindedOutput> = Vec::with_capacity(output_count);
+        if require_change_output {
+            let change_amount = utxos_total_value
+                .checked_sub(fee)
+                .ok_or(OutputManagerError::NotEnoughFunds)?
+                .checked_sub(amount)
+                .ok_or(OutputManagerError::NotEnoughFunds)?;
+            let (spending_key, script_private_key) = self
+                .resources
+                .master_key_manager
+                .get_next_spend_and_script_key()
+                .await?;
+            let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+            let script = script!(Nop);
+            let output_features = OutputFeatures::default();
+            let metadata_signature = TransactionOutput::create_final_metadata_signature(
+                &change_amount,
+                &spending_key,
+                &script,
+                &output_features,
+                &sender_offset_private_key,
+            )?;
+            let utxo = DbUnblindedOutput::from_unblinded_output(
+                UnblindedOutput::new(
+                    change_amount,
+                    spending_key,
+                    output_features,
+                    script,
+                    inputs!(PublicKey::from_secret_key(&script_private_key)),
+                    script_private_key,
+                    PublicKey::from_secret_key(&sender_offset_private_key),
+                    metadata_signature,
+                ),
+                &self.resources.factories,
+            )?;
+            outputs.push(utxo.clone());
+            builder
+                .with_output(utxo.unblinded_output, sender_offset_private_key)
+                .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        }
+
+        trace!(target: LOG_TARGET, "Build burn transaction.");
+        let mut stp = builder
+            .build::<HashDigest>(&self.resources.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        let tx_id = stp.get_tx_id()?;
+        trace!(target: LOG_TARGET, "Encumber burn transaction ({}) outputs.", tx_id);
+        self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
+        self.confirm_encumberance(tx_id).await?;
+        trace!(target: LOG_TARGET, "Finalize burn transaction ({}).", tx_id);
+        stp.finalize(KernelFeatures::empty(), &self.resources.factories, None, None)?;
         let tx = stp.take_transaction()?;
         Ok((tx_id, tx, fee, utxos_total_value))
     }
 
+    /// Persist a hash time locked contract output sent by this wallet. It is kept out of the balance and is only used
+    /// to construct a refund transaction once the time lock has expired.
+    async fn add_refundable_htlc_output(
+        &mut self,
+        tx_id: TxId,
+        output: UnblindedOutput,
+    ) -> Result<(), OutputManagerError> {
+        debug!(
+            target: LOG_TARGET,
+            "Add refundable HTLC output of value {} for TxId: {}", output.value, tx_id
+        );
+        let output = DbUnblindedOutput::from_unblinded_output(output, &self.resources.factories)?;
+        self.resources.db.add_refundable_htlc_output(tx_id, output).await?;
+        Ok(())
+    }
+
+    /// Create a transaction that spends a hash time locked contract output into a single new output owned by this
+    /// wallet, less the fee. The input data and script private key of the provided output select which branch of the
+    /// HTLC script is executed, i.e. whether this is a claim or a refund. `owned` indicates whether the output is
+    /// stored in this wallet's database and should be encumbered.
+    async fn create_htlc_spend_transaction(
+        &mut self,
+        htlc_output: DbUnblindedOutput,
+        owned: bool,
+        fee_per_gram: MicroTari,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
+        let tx_id = OsRng.next_u64();
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset)
+            .with_private_nonce(nonce)
+            .with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone())
+            .with_tx_id(tx_id)
+            .with_input(
+                htlc_output
+                    .unblinded_output
+                    .as_transaction_input(&self.resources.factories.commitment)?,
+                htlc_output.unblinded_output.clone(),
+            );
+
+        let (spending_key, script_private_key) = self
+            .resources
+            .master_key_manager
+            .get_next_spend_and_script_key()
+            .await?;
+        builder.with_change_secret(spending_key);
+        builder.with_change_script(
+            script!(Nop),
+            inputs!(PublicKey::from_secret_key(&script_private_key)),
+            script_private_key,
+        );
+
+        let mut stp = builder
+            .build::<HashDigest>(&self.resources.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        let received_output = stp.get_change_unblinded_output()?.ok_or_else(|| {
+            OutputManagerError::BuildError("The HTLC output value is not enough to cover the fee".to_string())
+        })?;
+        let amount = received_output.value;
+        let outputs = vec![DbUnblindedOutput::from_unblinded_output(
+            received_output,
+            &self.resources.factories,
+        )?];
+        let inputs = if owned { vec![htlc_output] } else { Vec::new() };
+
+        trace!(target: LOG_TARGET, "Encumber HTLC spend transaction ({}) outputs.", tx_id);
+        self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
+        self.confirm_encumberance(tx_id).await?;
+        let fee = stp.get_fee_amount()?;
+        // The HTLC script is time dependent, so it must be validated in the context of the next block
+        let (prev_header, height) = match self.base_node_service.get_chain_metadata().await? {
+            Some(metadata) => (
+                Some(metadata.best_block().clone()),
+                Some(metadata.height_of_longest_chain() + 1),
+            ),
+            None => (None, None),
+        };
+        trace!(target: LOG_TARGET, "Finalize HTLC spend transaction ({}).", tx_id);
+        stp.finalize(KernelFeatures::empty(), &self.resources.factories, prev_header, height)?;
+        let tx = stp.take_transaction()?;
+
+        Ok((tx_id, tx, fee, amount))
+    }
+
+    /// Create a child-pays-for-parent transaction that spends the pending change outputs of a stuck parent transaction
+    /// into a new output in this wallet. The base node mempool mines a transaction together with the unconfirmed
+    /// transactions it depends on, so the fee of this transaction is what gets the parent mined.
+    async fn create_fee_bump_transaction(
+        &mut self,
+        parent_tx_id: TxId,
+        parent_fee: MicroTari,
+        parent_weight: u64,
+        fee_per_gram: MicroTari,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
+        let inputs = match self.resources.db.fetch_pending_transaction_outputs(parent_tx_id).await {
+            Ok(pending) => pending.outputs_to_be_received,
+            Err(OutputManagerStorageError::ValueNotFound) => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if inputs.is_empty() {
+            return Err(OutputManagerError::NoFeeBumpOutput(parent_tx_id));
+        }
+
+        let tx_id = OsRng.next_u64();
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let child_fee_per_gram = fee_bump_fee_per_gram(
+            parent_fee,
+            parent_weight,
+            Fee::calculate_weight(1, inputs.len(), 1, 0),
+            fee_per_gram,
+        );
+
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(child_fee_per_gram)
+            .with_offset(offset)
+            .with_private_nonce(nonce)
+            .with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone())
+            .with_tx_id(tx_id);
+        for input in &inputs {
+            builder.with_input(
+                input
+                    .unblinded_output
+                    .as_transaction_input(&self.resources.factories.commitment)?,
+                input.unblinded_output.clone(),
+            );
+        }
+
+        // The bumped change stays in the account of the parent's change
+        let account_id = inputs.first().map(|o| o.account_id).unwrap_or(DEFAULT_ACCOUNT_ID);
+        let (spending_key, script_private_key) = self
+            .resources
+            .master_key_manager
+            .get_next_spend_and_script_key_for_account(account_id)
+            .await?;
+        builder.with_change_secret(spending_key);
+        builder.with_change_script(
+            script!(Nop),
+            inputs!(PublicKey::from_secret_key(&script_private_key)),
+            script_private_key,
+        );
+
+        let mut stp = builder
+            .build::<HashDigest>(&self.resources.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        let received_output = stp.get_change_unblinded_output()?.ok_or_else(|| {
+            OutputManagerError::BuildError("The change being spent is not enough to cover the bumped fee".to_string())
+        })?;
+        let amount = received_output.value;
+        let outputs = vec![
+            DbUnblindedOutput::from_unblinded_output(received_output, &self.resources.factories)?
+                .with_account(account_id),
+        ];
+
+        trace!(
+            target: LOG_TARGET,
+            "Encumber fee bump transaction ({}) of transaction ({}) outputs.",
+            tx_id,
+            parent_tx_id
+        );
+        self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
+        self.confirm_encumberance(tx_id).await?;
+        let fee = stp.get_fee_amount()?;
+        trace!(target: LOG_TARGET, "Finalize fee bump transaction ({}).", tx_id);
+        stp.finalize(KernelFeatures::empty(), &self.resources.factories, None, None)?;
+        let tx = stp.take_transaction()?;
+
+        Ok((tx_id, tx, fee, amount))
+    }
+
     /// Persist a one-sided payment script for a Comms Public/Private key. These are the scripts that this wallet knows
     /// to look for when scanning for one-sided payments
     async fn add_known_script(&mut self, known_script: KnownOneSidedPaymentScript) -> Result<(), OutputManagerError> {
@@ -1245,12 +1997,15 @@ where TBackend: OutputManagerBackend + 'static
         let known_one_sided_payment_scripts: Vec<KnownOneSidedPaymentScript> =
             self.resources.db.get_all_known_one_sided_payment_scripts().await?;
 
+        // A watch-only wallet only holds the stealth view key if it was exported with it
+        let stealth_view_key = self.resources.master_key_manager.get_stealth_view_key().ok();
+
         let mut rewound_outputs: Vec<UnblindedOutput> = Vec::new();
         for output in outputs {
             let position = known_one_sided_payment_scripts
                 .iter()
                 .position(|known_one_sided_script| known_one_sided_script.script == output.script);
-            if let Some(i) = position {
+            let rewound_output = if let Some(i) = position {
                 let spending_key = PrivateKey::from_bytes(
                     CommsPublicKey::shared_secret(
                         &known_one_sided_payment_scripts[i].private_key,
@@ -1263,44 +2018,72 @@ where TBackend: OutputManagerBackend + 'static
                 let rewound =
                     output.full_rewind_range_proof(&self.resources.factories.range_proof, &rewind_key, &blinding_key);
 
-                if let Ok(rewound_result) = rewound {
-                    let rewound_output = UnblindedOutput::new(
+                match rewound {
+                    Ok(rewound_result) => UnblindedOutput::new(
                         rewound_result.committed_value,
                         rewound_result.blinding_factor.clone(),
-                        output.features,
+                        output.features.clone(),
                         known_one_sided_payment_scripts[i].script.clone(),
                         known_one_sided_payment_scripts[i].input.clone(),
                         known_one_sided_payment_scripts[i].private_key.clone(),
-                        output.sender_offset_public_key,
-                        output.metadata_signature,
-                    );
-                    let db_output =
-                        DbUnblindedOutput::from_unblinded_output(rewound_output.clone(), &self.resources.factories)?;
+                        output.sender_offset_public_key.clone(),
+                        output.metadata_signature.clone(),
+                    ),
+                    Err(_) => continue,
+                }
+            } else if let Some(scanned) = stealth_view_key
+                .as_ref()
+                .map(|view_key| view_key.scan(&output, &self.resources.factories))
+                .transpose()?
+                .flatten()
+            {
+                // Without the stealth spend key a watch-only wallet records the output without its script private
+                // key, like the outputs that it rewinds
+                let script_private_key = if self.resources.master_key_manager.is_watch_only() {
+                    PrivateKey::default()
+                } else {
+                    self.resources
+                        .master_key_manager
+                        .get_stealth_script_private_key(&scanned.keys.tweak)?
+                };
+                UnblindedOutput::new(
+                    scanned.value,
+                    scanned.keys.spending_key.clone(),
+                    output.features.clone(),
+                    scanned.keys.script(),
+                    ExecutionStack::default(),
+                    script_private_key,
+                    output.sender_offset_public_key.clone(),
+                    output.metadata_signature.clone(),
+                )
+            } else {
+                continue;
+            };
 
-                    let output_hex = output.commitment.to_hex();
-                    match self.resources.db.add_unspent_output(db_output).await {
-                        Ok(_) => {
-                            rewound_outputs.push(rewound_output);
-                        },
-                        Err(OutputManagerStorageError::DuplicateOutput) => {
-                            warn!(
-                                target: LOG_TARGET,
-                                "Attempt to add scanned output {} that already exists. Ignoring the output.",
-                                output_hex
-                            );
-                        },
-                        Err(err) => {
-                            return Err(err.into());
-                        },
-                    }
-                    trace!(
+            let db_output =
+                DbUnblindedOutput::from_unblinded_output(rewound_output.clone(), &self.resources.factories)?;
+            let output_hex = output.commitment.to_hex();
+            let value = rewound_output.value;
+            match self.resources.db.add_unspent_output(db_output).await {
+                Ok(_) => {
+                    rewound_outputs.push(rewound_output);
+                },
+                Err(OutputManagerStorageError::DuplicateOutput) => {
+                    warn!(
                         target: LOG_TARGET,
-                        "One-sided payment Output {} with value {} recovered",
-                        output_hex,
-                        rewound_result.committed_value,
+                        "Attempt to add scanned output {} that already exists. Ignoring the output.", output_hex
                     );
-                }
+                },
+                Err(err) => {
+                    return Err(err.into());
+                },
             }
+            trace!(
+                target: LOG_TARGET,
+                "One-sided payment Output {} with value {} recovered",
+                output_hex,
+                value,
+            );
         }
 
         Ok(rewound_outputs)
@@ -1366,6 +2149,21 @@ impl fmt::Display for Balance {
     }
 }
 
+/// The fee per gram a fee bump transaction of `child_weight` must pay so that it and a parent transaction, which paid
+/// `parent_fee` for `parent_weight`, together pay `fee_per_gram` over their combined weight. This is never less than
+/// `fee_per_gram` itself.
+fn fee_bump_fee_per_gram(
+    parent_fee: MicroTari,
+    parent_weight: u64,
+    child_weight: u64,
+    fee_per_gram: MicroTari,
+) -> MicroTari {
+    let package_fee = u64::from(fee_per_gram) * (parent_weight + child_weight);
+    let child_fee = package_fee.saturating_sub(u64::from(parent_fee));
+    let child_fee_per_gram = (child_fee + child_weight - 1) / child_weight;
+    MicroTari::from(child_fee_per_gram.max(u64::from(fee_per_gram)))
+}
+
 fn hash_secret_key(key: &PrivateKey) -> Vec<u8> {
     HashDigest::new().chain(key.as_bytes()).finalize().to_vec()
 }
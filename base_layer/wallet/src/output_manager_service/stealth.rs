@@ -0,0 +1,228 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Stealth address one-sided payments. A stealth address publishes a scan key `A = a·G` and a spend key `B = b·G`.
+//! The sender picks the sender offset key `r` of the output and derives the shared secret `r·A = a·R`, from which
+//! follow the tweak `c`, the one-time script key `P = B + c·G` and the commitment mask and rewind keys of the output.
+//!
+//! The receiver finds its outputs with the private scan key `a` alone, which is what a watch-only wallet holds, but
+//! only the owner of `b` knows the script private key `b + c` needed to spend them.
+
+use crate::output_manager_service::error::OutputManagerError;
+use digest::Digest;
+use tari_common_types::{
+    stealth_address::StealthAddress,
+    types::{PrivateKey, PublicKey},
+};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::TransactionOutput,
+    transaction_protocol::RewindData,
+    CryptoFactories,
+};
+use tari_crypto::{
+    common::Blake256,
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait},
+    range_proof::REWIND_USER_MESSAGE_LENGTH,
+    script,
+    script::TariScript,
+    tari_utilities::ByteArray,
+};
+
+const STEALTH_TWEAK_DOMAIN: &[u8] = b"com.tari.wallet.stealth.tweak";
+const STEALTH_MASK_DOMAIN: &[u8] = b"com.tari.wallet.stealth.mask";
+const STEALTH_REWIND_DOMAIN: &[u8] = b"com.tari.wallet.stealth.rewind";
+const STEALTH_REWIND_BLINDING_DOMAIN: &[u8] = b"com.tari.wallet.stealth.rewind_blinding";
+
+fn hash_shared_secret(domain: &[u8], shared_secret: &PublicKey) -> Result<PrivateKey, OutputManagerError> {
+    let hash = Blake256::new().chain(domain).chain(shared_secret.as_bytes()).finalize();
+    Ok(PrivateKey::from_bytes(hash.as_slice())?)
+}
+
+/// The keys of a stealth output that both its sender and its receiver can derive from their shared secret
+#[derive(Clone, Debug)]
+pub struct StealthOutputKeys {
+    /// The tweak `c` that is added to the spend key
+    pub tweak: PrivateKey,
+    /// The one-time public key `P = B + c·G` that the output script pushes
+    pub script_public_key: PublicKey,
+    /// The commitment mask of the output
+    pub spending_key: PrivateKey,
+    pub rewind_data: RewindData,
+}
+
+impl StealthOutputKeys {
+    fn from_shared_secret(shared_secret: &PublicKey, spend_key: &PublicKey) -> Result<Self, OutputManagerError> {
+        let tweak = hash_shared_secret(STEALTH_TWEAK_DOMAIN, shared_secret)?;
+        let script_public_key = spend_key + &PublicKey::from_secret_key(&tweak);
+        Ok(Self {
+            tweak,
+            script_public_key,
+            spending_key: hash_shared_secret(STEALTH_MASK_DOMAIN, shared_secret)?,
+            rewind_data: RewindData {
+                rewind_key: hash_shared_secret(STEALTH_REWIND_DOMAIN, shared_secret)?,
+                rewind_blinding_key: hash_shared_secret(STEALTH_REWIND_BLINDING_DOMAIN, shared_secret)?,
+                proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
+            },
+        })
+    }
+
+    /// Derive the output keys for a payment to `address` using the output's sender offset private key
+    pub fn for_recipient(
+        address: &StealthAddress,
+        sender_offset_private_key: &PrivateKey,
+    ) -> Result<Self, OutputManagerError> {
+        let shared_secret = PublicKey::shared_secret(sender_offset_private_key, &address.scan_key);
+        Self::from_shared_secret(&shared_secret, &address.spend_key)
+    }
+
+    /// The script that locks the output to the one-time key
+    pub fn script(&self) -> TariScript {
+        script!(PushPubKey(Box::new(self.script_public_key.clone())))
+    }
+}
+
+/// An output to a stealth address found by a [StealthViewKey]
+#[derive(Clone, Debug)]
+pub struct StealthScannedOutput {
+    pub value: MicroTari,
+    pub keys: StealthOutputKeys,
+}
+
+/// The keys needed to detect payments to a stealth address and read their values, without being able to spend them
+#[derive(Clone, Debug, PartialEq)]
+pub struct StealthViewKey {
+    pub scan_key: PrivateKey,
+    pub spend_public_key: PublicKey,
+}
+
+impl StealthViewKey {
+    pub fn new(scan_key: PrivateKey, spend_public_key: PublicKey) -> Self {
+        Self {
+            scan_key,
+            spend_public_key,
+        }
+    }
+
+    /// The stealth address that this view key scans for
+    pub fn address(&self) -> StealthAddress {
+        StealthAddress::new(
+            PublicKey::from_secret_key(&self.scan_key),
+            self.spend_public_key.clone(),
+        )
+    }
+
+    /// Check whether the output pays to this view key's stealth address and if so, rewind its value. Returns `None`
+    /// for outputs that belong to someone else.
+    pub fn scan(
+        &self,
+        output: &TransactionOutput,
+        factories: &CryptoFactories,
+    ) -> Result<Option<StealthScannedOutput>, OutputManagerError> {
+        let shared_secret = PublicKey::shared_secret(&self.scan_key, &output.sender_offset_public_key);
+        let keys = StealthOutputKeys::from_shared_secret(&shared_secret, &self.spend_public_key)?;
+        if output.script != keys.script() {
+            return Ok(None);
+        }
+        let rewound = match output.full_rewind_range_proof(
+            &factories.range_proof,
+            &keys.rewind_data.rewind_key,
+            &keys.rewind_data.rewind_blinding_key,
+        ) {
+            Ok(rewound) if rewound.blinding_factor == keys.spending_key => rewound,
+            _ => return Ok(None),
+        };
+        Ok(Some(StealthScannedOutput {
+            value: rewound.committed_value,
+            keys,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_core::transactions::{
+        helpers::TestParams,
+        tari_amount::uT,
+        transaction::{OutputFeatures, UnblindedOutput},
+    };
+    use tari_crypto::{inputs, keys::SecretKey};
+
+    fn stealth_output(
+        address: &StealthAddress,
+        value: MicroTari,
+        factories: &CryptoFactories,
+    ) -> (TransactionOutput, StealthOutputKeys) {
+        let test_params = TestParams::new();
+        let keys = StealthOutputKeys::for_recipient(address, &test_params.sender_offset_private_key).unwrap();
+        let output = UnblindedOutput::new(
+            value,
+            keys.spending_key.clone(),
+            OutputFeatures::default(),
+            keys.script(),
+            inputs!(PublicKey::from_secret_key(&test_params.script_private_key)),
+            test_params.script_private_key.clone(),
+            PublicKey::from_secret_key(&test_params.sender_offset_private_key),
+            Default::default(),
+        )
+        .as_rewindable_transaction_output(factories, &keys.rewind_data)
+        .unwrap();
+        (output, keys)
+    }
+
+    #[test]
+    fn view_key_detects_its_outputs_only() {
+        let factories = CryptoFactories::default();
+        let spend_key = PrivateKey::random(&mut OsRng);
+        let view_key = StealthViewKey::new(PrivateKey::random(&mut OsRng), PublicKey::from_secret_key(&spend_key));
+        let (output, keys) = stealth_output(&view_key.address(), 1234 * uT, &factories);
+
+        let scanned = view_key.scan(&output, &factories).unwrap().unwrap();
+        assert_eq!(scanned.value, 1234 * uT);
+        assert_eq!(scanned.keys.spending_key, keys.spending_key);
+        // Only the owner of the spend key can derive the private key of the one-time script key
+        assert_eq!(
+            PublicKey::from_secret_key(&(spend_key + scanned.keys.tweak)),
+            keys.script_public_key
+        );
+
+        let other_view_key = StealthViewKey::new(PrivateKey::random(&mut OsRng), view_key.spend_public_key.clone());
+        assert!(other_view_key.scan(&output, &factories).unwrap().is_none());
+    }
+
+    #[test]
+    fn outputs_to_the_same_address_are_unlinkable() {
+        let factories = CryptoFactories::default();
+        let view_key = StealthViewKey::new(
+            PrivateKey::random(&mut OsRng),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        );
+        let (output1, _) = stealth_output(&view_key.address(), 100 * uT, &factories);
+        let (output2, _) = stealth_output(&view_key.address(), 100 * uT, &factories);
+        assert_ne!(output1.script, output2.script);
+        assert!(view_key.scan(&output1, &factories).unwrap().is_some());
+        assert!(view_key.scan(&output2, &factories).unwrap().is_some());
+    }
+}
@@ -23,7 +23,7 @@
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
     service::Balance,
-    storage::models::{DbUnblindedOutput, KnownOneSidedPaymentScript, OutputStatus},
+    storage::models::{AccountId, DbUnblindedOutput, KnownOneSidedPaymentScript, OutputStatus, WalletAccount},
     TxId,
 };
 use aes_gcm::Aes256Gcm;
@@ -99,6 +99,23 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
         &self,
         commitment: &Commitment,
     ) -> Result<DbUnblindedOutput, OutputManagerStorageError>;
+    /// Cancel a fee bump transaction like `cancel_pending_transaction`, except that the change outputs it spends are
+    /// returned to the `outputs_to_be_received` of the parent transaction if that is still pending.
+    fn cancel_fee_bump_transaction(&self, tx_id: TxId, parent_tx_id: TxId) -> Result<(), OutputManagerStorageError>;
+    /// Create a new account with the given name. Account names are unique. Accounts are numbered sequentially after
+    /// the highest existing account, so that recovery can find the key branch of each account from its id.
+    fn create_account(&self, name: &str) -> Result<WalletAccount, OutputManagerStorageError>;
+    /// Recreate an account found during recovery if it does not exist yet, and raise its key index to at least
+    /// `key_index`
+    fn restore_account(
+        &self,
+        account_id: AccountId,
+        key_index: u64,
+    ) -> Result<WalletAccount, OutputManagerStorageError>;
+    /// Fetch all the accounts of the wallet ordered by their id, starting with the default account
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, OutputManagerStorageError>;
+    /// Increment the key index of the given account and return the new key index
+    fn increment_account_key_index(&self, account_id: AccountId) -> Result<u64, OutputManagerStorageError>;
 }
 
 /// Holds the outputs that have been selected for a given pending transaction waiting for confirmation
@@ -154,6 +171,7 @@ pub enum DbKeyValuePair {
     SpentOutput(Commitment, Box<DbUnblindedOutput>),
     UnspentOutput(Commitment, Box<DbUnblindedOutput>),
     UnspentOutputWithTxId(Commitment, (TxId, Box<DbUnblindedOutput>)),
+    RefundableHtlcOutput(Commitment, (TxId, Box<DbUnblindedOutput>)),
     PendingTransactionOutputs(TxId, Box<PendingTransactionOutputs>),
     KeyManagerState(KeyManagerState),
     KnownOneSidedPaymentScripts(KnownOneSidedPaymentScript),
@@ -266,7 +284,71 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    /// Persist a hash time locked contract output sent by this wallet so that it can be refunded once its time lock
+    /// expires. The output is not included in the wallet balance.
+    pub async fn add_refundable_htlc_output(
+        &self,
+        tx_id: TxId,
+        output: DbUnblindedOutput,
+    ) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::RefundableHtlcOutput(
+                output.commitment.clone(),
+                (tx_id, Box::new(output)),
+            )))
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
+
+        Ok(())
+    }
+
+    /// Fetch the refundable hash time locked contract output that was sent in the transaction with the given TxId
+    pub async fn fetch_refundable_htlc_output(
+        &self,
+        tx_id: TxId,
+    ) -> Result<DbUnblindedOutput, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        let key = DbKey::OutputsByTxIdAndStatus(tx_id, OutputStatus::RefundableHtlc);
+        let mut outputs = tokio::task::spawn_blocking(move || match db_clone.fetch(&key) {
+            Ok(None) => Err(OutputManagerStorageError::ValueNotFound),
+            Ok(Some(DbValue::AnyOutputs(o))) => Ok(o),
+            Ok(Some(other)) => unexpected_result(key, other),
+            Err(e) => log_error(key, e),
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)?;
+
+        if outputs.len() > 1 {
+            return Err(OutputManagerStorageError::UnexpectedResult(
+                "There should be only 1 refundable HTLC output for a transaction but more were found".to_string(),
+            ));
+        }
+        outputs.pop().ok_or(OutputManagerStorageError::ValueNotFound)
+    }
+
+    /// Get the balance of the whole wallet, across all of its accounts
     pub async fn get_balance(&self, current_chain_tip: Option<u64>) -> Result<Balance, OutputManagerStorageError> {
+        self.get_balance_of(None, current_chain_tip).await
+    }
+
+    /// Get the balance of the outputs that belong to the given account
+    pub async fn get_account_balance(
+        &self,
+        account_id: AccountId,
+        current_chain_tip: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        self.get_balance_of(Some(account_id), current_chain_tip).await
+    }
+
+    async fn get_balance_of(
+        &self,
+        account_id: Option<AccountId>,
+        current_chain_tip: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        let in_account = move |o: &&DbUnblindedOutput| account_id.map(|id| o.account_id == id).unwrap_or(true);
         let db_clone = self.db.clone();
         let db_clone2 = self.db.clone();
         let db_clone3 = self.db.clone();
@@ -293,6 +375,7 @@ where T: OutputManagerBackend + 'static
             if let DbValue::AllPendingTransactionOutputs(pto) = pending_txs {
                 let available_balance = uo
                     .iter()
+                    .filter(in_account)
                     .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                 let time_locked_balance = if let Some(tip) = current_chain_tip {
                     let time_locked_outputs = tokio::task::spawn_blocking(move || {
@@ -308,6 +391,7 @@ where T: OutputManagerBackend + 'static
                         Some(
                             time_locked_uo
                                 .iter()
+                                .filter(in_account)
                                 .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value),
                         )
                     } else {
@@ -323,10 +407,12 @@ where T: OutputManagerBackend + 'static
                     pending_incoming += v
                         .outputs_to_be_received
                         .iter()
+                        .filter(in_account)
                         .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                     pending_outgoing += v
                         .outputs_to_be_spent
                         .iter()
+                        .filter(in_account)
                         .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                 }
 
@@ -457,6 +543,54 @@ where T: OutputManagerBackend + 'static
             .and_then(|inner_result| inner_result)
     }
 
+    pub async fn cancel_fee_bump_transaction_outputs(
+        &self,
+        tx_id: TxId,
+        parent_tx_id: TxId,
+    ) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.cancel_fee_bump_transaction(tx_id, parent_tx_id))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn create_account(&self, name: String) -> Result<WalletAccount, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.create_account(&name))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn restore_account(
+        &self,
+        account_id: AccountId,
+        key_index: u64,
+    ) -> Result<WalletAccount, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.restore_account(account_id, key_index))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn get_accounts(&self) -> Result<Vec<WalletAccount>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.fetch_accounts())
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn increment_account_key_index(&self, account_id: AccountId) -> Result<u64, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.increment_account_key_index(account_id))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
     /// This method is check all pending transactions to see if any are older that the provided duration. If they are
     /// they will be cancelled.
     pub async fn timeout_pending_transaction_outputs(&self, period: Duration) -> Result<(), OutputManagerStorageError> {
@@ -20,8 +20,9 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt};
 
+use chrono::NaiveDateTime;
 use tari_crypto::script::{ExecutionStack, TariScript};
 
 use tari_common_types::types::{Commitment, HashOutput, PrivateKey};
@@ -37,6 +38,7 @@ pub struct DbUnblindedOutput {
     pub commitment: Commitment,
     pub unblinded_output: UnblindedOutput,
     pub hash: HashOutput,
+    pub account_id: AccountId,
 }
 
 impl DbUnblindedOutput {
@@ -49,6 +51,7 @@ impl DbUnblindedOutput {
             hash: tx_out.hash(),
             commitment: tx_out.commitment,
             unblinded_output: output,
+            account_id: DEFAULT_ACCOUNT_ID,
         })
     }
 
@@ -62,8 +65,15 @@ impl DbUnblindedOutput {
             hash: tx_out.hash(),
             commitment: tx_out.commitment,
             unblinded_output: output,
+            account_id: DEFAULT_ACCOUNT_ID,
         })
     }
+
+    /// Assign this output to the given account
+    pub fn with_account(mut self, account_id: AccountId) -> Self {
+        self.account_id = account_id;
+        self
+    }
 }
 
 impl From<DbUnblindedOutput> for UnblindedOutput {
@@ -92,6 +102,28 @@ impl Ord for DbUnblindedOutput {
 
 impl Eq for DbUnblindedOutput {}
 
+pub type AccountId = u64;
+
+/// The account that received outputs and the history of a wallet without additional accounts belong to
+pub const DEFAULT_ACCOUNT_ID: AccountId = 0;
+
+/// An account of the wallet. Every account derives the keys of its outputs from its own branch of the wallet's seed,
+/// so that the balances and histories of the accounts in one wallet database are kept apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletAccount {
+    pub id: AccountId,
+    pub name: String,
+    /// The index of the last key derived from the account's key branch
+    pub key_index: u64,
+    pub created_at: NaiveDateTime,
+}
+
+impl fmt::Display for WalletAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (id: {})", self.name, self.id)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct KnownOneSidedPaymentScript {
     pub script_hash: Vec<u8>,
@@ -115,4 +147,6 @@ pub enum OutputStatus {
     EncumberedToBeSpent,
     Invalid,
     CancelledInbound,
+    /// A hash time locked contract output sent by this wallet that can be refunded once its time lock has expired
+    RefundableHtlc,
 }
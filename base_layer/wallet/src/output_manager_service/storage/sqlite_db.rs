@@ -64,11 +64,24 @@ use crate::{
                 PendingTransactionOutputs,
                 WriteOperation,
             },
-            models::{DbUnblindedOutput, KnownOneSidedPaymentScript, OutputStatus},
+            models::{
+                AccountId,
+                DbUnblindedOutput,
+                KnownOneSidedPaymentScript,
+                OutputStatus,
+                WalletAccount,
+                DEFAULT_ACCOUNT_ID,
+            },
         },
         TxId,
     },
-    schema::{key_manager_states, known_one_sided_payment_scripts, outputs, pending_transaction_outputs},
+    schema::{
+        key_manager_states,
+        known_one_sided_payment_scripts,
+        outputs,
+        pending_transaction_outputs,
+        wallet_accounts,
+    },
     storage::sqlite_utilities::WalletDbConnection,
     util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
 };
@@ -90,6 +103,11 @@ impl OutputManagerSqliteDatabase {
         }
     }
 
+    /// The cipher of this backend, which is shared by all of its clones
+    pub(crate) fn cipher(&self) -> Arc<RwLock<Option<Aes256Gcm>>> {
+        self.cipher.clone()
+    }
+
     fn decrypt_if_necessary<T: Encryptable<Aes256Gcm>>(&self, o: &mut T) -> Result<(), OutputManagerStorageError> {
         let cipher = acquire_read_lock!(self.cipher);
         if let Some(cipher) = cipher.as_ref() {
@@ -323,6 +341,14 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                     self.encrypt_if_necessary(&mut new_output)?;
                     new_output.commit(&(*conn))?
                 },
+                DbKeyValuePair::RefundableHtlcOutput(c, (tx_id, o)) => {
+                    if OutputSql::find_by_commitment_and_cancelled(&c.to_vec(), false, &(*conn)).is_ok() {
+                        return Err(OutputManagerStorageError::DuplicateOutput);
+                    }
+                    let mut new_output = NewOutputSql::new(*o, OutputStatus::RefundableHtlc, Some(tx_id))?;
+                    self.encrypt_if_necessary(&mut new_output)?;
+                    new_output.commit(&(*conn))?
+                },
                 DbKeyValuePair::PendingTransactionOutputs(tx_id, p) => {
                     if PendingTransactionOutputSql::find(tx_id, &(*conn)).is_ok() {
                         return Err(OutputManagerStorageError::DuplicateTransaction);
@@ -738,6 +764,150 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         DbUnblindedOutput::try_from(o)
     }
 
+    fn cancel_fee_bump_transaction(&self, tx_id: u64, parent_tx_id: u64) -> Result<(), OutputManagerStorageError> {
+        let conn = self.database_connection.acquire_lock();
+
+        let pending_transaction = match PendingTransactionOutputSql::find(tx_id, &(*conn)) {
+            Ok(p) => p,
+            Err(OutputManagerStorageError::DieselError(DieselError::NotFound)) => {
+                return Err(OutputManagerStorageError::ValueNotFound)
+            },
+            Err(e) => return Err(e),
+        };
+        let parent_pending = match PendingTransactionOutputSql::find(parent_tx_id, &(*conn)) {
+            Ok(_) => true,
+            Err(OutputManagerStorageError::DieselError(DieselError::NotFound)) => false,
+            Err(e) => return Err(e),
+        };
+
+        let outputs = OutputSql::find_by_tx_id_and_encumbered(tx_id, &(*conn))?;
+        for o in outputs {
+            if o.status == (OutputStatus::EncumberedToBeReceived as i32) {
+                o.update(
+                    UpdateOutput {
+                        status: Some(OutputStatus::CancelledInbound),
+                        tx_id: None,
+                        spending_key: None,
+                        script_private_key: None,
+                        metadata_signature_nonce: None,
+                        metadata_signature_u_key: None,
+                    },
+                    &(*conn),
+                )?;
+            } else if o.status == (OutputStatus::EncumberedToBeSpent as i32) {
+                if parent_pending {
+                    // The parent has not been mined yet, so its change output does not exist on chain
+                    o.update(
+                        UpdateOutput {
+                            status: Some(OutputStatus::EncumberedToBeReceived),
+                            tx_id: Some(parent_tx_id),
+                            spending_key: None,
+                            script_private_key: None,
+                            metadata_signature_nonce: None,
+                            metadata_signature_u_key: None,
+                        },
+                        &(*conn),
+                    )?;
+                } else {
+                    o.update(
+                        UpdateOutput {
+                            status: Some(OutputStatus::Unspent),
+                            tx_id: None,
+                            spending_key: None,
+                            script_private_key: None,
+                            metadata_signature_nonce: None,
+                            metadata_signature_u_key: None,
+                        },
+                        &(*conn),
+                    )?;
+                    o.update_null(NullOutputSql { tx_id: None }, &(*conn))?;
+                }
+            }
+        }
+
+        pending_transaction.delete(&(*conn))?;
+
+        Ok(())
+    }
+
+    fn create_account(&self, name: &str) -> Result<WalletAccount, OutputManagerStorageError> {
+        let conn = self.database_connection.acquire_lock();
+
+        match WalletAccountSql::find_by_name(name, &(*conn)) {
+            Ok(_) => return Err(OutputManagerStorageError::DuplicateAccount(name.to_string())),
+            Err(OutputManagerStorageError::DieselError(DieselError::NotFound)) => (),
+            Err(e) => return Err(e),
+        }
+        let id = WalletAccountSql::index(&(*conn))?
+            .iter()
+            .map(|a| a.id)
+            .max()
+            .unwrap_or(DEFAULT_ACCOUNT_ID as i32) +
+            1;
+        NewWalletAccountSql {
+            id,
+            name: name.to_string(),
+            key_index: 0,
+            created_at: Utc::now().naive_utc(),
+        }
+        .commit(&(*conn))?;
+
+        Ok(WalletAccountSql::find_by_name(name, &(*conn))?.into())
+    }
+
+    fn restore_account(
+        &self,
+        account_id: AccountId,
+        key_index: u64,
+    ) -> Result<WalletAccount, OutputManagerStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        let account = match WalletAccountSql::find(account_id, &(*conn)) {
+            Ok(a) => a,
+            Err(OutputManagerStorageError::DieselError(DieselError::NotFound)) => {
+                let name = format!("account-{}", account_id);
+                if WalletAccountSql::find_by_name(&name, &(*conn)).is_ok() {
+                    return Err(OutputManagerStorageError::DuplicateAccount(name));
+                }
+                NewWalletAccountSql {
+                    id: account_id as i32,
+                    name,
+                    key_index: 0,
+                    created_at: Utc::now().naive_utc(),
+                }
+                .commit(&(*conn))?;
+                WalletAccountSql::find(account_id, &(*conn))?
+            },
+            Err(e) => return Err(e),
+        };
+        if (account.key_index as u64) < key_index {
+            account.set_key_index(key_index as i64, &(*conn))?;
+        }
+
+        Ok(WalletAccountSql::find(account_id, &(*conn))?.into())
+    }
+
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, OutputManagerStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        Ok(WalletAccountSql::index(&(*conn))?
+            .into_iter()
+            .map(WalletAccount::from)
+            .collect())
+    }
+
+    fn increment_account_key_index(&self, account_id: AccountId) -> Result<u64, OutputManagerStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        let account = match WalletAccountSql::find(account_id, &(*conn)) {
+            Ok(a) => a,
+            Err(OutputManagerStorageError::DieselError(DieselError::NotFound)) => {
+                return Err(OutputManagerStorageError::ValueNotFound)
+            },
+            Err(e) => return Err(e),
+        };
+        let key_index = account.key_index + 1;
+        account.set_key_index(key_index, &(*conn))?;
+        Ok(key_index as u64)
+    }
+
     fn cancel_pending_transaction_at_block_height(&self, block_height: u64) -> Result<(), OutputManagerStorageError> {
         let pending_txs;
         {
@@ -848,6 +1018,49 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     }
 }
 
+/// Re-encrypt the outputs, key manager state and known one-sided payment scripts with a new cipher, returning the
+/// number of rows that were re-encrypted. The caller is expected to hold a database transaction on the connection.
+pub(crate) fn rotate_encryption(
+    conn: &SqliteConnection,
+    current_cipher: &Aes256Gcm,
+    new_cipher: &Aes256Gcm,
+) -> Result<usize, OutputManagerStorageError> {
+    let mut rows = 0;
+    let mut outputs = OutputSql::index(conn)?;
+    for o in outputs.iter_mut() {
+        o.decrypt(current_cipher)
+            .map_err(|_| OutputManagerStorageError::AeadError("Decryption Error".to_string()))?;
+        o.encrypt(new_cipher)
+            .map_err(|_| OutputManagerStorageError::AeadError("Encryption Error".to_string()))?;
+        o.update_encryption(conn)?;
+        rows += 1;
+    }
+
+    let mut key_manager_state = KeyManagerStateSql::get_state(conn)?;
+    key_manager_state
+        .decrypt(current_cipher)
+        .map_err(|_| OutputManagerStorageError::AeadError("Decryption Error".to_string()))?;
+    key_manager_state
+        .encrypt(new_cipher)
+        .map_err(|_| OutputManagerStorageError::AeadError("Encryption Error".to_string()))?;
+    key_manager_state.set_state(conn)?;
+    rows += 1;
+
+    let mut known_one_sided_payment_scripts = KnownOneSidedPaymentScriptSql::index(conn)?;
+    for script in known_one_sided_payment_scripts.iter_mut() {
+        script
+            .decrypt(current_cipher)
+            .map_err(|_| OutputManagerStorageError::AeadError("Decryption Error".to_string()))?;
+        script
+            .encrypt(new_cipher)
+            .map_err(|_| OutputManagerStorageError::AeadError("Encryption Error".to_string()))?;
+        script.update_encryption(conn)?;
+        rows += 1;
+    }
+
+    Ok(rows)
+}
+
 /// A utility function to construct a PendingTransactionOutputs structure for a TxId, set of Outputs and a Timestamp
 fn pending_transaction_outputs_from_sql_outputs(
     tx_id: TxId,
@@ -885,6 +1098,7 @@ impl TryFrom<i32> for OutputStatus {
             3 => Ok(OutputStatus::EncumberedToBeSpent),
             4 => Ok(OutputStatus::Invalid),
             5 => Ok(OutputStatus::CancelledInbound),
+            6 => Ok(OutputStatus::RefundableHtlc),
             _ => Err(OutputManagerStorageError::ConversionError),
         }
     }
@@ -910,6 +1124,8 @@ struct NewOutputSql {
     metadata_signature_nonce: Vec<u8>,
     metadata_signature_u_key: Vec<u8>,
     metadata_signature_v_key: Vec<u8>,
+    encrypted_memo: Option<Vec<u8>>,
+    account_id: i64,
 }
 
 impl NewOutputSql {
@@ -934,6 +1150,8 @@ impl NewOutputSql {
             metadata_signature_nonce: output.unblinded_output.metadata_signature.public_nonce().to_vec(),
             metadata_signature_u_key: output.unblinded_output.metadata_signature.u().to_vec(),
             metadata_signature_v_key: output.unblinded_output.metadata_signature.v().to_vec(),
+            encrypted_memo: Some(output.unblinded_output.features.encrypted_memo.clone()).filter(|m| !m.is_empty()),
+            account_id: output.account_id as i64,
         })
     }
 
@@ -977,6 +1195,8 @@ struct OutputSql {
     metadata_signature_nonce: Vec<u8>,
     metadata_signature_u_key: Vec<u8>,
     metadata_signature_v_key: Vec<u8>,
+    encrypted_memo: Option<Vec<u8>>,
+    account_id: i64,
 }
 
 impl OutputSql {
@@ -1154,6 +1374,7 @@ impl TryFrom<OutputSql> for DbUnblindedOutput {
             OutputFeatures {
                 flags: OutputFlags::from_bits(o.flags as u8).ok_or(OutputManagerStorageError::ConversionError)?,
                 maturity: o.maturity as u64,
+                encrypted_memo: o.encrypted_memo.unwrap_or_default(),
             },
             TariScript::from_bytes(o.script.as_slice())?,
             ExecutionStack::from_bytes(o.input_data.as_slice())?,
@@ -1217,6 +1438,7 @@ impl TryFrom<OutputSql> for DbUnblindedOutput {
             commitment,
             unblinded_output,
             hash,
+            account_id: o.account_id as AccountId,
         })
     }
 }
@@ -1253,6 +1475,8 @@ impl From<OutputSql> for NewOutputSql {
             metadata_signature_nonce: o.metadata_signature_nonce,
             metadata_signature_u_key: o.metadata_signature_u_key,
             metadata_signature_v_key: o.metadata_signature_v_key,
+            encrypted_memo: o.encrypted_memo,
+            account_id: o.account_id,
         }
     }
 }
@@ -1564,6 +1788,78 @@ impl Encryptable<Aes256Gcm> for KeyManagerStateSql {
     }
 }
 
+#[derive(Clone, Debug, Insertable)]
+#[table_name = "wallet_accounts"]
+struct NewWalletAccountSql {
+    id: i32,
+    name: String,
+    key_index: i64,
+    created_at: NaiveDateTime,
+}
+
+impl NewWalletAccountSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::insert_into(wallet_accounts::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Identifiable, PartialEq)]
+#[table_name = "wallet_accounts"]
+struct WalletAccountSql {
+    id: i32,
+    name: String,
+    key_index: i64,
+    created_at: NaiveDateTime,
+}
+
+impl WalletAccountSql {
+    /// Return all accounts ordered by their id
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<WalletAccountSql>, OutputManagerStorageError> {
+        Ok(wallet_accounts::table.order(wallet_accounts::id.asc()).load(conn)?)
+    }
+
+    pub fn find(account_id: AccountId, conn: &SqliteConnection) -> Result<WalletAccountSql, OutputManagerStorageError> {
+        Ok(wallet_accounts::table
+            .filter(wallet_accounts::id.eq(account_id as i32))
+            .first::<WalletAccountSql>(conn)?)
+    }
+
+    pub fn find_by_name(name: &str, conn: &SqliteConnection) -> Result<WalletAccountSql, OutputManagerStorageError> {
+        Ok(wallet_accounts::table
+            .filter(wallet_accounts::name.eq(name))
+            .first::<WalletAccountSql>(conn)?)
+    }
+
+    pub fn set_key_index(&self, key_index: i64, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        let num_updated = diesel::update(wallet_accounts::table.filter(wallet_accounts::id.eq(self.id)))
+            .set(wallet_accounts::key_index.eq(key_index))
+            .execute(conn)?;
+
+        if num_updated == 0 {
+            return Err(OutputManagerStorageError::UnexpectedResult(
+                "Database update error".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<WalletAccountSql> for WalletAccount {
+    fn from(a: WalletAccountSql) -> Self {
+        Self {
+            id: a.id as AccountId,
+            name: a.name,
+            key_index: a.key_index as u64,
+            created_at: a.created_at,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Queryable, Insertable, Identifiable, PartialEq, AsChangeset)]
 #[table_name = "known_one_sided_payment_scripts"]
 #[primary_key(script_hash)]
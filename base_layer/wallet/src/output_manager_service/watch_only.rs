@@ -0,0 +1,150 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Watch-only wallets. A watch-only wallet holds the rewind keys of another wallet instead of its master key, so it can
+//! find the outputs of that wallet on chain and read their values, but it cannot derive the script private keys that
+//! spending them requires. It can reserve outputs for a transaction that the wallet holding the master key signs
+//! offline. With the stealth view key of the watched wallet it also detects the stealth payments to that wallet.
+
+use crate::output_manager_service::{error::OutputManagerError, stealth::StealthViewKey};
+use tari_common_types::types::{PrivateKey, PublicKey};
+use tari_comms::types::CommsSecretKey;
+use tari_core::transactions::transaction_protocol::RewindData;
+use tari_crypto::{
+    range_proof::REWIND_USER_MESSAGE_LENGTH,
+    tari_utilities::{hex::Hex, ByteArray},
+};
+
+const KEY_SIZE: usize = 32;
+
+/// The keys that a watch-only wallet uses to rewind the outputs of the wallet that it watches
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchOnlyKeys {
+    pub rewind_key: PrivateKey,
+    pub rewind_blinding_key: PrivateKey,
+    /// The view key of the stealth address of the watched wallet, which keys exported before stealth addresses were
+    /// supported do not have
+    pub stealth_view_key: Option<StealthViewKey>,
+}
+
+impl WatchOnlyKeys {
+    pub fn new(rewind_key: PrivateKey, rewind_blinding_key: PrivateKey) -> Self {
+        Self {
+            rewind_key,
+            rewind_blinding_key,
+            stealth_view_key: None,
+        }
+    }
+
+    pub fn with_stealth_view_key(mut self, stealth_view_key: StealthViewKey) -> Self {
+        self.stealth_view_key = Some(stealth_view_key);
+        self
+    }
+
+    /// The rewind data that the watched wallet attaches to its outputs
+    pub fn rewind_data(&self) -> RewindData {
+        RewindData {
+            rewind_key: self.rewind_key.clone(),
+            rewind_blinding_key: self.rewind_blinding_key.clone(),
+            proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
+        }
+    }
+
+    /// Encode the keys as a single hex string, the rewind key followed by the rewind blinding key and, if present, the
+    /// stealth scan key and the stealth spend public key
+    pub fn to_hex(&self) -> String {
+        let mut hex = format!("{}{}", self.rewind_key.to_hex(), self.rewind_blinding_key.to_hex());
+        if let Some(view_key) = &self.stealth_view_key {
+            hex.push_str(&view_key.scan_key.to_hex());
+            hex.push_str(&view_key.spend_public_key.to_hex());
+        }
+        hex
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Self, OutputManagerError> {
+        let bytes = Vec::<u8>::from_hex(hex.trim())
+            .map_err(|e| OutputManagerError::ConversionError(format!("Invalid watch-only keys: {}", e)))?;
+        if bytes.len() != 2 * KEY_SIZE && bytes.len() != 4 * KEY_SIZE {
+            return Err(OutputManagerError::ConversionError(format!(
+                "Watch-only keys must be {} or {} bytes long",
+                2 * KEY_SIZE,
+                4 * KEY_SIZE
+            )));
+        }
+        let keys = Self::new(
+            PrivateKey::from_bytes(&bytes[..KEY_SIZE])?,
+            PrivateKey::from_bytes(&bytes[KEY_SIZE..2 * KEY_SIZE])?,
+        );
+        if bytes.len() == 2 * KEY_SIZE {
+            return Ok(keys);
+        }
+        Ok(keys.with_stealth_view_key(StealthViewKey::new(
+            PrivateKey::from_bytes(&bytes[2 * KEY_SIZE..3 * KEY_SIZE])?,
+            PublicKey::from_bytes(&bytes[3 * KEY_SIZE..])?,
+        )))
+    }
+}
+
+/// The keys that the output manager derives its keys from
+#[derive(Clone)]
+pub enum OutputManagerKeys {
+    /// The master secret key of a wallet that can spend its outputs
+    MasterKey(CommsSecretKey),
+    /// The master secret key of a wallet protected by a seed words passphrase, which is derived from the passphrase and
+    /// the seed key that its seed words encode
+    Passphrase {
+        master_key: CommsSecretKey,
+        seed_key: CommsSecretKey,
+    },
+    /// The rewind keys of a watch-only wallet
+    WatchOnly(WatchOnlyKeys),
+}
+
+impl From<CommsSecretKey> for OutputManagerKeys {
+    fn from(master_secret_key: CommsSecretKey) -> Self {
+        OutputManagerKeys::MasterKey(master_secret_key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+    #[test]
+    fn keys_round_trip_through_hex() {
+        let keys = WatchOnlyKeys::new(PrivateKey::random(&mut OsRng), PrivateKey::random(&mut OsRng));
+        assert_eq!(WatchOnlyKeys::from_hex(&keys.to_hex()).unwrap(), keys);
+        assert!(WatchOnlyKeys::from_hex(&keys.rewind_key.to_hex()).is_err());
+        assert!(WatchOnlyKeys::from_hex("not hex").is_err());
+
+        let view_key = StealthViewKey::new(
+            PrivateKey::random(&mut OsRng),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        );
+        let keys = keys.with_stealth_view_key(view_key);
+        assert_eq!(WatchOnlyKeys::from_hex(&keys.to_hex()).unwrap(), keys);
+        assert!(WatchOnlyKeys::from_hex(&keys.to_hex()[..6 * KEY_SIZE]).is_err());
+    }
+}
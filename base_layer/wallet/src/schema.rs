@@ -68,6 +68,50 @@ table! {
     }
 }
 
+table! {
+    multisig_legacy_sessions (session_id) {
+        session_id -> BigInt,
+        threshold -> Integer,
+        participants -> Binary,
+        timestamp -> Timestamp,
+    }
+}
+
+table! {
+    multisig_legacy_signing_rounds (signing_id) {
+        signing_id -> BigInt,
+        session_id -> BigInt,
+        signing_round -> Text,
+    }
+}
+
+table! {
+    multisig_outputs (commitment) {
+        commitment -> Binary,
+        session_id -> BigInt,
+        multisig_output -> Text,
+    }
+}
+
+table! {
+    multisig_sessions (session_id) {
+        session_id -> BigInt,
+        threshold -> Integer,
+        participants -> Binary,
+        timestamp -> Timestamp,
+        dealings -> Text,
+        accepted -> Bool,
+    }
+}
+
+table! {
+    multisig_signing_rounds (signing_id) {
+        signing_id -> BigInt,
+        session_id -> BigInt,
+        signing_round -> Text,
+    }
+}
+
 table! {
     outbound_transactions (tx_id) {
         tx_id -> BigInt,
@@ -102,6 +146,8 @@ table! {
         metadata_signature_nonce -> Binary,
         metadata_signature_u_key -> Binary,
         metadata_signature_v_key -> Binary,
+        encrypted_memo -> Nullable<Binary>,
+        account_id -> BigInt,
     }
 }
 
@@ -114,6 +160,40 @@ table! {
     }
 }
 
+table! {
+    transaction_accounts (tx_id, account_id) {
+        tx_id -> BigInt,
+        account_id -> BigInt,
+    }
+}
+
+table! {
+    transaction_cancellations (tx_id) {
+        tx_id -> BigInt,
+        reason -> Text,
+        cancelled_at -> Timestamp,
+        outputs_released -> Integer,
+    }
+}
+
+table! {
+    transaction_fee_bumps (tx_id) {
+        tx_id -> BigInt,
+        parent_tx_id -> BigInt,
+        fee_per_gram -> BigInt,
+        timestamp -> Timestamp,
+    }
+}
+
+table! {
+    wallet_accounts (id) {
+        id -> Integer,
+        name -> Text,
+        key_index -> BigInt,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     wallet_settings (key) {
         key -> Text,
@@ -128,8 +208,17 @@ allow_tables_to_appear_in_same_query!(
     inbound_transactions,
     key_manager_states,
     known_one_sided_payment_scripts,
+    multisig_legacy_sessions,
+    multisig_legacy_signing_rounds,
+    multisig_outputs,
+    multisig_sessions,
+    multisig_signing_rounds,
     outbound_transactions,
     outputs,
     pending_transaction_outputs,
+    transaction_accounts,
+    transaction_cancellations,
+    transaction_fee_bumps,
+    wallet_accounts,
     wallet_settings,
 );
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::error::WalletStorageError;
+use crate::{error::WalletStorageError, output_manager_service::watch_only::WatchOnlyKeys};
 use aes_gcm::Aes256Gcm;
 use log::*;
 use std::{
@@ -34,6 +34,7 @@ use tari_comms::{
     tor::TorIdentity,
     types::{CommsPublicKey, CommsSecretKey},
 };
+use tokio::sync::mpsc;
 
 const LOG_TARGET: &str = "wallet::database";
 
@@ -47,6 +48,50 @@ pub trait WalletBackend: Send + Sync + Clone {
     fn apply_encryption(&self, cipher: Aes256Gcm) -> Result<(), WalletStorageError>;
     /// Remove encryption from the backend.
     fn remove_encryption(&self) -> Result<(), WalletStorageError>;
+    /// Re-encrypt every encrypted part of the wallet database with a new cipher in a single database transaction, so
+    /// that no values are written in plaintext and a failure leaves the current encryption in place. Returns the
+    /// number of rows that were re-encrypted.
+    fn rotate_encryption(
+        &self,
+        current_cipher: Aes256Gcm,
+        new_cipher: Aes256Gcm,
+        progress: &mut dyn FnMut(EncryptionRotationProgress),
+    ) -> Result<usize, WalletStorageError>;
+}
+
+/// The parts of the wallet database that are re-encrypted, in order, when the encryption is rotated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EncryptionRotationStage {
+    WalletSettings,
+    Outputs,
+    Transactions,
+    Multisig,
+}
+
+impl Display for EncryptionRotationStage {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            EncryptionRotationStage::WalletSettings => write!(f, "wallet settings"),
+            EncryptionRotationStage::Outputs => write!(f, "outputs"),
+            EncryptionRotationStage::Transactions => write!(f, "transactions"),
+            EncryptionRotationStage::Multisig => write!(f, "multisig sessions and outputs"),
+        }
+    }
+}
+
+/// Progress events emitted while the wallet encryption is rotated
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncryptionRotationProgress {
+    Started(EncryptionRotationStage),
+    /// The stage has been re-encrypted, it is written together with the other stages once they have all completed
+    Completed {
+        stage: EncryptionRotationStage,
+        rows: usize,
+    },
+    /// Every stage has been written, from now on the wallet database can only be opened with the new passphrase
+    Committed {
+        rows: usize,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -58,6 +103,8 @@ pub enum DbKey {
     ClientKey(String),
     MasterSecretKey,
     MasterPublicKey,
+    WatchOnlyKeys,
+    SeedWordsKey,
 }
 
 pub enum DbValue {
@@ -69,6 +116,8 @@ pub enum DbValue {
     BaseNodeChainMetadata(ChainMetadata),
     MasterSecretKey(CommsSecretKey),
     MasterPublicKey(CommsPublicKey),
+    WatchOnlyKeys(WatchOnlyKeys),
+    SeedWordsKey(CommsSecretKey),
 }
 
 #[derive(Clone)]
@@ -79,6 +128,8 @@ pub enum DbKeyValuePair {
     MasterSecretKey(CommsSecretKey),
     CommsAddress(Multiaddr),
     CommsFeatures(PeerFeatures),
+    WatchOnlyKeys(WatchOnlyKeys),
+    SeedWordsKey(CommsSecretKey),
 }
 
 pub enum WriteOperation {
@@ -154,6 +205,58 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    /// The rewind keys of the wallet this database watches, if it was created as a watch-only wallet
+    pub async fn get_watch_only_keys(&self) -> Result<Option<WatchOnlyKeys>, WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        let c = tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::WatchOnlyKeys) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::WatchOnlyKeys(k))) => Ok(Some(k)),
+            Ok(Some(other)) => unexpected_result(DbKey::WatchOnlyKeys, other),
+            Err(e) => log_error(DbKey::WatchOnlyKeys, e),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(c)
+    }
+
+    pub async fn set_watch_only_keys(&self, keys: WatchOnlyKeys) -> Result<(), WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::WatchOnlyKeys(keys)))
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    /// The key encoded by the seed words of a wallet protected by a seed words passphrase. The master secret key of
+    /// such a wallet is derived from this key and the passphrase, so its seed words cannot be recovered from the master
+    /// secret key.
+    pub async fn get_seed_words_key(&self) -> Result<Option<CommsSecretKey>, WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        let c = tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::SeedWordsKey) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::SeedWordsKey(k))) => Ok(Some(k)),
+            Ok(Some(other)) => unexpected_result(DbKey::SeedWordsKey, other),
+            Err(e) => log_error(DbKey::SeedWordsKey, e),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(c)
+    }
+
+    pub async fn set_seed_words_key(&self, key: CommsSecretKey) -> Result<(), WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || db_clone.write(WriteOperation::Insert(DbKeyValuePair::SeedWordsKey(key))))
+            .await
+            .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
     pub async fn get_node_address(&self) -> Result<Option<Multiaddr>, WalletStorageError> {
         let db_clone = self.db.clone();
 
@@ -245,6 +348,26 @@ where T: WalletBackend + 'static
             .and_then(|inner_result| inner_result)
     }
 
+    pub async fn rotate_encryption(
+        &self,
+        current_cipher: Aes256Gcm,
+        new_cipher: Aes256Gcm,
+        progress: Option<mpsc::Sender<EncryptionRotationProgress>>,
+    ) -> Result<usize, WalletStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.rotate_encryption(current_cipher, new_cipher, &mut |event| {
+                if let Some(progress) = progress.as_ref() {
+                    // The receiver may have stopped listening, which does not affect the rotation
+                    let _ = progress.blocking_send(event);
+                }
+            })
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
     pub async fn set_client_key_value(&self, key: String, value: String) -> Result<(), WalletStorageError> {
         let db_clone = self.db.clone();
 
@@ -320,6 +443,8 @@ impl Display for DbKey {
             DbKey::CommsAddress => f.write_str(&"CommsAddress".to_string()),
             DbKey::CommsFeatures => f.write_str(&"Node features".to_string()),
             DbKey::TorId => f.write_str(&"TorId".to_string()),
+            DbKey::WatchOnlyKeys => f.write_str(&"WatchOnlyKeys".to_string()),
+            DbKey::SeedWordsKey => f.write_str(&"SeedWordsKey".to_string()),
             DbKey::ClientKey(k) => f.write_str(&format!("ClientKey: {:?}", k)),
             DbKey::BaseNodeChainMetadata => f.write_str(&"Last seen Chain metadata from base node".to_string()),
         }
@@ -336,6 +461,8 @@ impl Display for DbValue {
             DbValue::CommsFeatures(_) => f.write_str(&"Node features".to_string()),
             DbValue::CommsAddress(_) => f.write_str(&"Comms Address".to_string()),
             DbValue::TorId(v) => f.write_str(&format!("Tor ID: {}", v)),
+            DbValue::WatchOnlyKeys(_) => f.write_str(&"Watch-only keys".to_string()),
+            DbValue::SeedWordsKey(_) => f.write_str(&"Seed words key".to_string()),
             DbValue::BaseNodeChainMetadata(v) => f.write_str(&format!("Last seen Chain metadata from base node:{}", v)),
         }
     }
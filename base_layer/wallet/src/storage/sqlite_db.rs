@@ -22,11 +22,22 @@
 
 use crate::{
     error::WalletStorageError,
+    multisig_service::storage::sqlite_db as multisig_sqlite_db,
+    output_manager_service::{storage::sqlite_db as output_manager_sqlite_db, watch_only::WatchOnlyKeys},
     schema::{client_key_values, wallet_settings},
     storage::{
-        database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
+        database::{
+            DbKey,
+            DbKeyValuePair,
+            DbValue,
+            EncryptionRotationProgress,
+            EncryptionRotationStage,
+            WalletBackend,
+            WriteOperation,
+        },
         sqlite_utilities::WalletDbConnection,
     },
+    transaction_service::storage::sqlite_db as transaction_sqlite_db,
     util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable, AES_NONCE_BYTES},
 };
 use aes_gcm::{
@@ -63,6 +74,7 @@ const LOG_TARGET: &str = "wallet::storage::sqlite_db";
 pub struct WalletSqliteDatabase {
     database_connection: WalletDbConnection,
     cipher: Arc<RwLock<Option<Aes256Gcm>>>,
+    backend_ciphers: Vec<Arc<RwLock<Option<Aes256Gcm>>>>,
 }
 impl WalletSqliteDatabase {
     pub fn new(database_connection: WalletDbConnection, cipher: Option<Aes256Gcm>) -> Result<Self, WalletStorageError> {
@@ -71,9 +83,17 @@ impl WalletSqliteDatabase {
         Ok(Self {
             database_connection,
             cipher: Arc::new(RwLock::new(cipher)),
+            backend_ciphers: Vec::new(),
         })
     }
 
+    /// The ciphers of the other service backends that share this database connection. They are switched to the new
+    /// cipher once the encryption of the whole database has been rotated.
+    pub(crate) fn with_backend_ciphers(mut self, backend_ciphers: Vec<Arc<RwLock<Option<Aes256Gcm>>>>) -> Self {
+        self.backend_ciphers = backend_ciphers;
+        self
+    }
+
     fn set_master_secret_key(
         &self,
         secret_key: &CommsSecretKey,
@@ -197,6 +217,76 @@ impl WalletSqliteDatabase {
         }
     }
 
+    fn set_watch_only_keys(&self, keys: WatchOnlyKeys, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        match cipher.as_ref() {
+            None => {
+                WalletSettingSql::new(DbKey::WatchOnlyKeys.to_string(), keys.to_hex()).set(conn)?;
+            },
+            Some(cipher) => {
+                let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(cipher, keys.to_hex().into_bytes())
+                    .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+                WalletSettingSql::new(DbKey::WatchOnlyKeys.to_string(), ciphertext_integral_nonce.to_hex())
+                    .set(conn)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    fn get_watch_only_keys(&self, conn: &SqliteConnection) -> Result<Option<WatchOnlyKeys>, WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if let Some(key_str) = WalletSettingSql::get(DbKey::WatchOnlyKeys.to_string(), conn)? {
+            let keys_hex = match cipher.as_ref() {
+                None => key_str,
+                Some(cipher) => {
+                    let decrypted_key_bytes = decrypt_bytes_integral_nonce(cipher, from_hex(&key_str)?)
+                        .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+                    String::from_utf8(decrypted_key_bytes)
+                        .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?
+                },
+            };
+            let keys =
+                WatchOnlyKeys::from_hex(&keys_hex).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            Ok(Some(keys))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn set_seed_words_key(&self, key: &CommsSecretKey, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        match cipher.as_ref() {
+            None => {
+                WalletSettingSql::new(DbKey::SeedWordsKey.to_string(), key.to_hex()).set(conn)?;
+            },
+            Some(cipher) => {
+                let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(cipher, key.to_vec())
+                    .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+                WalletSettingSql::new(DbKey::SeedWordsKey.to_string(), ciphertext_integral_nonce.to_hex()).set(conn)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    fn get_seed_words_key(&self, conn: &SqliteConnection) -> Result<Option<CommsSecretKey>, WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if let Some(key_str) = WalletSettingSql::get(DbKey::SeedWordsKey.to_string(), conn)? {
+            let key = match cipher.as_ref() {
+                None => CommsSecretKey::from_hex(key_str.as_str())?,
+                Some(cipher) => {
+                    let decrypted_key_bytes = decrypt_bytes_integral_nonce(cipher, from_hex(&key_str)?)
+                        .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+                    CommsSecretKey::from_bytes(decrypted_key_bytes.as_slice())?
+                },
+            };
+            Ok(Some(key))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn set_chain_metadata(&self, chain: ChainMetadata, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
         let bytes = bincode::serialize(&chain).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
         WalletSettingSql::new(DbKey::BaseNodeChainMetadata.to_string(), bytes.to_hex()).set(conn)?;
@@ -222,6 +312,12 @@ impl WalletSqliteDatabase {
             DbKeyValuePair::TorId(node_id) => {
                 self.set_tor_id(node_id, &(*conn))?;
             },
+            DbKeyValuePair::WatchOnlyKeys(keys) => {
+                self.set_watch_only_keys(keys, &(*conn))?;
+            },
+            DbKeyValuePair::SeedWordsKey(key) => {
+                self.set_seed_words_key(&key, &(*conn))?;
+            },
             DbKeyValuePair::BaseNodeChainMetadata(metadata) => {
                 self.set_chain_metadata(metadata, &(*conn))?;
             },
@@ -275,6 +371,12 @@ impl WalletSqliteDatabase {
             DbKey::TorId => {
                 let _ = WalletSettingSql::clear(DbKey::TorId.to_string(), &conn)?;
             },
+            DbKey::WatchOnlyKeys => {
+                let _ = WalletSettingSql::clear(DbKey::WatchOnlyKeys.to_string(), &conn)?;
+            },
+            DbKey::SeedWordsKey => {
+                let _ = WalletSettingSql::clear(DbKey::SeedWordsKey.to_string(), &conn)?;
+            },
         };
         Ok(None)
     }
@@ -302,6 +404,8 @@ impl WalletBackend for WalletSqliteDatabase {
             },
             DbKey::CommsAddress => self.get_comms_address(&conn)?.map(DbValue::CommsAddress),
             DbKey::TorId => self.get_tor_id(&conn)?,
+            DbKey::WatchOnlyKeys => self.get_watch_only_keys(&conn)?.map(DbValue::WatchOnlyKeys),
+            DbKey::SeedWordsKey => self.get_seed_words_key(&conn)?.map(DbValue::SeedWordsKey),
             DbKey::CommsFeatures => self.get_comms_features(&conn)?.map(DbValue::CommsFeatures),
             DbKey::BaseNodeChainMetadata => self.get_chain_metadata(&conn)?.map(DbValue::BaseNodeChainMetadata),
         };
@@ -351,6 +455,21 @@ impl WalletBackend for WalletSqliteDatabase {
             WalletSettingSql::new(DbKey::TorId.to_string(), ciphertext_integral_nonce.to_hex()).set(&conn)?;
         }
 
+        // Encrypt the watch-only keys if present
+        if let Some(v) = WalletSettingSql::get(DbKey::WatchOnlyKeys.to_string(), &conn)? {
+            let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(&cipher, v.into_bytes())
+                .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+            WalletSettingSql::new(DbKey::WatchOnlyKeys.to_string(), ciphertext_integral_nonce.to_hex()).set(&conn)?;
+        }
+
+        // Encrypt the seed words key if present
+        if let Some(v) = WalletSettingSql::get(DbKey::SeedWordsKey.to_string(), &conn)? {
+            let key = CommsSecretKey::from_hex(&v)?;
+            let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(&cipher, key.to_vec())
+                .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+            WalletSettingSql::new(DbKey::SeedWordsKey.to_string(), ciphertext_integral_nonce.to_hex()).set(&conn)?;
+        }
+
         (*current_cipher) = Some(cipher);
 
         Ok(())
@@ -395,11 +514,155 @@ impl WalletBackend for WalletSqliteDatabase {
             WalletSettingSql::new(DbKey::TorId.to_string(), tor_string).set(&conn)?;
         }
 
+        // remove watch-only keys encryption if present
+        if let Some(v) = WalletSettingSql::get(DbKey::WatchOnlyKeys.to_string(), &conn)? {
+            let decrypted_key_bytes = decrypt_bytes_integral_nonce(&cipher, from_hex(v.as_str())?)
+                .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+            let keys_hex = String::from_utf8(decrypted_key_bytes)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            WalletSettingSql::new(DbKey::WatchOnlyKeys.to_string(), keys_hex).set(&conn)?;
+        }
+
+        // remove seed words key encryption if present
+        if let Some(v) = WalletSettingSql::get(DbKey::SeedWordsKey.to_string(), &conn)? {
+            let decrypted_key_bytes = decrypt_bytes_integral_nonce(&cipher, from_hex(v.as_str())?)
+                .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+            let key = CommsSecretKey::from_bytes(decrypted_key_bytes.as_slice())?;
+            WalletSettingSql::new(DbKey::SeedWordsKey.to_string(), key.to_hex()).set(&conn)?;
+        }
+
         // Now that all the decryption has been completed we can safely remove the cipher fully
         let _ = (*current_cipher).take();
 
         Ok(())
     }
+
+    fn rotate_encryption(
+        &self,
+        current_cipher: Aes256Gcm,
+        new_cipher: Aes256Gcm,
+        progress: &mut dyn FnMut(EncryptionRotationProgress),
+    ) -> Result<usize, WalletStorageError> {
+        // The connection is locked before the ciphers, in the same order as every other operation on the database
+        let conn = self.database_connection.acquire_lock();
+        let mut cipher = acquire_write_lock!(self.cipher);
+        if cipher.is_none() {
+            return Err(WalletStorageError::NotEncrypted);
+        }
+        let mut backend_ciphers = self
+            .backend_ciphers
+            .iter()
+            .map(|c| acquire_write_lock!(c))
+            .collect::<Vec<_>>();
+
+        let rows = conn.transaction::<_, WalletStorageError, _>(|| {
+            let mut rows = 0;
+            for stage in ENCRYPTION_ROTATION_STAGES.iter().copied() {
+                progress(EncryptionRotationProgress::Started(stage));
+                let stage_rows = match stage {
+                    EncryptionRotationStage::WalletSettings => {
+                        rotate_wallet_settings_encryption(&conn, &current_cipher, &new_cipher)
+                    },
+                    EncryptionRotationStage::Outputs => {
+                        output_manager_sqlite_db::rotate_encryption(&conn, &current_cipher, &new_cipher)
+                            .map_err(|e| WalletStorageError::EncryptionRotationError(stage, e.to_string()))
+                    },
+                    EncryptionRotationStage::Transactions => {
+                        transaction_sqlite_db::rotate_encryption(&conn, &current_cipher, &new_cipher)
+                            .map_err(|e| WalletStorageError::EncryptionRotationError(stage, e.to_string()))
+                    },
+                    EncryptionRotationStage::Multisig => {
+                        multisig_sqlite_db::rotate_encryption(&conn, &current_cipher, &new_cipher)
+                            .map_err(|e| WalletStorageError::EncryptionRotationError(stage, e.to_string()))
+                    },
+                }?;
+                debug!(target: LOG_TARGET, "Re-encrypted {} rows of {}", stage_rows, stage);
+                progress(EncryptionRotationProgress::Completed {
+                    stage,
+                    rows: stage_rows,
+                });
+                rows += stage_rows;
+            }
+            Ok(rows)
+        })?;
+
+        // The transaction has been committed so every backend can use the new cipher from now on
+        for backend_cipher in backend_ciphers.iter_mut() {
+            (**backend_cipher) = Some(new_cipher.clone());
+        }
+        (*cipher) = Some(new_cipher);
+        progress(EncryptionRotationProgress::Committed { rows });
+
+        Ok(rows)
+    }
+}
+
+/// The parts of the wallet database that are re-encrypted when the encryption is rotated, in the order they are
+/// re-encrypted
+const ENCRYPTION_ROTATION_STAGES: [EncryptionRotationStage; 4] = [
+    EncryptionRotationStage::WalletSettings,
+    EncryptionRotationStage::Outputs,
+    EncryptionRotationStage::Transactions,
+    EncryptionRotationStage::Multisig,
+];
+
+/// Re-encrypt the encrypted wallet settings and client key values with a new cipher, returning the number of rows
+/// that were re-encrypted. The caller is expected to hold a database transaction on the connection.
+fn rotate_wallet_settings_encryption(
+    conn: &SqliteConnection,
+    current_cipher: &Aes256Gcm,
+    new_cipher: &Aes256Gcm,
+) -> Result<usize, WalletStorageError> {
+    let mut rows = 0;
+    let secret_key_str = match WalletSettingSql::get(DbKey::MasterSecretKey.to_string(), conn)? {
+        None => return Err(WalletStorageError::ValueNotFound(DbKey::MasterSecretKey)),
+        Some(sk) => sk,
+    };
+    let secret_key_bytes = decrypt_bytes_integral_nonce(current_cipher, from_hex(secret_key_str.as_str())?)
+        .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+    let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(new_cipher, secret_key_bytes)
+        .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+    WalletSettingSql::new(DbKey::MasterSecretKey.to_string(), ciphertext_integral_nonce.to_hex()).set(conn)?;
+    rows += 1;
+
+    let mut client_key_values = ClientKeyValueSql::index(conn)?;
+    for ckv in client_key_values.iter_mut() {
+        ckv.decrypt(current_cipher)
+            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+        ckv.encrypt(new_cipher)
+            .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+        ckv.set(conn)?;
+        rows += 1;
+    }
+
+    if let Some(v) = WalletSettingSql::get(DbKey::TorId.to_string(), conn)? {
+        let tor_id_bytes = decrypt_bytes_integral_nonce(current_cipher, from_hex(v.as_str())?)
+            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+        let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(new_cipher, tor_id_bytes)
+            .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+        WalletSettingSql::new(DbKey::TorId.to_string(), ciphertext_integral_nonce.to_hex()).set(conn)?;
+        rows += 1;
+    }
+
+    if let Some(v) = WalletSettingSql::get(DbKey::WatchOnlyKeys.to_string(), conn)? {
+        let keys_bytes = decrypt_bytes_integral_nonce(current_cipher, from_hex(v.as_str())?)
+            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+        let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(new_cipher, keys_bytes)
+            .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+        WalletSettingSql::new(DbKey::WatchOnlyKeys.to_string(), ciphertext_integral_nonce.to_hex()).set(conn)?;
+        rows += 1;
+    }
+
+    if let Some(v) = WalletSettingSql::get(DbKey::SeedWordsKey.to_string(), conn)? {
+        let key_bytes = decrypt_bytes_integral_nonce(current_cipher, from_hex(v.as_str())?)
+            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e.to_string())))?;
+        let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(new_cipher, key_bytes)
+            .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+        WalletSettingSql::new(DbKey::SeedWordsKey.to_string(), ciphertext_integral_nonce.to_hex()).set(conn)?;
+        rows += 1;
+    }
+
+    Ok(rows)
 }
 
 /// Confirm if database is encrypted or not and if a cipher is provided confirm the cipher is correct.
@@ -606,16 +869,20 @@ impl Encryptable<Aes256Gcm> for ClientKeyValueSql {
 
 #[cfg(test)]
 mod test {
-    use crate::storage::{
-        database::{DbKey, DbValue, WalletBackend},
-        sqlite_db::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
-        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    use crate::{
+        output_manager_service::watch_only::WatchOnlyKeys,
+        storage::{
+            database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
+            sqlite_db::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
+            sqlite_utilities::run_migration_and_create_sqlite_connection,
+        },
     };
     use aes_gcm::{
         aead::{generic_array::GenericArray, Aead, NewAead},
         Aes256Gcm,
     };
     use rand::{rngs::OsRng, RngCore};
+    use tari_common_types::types::PrivateKey;
     use tari_comms::types::{CommsPublicKey, CommsSecretKey};
     use tari_crypto::{
         keys::{PublicKey, SecretKey},
@@ -826,6 +1093,45 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_watch_only_keys_survive_encryption() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}/{}", db_folder, db_name);
+        let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+        let db = WalletSqliteDatabase::new(connection.clone(), None).unwrap();
+        {
+            let conn = connection.acquire_lock();
+            db.set_master_secret_key(&CommsSecretKey::random(&mut OsRng), &conn)
+                .unwrap();
+        }
+        let keys = WatchOnlyKeys::new(PrivateKey::random(&mut OsRng), PrivateKey::random(&mut OsRng));
+        db.write(WriteOperation::Insert(DbKeyValuePair::WatchOnlyKeys(keys.clone())))
+            .unwrap();
+
+        let key = GenericArray::from_slice(b"an example very very secret key.");
+        db.apply_encryption(Aes256Gcm::new(key)).unwrap();
+        {
+            let conn = connection.acquire_lock();
+            let stored = WalletSettingSql::get(DbKey::WatchOnlyKeys.to_string(), &conn)
+                .unwrap()
+                .unwrap();
+            assert_ne!(stored, keys.to_hex());
+        }
+        match db.fetch(&DbKey::WatchOnlyKeys).unwrap().unwrap() {
+            DbValue::WatchOnlyKeys(k) => assert_eq!(k, keys),
+            _ => panic!("Should be able to read the watch-only keys"),
+        }
+
+        db.remove_encryption().unwrap();
+        match db.fetch(&DbKey::WatchOnlyKeys).unwrap().unwrap() {
+            DbValue::WatchOnlyKeys(k) => assert_eq!(k, keys),
+            _ => panic!("Should be able to read the watch-only keys"),
+        }
+    }
+
     #[test]
     fn test_client_key_value_store() {
         let db_name = format!("{}.sqlite3", string(8).as_str());
@@ -23,6 +23,7 @@
 use crate::{
     contacts_service::storage::sqlite_db::ContactsServiceSqliteDatabase,
     error::WalletStorageError,
+    multisig_service::storage::sqlite_db::MultisigServiceSqliteDatabase,
     output_manager_service::storage::sqlite_db::OutputManagerSqliteDatabase,
     storage::{database::WalletDatabase, sqlite_db::WalletSqliteDatabase},
     transaction_service::storage::sqlite_db::TransactionServiceSqliteDatabase,
@@ -146,6 +147,7 @@ pub fn initialize_sqlite_database_backends(
         TransactionServiceSqliteDatabase,
         OutputManagerSqliteDatabase,
         ContactsServiceSqliteDatabase,
+        MultisigServiceSqliteDatabase,
     ),
     WalletStorageError,
 > {
@@ -165,13 +167,20 @@ pub fn initialize_sqlite_database_backends(
 
     let wallet_backend = WalletSqliteDatabase::new(connection.clone(), cipher.clone())?;
     let transaction_backend = TransactionServiceSqliteDatabase::new(connection.clone(), cipher.clone());
-    let output_manager_backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher);
-    let contacts_backend = ContactsServiceSqliteDatabase::new(connection);
+    let output_manager_backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher.clone());
+    let contacts_backend = ContactsServiceSqliteDatabase::new(connection.clone());
+    let multisig_backend = MultisigServiceSqliteDatabase::new(connection, cipher);
+    let wallet_backend = wallet_backend.with_backend_ciphers(vec![
+        transaction_backend.cipher(),
+        output_manager_backend.cipher(),
+        multisig_backend.cipher(),
+    ]);
 
     Ok((
         wallet_backend,
         transaction_backend,
         output_manager_backend,
         contacts_backend,
+        multisig_backend,
     ))
 }
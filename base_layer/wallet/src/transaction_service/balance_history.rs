@@ -0,0 +1,181 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! A time series of the wallet's balance, derived from the transaction history rather than recorded separately, so
+//! that it also covers the time before the wallet was upgraded and is rebuilt by wallet recovery.
+//!
+//! Only transactions that are mined or imported are counted. The balance at the end of a day is the sum of what they
+//! added to or took from the wallet up to that day.
+
+use crate::transaction_service::storage::models::{CompletedTransaction, TransactionDirection, TransactionStatus};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tari_core::transactions::tari_amount::MicroTari;
+
+/// The balance of the wallet at the end of a day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    pub date: NaiveDate,
+    pub balance: MicroTari,
+}
+
+impl fmt::Display for BalanceSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.date, self.balance)
+    }
+}
+
+/// Returns one snapshot for each of the `days` days up to and including `end`, oldest first
+pub fn balance_history<'a, I>(transactions: I, end: NaiveDate, days: u32) -> Vec<BalanceSnapshot>
+where I: IntoIterator<Item = &'a CompletedTransaction> {
+    if days == 0 {
+        return Vec::new();
+    }
+    let start = end - Duration::days(i64::from(days) - 1);
+    let changes = transactions
+        .into_iter()
+        .filter_map(|tx| balance_change(tx).map(|change| (tx.timestamp.date(), change)))
+        .collect::<Vec<_>>();
+
+    let mut balance = changes
+        .iter()
+        .filter(|(date, _)| *date < start)
+        .map(|(_, change)| change)
+        .sum::<i128>();
+    (0..days)
+        .map(|i| start + Duration::days(i64::from(i)))
+        .map(|date| {
+            balance += changes
+                .iter()
+                .filter(|(d, _)| *d == date)
+                .map(|(_, change)| change)
+                .sum::<i128>();
+            BalanceSnapshot {
+                date,
+                balance: MicroTari::from(balance.max(0) as u64),
+            }
+        })
+        .collect()
+}
+
+/// The amount that a transaction added to (positive) or took from (negative) the wallet's balance, or None if it
+/// does not count towards the balance history
+pub fn balance_change(tx: &CompletedTransaction) -> Option<i128> {
+    if tx.cancelled || !tx.valid {
+        return None;
+    }
+    if !matches!(
+        tx.status,
+        TransactionStatus::MinedUnconfirmed | TransactionStatus::MinedConfirmed | TransactionStatus::Imported
+    ) {
+        return None;
+    }
+    let amount = i128::from(u64::from(tx.amount));
+    let fee = i128::from(u64::from(tx.fee));
+    let change = match tx.direction {
+        TransactionDirection::Outbound => -(amount + fee),
+        // Coin splits and transfers between accounts are payments to this wallet, they only cost the fee
+        _ if tx.source_public_key == tx.destination_public_key && tx.coinbase_block_height.is_none() => -fee,
+        _ => amount,
+    };
+    Some(change)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDateTime;
+    use rand::rngs::OsRng;
+    use tari_common_types::types::{PrivateKey, PublicKey};
+    use tari_core::transactions::transaction::Transaction;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    fn transaction(
+        day: u32,
+        amount: u64,
+        fee: u64,
+        direction: TransactionDirection,
+        status: TransactionStatus,
+        destination: PublicKey,
+    ) -> CompletedTransaction {
+        CompletedTransaction::new(
+            u64::from(day),
+            PublicKey::default(),
+            destination,
+            MicroTari::from(amount),
+            MicroTari::from(fee),
+            Transaction::new(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                PrivateKey::default(),
+                PrivateKey::default(),
+            ),
+            status,
+            String::new(),
+            NaiveDateTime::new(NaiveDate::from_ymd(2021, 10, day), Default::default()),
+            direction,
+            None,
+        )
+    }
+
+    #[test]
+    fn it_derives_the_balance_history() {
+        let (_, other) = PublicKey::random_keypair(&mut OsRng);
+        let mined = TransactionStatus::MinedConfirmed;
+        let mut cancelled = transaction(4, 500, 0, TransactionDirection::Inbound, mined.clone(), other.clone());
+        cancelled.cancelled = true;
+        let transactions = vec![
+            transaction(1, 1000, 0, TransactionDirection::Inbound, mined.clone(), other.clone()),
+            transaction(3, 300, 10, TransactionDirection::Outbound, mined.clone(), other.clone()),
+            transaction(
+                3,
+                200,
+                0,
+                TransactionDirection::Inbound,
+                TransactionStatus::Imported,
+                other.clone(),
+            ),
+            // A payment to this wallet only costs the fee
+            transaction(4, 400, 5, TransactionDirection::Inbound, mined, PublicKey::default()),
+            transaction(
+                4,
+                100,
+                0,
+                TransactionDirection::Inbound,
+                TransactionStatus::Broadcast,
+                other,
+            ),
+            cancelled,
+        ];
+
+        let history = balance_history(&transactions, NaiveDate::from_ymd(2021, 10, 5), 4);
+        let balances = history.iter().map(|s| u64::from(s.balance)).collect::<Vec<_>>();
+        assert_eq!(history[0].date, NaiveDate::from_ymd(2021, 10, 2));
+        assert_eq!(history[3].date, NaiveDate::from_ymd(2021, 10, 5));
+        assert_eq!(balances, vec![1000, 890, 885, 885]);
+
+        assert!(balance_history(&transactions, NaiveDate::from_ymd(2021, 10, 5), 0).is_empty());
+    }
+}
@@ -35,6 +35,9 @@ pub struct TransactionServiceConfig {
     pub transaction_resend_period: Duration,
     pub resend_response_cooldown: Duration,
     pub pending_transaction_cancellation_timeout: Duration,
+    /// How long the outputs spent by a cancelled, already broadcast transaction are held back after the cancellation
+    /// before they are released, provided the base node does not have the transaction
+    pub cancelled_output_release_timeout: Duration,
     pub num_confirmations_required: u64,
     pub max_tx_query_batch_size: usize,
     pub transaction_routing_mechanism: TransactionRoutingMechanism,
@@ -52,6 +55,7 @@ impl Default for TransactionServiceConfig {
             transaction_resend_period: Duration::from_secs(3600),
             resend_response_cooldown: Duration::from_secs(300),
             pending_transaction_cancellation_timeout: Duration::from_secs(259200), // 3 Days
+            cancelled_output_release_timeout: Duration::from_secs(3600),
             num_confirmations_required: 3,
             max_tx_query_batch_size: 5000,
             transaction_routing_mechanism: TransactionRoutingMechanism::default(),
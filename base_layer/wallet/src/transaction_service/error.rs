@@ -23,7 +23,7 @@
 use crate::{
     error::WalletStorageError,
     output_manager_service::{error::OutputManagerError, TxId},
-    transaction_service::storage::database::DbKey,
+    transaction_service::{output_memo::MAX_MEMO_LENGTH, storage::database::DbKey},
 };
 use diesel::result::Error as DieselError;
 use futures::channel::oneshot::Canceled;
@@ -43,6 +43,14 @@ pub enum TransactionServiceError {
     InvalidStateError,
     #[error("One-sided transaction error: `{0}`")]
     OneSidedTransactionError(String),
+    #[error("Script template error: `{0}`")]
+    ScriptTemplateError(#[from] ScriptTemplateError),
+    #[error("Output memo error: `{0}`")]
+    OutputMemoError(#[from] OutputMemoError),
+    #[error("Offline signing error: `{0}`")]
+    OfflineSigningError(#[from] OfflineSigningError),
+    #[error("Atomic swap transaction error: `{0}`")]
+    AtomicSwapTransactionError(String),
     #[error("Transaction Protocol Error: `{0}`")]
     TransactionProtocolError(#[from] TransactionProtocolError),
     #[error("The message being processed is not recognized by the Transaction Manager")]
@@ -145,6 +153,10 @@ pub enum TransactionServiceError {
     ServiceError(String),
     #[error("Wallet Recovery in progress so Transaction Service Messaging Requests ignored")]
     WalletRecoveryInProgress,
+    #[error("Transaction (TxId: {0}) cannot be cancelled: {1}")]
+    TransactionCannotBeCancelled(TxId, String),
+    #[error("The fee of transaction (TxId: {0}) cannot be bumped: {1}")]
+    TransactionCannotBeFeeBumped(TxId, String),
 }
 
 #[derive(Debug, Error)]
@@ -204,3 +216,37 @@ impl From<TransactionServiceProtocolError> for TransactionServiceError {
         tspe.error
     }
 }
+
+#[derive(Debug, Error)]
+pub enum ScriptTemplateError {
+    #[error("The chain tip height is required to build a relative time lock but is not known")]
+    ChainTipUnknown,
+    #[error("Time lock of {1} blocks from height {0} overflows")]
+    HeightOverflow(u64, u64),
+}
+
+#[derive(Debug, Error)]
+pub enum OutputMemoError {
+    #[error("The memo is {0} bytes, which exceeds the maximum of {} bytes", MAX_MEMO_LENGTH)]
+    TooLong(usize),
+    #[error("The memo could not be encrypted")]
+    EncryptionFailed,
+}
+
+#[derive(Debug, Error)]
+pub enum OfflineSigningError {
+    #[error("Package version {0} is not supported")]
+    UnsupportedVersion(u8),
+    #[error("Expected a {expected} package but found a {found} package")]
+    WrongPackageKind { expected: &'static str, found: String },
+    #[error("Invalid package chunk: `{0}`")]
+    InvalidChunk(String),
+    #[error("Expected {expected} package chunks but found {found}")]
+    MissingChunks { expected: usize, found: usize },
+    #[error("Serde json error: `{0}`")]
+    SerdeJsonError(#[from] SerdeJsonError),
+    #[error("No outputs are reserved for transaction {0}")]
+    OutputsNotReserved(TxId),
+    #[error("The signed transaction does not spend the outputs reserved for transaction {0}")]
+    InputMismatch(TxId),
+}
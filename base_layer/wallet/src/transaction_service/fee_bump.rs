@@ -0,0 +1,113 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Child-pays-for-parent fee bumping. A fee bump transaction spends the pending change of a stuck outbound
+//! transaction back into this wallet with a fee high enough for the two transactions together to be worth mining.
+//! Because a fee bump depends on the change of its parent, the two have to be released together when the parent will
+//! not be mined.
+
+use crate::{
+    output_manager_service::TxId,
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::TransactionEvent,
+        service::TransactionServiceResources,
+        storage::database::TransactionBackend,
+    },
+};
+use log::*;
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "wallet::transaction_service::fee_bump";
+
+/// Release the outputs encumbered by a transaction that will not be mined. Any fee bumps of the transaction can not be
+/// mined either, so they are cancelled and released first. If the transaction is itself a fee bump, the change it
+/// spends is handed back to its parent.
+pub async fn release_transaction_outputs<TBackend>(
+    resources: &TransactionServiceResources<TBackend>,
+    tx_id: TxId,
+) -> Result<(), TransactionServiceError>
+where
+    TBackend: TransactionBackend + 'static,
+{
+    let mut output_manager_service = resources.output_manager_service.clone();
+
+    // Every fee bump is listed before the fee bumps that depend on it, so they are released in reverse
+    let mut fee_bumps = Vec::new();
+    let mut parents = vec![tx_id];
+    while let Some(parent_tx_id) = parents.pop() {
+        for fee_bump in resources.db.get_fee_bumps_for_parent(parent_tx_id).await? {
+            parents.push(fee_bump.tx_id);
+            fee_bumps.push(fee_bump);
+        }
+    }
+
+    for fee_bump in fee_bumps.into_iter().rev() {
+        let cancellation = resources.db.get_transaction_cancellation(fee_bump.tx_id).await?;
+        if cancellation.as_ref().map(|c| c.outputs_released).unwrap_or(false) {
+            continue;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Cancelling fee bump transaction (TxId: {}) because its parent (TxId: {}) will not be mined",
+            fee_bump.tx_id,
+            fee_bump.parent_tx_id
+        );
+        output_manager_service
+            .cancel_fee_bump_transaction(fee_bump.tx_id, fee_bump.parent_tx_id)
+            .await?;
+        if cancellation.is_some() {
+            resources
+                .db
+                .set_transaction_cancellation_outputs_released(fee_bump.tx_id)
+                .await?;
+        } else {
+            resources.db.cancel_completed_transaction(fee_bump.tx_id).await?;
+            resources
+                .db
+                .record_transaction_cancellation(
+                    fee_bump.tx_id,
+                    format!(
+                        "The bumped transaction (TxId: {}) will not be mined",
+                        fee_bump.parent_tx_id
+                    ),
+                    true,
+                )
+                .await?;
+            let _ = resources
+                .event_publisher
+                .send(Arc::new(TransactionEvent::TransactionCancelled(fee_bump.tx_id)));
+        }
+    }
+
+    match resources.db.get_fee_bump(tx_id).await? {
+        Some(fee_bump) => {
+            output_manager_service
+                .cancel_fee_bump_transaction(tx_id, fee_bump.parent_tx_id)
+                .await?
+        },
+        None => output_manager_service.cancel_transaction(tx_id).await?,
+    }
+
+    Ok(())
+}
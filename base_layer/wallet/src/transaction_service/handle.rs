@@ -21,16 +21,32 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    output_manager_service::TxId,
+    output_manager_service::{storage::models::AccountId, TxId},
     transaction_service::{
         error::TransactionServiceError,
-        storage::models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
+        offline_signing::{SignedTransactionPackage, UnsignedTransactionPackage},
+        script_templates::ScriptTemplate,
+        storage::models::{
+            CompletedTransaction,
+            InboundTransaction,
+            OutboundTransaction,
+            TransactionCancellation,
+            WalletTransaction,
+        },
     },
 };
 use aes_gcm::Aes256Gcm;
 use std::{collections::HashMap, fmt, sync::Arc};
+use tari_common_types::{
+    stealth_address::StealthAddress,
+    types::{HashOutput, PrivateKey, PublicKey},
+};
 use tari_comms::types::CommsPublicKey;
-use tari_core::transactions::{tari_amount::MicroTari, transaction::Transaction};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{Transaction, TransactionOutput},
+};
+use tari_crypto::tari_utilities::hex::Hex;
 use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
 use tower::Service;
@@ -51,7 +67,23 @@ pub enum TransactionServiceRequest {
     SetBaseNodePublicKey(CommsPublicKey),
     SendTransaction(CommsPublicKey, MicroTari, MicroTari, String),
     SendOneSidedTransaction(CommsPublicKey, MicroTari, MicroTari, String),
-    CancelTransaction(TxId),
+    SendTransactionFromAccount(AccountId, CommsPublicKey, MicroTari, MicroTari, String),
+    SendOneSidedTransactionFromAccount(AccountId, CommsPublicKey, MicroTari, MicroTari, String),
+    TransferBetweenAccounts(AccountId, AccountId, MicroTari, MicroTari, String),
+    GetAccountCompletedTransactions(AccountId),
+    SendStealthTransaction(StealthAddress, MicroTari, MicroTari, String),
+    SendShaAtomicSwapTransaction(CommsPublicKey, MicroTari, MicroTari, u64, String),
+    SendTemplatedTransaction(CommsPublicKey, MicroTari, MicroTari, ScriptTemplate, String),
+    SendMultisigFundingTransaction(PublicKey, MicroTari, MicroTari),
+    ClaimShaAtomicSwapTransaction(Box<TransactionOutput>, PublicKey, MicroTari),
+    RefundShaAtomicSwapTransaction(TxId, MicroTari),
+    BumpFee(TxId, MicroTari),
+    BurnTari(MicroTari, MicroTari, String),
+    ExportUnsignedTransaction(CommsPublicKey, MicroTari, MicroTari, String),
+    SignOfflineTransaction(Box<UnsignedTransactionPackage>),
+    BroadcastSignedTransaction(Box<SignedTransactionPackage>),
+    CancelTransaction(TxId, Option<String>),
+    GetTransactionCancellation(TxId),
     ImportUtxo(MicroTari, CommsPublicKey, String, Option<u64>),
     SubmitCoinSplitTransaction(TxId, Transaction, MicroTari, MicroTari, String),
     SetLowPowerMode,
@@ -82,7 +114,49 @@ impl fmt::Display for TransactionServiceRequest {
             Self::SendOneSidedTransaction(k, v, _, msg) => {
                 f.write_str(&format!("SendOneSidedTransaction (to {}, {}, {})", k, v, msg))
             },
-            Self::CancelTransaction(t) => f.write_str(&format!("CancelTransaction ({})", t)),
+            Self::SendStealthTransaction(a, v, _, msg) => {
+                f.write_str(&format!("SendStealthTransaction (to {}, {}, {})", a, v, msg))
+            },
+            Self::SendShaAtomicSwapTransaction(k, v, _, h, msg) => f.write_str(&format!(
+                "SendShaAtomicSwapTransaction (to {}, {}, refund height {}, {})",
+                k, v, h, msg
+            )),
+            Self::SendTemplatedTransaction(k, v, _, t, msg) => {
+                f.write_str(&format!("SendTemplatedTransaction (to {}, {}, {}, {})", k, v, t, msg))
+            },
+            Self::SendMultisigFundingTransaction(k, v, _) => {
+                f.write_str(&format!("SendMultisigFundingTransaction (to {}, {})", k, v))
+            },
+            Self::ClaimShaAtomicSwapTransaction(o, _, _) => {
+                f.write_str(&format!("ClaimShaAtomicSwapTransaction ({})", o.commitment.to_hex()))
+            },
+            Self::RefundShaAtomicSwapTransaction(t, _) => {
+                f.write_str(&format!("RefundShaAtomicSwapTransaction ({})", t))
+            },
+            Self::BurnTari(amount, _, msg) => f.write_str(&format!("BurnTari ({}, {})", amount, msg)),
+            Self::ExportUnsignedTransaction(k, v, _, msg) => {
+                f.write_str(&format!("ExportUnsignedTransaction (to {}, {}, {})", k, v, msg))
+            },
+            Self::SignOfflineTransaction(p) => f.write_str(&format!("SignOfflineTransaction ({})", p.tx_id)),
+            Self::BroadcastSignedTransaction(p) => f.write_str(&format!("BroadcastSignedTransaction ({})", p.tx_id)),
+            Self::BumpFee(t, fee) => f.write_str(&format!("BumpFee ({}: {} per gram)", t, fee)),
+            Self::SendTransactionFromAccount(a, k, v, _, msg) => f.write_str(&format!(
+                "SendTransactionFromAccount (from account {} to {}, {}, {})",
+                a, k, v, msg
+            )),
+            Self::SendOneSidedTransactionFromAccount(a, k, v, _, msg) => f.write_str(&format!(
+                "SendOneSidedTransactionFromAccount (from account {} to {}, {}, {})",
+                a, k, v, msg
+            )),
+            Self::TransferBetweenAccounts(from, to, v, _, _) => f.write_str(&format!(
+                "TransferBetweenAccounts ({} from account {} to account {})",
+                v, from, to
+            )),
+            Self::GetAccountCompletedTransactions(a) => {
+                f.write_str(&format!("GetAccountCompletedTransactions ({})", a))
+            },
+            Self::CancelTransaction(t, _) => f.write_str(&format!("CancelTransaction ({})", t)),
+            Self::GetTransactionCancellation(t) => f.write_str(&format!("GetTransactionCancellation ({})", t)),
             Self::ImportUtxo(v, k, msg, maturity) => f.write_str(&format!(
                 "ImportUtxo (from {}, {}, {} with maturity: {})",
                 k,
@@ -118,7 +192,12 @@ impl fmt::Display for TransactionServiceRequest {
 #[derive(Debug)]
 pub enum TransactionServiceResponse {
     TransactionSent(TxId),
+    ShaAtomicSwapTransactionSent(Box<(TxId, PublicKey, HashOutput)>),
+    MultisigFundingTransactionSent(Box<(TxId, TransactionOutput, PrivateKey)>),
     TransactionCancelled,
+    TransactionCancellation(Option<TransactionCancellation>),
+    UnsignedTransactionExported(Box<UnsignedTransactionPackage>),
+    OfflineTransactionSigned(Box<SignedTransactionPackage>),
     PendingInboundTransactions(HashMap<u64, InboundTransaction>),
     PendingOutboundTransactions(HashMap<u64, OutboundTransaction>),
     CompletedTransactions(HashMap<u64, CompletedTransaction>),
@@ -234,10 +313,315 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Send a transaction that spends the outputs of the given account, with any change returned to that account
+    pub async fn send_transaction_from_account(
+        &mut self,
+        account_id: AccountId,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTransactionFromAccount(
+                account_id,
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Send a one-sided payment that spends the outputs of the given account, with any change returned to that account
+    pub async fn send_one_sided_transaction_from_account(
+        &mut self,
+        account_id: AccountId,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOneSidedTransactionFromAccount(
+                account_id,
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Move funds from one account of this wallet to another with a transaction to self
+    pub async fn transfer_between_accounts(
+        &mut self,
+        from_account: AccountId,
+        to_account: AccountId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::TransferBetweenAccounts(
+                from_account,
+                to_account,
+                amount,
+                fee_per_gram,
+                message,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Send a one-sided payment to a stealth address. The recipient detects the payment with the scan key of the
+    /// address.
+    pub async fn send_stealth_transaction(
+        &mut self,
+        address: StealthAddress,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendStealthTransaction(
+                address,
+                amount,
+                fee_per_gram,
+                message,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Send a one-sided payment to `dest_pubkey` with the output locked by the script built from `template`. The
+    /// recipient will only detect the payment if it knows the script in advance.
+    pub async fn send_templated_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        template: ScriptTemplate,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTemplatedTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                template,
+                message,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Send a hash time locked contract payment to `dest_pubkey`. Returns the transaction id, the pre-image the
+    /// recipient needs to claim the funds and the hash of the HTLC output.
+    pub async fn send_sha_atomic_swap_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        refund_height: u64,
+        message: String,
+    ) -> Result<(TxId, PublicKey, HashOutput), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendShaAtomicSwapTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                refund_height,
+                message,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::ShaAtomicSwapTransactionSent(boxed) => Ok(*boxed),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Fund the group key of a multisig session with a one-sided payment. Returns the transaction id, the multisig
+    /// output and its spending key, which the other participants of the session need to spend it.
+    pub async fn send_multisig_funding_transaction(
+        &mut self,
+        group_public_key: PublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+    ) -> Result<(TxId, TransactionOutput, PrivateKey), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendMultisigFundingTransaction(
+                group_public_key,
+                amount,
+                fee_per_gram,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::MultisigFundingTransactionSent(boxed) => Ok(*boxed),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn claim_sha_atomic_swap_transaction(
+        &mut self,
+        output: TransactionOutput,
+        pre_image: PublicKey,
+        fee_per_gram: MicroTari,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ClaimShaAtomicSwapTransaction(
+                Box::new(output),
+                pre_image,
+                fee_per_gram,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn refund_sha_atomic_swap_transaction(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroTari,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::RefundShaAtomicSwapTransaction(
+                tx_id,
+                fee_per_gram,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Burn the given amount, removing it from the circulating supply. The burnt value cannot be spent again.
+    pub async fn burn_tari(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::BurnTari(amount, fee_per_gram, message))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Reserve the inputs of a one-sided payment and export them as a package to be signed by an offline wallet
+    pub async fn export_unsigned_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<UnsignedTransactionPackage, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ExportUnsignedTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::UnsignedTransactionExported(package) => Ok(*package),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sign a package exported by an online wallet. The transaction is not broadcast.
+    pub async fn sign_offline_transaction(
+        &mut self,
+        package: UnsignedTransactionPackage,
+    ) -> Result<SignedTransactionPackage, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SignOfflineTransaction(Box::new(package)))
+            .await??
+        {
+            TransactionServiceResponse::OfflineTransactionSigned(package) => Ok(*package),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Broadcast a package signed by an offline wallet, after checking that it spends the inputs this wallet reserved
+    pub async fn broadcast_signed_transaction(
+        &mut self,
+        package: SignedTransactionPackage,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::BroadcastSignedTransaction(Box::new(package)))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        self.cancel_transaction_with_reason(tx_id, None).await
+    }
+
+    /// Bump the fee of a stuck outbound transaction with a child-pays-for-parent transaction that spends its change back
+    /// into this wallet. `fee_per_gram` is the rate that the two transactions pay together. Returns the TxId of the fee
+    /// bump transaction.
+    pub async fn bump_fee(&mut self, tx_id: TxId, fee_per_gram: MicroTari) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::BumpFee(tx_id, fee_per_gram))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Cancel a transaction and record the given reason in its cancellation record. The outputs spent by a completed
+    /// transaction that has not been mined are only released once it is known that the transaction will not be mined.
+    pub async fn cancel_transaction_with_reason(
+        &mut self,
+        tx_id: TxId,
+        reason: Option<String>,
+    ) -> Result<(), TransactionServiceError> {
         match self
             .handle
-            .call(TransactionServiceRequest::CancelTransaction(tx_id))
+            .call(TransactionServiceRequest::CancelTransaction(tx_id, reason))
             .await??
         {
             TransactionServiceResponse::TransactionCancelled => Ok(()),
@@ -245,6 +629,21 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Returns the cancellation record of a transaction, or None if the transaction has not been cancelled
+    pub async fn get_transaction_cancellation(
+        &mut self,
+        tx_id: TxId,
+    ) -> Result<Option<TransactionCancellation>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionCancellation(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::TransactionCancellation(cancellation) => Ok(cancellation),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_pending_inbound_transactions(
         &mut self,
     ) -> Result<HashMap<u64, InboundTransaction>, TransactionServiceError> {
@@ -310,6 +709,21 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Get the completed transactions in the history of the given account
+    pub async fn get_account_completed_transactions(
+        &mut self,
+        account_id: AccountId,
+    ) -> Result<HashMap<u64, CompletedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetAccountCompletedTransactions(account_id))
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_cancelled_completed_transactions(
         &mut self,
     ) -> Result<HashMap<u64, CompletedTransaction>, TransactionServiceError> {
@@ -56,10 +56,15 @@ use tari_service_framework::{
     ServiceInitializerContext,
 };
 
+pub mod balance_history;
 pub mod config;
 pub mod error;
+pub mod fee_bump;
 pub mod handle;
+pub mod offline_signing;
+pub mod output_memo;
 pub mod protocols;
+pub mod script_templates;
 pub mod service;
 pub mod storage;
 pub mod tasks;
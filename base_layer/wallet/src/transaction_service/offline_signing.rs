@@ -0,0 +1,332 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Packages exchanged by the offline signing workflow, in which an online wallet prepares a one-sided payment that is
+//! signed by an air-gapped wallet holding the spending keys, and then broadcast by the online wallet:
+//!
+//! 1. The online wallet reserves the inputs and exports an [UnsignedTransactionPackage], which contains no keys. Each
+//!    input carries a range proof made with the rewind keys shared by both wallets.
+//! 2. The offline wallet rewinds the inputs to recover their spending keys, derives their script keys from its key
+//!    chain and exports a [SignedTransactionPackage]. It does not need the inputs in its own database and stores
+//!    nothing.
+//! 3. The online wallet checks that the signed transaction spends the inputs it reserved, adds the change output and
+//!    broadcasts it.
+//!
+//! Since the online wallet only needs the rewind keys, it can be a watch-only wallet.
+//!
+//! Packages are carried either as a JSON file or as a sequence of text chunks that can each be shown as a QR code. A
+//! chunk is laid out as `TARI-OFFLINE-TX:<kind>:<index>/<count>:<payload>` and only uses characters of the QR
+//! alphanumeric mode, so that the chunks can be scanned in any order.
+
+use crate::{output_manager_service::TxId, transaction_service::error::OfflineSigningError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{Transaction, TransactionOutput},
+};
+use tari_crypto::tari_utilities::hex::{from_hex, to_hex};
+
+/// The version of the package format written by this wallet
+pub const OFFLINE_PACKAGE_VERSION: u8 = 2;
+/// The default number of payload characters in a package chunk, which keeps each QR code small enough to be shown in
+/// a terminal
+pub const DEFAULT_CHUNK_SIZE: usize = 500;
+const CHUNK_PREFIX: &str = "TARI-OFFLINE-TX";
+
+/// A package that can be exported by one wallet and imported by another
+pub trait OfflinePackage: Serialize + DeserializeOwned {
+    /// The kind of package, which prevents one kind of package from being imported as another
+    const KIND: &'static str;
+
+    fn version(&self) -> u8;
+
+    fn to_json(&self) -> Result<String, OfflineSigningError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    fn from_json(json: &str) -> Result<Self, OfflineSigningError> {
+        let package = serde_json::from_str::<Self>(json)?;
+        if package.version() != OFFLINE_PACKAGE_VERSION {
+            return Err(OfflineSigningError::UnsupportedVersion(package.version()));
+        }
+        Ok(package)
+    }
+
+    /// Split the package into chunks of at most `chunk_size` payload characters
+    fn to_chunks(&self, chunk_size: usize) -> Result<Vec<String>, OfflineSigningError> {
+        let payload = to_hex(serde_json::to_string(self)?.as_bytes()).to_uppercase();
+        let parts = payload.as_bytes().chunks(chunk_size.max(1)).collect::<Vec<_>>();
+        Ok(parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                format!(
+                    "{}:{}:{}/{}:{}",
+                    CHUNK_PREFIX,
+                    Self::KIND,
+                    i + 1,
+                    parts.len(),
+                    String::from_utf8_lossy(part)
+                )
+            })
+            .collect())
+    }
+
+    /// Reassemble a package from its chunks, which may be given in any order
+    fn from_chunks<S: AsRef<str>>(chunks: &[S]) -> Result<Self, OfflineSigningError> {
+        let mut parts = BTreeMap::new();
+        let mut count = None;
+        for chunk in chunks {
+            let chunk = chunk.as_ref().trim();
+            let invalid = || OfflineSigningError::InvalidChunk(chunk.to_string());
+            let mut fields = chunk.splitn(4, ':');
+            if fields.next() != Some(CHUNK_PREFIX) {
+                return Err(invalid());
+            }
+            let kind = fields.next().ok_or_else(invalid)?;
+            if kind != Self::KIND {
+                return Err(OfflineSigningError::WrongPackageKind {
+                    expected: Self::KIND,
+                    found: kind.to_string(),
+                });
+            }
+            let (index, total) = fields
+                .next()
+                .and_then(|position| position.split_once('/'))
+                .ok_or_else(invalid)?;
+            let index = index.parse::<usize>().map_err(|_| invalid())?;
+            let total = total.parse::<usize>().map_err(|_| invalid())?;
+            if index == 0 || index > total || count.map_or(false, |count| count != total) {
+                return Err(invalid());
+            }
+            count = Some(total);
+            parts.insert(index, fields.next().ok_or_else(invalid)?.to_string());
+        }
+
+        let count = count.unwrap_or(0);
+        if count == 0 || parts.len() != count {
+            return Err(OfflineSigningError::MissingChunks {
+                expected: count,
+                found: parts.len(),
+            });
+        }
+        let payload = parts.into_iter().map(|(_, part)| part).collect::<String>();
+        let bytes =
+            from_hex(&payload.to_lowercase()).map_err(|_| OfflineSigningError::InvalidChunk("payload".to_string()))?;
+        let json = String::from_utf8(bytes).map_err(|_| OfflineSigningError::InvalidChunk("payload".to_string()))?;
+        Self::from_json(&json)
+    }
+
+    /// Read a package from either its JSON or its chunks, one per line
+    fn read(text: &str) -> Result<Self, OfflineSigningError> {
+        if text.trim_start().starts_with(CHUNK_PREFIX) {
+            let chunks = text.lines().filter(|l| !l.trim().is_empty()).collect::<Vec<_>>();
+            Self::from_chunks(&chunks)
+        } else {
+            Self::from_json(text)
+        }
+    }
+}
+
+/// An unspent output reserved by the online wallet, with a range proof that the offline wallet can rewind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OfflineInput {
+    pub output: TransactionOutput,
+    pub value: MicroTari,
+}
+
+/// A one-sided payment prepared by the online wallet, to be signed by the offline wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsignedTransactionPackage {
+    pub version: u8,
+    pub tx_id: TxId,
+    pub destination: CommsPublicKey,
+    pub amount: MicroTari,
+    pub fee_per_gram: MicroTari,
+    pub message: String,
+    pub inputs: Vec<OfflineInput>,
+}
+
+impl UnsignedTransactionPackage {
+    pub fn new(
+        tx_id: TxId,
+        destination: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        inputs: Vec<OfflineInput>,
+    ) -> Self {
+        Self {
+            version: OFFLINE_PACKAGE_VERSION,
+            tx_id,
+            destination,
+            amount,
+            fee_per_gram,
+            message,
+            inputs,
+        }
+    }
+
+    /// The total value of the reserved inputs
+    pub fn input_value(&self) -> MicroTari {
+        self.inputs.iter().map(|i| i.value).sum()
+    }
+}
+
+impl OfflinePackage for UnsignedTransactionPackage {
+    const KIND: &'static str = "UNSIGNED";
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+}
+
+/// A one-sided payment signed by the offline wallet, to be broadcast by the online wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedTransactionPackage {
+    pub version: u8,
+    pub tx_id: TxId,
+    pub destination: CommsPublicKey,
+    pub amount: MicroTari,
+    pub fee: MicroTari,
+    pub message: String,
+    pub transaction: Transaction,
+}
+
+impl SignedTransactionPackage {
+    pub fn new(unsigned: &UnsignedTransactionPackage, transaction: Transaction, fee: MicroTari) -> Self {
+        Self {
+            version: OFFLINE_PACKAGE_VERSION,
+            tx_id: unsigned.tx_id,
+            destination: unsigned.destination.clone(),
+            amount: unsigned.amount,
+            fee,
+            message: unsigned.message.clone(),
+            transaction,
+        }
+    }
+}
+
+impl OfflinePackage for SignedTransactionPackage {
+    const KIND: &'static str = "SIGNED";
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_common_types::types::PublicKey;
+    use tari_core::transactions::{
+        helpers::{create_unblinded_output, TestParams},
+        transaction::OutputFeatures,
+        CryptoFactories,
+    };
+    use tari_crypto::{keys::PublicKey as PublicKeyTrait, script};
+
+    fn unsigned_package() -> UnsignedTransactionPackage {
+        let factories = CryptoFactories::default();
+        let (_, destination) = PublicKey::random_keypair(&mut OsRng);
+        let inputs = [1_000u64, 2_500]
+            .iter()
+            .map(|v| {
+                let value = MicroTari::from(*v);
+                let output = create_unblinded_output(script!(Nop), OutputFeatures::default(), TestParams::new(), value)
+                    .as_transaction_output(&factories)
+                    .unwrap();
+                OfflineInput { output, value }
+            })
+            .collect();
+        UnsignedTransactionPackage::new(
+            42,
+            destination,
+            MicroTari::from(3_000),
+            MicroTari::from(5),
+            "Invoice 7".to_string(),
+            inputs,
+        )
+    }
+
+    #[test]
+    fn it_round_trips_through_json() {
+        let package = unsigned_package();
+        assert_eq!(package.input_value(), MicroTari::from(3_500));
+        let json = package.to_json().unwrap();
+        assert_eq!(UnsignedTransactionPackage::from_json(&json).unwrap(), package);
+        assert_eq!(UnsignedTransactionPackage::read(&json).unwrap(), package);
+    }
+
+    #[test]
+    fn it_round_trips_through_chunks_in_any_order() {
+        let package = unsigned_package();
+        let mut chunks = package.to_chunks(100).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.starts_with("TARI-OFFLINE-TX:UNSIGNED:")));
+        assert!(chunks.iter().all(|c| c
+            .chars()
+            .all(|ch| ch.is_ascii_digit() || ch.is_ascii_uppercase() || "-:/".contains(ch))));
+        chunks.reverse();
+        assert_eq!(UnsignedTransactionPackage::from_chunks(&chunks).unwrap(), package);
+        assert_eq!(UnsignedTransactionPackage::read(&chunks.join("\n")).unwrap(), package);
+    }
+
+    #[test]
+    fn it_rejects_incomplete_chunks() {
+        let mut chunks = unsigned_package().to_chunks(100).unwrap();
+        let expected = chunks.len();
+        chunks.pop();
+        assert!(matches!(
+            UnsignedTransactionPackage::from_chunks(&chunks),
+            Err(OfflineSigningError::MissingChunks { expected: e, found }) if e == expected && found == expected - 1
+        ));
+        assert!(matches!(
+            UnsignedTransactionPackage::from_chunks::<&str>(&[]),
+            Err(OfflineSigningError::MissingChunks { .. })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_the_wrong_kind_of_package() {
+        let chunks = unsigned_package().to_chunks(DEFAULT_CHUNK_SIZE).unwrap();
+        assert!(matches!(
+            SignedTransactionPackage::from_chunks(&chunks),
+            Err(OfflineSigningError::WrongPackageKind { expected: "SIGNED", .. })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_unsupported_versions() {
+        let mut package = unsigned_package();
+        package.version = OFFLINE_PACKAGE_VERSION + 1;
+        let json = serde_json::to_string(&package).unwrap();
+        assert!(matches!(
+            UnsignedTransactionPackage::from_json(&json),
+            Err(OfflineSigningError::UnsupportedVersion(v)) if v == OFFLINE_PACKAGE_VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Encryption of the memos that can be attached to outputs. A memo is encrypted to the receiver's public key with a
+//! key derived from an ephemeral Diffie-Hellman exchange, so that it survives one-sided payments and wallet recovery
+//! without revealing anything to other observers of the chain.
+//!
+//! The encrypted memo is laid out as `ephemeral public key | AES-GCM nonce | ciphertext and tag`.
+
+use crate::{
+    transaction_service::error::OutputMemoError,
+    util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, AES_NONCE_BYTES},
+};
+use aes_gcm::{
+    aead::{generic_array::GenericArray, NewAead},
+    Aes256Gcm,
+};
+use digest::Digest;
+use rand::rngs::OsRng;
+use tari_common_types::types::{PrivateKey, PublicKey};
+use tari_core::transactions::transaction::MAX_OUTPUT_MEMO_SIZE;
+use tari_crypto::{
+    common::Blake256,
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait},
+    tari_utilities::ByteArray,
+};
+
+const MEMO_KEY_DOMAIN: &[u8] = b"com.tari.wallet.output_memo";
+const PUBLIC_KEY_BYTES: usize = 32;
+const AES_TAG_BYTES: usize = 16;
+
+/// The maximum length in bytes of a memo before encryption
+pub const MAX_MEMO_LENGTH: usize = MAX_OUTPUT_MEMO_SIZE - PUBLIC_KEY_BYTES - AES_NONCE_BYTES - AES_TAG_BYTES;
+
+fn memo_cipher(shared_secret: &PublicKey) -> Aes256Gcm {
+    let key = Blake256::new()
+        .chain(MEMO_KEY_DOMAIN)
+        .chain(shared_secret.as_bytes())
+        .finalize();
+    Aes256Gcm::new(GenericArray::from_slice(key.as_slice()))
+}
+
+/// Encrypt `memo` so that only the owner of `recipient` can read it
+pub fn encrypt_memo(recipient: &PublicKey, memo: &str) -> Result<Vec<u8>, OutputMemoError> {
+    if memo.len() > MAX_MEMO_LENGTH {
+        return Err(OutputMemoError::TooLong(memo.len()));
+    }
+    let (ephemeral_secret, ephemeral_public) = PublicKey::random_keypair(&mut OsRng);
+    let cipher = memo_cipher(&PublicKey::shared_secret(&ephemeral_secret, recipient));
+    let ciphertext = encrypt_bytes_integral_nonce(&cipher, memo.as_bytes().to_vec())
+        .map_err(|_| OutputMemoError::EncryptionFailed)?;
+
+    let mut encrypted_memo = ephemeral_public.as_bytes().to_vec();
+    encrypted_memo.extend(ciphertext);
+    Ok(encrypted_memo)
+}
+
+/// Decrypt a memo with the receiver's secret key. Returns `None` if the memo was not encrypted to this key.
+pub fn decrypt_memo(secret_key: &PrivateKey, encrypted_memo: &[u8]) -> Option<String> {
+    if encrypted_memo.len() < PUBLIC_KEY_BYTES {
+        return None;
+    }
+    let (ephemeral_public, ciphertext) = encrypted_memo.split_at(PUBLIC_KEY_BYTES);
+    let ephemeral_public = PublicKey::from_bytes(ephemeral_public).ok()?;
+    let cipher = memo_cipher(&PublicKey::shared_secret(secret_key, &ephemeral_public));
+    let memo = decrypt_bytes_integral_nonce(&cipher, ciphertext.to_vec()).ok()?;
+    String::from_utf8(memo).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_a_memo() {
+        let (secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let encrypted_memo = encrypt_memo(&public_key, "Order #1234").unwrap();
+        assert!(encrypted_memo.len() <= MAX_OUTPUT_MEMO_SIZE);
+        assert_eq!(decrypt_memo(&secret_key, &encrypted_memo).unwrap(), "Order #1234");
+    }
+
+    #[test]
+    fn it_cannot_be_read_with_another_key() {
+        let (_, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let (other_secret_key, _) = PublicKey::random_keypair(&mut OsRng);
+        let encrypted_memo = encrypt_memo(&public_key, "Order #1234").unwrap();
+        assert!(decrypt_memo(&other_secret_key, &encrypted_memo).is_none());
+        assert!(decrypt_memo(&other_secret_key, &[1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn it_rejects_long_memos() {
+        let (_, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let memo = "a".repeat(MAX_MEMO_LENGTH);
+        assert_eq!(encrypt_memo(&public_key, &memo).unwrap().len(), MAX_OUTPUT_MEMO_SIZE);
+        let memo = "a".repeat(MAX_MEMO_LENGTH + 1);
+        assert!(matches!(
+            encrypt_memo(&public_key, &memo),
+            Err(OutputMemoError::TooLong(len)) if len == MAX_MEMO_LENGTH + 1
+        ));
+    }
+}
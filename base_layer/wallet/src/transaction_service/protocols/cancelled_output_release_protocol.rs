@@ -0,0 +1,252 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::transaction_service::{
+    error::{TransactionServiceError, TransactionServiceProtocolError},
+    fee_bump::release_transaction_outputs,
+    handle::TransactionEvent,
+    service::TransactionServiceResources,
+    storage::{
+        database::TransactionBackend,
+        models::{CompletedTransaction, TransactionCancellation},
+    },
+};
+use chrono::Utc;
+use log::*;
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
+use tari_core::{
+    base_node::{
+        proto::wallet_rpc::{TxLocation, TxQueryResponse},
+        rpc::BaseNodeWalletRpcClient,
+    },
+    proto::base_node::FetchMatchingUtxos,
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::protocols::cancelled_output_release_protocol";
+
+/// A cancelled transaction that was already broadcast could still be mined, so the outputs it spends are held until
+/// this protocol decides what to do with them. The outputs are released once the base node reports that the
+/// transaction is not in its mempool and either one of its inputs has been spent by another transaction or the
+/// release timeout has passed since the cancellation. A cancelled transaction that turns out to be mined is reinstated.
+pub struct CancelledOutputReleaseProtocol<TBackend>
+where TBackend: TransactionBackend + 'static
+{
+    id: u64,
+    resources: TransactionServiceResources<TBackend>,
+    base_node_public_key: CommsPublicKey,
+    timeout: Duration,
+}
+
+impl<TBackend> CancelledOutputReleaseProtocol<TBackend>
+where TBackend: TransactionBackend + 'static
+{
+    pub fn new(
+        id: u64,
+        resources: TransactionServiceResources<TBackend>,
+        base_node_public_key: CommsPublicKey,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            id,
+            resources,
+            base_node_public_key,
+            timeout,
+        }
+    }
+
+    /// The task that defines the execution of the protocol.
+    pub async fn execute(self) -> Result<u64, TransactionServiceProtocolError> {
+        let cancellations = self
+            .resources
+            .db
+            .get_unreleased_transaction_cancellations()
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+        if cancellations.is_empty() {
+            return Ok(self.id);
+        }
+
+        let base_node_node_id = NodeId::from_key(&self.base_node_public_key);
+        let mut base_node_connection = match self.resources.connectivity_manager.dial_peer(base_node_node_id).await {
+            Ok(c) => c,
+            Err(e) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Problem connecting to base node: {}, the outputs of cancelled transactions will be checked later", e
+                );
+                return Ok(self.id);
+            },
+        };
+        let mut client = base_node_connection
+            .connect_rpc_using_builder(
+                BaseNodeWalletRpcClient::builder()
+                    .with_deadline(self.timeout)
+                    .with_handshake_timeout(self.timeout),
+            )
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
+        for cancellation in cancellations {
+            if let Err(e) = self.check_cancelled_transaction(&cancellation, &mut client).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not check the cancelled transaction (TxId: {}): {}", cancellation.tx_id, e
+                );
+            }
+        }
+
+        Ok(self.id)
+    }
+
+    async fn check_cancelled_transaction(
+        &self,
+        cancellation: &TransactionCancellation,
+        client: &mut BaseNodeWalletRpcClient,
+    ) -> Result<(), TransactionServiceError> {
+        let tx_id = cancellation.tx_id;
+        let completed_tx = self
+            .resources
+            .db
+            .get_completed_transaction_cancelled_or_not(tx_id)
+            .await?;
+        if !completed_tx.cancelled {
+            // The transaction has been reinstated since it was cancelled
+            self.resources.db.remove_transaction_cancellation(tx_id).await?;
+            return Ok(());
+        }
+        let signature = completed_tx
+            .transaction
+            .first_kernel_excess_sig()
+            .ok_or(TransactionServiceError::InvalidTransaction)?
+            .clone();
+
+        // The inputs are checked before the transaction so that a transaction that is mined in between the two
+        // queries is not mistaken for a double spend
+        let inputs = completed_tx.transaction.body.inputs();
+        let utxos = client
+            .fetch_matching_utxos(FetchMatchingUtxos {
+                output_hashes: inputs.iter().map(|input| input.output_hash()).collect(),
+            })
+            .await?;
+        let double_spent = utxos.is_synced && utxos.outputs.len() < inputs.len();
+
+        let response = TxQueryResponse::try_from(client.transaction_query(signature.into()).await?)
+            .map_err(TransactionServiceError::InvalidMessageError)?;
+        if !response.is_synced {
+            debug!(
+                target: LOG_TARGET,
+                "Base node is not synced, cancelled transaction (TxId: {}) will be checked later", tx_id
+            );
+            return Ok(());
+        }
+
+        match response.location {
+            TxLocation::Mined => {
+                self.reinstate_mined_transaction(completed_tx, response.confirmations)
+                    .await
+            },
+            TxLocation::InMempool => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Cancelled transaction (TxId: {}) is still in the base node mempool, its outputs stay encumbered",
+                    tx_id
+                );
+                Ok(())
+            },
+            TxLocation::NotStored => {
+                let elapsed = Utc::now()
+                    .naive_utc()
+                    .signed_duration_since(cancellation.cancelled_at)
+                    .to_std()
+                    .unwrap_or_default();
+                if !double_spent && elapsed < self.resources.config.cancelled_output_release_timeout {
+                    return Ok(());
+                }
+                release_transaction_outputs(&self.resources, tx_id).await?;
+                self.resources
+                    .db
+                    .set_transaction_cancellation_outputs_released(tx_id)
+                    .await?;
+                info!(
+                    target: LOG_TARGET,
+                    "Released the outputs of cancelled transaction (TxId: {}) {}",
+                    tx_id,
+                    if double_spent {
+                        "because one of its inputs has been spent by another transaction"
+                    } else {
+                        "after the release timeout"
+                    }
+                );
+                Ok(())
+            },
+        }
+    }
+
+    async fn reinstate_mined_transaction(
+        &self,
+        completed_tx: CompletedTransaction,
+        confirmations: u64,
+    ) -> Result<(), TransactionServiceError> {
+        let tx_id = completed_tx.tx_id;
+        warn!(
+            target: LOG_TARGET,
+            "Cancelled transaction (TxId: {}) was mined anyway, reinstating it", tx_id
+        );
+        self.resources.db.uncancel_completed_transaction(tx_id).await?;
+        self.resources.db.remove_transaction_cancellation(tx_id).await?;
+        self.resources
+            .output_manager_service
+            .confirm_transaction(
+                tx_id,
+                completed_tx.transaction.body.inputs().clone(),
+                completed_tx.transaction.body.outputs().clone(),
+            )
+            .await?;
+        self.resources
+            .db
+            .set_transaction_confirmations(tx_id, confirmations)
+            .await?;
+
+        let event = if confirmations >= self.resources.config.num_confirmations_required {
+            self.resources
+                .db
+                .confirm_broadcast_or_coinbase_transaction(tx_id)
+                .await?;
+            TransactionEvent::TransactionMined(tx_id)
+        } else {
+            self.resources.db.mine_completed_transaction(tx_id).await?;
+            TransactionEvent::TransactionMinedUnconfirmed(tx_id, confirmations)
+        };
+        let _ = self.resources.event_publisher.send(Arc::new(event)).map_err(|e| {
+            trace!(
+                target: LOG_TARGET,
+                "Error sending event because there are no subscribers: {:?}",
+                e
+            );
+            e
+        });
+
+        Ok(())
+    }
+}
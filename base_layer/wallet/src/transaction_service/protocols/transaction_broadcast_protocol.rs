@@ -24,6 +24,7 @@ use crate::{
     output_manager_service::TxId,
     transaction_service::{
         error::{TransactionServiceError, TransactionServiceProtocolError},
+        fee_bump::release_transaction_outputs,
         handle::TransactionEvent,
         service::TransactionServiceResources,
         storage::{
@@ -193,7 +194,19 @@ where TBackend: TransactionBackend + 'static
                 Some(c) => c,
             };
 
-            let completed_tx = match self.resources.db.get_completed_transaction(self.tx_id).await {
+            let completed_tx = match self
+                .resources
+                .db
+                .get_completed_transaction_cancelled_or_not(self.tx_id)
+                .await
+            {
+                Ok(tx) if tx.cancelled => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Transaction (TxId: {}) has been cancelled and will stop being broadcast", self.tx_id
+                    );
+                    return Ok(self.tx_id);
+                },
                 Ok(tx) => tx,
                 Err(e) => {
                     error!(
@@ -375,7 +388,11 @@ where TBackend: TransactionBackend + 'static
                 "Transaction (TxId: {}) rejected by Base Node for reason: {}", self.tx_id, response.rejection_reason
             );
 
-            self.cancel_transaction().await;
+            self.cancel_transaction(
+                format!("Rejected by the base node: {}", response.rejection_reason),
+                true,
+            )
+            .await;
 
             let _ = self
                 .resources
@@ -555,7 +572,13 @@ where TBackend: TransactionBackend + 'static
                      cancelling transaction",
                     self.tx_id
                 );
-                self.cancel_transaction().await;
+                // The transaction was accepted before, so it could still be in the mempool of another base node. Its
+                // outputs are released once it is known that it will not be mined.
+                self.cancel_transaction(
+                    "Not found in the base node mempool after being submitted twice".to_string(),
+                    false,
+                )
+                .await;
 
                 let _ = self
                     .resources
@@ -612,22 +635,32 @@ where TBackend: TransactionBackend + 'static
         }
     }
 
-    async fn cancel_transaction(&mut self) {
-        if let Err(e) = self
-            .resources
-            .output_manager_service
-            .cancel_transaction(self.tx_id)
-            .await
-        {
+    /// Cancel the transaction and record the reason. When `release_outputs` is false the outputs stay encumbered until
+    /// the Cancelled Output Release protocol releases them.
+    async fn cancel_transaction(&mut self, reason: String, release_outputs: bool) {
+        if release_outputs {
+            if let Err(e) = release_transaction_outputs(&self.resources, self.tx_id).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to Cancel outputs for TxId: {} after failed sending attempt with error {:?}", self.tx_id, e
+                );
+            }
+        }
+        if let Err(e) = self.resources.db.cancel_completed_transaction(self.tx_id).await {
             warn!(
                 target: LOG_TARGET,
-                "Failed to Cancel outputs for TxId: {} after failed sending attempt with error {:?}", self.tx_id, e
+                "Failed to Cancel TxId: {} after failed sending attempt with error {:?}", self.tx_id, e
             );
         }
-        if let Err(e) = self.resources.db.cancel_completed_transaction(self.tx_id).await {
+        if let Err(e) = self
+            .resources
+            .db
+            .record_transaction_cancellation(self.tx_id, reason, release_outputs)
+            .await
+        {
             warn!(
                 target: LOG_TARGET,
-                "Failed to Cancel TxId: {} after failed sending attempt with error {:?}", self.tx_id, e
+                "Failed to record the cancellation of TxId: {} with error {:?}", self.tx_id, e
             );
         }
     }
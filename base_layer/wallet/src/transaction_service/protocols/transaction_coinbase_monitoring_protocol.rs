@@ -589,5 +589,20 @@ where TBackend: TransactionBackend + 'static
                 "Failed to Cancel Coinbase transaction (TxId: {}) with error: {:?}", self.tx_id, e
             );
         }
+        if let Err(e) = self
+            .resources
+            .db
+            .record_transaction_cancellation(
+                self.tx_id,
+                "The coinbase was not mined before the chain moved past its block height".to_string(),
+                true,
+            )
+            .await
+        {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to record the cancellation of Coinbase transaction (TxId: {}) with error: {:?}", self.tx_id, e
+            );
+        }
     }
 }
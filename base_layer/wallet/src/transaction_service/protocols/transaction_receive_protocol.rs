@@ -377,7 +377,7 @@ where TBackend: TransactionBackend + 'static
             );
 
             finalized_transaction
-                .validate_internal_consistency(true, &self.resources.factories, None)
+                .validate_internal_consistency(true, &self.resources.factories, None, None, None)
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
             // Find your own output in the transaction
@@ -504,6 +504,16 @@ where TBackend: TransactionBackend + 'static
             .await
             .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
+        self.resources
+            .db
+            .record_transaction_cancellation(
+                self.id,
+                "Timed out waiting for the sender to finalize the transaction".to_string(),
+                true,
+            )
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
         let _ = self
             .resources
             .event_publisher
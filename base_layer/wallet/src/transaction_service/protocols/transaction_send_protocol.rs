@@ -20,19 +20,22 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::transaction_service::{
-    config::TransactionRoutingMechanism,
-    error::{TransactionServiceError, TransactionServiceProtocolError},
-    handle::{TransactionEvent, TransactionServiceResponse},
-    service::TransactionServiceResources,
-    storage::{
-        database::TransactionBackend,
-        models::{CompletedTransaction, OutboundTransaction, TransactionDirection, TransactionStatus},
-    },
-    tasks::{
-        send_finalized_transaction::send_finalized_transaction_message,
-        send_transaction_cancelled::send_transaction_cancelled_message,
-        wait_on_dial::wait_on_dial,
+use crate::{
+    output_manager_service::storage::models::AccountId,
+    transaction_service::{
+        config::TransactionRoutingMechanism,
+        error::{TransactionServiceError, TransactionServiceProtocolError},
+        handle::{TransactionEvent, TransactionServiceResponse},
+        service::TransactionServiceResources,
+        storage::{
+            database::TransactionBackend,
+            models::{CompletedTransaction, OutboundTransaction, TransactionDirection, TransactionStatus},
+        },
+        tasks::{
+            send_finalized_transaction::send_finalized_transaction_message,
+            send_transaction_cancelled::send_transaction_cancelled_message,
+            wait_on_dial::wait_on_dial,
+        },
     },
 };
 use chrono::Utc;
@@ -46,7 +49,7 @@ use tari_comms_dht::{
 };
 use tari_core::transactions::{
     tari_amount::MicroTari,
-    transaction::KernelFeatures,
+    transaction::{KernelFeatures, OutputFeatures},
     transaction_protocol::{proto, recipient::RecipientSignedMessage, sender::SingleRoundSenderData},
     SenderTransactionProtocol,
 };
@@ -70,6 +73,7 @@ pub struct TransactionSendProtocol<TBackend>
 where TBackend: TransactionBackend + 'static
 {
     id: u64,
+    account_id: AccountId,
     dest_pubkey: CommsPublicKey,
     amount: MicroTari,
     fee_per_gram: MicroTari,
@@ -90,6 +94,7 @@ where TBackend: TransactionBackend + 'static
         resources: TransactionServiceResources<TBackend>,
         transaction_reply_receiver: Receiver<(CommsPublicKey, RecipientSignedMessage)>,
         cancellation_receiver: oneshot::Receiver<()>,
+        account_id: AccountId,
         dest_pubkey: CommsPublicKey,
         amount: MicroTari,
         fee_per_gram: MicroTari,
@@ -104,6 +109,7 @@ where TBackend: TransactionBackend + 'static
             resources,
             transaction_reply_receiver: Some(transaction_reply_receiver),
             cancellation_receiver: Some(cancellation_receiver),
+            account_id,
             dest_pubkey,
             amount,
             fee_per_gram,
@@ -152,13 +158,16 @@ where TBackend: TransactionBackend + 'static
         match self
             .resources
             .output_manager_service
-            .prepare_transaction_to_send(
+            .prepare_transaction_to_send_with_features(
+                self.account_id,
                 self.id,
                 self.amount,
                 self.fee_per_gram,
                 None,
                 self.message.clone(),
                 script!(Nop),
+                OutputFeatures::default(),
+                None,
             )
             .await
         {
@@ -463,7 +472,7 @@ where TBackend: TransactionBackend + 'static
 
         outbound_tx
             .sender_protocol
-            .finalize(KernelFeatures::empty(), &self.resources.factories)
+            .finalize(KernelFeatures::empty(), &self.resources.factories, None, None)
             .map_err(|e| {
                 error!(
                     target: LOG_TARGET,
@@ -820,6 +829,16 @@ where TBackend: TransactionBackend + 'static
             .await
             .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
+        self.resources
+            .db
+            .record_transaction_cancellation(
+                self.id,
+                "Timed out waiting for the recipient to reply".to_string(),
+                true,
+            )
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
         let _ = self
             .resources
             .event_publisher
@@ -0,0 +1,217 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Templates for the TariScript programs that are commonly used to lock outputs, so that callers can construct them
+//! from typed parameters instead of assembling the opcodes by hand.
+
+use crate::{
+    multisig_service::crypto::multisig_script,
+    transaction_service::{error::ScriptTemplateError, service::sha_atomic_swap_hash},
+};
+use std::fmt;
+use tari_common_types::types::PublicKey;
+use tari_crypto::{script, script::TariScript, tari_utilities::hex::Hex};
+
+/// A TariScript program with typed parameters. Every template leaves a single public key on the stack, so the
+/// output can only be spent with a script signature from the owner of that key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptTemplate {
+    /// Spendable by the owner of `key` at any time
+    PayToKey { key: PublicKey },
+    /// Spendable by the owner of `key` once the chain has reached `height`
+    AbsoluteTimeLock { key: PublicKey, height: u64 },
+    /// Spendable by the owner of `key` once `blocks` blocks have been added to the chain. Scripts cannot inspect the
+    /// height at which their output was mined, so the lock is converted to an absolute height using the chain tip
+    /// when the script is built.
+    RelativeTimeLock { key: PublicKey, blocks: u64 },
+    /// Spendable by any `threshold` participants of a multisig session signing together for the session's group key,
+    /// see [multisig_script]
+    MultiSig { group_public_key: PublicKey },
+    /// Spendable by the owner of `key` when the input stack contains the pre-image of the SHA-256 `hash`
+    HashLock { hash: [u8; 32], key: PublicKey },
+}
+
+impl ScriptTemplate {
+    /// A hash lock that is opened with `pre_image`. The pre-image is a public key so that it can be provided as a
+    /// script input.
+    pub fn hash_lock(pre_image: &PublicKey, key: PublicKey) -> Self {
+        ScriptTemplate::HashLock {
+            hash: sha_atomic_swap_hash(pre_image),
+            key,
+        }
+    }
+
+    /// Returns true if the chain tip height must be provided to [ScriptTemplate::build]
+    pub fn requires_tip_height(&self) -> bool {
+        matches!(self, ScriptTemplate::RelativeTimeLock { .. })
+    }
+
+    /// Build the script. `tip_height` is only used by relative time locks.
+    pub fn build(&self, tip_height: Option<u64>) -> Result<TariScript, ScriptTemplateError> {
+        use ScriptTemplate::*;
+        let script = match self {
+            PayToKey { key } => script!(PushPubKey(Box::new(key.clone()))),
+            AbsoluteTimeLock { key, height } => time_lock_script(key, *height),
+            RelativeTimeLock { key, blocks } => {
+                let tip_height = tip_height.ok_or(ScriptTemplateError::ChainTipUnknown)?;
+                let height = tip_height
+                    .checked_add(*blocks)
+                    .ok_or(ScriptTemplateError::HeightOverflow(tip_height, *blocks))?;
+                time_lock_script(key, height)
+            },
+            MultiSig { group_public_key } => multisig_script(group_public_key),
+            HashLock { hash, key } => script!(
+                HashSha256 PushHash(Box::new(*hash)) EqualVerify PushPubKey(Box::new(key.clone()))
+            ),
+        };
+        Ok(script)
+    }
+}
+
+impl fmt::Display for ScriptTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ScriptTemplate::*;
+        match self {
+            PayToKey { key } => write!(f, "PayToKey({})", key),
+            AbsoluteTimeLock { key, height } => write!(f, "AbsoluteTimeLock({}, height {})", key, height),
+            RelativeTimeLock { key, blocks } => write!(f, "RelativeTimeLock({}, {} blocks)", key, blocks),
+            MultiSig { group_public_key } => write!(f, "MultiSig({})", group_public_key),
+            HashLock { hash, key } => write!(f, "HashLock({}, {})", hash.to_hex(), key),
+        }
+    }
+}
+
+fn time_lock_script(key: &PublicKey, height: u64) -> TariScript {
+    script!(CheckHeightVerify(height) PushPubKey(Box::new(key.clone())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_crypto::{
+        keys::PublicKey as PublicKeyTrait,
+        script::{ExecutionStack, Opcode, StackItem},
+    };
+
+    fn random_key() -> PublicKey {
+        PublicKey::random_keypair(&mut OsRng).1
+    }
+
+    fn round_trip(script: &TariScript) -> TariScript {
+        TariScript::from_bytes(&script.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn it_builds_and_serializes_each_template() {
+        let key = random_key();
+        let templates = vec![
+            ScriptTemplate::PayToKey { key: key.clone() },
+            ScriptTemplate::AbsoluteTimeLock {
+                key: key.clone(),
+                height: 1000,
+            },
+            ScriptTemplate::RelativeTimeLock {
+                key: key.clone(),
+                blocks: 50,
+            },
+            ScriptTemplate::MultiSig {
+                group_public_key: random_key(),
+            },
+            ScriptTemplate::hash_lock(&random_key(), key),
+        ];
+        for template in templates {
+            let script = template.build(Some(100)).unwrap();
+            assert_eq!(round_trip(&script), script, "{}", template);
+        }
+    }
+
+    #[test]
+    fn it_resolves_relative_time_locks_against_the_tip() {
+        let key = random_key();
+        let relative = ScriptTemplate::RelativeTimeLock {
+            key: key.clone(),
+            blocks: 50,
+        };
+        assert!(relative.requires_tip_height());
+        assert!(matches!(
+            relative.build(None),
+            Err(ScriptTemplateError::ChainTipUnknown)
+        ));
+        let absolute = ScriptTemplate::AbsoluteTimeLock { key, height: 150 };
+        assert!(!absolute.requires_tip_height());
+        assert_eq!(relative.build(Some(100)).unwrap(), absolute.build(None).unwrap());
+
+        let overflow = ScriptTemplate::RelativeTimeLock {
+            key: random_key(),
+            blocks: u64::MAX,
+        };
+        assert!(matches!(
+            overflow.build(Some(1)),
+            Err(ScriptTemplateError::HeightOverflow(1, u64::MAX))
+        ));
+    }
+
+    #[test]
+    fn it_builds_the_expected_opcodes() {
+        let key = random_key();
+        let script = ScriptTemplate::AbsoluteTimeLock {
+            key: key.clone(),
+            height: 10,
+        }
+        .build(None)
+        .unwrap();
+        assert_eq!(
+            script,
+            TariScript::new(vec![Opcode::CheckHeightVerify(10), Opcode::PushPubKey(Box::new(key))])
+        );
+    }
+
+    #[test]
+    fn it_opens_a_hash_lock_with_the_pre_image() {
+        let key = random_key();
+        let pre_image = random_key();
+        let script = ScriptTemplate::hash_lock(&pre_image, key.clone()).build(None).unwrap();
+        let result = script
+            .execute(&ExecutionStack::new(vec![StackItem::PublicKey(pre_image)]))
+            .unwrap();
+        assert_eq!(result, StackItem::PublicKey(key));
+        assert!(script
+            .execute(&ExecutionStack::new(vec![StackItem::PublicKey(random_key())]))
+            .is_err());
+    }
+
+    #[test]
+    fn it_locks_multisig_outputs_to_the_group_key() {
+        let group_public_key = random_key();
+        let script = ScriptTemplate::MultiSig {
+            group_public_key: group_public_key.clone(),
+        }
+        .build(None)
+        .unwrap();
+        assert_eq!(
+            script.execute(&ExecutionStack::default()).unwrap(),
+            StackItem::PublicKey(group_public_key)
+        );
+    }
+}
@@ -21,19 +21,33 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    output_manager_service::{handle::OutputManagerHandle, TxId},
+    output_manager_service::{
+        handle::OutputManagerHandle,
+        stealth::StealthOutputKeys,
+        storage::models::{AccountId, DEFAULT_ACCOUNT_ID},
+        TxId,
+    },
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::{
         config::TransactionServiceConfig,
-        error::{TransactionServiceError, TransactionServiceProtocolError},
+        error::{
+            OfflineSigningError,
+            TransactionServiceError,
+            TransactionServiceProtocolError,
+            TransactionStorageError,
+        },
         handle::{TransactionEvent, TransactionEventSender, TransactionServiceRequest, TransactionServiceResponse},
+        offline_signing::{OfflineInput, SignedTransactionPackage, UnsignedTransactionPackage},
+        output_memo,
         protocols::{
+            cancelled_output_release_protocol::CancelledOutputReleaseProtocol,
             transaction_broadcast_protocol::TransactionBroadcastProtocol,
             transaction_coinbase_monitoring_protocol::TransactionCoinbaseMonitoringProtocol,
             transaction_receive_protocol::{TransactionReceiveProtocol, TransactionReceiveProtocolStage},
             transaction_send_protocol::{TransactionSendProtocol, TransactionSendProtocolStage},
             transaction_validation_protocol::TransactionValidationProtocol,
         },
+        script_templates::ScriptTemplate,
         storage::{
             database::{TransactionBackend, TransactionDatabase},
             models::{CompletedTransaction, TransactionDirection, TransactionStatus},
@@ -52,13 +66,17 @@ use digest::Digest;
 use futures::{pin_mut, stream::FuturesUnordered, Stream, StreamExt};
 use log::*;
 use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
 use std::{
     collections::{HashMap, HashSet},
     convert::TryInto,
     sync::Arc,
     time::{Duration, Instant},
 };
-use tari_common_types::types::PrivateKey;
+use tari_common_types::{
+    stealth_address::StealthAddress,
+    types::{HashOutput, PrivateKey, PublicKey},
+};
 use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeIdentity, types::CommsPublicKey};
 use tari_comms_dht::outbound::OutboundMessageRequester;
 use tari_core::{
@@ -66,7 +84,7 @@ use tari_core::{
     proto::base_node as base_node_proto,
     transactions::{
         tari_amount::MicroTari,
-        transaction::{KernelFeatures, OutputFeatures, Transaction},
+        transaction::{KernelFeatures, OutputFeatures, Transaction, TransactionOutput, UnblindedOutput},
         transaction_protocol::{
             proto,
             recipient::RecipientSignedMessage,
@@ -77,7 +95,14 @@ use tari_core::{
         ReceiverTransactionProtocol,
     },
 };
-use tari_crypto::{keys::DiffieHellmanSharedSecret, script, tari_utilities::ByteArray};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    inputs,
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait},
+    script,
+    script::TariScript,
+    tari_utilities::{ByteArray, Hashable},
+};
 use tari_p2p::domain_message::DomainMessage;
 use tari_service_framework::{reply_channel, reply_channel::Receiver};
 use tari_shutdown::ShutdownSignal;
@@ -87,6 +112,8 @@ use tokio::{
 };
 
 const LOG_TARGET: &str = "wallet::transaction_service::service";
+/// How often the outputs held back by cancelled transactions are checked for release
+const CANCELLED_OUTPUT_RELEASE_CHECK_INTERVAL: Duration = Duration::from_secs(300);
 
 /// TransactionService allows for the management of multiple inbound and outbound transaction protocols
 /// which are uniquely identified by a tx_id. The TransactionService generates and accepts the various protocol
@@ -288,6 +315,11 @@ where
             JoinHandle<Result<u64, TransactionServiceProtocolError>>,
         > = FuturesUnordered::new();
 
+        let mut cancelled_output_release_protocol_handles: FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        > = FuturesUnordered::new();
+        let mut cancelled_output_release_interval = tokio::time::interval(CANCELLED_OUTPUT_RELEASE_CHECK_INTERVAL);
+
         info!(target: LOG_TARGET, "Transaction Service started");
         loop {
             tokio::select! {
@@ -491,6 +523,20 @@ where
                         Ok(join_result_inner) => self.complete_transaction_validation_protocol(join_result_inner).await,
                         Err(e) => error!(target: LOG_TARGET, "Error resolving Transaction Validation protocol: {:?}", e),
                     };
+                }
+                _ = cancelled_output_release_interval.tick() => {
+                    if cancelled_output_release_protocol_handles.is_empty() {
+                        self.start_cancelled_output_release_protocol(&mut cancelled_output_release_protocol_handles);
+                    }
+                }
+                Some(join_result) = cancelled_output_release_protocol_handles.next() => {
+                    trace!(target: LOG_TARGET, "Cancelled Output Release protocol has ended with result {:?}", join_result);
+                    match join_result {
+                        Ok(Err(TransactionServiceProtocolError { id, error })) => warn!(target: LOG_TARGET,
+                            "Error completing Cancelled Output Release protocol (id: {}): {:?}", id, error),
+                        Err(e) => error!(target: LOG_TARGET, "Error resolving Cancelled Output Release protocol: {:?}", e),
+                        Ok(Ok(_)) => (),
+                    };
                 }
                  _ = shutdown.wait() => {
                     info!(target: LOG_TARGET, "Transaction service shutting down because it received the shutdown signal");
@@ -528,6 +574,7 @@ where
             TransactionServiceRequest::SendTransaction(dest_pubkey, amount, fee_per_gram, message) => {
                 let rp = reply_channel.take().expect("Cannot be missing");
                 self.send_transaction(
+                    DEFAULT_ACCOUNT_ID,
                     dest_pubkey,
                     amount,
                     fee_per_gram,
@@ -541,18 +588,158 @@ where
             },
             TransactionServiceRequest::SendOneSidedTransaction(dest_pubkey, amount, fee_per_gram, message) => self
                 .send_one_sided_transaction(
+                    DEFAULT_ACCOUNT_ID,
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendTransactionFromAccount(
+                account_id,
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            ) => {
+                let rp = reply_channel.take().expect("Cannot be missing");
+                self.send_transaction(
+                    account_id,
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    send_transaction_join_handles,
+                    transaction_broadcast_join_handles,
+                    rp,
+                )
+                .await?;
+                return Ok(());
+            },
+            TransactionServiceRequest::SendOneSidedTransactionFromAccount(
+                account_id,
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            ) => self
+                .send_one_sided_transaction(
+                    account_id,
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::TransferBetweenAccounts(
+                from_account,
+                to_account,
+                amount,
+                fee_per_gram,
+                message,
+            ) => self
+                .transfer_between_accounts(
+                    from_account,
+                    to_account,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendStealthTransaction(address, amount, fee_per_gram, message) => self
+                .send_stealth_transaction(
+                    address,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendTemplatedTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                template,
+                message,
+            ) => self
+                .send_templated_transaction(
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    template,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendShaAtomicSwapTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                refund_height,
+                message,
+            ) => self
+                .send_sha_atomic_swap_transaction(
                     dest_pubkey,
                     amount,
                     fee_per_gram,
+                    refund_height,
                     message,
                     transaction_broadcast_join_handles,
                 )
                 .await
+                .map(|result| TransactionServiceResponse::ShaAtomicSwapTransactionSent(Box::new(result))),
+            TransactionServiceRequest::SendMultisigFundingTransaction(group_public_key, amount, fee_per_gram) => self
+                .send_multisig_funding_transaction(
+                    group_public_key,
+                    amount,
+                    fee_per_gram,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(|result| TransactionServiceResponse::MultisigFundingTransactionSent(Box::new(result))),
+            TransactionServiceRequest::ClaimShaAtomicSwapTransaction(output, pre_image, fee_per_gram) => self
+                .claim_sha_atomic_swap_transaction(*output, pre_image, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::RefundShaAtomicSwapTransaction(tx_id, fee_per_gram) => self
+                .refund_sha_atomic_swap_transaction(tx_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::BurnTari(amount, fee_per_gram, message) => self
+                .burn_tari(amount, fee_per_gram, message, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::ExportUnsignedTransaction(dest_pubkey, amount, fee_per_gram, message) => self
+                .export_unsigned_transaction(dest_pubkey, amount, fee_per_gram, message)
+                .await
+                .map(|package| TransactionServiceResponse::UnsignedTransactionExported(Box::new(package))),
+            TransactionServiceRequest::SignOfflineTransaction(package) => self
+                .sign_offline_transaction(*package)
+                .await
+                .map(|package| TransactionServiceResponse::OfflineTransactionSigned(Box::new(package))),
+            TransactionServiceRequest::BroadcastSignedTransaction(package) => self
+                .broadcast_signed_transaction(*package, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::BumpFee(tx_id, fee_per_gram) => self
+                .bump_fee(tx_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
                 .map(TransactionServiceResponse::TransactionSent),
-            TransactionServiceRequest::CancelTransaction(tx_id) => self
-                .cancel_pending_transaction(tx_id)
+            TransactionServiceRequest::CancelTransaction(tx_id, reason) => self
+                .cancel_transaction(tx_id, reason)
                 .await
                 .map(|_| TransactionServiceResponse::TransactionCancelled),
+            TransactionServiceRequest::GetTransactionCancellation(tx_id) => Ok(
+                TransactionServiceResponse::TransactionCancellation(self.db.get_transaction_cancellation(tx_id).await?),
+            ),
             TransactionServiceRequest::GetPendingInboundTransactions => {
                 Ok(TransactionServiceResponse::PendingInboundTransactions(
                     self.db.get_pending_inbound_transactions().await?,
@@ -567,6 +754,11 @@ where
             TransactionServiceRequest::GetCompletedTransactions => Ok(
                 TransactionServiceResponse::CompletedTransactions(self.db.get_completed_transactions().await?),
             ),
+            TransactionServiceRequest::GetAccountCompletedTransactions(account_id) => {
+                Ok(TransactionServiceResponse::CompletedTransactions(
+                    self.db.get_account_completed_transactions(account_id).await?,
+                ))
+            },
             TransactionServiceRequest::GetCancelledPendingInboundTransactions => {
                 Ok(TransactionServiceResponse::PendingInboundTransactions(
                     self.db.get_cancelled_pending_inbound_transactions().await?,
@@ -654,181 +846,917 @@ where
                 .map(|_| TransactionServiceResponse::CompletedTransactionValidityChanged),
         };
 
-        // If the individual handlers did not already send the API response then do it here.
-        if let Some(rp) = reply_channel {
-            let _ = rp.send(response).map_err(|e| {
-                warn!(target: LOG_TARGET, "Failed to send reply");
-                e
-            });
-        }
-        Ok(())
+        // If the individual handlers did not already send the API response then do it here.
+        if let Some(rp) = reply_channel {
+            let _ = rp.send(response).map_err(|e| {
+                warn!(target: LOG_TARGET, "Failed to send reply");
+                e
+            });
+        }
+        Ok(())
+    }
+
+    /// Sends a new transaction to a recipient
+    /// # Arguments
+    /// 'account_id': The account whose outputs are spent
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_transaction(
+        &mut self,
+        account_id: AccountId,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        join_handles: &mut FuturesUnordered<JoinHandle<Result<u64, TransactionServiceProtocolError>>>,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) -> Result<(), TransactionServiceError> {
+        let tx_id = OsRng.next_u64();
+        if account_id != DEFAULT_ACCOUNT_ID {
+            self.db.add_transaction_to_account(tx_id, account_id).await?;
+        }
+
+        // If we're paying ourselves, let's complete and submit the transaction immediately
+        if self.node_identity.public_key() == &dest_pubkey {
+            debug!(
+                target: LOG_TARGET,
+                "Received transaction with spend-to-self transaction"
+            );
+
+            let (fee, transaction) = self
+                .output_manager_service
+                .create_account_transfer_transaction(
+                    tx_id,
+                    account_id,
+                    account_id,
+                    amount,
+                    fee_per_gram,
+                    message.clone(),
+                )
+                .await?;
+
+            // Notify that the transaction was successfully resolved.
+            let _ = self
+                .event_publisher
+                .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+            self.submit_transaction(
+                transaction_broadcast_join_handles,
+                CompletedTransaction::new(
+                    tx_id,
+                    self.node_identity.public_key().clone(),
+                    self.node_identity.public_key().clone(),
+                    amount,
+                    fee,
+                    transaction,
+                    TransactionStatus::Completed,
+                    message,
+                    Utc::now().naive_utc(),
+                    TransactionDirection::Inbound,
+                    None,
+                ),
+            )
+            .await?;
+
+            let _ = reply_channel
+                .send(Ok(TransactionServiceResponse::TransactionSent(tx_id)))
+                .map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+
+            return Ok(());
+        }
+
+        let (tx_reply_sender, tx_reply_receiver) = mpsc::channel(100);
+        let (cancellation_sender, cancellation_receiver) = oneshot::channel();
+        self.pending_transaction_reply_senders.insert(tx_id, tx_reply_sender);
+        self.send_transaction_cancellation_senders
+            .insert(tx_id, cancellation_sender);
+
+        let protocol = TransactionSendProtocol::new(
+            tx_id,
+            self.resources.clone(),
+            tx_reply_receiver,
+            cancellation_receiver,
+            account_id,
+            dest_pubkey,
+            amount,
+            fee_per_gram,
+            message,
+            Some(reply_channel),
+            TransactionSendProtocolStage::Initial,
+        );
+
+        let join_handle = tokio::spawn(protocol.execute());
+        join_handles.push(join_handle);
+
+        Ok(())
+    }
+
+    /// Moves funds between two accounts of this wallet with a transaction to self. The transaction is part of the
+    /// history of both accounts.
+    pub async fn transfer_between_accounts(
+        &mut self,
+        from_account: AccountId,
+        to_account: AccountId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let tx_id = OsRng.next_u64();
+        let (fee, transaction) = self
+            .output_manager_service
+            .create_account_transfer_transaction(tx_id, from_account, to_account, amount, fee_per_gram, message.clone())
+            .await?;
+        for account_id in [from_account, to_account].iter() {
+            if *account_id != DEFAULT_ACCOUNT_ID {
+                self.db.add_transaction_to_account(tx_id, *account_id).await?;
+            }
+        }
+
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        self.submit_transaction(
+            transaction_broadcast_join_handles,
+            CompletedTransaction::new(
+                tx_id,
+                self.node_identity.public_key().clone(),
+                self.node_identity.public_key().clone(),
+                amount,
+                fee,
+                transaction,
+                TransactionStatus::Completed,
+                message,
+                Utc::now().naive_utc(),
+                TransactionDirection::Inbound,
+                None,
+            ),
+        )
+        .await?;
+        info!(
+            target: LOG_TARGET,
+            "Transferred {} from account {} to account {} (TxId: {})", amount, from_account, to_account, tx_id
+        );
+
+        Ok(tx_id)
+    }
+
+    /// Sends a one side payment transaction to a recipient
+    /// # Arguments
+    /// 'account_id': The account whose outputs are spent
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    pub async fn send_one_sided_transaction(
+        &mut self,
+        account_id: AccountId,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            warn!(target: LOG_TARGET, "One-sided spend-to-self transactions not supported");
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "One-sided spend-to-self transactions not supported".to_string(),
+            ));
+        }
+
+        let script = script!(PushPubKey(Box::new(dest_pubkey.clone())));
+        let (tx_id, _) = self
+            .send_one_sided_transaction_with_script(
+                account_id,
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+                script,
+                transaction_broadcast_join_handles,
+            )
+            .await?;
+
+        Ok(tx_id)
+    }
+
+    /// Sends a one-sided payment to a recipient with the output locked by the script built from `template`. Relative
+    /// time locks are resolved against the last chain tip received from the base node.
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    /// 'template': The script template that locks the recipient's output
+    pub async fn send_templated_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        template: ScriptTemplate,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            warn!(target: LOG_TARGET, "One-sided spend-to-self transactions not supported");
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "One-sided spend-to-self transactions not supported".to_string(),
+            ));
+        }
+
+        let tip_height = if template.requires_tip_height() {
+            self.wallet_db
+                .get_chain_metadata()
+                .await?
+                .map(|metadata| metadata.height_of_longest_chain())
+        } else {
+            None
+        };
+        let script = template.build(tip_height)?;
+        let (tx_id, _) = self
+            .send_one_sided_transaction_with_script(
+                DEFAULT_ACCOUNT_ID,
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+                script,
+                transaction_broadcast_join_handles,
+            )
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "Sent templated transaction TxId: {} locked with {}", tx_id, template
+        );
+
+        Ok(tx_id)
+    }
+
+    /// Sends a hash time locked contract (HTLC) payment to a recipient. The recipient can claim the funds with the
+    /// returned pre-image, otherwise this wallet can reclaim the funds once `refund_height` has been reached.
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    /// 'refund_height': The block height from which the funds can be refunded to this wallet
+    pub async fn send_sha_atomic_swap_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        refund_height: u64,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<(TxId, PublicKey, HashOutput), TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            warn!(target: LOG_TARGET, "Atomic swap spend-to-self transactions not supported");
+            return Err(TransactionServiceError::AtomicSwapTransactionError(
+                "Atomic swap spend-to-self transactions not supported".to_string(),
+            ));
+        }
+
+        // The pre-image is a random public key, so that it can be provided as script input on the stack
+        let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let script = sha_atomic_swap_script(
+            &pre_image,
+            &dest_pubkey,
+            self.resources.node_identity.public_key(),
+            refund_height,
+        );
+        let (tx_id, output) = self
+            .send_one_sided_transaction_with_script(
+                DEFAULT_ACCOUNT_ID,
+                dest_pubkey.clone(),
+                amount,
+                fee_per_gram,
+                message,
+                script.clone(),
+                transaction_broadcast_join_handles,
+            )
+            .await?;
+
+        // Keep the HTLC output so that this wallet is able to reclaim the funds if the swap is never completed
+        let spending_key = PrivateKey::from_bytes(
+            CommsPublicKey::shared_secret(&output.sender_offset_private_key, &dest_pubkey).as_bytes(),
+        )?;
+        let refundable_output = UnblindedOutput::new(
+            amount,
+            spending_key,
+            output.output.features.clone(),
+            script,
+            inputs!(PublicKey::default()),
+            self.resources.node_identity.secret_key().clone(),
+            output.output.sender_offset_public_key.clone(),
+            output.output.metadata_signature.clone(),
+        );
+        self.output_manager_service
+            .add_refundable_htlc_output(tx_id, refundable_output)
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "Sent atomic swap transaction TxId: {} with refund height {}", tx_id, refund_height
+        );
+
+        Ok((tx_id, pre_image, output.output.hash()))
+    }
+
+    /// Sends a one-sided payment to the group key of a multisig session. Returns the transaction id, the multisig
+    /// output and its spending key.
+    pub async fn send_multisig_funding_transaction(
+        &mut self,
+        group_public_key: PublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<(TxId, TransactionOutput, PrivateKey), TransactionServiceError> {
+        // No one knows the private key of the group key, so a memo encrypted for it could never be read
+        let script = script!(PushPubKey(Box::new(group_public_key.clone())));
+        let (tx_id, output) = self
+            .send_one_sided_transaction_with_script(
+                DEFAULT_ACCOUNT_ID,
+                group_public_key.clone(),
+                amount,
+                fee_per_gram,
+                String::new(),
+                script,
+                transaction_broadcast_join_handles,
+            )
+            .await?;
+        let spending_key = PrivateKey::from_bytes(
+            CommsPublicKey::shared_secret(&output.sender_offset_private_key, &group_public_key).as_bytes(),
+        )?;
+        info!(target: LOG_TARGET, "Sent multisig funding transaction TxId: {}", tx_id);
+
+        Ok((tx_id, output.output, spending_key))
+    }
+
+    /// Claim the funds locked in a hash time locked contract output sent to this wallet, using the pre-image that was
+    /// provided by the sender.
+    pub async fn claim_sha_atomic_swap_transaction(
+        &mut self,
+        output: TransactionOutput,
+        pre_image: PublicKey,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        // The HTLC output was sent as a one-sided payment, so the spending key is the Diffie-Hellman shared secret
+        let spending_key = PrivateKey::from_bytes(
+            CommsPublicKey::shared_secret(
+                self.resources.node_identity.secret_key(),
+                &output.sender_offset_public_key,
+            )
+            .as_bytes(),
+        )?;
+        let rewind_key = PrivateKey::from_bytes(&hash_secret_key(&spending_key))?;
+        let blinding_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_key))?;
+        let rewound = output
+            .full_rewind_range_proof(&self.resources.factories.range_proof, &rewind_key, &blinding_key)
+            .map_err(|_| {
+                TransactionServiceError::AtomicSwapTransactionError(
+                    "The atomic swap output was not sent to this wallet".to_string(),
+                )
+            })?;
+        if rewound.blinding_factor != spending_key {
+            return Err(TransactionServiceError::AtomicSwapTransactionError(
+                "The atomic swap output was not sent to this wallet".to_string(),
+            ));
+        }
+
+        let htlc_output = UnblindedOutput::new(
+            rewound.committed_value,
+            spending_key,
+            output.features,
+            output.script,
+            inputs!(pre_image),
+            self.resources.node_identity.secret_key().clone(),
+            output.sender_offset_public_key,
+            output.metadata_signature,
+        );
+        let (tx_id, tx, fee, amount) = self
+            .output_manager_service
+            .create_htlc_claim_transaction(htlc_output, fee_per_gram)
+            .await?;
+        info!(target: LOG_TARGET, "Claiming atomic swap output in TxId: {}", tx_id);
+        self.submit_coin_split_transaction(
+            transaction_broadcast_join_handles,
+            tx_id,
+            tx,
+            fee,
+            amount,
+            "Claimed atomic swap".to_string(),
+        )
+        .await?;
+
+        Ok(tx_id)
+    }
+
+    /// Reclaim the funds locked in a hash time locked contract output sent by this wallet. This is only valid once
+    /// the refund height of the contract has been reached.
+    pub async fn refund_sha_atomic_swap_transaction(
+        &mut self,
+        htlc_tx_id: TxId,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let (tx_id, tx, fee, amount) = self
+            .output_manager_service
+            .create_htlc_refund_transaction(htlc_tx_id, fee_per_gram)
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "Refunding atomic swap TxId: {} in TxId: {}", htlc_tx_id, tx_id
+        );
+        self.submit_coin_split_transaction(
+            transaction_broadcast_join_handles,
+            tx_id,
+            tx,
+            fee,
+            amount,
+            "Refunded atomic swap".to_string(),
+        )
+        .await?;
+
+        Ok(tx_id)
+    }
+
+    /// Bump the fee of a stuck transaction sent by this wallet by spending its pending change back into the wallet in a
+    /// child-pays-for-parent transaction. The base node mines a transaction together with the unconfirmed transactions
+    /// it spends from, so the new transaction pays enough for both to be mined at `fee_per_gram`.
+    pub async fn bump_fee(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let parent = match self.db.get_completed_transaction(tx_id).await {
+            Ok(tx) => tx,
+            Err(TransactionStorageError::ValueNotFound(_)) => {
+                return Err(TransactionServiceError::TransactionCannotBeFeeBumped(
+                    tx_id,
+                    "it is not a completed transaction that is still active".to_string(),
+                ))
+            },
+            Err(e) => return Err(e.into()),
+        };
+        if &parent.source_public_key != self.node_identity.public_key() {
+            return Err(TransactionServiceError::TransactionCannotBeFeeBumped(
+                tx_id,
+                "it was not sent by this wallet".to_string(),
+            ));
+        }
+        if !matches!(
+            parent.status,
+            TransactionStatus::Completed | TransactionStatus::Broadcast
+        ) {
+            return Err(TransactionServiceError::TransactionCannotBeFeeBumped(
+                tx_id,
+                format!("its status is {}", parent.status),
+            ));
+        }
+        let parent_weight = parent.transaction.calculate_weight();
+        if u64::from(fee_per_gram) * parent_weight <= u64::from(parent.fee) {
+            return Err(TransactionServiceError::TransactionCannotBeFeeBumped(
+                tx_id,
+                format!("it already pays at least {} per gram", fee_per_gram),
+            ));
+        }
+        for fee_bump in self.db.get_fee_bumps_for_parent(tx_id).await? {
+            if self.db.get_completed_transaction(fee_bump.tx_id).await.is_ok() {
+                return Err(TransactionServiceError::TransactionCannotBeFeeBumped(
+                    tx_id,
+                    format!(
+                        "it has already been bumped by transaction {}, bump that transaction instead",
+                        fee_bump.tx_id
+                    ),
+                ));
+            }
+        }
+
+        let (fee_bump_tx_id, tx, fee, amount) = self
+            .output_manager_service
+            .create_fee_bump_transaction(tx_id, parent.fee, parent_weight, fee_per_gram)
+            .await?;
+        // The link is recorded before the broadcast starts so that a rejected fee bump hands the change back to its
+        // parent
+        self.db.record_fee_bump(fee_bump_tx_id, tx_id, fee_per_gram).await?;
+        info!(
+            target: LOG_TARGET,
+            "Bumping the fee of TxId: {} to {} per gram in TxId: {} (fee: {})", tx_id, fee_per_gram, fee_bump_tx_id, fee
+        );
+        self.submit_coin_split_transaction(
+            transaction_broadcast_join_handles,
+            fee_bump_tx_id,
+            tx,
+            fee,
+            amount,
+            format!("Fee bump for transaction {}", tx_id),
+        )
+        .await?;
+
+        Ok(fee_bump_tx_id)
+    }
+
+    /// Reserves the inputs of a one-sided payment to a recipient and exports them as a package to be signed by an
+    /// offline wallet holding the same keys. The inputs stay encumbered until the signed transaction is broadcast and
+    /// mined, or the pending transaction times out.
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    pub async fn export_unsigned_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<UnsignedTransactionPackage, TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            warn!(target: LOG_TARGET, "One-sided spend-to-self transactions not supported");
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "One-sided spend-to-self transactions not supported".to_string(),
+            ));
+        }
+
+        let tx_id = OsRng.next_u64();
+        let inputs = self
+            .output_manager_service
+            .reserve_outputs_for_offline_transaction(tx_id, amount, fee_per_gram)
+            .await?
+            .into_iter()
+            .map(|(output, value)| OfflineInput { output, value })
+            .collect();
+        info!(
+            target: LOG_TARGET,
+            "Exported unsigned transaction TxId: {} for offline signing", tx_id
+        );
+
+        Ok(UnsignedTransactionPackage::new(
+            tx_id,
+            dest_pubkey,
+            amount,
+            fee_per_gram,
+            message,
+            inputs,
+        ))
+    }
+
+    /// Signs a one-sided payment exported by an online wallet, spending exactly the inputs it reserved. The inputs are
+    /// rewound rather than looked up in this wallet's database and nothing is stored, so the signing wallet needs no
+    /// more than its seed. The transaction is not broadcast, since the signing wallet is expected to be offline.
+    pub async fn sign_offline_transaction(
+        &mut self,
+        package: UnsignedTransactionPackage,
+    ) -> Result<SignedTransactionPackage, TransactionServiceError> {
+        let script = script!(PushPubKey(Box::new(package.destination.clone())));
+        let (recipient_features, recipient_keys) = one_sided_recipient(&package.destination, &package.message)?;
+        let inputs = package.inputs.iter().map(|i| i.output.clone()).collect();
+        let (tx, fee, _) = self
+            .build_one_sided_transaction(
+                DEFAULT_ACCOUNT_ID,
+                package.tx_id,
+                Some(inputs),
+                package.amount,
+                package.fee_per_gram,
+                package.message.clone(),
+                script,
+                recipient_features,
+                recipient_keys,
+            )
+            .await?;
+        info!(target: LOG_TARGET, "Signed offline transaction TxId: {}", package.tx_id);
+
+        Ok(SignedTransactionPackage::new(&package, tx, fee))
+    }
+
+    /// Broadcasts a one-sided payment signed by an offline wallet. The transaction must spend exactly the inputs that
+    /// this wallet reserved when exporting it.
+    pub async fn broadcast_signed_transaction(
+        &mut self,
+        package: SignedTransactionPackage,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let tx_id = package.tx_id;
+        let reserved = self
+            .output_manager_service
+            .get_pending_transactions()
+            .await?
+            .remove(&tx_id)
+            .ok_or(OfflineSigningError::OutputsNotReserved(tx_id))?
+            .outputs_to_be_spent
+            .into_iter()
+            .map(|o| o.commitment)
+            .collect::<HashSet<_>>();
+        let spent = package
+            .transaction
+            .body
+            .inputs()
+            .iter()
+            .map(|i| i.commitment.clone())
+            .collect::<HashSet<_>>();
+        if spent != reserved || spent.len() != package.transaction.body.inputs().len() {
+            return Err(OfflineSigningError::InputMismatch(tx_id).into());
+        }
+        package
+            .transaction
+            .validate_internal_consistency(false, &self.resources.factories, None, None, None)?;
+        self.output_manager_service
+            .add_offline_change_outputs(tx_id, package.transaction.body.outputs().clone())
+            .await?;
+
+        self.submit_transaction(
+            transaction_broadcast_join_handles,
+            CompletedTransaction::new(
+                tx_id,
+                self.resources.node_identity.public_key().clone(),
+                package.destination,
+                package.amount,
+                package.fee,
+                package.transaction,
+                TransactionStatus::Completed,
+                package.message,
+                Utc::now().naive_utc(),
+                TransactionDirection::Outbound,
+                None,
+            ),
+        )
+        .await?;
+        info!(target: LOG_TARGET, "Broadcast offline signed transaction TxId: {}", tx_id);
+
+        Ok(tx_id)
+    }
+
+    /// Burn the given amount. The burn is recorded in a burn kernel and any change is returned to this wallet.
+    pub async fn burn_tari(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let (tx_id, tx, fee, _) = self
+            .output_manager_service
+            .create_burn_transaction(amount, fee_per_gram, None)
+            .await?;
+        info!(target: LOG_TARGET, "Burning {} in TxId: {}", amount, tx_id);
+        self.submit_coin_split_transaction(transaction_broadcast_join_handles, tx_id, tx, fee, amount, message)
+            .await?;
+
+        Ok(tx_id)
     }
 
-    /// Sends a new transaction to a recipient
+    /// Sends a one-sided payment to a stealth address. The recipient's output is locked to a one-time key that only
+    /// the recipient can link to their address, and any message is attached as a memo encrypted to the scan key.
     /// # Arguments
-    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'address': The stealth address of the recipient
     /// 'amount': The amount of Tari to send to the recipient
     /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
-    pub async fn send_transaction(
+    pub async fn send_stealth_transaction(
         &mut self,
-        dest_pubkey: CommsPublicKey,
+        address: StealthAddress,
         amount: MicroTari,
         fee_per_gram: MicroTari,
         message: String,
-        join_handles: &mut FuturesUnordered<JoinHandle<Result<u64, TransactionServiceProtocolError>>>,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<u64, TransactionServiceProtocolError>>,
         >,
-        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
-    ) -> Result<(), TransactionServiceError> {
-        let tx_id = OsRng.next_u64();
-
-        // If we're paying ourselves, let's complete and submit the transaction immediately
-        if self.node_identity.public_key() == &dest_pubkey {
-            debug!(
-                target: LOG_TARGET,
-                "Received transaction with spend-to-self transaction"
-            );
-
-            let (fee, transaction) = self
-                .output_manager_service
-                .create_pay_to_self_transaction(tx_id, amount, fee_per_gram, None, message.clone())
-                .await?;
-
-            // Notify that the transaction was successfully resolved.
-            let _ = self
-                .event_publisher
-                .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+    ) -> Result<TxId, TransactionServiceError> {
+        let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+        let stealth_keys = StealthOutputKeys::for_recipient(&address, &sender_offset_private_key)?;
+        let recipient_features = if message.is_empty() {
+            OutputFeatures::default()
+        } else {
+            OutputFeatures::with_encrypted_memo(output_memo::encrypt_memo(&address.scan_key, &message)?)
+        };
 
-            self.submit_transaction(
+        // A stealth address is not a comms public key, so the spend key stands in for it in the transaction history
+        let (tx_id, _) = self
+            .send_one_sided_output(
+                DEFAULT_ACCOUNT_ID,
+                address.spend_key.clone(),
+                amount,
+                fee_per_gram,
+                message,
+                stealth_keys.script(),
+                recipient_features,
+                OneSidedRecipientKeys {
+                    sender_offset_private_key,
+                    spending_key: stealth_keys.spending_key,
+                    rewind_data: stealth_keys.rewind_data,
+                },
                 transaction_broadcast_join_handles,
-                CompletedTransaction::new(
-                    tx_id,
-                    self.node_identity.public_key().clone(),
-                    self.node_identity.public_key().clone(),
-                    amount,
-                    fee,
-                    transaction,
-                    TransactionStatus::Completed,
-                    message,
-                    Utc::now().naive_utc(),
-                    TransactionDirection::Inbound,
-                    None,
-                ),
             )
             .await?;
 
-            let _ = reply_channel
-                .send(Ok(TransactionServiceResponse::TransactionSent(tx_id)))
-                .map_err(|e| {
-                    warn!(target: LOG_TARGET, "Failed to send service reply");
-                    e
-                });
-
-            return Ok(());
-        }
-
-        let (tx_reply_sender, tx_reply_receiver) = mpsc::channel(100);
-        let (cancellation_sender, cancellation_receiver) = oneshot::channel();
-        self.pending_transaction_reply_senders.insert(tx_id, tx_reply_sender);
-        self.send_transaction_cancellation_senders
-            .insert(tx_id, cancellation_sender);
+        Ok(tx_id)
+    }
 
-        let protocol = TransactionSendProtocol::new(
-            tx_id,
-            self.resources.clone(),
-            tx_reply_receiver,
-            cancellation_receiver,
+    /// Sends a one-sided transaction where the recipient's output is locked with the provided script. Returns the
+    /// transaction id and the recipient's output.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_one_sided_transaction_with_script(
+        &mut self,
+        account_id: AccountId,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        script: TariScript,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<(TxId, OneSidedRecipientOutput), TransactionServiceError> {
+        let (recipient_features, recipient_keys) = one_sided_recipient(&dest_pubkey, &message)?;
+        self.send_one_sided_output(
+            account_id,
             dest_pubkey,
             amount,
             fee_per_gram,
             message,
-            Some(reply_channel),
-            TransactionSendProtocolStage::Initial,
-        );
-
-        let join_handle = tokio::spawn(protocol.execute());
-        join_handles.push(join_handle);
-
-        Ok(())
+            script,
+            recipient_features,
+            recipient_keys,
+            transaction_broadcast_join_handles,
+        )
+        .await
     }
 
-    /// Sends a one side payment transaction to a recipient
-    /// # Arguments
-    /// 'dest_pubkey': The Comms pubkey of the recipient node
-    /// 'amount': The amount of Tari to send to the recipient
-    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
-    pub async fn send_one_sided_transaction(
+    /// Builds, finalizes and broadcasts a one-sided transaction, playing the part of the recipient with the given
+    /// recipient output keys
+    #[allow(clippy::too_many_arguments)]
+    async fn send_one_sided_output(
         &mut self,
+        account_id: AccountId,
         dest_pubkey: CommsPublicKey,
         amount: MicroTari,
         fee_per_gram: MicroTari,
         message: String,
+        script: TariScript,
+        recipient_features: OutputFeatures,
+        recipient_keys: OneSidedRecipientKeys,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<u64, TransactionServiceProtocolError>>,
         >,
-    ) -> Result<TxId, TransactionServiceError> {
-        if self.node_identity.public_key() == &dest_pubkey {
-            warn!(target: LOG_TARGET, "One-sided spend-to-self transactions not supported");
-            return Err(TransactionServiceError::OneSidedTransactionError(
-                "One-sided spend-to-self transactions not supported".to_string(),
-            ));
-        }
-
+    ) -> Result<(TxId, OneSidedRecipientOutput), TransactionServiceError> {
         let tx_id = OsRng.next_u64();
-
-        // Prepare sender part of the transaction
-        let mut stp = self
-            .output_manager_service
-            .prepare_transaction_to_send(
+        if account_id != DEFAULT_ACCOUNT_ID {
+            self.db.add_transaction_to_account(tx_id, account_id).await?;
+        }
+        let (tx, fee, recipient_output) = self
+            .build_one_sided_transaction(
+                account_id,
                 tx_id,
+                None,
                 amount,
                 fee_per_gram,
-                None,
                 message.clone(),
-                script!(PushPubKey(Box::new(dest_pubkey.clone()))),
+                script,
+                recipient_features,
+                recipient_keys,
             )
             .await?;
 
+        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
+        // there are no subscribers.
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        // Broadcast one-sided transaction
+
+        self.submit_transaction(
+            transaction_broadcast_join_handles,
+            CompletedTransaction::new(
+                tx_id,
+                self.resources.node_identity.public_key().clone(),
+                dest_pubkey.clone(),
+                amount,
+                fee,
+                tx,
+                TransactionStatus::Completed,
+                message,
+                Utc::now().naive_utc(),
+                TransactionDirection::Outbound,
+                None,
+            ),
+        )
+        .await?;
+
+        Ok((tx_id, recipient_output))
+    }
+
+    /// Builds and finalizes a one-sided transaction, playing the part of the recipient with the given recipient output
+    /// keys. The output manager selects the inputs from the given account unless `inputs` names the outputs to spend.
+    /// Returns the transaction, its fee and the recipient's output.
+    #[allow(clippy::too_many_arguments)]
+    async fn build_one_sided_transaction(
+        &mut self,
+        account_id: AccountId,
+        tx_id: TxId,
+        inputs: Option<Vec<TransactionOutput>>,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        script: TariScript,
+        recipient_features: OutputFeatures,
+        recipient_keys: OneSidedRecipientKeys,
+    ) -> Result<(Transaction, MicroTari, OneSidedRecipientOutput), TransactionServiceError> {
+        let OneSidedRecipientKeys {
+            sender_offset_private_key,
+            spending_key,
+            rewind_data,
+        } = recipient_keys;
+
+        // Prepare sender part of the transaction. The inputs of an offline transaction are not encumbered by this
+        // wallet, so there is no pending transaction to confirm.
+        let mut stp = match inputs {
+            None => {
+                let stp = self
+                    .output_manager_service
+                    .prepare_transaction_to_send_with_features(
+                        account_id,
+                        tx_id,
+                        amount,
+                        fee_per_gram,
+                        None,
+                        message,
+                        script,
+                        recipient_features,
+                        Some(sender_offset_private_key.clone()),
+                    )
+                    .await?;
+                self.output_manager_service
+                    .confirm_pending_transaction(tx_id)
+                    .await
+                    .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+                stp
+            },
+            Some(inputs) => {
+                self.output_manager_service
+                    .prepare_offline_transaction(
+                        tx_id,
+                        inputs,
+                        amount,
+                        fee_per_gram,
+                        message,
+                        script,
+                        recipient_features,
+                        Some(sender_offset_private_key.clone()),
+                    )
+                    .await?
+            },
+        };
+
         // This call is needed to advance the state from `SingleRoundMessageReady` to `SingleRoundMessageReady`,
         // but the returned value is not used
         let _ = stp
             .build_single_round_message()
             .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
 
-        self.output_manager_service
-            .confirm_pending_transaction(tx_id)
-            .await
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-
         // Prepare receiver part of the transaction
-
-        // Diffie-Hellman shared secret `k_Ob * K_Sb = K_Ob * k_Sb` results in a public key, which is converted to
-        // bytes to enable conversion into a private key to be used as the spending key
-        let sender_offset_private_key = stp
-            .get_recipient_sender_offset_private_key(0)
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-        // TODO: Add a standardized Diffie-Hellman method to the tari_crypto library that will return a private key,
-        // TODO: then come back and use it here.
-        let spending_key = PrivateKey::from_bytes(
-            CommsPublicKey::shared_secret(&sender_offset_private_key.clone(), &dest_pubkey.clone()).as_bytes(),
-        )
-        .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-
-        let sender_message = TransactionSenderMessage::new_single_round_message(stp.get_single_round_message()?);
-        let rewind_key = PrivateKey::from_bytes(&hash_secret_key(&spending_key))?;
-        let blinding_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_key))?;
-        let rewind_data = RewindData {
-            rewind_key: rewind_key.clone(),
-            rewind_blinding_key: blinding_key.clone(),
-            proof_message: [0u8; 21],
-        };
+        let recipient_commitment = self
+            .resources
+            .factories
+            .commitment
+            .commit_value(&spending_key, amount.into());
+
+        // The output manager may have dropped a memo that the network does not allow, so the recipient's output uses
+        // the features the sender committed to
+        let sender_data = stp.get_single_round_message()?;
+        let recipient_features = sender_data.features.clone();
+        let sender_message = TransactionSenderMessage::new_single_round_message(sender_data);
 
         let rtp = ReceiverTransactionProtocol::new_with_rewindable_output(
             sender_message,
             PrivateKey::random(&mut OsRng),
             spending_key,
-            OutputFeatures::default(),
+            recipient_features,
             &self.resources.factories,
             &rewind_data,
         );
@@ -842,7 +1770,7 @@ where
 
         // Finalize
 
-        stp.finalize(KernelFeatures::empty(), &self.resources.factories)
+        stp.finalize(KernelFeatures::empty(), &self.resources.factories, None, None)
             .map_err(|e| {
                 error!(
                     target: LOG_TARGET,
@@ -852,39 +1780,24 @@ where
             })?;
         info!(target: LOG_TARGET, "Finalized one-side transaction TxId: {}", tx_id);
 
-        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
-        // there are no subscribers.
-        let _ = self
-            .event_publisher
-            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
-
-        // Broadcast one-sided transaction
-
         let tx = stp
             .get_transaction()
             .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
         let fee = stp
             .get_fee_amount()
             .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-        self.submit_transaction(
-            transaction_broadcast_join_handles,
-            CompletedTransaction::new(
-                tx_id,
-                self.resources.node_identity.public_key().clone(),
-                dest_pubkey.clone(),
-                amount,
-                fee,
-                tx.clone(),
-                TransactionStatus::Completed,
-                message.clone(),
-                Utc::now().naive_utc(),
-                TransactionDirection::Outbound,
-                None,
-            ),
-        )
-        .await?;
-
-        Ok(tx_id)
+        let recipient_output = tx
+            .body
+            .outputs()
+            .iter()
+            .find(|o| o.commitment == recipient_commitment)
+            .cloned()
+            .ok_or(TransactionServiceError::ReceiverOutputNotFound)?;
+
+        Ok((tx.clone(), fee, OneSidedRecipientOutput {
+            output: recipient_output,
+            sender_offset_private_key,
+        }))
     }
 
     /// Accept the public reply from a recipient and apply the reply to the relevant transaction protocol
@@ -1075,7 +1988,63 @@ where
     }
 
     /// Cancel a pending transaction
-    async fn cancel_pending_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+    /// Cancel a transaction at the request of the user. A transaction that is still being negotiated cannot be mined,
+    /// so it is cancelled and its outputs are released straight away. An outbound transaction that has been completed
+    /// but not mined may already be in a mempool, so the outputs it spends stay encumbered until the Cancelled Output
+    /// Release protocol finds that the transaction will not be mined.
+    async fn cancel_transaction(&mut self, tx_id: TxId, reason: Option<String>) -> Result<(), TransactionServiceError> {
+        let reason = reason
+            .filter(|r| !r.trim().is_empty())
+            .unwrap_or_else(|| "Cancelled by user".to_string());
+        let completed_tx = match self.db.get_completed_transaction(tx_id).await {
+            Ok(tx) => tx,
+            Err(TransactionStorageError::ValueNotFound(_)) => {
+                return self.cancel_pending_transaction(tx_id, reason).await;
+            },
+            Err(e) => return Err(e.into()),
+        };
+
+        if completed_tx.direction != TransactionDirection::Outbound {
+            return Err(TransactionServiceError::TransactionCannotBeCancelled(
+                tx_id,
+                "only outbound transactions can be cancelled once they are completed".to_string(),
+            ));
+        }
+        if !(completed_tx.status == TransactionStatus::Completed || completed_tx.status == TransactionStatus::Broadcast)
+        {
+            return Err(TransactionServiceError::TransactionCannotBeCancelled(
+                tx_id,
+                format!("the transaction status is {}", completed_tx.status),
+            ));
+        }
+
+        // The broadcast protocol stops once it sees that the transaction has been cancelled
+        self.db.cancel_completed_transaction(tx_id).await?;
+        self.db.record_transaction_cancellation(tx_id, reason, false).await?;
+
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCancelled(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+
+        info!(
+            target: LOG_TARGET,
+            "Completed Transaction (TxId: {}) cancelled, its outputs will be released once it is known that it will not \
+             be mined",
+            tx_id
+        );
+
+        Ok(())
+    }
+
+    async fn cancel_pending_transaction(&mut self, tx_id: TxId, reason: String) -> Result<(), TransactionServiceError> {
         self.db.cancel_pending_transaction(tx_id).await.map_err(|e| {
             warn!(
                 target: LOG_TARGET,
@@ -1085,6 +2054,7 @@ where
         })?;
 
         self.output_manager_service.cancel_transaction(tx_id).await?;
+        self.db.record_transaction_cancellation(tx_id, reason, true).await?;
 
         if let Some(cancellation_sender) = self.send_transaction_cancellation_senders.remove(&tx_id) {
             let _ = cancellation_sender.send(());
@@ -1138,7 +2108,8 @@ where
         // is the same as the cancellation message
         if let Ok(inbound_tx) = self.db.get_pending_inbound_transaction(tx_id).await {
             if inbound_tx.source_public_key == source_pubkey {
-                self.cancel_pending_transaction(tx_id).await?;
+                self.cancel_pending_transaction(tx_id, "Cancelled by the sender".to_string())
+                    .await?;
             } else {
                 trace!(
                     target: LOG_TARGET,
@@ -1173,6 +2144,8 @@ where
                     self.resources.clone(),
                     tx_reply_receiver,
                     cancellation_receiver,
+                    // The outputs of a restarted transaction are already selected, so its account no longer matters
+                    DEFAULT_ACCOUNT_ID,
                     tx.destination_public_key,
                     tx.amount,
                     tx.fee,
@@ -1283,7 +2256,7 @@ where
                 trace!(
                     target: LOG_TARGET,
                     "Transaction (TxId: {}) has already been received, this is probably a repeated message, Trace:
-            {}.",
+                {}.",
                     data.tx_id,
                     traced_message_tag
                 );
@@ -1580,6 +2553,23 @@ where
         Ok(id)
     }
 
+    fn start_cancelled_output_release_protocol(
+        &mut self,
+        join_handles: &mut FuturesUnordered<JoinHandle<Result<u64, TransactionServiceProtocolError>>>,
+    ) {
+        let base_node_public_key = match self.base_node_public_key.clone() {
+            Some(pk) => pk,
+            None => return,
+        };
+        let protocol = CancelledOutputReleaseProtocol::new(
+            OsRng.next_u64(),
+            self.resources.clone(),
+            base_node_public_key,
+            self.config.broadcast_monitoring_timeout,
+        );
+        join_handles.push(tokio::spawn(protocol.execute()));
+    }
+
     /// Handle the final clean up after a Transaction Validation protocol completes
     async fn complete_transaction_validation_protocol(
         &mut self,
@@ -2104,6 +3094,80 @@ pub struct PendingCoinbaseSpendingKey {
     pub spending_key: PrivateKey,
 }
 
+/// The recipient's output of a one-sided transaction, along with the sender offset private key used to derive its
+/// spending key
+struct OneSidedRecipientOutput {
+    output: TransactionOutput,
+    sender_offset_private_key: PrivateKey,
+}
+
+/// The keys of the recipient's output of a one-sided transaction, which the sender derives on the recipient's behalf
+struct OneSidedRecipientKeys {
+    sender_offset_private_key: PrivateKey,
+    spending_key: PrivateKey,
+    rewind_data: RewindData,
+}
+
+/// Derive the output features and keys of the recipient's output of a one-sided transaction to `dest_pubkey`
+fn one_sided_recipient(
+    dest_pubkey: &CommsPublicKey,
+    message: &str,
+) -> Result<(OutputFeatures, OneSidedRecipientKeys), TransactionServiceError> {
+    // The recipient only sees the message of a one-sided transaction if it travels on chain, so it is attached to
+    // the recipient's output as an encrypted memo
+    let recipient_features = if message.is_empty() {
+        OutputFeatures::default()
+    } else {
+        OutputFeatures::with_encrypted_memo(output_memo::encrypt_memo(dest_pubkey, message)?)
+    };
+
+    // Diffie-Hellman shared secret `k_Ob * K_Sb = K_Ob * k_Sb` results in a public key, which is converted to
+    // bytes to enable conversion into a private key to be used as the spending key
+    let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+    // TODO: Add a standardized Diffie-Hellman method to the tari_crypto library that will return a private key,
+    // TODO: then come back and use it here.
+    let spending_key =
+        PrivateKey::from_bytes(CommsPublicKey::shared_secret(&sender_offset_private_key, dest_pubkey).as_bytes())?;
+    let rewind_key = PrivateKey::from_bytes(&hash_secret_key(&spending_key))?;
+    let blinding_key = PrivateKey::from_bytes(&hash_secret_key(&rewind_key))?;
+    let rewind_data = RewindData {
+        rewind_key,
+        rewind_blinding_key: blinding_key,
+        proof_message: [0u8; 21],
+    };
+
+    Ok((recipient_features, OneSidedRecipientKeys {
+        sender_offset_private_key,
+        spending_key,
+        rewind_data,
+    }))
+}
+
 fn hash_secret_key(key: &PrivateKey) -> Vec<u8> {
     HashDigest::new().chain(key.as_bytes()).finalize().to_vec()
 }
+
+/// The SHA-256 hash of an atomic swap pre-image, as computed by the `HashSha256` script op
+pub fn sha_atomic_swap_hash(pre_image: &PublicKey) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(Sha256::digest(pre_image.as_bytes()).as_slice());
+    hash
+}
+
+/// Hash time locked contract script. The output can be spent by `dest_pubkey` when the pre-image of the hash is
+/// provided as input, or by `refund_pubkey` once the chain has reached `refund_height`.
+pub fn sha_atomic_swap_script(
+    pre_image: &PublicKey,
+    dest_pubkey: &PublicKey,
+    refund_pubkey: &PublicKey,
+    refund_height: u64,
+) -> TariScript {
+    let hash = sha_atomic_swap_hash(pre_image);
+    script!(
+        HashSha256 PushHash(Box::new(hash)) Equal IfThen
+            PushPubKey(Box::new(dest_pubkey.clone()))
+        Else
+            CheckHeightVerify(refund_height) PushPubKey(Box::new(refund_pubkey.clone()))
+        EndIf
+    )
+}
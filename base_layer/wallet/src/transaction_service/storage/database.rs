@@ -21,13 +21,18 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    output_manager_service::TxId,
+    output_manager_service::{
+        storage::models::{AccountId, DEFAULT_ACCOUNT_ID},
+        TxId,
+    },
     transaction_service::{
         error::TransactionStorageError,
         storage::models::{
             CompletedTransaction,
+            FeeBump,
             InboundTransaction,
             OutboundTransaction,
+            TransactionCancellation,
             TransactionDirection,
             TransactionStatus,
         },
@@ -120,6 +125,36 @@ pub trait TransactionBackend: Send + Sync + Clone {
     fn update_confirmations(&self, tx_id: TxId, confirmations: u64) -> Result<(), TransactionStorageError>;
     /// Update a transactions mined height
     fn update_mined_height(&self, tx_id: TxId, mined_height: u64) -> Result<(), TransactionStorageError>;
+    /// Reverse the cancellation of a Completed transaction
+    fn uncancel_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Insert or replace the cancellation record of a transaction
+    fn insert_transaction_cancellation(
+        &self,
+        cancellation: TransactionCancellation,
+    ) -> Result<(), TransactionStorageError>;
+    /// Fetch the cancellation record of a transaction, if it has been cancelled
+    fn fetch_transaction_cancellation(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Option<TransactionCancellation>, TransactionStorageError>;
+    /// Fetch the cancellation records of all cancelled transactions whose outputs have not been released yet
+    fn fetch_unreleased_transaction_cancellations(
+        &self,
+    ) -> Result<Vec<TransactionCancellation>, TransactionStorageError>;
+    /// Mark the outputs of a cancelled transaction as released
+    fn set_transaction_cancellation_outputs_released(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Remove the cancellation record of a transaction that has been reinstated
+    fn remove_transaction_cancellation(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Record that a transaction is a fee bump of a parent transaction
+    fn insert_fee_bump(&self, fee_bump: FeeBump) -> Result<(), TransactionStorageError>;
+    /// Fetch the fee bump record of a transaction, if it is a fee bump
+    fn fetch_fee_bump(&self, tx_id: TxId) -> Result<Option<FeeBump>, TransactionStorageError>;
+    /// Fetch the fee bump records of all the transactions that spend the change of the provided transaction
+    fn fetch_fee_bumps_for_parent(&self, parent_tx_id: TxId) -> Result<Vec<FeeBump>, TransactionStorageError>;
+    /// Record that a transaction belongs to the history of an account other than the default account
+    fn insert_transaction_account(&self, tx_id: TxId, account_id: AccountId) -> Result<(), TransactionStorageError>;
+    /// Fetch the accounts of all the transactions that belong to accounts other than the default account
+    fn fetch_transaction_accounts(&self) -> Result<HashMap<TxId, Vec<AccountId>>, TransactionStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -742,6 +777,135 @@ where T: TransactionBackend + 'static
             .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
         Ok(())
     }
+
+    pub async fn uncancel_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.uncancel_completed_transaction(tx_id))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    /// Records why a transaction was cancelled. `outputs_released` indicates whether the outputs encumbered by the
+    /// transaction have already been released.
+    pub async fn record_transaction_cancellation(
+        &self,
+        tx_id: TxId,
+        reason: String,
+        outputs_released: bool,
+    ) -> Result<(), TransactionStorageError> {
+        let db_clone = self.db.clone();
+        let cancellation = TransactionCancellation {
+            tx_id,
+            reason,
+            cancelled_at: Utc::now().naive_utc(),
+            outputs_released,
+        };
+        tokio::task::spawn_blocking(move || db_clone.insert_transaction_cancellation(cancellation))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn get_transaction_cancellation(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Option<TransactionCancellation>, TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.fetch_transaction_cancellation(tx_id))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
+
+    pub async fn get_unreleased_transaction_cancellations(
+        &self,
+    ) -> Result<Vec<TransactionCancellation>, TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.fetch_unreleased_transaction_cancellations())
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
+
+    pub async fn set_transaction_cancellation_outputs_released(
+        &self,
+        tx_id: TxId,
+    ) -> Result<(), TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.set_transaction_cancellation_outputs_released(tx_id))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn remove_transaction_cancellation(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.remove_transaction_cancellation(tx_id))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn record_fee_bump(
+        &self,
+        tx_id: TxId,
+        parent_tx_id: TxId,
+        fee_per_gram: MicroTari,
+    ) -> Result<(), TransactionStorageError> {
+        let db_clone = self.db.clone();
+        let fee_bump = FeeBump {
+            tx_id,
+            parent_tx_id,
+            fee_per_gram,
+            timestamp: Utc::now().naive_utc(),
+        };
+        tokio::task::spawn_blocking(move || db_clone.insert_fee_bump(fee_bump))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    pub async fn get_fee_bump(&self, tx_id: TxId) -> Result<Option<FeeBump>, TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.fetch_fee_bump(tx_id))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
+
+    pub async fn get_fee_bumps_for_parent(&self, parent_tx_id: TxId) -> Result<Vec<FeeBump>, TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.fetch_fee_bumps_for_parent(parent_tx_id))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
+
+    pub async fn add_transaction_to_account(
+        &self,
+        tx_id: TxId,
+        account_id: AccountId,
+    ) -> Result<(), TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.insert_transaction_account(tx_id, account_id))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
+
+    /// Get the completed transactions in the history of the given account. Transactions that were not recorded against
+    /// another account, such as all received transactions, belong to the default account.
+    pub async fn get_account_completed_transactions(
+        &self,
+        account_id: AccountId,
+    ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionStorageError> {
+        let db_clone = self.db.clone();
+        let transaction_accounts = tokio::task::spawn_blocking(move || db_clone.fetch_transaction_accounts())
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        let mut transactions = self.get_completed_transactions().await?;
+        transactions.retain(|tx_id, _| match transaction_accounts.get(tx_id) {
+            Some(accounts) => accounts.contains(&account_id),
+            None => account_id == DEFAULT_ACCOUNT_ID,
+        });
+        Ok(transactions)
+    }
 }
 
 impl Display for DbKey {
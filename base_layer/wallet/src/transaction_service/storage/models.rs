@@ -362,6 +362,28 @@ impl From<InboundTransaction> for CompletedTransaction {
     }
 }
 
+/// The record of when and why a transaction was cancelled
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionCancellation {
+    pub tx_id: TxId,
+    pub reason: String,
+    pub cancelled_at: NaiveDateTime,
+    /// A cancelled transaction that was already broadcast could still be mined, so the outputs it spends are only
+    /// released once it is known that it will not be.
+    pub outputs_released: bool,
+}
+
+/// Links a child-pays-for-parent fee bump transaction to the stuck transaction whose change output it spends
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeBump {
+    /// The fee bump transaction
+    pub tx_id: TxId,
+    pub parent_tx_id: TxId,
+    /// The fee per gram targeted for the parent and fee bump transactions together
+    pub fee_per_gram: MicroTari,
+    pub timestamp: NaiveDateTime,
+}
+
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum WalletTransaction {
@@ -41,8 +41,15 @@ use tari_comms::types::CommsPublicKey;
 use tari_core::transactions::tari_amount::MicroTari;
 
 use crate::{
-    output_manager_service::TxId,
-    schema::{completed_transactions, inbound_transactions, outbound_transactions},
+    output_manager_service::{storage::models::AccountId, TxId},
+    schema::{
+        completed_transactions,
+        inbound_transactions,
+        outbound_transactions,
+        transaction_accounts,
+        transaction_cancellations,
+        transaction_fee_bumps,
+    },
     storage::sqlite_utilities::WalletDbConnection,
     transaction_service::{
         error::TransactionStorageError,
@@ -50,8 +57,10 @@ use crate::{
             database::{DbKey, DbKeyValuePair, DbValue, TransactionBackend, WriteOperation},
             models::{
                 CompletedTransaction,
+                FeeBump,
                 InboundTransaction,
                 OutboundTransaction,
+                TransactionCancellation,
                 TransactionDirection,
                 TransactionStatus,
                 WalletTransaction,
@@ -78,6 +87,11 @@ impl TransactionServiceSqliteDatabase {
         }
     }
 
+    /// The cipher of this backend, which is shared by all of its clones
+    pub(crate) fn cipher(&self) -> Arc<RwLock<Option<Aes256Gcm>>> {
+        self.cipher.clone()
+    }
+
     fn insert(&self, kvp: DbKeyValuePair, conn: MutexGuard<SqliteConnection>) -> Result<(), TransactionStorageError> {
         match kvp {
             DbKeyValuePair::PendingOutboundTransaction(k, v) => {
@@ -913,6 +927,157 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         };
         Ok(())
     }
+
+    fn uncancel_completed_transaction(&self, tx_id: u64) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        match CompletedTransactionSql::find_by_cancelled(tx_id, true, &(*conn)) {
+            Ok(v) => {
+                v.uncancel(&(*conn))?;
+            },
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                return Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
+                    tx_id,
+                )));
+            },
+            Err(e) => return Err(e),
+        };
+        Ok(())
+    }
+
+    fn insert_transaction_cancellation(
+        &self,
+        cancellation: TransactionCancellation,
+    ) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        TransactionCancellationSql::from(cancellation).commit(&(*conn))
+    }
+
+    fn fetch_transaction_cancellation(
+        &self,
+        tx_id: u64,
+    ) -> Result<Option<TransactionCancellation>, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        match TransactionCancellationSql::find(tx_id, &(*conn)) {
+            Ok(c) => Ok(Some(c.into())),
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn fetch_unreleased_transaction_cancellations(
+        &self,
+    ) -> Result<Vec<TransactionCancellation>, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        Ok(TransactionCancellationSql::index_unreleased(&(*conn))?
+            .into_iter()
+            .map(TransactionCancellation::from)
+            .collect())
+    }
+
+    fn set_transaction_cancellation_outputs_released(&self, tx_id: u64) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        let num_updated =
+            diesel::update(transaction_cancellations::table.filter(transaction_cancellations::tx_id.eq(tx_id as i64)))
+                .set(transaction_cancellations::outputs_released.eq(1i32))
+                .execute(&(*conn))?;
+        if num_updated == 0 {
+            return Err(TransactionStorageError::UnexpectedResult(
+                "Updating transaction cancellation failed. No rows were affected".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn remove_transaction_cancellation(&self, tx_id: u64) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        diesel::delete(transaction_cancellations::table.filter(transaction_cancellations::tx_id.eq(tx_id as i64)))
+            .execute(&(*conn))?;
+        Ok(())
+    }
+
+    fn insert_fee_bump(&self, fee_bump: FeeBump) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        FeeBumpSql::from(fee_bump).commit(&(*conn))
+    }
+
+    fn fetch_fee_bump(&self, tx_id: u64) -> Result<Option<FeeBump>, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        match FeeBumpSql::find(tx_id, &(*conn)) {
+            Ok(f) => Ok(Some(f.into())),
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn fetch_fee_bumps_for_parent(&self, parent_tx_id: u64) -> Result<Vec<FeeBump>, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        Ok(FeeBumpSql::index_by_parent(parent_tx_id, &(*conn))?
+            .into_iter()
+            .map(FeeBump::from)
+            .collect())
+    }
+
+    fn insert_transaction_account(&self, tx_id: u64, account_id: AccountId) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        TransactionAccountSql {
+            tx_id: tx_id as i64,
+            account_id: account_id as i64,
+        }
+        .commit(&(*conn))
+    }
+
+    fn fetch_transaction_accounts(&self) -> Result<HashMap<u64, Vec<AccountId>>, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        let mut transaction_accounts = HashMap::new();
+        for t in TransactionAccountSql::index(&(*conn))? {
+            transaction_accounts
+                .entry(t.tx_id as u64)
+                .or_insert_with(Vec::new)
+                .push(t.account_id as AccountId);
+        }
+        Ok(transaction_accounts)
+    }
+}
+
+/// Re-encrypt the inbound, outbound and completed transactions with a new cipher, returning the number of rows that
+/// were re-encrypted. The caller is expected to hold a database transaction on the connection.
+pub(crate) fn rotate_encryption(
+    conn: &SqliteConnection,
+    current_cipher: &Aes256Gcm,
+    new_cipher: &Aes256Gcm,
+) -> Result<usize, TransactionStorageError> {
+    let mut rows = 0;
+    let mut inbound_txs = InboundTransactionSql::index(conn)?;
+    for tx in inbound_txs.iter_mut() {
+        tx.decrypt(current_cipher)
+            .map_err(|_| TransactionStorageError::AeadError("Decryption Error".to_string()))?;
+        tx.encrypt(new_cipher)
+            .map_err(|_| TransactionStorageError::AeadError("Encryption Error".to_string()))?;
+        tx.update_encryption(conn)?;
+        rows += 1;
+    }
+
+    let mut outbound_txs = OutboundTransactionSql::index(conn)?;
+    for tx in outbound_txs.iter_mut() {
+        tx.decrypt(current_cipher)
+            .map_err(|_| TransactionStorageError::AeadError("Decryption Error".to_string()))?;
+        tx.encrypt(new_cipher)
+            .map_err(|_| TransactionStorageError::AeadError("Encryption Error".to_string()))?;
+        tx.update_encryption(conn)?;
+        rows += 1;
+    }
+
+    let mut completed_txs = CompletedTransactionSql::index(conn)?;
+    for tx in completed_txs.iter_mut() {
+        tx.decrypt(current_cipher)
+            .map_err(|_| TransactionStorageError::AeadError("Decryption Error".to_string()))?;
+        tx.encrypt(new_cipher)
+            .map_err(|_| TransactionStorageError::AeadError("Encryption Error".to_string()))?;
+        tx.update_encryption(conn)?;
+        rows += 1;
+    }
+
+    Ok(rows)
 }
 
 #[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
@@ -1401,6 +1566,26 @@ impl CompletedTransactionSql {
         Ok(())
     }
 
+    pub fn uncancel(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        self.update(
+            UpdateCompletedTransactionSql {
+                status: None,
+                timestamp: None,
+                cancelled: Some(0i32),
+                direction: None,
+                transaction_protocol: None,
+                send_count: None,
+                last_send_timestamp: None,
+                valid: None,
+                confirmations: None,
+                mined_height: None,
+            },
+            conn,
+        )?;
+
+        Ok(())
+    }
+
     pub fn confirm(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
         self.update(
             UpdateCompletedTransactionSql {
@@ -1649,6 +1834,139 @@ impl From<UpdateCompletedTransaction> for UpdateCompletedTransactionSql {
     }
 }
 
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "transaction_cancellations"]
+struct TransactionCancellationSql {
+    tx_id: i64,
+    reason: String,
+    cancelled_at: NaiveDateTime,
+    outputs_released: i32,
+}
+
+impl TransactionCancellationSql {
+    /// Write this struct to the database, replacing any previous cancellation record of the transaction
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(transaction_cancellations::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn find(tx_id: u64, conn: &SqliteConnection) -> Result<TransactionCancellationSql, TransactionStorageError> {
+        Ok(transaction_cancellations::table
+            .filter(transaction_cancellations::tx_id.eq(tx_id as i64))
+            .first::<TransactionCancellationSql>(conn)?)
+    }
+
+    pub fn index_unreleased(
+        conn: &SqliteConnection,
+    ) -> Result<Vec<TransactionCancellationSql>, TransactionStorageError> {
+        Ok(transaction_cancellations::table
+            .filter(transaction_cancellations::outputs_released.eq(0i32))
+            .load::<TransactionCancellationSql>(conn)?)
+    }
+}
+
+impl From<TransactionCancellation> for TransactionCancellationSql {
+    fn from(c: TransactionCancellation) -> Self {
+        Self {
+            tx_id: c.tx_id as i64,
+            reason: c.reason,
+            cancelled_at: c.cancelled_at,
+            outputs_released: c.outputs_released as i32,
+        }
+    }
+}
+
+impl From<TransactionCancellationSql> for TransactionCancellation {
+    fn from(c: TransactionCancellationSql) -> Self {
+        Self {
+            tx_id: c.tx_id as u64,
+            reason: c.reason,
+            cancelled_at: c.cancelled_at,
+            outputs_released: c.outputs_released != 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "transaction_fee_bumps"]
+struct FeeBumpSql {
+    tx_id: i64,
+    parent_tx_id: i64,
+    fee_per_gram: i64,
+    timestamp: NaiveDateTime,
+}
+
+impl FeeBumpSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(transaction_fee_bumps::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn find(tx_id: u64, conn: &SqliteConnection) -> Result<FeeBumpSql, TransactionStorageError> {
+        Ok(transaction_fee_bumps::table
+            .filter(transaction_fee_bumps::tx_id.eq(tx_id as i64))
+            .first::<FeeBumpSql>(conn)?)
+    }
+
+    pub fn index_by_parent(
+        parent_tx_id: u64,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<FeeBumpSql>, TransactionStorageError> {
+        Ok(transaction_fee_bumps::table
+            .filter(transaction_fee_bumps::parent_tx_id.eq(parent_tx_id as i64))
+            .order_by(transaction_fee_bumps::timestamp.asc())
+            .load::<FeeBumpSql>(conn)?)
+    }
+}
+
+impl From<FeeBump> for FeeBumpSql {
+    fn from(f: FeeBump) -> Self {
+        Self {
+            tx_id: f.tx_id as i64,
+            parent_tx_id: f.parent_tx_id as i64,
+            fee_per_gram: u64::from(f.fee_per_gram) as i64,
+            timestamp: f.timestamp,
+        }
+    }
+}
+
+impl From<FeeBumpSql> for FeeBump {
+    fn from(f: FeeBumpSql) -> Self {
+        Self {
+            tx_id: f.tx_id as u64,
+            parent_tx_id: f.parent_tx_id as u64,
+            fee_per_gram: MicroTari::from(f.fee_per_gram as u64),
+            timestamp: f.timestamp,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "transaction_accounts"]
+struct TransactionAccountSql {
+    tx_id: i64,
+    account_id: i64,
+}
+
+impl TransactionAccountSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(transaction_accounts::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<TransactionAccountSql>, TransactionStorageError> {
+        Ok(transaction_accounts::table.load::<TransactionAccountSql>(conn)?)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::convert::TryFrom;
@@ -56,6 +56,7 @@ where T: WalletBackend + 'static
     backend: Option<WalletDatabase<T>>,
     factories: CryptoFactories,
     node_identity: Arc<NodeIdentity>,
+    mode: UtxoScannerMode,
 }
 
 impl<T> UtxoScannerServiceInitializer<T>
@@ -66,12 +67,14 @@ where T: WalletBackend + 'static
         backend: WalletDatabase<T>,
         factories: CryptoFactories,
         node_identity: Arc<NodeIdentity>,
+        mode: UtxoScannerMode,
     ) -> Self {
         Self {
             interval,
             backend: Some(backend),
             factories,
             node_identity,
+            mode,
         }
     }
 }
@@ -97,6 +100,7 @@ where T: WalletBackend + 'static
         let factories = self.factories.clone();
         let interval = self.interval;
         let node_identity = self.node_identity.clone();
+        let mode = self.mode.clone();
 
         context.spawn_when_ready(move |handles| async move {
             let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
@@ -107,7 +111,7 @@ where T: WalletBackend + 'static
                 .with_peers(vec![])
                 .with_retry_limit(2)
                 .with_scanning_interval(interval)
-                .with_mode(UtxoScannerMode::Scanning)
+                .with_mode(mode)
                 .build_with_resources(
                     backend,
                     connectivity_manager,
@@ -35,7 +35,7 @@ use log::*;
 use serde::{Deserialize, Serialize};
 use tokio::{sync::broadcast, task, time};
 
-use tari_common_types::types::HashOutput;
+use tari_common_types::types::{HashOutput, PrivateKey};
 use tari_comms::{
     connectivity::ConnectivityRequester,
     peer_manager::NodeId,
@@ -67,7 +67,7 @@ use crate::{
         database::{WalletBackend, WalletDatabase},
         sqlite_db::WalletSqliteDatabase,
     },
-    transaction_service::handle::TransactionServiceHandle,
+    transaction_service::{handle::TransactionServiceHandle, output_memo},
     utxo_scanner_service::{
         error::UtxoScannerError,
         handle::{UtxoScannerEvent, UtxoScannerRequest, UtxoScannerResponse},
@@ -85,6 +85,9 @@ const SCANNING_KEY: &str = "scanning_data";
 pub enum UtxoScannerMode {
     Recovery,
     Scanning,
+    /// Scan continuously, rewinding the outputs of the watched wallet as recovery does, since a watch-only wallet does
+    /// not take part in the transactions that create them
+    WatchOnly,
 }
 
 impl Default for UtxoScannerMode {
@@ -462,7 +465,15 @@ where TBackend: WalletBackend + 'static
         outputs: Vec<TransactionOutput>,
     ) -> Result<Vec<(UnblindedOutput, String)>, UtxoScannerError> {
         let mut found_outputs: Vec<(UnblindedOutput, String)> = Vec::new();
-        if self.mode == UtxoScannerMode::Recovery {
+        // A watch-only wallet only holds the stealth scan key if its keys were exported with it
+        let stealth_scan_key = self
+            .resources
+            .output_manager_service
+            .get_stealth_view_key()
+            .await
+            .ok()
+            .map(|view_key| view_key.scan_key);
+        if matches!(self.mode, UtxoScannerMode::Recovery | UtxoScannerMode::WatchOnly) {
             found_outputs.append(
                 &mut self
                     .resources
@@ -470,7 +481,12 @@ where TBackend: WalletBackend + 'static
                     .scan_for_recoverable_outputs(outputs.clone())
                     .await?
                     .into_iter()
-                    .map(|v| (v, format!("Recovered on {}.", Utc::now().naive_utc())))
+                    .map(|v| {
+                        let message = self
+                            .decrypt_output_memo(&v, stealth_scan_key.as_ref())
+                            .unwrap_or_else(|| format!("Recovered on {}.", Utc::now().naive_utc()));
+                        (v, message)
+                    })
                     .collect(),
             );
         };
@@ -482,16 +498,31 @@ where TBackend: WalletBackend + 'static
                 .await?
                 .into_iter()
                 .map(|v| {
-                    (
-                        v,
-                        format!("Detected one-sided transaction on {}.", Utc::now().naive_utc()),
-                    )
+                    let message = self
+                        .decrypt_output_memo(&v, stealth_scan_key.as_ref())
+                        .unwrap_or_else(|| format!("Detected one-sided transaction on {}.", Utc::now().naive_utc()));
+                    (v, message)
                 })
                 .collect(),
         );
         Ok(found_outputs)
     }
 
+    /// The memo attached by the sender of the output, if there is one and it was encrypted to this wallet's node
+    /// identity or, for stealth payments, to its stealth scan key
+    fn decrypt_output_memo(&self, output: &UnblindedOutput, stealth_scan_key: Option<&PrivateKey>) -> Option<String> {
+        if output.features.encrypted_memo.is_empty() {
+            return None;
+        }
+        output_memo::decrypt_memo(
+            self.resources.node_identity.secret_key(),
+            &output.features.encrypted_memo,
+        )
+        .or_else(|| {
+            stealth_scan_key.and_then(|scan_key| output_memo::decrypt_memo(scan_key, &output.features.encrypted_memo))
+        })
+    }
+
     async fn import_utxos_to_transaction_service(
         &mut self,
         utxos: Vec<(UnblindedOutput, String)>,
@@ -518,7 +549,7 @@ where TBackend: WalletBackend + 'static
     fn get_db_mode_key(&self) -> String {
         match self.mode {
             UtxoScannerMode::Recovery => RECOVERY_KEY.to_owned(),
-            UtxoScannerMode::Scanning => SCANNING_KEY.to_owned(),
+            UtxoScannerMode::Scanning | UtxoScannerMode::WatchOnly => SCANNING_KEY.to_owned(),
         }
     }
 
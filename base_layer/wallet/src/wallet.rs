@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{marker::PhantomData, sync::Arc};
+use std::{convert::TryFrom, marker::PhantomData, sync::Arc};
 
 use aes_gcm::{
     aead::{generic_array::GenericArray, NewAead},
@@ -37,7 +37,7 @@ use tari_crypto::{
     script,
     script::{ExecutionStack, TariScript},
     signatures::{SchnorrSignature, SchnorrSignatureError},
-    tari_utilities::hex::Hex,
+    tari_utilities::{hex::Hex, Hashable},
 };
 
 use tari_common_types::types::{ComSignature, PrivateKey, PublicKey};
@@ -50,10 +50,13 @@ use tari_comms::{
     UnspawnedCommsNode,
 };
 use tari_comms_dht::{store_forward::StoreAndForwardRequester, Dht};
-use tari_core::transactions::{
-    tari_amount::MicroTari,
-    transaction::{OutputFeatures, UnblindedOutput},
-    CryptoFactories,
+use tari_core::{
+    proto::base_node::FetchMatchingUtxos,
+    transactions::{
+        tari_amount::MicroTari,
+        transaction::{OutputFeatures, TransactionOutput, UnblindedOutput},
+        CryptoFactories,
+    },
 };
 use tari_key_manager::key_manager::KeyManager;
 use tari_p2p::{
@@ -64,6 +67,7 @@ use tari_p2p::{
 };
 use tari_service_framework::StackBuilder;
 use tari_shutdown::ShutdownSignal;
+use tokio::{sync::mpsc, task::JoinHandle};
 
 use crate::{
     base_node_service::{handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
@@ -71,10 +75,17 @@ use crate::{
     connectivity_service::{WalletConnectivityHandle, WalletConnectivityInitializer},
     contacts_service::{handle::ContactsServiceHandle, storage::database::ContactsBackend, ContactsServiceInitializer},
     error::WalletError,
+    multisig_service::{
+        crypto::MultisigKey,
+        handle::MultisigServiceHandle,
+        storage::database::MultisigBackend,
+        MultisigServiceInitializer,
+    },
     output_manager_service::{
         error::OutputManagerError,
         handle::OutputManagerHandle,
         storage::{database::OutputManagerBackend, models::KnownOneSidedPaymentScript},
+        watch_only::OutputManagerKeys,
         OutputManagerServiceInitializer,
         TxId,
     },
@@ -85,20 +96,23 @@ use crate::{
         TransactionServiceInitializer,
     },
     types::KeyDigest,
-    utxo_scanner_service::{handle::UtxoScannerHandle, UtxoScannerServiceInitializer},
+    utxo_scanner_service::{handle::UtxoScannerHandle, utxo_scanning::UtxoScannerMode, UtxoScannerServiceInitializer},
 };
 
+pub use crate::storage::database::{EncryptionRotationProgress, EncryptionRotationStage};
+
 const LOG_TARGET: &str = "wallet";
 
 /// A structure containing the config and services that a Wallet application will require. This struct will start up all
 /// the services and provide the APIs that applications will use to interact with the services
 #[derive(Clone)]
-pub struct Wallet<T, U, V, W>
+pub struct Wallet<T, U, V, W, X>
 where
     T: WalletBackend + 'static,
     U: TransactionBackend + 'static,
     V: OutputManagerBackend + 'static,
     W: ContactsBackend + 'static,
+    X: MultisigBackend + 'static,
 {
     pub comms: CommsNode,
     pub dht_service: Dht,
@@ -107,6 +121,7 @@ where
     pub transaction_service: TransactionServiceHandle,
     pub wallet_connectivity: WalletConnectivityHandle,
     pub contacts_service: ContactsServiceHandle,
+    pub multisig_service: MultisigServiceHandle,
     pub base_node_service: BaseNodeServiceHandle,
     pub utxo_scanner_service: UtxoScannerHandle,
     pub updater_service: Option<SoftwareUpdaterHandle>,
@@ -115,14 +130,16 @@ where
     _u: PhantomData<U>,
     _v: PhantomData<V>,
     _w: PhantomData<W>,
+    _x: PhantomData<X>,
 }
 
-impl<T, U, V, W> Wallet<T, U, V, W>
+impl<T, U, V, W, X> Wallet<T, U, V, W, X>
 where
     T: WalletBackend + 'static,
     U: TransactionBackend + 'static,
     V: OutputManagerBackend + 'static,
     W: ContactsBackend + 'static,
+    X: MultisigBackend + 'static,
 {
     pub async fn start(
         config: WalletConfig,
@@ -130,12 +147,32 @@ where
         transaction_backend: U,
         output_manager_backend: V,
         contacts_backend: W,
+        multisig_backend: X,
         shutdown_signal: ShutdownSignal,
         recovery_master_key: Option<CommsSecretKey>,
-    ) -> Result<Wallet<T, U, V, W>, WalletError> {
+    ) -> Result<Wallet<T, U, V, W, X>, WalletError> {
         let master_secret_key =
             read_or_create_master_secret_key(recovery_master_key, &mut wallet_database.clone()).await?;
         let comms_secret_key = derive_comms_secret_key(&master_secret_key)?;
+        let multisig_key = MultisigKey::from_master_key(master_secret_key.clone())?;
+        // A watch-only wallet keeps a random master key for its comms identity and the encryption checks, and only
+        // rewinds the outputs of the wallet it watches
+        let (output_manager_keys, utxo_scanner_mode) = match wallet_database.get_watch_only_keys().await? {
+            Some(keys) => (OutputManagerKeys::WatchOnly(keys), UtxoScannerMode::WatchOnly),
+            None => match wallet_database.get_seed_words_key().await? {
+                Some(seed_key) => (
+                    OutputManagerKeys::Passphrase {
+                        master_key: master_secret_key,
+                        seed_key,
+                    },
+                    UtxoScannerMode::Scanning,
+                ),
+                None => (
+                    OutputManagerKeys::MasterKey(master_secret_key),
+                    UtxoScannerMode::Scanning,
+                ),
+            },
+        };
 
         let node_identity = Arc::new(NodeIdentity::new(
             comms_secret_key,
@@ -179,17 +216,24 @@ where
                 output_manager_backend,
                 factories.clone(),
                 config.network,
-                master_secret_key,
+                output_manager_keys,
             ))
             .add_initializer(TransactionServiceInitializer::new(
                 config.transaction_service_config.unwrap_or_default(),
-                peer_message_subscription_factory,
+                peer_message_subscription_factory.clone(),
                 transaction_backend,
                 node_identity.clone(),
                 factories.clone(),
                 wallet_database.clone(),
             ))
             .add_initializer(ContactsServiceInitializer::new(contacts_backend))
+            .add_initializer(MultisigServiceInitializer::new(
+                multisig_backend,
+                peer_message_subscription_factory,
+                node_identity.clone(),
+                multisig_key,
+                factories.clone(),
+            ))
             .add_initializer(BaseNodeServiceInitializer::new(
                 config.base_node_service_config.clone(),
                 bn_service_db,
@@ -200,6 +244,7 @@ where
                 wallet_database.clone(),
                 factories.clone(),
                 node_identity.clone(),
+                utxo_scanner_mode,
             ));
 
         // Check if we have update config. FFI wallets don't do this, the update on mobile is done differently.
@@ -226,6 +271,7 @@ where
         let mut output_manager_handle = handles.expect_handle::<OutputManagerHandle>();
         let transaction_service_handle = handles.expect_handle::<TransactionServiceHandle>();
         let contacts_handle = handles.expect_handle::<ContactsServiceHandle>();
+        let multisig_handle = handles.expect_handle::<MultisigServiceHandle>();
         let dht = handles.expect_handle::<Dht>();
         let store_and_forward_requester = dht.store_and_forward_requester();
 
@@ -259,6 +305,7 @@ where
             output_manager_service: output_manager_handle,
             transaction_service: transaction_service_handle,
             contacts_service: contacts_handle,
+            multisig_service: multisig_handle,
             base_node_service: base_node_service_handle,
             utxo_scanner_service: utxo_scanner_service_handle,
             updater_service: updater_handle,
@@ -268,6 +315,7 @@ where
             _u: PhantomData,
             _v: PhantomData,
             _w: PhantomData,
+            _x: PhantomData,
         })
     }
 
@@ -319,6 +367,16 @@ where
         Ok(())
     }
 
+    /// Set the prioritized list of base nodes that the wallet fails over to when the current base node fails its
+    /// health checks. The current base node is not changed.
+    pub async fn set_base_node_peers(&mut self, peers: Vec<Peer>) -> Result<(), WalletError> {
+        for peer in &peers {
+            self.comms.peer_manager().add_peer(peer.clone()).await?;
+        }
+        self.base_node_service.set_base_node_peers(peers).await?;
+        Ok(())
+    }
+
     pub async fn get_base_node_peer(&mut self) -> Result<Option<Peer>, WalletError> {
         self.base_node_service
             .get_base_node_peer()
@@ -446,6 +504,42 @@ where
         Ok(tx_id)
     }
 
+    /// Import an external UTXO, such as one claimed from a faucet, after checking with the connected base node that it
+    /// is in the UTXO set. The output is then imported in the same way as `import_unblinded_utxo`, and is tracked by
+    /// the output manager's validation from then on. The TxId of the generated import transaction is returned.
+    pub async fn import_external_utxo(
+        &mut self,
+        unblinded_output: UnblindedOutput,
+        source_public_key: &CommsPublicKey,
+        message: String,
+    ) -> Result<TxId, WalletError> {
+        let output = unblinded_output.as_transaction_output(&self.factories)?;
+        let mut client = self
+            .wallet_connectivity
+            .obtain_base_node_wallet_rpc_client()
+            .await
+            .ok_or(WalletError::BaseNodeNotConnected)?;
+        let response = client
+            .fetch_matching_utxos(FetchMatchingUtxos {
+                output_hashes: vec![output.hash()],
+            })
+            .await?;
+        if !response.is_synced {
+            return Err(WalletError::BaseNodeNotSynced);
+        }
+        let found = response
+            .outputs
+            .into_iter()
+            .filter_map(|o| TransactionOutput::try_from(o).ok())
+            .any(|o| o.commitment == output.commitment);
+        if !found {
+            return Err(WalletError::UtxoNotFoundOnChain(output.commitment.to_hex()));
+        }
+
+        self.import_unblinded_utxo(unblinded_output, source_public_key, message)
+            .await
+    }
+
     pub fn sign_message(
         &mut self,
         secret: RistrettoSecretKey,
@@ -501,13 +595,12 @@ where
     /// in which case this will fail.
     pub async fn apply_encryption(&mut self, passphrase: String) -> Result<(), WalletError> {
         debug!(target: LOG_TARGET, "Applying wallet encryption.");
-        let passphrase_hash = Blake256::new().chain(passphrase.as_bytes()).finalize();
-        let key = GenericArray::from_slice(passphrase_hash.as_slice());
-        let cipher = Aes256Gcm::new(key);
+        let cipher = passphrase_to_cipher(&passphrase);
 
         self.db.apply_encryption(cipher.clone()).await?;
         self.output_manager_service.apply_encryption(cipher.clone()).await?;
-        self.transaction_service.apply_encryption(cipher).await?;
+        self.transaction_service.apply_encryption(cipher.clone()).await?;
+        self.multisig_service.apply_encryption(cipher).await?;
         Ok(())
     }
 
@@ -517,9 +610,30 @@ where
         self.db.remove_encryption().await?;
         self.output_manager_service.remove_encryption().await?;
         self.transaction_service.remove_encryption().await?;
+        self.multisig_service.remove_encryption().await?;
         Ok(())
     }
 
+    /// Re-encrypt the whole Wallet database with the new passphrase without removing the encryption in between. The
+    /// rotation runs on a background task and re-encrypts every table in a single database transaction, so if it fails
+    /// the wallet remains encrypted with the current passphrase. Progress is reported on the optional channel.
+    pub fn rotate_encryption(
+        &self,
+        current_passphrase: String,
+        new_passphrase: String,
+        progress: Option<mpsc::Sender<EncryptionRotationProgress>>,
+    ) -> JoinHandle<Result<usize, WalletError>> {
+        debug!(target: LOG_TARGET, "Rotating wallet encryption.");
+        let current_cipher = passphrase_to_cipher(&current_passphrase);
+        let new_cipher = passphrase_to_cipher(&new_passphrase);
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            let rows = db.rotate_encryption(current_cipher, new_cipher, progress).await?;
+            debug!(target: LOG_TARGET, "Re-encrypted {} rows of the wallet database", rows);
+            Ok(rows)
+        })
+    }
+
     /// Utility function to find out if there is data in the database indicating that there is an incomplete recovery
     /// process in progress
     pub async fn is_recovery_in_progress(&self) -> Result<bool, WalletError> {
@@ -528,6 +642,12 @@ where
     }
 }
 
+fn passphrase_to_cipher(passphrase: &str) -> Aes256Gcm {
+    let passphrase_hash = Blake256::new().chain(passphrase.as_bytes()).finalize();
+    let key = GenericArray::from_slice(passphrase_hash.as_slice());
+    Aes256Gcm::new(key)
+}
+
 async fn read_or_create_master_secret_key<T: WalletBackend + 'static>(
     recovery_master_key: Option<CommsSecretKey>,
     db: &mut WalletDatabase<T>,
@@ -44,7 +44,7 @@ use tari_core::{
         fee::Fee,
         helpers::{create_unblinded_output, TestParams as TestParamsHelpers},
         tari_amount::{uT, MicroTari},
-        transaction::{KernelFeatures, OutputFeatures, Transaction},
+        transaction::{KernelFeatures, OutputFeatures, Transaction, UnblindedOutput},
         transaction_protocol::{
             recipient::RecipientState,
             sender::TransactionSenderMessage,
@@ -71,11 +71,13 @@ use tari_wallet::{
         error::{OutputManagerError, OutputManagerStorageError},
         handle::{OutputManagerEvent, OutputManagerHandle},
         service::OutputManagerService,
+        stealth::StealthOutputKeys,
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, OutputManagerBackend, OutputManagerDatabase, WriteOperation},
-            models::{DbUnblindedOutput, OutputStatus},
+            models::{DbUnblindedOutput, OutputStatus, DEFAULT_ACCOUNT_ID},
             sqlite_db::OutputManagerSqliteDatabase,
         },
+        watch_only::OutputManagerKeys,
         TxId,
         TxoValidationType,
     },
@@ -100,6 +102,23 @@ async fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
     Arc<NodeIdentity>,
     BaseNodeWalletRpcMockState,
     ConnectivityManagerMockState,
+) {
+    setup_output_manager_service_with_keys(backend, with_connection, CommsSecretKey::default().into()).await
+}
+
+#[allow(clippy::type_complexity)]
+async fn setup_output_manager_service_with_keys<T: OutputManagerBackend + 'static>(
+    backend: T,
+    with_connection: bool,
+    keys: OutputManagerKeys,
+) -> (
+    OutputManagerHandle,
+    Shutdown,
+    TransactionServiceHandle,
+    MockRpcServer<BaseNodeWalletRpcServer<BaseNodeWalletRpcMockService>>,
+    Arc<NodeIdentity>,
+    BaseNodeWalletRpcMockState,
+    ConnectivityManagerMockState,
 ) {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -158,7 +177,7 @@ async fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
         shutdown.to_signal(),
         basenode_service_handle,
         connectivity_manager,
-        CommsSecretKey::default(),
+        keys,
     )
     .await
     .unwrap();
@@ -205,7 +224,7 @@ async fn complete_transaction(mut stp: SenderTransactionProtocol, mut oms: Outpu
     .unwrap();
     stp.add_single_recipient_info(recv_info, &factories.range_proof)
         .unwrap();
-    stp.finalize(KernelFeatures::empty(), &factories).unwrap();
+    stp.finalize(KernelFeatures::empty(), &factories, None, None).unwrap();
     stp.get_transaction().unwrap().clone()
 }
 
@@ -258,7 +277,7 @@ pub async fn setup_oms_with_bn_state<T: OutputManagerBackend + 'static>(
         shutdown.to_signal(),
         base_node_service_handle.clone(),
         connectivity_manager,
-        CommsSecretKey::default(),
+        CommsSecretKey::default().into(),
     )
     .await
     .unwrap();
@@ -336,7 +355,7 @@ async fn fee_estimate() {
             .fee_estimate(MicroTari::from(100), fee_per_gram, 1, outputs)
             .await
             .unwrap();
-        assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 1, outputs as usize));
+        assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 1, outputs as usize, 0));
     }
 
     // not enough funds
@@ -707,7 +726,7 @@ async fn send_no_change() {
     let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
 
     let fee_per_gram = MicroTari::from(20);
-    let fee_without_change = Fee::calculate(fee_per_gram, 1, 2, 1);
+    let fee_without_change = Fee::calculate(fee_per_gram, 1, 2, 1, 0);
     let value1 = 500;
     oms.add_output(create_unblinded_output(
         script!(Nop),
@@ -760,7 +779,7 @@ async fn send_no_change() {
     stp.add_single_recipient_info(recv_info, &factories.range_proof)
         .unwrap();
 
-    stp.finalize(KernelFeatures::empty(), &factories).unwrap();
+    stp.finalize(KernelFeatures::empty(), &factories, None, None).unwrap();
 
     let tx = stp.get_transaction().unwrap();
 
@@ -781,7 +800,7 @@ async fn send_not_enough_for_change() {
     let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
 
     let fee_per_gram = MicroTari::from(20);
-    let fee_without_change = Fee::calculate(fee_per_gram, 1, 2, 1);
+    let fee_without_change = Fee::calculate(fee_per_gram, 1, 2, 1, 0);
     let value1 = 500;
     oms.add_output(create_unblinded_output(
         TariScript::default(),
@@ -930,6 +949,65 @@ async fn cancel_transaction_and_reinstate_inbound_tx() {
     assert_eq!(balance.pending_incoming_balance, value);
 }
 
+#[tokio::test]
+async fn fee_bump_transaction() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(100_000), &factories.commitment);
+    oms.add_output(uo).await.unwrap();
+    let stp = oms
+        .prepare_transaction_to_send(
+            OsRng.next_u64(),
+            MicroTari::from(10_000),
+            MicroTari::from(5),
+            None,
+            "".to_string(),
+            script!(Nop),
+        )
+        .await
+        .unwrap();
+    let parent_tx_id = stp.get_tx_id().unwrap();
+    let parent_fee = stp.get_fee_amount().unwrap();
+    let parent_weight = Fee::calculate_weight(1, 1, 2, 0);
+    let fee_per_gram = MicroTari::from(50);
+
+    match oms
+        .create_fee_bump_transaction(1, parent_fee, parent_weight, fee_per_gram)
+        .await
+    {
+        Err(OutputManagerError::NoFeeBumpOutput(1)) => {},
+        _ => panic!("Transaction 1 has no change to spend"),
+    }
+
+    let (tx_id, tx, fee, amount) = oms
+        .create_fee_bump_transaction(parent_tx_id, parent_fee, parent_weight, fee_per_gram)
+        .await
+        .unwrap();
+    assert_eq!(tx.body.inputs().len(), 1);
+    let child_weight = Fee::calculate_weight(1, 1, 1, 0);
+    assert!(u64::from(parent_fee + fee) >= u64::from(fee_per_gram) * (parent_weight + child_weight));
+
+    let pending_txs = oms.get_pending_transactions().await.unwrap();
+    assert!(pending_txs[&parent_tx_id].outputs_to_be_received.is_empty());
+    assert_eq!(pending_txs[&tx_id].outputs_to_be_spent.len(), 1);
+    assert_eq!(
+        pending_txs[&tx_id].outputs_to_be_received[0].unblinded_output.value,
+        amount
+    );
+
+    // The parent is still pending, so cancelling the fee bump hands the change back to it
+    oms.cancel_fee_bump_transaction(tx_id, parent_tx_id).await.unwrap();
+    let pending_txs = oms.get_pending_transactions().await.unwrap();
+    assert!(!pending_txs.contains_key(&tx_id));
+    assert_eq!(pending_txs[&parent_tx_id].outputs_to_be_received.len(), 1);
+    assert!(oms.get_unspent_outputs().await.unwrap().is_empty());
+}
+
 #[tokio::test]
 async fn timeout_transaction() {
     let factories = CryptoFactories::default();
@@ -1022,6 +1100,117 @@ async fn test_get_balance() {
     assert_eq!(output_val, balance.pending_outgoing_balance);
 }
 
+#[tokio::test]
+async fn test_account_transfer_and_balances() {
+    let factories = CryptoFactories::default();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
+
+    let savings = oms.create_account("savings".to_string()).await.unwrap();
+    assert!(oms.create_account("savings".to_string()).await.is_err());
+    let accounts = oms.get_accounts().await.unwrap();
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(accounts[0].id, DEFAULT_ACCOUNT_ID);
+    assert_eq!(oms.find_account("savings").await.unwrap().id, savings.id);
+    assert!(oms.find_account("checking").await.is_err());
+
+    let output_val = MicroTari::from(5000);
+    let (_ti, uo) = make_input(&mut OsRng.clone(), output_val, &factories.commitment);
+    oms.add_output(uo).await.unwrap();
+
+    let default_balance = oms.get_account_balance(DEFAULT_ACCOUNT_ID).await.unwrap();
+    assert_eq!(default_balance.available_balance, output_val);
+    let savings_balance = oms.get_account_balance(savings.id).await.unwrap();
+    assert_eq!(savings_balance.available_balance, MicroTari::from(0));
+
+    let amount = MicroTari::from(1000);
+    let (fee, _tx) = oms
+        .create_account_transfer_transaction(
+            OsRng.next_u64(),
+            DEFAULT_ACCOUNT_ID,
+            savings.id,
+            amount,
+            MicroTari::from(5),
+            "".to_string(),
+        )
+        .await
+        .unwrap();
+
+    let savings_balance = oms.get_account_balance(savings.id).await.unwrap();
+    assert_eq!(savings_balance.pending_incoming_balance, amount);
+    assert_eq!(savings_balance.pending_outgoing_balance, MicroTari::from(0));
+    let default_balance = oms.get_account_balance(DEFAULT_ACCOUNT_ID).await.unwrap();
+    assert_eq!(default_balance.available_balance, MicroTari::from(0));
+    assert_eq!(default_balance.pending_outgoing_balance, output_val);
+    assert_eq!(default_balance.pending_incoming_balance, output_val - amount - fee);
+
+    // Savings has nothing to spend until the transfer is mined
+    assert!(oms
+        .create_account_transfer_transaction(
+            OsRng.next_u64(),
+            savings.id,
+            DEFAULT_ACCOUNT_ID,
+            MicroTari::from(100),
+            MicroTari::from(5),
+            "".to_string(),
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_accounts_are_recovered_from_their_key_branches() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
+
+    // Account ids are assigned sequentially, so that recovery can derive the key branch of each account
+    let savings = oms.create_account("savings".to_string()).await.unwrap();
+    assert_eq!(savings.id, DEFAULT_ACCOUNT_ID + 1);
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(5000), &factories.commitment);
+    oms.add_output(uo).await.unwrap();
+    let amount = MicroTari::from(1000);
+    let (fee, tx) = oms
+        .create_account_transfer_transaction(
+            OsRng.next_u64(),
+            DEFAULT_ACCOUNT_ID,
+            savings.id,
+            amount,
+            MicroTari::from(5),
+            "".to_string(),
+        )
+        .await
+        .unwrap();
+
+    // A wallet restored from the same seed recreates the account from the outputs it finds
+    let (connection, _tempdir2) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut recovered_oms, _shutdown2, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
+    let recovered = recovered_oms
+        .scan_for_recoverable_outputs(tx.body.outputs().clone())
+        .await
+        .unwrap();
+    assert_eq!(recovered.len(), 2);
+
+    let accounts = recovered_oms.get_accounts().await.unwrap();
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(accounts[1].id, savings.id);
+    assert_eq!(accounts[1].key_index, 1);
+    let savings_balance = recovered_oms.get_account_balance(savings.id).await.unwrap();
+    assert_eq!(savings_balance.available_balance, amount);
+    let default_balance = recovered_oms.get_account_balance(DEFAULT_ACCOUNT_ID).await.unwrap();
+    assert_eq!(default_balance.available_balance, MicroTari::from(5000) - amount - fee);
+
+    // New accounts of the recovered wallet do not reuse the ids of recovered accounts
+    let checking = recovered_oms.create_account("checking".to_string()).await.unwrap();
+    assert_eq!(checking.id, savings.id + 1);
+}
+
 #[tokio::test]
 async fn test_confirming_received_output() {
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
@@ -1169,7 +1358,7 @@ async fn coin_split_with_change() {
         .unwrap();
     assert_eq!(coin_split_tx.body.inputs().len(), 2);
     assert_eq!(coin_split_tx.body.outputs().len(), split_count + 1);
-    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 2, split_count + 1));
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 2, split_count + 1, 0));
     assert_eq!(amount, val2 + val3);
 }
 
@@ -1182,7 +1371,7 @@ async fn coin_split_no_change() {
 
     let fee_per_gram = MicroTari::from(25);
     let split_count = 15;
-    let fee = Fee::calculate(fee_per_gram, 1, 3, 15);
+    let fee = Fee::calculate(fee_per_gram, 1, 3, 15, 0);
     let val1 = 4_000 * uT;
     let val2 = 5_000 * uT;
     let val3 = 6_000 * uT + fee;
@@ -1199,10 +1388,214 @@ async fn coin_split_no_change() {
         .unwrap();
     assert_eq!(coin_split_tx.body.inputs().len(), 3);
     assert_eq!(coin_split_tx.body.outputs().len(), split_count);
-    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 3, split_count));
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 3, split_count, 0));
     assert_eq!(amount, val1 + val2 + val3);
 }
 
+/// Receive an output keyed from the wallet's key chain and confirm it as unspent
+async fn receive_output(oms: &mut OutputManagerHandle, value: MicroTari) {
+    let (tx_id, sender_message) = generate_sender_transaction_message(value);
+    let rtp = oms.get_recipient_transaction(sender_message).await.unwrap();
+    let output = match rtp.state {
+        RecipientState::Finalized(s) => s.output,
+        RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+    };
+    oms.confirm_transaction(tx_id, vec![], vec![output]).await.unwrap();
+}
+
+#[tokio::test]
+async fn offline_transaction_reservation_and_signing() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
+
+    for value in [2_000, 3_000, 4_000] {
+        receive_output(&mut oms, MicroTari::from(value)).await;
+    }
+
+    // The online wallet reserves the inputs without building a transaction
+    let tx_id = OsRng.next_u64();
+    let reserved = oms
+        .reserve_outputs_for_offline_transaction(tx_id, MicroTari::from(5_000), MicroTari::from(20))
+        .await
+        .unwrap();
+    assert!(!reserved.is_empty());
+    let pending = oms.get_pending_transactions().await.unwrap();
+    assert_eq!(pending.get(&tx_id).unwrap().outputs_to_be_spent.len(), reserved.len());
+    assert!(pending.get(&tx_id).unwrap().outputs_to_be_received.is_empty());
+    assert_eq!(oms.get_unspent_outputs().await.unwrap().len(), 3 - reserved.len());
+    oms.cancel_transaction(tx_id).await.unwrap();
+
+    // The signer rewinds the reserved outputs without encumbering anything or registering the change
+    let inputs = reserved.into_iter().map(|(output, _)| output).collect::<Vec<_>>();
+    let mut stp = oms
+        .prepare_offline_transaction(
+            tx_id,
+            inputs.clone(),
+            MicroTari::from(5_000),
+            MicroTari::from(20),
+            "".to_string(),
+            script!(Nop),
+            OutputFeatures::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(oms.get_pending_transactions().await.unwrap().is_empty());
+    assert_eq!(oms.get_unspent_outputs().await.unwrap().len(), 3);
+
+    let msg = stp.build_single_round_message().unwrap();
+    let b = TestParams::new(&mut OsRng);
+    let recv_info = SingleReceiverTransactionProtocol::create(
+        &msg,
+        b.nonce,
+        b.spend_key,
+        OutputFeatures::default(),
+        &factories,
+        None,
+    )
+    .unwrap();
+    stp.add_single_recipient_info(recv_info, &factories.range_proof)
+        .unwrap();
+    stp.finalize(KernelFeatures::empty(), &factories, None, None).unwrap();
+    let tx = stp.get_transaction().unwrap();
+    assert_eq!(tx.body.inputs().len(), inputs.len());
+    assert!(tx
+        .body
+        .inputs()
+        .iter()
+        .all(|i| inputs.iter().any(|o| o.commitment == i.commitment)));
+
+    // Outputs that cannot be rewound with the wallet's keys cannot be spent
+    let unknown = create_unblinded_output(
+        script!(Nop),
+        OutputFeatures::default(),
+        TestParamsHelpers::new(),
+        MicroTari::from(1_000),
+    )
+    .as_transaction_output(&factories)
+    .unwrap();
+    let result = oms
+        .prepare_offline_transaction(
+            OsRng.next_u64(),
+            vec![unknown],
+            MicroTari::from(500),
+            MicroTari::from(20),
+            "".to_string(),
+            script!(Nop),
+            OutputFeatures::default(),
+            None,
+        )
+        .await;
+    assert!(matches!(result, Err(OutputManagerError::UnknownInput(_))));
+}
+
+#[tokio::test]
+async fn watch_only_wallet_recovers_outputs_but_cannot_spend() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
+    receive_output(&mut oms, MicroTari::from(5_000)).await;
+    let outputs = oms
+        .reserve_outputs_for_offline_transaction(OsRng.next_u64(), MicroTari::from(1_000), MicroTari::from(20))
+        .await
+        .unwrap();
+
+    let keys = oms.get_watch_only_keys().await.unwrap();
+    let (connection, _tempdir2) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut watch_only, _shutdown2, _, _, _, _, _) =
+        setup_output_manager_service_with_keys(backend, true, OutputManagerKeys::WatchOnly(keys)).await;
+
+    let recovered = watch_only
+        .scan_for_recoverable_outputs(outputs.into_iter().map(|(output, _)| output).collect())
+        .await
+        .unwrap();
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(recovered[0].value, MicroTari::from(5_000));
+    assert_eq!(
+        watch_only.get_balance().await.unwrap().available_balance,
+        MicroTari::from(5_000)
+    );
+
+    let result = watch_only
+        .prepare_transaction_to_send(
+            OsRng.next_u64(),
+            MicroTari::from(1_000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+            script!(Nop),
+        )
+        .await;
+    assert!(matches!(result, Err(OutputManagerError::WatchOnlyWallet)));
+
+    // The watch-only wallet can still reserve the outputs for an offline signer
+    let reserved = watch_only
+        .reserve_outputs_for_offline_transaction(OsRng.next_u64(), MicroTari::from(1_000), MicroTari::from(20))
+        .await
+        .unwrap();
+    assert_eq!(reserved.len(), 1);
+}
+
+#[tokio::test]
+async fn watch_only_wallet_detects_stealth_payments() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(backend, true).await;
+    let address = oms.get_stealth_address().await.unwrap();
+    let keys = oms.get_watch_only_keys().await.unwrap();
+    assert!(keys.stealth_view_key.is_some());
+
+    let (connection, _tempdir2) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut watch_only, _shutdown2, _, _, _, _, _) =
+        setup_output_manager_service_with_keys(backend, true, OutputManagerKeys::WatchOnly(keys)).await;
+    assert_eq!(watch_only.get_stealth_address().await.unwrap(), address);
+
+    let sender_offset_private_key = PrivateKey::random(&mut OsRng);
+    let script_private_key = PrivateKey::random(&mut OsRng);
+    let stealth_keys = StealthOutputKeys::for_recipient(&address, &sender_offset_private_key).unwrap();
+    let output = UnblindedOutput::new(
+        MicroTari::from(3_000),
+        stealth_keys.spending_key.clone(),
+        OutputFeatures::default(),
+        stealth_keys.script(),
+        inputs!(PublicKey::from_secret_key(&script_private_key)),
+        script_private_key,
+        PublicKey::from_secret_key(&sender_offset_private_key),
+        Default::default(),
+    )
+    .as_rewindable_transaction_output(&factories, &stealth_keys.rewind_data)
+    .unwrap();
+
+    let detected = watch_only
+        .scan_outputs_for_one_sided_payments(vec![output])
+        .await
+        .unwrap();
+    assert_eq!(detected.len(), 1);
+    assert_eq!(detected[0].value, MicroTari::from(3_000));
+    assert_eq!(detected[0].script_private_key, PrivateKey::default());
+    assert_eq!(
+        watch_only.get_balance().await.unwrap().available_balance,
+        MicroTari::from(3_000)
+    );
+
+    let result = watch_only
+        .prepare_transaction_to_send(
+            OsRng.next_u64(),
+            MicroTari::from(1_000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+            script!(Nop),
+        )
+        .await;
+    assert!(matches!(result, Err(OutputManagerError::WatchOnlyWallet)));
+}
+
 #[tokio::test]
 async fn handle_coinbase() {
     let factories = CryptoFactories::default();
@@ -1900,7 +2293,7 @@ async fn test_oms_key_manager_discrepancy() {
         shutdown.to_signal(),
         basenode_service_handle.clone(),
         connectivity_manager.clone(),
-        master_key1.clone(),
+        master_key1.clone().into(),
     )
     .await
     .unwrap();
@@ -1919,7 +2312,7 @@ async fn test_oms_key_manager_discrepancy() {
         shutdown.to_signal(),
         basenode_service_handle.clone(),
         connectivity_manager.clone(),
-        master_key1,
+        master_key1.into(),
     )
     .await
     .expect("Should be able to make a new OMS with same master key");
@@ -1938,7 +2331,7 @@ async fn test_oms_key_manager_discrepancy() {
         shutdown.to_signal(),
         basenode_service_handle,
         connectivity_manager,
-        master_key2,
+        master_key2.into(),
     )
     .await;
 
@@ -568,3 +568,31 @@ pub async fn test_no_duplicate_outputs() {
     let outputs = db.get_unspent_outputs().await.unwrap();
     assert_eq!(outputs.len(), 1);
 }
+
+#[tokio::test]
+pub async fn test_refundable_htlc_output() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let db = OutputManagerDatabase::new(backend);
+
+    let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(1000), &factories.commitment);
+    let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
+    let tx_id = OsRng.next_u64();
+
+    assert!(db.fetch_refundable_htlc_output(tx_id).await.is_err());
+    db.add_refundable_htlc_output(tx_id, uo.clone()).await.unwrap();
+
+    // the HTLC output is not part of the spendable balance
+    let outputs = db.get_unspent_outputs().await.unwrap();
+    assert!(outputs.is_empty());
+    let balance = db.get_balance(None).await.unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+
+    let htlc_output = db.fetch_refundable_htlc_output(tx_id).await.unwrap();
+    assert_eq!(htlc_output.commitment, uo.commitment);
+
+    // adding it again should be an error
+    let err = db.add_refundable_htlc_output(tx_id, uo).await.unwrap_err();
+    assert!(matches!(err, OutputManagerStorageError::DuplicateOutput));
+}
@@ -39,6 +39,8 @@ use tari_core::{
             ChainMetadata,
             FetchMatchingUtxos,
             FetchUtxosResponse,
+            GetMempoolFeePerGramStatsRequest,
+            GetMempoolFeePerGramStatsResponse,
             Signatures as SignaturesProto,
             TipInfoResponse,
             TxQueryBatchResponse as TxQueryBatchResponseProto,
@@ -87,6 +89,7 @@ pub struct BaseNodeWalletRpcMockState {
     response_delay: Arc<Mutex<Option<Duration>>>,
     rpc_status_error: Arc<Mutex<Option<RpcStatus>>>,
     get_header_response: Arc<Mutex<Option<BlockHeader>>>,
+    fee_per_gram_stats_response: Arc<Mutex<GetMempoolFeePerGramStatsResponse>>,
     synced: Arc<Mutex<bool>>,
     utxos: Arc<Mutex<Vec<TransactionOutput>>>,
 }
@@ -123,6 +126,7 @@ impl BaseNodeWalletRpcMockState {
             response_delay: Arc::new(Mutex::new(None)),
             rpc_status_error: Arc::new(Mutex::new(None)),
             get_header_response: Arc::new(Mutex::new(None)),
+            fee_per_gram_stats_response: Arc::new(Mutex::new(GetMempoolFeePerGramStatsResponse { stats: Vec::new() })),
             synced: Arc::new(Mutex::new(true)),
             utxos: Arc::new(Mutex::new(Vec::new())),
         }
@@ -133,6 +137,11 @@ impl BaseNodeWalletRpcMockState {
         *lock = response;
     }
 
+    pub fn set_fee_per_gram_stats_response(&self, response: GetMempoolFeePerGramStatsResponse) {
+        let mut lock = acquire_lock!(self.fee_per_gram_stats_response);
+        *lock = response;
+    }
+
     pub fn set_submit_transaction_response(&self, response: TxSubmissionResponse) {
         let mut lock = acquire_lock!(self.submit_transaction_response);
         *lock = response;
@@ -469,6 +478,19 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
             .ok_or_else(|| RpcStatus::not_found("get_header_response set to None"))?;
         Ok(Response::new(resp.into()))
     }
+
+    async fn get_mempool_fee_per_gram_stats(
+        &self,
+        _: Request<GetMempoolFeePerGramStatsRequest>,
+    ) -> Result<Response<GetMempoolFeePerGramStatsResponse>, RpcStatus> {
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        let lock = acquire_lock!(self.state.fee_per_gram_stats_response);
+        Ok(Response::new(lock.clone()))
+    }
 }
 
 #[cfg(test)]
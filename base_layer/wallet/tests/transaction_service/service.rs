@@ -89,6 +89,7 @@ use tari_core::{
     transactions::{
         fee::Fee,
         helpers::{create_unblinded_output, TestParams as TestParamsHelpers},
+        script_debug::ScriptDebugReport,
         tari_amount::*,
         transaction::{KernelBuilder, KernelFeatures, OutputFeatures, Transaction},
         transaction_protocol::{proto, recipient::RecipientSignedMessage, sender::TransactionSenderMessage},
@@ -130,7 +131,7 @@ use tari_wallet::{
         config::TransactionServiceConfig,
         error::TransactionServiceError,
         handle::{TransactionEvent, TransactionServiceHandle},
-        service::TransactionService,
+        service::{sha_atomic_swap_script, TransactionService},
         storage::{
             database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
             models::{
@@ -196,7 +197,7 @@ pub fn setup_transaction_service<P: AsRef<Path>>(
             oms_backend,
             factories.clone(),
             Network::Weatherwax.into(),
-            CommsSecretKey::default(),
+            CommsSecretKey::default().into(),
         ))
         .add_initializer(TransactionServiceInitializer::new(
             TransactionServiceConfig {
@@ -346,7 +347,7 @@ pub fn setup_transaction_service_no_comms_and_oms_backend(
             shutdown.to_signal(),
             basenode_service_handle,
             connectivity_manager.clone(),
-            CommsSecretKey::default(),
+            CommsSecretKey::default().into(),
         ))
         .unwrap();
 
@@ -811,6 +812,36 @@ fn send_one_sided_transaction_to_other() {
     });
 }
 
+#[test]
+fn sha_atomic_swap_script_spending_conditions() {
+    let factories = CryptoFactories::default();
+    let (_, dest_pubkey) = PublicKey::random_keypair(&mut OsRng);
+    let (_, refund_pubkey) = PublicKey::random_keypair(&mut OsRng);
+    let (_, pre_image) = PublicKey::random_keypair(&mut OsRng);
+    let (_, wrong_pre_image) = PublicKey::random_keypair(&mut OsRng);
+    let refund_height = 100;
+    let script = sha_atomic_swap_script(&pre_image, &dest_pubkey, &refund_pubkey, refund_height);
+    let commitment = factories.commitment.commit_value(&PrivateKey::random(&mut OsRng), 1000);
+    let execute = |input: &PublicKey, height: u64| {
+        ScriptDebugReport::execute(&script, &inputs!(input.clone()), height, [0u8; 32], &commitment)
+    };
+
+    // With the pre-image the recipient can claim the output, before and after the refund height
+    assert_eq!(execute(&pre_image, refund_height - 1).public_key(), Some(&dest_pubkey));
+    assert_eq!(execute(&pre_image, refund_height + 1).public_key(), Some(&dest_pubkey));
+
+    // Without it the output is locked until the refund height, after which only the sender can spend it
+    assert!(!execute(&wrong_pre_image, refund_height - 1).is_success());
+    assert_eq!(
+        execute(&wrong_pre_image, refund_height).public_key(),
+        Some(&refund_pubkey)
+    );
+    assert_eq!(
+        execute(&wrong_pre_image, refund_height + 1).public_key(),
+        Some(&refund_pubkey)
+    );
+}
+
 #[test]
 fn recover_one_sided_transaction() {
     let mut runtime = create_runtime();
@@ -1451,7 +1482,7 @@ fn finalize_tx_with_incorrect_pubkey() {
 
     stp.add_single_recipient_info(recipient_reply.clone(), &factories.range_proof)
         .unwrap();
-    stp.finalize(KernelFeatures::empty(), &factories).unwrap();
+    stp.finalize(KernelFeatures::empty(), &factories, None, None).unwrap();
     let tx = stp.get_transaction().unwrap();
 
     let finalized_transaction_message = proto::TransactionFinalizedMessage {
@@ -1577,7 +1608,7 @@ fn finalize_tx_with_missing_output() {
 
     stp.add_single_recipient_info(recipient_reply.clone(), &factories.range_proof)
         .unwrap();
-    stp.finalize(KernelFeatures::empty(), &factories).unwrap();
+    stp.finalize(KernelFeatures::empty(), &factories, None, None).unwrap();
 
     let finalized_transaction_message = proto::TransactionFinalizedMessage {
         tx_id: recipient_reply.tx_id,
@@ -2741,7 +2772,7 @@ fn test_restarting_transaction_protocols() {
     let bob = TestParams::new(&mut OsRng);
     let (utxo, input) = make_input(&mut OsRng, MicroTari(2000), &factories.commitment);
     let mut builder = SenderTransactionProtocol::builder(1);
-    let fee = Fee::calculate(MicroTari(20), 1, 1, 1);
+    let fee = Fee::calculate(MicroTari(20), 1, 1, 1, 0);
     let script_private_key = PrivateKey::random(&mut OsRng);
     builder
         .with_lock_height(0)
@@ -2783,7 +2814,7 @@ fn test_restarting_transaction_protocols() {
         .add_single_recipient_info(alice_reply.clone(), &factories.range_proof)
         .unwrap();
 
-    match bob_stp.finalize(KernelFeatures::empty(), &factories) {
+    match bob_stp.finalize(KernelFeatures::empty(), &factories, None, None) {
         Ok(_) => (),
         Err(e) => panic!("Should be able to finalize tx: {}", e),
     };
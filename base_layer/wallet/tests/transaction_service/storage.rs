@@ -370,6 +370,75 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
         panic!("Should have found cancelled completed tx");
     }
 
+    assert!(runtime
+        .block_on(db.get_transaction_cancellation(cancelled_tx_id))
+        .unwrap()
+        .is_none());
+    runtime
+        .block_on(db.record_transaction_cancellation(cancelled_tx_id, "Cancelled by user".to_string(), false))
+        .unwrap();
+    let cancellation = runtime
+        .block_on(db.get_transaction_cancellation(cancelled_tx_id))
+        .unwrap()
+        .expect("Should find the cancellation record");
+    assert_eq!(cancellation.reason, "Cancelled by user");
+    assert!(!cancellation.outputs_released);
+    let unreleased = runtime.block_on(db.get_unreleased_transaction_cancellations()).unwrap();
+    assert_eq!(unreleased, vec![cancellation]);
+
+    runtime
+        .block_on(db.set_transaction_cancellation_outputs_released(cancelled_tx_id))
+        .unwrap();
+    assert!(runtime
+        .block_on(db.get_unreleased_transaction_cancellations())
+        .unwrap()
+        .is_empty());
+    assert!(
+        runtime
+            .block_on(db.get_transaction_cancellation(cancelled_tx_id))
+            .unwrap()
+            .unwrap()
+            .outputs_released
+    );
+
+    runtime
+        .block_on(db.uncancel_completed_transaction(cancelled_tx_id))
+        .unwrap();
+    runtime
+        .block_on(db.remove_transaction_cancellation(cancelled_tx_id))
+        .unwrap();
+    assert!(runtime
+        .block_on(db.get_transaction_cancellation(cancelled_tx_id))
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        runtime.block_on(db.get_completed_transactions()).unwrap().len(),
+        num_completed_txs
+    );
+    runtime
+        .block_on(db.cancel_completed_transaction(cancelled_tx_id))
+        .unwrap();
+
+    let fee_bump_tx_id = 2000;
+    assert!(runtime.block_on(db.get_fee_bump(fee_bump_tx_id)).unwrap().is_none());
+    runtime
+        .block_on(db.record_fee_bump(fee_bump_tx_id, cancelled_tx_id, 100 * uT))
+        .unwrap();
+    let fee_bump = runtime
+        .block_on(db.get_fee_bump(fee_bump_tx_id))
+        .unwrap()
+        .expect("Should find the fee bump record");
+    assert_eq!(fee_bump.parent_tx_id, cancelled_tx_id);
+    assert_eq!(fee_bump.fee_per_gram, 100 * uT);
+    assert_eq!(
+        runtime.block_on(db.get_fee_bumps_for_parent(cancelled_tx_id)).unwrap(),
+        vec![fee_bump]
+    );
+    assert!(runtime
+        .block_on(db.get_fee_bumps_for_parent(fee_bump_tx_id))
+        .unwrap()
+        .is_empty());
+
     runtime
         .block_on(db.add_pending_inbound_transaction(
             999,
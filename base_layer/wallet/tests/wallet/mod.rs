@@ -43,7 +43,7 @@ use tari_common_types::{
 };
 use tari_comms::{
     multiaddr::Multiaddr,
-    peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags},
+    peer_manager::{NodeId, NodeIdentity, Peer, PeerCapabilities, PeerFeatures, PeerFlags},
     types::{CommsPublicKey, CommsSecretKey},
 };
 use tari_comms_dht::DhtConfig;
@@ -53,12 +53,17 @@ use tari_core::transactions::{
     transaction::OutputFeatures,
     CryptoFactories,
 };
+use tari_key_manager::{
+    key_manager::apply_seed_words_passphrase,
+    mnemonic::{from_secret_key, to_secretkey, MnemonicLanguage},
+};
 use tari_p2p::{initialization::P2pConfig, transport::TransportType, Network, DEFAULT_DNS_NAME_SERVER};
 use tari_shutdown::{Shutdown, ShutdownSignal};
 use tari_test_utils::random;
 use tari_wallet::{
     contacts_service::storage::{database::Contact, sqlite_db::ContactsServiceSqliteDatabase},
     error::{WalletError, WalletStorageError},
+    multisig_service::storage::sqlite_db::MultisigServiceSqliteDatabase,
     output_manager_service::storage::sqlite_db::OutputManagerSqliteDatabase,
     storage::{
         database::{DbKeyValuePair, WalletBackend, WalletDatabase, WriteOperation},
@@ -75,6 +80,8 @@ use tari_wallet::{
         handle::TransactionEvent,
         storage::sqlite_db::TransactionServiceSqliteDatabase,
     },
+    types::KeyDigest,
+    wallet::EncryptionRotationProgress,
     Wallet,
     WalletConfig,
     WalletSqlite,
@@ -124,12 +131,16 @@ async fn create_wallet(
         },
         allow_test_addresses: true,
         listener_liveness_allowlist_cidrs: Vec::new(),
+        allowed_addresses: Vec::new(),
+        blocked_addresses: Vec::new(),
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
+        capabilities: PeerCapabilities::NONE,
         dns_seeds_name_server: DEFAULT_DNS_NAME_SERVER.parse().unwrap(),
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: false,
+        peer_snapshot_path: None,
     };
 
     let sql_database_path = comms_config
@@ -138,7 +149,7 @@ async fn create_wallet(
         .join(database_name)
         .with_extension("sqlite3");
 
-    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend) =
+    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend, multisig_backend) =
         initialize_sqlite_database_backends(sql_database_path, passphrase).unwrap();
 
     let transaction_service_config = TransactionServiceConfig {
@@ -169,6 +180,7 @@ async fn create_wallet(
         transaction_backend,
         output_manager_backend,
         contacts_backend,
+        multisig_backend,
         shutdown_signal,
         recovery_master_key,
     )
@@ -470,6 +482,158 @@ async fn test_do_not_overwrite_master_key() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn test_passphrase_wallet_recovers_from_its_seed_words() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+    let seed_key = PrivateKey::random(&mut OsRng);
+    let master_key = apply_seed_words_passphrase::<PrivateKey, KeyDigest>(seed_key.clone(), "passphrase").unwrap();
+
+    // The seed key of a passphrase wallet is stored before the wallet is first started
+    let (wallet_backend, _, _, _, _) =
+        initialize_sqlite_database_backends(dir.path().join("wallet_db").with_extension("sqlite3"), None).unwrap();
+    wallet_backend
+        .write(WriteOperation::Insert(DbKeyValuePair::SeedWordsKey(seed_key.clone())))
+        .unwrap();
+    drop(wallet_backend);
+
+    let mut shutdown = Shutdown::new();
+    let mut wallet = create_wallet(
+        dir.path(),
+        "wallet_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        Some(master_key.clone()),
+    )
+    .await
+    .unwrap();
+    let seed_words = wallet.output_manager_service.get_seed_words().await.unwrap();
+    assert_eq!(
+        seed_words,
+        from_secret_key(&seed_key, &MnemonicLanguage::English).unwrap()
+    );
+    let public_key = wallet.comms.node_identity().public_key().clone();
+
+    // The seed key survives encrypting the wallet
+    wallet.apply_encryption("wallet password".to_string()).await.unwrap();
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+    let mut shutdown = Shutdown::new();
+    let mut wallet = create_wallet(
+        dir.path(),
+        "wallet_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        Some("wallet password".to_string()),
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        wallet.output_manager_service.get_seed_words().await.unwrap(),
+        seed_words
+    );
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+
+    // The seed words only recover the wallet together with the passphrase
+    let recovered_seed_key = to_secretkey::<PrivateKey>(&seed_words).unwrap();
+    assert_ne!(recovered_seed_key, master_key);
+    let recovered_master_key =
+        apply_seed_words_passphrase::<PrivateKey, KeyDigest>(recovered_seed_key, "passphrase").unwrap();
+    let dir = tempdir().unwrap();
+    let shutdown = Shutdown::new();
+    let recovered_wallet = create_wallet(
+        dir.path(),
+        "wallet_db",
+        factories,
+        shutdown.to_signal(),
+        None,
+        Some(recovered_master_key),
+    )
+    .await
+    .unwrap();
+    assert_eq!(recovered_wallet.comms.node_identity().public_key(), &public_key);
+}
+
+#[tokio::test]
+async fn test_rotate_encryption() {
+    let factories = CryptoFactories::default();
+    let dir = tempdir().unwrap();
+    let wallet_path = dir.path().join("wallet_db").with_extension("sqlite3");
+
+    let mut shutdown = Shutdown::new();
+    let mut wallet = create_wallet(
+        dir.path(),
+        "wallet_db",
+        factories.clone(),
+        shutdown.to_signal(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    wallet.apply_encryption("old passphrase".to_string()).await.unwrap();
+
+    // Rotating with the wrong current passphrase must fail and leave the encryption untouched
+    let result = wallet
+        .rotate_encryption("wrong passphrase".to_string(), "new passphrase".to_string(), None)
+        .await
+        .unwrap();
+    assert!(result.is_err());
+
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::channel(10);
+    wallet
+        .rotate_encryption(
+            "old passphrase".to_string(),
+            "new passphrase".to_string(),
+            Some(progress_tx),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    let mut completed = 0;
+    let mut committed = false;
+    while let Some(progress) = progress_rx.recv().await {
+        match progress {
+            EncryptionRotationProgress::Completed { .. } => completed += 1,
+            EncryptionRotationProgress::Committed { .. } => committed = true,
+            EncryptionRotationProgress::Started(_) => {},
+        }
+    }
+    assert_eq!(completed, 4);
+    assert!(committed);
+
+    shutdown.trigger();
+    wallet.wait_until_shutdown().await;
+
+    let connection = run_migration_and_create_sqlite_connection(&wallet_path).expect("Could not open Sqlite db");
+    let passphrase_hash = Blake256::new().chain("old passphrase".as_bytes()).finalize();
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(passphrase_hash.as_slice()));
+    assert!(matches!(
+        WalletSqliteDatabase::new(connection.clone(), Some(cipher)),
+        Err(WalletStorageError::IncorrectPassword)
+    ));
+    let passphrase_hash = Blake256::new().chain("new passphrase".as_bytes()).finalize();
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(passphrase_hash.as_slice()));
+    let db = WalletSqliteDatabase::new(connection, Some(cipher)).expect("Should be able to instantiate db with cipher");
+    drop(db);
+
+    // The outputs and transactions must also be readable with the new passphrase
+    let shutdown = Shutdown::new();
+    let _wallet = create_wallet(
+        dir.path(),
+        "wallet_db",
+        factories,
+        shutdown.to_signal(),
+        Some("new passphrase".to_string()),
+        None,
+    )
+    .await
+    .unwrap();
+}
+
 #[tokio::test]
 async fn test_sign_message() {
     let factories = CryptoFactories::default();
@@ -700,12 +864,16 @@ async fn test_import_utxo() {
         dht: Default::default(),
         allow_test_addresses: true,
         listener_liveness_allowlist_cidrs: Vec::new(),
+        allowed_addresses: Vec::new(),
+        blocked_addresses: Vec::new(),
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
+        capabilities: PeerCapabilities::NONE,
         dns_seeds_name_server: DEFAULT_DNS_NAME_SERVER.parse().unwrap(),
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: false,
+        peer_snapshot_path: None,
     };
     let config = WalletConfig::new(
         comms_config,
@@ -725,7 +893,8 @@ async fn test_import_utxo() {
         WalletDatabase::new(WalletSqliteDatabase::new(connection.clone(), None).unwrap()),
         TransactionServiceSqliteDatabase::new(connection.clone(), None),
         OutputManagerSqliteDatabase::new(connection.clone(), None),
-        ContactsServiceSqliteDatabase::new(connection),
+        ContactsServiceSqliteDatabase::new(connection.clone()),
+        MultisigServiceSqliteDatabase::new(connection, None),
         shutdown.to_signal(),
         None,
     )
@@ -150,7 +150,7 @@ use tari_common_types::{
 };
 use tari_comms::{
     multiaddr::Multiaddr,
-    peer_manager::{NodeIdentity, PeerFeatures},
+    peer_manager::{NodeIdentity, PeerCapabilities, PeerFeatures},
     socks,
     tor,
     transports::MemoryTransport,
@@ -2603,12 +2603,16 @@ pub unsafe extern "C" fn comms_config_create(
                         //       docstring for more info.
                         allow_test_addresses: true,
                         listener_liveness_allowlist_cidrs: Vec::new(),
+                        allowed_addresses: Vec::new(),
+                        blocked_addresses: Vec::new(),
                         listener_liveness_max_sessions: 0,
                         user_agent: format!("tari/wallet/{}", env!("CARGO_PKG_VERSION")),
+                        capabilities: PeerCapabilities::NONE,
                         dns_seeds_name_server: "1.1.1.1:53".parse().unwrap(),
                         peer_seeds: Default::default(),
                         dns_seeds: Default::default(),
                         dns_seeds_use_dnssec: true,
+                        peer_snapshot_path: None,
                     };
 
                     Box::into_raw(Box::new(config))
@@ -2835,9 +2839,9 @@ pub unsafe extern "C" fn wallet_create(
         .with_extension("sqlite3");
 
     debug!(target: LOG_TARGET, "Running Wallet database migrations");
-    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend) =
+    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend, multisig_backend) =
         match initialize_sqlite_database_backends(sql_database_path, passphrase_option) {
-            Ok((w, t, o, c)) => (w, t, o, c),
+            Ok((w, t, o, c, m)) => (w, t, o, c, m),
             Err(e) => {
                 error = LibWalletError::from(WalletError::WalletStorageError(e)).code;
                 ptr::swap(error_out, &mut error as *mut c_int);
@@ -2892,6 +2896,7 @@ pub unsafe extern "C" fn wallet_create(
         transaction_backend.clone(),
         output_manager_backend,
         contacts_backend,
+        multisig_backend,
         shutdown.to_signal(),
         recovery_master_key,
     ));
@@ -4209,8 +4214,8 @@ pub unsafe extern "C" fn wallet_import_utxo(
     source_public_key: *mut TariPublicKey,
     message: *const c_char,
     error_out: *mut c_int,
-    /* TODO: Update this interface to add the metadata signature, script private key and script offset public keys
-     * here. */
+    // TODO: Update this interface to add the metadata signature, script private key and script offset public keys
+    // here.
 ) -> c_ulonglong {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
@@ -45,18 +45,21 @@
 //!         --config <config>            A path to the configuration file to use (config.toml)
 //!         --log-config <log-config>    The path to the log configuration file. It is set using the following precedence
 //!                                      set: [env: TARI_LOG_CONFIGURATION=]
+//!         --log-format <log-format>    The format log messages are written in: `text` or `json` [env: TARI_LOG_FORMAT=]
 //! ```
 
 use super::{
     error::ConfigError,
+    profile::{profiles_dir, Profile, PROFILE_FILE_NAME},
     utils::{install_default_config_file, load_configuration},
 };
 use crate::{
     dir_utils,
-    initialize_logging,
     logging,
+    logging::{initialize_logging_with_format, LogFormat},
     DEFAULT_BASE_NODE_LOG_CONFIG,
     DEFAULT_CONFIG,
+    DEFAULT_INDEXER_LOG_CONFIG,
     DEFAULT_MERGE_MINING_PROXY_LOG_CONFIG,
     DEFAULT_MINING_NODE_LOG_CONFIG,
     DEFAULT_STRATUM_TRANSCODER_LOG_CONFIG,
@@ -128,6 +131,10 @@ pub struct ConfigBootstrap {
     /// Supply the optional wallet seed words for recovery on the command line
     #[structopt(long, alias = "seed_words")]
     pub seed_words: Option<String>,
+    /// Supply the optional passphrase (the "25th word") that was combined with the wallet seed words. A different
+    /// passphrase produces a different wallet
+    #[structopt(long, alias = "seed_words_passphrase")]
+    pub seed_words_passphrase: Option<String>,
     /// Supply the optional file name to save the wallet seed words into
     #[structopt(long, aliases = &["seed_words_file_name", "seed-words-file"], parse(from_os_str))]
     pub seed_words_file_name: Option<PathBuf>,
@@ -151,6 +158,24 @@ pub struct ConfigBootstrap {
     /// Supply a network (overrides existing configuration)
     #[structopt(long, alias = "network")]
     pub network: Option<String>,
+    /// Print the effective configuration, where each value comes from and any configuration problems, then exit
+    #[structopt(long, alias = "config_doctor")]
+    pub config_doctor: bool,
+    /// The format log messages are written in: `text` uses the encoders of the log configuration file, `json` writes
+    /// one JSON object per message
+    #[structopt(long, alias = "log_format", env = "TARI_LOG_FORMAT", default_value = "text")]
+    pub log_format: LogFormat,
+    /// Write a JSON report with the exit code and the errors that caused the application to exit, to stderr or to
+    /// the given file
+    #[structopt(long, alias = "machine_readable_errors", parse(from_os_str))]
+    pub machine_readable_errors: Option<Option<PathBuf>>,
+    /// Use the named profile: a directory under `<base path>/profiles` with its own configuration, data, node
+    /// identities and ports. The profile is created for the `--network` network when it does not exist.
+    #[structopt(long)]
+    pub profile: Option<String>,
+    /// Print the profiles and exit
+    #[structopt(long, alias = "list_profiles")]
+    pub list_profiles: bool,
 }
 
 fn normalize_path(path: PathBuf) -> PathBuf {
@@ -178,6 +203,7 @@ impl Default for ConfigBootstrap {
             change_password: false,
             recovery: false,
             seed_words: None,
+            seed_words_passphrase: None,
             seed_words_file_name: None,
             wallet_notify: None,
             command_mode_auto_exit: false,
@@ -187,6 +213,11 @@ impl Default for ConfigBootstrap {
             miner_max_diff: None,
             tracing_enabled: false,
             network: None,
+            config_doctor: false,
+            log_format: LogFormat::Text,
+            machine_readable_errors: None,
+            profile: None,
+            list_profiles: false,
         }
     }
 }
@@ -200,10 +231,10 @@ impl ConfigBootstrap {
     /// Without `--init` flag provided configuration and directories will be created only
     /// after user's confirmation.
     pub fn init_dirs(&mut self, application_type: ApplicationType) -> Result<(), ConfigError> {
-        if self.base_path.to_str() == Some("") {
-            self.base_path = dir_utils::default_path("", None);
-        } else {
-            self.base_path = dir_utils::absolute_path(&self.base_path);
+        self.base_path = self.root_path();
+        if let Some(ref name) = self.profile {
+            let profile = Profile::open_or_create(&profiles_dir(&self.base_path), name, self.network.as_deref())?;
+            self.base_path = profile.path;
         }
 
         // Create the tari data directory
@@ -250,6 +281,12 @@ impl ConfigBootstrap {
                         Some(&self.base_path),
                     ))
                 },
+                ApplicationType::Indexer => {
+                    self.log_config = normalize_path(dir_utils::default_path(
+                        DEFAULT_INDEXER_LOG_CONFIG,
+                        Some(&self.base_path),
+                    ))
+                },
             }
         }
 
@@ -300,6 +337,9 @@ impl ConfigBootstrap {
                     ApplicationType::MiningNode => {
                         install_configuration(&self.log_config, logging::install_default_mining_node_logfile_config)
                     },
+                    ApplicationType::Indexer => {
+                        install_configuration(&self.log_config, logging::install_default_indexer_logfile_config)
+                    },
                 }
             }
         };
@@ -309,7 +349,7 @@ impl ConfigBootstrap {
     /// Set up application-level logging using the Log4rs configuration file
     /// based on supplied CLI arguments
     pub fn initialize_logging(&self) -> Result<(), ConfigError> {
-        if initialize_logging(&self.log_config, &self.base_path) {
+        if initialize_logging_with_format(&self.log_config, &self.base_path, self.log_format) {
             Ok(())
         } else {
             Err(ConfigError::new("Failed to initialize logging", None))
@@ -320,6 +360,26 @@ impl ConfigBootstrap {
     pub fn load_configuration(&self) -> Result<config::Config, ConfigError> {
         load_configuration(self)
     }
+
+    /// The profiles in the base directory. This must be called before [`ConfigBootstrap::init_dirs()`], which
+    /// replaces the base directory with the directory of the selected profile.
+    pub fn profiles(&self) -> Result<Vec<Profile>, ConfigError> {
+        Profile::list(&profiles_dir(&self.root_path()))
+    }
+
+    /// The profile file to merge over the configuration file, if a profile is used
+    pub fn profile_file(&self) -> Option<PathBuf> {
+        self.profile.as_ref().map(|_| self.base_path.join(PROFILE_FILE_NAME))
+    }
+
+    /// The base directory given on the command line, or the default one
+    fn root_path(&self) -> PathBuf {
+        if self.base_path.to_str() == Some("") {
+            dir_utils::default_path("", None)
+        } else {
+            dir_utils::absolute_path(&self.base_path)
+        }
+    }
 }
 
 pub fn prompt(question: &str) -> bool {
@@ -348,6 +408,7 @@ pub enum ApplicationType {
     MergeMiningProxy,
     MiningNode,
     StratumTranscoder,
+    Indexer,
 }
 
 impl ApplicationType {
@@ -359,6 +420,7 @@ impl ApplicationType {
             MergeMiningProxy => "Tari Merge Mining Proxy",
             MiningNode => "Tari Mining Node",
             StratumTranscoder => "Tari Stratum Transcoder",
+            Indexer => "Tari Indexer",
         }
     }
 
@@ -370,6 +432,7 @@ impl ApplicationType {
             MergeMiningProxy => "merge_mining_proxy",
             MiningNode => "miner",
             StratumTranscoder => "stratum-transcoder",
+            Indexer => "indexer",
         }
     }
 }
@@ -385,6 +448,7 @@ impl FromStr for ApplicationType {
             "mm-proxy" | "mm_proxy" => Ok(MergeMiningProxy),
             "miner" => Ok(MiningNode),
             "stratum-proxy" => Ok(StratumTranscoder),
+            "indexer" => Ok(Indexer),
             _ => Err(ConfigError::new("Invalid ApplicationType", None)),
         }
     }
@@ -0,0 +1,602 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! # Configuration doctor
+//!
+//! Misconfiguration is easy to miss because the configuration is assembled from several layers: defaults, the
+//! `config.toml` file, the `profile.toml` file of the selected profile, `TARI_<SECTION>__<KEY>` environment variables
+//! and command line arguments. The doctor validates the file, profile and environment layers against a schema of all
+//! known keys, reporting unknown (e.g. misspelled) keys with suggestions and values of the wrong type. Run any
+//! application with `--config-doctor` to print the effective configuration together with the layer every value came
+//! from.
+//!
+//! The schema is made up of the defaults from [default_config] and every key documented in the preset
+//! configuration files, including commented out keys. Network specific sections such as `[base_node.weatherwax]`
+//! match every network.
+
+use crate::{
+    configuration::{
+        utils::{config_environment, default_config},
+        Network,
+    },
+    ConfigBootstrap,
+    ConfigError,
+};
+use config::Config;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+const DOCUMENTED_CONFIGS: &[&str] = &[
+    include_str!("../../config/presets/tari_config_example.toml"),
+    include_str!("../../config/presets/tari_igor_config.toml"),
+];
+/// Maximum number of edits between an unknown key and a known key for it to be suggested
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+/// Values of keys containing any of these are not printed
+const SECRET_KEY_PARTS: &[&str] = &["password", "passphrase", "secret", "token", "logins", "_auth"];
+const NETWORK_PLACEHOLDER: &str = "*";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File,
+    Profile,
+    Environment,
+    CommandLine,
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => f.write_str("default"),
+            ConfigSource::File => f.write_str("file"),
+            ConfigSource::Profile => f.write_str("profile"),
+            ConfigSource::Environment => f.write_str("env"),
+            ConfigSource::CommandLine => f.write_str("CLI"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Boolean,
+    Number,
+    String,
+    Array,
+}
+
+impl ValueType {
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(_) => Some(ValueType::Boolean),
+            Value::Number(_) => Some(ValueType::Number),
+            Value::String(_) => Some(ValueType::String),
+            Value::Array(_) => Some(ValueType::Array),
+            Value::Null | Value::Object(_) => None,
+        }
+    }
+
+    /// Whether the value can be read as this type. Environment variables are always strings and many list settings
+    /// also accept a comma separated string.
+    fn accepts(self, value: &Value) -> bool {
+        match (self, value) {
+            (_, Value::Null) => true,
+            (ValueType::Boolean, Value::Bool(_)) => true,
+            (ValueType::Boolean, Value::String(s)) => {
+                matches!(
+                    s.to_lowercase().as_str(),
+                    "true" | "false" | "1" | "0" | "on" | "off" | "yes" | "no"
+                )
+            },
+            (ValueType::Number, Value::Number(_)) => true,
+            (ValueType::Number, Value::String(s)) => s.trim().parse::<f64>().is_ok(),
+            (ValueType::String, Value::Bool(_) | Value::Number(_) | Value::String(_)) => true,
+            (ValueType::Array, Value::Array(_) | Value::String(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Display for ValueType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueType::Boolean => f.write_str("a boolean"),
+            ValueType::Number => f.write_str("a number"),
+            ValueType::String => f.write_str("a string"),
+            ValueType::Array => f.write_str("an array"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigIssue {
+    UnknownKey {
+        key: String,
+        source: ConfigSource,
+        suggestion: Option<String>,
+    },
+    InvalidType {
+        key: String,
+        source: ConfigSource,
+        expected: ValueType,
+        value: Value,
+    },
+}
+
+impl Display for ConfigIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigIssue::UnknownKey {
+                key,
+                source,
+                suggestion,
+            } => {
+                write!(f, "Unknown key '{}' ({})", display_key(key, *source), source)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean '{}'?", display_key(suggestion, *source))?;
+                }
+                Ok(())
+            },
+            ConfigIssue::InvalidType {
+                key,
+                source,
+                expected,
+                value,
+            } => write!(
+                f,
+                "Invalid value {} for '{}' ({}), expected {}",
+                value,
+                display_key(key, *source),
+                source,
+                expected
+            ),
+        }
+    }
+}
+
+/// The known configuration keys, with the network segment of network specific keys replaced by a placeholder
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSchema {
+    keys: BTreeMap<String, Option<ValueType>>,
+}
+
+impl ConfigSchema {
+    pub fn new(defaults: &BTreeMap<String, Value>) -> Self {
+        let mut schema = Self::default();
+        for documented in DOCUMENTED_CONFIGS {
+            schema.keys.extend(Self::from_documented(documented).keys);
+        }
+        for (key, value) in defaults {
+            let value_type = ValueType::of(value);
+            let entry = schema.keys.entry(normalize_key(key)).or_insert(value_type);
+            if value_type.is_some() {
+                *entry = value_type;
+            }
+        }
+        schema
+    }
+
+    /// Collects the keys of a documented configuration file. Commented out keys are included, their type is taken
+    /// from the example value.
+    fn from_documented(documented: &str) -> Self {
+        let mut keys = BTreeMap::new();
+        let mut section = String::new();
+        for line in documented.lines() {
+            let line = line.trim().trim_start_matches('#').trim();
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = &line[1..line.len() - 1];
+                if is_key(name) {
+                    section = name.to_string();
+                }
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let (name, value) = match (parts.next().map(str::trim), parts.next().map(str::trim)) {
+                (Some(name), Some(value)) if is_key(name) && !value.is_empty() => (name, value),
+                _ => continue,
+            };
+            let key = if section.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", section, name)
+            };
+            let value_type = toml::from_str::<BTreeMap<String, Value>>(&format!("value = {}", value))
+                .ok()
+                .and_then(|v| v.get("value").and_then(ValueType::of));
+            keys.insert(normalize_key(&key), value_type);
+        }
+        Self { keys }
+    }
+
+    /// Checks the values of a configuration layer against the schema
+    pub fn validate<'a, I>(&self, values: I, source: ConfigSource) -> Vec<ConfigIssue>
+    where I: IntoIterator<Item = (&'a String, &'a Value)> {
+        values
+            .into_iter()
+            .filter_map(|(key, value)| match self.lookup(key) {
+                None => Some(ConfigIssue::UnknownKey {
+                    key: key.clone(),
+                    source,
+                    suggestion: self.suggest(key),
+                }),
+                Some(Some(expected)) if !expected.accepts(value) => Some(ConfigIssue::InvalidType {
+                    key: key.clone(),
+                    source,
+                    expected,
+                    value: value.clone(),
+                }),
+                Some(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns the type of a known key. Keys may be set for all networks or for a single network, so both forms are
+    /// looked up.
+    fn lookup(&self, key: &str) -> Option<Option<ValueType>> {
+        let normalized = normalize_key(key);
+        let mut segments = normalized.split('.').collect::<Vec<_>>();
+        if let Some(value_type) = self.keys.get(&normalized) {
+            return Some(*value_type);
+        }
+        if segments.len() > 2 && segments[1] == NETWORK_PLACEHOLDER {
+            segments.remove(1);
+        } else if segments.len() > 1 {
+            segments.insert(1, NETWORK_PLACEHOLDER);
+        }
+        self.keys.get(&segments.join(".")).copied()
+    }
+
+    /// Suggests the closest known key, or a key with the same name in another section
+    fn suggest(&self, key: &str) -> Option<String> {
+        let normalized = normalize_key(key);
+        let closest = self
+            .keys
+            .keys()
+            .map(|known| (edit_distance(&normalized, known), known))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, known)| known);
+        let name = normalized.rsplit('.').next().unwrap_or_default();
+        let same_name = || self.keys.keys().find(|known| known.rsplit('.').next() == Some(name));
+        closest.or_else(same_name).map(|known| denormalize_key(known, key))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: Value,
+    pub source: ConfigSource,
+}
+
+/// The effective configuration with the provenance of every value, and the issues found in it
+#[derive(Debug, Clone)]
+pub struct ConfigDoctor {
+    entries: Vec<ConfigEntry>,
+    issues: Vec<ConfigIssue>,
+}
+
+/// The configuration layers that the doctor reads, except for the command line
+struct ConfigLayers {
+    defaults: BTreeMap<String, Value>,
+    file: BTreeMap<String, Value>,
+    profile: BTreeMap<String, Value>,
+    env: BTreeMap<String, Value>,
+}
+
+impl ConfigLayers {
+    fn load(bootstrap: &ConfigBootstrap) -> Result<Self, ConfigError> {
+        let defaults = flatten_config(default_config(bootstrap))?;
+
+        let mut file = Config::new();
+        let filename = bootstrap
+            .config
+            .to_str()
+            .ok_or_else(|| ConfigError::new("Invalid config file path", None))?;
+        file.merge(config::File::with_name(filename))
+            .map_err(|e| ConfigError::new("Failed to parse the configuration file", Some(e.to_string())))?;
+        let file = flatten_config(file)?;
+
+        let mut profile = BTreeMap::new();
+        if let Some(profile_file) = bootstrap.profile_file() {
+            let mut cfg = Config::new();
+            cfg.merge(config::File::from(profile_file))
+                .map_err(|e| ConfigError::new("Failed to parse the profile", Some(e.to_string())))?;
+            // The `[profile]` section describes the profile itself
+            profile = flatten_config(cfg)?
+                .into_iter()
+                .filter(|(key, _)| !key.starts_with("profile."))
+                .collect();
+        }
+
+        let mut env = Config::new();
+        env.merge(config_environment())
+            .map_err(|e| ConfigError::new("Failed to read environment variables", Some(e.to_string())))?;
+        // Only variables using the `__` section separator are configuration keys, others such as
+        // `TARI_LOG_CONFIGURATION` are read by the command line parser
+        let env = flatten_config(env)?
+            .into_iter()
+            .filter(|(key, _)| key.contains('.'))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(Self {
+            defaults,
+            file,
+            profile,
+            env,
+        })
+    }
+
+    fn issues(&self) -> Vec<ConfigIssue> {
+        let schema = ConfigSchema::new(&self.defaults);
+        let mut issues = schema.validate(&self.file, ConfigSource::File);
+        issues.extend(schema.validate(&self.profile, ConfigSource::Profile));
+        issues.extend(schema.validate(&self.env, ConfigSource::Environment));
+        issues
+    }
+}
+
+impl ConfigDoctor {
+    /// Only checks the configuration for problems, without collecting the effective configuration. Applications do
+    /// this at startup.
+    pub fn check(bootstrap: &ConfigBootstrap) -> Result<Vec<ConfigIssue>, ConfigError> {
+        Ok(ConfigLayers::load(bootstrap)?.issues())
+    }
+
+    /// Checks the configuration and collects the effective value of every key together with its source
+    pub fn diagnose(bootstrap: &ConfigBootstrap) -> Result<Self, ConfigError> {
+        let layers = ConfigLayers::load(bootstrap)?;
+        let issues = layers.issues();
+
+        let mut cli = BTreeMap::new();
+        if let Some(ref network) = bootstrap.network {
+            cli.insert("common.network".to_string(), Value::String(network.clone()));
+        }
+
+        let mut merged = BTreeMap::new();
+        for (layer, source) in vec![
+            (layers.defaults, ConfigSource::Default),
+            (layers.file, ConfigSource::File),
+            (layers.profile, ConfigSource::Profile),
+            (layers.env, ConfigSource::Environment),
+            (cli, ConfigSource::CommandLine),
+        ] {
+            for (key, value) in layer {
+                merged.insert(key.clone(), ConfigEntry { key, value, source });
+            }
+        }
+
+        Ok(Self {
+            entries: merged.into_iter().map(|(_, entry)| entry).collect(),
+            issues,
+        })
+    }
+
+    pub fn entries(&self) -> &[ConfigEntry] {
+        &self.entries
+    }
+
+    pub fn issues(&self) -> &[ConfigIssue] {
+        &self.issues
+    }
+}
+
+impl Display for ConfigDoctor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Effective configuration (source in brackets):")?;
+        for entry in &self.entries {
+            let value = if SECRET_KEY_PARTS.iter().any(|part| entry.key.contains(part)) {
+                "\"********\"".to_string()
+            } else {
+                entry.value.to_string()
+            };
+            writeln!(f, "  {} = {} [{}]", entry.key, value, entry.source)?;
+        }
+        writeln!(f)?;
+        if self.issues.is_empty() {
+            writeln!(f, "No configuration problems found.")
+        } else {
+            writeln!(f, "{} configuration problem(s) found:", self.issues.len())?;
+            for issue in &self.issues {
+                writeln!(f, "  - {}", issue)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn flatten_config(cfg: Config) -> Result<BTreeMap<String, Value>, ConfigError> {
+    let value = cfg
+        .try_into::<Value>()
+        .map_err(|e| ConfigError::new("Failed to read the configuration", Some(e.to_string())))?;
+    let mut flattened = BTreeMap::new();
+    flatten_value(String::new(), value, &mut flattened);
+    Ok(flattened)
+}
+
+fn flatten_value(prefix: String, value: Value, flattened: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (name, value) in map {
+                let key = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                flatten_value(key, value, flattened);
+            }
+        },
+        value => {
+            flattened.insert(prefix, value);
+        },
+    }
+}
+
+fn is_key(s: &str) -> bool {
+    !s.is_empty() &&
+        s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.' || c == '-')
+}
+
+pub(crate) fn normalize_key(key: &str) -> String {
+    key.split('.')
+        .map(|segment| {
+            if Network::from_str(segment).is_ok() {
+                NETWORK_PLACEHOLDER
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Puts the network of the original key back into a normalized key
+fn denormalize_key(normalized: &str, original: &str) -> String {
+    let network = original
+        .split('.')
+        .find(|segment| Network::from_str(segment).is_ok())
+        .unwrap_or("<network>");
+    normalized
+        .split('.')
+        .map(|segment| {
+            if segment == NETWORK_PLACEHOLDER {
+                network
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Keys of the environment layer are shown as the variable that sets them
+fn display_key(key: &str, source: ConfigSource) -> String {
+    match source {
+        ConfigSource::Environment => format!("TARI_{}", key.replace('.', "__").to_uppercase()),
+        _ => key.to_string(),
+    }
+}
+
+/// The Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn values(pairs: &[(&str, Value)]) -> BTreeMap<String, Value> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    #[test]
+    fn it_calculates_edit_distance() {
+        assert_eq!(edit_distance("grpc_address", "grpc_address"), 0);
+        assert_eq!(edit_distance("grpc_adress", "grpc_address"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn it_reads_documented_keys() {
+        let schema = ConfigSchema::from_documented(
+            "[common]\n# The network\n#network = \"weatherwax\"\n[base_node.igor]\ndb_type = \"lmdb\"\n# \
+             Flag\n#grpc_enabled = true\n# not = a key value\n",
+        );
+        assert_eq!(schema.lookup("common.network"), Some(Some(ValueType::String)));
+        assert_eq!(
+            schema.lookup("base_node.mainnet.db_type"),
+            Some(Some(ValueType::String))
+        );
+        assert_eq!(schema.lookup("base_node.grpc_enabled"), Some(Some(ValueType::Boolean)));
+        assert_eq!(schema.lookup("common.db_type"), None);
+    }
+
+    #[test]
+    fn it_reports_unknown_keys_with_suggestions() {
+        let schema = ConfigSchema::new(&values(&[
+            (
+                "base_node.weatherwax.grpc_base_node_address",
+                Value::from("127.0.0.1:18142"),
+            ),
+            ("common.message_cache_size", Value::from(10)),
+        ]));
+        let issues = schema.validate(
+            &values(&[
+                ("base_node.igor.grpc_base_node_adress", Value::from("127.0.0.1:18142")),
+                ("wallet.message_cache_size", Value::from(10)),
+                ("common.message_cache_size", Value::from("ten")),
+                ("common.network", Value::from("igor")),
+            ]),
+            ConfigSource::File,
+        );
+        assert_eq!(issues, vec![
+            ConfigIssue::UnknownKey {
+                key: "base_node.igor.grpc_base_node_adress".to_string(),
+                source: ConfigSource::File,
+                suggestion: Some("base_node.igor.grpc_base_node_address".to_string()),
+            },
+            ConfigIssue::InvalidType {
+                key: "common.message_cache_size".to_string(),
+                source: ConfigSource::File,
+                expected: ValueType::Number,
+                value: Value::from("ten"),
+            },
+            ConfigIssue::UnknownKey {
+                key: "wallet.message_cache_size".to_string(),
+                source: ConfigSource::File,
+                suggestion: Some("common.message_cache_size".to_string()),
+            },
+        ]);
+    }
+
+    #[test]
+    fn it_displays_environment_keys_as_variables() {
+        let issue = ConfigIssue::UnknownKey {
+            key: "base_node.weatherwax.grpc_enabeld".to_string(),
+            source: ConfigSource::Environment,
+            suggestion: Some("base_node.weatherwax.grpc_enabled".to_string()),
+        };
+        assert_eq!(
+            issue.to_string(),
+            "Unknown key 'TARI_BASE_NODE__WEATHERWAX__GRPC_ENABELD' (env), did you mean \
+             'TARI_BASE_NODE__WEATHERWAX__GRPC_ENABLED'?"
+        );
+    }
+}
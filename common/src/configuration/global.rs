@@ -23,11 +23,11 @@
 //! # Global configuration of tari base layer system
 
 use crate::{
-    configuration::{bootstrap::ApplicationType, Network},
+    configuration::{bootstrap::ApplicationType, utils::config_environment, Network},
     ConfigurationError,
 };
-use config::{Config, ConfigError, Environment};
-use multiaddr::Multiaddr;
+use config::{Config, ConfigError};
+use multiaddr::{Multiaddr, Protocol};
 use std::{
     convert::TryInto,
     fmt,
@@ -43,6 +43,7 @@ use tari_storage::lmdb_store::LMDBConfig;
 const DB_INIT_DEFAULT_MB: usize = 1000;
 const DB_GROW_SIZE_DEFAULT_MB: usize = 500;
 const DB_RESIZE_THRESHOLD_DEFAULT_MB: usize = 100;
+const DB_GROW_PERCENT_DEFAULT: usize = 10;
 
 const DB_INIT_MIN_MB: i64 = 100;
 const DB_GROW_SIZE_MIN_MB: i64 = 20;
@@ -56,30 +57,51 @@ pub struct GlobalConfig {
     pub autoupdate_dns_hosts: Vec<String>,
     pub autoupdate_hashes_url: String,
     pub autoupdate_hashes_sig_url: String,
+    pub autoupdate_socks5_proxy_address: Option<Multiaddr>,
+    pub autoupdate_socks5_auth: SocksAuthentication,
     pub network: Network,
     pub comms_transport: CommsTransport,
     pub auxilary_tcp_listener_address: Option<Multiaddr>,
     pub allow_test_addresses: bool,
     pub listnener_liveness_max_sessions: usize,
     pub listener_liveness_allowlist_cidrs: Vec<String>,
+    pub allowed_addresses: Vec<String>,
+    pub blocked_addresses: Vec<String>,
     pub rpc_max_simultaneous_sessions: Option<usize>,
+    pub rpc_max_sessions_per_peer: Option<usize>,
+    pub rpc_session_bandwidth_limit: Option<u64>,
     pub data_dir: PathBuf,
     pub db_type: DatabaseType,
     pub db_config: LMDBConfig,
     pub orphan_storage_capacity: usize,
     pub orphan_db_clean_out_threshold: usize,
     pub pruning_horizon: u64,
+    pub sync_mode: SyncMode,
+    pub pruning_retained_commitments: Vec<String>,
     pub pruned_mode_cleanup_interval: u64,
+    pub header_cache_size: usize,
+    pub verification_cache_size: usize,
+    pub pruned_mode_gc_batch_size: u64,
+    pub pruned_mode_gc_batch_delay: Duration,
+    pub pruned_mode_gc_interval: Duration,
     pub core_threads: Option<usize>,
     pub base_node_identity_file: PathBuf,
     pub public_address: Multiaddr,
     pub grpc_enabled: bool,
     pub grpc_base_node_address: SocketAddr,
+    pub grpc_base_node_read_only_address: Option<SocketAddr>,
+    pub grpc_base_node_admin_token: Option<String>,
     pub grpc_console_wallet_address: SocketAddr,
+    pub grpc_slow_request_threshold: Option<Duration>,
+    pub grpc_rate_limit: u32,
+    pub grpc_rate_limit_burst: u32,
     pub peer_seeds: Vec<String>,
     pub dns_seeds: Vec<String>,
     pub dns_seeds_name_server: SocketAddr,
     pub dns_seeds_use_dnssec: bool,
+    pub seed_mode: bool,
+    pub balance_audit_enabled: bool,
+    pub balance_audit_min_interval: Duration,
     pub peer_db_path: PathBuf,
     pub num_mining_threads: usize,
     pub base_node_tor_identity_file: PathBuf,
@@ -106,6 +128,7 @@ pub struct GlobalConfig {
     pub transaction_broadcast_send_timeout: Duration,
     pub transaction_routing_mechanism: String,
     pub transaction_num_confirmations_required: u64,
+    pub transaction_cancelled_output_release_timeout: Duration,
     pub transaction_event_channel_size: usize,
     pub base_node_event_channel_size: usize,
     pub output_manager_event_channel_size: usize,
@@ -118,20 +141,70 @@ pub struct GlobalConfig {
     pub wallet_base_node_service_request_max_age: u64,
     pub wallet_balance_enquiry_cooldown_period: u64,
     pub prevent_fee_gt_amount: bool,
-    pub monerod_url: String,
+    pub monerod_url: Vec<String>,
     pub monerod_username: String,
     pub monerod_password: String,
     pub monerod_use_auth: bool,
     pub proxy_host_address: SocketAddr,
     pub transcoder_host_address: SocketAddr,
+    pub transcoder_logins: Vec<String>,
     pub proxy_submit_to_origin: bool,
     pub force_sync_peers: Vec<String>,
     pub wait_for_initial_sync_at_startup: bool,
     pub max_randomx_vms: usize,
     pub console_wallet_notify_file: Option<PathBuf>,
+    pub console_wallet_grpc_slow_request_threshold: Option<Duration>,
+    pub console_wallet_grpc_rate_limit: u32,
+    pub console_wallet_grpc_rate_limit_burst: u32,
+    pub console_wallet_faucet_enabled: bool,
+    pub console_wallet_faucet_amount: u64,
+    pub console_wallet_faucet_fee_per_gram: u64,
+    pub console_wallet_faucet_cooldown: Duration,
+    pub console_wallet_faucet_max_payouts_per_hour: u32,
+    pub console_wallet_faucet_tokens: Vec<String>,
     pub auto_ping_interval: u64,
+    pub auto_ping_interval_max: u64,
     pub blocks_behind_before_considered_lagging: u64,
+    pub block_sync_commit_batch_size: usize,
+    pub warmup_recent_peers: usize,
+    pub warmup_sync_candidates: usize,
+    pub warmup_timeout: Duration,
+    pub warmup_sleep_threshold: Duration,
+    pub shutdown_stop_accepting_work_timeout: Duration,
+    pub shutdown_flush_databases_timeout: Duration,
+    pub shutdown_close_comms_timeout: Duration,
+    pub chain_monitor_reference_nodes: Vec<String>,
+    pub chain_monitor_interval: Duration,
+    pub chain_monitor_fork_depth: u64,
+    pub block_timing_window: usize,
+    pub mining_distribution_window: usize,
+    pub time_sync_interval: Duration,
+    pub time_sync_ntp_servers: Vec<String>,
+    pub max_clock_skew: Duration,
+    pub refuse_mining_on_clock_skew: bool,
+    pub event_journal_max_events: usize,
+    pub alert_webhook_url: Option<String>,
+    pub alert_slack_webhook_url: Option<String>,
+    pub alert_telegram_bot_token: Option<String>,
+    pub alert_telegram_chat_id: Option<String>,
+    pub alert_blocks_behind: u64,
+    pub alert_mining_share_percent: u64,
+    pub alert_reorg_depth: u64,
+    pub alert_on_peer_ban: bool,
+    pub alert_min_free_disk_space_mb: u64,
+    pub disk_space_check_interval: Duration,
+    pub disk_space_warning_mb: u64,
+    pub disk_space_read_only_mb: u64,
+    pub checkpoints: Vec<String>,
+    pub assume_valid_before_height: u64,
     pub flood_ban_max_msg_count: usize,
+    pub min_relay_fee_per_gram: u64,
+    pub mempool_ingress_workers: usize,
+    pub mempool_ingress_queue_size: usize,
+    pub mempool_ingress_max_queued_per_peer: usize,
+    pub status_line_interval: Option<Duration>,
+    pub status_line_fields: Vec<String>,
+    pub console_aliases: Vec<String>,
     pub mine_on_tip_only: bool,
     pub validate_tip_timeout_sec: u64,
     pub mining_pool_address: String,
@@ -144,8 +217,7 @@ impl GlobalConfig {
     pub fn convert_from(application: ApplicationType, mut cfg: Config) -> Result<Self, ConfigurationError> {
         // Add in settings from the environment (with a prefix of TARI_NODE)
         // Eg.. `TARI_NODE_DEBUG=1 ./target/app` would set the `debug` key
-        let env = Environment::with_prefix("tari").separator("__");
-        cfg.merge(env)
+        cfg.merge(config_environment())
             .map_err(|e| ConfigurationError::new("environment variable", &e.to_string()))?;
 
         let network = one_of::<Network>(&cfg, &[
@@ -247,7 +319,20 @@ fn convert_node_config(
         },
     };
 
-    let db_config = LMDBConfig::new_from_mb(init_size_mb, grow_size_mb, resize_threshold_mb);
+    let key = config_string("base_node", net_str, "db_grow_percent");
+    let grow_percent = match optional(cfg.get_int(&key))? {
+        Some(percent) if !(0..=100).contains(&percent) => {
+            return Err(ConfigurationError::new(
+                &key,
+                "DB grow percentage must be between 0 and 100.",
+            ))
+        },
+        Some(percent) => percent as usize,
+        None => DB_GROW_PERCENT_DEFAULT,
+    };
+
+    let db_config =
+        LMDBConfig::new_from_mb(init_size_mb, grow_size_mb, resize_threshold_mb).with_grow_percent(grow_percent);
 
     let key = config_string("base_node", net_str, "orphan_storage_capacity");
     let orphan_storage_capacity = cfg
@@ -264,11 +349,51 @@ fn convert_node_config(
         .get_int(&key)
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as u64;
 
+    let key = config_string("base_node", net_str, "sync_mode");
+    let sync_mode = match optional(cfg.get_str(&key))? {
+        Some(s) => s.parse::<SyncMode>().map_err(|e| ConfigurationError::new(&key, &e))?,
+        None => SyncMode::from_pruning_horizon(pruning_horizon),
+    };
+    sync_mode
+        .check_pruning_horizon(pruning_horizon)
+        .map_err(|e| ConfigurationError::new(&key, &e))?;
+
+    let key = config_string("base_node", net_str, "pruning_retained_commitments");
+    let pruning_retained_commitments = optional(cfg.get_array(&key))?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect();
+
     let key = config_string("base_node", net_str, "pruned_mode_cleanup_interval");
     let pruned_mode_cleanup_interval = cfg
         .get_int(&key)
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as u64;
 
+    let key = config_string("base_node", net_str, "header_cache_size");
+    let header_cache_size = optional(cfg.get_int(&key))?.unwrap_or(1000);
+    if header_cache_size < 0 {
+        return Err(ConfigurationError::new(&key, "Header cache size cannot be negative."));
+    }
+    let header_cache_size = header_cache_size as usize;
+
+    let key = config_string("base_node", net_str, "verification_cache_size");
+    let verification_cache_size = optional(cfg.get_int(&key))?.unwrap_or(50_000);
+    if verification_cache_size < 0 {
+        return Err(ConfigurationError::new(
+            &key,
+            "Verification cache size cannot be negative.",
+        ));
+    }
+    let verification_cache_size = verification_cache_size as usize;
+
+    let key = config_string("base_node", net_str, "pruned_mode_gc_batch_size");
+    let pruned_mode_gc_batch_size = optional(cfg.get_int(&key))?.unwrap_or(100) as u64;
+    let key = config_string("base_node", net_str, "pruned_mode_gc_batch_delay_ms");
+    let pruned_mode_gc_batch_delay = Duration::from_millis(optional(cfg.get_int(&key))?.unwrap_or(500) as u64);
+    let key = config_string("base_node", net_str, "pruned_mode_gc_interval");
+    let pruned_mode_gc_interval = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(600) as u64);
+
     // Thread counts
     let key = config_string("base_node", net_str, "core_threads");
     let core_threads =
@@ -348,6 +473,24 @@ fn convert_node_config(
                 .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
         })?;
 
+    let key = config_string("base_node", net_str, "grpc_base_node_read_only_address");
+    let grpc_base_node_read_only_address = optional(cfg.get_str(&key))?
+        .map(|addr| {
+            addr.parse::<SocketAddr>()
+                .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
+        })
+        .transpose()?;
+
+    let key = config_string("base_node", net_str, "grpc_base_node_admin_token");
+    let grpc_base_node_admin_token = optional(cfg.get_str(&key))?.filter(|token| !token.is_empty());
+
+    let key = config_string("base_node", net_str, "grpc_slow_request_threshold_ms");
+    let grpc_slow_request_threshold = millis_or_disabled(optional(cfg.get_int(&key))?.unwrap_or(1000));
+    let key = config_string("base_node", net_str, "grpc_rate_limit");
+    let grpc_rate_limit = optional(cfg.get_int(&key))?.unwrap_or(0).max(0) as u32;
+    let key = config_string("base_node", net_str, "grpc_rate_limit_burst");
+    let grpc_rate_limit_burst = optional(cfg.get_int(&key))?.unwrap_or(20).max(0) as u32;
+
     let key = config_string("base_node", net_str, "grpc_console_wallet_address");
     let grpc_console_wallet_address = cfg
         .get_str(&key)
@@ -391,6 +534,14 @@ fn convert_node_config(
         .map(|v| v.into_str().unwrap())
         .collect::<Vec<_>>();
 
+    let key = config_string("base_node", net_str, "seed_mode");
+    let seed_mode = optional(cfg.get_bool(&key))?.unwrap_or(false);
+
+    let key = config_string("base_node", net_str, "balance_audit_enabled");
+    let balance_audit_enabled = optional(cfg.get_bool(&key))?.unwrap_or(false);
+    let key = config_string("base_node", net_str, "balance_audit_min_interval_secs");
+    let balance_audit_min_interval = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(600).max(0) as u64);
+
     // Peer DB path
     let peer_db_path = data_dir.join("peer_db");
     let wallet_peer_db_path = data_dir.join("wallet_peer_db");
@@ -401,6 +552,36 @@ fn convert_node_config(
         .get_int(&key)
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as usize;
 
+    let key = config_string("base_node", net_str, "min_relay_fee_per_gram");
+    let min_relay_fee_per_gram = optional(cfg.get_int(&key))?.unwrap_or(0) as u64;
+
+    let key = config_string("base_node", net_str, "mempool_ingress_workers");
+    let mempool_ingress_workers = optional(cfg.get_int(&key))?.unwrap_or(4).max(1) as usize;
+
+    let key = config_string("base_node", net_str, "mempool_ingress_queue_size");
+    let mempool_ingress_queue_size = optional(cfg.get_int(&key))?.unwrap_or(1_000).max(1) as usize;
+
+    let key = config_string("base_node", net_str, "mempool_ingress_max_queued_per_peer");
+    let mempool_ingress_max_queued_per_peer = optional(cfg.get_int(&key))?.unwrap_or(100).max(1) as usize;
+
+    let key = config_string("base_node", net_str, "status_line_interval");
+    let status_line_interval = optional(cfg.get_int(&key))?.map(|secs| Duration::from_secs(secs.max(0) as u64));
+
+    let key = config_string("base_node", net_str, "status_line_fields");
+    let status_line_fields = match cfg.get_array(&key) {
+        Ok(fields) => fields.into_iter().map(|v| v.into_str().unwrap()).collect(),
+        Err(..) => match cfg.get_str(&key) {
+            Ok(s) => s.split(',').map(|v| v.trim().to_string()).collect(),
+            Err(..) => vec![],
+        },
+    };
+
+    let key = config_string("base_node", net_str, "console_aliases");
+    let console_aliases = match cfg.get_array(&key) {
+        Ok(aliases) => aliases.into_iter().map(|v| v.into_str().unwrap()).collect(),
+        Err(..) => vec![],
+    };
+
     // block sync
     let key = config_string("base_node", net_str, "force_sync_peers");
     let force_sync_peers = match cfg.get_array(&key) {
@@ -418,11 +599,113 @@ fn convert_node_config(
         Err(ConfigError::NotFound(_)) => 30,
         Err(e) => return Err(ConfigurationError::new(&key, &e.to_string())),
     };
+    // The interval that chain metadata broadcasts back off to while synced; adaptation is off if not above the minimum
+    let key = config_string("base_node", net_str, "auto_ping_interval_max");
+    let auto_ping_interval_max = optional(cfg.get_int(&key))?.map(|s| s as u64).unwrap_or(120);
 
     // blocks_behind_before_considered_lagging when a node should switch over from listening to lagging
     let key = config_string("base_node", net_str, "blocks_behind_before_considered_lagging");
     let blocks_behind_before_considered_lagging = optional(cfg.get_int(&key))?.unwrap_or(0) as u64;
 
+    let key = config_string("base_node", net_str, "block_sync_commit_batch_size");
+    let block_sync_commit_batch_size = optional(cfg.get_int(&key))?.unwrap_or(50).max(1) as usize;
+
+    // The peers dialed on startup, and after the host has been asleep, before listening for chain metadata
+    let key = config_string("base_node", net_str, "warmup_recent_peers");
+    let warmup_recent_peers = optional(cfg.get_int(&key))?.unwrap_or(8).max(0) as usize;
+    let key = config_string("base_node", net_str, "warmup_sync_candidates");
+    let warmup_sync_candidates = optional(cfg.get_int(&key))?.unwrap_or(4).max(0) as usize;
+    let key = config_string("base_node", net_str, "warmup_timeout");
+    let warmup_timeout = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(30).max(1) as u64);
+    let key = config_string("base_node", net_str, "warmup_sleep_threshold");
+    let warmup_sleep_threshold = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(300).max(0) as u64);
+
+    // The time allowed for each phase of a graceful shutdown
+    let key = config_string("base_node", net_str, "shutdown_stop_accepting_work_timeout");
+    let shutdown_stop_accepting_work_timeout = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(10) as u64);
+    let key = config_string("base_node", net_str, "shutdown_flush_databases_timeout");
+    let shutdown_flush_databases_timeout = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(60) as u64);
+    let key = config_string("base_node", net_str, "shutdown_close_comms_timeout");
+    let shutdown_close_comms_timeout = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(10) as u64);
+
+    let key = config_string("base_node", net_str, "chain_monitor_reference_nodes");
+    let chain_monitor_reference_nodes = optional(cfg.get_array(&key))?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect();
+    let key = config_string("base_node", net_str, "chain_monitor_interval");
+    let chain_monitor_interval = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(300) as u64);
+    let key = config_string("base_node", net_str, "chain_monitor_fork_depth");
+    let chain_monitor_fork_depth = optional(cfg.get_int(&key))?.unwrap_or(3) as u64;
+    let key = config_string("base_node", net_str, "block_timing_window");
+    let block_timing_window = optional(cfg.get_int(&key))?.unwrap_or(60).max(0) as usize;
+    let key = config_string("base_node", net_str, "mining_distribution_window");
+    let mining_distribution_window = optional(cfg.get_int(&key))?.unwrap_or(720).max(0) as usize;
+
+    let key = config_string("base_node", net_str, "time_sync_interval");
+    let time_sync_interval = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(1800).max(0) as u64);
+    let key = config_string("base_node", net_str, "time_sync_ntp_servers");
+    let time_sync_ntp_servers = optional(cfg.get_array(&key))?
+        .map(|servers| servers.into_iter().map(|v| v.to_string()).collect())
+        .unwrap_or_else(|| vec!["pool.ntp.org".to_string()]);
+    let key = config_string("base_node", net_str, "max_clock_skew");
+    let max_clock_skew = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(30).max(1) as u64);
+    let key = config_string("base_node", net_str, "refuse_mining_on_clock_skew");
+    let refuse_mining_on_clock_skew = optional(cfg.get_bool(&key))?.unwrap_or(false);
+
+    let key = config_string("base_node", net_str, "event_journal_max_events");
+    let event_journal_max_events = optional(cfg.get_int(&key))?.unwrap_or(10_000).max(1) as usize;
+
+    let key = config_string("base_node", net_str, "alert_webhook_url");
+    let alert_webhook_url = optional(cfg.get_str(&key))?.filter(|url| !url.is_empty());
+    let key = config_string("base_node", net_str, "alert_slack_webhook_url");
+    let alert_slack_webhook_url = optional(cfg.get_str(&key))?.filter(|url| !url.is_empty());
+    let key = config_string("base_node", net_str, "alert_telegram_bot_token");
+    let alert_telegram_bot_token = optional(cfg.get_str(&key))?.filter(|token| !token.is_empty());
+    let key = config_string("base_node", net_str, "alert_telegram_chat_id");
+    let alert_telegram_chat_id = optional(cfg.get_str(&key))?.filter(|id| !id.is_empty());
+    if alert_telegram_bot_token.is_some() != alert_telegram_chat_id.is_some() {
+        return Err(ConfigurationError::new(
+            &key,
+            "alert_telegram_bot_token and alert_telegram_chat_id must be set together",
+        ));
+    }
+    let key = config_string("base_node", net_str, "alert_blocks_behind");
+    let alert_blocks_behind = optional(cfg.get_int(&key))?.unwrap_or(10).max(0) as u64;
+    let key = config_string("base_node", net_str, "alert_mining_share_percent");
+    let alert_mining_share_percent = optional(cfg.get_int(&key))?.unwrap_or(50).max(0).min(100) as u64;
+    let key = config_string("base_node", net_str, "alert_reorg_depth");
+    let alert_reorg_depth = optional(cfg.get_int(&key))?.unwrap_or(3).max(0) as u64;
+    let key = config_string("base_node", net_str, "alert_on_peer_ban");
+    let alert_on_peer_ban = optional(cfg.get_bool(&key))?.unwrap_or(false);
+    let key = config_string("base_node", net_str, "alert_min_free_disk_space_mb");
+    let alert_min_free_disk_space_mb = optional(cfg.get_int(&key))?.unwrap_or(1024).max(0) as u64;
+
+    let key = config_string("base_node", net_str, "disk_space_check_interval");
+    let disk_space_check_interval = optional(cfg.get_int(&key))?
+        .map(|secs| Duration::from_secs(secs.max(1) as u64))
+        .unwrap_or_else(|| Duration::from_secs(60));
+    let key = config_string("base_node", net_str, "disk_space_warning_mb");
+    let disk_space_warning_mb = optional(cfg.get_int(&key))?.unwrap_or(2048).max(0) as u64;
+    let key = config_string("base_node", net_str, "disk_space_read_only_mb");
+    let disk_space_read_only_mb = optional(cfg.get_int(&key))?.unwrap_or(256).max(0) as u64;
+    if disk_space_warning_mb > 0 && disk_space_warning_mb <= disk_space_read_only_mb {
+        return Err(ConfigurationError::new(
+            &key,
+            "disk_space_warning_mb must be larger than disk_space_read_only_mb",
+        ));
+    }
+
+    let key = config_string("base_node", net_str, "checkpoints");
+    let checkpoints = optional(cfg.get_array(&key))?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect();
+    let key = config_string("base_node", net_str, "assume_valid_before_height");
+    let assume_valid_before_height = optional(cfg.get_int(&key))?.unwrap_or(0) as u64;
+
     // set wallet_db_file
     let key = "wallet.wallet_db_file".to_string();
     let wallet_db_file = cfg
@@ -478,6 +761,10 @@ fn convert_node_config(
     let key = "wallet.transaction_num_confirmations_required";
     let transaction_num_confirmations_required = optional(cfg.get_int(key))?.unwrap_or(3) as u64;
 
+    let key = "wallet.transaction_cancelled_output_release_timeout";
+    let transaction_cancelled_output_release_timeout =
+        Duration::from_secs(optional(cfg.get_int(key))?.unwrap_or(3600) as u64);
+
     let key = "wallet.transaction_event_channel_size";
     let transaction_event_channel_size = optional(cfg.get_int(key))?.unwrap_or(1000) as usize;
 
@@ -521,6 +808,29 @@ fn convert_node_config(
     let key = "wallet.notify";
     let console_wallet_notify_file = optional(cfg.get_str(key))?.map(PathBuf::from);
 
+    let key = "wallet.grpc_slow_request_threshold_ms";
+    let console_wallet_grpc_slow_request_threshold = millis_or_disabled(optional(cfg.get_int(key))?.unwrap_or(1000));
+    let key = "wallet.grpc_rate_limit";
+    let console_wallet_grpc_rate_limit = optional(cfg.get_int(key))?.unwrap_or(0).max(0) as u32;
+    let key = "wallet.grpc_rate_limit_burst";
+    let console_wallet_grpc_rate_limit_burst = optional(cfg.get_int(key))?.unwrap_or(20).max(0) as u32;
+
+    let key = "wallet.faucet_enabled";
+    let console_wallet_faucet_enabled = optional(cfg.get_bool(key))?.unwrap_or(false);
+    let key = "wallet.faucet_amount";
+    let console_wallet_faucet_amount = optional(cfg.get_int(key))?.unwrap_or(10_000_000).max(0) as u64;
+    let key = "wallet.faucet_fee_per_gram";
+    let console_wallet_faucet_fee_per_gram = optional(cfg.get_int(key))?.unwrap_or(5).max(0) as u64;
+    let key = "wallet.faucet_cooldown_secs";
+    let console_wallet_faucet_cooldown =
+        Duration::from_secs(optional(cfg.get_int(key))?.unwrap_or(24 * 60 * 60).max(0) as u64);
+    let key = "wallet.faucet_max_payouts_per_hour";
+    let console_wallet_faucet_max_payouts_per_hour = optional(cfg.get_int(key))?.unwrap_or(60).max(0) as u32;
+    let key = "wallet.faucet_tokens";
+    let console_wallet_faucet_tokens = optional(cfg.get_array(key))?
+        .map(|values| values.into_iter().map(|v| v.to_string()).collect())
+        .unwrap_or_default();
+
     let key = "wallet.base_node_service_refresh_interval";
     let wallet_base_node_service_refresh_interval = cfg
         .get_int(key)
@@ -555,6 +865,20 @@ fn convert_node_config(
         .map(|values| values.iter().map(ToString::to_string).collect())
         .unwrap_or_else(|_| vec!["127.0.0.1/32".to_string()]);
 
+    let key = config_string("base_node", net_str, "allowed_addresses");
+    let allowed_addresses = optional(cfg.get_array(&key))?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect();
+
+    let key = config_string("base_node", net_str, "blocked_addresses");
+    let blocked_addresses = optional(cfg.get_array(&key))?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect();
+
     let key = "common.rpc_max_simultaneous_sessions";
     let rpc_max_simultaneous_sessions = cfg
         .get_int(key)
@@ -568,6 +892,30 @@ fn convert_node_config(
             )),
         })?;
 
+    let key = "common.rpc_max_sessions_per_peer";
+    let rpc_max_sessions_per_peer = match optional(cfg.get_int(key))?.unwrap_or(10) {
+        0 => None,
+        n if n.is_positive() => Some(n as usize),
+        v => {
+            return Err(ConfigurationError::new(
+                key,
+                &format!("invalid value {} for rpc_max_sessions_per_peer", v),
+            ))
+        },
+    };
+
+    let key = "common.rpc_session_bandwidth_limit_kib";
+    let rpc_session_bandwidth_limit = match optional(cfg.get_int(key))?.unwrap_or(0) {
+        0 => None,
+        n if n.is_positive() => Some(n as u64 * 1024),
+        v => {
+            return Err(ConfigurationError::new(
+                key,
+                &format!("invalid value {} for rpc_session_bandwidth_limit_kib", v),
+            ))
+        },
+    };
+
     let key = "common.buffer_size_base_node";
     let buffer_size_base_node = cfg
         .get_int(key)
@@ -612,9 +960,14 @@ fn convert_node_config(
     );
 
     let key = config_string("merge_mining_proxy", net_str, "monerod_url");
-    let monerod_url = cfg
-        .get_str(&key)
-        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?;
+    // Monerod URLs can be an array or a comma separated list, the first one is used until it fails
+    let monerod_url = match cfg.get_array(&key) {
+        Ok(urls) => urls.into_iter().map(|v| v.into_str().unwrap()).collect(),
+        Err(..) => match cfg.get_str(&key) {
+            Ok(s) => s.split(',').map(|v| v.trim().to_string()).collect(),
+            Err(err) => return Err(ConfigurationError::new(&key, &err.to_string())),
+        },
+    };
 
     let key = config_string("merge_mining_proxy", net_str, "monerod_use_auth");
     let monerod_use_auth = cfg
@@ -649,6 +1002,16 @@ fn convert_node_config(
                 .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
         })?;
 
+    let key = config_string("stratum_transcoder", net_str, "logins");
+    // Logins can be an array or a comma separated list (e.g. in an ENVVAR)
+    let transcoder_logins = match cfg.get_array(&key) {
+        Ok(logins) => logins.into_iter().map(|v| v.into_str().unwrap()).collect(),
+        Err(..) => match cfg.get_str(&key) {
+            Ok(s) => s.split(',').map(|v| v.trim().to_string()).collect(),
+            Err(..) => vec![],
+        },
+    };
+
     let key = config_string("merge_mining_proxy", net_str, "wait_for_initial_sync_at_startup");
     let wait_for_initial_sync_at_startup = cfg
         .get_bool(&key)
@@ -691,6 +1054,18 @@ fn convert_node_config(
     let key = "common.auto_update.hashes_sig_url";
     let autoupdate_hashes_sig_url = cfg.get_str(key)?;
 
+    // Update checks and downloads go through this proxy instead of the one used by the p2p transport
+    let key = "common.auto_update.socks5_proxy_address";
+    let autoupdate_socks5_proxy_address = match optional(cfg.get_str(key))? {
+        Some(addr) => Some(parse_socks_proxy_address(key, &addr)?),
+        None => None,
+    };
+    let key = "common.auto_update.socks5_auth";
+    let autoupdate_socks5_auth = match optional(cfg.get_str(key))? {
+        Some(auth) => auth.parse().map_err(|err: String| ConfigurationError::new(key, &err))?,
+        None => SocksAuthentication::None,
+    };
+
     let key = "mining_node.mining_pool_address";
     let mining_pool_address = cfg.get_str(key).unwrap_or_else(|_| "".to_string());
     let key = "mining_node.mining_wallet_address";
@@ -708,30 +1083,51 @@ fn convert_node_config(
         autoupdate_dns_hosts,
         autoupdate_hashes_url,
         autoupdate_hashes_sig_url,
+        autoupdate_socks5_proxy_address,
+        autoupdate_socks5_auth,
         network,
         comms_transport,
         auxilary_tcp_listener_address,
         allow_test_addresses,
         listnener_liveness_max_sessions: liveness_max_sessions,
         listener_liveness_allowlist_cidrs: liveness_allowlist_cidrs,
+        allowed_addresses,
+        blocked_addresses,
         rpc_max_simultaneous_sessions,
+        rpc_max_sessions_per_peer,
+        rpc_session_bandwidth_limit,
         data_dir,
         db_type,
         db_config,
         orphan_storage_capacity,
         orphan_db_clean_out_threshold,
         pruning_horizon,
+        sync_mode,
+        pruning_retained_commitments,
         pruned_mode_cleanup_interval,
+        header_cache_size,
+        verification_cache_size,
+        pruned_mode_gc_batch_size,
+        pruned_mode_gc_batch_delay,
+        pruned_mode_gc_interval,
         core_threads,
         base_node_identity_file,
         public_address,
         grpc_enabled,
         grpc_base_node_address,
+        grpc_base_node_read_only_address,
+        grpc_base_node_admin_token,
         grpc_console_wallet_address,
+        grpc_slow_request_threshold,
+        grpc_rate_limit,
+        grpc_rate_limit_burst,
         peer_seeds,
         dns_seeds,
         dns_seeds_name_server,
         dns_seeds_use_dnssec,
+        seed_mode,
+        balance_audit_enabled,
+        balance_audit_min_interval,
         peer_db_path,
         num_mining_threads,
         base_node_tor_identity_file,
@@ -758,6 +1154,7 @@ fn convert_node_config(
         transaction_broadcast_send_timeout,
         transaction_routing_mechanism,
         transaction_num_confirmations_required,
+        transaction_cancelled_output_release_timeout,
         transaction_event_channel_size,
         base_node_event_channel_size,
         output_manager_event_channel_size,
@@ -772,6 +1169,7 @@ fn convert_node_config(
         prevent_fee_gt_amount,
         proxy_host_address,
         transcoder_host_address,
+        transcoder_logins,
         proxy_submit_to_origin,
         monerod_url,
         monerod_username,
@@ -781,9 +1179,58 @@ fn convert_node_config(
         wait_for_initial_sync_at_startup,
         max_randomx_vms,
         console_wallet_notify_file,
+        console_wallet_grpc_slow_request_threshold,
+        console_wallet_grpc_rate_limit,
+        console_wallet_grpc_rate_limit_burst,
+        console_wallet_faucet_enabled,
+        console_wallet_faucet_amount,
+        console_wallet_faucet_fee_per_gram,
+        console_wallet_faucet_cooldown,
+        console_wallet_faucet_max_payouts_per_hour,
+        console_wallet_faucet_tokens,
         auto_ping_interval,
+        auto_ping_interval_max,
         blocks_behind_before_considered_lagging,
+        block_sync_commit_batch_size,
+        warmup_recent_peers,
+        warmup_sync_candidates,
+        warmup_timeout,
+        warmup_sleep_threshold,
+        shutdown_stop_accepting_work_timeout,
+        shutdown_flush_databases_timeout,
+        shutdown_close_comms_timeout,
+        chain_monitor_reference_nodes,
+        chain_monitor_interval,
+        chain_monitor_fork_depth,
+        block_timing_window,
+        mining_distribution_window,
+        time_sync_interval,
+        time_sync_ntp_servers,
+        max_clock_skew,
+        refuse_mining_on_clock_skew,
+        event_journal_max_events,
+        alert_webhook_url,
+        alert_slack_webhook_url,
+        alert_telegram_bot_token,
+        alert_telegram_chat_id,
+        alert_blocks_behind,
+        alert_mining_share_percent,
+        alert_reorg_depth,
+        alert_on_peer_ban,
+        alert_min_free_disk_space_mb,
+        disk_space_check_interval,
+        disk_space_warning_mb,
+        disk_space_read_only_mb,
+        checkpoints,
+        assume_valid_before_height,
         flood_ban_max_msg_count,
+        min_relay_fee_per_gram,
+        mempool_ingress_workers,
+        mempool_ingress_queue_size,
+        mempool_ingress_max_queued_per_peer,
+        status_line_interval,
+        status_line_fields,
+        console_aliases,
         mine_on_tip_only,
         validate_tip_timeout_sec,
         mining_pool_address,
@@ -794,6 +1241,15 @@ fn convert_node_config(
 }
 
 /// Changes ConfigError::NotFound into None
+/// A duration in milliseconds, where 0 or less disables the setting
+fn millis_or_disabled(millis: i64) -> Option<Duration> {
+    if millis <= 0 {
+        None
+    } else {
+        Some(Duration::from_millis(millis as u64))
+    }
+}
+
 fn optional<T>(result: Result<T, ConfigError>) -> Result<Option<T>, ConfigError> {
     match result {
         Ok(v) => Ok(Some(v)),
@@ -854,9 +1310,19 @@ fn network_transport_config(
             let key = config_string(app_str, network, "tcp_listener_address");
             let listener_address = get_conf_multiaddr(&key)?;
             let key = config_string(app_str, network, "tcp_tor_socks_address");
-            let tor_socks_address = get_conf_multiaddr(&key).ok();
+            let tor_socks_address = match get_conf_str(&key).ok() {
+                Some(addr) => Some(parse_socks_proxy_address(&key, &addr)?),
+                None => None,
+            };
             let key = config_string(app_str, network, "tcp_tor_socks_auth");
-            let tor_socks_auth = get_conf_str(&key).ok().and_then(|auth_str| auth_str.parse().ok());
+            let tor_socks_auth = match get_conf_str(&key).ok() {
+                Some(auth_str) => Some(
+                    auth_str
+                        .parse()
+                        .map_err(|err: String| ConfigurationError::new(&key, &err))?,
+                ),
+                None => None,
+            };
 
             Ok(CommsTransport::Tcp {
                 listener_address,
@@ -915,7 +1381,7 @@ fn network_transport_config(
         },
         "socks5" => {
             let key = config_string(app_str, network, "socks5_proxy_address");
-            let proxy_address = get_conf_multiaddr(&key)?;
+            let proxy_address = parse_socks_proxy_address(&key, &get_conf_str(&key)?)?;
 
             let key = config_string(app_str, network, "socks5_auth");
             let auth_str = get_conf_str(&key)?;
@@ -939,10 +1405,106 @@ fn network_transport_config(
     }
 }
 
+/// Parses the address of a SOCKS5 proxy, which must be an IP address or DNS name followed by a TCP port
+fn parse_socks_proxy_address(key: &str, addr: &str) -> Result<Multiaddr, ConfigurationError> {
+    let address = addr
+        .parse::<Multiaddr>()
+        .map_err(|err| ConfigurationError::new(key, &err.to_string()))?;
+    let mut protocols = address.iter();
+    let has_host = matches!(
+        protocols.next(),
+        Some(Protocol::Ip4(_)) |
+            Some(Protocol::Ip6(_)) |
+            Some(Protocol::Dns(_)) |
+            Some(Protocol::Dns4(_)) |
+            Some(Protocol::Dns6(_))
+    );
+    let has_port = matches!(protocols.next(), Some(Protocol::Tcp(_)));
+    if !has_host || !has_port || protocols.next().is_some() {
+        return Err(ConfigurationError::new(
+            key,
+            &format!(
+                "Invalid SOCKS5 proxy address '{}'. It should be an IP address or DNS name and a TCP port, e.g. \
+                 '/ip4/127.0.0.1/tcp/1080'",
+                addr
+            ),
+        ));
+    }
+    Ok(address)
+}
+
 fn config_string(prefix: &str, network: &str, key: &str) -> String {
     format!("{}.{}.{}", prefix, network, key)
 }
 
+//---------------------------------------------        Sync mode          ------------------------------------------//
+/// The strategy a base node uses to sync the blockchain when it starts up or falls behind the network. Every mode
+/// syncs and validates the block headers first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Download every block from genesis and keep the full history. Requires a pruning horizon of 0.
+    Archival,
+    /// Download the kernels and UTXO set at the pruning horizon from a peer, then the blocks above it. Requires a
+    /// pruning horizon.
+    Pruned,
+    /// Download every block from genesis like an archival node and prune the history below the pruning horizon
+    /// afterwards. Slower than `Pruned`, but the whole chain is validated and sync peers do not have to serve the
+    /// horizon state. Requires a pruning horizon.
+    HeadersFirst,
+}
+
+impl SyncMode {
+    /// The mode used when none is configured: `Pruned` if there is a pruning horizon, otherwise `Archival`
+    pub fn from_pruning_horizon(pruning_horizon: u64) -> Self {
+        if pruning_horizon > 0 {
+            SyncMode::Pruned
+        } else {
+            SyncMode::Archival
+        }
+    }
+
+    /// Returns an error if this mode cannot be used with the pruning horizon
+    pub fn check_pruning_horizon(self, pruning_horizon: u64) -> Result<(), String> {
+        match self {
+            SyncMode::Archival if pruning_horizon > 0 => Err(format!(
+                "The archival sync mode keeps the full history but a pruning horizon of {} is configured",
+                pruning_horizon
+            )),
+            SyncMode::Pruned | SyncMode::HeadersFirst if pruning_horizon == 0 => Err(format!(
+                "The {} sync mode requires a pruning horizon, use the archival sync mode for a pruning horizon of 0",
+                self
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for SyncMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "archival" => Ok(SyncMode::Archival),
+            "pruned" => Ok(SyncMode::Pruned),
+            "headers-first" => Ok(SyncMode::HeadersFirst),
+            s => Err(format!(
+                "Invalid sync mode '{}', expected 'archival', 'pruned' or 'headers-first'",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for SyncMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncMode::Archival => f.write_str("archival"),
+            SyncMode::Pruned => f.write_str("pruned"),
+            SyncMode::HeadersFirst => f.write_str("headers-first"),
+        }
+    }
+}
+
 //---------------------------------------------      Database type        ------------------------------------------//
 #[derive(Debug, Clone)]
 pub enum DatabaseType {
@@ -1012,20 +1574,29 @@ impl FromStr for SocksAuthentication {
         match auth_type.as_str() {
             "none" => Ok(SocksAuthentication::None),
             "username_password" => {
+                // The username is case sensitive, so it is not split off with parse_key_value
                 let (username, password) = maybe_value
                     .and_then(|value| {
-                        let (un, pwd) = parse_key_value(value, ':');
-                        // If pwd is None, return None
-                        pwd.map(|p| (un, p))
+                        let mut parts = value.splitn(2, ':');
+                        let username = parts.next()?;
+                        parts.next().map(|pwd| (username, pwd))
                     })
+                    .filter(|(un, _)| !un.is_empty())
                     .ok_or_else(|| {
                         "Invalid format for 'username-password' socks authentication type. It should be in the format \
                          'username_password=my_username:xxxxxx'."
                             .to_string()
                     })?;
-                Ok(SocksAuthentication::UsernamePassword(username, password.to_string()))
+                // RFC 1929 limits the username and password to 255 bytes each
+                if username.len() > 255 || password.len() > 255 {
+                    return Err("The socks username and password may not be longer than 255 bytes".to_string());
+                }
+                Ok(SocksAuthentication::UsernamePassword(
+                    username.to_string(),
+                    password.to_string(),
+                ))
             },
-            s => Err(format!("Invalid tor auth type '{}'", s)),
+            s => Err(format!("Invalid socks auth type '{}'", s)),
         }
     }
 }
@@ -1059,3 +1630,34 @@ pub enum CommsTransport {
         listener_address: Multiaddr,
     },
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn socks_authentication_from_str() {
+        assert!(matches!("none".parse(), Ok(SocksAuthentication::None)));
+        match "username_password=CorpUser:p@ss:word".parse() {
+            Ok(SocksAuthentication::UsernamePassword(username, password)) => {
+                assert_eq!(username, "CorpUser");
+                assert_eq!(password, "p@ss:word");
+            },
+            _ => panic!("Expected username and password authentication"),
+        }
+        assert!("username_password=user".parse::<SocksAuthentication>().is_err());
+        assert!("username_password=:password".parse::<SocksAuthentication>().is_err());
+        let long_username = format!("username_password={}:password", "u".repeat(256));
+        assert!(long_username.parse::<SocksAuthentication>().is_err());
+        assert!("basic".parse::<SocksAuthentication>().is_err());
+    }
+
+    #[test]
+    fn socks_proxy_address() {
+        assert!(parse_socks_proxy_address("key", "/ip4/127.0.0.1/tcp/1080").is_ok());
+        assert!(parse_socks_proxy_address("key", "/dns4/proxy.example.com/tcp/1080").is_ok());
+        assert!(parse_socks_proxy_address("key", "/ip4/127.0.0.1").is_err());
+        assert!(parse_socks_proxy_address("key", "/ip4/127.0.0.1/udp/1080").is_err());
+        assert!(parse_socks_proxy_address("key", "127.0.0.1:1080").is_err());
+    }
+}
@@ -35,11 +35,14 @@
 //! ```
 
 pub mod bootstrap;
+pub mod doctor;
 pub mod error;
 pub mod global;
 pub mod loader;
 mod network;
+pub mod profile;
 pub use network::Network;
+pub mod reload;
 pub mod seconds;
 pub mod utils;
 pub mod writer;
@@ -0,0 +1,342 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! # Profiles
+//!
+//! A profile is a named base directory under `<base path>/profiles/` holding its own configuration, data
+//! directories and node identities, so that nodes for several networks can run side by side without sharing a
+//! database. Run any application with `--profile <name>` to use a profile, it is created on first use:
+//!
+//! - The network is the one given with `--network`, or the profile name if that is a network name.
+//! - Listening ports are moved by a port offset that is unique among the profiles, e.g. the gRPC server of the profile
+//!   with offset 1000 listens on port 19142 rather than 18142.
+//!
+//! Both are written to `profile.toml` in the profile directory, which is merged over the profile's `config.toml`
+//! and can be edited. Run with `--list-profiles` to print the profiles.
+
+use crate::{
+    configuration::{utils::default_config, Network},
+    ConfigBootstrap,
+    ConfigError,
+};
+use config::Config;
+use std::{
+    collections::BTreeSet,
+    fmt,
+    fmt::{Display, Formatter},
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use toml::value::{Table, Value};
+
+pub const PROFILES_DIR: &str = "profiles";
+pub const PROFILE_FILE_NAME: &str = "profile.toml";
+/// Each profile's ports are moved by a multiple of this, which is larger than the range of the default ports
+const PORT_OFFSET_STEP: u16 = 1000;
+/// The most profiles there can be before the ports of the last one no longer fit
+const MAX_PROFILES: u16 = 40;
+/// Keys of the listening ports of a network, `{}` is replaced with the network name
+const PORT_KEYS: &[&str] = &[
+    "base_node.{}.tcp_listener_address",
+    "base_node.{}.tor_onion_port",
+    "base_node.{}.grpc_base_node_address",
+    "base_node.{}.grpc_console_wallet_address",
+    "wallet.{}.tcp_listener_address",
+    "wallet.{}.tor_onion_port",
+    "merge_mining_proxy.{}.proxy_host_address",
+    "stratum_transcoder.{}.transcoder_host_address",
+];
+
+/// The directory the profiles are kept in
+pub fn profiles_dir(base_path: &Path) -> PathBuf {
+    base_path.join(PROFILES_DIR)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub network: Network,
+    pub port_offset: u16,
+    pub path: PathBuf,
+}
+
+impl Profile {
+    /// Opens the named profile, or creates it for the given network. Fails if the profile exists but is for a
+    /// different network.
+    pub fn open_or_create(profiles_dir: &Path, name: &str, network: Option<&str>) -> Result<Self, ConfigError> {
+        validate_name(name)?;
+        let network = network
+            .map(|network| {
+                Network::from_str(network)
+                    .map_err(|err| ConfigError::new("Invalid network for the profile", Some(err.to_string())))
+            })
+            .transpose()?;
+
+        let path = profiles_dir.join(name);
+        if path.join(PROFILE_FILE_NAME).exists() {
+            let profile = Self::load(&path)?;
+            return match network {
+                Some(network) if network != profile.network => Err(ConfigError::new(
+                    "The profile is for a different network",
+                    Some(format!(
+                        "profile '{}' is for {}, not {}",
+                        profile.name, profile.network, network
+                    )),
+                )),
+                _ => Ok(profile),
+            };
+        }
+
+        let network = match network {
+            Some(network) => network,
+            None => Network::from_str(name).map_err(|_| {
+                ConfigError::new(
+                    "Use --network to choose the network of a new profile",
+                    Some(format!("'{}' is not a network name", name)),
+                )
+            })?,
+        };
+        let used_offsets = Self::list(profiles_dir)?
+            .into_iter()
+            .map(|profile| profile.port_offset)
+            .collect::<BTreeSet<_>>();
+        let port_offset = (1..=MAX_PROFILES)
+            .map(|i| i * PORT_OFFSET_STEP)
+            .find(|offset| !used_offsets.contains(offset))
+            .ok_or_else(|| ConfigError::new("There are too many profiles to assign ports to a new one", None))?;
+
+        let profile = Self {
+            name: name.to_string(),
+            network,
+            port_offset,
+            path,
+        };
+        profile.write()?;
+        println!(
+            "Created profile '{}' for {} at {}",
+            profile.name,
+            network,
+            profile.path.display()
+        );
+        Ok(profile)
+    }
+
+    /// Loads the profile in the given directory
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let mut cfg = Config::new();
+        cfg.merge(config::File::from(path.join(PROFILE_FILE_NAME)))
+            .map_err(|e| ConfigError::new("Failed to read the profile", Some(e.to_string())))?;
+        let network = cfg
+            .get_str("common.network")
+            .map_err(|e| e.to_string())
+            .and_then(|network| Network::from_str(&network).map_err(|e| e.to_string()))
+            .map_err(|e| ConfigError::new("The profile has no valid network", Some(e)))?;
+        let port_offset = cfg
+            .get_int("profile.port_offset")
+            .map_err(|e| ConfigError::new("The profile has no valid port offset", Some(e.to_string())))?;
+        Ok(Self {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            network,
+            port_offset: port_offset as u16,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// All the profiles in the given directory, ordered by name
+    pub fn list(profiles_dir: &Path) -> Result<Vec<Self>, ConfigError> {
+        if !profiles_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = fs::read_dir(profiles_dir)
+            .map_err(|e| ConfigError::new("Failed to read the profiles directory", Some(e.to_string())))?;
+        let mut profiles = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join(PROFILE_FILE_NAME).exists())
+            .map(|path| Self::load(&path))
+            .collect::<Result<Vec<_>, _>>()?;
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+
+    fn write(&self) -> Result<(), ConfigError> {
+        let defaults = default_config(&ConfigBootstrap {
+            base_path: self.path.clone(),
+            ..Default::default()
+        });
+        let mut contents = Table::new();
+        insert(
+            &mut contents,
+            "profile.port_offset",
+            Value::Integer(i64::from(self.port_offset)),
+        );
+        insert(&mut contents, "common.network", Value::String(self.network.to_string()));
+        for key in PORT_KEYS {
+            let key = key.replace("{}", self.network.as_str());
+            if let Some(value) = defaults
+                .get_str(&key)
+                .ok()
+                .and_then(|v| offset_port(&v, self.port_offset))
+            {
+                insert(&mut contents, &key, value);
+            }
+        }
+        let contents = toml::to_string(&contents)
+            .map_err(|e| ConfigError::new("Failed to write the profile", Some(e.to_string())))?;
+
+        fs::create_dir_all(&self.path)
+            .and_then(|_| {
+                fs::write(
+                    self.path.join(PROFILE_FILE_NAME),
+                    format!(
+                        "# The network and listening ports of the '{}' profile, these override the profile's \
+                         config.toml\n\n{}",
+                        self.name, contents
+                    ),
+                )
+            })
+            .map_err(|e| ConfigError::new("Failed to write the profile", Some(e.to_string())))
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<16} {:<12} port offset {:<6} {}",
+            self.name,
+            self.network,
+            self.port_offset,
+            self.path.display()
+        )
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), ConfigError> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(ConfigError::new(
+            "Profile names may only contain letters, digits, '-' and '_'",
+            Some(name.to_string()),
+        ));
+    }
+    Ok(())
+}
+
+/// Moves the port of a port number, a socket address or a multiaddr by the given offset. Port 0 (any port) is kept.
+fn offset_port(value: &str, offset: u16) -> Option<Value> {
+    let shift = |port: u16| if port == 0 { Some(0) } else { port.checked_add(offset) };
+    if let Ok(port) = value.parse::<u16>() {
+        return shift(port).map(|port| Value::Integer(i64::from(port)));
+    }
+    if let Ok(mut addr) = value.parse::<SocketAddr>() {
+        addr.set_port(shift(addr.port())?);
+        return Some(Value::String(addr.to_string()));
+    }
+    let mut parts = value.split('/').map(str::to_string).collect::<Vec<_>>();
+    let port_index = parts.iter().position(|part| part == "tcp" || part == "udp")? + 1;
+    let port = parts.get(port_index)?.parse::<u16>().ok()?;
+    parts[port_index] = shift(port)?.to_string();
+    Some(Value::String(parts.join("/")))
+}
+
+/// Inserts the value at the dotted key, creating the tables on the way
+fn insert(table: &mut Table, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((section, rest)) => {
+            let entry = table
+                .entry(section.to_string())
+                .or_insert_with(|| Value::Table(Table::new()));
+            if let Value::Table(section) = entry {
+                insert(section, rest, value);
+            }
+        },
+        None => {
+            table.insert(key.to_string(), value);
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn it_offsets_ports() {
+        assert_eq!(offset_port("18141", 1000), Some(Value::Integer(19141)));
+        assert_eq!(
+            offset_port("127.0.0.1:18142", 2000),
+            Some(Value::String("127.0.0.1:20142".to_string()))
+        );
+        assert_eq!(
+            offset_port("/ip4/0.0.0.0/tcp/18189", 1000),
+            Some(Value::String("/ip4/0.0.0.0/tcp/19189".to_string()))
+        );
+        assert_eq!(
+            offset_port("/ip4/127.0.0.1/tcp/0", 1000),
+            Some(Value::String("/ip4/127.0.0.1/tcp/0".to_string()))
+        );
+        assert_eq!(offset_port("ipc://base-node.sock", 1000), None);
+        assert_eq!(offset_port("65000", 1000), None);
+    }
+
+    #[test]
+    fn it_creates_and_reopens_profiles() {
+        let dir = tempdir().unwrap();
+        let weatherwax = Profile::open_or_create(dir.path(), "weatherwax", None).unwrap();
+        assert_eq!(weatherwax.network, Network::Weatherwax);
+        assert_eq!(weatherwax.port_offset, 1000);
+        assert_eq!(weatherwax.path, dir.path().join("weatherwax"));
+
+        let main = Profile::open_or_create(dir.path(), "main", Some("mainnet")).unwrap();
+        assert_eq!(main.network, Network::MainNet);
+        assert_eq!(main.port_offset, 2000);
+
+        assert_eq!(Profile::open_or_create(dir.path(), "main", None).unwrap(), main);
+        assert!(Profile::open_or_create(dir.path(), "main", Some("igor")).is_err());
+        assert!(Profile::open_or_create(dir.path(), "other", None).is_err());
+        assert!(Profile::open_or_create(dir.path(), "../escape", Some("igor")).is_err());
+
+        assert_eq!(Profile::list(dir.path()).unwrap(), vec![main, weatherwax]);
+    }
+
+    #[test]
+    fn it_writes_the_ports_of_the_network() {
+        let dir = tempdir().unwrap();
+        let profile = Profile::open_or_create(dir.path(), "igor", None).unwrap();
+        let mut cfg = Config::new();
+        cfg.merge(config::File::from(profile.path.join(PROFILE_FILE_NAME)))
+            .unwrap();
+        assert_eq!(cfg.get_str("common.network").unwrap(), "igor");
+        assert_eq!(
+            cfg.get_str("base_node.igor.grpc_base_node_address").unwrap(),
+            "127.0.0.1:19142"
+        );
+        assert_eq!(cfg.get_int("base_node.igor.tor_onion_port").unwrap(), 19141);
+    }
+}
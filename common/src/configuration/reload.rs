@@ -0,0 +1,181 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! # Configuration reload
+//!
+//! Long running applications can re-read their configuration without restarting, but only some settings can be
+//! applied to running services. [ConfigChanges] finds the keys that differ between two configurations and sorts them
+//! into a [ReloadReport] of the keys the application applied and the keys that only take effect after a restart.
+
+use crate::{
+    configuration::doctor::{flatten_config, normalize_key},
+    ConfigError,
+};
+use config::Config;
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+};
+
+/// The keys whose values differ between two configurations
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigChanges {
+    keys: Vec<String>,
+}
+
+impl ConfigChanges {
+    /// Compares two configurations. Keys that were added, removed or given a new value are included.
+    pub fn between(old: &Config, new: &Config) -> Result<Self, ConfigError> {
+        let old = flatten_config(old.clone())?;
+        let new = flatten_config(new.clone())?;
+        let mut keys = old
+            .iter()
+            .filter(|(key, value)| new.get(*key) != Some(value))
+            .map(|(key, _)| key.clone())
+            .chain(new.keys().filter(|key| !old.contains_key(*key)).cloned())
+            .collect::<Vec<_>>();
+        keys.sort();
+        Ok(Self { keys })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Sorts the changed keys into a report. `reloadable` lists the keys, or whole sections, that the application
+    /// applies while running. A `*` segment matches any network, e.g. `base_node.*.flood_ban_max_msg_count`.
+    pub fn into_report(self, reloadable: &[&str]) -> ReloadReport {
+        let (applied, restart_required) = self.keys.into_iter().partition(|key| is_reloadable(key, reloadable));
+        ReloadReport {
+            applied,
+            restart_required,
+        }
+    }
+}
+
+/// The outcome of a configuration reload
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReloadReport {
+    /// Changed keys that were applied to the running application
+    pub applied: Vec<String>,
+    /// Changed keys that only take effect once the application is restarted
+    pub restart_required: Vec<String>,
+}
+
+impl Display for ReloadReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.applied.is_empty() && self.restart_required.is_empty() {
+            return writeln!(f, "The configuration has not changed.");
+        }
+        if !self.applied.is_empty() {
+            writeln!(f, "Applied {} changed setting(s):", self.applied.len())?;
+            for key in &self.applied {
+                writeln!(f, "  - {}", key)?;
+            }
+        }
+        if !self.restart_required.is_empty() {
+            writeln!(
+                f,
+                "{} changed setting(s) take effect after a restart:",
+                self.restart_required.len()
+            )?;
+            for key in &self.restart_required {
+                writeln!(f, "  - {}", key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_reloadable(key: &str, reloadable: &[&str]) -> bool {
+    let key = normalize_key(key);
+    reloadable
+        .iter()
+        .any(|pattern| key == *pattern || key.starts_with(&format!("{}.", pattern)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RELOADABLE: &[&str] = &["base_node.*.flood_ban_max_msg_count", "mempool.unconfirmed_pool"];
+
+    fn config(values: &[(&str, i64)]) -> Config {
+        let mut cfg = Config::new();
+        for (key, value) in values {
+            cfg.set(key, *value).unwrap();
+        }
+        cfg
+    }
+
+    #[test]
+    fn it_finds_changed_keys() {
+        let old = config(&[
+            ("common.buffer_size", 100),
+            ("mempool.unconfirmed_pool.storage_capacity", 10),
+        ]);
+        assert!(ConfigChanges::between(&old, &old).unwrap().is_empty());
+
+        let new = config(&[
+            ("common.buffer_size", 200),
+            ("mempool.unconfirmed_pool.storage_capacity", 10),
+            ("base_node.igor.flood_ban_max_msg_count", 1000),
+        ]);
+        let changes = ConfigChanges::between(&old, &new).unwrap();
+        assert_eq!(changes.keys(), &[
+            "base_node.igor.flood_ban_max_msg_count".to_string(),
+            "common.buffer_size".to_string()
+        ]);
+
+        let removed = ConfigChanges::between(&new, &old).unwrap();
+        assert_eq!(removed.keys(), changes.keys());
+    }
+
+    #[test]
+    fn it_sorts_keys_into_the_report() {
+        let old = config(&[]);
+        let new = config(&[
+            ("base_node.weatherwax.flood_ban_max_msg_count", 1000),
+            ("base_node.weatherwax.db_type", 1),
+            ("mempool.unconfirmed_pool.storage_capacity", 10),
+            ("mempool.unconfirmed_pool_size", 10),
+        ]);
+        let report = ConfigChanges::between(&old, &new).unwrap().into_report(RELOADABLE);
+        assert_eq!(report.applied, vec![
+            "base_node.weatherwax.flood_ban_max_msg_count".to_string(),
+            "mempool.unconfirmed_pool.storage_capacity".to_string()
+        ]);
+        assert_eq!(report.restart_required, vec![
+            "base_node.weatherwax.db_type".to_string(),
+            "mempool.unconfirmed_pool_size".to_string()
+        ]);
+        assert!(report.to_string().contains("take effect after a restart"));
+        assert_eq!(
+            ReloadReport::default().to_string(),
+            "The configuration has not changed.\n"
+        );
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     ConfigError,
     LOG_TARGET,
 };
-use config::Config;
+use config::{Config, Environment};
 use log::{debug, info};
 use multiaddr::{Multiaddr, Protocol};
 use std::{fs, fs::File, io::Write, path::Path};
@@ -30,9 +30,23 @@ pub fn load_configuration(bootstrap: &ConfigBootstrap) -> Result<Config, ConfigE
         .map_err(|e| ConfigError::new("Failed to parse the configuration file", Some(e.to_string())))?;
     info!(target: LOG_TARGET, "Configuration file loaded.");
 
+    if let Some(profile_file) = bootstrap.profile_file() {
+        cfg.merge(config::File::from(profile_file))
+            .map_err(|e| ConfigError::new("Failed to parse the profile", Some(e.to_string())))?;
+    }
+
+    cfg.merge(config_environment())
+        .map_err(|e| ConfigError::new("Failed to read environment variables", Some(e.to_string())))?;
+
     Ok(cfg)
 }
 
+/// Environment variables override every configuration key, e.g. `TARI_BASE_NODE__WEATHERWAX__GRPC_ENABLED=false`
+/// sets `base_node.weatherwax.grpc_enabled`
+pub fn config_environment() -> Environment {
+    Environment::with_prefix("tari").separator("__")
+}
+
 /// Installs a new configuration file template, copied from `tari_config_example.toml` to the given path.
 pub fn install_default_config_file(path: &Path) -> Result<(), std::io::Error> {
     let source = include_str!("../../config/presets/tari_config_example.toml");
@@ -43,6 +57,64 @@ pub fn install_default_config_file(path: &Path) -> Result<(), std::io::Error> {
     file.write_all(source.as_ref())
 }
 
+/// Sets `key = value` in the `[section]` table of a configuration file, leaving the rest of the file, including
+/// comments, as it is. `value` must already be TOML encoded, e.g. quoted if it is a string.
+pub fn set_config_file_value(path: &Path, section: &str, key: &str, value: &str) -> Result<(), std::io::Error> {
+    let contents = fs::read_to_string(path)?;
+    fs::write(path, set_config_value(&contents, section, key, value))
+}
+
+/// Replaces the assignment of `key` in the `[section]` table, or a commented out one if there is none. Otherwise the
+/// assignment is added at the start of the table, which is appended if it does not exist.
+fn set_config_value(contents: &str, section: &str, key: &str, value: &str) -> String {
+    let header = format!("[{}]", section);
+    let assignment = format!("{} = {}", key, value);
+    let mut lines = contents.lines().map(String::from).collect::<Vec<_>>();
+    match lines.iter().position(|line| line.trim() == header) {
+        Some(start) => {
+            let start = start + 1;
+            let end = lines[start..]
+                .iter()
+                .position(|line| line.trim_start().starts_with('['))
+                .map(|i| start + i)
+                .unwrap_or_else(|| lines.len());
+            let table = &lines[start..end];
+            let existing = table
+                .iter()
+                .position(|line| is_assignment_of(line, key))
+                .or_else(|| table.iter().position(|line| is_commented_assignment_of(line, key)));
+            match existing {
+                Some(i) => lines[start + i] = assignment,
+                None => lines.insert(start, assignment),
+            }
+        },
+        None => {
+            if lines.last().map(|line| !line.trim().is_empty()).unwrap_or(false) {
+                lines.push(String::new());
+            }
+            lines.push(header);
+            lines.push(assignment);
+        },
+    }
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+
+fn is_assignment_of(line: &str, key: &str) -> bool {
+    line.trim_start()
+        .strip_prefix(key)
+        .map(|rest| rest.trim_start().starts_with('='))
+        .unwrap_or(false)
+}
+
+fn is_commented_assignment_of(line: &str, key: &str) -> bool {
+    line.trim_start()
+        .strip_prefix('#')
+        .map(|line| is_assignment_of(line, key))
+        .unwrap_or(false)
+}
+
 //-------------------------------------      Configuration file defaults      --------------------------------------//
 
 /// Generate the global Tari configuration instance.
@@ -234,6 +306,8 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
     cfg.set_default("base_node.weatherwax.dns_seeds_use_dnssec", true)
         .unwrap();
     cfg.set_default("base_node.weatherwax.auto_ping_interval", 30).unwrap();
+    cfg.set_default("base_node.weatherwax.auto_ping_interval_max", 120)
+        .unwrap();
 
     cfg.set_default("wallet.base_node_service_peers", Vec::<String>::new())
         .unwrap();
@@ -259,6 +333,7 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
         .unwrap();
     cfg.set_default("base_node.igor.dns_seeds_use_dnssec", true).unwrap();
     cfg.set_default("base_node.igor.auto_ping_interval", 30).unwrap();
+    cfg.set_default("base_node.igor.auto_ping_interval_max", 120).unwrap();
 
     set_transport_defaults(&mut cfg).unwrap();
     set_merge_mining_defaults(&mut cfg);
@@ -443,3 +518,37 @@ fn get_local_ip() -> Option<Multiaddr> {
             })
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: &str = concat!(
+        "[base_node]\nnetwork = \"weatherwax\"\n\n",
+        "[base_node.weatherwax]\n# The sync mode\n#sync_mode = \"archival\"\npruning_horizon = 0\n\n",
+        "[mempool.weatherwax]\nsync_mode = 1\n"
+    );
+
+    #[test]
+    fn it_replaces_a_commented_out_value() {
+        let contents = set_config_value(CONFIG, "base_node.weatherwax", "sync_mode", "\"pruned\"");
+        assert_eq!(
+            contents,
+            CONFIG.replace("#sync_mode = \"archival\"", "sync_mode = \"pruned\"")
+        );
+        // A value that is already set is replaced
+        let contents = set_config_value(&contents, "base_node.weatherwax", "sync_mode", "\"archival\"");
+        assert_eq!(
+            contents,
+            CONFIG.replace("#sync_mode = \"archival\"", "sync_mode = \"archival\"")
+        );
+    }
+
+    #[test]
+    fn it_adds_missing_values_and_tables() {
+        let contents = set_config_value(CONFIG, "base_node.weatherwax", "pruning_horizon_max", "10");
+        assert!(contents.contains("[base_node.weatherwax]\npruning_horizon_max = 10\n# The sync mode"));
+        let contents = set_config_value(CONFIG, "base_node.igor", "sync_mode", "\"pruned\"");
+        assert!(contents.ends_with("sync_mode = 1\n\n[base_node.igor]\nsync_mode = \"pruned\"\n"));
+    }
+}
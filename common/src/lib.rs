@@ -36,10 +36,14 @@
 //! Setting of configuration parameters is applied using the following order of precedence:
 //!
 //! 1. Command-line argument
-//! 2. Environment variable
+//! 2. Environment variable, `TARI_<SECTION>__<KEY>` e.g. `TARI_COMMON__NETWORK=igor`
 //! 3. `config.toml` file value (see details: [configuration])
 //! 4. Configuration default
 //!
+//! Unknown keys and values of the wrong type are reported when an application starts. Run an application with
+//! `--config-doctor` to print the effective configuration and where each value came from (see
+//! [configuration::doctor]).
+//!
 //! The utilities exposed in this crate are opinionated, but flexible. In general, all data is stored in a `.tari`
 //! folder under your home folder.
 //!
@@ -79,15 +83,15 @@
 #[cfg(any(feature = "build", feature = "static-application-info"))]
 pub mod build;
 #[macro_use]
-mod logging;
+pub mod logging;
 
 pub mod configuration;
 pub use configuration::{
     bootstrap::{install_configuration, ConfigBootstrap},
     error::ConfigError,
-    global::{CommsTransport, DatabaseType, GlobalConfig, SocksAuthentication, TorControlAuthentication},
+    global::{CommsTransport, DatabaseType, GlobalConfig, SocksAuthentication, SyncMode, TorControlAuthentication},
     loader::{ConfigLoader, ConfigPath, ConfigurationError, DefaultConfigLoader, NetworkConfigPath},
-    utils::{default_config, install_default_config_file, load_configuration},
+    utils::{default_config, install_default_config_file, load_configuration, set_config_file_value},
 };
 
 pub mod dir_utils;
@@ -100,5 +104,6 @@ pub const DEFAULT_WALLET_LOG_CONFIG: &str = "config/log4rs_console_wallet.yml";
 pub const DEFAULT_MERGE_MINING_PROXY_LOG_CONFIG: &str = "config/log4rs_merge_mining_proxy.yml";
 pub const DEFAULT_STRATUM_TRANSCODER_LOG_CONFIG: &str = "config/log4rs_miningcore_transcoder.yml";
 pub const DEFAULT_MINING_NODE_LOG_CONFIG: &str = "config/log4rs_mining_node.yml";
+pub const DEFAULT_INDEXER_LOG_CONFIG: &str = "config/log4rs_indexer.yml";
 
 pub(crate) const LOG_TARGET: &str = "common::config";
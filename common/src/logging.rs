@@ -21,91 +21,319 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-// use log::LevelFilter;
-// use simplelog::*;
-use std::{fs, fs::File, io::Write, path::Path};
+//! # Logging
+//!
+//! All Tari applications log through log4rs, configured by a YAML file (see the samples in `common/logging`). On top of
+//! the configuration file this module lets applications
+//! - write every appender as JSON ([LogFormat::Json]), one object per message, for log aggregators such as Loki or
+//!   Elasticsearch,
+//! - change the level of any log target while running with [set_log_level].
+//!
+//! Levels set at runtime are kept when the configuration file is reloaded after it changed (see `refresh_rate`).
+
+use lazy_static::lazy_static;
+use log::LevelFilter;
+use log4rs::{
+    config::{Config, Deserializers, Logger, RawConfig, Root},
+    Handle,
+};
+use serde_yaml::{Mapping, Value};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs,
+    fs::File,
+    io,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// The target that sets the level of the root logger
+pub const ROOT_TARGET: &str = "root";
+
+lazy_static! {
+    static ref LOGGING: Mutex<Option<LoggingState>> = Mutex::new(None);
+}
+
+/// The format log messages are written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Each appender uses the encoder from the log configuration file
+    Text,
+    /// Every appender writes one JSON object per message
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Text
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = LoggingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(LoggingError::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Text => f.write_str("text"),
+            LogFormat::Json => f.write_str("json"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoggingError {
+    NotInitialized,
+    InvalidFormat(String),
+    InvalidConfig(String),
+    Io(io::Error),
+}
+
+impl std::error::Error for LoggingError {}
+
+impl fmt::Display for LoggingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoggingError::NotInitialized => f.write_str("Logging has not been initialized"),
+            LoggingError::InvalidFormat(format) => {
+                write!(f, "Invalid log format '{}', expected 'text' or 'json'", format)
+            },
+            LoggingError::InvalidConfig(msg) => write!(f, "Invalid logging configuration: {}", msg),
+            LoggingError::Io(err) => write!(f, "Could not read the logging configuration: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for LoggingError {
+    fn from(err: io::Error) -> Self {
+        LoggingError::Io(err)
+    }
+}
+
+struct LoggingState {
+    config_file: PathBuf,
+    base_path: PathBuf,
+    format: LogFormat,
+    /// Levels set at runtime, these take precedence over the levels in the configuration file
+    levels: BTreeMap<String, LevelFilter>,
+    log_files: Vec<PathBuf>,
+    modified: Option<SystemTime>,
+    handle: Handle,
+}
+
+/// A log4rs configuration loaded from file
+struct LoadedConfig {
+    config: Config,
+    refresh_rate: Option<Duration>,
+    /// The files written by the file appenders
+    log_files: Vec<PathBuf>,
+}
+
+impl LoggingState {
+    fn apply(&mut self) -> Result<(), LoggingError> {
+        let modified = modified_time(&self.config_file);
+        let loaded = load_config(&self.config_file, &self.base_path, self.format, &self.levels)?;
+        self.handle.set_config(loaded.config);
+        self.log_files = loaded.log_files;
+        self.modified = modified;
+        Ok(())
+    }
+}
 
 /// Set up application-level logging using the Log4rs configuration file specified in
 pub fn initialize_logging(config_file: &Path, base_path: &Path) -> bool {
-    println!(
+    initialize_logging_with_format(config_file, base_path, LogFormat::Text)
+}
+
+/// Set up application-level logging using the Log4rs configuration file, writing messages in the given format.
+/// Relative file paths in the configuration are relative to `base_path`.
+pub fn initialize_logging_with_format(config_file: &Path, base_path: &Path, format: LogFormat) -> bool {
+    eprintln!(
         "Initializing logging according to {:?}",
         config_file.to_str().unwrap_or("[??]")
     );
 
-    let current_working_dir = std::env::current_dir().unwrap_or_default();
-
-    if std::env::set_current_dir(&base_path).is_err() {
-        println!(
-            "Logging initialized in {}, could not initialize in {}.",
-            &current_working_dir.display(),
-            &base_path.display()
-        );
+    let loaded = match load_config(config_file, base_path, format, &BTreeMap::new()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("We couldn't load a logging configuration file. {}", e);
+            return false;
+        },
+    };
+    let handle = match log4rs::init_config(loaded.config) {
+        Ok(handle) => handle,
+        Err(e) => {
+            eprintln!("We couldn't initialize logging. {}", e);
+            return false;
+        },
     };
 
-    if let Err(e) = log4rs::init_file(config_file, Default::default()) {
-        println!("We couldn't load a logging configuration file. {}", e.to_string());
-        return false;
+    let mut logging = LOGGING.lock().expect("logging state lock poisoned");
+    *logging = Some(LoggingState {
+        config_file: config_file.to_path_buf(),
+        base_path: base_path.to_path_buf(),
+        format,
+        levels: BTreeMap::new(),
+        log_files: loaded.log_files,
+        modified: modified_time(config_file),
+        handle,
+    });
+    if let Some(refresh_rate) = loaded.refresh_rate {
+        thread::spawn(move || refresh_config(refresh_rate));
     }
 
-    if std::env::set_current_dir(&current_working_dir).is_err() {
-        println!(
-            "Working directory could not be changed back to {} after logging has been initialized. New working \
-             directory is {}",
-            &current_working_dir.display(),
-            &std::env::current_dir().unwrap_or_default().display()
-        );
-    };
+    true
+}
 
-    // simplelog config - perhaps for future use
-    // let config = ConfigBuilder::new()
-    //     .set_thread_level(LevelFilter::Error)
-    //     .set_time_to_local(true)
-    //     .set_time_format_str("%H:%M")
-    //     .build();
-    //
-    // let network_config = ConfigBuilder::new()
-    //     .set_thread_level(LevelFilter::Error)
-    //     .set_time_to_local(true)
-    //     .build();
-    //
-    // let log_level = env::var("RUST_LOG").unwrap_or("Info".to_string());
-    // CombinedLogger::init(vec![
-    //     TermLogger::new(
-    //         LevelFilter::Warn,
-    //         ConfigBuilder::new()
-    //             .set_thread_level(LevelFilter::Error)
-    //             .set_time_to_local(true)
-    //             .set_time_format_str("%H:%M")
-    //             .build(),
-    //         TerminalMode::Mixed,
-    //         ColorChoice::Auto,
-    //     ),
-    //     WriteLogger::new(
-    //         LevelFilter::from_str(log_level.as_str()).unwrap_or(LevelFilter::Info),
-    //         ConfigBuilder::new()
-    //             .set_thread_level(LevelFilter::Error)
-    //             .add_filter_ignore_str("comms")
-    //             .add_filter_ignore_str("p2p")
-    //             .add_filter_ignore_str("yamux")
-    //             .add_filter_ignore_str("mio")
-    //             .build(),
-    //         File::create("log/log.log").unwrap(),
-    //     ),
-    //     WriteLogger::new(
-    //         LevelFilter::from_str(log_level.as_str()).unwrap_or(LevelFilter::Info),
-    //         ConfigBuilder::new()
-    //             .set_thread_level(LevelFilter::Error)
-    //             .add_filter_allow_str("comms")
-    //             .add_filter_allow_str("p2p")
-    //             .add_filter_allow_str("yamux")
-    //             .add_filter_allow_str("mio")
-    //             .build(),
-    //         File::create("log/network.log").unwrap(),
-    //     ),
-    // ])
-    // .unwrap();
+/// Sets the level of a log target, and every target below it, e.g. `comms` also sets `comms::dht`, unless a more
+/// specific level was configured. Use [ROOT_TARGET] for the root logger.
+pub fn set_log_level(target: &str, level: LevelFilter) -> Result<(), LoggingError> {
+    let mut logging = LOGGING.lock().expect("logging state lock poisoned");
+    let state = logging.as_mut().ok_or(LoggingError::NotInitialized)?;
+    let previous = state.levels.insert(target.to_string(), level);
+    let result = state.apply();
+    if result.is_err() {
+        match previous {
+            Some(level) => state.levels.insert(target.to_string(), level),
+            None => state.levels.remove(target),
+        };
+    }
+    result
+}
 
-    true
+/// The levels set with [set_log_level]
+pub fn log_levels() -> Vec<(String, LevelFilter)> {
+    LOGGING
+        .lock()
+        .expect("logging state lock poisoned")
+        .as_ref()
+        .map(|state| state.levels.iter().map(|(t, l)| (t.clone(), *l)).collect())
+        .unwrap_or_default()
+}
+
+/// The files the configured appenders write to, e.g. to collect recent log messages for diagnostics
+pub fn log_files() -> Vec<PathBuf> {
+    LOGGING
+        .lock()
+        .expect("logging state lock poisoned")
+        .as_ref()
+        .map(|state| state.log_files.clone())
+        .unwrap_or_default()
+}
+
+/// Reloads the configuration file whenever it changes, like log4rs does for files with a `refresh_rate`
+fn refresh_config(refresh_rate: Duration) {
+    loop {
+        thread::sleep(refresh_rate);
+        let mut logging = LOGGING.lock().expect("logging state lock poisoned");
+        if let Some(state) = logging.as_mut() {
+            if modified_time(&state.config_file) != state.modified {
+                if let Err(e) = state.apply() {
+                    eprintln!("Could not reload the logging configuration. {}", e);
+                    // Only retry once the file changes again
+                    state.modified = modified_time(&state.config_file);
+                }
+            }
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Loads the log4rs configuration file, resolving relative file paths against `base_path` and applying the format and
+/// levels.
+fn load_config(
+    config_file: &Path,
+    base_path: &Path,
+    format: LogFormat,
+    levels: &BTreeMap<String, LevelFilter>,
+) -> Result<LoadedConfig, LoggingError> {
+    let contents = fs::read_to_string(config_file)?;
+    let mut value = serde_yaml::from_str::<Value>(&contents).map_err(|e| LoggingError::InvalidConfig(e.to_string()))?;
+    let mut log_files = Vec::new();
+    if let Some(appenders) = value.get_mut("appenders").and_then(Value::as_mapping_mut) {
+        for (_, appender) in appenders.iter_mut() {
+            resolve_path(appender.get_mut("path"), base_path);
+            if let Some(path) = appender.get("path").and_then(Value::as_str) {
+                log_files.push(PathBuf::from(path));
+            }
+            resolve_path(
+                appender
+                    .get_mut("policy")
+                    .and_then(|policy| policy.get_mut("roller"))
+                    .and_then(|roller| roller.get_mut("pattern")),
+                base_path,
+            );
+            if let (LogFormat::Json, Some(appender)) = (format, appender.as_mapping_mut()) {
+                let mut encoder = Mapping::new();
+                encoder.insert("kind".into(), "json".into());
+                appender.insert("encoder".into(), Value::Mapping(encoder));
+            }
+        }
+    }
+    let raw = serde_yaml::from_value::<RawConfig>(value).map_err(|e| LoggingError::InvalidConfig(e.to_string()))?;
+
+    let (appenders, errors) = raw.appenders_lossy(&Deserializers::default());
+    if !errors.is_empty() {
+        return Err(LoggingError::InvalidConfig(format!("{:?}", errors)));
+    }
+    let mut loggers = raw
+        .loggers()
+        .into_iter()
+        .map(|logger| (logger.name().to_string(), logger))
+        .collect::<BTreeMap<_, _>>();
+    let mut root = raw.root();
+    for (target, level) in levels {
+        if target == ROOT_TARGET {
+            root = Root::builder()
+                .appenders(root.appenders().iter().cloned())
+                .build(*level);
+            continue;
+        }
+        let logger = match loggers.remove(target) {
+            Some(logger) => Logger::builder()
+                .appenders(logger.appenders().iter().cloned())
+                .additive(logger.additive())
+                .build(target, *level),
+            None => Logger::builder().build(target, *level),
+        };
+        loggers.insert(target.clone(), logger);
+    }
+
+    let config = Config::builder()
+        .appenders(appenders)
+        .loggers(loggers.into_iter().map(|(_, logger)| logger))
+        .build(root)
+        .map_err(|e| LoggingError::InvalidConfig(e.to_string()))?;
+    Ok(LoadedConfig {
+        config,
+        refresh_rate: raw.refresh_rate(),
+        log_files,
+    })
+}
+
+fn resolve_path(path: Option<&mut Value>, base_path: &Path) {
+    if let Some(Value::String(path)) = path {
+        if Path::new(path.as_str()).is_relative() {
+            *path = base_path.join(path.as_str()).to_string_lossy().into_owned();
+        }
+    }
 }
 
 /// Installs a new default logfile configuration, copied from `log4rs_sample_base_node.yml` to the given path.
@@ -163,6 +391,17 @@ pub fn install_default_mining_node_logfile_config(path: &Path) -> Result<(), std
     file.write_all(source.as_ref())
 }
 
+/// Installs a new default logfile configuration, copied from `log4rs_sample_indexer.yml` to the given path.
+pub fn install_default_indexer_logfile_config(path: &Path) -> Result<(), std::io::Error> {
+    let source = include_str!("../logging/log4rs_sample_indexer.yml");
+    if let Some(d) = path.parent() {
+        fs::create_dir_all(d)?
+    };
+    // Note: `fs::write(path, source)` did not work as expected, as the file name was not changed
+    let mut file = File::create(path)?;
+    file.write_all(source.as_ref())
+}
+
 /// Log an error if an `Err` is returned from the `$expr`. If the given expression is `Ok(v)`,
 /// `Some(v)` is returned, otherwise `None` is returned (same as `Result::ok`).
 /// Useful in cases where the error should be logged and ignored.
@@ -218,6 +457,70 @@ macro_rules! log_if_error_fmt {
 
 #[cfg(test)]
 mod test {
+    use super::*;
+
+    #[test]
+    fn it_applies_the_format_and_levels() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = dir.path().join("log4rs.yml");
+        fs::write(
+            &config_file,
+            r#"
+appenders:
+  network:
+    kind: rolling_file
+    path: "log/network.log"
+    policy:
+      kind: compound
+      trigger:
+        kind: size
+        limit: 10mb
+      roller:
+        kind: fixed_window
+        base: 1
+        count: 5
+        pattern: "log/network.{}.log"
+root:
+  level: warn
+  appenders:
+    - network
+loggers:
+  comms:
+    level: info
+    appenders:
+      - network
+    additive: false
+"#,
+        )
+        .unwrap();
+        let mut levels = BTreeMap::new();
+        levels.insert("comms".to_string(), LevelFilter::Debug);
+        levels.insert("c::mp".to_string(), LevelFilter::Trace);
+        levels.insert(ROOT_TARGET.to_string(), LevelFilter::Error);
+
+        let LoadedConfig {
+            config,
+            refresh_rate,
+            log_files,
+        } = load_config(&config_file, dir.path(), LogFormat::Json, &levels).unwrap();
+        assert!(refresh_rate.is_none());
+        assert_eq!(config.root().level(), LevelFilter::Error);
+        assert_eq!(config.root().appenders(), &["network".to_string()]);
+        let comms = config.loggers().iter().find(|l| l.name() == "comms").unwrap();
+        assert_eq!(comms.level(), LevelFilter::Debug);
+        assert_eq!(comms.appenders(), &["network".to_string()]);
+        assert!(!comms.additive());
+        let mempool = config.loggers().iter().find(|l| l.name() == "c::mp").unwrap();
+        assert_eq!(mempool.level(), LevelFilter::Trace);
+        assert!(mempool.additive());
+        // Relative paths are resolved against the base path
+        assert_eq!(log_files, vec![dir.path().join("log/network.log")]);
+        assert!(dir.path().join("log/network.log").exists());
+
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
     #[test]
     fn log_if_error() {
         let err = Result::<(), _>::Err("What a shame");
@@ -43,6 +43,7 @@ use std::{cmp, fmt, fmt::Display, sync::Arc};
 use tari_comms::{
     connectivity::{ConnectivityError, ConnectivityRequester, ConnectivitySelection},
     peer_manager::{NodeId, NodeIdentity, PeerFeatures, PeerManager, PeerManagerError, PeerQuery, PeerQuerySortBy},
+    runtime,
     types::CommsPublicKey,
 };
 use tari_crypto::tari_utilities::hex::Hex;
@@ -51,7 +52,6 @@ use tari_utilities::message_format::{MessageFormat, MessageFormatError};
 use thiserror::Error;
 use tokio::{
     sync::{mpsc, oneshot},
-    task,
     time,
     time::MissedTickBehavior,
 };
@@ -243,7 +243,7 @@ impl DhtActor {
     }
 
     pub fn spawn(self) {
-        task::spawn(async move {
+        runtime::spawn_named("dht::actor", async move {
             if let Err(err) = self.run().await {
                 error!(target: LOG_TARGET, "DhtActor failed to start with error: {:?}", err);
             }
@@ -25,11 +25,8 @@ use std::{
     collections::{hash_map::Entry, HashMap, VecDeque},
     time::{Duration, Instant},
 };
-use tari_comms::peer_manager::NodeId;
-use tokio::{
-    sync::{mpsc, oneshot},
-    task,
-};
+use tari_comms::{peer_manager::NodeId, runtime};
+use tokio::sync::{mpsc, oneshot};
 
 const LOG_TARGET: &str = "comms::dht::metrics";
 
@@ -132,7 +129,7 @@ impl MetricsCollector {
             stream: metrics_rx,
             state: Default::default(),
         };
-        task::spawn(collector.run());
+        runtime::spawn_named("dht::metrics_collector", collector.run());
         metrics_collector
     }
 
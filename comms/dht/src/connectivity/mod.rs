@@ -32,13 +32,19 @@ use std::{sync::Arc, time::Instant};
 use tari_comms::{
     connectivity::{ConnectivityError, ConnectivityEvent, ConnectivityEventRx, ConnectivityRequester},
     peer_manager::{node_id::NodeDistance, NodeId, PeerManagerError, PeerQuery, PeerQuerySortBy},
+    runtime,
     NodeIdentity,
     PeerConnection,
     PeerManager,
 };
 use tari_shutdown::ShutdownSignal;
 use thiserror::Error;
-use tokio::{sync::broadcast, task, task::JoinHandle, time, time::MissedTickBehavior};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+    time,
+    time::MissedTickBehavior,
+};
 
 const LOG_TARGET: &str = "comms::dht::connectivity";
 
@@ -80,6 +86,7 @@ pub struct DhtConnectivity {
     stats: Stats,
     dht_events: broadcast::Receiver<Arc<DhtEvent>>,
     metrics_collector: MetricsCollectorHandle,
+    flood_ban_max_msg_count: watch::Receiver<usize>,
     shutdown_signal: ShutdownSignal,
 }
 
@@ -93,6 +100,7 @@ impl DhtConnectivity {
         dht_requester: DhtRequester,
         dht_events: broadcast::Receiver<Arc<DhtEvent>>,
         metrics_collector: MetricsCollectorHandle,
+        flood_ban_max_msg_count: watch::Receiver<usize>,
         shutdown_signal: ShutdownSignal,
     ) -> Self {
         Self {
@@ -105,6 +113,7 @@ impl DhtConnectivity {
             connectivity,
             dht_requester,
             metrics_collector,
+            flood_ban_max_msg_count,
             random_pool_last_refresh: None,
             stats: Stats::new(),
             dht_events,
@@ -117,7 +126,7 @@ impl DhtConnectivity {
     pub fn spawn(mut self) -> JoinHandle<Result<(), DhtConnectivityError>> {
         // Listen to events as early as possible
         let connectivity_events = self.connectivity.get_event_subscription();
-        task::spawn(async move {
+        runtime::spawn_named("dht::connectivity", async move {
             debug!(target: LOG_TARGET, "Waiting for connectivity manager to start");
             let _ = self.connectivity.wait_started().await;
             match self.run(connectivity_events).await {
@@ -235,9 +244,10 @@ impl DhtConnectivity {
     }
 
     async fn check_and_ban_flooding_peers(&mut self) -> Result<(), DhtConnectivityError> {
+        let max_msg_count = *self.flood_ban_max_msg_count.borrow();
         let nodes = self
             .metrics_collector
-            .get_message_rates_exceeding(self.config.flood_ban_max_msg_count, self.config.flood_ban_timespan)
+            .get_message_rates_exceeding(max_msg_count, self.config.flood_ban_timespan)
             .await?;
 
         for (peer, mps) in nodes {
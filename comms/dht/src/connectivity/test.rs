@@ -41,7 +41,7 @@ use tari_comms::{
 };
 use tari_shutdown::Shutdown;
 use tari_test_utils::async_assert;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
 
 async fn setup(
     config: DhtConfig,
@@ -68,6 +68,7 @@ async fn setup(
     let dht_state = mock.get_shared_state();
     mock.spawn();
     let (event_publisher, _) = broadcast::channel(1);
+    let (_, flood_ban_max_msg_count) = watch::channel(config.flood_ban_max_msg_count);
 
     let dht_connectivity = DhtConnectivity::new(
         config,
@@ -77,6 +78,7 @@ async fn setup(
         dht_requester,
         event_publisher.subscribe(),
         MetricsCollector::spawn(),
+        flood_ban_max_msg_count,
         shutdown.to_signal(),
     );
 
@@ -54,7 +54,7 @@ use tari_comms::{
 };
 use tari_shutdown::ShutdownSignal;
 use thiserror::Error;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, watch};
 use tower::{layer::Layer, Service, ServiceBuilder};
 
 const LOG_TARGET: &str = "comms::dht";
@@ -102,6 +102,9 @@ pub struct Dht {
     event_publisher: DhtEventSender,
     /// Used by MetricsLayer to collect metrics and to inform heuristics for peer banning
     metrics_collector: MetricsCollectorHandle,
+    /// Updates the message rate above which DhtConnectivity bans a peer for flooding
+    flood_ban_max_msg_count: Arc<watch::Sender<usize>>,
+    flood_ban_max_msg_count_rx: watch::Receiver<usize>,
 }
 
 impl Dht {
@@ -120,6 +123,7 @@ impl Dht {
         let (event_publisher, _) = broadcast::channel(DHT_EVENT_BROADCAST_CHANNEL_SIZE);
 
         let metrics_collector = MetricsCollector::spawn();
+        let (flood_ban_max_msg_count, flood_ban_max_msg_count_rx) = watch::channel(config.flood_ban_max_msg_count);
 
         let dht = Self {
             node_identity,
@@ -133,6 +137,8 @@ impl Dht {
             connectivity,
             discovery_sender,
             event_publisher: event_publisher.clone(),
+            flood_ban_max_msg_count: Arc::new(flood_ban_max_msg_count),
+            flood_ban_max_msg_count_rx,
         };
 
         let conn = DbConnection::connect_and_migrate(dht.config.database_url.clone())
@@ -160,6 +166,13 @@ impl Dht {
         DhtBuilder::new()
     }
 
+    /// Change the maximum message rate a peer may sustain before it is banned for flooding. The new limit is used from
+    /// the next flood check onwards.
+    pub fn set_flood_ban_max_msg_count(&self, max_msg_count: usize) {
+        // The receiver held by this instance keeps the channel open
+        let _ = self.flood_ban_max_msg_count.send(max_msg_count);
+    }
+
     /// Create a DHT RPC service
     pub fn rpc_service(&self) -> rpc::DhtService<rpc::DhtRpcServiceImpl> {
         rpc::DhtService::new(rpc::DhtRpcServiceImpl::new(self.peer_manager.clone()))
@@ -209,6 +222,7 @@ impl Dht {
             self.dht_requester(),
             self.event_publisher.subscribe(),
             self.metrics_collector.clone(),
+            self.flood_ban_max_msg_count_rx.clone(),
             shutdown_signal,
         )
     }
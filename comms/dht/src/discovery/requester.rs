@@ -31,12 +31,23 @@ use tokio::{
     time,
 };
 
+/// The time taken by the stages of a successful peer discovery
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiscoveryTimings {
+    /// The time taken to send the discovery message to the neighbouring peers that propagate it
+    pub sent: Duration,
+    /// The time from sending the discovery message until the response was received. This includes the propagation
+    /// of the message and the response through the network, and the time store and forward nodes held the message
+    /// until the destination came online.
+    pub response: Duration,
+}
+
 #[derive(Debug)]
 pub enum DhtDiscoveryRequest {
     DiscoverPeer(
         Box<CommsPublicKey>,
         NodeDestination,
-        oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
+        oneshot::Sender<Result<(Peer, DiscoveryTimings), DhtDiscoveryError>>,
     ),
     NotifyDiscoveryResponseReceived(Box<DiscoveryResponseMessage>),
 }
@@ -86,6 +97,17 @@ impl DhtDiscoveryRequester {
         dest_public_key: Box<CommsPublicKey>,
         destination: NodeDestination,
     ) -> Result<Peer, DhtDiscoveryError> {
+        let (peer, _) = self.discover_peer_with_timings(dest_public_key, destination).await?;
+        Ok(peer)
+    }
+
+    /// Initiate a peer discovery, see [discover_peer](Self::discover_peer), and return the time taken by each stage of
+    /// the discovery with the peer
+    pub async fn discover_peer_with_timings(
+        &mut self,
+        dest_public_key: Box<CommsPublicKey>,
+        destination: NodeDestination,
+    ) -> Result<(Peer, DiscoveryTimings), DhtDiscoveryError> {
         let (reply_tx, reply_rx) = oneshot::channel();
 
         self.sender
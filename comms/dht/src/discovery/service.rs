@@ -21,7 +21,10 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    discovery::{requester::DhtDiscoveryRequest, DhtDiscoveryError},
+    discovery::{
+        requester::{DhtDiscoveryRequest, DiscoveryTimings},
+        DhtDiscoveryError,
+    },
     envelope::{DhtMessageType, NodeDestination},
     outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
     proto::dht::{DiscoveryMessage, DiscoveryResponseMessage},
@@ -29,34 +32,48 @@ use crate::{
 };
 use log::*;
 use rand::{rngs::OsRng, RngCore};
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tari_comms::{
     log_if_error,
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManager},
+    runtime,
     types::CommsPublicKey,
     validate_peer_addresses,
 };
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::{hex::Hex, ByteArray};
-use tokio::{
-    sync::{mpsc, oneshot},
-    task,
-};
+use tokio::sync::{mpsc, oneshot};
 
 const LOG_TARGET: &str = "comms::dht::discovery_service";
 
+type DiscoveryReply = oneshot::Sender<Result<(Peer, DiscoveryTimings), DhtDiscoveryError>>;
+
 struct DiscoveryRequestState {
-    reply_tx: oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
+    reply_tx: DiscoveryReply,
     public_key: Box<CommsPublicKey>,
     start_ts: Instant,
+    /// The time taken to send the discovery message
+    sent: Duration,
 }
 
 impl DiscoveryRequestState {
-    pub fn new(public_key: Box<CommsPublicKey>, reply_tx: oneshot::Sender<Result<Peer, DhtDiscoveryError>>) -> Self {
+    pub fn new(public_key: Box<CommsPublicKey>, reply_tx: DiscoveryReply, start_ts: Instant) -> Self {
         Self {
             public_key,
             reply_tx,
-            start_ts: Instant::now(),
+            start_ts,
+            sent: start_ts.elapsed(),
+        }
+    }
+
+    fn timings(&self) -> DiscoveryTimings {
+        DiscoveryTimings {
+            sent: self.sent,
+            response: self.start_ts.elapsed().saturating_sub(self.sent),
         }
     }
 }
@@ -92,7 +109,7 @@ impl DhtDiscoveryService {
     }
 
     pub fn spawn(self) {
-        task::spawn(async move {
+        runtime::spawn_named("dht::discovery", async move {
             info!(target: LOG_TARGET, "Discovery service started");
             self.run().await
         });
@@ -164,10 +181,12 @@ impl DhtDiscoveryService {
 
         match self.inflight_discoveries.remove(&discovery_msg.nonce) {
             Some(request) => {
+                let timings = request.timings();
                 let DiscoveryRequestState {
                     public_key,
                     reply_tx,
                     start_ts,
+                    ..
                 } = request;
 
                 let result = self.validate_then_add_peer(&public_key, discovery_msg).await;
@@ -184,7 +203,8 @@ impl DhtDiscoveryService {
 
                         for request in self.collect_all_discovery_requests(&public_key) {
                             if !reply_tx.is_closed() {
-                                let _ = request.reply_tx.send(Ok(peer.clone()));
+                                let timings = request.timings();
+                                let _ = request.reply_tx.send(Ok((peer.clone(), timings)));
                             }
                         }
 
@@ -205,7 +225,7 @@ impl DhtDiscoveryService {
                     },
                 }
 
-                let _ = reply_tx.send(result);
+                let _ = reply_tx.send(result.map(|peer| (peer, timings)));
             },
             None => {
                 debug!(
@@ -269,9 +289,10 @@ impl DhtDiscoveryService {
         &mut self,
         dest_pubkey: Box<CommsPublicKey>,
         destination: NodeDestination,
-        reply_tx: oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
+        reply_tx: DiscoveryReply,
     ) -> Result<(), DhtDiscoveryError> {
         let nonce = OsRng.next_u64();
+        let start_ts = Instant::now();
         if let Err(err) = self.send_discover(nonce, destination, dest_pubkey.clone()).await {
             let _ = reply_tx.send(Err(err));
             return Ok(());
@@ -294,7 +315,7 @@ impl DhtDiscoveryService {
 
         // Add the new inflight request.
         self.inflight_discoveries
-            .insert(nonce, DiscoveryRequestState::new(dest_pubkey, reply_tx));
+            .insert(nonce, DiscoveryRequestState::new(dest_pubkey, reply_tx, start_ts));
 
         trace!(
             target: LOG_TARGET,
@@ -350,8 +371,8 @@ mod test {
         test_utils::{build_peer_manager, make_node_identity},
     };
     use std::time::Duration;
-    use tari_comms::runtime;
     use tari_shutdown::Shutdown;
+    use tokio::task;
 
     #[runtime::test]
     async fn send_discovery() {
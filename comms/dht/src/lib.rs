@@ -142,7 +142,7 @@ mod dht;
 pub use dht::{Dht, DhtInitializationError};
 
 mod discovery;
-pub use discovery::DhtDiscoveryRequester;
+pub use discovery::{DhtDiscoveryRequester, DiscoveryTimings};
 
 mod network_discovery;
 pub use network_discovery::NetworkDiscoveryConfig;
@@ -43,7 +43,7 @@ use std::{
         Arc,
     },
 };
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, NodeIdentity, PeerManager};
+use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, runtime, NodeIdentity, PeerManager};
 use tari_shutdown::ShutdownSignal;
 use tokio::{
     sync::{broadcast, RwLock},
@@ -253,7 +253,7 @@ impl DhtNetworkDiscovery {
     }
 
     pub fn spawn(self) -> task::JoinHandle<()> {
-        task::spawn(self.run())
+        runtime::spawn_named("dht::network_discovery", self.run())
     }
 
     pub async fn run(mut self) {
@@ -41,13 +41,13 @@ use std::{convert::TryFrom, sync::Arc, time::Duration};
 use tari_comms::{
     connectivity::{ConnectivityEvent, ConnectivityEventRx, ConnectivityRequester},
     peer_manager::{NodeId, PeerFeatures},
+    runtime,
     types::CommsPublicKey,
     PeerManager,
 };
 use tari_shutdown::ShutdownSignal;
 use tokio::{
     sync::{mpsc, oneshot},
-    task,
     time,
     time::MissedTickBehavior,
 };
@@ -208,7 +208,7 @@ impl StoreAndForwardService {
 
     pub fn spawn(self) {
         info!(target: LOG_TARGET, "Store and forward service started");
-        task::spawn(Self::run(self));
+        runtime::spawn_named("dht::store_forward", Self::run(self));
     }
 
     async fn run(mut self) {
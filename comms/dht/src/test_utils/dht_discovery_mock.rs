@@ -101,7 +101,7 @@ impl DhtDiscoveryMock {
         match req {
             DiscoverPeer(_, _, reply_tx) => {
                 let lock = self.state.discover_peer.read().unwrap();
-                reply_tx.send(Ok(lock.clone())).unwrap();
+                reply_tx.send(Ok((lock.clone(), Default::default()))).unwrap();
             },
             NotifyDiscoveryResponseReceived(_) => {},
         }
@@ -215,6 +215,10 @@ impl RpcCodeGenerator {
                 self.inner.ping().await
             }
 
+            pub fn compression_stats(&self) -> &#dep_mod::CompressionStats {
+                self.inner.compression_stats()
+            }
+
             pub async fn close(&mut self) {
                 self.inner.close().await;
             }
@@ -23,6 +23,7 @@
 use super::{CommsBuilderError, CommsShutdown};
 use crate::{
     connection_manager::{
+        AccessList,
         ConnectionManager,
         ConnectionManagerEvent,
         ConnectionManagerRequest,
@@ -172,6 +173,7 @@ impl UnspawnedCommsNode {
         //---------------------------------- Connection Manager --------------------------------------------//
 
         let noise_config = NoiseConfig::new(node_identity.clone());
+        let access_list = connection_manager_config.access_list.clone();
 
         let mut connection_manager = ConnectionManager::new(
             connection_manager_config,
@@ -227,6 +229,7 @@ impl UnspawnedCommsNode {
             node_identity,
             peer_manager,
             hidden_service,
+            access_list,
             complete_signals: ext_context.drain_complete_signals(),
         })
     }
@@ -272,6 +275,8 @@ pub struct CommsNode {
     listening_info: ListenerInfo,
     /// `Some` if the comms node is configured to run via a hidden service, otherwise `None`
     hidden_service: Option<tor::HiddenService>,
+    /// The connection access list shared with the connection manager
+    access_list: AccessList,
     /// The 'reciprocal' shutdown signals for each comms service
     complete_signals: Vec<ShutdownSignal>,
 }
@@ -317,6 +322,11 @@ impl CommsNode {
         self.connectivity_requester.clone()
     }
 
+    /// Return the connection access list. Changes to the list apply to new inbound and outbound connections.
+    pub fn access_list(&self) -> &AccessList {
+        &self.access_list
+    }
+
     /// Returns a new `ShutdownSignal`
     pub fn shutdown_signal(&self) -> ShutdownSignal {
         self.shutdown_signal.clone()
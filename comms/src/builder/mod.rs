@@ -43,7 +43,7 @@ mod tests;
 
 use crate::{
     backoff::{Backoff, BoxedBackoff, ExponentialBackoff},
-    connection_manager::{ConnectionManagerConfig, ConnectionManagerRequester},
+    connection_manager::{AccessList, ConnectionManagerConfig, ConnectionManagerRequester},
     connectivity::{ConnectivityConfig, ConnectivityRequester},
     multiaddr::Multiaddr,
     peer_manager::{NodeIdentity, PeerManager},
@@ -159,6 +159,13 @@ impl CommsBuilder {
         self
     }
 
+    /// Set the access list of addresses and peers that are allowed or blocked from connecting to this node, or being
+    /// dialed by it.
+    pub fn with_access_list(mut self, access_list: AccessList) -> Self {
+        self.connection_manager_config.access_list = access_list;
+        self
+    }
+
     /// The maximum number of connection tasks that will be spawned at the same time. Once this limit is reached, peers
     /// attempting to connect will have to wait for another connection attempt to complete.
     pub fn with_max_simultaneous_inbound_connects(mut self, max_simultaneous_inbound_connects: usize) -> Self {
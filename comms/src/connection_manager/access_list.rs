@@ -0,0 +1,242 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{
+    multiaddr::{Multiaddr, Protocol},
+    types::CommsPublicKey,
+};
+use std::{
+    fmt,
+    net::IpAddr,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+use tari_crypto::tari_utilities::hex::Hex;
+
+/// A rule of an [AccessList](self::AccessList): an IP address range, an onion address or a peer's public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessRule {
+    Cidr(cidr::AnyIpCidr),
+    /// The host part of an onion v3 address, without the `.onion` suffix
+    Onion(String),
+    PublicKey(CommsPublicKey),
+}
+
+impl AccessRule {
+    fn matches_address(&self, address: &Multiaddr) -> bool {
+        address.iter().any(|protocol| match (self, protocol) {
+            (AccessRule::Cidr(cidr), Protocol::Ip4(ip)) => cidr.contains(&IpAddr::V4(ip)),
+            (AccessRule::Cidr(cidr), Protocol::Ip6(ip)) => cidr.contains(&IpAddr::V6(ip)),
+            (AccessRule::Onion(host), protocol @ Protocol::Onion3(_)) => {
+                onion_host(&protocol.to_string()) == host.as_str()
+            },
+            _ => false,
+        })
+    }
+
+    fn matches_public_key(&self, public_key: &CommsPublicKey) -> bool {
+        matches!(self, AccessRule::PublicKey(pk) if pk == public_key)
+    }
+}
+
+/// Returns the host of an onion address given as `<host>.onion`, `<host>` or `/onion3/<host>:<port>`
+fn onion_host(s: &str) -> &str {
+    let s = s.trim_start_matches("/onion3/");
+    let s = s.split(':').next().unwrap_or(s);
+    s.trim_end_matches(".onion")
+}
+
+impl FromStr for AccessRule {
+    type Err = String;
+
+    /// Parses an IP address or CIDR range (`10.0.0.0/8`), an onion address (`<host>.onion` or
+    /// `/onion3/<host>:<port>`) or a hex public key
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(ip) = IpAddr::from_str(s) {
+            let prefix = if ip.is_ipv4() { 32 } else { 128 };
+            return Ok(AccessRule::Cidr(
+                cidr::AnyIpCidr::from_str(&format!("{}/{}", ip, prefix)).map_err(|e| e.to_string())?,
+            ));
+        }
+        if let Ok(cidr) = cidr::AnyIpCidr::from_str(s) {
+            return Ok(AccessRule::Cidr(cidr));
+        }
+        if s.starts_with("/onion3/") || s.ends_with(".onion") {
+            let host = onion_host(s);
+            if host.len() == 56 && host.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Ok(AccessRule::Onion(host.to_lowercase()));
+            }
+            return Err(format!("Invalid onion address '{}'", s));
+        }
+        CommsPublicKey::from_hex(s)
+            .map(AccessRule::PublicKey)
+            .map_err(|_| format!("'{}' is not an IP address, CIDR range, onion address or public key", s))
+    }
+}
+
+impl fmt::Display for AccessRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessRule::Cidr(cidr) => write!(f, "{}", cidr),
+            AccessRule::Onion(host) => write!(f, "{}.onion", host),
+            AccessRule::PublicKey(pk) => write!(f, "{}", pk),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct AccessListInner {
+    allowed: Vec<AccessRule>,
+    blocked: Vec<AccessRule>,
+}
+
+impl AccessListInner {
+    fn permits<F: Fn(&AccessRule) -> bool>(&self, matches: F) -> bool {
+        !self.blocked.iter().any(&matches) && (self.allowed.is_empty() || self.allowed.iter().any(matches))
+    }
+}
+
+/// Firewall-style allow and block lists for peer connections, enforced by the listener and the dialer. A peer or
+/// address is permitted if it matches no blocked rule and, if there are allowed rules, matches one of them. The lists
+/// are shared by all clones, so changes take effect for new connections immediately.
+#[derive(Debug, Clone, Default)]
+pub struct AccessList {
+    inner: Arc<RwLock<AccessListInner>>,
+}
+
+impl AccessList {
+    pub fn new(allowed: Vec<AccessRule>, blocked: Vec<AccessRule>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(AccessListInner { allowed, blocked })),
+        }
+    }
+
+    /// Whether connections to and from the address are permitted
+    pub fn permits_address(&self, address: &Multiaddr) -> bool {
+        acquire_read_lock!(self.inner).permits(|rule| rule.matches_address(address))
+    }
+
+    /// Whether connections to and from the peer with this public key are permitted
+    pub fn permits_public_key(&self, public_key: &CommsPublicKey) -> bool {
+        acquire_read_lock!(self.inner).permits(|rule| rule.matches_public_key(public_key))
+    }
+
+    /// Adds the rule to the allowed rules, and removes it from the blocked rules
+    pub fn allow(&self, rule: AccessRule) {
+        let mut inner = acquire_write_lock!(self.inner);
+        inner.blocked.retain(|r| *r != rule);
+        if !inner.allowed.contains(&rule) {
+            inner.allowed.push(rule);
+        }
+    }
+
+    /// Adds the rule to the blocked rules, and removes it from the allowed rules
+    pub fn block(&self, rule: AccessRule) {
+        let mut inner = acquire_write_lock!(self.inner);
+        inner.allowed.retain(|r| *r != rule);
+        if !inner.blocked.contains(&rule) {
+            inner.blocked.push(rule);
+        }
+    }
+
+    /// Removes the rule from both lists. Returns true if it was in either list.
+    pub fn remove(&self, rule: &AccessRule) -> bool {
+        let mut inner = acquire_write_lock!(self.inner);
+        let len = inner.allowed.len() + inner.blocked.len();
+        inner.allowed.retain(|r| r != rule);
+        inner.blocked.retain(|r| r != rule);
+        inner.allowed.len() + inner.blocked.len() != len
+    }
+
+    pub fn allowed(&self) -> Vec<AccessRule> {
+        acquire_read_lock!(self.inner).allowed.clone()
+    }
+
+    pub fn blocked(&self) -> Vec<AccessRule> {
+        acquire_read_lock!(self.inner).blocked.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::peer_manager::NodeIdentity;
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey;
+
+    const ONION: &str = "/onion3/exe2zgehnw3tvrbef3ep6taiacr6sdyeb54be2s25fpru357r4skhtad:18141";
+
+    fn rule(s: &str) -> AccessRule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn it_parses_rules() {
+        assert_eq!(rule("10.0.0.1").to_string(), "10.0.0.1/32");
+        assert_eq!(rule("10.0.0.0/8").to_string(), "10.0.0.0/8");
+        assert_eq!(
+            rule(ONION),
+            AccessRule::Onion("exe2zgehnw3tvrbef3ep6taiacr6sdyeb54be2s25fpru357r4skhtad".to_string())
+        );
+        assert_eq!(
+            rule("exe2zgehnw3tvrbef3ep6taiacr6sdyeb54be2s25fpru357r4skhtad.onion"),
+            rule(ONION)
+        );
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        assert_eq!(rule(&public_key.to_hex()), AccessRule::PublicKey(public_key));
+        assert!("not-a-rule".parse::<AccessRule>().is_err());
+        assert!("short.onion".parse::<AccessRule>().is_err());
+    }
+
+    #[test]
+    fn it_permits_addresses() {
+        let list = AccessList::default();
+        let addr = "/ip4/10.1.2.3/tcp/18189".parse().unwrap();
+        let onion = ONION.parse().unwrap();
+        assert!(list.permits_address(&addr));
+
+        list.block(rule("10.0.0.0/8"));
+        assert!(!list.permits_address(&addr));
+        assert!(list.permits_address(&onion));
+
+        list.allow(rule("10.0.0.0/8"));
+        assert!(list.permits_address(&addr));
+        // Only allowed addresses are permitted once there is an allowed rule
+        assert!(!list.permits_address(&onion));
+
+        assert!(list.remove(&rule("10.0.0.0/8")));
+        list.block(rule(ONION));
+        assert!(list.permits_address(&addr));
+        assert!(!list.permits_address(&onion));
+    }
+
+    #[test]
+    fn it_permits_public_keys() {
+        let node_identity = NodeIdentity::random(&mut OsRng, Multiaddr::empty(), Default::default());
+        let list = AccessList::new(vec![], vec![AccessRule::PublicKey(node_identity.public_key().clone())]);
+        assert!(!list.permits_public_key(node_identity.public_key()));
+        let (_, other) = CommsPublicKey::random_keypair(&mut OsRng);
+        assert!(list.permits_public_key(&other));
+    }
+}
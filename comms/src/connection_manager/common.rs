@@ -25,7 +25,7 @@ use crate::{
     connection_manager::error::ConnectionManagerError,
     multiaddr::{Multiaddr, Protocol},
     multiplexing::Yamux,
-    peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags},
+    peer_manager::{NodeId, NodeIdentity, Peer, PeerCapabilities, PeerFeatures, PeerFlags},
     proto::identity::PeerIdentityMsg,
     protocol,
     protocol::{NodeNetworkInfo, ProtocolId},
@@ -126,6 +126,7 @@ pub async fn validate_and_add_peer_from_peer_identity(
             peer.features = PeerFeatures::from_bits_truncate(peer_identity.features);
             peer.supported_protocols = supported_protocols.clone();
             peer.user_agent = peer_identity.user_agent;
+            peer.capabilities = PeerCapabilities::from_bits_truncate(peer_identity.capabilities);
             peer
         },
         None => {
@@ -143,6 +144,7 @@ pub async fn validate_and_add_peer_from_peer_identity(
                 supported_protocols.clone(),
                 peer_identity.user_agent,
             );
+            new_peer.capabilities = PeerCapabilities::from_bits_truncate(peer_identity.capabilities);
             new_peer.connection_stats.set_connection_success();
             if let Some(addr) = dialed_addr {
                 new_peer.addresses.mark_successful_connection_attempt(addr);
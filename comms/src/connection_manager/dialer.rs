@@ -46,7 +46,11 @@ use futures::{
     FutureExt,
 };
 use log::*;
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tari_shutdown::{Shutdown, ShutdownSignal};
 use tokio::{
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
@@ -128,7 +132,7 @@ where
     }
 
     pub fn spawn(self) -> JoinHandle<()> {
-        runtime::current().spawn(self.run())
+        runtime::spawn_named("comms::dialer", self.run())
     }
 
     pub async fn run(mut self) {
@@ -285,6 +289,15 @@ where
 
         let span = span!(Level::TRACE, "handle_dial_peer_request_inner1");
         let dial_fut = async move {
+            if !config.access_list.permits_public_key(&dial_state.peer().public_key) {
+                debug!(
+                    target: LOG_TARGET,
+                    "Dial to peer '{}' denied by the access list",
+                    dial_state.peer().node_id.short_str()
+                );
+                return (dial_state, Err(ConnectionManagerError::AccessDenied));
+            }
+
             let (dial_state, dial_result) =
                 Self::dial_peer_with_retry(dial_state, noise_config, transport, backoff, &config).await;
 
@@ -364,6 +377,7 @@ where
             return Err(ConnectionManagerError::DialCancelled);
         }
 
+        let identity_exchange_start = Instant::now();
         let peer_identity = common::perform_identity_exchange(
             &mut muxer,
             &node_identity,
@@ -372,6 +386,7 @@ where
             config.network_info.clone(),
         )
         .await?;
+        let identity_exchange_time = identity_exchange_start.elapsed();
         if cancel_signal.is_terminated() {
             muxer.get_yamux_control().close().await?;
             return Err(ConnectionManagerError::DialCancelled);
@@ -421,6 +436,7 @@ where
             our_supported_protocols,
             their_supported_protocols,
         )
+        .map(|conn| conn.with_identity_exchange_time(identity_exchange_time))
     }
 
     #[tracing::instrument(skip(dial_state, noise_config, transport, backoff, config))]
@@ -452,7 +468,7 @@ where
             tokio::select! {
                 _ = delay => {
                     debug!(target: LOG_TARGET, "[Attempt {}] Connecting to peer '{}'", current_state.num_attempts(), current_state.peer().node_id.short_str());
-                    match Self::dial_peer(current_state, &noise_config, &current_transport, config.network_info.network_byte).await {
+                    match Self::dial_peer(current_state, &noise_config, &current_transport, config).await {
                         (state, Ok((socket, addr))) => {
                             debug!(target: LOG_TARGET, "Dial succeeded for peer '{}' after {} attempt(s)", state.peer().node_id.short_str(), state.num_attempts());
                             break (state, Ok((socket, addr)));
@@ -486,12 +502,18 @@ where
         dial_state: DialState,
         noise_config: &NoiseConfig,
         transport: &TTransport,
-        network_byte: u8,
+        config: &ConnectionManagerConfig,
     ) -> (
         DialState,
         Result<(NoiseSocket<TTransport::Output>, Multiaddr), ConnectionManagerError>,
     ) {
-        let mut addr_iter = dial_state.peer().addresses.iter();
+        let network_byte = config.network_info.network_byte;
+        let access_list = &config.access_list;
+        let mut addr_iter = dial_state
+            .peer()
+            .addresses
+            .iter()
+            .filter(|address| access_list.permits_address(address));
         let cancel_signal = dial_state.get_cancel_signal();
         loop {
             let result = match addr_iter.next() {
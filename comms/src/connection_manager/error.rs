@@ -67,6 +67,8 @@ pub enum ConnectionManagerError {
     IncomingListenerStreamClosed,
     #[error("Peer is banned, denying connection")]
     PeerBanned,
+    #[error("Peer or address is not permitted by the access list, denying connection")]
+    AccessDenied,
     #[error("Unable to parse any of the network addresses offered by the connecting peer")]
     PeerIdentityNoValidAddresses,
     #[error("Identity protocol failed: {0}")]
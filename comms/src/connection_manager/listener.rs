@@ -133,7 +133,7 @@ where
 
     pub async fn listen(self) -> Result<Multiaddr, ConnectionManagerError> {
         let on_listening = self.on_listening();
-        runtime::current().spawn(self.run());
+        runtime::spawn_named("comms::listener", self.run());
         on_listening.await
     }
 
@@ -240,6 +240,15 @@ where
 
         let span = span!(Level::TRACE, "connection_mann::listener::inbound_task",);
         let inbound_fut = async move {
+            if !config.access_list.permits_address(&peer_addr) {
+                debug!(
+                    target: LOG_TARGET,
+                    "Inbound connection from address '{}' denied by the access list", peer_addr
+                );
+                let _ = socket.shutdown().await;
+                return;
+            }
+
             match Self::read_wire_format(&mut socket, config.time_to_first_byte).await {
                 Ok(WireMode::Comms(byte)) if byte == config.network_info.network_byte => {
                     let this_node_id_str = node_identity.node_id().short_str();
@@ -388,6 +397,10 @@ where
             .get_remote_public_key()
             .ok_or(ConnectionManagerError::InvalidStaticPublicKey)?;
 
+        if !config.access_list.permits_public_key(&authenticated_public_key) {
+            return Err(ConnectionManagerError::AccessDenied);
+        }
+
         // Check if we know the peer and if it is banned
         let known_peer = common::find_unbanned_peer(&peer_manager, &authenticated_public_key).await?;
 
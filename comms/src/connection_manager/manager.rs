@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::{
+    access_list::AccessList,
     dialer::{Dialer, DialerRequest},
     error::ConnectionManagerError,
     listener::PeerListener,
@@ -33,6 +34,7 @@ use crate::{
     noise::NoiseConfig,
     peer_manager::{NodeId, NodeIdentity},
     protocol::{NodeNetworkInfo, ProtocolEvent, ProtocolId, Protocols},
+    runtime,
     transports::{TcpTransport, Transport},
     PeerManager,
 };
@@ -108,6 +110,9 @@ pub struct ConnectionManagerConfig {
     /// If set, an additional TCP-only p2p listener will be started. This is useful for local wallet connections.
     /// Default: None (disabled)
     pub auxilary_tcp_listener_address: Option<Multiaddr>,
+    /// Addresses and peers that are allowed or blocked from connecting. The list is shared, so changes made after the
+    /// connection manager has started apply to new connections. Default: Empty (all permitted)
+    pub access_list: AccessList,
 }
 
 impl Default for ConnectionManagerConfig {
@@ -131,6 +136,7 @@ impl Default for ConnectionManagerConfig {
             time_to_first_byte: Duration::from_secs(45),
             liveness_cidr_allowlist: vec![cidr::AnyIpCidr::V4("127.0.0.1/32".parse().unwrap())],
             auxilary_tcp_listener_address: None,
+            access_list: Default::default(),
         }
     }
 }
@@ -252,7 +258,7 @@ where
     }
 
     pub fn spawn(self) -> task::JoinHandle<()> {
-        task::spawn(self.run())
+        runtime::spawn_named("comms::connection_manager", self.run())
     }
 
     pub async fn run(mut self) {
@@ -24,6 +24,9 @@ mod dial_state;
 mod dialer;
 mod listener;
 
+mod access_list;
+pub use access_list::{AccessList, AccessRule};
+
 mod common;
 pub use common::validate_peer_addresses;
 
@@ -105,7 +105,7 @@ pub fn create(
         our_supported_protocols,
         their_supported_protocols,
     );
-    runtime::current().spawn(peer_actor.run());
+    runtime::spawn_named("comms::peer_connection", peer_actor.run());
 
     Ok(peer_conn)
 }
@@ -134,6 +134,7 @@ pub struct PeerConnection {
     address: Arc<Multiaddr>,
     direction: ConnectionDirection,
     started_at: Instant,
+    identity_exchange_time: Option<Duration>,
     substream_counter: AtomicRefCounter,
     handle_counter: Arc<()>,
 }
@@ -156,11 +157,17 @@ impl PeerConnection {
             address: Arc::new(address),
             direction,
             started_at: Instant::now(),
+            identity_exchange_time: None,
             substream_counter,
             handle_counter: Arc::new(()),
         }
     }
 
+    pub(crate) fn with_identity_exchange_time(mut self, identity_exchange_time: Duration) -> Self {
+        self.identity_exchange_time = Some(identity_exchange_time);
+        self
+    }
+
     pub fn peer_node_id(&self) -> &NodeId {
         &self.peer_node_id
     }
@@ -189,6 +196,11 @@ impl PeerConnection {
         self.started_at.elapsed()
     }
 
+    /// The time the peer identity exchange took when this node dialed the peer. None for inbound connections.
+    pub fn identity_exchange_time(&self) -> Option<Duration> {
+        self.identity_exchange_time
+    }
+
     pub fn substream_count(&self) -> usize {
         self.substream_counter.get()
     }
@@ -30,7 +30,7 @@ use crate::{
         PeerConnectionError,
     },
     noise::NoiseConfig,
-    peer_manager::{NodeId, Peer, PeerFeatures, PeerFlags, PeerManagerError},
+    peer_manager::{NodeId, Peer, PeerCapabilities, PeerFeatures, PeerFlags, PeerManagerError},
     protocol::{ProtocolEvent, ProtocolId, Protocols, IDENTITY_PROTOCOL},
     runtime,
     runtime::task,
@@ -127,6 +127,7 @@ async fn dial_success() {
                 ..Default::default()
             };
             config.connection_manager_config.network_info.user_agent = "node2".to_string();
+            config.connection_manager_config.network_info.capabilities = PeerCapabilities::BASE_NODE;
             config
         },
         MemoryTransport,
@@ -156,6 +157,7 @@ async fn dial_success() {
     let peer2 = peer_manager1.find_by_node_id(conn_out.peer_node_id()).await.unwrap();
     assert_eq!(peer2.supported_protocols, [&IDENTITY_PROTOCOL, &TEST_PROTO]);
     assert_eq!(peer2.user_agent, "node2");
+    assert_eq!(peer2.capabilities, PeerCapabilities::BASE_NODE);
 
     let event = subscription2.recv().await.unwrap();
     unpack_enum!(ConnectionManagerEvent::PeerConnected(conn_in) = &*event);
@@ -164,6 +166,7 @@ async fn dial_success() {
     let peer1 = peer_manager2.find_by_node_id(node_identity1.node_id()).await.unwrap();
     assert_eq!(peer1.supported_protocols(), [&IDENTITY_PROTOCOL, &TEST_PROTO]);
     assert_eq!(peer1.user_agent, "node1");
+    assert_eq!(peer1.capabilities, PeerCapabilities::NONE);
 
     let err = conn_out
         .open_substream(&ProtocolId::from_static(b"/tari/invalid"))
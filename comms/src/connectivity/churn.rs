@@ -0,0 +1,165 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// The window over which recent connection churn is reported
+pub const CHURN_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChurnEvent {
+    Connected,
+    Disconnected,
+}
+
+/// Tracks how often peers connect and disconnect
+#[derive(Debug)]
+pub(super) struct ConnectionChurn {
+    started: Instant,
+    num_connected: u64,
+    num_disconnected: u64,
+    recent: VecDeque<(Instant, ChurnEvent)>,
+}
+
+impl ConnectionChurn {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            num_connected: 0,
+            num_disconnected: 0,
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn record_connected(&mut self) {
+        self.num_connected += 1;
+        self.record(ChurnEvent::Connected);
+    }
+
+    pub fn record_disconnected(&mut self) {
+        self.num_disconnected += 1;
+        self.record(ChurnEvent::Disconnected);
+    }
+
+    pub fn stats(&mut self) -> ConnectionChurnStats {
+        self.prune();
+        ConnectionChurnStats {
+            uptime: self.started.elapsed(),
+            num_connected: self.num_connected,
+            num_disconnected: self.num_disconnected,
+            num_recent_connected: self.count_recent(ChurnEvent::Connected),
+            num_recent_disconnected: self.count_recent(ChurnEvent::Disconnected),
+            window: CHURN_WINDOW,
+        }
+    }
+
+    fn record(&mut self, event: ChurnEvent) {
+        self.recent.push_back((Instant::now(), event));
+        self.prune();
+    }
+
+    fn prune(&mut self) {
+        while let Some((at, _)) = self.recent.front() {
+            if at.elapsed() <= CHURN_WINDOW {
+                break;
+            }
+            self.recent.pop_front();
+        }
+    }
+
+    fn count_recent(&self, event: ChurnEvent) -> usize {
+        self.recent.iter().filter(|(_, e)| *e == event).count()
+    }
+}
+
+/// Connection churn since the connectivity manager started, and within the last `window`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionChurnStats {
+    pub uptime: Duration,
+    pub num_connected: u64,
+    pub num_disconnected: u64,
+    pub num_recent_connected: usize,
+    pub num_recent_disconnected: usize,
+    pub window: Duration,
+}
+
+impl ConnectionChurnStats {
+    /// The number of connects and disconnects per minute within the recent window
+    pub fn recent_churn_per_minute(&self) -> f64 {
+        let minutes = self.window.min(self.uptime).as_secs_f64() / 60.0;
+        if minutes == 0.0 {
+            return 0.0;
+        }
+        (self.num_recent_connected + self.num_recent_disconnected) as f64 / minutes
+    }
+}
+
+impl fmt::Display for ConnectionChurnStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "+{}/-{} in the last {}m ({:.1}/min), +{}/-{} total",
+            self.num_recent_connected,
+            self.num_recent_disconnected,
+            self.window.as_secs() / 60,
+            self.recent_churn_per_minute(),
+            self.num_connected,
+            self.num_disconnected
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_counts_connects_and_disconnects() {
+        let mut churn = ConnectionChurn::new();
+        churn.record_connected();
+        churn.record_connected();
+        churn.record_disconnected();
+        let stats = churn.stats();
+        assert_eq!(stats.num_connected, 2);
+        assert_eq!(stats.num_disconnected, 1);
+        assert_eq!(stats.num_recent_connected, 2);
+        assert_eq!(stats.num_recent_disconnected, 1);
+    }
+
+    #[test]
+    fn it_calculates_the_churn_rate() {
+        let stats = ConnectionChurnStats {
+            uptime: Duration::from_secs(60 * 60),
+            num_connected: 100,
+            num_disconnected: 90,
+            num_recent_connected: 12,
+            num_recent_disconnected: 8,
+            window: CHURN_WINDOW,
+        };
+        assert!((stats.recent_churn_per_minute() - 2.0).abs() < f64::EPSILON);
+    }
+}
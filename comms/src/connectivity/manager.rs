@@ -20,6 +20,7 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use super::{
+    churn::ConnectionChurn,
     config::ConnectivityConfig,
     connection_pool::{ConnectionPool, ConnectionStatus},
     connection_stats::PeerConnectionStats,
@@ -36,7 +37,7 @@ use crate::{
     },
     connectivity::ConnectivityEventTx,
     peer_manager::NodeId,
-    runtime::task,
+    runtime::{self, task},
     utils::datetime::format_duration,
     NodeIdentity,
     PeerConnection,
@@ -88,6 +89,7 @@ impl ConnectivityManager {
             peer_manager: self.peer_manager.clone(),
             event_tx: self.event_tx,
             connection_stats: HashMap::new(),
+            churn: ConnectionChurn::new(),
             node_identity: self.node_identity,
             pool: ConnectionPool::new(),
             shutdown_signal: self.shutdown_signal,
@@ -143,13 +145,14 @@ struct ConnectivityManagerActor {
     peer_manager: Arc<PeerManager>,
     event_tx: ConnectivityEventTx,
     connection_stats: HashMap<NodeId, PeerConnectionStats>,
+    churn: ConnectionChurn,
     pool: ConnectionPool,
     shutdown_signal: ShutdownSignal,
 }
 
 impl ConnectivityManagerActor {
     pub fn spawn(self) -> JoinHandle<()> {
-        task::spawn(Self::run(self))
+        runtime::spawn_named("comms::connectivity_manager", Self::run(self))
     }
 
     #[tracing::instrument(name = "connectivity_manager_actor::run", skip(self))]
@@ -269,6 +272,9 @@ impl ConnectivityManagerActor {
                     error!(target: LOG_TARGET, "Error when banning peer: {:?}", err);
                 }
             },
+            GetConnectionChurn(reply) => {
+                let _ = reply.send(self.churn.stats());
+            },
             GetActiveConnections(reply) => {
                 let _ = reply.send(
                     self.pool
@@ -549,6 +555,7 @@ impl ConnectivityManagerActor {
         match (old_status, new_status) {
             (_, Connected) => {
                 self.mark_peer_succeeded(node_id.clone());
+                self.churn.record_connected();
                 match self.pool.get_connection(&node_id).cloned() {
                     Some(conn) => {
                         self.publish_event(ConnectivityEvent::PeerConnected(conn));
@@ -560,6 +567,7 @@ impl ConnectivityManagerActor {
                 }
             },
             (Connected, Disconnected) => {
+                self.churn.record_disconnected();
                 self.publish_event(ConnectivityEvent::PeerDisconnected(node_id));
             },
             // Was not connected so don't broadcast event
@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::{
+    churn::ConnectionChurnStats,
     connection_pool::PeerConnectionState,
     error::ConnectivityError,
     manager::ConnectivityStatus,
@@ -100,6 +101,7 @@ pub enum ConnectivityRequest {
     GetAllConnectionStates(oneshot::Sender<Vec<PeerConnectionState>>),
     GetActiveConnections(oneshot::Sender<Vec<PeerConnection>>),
     BanPeer(NodeId, Duration, String),
+    GetConnectionChurn(oneshot::Sender<ConnectionChurnStats>),
 }
 
 #[derive(Debug, Clone)]
@@ -228,6 +230,16 @@ impl ConnectivityRequester {
         reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
     }
 
+    /// Returns the number of peer connects and disconnects since the node started and within the recent window
+    pub async fn get_connection_churn(&mut self) -> Result<ConnectionChurnStats, ConnectivityError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectivityRequest::GetConnectionChurn(reply_tx))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
+    }
+
     pub async fn ban_peer_until(
         &mut self,
         node_id: NodeId,
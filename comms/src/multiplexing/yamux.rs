@@ -93,7 +93,7 @@ impl Yamux {
         let shutdown = Shutdown::new();
         let (incoming_tx, incoming_rx) = mpsc::channel(10);
         let incoming = IncomingWorker::new(connection, incoming_tx, shutdown.to_signal());
-        runtime::task::spawn(incoming.run());
+        runtime::spawn_named("comms::yamux::incoming", incoming.run());
         IncomingSubstreams::new(incoming_rx, counter, shutdown)
     }
 
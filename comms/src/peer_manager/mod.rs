@@ -84,6 +84,9 @@ pub use node_identity::NodeIdentity;
 mod peer;
 pub use peer::{Peer, PeerFlags};
 
+mod peer_capabilities;
+pub use peer_capabilities::PeerCapabilities;
+
 mod peer_features;
 pub use peer_features::PeerFeatures;
 
@@ -24,6 +24,7 @@ use super::{
     connection_stats::PeerConnectionStats,
     node_id::{deserialize_node_id_from_hex, NodeId},
     peer_id::PeerId,
+    PeerCapabilities,
     PeerFeatures,
 };
 use crate::{
@@ -88,6 +89,8 @@ pub struct Peer {
     pub added_at: NaiveDateTime,
     /// User agent advertised by the peer
     pub user_agent: String,
+    /// Optional protocol capabilities advertised by the peer
+    pub capabilities: PeerCapabilities,
     /// Metadata field. This field is for use by upstream clients to record extra info about a peer.
     /// We use a hashmap here so that we can use more than one "info set"
     pub metadata: HashMap<u8, Vec<u8>>,
@@ -118,6 +121,7 @@ impl Peer {
             added_at: Utc::now().naive_utc(),
             supported_protocols,
             user_agent,
+            capabilities: PeerCapabilities::NONE,
             metadata: HashMap::new(),
         }
     }
@@ -0,0 +1,108 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+bitflags! {
+    /// Optional protocols and protocol extensions that a node supports. Capabilities are advertised in the identity
+    /// exchange and recorded against the peer, so that new protocol features can be rolled out gradually by only using
+    /// them with peers that advertise them. Unknown bits received from newer peers are ignored.
+    #[derive(Serialize, Deserialize)]
+    pub struct PeerCapabilities: u64 {
+        const NONE = 0b0000_0000;
+        /// Serves header and block sync
+        const BLOCK_SYNC = 0b0000_0001;
+        /// Serves mempool sync
+        const MEMPOOL_SYNC = 0b0000_0010;
+        /// Relays new blocks in compact form
+        const COMPACT_BLOCK_RELAY = 0b0000_0100;
+        /// Advertises a minimum fee per gram for transaction relay
+        const FEE_FILTER = 0b0000_1000;
+
+        const BASE_NODE = Self::BLOCK_SYNC.bits
+            | Self::MEMPOOL_SYNC.bits
+            | Self::COMPACT_BLOCK_RELAY.bits
+            | Self::FEE_FILTER.bits;
+    }
+}
+
+impl PeerCapabilities {
+    /// Returns the capabilities that both this node and the peer support
+    #[inline]
+    pub fn negotiate(self, peer_capabilities: PeerCapabilities) -> PeerCapabilities {
+        self & peer_capabilities
+    }
+}
+
+impl Default for PeerCapabilities {
+    fn default() -> Self {
+        PeerCapabilities::NONE
+    }
+}
+
+impl fmt::Display for PeerCapabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("NONE");
+        }
+        let names = [
+            (PeerCapabilities::BLOCK_SYNC, "BLOCK_SYNC"),
+            (PeerCapabilities::MEMPOOL_SYNC, "MEMPOOL_SYNC"),
+            (PeerCapabilities::COMPACT_BLOCK_RELAY, "COMPACT_BLOCK_RELAY"),
+            (PeerCapabilities::FEE_FILTER, "FEE_FILTER"),
+        ];
+        let s = names
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join(",");
+        f.write_str(&s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negotiate() {
+        let ours = PeerCapabilities::BASE_NODE;
+        let theirs = PeerCapabilities::BLOCK_SYNC | PeerCapabilities::FEE_FILTER;
+        assert_eq!(ours.negotiate(theirs), theirs);
+        assert_eq!(ours.negotiate(PeerCapabilities::NONE), PeerCapabilities::NONE);
+        // Bits this node does not know about are dropped
+        assert_eq!(PeerCapabilities::from_bits_truncate(1 << 63), PeerCapabilities::NONE);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(PeerCapabilities::NONE.to_string(), "NONE");
+        assert_eq!(
+            (PeerCapabilities::BLOCK_SYNC | PeerCapabilities::FEE_FILTER).to_string(),
+            "BLOCK_SYNC,FEE_FILTER"
+        );
+    }
+}
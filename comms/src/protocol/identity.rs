@@ -94,6 +94,7 @@ where
         major: network_info.major_version,
         minor: network_info.minor_version,
         user_agent: network_info.user_agent,
+        capabilities: network_info.capabilities.bits(),
     }
     .to_encoded_bytes();
 
@@ -166,7 +167,7 @@ impl From<prost::DecodeError> for IdentityProtocolError {
 mod test {
     use crate::{
         connection_manager::ConnectionDirection,
-        peer_manager::PeerFeatures,
+        peer_manager::{PeerCapabilities, PeerFeatures},
         protocol::{IdentityProtocolError, NodeNetworkInfo},
         runtime,
         test_utils::node_identity::build_node_identity,
@@ -195,6 +196,7 @@ mod test {
                 &[],
                 NodeNetworkInfo {
                     minor_version: 1,
+                    capabilities: PeerCapabilities::BASE_NODE,
                     ..Default::default()
                 },
                 in_sock,
@@ -218,9 +220,11 @@ mod test {
 
         assert_eq!(identity1.features, node_identity1.features().bits());
         assert_eq!(identity1.addresses, vec![node_identity1.public_address().to_vec()]);
+        assert_eq!(identity1.capabilities, PeerCapabilities::BASE_NODE.bits());
 
         assert_eq!(identity2.features, node_identity2.features().bits());
         assert_eq!(identity2.addresses, vec![node_identity2.public_address().to_vec()]);
+        assert_eq!(identity2.capabilities, PeerCapabilities::NONE.bits());
     }
 
     #[runtime::test]
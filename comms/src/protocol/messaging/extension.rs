@@ -32,7 +32,6 @@ use crate::{
         ProtocolExtensionError,
     },
     runtime,
-    runtime::task,
 };
 use std::fmt;
 use tokio::sync::mpsc;
@@ -91,7 +90,7 @@ where
         context.register_complete_signal(messaging.complete_signal());
 
         // Spawn messaging protocol
-        task::spawn(messaging.run());
+        runtime::spawn_named("comms::messaging", messaging.run());
 
         // Spawn inbound pipeline
         let bounded_executor = BoundedExecutor::from_current(self.pipeline.max_concurrent_inbound_tasks);
@@ -101,11 +100,11 @@ where
             self.pipeline.inbound,
             context.shutdown_signal(),
         );
-        task::spawn(inbound.run());
+        runtime::spawn_named("comms::pipeline::inbound", inbound.run());
 
         // Spawn outbound pipeline
         let outbound = pipeline::Outbound::new(runtime::current(), self.pipeline.outbound, messaging_request_tx);
-        task::spawn(outbound.run());
+        runtime::spawn_named("comms::pipeline::outbound", outbound.run());
 
         Ok(())
     }
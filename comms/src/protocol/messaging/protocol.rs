@@ -32,7 +32,7 @@ use crate::{
         ProtocolEvent,
         ProtocolNotification,
     },
-    runtime::task,
+    runtime,
 };
 use bytes::Bytes;
 use log::*;
@@ -296,7 +296,7 @@ impl MessagingProtocol {
         let (msg_tx, msg_rx) = mpsc::unbounded_channel();
         let outbound_messaging =
             OutboundMessaging::new(connectivity, events_tx, msg_rx, peer_node_id, inactivity_timeout);
-        task::spawn(outbound_messaging.run());
+        runtime::spawn_named("comms::messaging::outbound", outbound_messaging.run());
         msg_tx
     }
 
@@ -311,7 +311,7 @@ impl MessagingProtocol {
             RATE_LIMIT_RESTOCK_INTERVAL,
             self.config.inactivity_timeout,
         );
-        task::spawn(inbound_messaging.run(substream));
+        runtime::spawn_named("comms::messaging::inbound", inbound_messaging.run(substream));
     }
 
     async fn handle_protocol_notification(&mut self, notification: ProtocolNotification<Substream>) {
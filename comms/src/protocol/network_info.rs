@@ -20,6 +20,8 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::peer_manager::PeerCapabilities;
+
 /// Represents the current nodes network info
 #[derive(Debug, Clone, Default)]
 pub struct NodeNetworkInfo {
@@ -35,4 +37,6 @@ pub struct NodeNetworkInfo {
     pub network_byte: u8,
     /// The user agent string for this node
     pub user_agent: String,
+    /// The optional protocol capabilities that this node advertises to its peers
+    pub capabilities: PeerCapabilities,
 }
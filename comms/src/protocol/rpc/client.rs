@@ -29,6 +29,7 @@ use crate::{
         rpc,
         rpc::{
             body::ClientStreaming,
+            compression::{CompressionStats, RpcCompression},
             message::{BaseRequest, RpcMessageFlags},
             Handshake,
             NamedProtocolService,
@@ -76,6 +77,7 @@ const LOG_TARGET: &str = "comms::rpc::client";
 #[derive(Clone)]
 pub struct RpcClient {
     connector: ClientConnector,
+    compression_stats: Arc<CompressionStats>,
 }
 
 impl RpcClient {
@@ -92,20 +94,32 @@ impl RpcClient {
         let shutdown = Shutdown::new();
         let shutdown_signal = shutdown.to_signal();
         let connector = ClientConnector::new(request_tx, shutdown);
+        let compression_stats = Arc::new(CompressionStats::new());
         let (ready_tx, ready_rx) = oneshot::channel();
         let tracing_id = tracing::Span::current().id();
         task::spawn({
             let span = span!(Level::TRACE, "start_rpc_worker");
             span.follows_from(tracing_id);
 
-            RpcClientWorker::new(config, request_rx, framed, ready_tx, protocol_name, shutdown_signal)
-                .run()
-                .instrument(span)
+            RpcClientWorker::new(
+                config,
+                request_rx,
+                framed,
+                ready_tx,
+                protocol_name,
+                shutdown_signal,
+                compression_stats.clone(),
+            )
+            .run()
+            .instrument(span)
         });
         ready_rx
             .await
             .expect("ready_rx oneshot is never dropped without a reply")?;
-        Ok(Self { connector })
+        Ok(Self {
+            connector,
+            compression_stats,
+        })
     }
 
     /// Perform a single request and single response
@@ -159,6 +173,11 @@ impl RpcClient {
         self.connector.send_ping()
     }
 
+    /// Returns the response compression statistics for this session
+    pub fn compression_stats(&self) -> &CompressionStats {
+        &self.compression_stats
+    }
+
     async fn call_inner(
         &mut self,
         request: BaseRequest<Bytes>,
@@ -234,6 +253,16 @@ where TClient: From<RpcClient> + NamedProtocolService
         self
     }
 
+    /// Request that the server compresses responses using the given algorithm. If the server does not support it,
+    /// responses are sent uncompressed. This is worthwhile for sessions that transfer large, compressible payloads such
+    /// as block and UTXO sync.
+    ///
+    /// Default: no compression
+    pub fn with_compression(mut self, compression: RpcCompression) -> Self {
+        self.config.compression = compression;
+        self
+    }
+
     /// Negotiates and establishes a session to the peer's RPC service
     pub async fn connect<TSubstream>(self, framed: CanonicalFraming<TSubstream>) -> Result<TClient, RpcError>
     where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId + 'static {
@@ -252,6 +281,7 @@ pub struct RpcClientConfig {
     pub deadline: Option<Duration>,
     pub deadline_grace_period: Duration,
     pub handshake_timeout: Duration,
+    pub compression: RpcCompression,
 }
 
 impl RpcClientConfig {
@@ -272,6 +302,7 @@ impl Default for RpcClientConfig {
             deadline: Some(Duration::from_secs(120)),
             deadline_grace_period: Duration::from_secs(60),
             handshake_timeout: Duration::from_secs(90),
+            compression: RpcCompression::None,
         }
     }
 }
@@ -362,6 +393,8 @@ struct RpcClientWorker<TSubstream> {
     last_request_latency: Option<Duration>,
     protocol_id: ProtocolId,
     shutdown_signal: ShutdownSignal,
+    compression: RpcCompression,
+    compression_stats: Arc<CompressionStats>,
 }
 
 impl<TSubstream> RpcClientWorker<TSubstream>
@@ -374,6 +407,7 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
         ready_tx: oneshot::Sender<Result<(), RpcError>>,
         protocol_id: ProtocolId,
         shutdown_signal: ShutdownSignal,
+        compression_stats: Arc<CompressionStats>,
     ) -> Self {
         Self {
             config,
@@ -384,6 +418,8 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
             last_request_latency: None,
             protocol_id,
             shutdown_signal,
+            compression: RpcCompression::None,
+            compression_stats,
         }
     }
 
@@ -404,17 +440,26 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
             self.protocol_name()
         );
         let start = Instant::now();
-        let mut handshake = Handshake::new(&mut self.framed).with_timeout(self.config.handshake_timeout());
+        let supported_compression = if self.config.compression.is_none() {
+            vec![]
+        } else {
+            vec![self.config.compression]
+        };
+        let mut handshake = Handshake::new(&mut self.framed)
+            .with_timeout(self.config.handshake_timeout())
+            .with_supported_compression(&supported_compression);
         match handshake.perform_client_handshake().await {
-            Ok(_) => {
+            Ok(params) => {
                 let latency = start.elapsed();
                 debug!(
                     target: LOG_TARGET,
-                    "(stream={}) RPC Session ({}) negotiation completed. Latency: {:.0?}",
+                    "(stream={}) RPC Session ({}) negotiation completed (compression: {}). Latency: {:.0?}",
                     self.stream_id(),
                     self.protocol_name(),
+                    params.compression,
                     latency
                 );
+                self.compression = params.compression;
                 self.last_request_latency = Some(latency);
                 if let Some(r) = self.ready_tx.take() {
                     let _ = r.send(Ok(()));
@@ -458,6 +503,17 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
             );
         }
 
+        if !self.compression.is_none() {
+            debug!(
+                target: LOG_TARGET,
+                "(stream={}) RPC session ({}) {} compression: {}",
+                self.stream_id(),
+                self.protocol_name(),
+                self.compression,
+                self.compression_stats
+            );
+        }
+
         debug!(
             target: LOG_TARGET,
             "(stream={}) RpcClientWorker ({}) terminated.",
@@ -647,7 +703,7 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
                 },
             };
 
-            match Self::convert_to_result(resp) {
+            match self.decompress_response(resp).and_then(Self::convert_to_result) {
                 Ok(Ok(resp)) => {
                     // The consumer may drop the receiver before all responses are received.
                     // We just ignore that as we still want obey the protocol and receive messages until the FIN flag or
@@ -715,6 +771,29 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
         next_id
     }
 
+    fn decompress_response(&self, mut resp: proto::rpc::RpcResponse) -> Result<proto::rpc::RpcResponse, RpcError> {
+        let flags = resp.flags();
+        if !flags.is_compressed() {
+            return Ok(resp);
+        }
+
+        let payload = self
+            .compression
+            .decompress(&resp.payload, rpc::max_response_payload_size())?;
+        trace!(
+            target: LOG_TARGET,
+            "(stream={}) Decompressed response payload using {} ({} bytes -> {} bytes)",
+            self.stream_id(),
+            self.compression,
+            resp.payload.len(),
+            payload.len()
+        );
+        self.compression_stats.record(payload.len(), resp.payload.len());
+        resp.payload = payload;
+        resp.flags = (flags - RpcMessageFlags::COMPRESSED).bits().into();
+        Ok(resp)
+    }
+
     fn convert_to_result(resp: proto::rpc::RpcResponse) -> Result<Result<Response<Bytes>, RpcStatus>, RpcError> {
         let status = RpcStatus::from(&resp);
         if !status.is_ok() {
@@ -0,0 +1,233 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{
+    convert::TryInto,
+    fmt,
+    io,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use thiserror::Error;
+
+/// Payloads smaller than this are never compressed, the framing overhead outweighs any gain
+pub(super) const RPC_COMPRESSION_THRESHOLD: usize = 1024;
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+/// lz4_flex prepends the uncompressed size as a little-endian u32
+const LZ4_SIZE_PREFIX_LEN: usize = 4;
+
+/// Compression algorithms that may be negotiated for an RPC session. The numeric values are used on the wire and MUST
+/// NOT change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcCompression {
+    None = 0,
+    Lz4 = 1,
+    Zstd = 2,
+}
+
+impl RpcCompression {
+    pub fn from_u32(v: u32) -> Option<Self> {
+        match v {
+            0 => Some(RpcCompression::None),
+            1 => Some(RpcCompression::Lz4),
+            2 => Some(RpcCompression::Zstd),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(self) -> u32 {
+        self as u32
+    }
+
+    pub fn is_none(self) -> bool {
+        self == RpcCompression::None
+    }
+
+    /// Compress the given bytes using this algorithm
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, RpcCompressionError> {
+        match self {
+            RpcCompression::None => Ok(data.to_vec()),
+            RpcCompression::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+            RpcCompression::Zstd => Ok(zstd::bulk::compress(data, ZSTD_COMPRESSION_LEVEL)?),
+        }
+    }
+
+    /// Decompress the given bytes using this algorithm. An error is returned if the decompressed payload would exceed
+    /// `max_size` bytes.
+    pub fn decompress(self, data: &[u8], max_size: usize) -> Result<Vec<u8>, RpcCompressionError> {
+        match self {
+            RpcCompression::None => Err(RpcCompressionError::NotNegotiated),
+            RpcCompression::Lz4 => {
+                let prefix: [u8; LZ4_SIZE_PREFIX_LEN] = data
+                    .get(..LZ4_SIZE_PREFIX_LEN)
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or(RpcCompressionError::MalformedPayload)?;
+                let size = u32::from_le_bytes(prefix) as usize;
+                if size > max_size {
+                    return Err(RpcCompressionError::DecompressedSizeExceeded {
+                        got: size,
+                        max: max_size,
+                    });
+                }
+                lz4_flex::decompress_size_prepended(data).map_err(|_| RpcCompressionError::MalformedPayload)
+            },
+            // zstd fails if the decompressed payload does not fit in the given capacity
+            RpcCompression::Zstd => Ok(zstd::bulk::decompress(data, max_size)?),
+        }
+    }
+}
+
+impl Default for RpcCompression {
+    fn default() -> Self {
+        RpcCompression::None
+    }
+}
+
+impl fmt::Display for RpcCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcCompression::None => write!(f, "none"),
+            RpcCompression::Lz4 => write!(f, "lz4"),
+            RpcCompression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RpcCompressionError {
+    #[error("IO Error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Received a compressed payload but compression was not negotiated for the session")]
+    NotNegotiated,
+    #[error("Compressed payload is malformed")]
+    MalformedPayload,
+    #[error("Decompressed payload size {got} exceeds the maximum of {max} bytes")]
+    DecompressedSizeExceeded { got: usize, max: usize },
+}
+
+/// Tracks the number of payload bytes before and after compression for an RPC session.
+#[derive(Debug, Default)]
+pub struct CompressionStats {
+    uncompressed_bytes: AtomicU64,
+    compressed_bytes: AtomicU64,
+    num_messages: AtomicU64,
+}
+
+impl CompressionStats {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub(super) fn record(&self, uncompressed_len: usize, compressed_len: usize) {
+        self.uncompressed_bytes
+            .fetch_add(uncompressed_len as u64, Ordering::Relaxed);
+        self.compressed_bytes
+            .fetch_add(compressed_len as u64, Ordering::Relaxed);
+        self.num_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Total size of compressed payloads before compression
+    pub fn uncompressed_bytes(&self) -> u64 {
+        self.uncompressed_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Total size of compressed payloads on the wire
+    pub fn compressed_bytes(&self) -> u64 {
+        self.compressed_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Number of compressed payloads
+    pub fn num_messages(&self) -> u64 {
+        self.num_messages.load(Ordering::Relaxed)
+    }
+
+    /// Returns the compression ratio (uncompressed / compressed), or 1.0 if nothing has been compressed
+    pub fn ratio(&self) -> f64 {
+        let compressed = self.compressed_bytes();
+        if compressed == 0 {
+            return 1.0;
+        }
+        self.uncompressed_bytes() as f64 / compressed as f64
+    }
+}
+
+impl fmt::Display for CompressionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} message(s), {} bytes -> {} bytes (ratio {:.2})",
+            self.num_messages(),
+            self.uncompressed_bytes(),
+            self.compressed_bytes(),
+            self.ratio()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::iter;
+
+    fn compressible_payload() -> Vec<u8> {
+        iter::repeat(b"range proof bytes ".to_vec())
+            .take(1000)
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn it_round_trips() {
+        let payload = compressible_payload();
+        for compression in &[RpcCompression::Lz4, RpcCompression::Zstd] {
+            let compressed = compression.compress(&payload).unwrap();
+            assert!(compressed.len() < payload.len());
+            let decompressed = compression.decompress(&compressed, payload.len()).unwrap();
+            assert_eq!(decompressed, payload);
+        }
+    }
+
+    #[test]
+    fn it_limits_the_decompressed_size() {
+        let payload = compressible_payload();
+        for compression in &[RpcCompression::Lz4, RpcCompression::Zstd] {
+            let compressed = compression.compress(&payload).unwrap();
+            compression.decompress(&compressed, payload.len() - 1).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn it_rejects_decompression_if_not_negotiated() {
+        let err = RpcCompression::None.decompress(b"abc", 10).unwrap_err();
+        assert!(matches!(err, RpcCompressionError::NotNegotiated));
+    }
+
+    #[test]
+    fn it_calculates_the_ratio() {
+        let stats = CompressionStats::new();
+        assert!((stats.ratio() - 1.0).abs() < f64::EPSILON);
+        stats.record(1000, 250);
+        stats.record(1000, 250);
+        assert_eq!(stats.num_messages(), 2);
+        assert!((stats.ratio() - 4.0).abs() < f64::EPSILON);
+    }
+}
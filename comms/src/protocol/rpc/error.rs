@@ -20,7 +20,7 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use super::{handshake::RpcHandshakeError, server::RpcServerError, RpcStatus};
+use super::{compression::RpcCompressionError, handshake::RpcHandshakeError, server::RpcServerError, RpcStatus};
 use crate::{
     connectivity::ConnectivityError,
     peer_manager::PeerManagerError,
@@ -69,6 +69,8 @@ pub enum RpcError {
     ExceededMaxChunkCount { expected: usize },
     #[error("Request body was too large. Expected <= {expected} but got {got}")]
     MaxRequestSizeExceeded { got: usize, expected: usize },
+    #[error("Compression error: {0}")]
+    CompressionError(#[from] RpcCompressionError),
     #[error(transparent)]
     UnknownError(#[from] anyhow::Error),
 }
@@ -20,7 +20,12 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{framing::CanonicalFraming, message::MessageExt, proto, protocol::rpc::error::HandshakeRejectReason};
+use crate::{
+    framing::CanonicalFraming,
+    message::MessageExt,
+    proto,
+    protocol::rpc::{error::HandshakeRejectReason, RpcCompression},
+};
 use bytes::BytesMut;
 use futures::{SinkExt, StreamExt};
 use prost::{DecodeError, Message};
@@ -53,12 +58,22 @@ pub enum RpcHandshakeError {
     Rejected(#[from] HandshakeRejectReason),
     #[error("The client connection is closed")]
     ClientClosed,
+    #[error("The server selected a compression algorithm ({0}) that was not requested")]
+    UnexpectedCompression(u32),
+}
+
+/// The parameters agreed upon by the client and server in the handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcSessionParams {
+    pub version: u32,
+    pub compression: RpcCompression,
 }
 
 /// Handshake protocol
 pub struct Handshake<'a, T> {
     framed: &'a mut CanonicalFraming<T>,
     timeout: Option<Duration>,
+    supported_compression: &'a [RpcCompression],
 }
 
 impl<'a, T> Handshake<'a, T>
@@ -66,7 +81,11 @@ where T: AsyncRead + AsyncWrite + Unpin
 {
     /// Create a Handshake using the given framing and no timeout. To set a timeout, use `with_timeout`.
     pub fn new(framed: &'a mut CanonicalFraming<T>) -> Self {
-        Self { framed, timeout: None }
+        Self {
+            framed,
+            timeout: None,
+            supported_compression: &[],
+        }
     }
 
     /// Set the length of time that a client/server should wait for the other side to respond before timing out.
@@ -75,9 +94,17 @@ where T: AsyncRead + AsyncWrite + Unpin
         self
     }
 
+    /// Set the compression algorithms supported by this side of the session. For the client, these are requested in
+    /// order of preference. For the server, the first algorithm requested by the client that is in this list is
+    /// selected. No compression is used by default.
+    pub fn with_supported_compression(mut self, supported_compression: &'a [RpcCompression]) -> Self {
+        self.supported_compression = supported_compression;
+        self
+    }
+
     /// Server-side handshake protocol
     #[tracing::instrument(name = "rpc::server::perform_server_handshake", skip(self), err, fields(comms.direction="inbound"))]
-    pub async fn perform_server_handshake(&mut self) -> Result<u32, RpcHandshakeError> {
+    pub async fn perform_server_handshake(&mut self) -> Result<RpcSessionParams, RpcHandshakeError> {
         match self.recv_next_frame().await {
             Ok(Some(Ok(msg))) => {
                 event!(Level::DEBUG, "Handshake bytes received");
@@ -87,9 +114,19 @@ where T: AsyncRead + AsyncWrite + Unpin
                     .find(|v| msg.supported_versions.contains(v));
                 if let Some(version) = version {
                     event!(Level::INFO, version = version, "Server accepted version");
-                    debug!(target: LOG_TARGET, "Server accepted version {}", version);
+                    let compression = msg
+                        .supported_compression
+                        .iter()
+                        .filter_map(|c| RpcCompression::from_u32(*c))
+                        .find(|c| self.supported_compression.contains(c))
+                        .unwrap_or_default();
+                    debug!(
+                        target: LOG_TARGET,
+                        "Server accepted version {} (compression: {})", version, compression
+                    );
                     let reply = proto::rpc::RpcSessionReply {
                         session_result: Some(proto::rpc::rpc_session_reply::SessionResult::AcceptedVersion(*version)),
+                        accepted_compression: compression.as_u32(),
                         ..Default::default()
                     };
                     let span = span!(Level::INFO, "rpc::server::handshake::send_accept_version_reply");
@@ -97,7 +134,10 @@ where T: AsyncRead + AsyncWrite + Unpin
                         .send(reply.to_encoded_bytes().into())
                         .instrument(span)
                         .await?;
-                    return Ok(*version);
+                    return Ok(RpcSessionParams {
+                        version: *version,
+                        compression,
+                    });
                 }
 
                 let span = span!(Level::INFO, "rpc::server::handshake::send_rejection");
@@ -126,6 +166,7 @@ where T: AsyncRead + AsyncWrite + Unpin
         let reply = proto::rpc::RpcSessionReply {
             session_result: Some(proto::rpc::rpc_session_reply::SessionResult::Rejected(true)),
             reject_reason: reject_reason.as_i32(),
+            ..Default::default()
         };
         self.framed.send(reply.to_encoded_bytes().into()).await?;
         self.framed.close().await?;
@@ -134,9 +175,10 @@ where T: AsyncRead + AsyncWrite + Unpin
 
     /// Client-side handshake protocol
     #[tracing::instrument(name = "rpc::client::perform_client_handshake", skip(self), err, fields(comms.direction="outbound"))]
-    pub async fn perform_client_handshake(&mut self) -> Result<(), RpcHandshakeError> {
+    pub async fn perform_client_handshake(&mut self) -> Result<RpcSessionParams, RpcHandshakeError> {
         let msg = proto::rpc::RpcSession {
             supported_versions: SUPPORTED_RPC_VERSIONS.to_vec(),
+            supported_compression: self.supported_compression.iter().map(|c| c.as_u32()).collect(),
         };
         let payload = msg.to_encoded_bytes();
         debug!(target: LOG_TARGET, "Sending client handshake ({} bytes)", payload.len());
@@ -155,8 +197,14 @@ where T: AsyncRead + AsyncWrite + Unpin
                 let msg = proto::rpc::RpcSessionReply::decode(&mut msg.freeze())?;
                 let version = msg.result()?;
                 event!(Level::INFO, "Server accepted version: {}", version);
-                debug!(target: LOG_TARGET, "Server accepted version {}", version);
-                Ok(())
+                let compression = RpcCompression::from_u32(msg.accepted_compression)
+                    .filter(|c| c.is_none() || self.supported_compression.contains(c))
+                    .ok_or(RpcHandshakeError::UnexpectedCompression(msg.accepted_compression))?;
+                debug!(
+                    target: LOG_TARGET,
+                    "Server accepted version {} (compression: {})", version, compression
+                );
+                Ok(RpcSessionParams { version, compression })
             },
             Ok(Some(Err(err))) => {
                 event!(Level::ERROR, "Error: {}", err);
@@ -61,13 +61,16 @@ pub use body::{Body, ClientStreaming, IntoBody, Streaming};
 mod context;
 
 mod server;
-pub use server::{mock, NamedProtocolService, RpcServer, RpcServerError, RpcServerHandle};
+pub use server::{mock, NamedProtocolService, RpcServer, RpcServerError, RpcServerHandle, RpcSessionInfo};
 
 mod client;
 pub use client::{RpcClient, RpcClientBuilder, RpcClientConfig};
 
 mod either;
 
+mod compression;
+pub use compression::{CompressionStats, RpcCompression, RpcCompressionError};
+
 mod message;
 pub use message::{Request, Response};
 
@@ -75,7 +78,7 @@ mod error;
 pub use error::RpcError;
 
 mod handshake;
-pub use handshake::{Handshake, RpcHandshakeError};
+pub use handshake::{Handshake, RpcHandshakeError, RpcSessionParams};
 
 mod client_pool;
 pub use client_pool::{RpcClientLease, RpcClientPool, RpcClientPoolError, RpcPoolClient};
@@ -93,6 +96,7 @@ pub mod __macro_reexports {
         protocol::{
             rpc::{
                 client_pool::RpcPoolClient,
+                compression::CompressionStats,
                 message::{Request, Response},
                 server::{NamedProtocolService, RpcServerError},
                 Body,
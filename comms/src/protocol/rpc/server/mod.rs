@@ -35,8 +35,13 @@ pub mod mock;
 mod router;
 use router::Router;
 
+mod session;
+pub use session::RpcSessionInfo;
+use session::{ActiveSessions, BandwidthLimiter, SessionHandle};
+
 use super::{
     body::Body,
+    compression::{CompressionStats, RpcCompression, RPC_COMPRESSION_THRESHOLD},
     context::{RequestContext, RpcCommsProvider},
     error::HandshakeRejectReason,
     message::{Request, Response, RpcMessageFlags},
@@ -53,6 +58,7 @@ use crate::{
     peer_manager::NodeId,
     proto,
     protocol::{
+        rpc,
         rpc::{body::BodyBytes, message::RpcResponse},
         ProtocolEvent,
         ProtocolId,
@@ -71,7 +77,10 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
-use tokio::{sync::mpsc, time};
+use tokio::{
+    sync::{mpsc, Semaphore},
+    time,
+};
 use tower::Service;
 use tower_make::MakeService;
 use tracing::{debug, error, instrument, span, trace, warn, Instrument, Level};
@@ -160,6 +169,10 @@ pub struct RpcServerBuilder {
     maximum_simultaneous_sessions: Option<usize>,
     minimum_client_deadline: Duration,
     handshake_timeout: Duration,
+    supported_compression: Vec<RpcCompression>,
+    maximum_sessions_per_client: Option<usize>,
+    session_bandwidth_limit: Option<u64>,
+    maximum_concurrent_writes: usize,
 }
 
 impl RpcServerBuilder {
@@ -182,6 +195,39 @@ impl RpcServerBuilder {
         self
     }
 
+    /// Set the response compression algorithms that clients may request. Pass an empty Vec to disable compression.
+    ///
+    /// Default: zstd, lz4
+    pub fn with_supported_compression(mut self, supported_compression: Vec<RpcCompression>) -> Self {
+        self.supported_compression = supported_compression;
+        self
+    }
+
+    /// Limit the number of simultaneous sessions a single peer may have open.
+    ///
+    /// Default: unlimited
+    pub fn with_maximum_sessions_per_client(mut self, limit: usize) -> Self {
+        self.maximum_sessions_per_client = Some(limit);
+        self
+    }
+
+    /// Limit the upload bandwidth of each session to the given number of bytes per second.
+    ///
+    /// Default: unlimited
+    pub fn with_session_bandwidth_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.session_bandwidth_limit = Some(bytes_per_sec);
+        self
+    }
+
+    /// Set the number of response chunks that may be written at the same time across all sessions. Sessions waiting to
+    /// write are served in the order they started waiting, so a busy session cannot starve the others.
+    ///
+    /// Default: 16
+    pub fn with_maximum_concurrent_writes(mut self, limit: usize) -> Self {
+        self.maximum_concurrent_writes = limit;
+        self
+    }
+
     pub fn finish(self) -> RpcServer {
         let (request_tx, request_rx) = mpsc::channel(10);
         RpcServer {
@@ -198,6 +244,10 @@ impl Default for RpcServerBuilder {
             maximum_simultaneous_sessions: Some(1000),
             minimum_client_deadline: Duration::from_secs(1),
             handshake_timeout: Duration::from_secs(15),
+            supported_compression: vec![RpcCompression::Zstd, RpcCompression::Lz4],
+            maximum_sessions_per_client: None,
+            session_bandwidth_limit: None,
+            maximum_concurrent_writes: 16,
         }
     }
 }
@@ -209,6 +259,8 @@ pub(super) struct PeerRpcServer<TSvc, TCommsProvider> {
     protocol_notifications: Option<ProtocolNotificationRx<Substream>>,
     comms_provider: TCommsProvider,
     request_rx: mpsc::Receiver<RpcServerRequest>,
+    sessions: ActiveSessions,
+    write_permits: Arc<Semaphore>,
 }
 
 impl<TSvc, TCommsProvider> PeerRpcServer<TSvc, TCommsProvider>
@@ -238,11 +290,13 @@ where
                 Some(num) => BoundedExecutor::from_current(num),
                 None => BoundedExecutor::allow_maximum(),
             },
+            write_permits: Arc::new(Semaphore::new(config.maximum_concurrent_writes)),
             config,
             service,
             protocol_notifications: Some(protocol_notifications),
             comms_provider,
             request_rx,
+            sessions: ActiveSessions::new(),
         }
     }
 
@@ -287,6 +341,9 @@ where
                 let num_active = max_sessions.saturating_sub(self.executor.num_available());
                 let _ = reply.send(num_active);
             },
+            GetActiveSessions(reply) => {
+                let _ = reply.send(self.sessions.to_session_info());
+            },
         }
     }
 
@@ -324,7 +381,9 @@ where
         node_id: NodeId,
         mut framed: CanonicalFraming<Substream>,
     ) -> Result<(), RpcServerError> {
-        let mut handshake = Handshake::new(&mut framed).with_timeout(self.config.handshake_timeout);
+        let mut handshake = Handshake::new(&mut framed)
+            .with_timeout(self.config.handshake_timeout)
+            .with_supported_compression(&self.config.supported_compression);
 
         if !self.executor.can_spawn() {
             debug!(
@@ -339,6 +398,22 @@ where
             return Err(RpcServerError::MaximumSessionsReached);
         }
 
+        if let Some(limit) = self.config.maximum_sessions_per_client {
+            if self.sessions.count_for_peer(&node_id) >= limit {
+                debug!(
+                    target: LOG_TARGET,
+                    "Rejecting RPC session request for peer `{}` because the peer has reached the limit of {} \
+                     session(s)",
+                    node_id,
+                    limit
+                );
+                handshake
+                    .reject_with_reason(HandshakeRejectReason::NoSessionsAvailable)
+                    .await?;
+                return Err(RpcServerError::MaximumSessionsPerClientReached);
+            }
+        }
+
         let service = match self.service.make_service(protocol.clone()).await {
             Ok(s) => s,
             Err(err) => {
@@ -355,12 +430,20 @@ where
             },
         };
 
-        let version = handshake.perform_server_handshake().await?;
+        let params = handshake.perform_server_handshake().await?;
         debug!(
             target: LOG_TARGET,
-            "Server negotiated RPC v{} with client node `{}`", version, node_id
+            "Server negotiated RPC v{} (compression: {}) with client node `{}`",
+            params.version,
+            params.compression,
+            node_id
         );
 
+        let session = self.sessions.register(
+            node_id.clone(),
+            String::from_utf8_lossy(&protocol).to_string(),
+            params.compression,
+        );
         let service = ActivePeerRpcService::new(
             self.config.clone(),
             protocol,
@@ -368,6 +451,9 @@ where
             service,
             framed,
             self.comms_provider.clone(),
+            params.compression,
+            session,
+            self.write_permits.clone(),
         );
 
         self.executor
@@ -386,6 +472,11 @@ struct ActivePeerRpcService<TSvc, TCommsProvider> {
     framed: CanonicalFraming<Substream>,
     comms_provider: TCommsProvider,
     logging_context_string: Arc<String>,
+    compression: RpcCompression,
+    compression_stats: Arc<CompressionStats>,
+    session: SessionHandle,
+    write_permits: Arc<Semaphore>,
+    bandwidth_limiter: Option<BandwidthLimiter>,
 }
 
 impl<TSvc, TCommsProvider> ActivePeerRpcService<TSvc, TCommsProvider>
@@ -400,6 +491,9 @@ where
         service: TSvc,
         framed: CanonicalFraming<Substream>,
         comms_provider: TCommsProvider,
+        compression: RpcCompression,
+        session: SessionHandle,
+        write_permits: Arc<Semaphore>,
     ) -> Self {
         Self {
             logging_context_string: Arc::new(format!(
@@ -409,12 +503,17 @@ where
                 String::from_utf8_lossy(&protocol)
             )),
 
-            config,
             protocol,
             node_id,
             service,
             framed,
             comms_provider,
+            compression,
+            compression_stats: Arc::new(CompressionStats::new()),
+            session,
+            write_permits,
+            bandwidth_limiter: config.session_bandwidth_limit.map(BandwidthLimiter::new),
+            config,
         }
     }
 
@@ -429,6 +528,12 @@ where
                 "({}) Rpc server exited with an error: {}", self.logging_context_string, err
             );
         }
+        if !self.compression.is_none() {
+            debug!(
+                target: LOG_TARGET,
+                "({}) Rpc server {} compression: {}", self.logging_context_string, self.compression, self.compression_stats
+            );
+        }
     }
 
     async fn run(&mut self) -> Result<(), RpcServerError> {
@@ -475,6 +580,7 @@ where
     #[instrument(name = "rpc::server::handle_req", skip(self, request), err, fields(request_size = request.len()))]
     async fn handle_request(&mut self, mut request: Bytes) -> Result<(), RpcServerError> {
         let decoded_msg = proto::rpc::RpcRequest::decode(&mut request)?;
+        self.session.record_request();
 
         let request_id = decoded_msg.request_id;
         let method = decoded_msg.method.into();
@@ -587,9 +693,12 @@ where
         body: Response<Body>,
     ) -> Result<(), RpcServerError> {
         trace!(target: LOG_TARGET, "Service call succeeded");
+        let compression = self.compression;
+        let compression_stats = self.compression_stats.clone();
         let mut stream = body
             .into_message()
             .map(|result| into_response(request_id, result))
+            .map(move |resp| compress_response(compression, &compression_stats, resp))
             .flat_map(|message| stream::iter(ChunkedResponseIter::new(message)))
             .map(|resp| Bytes::from(resp.to_encoded_bytes()));
 
@@ -609,7 +718,19 @@ where
                         msg.len()
                     );
 
+                    if let Some(limiter) = self.bandwidth_limiter.as_mut() {
+                        limiter.throttle(msg.len()).await;
+                    }
+                    // Sessions take turns to write a chunk. The semaphore is fair so waiting sessions are served in
+                    // FIFO order, which interleaves large responses from concurrent sessions.
+                    let _permit = self
+                        .write_permits
+                        .acquire()
+                        .await
+                        .expect("write_permits semaphore is never closed");
+                    let len = msg.len();
                     self.framed.send(msg).await?;
+                    self.session.record_sent(len);
                 },
                 Ok(None) => {
                     debug!(target: LOG_TARGET, "{} Request complete", self.logging_context_string,);
@@ -679,3 +800,40 @@ fn into_response(request_id: u32, result: Result<BodyBytes, RpcStatus>) -> RpcRe
         },
     }
 }
+
+/// Compresses the response payload if compression was negotiated and the payload is large enough to benefit from it.
+/// The compressed payload is only used if it is smaller than the original.
+fn compress_response(compression: RpcCompression, stats: &CompressionStats, mut response: RpcResponse) -> RpcResponse {
+    let len = response.payload.len();
+    // Payloads that exceed the maximum size are left as is so that the chunker reports the error to the client
+    if compression.is_none() ||
+        !response.status.is_ok() ||
+        len < RPC_COMPRESSION_THRESHOLD ||
+        len > rpc::max_response_payload_size()
+    {
+        return response;
+    }
+
+    match compression.compress(&response.payload) {
+        Ok(compressed) if compressed.len() < len => {
+            trace!(
+                target: LOG_TARGET,
+                "Compressed response payload using {} ({} bytes -> {} bytes)",
+                compression,
+                len,
+                compressed.len()
+            );
+            stats.record(len, compressed.len());
+            response.payload = compressed.into();
+            response.flags |= RpcMessageFlags::COMPRESSED;
+        },
+        Ok(_) => {},
+        Err(err) => {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to compress response payload using {}: {}. Sending uncompressed.", compression, err
+            );
+        },
+    }
+    response
+}
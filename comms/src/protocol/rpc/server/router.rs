@@ -40,7 +40,7 @@ use crate::{
         ProtocolId,
         ProtocolNotificationRx,
     },
-    runtime::task,
+    runtime,
     Bytes,
     Substream,
 };
@@ -209,7 +209,7 @@ where
         let (proto_notif_tx, proto_notif_rx) = mpsc::channel(20);
         context.add_protocol(&self.protocol_names, proto_notif_tx);
         let rpc_context = RpcCommsBackend::new(context.peer_manager(), context.connectivity());
-        task::spawn(self.serve(proto_notif_rx, rpc_context));
+        runtime::spawn_named("comms::rpc_server", self.serve(proto_notif_rx, rpc_context));
         Ok(())
     }
 }
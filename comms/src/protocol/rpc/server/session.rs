@@ -0,0 +1,227 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crate::{peer_manager::NodeId, protocol::rpc::RpcCompression};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::time;
+
+/// The window over which the per-session bandwidth limit is measured. Unused allowance does not carry over to the
+/// next window, so an idle session cannot save up for a burst.
+const BANDWIDTH_WINDOW: Duration = Duration::from_secs(1);
+
+/// Information about an active RPC session
+#[derive(Debug, Clone)]
+pub struct RpcSessionInfo {
+    pub session_id: u64,
+    pub node_id: NodeId,
+    pub protocol: String,
+    pub compression: RpcCompression,
+    pub age: Duration,
+    pub num_requests: u64,
+    pub bytes_sent: u64,
+}
+
+impl RpcSessionInfo {
+    /// Average upload throughput in bytes per second over the lifetime of the session
+    pub fn throughput(&self) -> f64 {
+        let secs = self.age.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.bytes_sent as f64 / secs
+    }
+}
+
+#[derive(Debug)]
+struct SessionState {
+    node_id: NodeId,
+    protocol: String,
+    compression: RpcCompression,
+    started: Instant,
+    num_requests: AtomicU64,
+    bytes_sent: AtomicU64,
+}
+
+/// Registry of the sessions that are currently being served
+#[derive(Debug, Clone, Default)]
+pub(super) struct ActiveSessions {
+    sessions: Arc<Mutex<HashMap<u64, Arc<SessionState>>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl ActiveSessions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers a new session. The session is removed from the registry when the returned handle is dropped.
+    pub fn register(&self, node_id: NodeId, protocol: String, compression: RpcCompression) -> SessionHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let state = Arc::new(SessionState {
+            node_id,
+            protocol,
+            compression,
+            started: Instant::now(),
+            num_requests: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+        });
+        self.lock().insert(id, state.clone());
+        SessionHandle {
+            id,
+            state,
+            sessions: self.clone(),
+        }
+    }
+
+    pub fn count_for_peer(&self, node_id: &NodeId) -> usize {
+        self.lock().values().filter(|s| s.node_id == *node_id).count()
+    }
+
+    pub fn to_session_info(&self) -> Vec<RpcSessionInfo> {
+        let mut sessions = self
+            .lock()
+            .iter()
+            .map(|(id, state)| RpcSessionInfo {
+                session_id: *id,
+                node_id: state.node_id.clone(),
+                protocol: state.protocol.clone(),
+                compression: state.compression,
+                age: state.started.elapsed(),
+                num_requests: state.num_requests.load(Ordering::Relaxed),
+                bytes_sent: state.bytes_sent.load(Ordering::Relaxed),
+            })
+            .collect::<Vec<_>>();
+        sessions.sort_by_key(|s| s.session_id);
+        sessions
+    }
+
+    fn remove(&self, id: u64) {
+        self.lock().remove(&id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Arc<SessionState>>> {
+        self.sessions.lock().expect("ActiveSessions lock poisoned")
+    }
+}
+
+/// Handle used by a session to record its activity
+#[derive(Debug)]
+pub(super) struct SessionHandle {
+    id: u64,
+    state: Arc<SessionState>,
+    sessions: ActiveSessions,
+}
+
+impl SessionHandle {
+    pub fn record_request(&self) {
+        self.state.num_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_sent(&self, num_bytes: usize) {
+        self.state.bytes_sent.fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SessionHandle {
+    fn drop(&mut self) {
+        self.sessions.remove(self.id);
+    }
+}
+
+/// Limits the rate at which a single session may send bytes
+#[derive(Debug)]
+pub(super) struct BandwidthLimiter {
+    bytes_per_sec: u64,
+    window_start: Instant,
+    window_bytes: u64,
+}
+
+impl BandwidthLimiter {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec,
+            window_start: Instant::now(),
+            window_bytes: 0,
+        }
+    }
+
+    /// Waits until `num_bytes` may be sent without exceeding the bandwidth limit
+    pub async fn throttle(&mut self, num_bytes: usize) {
+        if self.window_start.elapsed() >= BANDWIDTH_WINDOW {
+            self.window_start = Instant::now();
+            self.window_bytes = 0;
+        }
+        self.window_bytes += num_bytes as u64;
+        let allowed_at = Duration::from_secs_f64(self.window_bytes as f64 / self.bytes_per_sec as f64);
+        let elapsed = self.window_start.elapsed();
+        if allowed_at > elapsed {
+            time::sleep(allowed_at - elapsed).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runtime;
+
+    #[test]
+    fn it_removes_sessions_when_the_handle_is_dropped() {
+        let sessions = ActiveSessions::new();
+        let node_id = NodeId::default();
+        let handle1 = sessions.register(node_id.clone(), "t/1".to_string(), RpcCompression::None);
+        let handle2 = sessions.register(node_id.clone(), "t/2".to_string(), RpcCompression::Zstd);
+        handle2.record_request();
+        handle2.record_sent(100);
+        assert_eq!(sessions.count_for_peer(&node_id), 2);
+
+        let info = sessions.to_session_info();
+        assert_eq!(info.len(), 2);
+        assert_eq!(info[1].protocol, "t/2");
+        assert_eq!(info[1].num_requests, 1);
+        assert_eq!(info[1].bytes_sent, 100);
+
+        drop(handle1);
+        assert_eq!(sessions.count_for_peer(&node_id), 1);
+        drop(handle2);
+        assert!(sessions.to_session_info().is_empty());
+    }
+
+    #[runtime::test]
+    async fn it_throttles_to_the_bandwidth_limit() {
+        let mut limiter = BandwidthLimiter::new(1000);
+        let start = Instant::now();
+        limiter.throttle(500).await;
+        assert!(start.elapsed() >= Duration::from_millis(450));
+        limiter.throttle(250).await;
+        assert!(start.elapsed() >= Duration::from_millis(700));
+    }
+}
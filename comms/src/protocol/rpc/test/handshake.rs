@@ -27,6 +27,7 @@ use crate::{
         error::HandshakeRejectReason,
         handshake::{RpcHandshakeError, SUPPORTED_RPC_VERSIONS},
         Handshake,
+        RpcCompression,
     },
     runtime,
     runtime::task,
@@ -46,9 +47,52 @@ async fn it_performs_the_handshake() {
     let mut client_framed = framing::canonical(client, 1024);
     let mut handshake_client = Handshake::new(&mut client_framed);
 
-    handshake_client.perform_client_handshake().await.unwrap();
-    let v = handshake_result.await.unwrap().unwrap();
-    assert!(SUPPORTED_RPC_VERSIONS.contains(&v));
+    let client_params = handshake_client.perform_client_handshake().await.unwrap();
+    let server_params = handshake_result.await.unwrap().unwrap();
+    assert!(SUPPORTED_RPC_VERSIONS.contains(&server_params.version));
+    assert_eq!(client_params, server_params);
+    assert_eq!(server_params.compression, RpcCompression::None);
+}
+
+#[runtime::test]
+async fn it_negotiates_compression() {
+    let (client, server) = MemorySocket::new_pair();
+
+    let handshake_result = task::spawn(async move {
+        let mut server_framed = framing::canonical(server, 1024);
+        let mut handshake_server =
+            Handshake::new(&mut server_framed).with_supported_compression(&[RpcCompression::Zstd, RpcCompression::Lz4]);
+        handshake_server.perform_server_handshake().await
+    });
+
+    let mut client_framed = framing::canonical(client, 1024);
+    let mut handshake_client =
+        Handshake::new(&mut client_framed).with_supported_compression(&[RpcCompression::Lz4, RpcCompression::Zstd]);
+
+    let client_params = handshake_client.perform_client_handshake().await.unwrap();
+    let server_params = handshake_result.await.unwrap().unwrap();
+    // The client's preference is honoured
+    assert_eq!(client_params.compression, RpcCompression::Lz4);
+    assert_eq!(server_params.compression, RpcCompression::Lz4);
+}
+
+#[runtime::test]
+async fn it_falls_back_to_no_compression() {
+    let (client, server) = MemorySocket::new_pair();
+
+    let handshake_result = task::spawn(async move {
+        let mut server_framed = framing::canonical(server, 1024);
+        let mut handshake_server = Handshake::new(&mut server_framed);
+        handshake_server.perform_server_handshake().await
+    });
+
+    let mut client_framed = framing::canonical(client, 1024);
+    let mut handshake_client = Handshake::new(&mut client_framed).with_supported_compression(&[RpcCompression::Zstd]);
+
+    let client_params = handshake_client.perform_client_handshake().await.unwrap();
+    let server_params = handshake_result.await.unwrap().unwrap();
+    assert_eq!(client_params.compression, RpcCompression::None);
+    assert_eq!(server_params.compression, RpcCompression::None);
 }
 
 #[runtime::test]
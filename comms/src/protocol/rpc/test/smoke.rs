@@ -41,6 +41,7 @@ use crate::{
                 },
                 mock::create_mocked_rpc_context,
             },
+            RpcCompression,
             RpcError,
             RpcServer,
             RpcStatusCode,
@@ -270,6 +271,41 @@ async fn response_too_big() {
         .unwrap();
 }
 
+#[runtime::test]
+async fn compressed_responses() {
+    for compression in &[RpcCompression::Lz4, RpcCompression::Zstd] {
+        let (mut muxer, _outbound, _, _, _shutdown) = setup(GreetingService::new(&[]), 1).await;
+        let socket = muxer.incoming_mut().next().await.unwrap();
+
+        let framed = framing::canonical(socket, rpc::max_response_size());
+        let mut client = GreetingClient::builder()
+            .with_deadline(Duration::from_secs(5))
+            .with_compression(*compression)
+            .connect(framed)
+            .await
+            .unwrap();
+
+        let resp = client.reply_with_msg_of_size(1024 * 1024).await.unwrap();
+        assert_eq!(resp.len(), 1024 * 1024);
+        assert!(resp.iter().all(|b| *b == 0));
+
+        // Small payloads are not compressed
+        let resp = client
+            .say_hello(SayHelloRequest {
+                name: "Yathvan".to_string(),
+                language: 1,
+            })
+            .await
+            .unwrap();
+        assert_eq!(resp.greeting, "Jambo Yathvan");
+
+        let stats = client.compression_stats();
+        assert_eq!(stats.num_messages(), 1);
+        assert!(stats.uncompressed_bytes() > 1024 * 1024);
+        assert!(stats.ratio() > 10.0);
+    }
+}
+
 #[runtime::test]
 async fn ping_latency() {
     let (mut muxer, _outbound, _, _, _shutdown) = setup(GreetingService::new(&[]), 1).await;
@@ -20,7 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use tokio::runtime;
+use std::future::Future;
+use tokio::{runtime, task::JoinHandle};
 
 // Re-export
 pub use tokio::{runtime::Handle, task};
@@ -32,3 +33,22 @@ pub use tokio::test;
 pub fn current() -> runtime::Handle {
     runtime::Handle::current()
 }
+
+/// Spawn a task with a name that is shown in tokio-console. The name is only recorded when built with the
+/// `tokio-console` feature and `RUSTFLAGS="--cfg tokio_unstable"`, otherwise this is the same as `task::spawn`.
+#[inline]
+pub fn spawn_named<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tokio-console"))]
+    {
+        task::Builder::new().name(name).spawn(future)
+    }
+    #[cfg(not(all(tokio_unstable, feature = "tokio-console")))]
+    {
+        let _ = name;
+        task::spawn(future)
+    }
+}
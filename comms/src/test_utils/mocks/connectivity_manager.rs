@@ -261,6 +261,7 @@ impl ConnectivityManagerMock {
                     .await
             },
             GetAllConnectionStates(_) => unimplemented!(),
+            GetConnectionChurn(_) => unimplemented!(),
             BanPeer(_, _, _) => {},
             GetActiveConnections(reply) => {
                 self.state
@@ -28,6 +28,7 @@
 #![deny(unknown_lints)]
 
 pub mod oneshot_trigger;
+pub mod phases;
 
 use crate::oneshot_trigger::OneshotSignal;
 use futures::future::FusedFuture;
@@ -0,0 +1,329 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Graceful shutdown in ordered phases.
+//!
+//! A single [Shutdown] stops every service at once, so a process may exit while a database is still being written to.
+//! A [ShutdownOrchestrator] instead starts the phases of [ShutdownPhase] one after the other. Services register for
+//! the phase they shut down in and report when they are done, the next phase starts once every service of the
+//! current phase is done or the phase timed out. The returned [ShutdownReport] lists which services shut down
+//! cleanly.
+
+use crate::{Shutdown, ShutdownSignal};
+use futures::channel::oneshot;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
+use tokio::time;
+
+/// The phases of a graceful shutdown, in the order they are started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShutdownPhase {
+    /// Stop accepting requests and new work, such as RPC calls, block sync and new transactions
+    StopAcceptingWork,
+    /// Write buffered database transactions to disk
+    FlushDatabases,
+    /// Close peer connections and the comms stack
+    CloseComms,
+}
+
+impl ShutdownPhase {
+    pub const ALL: [ShutdownPhase; 3] = [
+        ShutdownPhase::StopAcceptingWork,
+        ShutdownPhase::FlushDatabases,
+        ShutdownPhase::CloseComms,
+    ];
+}
+
+impl fmt::Display for ShutdownPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShutdownPhase::StopAcceptingWork => write!(f, "stop accepting work"),
+            ShutdownPhase::FlushDatabases => write!(f, "flush databases"),
+            ShutdownPhase::CloseComms => write!(f, "close comms"),
+        }
+    }
+}
+
+/// How long the services of each phase are waited for before the next phase starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownTimeouts {
+    pub stop_accepting_work: Duration,
+    pub flush_databases: Duration,
+    pub close_comms: Duration,
+}
+
+impl ShutdownTimeouts {
+    pub fn get(&self, phase: ShutdownPhase) -> Duration {
+        match phase {
+            ShutdownPhase::StopAcceptingWork => self.stop_accepting_work,
+            ShutdownPhase::FlushDatabases => self.flush_databases,
+            ShutdownPhase::CloseComms => self.close_comms,
+        }
+    }
+}
+
+impl Default for ShutdownTimeouts {
+    fn default() -> Self {
+        Self {
+            stop_accepting_work: Duration::from_secs(10),
+            flush_databases: Duration::from_secs(60),
+            close_comms: Duration::from_secs(10),
+        }
+    }
+}
+
+struct Phase {
+    trigger: Shutdown,
+    services: Vec<(String, oneshot::Receiver<()>)>,
+}
+
+/// Starts the shutdown phases in order and waits for the services registered in each of them.
+///
+/// _Note_: Like [Shutdown], every phase is triggered at once when the orchestrator is dropped without being run.
+pub struct ShutdownOrchestrator {
+    phases: BTreeMap<ShutdownPhase, Phase>,
+}
+
+impl ShutdownOrchestrator {
+    pub fn new() -> Self {
+        let phases = ShutdownPhase::ALL
+            .iter()
+            .map(|phase| {
+                (*phase, Phase {
+                    trigger: Shutdown::new(),
+                    services: Vec::new(),
+                })
+            })
+            .collect();
+        Self { phases }
+    }
+
+    /// Returns a signal that resolves when the given phase starts. Services using this signal are not waited for, use
+    /// `register` for services that should be included in the report.
+    pub fn phase_signal(&self, phase: ShutdownPhase) -> ShutdownSignal {
+        self.phases[&phase].trigger.to_signal()
+    }
+
+    /// Registers a service that shuts down in the given phase. The service is expected to call
+    /// [ShutdownHandle::complete] once it has shut down after the phase started.
+    pub fn register<T: Into<String>>(&mut self, name: T, phase: ShutdownPhase) -> ShutdownHandle {
+        let (tx, rx) = oneshot::channel();
+        let phase = self.phases.get_mut(&phase).expect("every phase is present");
+        phase.services.push((name.into(), rx));
+        ShutdownHandle {
+            signal: phase.trigger.to_signal(),
+            completed: tx,
+        }
+    }
+
+    /// Starts each phase in order. The next phase starts once all services of the current phase have completed or
+    /// the timeout of the phase has elapsed.
+    pub async fn run(self, timeouts: &ShutdownTimeouts) -> ShutdownReport {
+        let mut services = Vec::new();
+        for (
+            phase,
+            Phase {
+                mut trigger,
+                services: registered,
+            },
+        ) in self.phases
+        {
+            trigger.trigger();
+            let started = Instant::now();
+            let deadline = time::Instant::now() + timeouts.get(phase);
+            for (name, completed) in registered {
+                let status = match time::timeout_at(deadline, completed).await {
+                    Ok(Ok(_)) => ServiceShutdownStatus::Clean,
+                    Ok(Err(_)) => ServiceShutdownStatus::Aborted,
+                    Err(_) => ServiceShutdownStatus::TimedOut,
+                };
+                services.push(ServiceShutdown {
+                    name,
+                    phase,
+                    status,
+                    elapsed: started.elapsed(),
+                });
+            }
+        }
+        ShutdownReport { services }
+    }
+}
+
+impl Default for ShutdownOrchestrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle given to a service that shuts down in a [ShutdownPhase]
+#[derive(Debug)]
+pub struct ShutdownHandle {
+    signal: ShutdownSignal,
+    completed: oneshot::Sender<()>,
+}
+
+impl ShutdownHandle {
+    /// Returns a signal that resolves when the phase of this service starts
+    pub fn to_signal(&self) -> ShutdownSignal {
+        self.signal.clone()
+    }
+
+    /// Wait for the phase of this service to start
+    pub async fn wait(&mut self) {
+        self.signal.wait().await;
+    }
+
+    /// Reports that the service has shut down cleanly. A handle that is dropped without completing is reported as
+    /// aborted.
+    pub fn complete(self) {
+        let _ = self.completed.send(());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceShutdownStatus {
+    /// The service completed its shutdown within the phase timeout
+    Clean,
+    /// The service did not complete its shutdown within the phase timeout
+    TimedOut,
+    /// The service stopped without reporting that it shut down cleanly
+    Aborted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceShutdown {
+    pub name: String,
+    pub phase: ShutdownPhase,
+    pub status: ServiceShutdownStatus,
+    /// The time from the start of the phase until the service completed or gave up
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for ServiceShutdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            ServiceShutdownStatus::Clean => "shut down cleanly",
+            ServiceShutdownStatus::TimedOut => "timed out",
+            ServiceShutdownStatus::Aborted => "aborted",
+        };
+        write!(
+            f,
+            "[{}] {} {} after {:.2}s",
+            self.phase,
+            self.name,
+            status,
+            self.elapsed.as_secs_f32()
+        )
+    }
+}
+
+/// The outcome of a graceful shutdown for every registered service
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownReport {
+    services: Vec<ServiceShutdown>,
+}
+
+impl ShutdownReport {
+    pub fn services(&self) -> &[ServiceShutdown] {
+        &self.services
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.services
+            .iter()
+            .all(|service| service.status == ServiceShutdownStatus::Clean)
+    }
+}
+
+impl fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for service in &self.services {
+            writeln!(f, "{}", service)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+    use tokio::task;
+
+    #[tokio::test]
+    async fn it_runs_phases_in_order() {
+        let mut orchestrator = ShutdownOrchestrator::new();
+        let mut db = orchestrator.register("db", ShutdownPhase::FlushDatabases);
+        let mut work = orchestrator.register("work", ShutdownPhase::StopAcceptingWork);
+        let work_stopped = Arc::new(AtomicBool::new(false));
+        let work_task = task::spawn({
+            let work_stopped = work_stopped.clone();
+            async move {
+                work.wait().await;
+                work_stopped.store(true, Ordering::SeqCst);
+                work.complete();
+            }
+        });
+        let db_task = task::spawn(async move {
+            db.wait().await;
+            assert!(work_stopped.load(Ordering::SeqCst));
+            db.complete();
+        });
+
+        let report = orchestrator.run(&ShutdownTimeouts::default()).await;
+        work_task.await.unwrap();
+        db_task.await.unwrap();
+        assert!(report.is_clean());
+        let names = report.services().iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["work", "db"]);
+    }
+
+    #[tokio::test]
+    async fn it_reports_services_that_did_not_complete() {
+        let mut orchestrator = ShutdownOrchestrator::new();
+        let _stuck = orchestrator.register("stuck", ShutdownPhase::StopAcceptingWork);
+        let dropped = orchestrator.register("dropped", ShutdownPhase::FlushDatabases);
+        drop(dropped);
+        let closed = orchestrator.register("closed", ShutdownPhase::CloseComms);
+        closed.complete();
+
+        let timeouts = ShutdownTimeouts {
+            stop_accepting_work: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let report = orchestrator.run(&timeouts).await;
+        assert!(!report.is_clean());
+        let statuses = report.services().iter().map(|s| s.status).collect::<Vec<_>>();
+        assert_eq!(statuses, vec![
+            ServiceShutdownStatus::TimedOut,
+            ServiceShutdownStatus::Aborted,
+            ServiceShutdownStatus::Clean
+        ]);
+    }
+}
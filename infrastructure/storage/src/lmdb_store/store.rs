@@ -45,6 +45,7 @@ pub struct LMDBConfig {
     init_size_bytes: usize,
     grow_size_bytes: usize,
     resize_threshold_bytes: usize,
+    grow_percent: usize,
 }
 
 impl LMDBConfig {
@@ -54,6 +55,7 @@ impl LMDBConfig {
             init_size_bytes,
             grow_size_bytes,
             resize_threshold_bytes,
+            grow_percent: 0,
         }
     }
 
@@ -63,9 +65,17 @@ impl LMDBConfig {
             init_size_bytes: init_size_mb * BYTES_PER_MB,
             grow_size_bytes: grow_size_mb * BYTES_PER_MB,
             resize_threshold_bytes: resize_threshold_mb * BYTES_PER_MB,
+            grow_percent: 0,
         }
     }
 
+    /// Grow the LMDB environment by this percentage of its current size when that is more than the grow size. This
+    /// keeps the number of resizes of large databases down. 0 always grows by the grow size.
+    pub fn with_grow_percent(mut self, grow_percent: usize) -> Self {
+        self.grow_percent = grow_percent;
+        self
+    }
+
     /// Get the initial size of the LMDB environment in bytes.
     pub fn init_size_bytes(&self) -> usize {
         self.init_size_bytes
@@ -81,6 +91,16 @@ impl LMDBConfig {
     pub fn resize_threshold_bytes(&self) -> usize {
         self.resize_threshold_bytes
     }
+
+    /// Get the percentage of the current size the LMDB environment is grown by, if that is more than the grow size
+    pub fn grow_percent(&self) -> usize {
+        self.grow_percent
+    }
+
+    /// The number of bytes an LMDB environment of `current_size_bytes` is grown by
+    pub fn grow_amount_bytes(&self, current_size_bytes: usize) -> usize {
+        max(self.grow_size_bytes, current_size_bytes / 100 * self.grow_percent)
+    }
 }
 
 impl Default for LMDBConfig {
@@ -412,20 +432,19 @@ impl LMDBStore {
         );
 
         if size_left_bytes <= config.resize_threshold_bytes {
-            Self::resize(env, config)?;
             debug!(
                 target: LOG_TARGET,
-                "({}) LMDB size used {:?} MB, environment space left {:?} MB, increased by {:?} MB",
+                "({}) LMDB size used {:?} MB, environment space left {:?} MB",
                 env.path()?.to_str()?,
                 size_used_bytes / BYTES_PER_MB,
                 size_left_bytes / BYTES_PER_MB,
-                config.grow_size_bytes / BYTES_PER_MB,
             );
+            Self::resize(env, config)?;
         }
         Ok(())
     }
 
-    /// Grows the LMDB environment by the configured amount
+    /// Grows the LMDB environment by the configured amount, see [LMDBConfig::grow_amount_bytes]
     ///
     /// # Safety
     /// This may only be called if no write transactions are active in the current process. Note that the library does
@@ -435,16 +454,17 @@ impl LMDBStore {
     pub unsafe fn resize(env: &Environment, config: &LMDBConfig) -> Result<(), LMDBError> {
         let env_info = env.info()?;
         let current_mapsize = env_info.mapsize;
-        env.set_mapsize(current_mapsize + config.grow_size_bytes)?;
+        let grow_amount = config.grow_amount_bytes(current_mapsize);
+        env.set_mapsize(current_mapsize + grow_amount)?;
         let env_info = env.info()?;
         let new_mapsize = env_info.mapsize;
-        debug!(
+        info!(
             target: LOG_TARGET,
-            "({}) LMDB MB, mapsize was grown from {:?} MB to {:?} MB, increased by {:?} MB",
+            "({}) LMDB mapsize was grown from {:?} MB to {:?} MB, increased by {:?} MB",
             env.path()?.to_str()?,
             current_mapsize / BYTES_PER_MB,
             new_mapsize / BYTES_PER_MB,
-            config.grow_size_bytes / BYTES_PER_MB,
+            grow_amount / BYTES_PER_MB,
         );
 
         Ok(())
@@ -772,4 +792,14 @@ mod test {
             .unwrap();
         assert_eq!(store.databases.len(), 2);
     }
+
+    #[test]
+    fn test_grow_amount() {
+        const MB: usize = 1024 * 1024;
+        let config = LMDBConfig::new_from_mb(16, 16, 4);
+        assert_eq!(config.grow_amount_bytes(1000 * MB), 16 * MB);
+        let config = config.with_grow_percent(10);
+        assert_eq!(config.grow_amount_bytes(100 * MB), 16 * MB);
+        assert_eq!(config.grow_amount_bytes(1000 * MB), 100 * MB);
+    }
 }